@@ -0,0 +1,155 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#ifndef CEDAR_H
+#define CEDAR_H
+
+/* Generated by cbindgen from cedar-ffi/src/lib.rs. Do not edit by hand. */
+
+#include <stddef.h>
+
+// A parsed set of entities.
+//
+// Create with [`cedar_entities_new()`] and release with
+// [`cedar_entities_free()`].
+typedef struct CedarEntities CedarEntities;
+
+// A parsed policy set.
+//
+// Create with [`cedar_policy_set_new()`] and release with
+// [`cedar_policy_set_free()`].
+typedef struct CedarPolicySet CedarPolicySet;
+
+// A parsed schema.
+//
+// Create with [`cedar_schema_new()`] and release with [`cedar_schema_free()`].
+typedef struct CedarSchema CedarSchema;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse a policy set written in the Cedar policy syntax. Policies are named
+// `policy0`, `policy1`, etc., in the order they appear.
+//
+// Returns null if the policy set can't be parsed. If `diagnostics` is not
+// null, a JSON object `{"errors": [...], "warnings": [...]}` is written
+// through it, whether or not parsing succeeded; the caller must release it
+// with [`cedar_string_free()`].
+//
+// # Safety
+//
+// `policies` must be a NUL-terminated string, and `diagnostics` must be null
+// or valid for writes.
+struct CedarPolicySet *cedar_policy_set_new(const char *policies, char **diagnostics);
+
+// Release a policy set created by [`cedar_policy_set_new()`]
+//
+// # Safety
+//
+// `policies` must be null or a handle returned by [`cedar_policy_set_new()`]
+// which has not been released yet.
+void cedar_policy_set_free(struct CedarPolicySet *policies);
+
+// Parse a schema, either in the Cedar schema syntax or, if the first
+// non-whitespace character is `{`, in the JSON schema format.
+//
+// Returns null if the schema can't be parsed. Diagnostics are reported as in
+// [`cedar_policy_set_new()`].
+//
+// # Safety
+//
+// `schema` must be a NUL-terminated string, and `diagnostics` must be null
+// or valid for writes.
+struct CedarSchema *cedar_schema_new(const char *schema, char **diagnostics);
+
+// Release a schema created by [`cedar_schema_new()`]
+//
+// # Safety
+//
+// `schema` must be null or a handle returned by [`cedar_schema_new()`] which
+// has not been released yet.
+void cedar_schema_free(struct CedarSchema *schema);
+
+// Parse entities from a JSON array in the Cedar entity format. If `schema`
+// is not null, the entities are checked against it, and action entities
+// declared in it are added.
+//
+// Returns null if the entities can't be parsed. Diagnostics are reported as
+// in [`cedar_policy_set_new()`].
+//
+// # Safety
+//
+// `entities` must be a NUL-terminated string, `schema` must be null or a live
+// handle, and `diagnostics` must be null or valid for writes.
+struct CedarEntities *cedar_entities_new(const char *entities,
+                                         const struct CedarSchema *schema,
+                                         char **diagnostics);
+
+// Release entities created by [`cedar_entities_new()`]
+//
+// # Safety
+//
+// `entities` must be null or a handle returned by [`cedar_entities_new()`]
+// which has not been released yet.
+void cedar_entities_free(struct CedarEntities *entities);
+
+// Authorize a request against `policies` and `entities`.
+//
+// `request` is a JSON object with `principal`, `action`, and `resource`
+// entity uids (e.g., `{"type": "User", "id": "alice"}`) and an optional
+// `context` record. `entities` may be null for an empty entity set. If
+// `schema` is not null, the request is validated against it.
+//
+// Returns a JSON-encoded `AuthorizationAnswer`, as produced by
+// `cedar_policy::ffi::is_authorized_json_str()`, which the caller must
+// release with [`cedar_string_free()`]. Invalid input produces a `"failure"`
+// answer rather than null.
+//
+// # Safety
+//
+// `policies`, `entities`, and `schema` must each be null or a live handle,
+// and `request` must be a NUL-terminated string.
+char *cedar_is_authorized(const struct CedarPolicySet *policies,
+                          const struct CedarEntities *entities,
+                          const struct CedarSchema *schema,
+                          const char *request);
+
+// Validate `policies` against `schema` in the default (strict) mode.
+//
+// Returns a JSON-encoded `ValidationAnswer`, as produced by
+// `cedar_policy::ffi::validate_json_str()`, which the caller must release
+// with [`cedar_string_free()`]. Null handles produce a `"failure"` answer
+// rather than null.
+//
+// # Safety
+//
+// `policies` and `schema` must each be null or a live handle.
+char *cedar_validate(const struct CedarPolicySet *policies, const struct CedarSchema *schema);
+
+// Release a string returned by this library
+//
+// # Safety
+//
+// `s` must be null or a string returned by this library which has not been
+// released yet.
+void cedar_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CEDAR_H */
@@ -0,0 +1,448 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A stable C ABI for embedding Cedar in policy enforcement points written in
+//! languages other than Rust. The C declarations are in `include/cedar.h`,
+//! which is generated from this file with `cbindgen`.
+//!
+//! Policy sets, schemas, and entities are parsed once into opaque handles
+//! ([`CedarPolicySet`], [`CedarSchema`], and [`CedarEntities`]), which can then
+//! be passed to [`cedar_is_authorized()`] and [`cedar_validate()`] any number
+//! of times. Answers and diagnostics are returned as JSON strings, in the same
+//! shapes as the JSON interface in [`cedar_policy::ffi`].
+//!
+//! # Ownership
+//!
+//! * Every string passed in must be a NUL-terminated, UTF-8 string. It is only
+//!   borrowed for the duration of the call, so the caller keeps ownership of
+//!   it and may free it as soon as the call returns.
+//! * Every handle returned by a `cedar_*_new()` function is owned by the
+//!   caller and must be released exactly once with the matching
+//!   `cedar_*_free()` function. Handles passed to other functions are only
+//!   borrowed.
+//! * Every `char *` returned by this library, including diagnostics written
+//!   through an out-parameter, is owned by the caller and must be released
+//!   exactly once with [`cedar_string_free()`], never with `free()`.
+//! * All `*_free()` functions accept null, and do nothing in that case.
+//!
+//! Handles are immutable once created, so a handle may be shared between
+//! threads, e.g., to authorize many requests against one policy set
+//! concurrently, as long as it is not freed while another thread is using it.
+//!
+//! Errors are reported as data rather than by unwinding: a panic inside this
+//! library aborts the process.
+#![deny(missing_docs, rustdoc::broken_intra_doc_links)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::str::FromStr;
+
+use cedar_policy::ffi::{AuthorizationAnswer, DetailedError, ValidationAnswer, ValidationError};
+use cedar_policy::{Authorizer, Context, Entities, PolicySet, Request, Schema, Validator};
+use serde::{Deserialize, Serialize};
+
+/// A parsed policy set.
+///
+/// Create with [`cedar_policy_set_new()`] and release with
+/// [`cedar_policy_set_free()`].
+#[derive(Debug)]
+pub struct CedarPolicySet(PolicySet);
+
+/// A parsed schema.
+///
+/// Create with [`cedar_schema_new()`] and release with [`cedar_schema_free()`].
+#[derive(Debug)]
+pub struct CedarSchema(Schema);
+
+/// A parsed set of entities.
+///
+/// Create with [`cedar_entities_new()`] and release with
+/// [`cedar_entities_free()`].
+#[derive(Debug)]
+pub struct CedarEntities(Entities);
+
+/// JSON shape of the diagnostics written by the `cedar_*_new()` functions
+#[derive(Debug, Default, Serialize)]
+struct Diagnostics {
+    errors: Vec<DetailedError>,
+    warnings: Vec<DetailedError>,
+}
+
+/// JSON shape of the request passed to [`cedar_is_authorized()`]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequestJson {
+    principal: cedar_policy::ffi::EntityUid,
+    action: cedar_policy::ffi::EntityUid,
+    resource: cedar_policy::ffi::EntityUid,
+    #[serde(default)]
+    context: Option<cedar_policy::ffi::Context>,
+}
+
+fn error(message: impl Into<String>) -> DetailedError {
+    DetailedError {
+        message: message.into(),
+        ..Default::default()
+    }
+}
+
+/// Box a [`DetailedError`], which is too large to return unboxed
+fn boxed(e: impl Into<DetailedError>) -> Box<DetailedError> {
+    Box::new(e.into())
+}
+
+/// Borrow a string from the caller, naming it `what` in errors
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string which stays valid and
+/// unmodified for `'a`.
+unsafe fn borrow_str<'a>(s: *const c_char, what: &str) -> Result<&'a str, Box<DetailedError>> {
+    if s.is_null() {
+        return Err(boxed(error(format!("{what} must not be null"))));
+    }
+    // SAFETY: `s` is non-null, and the caller guarantees it is NUL-terminated
+    // and valid for `'a`
+    let s = unsafe { CStr::from_ptr(s) };
+    s.to_str()
+        .map_err(|e| boxed(error(format!("{what} is not valid UTF-8: {e}"))))
+}
+
+/// Hand a string over to the caller, who must release it with
+/// [`cedar_string_free()`]
+fn into_c_string(s: String) -> *mut c_char {
+    // JSON never contains a NUL byte, since `serde_json` escapes it
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+fn to_json(value: &impl Serialize) -> *mut c_char {
+    serde_json::to_string(value).map_or(ptr::null_mut(), into_c_string)
+}
+
+/// Write `diagnostics` through `out` as JSON, and turn `result` into a handle
+/// owned by the caller
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn into_handle<T>(
+    result: Result<(T, Vec<DetailedError>), Box<DetailedError>>,
+    out: *mut *mut c_char,
+) -> *mut T {
+    let (handle, diagnostics) = match result {
+        Ok((t, warnings)) => (
+            Box::into_raw(Box::new(t)),
+            Diagnostics {
+                errors: Vec::new(),
+                warnings,
+            },
+        ),
+        Err(e) => (
+            ptr::null_mut(),
+            Diagnostics {
+                errors: vec![*e],
+                warnings: Vec::new(),
+            },
+        ),
+    };
+    if !out.is_null() {
+        // SAFETY: `out` is non-null, and the caller guarantees it is valid
+        // for writes
+        unsafe { out.write(to_json(&diagnostics)) };
+    }
+    handle
+}
+
+/// Release a handle created by one of the `cedar_*_new()` functions
+///
+/// # Safety
+///
+/// `handle` must be null or have been returned by the matching `*_new()`
+/// function, and not released yet.
+unsafe fn free_handle<T>(handle: *mut T) {
+    if !handle.is_null() {
+        // SAFETY: the caller guarantees `handle` came from `Box::into_raw()`
+        // in `into_handle()` and has not been released yet
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
+
+/// Parse a policy set written in the Cedar policy syntax. Policies are named
+/// `policy0`, `policy1`, etc., in the order they appear.
+///
+/// Returns null if the policy set can't be parsed. If `diagnostics` is not
+/// null, a JSON object `{"errors": [...], "warnings": [...]}` is written
+/// through it, whether or not parsing succeeded; the caller must release it
+/// with [`cedar_string_free()`].
+///
+/// # Safety
+///
+/// `policies` must be a NUL-terminated string, and `diagnostics` must be null
+/// or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_policy_set_new(
+    policies: *const c_char,
+    diagnostics: *mut *mut c_char,
+) -> *mut CedarPolicySet {
+    // SAFETY: the caller guarantees `policies` is a NUL-terminated string
+    let result = unsafe { borrow_str(policies, "policies") }.and_then(|src| {
+        PolicySet::from_str(src)
+            .map(|pset| (CedarPolicySet(pset), Vec::new()))
+            .map_err(|e| boxed(&e))
+    });
+    // SAFETY: the caller guarantees `diagnostics` is null or valid for writes
+    unsafe { into_handle(result, diagnostics) }
+}
+
+/// Release a policy set created by [`cedar_policy_set_new()`]
+///
+/// # Safety
+///
+/// `policies` must be null or a handle returned by [`cedar_policy_set_new()`]
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_policy_set_free(policies: *mut CedarPolicySet) {
+    // SAFETY: forwarded from the caller
+    unsafe { free_handle(policies) }
+}
+
+/// Parse a schema, either in the Cedar schema syntax or, if the first
+/// non-whitespace character is `{`, in the JSON schema format.
+///
+/// Returns null if the schema can't be parsed. Diagnostics are reported as in
+/// [`cedar_policy_set_new()`].
+///
+/// # Safety
+///
+/// `schema` must be a NUL-terminated string, and `diagnostics` must be null
+/// or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_schema_new(
+    schema: *const c_char,
+    diagnostics: *mut *mut c_char,
+) -> *mut CedarSchema {
+    // SAFETY: the caller guarantees `schema` is a NUL-terminated string
+    let result = unsafe { borrow_str(schema, "schema") }.and_then(|src| {
+        if src.trim_start().starts_with('{') {
+            Schema::from_json_str(src)
+                .map(|schema| (CedarSchema(schema), Vec::new()))
+                .map_err(|e| boxed(&e))
+        } else {
+            Schema::from_cedarschema_str(src)
+                .map(|(schema, warnings)| {
+                    let warnings = warnings.map(|w| DetailedError::from(&w)).collect();
+                    (CedarSchema(schema), warnings)
+                })
+                .map_err(|e| boxed(&e))
+        }
+    });
+    // SAFETY: the caller guarantees `diagnostics` is null or valid for writes
+    unsafe { into_handle(result, diagnostics) }
+}
+
+/// Release a schema created by [`cedar_schema_new()`]
+///
+/// # Safety
+///
+/// `schema` must be null or a handle returned by [`cedar_schema_new()`] which
+/// has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_schema_free(schema: *mut CedarSchema) {
+    // SAFETY: forwarded from the caller
+    unsafe { free_handle(schema) }
+}
+
+/// Parse entities from a JSON array in the Cedar entity format. If `schema`
+/// is not null, the entities are checked against it, and action entities
+/// declared in it are added.
+///
+/// Returns null if the entities can't be parsed. Diagnostics are reported as
+/// in [`cedar_policy_set_new()`].
+///
+/// # Safety
+///
+/// `entities` must be a NUL-terminated string, `schema` must be null or a live
+/// handle, and `diagnostics` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_entities_new(
+    entities: *const c_char,
+    schema: *const CedarSchema,
+    diagnostics: *mut *mut c_char,
+) -> *mut CedarEntities {
+    // SAFETY: the caller guarantees `schema` is null or a live handle
+    let schema = unsafe { schema.as_ref() }.map(|s| &s.0);
+    // SAFETY: the caller guarantees `entities` is a NUL-terminated string
+    let result = unsafe { borrow_str(entities, "entities") }.and_then(|src| {
+        Entities::from_json_str(src, schema)
+            .map(|entities| (CedarEntities(entities), Vec::new()))
+            .map_err(|e| boxed(&e))
+    });
+    // SAFETY: the caller guarantees `diagnostics` is null or valid for writes
+    unsafe { into_handle(result, diagnostics) }
+}
+
+/// Release entities created by [`cedar_entities_new()`]
+///
+/// # Safety
+///
+/// `entities` must be null or a handle returned by [`cedar_entities_new()`]
+/// which has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_entities_free(entities: *mut CedarEntities) {
+    // SAFETY: forwarded from the caller
+    unsafe { free_handle(entities) }
+}
+
+/// Parse a request from `request`, a JSON object with `principal`, `action`,
+/// and `resource` entity uids, and an optional `context` record
+fn parse_request(request: &str, schema: Option<&Schema>) -> Result<Request, Box<DetailedError>> {
+    let request: RequestJson =
+        serde_json::from_str(request).map_err(|e| error(format!("invalid request: {e}")))?;
+    let principal = request.principal.parse(Some("principal")).map_err(boxed)?;
+    let action = request.action.parse(Some("action")).map_err(boxed)?;
+    let resource = request.resource.parse(Some("resource")).map_err(boxed)?;
+    let context = match request.context {
+        Some(context) => context.parse(schema, Some(&action)).map_err(boxed)?,
+        None => Context::empty(),
+    };
+    Request::new(principal, action, resource, context, schema).map_err(|e| boxed(&e))
+}
+
+/// Authorize a request against `policies` and `entities`.
+///
+/// `request` is a JSON object with `principal`, `action`, and `resource`
+/// entity uids (e.g., `{"type": "User", "id": "alice"}`) and an optional
+/// `context` record. `entities` may be null for an empty entity set. If
+/// `schema` is not null, the request is validated against it.
+///
+/// Returns a JSON-encoded `AuthorizationAnswer`, as produced by
+/// `cedar_policy::ffi::is_authorized_json_str()`, which the caller must
+/// release with [`cedar_string_free()`]. Invalid input produces a `"failure"`
+/// answer rather than null.
+///
+/// # Safety
+///
+/// `policies`, `entities`, and `schema` must each be null or a live handle,
+/// and `request` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_is_authorized(
+    policies: *const CedarPolicySet,
+    entities: *const CedarEntities,
+    schema: *const CedarSchema,
+    request: *const c_char,
+) -> *mut c_char {
+    // SAFETY: the caller guarantees `policies` is null or a live handle
+    let policies = unsafe { policies.as_ref() };
+    // SAFETY: the caller guarantees `entities` is null or a live handle
+    let entities = unsafe { entities.as_ref() };
+    // SAFETY: the caller guarantees `schema` is null or a live handle
+    let schema = unsafe { schema.as_ref() }.map(|s| &s.0);
+    // SAFETY: the caller guarantees `request` is a NUL-terminated string
+    let request = unsafe { borrow_str(request, "request") };
+
+    let answer = match (policies, request.and_then(|r| parse_request(r, schema))) {
+        (Some(policies), Ok(request)) => {
+            let empty = Entities::empty();
+            let entities = entities.map_or(&empty, |e| &e.0);
+            let response = Authorizer::new().is_authorized(&request, &policies.0, entities);
+            AuthorizationAnswer::Success {
+                response: response.into(),
+                warnings: Vec::new(),
+            }
+        }
+        (None, _) => AuthorizationAnswer::Failure {
+            errors: vec![error("policies must not be null")],
+            warnings: Vec::new(),
+        },
+        (Some(_), Err(e)) => AuthorizationAnswer::Failure {
+            errors: vec![*e],
+            warnings: Vec::new(),
+        },
+    };
+    to_json(&answer)
+}
+
+/// Validate `policies` against `schema` in the default (strict) mode.
+///
+/// Returns a JSON-encoded `ValidationAnswer`, as produced by
+/// `cedar_policy::ffi::validate_json_str()`, which the caller must release
+/// with [`cedar_string_free()`]. Null handles produce a `"failure"` answer
+/// rather than null.
+///
+/// # Safety
+///
+/// `policies` and `schema` must each be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_validate(
+    policies: *const CedarPolicySet,
+    schema: *const CedarSchema,
+) -> *mut c_char {
+    // SAFETY: the caller guarantees `policies` is null or a live handle
+    let policies = unsafe { policies.as_ref() };
+    // SAFETY: the caller guarantees `schema` is null or a live handle
+    let schema = unsafe { schema.as_ref() };
+
+    let answer = match (policies, schema) {
+        (Some(policies), Some(schema)) => {
+            let result = Validator::new(schema.0.clone())
+                .validate(&policies.0, cedar_policy::ValidationMode::default());
+            ValidationAnswer::Success {
+                validation_errors: result
+                    .validation_errors()
+                    .map(|e| ValidationError {
+                        policy_id: e.policy_id().clone(),
+                        error: DetailedError::from(e),
+                    })
+                    .collect(),
+                validation_warnings: result
+                    .validation_warnings()
+                    .map(|w| ValidationError {
+                        policy_id: w.policy_id().clone(),
+                        error: DetailedError::from(w),
+                    })
+                    .collect(),
+                other_warnings: Vec::new(),
+            }
+        }
+        (policies, schema) => ValidationAnswer::Failure {
+            errors: [
+                (policies.is_none(), "policies"),
+                (schema.is_none(), "schema"),
+            ]
+            .into_iter()
+            .filter(|(missing, _)| *missing)
+            .map(|(_, what)| error(format!("{what} must not be null")))
+            .collect(),
+            warnings: Vec::new(),
+        },
+    };
+    to_json(&answer)
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library which has not been
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn cedar_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the caller guarantees `s` came from `CString::into_raw()` in
+        // `into_c_string()` and has not been released yet
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
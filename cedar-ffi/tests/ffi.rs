@@ -0,0 +1,275 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests which call the C ABI the way a C caller would
+#![expect(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    reason = "Tests fail by panicking. Indexing a `serde_json::Value` doesn't panic, and gives `Null` for missing keys"
+)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use cedar_ffi::*;
+use serde_json::{json, Value};
+
+const POLICIES: &str = r#"
+permit(principal == User::"alice", action == Action::"view", resource)
+when { context.authenticated };
+forbid(principal, action, resource == Photo::"secret");
+"#;
+
+const SCHEMA: &str = r#"
+entity User;
+entity Photo;
+action view appliesTo { principal: User, resource: Photo, context: { authenticated: Bool } };
+"#;
+
+/// Take ownership of a string returned by the library, as JSON
+fn take_json(s: *mut c_char) -> Value {
+    assert!(!s.is_null());
+    // SAFETY: `s` was returned by the library and is released right after
+    let json = serde_json::from_str(unsafe { CStr::from_ptr(s) }.to_str().unwrap()).unwrap();
+    // SAFETY: `s` was returned by the library and not released yet
+    unsafe { cedar_string_free(s) };
+    json
+}
+
+fn policy_set(src: &str) -> (*mut CedarPolicySet, Value) {
+    let src = CString::new(src).unwrap();
+    let mut diagnostics = ptr::null_mut();
+    // SAFETY: `src` is NUL-terminated and `diagnostics` is valid for writes
+    let pset = unsafe { cedar_policy_set_new(src.as_ptr(), &mut diagnostics) };
+    (pset, take_json(diagnostics))
+}
+
+fn schema(src: &str) -> (*mut CedarSchema, Value) {
+    let src = CString::new(src).unwrap();
+    let mut diagnostics = ptr::null_mut();
+    // SAFETY: `src` is NUL-terminated and `diagnostics` is valid for writes
+    let schema = unsafe { cedar_schema_new(src.as_ptr(), &mut diagnostics) };
+    (schema, take_json(diagnostics))
+}
+
+fn entities(json: &Value, schema: *const CedarSchema) -> (*mut CedarEntities, Value) {
+    let src = CString::new(json.to_string()).unwrap();
+    let mut diagnostics = ptr::null_mut();
+    // SAFETY: `src` is NUL-terminated, `schema` is null or live, and
+    // `diagnostics` is valid for writes
+    let entities = unsafe { cedar_entities_new(src.as_ptr(), schema, &mut diagnostics) };
+    (entities, take_json(diagnostics))
+}
+
+fn is_authorized(
+    policies: *const CedarPolicySet,
+    entities: *const CedarEntities,
+    schema: *const CedarSchema,
+    request: &Value,
+) -> Value {
+    let request = CString::new(request.to_string()).unwrap();
+    // SAFETY: all handles are null or live, and `request` is NUL-terminated
+    take_json(unsafe { cedar_is_authorized(policies, entities, schema, request.as_ptr()) })
+}
+
+fn request(resource: &str, authenticated: bool) -> Value {
+    json!({
+        "principal": { "type": "User", "id": "alice" },
+        "action": { "type": "Action", "id": "view" },
+        "resource": { "type": "Photo", "id": resource },
+        "context": { "authenticated": authenticated },
+    })
+}
+
+#[test]
+fn authorize_with_handles() {
+    let (pset, diagnostics) = policy_set(POLICIES);
+    assert!(!pset.is_null());
+    assert_eq!(diagnostics, json!({ "errors": [], "warnings": [] }));
+    let (schema, _) = schema(SCHEMA);
+    assert!(!schema.is_null());
+    let (entities, _) = entities(
+        &json!([{ "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }]),
+        schema,
+    );
+    assert!(!entities.is_null());
+
+    let answer = is_authorized(pset, entities, schema, &request("vacation", true));
+    assert_eq!(answer["type"], "success");
+    assert_eq!(answer["response"]["decision"], "allow");
+    assert_eq!(
+        answer["response"]["diagnostics"]["reason"],
+        json!(["policy0"])
+    );
+
+    let answer = is_authorized(pset, entities, schema, &request("vacation", false));
+    assert_eq!(answer["response"]["decision"], "deny");
+
+    let answer = is_authorized(pset, entities, schema, &request("secret", true));
+    assert_eq!(answer["response"]["decision"], "deny");
+    assert_eq!(
+        answer["response"]["diagnostics"]["reason"],
+        json!(["policy1"])
+    );
+
+    // SAFETY: each handle was returned by the library and is released once
+    unsafe { cedar_policy_set_free(pset) };
+    // SAFETY: as above
+    unsafe { cedar_entities_free(entities) };
+    // SAFETY: as above
+    unsafe { cedar_schema_free(schema) };
+}
+
+#[test]
+fn authorize_without_entities_or_schema() {
+    let (pset, _) = policy_set(POLICIES);
+    let answer = is_authorized(pset, ptr::null(), ptr::null(), &request("vacation", true));
+    assert_eq!(answer["response"]["decision"], "allow");
+    // SAFETY: `pset` was returned by the library and is released once
+    unsafe { cedar_policy_set_free(pset) };
+}
+
+#[test]
+fn invalid_requests_are_failures() {
+    let (pset, _) = policy_set(POLICIES);
+    let (schema, _) = schema(SCHEMA);
+
+    let answer = is_authorized(pset, ptr::null(), ptr::null(), &json!({ "principal": 1 }));
+    assert_eq!(answer["type"], "failure");
+    assert!(answer["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid request"));
+
+    // `authenticated` must be a boolean according to the schema
+    let mut bad_context = request("vacation", true);
+    bad_context["context"] = json!({ "authenticated": "yes" });
+    let answer = is_authorized(pset, ptr::null(), schema, &bad_context);
+    assert_eq!(answer["type"], "failure");
+
+    let answer = is_authorized(ptr::null(), ptr::null(), ptr::null(), &request("a", true));
+    assert_eq!(answer["errors"][0]["message"], "policies must not be null");
+
+    // SAFETY: each handle was returned by the library and is released once
+    unsafe { cedar_policy_set_free(pset) };
+    // SAFETY: as above
+    unsafe { cedar_schema_free(schema) };
+}
+
+#[test]
+fn parse_errors_are_diagnostics() {
+    let (pset, diagnostics) = policy_set("permit(principal, action);");
+    assert!(pset.is_null());
+    assert_eq!(diagnostics["errors"].as_array().unwrap().len(), 1);
+    assert!(!diagnostics["errors"][0]["sourceLocations"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let (schema_handle, diagnostics) = schema("entity User in [");
+    assert!(schema_handle.is_null());
+    assert_eq!(diagnostics["errors"].as_array().unwrap().len(), 1);
+
+    let (schema, _) = schema(SCHEMA);
+    let (entities, diagnostics) = entities(
+        &json!([{ "uid": { "type": "Folder", "id": "f" }, "attrs": {}, "parents": [] }]),
+        schema,
+    );
+    assert!(entities.is_null());
+    assert!(diagnostics["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Folder"));
+
+    // Diagnostics are optional
+    let bad = CString::new("permit(").unwrap();
+    // SAFETY: `bad` is NUL-terminated and `diagnostics` may be null
+    let pset = unsafe { cedar_policy_set_new(bad.as_ptr(), ptr::null_mut()) };
+    assert!(pset.is_null());
+
+    // SAFETY: `schema` was returned by the library and is released once
+    unsafe { cedar_schema_free(schema) };
+}
+
+#[test]
+fn json_schemas_are_accepted() {
+    let (schema, diagnostics) =
+        schema(&json!({ "": { "entityTypes": { "User": {} }, "actions": {} } }).to_string());
+    assert!(!schema.is_null(), "{diagnostics}");
+    // SAFETY: `schema` was returned by the library and is released once
+    unsafe { cedar_schema_free(schema) };
+}
+
+#[test]
+fn validate_reports_errors() {
+    let (schema, _) = schema(SCHEMA);
+    let (good, _) = policy_set(POLICIES);
+    // SAFETY: both handles are live
+    let answer = take_json(unsafe { cedar_validate(good, schema) });
+    assert_eq!(answer["type"], "success");
+    assert_eq!(answer["validationErrors"], json!([]));
+
+    let (bad, _) = policy_set(r#"permit(principal, action, resource) when { principal.age > 3 };"#);
+    // SAFETY: both handles are live
+    let answer = take_json(unsafe { cedar_validate(bad, schema) });
+    assert_eq!(answer["type"], "success");
+    assert_eq!(answer["validationErrors"][0]["policyId"], "policy0");
+
+    // SAFETY: a null handle is allowed
+    let answer = take_json(unsafe { cedar_validate(bad, ptr::null()) });
+    assert_eq!(answer["type"], "failure");
+    assert_eq!(answer["errors"][0]["message"], "schema must not be null");
+
+    // SAFETY: each handle was returned by the library and is released once
+    unsafe { cedar_policy_set_free(good) };
+    // SAFETY: as above
+    unsafe { cedar_policy_set_free(bad) };
+    // SAFETY: as above
+    unsafe { cedar_schema_free(schema) };
+}
+
+#[test]
+fn invalid_strings_are_rejected() {
+    let mut diagnostics = ptr::null_mut();
+    // SAFETY: a null string is allowed, and `diagnostics` is valid for writes
+    let pset = unsafe { cedar_policy_set_new(ptr::null(), &mut diagnostics) };
+    assert!(pset.is_null());
+    assert_eq!(
+        take_json(diagnostics)["errors"][0]["message"],
+        "policies must not be null"
+    );
+
+    let not_utf8 = CString::new(vec![0xff, 0xfe]).unwrap();
+    // SAFETY: `not_utf8` is NUL-terminated and `diagnostics` is valid for writes
+    let pset = unsafe { cedar_policy_set_new(not_utf8.as_ptr(), &mut diagnostics) };
+    assert!(pset.is_null());
+    assert!(take_json(diagnostics)["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("policies is not valid UTF-8"));
+}
+
+#[test]
+fn free_functions_accept_null() {
+    // SAFETY: all release functions accept null
+    unsafe { cedar_policy_set_free(ptr::null_mut()) };
+    // SAFETY: as above
+    unsafe { cedar_schema_free(ptr::null_mut()) };
+    // SAFETY: as above
+    unsafe { cedar_entities_free(ptr::null_mut()) };
+    // SAFETY: as above
+    unsafe { cedar_string_free(ptr::null_mut()) };
+}
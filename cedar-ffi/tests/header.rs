@@ -0,0 +1,38 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+
+/// Check that `include/cedar.h` matches what `cbindgen` generates from the
+/// current source, so the header can't drift from the ABI
+#[test]
+fn header_is_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(dir.join("src/lib.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+    let checked_in = std::fs::read_to_string(dir.join("include/cedar.h")).unwrap();
+    assert_eq!(
+        String::from_utf8(generated).unwrap(),
+        checked_in,
+        "include/cedar.h is out of date; regenerate it with `cbindgen --config cbindgen.toml --output include/cedar.h`"
+    );
+}
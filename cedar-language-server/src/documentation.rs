@@ -86,9 +86,16 @@ impl ToDocumentationString for BinaryOp {
             Self::ContainsAny => ContainsAnyDocumentation.to_documentation_string(schema),
             Self::GetTag => GetTagDocumentation.to_documentation_string(schema),
             Self::HasTag => HasTagDocumentation.to_documentation_string(schema),
+            Self::GetKey => GetKeyDocumentation.to_documentation_string(schema),
+            Self::ContainsKey => ContainsKeyDocumentation.to_documentation_string(schema),
         }
     }
 }
 
 impl_documentation_from_markdown_file!(GetTagDocumentation, "documentation/markdown/get_tag.md");
 impl_documentation_from_markdown_file!(HasTagDocumentation, "documentation/markdown/has_tag.md");
+impl_documentation_from_markdown_file!(GetKeyDocumentation, "documentation/markdown/get_key.md");
+impl_documentation_from_markdown_file!(
+    ContainsKeyDocumentation,
+    "documentation/markdown/contains_key.md"
+);
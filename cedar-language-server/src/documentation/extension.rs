@@ -30,6 +30,8 @@ impl ToDocumentationString for ExtensionName<'_> {
             "isLoopback" => IsLoopbackDocumentation.to_documentation_string(schema),
             "isMulticast" => IsMulticastDocumentation.to_documentation_string(schema),
             "isInRange" => IsInRangeDocumentation.to_documentation_string(schema),
+            "ipset" => IpSetDocumentation.to_documentation_string(schema),
+            "isInSet" => IsInSetDocumentation.to_documentation_string(schema),
             "decimal" => DecimalDocumentation.to_documentation_string(schema),
             "lessThan" => DecimalLessThanDocumentation.to_documentation_string(schema),
             "lessThanOrEqual" => {
@@ -39,12 +41,17 @@ impl ToDocumentationString for ExtensionName<'_> {
             "greaterThanOrEqual" => {
                 DecimalGreaterThanOrEqualDocumentation.to_documentation_string(schema)
             }
+            "add" => DecimalAddDocumentation.to_documentation_string(schema),
+            "subtract" => DecimalSubtractDocumentation.to_documentation_string(schema),
+            "multiply" => DecimalMultiplyDocumentation.to_documentation_string(schema),
             "datetime" => DateTimeDocumentation.to_documentation_string(schema),
             "duration" => DurationDocumentation.to_documentation_string(schema),
             "offset" => OffsetDocumentation.to_documentation_string(schema),
             "durationSince" => DurationSinceDocumentation.to_documentation_string(schema),
             "toDate" => ToDateDocumentation.to_documentation_string(schema),
             "toTime" => ToTimeDocumentation.to_documentation_string(schema),
+            "dayOfWeek" => DayOfWeekDocumentation.to_documentation_string(schema),
+            "hour" => HourDocumentation.to_documentation_string(schema),
             "toMilliseconds" => ToMillisecondsDocumentation.to_documentation_string(schema),
             "toSeconds" => ToSecondsDocumentation.to_documentation_string(schema),
             "toMinutes" => ToMinutesDocumentation.to_documentation_string(schema),
@@ -78,6 +85,18 @@ mod decimal {
         DecimalGreaterThanOrEqualDocumentation,
         "markdown/extension/decimal/greater_than_or_equal.md"
     );
+    impl_documentation_from_markdown_file!(
+        DecimalAddDocumentation,
+        "markdown/extension/decimal/add.md"
+    );
+    impl_documentation_from_markdown_file!(
+        DecimalSubtractDocumentation,
+        "markdown/extension/decimal/subtract.md"
+    );
+    impl_documentation_from_markdown_file!(
+        DecimalMultiplyDocumentation,
+        "markdown/extension/decimal/multiply.md"
+    );
 }
 
 pub(crate) use ip::*;
@@ -98,6 +117,11 @@ mod ip {
         IsInRangeDocumentation,
         "markdown/extension/ip/is_in_range.md"
     );
+    impl_documentation_from_markdown_file!(IpSetDocumentation, "markdown/extension/ip/ipset.md");
+    impl_documentation_from_markdown_file!(
+        IsInSetDocumentation,
+        "markdown/extension/ip/is_in_set.md"
+    );
 }
 
 pub(crate) use datetime::*;
@@ -127,6 +151,14 @@ mod datetime {
         ToTimeDocumentation,
         "markdown/extension/datetime/to_time.md"
     );
+    impl_documentation_from_markdown_file!(
+        DayOfWeekDocumentation,
+        "markdown/extension/datetime/day_of_week.md"
+    );
+    impl_documentation_from_markdown_file!(
+        HourDocumentation,
+        "markdown/extension/datetime/hour.md"
+    );
     impl_documentation_from_markdown_file!(
         ToMillisecondsDocumentation,
         "markdown/extension/datetime/to_milliseconds.md"
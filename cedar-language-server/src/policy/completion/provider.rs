@@ -15,6 +15,7 @@
  */
 
 use ast::ConditionCompletionVisitor;
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::validator::ValidatorSchema;
 use cedar_policy_core::{
     ast::PolicyID,
@@ -106,8 +107,10 @@ impl PolicyCompletionProvider {
             return vec![];
         };
 
-        let Ok(policy) = cst_policy.to_policy_template_tolerant(PolicyID::from_smolstr("0".into()))
-        else {
+        let Ok(policy) = cst_policy.to_policy_template_tolerant(
+            PolicyID::from_smolstr("0".into()),
+            Extensions::all_available(),
+        ) else {
             info!("Error parsing policy to policy template");
             return vec![];
         };
@@ -615,6 +618,7 @@ pub(crate) mod tests {
         r#"permit(principal, action, resource) when { ip("127.0.0.1").i|caret| };"#,
         vec![
             "isInRange(${1:ipaddr})",
+            "isInSet(${1:ipset})",
             "isIpv4()",
             "isIpv6()",
             "isLoopback()",
@@ -627,6 +631,7 @@ pub(crate) mod tests {
         r#"permit(principal, action, resource) when { ip("127.0.0.1").|caret| };"#,
         vec![
             "isInRange(${1:ipaddr})",
+            "isInSet(${1:ipset})",
             "isIpv4()",
             "isIpv6()",
             "isLoopback()",
@@ -638,10 +643,13 @@ pub(crate) mod tests {
         complete_extension_methods_decimal,
         r#"permit(principal, action, resource) when { decimal("127").g|caret| };"#,
         vec![
+            "add(${1:decimal})",
             "greaterThan(${1:decimal})",
             "greaterThanOrEqual(${1:decimal})",
             "lessThan(${1:decimal})",
-            "lessThanOrEqual(${1:decimal})"
+            "lessThanOrEqual(${1:decimal})",
+            "multiply(${1:decimal})",
+            "subtract(${1:decimal})"
         ]
     );
 
@@ -649,7 +657,9 @@ pub(crate) mod tests {
         complete_extension_methods_datetime,
         r#"permit(principal, action, resource) when { datetime("127").t|caret| };"#,
         vec![
+            "dayOfWeek(${1:duration})",
             "durationSince(${1:duration})",
+            "hour(${1:duration})",
             "offset(${1:duration})",
             "toDate()",
             "toDays()",
@@ -15,6 +15,7 @@
  */
 
 use cedar_policy_core::ast::PolicyID;
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::validator::ValidatorSchema;
 use itertools::Itertools;
 use tower_lsp_server::ls_types::{GotoDefinitionResponse, Location, Position, Uri};
@@ -60,7 +61,10 @@ pub(crate) fn policy_goto_definition(
         .rfind(|p| position_within_loc(position, p.loc.as_ref()))?;
 
     let policy = cst
-        .to_policy_template(PolicyID::from_smolstr("0".into()))
+        .to_policy_template(
+            PolicyID::from_smolstr("0".into()),
+            Extensions::all_available(),
+        )
         .ok()?;
     let (schema_info, schema_uri) = schema.zip(schema_uri)?;
 
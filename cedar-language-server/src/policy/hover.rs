@@ -27,6 +27,7 @@ use cedar_policy_core::ast::{
     ActionConstraint, ExprVisitor, PolicyID, PrincipalConstraint, PrincipalOrResourceConstraint,
     ResourceConstraint,
 };
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::validator::ValidatorSchema;
 use tower_lsp_server::ls_types::{self, Hover, HoverContents, MarkupKind, Position};
 use visitor::HoverVisitor;
@@ -68,7 +69,10 @@ pub fn policy_hover(
         .rfind(|p| position_within_loc(position, p.loc.as_ref()))?;
 
     let policy = cst
-        .to_policy_template(PolicyID::from_smolstr("0".into()))
+        .to_policy_template(
+            PolicyID::from_smolstr("0".into()),
+            Extensions::all_available(),
+        )
         .ok()?;
 
     let validator = schema.and_then(|schema| ValidatorSchema::try_from(&schema).ok());
@@ -250,14 +254,14 @@ mod tests {
             ContainsAllDocumentation, ContainsAnyDocumentation, ContainsDocumentation,
             DecimalDocumentation, DecimalGreaterThanDocumentation,
             DecimalGreaterThanOrEqualDocumentation, DecimalLessThanDocumentation,
-            DecimalLessThanOrEqualDocumentation, EqualsDocumentation, GreaterThanDocumentation,
-            GreaterThanOrEqualsDocumentation, HasDocumentation, IfDocumentation, InDocumentation,
-            IpDocumentation, IsDocumentation, IsEmptyDocumentation, IsInRangeDocumentation,
-            IsIpv4Documentation, IsIpv6Documentation, IsLoopbackDocumentation,
-            IsMulticastDocumentation, LessThanDocumentation, LessThanOrEqualsDocumentation,
-            LongDocumentation, MultiplyDocumentation, NotDocumentation, NotEqualsDocumentation,
-            OrDocumentation, PrincipalDocumentation, ResourceDocumentation, SetDocumentation,
-            StringDocumentation, SubtractDocumentation,
+            DecimalLessThanOrEqualDocumentation, DecimalMultiplyDocumentation, EqualsDocumentation,
+            GreaterThanDocumentation, GreaterThanOrEqualsDocumentation, HasDocumentation,
+            IfDocumentation, InDocumentation, IpDocumentation, IsDocumentation,
+            IsEmptyDocumentation, IsInRangeDocumentation, IsIpv4Documentation, IsIpv6Documentation,
+            IsLoopbackDocumentation, IsMulticastDocumentation, LessThanDocumentation,
+            LessThanOrEqualsDocumentation, LongDocumentation, MultiplyDocumentation,
+            NotDocumentation, NotEqualsDocumentation, OrDocumentation, PrincipalDocumentation,
+            ResourceDocumentation, SetDocumentation, StringDocumentation, SubtractDocumentation,
         },
         policy::{cedar::EntityTypeKind, hover::ToHover, types::cedar::CedarTypeKind},
         utils::tests::{remove_caret_marker, schema_document_context, schema_info},
@@ -639,6 +643,12 @@ mod tests {
         expr: DecimalGreaterThanOrEqualDocumentation
     );
 
+    schema_hover_test!(
+        hover_over_decimal_multiply,
+        r#"permit(principal, action, resource) when { decimal("3.45").m|caret|ultiply(decimal("2.0")).lessThan(decimal("10.0")) };"#,
+        expr: DecimalMultiplyDocumentation
+    );
+
     schema_hover_test!(
         hover_over_add_within_condition,
         r"permit(principal, action, resource) when { 1 |caret|+ 1 == 2 };",
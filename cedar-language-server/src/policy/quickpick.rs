@@ -16,6 +16,7 @@
 
 use std::str::FromStr;
 
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::{ast::AnyId, parser::text_to_cst::parse_policies_tolerant};
 use tower_lsp_server::ls_types::Range;
 
@@ -32,8 +33,8 @@ pub(crate) fn quickpick_list(
     policies: &str,
     selected_range: Range,
 ) -> anyhow::Result<Vec<QuickPickItem>> {
-    let policies =
-        parse_policies_tolerant(policies).and_then(|policies| policies.to_policyset_tolerant())?;
+    let policies = parse_policies_tolerant(policies)
+        .and_then(|policies| policies.to_policyset_tolerant(Extensions::all_available()))?;
 
     let items = policies
         .into_policies()
@@ -16,6 +16,7 @@
 
 use std::str::FromStr;
 
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::{
     ast::{AnyId, Template},
     parser::text_to_cst::parse_policies_tolerant,
@@ -60,7 +61,11 @@ use crate::{lsp::new_symbol, utils::ToRange};
 pub(crate) fn policy_set_symbols(policy_str: &str) -> Option<Vec<DocumentSymbol>> {
     let policies = parse_policies_tolerant(policy_str)
         .ok()
-        .and_then(|policies| policies.to_policyset_tolerant().ok())?;
+        .and_then(|policies| {
+            policies
+                .to_policyset_tolerant(Extensions::all_available())
+                .ok()
+        })?;
 
     let template_ranges = policies.templates().filter_map(to_symbol);
 
@@ -47,8 +47,9 @@ pub(crate) use method::*;
 use crate::{
     documentation::{
         BoolDocumentation, ContainsAllDocumentation, ContainsAnyDocumentation,
-        ContainsDocumentation, ExtensionName, IsEmptyDocumentation, LongDocumentation,
-        SetDocumentation, StringDocumentation, ToDocumentationString,
+        ContainsDocumentation, ContainsKeyDocumentation, ExtensionName, GetKeyDocumentation,
+        IsEmptyDocumentation, LongDocumentation, SetDocumentation, StringDocumentation,
+        ToDocumentationString,
     },
     markdown::MarkdownBuilder,
 };
@@ -73,6 +74,8 @@ pub(crate) enum CedarTypeKind {
     Set(Box<CedarTypeKind>),
     /// Empty set type, with indeterminate element type.
     EmptySet,
+    /// Map type, from `String` keys to values of a specific type.
+    Map(Box<CedarTypeKind>),
     /// Record type, containing named attributes with potentially different types.
     ///
     /// Example: `{ "name": "Alice", "age": 30 }`
@@ -159,6 +162,7 @@ impl CedarTypeKind {
         match self {
             Self::EmptySet => get_set_methods("?"),
             Self::Set(element_type) => get_set_methods(element_type.to_string()),
+            Self::Map(value_type) => get_map_methods(value_type.to_string()),
             Self::Extension(name) if name.to_string() == "ip" => {
                 MethodInfo::from_extension(&ipaddr::extension())
             }
@@ -180,6 +184,7 @@ impl Display for CedarTypeKind {
             Self::Long => write!(f, "long"),
             Self::String => write!(f, "string"),
             Self::Set(element_type) => write!(f, "Set<{element_type}>"),
+            Self::Map(value_type) => write!(f, "Map<String, {value_type}>"),
             Self::Record(fields) => {
                 let field_strs: Vec<String> = fields
                     .attrs
@@ -207,10 +212,6 @@ impl Display for CedarTypeKind {
     }
 }
 
-#[expect(
-    clippy::fallible_impl_from,
-    reason = "see comment on `expect(clippy::unwrap_used)` below"
-)]
 impl From<Type> for CedarTypeKind {
     fn from(ty: Type) -> Self {
         match ty {
@@ -221,6 +222,9 @@ impl From<Type> for CedarTypeKind {
             Type::Set { element_type } => element_type.map_or(Self::EmptySet, |ty| {
                 Self::Set(Box::new(Self::from(ty.as_ref().clone())))
             }),
+            Type::Map { value_type } => value_type.map_or(Self::Error, |ty| {
+                Self::Map(Box::new(Self::from(ty.as_ref().clone())))
+            }),
             Type::Record { attrs, .. } => {
                 let m = attrs
                     .into_iter()
@@ -231,15 +235,14 @@ impl From<Type> for CedarTypeKind {
             }
             Type::Entity(entity_kind) => match entity_kind {
                 EntityKind::AnyEntity => Self::Error,
-                EntityKind::Entity(entity_lub) => {
-                    // FIXME: This feels like an easy assumption to break. We should handle it gracefully
-                    #[expect(
-                        clippy::unwrap_used,
-                        reason = "LSP is only used with strict validation, so all entities are singleton"
-                    )]
-                    let e = entity_lub.into_single_entity().unwrap();
-                    Self::EntityType(EntityTypeKind::Concrete(Arc::new(e)))
-                }
+                // Entity types are only non-singleton for attributes declared
+                // with a union type in the schema
+                EntityKind::Entity(entity_lub) => match entity_lub.get_single_entity() {
+                    Some(e) => Self::EntityType(EntityTypeKind::Concrete(Arc::new(e.clone()))),
+                    None => Self::EntityType(EntityTypeKind::Set(
+                        entity_lub.iter().cloned().map(Arc::new).collect(),
+                    )),
+                },
             },
             Type::ExtensionType { name } => Self::Extension(name),
         }
@@ -308,6 +311,24 @@ impl From<&Literal> for CedarTypeKind {
     }
 }
 
+fn get_map_methods<T: Into<String>>(value_type_str: T) -> Vec<MethodInfo> {
+    let value_type_str: String = value_type_str.into();
+    vec![
+        MethodInfo::new(
+            "containsKey",
+            Some(ContainsKeyDocumentation.to_documentation_string(None)),
+            vec![("key".to_string(), "String".to_string())],
+            "bool",
+        ),
+        MethodInfo::new(
+            "get",
+            Some(GetKeyDocumentation.to_documentation_string(None)),
+            vec![("key".to_string(), "String".to_string())],
+            &value_type_str,
+        ),
+    ]
+}
+
 fn get_set_methods<T: Into<String>>(element_type_str: T) -> Vec<MethodInfo> {
     let element_type_str: String = element_type_str.into();
     vec![
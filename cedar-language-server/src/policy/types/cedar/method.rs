@@ -16,7 +16,7 @@
 
 use std::borrow::Cow;
 
-use cedar_policy_core::ast::{Extension, ExtensionFunction};
+use cedar_policy_core::ast::{CallStyle, Extension, ExtensionFunction};
 use tower_lsp_server::ls_types::{
     CompletionItem, CompletionItemKind, CompletionItemLabelDetails, Documentation,
     InsertTextFormat, MarkupContent,
@@ -124,7 +124,7 @@ impl MethodInfo {
 
     pub(crate) fn from_extension(ext: &Extension) -> Vec<Self> {
         ext.funcs()
-            .filter(|f| !f.is_single_arg_constructor())
+            .filter(|f| f.style() == CallStyle::MethodStyle)
             .map(Self::from)
             .collect()
     }
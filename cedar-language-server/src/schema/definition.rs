@@ -194,6 +194,7 @@ impl FindDefinition for Type {
             Self::Set { element_type } => {
                 element_type.as_ref().and_then(|el| el.find_definition(cx))
             }
+            Self::Map { value_type } => value_type.as_ref().and_then(|v| v.find_definition(cx)),
             Self::Entity(EntityKind::AnyEntity)
             | Self::Never
             | Self::Bool(_)
@@ -616,6 +616,7 @@ pub(crate) mod tests {
     use std::{fs::read_to_string, str::FromStr};
 
     use cedar_policy_core::ast::PolicyID;
+    use cedar_policy_core::extensions::Extensions;
     use cedar_policy_core::validator::ValidatorSchema;
     use tracing_test::traced_test;
 
@@ -705,7 +706,7 @@ pub(crate) mod tests {
         let template =
             cedar_policy_core::parser::text_to_cst::parse_policy_tolerant(policy).unwrap();
         let ast = template
-            .to_policy_template_tolerant(PolicyID::from_string("0"))
+            .to_policy_template_tolerant(PolicyID::from_string("0"), Extensions::all_available())
             .unwrap();
         DocumentContext::new(
             Some(schema()),
@@ -34,6 +34,9 @@ mod partial_eval;
 pub use partial_eval::*;
 mod run_test;
 pub use run_test::*;
+#[cfg(feature = "server")]
+mod serve;
+pub use serve::*;
 mod link;
 pub use link::*;
 mod format;
@@ -85,6 +88,18 @@ mod symcc {
     }
 }
 
+#[cfg(not(feature = "server"))]
+mod serve {
+    use crate::CedarExitCode;
+    #[derive(Debug, clap::Args)]
+    pub struct ServeArgs;
+
+    pub fn serve(_: &ServeArgs) -> CedarExitCode {
+        eprintln!("Error: subcommand `serve` requires this executable to be built with the `server` feature enabled");
+        CedarExitCode::Failure
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Evaluate an authorization request
@@ -128,6 +143,15 @@ pub enum Commands {
     RunTests(RunTestsArgs),
     /// Symbolic analysis of Cedar policies using SymCC
     Symcc(SymccArgs),
+    /// Serve authorization requests over HTTP, using the policies in a directory
+    ///
+    /// Endpoints use the JSON formats of the `cedar_policy::ffi` module:
+    ///   - POST /v1/is-authorized: authorize a request against the served policies
+    ///   - POST /v1/validate: validate policies against a schema
+    ///   - POST /v1/check-parse: check that a policy set parses
+    ///   - GET /health and GET /metrics (in the Prometheus text format)
+    #[clap(verbatim_doc_comment)]
+    Serve(ServeArgs),
     /// Print Cedar language version
     LanguageVersion,
 }
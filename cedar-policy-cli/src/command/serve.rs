@@ -0,0 +1,916 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cedar_policy::ffi::{self, AuthorizationAnswer, CheckParseAnswer, StatefulAuthorizationCall};
+use cedar_policy::Decision;
+use clap::Args;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{read_from_file_or_stdin, CedarExitCode, SchemaFormat};
+
+/// Name under which the served policy set is preparsed
+const POLICY_SET_ID: &str = "served";
+/// Name under which the served schema is preparsed
+const SCHEMA_NAME: &str = "served";
+/// Maximum size of the request line and headers of a request
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Directory containing the `.cedar` files with the policies and templates
+    /// to authorize against. The files are concatenated in order of their
+    /// names, and reloaded whenever they change.
+    #[arg(long = "policies-dir", value_name = "DIR")]
+    pub policies_dir: PathBuf,
+    /// File containing the schema to parse requests with and validate them against
+    #[arg(short, long = "schema", value_name = "FILE")]
+    pub schema_file: Option<PathBuf>,
+    /// Schema format
+    #[arg(long, value_enum, default_value_t)]
+    pub schema_format: SchemaFormat,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8180")]
+    pub listen: String,
+    /// Minimum number of seconds between checks for changes to the policies
+    #[arg(long, default_value_t = 2)]
+    pub reload_interval: u64,
+    /// Number of requests served at once. Each worker thread reads and
+    /// answers one request at a time, so this also limits how many requests
+    /// are read at once.
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..))]
+    pub workers: u16,
+    /// Maximum size of a request body in bytes. Larger requests are rejected
+    /// with status 413.
+    #[arg(long, default_value_t = 1024 * 1024)]
+    pub max_body_bytes: u64,
+    /// Maximum number of seconds to wait for a request, including its body.
+    /// Requests which take longer are rejected with status 408.
+    #[arg(long, default_value_t = 30)]
+    pub read_timeout: u64,
+}
+
+/// Serve authorization, validation and parsing over HTTP.
+///
+/// Endpoints take and return the JSON formats of [`cedar_policy::ffi`]:
+///   - `POST /v1/is-authorized` takes a request and entities, in the format of
+///     an `AuthorizationCall` without `policies` or `schema`, and authorizes
+///     it against the served policies (and schema, if any)
+///   - `POST /v1/validate` takes a `ValidationCall`
+///   - `POST /v1/check-parse` takes a policy set
+///   - `GET /health` reports whether the server is up
+///   - `GET /metrics` reports request counts and policy reloads in the
+///     Prometheus text format
+///
+/// Requests are answered by `--workers` threads, one request per connection.
+/// Request bodies are limited to `--max-body-bytes`, and requests must be
+/// received within `--read-timeout` seconds.
+pub fn serve(args: &ServeArgs) -> CedarExitCode {
+    let pdp = match Pdp::new(args) {
+        Ok(pdp) => Mutex::new(pdp),
+        Err(e) => {
+            eprintln!("{e:?}");
+            return CedarExitCode::Failure;
+        }
+    };
+    let listener = match TcpListener::bind(&args.listen) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: failed to listen on {}: {e}", args.listen);
+            return CedarExitCode::Failure;
+        }
+    };
+    eprintln!("Serving Cedar authorization on {}", args.listen);
+    let limits = Limits {
+        max_body_bytes: args.max_body_bytes,
+        read_timeout: Duration::from_secs(args.read_timeout),
+    };
+    std::thread::scope(|scope| {
+        for _ in 0..args.workers {
+            scope.spawn(|| {
+                let mut worker = Worker::new(&pdp);
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => worker.answer(&stream, &limits),
+                        Err(e) => eprintln!("Error: failed to accept a connection: {e}"),
+                    }
+                }
+            });
+        }
+    });
+    CedarExitCode::Success
+}
+
+/// Limits on the requests a worker reads
+#[derive(Debug)]
+struct Limits {
+    max_body_bytes: u64,
+    /// Time allowed for receiving a request, and for sending the reply
+    read_timeout: Duration,
+}
+
+/// A thread answering requests.
+///
+/// The `ffi` module caches preparsed policies per thread, so each worker
+/// preparses the served policies and schema itself, and again whenever the
+/// policies are reloaded.
+#[derive(Debug)]
+struct Worker<'a> {
+    pdp: &'a Mutex<Pdp>,
+    /// The generation of the policies this worker has preparsed, if any
+    generation: Option<u64>,
+}
+
+impl<'a> Worker<'a> {
+    fn new(pdp: &'a Mutex<Pdp>) -> Self {
+        Self {
+            pdp,
+            generation: None,
+        }
+    }
+
+    fn pdp(&self) -> MutexGuard<'a, Pdp> {
+        self.pdp.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Read a request from `stream` and reply to it. The request must be
+    /// received before `limits.read_timeout` has passed, which is enforced
+    /// with read timeouts on the socket, so that clients sending requests
+    /// slowly can't hold on to the worker.
+    fn answer(&mut self, stream: &TcpStream, limits: &Limits) {
+        let reader = DeadlineReader {
+            stream,
+            deadline: Instant::now() + limits.read_timeout,
+        };
+        let reply = match read_request(reader, limits.max_body_bytes) {
+            Ok(request) => self.handle(&request.method, &request.url, &request.body),
+            Err(reply) => reply,
+        };
+        let written = stream
+            .set_write_timeout(Some(limits.read_timeout))
+            .and_then(|()| write_reply(stream, &reply));
+        if let Err(e) = written {
+            eprintln!("Error: failed to respond: {e}");
+        }
+    }
+
+    fn handle(&mut self, method: &str, url: &str, body: &str) -> Reply {
+        match (method, url) {
+            ("POST", "/v1/is-authorized") => self.is_authorized(body),
+            ("POST", "/v1/validate") => {
+                self.pdp().metrics.validate_requests += 1;
+                json_reply(ffi::validate_json_str(body))
+            }
+            ("POST", "/v1/check-parse") => {
+                self.pdp().metrics.check_parse_requests += 1;
+                json_reply(ffi::check_parse_policy_set_json_str(body))
+            }
+            ("GET", "/health") => Reply::json(200, &json!({ "status": "ok" })),
+            ("GET", "/metrics") => Reply {
+                status: 200,
+                content_type: "text/plain; version=0.0.4",
+                body: self.pdp().metrics.render(),
+            },
+            (
+                _,
+                "/v1/is-authorized" | "/v1/validate" | "/v1/check-parse" | "/health" | "/metrics",
+            ) => Reply::error(405, &format!("method {method} is not allowed for {url}")),
+            _ => Reply::error(404, &format!("no endpoint {url}")),
+        }
+    }
+
+    fn is_authorized(&mut self, body: &str) -> Reply {
+        let has_schema = match self.preparse() {
+            Ok(has_schema) => has_schema,
+            Err(reply) => return reply,
+        };
+        let mut call: Value = match serde_json::from_str(body) {
+            Ok(call) => call,
+            Err(e) => return Reply::error(400, &e.to_string()),
+        };
+        let Some(fields) = call.as_object_mut() else {
+            return Reply::error(400, "expected a JSON object");
+        };
+        fields.insert("preparsedPolicySetId".into(), POLICY_SET_ID.into());
+        if has_schema {
+            fields.insert("preparsedSchemaName".into(), SCHEMA_NAME.into());
+        }
+        let call: StatefulAuthorizationCall = match serde_json::from_value(call) {
+            Ok(call) => call,
+            Err(e) => return Reply::error(400, &e.to_string()),
+        };
+        let answer = ffi::stateful_is_authorized(call);
+        let metrics = &mut self.pdp().metrics;
+        match &answer {
+            AuthorizationAnswer::Success { response, .. } => match response.decision() {
+                Decision::Allow => metrics.allow_decisions += 1,
+                Decision::Deny => metrics.deny_decisions += 1,
+            },
+            AuthorizationAnswer::Failure { .. } => metrics.authorization_failures += 1,
+        }
+        json_reply(serde_json::to_value(answer))
+    }
+
+    /// Count an authorization request, reload the policies if they changed,
+    /// and preparse them on this thread if this worker hasn't yet. Returns
+    /// whether there is a schema.
+    fn preparse(&mut self) -> std::result::Result<bool, Reply> {
+        let mut pdp = self.pdp();
+        pdp.metrics.is_authorized_requests += 1;
+        pdp.reload_if_changed();
+        let has_schema = pdp.schema.is_some();
+        if self.generation == Some(pdp.generation) {
+            return Ok(has_schema);
+        }
+        let generation = pdp.generation;
+        let policies = pdp.policies.clone();
+        let schema = pdp.schema.clone().filter(|_| self.generation.is_none());
+        drop(pdp);
+
+        let failed = |failure: &CheckParseAnswer| {
+            Reply::error(
+                500,
+                &format!(
+                    "failed to preparse the served policies: {}",
+                    serde_json::to_string(failure).unwrap_or_default()
+                ),
+            )
+        };
+        if let Some(schema) = schema {
+            if let failure @ CheckParseAnswer::Failure { .. } =
+                ffi::preparse_schema(SCHEMA_NAME.to_string(), schema)
+            {
+                return Err(failed(&failure));
+            }
+        }
+        if let failure @ CheckParseAnswer::Failure { .. } =
+            ffi::preparse_policy_set(POLICY_SET_ID.to_string(), policies)
+        {
+            return Err(failed(&failure));
+        }
+        self.generation = Some(generation);
+        Ok(has_schema)
+    }
+}
+
+/// Reads from a socket, failing with [`io::ErrorKind::TimedOut`] once
+/// `deadline` has passed. The read timeout of the socket is shortened before
+/// each read, so a client can't extend the deadline by sending a few bytes at
+/// a time.
+#[derive(Debug)]
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        let mut stream = self.stream;
+        match stream.read(buf) {
+            // Depending on the platform, a read timing out fails with either
+            // of these
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            result => result,
+        }
+    }
+}
+
+/// An HTTP request
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    url: String,
+    body: String,
+}
+
+/// Read an HTTP/1.x request from `reader`, with a body of at most
+/// `max_body_bytes`
+fn read_request(reader: impl Read, max_body_bytes: u64) -> std::result::Result<HttpRequest, Reply> {
+    let mut reader = BufReader::new(reader);
+    let mut head = (&mut reader).take(MAX_HEAD_BYTES);
+    let request_line = read_line(&mut head)?;
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(url), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Reply::error(400, "malformed request line"));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(Reply::error(505, &format!("unsupported version {version}")));
+    }
+    let mut body_length = None;
+    loop {
+        let line = read_line(&mut head)?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Reply::error(400, "malformed header"));
+        };
+        if name.eq_ignore_ascii_case("Content-Length") {
+            let length = value
+                .trim()
+                .parse()
+                .map_err(|_| Reply::error(400, "invalid Content-Length"))?;
+            body_length = Some(length);
+        } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+            return Err(Reply::error(
+                411,
+                "request bodies must have a Content-Length",
+            ));
+        }
+    }
+    let body = read_body(
+        reader.take(body_length.unwrap_or(0)),
+        body_length,
+        max_body_bytes,
+    )?;
+    Ok(HttpRequest {
+        method: method.to_string(),
+        url: url.to_string(),
+        body,
+    })
+}
+
+/// Read a line of the request line and headers, without its line ending
+fn read_line(head: &mut impl BufRead) -> std::result::Result<String, Reply> {
+    let mut line = String::new();
+    head.read_line(&mut line).map_err(|e| read_error(&e))?;
+    match line.strip_suffix('\n') {
+        Some(line) => Ok(line.strip_suffix('\r').unwrap_or(line).to_string()),
+        None => Err(Reply::error(
+            400,
+            &format!("request headers are incomplete or larger than {MAX_HEAD_BYTES} bytes"),
+        )),
+    }
+}
+
+/// Read a request body of at most `max_body_bytes` from `reader`, where
+/// `body_length` is the length declared by the request, if any
+fn read_body(
+    reader: impl Read,
+    body_length: Option<u64>,
+    max_body_bytes: u64,
+) -> std::result::Result<String, Reply> {
+    let too_large = || {
+        Reply::error(
+            413,
+            &format!("request body is larger than {max_body_bytes} bytes"),
+        )
+    };
+    if body_length.is_some_and(|len| len > max_body_bytes) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    reader
+        .take(max_body_bytes.saturating_add(1))
+        .read_to_string(&mut body)
+        .map_err(|e| read_error(&e))?;
+    if body.len() as u64 > max_body_bytes {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// The reply to a request which couldn't be read because of `e`
+fn read_error(e: &io::Error) -> Reply {
+    if e.kind() == io::ErrorKind::TimedOut {
+        Reply::error(408, "timed out reading the request")
+    } else {
+        Reply::error(400, &format!("failed to read request: {e}"))
+    }
+}
+
+/// Write `reply` as an HTTP/1.1 response, after which the connection is closed
+fn write_reply(mut writer: impl Write, reply: &Reply) -> io::Result<()> {
+    let reason = match reply.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        500 => "Internal Server Error",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        reply.status,
+        reply.content_type,
+        reply.body.len(),
+        reply.body
+    )?;
+    writer.flush()
+}
+
+/// A response to an HTTP request
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+/// Counters reported by `GET /metrics`
+#[derive(Debug, Default)]
+struct Metrics {
+    is_authorized_requests: u64,
+    validate_requests: u64,
+    check_parse_requests: u64,
+    allow_decisions: u64,
+    deny_decisions: u64,
+    authorization_failures: u64,
+    policy_reloads: u64,
+    policy_reload_failures: u64,
+    policy_files: usize,
+}
+
+/// The policy decision point's state, shared by the workers: the served
+/// policies and schema, and its metrics
+#[derive(Debug)]
+struct Pdp {
+    policies_dir: PathBuf,
+    schema: Option<ffi::Schema>,
+    reload_interval: Duration,
+    /// When the policies directory was last checked for changes
+    last_checked: Instant,
+    /// The policy files currently served, with their modification times
+    files: Vec<(PathBuf, SystemTime)>,
+    /// The policies currently served
+    policies: ffi::PolicySet,
+    /// Incremented whenever the served policies change, so that workers know
+    /// to preparse them again
+    generation: u64,
+    metrics: Metrics,
+}
+
+impl Pdp {
+    fn new(args: &ServeArgs) -> Result<Self> {
+        let schema = args
+            .schema_file
+            .as_deref()
+            .map(|path| read_schema(path, args.schema_format))
+            .transpose()?;
+        let files = policy_files(&args.policies_dir)?;
+        let policies = read_policies(&args.policies_dir, &files)?;
+        let metrics = Metrics {
+            policy_files: files.len(),
+            ..Metrics::default()
+        };
+        Ok(Self {
+            policies_dir: args.policies_dir.clone(),
+            schema,
+            reload_interval: Duration::from_secs(args.reload_interval),
+            last_checked: Instant::now(),
+            files,
+            policies,
+            generation: 0,
+            metrics,
+        })
+    }
+
+    /// Serve the policies in `files`, if they parse
+    fn load(&mut self, files: &[(PathBuf, SystemTime)]) -> Result<()> {
+        self.policies = read_policies(&self.policies_dir, files)?;
+        self.generation += 1;
+        self.metrics.policy_files = files.len();
+        Ok(())
+    }
+
+    /// Reload the policies if the reload interval has elapsed and they have
+    /// changed. If they no longer parse, keep serving the previous policies
+    /// until they change again.
+    fn reload_if_changed(&mut self) {
+        if self.last_checked.elapsed() < self.reload_interval {
+            return;
+        }
+        self.last_checked = Instant::now();
+        let result = policy_files(&self.policies_dir).and_then(|files| {
+            if files == self.files {
+                return Ok(false);
+            }
+            let loaded = self.load(&files);
+            self.files = files;
+            loaded.map(|()| true)
+        });
+        match result {
+            Ok(true) => self.metrics.policy_reloads += 1,
+            Ok(false) => (),
+            Err(e) => {
+                self.metrics.policy_reload_failures += 1;
+                eprintln!("{:?}", e.wrap_err("failed to reload policies"));
+            }
+        }
+    }
+}
+
+impl Metrics {
+    /// Render the metrics in the Prometheus text format
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        };
+        metric(
+            "cedar_requests_total",
+            "counter",
+            "Requests received, by endpoint",
+            &[
+                (
+                    "{endpoint=\"is-authorized\"}",
+                    self.is_authorized_requests.to_string(),
+                ),
+                (
+                    "{endpoint=\"validate\"}",
+                    self.validate_requests.to_string(),
+                ),
+                (
+                    "{endpoint=\"check-parse\"}",
+                    self.check_parse_requests.to_string(),
+                ),
+            ],
+        );
+        metric(
+            "cedar_decisions_total",
+            "counter",
+            "Authorization decisions, by decision",
+            &[
+                ("{decision=\"allow\"}", self.allow_decisions.to_string()),
+                ("{decision=\"deny\"}", self.deny_decisions.to_string()),
+            ],
+        );
+        metric(
+            "cedar_authorization_failures_total",
+            "counter",
+            "Authorization requests which could not be parsed or validated",
+            &[("", self.authorization_failures.to_string())],
+        );
+        metric(
+            "cedar_policy_reloads_total",
+            "counter",
+            "Reloads of changed policies",
+            &[("", self.policy_reloads.to_string())],
+        );
+        metric(
+            "cedar_policy_reload_failures_total",
+            "counter",
+            "Reloads of changed policies which failed to parse",
+            &[("", self.policy_reload_failures.to_string())],
+        );
+        metric(
+            "cedar_policy_files",
+            "gauge",
+            "Policy files currently served",
+            &[("", self.policy_files.to_string())],
+        );
+        out
+    }
+}
+
+fn json_reply(result: Result<impl ToString, serde_json::Error>) -> Reply {
+    match result {
+        Ok(answer) => Reply {
+            status: 200,
+            content_type: "application/json",
+            body: answer.to_string(),
+        },
+        Err(e) => Reply::error(400, &e.to_string()),
+    }
+}
+
+/// The `.cedar` files in `dir`, sorted by name, with their modification times
+fn policy_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>> {
+    let context = || format!("failed to read policies directory {}", dir.display());
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .into_diagnostic()
+        .wrap_err_with(context)?
+    {
+        let path = entry.into_diagnostic().wrap_err_with(context)?.path();
+        if path.extension().is_some_and(|ext| ext == "cedar") {
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .into_diagnostic()
+                .wrap_err_with(context)?;
+            files.push((path, modified));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read the policies in `files` of `dir`, checking that they parse
+fn read_policies(dir: &Path, files: &[(PathBuf, SystemTime)]) -> Result<ffi::PolicySet> {
+    let mut src = String::new();
+    for (path, _) in files {
+        src.push_str(&read_from_file_or_stdin(Some(path), "policy set")?);
+        src.push('\n');
+    }
+    let policies: ffi::PolicySet =
+        serde_json::from_value(json!({ "staticPolicies": src })).into_diagnostic()?;
+    match ffi::check_parse_policy_set(policies.clone()) {
+        CheckParseAnswer::Success => Ok(policies),
+        failure @ CheckParseAnswer::Failure { .. } => Err(miette::miette!(
+            "failed to parse policies in {}: {}",
+            dir.display(),
+            serde_json::to_string(&failure).unwrap_or_default()
+        )),
+    }
+}
+
+/// Read the schema in `path`, checking that it parses
+fn read_schema(path: &Path, format: SchemaFormat) -> Result<ffi::Schema> {
+    let src = read_from_file_or_stdin(Some(&path), "schema")?;
+    let schema: ffi::Schema = match format {
+        SchemaFormat::Cedar => serde_json::from_value(Value::String(src)),
+        SchemaFormat::Json => serde_json::from_str(&src),
+    }
+    .into_diagnostic()
+    .wrap_err_with(|| format!("failed to parse schema from file {}", path.display()))?;
+    match ffi::check_parse_schema(schema.clone()) {
+        CheckParseAnswer::Success => Ok(schema),
+        failure @ CheckParseAnswer::Failure { .. } => Err(miette::miette!(
+            "failed to parse schema from file {}: {}",
+            path.display(),
+            serde_json::to_string(&failure).unwrap_or_default()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE_VIEWS: &str =
+        r#"permit(principal == User::"alice", action == Action::"view", resource);"#;
+    const BOB_VIEWS: &str =
+        r#"permit(principal == User::"bob", action == Action::"view", resource);"#;
+
+    fn pdp(dir: &Path) -> Mutex<Pdp> {
+        Mutex::new(
+            Pdp::new(&ServeArgs {
+                policies_dir: dir.to_path_buf(),
+                schema_file: None,
+                schema_format: SchemaFormat::Cedar,
+                listen: String::new(),
+                reload_interval: 0,
+                workers: 1,
+                max_body_bytes: 1024,
+                read_timeout: 1,
+            })
+            .unwrap(),
+        )
+    }
+
+    fn authorize(worker: &mut Worker<'_>, principal: &str) -> Value {
+        let request = json!({
+            "principal": { "type": "User", "id": principal },
+            "action": { "type": "Action", "id": "view" },
+            "resource": { "type": "Photo", "id": "vacation" },
+            "context": {},
+            "entities": [],
+        });
+        let reply = worker.handle("POST", "/v1/is-authorized", &request.to_string());
+        assert_eq!(reply.status, 200, "{}", reply.body);
+        let answer: Value = serde_json::from_str(&reply.body).unwrap();
+        answer["response"]["decision"].clone()
+    }
+
+    #[test]
+    fn is_authorized_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alice.cedar"), ALICE_VIEWS).unwrap();
+        let pdp = pdp(dir.path());
+        let mut worker = Worker::new(&pdp);
+        assert_eq!(authorize(&mut worker, "alice"), "allow");
+        assert_eq!(authorize(&mut worker, "bob"), "deny");
+
+        std::fs::write(dir.path().join("bob.cedar"), BOB_VIEWS).unwrap();
+        assert_eq!(authorize(&mut worker, "bob"), "allow");
+        assert_eq!(pdp.lock().unwrap().metrics.policy_reloads, 1);
+
+        // Policies which don't parse are not served
+        std::fs::write(dir.path().join("broken.cedar"), "permit(").unwrap();
+        assert_eq!(authorize(&mut worker, "bob"), "allow");
+        assert_eq!(authorize(&mut worker, "bob"), "allow");
+        assert_eq!(pdp.lock().unwrap().metrics.policy_reload_failures, 1);
+        assert_eq!(pdp.lock().unwrap().metrics.policy_files, 2);
+
+        let metrics = worker.handle("GET", "/metrics", "");
+        assert!(metrics
+            .body
+            .contains("cedar_requests_total{endpoint=\"is-authorized\"} 5\n"));
+        assert!(metrics
+            .body
+            .contains("cedar_decisions_total{decision=\"allow\"} 4\n"));
+        assert!(metrics.body.contains("cedar_policy_reloads_total 1\n"));
+    }
+
+    #[test]
+    fn workers_preparse_reloaded_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alice.cedar"), ALICE_VIEWS).unwrap();
+        let pdp = pdp(dir.path());
+        let mut worker = Worker::new(&pdp);
+        assert_eq!(authorize(&mut worker, "bob"), "deny");
+
+        // the policies are reloaded by a worker on another thread...
+        std::fs::write(dir.path().join("bob.cedar"), BOB_VIEWS).unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(authorize(&mut Worker::new(&pdp), "bob"), "allow"));
+        });
+        assert_eq!(pdp.lock().unwrap().metrics.policy_reloads, 1);
+        // ...and preparsed again by this one
+        assert_eq!(authorize(&mut worker, "bob"), "allow");
+    }
+
+    #[test]
+    fn endpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("alice.cedar"), ALICE_VIEWS).unwrap();
+        let pdp = pdp(dir.path());
+        let mut worker = Worker::new(&pdp);
+
+        let reply = worker.handle("GET", "/health", "");
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body, r#"{"status":"ok"}"#);
+
+        let reply = worker.handle(
+            "POST",
+            "/v1/check-parse",
+            &json!({ "staticPolicies": "permit(" }).to_string(),
+        );
+        assert_eq!(reply.status, 200);
+        let answer: Value = serde_json::from_str(&reply.body).unwrap();
+        assert_eq!(answer["type"], "failure");
+
+        let reply = worker.handle("POST", "/v1/is-authorized", "not json");
+        assert_eq!(reply.status, 400);
+        let reply = worker.handle("GET", "/v1/is-authorized", "");
+        assert_eq!(reply.status, 405);
+        let reply = worker.handle("GET", "/v2/is-authorized", "");
+        assert_eq!(reply.status, 404);
+
+        let mut written = Vec::new();
+        write_reply(&mut written, &reply).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert!(
+            written.starts_with("HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n"),
+            "{written}"
+        );
+        assert!(
+            written.ends_with(&format!("\r\n\r\n{}", reply.body)),
+            "{written}"
+        );
+    }
+
+    #[test]
+    fn requests() {
+        let request = read_request(
+            "POST /v1/check-parse HTTP/1.1\r\nHost: localhost\r\ncontent-length: 2\r\n\r\n{}"
+                .as_bytes(),
+            2,
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "/v1/check-parse");
+        assert_eq!(request.body, "{}");
+        // without a length, a request has no body
+        let request = read_request("GET /health HTTP/1.1\r\n\r\n{}".as_bytes(), 2).unwrap();
+        assert_eq!(request.body, "");
+
+        let status = |src: &str| read_request(src.as_bytes(), 2).unwrap_err().status;
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\n{ }"),
+            413
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nContent-Length: two\r\n\r\n"),
+            400
+        );
+        assert_eq!(
+            status("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"),
+            411
+        );
+        assert_eq!(status("GET / HTTP/2\r\n\r\n"), 505);
+        assert_eq!(status("GET /\r\n\r\n"), 400);
+        assert_eq!(status("GET / HTTP/1.1\r\nHost: local"), 400);
+        let large_header = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(16 * 1024));
+        assert_eq!(status(&large_header), 400);
+    }
+
+    #[test]
+    fn body_limits() {
+        let body = read_body("{}".as_bytes(), Some(2), 2).unwrap();
+        assert_eq!(body, "{}");
+        // a declared length over the limit is rejected without reading
+        let reply = read_body("{}".as_bytes(), Some(3), 2).unwrap_err();
+        assert_eq!(reply.status, 413);
+        // as is a body over the limit without a declared length
+        let reply = read_body("{ }".as_bytes(), None, 2).unwrap_err();
+        assert_eq!(reply.status, 413);
+        let reply = read_body([0xff].as_slice(), None, 2).unwrap_err();
+        assert_eq!(reply.status, 400);
+    }
+
+    #[test]
+    fn slow_requests_time_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdp = pdp(dir.path());
+        let mut worker = Worker::new(&pdp);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = Limits {
+            max_body_bytes: 1024,
+            read_timeout: Duration::from_millis(500),
+        };
+
+        // a body which never arrives
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"POST /v1/check-parse HTTP/1.1\r\nContent-Length: 10\r\n\r\n{")
+                .unwrap();
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).unwrap();
+            reply
+        });
+        let (stream, _) = listener.accept().unwrap();
+        worker.answer(&stream, &limits);
+        drop(stream);
+        let reply = client.join().unwrap();
+        assert!(
+            reply.starts_with("HTTP/1.1 408 Request Timeout\r\n"),
+            "{reply}"
+        );
+
+        // headers sent a byte at a time, each within the read timeout, still
+        // have to arrive before the deadline
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            for byte in b"GET /health HTTP/1.1\r\nX-Slow: aaaaaaaaaaaaaaaaaaaa" {
+                if stream.write_all(&[*byte]).is_err() {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let start = Instant::now();
+        worker.answer(&stream, &limits);
+        assert!(start.elapsed() < Duration::from_secs(2));
+        drop(stream);
+        client.join().unwrap();
+    }
+}
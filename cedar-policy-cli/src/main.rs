@@ -21,7 +21,7 @@ use miette::ErrorHook;
 
 use cedar_policy_cli::{
     authorize, check_parse, evaluate, format_policies, language_version, link, new,
-    partial_authorize, run_tests, symcc, tpe, translate_policy, translate_schema, validate,
+    partial_authorize, run_tests, serve, symcc, tpe, translate_policy, translate_schema, validate,
     visualize, CedarExitCode, Cli, Commands, ErrorFormat,
 };
 
@@ -58,6 +58,7 @@ fn main() -> CedarExitCode {
         Commands::Tpe(args) => tpe(&args),
         Commands::RunTests(args) => run_tests(&args),
         Commands::Symcc(args) => symcc(&args),
+        Commands::Serve(args) => serve(&args),
         Commands::LanguageVersion => language_version(),
     }
 }
@@ -43,6 +43,7 @@ pub use policy::*;
 mod policy_set;
 pub use policy_set::*;
 mod request;
+mod scope_index;
 pub use request::*;
 mod restricted_expr;
 pub use restricted_expr::*;
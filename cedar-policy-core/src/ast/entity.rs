@@ -466,6 +466,78 @@ impl<'a> arbitrary::Arbitrary<'a> for Eid {
     }
 }
 
+/// Opaque metadata describing where the data for an [`Entity`] came from,
+/// e.g., for citing data freshness in authorization diagnostics.
+///
+/// Provenance has no effect on authorization.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EntityProvenance {
+    /// The system the entity data was loaded from
+    source: Option<SmolStr>,
+    /// When the entity data was fetched from `source`
+    fetched_at: Option<std::time::SystemTime>,
+    /// The version of the entity data, in whatever form `source` uses
+    version: Option<SmolStr>,
+    /// Any other metadata
+    metadata: BTreeMap<SmolStr, SmolStr>,
+}
+
+impl EntityProvenance {
+    /// Create empty provenance metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the system the entity data was loaded from
+    pub fn with_source(mut self, source: impl Into<SmolStr>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Set when the entity data was fetched
+    pub fn with_fetched_at(mut self, fetched_at: std::time::SystemTime) -> Self {
+        self.fetched_at = Some(fetched_at);
+        self
+    }
+
+    /// Set the version of the entity data
+    pub fn with_version(mut self, version: impl Into<SmolStr>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Add an arbitrary key-value pair, replacing any previous value for `key`
+    pub fn with_metadata(mut self, key: impl Into<SmolStr>, value: impl Into<SmolStr>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// The system the entity data was loaded from, if known
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// When the entity data was fetched, if known
+    pub fn fetched_at(&self) -> Option<std::time::SystemTime> {
+        self.fetched_at
+    }
+
+    /// The version of the entity data, if known
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Get the value for an arbitrary metadata key
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(SmolStr::as_str)
+    }
+
+    /// Iterate over the arbitrary key-value metadata, sorted by key
+    pub fn metadata_iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.metadata.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
 /// Entity datatype
 #[derive(Debug, Clone)]
 pub struct Entity {
@@ -492,6 +564,10 @@ pub struct Entity {
     /// Like for `attrs`, we use a `BTreeMap` so that the tags have a
     /// deterministic order.
     tags: BTreeMap<SmolStr, PartialValue>,
+
+    /// Where the data for this `Entity` came from, if known. Not considered
+    /// by equality or [`Entity::deep_eq()`].
+    provenance: Option<Arc<EntityProvenance>>,
 }
 
 impl std::hash::Hash for Entity {
@@ -539,6 +615,7 @@ impl Entity {
             indirect_ancestors,
             parents,
             tags: evaluated_tags,
+            provenance: None,
         })
     }
 
@@ -559,6 +636,7 @@ impl Entity {
             indirect_ancestors,
             parents,
             tags: tags.into_iter().collect(),
+            provenance: None,
         }
     }
 
@@ -645,9 +723,21 @@ impl Entity {
             indirect_ancestors: HashSet::new(),
             parents: HashSet::new(),
             tags: BTreeMap::new(),
+            provenance: None,
         }
     }
 
+    /// Get the provenance metadata attached to this entity, if any
+    pub fn provenance(&self) -> Option<&EntityProvenance> {
+        self.provenance.as_deref()
+    }
+
+    /// Attach provenance metadata to this entity, replacing any previously
+    /// attached
+    pub fn set_provenance(&mut self, provenance: Arc<EntityProvenance>) {
+        self.provenance = Some(provenance);
+    }
+
     /// Test if two `Entity` objects are deep/structurally equal.
     /// That is, not only do they have the same UID, but also the same
     /// attributes, attribute values, and ancestors/parents.
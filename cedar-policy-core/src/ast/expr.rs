@@ -22,17 +22,19 @@ use {
 
 use crate::{
     ast::*,
+    entities::SchemaType,
     expr_builder::{self, ExprBuilder as _},
     extensions::Extensions,
     parser::{err::ParseErrors, Loc},
 };
 use educe::Educe;
 use miette::Diagnostic;
+use nonempty::{nonempty, NonEmpty};
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 use std::{
     borrow::Cow,
-    collections::{btree_map, BTreeMap, HashMap},
+    collections::{btree_map, BTreeMap, HashMap, HashSet},
     hash::{Hash, Hasher},
     mem,
     sync::Arc,
@@ -48,14 +50,54 @@ extern crate tsify;
 /// where the expression was written in policy source code, and some generic
 /// data which is stored on each node of the AST.
 /// Cloning is O(1).
-#[derive(Educe, Debug, Clone)]
-#[educe(PartialEq, Eq, Hash)]
+#[derive(Educe, Clone)]
+#[educe(Debug, PartialEq, Eq, Hash)]
 pub struct Expr<T = ()> {
     expr_kind: ExprKind<T>,
     #[educe(PartialEq(ignore))]
     #[educe(Hash(ignore))]
     source_loc: Option<Loc>,
     data: T,
+    /// If this is a set literal of literals, the set of them, built when the
+    /// expression is rather than each time it is evaluated
+    #[educe(Debug(ignore))]
+    #[educe(PartialEq(ignore))]
+    #[educe(Hash(ignore))]
+    literal_set: Option<Arc<LiteralSet>>,
+}
+
+/// The elements of a set literal whose elements are all literals
+#[derive(Debug)]
+pub(crate) struct LiteralSet {
+    /// The set, with its hashed representation
+    pub(crate) set: Set,
+    /// The elements, if they are all entities, so that `in` is a hash lookup
+    /// of the entity and its ancestors
+    pub(crate) entities: Option<HashSet<EntityUID>>,
+}
+
+impl LiteralSet {
+    /// The set of `items`, if they are all literals
+    fn new<T>(items: &[Expr<T>]) -> Option<Self> {
+        let lits = items
+            .iter()
+            .map(|item| match item.expr_kind() {
+                ExprKind::Lit(lit) => Some(lit.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let entities = lits
+            .iter()
+            .map(|lit| match lit {
+                Literal::EntityUID(uid) => Some(uid.as_ref().clone()),
+                _ => None,
+            })
+            .collect();
+        Some(Self {
+            set: Set::from_lits(lits),
+            entities,
+        })
+    }
 }
 
 /// The possible expression variants. This enum should be matched on by code
@@ -232,10 +274,15 @@ impl From<PartialValue> for Expr {
 
 impl<T> Expr<T> {
     pub(crate) fn new(expr_kind: ExprKind<T>, source_loc: Option<Loc>, data: T) -> Self {
+        let literal_set = match &expr_kind {
+            ExprKind::Set(items) => LiteralSet::new(items).map(Arc::new),
+            _ => None,
+        };
         Self {
             expr_kind,
             source_loc,
             data,
+            literal_set,
         }
     }
 
@@ -268,6 +315,12 @@ impl<T> Expr<T> {
         (self.expr_kind, self.source_loc, self.data)
     }
 
+    /// If this is a set literal whose elements are all literals, the set of
+    /// them
+    pub(crate) fn literal_set(&self) -> Option<&LiteralSet> {
+        self.literal_set.as_deref()
+    }
+
     /// Access the `Loc` stored on the `Expr`.
     pub fn source_loc(&self) -> Option<&Loc> {
         self.source_loc.as_ref()
@@ -321,7 +374,7 @@ impl<T> Expr<T> {
         self.subexpressions()
             .filter_map(|exp| match &exp.expr_kind {
                 ExprKind::Slot(slotid) => Some(Slot {
-                    id: *slotid,
+                    id: slotid.clone(),
                     loc: exp.source_loc().cloned(),
                 }),
                 _ => None,
@@ -405,11 +458,16 @@ impl<T> Expr<T> {
                 op: BinaryOp::HasTag,
                 ..
             } => Some(Type::Bool),
+            #[cfg(feature = "maps")]
+            ExprKind::BinaryApp {
+                op: BinaryOp::ContainsKey,
+                ..
+            } => Some(Type::Bool),
             ExprKind::ExtensionFunctionApp { fn_name, .. } => extensions
                 .func(fn_name)
                 .ok()?
                 .return_type()
-                .map(|rty| rty.clone().into()),
+                .and_then(|rty| rty.clone().try_into().ok()),
             // We could try to be more complete here, but we can't do all that
             // much better without evaluating the argument. Even if we know it's
             // a record `Type::Record` tells us nothing about the type of the
@@ -420,6 +478,11 @@ impl<T> Expr<T> {
                 op: BinaryOp::GetTag,
                 ..
             } => None,
+            #[cfg(feature = "maps")]
+            ExprKind::BinaryApp {
+                op: BinaryOp::GetKey,
+                ..
+            } => None,
             ExprKind::HasAttr { .. } => Some(Type::Bool),
             ExprKind::Like { .. } => Some(Type::Bool),
             ExprKind::Is { .. } => Some(Type::Bool),
@@ -679,6 +742,20 @@ impl Expr {
         ExprBuilder::new().has_tag(expr, tag)
     }
 
+    /// Create a `get` expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    pub fn get_key(expr: Expr, key: Expr) -> Self {
+        ExprBuilder::new().get_key(expr, key)
+    }
+
+    /// Create a `containsKey` expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    pub fn contains_key(expr: Expr, key: Expr) -> Self {
+        ExprBuilder::new().contains_key(expr, key)
+    }
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     pub fn set(exprs: impl IntoIterator<Item = Expr>) -> Self {
         ExprBuilder::new().set(exprs)
@@ -794,6 +871,21 @@ impl Expr {
         self.substitute_general::<TypedSubstitution>(definitions)
     }
 
+    /// Substitute unknowns with concrete values, like [`Expr::substitute`],
+    /// and replace the unknown which stands for the `context` variable (named
+    /// [`CONTEXT_UNKNOWN_NAME`]) with the variable itself, so that evaluating
+    /// the result uses whatever is known of the context at that point.
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn substitute_with_context_var(
+        &self,
+        definitions: &HashMap<SmolStr, Value>,
+    ) -> Expr {
+        match self.substitute_general::<ContextVarSubstitution>(definitions) {
+            Ok(e) => e,
+            Err(empty) => match empty {},
+        }
+    }
+
     /// Substitute unknowns with values
     ///
     /// Generic over the function implementing the substitution to allow for multiple error behaviors
@@ -879,6 +971,88 @@ impl Expr {
             ExprKind::Error { .. } => Ok(self.clone()),
         }
     }
+
+    /// Replace value slots with the expressions they are bound to in
+    /// `values`, keeping the source locations of the rest of the expression.
+    ///
+    /// Slots not bound in `values` are left in place.
+    pub fn substitute_value_slots(&self, values: &HashMap<SlotId, RestrictedExpr>) -> Expr {
+        let sub = |e: &Arc<Expr>| Arc::new(e.substitute_value_slots(values));
+        let kind = match self.expr_kind() {
+            ExprKind::Slot(slot) => match values.get(slot) {
+                Some(value) => {
+                    return Expr::from(value.clone())
+                        .with_maybe_source_loc(self.source_loc().cloned())
+                }
+                None => return self.clone(),
+            },
+            ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Unknown(_) => return self.clone(),
+            #[cfg(feature = "tolerant-ast")]
+            ExprKind::Error { .. } => return self.clone(),
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => ExprKind::If {
+                test_expr: sub(test_expr),
+                then_expr: sub(then_expr),
+                else_expr: sub(else_expr),
+            },
+            ExprKind::And { left, right } => ExprKind::And {
+                left: sub(left),
+                right: sub(right),
+            },
+            ExprKind::Or { left, right } => ExprKind::Or {
+                left: sub(left),
+                right: sub(right),
+            },
+            ExprKind::UnaryApp { op, arg } => ExprKind::UnaryApp {
+                op: *op,
+                arg: sub(arg),
+            },
+            ExprKind::BinaryApp { op, arg1, arg2 } => ExprKind::BinaryApp {
+                op: *op,
+                arg1: sub(arg1),
+                arg2: sub(arg2),
+            },
+            ExprKind::ExtensionFunctionApp { fn_name, args } => ExprKind::ExtensionFunctionApp {
+                fn_name: fn_name.clone(),
+                args: Arc::new(
+                    args.iter()
+                        .map(|e| e.substitute_value_slots(values))
+                        .collect(),
+                ),
+            },
+            ExprKind::GetAttr { expr, attr } => ExprKind::GetAttr {
+                expr: sub(expr),
+                attr: attr.clone(),
+            },
+            ExprKind::HasAttr { expr, attr } => ExprKind::HasAttr {
+                expr: sub(expr),
+                attr: attr.clone(),
+            },
+            ExprKind::Like { expr, pattern } => ExprKind::Like {
+                expr: sub(expr),
+                pattern: pattern.clone(),
+            },
+            ExprKind::Is { expr, entity_type } => ExprKind::Is {
+                expr: sub(expr),
+                entity_type: entity_type.clone(),
+            },
+            ExprKind::Set(members) => ExprKind::Set(Arc::new(
+                members
+                    .iter()
+                    .map(|e| e.substitute_value_slots(values))
+                    .collect(),
+            )),
+            ExprKind::Record(map) => ExprKind::Record(Arc::new(
+                map.iter()
+                    .map(|(k, e)| (k.clone(), e.substitute_value_slots(values)))
+                    .collect(),
+            )),
+        };
+        Expr::new(kind, self.source_loc().cloned(), ())
+    }
 }
 
 /// A trait for customizing the error behavior of substitution
@@ -899,15 +1073,15 @@ impl SubstitutionFunction for TypedSubstitution {
     type Err = SubstitutionError;
 
     fn substitute(value: &Unknown, substitute: Option<&Value>) -> Result<Expr, Self::Err> {
-        match (substitute, &value.type_annotation) {
+        match (substitute, value.possible_types()) {
             (None, _) => Ok(Expr::unknown(value.clone())),
             (Some(v), None) => Ok(v.clone().into()),
-            (Some(v), Some(t)) => {
-                if v.type_of() == *t {
+            (Some(v), Some(tys)) => {
+                if tys.contains(&v.type_of()) {
                     Ok(v.clone().into())
                 } else {
                     Err(SubstitutionError::TypeError {
-                        expected: t.clone(),
+                        expected: tys.head,
                         actual: v.type_of(),
                     })
                 }
@@ -928,6 +1102,22 @@ impl SubstitutionFunction for UntypedSubstitution {
     }
 }
 
+#[cfg(feature = "partial-eval")]
+struct ContextVarSubstitution {}
+
+#[cfg(feature = "partial-eval")]
+impl SubstitutionFunction for ContextVarSubstitution {
+    type Err = std::convert::Infallible;
+
+    fn substitute(value: &Unknown, substitute: Option<&Value>) -> Result<Expr, Self::Err> {
+        match substitute {
+            Some(v) => Ok(v.clone().into()),
+            None if value.name == CONTEXT_UNKNOWN_NAME => Ok(Expr::var(Var::Context)),
+            None => Ok(Expr::unknown(value.clone())),
+        }
+    }
+}
+
 impl<T: Clone> std::fmt::Display for Expr<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // To avoid code duplication between pretty-printers for AST Expr and EST Expr,
@@ -975,6 +1165,10 @@ pub struct Unknown {
     /// If `None`, we have no type annotation, and thus a value of any type can
     /// be substituted.
     pub type_annotation: Option<Type>,
+    /// The schema type of the values that can be substituted in for the
+    /// unknown, if it was declared with one. This refines `type_annotation`,
+    /// e.g., with the attributes of a record or the element type of a set.
+    pub schema_type: Option<SchemaType>,
 }
 
 impl Unknown {
@@ -983,6 +1177,7 @@ impl Unknown {
         Self {
             name: name.into(),
             type_annotation: None,
+            schema_type: None,
         }
     }
 
@@ -992,6 +1187,32 @@ impl Unknown {
         Self {
             name: name.into(),
             type_annotation: Some(ty),
+            schema_type: None,
+        }
+    }
+
+    /// Create a new `Unknown` with a schema type. (Only values conforming to
+    /// the given type can be substituted.)
+    pub fn new_with_schema_type(name: impl Into<SmolStr>, ty: SchemaType) -> Self {
+        let runtime_types = ty.runtime_types();
+        Self {
+            name: name.into(),
+            type_annotation: if runtime_types.len() == 1 {
+                Some(runtime_types.head)
+            } else {
+                None
+            },
+            schema_type: Some(ty),
+        }
+    }
+
+    /// The runtime types of the values that can be substituted in for the
+    /// unknown, or `None` if a value of any type can be substituted.
+    pub fn possible_types(&self) -> Option<NonEmpty<Type>> {
+        match (&self.schema_type, &self.type_annotation) {
+            (Some(ty), _) => Some(ty.runtime_types()),
+            (None, Some(ty)) => Some(nonempty![ty.clone()]),
+            (None, None) => None,
         }
     }
 }
@@ -1250,6 +1471,28 @@ impl<T: Default + Clone> expr_builder::ExprBuilder for ExprBuilder<T> {
         })
     }
 
+    /// Create a 'get' expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    fn get_key(self, expr: Expr<T>, key: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::GetKey,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(key),
+        })
+    }
+
+    /// Create a 'containsKey' expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    fn contains_key(self, expr: Expr<T>, key: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::ContainsKey,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(key),
+        })
+    }
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     fn set(self, exprs: impl IntoIterator<Item = Expr<T>>) -> Expr<T> {
         self.with_expr_kind(ExprKind::Set(Arc::new(exprs.into_iter().collect())))
@@ -1482,13 +1725,16 @@ impl<T> Expr<T> {
             (Var(v), Var(v1)) => v == v1,
             (Slot(s), Slot(s1)) => s == s1,
             (
+                // Like `cmp_shape()`, this ignores the schema type of the unknowns
                 Unknown(self::Unknown {
                     name: name1,
                     type_annotation: ta_1,
+                    ..
                 }),
                 Unknown(self::Unknown {
                     name: name2,
                     type_annotation: ta_2,
+                    ..
                 }),
             ) => (name1 == name2) && (ta_1 == ta_2),
             (
@@ -1601,7 +1847,10 @@ impl<T> Expr<T> {
             ExprKind::Lit(lit) => lit.hash(state),
             ExprKind::Var(v) => v.hash(state),
             ExprKind::Slot(s) => s.hash(state),
-            ExprKind::Unknown(u) => u.hash(state),
+            ExprKind::Unknown(u) => {
+                u.name.hash(state);
+                u.type_annotation.hash(state);
+            }
             ExprKind::If {
                 test_expr,
                 then_expr,
@@ -1687,13 +1936,17 @@ impl<T> Expr<T> {
             (Var(v), Var(v1)) => v.cmp(v1),
             (Slot(s), Slot(s1)) => s.cmp(s1),
             (
+                // The schema type of an unknown has no ordering, so unknowns
+                // are compared only by their name and type annotation
                 Unknown(self::Unknown {
                     name: name1,
                     type_annotation: ta_1,
+                    ..
                 }),
                 Unknown(self::Unknown {
                     name: name2,
                     type_annotation: ta_2,
+                    ..
                 }),
             ) => name1.cmp(name2).then_with(|| ta_1.cmp(ta_2)),
             (
@@ -2056,7 +2309,7 @@ mod test {
         let e = Expr::slot(SlotId::principal());
         let p = SlotId::principal();
         let r = SlotId::resource();
-        let set: HashSet<SlotId> = HashSet::from_iter([p]);
+        let set: HashSet<SlotId> = HashSet::from_iter([p.clone()]);
         assert_eq!(set, e.slots().map(|slot| slot.id).collect::<HashSet<_>>());
         let e = Expr::or(
             Expr::slot(SlotId::principal()),
@@ -2319,6 +2572,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            schema_type: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -2332,6 +2586,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Long),
+            schema_type: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -2345,6 +2600,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            schema_type: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -2358,6 +2614,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            schema_type: None,
         };
         let r = UntypedSubstitution::substitute(&u, None);
         match r {
@@ -2371,6 +2628,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            schema_type: None,
         };
         let e = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap();
         assert_eq!(e, Expr::val(1));
@@ -2381,6 +2639,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Long),
+            schema_type: None,
         };
         let e = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap();
         assert_eq!(e, Expr::val(1));
@@ -2391,6 +2650,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            schema_type: None,
         };
         let r = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap_err();
         assert_matches!(
@@ -2407,6 +2667,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            schema_type: None,
         };
         let r = TypedSubstitution::substitute(&u, None).unwrap();
         assert_eq!(r, Expr::unknown(u));
@@ -273,6 +273,28 @@ impl<T: Default + Clone> expr_builder::ExprBuilder for ExprWithErrsBuilder<T> {
         })
     }
 
+    /// Create a 'get' expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    fn get_key(self, expr: Expr<T>, key: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::GetKey,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(key),
+        })
+    }
+
+    /// Create a 'containsKey' expression.
+    /// `expr` must evaluate to Record type, `key` must evaluate to String type.
+    #[cfg(feature = "maps")]
+    fn contains_key(self, expr: Expr<T>, key: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::ContainsKey,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(key),
+        })
+    }
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     fn set(self, exprs: impl IntoIterator<Item = Expr<T>>) -> Expr<T> {
         self.with_expr_kind(ExprKind::Set(Arc::new(exprs.into_iter().collect())))
@@ -15,14 +15,18 @@
  */
 
 use crate::ast::*;
-use crate::entities::SchemaType;
+use crate::entities::{conformance::typecheck_value_against_schematype, SchemaType};
 use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+use crate::extensions::Extensions;
 use std::any::Any;
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
 use std::panic::{RefUnwindSafe, UnwindSafe};
 use std::sync::Arc;
 
+use smol_str::SmolStr;
+
 /// Cedar extension.
 ///
 /// An extension can define new types and functions on those types. (Currently,
@@ -134,6 +138,18 @@ pub type BinaryExtensionFunctionObject = extension_function_object!(&Value, &Val
 pub type TernaryExtensionFunctionObject = extension_function_object!(&Value, &Value, &Value);
 /// Trait object that implements the extension function call that takes one argument, followed by a variadic number of arguments.
 pub type VariadicExtensionFunctionObject = extension_function_object!(&Value, &[Value]);
+/// Trait object that implements a custom extension function; see [`ExtensionFunction::custom()`].
+pub type CustomExtensionFunctionObject =
+    Box<dyn Fn(&[Value]) -> std::result::Result<Value, String> + Sync + Send + 'static>;
+/// Trait object that tries to determine the result of an extension function
+/// call from its first arguments; see [`ExtensionFunction::with_short_circuit()`].
+pub type ShortCircuitFunctionObject =
+    Box<dyn Fn(&[Value]) -> Option<evaluator::Result<Value>> + Sync + Send + 'static>;
+/// Trait object that tries to determine the result of a custom extension
+/// function call from its first arguments; see
+/// [`ExtensionFunction::with_custom_short_circuit()`].
+pub type CustomShortCircuitFunctionObject =
+    Box<dyn Fn(&[Value]) -> Option<std::result::Result<Value, String>> + Sync + Send + 'static>;
 
 /// Extension function. These can be called by the given `name` in Ceder
 /// expressions.
@@ -158,6 +174,12 @@ pub struct ExtensionFunction {
     /// Whether this is a variadic function or not. If it is a variadic function it can accept 1 or more arguments
     /// of the last argument type.
     is_variadic: bool,
+    /// A short description of the function, for editors and other tools
+    doc: Option<SmolStr>,
+    /// If present, the arguments of this function are evaluated lazily, and
+    /// this is consulted before each argument after the first; see
+    /// [`ExtensionFunction::with_short_circuit()`]
+    short_circuit: Option<ShortCircuitFunctionObject>,
 }
 
 impl ExtensionFunction {
@@ -177,9 +199,84 @@ impl ExtensionFunction {
             return_type,
             arg_types,
             is_variadic,
+            doc: None,
+            short_circuit: None,
         }
     }
 
+    /// Attach a short description of the function, for editors and other
+    /// tools. This has no effect on evaluation.
+    #[must_use]
+    pub fn with_doc(mut self, doc: impl Into<SmolStr>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Make the arguments of this function lazy, so that it can short-circuit
+    /// like `&&` and `||`.
+    ///
+    /// The arguments of a call are evaluated left to right, and before each
+    /// argument after the first, `short_circuit` is called with the values of
+    /// the arguments evaluated so far. If it returns `Some`, that is the
+    /// result of the call, and the remaining arguments are never evaluated: an
+    /// expensive argument costs nothing, and an argument which would produce
+    /// an error produces none. Otherwise evaluation continues, and once all
+    /// arguments are evaluated the function is called as usual.
+    ///
+    /// `short_circuit` must agree with the function: whenever it returns a
+    /// result, calling the function with those arguments followed by any
+    /// valid remaining arguments must return the same result. Any value it
+    /// returns must have the function's return type.
+    #[must_use]
+    pub fn with_short_circuit(mut self, short_circuit: ShortCircuitFunctionObject) -> Self {
+        self.short_circuit = Some(short_circuit);
+        self
+    }
+
+    /// Make the arguments of a function created with
+    /// [`ExtensionFunction::custom()`] lazy; see
+    /// [`ExtensionFunction::with_short_circuit()`].
+    ///
+    /// Like the function itself, any value returned by `short_circuit` is
+    /// checked against the function's return type, and an `Err` becomes an
+    /// evaluation error with that message.
+    #[must_use]
+    pub fn with_custom_short_circuit(
+        self,
+        short_circuit: CustomShortCircuitFunctionObject,
+    ) -> Self {
+        let name = self.name.clone();
+        let expected_return_type = self.return_type.clone();
+        self.with_short_circuit(Box::new(move |known_args: &[Value]| {
+            let err = |kind, msg| {
+                evaluator::EvaluationError::failed_extension_function_application(
+                    name.clone(),
+                    kind,
+                    msg,
+                    None, // evaluator will add the source location later
+                    None,
+                )
+            };
+            let output = match short_circuit(known_args)? {
+                Ok(output) => output,
+                Err(msg) => return Some(Err(err(ExtensionErrorKind::Custom, msg))),
+            };
+            if let Some(expected_return_type) = &expected_return_type {
+                if let Err(type_err) = typecheck_value_against_schematype(
+                    &output.clone().into(),
+                    expected_return_type,
+                    Extensions::all_available(),
+                ) {
+                    return Some(Err(err(
+                        ExtensionErrorKind::InvalidReturnValue,
+                        format!("invalid return value: {type_err}"),
+                    )));
+                }
+            }
+            Some(Ok(output))
+        }))
+    }
+
     /// Create a new `ExtensionFunction` taking no arguments
     pub fn nullary(
         name: Name,
@@ -344,6 +441,80 @@ impl ExtensionFunction {
         )
     }
 
+    /// Create a new `ExtensionFunction` taking exactly `arg_types.len()`
+    /// arguments, for a custom extension registered with
+    /// [`crate::extensions::register_custom_extension()`].
+    ///
+    /// Unlike the other constructors, `func` is only called with arguments
+    /// conforming to `arg_types`, and any value it returns is checked against
+    /// `return_type`. This way a misbehaving custom function results in an
+    /// evaluation error rather than an ill-typed value. An `Err` returned by
+    /// `func` becomes an evaluation error with that message.
+    pub fn custom(
+        name: Name,
+        style: CallStyle,
+        func: CustomExtensionFunctionObject,
+        return_type: SchemaType,
+        arg_types: Vec<SchemaType>,
+    ) -> Self {
+        let expected_arg_types = arg_types.clone();
+        let expected_return_type = return_type.clone();
+        Self::new(
+            name.clone(),
+            style,
+            Box::new(move |args: &[Value]| {
+                if args.len() != expected_arg_types.len() {
+                    return Err(evaluator::EvaluationError::wrong_num_arguments(
+                        name.clone(),
+                        expected_arg_types.len(),
+                        args.len(),
+                        None, // evaluator will add the source location later
+                    ));
+                }
+                let extensions = Extensions::all_available();
+                for (i, (arg, ty)) in args.iter().zip(&expected_arg_types).enumerate() {
+                    typecheck_value_against_schematype(&arg.clone().into(), ty, extensions)
+                        .map_err(|err| {
+                            evaluator::EvaluationError::failed_extension_function_application(
+                                name.clone(),
+                                ExtensionErrorKind::WrongArgumentType,
+                                format!("argument {}: {err}", i + 1),
+                                None, // evaluator will add the source location later
+                                None,
+                            )
+                        })?;
+                }
+                let output = func(args).map_err(|msg| {
+                    evaluator::EvaluationError::failed_extension_function_application(
+                        name.clone(),
+                        ExtensionErrorKind::Custom,
+                        msg,
+                        None, // evaluator will add the source location later
+                        None,
+                    )
+                })?;
+                typecheck_value_against_schematype(
+                    &output.clone().into(),
+                    &expected_return_type,
+                    extensions,
+                )
+                .map_err(|err| {
+                    evaluator::EvaluationError::failed_extension_function_application(
+                        name.clone(),
+                        ExtensionErrorKind::InvalidReturnValue,
+                        format!("invalid return value: {err}"),
+                        None, // evaluator will add the source location later
+                        None,
+                    )
+                })?;
+                Ok(output.into())
+            }),
+            Some(return_type),
+            arg_types,
+            false,
+        )
+    }
+
     /// Get the `Name` of the `ExtensionFunction`
     pub fn name(&self) -> &Name {
         &self.name
@@ -371,6 +542,27 @@ impl ExtensionFunction {
         self.is_variadic
     }
 
+    /// Get the description of the function, if it has one
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
+    /// Whether the arguments of this function are evaluated lazily; see
+    /// [`ExtensionFunction::with_short_circuit()`]
+    pub fn has_lazy_args(&self) -> bool {
+        self.short_circuit.is_some()
+    }
+
+    /// Try to determine the result of a call from `known_args`, the values of
+    /// its first arguments, without evaluating the rest. Always returns `None`
+    /// if this function's arguments aren't lazy, or if `known_args` is empty.
+    pub fn short_circuit(&self, known_args: &[Value]) -> Option<evaluator::Result<Value>> {
+        match &self.short_circuit {
+            Some(short_circuit) if !known_args.is_empty() => short_circuit(known_args),
+            _ => None,
+        }
+    }
+
     /// Returns `true` if this function is considered a single argument
     /// constructor.
     ///
@@ -411,6 +603,13 @@ impl std::fmt::Debug for ExtensionFunction {
 /// Anything implementing this trait can be used as a first-class value in
 /// Cedar. For instance, the `ipaddr` extension uses this mechanism
 /// to implement IPAddr as a Cedar first-class value.
+///
+/// Values of the same extension type are ordered by the type's `Ord`
+/// implementation, which must compare the values themselves and not the
+/// strings they were constructed from, so that it is consistent with `Eq` and
+/// independent of the platform. Values of different extension types are
+/// ordered by typename. This total order determines the iteration (and hence
+/// serialization) order of sets containing extension values.
 pub trait ExtensionValue: Debug + Send + Sync + UnwindSafe + RefUnwindSafe {
     /// Get the name of the type of this value.
     ///
@@ -425,8 +624,9 @@ pub trait ExtensionValue: Debug + Send + Sync + UnwindSafe + RefUnwindSafe {
     /// differs from the one stored by the constructor. The canonical representation
     /// is likely to differ from the constructed value by the formatting of its
     /// arguments.
-    /// Used by TPE to normalize residuals.  The default (`None`) means "keep whatever the
-    /// constructor stored".
+    /// Used by TPE to normalize residuals, and by canonical serialization so
+    /// that equal values are serialized the same way.  The default (`None`)
+    /// means "keep whatever the constructor stored".
     fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
         None
     }
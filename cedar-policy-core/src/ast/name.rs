@@ -288,8 +288,7 @@ impl<'de> Deserialize<'de> for InternalName {
 /// Clone is O(1).
 // This simply wraps a separate enum -- currently [`ValidSlotId`] -- in case we
 // want to generalize later
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct SlotId(pub(crate) ValidSlotId);
 
 impl SlotId {
@@ -303,6 +302,17 @@ impl SlotId {
         Self(ValidSlotId::Resource)
     }
 
+    /// Get the slot written `?<name>`. This is the slot for `principal` or
+    /// `resource` if `name` is `principal` or `resource`, and otherwise a
+    /// value slot, which must be declared with a type in the template header.
+    pub fn named(name: Id) -> Self {
+        match name.as_ref() {
+            "principal" => Self::principal(),
+            "resource" => Self::resource(),
+            _ => Self(ValidSlotId::Value(name)),
+        }
+    }
+
     /// Check if a slot represents a principal
     pub fn is_principal(&self) -> bool {
         matches!(self, Self(ValidSlotId::Principal))
@@ -312,6 +322,12 @@ impl SlotId {
     pub fn is_resource(&self) -> bool {
         matches!(self, Self(ValidSlotId::Resource))
     }
+
+    /// Check if a slot is a value slot, i.e., neither `?principal` nor
+    /// `?resource`
+    pub fn is_value_slot(&self) -> bool {
+        matches!(self, Self(ValidSlotId::Value(_)))
+    }
 }
 
 impl From<PrincipalOrResource> for SlotId {
@@ -329,13 +345,55 @@ impl std::fmt::Display for SlotId {
     }
 }
 
-/// Two possible variants for Slots
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+impl FromStr for SlotId {
+    type Err = ParseErrors;
+    /// Parse a slot written with its leading `?`, e.g., `?principal` or
+    /// `?threshold`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || ParseErrors::singleton(ToASTError::new(ToASTErrorKind::InvalidSlot(s.into()), None));
+        // Same lexical rule as the `?<ident>` slot token in the grammar
+        let name = s.strip_prefix('?').ok_or_else(invalid)?;
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) if c == '_' || c.is_ascii_alphabetic() => (),
+            _ => return Err(invalid()),
+        }
+        if !chars.all(|c| c == '_' || c.is_ascii_alphanumeric()) {
+            return Err(invalid());
+        }
+        Ok(Self::named(Id::new_unchecked(name)))
+    }
+}
+
+impl Serialize for SlotId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SlotId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid slot `{s}`")))
+    }
+}
+
+/// Possible variants for Slots
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub(crate) enum ValidSlotId {
-    #[serde(rename = "?principal")]
     Principal,
-    #[serde(rename = "?resource")]
     Resource,
+    /// A typed value slot, e.g., `?threshold`. The identifier excludes the
+    /// leading `?`.
+    Value(Id),
 }
 
 impl std::fmt::Display for ValidSlotId {
@@ -343,6 +401,7 @@ impl std::fmt::Display for ValidSlotId {
         let s = match self {
             ValidSlotId::Principal => "principal",
             ValidSlotId::Resource => "resource",
+            ValidSlotId::Value(id) => id.as_ref(),
         };
         write!(f, "?{s}")
     }
@@ -374,6 +433,38 @@ mod vars_test {
     fn display() {
         assert_eq!(format!("{}", SlotId::principal()), "?principal")
     }
+
+    #[test]
+    fn named_slots() {
+        assert_eq!(
+            SlotId::named(Id::new_unchecked("principal")),
+            SlotId::principal()
+        );
+        assert_eq!(
+            SlotId::named(Id::new_unchecked("resource")),
+            SlotId::resource()
+        );
+        let threshold = SlotId::named(Id::new_unchecked("threshold"));
+        assert!(threshold.is_value_slot());
+        assert_eq!(threshold.to_string(), "?threshold");
+        assert_eq!("?threshold".parse::<SlotId>().unwrap(), threshold);
+        assert!("threshold".parse::<SlotId>().is_err());
+        assert!("?1abc".parse::<SlotId>().is_err());
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        for slot in [
+            SlotId::principal(),
+            SlotId::resource(),
+            SlotId::named(Id::new_unchecked("limit")),
+        ] {
+            let json = serde_json::to_value(&slot).unwrap();
+            assert_eq!(json, serde_json::json!(slot.to_string()));
+            assert_eq!(serde_json::from_value::<SlotId>(json).unwrap(), slot);
+        }
+        assert!(serde_json::from_value::<SlotId>(serde_json::json!("principal")).is_err());
+    }
 }
 
 /// A new type which indicates that the contained [`InternalName`] does not
@@ -110,6 +110,18 @@ pub enum BinaryOp {
     ///
     /// First argument must have Entity type, second argument must have String type.
     HasTag,
+
+    /// Get the value for a key of a map.
+    ///
+    /// First argument must have Record type, second argument must have String type.
+    #[cfg(feature = "maps")]
+    GetKey,
+
+    /// Does the given map contain the given key?
+    ///
+    /// First argument must have Record type, second argument must have String type.
+    #[cfg(feature = "maps")]
+    ContainsKey,
 }
 
 impl std::fmt::Display for BinaryOp {
@@ -127,6 +139,10 @@ impl std::fmt::Display for BinaryOp {
             BinaryOp::ContainsAny => write!(f, "containsAny"),
             BinaryOp::GetTag => write!(f, "getTag"),
             BinaryOp::HasTag => write!(f, "hasTag"),
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey => write!(f, "get"),
+            #[cfg(feature = "maps")]
+            BinaryOp::ContainsKey => write!(f, "containsKey"),
         }
     }
 }
@@ -25,7 +25,7 @@ use nonempty::{nonempty, NonEmpty};
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     str::FromStr,
     sync::Arc,
 };
@@ -89,6 +89,15 @@ pub struct Template {
     ///
     /// Note that `slots` may be empty, in which case this `Template` represents a static policy
     slots: Vec<Slot>,
+    /// Declared types of the value slots in `body`, e.g., `?threshold: Long`.
+    /// INVARIANT: every key is a value slot in `slots`.
+    /// This is maintained by `with_value_slot_declarations()`, which is the only way to add declarations
+    value_slot_types: BTreeMap<SlotId, SlotType>,
+    /// Default values of value slots, used when a link does not bind them.
+    /// INVARIANT: every key is a key of `value_slot_types`, and every value has
+    /// the type declared for its slot.
+    /// This is maintained by `with_value_slot_defaults()`, which is the only way to add defaults
+    value_slot_defaults: BTreeMap<SlotId, RestrictedExpr>,
 }
 
 impl From<Template> for TemplateBody {
@@ -110,6 +119,12 @@ impl Template {
             for slot in self.slots() {
                 assert!(self.body.condition().slots().contains(slot));
             }
+            for slot in self.value_slot_types.keys() {
+                assert!(self.slots.iter().any(|s| &s.id == slot));
+            }
+            for slot in self.value_slot_defaults.keys() {
+                assert!(self.value_slot_types.contains_key(slot));
+            }
         }
     }
 
@@ -234,9 +249,112 @@ impl Template {
         Template {
             body: self.body.new_id(id),
             slots: self.slots.clone(),
+            value_slot_types: self.value_slot_types.clone(),
+            value_slot_defaults: self.value_slot_defaults.clone(),
         }
     }
 
+    /// Declare the types of the value slots in this template.
+    ///
+    /// Every value slot in the template must be declared exactly once, and
+    /// every declared slot must be a value slot which occurs in the template.
+    pub fn with_value_slot_declarations(
+        mut self,
+        declarations: impl IntoIterator<Item = (Slot, SlotType)>,
+    ) -> Result<Self, ValueSlotDeclarationError> {
+        let mut value_slot_types = BTreeMap::new();
+        for (slot, ty) in declarations {
+            if !slot.id.is_value_slot() {
+                return Err(ValueSlotDeclarationError::NotAValueSlot(slot));
+            }
+            if value_slot_types.contains_key(&slot.id) {
+                return Err(ValueSlotDeclarationError::Duplicate(slot));
+            }
+            if !self.slots.iter().any(|s| s.id == slot.id) {
+                return Err(ValueSlotDeclarationError::Unused(slot));
+            }
+            value_slot_types.insert(slot.id, ty);
+        }
+        if let Some(slot) = self
+            .slots
+            .iter()
+            .find(|s| s.id.is_value_slot() && !value_slot_types.contains_key(&s.id))
+        {
+            return Err(ValueSlotDeclarationError::Undeclared(slot.clone()));
+        }
+        // INVARIANT (value slot declarations): every key was checked to occur in `slots`
+        self.value_slot_types = value_slot_types;
+        // INVARIANT (value slot defaults): defaults of the previous declarations no longer apply
+        self.value_slot_defaults.clear();
+        Ok(self)
+    }
+
+    /// Declared types of the value slots in this template
+    pub fn value_slot_types(&self) -> impl Iterator<Item = (&SlotId, &SlotType)> {
+        self.value_slot_types.iter()
+    }
+
+    pub(crate) fn value_slot_types_map(&self) -> &BTreeMap<SlotId, SlotType> {
+        &self.value_slot_types
+    }
+
+    /// Declared type of the given value slot, if it is declared in this template
+    pub fn value_slot_type(&self, slot: &SlotId) -> Option<&SlotType> {
+        self.value_slot_types.get(slot)
+    }
+
+    /// Set default values for value slots of this template, which are used
+    /// when a link does not bind those slots.
+    ///
+    /// Every slot given a default must be declared (see
+    /// [`Template::with_value_slot_declarations()`]), and its default must be
+    /// a restricted expression of the declared type.
+    pub fn with_value_slot_defaults(
+        mut self,
+        defaults: impl IntoIterator<Item = (Slot, Expr)>,
+    ) -> Result<Self, ValueSlotDeclarationError> {
+        let extensions = crate::extensions::Extensions::all_available();
+        let mut value_slot_defaults = BTreeMap::new();
+        for (slot, value) in defaults {
+            let Some(expected) = self.value_slot_types.get(&slot.id) else {
+                return Err(ValueSlotDeclarationError::Undeclared(slot));
+            };
+            if value_slot_defaults.contains_key(&slot.id) {
+                return Err(ValueSlotDeclarationError::Duplicate(slot));
+            }
+            let Ok(value) = RestrictedExpr::new(value) else {
+                return Err(ValueSlotDeclarationError::InvalidDefault(slot));
+            };
+            if crate::entities::conformance::typecheck_restricted_expr_against_schematype(
+                value.as_borrowed(),
+                &expected.to_schema_type(),
+                extensions,
+            )
+            .is_err()
+            {
+                return Err(ValueSlotDeclarationError::DefaultTypeMismatch {
+                    slot,
+                    expected: expected.clone(),
+                    value,
+                });
+            }
+            value_slot_defaults.insert(slot.id, value);
+        }
+        // INVARIANT (value slot defaults): every key was checked to be declared
+        self.value_slot_defaults = value_slot_defaults;
+        Ok(self)
+    }
+
+    /// Default values of the value slots in this template
+    pub fn value_slot_defaults(&self) -> impl Iterator<Item = (&SlotId, &RestrictedExpr)> {
+        self.value_slot_defaults.iter()
+    }
+
+    /// Default value of the given value slot, if it has one
+    pub fn value_slot_default(&self, slot: &SlotId) -> Option<&RestrictedExpr> {
+        self.value_slot_defaults.get(slot)
+    }
+
     /// Get the location of this policy
     pub fn loc(&self) -> Option<&Loc> {
         self.body.loc()
@@ -290,30 +408,54 @@ impl Template {
     pub fn check_binding(
         template: &Template,
         values: &HashMap<SlotId, EntityUID>,
+    ) -> Result<(), LinkingError> {
+        Self::check_bindings(template, values, &ValueSlotEnv::new())
+    }
+
+    /// Ensure that every `?principal` or `?resource` slot in the template is
+    /// bound by `values`, that every value slot is declared and bound by
+    /// `value_slots`, and that there are no extra bindings in either
+    fn check_bindings(
+        template: &Template,
+        values: &SlotEnv,
+        value_slots: &ValueSlotEnv,
     ) -> Result<(), LinkingError> {
         // Verify all slots bound
         let unbound = template
             .slots
             .iter()
-            .filter(|slot| !values.contains_key(&slot.id))
+            .filter(|slot| {
+                if slot.id.is_value_slot() {
+                    !template.value_slot_types.contains_key(&slot.id)
+                        || !value_slots.contains_key(&slot.id)
+                } else {
+                    !values.contains_key(&slot.id)
+                }
+            })
             .collect::<Vec<_>>();
 
         let extra = values
             .keys()
             .filter(|slot| {
-                !template
-                    .slots
-                    .iter()
-                    .any(|template_slot| template_slot.id == **slot)
+                slot.is_value_slot()
+                    || !template
+                        .slots
+                        .iter()
+                        .any(|template_slot| template_slot.id == **slot)
             })
+            .chain(
+                value_slots
+                    .keys()
+                    .filter(|slot| !template.value_slot_types.contains_key(slot)),
+            )
             .collect::<Vec<_>>();
 
         if unbound.is_empty() && extra.is_empty() {
             Ok(())
         } else {
             Err(LinkingError::from_unbound_and_extras(
-                unbound.into_iter().map(|slot| slot.id),
-                extra.into_iter().copied(),
+                unbound.into_iter().map(|slot| slot.id.clone()),
+                extra.into_iter().cloned(),
             ))
         }
     }
@@ -326,9 +468,65 @@ impl Template {
         new_id: PolicyID,
         values: HashMap<SlotId, EntityUID>,
     ) -> Result<Policy, LinkingError> {
-        // INVARIANT (policy total map) Relies on check_binding to uphold the invariant
-        Template::check_binding(&template, &values)
-            .map(|_| Policy::new(template, Some(new_id), values))
+        Self::link_with_value_slots(template, new_id, values, ValueSlotEnv::new())
+    }
+
+    /// Attempt to create a template-linked policy from a template which may
+    /// have value slots. `values` binds the `?principal` and `?resource`
+    /// slots, and `value_slots` binds the value slots. Each value must have
+    /// the type declared for its slot.
+    pub fn link_with_value_slots(
+        template: Arc<Template>,
+        new_id: PolicyID,
+        values: SlotEnv,
+        value_slots: ValueSlotEnv,
+    ) -> Result<Policy, LinkingError> {
+        Self::instantiate(template, Some(new_id), values, value_slots)
+    }
+
+    /// Check the bindings for `template` and create the policy. If the
+    /// template has value slots, the policy refers to a copy of `template`
+    /// with the values substituted in.
+    fn instantiate(
+        template: Arc<Template>,
+        link_id: Option<PolicyID>,
+        values: SlotEnv,
+        mut value_slots: ValueSlotEnv,
+    ) -> Result<Policy, LinkingError> {
+        // Value slots which are not bound take their default values
+        for (slot, default) in &template.value_slot_defaults {
+            value_slots
+                .entry(slot.clone())
+                .or_insert_with(|| default.clone());
+        }
+        // INVARIANT (policy total map) Relies on check_bindings to uphold the invariant
+        Template::check_bindings(&template, &values, &value_slots)?;
+        if value_slots.is_empty() {
+            return Ok(Policy::new(template, link_id, values));
+        }
+        let extensions = crate::extensions::Extensions::all_available();
+        for (slot, value) in &value_slots {
+            // `check_bindings` ensures every bound value slot is declared
+            if let Some(expected) = template.value_slot_types.get(slot) {
+                crate::entities::conformance::typecheck_restricted_expr_against_schematype(
+                    value.as_borrowed(),
+                    &expected.to_schema_type(),
+                    extensions,
+                )
+                .map_err(|_| LinkingError::ValueSlotTypeMismatch {
+                    slot: slot.clone(),
+                    expected: expected.clone(),
+                    value: value.clone(),
+                })?;
+            }
+        }
+        let specialized = Template::from(template.body.with_value_slots(&value_slots));
+        Ok(Policy {
+            template: Arc::new(specialized),
+            link: link_id,
+            values,
+            value_slots,
+        })
     }
 
     /// Take a static policy and create a template and a template-linked policy for it.
@@ -341,6 +539,8 @@ impl Template {
         let t = Arc::new(Self {
             body,
             slots: vec![],
+            value_slot_types: BTreeMap::new(),
+            value_slot_defaults: BTreeMap::new(),
         });
         t.check_invariant();
         let p = Policy::new(Arc::clone(&t), None, HashMap::new());
@@ -353,13 +553,99 @@ impl From<TemplateBody> for Template {
         // INVARIANT: (slot cache correctness)
         // Pull all the slots out of the template body's condition.
         let slots = body.condition().slots().collect::<Vec<_>>();
-        Self { body, slots }
+        Self {
+            body,
+            slots,
+            value_slot_types: BTreeMap::new(),
+            value_slot_defaults: BTreeMap::new(),
+        }
     }
 }
 
 impl std::fmt::Display for Template {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.body)
+        self.body
+            .fmt_with_header(f, &self.value_slot_types, &self.value_slot_defaults)
+    }
+}
+
+/// Errors declaring the value slots of a template
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make fields `pub`, don't make breaking changes, and use caution
+// when adding public methods.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValueSlotDeclarationError {
+    /// A value slot is used in the template but not declared in its header
+    #[error("`{}` is used but not declared in the template header", .0.id)]
+    Undeclared(Slot),
+    /// A slot is declared in the template header but not used in the template
+    #[error("`{}` is declared in the template header but not used in the template", .0.id)]
+    Unused(Slot),
+    /// A slot is declared more than once in the template header
+    #[error("`{}` is declared more than once in the template header", .0.id)]
+    Duplicate(Slot),
+    /// `?principal` or `?resource` is declared in the template header
+    #[error("`{}` cannot be declared in the template header", .0.id)]
+    NotAValueSlot(Slot),
+    /// The default value of a slot is not a restricted expression
+    #[error("the default value of `{}` is not a literal, extension function call, set, or record", .0.id)]
+    InvalidDefault(Slot),
+    /// The default value of a slot does not have its declared type
+    #[error("the default value `{value}` of `{}` does not have its declared type `{expected}`", .slot.id)]
+    DefaultTypeMismatch {
+        /// The slot whose default has the wrong type
+        slot: Slot,
+        /// The declared type of the slot
+        expected: SlotType,
+        /// The default value
+        value: RestrictedExpr,
+    },
+}
+
+impl ValueSlotDeclarationError {
+    /// The slot this error is about
+    pub fn slot(&self) -> &Slot {
+        match self {
+            Self::Undeclared(slot)
+            | Self::Unused(slot)
+            | Self::Duplicate(slot)
+            | Self::NotAValueSlot(slot)
+            | Self::InvalidDefault(slot)
+            | Self::DefaultTypeMismatch { slot, .. } => slot,
+        }
+    }
+}
+
+impl Diagnostic for ValueSlotDeclarationError {
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.slot().loc.as_ref().map(|loc| {
+            let label = miette::LabeledSpan::underline(loc.span);
+            Box::new(std::iter::once(label)) as _
+        })
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.slot()
+            .loc
+            .as_ref()
+            .map(|l| l as &dyn miette::SourceCode)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        match self {
+            Self::Undeclared(slot) => Some(Box::new(format!(
+                "declare it with its type in a header such as `template({}: Long) =>`",
+                slot.id
+            ))),
+            Self::NotAValueSlot(_) => Some(Box::new(
+                "`?principal` and `?resource` are always entity slots and are not declared",
+            )),
+            Self::Unused(_)
+            | Self::Duplicate(_)
+            | Self::InvalidDefault(_)
+            | Self::DefaultTypeMismatch { .. } => None,
+        }
     }
 }
 
@@ -389,6 +675,19 @@ pub enum LinkingError {
         /// [`PolicyID`] where the conflict exists
         id: PolicyID,
     },
+
+    /// The value provided for a value slot does not have the declared type
+    #[error(
+        "the value `{value}` provided for `{slot}` does not have the declared type `{expected}`"
+    )]
+    ValueSlotTypeMismatch {
+        /// Slot the value was provided for
+        slot: SlotId,
+        /// Type declared for the slot in the template
+        expected: SlotType,
+        /// Value that was provided
+        value: RestrictedExpr,
+    },
 }
 
 impl LinkingError {
@@ -439,6 +738,10 @@ pub struct Policy {
     /// The constructor `new` is only visible in this module,
     /// so it is the responsibility of callers to maintain
     values: HashMap<SlotId, EntityUID>,
+    /// Values the value slots of the original template were bound to. These
+    /// are already substituted into `template`, and are kept so that the
+    /// link can be reconstructed.
+    value_slots: ValueSlotEnv,
 }
 
 impl Policy {
@@ -458,6 +761,7 @@ impl Policy {
             template,
             link: link_id,
             values,
+            value_slots: ValueSlotEnv::new(),
         }
     }
 
@@ -499,7 +803,11 @@ impl Policy {
         (self.template, self.link, self.values)
     }
 
-    /// Get pointer to the template for this policy
+    /// Get pointer to the template for this policy.
+    ///
+    /// If the template this policy was linked from has value slots, this is a
+    /// copy of that template (with the same ID) in which the value slots are
+    /// replaced by the values they were bound to.
     pub fn template(&self) -> &Template {
         &self.template
     }
@@ -581,6 +889,12 @@ impl Policy {
         &self.values
     }
 
+    /// Get the mapping from value slots to the values they were bound to when
+    /// linking. (This will be empty unless the template had value slots.)
+    pub fn value_env(&self) -> &ValueSlotEnv {
+        &self.value_slots
+    }
+
     /// Get the ID of this policy.
     pub fn id(&self) -> &PolicyID {
         self.link.as_ref().unwrap_or_else(|| self.template.id())
@@ -593,11 +907,13 @@ impl Policy {
                 template: Arc::new(self.template.new_id(id)),
                 link: None,
                 values: self.values.clone(),
+                value_slots: self.value_slots.clone(),
             },
             Some(_) => Policy {
                 template: self.template.clone(),
                 link: Some(id),
                 values: self.values.clone(),
+                value_slots: self.value_slots.clone(),
             },
         }
     }
@@ -609,6 +925,7 @@ impl Policy {
             template: Arc::new(self.template.new_id(id)),
             link: Some(link),
             values: self.values,
+            value_slots: self.value_slots,
         })
     }
 
@@ -630,6 +947,7 @@ impl Policy {
                 |Unknown {
                      name,
                      type_annotation,
+                     ..
                  }| {
                     if matches!(type_annotation, Some(Type::Entity { .. })) {
                         EntityUID::from_str(name.as_str()).ok()
@@ -640,6 +958,26 @@ impl Policy {
             )
             .collect()
     }
+
+    /// Returns the attributes of the unknown context which the policy
+    /// accesses or checks for during evaluation. Uses of the context as a
+    /// whole aren't included.
+    pub fn unknown_context_attrs(&self) -> HashSet<SmolStr> {
+        self.condition()
+            .subexpressions()
+            .filter_map(|e| match e.expr_kind() {
+                ExprKind::GetAttr { expr, attr } | ExprKind::HasAttr { expr, attr } => {
+                    match expr.expr_kind() {
+                        ExprKind::Unknown(u) if u.name == CONTEXT_UNKNOWN_NAME => {
+                            Some(attr.clone())
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl std::fmt::Display for Policy {
@@ -651,7 +989,7 @@ impl std::fmt::Display for Policy {
                 f,
                 "Template Instance of {}, slots: [{}]",
                 self.template().id(),
-                display_slot_env(self.env())
+                display_slot_env(self.env(), self.value_env())
             )
         }
     }
@@ -660,6 +998,9 @@ impl std::fmt::Display for Policy {
 /// Map from Slot Ids to Entity UIDs which fill the slots
 pub type SlotEnv = HashMap<SlotId, EntityUID>;
 
+/// Map from value slots to the values which fill them
+pub type ValueSlotEnv = HashMap<SlotId, RestrictedExpr>;
+
 /// Represents either a static policy or a template linked policy.
 ///
 /// Contains less rich information than `Policy`. In particular, this form is
@@ -676,6 +1017,8 @@ pub struct LiteralPolicy {
     link_id: Option<PolicyID>,
     /// Values of the slots
     values: SlotEnv,
+    /// Values of the value slots
+    value_slots: ValueSlotEnv,
 }
 
 impl LiteralPolicy {
@@ -687,6 +1030,7 @@ impl LiteralPolicy {
             template_id,
             link_id: None,
             values: SlotEnv::new(),
+            value_slots: ValueSlotEnv::new(),
         }
     }
 
@@ -702,6 +1046,15 @@ impl LiteralPolicy {
             template_id,
             link_id: Some(link_id),
             values,
+            value_slots: ValueSlotEnv::new(),
+        }
+    }
+
+    /// Set the values of the value slots of a template-linked policy
+    pub fn with_value_slots(self, value_slots: ValueSlotEnv) -> Self {
+        Self {
+            value_slots,
+            ..self
         }
     }
 
@@ -709,6 +1062,11 @@ impl LiteralPolicy {
     pub fn value(&self, slot: &SlotId) -> Option<&EntityUID> {
         self.values.get(slot)
     }
+
+    /// Get the value bound to the given value slot, if it exists
+    pub fn value_slot(&self, slot: &SlotId) -> Option<&RestrictedExpr> {
+        self.value_slots.get(slot)
+    }
 }
 
 // Can we verify the hash property?
@@ -723,6 +1081,12 @@ impl std::hash::Hash for LiteralPolicy {
             id.hash(state);
             euid.hash(state);
         }
+        let mut buf = self.value_slots.iter().collect::<Vec<_>>();
+        buf.sort_by_key(|(id, _)| *id);
+        for (id, value) in buf {
+            id.hash(state);
+            value.hash(state);
+        }
     }
 }
 
@@ -749,6 +1113,7 @@ mod hashing_tests {
             template_id: PolicyID::from_string("template"),
             link_id: Some(PolicyID::from_string("id")),
             values: map,
+            value_slots: ValueSlotEnv::new(),
         }
     }
 
@@ -786,8 +1151,13 @@ impl LiteralPolicy {
             .get(&self.template_id)
             .ok_or_else(|| ReificationError::NoSuchTemplate(self.template_id().clone()))?;
         // INVARIANT (values total map)
-        Template::check_binding(template, &self.values).map_err(ReificationError::Linking)?;
-        Ok(Policy::new(template.clone(), self.link_id, self.values))
+        Template::instantiate(
+            Arc::clone(template),
+            self.link_id,
+            self.values,
+            self.value_slots,
+        )
+        .map_err(ReificationError::Linking)
     }
 
     /// Lookup the euid bound by a SlotId
@@ -813,9 +1183,14 @@ impl LiteralPolicy {
     }
 }
 
-fn display_slot_env(env: &SlotEnv) -> String {
+fn display_slot_env(env: &SlotEnv, value_env: &ValueSlotEnv) -> String {
     env.iter()
         .map(|(slot, value)| format!("{slot} -> {value}"))
+        .chain(
+            value_env
+                .iter()
+                .map(|(slot, value)| format!("{slot} -> {value}")),
+        )
         .join(",")
 }
 
@@ -828,7 +1203,7 @@ impl std::fmt::Display for LiteralPolicy {
                 f,
                 "Template linked policy of {}, slots: [{}]",
                 self.template_id(),
-                display_slot_env(&self.values),
+                display_slot_env(&self.values, &self.value_slots),
             )
         }
     }
@@ -840,6 +1215,7 @@ impl From<Policy> for LiteralPolicy {
             template_id: p.template.id().clone(),
             link_id: p.link,
             values: p.values,
+            value_slots: p.value_slots,
         }
     }
 }
@@ -1076,6 +1452,23 @@ impl TemplateBody {
         }
     }
 
+    /// Clone this policy with the value slots bound in `values` replaced by
+    /// their values. Value slots can only occur in the non-scope constraints.
+    pub(crate) fn with_value_slots(&self, values: &ValueSlotEnv) -> Self {
+        match self {
+            TemplateBody::TemplateBody(t) => {
+                let mut new = t.clone();
+                new.non_scope_constraints = t
+                    .non_scope_constraints
+                    .as_ref()
+                    .map(|e| Arc::new(e.substitute_value_slots(values)));
+                TemplateBody::TemplateBody(new)
+            }
+            #[cfg(feature = "tolerant-ast")]
+            TemplateBody::TemplateBodyError(..) => self.clone(),
+        }
+    }
+
     #[cfg(feature = "tolerant-ast")]
     /// Create a template body representing a policy that failed to parse
     pub fn error(id: PolicyID, loc: Option<Loc>) -> Self {
@@ -1356,9 +1749,36 @@ impl From<StaticPolicy> for TemplateBody {
 
 impl std::fmt::Display for TemplateBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with_header(f, &BTreeMap::new(), &BTreeMap::new())
+    }
+}
+
+impl TemplateBody {
+    /// Display this policy, with a `template(...) =>` header declaring the
+    /// given value slots and their defaults (if there are any) after the
+    /// annotations
+    fn fmt_with_header(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        value_slot_types: &BTreeMap<SlotId, SlotType>,
+        value_slot_defaults: &BTreeMap<SlotId, RestrictedExpr>,
+    ) -> std::fmt::Result {
         match self {
             TemplateBody::TemplateBody(template_body_impl) => {
-                template_body_impl.annotations.fmt(f)?;
+                write!(f, "{}", template_body_impl.annotations)?;
+                if !value_slot_types.is_empty() {
+                    writeln!(
+                        f,
+                        "template({}) =>",
+                        value_slot_types
+                            .iter()
+                            .map(|(slot, ty)| match value_slot_defaults.get(slot) {
+                                Some(default) => format!("{slot}: {ty} = {default}"),
+                                None => format!("{slot}: {ty}"),
+                            })
+                            .join(", ")
+                    )?;
+                }
                 write!(
                     f,
                     "{}(\n  {},\n  {},\n  {}\n)",
@@ -2174,7 +2594,7 @@ mod test {
             let t = Arc::new(template);
             let env = t
                 .slots()
-                .map(|slot| (slot.id, EntityUID::with_eid("eid")))
+                .map(|slot| (slot.id.clone(), EntityUID::with_eid("eid")))
                 .collect();
             let _ = Template::link(t, PolicyID::from_string("id"), env).expect("Linking failed");
         }
@@ -2297,6 +2717,176 @@ mod test {
         );
     }
 
+    fn value_slot_template() -> Arc<Template> {
+        Arc::new(
+            crate::parser::parse_policy_or_template(
+                Some(PolicyID::from_string("template")),
+                r#"template(?limit: Long, ?tags: Set<String>) => permit(principal == ?principal, action, resource) when { resource.size < ?limit && ?tags.contains(resource.tag) };"#,
+            )
+            .expect("should parse"),
+        )
+    }
+
+    #[test]
+    fn value_slot_binding() {
+        let t = value_slot_template();
+        assert_eq!(t.value_slot_types().count(), 2);
+        assert_eq!(
+            t.value_slot_type(&"?limit".parse().unwrap()),
+            Some(&SlotType::Long)
+        );
+
+        let iid = PolicyID::from_string("linked");
+        let values = HashMap::from([(SlotId::principal(), EntityUID::with_eid("alice"))]);
+        let value_slots = HashMap::from([
+            ("?limit".parse().unwrap(), RestrictedExpr::val(10)),
+            (
+                "?tags".parse().unwrap(),
+                RestrictedExpr::set([RestrictedExpr::val("a"), RestrictedExpr::val("b")]),
+            ),
+        ]);
+        let p = Template::link_with_value_slots(t, iid.clone(), values, value_slots.clone())
+            .expect("should link");
+        assert_eq!(p.id(), &iid);
+        assert_eq!(p.value_env(), &value_slots);
+        let expected: Expr = r#"resource.size < 10 && ["a", "b"].contains(resource.tag)"#
+            .parse()
+            .unwrap();
+        assert!(p.non_scope_constraints().unwrap().eq_shape(&expected));
+        assert_eq!(p.template().value_slot_types().count(), 0);
+    }
+
+    #[test]
+    fn value_slot_binding_missing_and_extra() {
+        let t = value_slot_template();
+        let values = HashMap::from([(SlotId::principal(), EntityUID::with_eid("alice"))]);
+        let value_slots = HashMap::from([
+            ("?limit".parse().unwrap(), RestrictedExpr::val(10)),
+            ("?other".parse().unwrap(), RestrictedExpr::val(10)),
+        ]);
+        assert_matches!(
+            Template::link_with_value_slots(t, PolicyID::from_string("linked"), values, value_slots),
+            Err(LinkingError::ArityError { unbound_values, extra_values }) => {
+                assert_eq!(unbound_values, vec!["?tags".parse().unwrap()]);
+                assert_eq!(extra_values, vec!["?other".parse().unwrap()]);
+            }
+        );
+    }
+
+    #[test]
+    fn value_slot_binding_type_mismatch() {
+        let t = value_slot_template();
+        let values = HashMap::from([(SlotId::principal(), EntityUID::with_eid("alice"))]);
+        let value_slots = HashMap::from([
+            ("?limit".parse().unwrap(), RestrictedExpr::val("ten")),
+            (
+                "?tags".parse().unwrap(),
+                RestrictedExpr::set([RestrictedExpr::val("a")]),
+            ),
+        ]);
+        let err = Template::link_with_value_slots(
+            t,
+            PolicyID::from_string("linked"),
+            values,
+            value_slots,
+        )
+        .unwrap_err();
+        assert_matches!(&err, LinkingError::ValueSlotTypeMismatch { slot, expected, .. } => {
+            assert_eq!(slot, &"?limit".parse().unwrap());
+            assert_eq!(expected, &SlotType::Long);
+        });
+        assert_eq!(
+            err.to_string(),
+            r#"the value `"ten"` provided for `?limit` does not have the declared type `Long`"#
+        );
+    }
+
+    #[test]
+    fn value_slot_defaults() {
+        let t = Arc::new(
+            crate::parser::parse_policy_or_template(
+                Some(PolicyID::from_string("template")),
+                r#"template(?limit: Long = 5, ?tags: Set<String>) => permit(principal, action, resource) when { resource.size < ?limit && ?tags.contains(resource.tag) };"#,
+            )
+            .expect("should parse"),
+        );
+        assert_eq!(
+            t.value_slot_default(&"?limit".parse().unwrap()),
+            Some(&RestrictedExpr::val(5))
+        );
+        assert_eq!(t.value_slot_default(&"?tags".parse().unwrap()), None);
+
+        // the default is used when the link does not bind the slot
+        let tags = ("?tags".parse().unwrap(), RestrictedExpr::set([]));
+        let p = Template::link_with_value_slots(
+            Arc::clone(&t),
+            PolicyID::from_string("defaulted"),
+            HashMap::new(),
+            HashMap::from([tags.clone()]),
+        )
+        .expect("should link");
+        assert_eq!(
+            p.value_env().get(&"?limit".parse().unwrap()),
+            Some(&RestrictedExpr::val(5))
+        );
+
+        // and overridden when it does
+        let p = Template::link_with_value_slots(
+            Arc::clone(&t),
+            PolicyID::from_string("overridden"),
+            HashMap::new(),
+            HashMap::from([tags, ("?limit".parse().unwrap(), RestrictedExpr::val(7))]),
+        )
+        .expect("should link");
+        assert_eq!(
+            p.value_env().get(&"?limit".parse().unwrap()),
+            Some(&RestrictedExpr::val(7))
+        );
+
+        // slots without defaults must still be bound
+        assert_matches!(
+            Template::link_with_value_slots(t, PolicyID::from_string("unbound"), HashMap::new(), HashMap::new()),
+            Err(LinkingError::ArityError { unbound_values, .. }) => {
+                assert_eq!(unbound_values, vec!["?tags".parse().unwrap()]);
+            }
+        );
+    }
+
+    #[test]
+    fn value_slot_default_errors() {
+        let t = Template::from(TemplateBody::from(
+            crate::parser::parse_policy(None, "permit(principal, action, resource);").unwrap(),
+        ));
+        let slot = Slot {
+            id: "?limit".parse().unwrap(),
+            loc: None,
+        };
+        assert_matches!(
+            t.with_value_slot_defaults([(slot.clone(), Expr::val(1))]),
+            Err(ValueSlotDeclarationError::Undeclared(_))
+        );
+
+        let t = value_slot_template();
+        let t = Template::clone(&t);
+        assert_matches!(
+            t.clone()
+                .with_value_slot_defaults([(slot.clone(), Expr::val("ten"))]),
+            Err(ValueSlotDeclarationError::DefaultTypeMismatch {
+                expected: SlotType::Long,
+                ..
+            })
+        );
+        assert_matches!(
+            t.clone()
+                .with_value_slot_defaults([(slot.clone(), Expr::var(Var::Principal))]),
+            Err(ValueSlotDeclarationError::InvalidDefault(_))
+        );
+        assert_matches!(
+            t.with_value_slot_defaults([(slot.clone(), Expr::val(1)), (slot, Expr::val(2))]),
+            Err(ValueSlotDeclarationError::Duplicate(_))
+        );
+    }
+
     #[test]
     fn isnt_template_implies_from_succeeds() {
         for template in all_templates() {
@@ -14,21 +14,26 @@
  * limitations under the License.
  */
 
+use super::scope_index::ScopeIndex;
 use super::{
-    EntityUID, LinkingError, LiteralPolicy, Policy, PolicyID, ReificationError, SlotId,
-    StaticPolicy, Template,
+    EntityUID, LinkingError, LiteralPolicy, Policy, PolicyID, ReificationError, Request, SlotId,
+    StaticPolicy, Template, ValueSlotEnv,
 };
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use linked_hash_map::{Entry, LinkedHashMap};
 use linked_hash_set::LinkedHashSet;
 
 use miette::Diagnostic;
 use smol_str::format_smolstr;
-use std::{borrow::Borrow, collections::HashMap, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    sync::{Arc, OnceLock},
+};
 use thiserror::Error;
 
 /// Represents a set of `Policy`s
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone)]
 pub struct PolicySet {
     /// `templates` contains all bodies of policies in the `PolicySet`.
     /// A body is either:
@@ -47,7 +52,27 @@ pub struct PolicySet {
     /// There is a key `t` iff `templates` contains the key `t`. The value of `t` will be a (possibly empty)
     /// set of every `p` in `links` s.t. `p.template().id() == t`.
     template_to_links_map: LinkedHashMap<PolicyID, LinkedHashSet<PolicyID>>,
+
+    /// Index of `links` by scope, built the first time it is needed and
+    /// cleared whenever `links` changes
+    scope_index: OnceLock<ScopeIndex>,
+
+    /// Ids of the disabled policies and templates. Every id is a key of
+    /// `links` or `templates`. A policy is skipped during authorization if it
+    /// or its template is disabled.
+    disabled: HashSet<PolicyID>,
+}
+
+impl PartialEq for PolicySet {
+    fn eq(&self, other: &Self) -> bool {
+        // `scope_index` is derived from `links`, so it is not compared
+        self.templates == other.templates
+            && self.links == other.links
+            && self.template_to_links_map == other.template_to_links_map
+            && self.disabled == other.disabled
+    }
 }
+impl Eq for PolicySet {}
 
 /// A Policy Set that contains less rich information than `PolicySet`.
 ///
@@ -123,6 +148,8 @@ impl TryFrom<LiteralPolicySet> for PolicySet {
             templates,
             links,
             template_to_links_map,
+            scope_index: OnceLock::new(),
+            disabled: HashSet::new(),
         })
     }
 }
@@ -212,6 +239,8 @@ impl PolicySet {
             templates: LinkedHashMap::new(),
             links: LinkedHashMap::new(),
             template_to_links_map: LinkedHashMap::new(),
+            scope_index: OnceLock::new(),
+            disabled: HashSet::new(),
         }
     }
 
@@ -228,6 +257,8 @@ impl PolicySet {
             templates,
             links,
             template_to_links_map,
+            scope_index: OnceLock::new(),
+            disabled: HashSet::new(),
         }
     }
 
@@ -278,6 +309,7 @@ impl PolicySet {
         if let Some(ventry) = link_ventry {
             ventry.insert(policy);
         }
+        self.scope_index.take();
 
         Ok(())
     }
@@ -421,6 +453,11 @@ impl PolicySet {
             self.template_to_links_map
                 .insert(tid.clone(), this_template_link_set);
         }
+        for pid in &other.disabled {
+            self.disabled
+                .insert(renaming.get(pid).unwrap_or(pid).clone());
+        }
+        self.scope_index.take();
         Ok(renaming)
     }
 
@@ -431,29 +468,25 @@ impl PolicySet {
     ) -> Result<Policy, PolicySetPolicyRemovalError> {
         // Invariant: if `policy_id` is a key in both `self.links` and `self.templates`,
         // then self.templates[policy_id] has exactly one link: self.links[policy_id]
-        let policy = match self.links.remove(policy_id) {
-            Some(p) => p,
-            None => {
-                return Err(PolicySetPolicyRemovalError::RemovePolicyNoLinkError(
-                    policy_id.clone(),
-                ))
-            }
-        };
-        //links mapped by `PolicyId`, so `policy` is unique
-        match self.templates.remove(policy_id) {
-            Some(_) => {
-                self.template_to_links_map.remove(policy_id);
-                Ok(policy)
-            }
-            None => {
-                //If we removed the link but failed to remove the template
-                //restore the link and return an error
-                self.links.insert(policy_id.clone(), policy);
-                Err(PolicySetPolicyRemovalError::RemovePolicyNoTemplateError(
-                    policy_id.clone(),
-                ))
-            }
+        if !self.links.contains_key(policy_id) {
+            return Err(PolicySetPolicyRemovalError::RemovePolicyNoLinkError(
+                policy_id.clone(),
+            ));
+        }
+        //If the link is not of a static policy, fail before removing
+        //anything, so that the order of the links is unchanged
+        if self.templates.remove(policy_id).is_none() {
+            return Err(PolicySetPolicyRemovalError::RemovePolicyNoTemplateError(
+                policy_id.clone(),
+            ));
         }
+        self.template_to_links_map.remove(policy_id);
+        self.disabled.remove(policy_id);
+        self.scope_index.take();
+        //links mapped by `PolicyId`, so `policy` is unique
+        self.links
+            .remove(policy_id)
+            .ok_or_else(|| PolicySetPolicyRemovalError::RemovePolicyNoLinkError(policy_id.clone()))
     }
 
     /// Add a `StaticPolicy` to the `PolicySet`.
@@ -473,6 +506,7 @@ impl PolicySet {
                 );
                 templates_entry.insert(t);
                 links_entry.insert(p);
+                self.scope_index.take();
                 Ok(())
             }
             (Entry::Occupied(oentry), _) => Err(PolicySetError::Occupied {
@@ -537,6 +571,7 @@ impl PolicySet {
         match self.templates.remove(policy_id) {
             Some(t) => {
                 self.template_to_links_map.remove(policy_id);
+                self.disabled.remove(policy_id);
                 Ok(Arc::unwrap_or_clone(t))
             }
             None => panic!("Found in template_to_links_map but not in templates"),
@@ -567,13 +602,26 @@ impl PolicySet {
         template_id: PolicyID,
         new_id: PolicyID,
         values: HashMap<SlotId, EntityUID>,
+    ) -> Result<&Policy, LinkingError> {
+        self.link_with_value_slots(template_id, new_id, values, ValueSlotEnv::new())
+    }
+
+    /// Like [`PolicySet::link`], but also binds the value slots of the
+    /// template. Additionally errors if a value does not have the type
+    /// declared for its slot.
+    pub fn link_with_value_slots(
+        &mut self,
+        template_id: PolicyID,
+        new_id: PolicyID,
+        values: HashMap<SlotId, EntityUID>,
+        value_slots: ValueSlotEnv,
     ) -> Result<&Policy, LinkingError> {
         let t =
             self.get_template_arc(&template_id)
                 .ok_or_else(|| LinkingError::NoSuchTemplate {
                     id: template_id.clone(),
                 })?;
-        let r = Template::link(t, new_id.clone(), values)?;
+        let r = Template::link_with_value_slots(t, new_id.clone(), values, value_slots)?;
 
         // Both maps must not contain the `new_id`
         match (
@@ -586,6 +634,7 @@ impl PolicySet {
                     .entry(template_id)
                     .or_default()
                     .insert(new_id);
+                self.scope_index.take();
                 Ok(links_entry.insert(r))
             }
             (Entry::Occupied(oentry), _) => Err(LinkingError::PolicyIdConflict {
@@ -613,6 +662,8 @@ impl PolicySet {
                         panic!("No template found for linked policy")
                     }
                 };
+                self.disabled.remove(policy_id);
+                self.scope_index.take();
                 Ok(p)
             }
             None => Err(PolicySetUnlinkError::UnlinkingError(policy_id.clone())),
@@ -624,6 +675,81 @@ impl PolicySet {
         self.links.values()
     }
 
+    /// Iterate over the enabled policies whose scopes may be satisfied by
+    /// `request`, in the same order as [`PolicySet::policies()`].
+    ///
+    /// Policies whose scope requires a different principal type, action, or
+    /// resource type than the request's are skipped using an index, which is
+    /// built on first use and rebuilt after the policies change. Every enabled
+    /// policy is returned if the principal, action, or resource of `request`
+    /// is unknown.
+    pub fn policies_for_request<'a>(
+        &'a self,
+        request: &Request,
+    ) -> impl Iterator<Item = &'a Policy> + 'a {
+        self.candidates_for_request(request)
+            .filter(|p| self.is_active(p))
+    }
+
+    /// Like [`PolicySet::policies_for_request()`], but for the policies which
+    /// are disabled
+    pub fn disabled_policies_for_request<'a>(
+        &'a self,
+        request: &Request,
+    ) -> impl Iterator<Item = &'a Policy> + 'a {
+        self.candidates_for_request(request)
+            .filter(|p| !self.is_active(p))
+    }
+
+    fn candidates_for_request<'a>(
+        &'a self,
+        request: &Request,
+    ) -> impl Iterator<Item = &'a Policy> + 'a {
+        let index = self
+            .scope_index
+            .get_or_init(|| ScopeIndex::new(self.links.values()));
+        match index.candidates(request) {
+            Some(ids) => Either::Left(ids.into_iter().filter_map(|id| self.links.get(id))),
+            None => Either::Right(self.policies()),
+        }
+    }
+
+    /// Iterate over the policies which are enabled, i.e., which are not
+    /// disabled and are not linked to a disabled template
+    pub fn enabled_policies(&self) -> impl Iterator<Item = &Policy> {
+        self.policies().filter(|p| self.is_active(p))
+    }
+
+    /// Enable or disable the policy or template `id`, returning whether it was
+    /// previously enabled, or `None` if there is no such policy or template.
+    ///
+    /// Disabled policies are kept in the policy set, but are skipped during
+    /// authorization. Disabling a template disables all of its links.
+    pub fn set_enabled(&mut self, id: &PolicyID, enabled: bool) -> Option<bool> {
+        if !self.links.contains_key(id) && !self.templates.contains_key(id) {
+            return None;
+        }
+        let was_enabled = self.is_enabled(id);
+        if enabled {
+            self.disabled.remove(id);
+        } else {
+            self.disabled.insert(id.clone());
+        }
+        Some(was_enabled)
+    }
+
+    /// Returns `false` if the policy or template `id` has been disabled with
+    /// [`PolicySet::set_enabled()`]. Note that a link to a disabled template
+    /// is skipped during authorization even if the link itself is enabled.
+    pub fn is_enabled(&self, id: &PolicyID) -> bool {
+        !self.disabled.contains(id)
+    }
+
+    /// Returns `true` if neither `policy` nor its template is disabled
+    fn is_active(&self, policy: &Policy) -> bool {
+        self.is_enabled(policy.id()) && self.is_enabled(policy.template().id())
+    }
+
     /// Consume the `PolicySet`, producing an iterator of all the policies in it
     pub fn into_policies(self) -> impl Iterator<Item = Policy> {
         self.links.into_iter().map(|(_, p)| p)
@@ -697,14 +823,16 @@ mod test {
     use super::*;
     use crate::{
         ast::{
-            annotation::Annotations, ActionConstraint, Effect, PrincipalConstraint,
-            ResourceConstraint,
+            annotation::Annotations, ActionConstraint, Context, Effect, EntityUIDEntry,
+            PrincipalConstraint, RequestSchemaAllPass, ResourceConstraint,
         },
+        extensions::Extensions,
         parser,
     };
 
+    use cool_asserts::assert_matches;
     use similar_asserts::assert_eq;
-    use std::collections::HashMap;
+    use std::{collections::HashMap, str::FromStr};
 
     #[test]
     fn link_conflicts() {
@@ -1253,4 +1381,161 @@ mod test {
             ids
         );
     }
+
+    #[test]
+    fn policies_for_request() {
+        let mut pset = parser::parse_policyset(
+            r#"
+            permit(principal, action, resource);
+            permit(principal == User::"alice", action, resource);
+            permit(principal is Admin, action, resource);
+            permit(principal in Group::"staff", action, resource);
+            permit(principal, action == Action::"view", resource);
+            permit(principal, action == Action::"edit", resource);
+            permit(principal, action in [Action::"edit"], resource);
+            permit(principal, action, resource is Photo in Album::"trip");
+            permit(principal is User, action == Action::"view", resource == Photo::"a");
+            forbid(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let request = |principal: &str, action: &str, resource: &str| {
+            Request::new(
+                (EntityUID::from_str(principal).unwrap(), None),
+                (EntityUID::from_str(action).unwrap(), None),
+                (EntityUID::from_str(resource).unwrap(), None),
+                Context::empty(),
+                None::<&RequestSchemaAllPass>,
+                Extensions::none(),
+            )
+            .unwrap()
+        };
+        let candidates = |pset: &PolicySet, request: &Request| {
+            pset.policies_for_request(request)
+                .map(|p| p.id().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let view_photo = request(r#"User::"bob""#, r#"Action::"view""#, r#"Photo::"a""#);
+        assert_eq!(
+            candidates(&pset, &view_photo),
+            ["policy0", "policy1", "policy3", "policy4", "policy6", "policy7", "policy8"]
+        );
+        let edit_doc = request(r#"Admin::"carol""#, r#"Action::"edit""#, r#"Doc::"d""#);
+        assert_eq!(
+            candidates(&pset, &edit_doc),
+            ["policy0", "policy2", "policy3", "policy5", "policy6"]
+        );
+
+        // The index is updated when the policies change
+        pset.link(
+            PolicyID::from_string("policy9"),
+            PolicyID::from_string("link"),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        assert_eq!(candidates(&pset, &view_photo).last().unwrap(), "link");
+        assert!(!candidates(&pset, &edit_doc).contains(&"link".to_string()));
+        pset.unlink(&PolicyID::from_string("link")).unwrap();
+        assert!(!candidates(&pset, &view_photo).contains(&"link".to_string()));
+
+        // Nor is it changed by failing to remove a link as a static policy
+        for id in ["link", "link2"] {
+            pset.link(
+                PolicyID::from_string("policy9"),
+                PolicyID::from_string(id),
+                HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+            )
+            .unwrap();
+        }
+        let ids = |pset: &PolicySet| {
+            pset.policies()
+                .map(|p| p.id().to_string())
+                .collect::<Vec<_>>()
+        };
+        let (before, before_ids) = (candidates(&pset, &view_photo), ids(&pset));
+        assert_matches!(
+            pset.remove_static(&PolicyID::from_string("link")),
+            Err(PolicySetPolicyRemovalError::RemovePolicyNoTemplateError(_))
+        );
+        assert_eq!(candidates(&pset, &view_photo), before);
+        assert_eq!(ids(&pset), before_ids);
+        pset.unlink(&PolicyID::from_string("link")).unwrap();
+        pset.unlink(&PolicyID::from_string("link2")).unwrap();
+
+        // Every policy may apply to a request with unknowns
+        let unknown = Request::new_with_unknowns(
+            EntityUIDEntry::unknown(),
+            EntityUIDEntry::known(r#"Action::"view""#.parse().unwrap(), None),
+            EntityUIDEntry::known(r#"Photo::"a""#.parse().unwrap(), None),
+            None,
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        assert_eq!(
+            pset.policies_for_request(&unknown).count(),
+            pset.policies().count()
+        );
+    }
+
+    #[test]
+    fn disabled_policies() {
+        let mut pset = parser::parse_policyset(
+            r#"
+            permit(principal, action, resource);
+            forbid(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let id = PolicyID::from_string;
+        pset.link(
+            id("policy1"),
+            id("link"),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let request = Request::new(
+            (r#"User::"bob""#.parse().unwrap(), None),
+            (r#"Action::"view""#.parse().unwrap(), None),
+            (r#"Photo::"a""#.parse().unwrap(), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let enabled = |pset: &PolicySet| {
+            pset.policies_for_request(&request)
+                .map(|p| p.id().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(enabled(&pset), ["policy0", "link"]);
+
+        assert_eq!(pset.set_enabled(&id("policy0"), false), Some(true));
+        assert_eq!(pset.set_enabled(&id("policy0"), false), Some(false));
+        assert_eq!(pset.set_enabled(&id("nope"), false), None);
+        assert!(!pset.is_enabled(&id("policy0")));
+        assert_eq!(enabled(&pset), ["link"]);
+        assert_eq!(pset.policies().count(), 2);
+
+        // Disabling a template disables its links
+        pset.set_enabled(&id("policy1"), false);
+        assert!(pset.is_enabled(&id("link")));
+        assert_eq!(enabled(&pset), Vec::<String>::new());
+        assert_eq!(pset.enabled_policies().count(), 0);
+        assert_eq!(pset.disabled_policies_for_request(&request).count(), 2);
+        pset.set_enabled(&id("policy1"), true);
+        assert_eq!(enabled(&pset), ["link"]);
+
+        // The flag is removed along with its policy
+        pset.set_enabled(&id("link"), false);
+        pset.unlink(&id("link")).unwrap();
+        pset.link(
+            id("policy1"),
+            id("link"),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        assert!(pset.is_enabled(&id("link")));
+    }
 }
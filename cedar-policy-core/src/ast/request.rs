@@ -15,8 +15,8 @@
  */
 
 use crate::entities::json::{
-    err::JsonSerializationError, ContextJsonDeserializationError, ContextJsonParser,
-    NullContextSchema,
+    canonicalize_json_keys, err::JsonSerializationError, ContextJsonDeserializationError,
+    ContextJsonParser, NullContextSchema,
 };
 use crate::entities::CedarValueJson;
 use crate::evaluator::{EvaluationError, RestrictedEvaluator};
@@ -24,15 +24,20 @@ use crate::extensions::Extensions;
 use crate::parser::Loc;
 use miette::Diagnostic;
 use smol_str::{SmolStr, ToSmolStr};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 use thiserror::Error;
 
 use super::{
     BorrowedRestrictedExpr, BoundedDisplay, EntityType, EntityUID, Expr, ExprKind,
-    ExpressionConstructionError, PartialValue, RestrictedExpr, Unknown, Value, ValueKind, Var,
+    ExpressionConstructionError, PartialValue, RestrictedExpr, StaticallyTyped, Type, Unknown,
+    Value, ValueKind, Var,
 };
 
+/// Name of the unknown which partial evaluation uses for the context of a
+/// request when the context, or some of its attributes, are unknown
+pub const CONTEXT_UNKNOWN_NAME: &str = "context";
+
 /// Represents the request tuple <P, A, R, C> (see the Cedar design doc).
 #[derive(Debug, Clone)]
 pub struct Request {
@@ -48,6 +53,12 @@ pub struct Request {
     /// Context associated with the request.
     /// `None` means that variable will result in a residual for partial evaluation.
     pub(crate) context: Option<Context>,
+
+    /// Attributes of the context which may or may not be present, and whose
+    /// values are unknown. Partial evaluation leaves residuals on the unknown
+    /// `context` for these attributes. Never contains an attribute of
+    /// `context`, and is always empty if `context` is `None`.
+    pub(crate) unknown_context_attrs: BTreeSet<SmolStr>,
 }
 
 /// Represents the principal type, resource type, and action UID.
@@ -184,6 +195,7 @@ impl Request {
             action: EntityUIDEntry::known(action.0, action.1),
             resource: EntityUIDEntry::known(resource.0, resource.1),
             context: Some(context),
+            unknown_context_attrs: BTreeSet::new(),
         };
         if let Some(schema) = schema {
             schema.validate_request(&req, extensions)?;
@@ -209,6 +221,7 @@ impl Request {
             action,
             resource,
             context,
+            unknown_context_attrs: BTreeSet::new(),
         };
         if let Some(schema) = schema {
             schema.validate_request(&req, extensions)?;
@@ -229,9 +242,55 @@ impl Request {
             action,
             resource,
             context,
+            unknown_context_attrs: BTreeSet::new(),
         }
     }
 
+    /// Declare attributes of the context which may or may not be present, and
+    /// whose values are unknown, for partial evaluation. Attributes which the
+    /// context already has are ignored, as are all attributes if the whole
+    /// context is unknown.
+    ///
+    /// Note that this doesn't validate the request; see
+    /// [`RequestSchema::validate_request()`].
+    pub fn with_unknown_context_attrs(mut self, attrs: impl IntoIterator<Item = SmolStr>) -> Self {
+        if let Some(context) = &self.context {
+            self.unknown_context_attrs
+                .extend(attrs.into_iter().filter(|attr| !context.contains_key(attr)));
+        }
+        self
+    }
+
+    /// Get the attributes of the context which may or may not be present, and
+    /// whose values are unknown
+    pub fn unknown_context_attrs(&self) -> impl Iterator<Item = &SmolStr> {
+        self.unknown_context_attrs.iter()
+    }
+
+    /// Get the context associated with the request, where each of the
+    /// [`Self::unknown_context_attrs()`] is present with an unknown value.
+    /// This is the context to validate against a schema, as an unknown value
+    /// conforms to any type.
+    pub(crate) fn context_with_unknown_attrs(&self) -> Option<Context> {
+        let context = self.context.as_ref()?;
+        if self.unknown_context_attrs.is_empty() {
+            return Some(context.clone());
+        }
+        let unknowns = self.unknown_context_attrs.iter().map(|attr| {
+            (
+                attr.clone(),
+                RestrictedExpr::unknown(Unknown::new_untyped(format!("context.{attr}"))),
+            )
+        });
+        // `unknown_context_attrs` never contains an attribute of `context`,
+        // so there are no duplicate keys
+        Context::from_pairs(
+            context.clone().into_iter().chain(unknowns),
+            Extensions::none(),
+        )
+        .ok()
+    }
+
     /// Get the principal associated with the request
     pub fn principal(&self) -> &EntityUIDEntry {
         &self.principal
@@ -385,6 +444,58 @@ impl Context {
         }
     }
 
+    /// Create a `Context` by layering the given contexts on top of each other,
+    /// in order. When an attribute appears in more than one layer, the value
+    /// from the last layer containing it is used.
+    ///
+    /// Layers are merged shallowly: a record-valued attribute in a later layer
+    /// replaces the whole record from earlier layers. It is an error for an
+    /// attribute to have values of different types in different layers
+    /// (values which are not yet known are not checked).
+    ///
+    /// `extensions` provides the `Extensions` which should be active for
+    /// evaluating the merged context.
+    pub fn layered(
+        layers: impl IntoIterator<Item = Context>,
+        extensions: &Extensions<'_>,
+    ) -> Result<Self, ContextCreationError> {
+        let mut attrs: BTreeMap<SmolStr, (RestrictedExpr, Option<Type>)> = BTreeMap::new();
+        for layer in layers {
+            let layer_attrs: Vec<(SmolStr, RestrictedExpr, Option<Type>)> = match layer {
+                Context::Value(record) => Arc::unwrap_or_clone(record)
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let ty = v.type_of();
+                        (k, RestrictedExpr::from(v), Some(ty))
+                    })
+                    .collect(),
+                // By INVARIANT(restricted), all the expressions here are
+                // restricted expressions
+                Context::RestrictedResidual(record) => Arc::unwrap_or_clone(record)
+                    .into_iter()
+                    .map(|(k, e)| (k, RestrictedExpr::new_unchecked(e), None))
+                    .collect(),
+            };
+            for (attr, expr, ty) in layer_attrs {
+                if let (Some((_, Some(existing))), Some(new)) = (attrs.get(&attr), &ty) {
+                    if existing != new {
+                        return Err(context_creation_errors::LayerTypeConflict {
+                            attr,
+                            existing: existing.clone(),
+                            new: new.clone(),
+                        }
+                        .into());
+                    }
+                }
+                attrs.insert(attr, (expr, ty));
+            }
+        }
+        Self::from_pairs(
+            attrs.into_iter().map(|(attr, (expr, _))| (attr, expr)),
+            extensions,
+        )
+    }
+
     /// Create a `Context` from a string containing JSON (which must be a JSON
     /// object, not any other JSON type, or you will get an error here).
     /// JSON here must use the `__entity` and `__extn` escapes for entity
@@ -444,6 +555,72 @@ impl Context {
         }
     }
 
+    /// Convert this `Context` to a canonical JSON string, suitable for content
+    /// hashing.
+    ///
+    /// Equal contexts always produce the same string: object keys are sorted,
+    /// set elements appear in the order given by `Ord` on values (which, for
+    /// extension values, is defined per extension type; see
+    /// [`super::ExtensionValue`]), and extension values use their canonical
+    /// encoding (e.g., `decimal("1.0")` and `decimal("1.00")` are encoded the
+    /// same way). The output is compact.
+    pub fn to_json_canonical(&self) -> Result<String, JsonSerializationError> {
+        let json = match self {
+            Self::Value(record) => record
+                .iter()
+                .map(|(k, v)| {
+                    let cjson = CedarValueJson::from_value(v.clone().normalize_ext_values())?;
+                    Ok((k.to_string(), serde_json::to_value(cjson)?))
+                })
+                .collect::<Result<_, JsonSerializationError>>()?,
+            Self::RestrictedResidual(_) => self.to_json_value()?,
+        };
+        Ok(serde_json::to_string(&canonicalize_json_keys(json))?)
+    }
+
+    /// Add the given attributes, whose values are known, to this `Context`,
+    /// replacing any attributes with the same names
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn with_attrs(self, attrs: impl IntoIterator<Item = (SmolStr, Value)>) -> Self {
+        match self {
+            Context::Value(record) => {
+                let mut record = Arc::unwrap_or_clone(record);
+                record.extend(attrs);
+                Context::Value(Arc::new(record))
+            }
+            Context::RestrictedResidual(record) => {
+                let mut record = Arc::unwrap_or_clone(record);
+                record.extend(attrs.into_iter().map(|(k, v)| (k, v.into())));
+                Context::RestrictedResidual(Arc::new(record))
+            }
+        }
+    }
+
+    /// Add the attributes of `other` to this `Context`, replacing any
+    /// attributes with the same names
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn with_context_attrs(self, other: Context) -> Self {
+        match other {
+            Context::Value(attrs) => self.with_attrs(Arc::unwrap_or_clone(attrs)),
+            Context::RestrictedResidual(attrs) => {
+                let mut record: BTreeMap<SmolStr, Expr> =
+                    self.into_pairs().map(|(k, v)| (k, v.into())).collect();
+                record.extend(Arc::unwrap_or_clone(attrs));
+                // All the expressions are restricted expressions, and those
+                // from `other` satisfy INVARIANT(unknown)
+                Context::RestrictedResidual(Arc::new(record))
+            }
+        }
+    }
+
+    /// Does this `Context` have the attribute `attr`?
+    pub fn contains_key(&self, attr: &str) -> bool {
+        match self {
+            Context::Value(record) => record.contains_key(attr),
+            Context::RestrictedResidual(record) => record.contains_key(attr),
+        }
+    }
+
     /// Get the number of keys in this `Context`.
     pub fn num_keys(&self) -> usize {
         match self {
@@ -604,6 +781,11 @@ pub enum ContextCreationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ExpressionConstruction(#[from] ExpressionConstructionError),
+    /// An attribute has values of different types in different layers.
+    /// Only returned by `Context::layered()`
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    LayerTypeConflict(#[from] context_creation_errors::LayerTypeConflict),
 }
 
 impl ContextCreationError {
@@ -616,9 +798,10 @@ impl ContextCreationError {
 
 /// Error subtypes for [`ContextCreationError`]
 pub mod context_creation_errors {
-    use super::Expr;
+    use super::{Expr, Type};
     use crate::impl_diagnostic_from_method_on_field;
     use miette::Diagnostic;
+    use smol_str::SmolStr;
     use thiserror::Error;
 
     /// Error type for an expression that needed to be a record, but is not
@@ -637,6 +820,30 @@ pub mod context_creation_errors {
     impl Diagnostic for NotARecord {
         impl_diagnostic_from_method_on_field!(expr, source_loc);
     }
+
+    /// Error type for an attribute which has values of different types in
+    /// different layers of a layered context
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("context attribute `{attr}` has type {existing} in one layer but type {new} in a later layer")]
+    pub struct LayerTypeConflict {
+        /// Attribute with conflicting types
+        pub(super) attr: SmolStr,
+        /// Type of the attribute in the earlier layer
+        pub(super) existing: Type,
+        /// Type of the attribute in the later layer
+        pub(super) new: Type,
+    }
+
+    impl LayerTypeConflict {
+        /// The attribute with conflicting types
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+    }
 }
 
 /// Trait for schemas capable of validating `Request`s
@@ -844,4 +1051,132 @@ mod test {
         .unwrap();
         assert_eq!(context, roundtrip_json(&context));
     }
+
+    fn pairs(pairs: impl IntoIterator<Item = (&'static str, RestrictedExpr)>) -> Context {
+        Context::from_pairs(
+            pairs.into_iter().map(|(k, v)| (k.into(), v)),
+            Extensions::none(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn layered_later_layers_take_precedence() {
+        let context = Context::layered(
+            [
+                pairs([
+                    ("service", RestrictedExpr::val("photos")),
+                    ("region", RestrictedExpr::val("us-east-1")),
+                ]),
+                pairs([("region", RestrictedExpr::val("eu-west-1"))]),
+                pairs([("risk", RestrictedExpr::val(3))]),
+            ],
+            Extensions::none(),
+        )
+        .unwrap();
+        let expected = pairs([
+            ("service", RestrictedExpr::val("photos")),
+            ("region", RestrictedExpr::val("eu-west-1")),
+            ("risk", RestrictedExpr::val(3)),
+        ]);
+        assert_eq!(context, expected);
+        assert_eq!(
+            Context::layered([], Extensions::none()).unwrap(),
+            Context::empty()
+        );
+    }
+
+    #[test]
+    fn layered_type_conflict() {
+        assert_matches!(
+            Context::layered(
+                [
+                    pairs([("risk", RestrictedExpr::val(3))]),
+                    pairs([("risk", RestrictedExpr::val("high"))]),
+                ],
+                Extensions::none(),
+            ),
+            Err(ContextCreationError::LayerTypeConflict(e)) => {
+                assert_eq!(e.attr(), "risk");
+                assert_eq!(
+                    e.to_string(),
+                    "context attribute `risk` has type long in one layer but type string in a later layer"
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn layered_with_unknowns() {
+        let context = Context::layered(
+            [
+                pairs([(
+                    "risk",
+                    RestrictedExpr::unknown(Unknown::new_untyped("risk")),
+                )]),
+                pairs([("region", RestrictedExpr::val("eu-west-1"))]),
+            ],
+            Extensions::none(),
+        )
+        .unwrap();
+        assert_matches!(context, Context::RestrictedResidual(_));
+        // overriding the only unknown attribute yields a concrete context
+        let context = Context::layered(
+            [context, pairs([("risk", RestrictedExpr::val("high"))])],
+            Extensions::none(),
+        )
+        .unwrap();
+        assert_eq!(
+            context,
+            pairs([
+                ("risk", RestrictedExpr::val("high")),
+                ("region", RestrictedExpr::val("eu-west-1")),
+            ])
+        );
+    }
+
+    #[test]
+    fn canonical_json_of_sets_of_extension_values() {
+        let a = Context::from_json_str(
+            r#"{
+                "ips": [{"__extn": {"fn": "ip", "arg": "10.0.0.1/32"}}, {"__extn": {"fn": "ip", "arg": "::1"}}],
+                "mixed": [
+                    {"__extn": {"fn": "decimal", "arg": "1.5"}},
+                    {"__extn": {"fn": "datetime", "arg": "2024-01-01"}},
+                    {"__extn": {"fn": "decimal", "arg": "-2.0"}},
+                    {"__extn": {"fn": "decimal", "arg": "1.50"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let b = Context::from_json_str(
+            r#"{
+                "mixed": [
+                    {"__extn": {"fn": "datetime", "arg": "2024-01-01T00:00:00Z"}},
+                    {"__extn": {"fn": "decimal", "arg": "-2.000"}},
+                    {"__extn": {"fn": "decimal", "arg": "1.5000"}}
+                ],
+                "ips": [{"__extn": {"fn": "ip", "arg": "::1/128"}}, {"__extn": {"fn": "ip", "arg": "10.0.0.1"}}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(a, b);
+        let canonical = a.to_json_canonical().unwrap();
+        assert_eq!(canonical, b.to_json_canonical().unwrap());
+        // IPv4 addresses are ordered before IPv6 addresses, and values of
+        // different extension types are ordered by typename
+        assert_eq!(
+            canonical,
+            concat!(
+                r#"{"ips":[{"__extn":{"arg":"10.0.0.1/32","fn":"ip"}},{"__extn":{"arg":"::1/128","fn":"ip"}}],"#,
+                r#""mixed":[{"__extn":{"args":[{"__extn":{"arg":"1970-01-01","fn":"datetime"}},{"__extn":{"arg":"1704067200000ms","fn":"duration"}}],"fn":"offset"}},"#,
+                r#"{"__extn":{"arg":"-2.0000","fn":"decimal"}},{"__extn":{"arg":"1.5000","fn":"decimal"}}]}"#,
+            )
+        );
+        assert_eq!(
+            Context::from_json_str(&canonical).unwrap(),
+            a,
+            "canonical JSON should round-trip"
+        );
+    }
 }
@@ -0,0 +1,117 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the index used by `PolicySet` to find the policies
+//! whose scopes may be satisfied by a request without scanning every policy.
+
+use super::{
+    ActionConstraint, EntityReference, EntityType, EntityUID, Policy, PolicyID,
+    PrincipalOrResourceConstraint, Request,
+};
+use std::collections::HashMap;
+
+/// The part of a policy's scope that the index is keyed on. `None` means the
+/// policy may apply to any principal type, action, or resource type
+/// (respectively).
+type ScopeKey = (Option<EntityType>, Option<EntityUID>, Option<EntityType>);
+
+/// Index from the principal type, action, and resource type that a policy's
+/// scope requires to the policies with that scope.
+///
+/// A policy is keyed on a principal (resource) type only if its scope is
+/// `principal == E`, `principal is T`, or `principal is T in E`, and on an
+/// action only if its scope is `action == A`; hierarchy constraints (`in`)
+/// can be satisfied by entities of any type, so they are not indexed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ScopeIndex {
+    /// The ids of all policies, in the order of the policy set
+    ids: Vec<PolicyID>,
+    /// Map from a scope key to the positions in `ids` of the policies with
+    /// that key, in increasing order
+    buckets: HashMap<ScopeKey, Vec<usize>>,
+}
+
+impl ScopeIndex {
+    /// Index `policies`, which are assumed to be in policy set order
+    pub(crate) fn new<'a>(policies: impl IntoIterator<Item = &'a Policy>) -> Self {
+        let mut index = Self::default();
+        for (pos, policy) in policies.into_iter().enumerate() {
+            let key = (
+                required_type(policy.principal_constraint().as_inner()),
+                required_action(policy.action_constraint()),
+                required_type(policy.resource_constraint().as_inner()),
+            );
+            index.buckets.entry(key).or_default().push(pos);
+            index.ids.push(policy.id().clone());
+        }
+        index
+    }
+
+    /// Get the ids of the policies whose scopes may be satisfied by `request`,
+    /// in policy set order. Returns `None` if the principal, action, or
+    /// resource of `request` is unknown, in which case every policy may apply.
+    pub(crate) fn candidates(&self, request: &Request) -> Option<Vec<&PolicyID>> {
+        let principal = request.principal().uid()?;
+        let action = request.action().uid()?;
+        let resource = request.resource().uid()?;
+        let principal_keys = [None, Some(principal.entity_type().clone())];
+        let action_keys = [None, Some(action.clone())];
+        let resource_keys = [None, Some(resource.entity_type().clone())];
+
+        let mut positions = Vec::new();
+        for p in &principal_keys {
+            for a in &action_keys {
+                for r in &resource_keys {
+                    if let Some(bucket) = self.buckets.get(&(p.clone(), a.clone(), r.clone())) {
+                        positions.extend_from_slice(bucket);
+                    }
+                }
+            }
+        }
+        positions.sort_unstable();
+        Some(
+            positions
+                .into_iter()
+                .filter_map(|pos| self.ids.get(pos))
+                .collect(),
+        )
+    }
+}
+
+/// The entity type that a principal or resource constraint requires, if any
+fn required_type(constraint: &PrincipalOrResourceConstraint) -> Option<EntityType> {
+    match constraint {
+        PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid)) => {
+            Some(euid.entity_type().clone())
+        }
+        PrincipalOrResourceConstraint::Is(ty) | PrincipalOrResourceConstraint::IsIn(ty, _) => {
+            Some(ty.as_ref().clone())
+        }
+        PrincipalOrResourceConstraint::Any
+        | PrincipalOrResourceConstraint::In(_)
+        | PrincipalOrResourceConstraint::Eq(EntityReference::Slot(_)) => None,
+    }
+}
+
+/// The action that an action constraint requires, if any
+fn required_action(constraint: &ActionConstraint) -> Option<EntityUID> {
+    match constraint {
+        ActionConstraint::Eq(euid) => Some(euid.as_ref().clone()),
+        ActionConstraint::Any | ActionConstraint::In(_) => None,
+        #[cfg(feature = "tolerant-ast")]
+        ActionConstraint::ErrorConstraint => None,
+    }
+}
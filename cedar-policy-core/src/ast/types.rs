@@ -14,7 +14,13 @@
  * limitations under the License.
  */
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
 use crate::ast::EntityType;
+use crate::entities::{AttributeType, SchemaType};
 
 use super::Name;
 
@@ -84,6 +90,96 @@ impl std::fmt::Display for Type {
     }
 }
 
+/// The declared type of a value slot in a template, e.g., `Long` in
+/// `template(?threshold: Long) => ...`.
+///
+/// The [`std::fmt::Display`] implementation prints the type as it is written in
+/// a template header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum SlotType {
+    /// Boolean
+    #[serde(rename = "Boolean")]
+    Bool,
+    /// Signed integer
+    Long,
+    /// String
+    String,
+    /// Set with elements of the given type
+    Set {
+        /// Element type
+        element: Box<SlotType>,
+    },
+    /// Record with exactly the given attributes, all of which are required
+    Record {
+        /// Attributes and their types
+        attributes: BTreeMap<SmolStr, SlotType>,
+    },
+    /// Entity of the given type
+    Entity {
+        /// Entity type
+        #[cfg_attr(feature = "wasm", tsify(type = "string"))]
+        name: EntityType,
+    },
+    /// Extension type, e.g., `decimal`
+    Extension {
+        /// Name of the extension type
+        #[cfg_attr(feature = "wasm", tsify(type = "string"))]
+        name: Name,
+    },
+}
+
+impl SlotType {
+    /// Get the [`SchemaType`] that values provided for a slot of this type
+    /// are checked against
+    pub fn to_schema_type(&self) -> SchemaType {
+        match self {
+            Self::Bool => SchemaType::Bool,
+            Self::Long => SchemaType::Long,
+            Self::String => SchemaType::String,
+            Self::Set { element } => SchemaType::Set {
+                element_ty: Box::new(element.to_schema_type()),
+            },
+            Self::Record { attributes } => SchemaType::Record {
+                attrs: attributes
+                    .iter()
+                    .map(|(k, ty)| (k.clone(), AttributeType::required(ty.to_schema_type())))
+                    .collect(),
+                open_attrs: false,
+            },
+            Self::Entity { name } => SchemaType::Entity { ty: name.clone() },
+            Self::Extension { name } => SchemaType::Extension { name: name.clone() },
+        }
+    }
+}
+
+impl std::fmt::Display for SlotType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool => write!(f, "Bool"),
+            Self::Long => write!(f, "Long"),
+            Self::String => write!(f, "String"),
+            Self::Set { element } => write!(f, "Set<{element}>"),
+            Self::Record { attributes } if attributes.is_empty() => write!(f, "{{}}"),
+            Self::Record { attributes } => {
+                write!(f, "{{ ")?;
+                for (i, (k, ty)) in attributes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{}\": {ty}", k.escape_debug())?;
+                }
+                write!(f, " }}")
+            }
+            Self::Entity { name } => write!(f, "{name}"),
+            Self::Extension { name } => write!(f, "{name}"),
+        }
+    }
+}
+
 /// Trait for everything in Cedar that has a type known statically.
 ///
 /// For instance, `Value` and `Entity` implement this, but `Expr` does not
@@ -148,6 +148,90 @@ impl Value {
     pub fn all_literal_uids(&self) -> HashSet<EntityUID> {
         self.value.all_literal_uids()
     }
+
+    /// If this value is (or contains) an extension value whose type provides a
+    /// [`ExtensionValue::canonical_repr`], rebuild it so that the stored
+    /// constructor and arguments match the canonical form. This ensures that
+    /// equal values are represented the same way (e.g., when serialized)
+    /// regardless of which constructor originally created them.
+    pub fn normalize_ext_values(self) -> Value {
+        normalize_ext_values_inner(&self).unwrap_or(self)
+    }
+}
+
+/// Returns `Some(normalized)` if the value needed normalization, `None` if it was already fine.
+fn normalize_ext_values_inner(value: &Value) -> Option<Value> {
+    match &value.value {
+        ValueKind::Lit(_) => None,
+        ValueKind::ExtensionValue(ev) => {
+            let (func, args) = ev.value().canonical_repr()?;
+            Some(Value {
+                value: ValueKind::ExtensionValue(Arc::new(RepresentableExtensionValue::new(
+                    ev.value.clone(),
+                    func,
+                    args,
+                ))),
+                loc: value.loc.clone(),
+            })
+        }
+        ValueKind::Set(s) if s.fast.is_some() => {
+            // due to invariant on set, this means all elements are literals, hence nothing to norm
+            None
+        }
+
+        // The Set and Record normalization attempt to avoid cloning by scanning whether
+        // normalization is needed. Cloning to get the normalization only happens when it is
+        // actually required.
+        ValueKind::Set(s) => {
+            // Find the first element that needs normalization or return None.
+            let (idx, normalized) = s
+                .iter()
+                .enumerate()
+                .find_map(|(i, x)| normalize_ext_values_inner(x).map(|n| (i, n)))?;
+            // Clone elements before `idx` as-is, insert the normalized one,
+            // then normalize the rest.
+            let vals: Vec<Value> = s
+                .iter()
+                .take(idx)
+                .cloned()
+                .chain(std::iter::once(normalized))
+                .chain(
+                    s.iter()
+                        .skip(idx + 1)
+                        .map(|v| normalize_ext_values_inner(v).unwrap_or_else(|| v.clone())),
+                )
+                .collect();
+            Some(Value {
+                value: ValueKind::Set(Set::new(vals)),
+                loc: value.loc.clone(),
+            })
+        }
+        ValueKind::Record(r) => {
+            let mut iter = r.iter().enumerate();
+            let (idx, key, normalized) = loop {
+                let (i, (k, v)) = iter.next()?;
+                if let Some(n) = normalize_ext_values_inner(v) {
+                    break (i, k.clone(), n);
+                }
+            };
+            let map: BTreeMap<_, _> = r
+                .iter()
+                .take(idx)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .chain(std::iter::once((key, normalized)))
+                .chain(r.iter().skip(idx + 1).map(|(k, v)| {
+                    (
+                        k.clone(),
+                        normalize_ext_values_inner(v).unwrap_or_else(|| v.clone()),
+                    )
+                }))
+                .collect();
+            Some(Value {
+                value: ValueKind::Record(Arc::new(map)),
+                loc: value.loc.clone(),
+            })
+        }
+    }
 }
 
 impl BoundedDisplay for Value {
@@ -22,6 +22,7 @@
 
 use crate::ast::*;
 use crate::entities::Entities;
+use crate::evaluator::bytecode::CompiledPolicySet;
 use crate::evaluator::Evaluator;
 use crate::extensions::Extensions;
 use itertools::{Either, Itertools};
@@ -32,8 +33,10 @@ use std::sync::Arc;
 #[cfg(feature = "wasm")]
 extern crate tsify;
 
+mod environment;
 mod err;
 mod partial_response;
+pub use environment::{Clock, EvalEnvironment, FixedClock, SystemClock};
 pub use err::{AuthorizationError, ConcretizationError, ReauthorizationError};
 
 pub use partial_response::ErrorState;
@@ -43,9 +46,11 @@ pub use partial_response::PartialResponse;
 #[derive(Clone)] // `Debug` implemented manually below
 pub struct Authorizer {
     /// Cedar `Extension`s which will be used during requests to this `Authorizer`
-    extensions: &'static Extensions<'static>,
+    extensions: Arc<Extensions<'static>>,
     /// Error-handling behavior of this `Authorizer`
     error_handling: ErrorHandling,
+    /// Environment requests to this `Authorizer` are evaluated in
+    environment: EvalEnvironment,
 }
 
 /// Describes the possible Cedar error-handling modes.
@@ -64,17 +69,97 @@ impl Authorizer {
     /// Create a new `Authorizer`
     pub fn new() -> Self {
         Self {
-            extensions: Extensions::all_available(), // set at compile time
+            extensions: Extensions::all_available_shared(),
             error_handling: Default::default(),
+            environment: EvalEnvironment::default(),
         }
     }
 
+    /// Evaluate requests to this `Authorizer` with `extensions`, rather than
+    /// [`Extensions::all_available()`]
+    pub fn with_extensions(mut self, extensions: Arc<Extensions<'static>>) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// The extensions requests to this `Authorizer` are evaluated with
+    pub fn extensions(&self) -> &Extensions<'static> {
+        &self.extensions
+    }
+
+    /// Evaluate requests to this `Authorizer` in `environment`
+    pub fn with_environment(mut self, environment: EvalEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// The environment requests to this `Authorizer` are evaluated in
+    pub fn environment(&self) -> &EvalEnvironment {
+        &self.environment
+    }
+
     /// Returns an authorization response for `q` with respect to the given `Slice`.
     ///
     /// The language spec and formal model give a precise definition of how this is
     /// computed.
+    ///
+    /// Policies whose scopes cannot be satisfied by `q` are not evaluated (see
+    /// [`PolicySet::policies_for_request()`]); this does not change the
+    /// response, which only reports satisfied policies and errors. Disabled
+    /// policies (see [`PolicySet::set_enabled()`]) are not evaluated either.
     pub fn is_authorized(&self, q: Request, pset: &PolicySet, entities: &Entities) -> Response {
-        self.is_authorized_core(q, pset, entities).concretize()
+        let q = self.environment.prepare_request(q);
+        let eval = Evaluator::new(q.clone(), entities, &self.extensions);
+        let policies = pset.policies_for_request(&q);
+        self.evaluate_policies(|p| eval.partial_evaluate(p), q, policies)
+            .concretize()
+    }
+
+    /// Like [`Authorizer::is_authorized()`], but evaluates the policies of
+    /// `compiled` with their compiled programs, which is faster when the same
+    /// policies are evaluated many times. The response is the same.
+    pub fn is_authorized_compiled(
+        &self,
+        q: Request,
+        compiled: &CompiledPolicySet,
+        entities: &Entities,
+    ) -> Response {
+        let q = self.environment.prepare_request(q);
+        let eval = Evaluator::new(q.clone(), entities, &self.extensions);
+        let policies = compiled.policies().policies_for_request(&q);
+        self.evaluate_policies(
+            |p| match compiled.program(p.id()) {
+                Some(program) => eval.evaluate_compiled(p, program),
+                None => eval.partial_evaluate(p),
+            },
+            q,
+            policies,
+        )
+        .concretize()
+    }
+
+    /// Like [`Authorizer::is_authorized()`], but also evaluates the disabled
+    /// policies in `pset`, returning the ids of those that `q` would have
+    /// satisfied had they been enabled. Disabled policies do not affect the
+    /// response.
+    pub fn is_authorized_dry_run(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+    ) -> (Response, HashSet<PolicyID>) {
+        let q = self.environment.prepare_request(q);
+        let eval = Evaluator::new(q.clone(), entities, &self.extensions);
+        let would_have_applied = pset
+            .disabled_policies_for_request(&q)
+            .filter(|p| matches!(eval.evaluate(p), Ok(true)))
+            .map(|p| p.id().clone())
+            .collect();
+        let policies = pset.policies_for_request(&q);
+        let response = self
+            .evaluate_policies(|p| eval.partial_evaluate(p), q, policies)
+            .concretize();
+        (response, would_have_applied)
     }
 
     /// Returns an authorization response for `q` with respect to the given `Slice`.
@@ -86,7 +171,8 @@ impl Authorizer {
         pset: &PolicySet,
         entities: &Entities,
     ) -> PartialResponse {
-        let eval = Evaluator::new(q.clone(), entities, self.extensions);
+        let q = self.environment.prepare_request(q);
+        let eval = Evaluator::new(q.clone(), entities, &self.extensions);
         self.is_authorized_core_internal(&eval, q, pset)
     }
 
@@ -97,6 +183,17 @@ impl Authorizer {
         eval: &Evaluator<'_>,
         q: Request,
         pset: &PolicySet,
+    ) -> PartialResponse {
+        self.evaluate_policies(|p| eval.partial_evaluate(p), q, pset.enabled_policies())
+    }
+
+    /// Evaluate each of `policies` for `q` with `evaluate`, collecting the
+    /// results into a [`PartialResponse`]
+    fn evaluate_policies<'a>(
+        &self,
+        evaluate: impl Fn(&Policy) -> crate::evaluator::Result<Either<bool, Expr>>,
+        q: Request,
+        policies: impl IntoIterator<Item = &'a Policy>,
     ) -> PartialResponse {
         let mut true_permits = vec![];
         let mut true_forbids = vec![];
@@ -106,9 +203,9 @@ impl Authorizer {
         let mut residual_forbids = vec![];
         let mut errors = vec![];
 
-        for p in pset.policies() {
+        for p in policies {
             let (id, annotations) = (p.id().clone(), p.annotations_arc().clone());
-            match eval.partial_evaluate(p) {
+            match evaluate(p) {
                 Ok(Either::Left(satisfied)) => match (satisfied, p.effect()) {
                     (true, Effect::Permit) => true_permits.push((id, annotations)),
                     (true, Effect::Forbid) => true_forbids.push((id, annotations)),
@@ -0,0 +1,135 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`EvalEnvironment`], the ambient state which an
+//! [`super::Authorizer`] may consult, and the [`Clock`] it reads the current
+//! time from.
+
+use crate::ast::Request;
+#[cfg(feature = "datetime")]
+use crate::{ast::Context, extensions::Extensions};
+use smol_str::SmolStr;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// A source of the current time
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> SystemTime;
+}
+
+/// A [`Clock`] which reads the system clock.
+///
+/// The system clock is not available on `wasm32-unknown-unknown`, where
+/// reading it panics; use a [`FixedClock`] or another [`Clock`] there.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A [`Clock`] frozen at a fixed time, e.g., for tests or for replaying past
+/// authorization requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(SystemTime);
+
+impl FixedClock {
+    /// A clock which is always at `time`
+    pub fn new(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+/// The environment in which an [`super::Authorizer`] evaluates requests.
+///
+/// Evaluation is deterministic: it uses no randomness, and the environment's
+/// [`Clock`] is the only ambient state that the authorizer reads. So with a
+/// [`FixedClock`], authorizing the same request against the same policies and
+/// entities always produces the same response.
+///
+/// The default environment reads the [`SystemClock`], but only when the
+/// current time is needed, e.g., for [`EvalEnvironment::with_current_time_attr()`].
+#[derive(Debug, Clone)]
+pub struct EvalEnvironment {
+    clock: Arc<dyn Clock>,
+    current_time_attr: Option<SmolStr>,
+}
+
+impl Default for EvalEnvironment {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl EvalEnvironment {
+    /// An environment reading the current time from `clock`
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Arc::new(clock),
+            current_time_attr: None,
+        }
+    }
+
+    /// Provide the current time, as a `datetime`, in the context attribute
+    /// `attr` of requests whose context doesn't set it. Requests which set
+    /// `attr`, or whose context is unknown, are not changed.
+    #[cfg(feature = "datetime")]
+    pub fn with_current_time_attr(mut self, attr: impl Into<SmolStr>) -> Self {
+        self.current_time_attr = Some(attr.into());
+        self
+    }
+
+    /// The context attribute provided with the current time, if any. See
+    /// [`EvalEnvironment::with_current_time_attr()`].
+    pub fn current_time_attr(&self) -> Option<&SmolStr> {
+        self.current_time_attr.as_ref()
+    }
+
+    /// The current time, according to this environment's [`Clock`]
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// `request` with its context extended by this environment, as it is
+    /// evaluated by an [`super::Authorizer`] using this environment
+    pub fn prepare_request(&self, request: Request) -> Request {
+        #[cfg(feature = "datetime")]
+        if let (Some(attr), Some(context)) = (&self.current_time_attr, &request.context) {
+            if !context.contains_key(attr) {
+                let now = crate::extensions::datetime::datetime_from_system_time(self.now());
+                let extensions = Extensions::all_available();
+                let layered = Context::from_pairs([(attr.clone(), now)], extensions)
+                    .and_then(|now| Context::layered([now, context.clone()], extensions));
+                if let Ok(context) = layered {
+                    return Request {
+                        context: Some(context),
+                        ..request
+                    };
+                }
+            }
+        }
+        request
+    }
+}
@@ -67,6 +67,13 @@ pub enum ConcretizationError {
         /// The provided value
         given_value: Value,
     },
+    /// Errors that occur when providing a context attribute which is not one
+    /// of the unknown attributes of the context
+    #[error("context attribute `{attr}` was not declared unknown")]
+    KnownContextAttr {
+        /// Name of the attribute
+        attr: SmolStr,
+    },
     /// Errors that occur when evaluating partial values
     #[error(transparent)]
     #[diagnostic(transparent)]
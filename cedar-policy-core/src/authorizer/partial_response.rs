@@ -26,7 +26,9 @@ use super::{
 use crate::{ast::PolicyID, evaluator::EvaluationError};
 
 #[cfg(feature = "partial-eval")]
-use smol_str::SmolStr;
+use smol_str::{SmolStr, ToSmolStr};
+#[cfg(feature = "partial-eval")]
+use std::collections::BTreeSet;
 
 #[cfg(feature = "partial-eval")]
 use crate::{entities::Entities, evaluator::Evaluator};
@@ -34,7 +36,7 @@ use crate::{entities::Entities, evaluator::Evaluator};
 #[cfg(feature = "partial-eval")]
 use super::{
     err::{ConcretizationError, ReauthorizationError},
-    Authorizer, Context, PolicySet, PolicySetError, Value,
+    Authorizer, Context, PolicySet, PolicySetError, Value, CONTEXT_UNKNOWN_NAME,
 };
 
 type PolicyComponents<'a> = (Effect, &'a PolicyID, &'a Arc<Expr>, &'a Arc<Annotations>);
@@ -333,26 +335,62 @@ impl PartialResponse {
         auth: &Authorizer,
         es: &Entities,
     ) -> Result<Self, ReauthorizationError> {
-        let policyset = self.all_residual_policies()?;
+        let policyset = self.all_residual_policies(|expr| expr.clone())?;
         let new_request = self.concretize_request(mapping)?;
         // Although this function takes a HashMap, keep the internal mapping function generic
         let unknowns_mapper =
             |unknown_name: &str| -> Option<Value> { mapping.get(unknown_name).cloned() };
         // Construct an evaluator resolving these specific unknown mappings
-        let eval = Evaluator::new(new_request.clone(), es, auth.extensions)
+        let eval = Evaluator::new(new_request.clone(), es, &auth.extensions)
             .with_unknowns_mapper(Box::new(unknowns_mapper));
         Ok(auth.is_authorized_core_internal(&eval, new_request, &policyset))
     }
 
+    /// Resume evaluation of this response with data which wasn't available
+    /// before: entities which were missing from the (partial) entity store,
+    /// and attributes of the context. Only the residuals are evaluated again.
+    ///
+    /// `additional_entities` is used to evaluate the residuals, so it must
+    /// contain the newly available entities, along with any others the
+    /// residuals still refer to. Entities which are still missing stay unknown
+    /// if it is a partial store.
+    ///
+    /// If the context was unknown, `additional_context` becomes the context,
+    /// unless it is empty. Otherwise, its attributes must be ones declared
+    /// with [`Request::with_unknown_context_attrs`], and the remaining unknown
+    /// attributes stay unknown.
+    #[cfg(feature = "partial-eval")]
+    pub fn resume(
+        &self,
+        auth: &Authorizer,
+        additional_entities: &Entities,
+        additional_context: &Context,
+    ) -> Result<Self, ReauthorizationError> {
+        let new_request = self.resume_request(additional_context)?;
+        // Unknowns standing for the newly available entities are replaced by
+        // the entities, and `unknown("context")` by the updated `context`
+        let entities: HashMap<SmolStr, Value> = additional_entities
+            .iter()
+            .map(|e| (e.uid().to_smolstr(), Value::from(e.uid().clone())))
+            .collect();
+        let policyset = self
+            .all_residual_policies(|expr| Arc::new(expr.substitute_with_context_var(&entities)))?;
+        let eval = Evaluator::new(new_request.clone(), additional_entities, &auth.extensions);
+        Ok(auth.is_authorized_core_internal(&eval, new_request, &policyset))
+    }
+
     #[cfg(feature = "partial-eval")]
-    fn all_residual_policies(&self) -> Result<PolicySet, PolicySetError> {
+    fn all_residual_policies(
+        &self,
+        rewrite: impl Fn(&Arc<Expr>) -> Arc<Expr>,
+    ) -> Result<PolicySet, PolicySetError> {
         PolicySet::try_from_iter(
             self.all_permit_residuals()
                 .chain(self.all_forbid_residuals())
                 .map(|(effect, id, expr, annotations)| {
                     Policy::from_when_clause_annos(
                         effect,
-                        expr.clone(),
+                        rewrite(expr),
                         id.clone(),
                         expr.source_loc().cloned(),
                         annotations.clone(),
@@ -367,6 +405,7 @@ impl PartialResponse {
         mapping: &HashMap<SmolStr, Value>,
     ) -> Result<Request, ConcretizationError> {
         let mut context = self.request.context.clone();
+        let mut unknown_context_attrs = self.request.unknown_context_attrs.clone();
 
         let principal = self.request.principal().concretize("principal", mapping)?;
 
@@ -374,9 +413,24 @@ impl PartialResponse {
 
         let resource = self.request.resource.concretize("resource", mapping)?;
 
-        if let Some((key, val)) = mapping.get_key_value("context") {
+        if let Some((key, val)) = mapping.get_key_value(CONTEXT_UNKNOWN_NAME) {
             if let Ok(attrs) = val.get_as_record() {
                 match self.request.context() {
+                    // The value provides the context attributes which were
+                    // unknown; those it doesn't have are absent
+                    Some(ctx) if !self.request.unknown_context_attrs.is_empty() => {
+                        context = Some(
+                            ctx.clone().with_attrs(
+                                attrs
+                                    .iter()
+                                    .filter(|(k, _)| {
+                                        self.request.unknown_context_attrs.contains(*k)
+                                    })
+                                    .map(|(k, v)| (k.clone(), v.clone())),
+                            ),
+                        );
+                        unknown_context_attrs = BTreeSet::new();
+                    }
                     Some(ctx) => {
                         return Err(ConcretizationError::VarConfictError {
                             id: key.to_owned(),
@@ -405,9 +459,30 @@ impl PartialResponse {
             action,
             resource,
             context,
+            unknown_context_attrs,
         })
     }
 
+    #[cfg(feature = "partial-eval")]
+    fn resume_request(&self, additional_context: &Context) -> Result<Request, ConcretizationError> {
+        let mut request = self.request.as_ref().clone();
+        if additional_context.num_keys() == 0 {
+            return Ok(request);
+        }
+        request.context = match request.context {
+            None => Some(additional_context.clone()),
+            Some(context) => {
+                for (attr, _) in additional_context.clone() {
+                    if !request.unknown_context_attrs.remove(&attr) {
+                        return Err(ConcretizationError::KnownContextAttr { attr });
+                    }
+                }
+                Some(context.with_context_attrs(additional_context.clone()))
+            }
+        };
+        Ok(request)
+    }
+
     fn errors(self) -> impl Iterator<Item = AuthorizationError> {
         self.residual_forbids
             .into_iter()
@@ -565,11 +640,14 @@ mod test {
 
     #[cfg(feature = "partial-eval")]
     use crate::{
-        authorizer::{EntityUID, RestrictedExpr, Unknown},
+        authorizer::{Entity, EntityUID, RestrictedExpr, Unknown},
+        entities::{NoEntitiesSchema, TCComputation},
         extensions::Extensions,
         parser::parse_policyset,
         FromNormalizedStr,
     };
+    #[cfg(feature = "partial-eval")]
+    use cool_asserts::assert_matches;
 
     use super::*;
 
@@ -806,12 +884,12 @@ mod test {
         )
         .unwrap();
 
-        let partial_request = Request {
-            principal: EntityUIDEntry::known(r#"NS::"a""#.parse().unwrap(), None),
-            action: EntityUIDEntry::unknown(),
-            resource: EntityUIDEntry::unknown(),
-            context: Some(context_unknown),
-        };
+        let partial_request = Request::new_unchecked(
+            EntityUIDEntry::known(r#"NS::"a""#.parse().unwrap(), None),
+            EntityUIDEntry::unknown(),
+            EntityUIDEntry::unknown(),
+            Some(context_unknown),
+        );
 
         let entities = Entities::new();
 
@@ -850,4 +928,89 @@ mod test {
             Some(Decision::Deny)
         );
     }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn resume() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action, resource) when {
+                principal.level > 3 && context has mfa && context.mfa
+            };
+            forbid(principal, action, resource) when { context.blocked };
+        "#,
+        )
+        .unwrap();
+        let request = Request::new_unchecked(
+            EntityUIDEntry::known(r#"NS::"a""#.parse().unwrap(), None),
+            EntityUIDEntry::known(r#"Action::"view""#.parse().unwrap(), None),
+            EntityUIDEntry::known(r#"NS::"b""#.parse().unwrap(), None),
+            Some(
+                Context::from_pairs(
+                    [("blocked".into(), RestrictedExpr::val(false))],
+                    Extensions::all_available(),
+                )
+                .unwrap(),
+            ),
+        )
+        .with_unknown_context_attrs(["mfa".into(), "ip".into()]);
+        let authorizer = Authorizer::new();
+        let response =
+            authorizer.is_authorized_core(request, &policies, &Entities::new().partial());
+        assert_eq!(response.decision(), None);
+        let residual = response.get(&PolicyID::from_string("policy0")).unwrap();
+        assert_eq!(residual.unknown_entities().len(), 1);
+
+        // The principal becomes available, but `context.mfa` is still unknown
+        let principal = Entity::new(
+            r#"NS::"a""#.parse().unwrap(),
+            [("level".into(), RestrictedExpr::val(5))],
+            HashSet::new(),
+            HashSet::new(),
+            [],
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let entities = Entities::from_entities(
+            [principal],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .partial();
+        let response = response
+            .resume(&authorizer, &entities, &Context::empty())
+            .unwrap();
+        assert_eq!(response.decision(), None);
+        let residual = response.get(&PolicyID::from_string("policy0")).unwrap();
+        assert_eq!(residual.unknown_entities(), HashSet::new());
+        assert_eq!(
+            residual.unknown_context_attrs(),
+            HashSet::from(["mfa".into()])
+        );
+
+        // Attributes which weren't declared unknown can't be provided
+        let blocked = Context::from_pairs(
+            [("blocked".into(), RestrictedExpr::val(true))],
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_matches!(
+            response.resume(&authorizer, &Entities::new(), &blocked),
+            Err(ReauthorizationError::ConcretizationError(
+                ConcretizationError::KnownContextAttr { attr }
+            )) if attr == "blocked"
+        );
+
+        let mfa = Context::from_pairs(
+            [("mfa".into(), RestrictedExpr::val(true))],
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let response = response
+            .resume(&authorizer, &Entities::new(), &mfa)
+            .unwrap();
+        assert_eq!(response.decision(), Some(Decision::Allow));
+    }
 }
@@ -14,7 +14,7 @@
  * limitations under the License.
  */
 
-//! This module contains the batched evaluator implementation and the (internal) definition of [`EntityLoader`]
+//! This module contains the batched evaluator implementation
 
 pub mod err;
 
@@ -37,14 +37,7 @@ use crate::{ast::PolicySet, extensions::Extensions};
 
 use crate::tpe::{entities::PartialEntities, evaluator::Evaluator};
 
-/// Loads entities based on their EntityUID.
-/// See the public version in `api.rs`
-pub trait EntityLoader {
-    /// Load all entities for the given set of entity UIDs.
-    /// Returns a map from [`EntityUID`] to `Option<Entity>`, where `None` indicates
-    /// the entity does not exist.
-    fn load_entities(&mut self, uids: &HashSet<EntityUID>) -> HashMap<EntityUID, Option<Entity>>;
-}
+pub use crate::entities::loader::EntityLoader;
 
 fn concrete_request_to_partial(
     request: &Request,
@@ -91,50 +84,93 @@ pub fn is_authorized_batched(
     loader: &mut dyn EntityLoader,
     max_iters: u32,
 ) -> Result<Decision, BatchedEvalError> {
-    let request = concrete_request_to_partial(request, schema)?;
-    let mut entities = PartialEntities::default();
-    let initial_evaluator = Evaluator {
-        request: &request,
-        entities: &entities,
-        extensions: Extensions::all_available(),
-    };
-    let mut residuals: Vec<ResidualPolicy> = policy_residual_map(&request, ps, schema)?
-        .into_iter()
-        .map(|(id, expr)| {
-            let residual = initial_evaluator.interpret(&expr);
-            #[expect(
-                clippy::unwrap_used,
-                reason = "exprs and policy set contain the same policy ids"
-            )]
-            ResidualPolicy::new(Arc::new(residual), Arc::new(ps.get(id).unwrap().clone()))
-        })
-        .collect();
-
+    let mut evaluation = BatchedEvaluation::new(request, ps, schema)?;
     for _i in 0..max_iters {
-        let ids = residuals.iter().flat_map(|r| r.all_literal_uids());
-        let mut to_load = HashSet::new();
-        // filter to_load for already loaded entities
-        for uid in ids {
-            if !entities.contains_entity(&uid) {
-                to_load.insert(uid);
-            }
+        let loaded_entities = loader.load_entities(&evaluation.entities_to_load());
+        evaluation.add_entities(loaded_entities)?;
+        if evaluation.is_done() {
+            break;
         }
-        // Subtle: missing entities are equivalent empty entities in both normal and partial evaluation.
-        let loaded_entities = loader.load_entities(&to_load);
+    }
+    evaluation.decision()
+}
+
+/// The state of a batched authorization, between loads of entities.
+/// [`is_authorized_batched`] drives it with an [`EntityLoader`]; callers which
+/// load entities some other way (e.g., asynchronously) can drive it
+/// themselves.
+#[derive(Debug)]
+pub struct BatchedEvaluation<'a> {
+    request: PartialRequest,
+    entities: PartialEntities,
+    residuals: Vec<ResidualPolicy>,
+    ps: &'a PolicySet,
+    schema: &'a ValidatorSchema,
+}
+
+impl<'a> BatchedEvaluation<'a> {
+    /// Start authorizing `request`, without any entities loaded
+    pub fn new(
+        request: &Request,
+        ps: &'a PolicySet,
+        schema: &'a ValidatorSchema,
+    ) -> Result<Self, BatchedEvalError> {
+        let request = concrete_request_to_partial(request, schema)?;
+        let entities = PartialEntities::default();
+        let initial_evaluator = Evaluator {
+            request: &request,
+            entities: &entities,
+            extensions: Extensions::all_available(),
+        };
+        let residuals: Vec<ResidualPolicy> = policy_residual_map(&request, ps, schema)?
+            .into_iter()
+            .map(|(id, expr)| {
+                let residual = initial_evaluator.interpret(&expr);
+                #[expect(
+                    clippy::unwrap_used,
+                    reason = "exprs and policy set contain the same policy ids"
+                )]
+                ResidualPolicy::new(Arc::new(residual), Arc::new(ps.get(id).unwrap().clone()))
+            })
+            .collect();
+        Ok(Self {
+            request,
+            entities,
+            residuals,
+            ps,
+            schema,
+        })
+    }
 
-        // check that all requested entities were loaded and return error otherwise
+    /// The entities the residual policies refer to which haven't been loaded
+    /// yet
+    pub fn entities_to_load(&self) -> HashSet<EntityUID> {
+        self.residuals
+            .iter()
+            .flat_map(|r| r.all_literal_uids())
+            // filter to_load for already loaded entities
+            .filter(|uid| !self.entities.contains_entity(uid))
+            .collect()
+    }
 
+    /// Add loaded entities, where `None` indicates that an entity does not
+    /// exist, and evaluate the residual policies again
+    pub fn add_entities(
+        &mut self,
+        loaded_entities: HashMap<EntityUID, Option<Entity>>,
+    ) -> Result<(), BatchedEvalError> {
+        // Subtle: missing entities are equivalent empty entities in both normal and partial evaluation.
         for (id, e_option) in loaded_entities {
             match e_option {
                 Some(e) => {
-                    entities.add_entities(
+                    self.entities.add_entities(
                         iter::once((id, PartialEntity::try_from(e)?)),
-                        schema,
+                        self.schema,
                         TCComputation::AssumeAlreadyComputed,
                     )?;
                 }
                 None => {
-                    entities.add_entity_trusted(
+                    self.entities.add_entity_trusted(
                         id.clone(),
                         PartialEntity::try_from(Entity::with_uid(id))?,
                     )?;
@@ -143,13 +179,13 @@ pub fn is_authorized_batched(
         }
 
         let evaluator = Evaluator {
-            request: &request,
-            entities: &entities,
+            request: &self.request,
+            entities: &self.entities,
             extensions: Extensions::all_available(),
         };
 
         // perform partial evaluation again
-        residuals = residuals
+        self.residuals = std::mem::take(&mut self.residuals)
             .into_iter()
             .map(|residual| {
                 #[expect(
@@ -158,24 +194,33 @@ pub fn is_authorized_batched(
                 )]
                 ResidualPolicy::new(
                     Arc::new(evaluator.interpret(&residual.get_residual())),
-                    Arc::new(ps.get(&residual.get_policy_id()).unwrap().clone()),
+                    Arc::new(self.ps.get(&residual.get_policy_id()).unwrap().clone()),
                 )
             })
             .collect();
+        Ok(())
+    }
 
-        // if all the residuals are done, exit
-        if residuals
+    /// Whether every residual policy has been fully evaluated
+    pub fn is_done(&self) -> bool {
+        self.residuals
             .iter()
             .all(|r| !matches!(*(r.get_residual()), Residual::Partial { .. }))
-        {
-            break;
-        }
     }
 
-    let response = Response::new(residuals.into_iter(), &request, &entities, schema);
-
-    match response.decision() {
-        Some(decision) => Ok(decision),
-        None => Err(InsufficientIterationsError {}.into()),
+    /// The decision, or an error if it depends on entities which haven't been
+    /// loaded
+    pub fn decision(self) -> Result<Decision, BatchedEvalError> {
+        let response = Response::new(
+            self.residuals.into_iter(),
+            &self.request,
+            &self.entities,
+            self.schema,
+        );
+
+        match response.decision() {
+            Some(decision) => Ok(decision),
+            None => Err(InsufficientIterationsError {}.into()),
+        }
     }
 }
@@ -26,17 +26,25 @@ use std::sync::Arc;
 pub mod conformance;
 /// Module for error types
 pub mod err;
+/// Module for exporting the entity hierarchy as a graph
+pub mod graph;
+/// Module for checking referential integrity of entity slices
+pub mod integrity;
 pub mod json;
+/// Module defining the trait for loading entities on demand
+pub mod loader;
 use json::err::JsonSerializationError;
 
 pub use json::{
     AllEntitiesNoAttrsSchema, AttributeType, CedarValueJson, ContextJsonParser, ContextSchema,
-    EntityJson, EntityJsonParser, EntityTypeDescription, EntityUidJson, FnAndArgs,
-    NoEntitiesSchema, NoStaticContext, Schema, SchemaType, TypeAndId,
+    EntityConstraint, EntityJson, EntityJsonParser, EntityTypeDescription, EntityUidJson,
+    FnAndArgs, JsonCoercion, NoEntitiesSchema, NoStaticContext, Refinement, Schema, SchemaType,
+    StringPattern, TypeAndId, UndeclaredAttrHandling, UndeclaredAttrs,
 };
 
 use conformance::EntitySchemaConformanceChecker;
 use err::*;
+use integrity::DanglingReference;
 #[cfg(feature = "partial-eval")]
 use smol_str::ToSmolStr;
 
@@ -112,6 +120,28 @@ impl Entities {
         self.entities.values().map(|e| e.as_ref())
     }
 
+    /// Attach `provenance` to every entity currently in this `Entities`,
+    /// replacing any provenance they already had.
+    ///
+    /// Provenance is attached to the entities themselves, so it is preserved
+    /// when they are later added to another `Entities` (e.g., with
+    /// [`Self::add_entities()`]). When an entity is added which is
+    /// structurally equal to one already present, the existing entity and its
+    /// provenance are kept.
+    pub fn with_provenance(mut self, provenance: EntityProvenance) -> Self {
+        let provenance = Arc::new(provenance);
+        for entity in self.entities.values_mut() {
+            Arc::make_mut(entity).set_provenance(Arc::clone(&provenance));
+        }
+        self
+    }
+
+    /// Get the provenance metadata attached to the entity with the given UID,
+    /// if the entity exists and has any
+    pub fn provenance(&self, uid: &EntityUID) -> Option<&EntityProvenance> {
+        self.entities.get(uid)?.provenance()
+    }
+
     /// Test if two entity hierarchies are structurally equal. The hierarchies
     /// must contain the same set of entity ids, and the entities with each id
     /// must be structurally equal (decided by [`Entity::deep_eq`]). Ancestor
@@ -130,6 +160,50 @@ impl Entities {
         })
     }
 
+    /// Find every reference from an entity in this store (in its parents,
+    /// attributes, or tags) to an entity UID that is not in this store.
+    ///
+    /// References to any UID in `allowed_dangling` are considered intentional
+    /// and are not reported. The result is sorted by referencing entity.
+    pub fn dangling_references(
+        &self,
+        allowed_dangling: &HashSet<EntityUID>,
+    ) -> Vec<DanglingReference> {
+        let mut refs: Vec<DanglingReference> = self
+            .entities
+            .values()
+            .flat_map(|entity| {
+                integrity::dangling_references_of(entity, |euid| {
+                    self.entities.contains_key(euid) || allowed_dangling.contains(euid)
+                })
+            })
+            .collect();
+        refs.sort_by(|a, b| {
+            (a.referrer.to_string(), &a.site, a.target.to_string()).cmp(&(
+                b.referrer.to_string(),
+                &b.site,
+                b.target.to_string(),
+            ))
+        });
+        refs
+    }
+
+    /// Check that every entity UID referenced by an entity in this store
+    /// exists in this store, except for the UIDs in `allowed_dangling`.
+    ///
+    /// # Errors
+    /// - [`DanglingReferencesError`] listing all dangling references, if there
+    ///   are any
+    pub fn check_referential_integrity(
+        &self,
+        allowed_dangling: &HashSet<EntityUID>,
+    ) -> std::result::Result<(), DanglingReferencesError> {
+        match nonempty::NonEmpty::from_vec(self.dangling_references(allowed_dangling)) {
+            None => Ok(()),
+            Some(references) => Err(DanglingReferencesError { references }),
+        }
+    }
+
     /// Adds the [`crate::ast::Entity`]s in the iterator to this [`Entities`].
     /// Fails if
     ///  - there is a pair of non-identical entities in the passed iterator with the same Entity UID, or
@@ -387,6 +461,26 @@ impl Entities {
         Ok(())
     }
 
+    /// Convert an `Entities` object into a canonical entities JSON string,
+    /// suitable for diffing and content hashing.
+    ///
+    /// Equal `Entities` always produce the same string: entities are sorted
+    /// by UID, parents are sorted, object keys (including in attribute
+    /// values) are sorted, and extension values use their canonical
+    /// encoding (e.g., `decimal("1.0")` and `decimal("1.00")` are encoded the
+    /// same way). The output is compact, and parse-able even with no `Schema`.
+    pub fn to_json_canonical(&self) -> Result<String> {
+        let mut entities: Vec<&Entity> = self.iter().collect();
+        entities.sort_by_cached_key(|entity| entity.uid().to_string());
+        let ejsons = entities
+            .into_iter()
+            .map(EntityJson::from_entity_canonical)
+            .collect::<std::result::Result<Vec<_>, JsonSerializationError>>()?;
+        let json = serde_json::to_value(ejsons).map_err(JsonSerializationError::from)?;
+        Ok(serde_json::to_string(&json::canonicalize_json_keys(json))
+            .map_err(JsonSerializationError::from)?)
+    }
+
     /// Internal helper function to convert this `Entities` into a `Vec<EntityJson>`
     fn to_ejsons(&self) -> Result<Vec<EntityJson>> {
         self.entities
@@ -411,6 +505,28 @@ impl Entities {
         entities_by_type
     }
 
+    /// Export the entity hierarchy as a typed graph, with an edge from each
+    /// entity to each of its direct parents
+    pub fn to_graph(&self) -> graph::EntityGraph {
+        graph::EntityGraph::new(self.iter())
+    }
+
+    /// Write entities into a DOT graph, with the given `options`. Unlike
+    /// [`Self::to_dot_str()`], the output is deterministic, can include
+    /// attribute summaries, and shows parents which are not in this
+    /// `Entities`.
+    ///
+    /// This function only returns an `Err` result on a failing `write!` to
+    /// `f`, so it is infallible if the `Write` implementation cannot fail
+    /// (e.g., `String`).
+    pub fn to_dot(
+        &self,
+        f: &mut impl std::fmt::Write,
+        options: &graph::DotOptions,
+    ) -> std::fmt::Result {
+        self.to_graph().write_dot(f, options)
+    }
+
     /// Write entities into a DOT graph.  This function only returns an `Err`
     /// result on a failing `write!` to `f`, so it is infallible if the `Write`
     /// implementation cannot fail (e.g., `String`).
@@ -2512,6 +2628,140 @@ mod entities_tests {
     }
 }
 
+#[cfg(test)]
+mod referential_integrity_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use integrity::ReferenceSite;
+
+    fn entities_with_refs() -> Entities {
+        let mut alice = Entity::new(
+            EntityUID::with_eid("alice"),
+            [
+                (
+                    "manager".into(),
+                    RestrictedExpr::val(EntityUID::with_eid("bob")),
+                ),
+                (
+                    "friends".into(),
+                    RestrictedExpr::set([
+                        RestrictedExpr::val(EntityUID::with_eid("carol")),
+                        RestrictedExpr::val(EntityUID::with_eid("dave")),
+                        RestrictedExpr::val(EntityUID::with_eid("dave")),
+                    ]),
+                ),
+            ],
+            HashSet::new(),
+            HashSet::new(),
+            [(
+                "owner".into(),
+                RestrictedExpr::record([(
+                    "who".into(),
+                    RestrictedExpr::val(EntityUID::with_eid("erin")),
+                )])
+                .unwrap(),
+            )],
+            Extensions::none(),
+        )
+        .unwrap();
+        alice.add_parent(EntityUID::with_eid("admins"));
+        alice.add_parent(EntityUID::with_eid("carol"));
+        let carol = Entity::with_uid(EntityUID::with_eid("carol"));
+        Entities::from_entities(
+            [alice, carol],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn no_dangling_references() {
+        let carol = Entity::with_uid(EntityUID::with_eid("carol"));
+        let mut alice = Entity::with_uid(EntityUID::with_eid("alice"));
+        alice.add_parent(EntityUID::with_eid("carol"));
+        let entities = Entities::from_entities(
+            [alice, carol],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        assert!(entities.dangling_references(&HashSet::new()).is_empty());
+        assert_matches!(
+            entities.check_referential_integrity(&HashSet::new()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reports_parents_attrs_and_tags() {
+        let entities = entities_with_refs();
+        let refs = entities.dangling_references(&HashSet::new());
+        let found: Vec<_> = refs
+            .iter()
+            .map(|r| {
+                (
+                    r.referrer().to_string(),
+                    r.site().clone(),
+                    r.target().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    r#"test_entity_type::"alice""#.to_string(),
+                    ReferenceSite::Parent,
+                    r#"test_entity_type::"admins""#.to_string()
+                ),
+                (
+                    r#"test_entity_type::"alice""#.to_string(),
+                    ReferenceSite::Attribute("friends".into()),
+                    r#"test_entity_type::"dave""#.to_string()
+                ),
+                (
+                    r#"test_entity_type::"alice""#.to_string(),
+                    ReferenceSite::Attribute("manager".into()),
+                    r#"test_entity_type::"bob""#.to_string()
+                ),
+                (
+                    r#"test_entity_type::"alice""#.to_string(),
+                    ReferenceSite::Tag("owner".into()),
+                    r#"test_entity_type::"erin""#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_dangling_references() {
+        let entities = entities_with_refs();
+        let allowed = HashSet::from([
+            EntityUID::with_eid("admins"),
+            EntityUID::with_eid("bob"),
+            EntityUID::with_eid("dave"),
+        ]);
+        assert_matches!(entities.check_referential_integrity(&allowed), Err(err) => {
+            let targets: Vec<_> = err.references().map(|r| r.target().clone()).collect();
+            assert_eq!(targets, vec![EntityUID::with_eid("erin")]);
+            assert_eq!(
+                err.to_string(),
+                r#"`test_entity_type::"alice"` references `test_entity_type::"erin"` in its tag `owner`, but that entity does not exist"#
+            );
+        });
+        let allowed = HashSet::from([
+            EntityUID::with_eid("admins"),
+            EntityUID::with_eid("bob"),
+            EntityUID::with_eid("dave"),
+            EntityUID::with_eid("erin"),
+        ]);
+        assert_matches!(entities.check_referential_integrity(&allowed), Ok(()));
+    }
+}
+
 #[cfg(test)]
 #[expect(clippy::panic, clippy::cognitive_complexity, reason = "Unit Test Code")]
 mod schema_based_parsing_tests {
@@ -3466,6 +3716,84 @@ mod schema_based_parsing_tests {
         });
     }
 
+    #[cfg(all(feature = "decimal", feature = "ipaddr"))]
+    /// unexpected entity attributes, dropped or preserved instead of erroring
+    #[test]
+    fn unexpected_entity_attr_dropped_or_preserved() {
+        let entitiesjson = json!(
+            [
+                {
+                    "uid": { "type": "Employee", "id": "12UA45" },
+                    "attrs": {
+                        "isFullTime": true,
+                        "numDirectReports": 3,
+                        "department": "Sales",
+                        "manager": { "type": "Employee", "id": "34FB87" },
+                        "hr_contacts": [
+                            { "type": "HR", "id": "aaaaa" },
+                            { "type": "HR", "id": "bbbbb" }
+                        ],
+                        "json_blob": {
+                            "inner1": false,
+                            "inner2": "-*/",
+                            "inner3": { "innerinner": { "type": "Employee", "id": "09AE76" }},
+                        },
+                        "home_ip": "222.222.222.101",
+                        "work_ip": { "fn": "ip", "arg": "2.2.2.0/24" },
+                        "trust_score": "5.7",
+                        "tricky": { "type": "Employee", "id": "34FB87" },
+                        "wat": "???",
+                        "nested": { "a": [1, 2] },
+                    },
+                    "parents": []
+                }
+            ]
+        );
+        let uid = EntityUID::with_eid_and_type("Employee", "12UA45").unwrap();
+
+        let eparser = EntityJsonParser::new(
+            Some(&MockSchema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        )
+        .with_undeclared_attr_handling(UndeclaredAttrHandling::Drop);
+        let (entities, undeclared) = eparser
+            .from_json_value_with_undeclared_attrs(entitiesjson.clone())
+            .expect("undeclared attributes should be dropped");
+        let entity = entities.entity(&uid).unwrap();
+        assert!(entity.get("wat").is_none());
+        assert!(entity.get("nested").is_none());
+        assert!(entity.get("department").is_some());
+        assert_eq!(
+            undeclared
+                .iter()
+                .map(|(uid, attr)| format!("{uid}.{attr}"))
+                .collect::<Vec<_>>(),
+            vec![r#"Employee::"12UA45".nested"#, r#"Employee::"12UA45".wat"#]
+        );
+        assert_eq!(undeclared.get(&uid, "wat"), None);
+        // the plain methods also drop the attribute, without reporting it
+        assert_matches!(eparser.from_json_value(entitiesjson.clone()), Ok(_));
+
+        let eparser = EntityJsonParser::new(
+            Some(&MockSchema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        )
+        .with_undeclared_attr_handling(UndeclaredAttrHandling::Preserve);
+        let (entities, undeclared) = eparser
+            .from_json_value_with_undeclared_attrs(entitiesjson)
+            .expect("undeclared attributes should be preserved");
+        let entity = entities.entity(&uid).unwrap();
+        assert!(entity.get("wat").is_none());
+        assert_eq!(undeclared.get(&uid, "wat"), Some(&json!("???")));
+        assert_eq!(
+            undeclared.get(&uid, "nested"),
+            Some(&json!({ "a": [1, 2] }))
+        );
+        assert_eq!(undeclared.attrs_of(&uid).count(), 2);
+    }
+
     /// unexpected entity tag
     #[test]
     fn unexpected_entity_tag() {
@@ -4105,3 +4433,244 @@ mod schema_based_parsing_tests {
         });
     }
 }
+
+#[cfg(test)]
+mod graph_tests {
+    use super::*;
+    use graph::DotOptions;
+
+    fn hierarchy() -> Entities {
+        let mut alice = Entity::new(
+            EntityUID::with_eid("alice"),
+            [
+                ("age".into(), RestrictedExpr::val(42)),
+                (
+                    "tags".into(),
+                    RestrictedExpr::set([
+                        RestrictedExpr::val("a"),
+                        RestrictedExpr::val("b"),
+                        RestrictedExpr::val("c"),
+                    ]),
+                ),
+            ],
+            HashSet::new(),
+            HashSet::new(),
+            [],
+            Extensions::none(),
+        )
+        .unwrap();
+        alice.add_parent(EntityUID::with_eid("admins"));
+        let mut admins = Entity::with_uid(EntityUID::with_eid("admins"));
+        admins.add_parent(EntityUID::with_eid("everyone"));
+        Entities::from_entities(
+            [alice, admins],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn graph_has_direct_edges_only() {
+        let graph = hierarchy().to_graph();
+        let nodes: Vec<_> = graph
+            .nodes()
+            .map(|n| (n.uid().eid().escaped().to_string(), n.in_slice()))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("admins".to_string(), true),
+                ("alice".to_string(), true),
+                ("everyone".to_string(), false),
+            ]
+        );
+        let edges: Vec<_> = graph
+            .edges()
+            .map(|(c, p)| (c.eid().escaped().to_string(), p.eid().escaped().to_string()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("admins".to_string(), "everyone".to_string()),
+                ("alice".to_string(), "admins".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn dot_is_deterministic() {
+        let mut first = String::new();
+        hierarchy()
+            .to_dot(&mut first, &DotOptions::default())
+            .unwrap();
+        let mut second = String::new();
+        hierarchy()
+            .to_dot(&mut second, &DotOptions::default())
+            .unwrap();
+        assert_eq!(first, second);
+        assert!(first.contains(r#"label="everyone" style=dashed]"#));
+        assert!(!first.contains("age"));
+    }
+
+    #[test]
+    fn dot_with_attrs() {
+        let mut dot = String::new();
+        hierarchy()
+            .to_dot(
+                &mut dot,
+                &DotOptions {
+                    include_attrs: true,
+                    max_attr_elements: Some(2),
+                },
+            )
+            .unwrap();
+        assert!(
+            dot.contains(r#"[label="alice\nage: 42\ntags: [\"a\", \"b\", .. ]"]"#),
+            "{dot}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod canonical_json_tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> Entities {
+        EntityJsonParser::new(
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        )
+        .from_json_value(json)
+        .unwrap()
+    }
+
+    #[test]
+    fn key_and_parent_order_do_not_matter() {
+        let first = parse(serde_json::json!([
+            {
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": { "b": 1, "a": { "y": true, "x": "s" } },
+                "parents": [
+                    { "type": "Group", "id": "b" },
+                    { "type": "Group", "id": "a" }
+                ]
+            },
+            { "uid": { "type": "Group", "id": "a" }, "attrs": {}, "parents": [] }
+        ]));
+        let second = parse(serde_json::json!([
+            { "parents": [], "attrs": {}, "uid": { "id": "a", "type": "Group" } },
+            {
+                "attrs": { "a": { "x": "s", "y": true }, "b": 1 },
+                "parents": [
+                    { "type": "Group", "id": "a" },
+                    { "type": "Group", "id": "b" }
+                ],
+                "uid": { "type": "User", "id": "alice" }
+            }
+        ]));
+        let canonical = first.to_json_canonical().unwrap();
+        assert_eq!(canonical, second.to_json_canonical().unwrap());
+        assert_eq!(
+            canonical,
+            r#"[{"attrs":{},"parents":[],"uid":{"id":"a","type":"Group"}},{"attrs":{"a":{"x":"s","y":true},"b":1},"parents":[{"id":"a","type":"Group"},{"id":"b","type":"Group"}],"uid":{"id":"alice","type":"User"}}]"#
+        );
+        // the canonical JSON can be parsed back in
+        assert_eq!(parse(serde_json::from_str(&canonical).unwrap()), first);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn extension_values_are_normalized() {
+        let entities = |d: &str| {
+            parse(serde_json::json!([{
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": { "limit": { "__extn": { "fn": "decimal", "arg": d } } },
+                "parents": []
+            }]))
+        };
+        let canonical = entities("1.0").to_json_canonical().unwrap();
+        assert_eq!(canonical, entities("1.00").to_json_canonical().unwrap());
+        assert!(canonical.contains(r#""arg":"1.0000""#), "{canonical}");
+    }
+}
+
+#[cfg(test)]
+mod provenance_tests {
+    use super::*;
+
+    fn entities(ids: &[&str]) -> Entities {
+        Entities::from_entities(
+            ids.iter()
+                .map(|id| Entity::with_uid(EntityUID::with_eid(id))),
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn provenance_survives_merges() {
+        let fetched_at = std::time::SystemTime::UNIX_EPOCH;
+        let directory = entities(&["alice", "bob"]).with_provenance(
+            EntityProvenance::new()
+                .with_source("directory")
+                .with_fetched_at(fetched_at)
+                .with_version("v7")
+                .with_metadata("region", "eu"),
+        );
+        let hr = entities(&["carol"]).with_provenance(EntityProvenance::new().with_source("hr"));
+        let merged = directory
+            .add_entities(
+                hr.into_iter().map(Arc::new),
+                None::<&NoEntitiesSchema>,
+                TCComputation::ComputeNow,
+                Extensions::none(),
+            )
+            .unwrap();
+
+        let alice = merged.provenance(&EntityUID::with_eid("alice")).unwrap();
+        assert_eq!(alice.source(), Some("directory"));
+        assert_eq!(alice.fetched_at(), Some(fetched_at));
+        assert_eq!(alice.version(), Some("v7"));
+        assert_eq!(alice.metadata("region"), Some("eu"));
+        assert_eq!(alice.metadata("zone"), None);
+        assert_eq!(
+            merged
+                .provenance(&EntityUID::with_eid("carol"))
+                .unwrap()
+                .source(),
+            Some("hr")
+        );
+        assert_eq!(merged.provenance(&EntityUID::with_eid("dave")), None);
+    }
+
+    #[test]
+    fn provenance_does_not_affect_equality() {
+        let plain = entities(&["alice"]);
+        let annotated = plain
+            .clone()
+            .with_provenance(EntityProvenance::new().with_source("directory"));
+        assert!(plain.deep_eq(&annotated));
+        // adding an equal entity from another source is not a conflict, and
+        // keeps the existing provenance
+        let merged = annotated
+            .add_entities(
+                plain.into_iter().map(Arc::new),
+                None::<&NoEntitiesSchema>,
+                TCComputation::ComputeNow,
+                Extensions::none(),
+            )
+            .unwrap();
+        assert_eq!(
+            merged
+                .provenance(&EntityUID::with_eid("alice"))
+                .unwrap()
+                .source(),
+            Some("directory")
+        );
+    }
+}
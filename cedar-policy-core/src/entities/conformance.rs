@@ -16,11 +16,14 @@
 
 pub mod err;
 
-use super::{json::err::TypeMismatchError, EntityTypeDescription, Schema, SchemaType};
+use super::Entities;
+use super::{json::err::TypeMismatchError, EntityTypeDescription, Refinement, Schema, SchemaType};
 use super::{Eid, EntityUID, ExprKind, Literal};
 use crate::ast::{
-    BorrowedRestrictedExpr, Entity, PartialValue, PartialValueToRestrictedExprError, RestrictedExpr,
+    BorrowedRestrictedExpr, Context, Entity, EntityUIDEntry, PartialValue,
+    PartialValueToRestrictedExprError, Request, RestrictedExpr, SlotEnv, Value, ValueKind,
 };
+use crate::evaluator::Evaluator;
 use crate::extensions::{ExtensionFunctionLookupError, Extensions};
 use err::{
     EntitySchemaConformanceError, InvalidEnumEntity, InvalidEnumEntityError, UndeclaredAction,
@@ -30,6 +33,7 @@ use miette::Diagnostic;
 use nonempty::NonEmpty;
 use smol_str::SmolStr;
 use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Struct used to check whether entities conform to a schema
@@ -127,7 +131,17 @@ impl<S: Schema> EntitySchemaConformanceChecker<'_, S> {
                 Some(expected_ty) => {
                     // typecheck: ensure that the entity attribute value matches
                     // the expected type
-                    match typecheck_value_against_schematype(val, &expected_ty, self.extensions) {
+                    let typecheck =
+                        typecheck_value_against_schematype(val, &expected_ty, self.extensions)
+                            .and_then(|()| match schema_etype.attr_refinement(attr) {
+                                Some(refinement) => typecheck_value_against_refinement(
+                                    val,
+                                    &expected_ty,
+                                    &refinement,
+                                ),
+                                None => Ok(()),
+                            });
+                    match typecheck {
                         Ok(()) => {} // typecheck passes
                         Err(TypecheckError::TypeMismatch(err)) => {
                             return Err(EntitySchemaConformanceError::type_mismatch(
@@ -153,6 +167,56 @@ impl<S: Schema> EntitySchemaConformanceChecker<'_, S> {
         Ok(())
     }
 
+    /// Validate that the attributes of an entity satisfy the constraints
+    /// declared for its type. This assumes the attributes have already been
+    /// validated with [`Self::validate_entity_attributes()`].
+    pub fn validate_entity_constraints<'a>(
+        &self,
+        uid: &EntityUID,
+        attrs: impl Iterator<Item = (&'a SmolStr, &'a PartialValue)>,
+        schema_etype: &impl EntityTypeDescription,
+    ) -> Result<(), EntitySchemaConformanceError> {
+        let mut constraints = schema_etype.constraints().peekable();
+        if constraints.peek().is_none() {
+            return Ok(());
+        }
+        // As for refinements, an entity whose attributes are not all known
+        // is considered as satisfying its constraints
+        let Some(attrs) = attrs
+            .map(|(attr, val)| match val {
+                PartialValue::Value(val) => Some((attr.clone(), val.clone())),
+                PartialValue::Residual(_) => None,
+            })
+            .collect::<Option<BTreeMap<_, _>>>()
+        else {
+            return Ok(());
+        };
+        let request = Request::new_unchecked(
+            EntityUIDEntry::unknown(),
+            EntityUIDEntry::unknown(),
+            EntityUIDEntry::unknown(),
+            Some(Context::Value(Arc::new(attrs))),
+        );
+        let entities = Entities::new();
+        let evaluator = Evaluator::new(request, &entities, self.extensions);
+        for constraint in constraints {
+            let err = match evaluator
+                .interpret(constraint.expr(), &SlotEnv::new())
+                .and_then(|val| val.get_as_bool())
+            {
+                Ok(true) => continue,
+                Ok(false) => None,
+                Err(err) => Some(err),
+            };
+            return Err(EntitySchemaConformanceError::unsatisfied_constraint(
+                uid.clone(),
+                constraint.as_str(),
+                err,
+            ));
+        }
+        Ok(())
+    }
+
     /// Validate tags of an entity
     pub fn validate_tags<'a>(
         &self,
@@ -221,6 +285,7 @@ impl<S: Schema> EntitySchemaConformanceChecker<'_, S> {
 
             validate_euid(self.schema, uid)?;
             self.validate_entity_attributes(uid, entity.attrs(), &schema_etype)?;
+            self.validate_entity_constraints(uid, entity.attrs(), &schema_etype)?;
             self.validate_entity_ancestors(uid, entity.ancestors(), &schema_etype)?;
             self.validate_tags(uid, entity.tags(), &schema_etype)?;
         }
@@ -297,20 +362,47 @@ fn validate_euids_in_subexpressions<'a>(
     })
 }
 
+/// Validate if the enumerated entities and action UIDs `entity` refers to (as
+/// its uid, parents, or in the values of its attributes and tags) are valid
+pub fn validate_euids_in_entity(
+    schema: &impl Schema,
+    entity: &Entity,
+) -> Result<(), ValidateEuidError> {
+    validate_euid(schema, entity.uid())?;
+    for parent in entity.parents() {
+        validate_euid(schema, parent)?;
+    }
+    for (_, val) in entity.attrs().chain(entity.tags()) {
+        validate_euids_in_partial_value(schema, val)?;
+    }
+    Ok(())
+}
+
 /// Validate if enumerated entities and action UIDs in `val` are valid
 pub fn validate_euids_in_partial_value(
     schema: &impl Schema,
     val: &PartialValue,
 ) -> Result<(), ValidateEuidError> {
     match val {
-        PartialValue::Value(val) => validate_euids_in_subexpressions(
-            RestrictedExpr::from(val.clone()).subexpressions(),
-            schema,
-        ),
+        PartialValue::Value(val) => validate_euids_in_value(schema, val),
         PartialValue::Residual(e) => validate_euids_in_subexpressions(e.subexpressions(), schema),
     }
 }
 
+/// Validate if enumerated entities and action UIDs in `val` are valid
+pub fn validate_euids_in_value(schema: &impl Schema, val: &Value) -> Result<(), ValidateEuidError> {
+    match val.value_kind() {
+        ValueKind::Lit(Literal::EntityUID(euid)) => validate_euid(schema, euid),
+        ValueKind::Lit(_) | ValueKind::ExtensionValue(_) => Ok(()),
+        ValueKind::Set(set) => set
+            .iter()
+            .try_for_each(|v| validate_euids_in_value(schema, v)),
+        ValueKind::Record(record) => record
+            .values()
+            .try_for_each(|v| validate_euids_in_value(schema, v)),
+    }
+}
+
 /// Check whether the given `PartialValue` typechecks with the given `SchemaType`.
 /// If the typecheck passes, return `Ok(())`.
 /// If the typecheck fails, return an appropriate `Err`.
@@ -338,6 +430,40 @@ pub fn typecheck_value_against_schematype(
     }
 }
 
+/// Check whether the given `PartialValue`, which typechecks with the given
+/// `SchemaType`, satisfies the given `Refinement`.
+/// If the check passes, return `Ok(())`.
+/// If the check fails, return an appropriate `Err`.
+pub fn typecheck_value_against_refinement(
+    value: &PartialValue,
+    expected_ty: &SchemaType,
+    refinement: &Refinement,
+) -> Result<(), TypecheckError> {
+    match RestrictedExpr::try_from(value.clone()) {
+        Ok(expr) => check_refinement(expr.as_borrowed(), expected_ty, refinement),
+        // As in `typecheck_value_against_schematype()`, a nontrivial residual
+        // is considered as passing
+        Err(PartialValueToRestrictedExprError::NontrivialResidual { .. }) => Ok(()),
+    }
+}
+
+fn check_refinement(
+    expr: BorrowedRestrictedExpr<'_>,
+    expected_ty: &SchemaType,
+    refinement: &Refinement,
+) -> Result<(), TypecheckError> {
+    if refinement.admits(expr) {
+        Ok(())
+    } else {
+        Err(TypeMismatchError::unsatisfied_refinement(
+            expected_ty.clone(),
+            refinement.clone(),
+            expr.to_owned(),
+        )
+        .into())
+    }
+}
+
 /// Check whether the given `RestrictedExpr` is a valid instance of
 /// `SchemaType`.  We do not have type information for unknowns, so this
 /// function liberally treats unknowns as implementing any schema type.  If the
@@ -439,6 +565,12 @@ pub fn typecheck_restricted_expr_against_schematype(
             }),
             None => type_mismatch_err(),
         },
+        Map { value_ty } => match expr.as_record_pairs() {
+            Some(mut pairs) => pairs.try_for_each(|(_, e)| {
+                typecheck_restricted_expr_against_schematype(e, value_ty, extensions)
+            }),
+            None => type_mismatch_err(),
+        },
         Record { attrs, open_attrs } => match expr.as_record_pairs() {
             Some(pairs) => {
                 let pairs_map: BTreeMap<&SmolStr, BorrowedRestrictedExpr<'_>> = pairs.collect();
@@ -468,11 +600,19 @@ pub fn typecheck_restricted_expr_against_schematype(
                 pairs_map
                     .iter()
                     .try_for_each(|(k, inner_e)| match attrs.get(*k) {
-                        Some(sch_ty) => typecheck_restricted_expr_against_schematype(
-                            *inner_e,
-                            &sch_ty.attr_type,
-                            extensions,
-                        ),
+                        Some(sch_ty) => {
+                            typecheck_restricted_expr_against_schematype(
+                                *inner_e,
+                                &sch_ty.attr_type,
+                                extensions,
+                            )?;
+                            match &sch_ty.refinement {
+                                Some(refinement) => {
+                                    check_refinement(*inner_e, &sch_ty.attr_type, refinement)
+                                }
+                                None => Ok(()),
+                            }
+                        }
                         None => {
                             if *open_attrs {
                                 Ok(())
@@ -496,6 +636,10 @@ pub fn typecheck_restricted_expr_against_schematype(
             Some(actual_euid) if actual_euid.entity_type() == ty => Ok(()),
             _ => type_mismatch_err(),
         },
+        EntityUnion { tys } => match expr.as_euid() {
+            Some(actual_euid) if tys.contains(actual_euid.entity_type()) => Ok(()),
+            _ => type_mismatch_err(),
+        },
     }
 }
 
@@ -732,6 +876,8 @@ mod test_typecheck {
                     AttributeType {
                         attr_type: SchemaType::Long,
                         required: true,
+                        default: None,
+                        refinement: None,
                     },
                 )]),
                 open_attrs: false,
@@ -747,6 +893,8 @@ mod test_typecheck {
                     AttributeType {
                         attr_type: SchemaType::Long,
                         required: false,
+                        default: None,
+                        refinement: None,
                     },
                 )]),
                 open_attrs: false,
@@ -775,7 +923,7 @@ mod test_typecheck {
         assert_matches!(
             typecheck_restricted_expr_against_schematype(
                 BorrowedRestrictedExpr::new(&"{a: false}".parse().unwrap()).unwrap(),
-                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true })]), open_attrs: false },
+                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true, default: None, refinement: None })]), open_attrs: false },
                 Extensions::all_available(),
             ),
             Err(e@TypecheckError::TypeMismatch(_)) => {
@@ -789,7 +937,7 @@ mod test_typecheck {
         assert_matches!(
             typecheck_restricted_expr_against_schematype(
                 BorrowedRestrictedExpr::new(&"{a: {}}".parse().unwrap()).unwrap(),
-                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: false })]), open_attrs: false },
+                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: false, default: None, refinement: None })]), open_attrs: false },
                 Extensions::all_available(),
             ),
             Err(e@TypecheckError::TypeMismatch(_)) => {
@@ -803,7 +951,7 @@ mod test_typecheck {
         assert_matches!(
             typecheck_restricted_expr_against_schematype(
                 BorrowedRestrictedExpr::new(&"{}".parse().unwrap()).unwrap(),
-                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true })]), open_attrs: false },
+                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true, default: None, refinement: None })]), open_attrs: false },
                 Extensions::all_available(),
             ),
             Err(e@TypecheckError::TypeMismatch(_)) => {
@@ -817,7 +965,7 @@ mod test_typecheck {
         assert_matches!(
             typecheck_restricted_expr_against_schematype(
                 BorrowedRestrictedExpr::new(&"{a: 1, b: 1}".parse().unwrap()).unwrap(),
-                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true })]), open_attrs: false },
+                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: true, default: None, refinement: None })]), open_attrs: false },
                 Extensions::all_available(),
             ),
             Err(e@TypecheckError::TypeMismatch(_)) => {
@@ -831,7 +979,7 @@ mod test_typecheck {
         assert_matches!(
             typecheck_restricted_expr_against_schematype(
                 BorrowedRestrictedExpr::new(&"{b: 1}".parse().unwrap()).unwrap(),
-                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: false })]), open_attrs: false },
+                &SchemaType::Record { attrs: BTreeMap::from([("a".to_smolstr(), AttributeType { attr_type: SchemaType::Long, required: false, default: None, refinement: None })]), open_attrs: false },
                 Extensions::all_available(),
             ),
             Err(e@TypecheckError::TypeMismatch(_)) => {
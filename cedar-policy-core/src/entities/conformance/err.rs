@@ -16,6 +16,7 @@
 //! This module cotnains errors around entities not conforming to schemas
 use super::TypeMismatchError;
 use crate::ast::{Eid, EntityType, EntityUID};
+use crate::evaluator::EvaluationError;
 use crate::extensions::ExtensionFunctionLookupError;
 use crate::impl_diagnostic_from_method_on_field;
 use itertools::Itertools;
@@ -77,6 +78,10 @@ pub enum EntitySchemaConformanceError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] InvalidEnumEntity),
+    /// An entity does not satisfy a constraint declared for its type
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnsatisfiedConstraint(UnsatisfiedConstraint),
 }
 
 impl EntitySchemaConformanceError {
@@ -143,6 +148,18 @@ impl EntitySchemaConformanceError {
             err,
         })
     }
+
+    pub(crate) fn unsatisfied_constraint(
+        uid: EntityUID,
+        constraint: impl Into<SmolStr>,
+        err: Option<EvaluationError>,
+    ) -> Self {
+        Self::UnsatisfiedConstraint(UnsatisfiedConstraint {
+            uid,
+            constraint: constraint.into(),
+            err,
+        })
+    }
 }
 
 /// Error looking up an extension function. This error can occur when
@@ -248,6 +265,23 @@ pub struct MissingRequiredEntityAttr {
     attr: SmolStr,
 }
 
+/// An entity does not satisfy a constraint declared for its type, either
+/// because the constraint evaluated to `false` or because evaluating it failed
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make fields `pub`, don't make breaking changes, and use caution
+// when adding public methods.
+#[derive(Debug, Error, Diagnostic)]
+#[error("entity `{uid}` does not satisfy the constraint `{constraint}`{}", .err.as_ref().map(|err| format!(": {err}")).unwrap_or_default())]
+pub struct UnsatisfiedConstraint {
+    /// Entity which does not satisfy the constraint
+    uid: EntityUID,
+    /// The constraint as written in the schema
+    constraint: SmolStr,
+    /// Error evaluating the constraint, if it did not evaluate to `false`
+    err: Option<EvaluationError>,
+}
+
 /// If the context is an attribute or a tag
 #[derive(Debug, Clone, Copy)]
 pub enum AttrOrTag {
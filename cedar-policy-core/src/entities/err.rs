@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+use super::integrity::DanglingReference;
 use super::EntityUID;
 use crate::transitive_closure;
 use miette::Diagnostic;
@@ -96,5 +97,33 @@ impl Duplicate {
     }
 }
 
+/// Error type for entity slices containing references to entities which are
+/// not present in the slice
+#[derive(Debug, PartialEq, Eq, Error, Diagnostic)]
+#[diagnostic(help(
+    "add the missing entities, or declare the references as intentionally dangling"
+))]
+pub struct DanglingReferencesError {
+    /// All of the dangling references that were found
+    pub(crate) references: nonempty::NonEmpty<DanglingReference>,
+}
+
+impl DanglingReferencesError {
+    /// All of the dangling references that were found
+    pub fn references(&self) -> impl Iterator<Item = &DanglingReference> {
+        self.references.iter()
+    }
+}
+
+impl std::fmt::Display for DanglingReferencesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.references.first())?;
+        match self.references.len() {
+            1 => Ok(()),
+            n => write!(f, " (and {} more dangling reference(s))", n - 1),
+        }
+    }
+}
+
 /// Type alias for convenience
 pub type Result<T> = std::result::Result<T, EntitiesError>;
@@ -0,0 +1,214 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A typed export of the entity hierarchy, and rendering of it in the graphviz
+//! DOT format.
+
+use crate::ast::{BoundedToString, EntityType, EntityUID, PartialValue};
+use smol_str::SmolStr;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Options controlling how an [`EntityGraph`] is rendered as DOT
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Whether to include a summary of each entity's attributes in its node
+    /// label
+    pub include_attrs: bool,
+    /// When attributes are included, the maximum number of set elements or
+    /// record key-value pairs to show for each attribute value before eliding
+    /// the rest with `..`. `None` means no bound.
+    pub max_attr_elements: Option<usize>,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            include_attrs: false,
+            max_attr_elements: Some(5),
+        }
+    }
+}
+
+/// A node in an [`EntityGraph`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityGraphNode {
+    /// The entity this node represents
+    pub(crate) uid: EntityUID,
+    /// The entity's attributes. Empty if the entity is not in the slice.
+    pub(crate) attrs: BTreeMap<SmolStr, PartialValue>,
+    /// Whether the entity is in the slice, or is only referenced as the parent
+    /// of an entity which is
+    pub(crate) in_slice: bool,
+}
+
+impl EntityGraphNode {
+    /// The entity this node represents
+    pub fn uid(&self) -> &EntityUID {
+        &self.uid
+    }
+
+    /// The entity's attributes, sorted by name. Empty if the entity is not in
+    /// the slice.
+    pub fn attrs(&self) -> impl Iterator<Item = (&SmolStr, &PartialValue)> {
+        self.attrs.iter()
+    }
+
+    /// Whether the entity is in the slice. This is `false` for nodes which
+    /// only exist because they are the parent of an entity in the slice.
+    pub fn in_slice(&self) -> bool {
+        self.in_slice
+    }
+}
+
+/// The entity hierarchy of an [`super::Entities`], as a graph whose nodes are
+/// entities and whose edges go from each entity to each of its direct parents.
+///
+/// Nodes and edges are sorted, so the graph (and its DOT rendering) is the
+/// same for equal entity slices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityGraph {
+    pub(crate) nodes: Vec<EntityGraphNode>,
+    pub(crate) edges: Vec<(EntityUID, EntityUID)>,
+}
+
+impl EntityGraph {
+    /// Build the graph for the given entities
+    pub(crate) fn new<'a>(entities: impl Iterator<Item = &'a crate::ast::Entity>) -> Self {
+        let mut nodes: BTreeMap<String, EntityGraphNode> = BTreeMap::new();
+        let mut edges: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut referenced: BTreeMap<String, EntityUID> = BTreeMap::new();
+        for entity in entities {
+            let key = entity.uid().to_string();
+            for parent in entity.parents() {
+                let parent_key = parent.to_string();
+                edges.insert((key.clone(), parent_key.clone()));
+                referenced.insert(parent_key, parent.clone());
+            }
+            nodes.insert(
+                key,
+                EntityGraphNode {
+                    uid: entity.uid().clone(),
+                    attrs: entity
+                        .attrs()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                    in_slice: true,
+                },
+            );
+        }
+        for (key, uid) in referenced {
+            nodes.entry(key).or_insert_with(|| EntityGraphNode {
+                uid,
+                attrs: BTreeMap::new(),
+                in_slice: false,
+            });
+        }
+        let edges = edges
+            .into_iter()
+            .filter_map(|(child, parent)| {
+                Some((
+                    nodes.get(&child)?.uid.clone(),
+                    nodes.get(&parent)?.uid.clone(),
+                ))
+            })
+            .collect();
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// The nodes of the graph, sorted by entity UID
+    pub fn nodes(&self) -> impl Iterator<Item = &EntityGraphNode> {
+        self.nodes.iter()
+    }
+
+    /// The edges of the graph, as `(child, parent)` pairs, sorted
+    pub fn edges(&self) -> impl Iterator<Item = (&EntityUID, &EntityUID)> {
+        self.edges.iter().map(|(child, parent)| (child, parent))
+    }
+
+    /// Write the graph in the DOT format. Entities are grouped into one
+    /// cluster per entity type, and entities which are only referenced as a
+    /// parent are drawn dashed.
+    ///
+    /// This function only returns an `Err` result on a failing `write!` to
+    /// `f`, so it is infallible if the `Write` implementation cannot fail
+    /// (e.g., `String`).
+    pub fn write_dot(
+        &self,
+        f: &mut impl std::fmt::Write,
+        options: &DotOptions,
+    ) -> std::fmt::Result {
+        // See `Entities::to_dot_str()` for why double-quoted strings are used
+        // for all IDs
+        fn to_dot_id(f: &mut impl std::fmt::Write, v: &impl std::fmt::Display) -> std::fmt::Result {
+            write!(f, "\"{}\"", v.to_string().escape_debug())
+        }
+
+        writeln!(
+            f,
+            "strict digraph {{\n\tordering=\"out\"\n\tnode[shape=box]"
+        )?;
+
+        let mut by_type: BTreeMap<String, (&EntityType, Vec<&EntityGraphNode>)> = BTreeMap::new();
+        for node in &self.nodes {
+            let ty = node.uid.entity_type();
+            by_type
+                .entry(ty.to_string())
+                .or_insert_with(|| (ty, Vec::new()))
+                .1
+                .push(node);
+        }
+
+        for (ty_str, (ty, nodes)) in by_type {
+            write!(f, "\tsubgraph ")?;
+            to_dot_id(f, &format!("cluster_{ty_str}"))?;
+            write!(f, " {{\n\t\tlabel=")?;
+            to_dot_id(f, ty)?;
+            writeln!(f)?;
+            for node in nodes {
+                let mut label = node.uid.eid().escaped().to_string();
+                if options.include_attrs {
+                    for (attr, val) in &node.attrs {
+                        label.push('\n');
+                        label.push_str(attr);
+                        label.push_str(": ");
+                        label.push_str(&BoundedToString::to_string(val, options.max_attr_elements));
+                    }
+                }
+                write!(f, "\t\t")?;
+                to_dot_id(f, &node.uid)?;
+                write!(f, " [label=")?;
+                to_dot_id(f, &label)?;
+                if !node.in_slice {
+                    write!(f, " style=dashed")?;
+                }
+                writeln!(f, "]")?;
+            }
+            writeln!(f, "\t}}")?;
+        }
+
+        for (child, parent) in &self.edges {
+            write!(f, "\t")?;
+            to_dot_id(f, child)?;
+            write!(f, " -> ")?;
+            to_dot_id(f, parent)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "}}")
+    }
+}
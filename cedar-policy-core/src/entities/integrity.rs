@@ -0,0 +1,147 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Referential integrity checking for entity slices: finding references from
+//! entities to entity UIDs which are not present in the slice.
+
+use crate::ast::{Entity, EntityUID, ExprKind, Literal, PartialValue, Value, ValueKind};
+use smol_str::SmolStr;
+use std::collections::HashSet;
+
+/// Where, within the referencing entity, a reference to another entity was
+/// found
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReferenceSite {
+    /// The reference is one of the entity's parents
+    Parent,
+    /// The reference occurs (possibly nested) in the value of this attribute
+    Attribute(SmolStr),
+    /// The reference occurs (possibly nested) in the value of this tag
+    Tag(SmolStr),
+}
+
+impl std::fmt::Display for ReferenceSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parent => write!(f, "parents"),
+            Self::Attribute(attr) => write!(f, "attribute `{attr}`"),
+            Self::Tag(tag) => write!(f, "tag `{tag}`"),
+        }
+    }
+}
+
+/// A reference from an entity to an entity UID which does not exist in the
+/// entity slice
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DanglingReference {
+    /// The entity containing the reference
+    pub(crate) referrer: EntityUID,
+    /// The referenced entity UID, which does not exist
+    pub(crate) target: EntityUID,
+    /// Where in `referrer` the reference was found
+    pub(crate) site: ReferenceSite,
+}
+
+impl DanglingReference {
+    /// The entity containing the reference
+    pub fn referrer(&self) -> &EntityUID {
+        &self.referrer
+    }
+
+    /// The referenced entity UID, which does not exist
+    pub fn target(&self) -> &EntityUID {
+        &self.target
+    }
+
+    /// Where in the referencing entity the reference was found
+    pub fn site(&self) -> &ReferenceSite {
+        &self.site
+    }
+}
+
+impl std::fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` references `{}` in its {}, but that entity does not exist",
+            self.referrer, self.target, self.site
+        )
+    }
+}
+
+/// Collect every reference from `entity` (in its parents, attributes, or
+/// tags) to an entity UID for which `exists` returns `false`.
+pub(crate) fn dangling_references_of(
+    entity: &Entity,
+    exists: impl Fn(&EntityUID) -> bool,
+) -> Vec<DanglingReference> {
+    let mut found = Vec::new();
+    let mut report = |target: &EntityUID, site: &ReferenceSite| {
+        if !exists(target) {
+            found.push(DanglingReference {
+                referrer: entity.uid().clone(),
+                target: target.clone(),
+                site: site.clone(),
+            });
+        }
+    };
+    for parent in entity.parents() {
+        report(parent, &ReferenceSite::Parent);
+    }
+    for (attr, val) in entity.attrs() {
+        let site = ReferenceSite::Attribute(attr.clone());
+        for_each_euid_in_partial_value(val, &mut |euid| report(euid, &site));
+    }
+    for (tag, val) in entity.tags() {
+        let site = ReferenceSite::Tag(tag.clone());
+        for_each_euid_in_partial_value(val, &mut |euid| report(euid, &site));
+    }
+    // Report each (target, site) pair once, even if e.g. a set attribute
+    // mentions the same missing entity several times
+    let mut seen = HashSet::new();
+    found.retain(|r| seen.insert((r.target.clone(), r.site.clone())));
+    found
+}
+
+fn for_each_euid_in_partial_value(pv: &PartialValue, f: &mut impl FnMut(&EntityUID)) {
+    match pv {
+        PartialValue::Value(v) => for_each_euid_in_value(v, f),
+        PartialValue::Residual(e) => {
+            for sub in e.subexpressions() {
+                if let ExprKind::Lit(Literal::EntityUID(euid)) = sub.expr_kind() {
+                    f(euid);
+                }
+            }
+        }
+    }
+}
+
+fn for_each_euid_in_value(v: &Value, f: &mut impl FnMut(&EntityUID)) {
+    match v.value_kind() {
+        ValueKind::Lit(Literal::EntityUID(euid)) => f(euid),
+        ValueKind::Lit(_) | ValueKind::ExtensionValue(_) => {}
+        ValueKind::Set(set) => {
+            for elem in set.iter() {
+                for_each_euid_in_value(elem, f);
+            }
+        }
+        ValueKind::Record(record) => {
+            for val in record.values() {
+                for_each_euid_in_value(val, f);
+            }
+        }
+    }
+}
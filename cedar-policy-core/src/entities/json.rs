@@ -38,5 +38,9 @@ pub use schema::*;
 mod schema_types;
 pub use schema_types::*;
 
+/// Schema-directed coercion of loosely-typed JSON before schema-based parsing.
+mod coercion;
+pub use coercion::*;
+
 /// Error types for JSON serialization and deserialization
 pub mod err;
@@ -0,0 +1,396 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Schema-directed coercion of loosely-typed JSON, for use before
+//! schema-based parsing.
+
+use super::SchemaType;
+use smol_str::{SmolStr, ToSmolStr};
+
+/// A single coercion performed by [`coerce_json`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonCoercion {
+    /// Path to the coerced value, from the root of the coerced JSON
+    pub(crate) path: Vec<SmolStr>,
+    /// The value before coercion
+    pub(crate) original: serde_json::Value,
+    /// The value after coercion
+    pub(crate) coerced: serde_json::Value,
+    /// The type the schema expects for the value
+    pub(crate) expected: SchemaType,
+}
+
+impl JsonCoercion {
+    /// Path to the coerced value, from the root of the coerced JSON. Record
+    /// attributes are identified by name, and set elements by their index.
+    pub fn path(&self) -> &[SmolStr] {
+        &self.path
+    }
+
+    /// The value before coercion
+    pub fn original(&self) -> &serde_json::Value {
+        &self.original
+    }
+
+    /// The value after coercion
+    pub fn coerced(&self) -> &serde_json::Value {
+        &self.coerced
+    }
+
+    /// The type the schema expects for the value
+    pub fn expected(&self) -> &SchemaType {
+        &self.expected
+    }
+}
+
+/// Coerce loosely-typed values in `val` into the JSON representation that
+/// schema-based parsing expects for `expected`, appending a [`JsonCoercion`]
+/// to `coercions` for each value changed. The following coercions are
+/// performed:
+///
+/// * strings containing an integer, and numbers with no fractional part
+///   (e.g., `3.0`), where a `Long` is expected
+/// * numbers, and strings containing a number, where a `decimal` is
+///   expected, as long as this does not lose precision
+/// * ISO 8601 date-time strings which are not in the form accepted by the
+///   `datetime` constructor (e.g., with a `+hh:mm` offset, or without
+///   seconds) where a `datetime` is expected
+///
+/// Values which cannot be coerced are left unchanged, so that schema-based
+/// parsing reports the usual error for them.
+pub fn coerce_json(
+    val: serde_json::Value,
+    expected: &SchemaType,
+    coercions: &mut Vec<JsonCoercion>,
+) -> serde_json::Value {
+    coerce_json_at(val, expected, &mut Vec::new(), coercions)
+}
+
+fn coerce_json_at(
+    val: serde_json::Value,
+    expected: &SchemaType,
+    path: &mut Vec<SmolStr>,
+    coercions: &mut Vec<JsonCoercion>,
+) -> serde_json::Value {
+    use serde_json::Value as Json;
+    let coerced = match (expected, val) {
+        (SchemaType::Set { element_ty }, Json::Array(elements)) => {
+            return Json::Array(
+                elements
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, element)| {
+                        path.push(i.to_smolstr());
+                        let element = coerce_json_at(element, element_ty, path, coercions);
+                        path.pop();
+                        element
+                    })
+                    .collect(),
+            );
+        }
+        (SchemaType::Map { value_ty }, Json::Object(actual_attrs)) => {
+            return Json::Object(
+                actual_attrs
+                    .into_iter()
+                    .map(|(k, v)| {
+                        path.push(k.to_smolstr());
+                        let v = coerce_json_at(v, value_ty, path, coercions);
+                        path.pop();
+                        (k, v)
+                    })
+                    .collect(),
+            );
+        }
+        (SchemaType::Record { attrs, .. }, Json::Object(actual_attrs)) => {
+            return Json::Object(
+                actual_attrs
+                    .into_iter()
+                    .map(|(k, v)| match attrs.get(k.as_str()) {
+                        Some(attr_ty) => {
+                            path.push(k.to_smolstr());
+                            let v = coerce_json_at(v, attr_ty.schema_type(), path, coercions);
+                            path.pop();
+                            (k, v)
+                        }
+                        None => (k, v),
+                    })
+                    .collect(),
+            );
+        }
+        (SchemaType::Long, Json::String(s)) => match s.trim().parse::<i64>() {
+            Ok(i) => (Json::String(s), Json::from(i)),
+            Err(_) => return Json::String(s),
+        },
+        (SchemaType::Long, Json::Number(n)) if !n.is_i64() => {
+            match n
+                .to_string()
+                .strip_suffix(".0")
+                .and_then(|s| s.parse::<i64>().ok())
+            {
+                Some(i) => (Json::Number(n), Json::from(i)),
+                None => return Json::Number(n),
+            }
+        }
+        (SchemaType::Extension { name }, Json::Number(n)) if name.to_smolstr() == "decimal" => {
+            match decimal_repr(&n.to_string()) {
+                Some(d) => (Json::Number(n), Json::String(d)),
+                None => return Json::Number(n),
+            }
+        }
+        (SchemaType::Extension { name }, Json::String(s)) if name.to_smolstr() == "decimal" => {
+            match decimal_repr(s.trim()) {
+                Some(d) if d != s => (Json::String(s), Json::String(d)),
+                _ => return Json::String(s),
+            }
+        }
+        (SchemaType::Extension { name }, Json::String(s)) if name.to_smolstr() == "datetime" => {
+            match normalize_datetime(s.trim()) {
+                Some(d) if d != s => (Json::String(s), Json::String(d)),
+                _ => return Json::String(s),
+            }
+        }
+        (_, val) => return val,
+    };
+    let (original, coerced) = coerced;
+    coercions.push(JsonCoercion {
+        path: path.clone(),
+        original,
+        coerced: coerced.clone(),
+        expected: expected.clone(),
+    });
+    coerced
+}
+
+/// Render the number `s` (optional `-`, digits, optional `.` and digits) in
+/// the form accepted by the `decimal` constructor, or return `None` if it is
+/// not such a number or has more fractional digits than `decimal` supports
+fn decimal_repr(s: &str) -> Option<String> {
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (unsigned, None),
+    };
+    if !is_digits(int) {
+        return None;
+    }
+    match frac {
+        None => Some(format!("{s}.0")),
+        Some(frac) if is_digits(frac) && frac.len() <= 4 => Some(s.to_owned()),
+        Some(_) => None,
+    }
+}
+
+/// Render the ISO 8601 date-time `s` in the form accepted by the `datetime`
+/// constructor, i.e., `YYYY-MM-DD`, optionally followed by `Thh:mm:ss`, an
+/// optional `.SSS`, and either `Z` or a `(+|-)hhmm` offset. Returns `None` if
+/// `s` is not an ISO 8601 date or a date-time with an explicit offset.
+fn normalize_datetime(s: &str) -> Option<String> {
+    let (date, rest) = s.split_at_checked(10)?;
+    let is_date = date.bytes().enumerate().all(|(i, b)| match i {
+        4 | 7 => b == b'-',
+        _ => b.is_ascii_digit(),
+    });
+    if !is_date {
+        return None;
+    }
+    if rest.is_empty() {
+        return Some(date.to_owned());
+    }
+    let rest = rest.strip_prefix(['T', 't', ' '])?;
+    let (hh, rest) = split_digits(rest, 2)?;
+    let (mm, rest) = split_digits(rest.strip_prefix(':')?, 2)?;
+    let (ss, rest) = match rest.strip_prefix(':') {
+        Some(rest) => split_digits(rest, 2)?,
+        None => ("00", rest),
+    };
+    let (millis, rest) = match rest.strip_prefix(['.', ',']) {
+        Some(rest) => {
+            let (frac, rest) = rest.split_at(
+                rest.find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len()),
+            );
+            if frac.is_empty() {
+                return None;
+            }
+            // `datetime` supports millisecond precision; finer precision is
+            // truncated
+            let mut millis: String = frac.chars().take(3).collect();
+            while millis.len() < 3 {
+                millis.push('0');
+            }
+            (Some(millis), rest)
+        }
+        None => (None, rest),
+    };
+    let offset = match rest {
+        "Z" | "z" => "Z".to_owned(),
+        _ => {
+            let (sign, rest) = rest.split_at_checked(1)?;
+            if sign != "+" && sign != "-" {
+                return None;
+            }
+            let (oh, rest) = split_digits(rest, 2)?;
+            let rest = rest.strip_prefix(':').unwrap_or(rest);
+            let (om, rest) = if rest.is_empty() {
+                ("00", rest)
+            } else {
+                split_digits(rest, 2)?
+            };
+            if !rest.is_empty() {
+                return None;
+            }
+            format!("{sign}{oh}{om}")
+        }
+    };
+    Some(match millis {
+        Some(millis) => format!("{date}T{hh}:{mm}:{ss}.{millis}{offset}"),
+        None => format!("{date}T{hh}:{mm}:{ss}{offset}"),
+    })
+}
+
+/// Split the first `n` bytes off `s`, if they are all ASCII digits
+fn split_digits(s: &str, n: usize) -> Option<(&str, &str)> {
+    let (digits, rest) = s.split_at_checked(n)?;
+    is_digits(digits).then_some((digits, rest))
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Name;
+    use crate::entities::AttributeType;
+    use serde_json::json;
+
+    fn ext(name: &str) -> SchemaType {
+        SchemaType::Extension {
+            name: Name::parse_unqualified_name(name).unwrap(),
+        }
+    }
+
+    #[test]
+    fn datetime_normalization() {
+        for (input, expected) in [
+            ("2024-01-01", Some("2024-01-01")),
+            ("2024-01-01T10:00:00Z", Some("2024-01-01T10:00:00Z")),
+            ("2024-01-01T10:00Z", Some("2024-01-01T10:00:00Z")),
+            (
+                "2024-01-01 10:00:00+01:00",
+                Some("2024-01-01T10:00:00+0100"),
+            ),
+            (
+                "2024-01-01T10:00:00.5-05",
+                Some("2024-01-01T10:00:00.500-0500"),
+            ),
+            (
+                "2024-01-01T10:00:00,123456z",
+                Some("2024-01-01T10:00:00.123Z"),
+            ),
+            // no offset, so the instant is ambiguous
+            ("2024-01-01T10:00:00", None),
+            ("01/02/2024", None),
+            ("2024-01-01T10:00:00.Z", None),
+        ] {
+            assert_eq!(
+                normalize_datetime(input).as_deref(),
+                expected,
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_repr_precision() {
+        assert_eq!(decimal_repr("12").as_deref(), Some("12.0"));
+        assert_eq!(decimal_repr("-1.25").as_deref(), Some("-1.25"));
+        assert_eq!(decimal_repr("1.23456"), None);
+        assert_eq!(decimal_repr("1e5"), None);
+        assert_eq!(decimal_repr("1."), None);
+    }
+
+    #[test]
+    fn coerce_record() {
+        let expected = SchemaType::Record {
+            attrs: [
+                ("count".into(), AttributeType::required(SchemaType::Long)),
+                ("price".into(), AttributeType::required(ext("decimal"))),
+                ("at".into(), AttributeType::optional(ext("datetime"))),
+                (
+                    "limits".into(),
+                    AttributeType::required(SchemaType::Set {
+                        element_ty: Box::new(SchemaType::Long),
+                    }),
+                ),
+                ("name".into(), AttributeType::required(SchemaType::String)),
+            ]
+            .into_iter()
+            .collect(),
+            open_attrs: false,
+        };
+        let mut coercions = Vec::new();
+        let coerced = coerce_json(
+            json!({
+                "count": "42",
+                "price": 10,
+                "at": "2024-01-01T10:00:00+01:00",
+                "limits": [1, "2", 3.0],
+                "name": "7",
+                "extra": "1",
+            }),
+            &expected,
+            &mut coercions,
+        );
+        assert_eq!(
+            coerced,
+            json!({
+                "count": 42,
+                "price": "10.0",
+                "at": "2024-01-01T10:00:00+0100",
+                "limits": [1, 2, 3],
+                "name": "7",
+                "extra": "1",
+            })
+        );
+        let mut paths: Vec<String> = coercions.iter().map(|c| c.path().join(".")).collect();
+        paths.sort();
+        assert_eq!(paths, ["at", "count", "limits.1", "limits.2", "price"]);
+        let count = coercions.iter().find(|c| c.path() == ["count"]).unwrap();
+        assert_eq!(count.original(), &json!("42"));
+        assert_eq!(count.coerced(), &json!(42));
+        assert_eq!(count.expected(), &SchemaType::Long);
+    }
+
+    #[test]
+    fn uncoercible_values_unchanged() {
+        let mut coercions = Vec::new();
+        assert_eq!(
+            coerce_json(json!("forty-two"), &SchemaType::Long, &mut coercions),
+            json!("forty-two")
+        );
+        assert_eq!(
+            coerce_json(json!(1.5), &SchemaType::Long, &mut coercions),
+            json!(1.5)
+        );
+        assert_eq!(
+            coerce_json(json!("1.5"), &ext("decimal"), &mut coercions),
+            json!("1.5")
+        );
+        assert!(coercions.is_empty());
+    }
+}
@@ -15,8 +15,9 @@
  */
 
 use super::{
+    coerce_json,
     err::{JsonDeserializationError, JsonDeserializationErrorContext},
-    SchemaType, ValueParser,
+    JsonCoercion, SchemaType, ValueParser,
 };
 use crate::ast::{Context, ContextCreationError};
 use crate::extensions::Extensions;
@@ -93,6 +94,24 @@ impl<S: ContextSchema> ContextJsonParser<'_, '_, S> {
             .map_err(ContextJsonDeserializationError::ContextCreation)
     }
 
+    /// Parse loosely-typed context JSON (in `serde_json::Value` form) into a
+    /// `Context` object, first coercing values into the types the schema
+    /// expects as described in [`coerce_json`]. Returns the coercions which
+    /// were performed along with the `Context`.
+    ///
+    /// If no schema is present, no coercions are performed.
+    pub fn from_json_value_coerced(
+        &self,
+        json: serde_json::Value,
+    ) -> Result<(Context, Vec<JsonCoercion>), ContextJsonDeserializationError> {
+        let mut coercions = Vec::new();
+        let json = match self.schema {
+            Some(schema) => coerce_json(json, &schema.context_type(), &mut coercions),
+            None => json,
+        };
+        Ok((self.from_json_value(json)?, coercions))
+    }
+
     /// Parse context JSON (in `std::io::Read` form) into a `Context` object
     pub fn from_json_file(
         &self,
@@ -20,7 +20,7 @@ use super::{
     ValueParser,
 };
 use crate::ast::{BorrowedRestrictedExpr, Entity, EntityUID, PartialValue, RestrictedExpr};
-use crate::entities::conformance::EntitySchemaConformanceChecker;
+use crate::entities::conformance::{validate_euids_in_entity, EntitySchemaConformanceChecker};
 use crate::entities::{
     conformance::err::{EntitySchemaConformanceError, UnexpectedEntityTypeError},
     Entities, EntitiesError, TCComputation,
@@ -32,7 +32,7 @@ use serde_with::serde_as;
 use smol_str::SmolStr;
 use std::sync::Arc;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::Read,
 };
 
@@ -83,6 +83,70 @@ pub struct EntityJsonParser<'e, 's, S = NoEntitiesSchema> {
     /// Whether to compute, enforce, or assume TC for entities parsed using this
     /// parser.
     tc_computation: TCComputation,
+
+    /// How to treat entity attributes which are not declared in the `schema`.
+    /// Has no effect if there is no `schema`.
+    undeclared_attrs: UndeclaredAttrHandling,
+}
+
+/// How schema-based entity parsing treats entity attributes which are not
+/// declared in the schema (for entity types which do not allow additional
+/// attributes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UndeclaredAttrHandling {
+    /// Return an error. This is the default.
+    #[default]
+    Error,
+    /// Remove the attribute from the entity, recording its name in the
+    /// [`UndeclaredAttrs`] report
+    Drop,
+    /// Remove the attribute from the entity, but retain its JSON value in the
+    /// [`UndeclaredAttrs`] side map
+    Preserve,
+}
+
+/// Attributes which were present in entity JSON but not declared in the
+/// schema, and were dropped or preserved rather than causing an error.
+/// See [`UndeclaredAttrHandling`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UndeclaredAttrs {
+    /// For each entity, the undeclared attributes, with their JSON values if
+    /// they were preserved (or `None` if they were dropped)
+    attrs: BTreeMap<EntityUID, BTreeMap<SmolStr, Option<serde_json::Value>>>,
+}
+
+impl UndeclaredAttrs {
+    /// Returns `true` if no undeclared attributes were encountered
+    pub fn is_empty(&self) -> bool {
+        self.attrs.is_empty()
+    }
+
+    /// Iterate over the (entity, attribute) pairs for every undeclared
+    /// attribute that was encountered, whether dropped or preserved
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityUID, &SmolStr)> {
+        self.attrs
+            .iter()
+            .flat_map(|(uid, attrs)| attrs.keys().map(move |attr| (uid, attr)))
+    }
+
+    /// Get the preserved JSON value of the undeclared attribute `attr` of
+    /// entity `uid`. Returns `None` if there is no such attribute, or if it
+    /// was dropped rather than preserved.
+    pub fn get(&self, uid: &EntityUID, attr: &str) -> Option<&serde_json::Value> {
+        self.attrs.get(uid)?.get(attr)?.as_ref()
+    }
+
+    /// Iterate over the undeclared attributes of entity `uid`, with their
+    /// JSON values if they were preserved
+    pub fn attrs_of(
+        &self,
+        uid: &EntityUID,
+    ) -> impl Iterator<Item = (&SmolStr, Option<&serde_json::Value>)> {
+        self.attrs
+            .get(uid)
+            .into_iter()
+            .flat_map(|attrs| attrs.iter().map(|(k, v)| (k, v.as_ref())))
+    }
 }
 
 /// Schema information about a single entity can take one of these forms:
@@ -126,6 +190,20 @@ impl<'e, 's, S> EntityJsonParser<'e, 's, S> {
             schema,
             extensions,
             tc_computation,
+            undeclared_attrs: UndeclaredAttrHandling::default(),
+        }
+    }
+
+    /// Set how this parser treats entity attributes which are not declared in
+    /// the schema. By default, they are an error.
+    ///
+    /// To find out which attributes were dropped or preserved, use one of the
+    /// `*_with_undeclared_attrs()` methods.
+    #[must_use]
+    pub fn with_undeclared_attr_handling(self, handling: UndeclaredAttrHandling) -> Self {
+        Self {
+            undeclared_attrs: handling,
+            ..self
         }
     }
 }
@@ -161,10 +239,47 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
         self.parse_ejsons(ejsons)
     }
 
+    /// Like [`Self::from_json_str()`], but also returns the attributes which
+    /// were not declared in the schema and were dropped or preserved according
+    /// to [`Self::with_undeclared_attr_handling()`].
+    pub fn from_json_str_with_undeclared_attrs(
+        &self,
+        json: &str,
+    ) -> Result<(Entities, UndeclaredAttrs), EntitiesError> {
+        let ejsons: Vec<EntityJson> =
+            serde_json::from_str(json).map_err(JsonDeserializationError::from)?;
+        self.parse_ejsons_with_undeclared_attrs(ejsons)
+    }
+
+    /// Like [`Self::from_json_value()`], but also returns the attributes which
+    /// were not declared in the schema and were dropped or preserved according
+    /// to [`Self::with_undeclared_attr_handling()`].
+    pub fn from_json_value_with_undeclared_attrs(
+        &self,
+        json: serde_json::Value,
+    ) -> Result<(Entities, UndeclaredAttrs), EntitiesError> {
+        let ejsons: Vec<EntityJson> =
+            serde_json::from_value(json).map_err(JsonDeserializationError::from)?;
+        self.parse_ejsons_with_undeclared_attrs(ejsons)
+    }
+
+    /// Like [`Self::from_json_file()`], but also returns the attributes which
+    /// were not declared in the schema and were dropped or preserved according
+    /// to [`Self::with_undeclared_attr_handling()`].
+    pub fn from_json_file_with_undeclared_attrs(
+        &self,
+        json: impl std::io::Read,
+    ) -> Result<(Entities, UndeclaredAttrs), EntitiesError> {
+        let ejsons: Vec<EntityJson> =
+            serde_json::from_reader(json).map_err(JsonDeserializationError::from)?;
+        self.parse_ejsons_with_undeclared_attrs(ejsons)
+    }
+
     /// Parse an entities JSON file (in [`&str`] form) into an iterator over [`Entity`]s.
     ///
     /// If the `EntityJsonParser` has a `schema`, this also adds `Action`
-    /// entities declared in the `schema`.
+    /// entities declared in the `schema`, and checks that the parsed entities
+    /// only refer to declared actions and entities of enumerated entity types.
     pub fn iter_from_json_str(
         &self,
         json: &str,
@@ -177,7 +292,8 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
     /// Parse an entities JSON file (in [`serde_json::Value`] form) into an iterator over [`Entity`]s.
     ///
     /// If the `EntityJsonParser` has a `schema`, this also adds `Action`
-    /// entities declared in the `schema`.
+    /// entities declared in the `schema`, and checks that the parsed entities
+    /// only refer to declared actions and entities of enumerated entity types.
     pub fn iter_from_json_value(
         &self,
         json: serde_json::Value,
@@ -190,7 +306,8 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
     /// Parse an entities JSON file (in [`std::io::Read`] form) into an iterator over [`Entity`]s.
     ///
     /// If the `EntityJsonParser` has a `schema`, this also adds `Action`
-    /// entities declared in the `schema`.
+    /// entities declared in the `schema`, and checks that the parsed entities
+    /// only refer to declared actions and entities of enumerated entity types.
     pub fn iter_from_json_file(
         &self,
         json: impl std::io::Read,
@@ -203,14 +320,26 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
     /// Internal function that converts an iterator over [`EntityJson`] into an
     /// iterator over [`Entity`] and also adds any `Action` entities declared in
     /// `self.schema`.
+    ///
+    /// The entities are not fully validated against `self.schema`, which
+    /// happens when they are added to an [`Entities`], but entities of
+    /// enumerated entity types and actions they refer to are checked to be
+    /// declared.
     fn iter_ejson_to_iter_entity(
         &self,
         ejsons: impl IntoIterator<Item = EntityJson>,
     ) -> Result<impl Iterator<Item = Entity> + '_, EntitiesError> {
         let mut entities: Vec<Entity> = ejsons
             .into_iter()
-            .map(|ejson| self.parse_ejson(ejson).map_err(EntitiesError::from))
-            .collect::<Result<_, _>>()?;
+            .map(|ejson| {
+                let entity = self.parse_ejson(ejson, &mut UndeclaredAttrs::default())?;
+                if let Some(schema) = self.schema {
+                    validate_euids_in_entity(schema, &entity)
+                        .map_err(EntitySchemaConformanceError::from)?;
+                }
+                Ok(entity)
+            })
+            .collect::<Result<_, EntitiesError>>()?;
         if let Some(schema) = &self.schema {
             entities.extend(
                 schema
@@ -244,7 +373,7 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
     }
 
     fn single_from_ejson(&self, ejson: EntityJson) -> Result<Entity, EntitiesError> {
-        let entity = self.parse_ejson(ejson)?;
+        let entity = self.parse_ejson(ejson, &mut UndeclaredAttrs::default())?;
         match self.schema {
             None => Ok(entity),
             Some(schema) => {
@@ -264,18 +393,38 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
         &self,
         ejsons: impl IntoIterator<Item = EntityJson>,
     ) -> Result<Entities, EntitiesError> {
+        self.parse_ejsons_with_undeclared_attrs(ejsons)
+            .map(|(entities, _)| entities)
+    }
+
+    /// Like [`Self::parse_ejsons()`], but also returns the undeclared
+    /// attributes which were dropped or preserved
+    fn parse_ejsons_with_undeclared_attrs(
+        &self,
+        ejsons: impl IntoIterator<Item = EntityJson>,
+    ) -> Result<(Entities, UndeclaredAttrs), EntitiesError> {
+        let mut undeclared = UndeclaredAttrs::default();
         let entities: Vec<Entity> = ejsons
             .into_iter()
-            .map(|ejson| self.parse_ejson(ejson))
+            .map(|ejson| self.parse_ejson(ejson, &mut undeclared))
             .collect::<Result<_, _>>()?;
-        Entities::from_entities(entities, self.schema, self.tc_computation, self.extensions)
+        let entities =
+            Entities::from_entities(entities, self.schema, self.tc_computation, self.extensions)?;
+        Ok((entities, undeclared))
     }
 
     /// Internal function that parses an `EntityJson` into an `Entity`.
     ///
     /// This function is not responsible for fully validating the `Entity`
     /// against the `schema`; that happens on construction of an `Entities`
-    fn parse_ejson(&self, ejson: EntityJson) -> Result<Entity, JsonDeserializationError> {
+    ///
+    /// Attributes not declared in the `schema` are recorded in `undeclared`,
+    /// unless they cause an error due to `self.undeclared_attrs`.
+    fn parse_ejson(
+        &self,
+        ejson: EntityJson,
+        undeclared: &mut UndeclaredAttrs,
+    ) -> Result<Entity, JsonDeserializationError> {
         let uid = ejson
             .uid
             .into_euid(&|| JsonDeserializationErrorContext::EntityUid)?;
@@ -303,55 +452,59 @@ impl<S: Schema> EntityJsonParser<'_, '_, S> {
             }
         };
         let vparser = ValueParser::new(self.extensions);
-        let attrs: HashMap<SmolStr, RestrictedExpr> = ejson
-            .attrs
-            .into_iter()
-            .map(|(k, v)| match &entity_schema_info {
-                EntitySchemaInfo::NoSchema => Ok((
-                    k.clone(),
-                    vparser.val_into_restricted_expr(v.into(), None, &|| {
-                        JsonDeserializationErrorContext::EntityAttribute {
-                            uid: uid.clone(),
-                            attr: k.clone(),
-                        }
-                    })?,
-                )),
+        let mut attrs: HashMap<SmolStr, RestrictedExpr> = HashMap::new();
+        for (k, v) in ejson.attrs {
+            let ctx = || JsonDeserializationErrorContext::EntityAttribute {
+                uid: uid.clone(),
+                attr: k.clone(),
+            };
+            let rexpr = match &entity_schema_info {
+                EntitySchemaInfo::NoSchema => {
+                    vparser.val_into_restricted_expr(v.into(), None, &ctx)?
+                }
                 EntitySchemaInfo::NonAction(desc) => {
                     // Depending on the expected type, we may parse the contents
                     // of the attribute differently.
-                    let rexpr = match desc.attr_type(&k) {
+                    match desc.attr_type(&k) {
                         // `None` indicates the attribute shouldn't exist -- see
                         // docs on the `attr_type()` trait method
+                        None if desc.open_attributes() => {
+                            vparser.val_into_restricted_expr(v.into(), None, &ctx)?
+                        }
                         None => {
-                            if desc.open_attributes() {
-                                vparser.val_into_restricted_expr(v.into(), None, &|| {
-                                    JsonDeserializationErrorContext::EntityAttribute {
-                                        uid: uid.clone(),
-                                        attr: k.clone(),
-                                    }
-                                })?
-                            } else {
-                                return Err(JsonDeserializationError::EntitySchemaConformance(
-                                    EntitySchemaConformanceError::unexpected_entity_attr(
-                                        uid.clone(),
-                                        k,
-                                    ),
-                                ));
-                            }
+                            let preserved = match self.undeclared_attrs {
+                                UndeclaredAttrHandling::Error => {
+                                    return Err(JsonDeserializationError::EntitySchemaConformance(
+                                        EntitySchemaConformanceError::unexpected_entity_attr(
+                                            uid.clone(),
+                                            k,
+                                        ),
+                                    ));
+                                }
+                                UndeclaredAttrHandling::Drop => None,
+                                UndeclaredAttrHandling::Preserve => Some(v.into()),
+                            };
+                            undeclared
+                                .attrs
+                                .entry(uid.clone())
+                                .or_default()
+                                .insert(k, preserved);
+                            continue;
                         }
-                        Some(expected_ty) => vparser.val_into_restricted_expr(
-                            v.into(),
-                            Some(&expected_ty),
-                            &|| JsonDeserializationErrorContext::EntityAttribute {
-                                uid: uid.clone(),
-                                attr: k.clone(),
-                            },
-                        )?,
-                    };
-                    Ok((k, rexpr))
+                        Some(expected_ty) => {
+                            vparser.val_into_restricted_expr(v.into(), Some(&expected_ty), &ctx)?
+                        }
+                    }
                 }
-            })
-            .collect::<Result<_, JsonDeserializationError>>()?;
+            };
+            attrs.insert(k, rexpr);
+        }
+        if let EntitySchemaInfo::NonAction(desc) = &entity_schema_info {
+            // Absent attributes take their default value, if they have one
+            for (k, default) in desc.attr_defaults() {
+                attrs.entry(k).or_insert(default);
+            }
+        }
         let tags: HashMap<SmolStr, RestrictedExpr> = ejson
             .tags
             .into_iter()
@@ -475,6 +628,64 @@ impl EntityJson {
                 .collect::<Result<_, JsonSerializationError>>()?,
         })
     }
+
+    /// Convert an `Entity` into an `EntityJson` in canonical form: parents
+    /// are sorted, and extension values in attributes and tags are encoded
+    /// using their canonical constructor and arguments, so that equal
+    /// entities produce equal JSON.
+    ///
+    /// Key ordering is not determined by the `EntityJson` itself; see
+    /// [`canonicalize_json_keys()`].
+    pub fn from_entity_canonical(entity: &Entity) -> Result<Self, JsonSerializationError> {
+        let serialize_kpvalue = |(k, pvalue): (&SmolStr, &PartialValue)| -> Result<_, _> {
+            let json = match pvalue {
+                PartialValue::Value(value) => {
+                    serialize_pvalue(&PartialValue::Value(value.clone().normalize_ext_values()))?
+                }
+                PartialValue::Residual(_) => serialize_pvalue(pvalue)?,
+            };
+            Ok((k.clone(), json))
+        };
+        let mut parents: Vec<&EntityUID> = entity.ancestors().collect();
+        parents.sort_by_cached_key(|euid| euid.to_string());
+        Ok(Self {
+            uid: EntityUidJson::ImplicitEntityEscape(TypeAndId::from(entity.uid())),
+            attrs: entity
+                .attrs()
+                .map(serialize_kpvalue)
+                .collect::<Result<_, JsonSerializationError>>()?,
+            parents: parents
+                .into_iter()
+                .map(|euid| EntityUidJson::ImplicitEntityEscape(TypeAndId::from(euid.clone())))
+                .collect(),
+            tags: entity
+                .tags()
+                .map(serialize_kpvalue)
+                .collect::<Result<_, JsonSerializationError>>()?,
+        })
+    }
+}
+
+/// Recursively sort the keys of every JSON object in `json`, so that its
+/// serialization does not depend on the order in which keys were inserted.
+/// Array elements are left in place.
+pub fn canonicalize_json_keys(json: serde_json::Value) -> serde_json::Value {
+    match json {
+        serde_json::Value::Object(map) => {
+            let mut pairs: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+            pairs.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            serde_json::Value::Object(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (k, canonicalize_json_keys(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(elems) => {
+            serde_json::Value::Array(elems.into_iter().map(canonicalize_json_keys).collect())
+        }
+        json => json,
+    }
 }
 
 #[expect(clippy::panic, reason = "unit test code")]
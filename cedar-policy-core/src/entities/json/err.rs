@@ -16,7 +16,7 @@
 
 use std::fmt::Display;
 
-use super::SchemaType;
+use super::{Refinement, SchemaType};
 use crate::ast::{
     BorrowedRestrictedExpr, EntityAttrEvaluationError, EntityUID, Expr, ExprKind, PolicyID,
     RestrictedExpr, RestrictedExpressionError, Type,
@@ -553,6 +553,12 @@ pub enum JsonDeserializationErrorContext {
     },
     /// The error occured while deserializing a template link
     TemplateLink,
+    /// The error occurred while deserializing the default value of the
+    /// attribute `attr` in a schema.
+    AttributeDefault {
+        /// Attribute whose default value we were deserializing
+        attr: SmolStr,
+    },
     /// The context was unknown, this shouldn't surface to users
     Unknown,
 }
@@ -585,6 +591,10 @@ enum TypeMismatchReason {
     /// attribute we expected.
     #[error("is missing the required attribute `{0}`")]
     MissingRequiredAtr(SmolStr),
+    /// We saw a value of the expected type, but it doesn't satisfy the
+    /// refinement on the attribute's type.
+    #[error("does not satisfy the refinement `{0}`")]
+    UnsatisfiedRefinement(Refinement),
     /// No further detail available.
     #[error("does not")]
     None,
@@ -629,6 +639,18 @@ impl TypeMismatchError {
             actual_val: Box::new(actual_val),
         }
     }
+
+    pub(crate) fn unsatisfied_refinement(
+        expected: SchemaType,
+        refinement: Refinement,
+        actual_val: RestrictedExpr,
+    ) -> Self {
+        Self {
+            expected: Box::new(expected),
+            mismatch_reason: TypeMismatchReason::UnsatisfiedRefinement(refinement),
+            actual_val: Box::new(actual_val),
+        }
+    }
 }
 
 impl std::fmt::Display for JsonDeserializationErrorContext {
@@ -641,6 +663,9 @@ impl std::fmt::Display for JsonDeserializationErrorContext {
             Self::Context => write!(f, "while parsing context"),
             Self::Policy { id } => write!(f, "while parsing JSON policy `{id}`"),
             Self::TemplateLink => write!(f, "while parsing a template link"),
+            Self::AttributeDefault { attr } => {
+                write!(f, "in the default value of attribute `{attr}`")
+            }
             Self::Unknown => write!(f, "parsing context was unknown, please file a bug report at https://github.com/cedar-policy/cedar so we can improve this error message"),
         }
     }
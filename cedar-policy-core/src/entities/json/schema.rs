@@ -14,8 +14,8 @@
  * limitations under the License.
  */
 
-use super::SchemaType;
-use crate::ast::{Eid, Entity, EntityType, EntityUID};
+use super::{EntityConstraint, Refinement, SchemaType};
+use crate::ast::{Eid, Entity, EntityType, EntityUID, RestrictedExpr};
 use crate::entities::{Name, UnreservedId};
 use nonempty::NonEmpty;
 use smol_str::SmolStr;
@@ -133,6 +133,25 @@ pub trait EntityTypeDescription {
     /// Get the names of all the required attributes for this entity type.
     fn required_attrs<'s>(&'s self) -> Box<dyn Iterator<Item = SmolStr> + 's>;
 
+    /// Get the default values of attributes for this entity type. Attributes
+    /// with a default value take that value when they are absent from an
+    /// entity.
+    fn attr_defaults<'s>(&'s self) -> Box<dyn Iterator<Item = (SmolStr, RestrictedExpr)> + 's> {
+        Box::new(std::iter::empty())
+    }
+
+    /// Get the restriction on the values of the given attribute beyond its
+    /// type, if it has one
+    fn attr_refinement(&self, _attr: &str) -> Option<Refinement> {
+        None
+    }
+
+    /// Get the invariants over their attributes which entities of this type
+    /// must satisfy
+    fn constraints<'s>(&'s self) -> Box<dyn Iterator<Item = EntityConstraint> + 's> {
+        Box::new(std::iter::empty())
+    }
+
     /// Get the entity types which are allowed to be parents of this entity type.
     fn allowed_parent_types(&self) -> Arc<HashSet<EntityType>>;
 
@@ -14,10 +14,19 @@
  * limitations under the License.
  */
 
-use crate::ast::{EntityType, Name, Type};
+use crate::ast::{
+    BorrowedRestrictedExpr, EntityType, Expr, Literal, Name, PartialValue, RestrictedExpr, Type,
+    Value, ValueKind,
+};
+use crate::evaluator::RestrictedEvaluator;
+use crate::extensions::Extensions;
+use crate::parser::err::{ParseError, ParseErrors, ToASTErrorKind};
 use itertools::Itertools;
+use nonempty::{nonempty, NonEmpty};
+use regex::Regex;
 use smol_str::SmolStr;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 
 /// Possible types that schema-based parsing can expect for Cedar values.
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
@@ -35,6 +44,12 @@ pub enum SchemaType {
     },
     /// Type of the empty set.  (Compatible with all `Set` types)
     EmptySet,
+    /// Map from `String` keys to values of the specified type. At runtime a
+    /// map is a record.
+    Map {
+        /// Type of the values
+        value_ty: Box<SchemaType>,
+    },
     /// Record, with the specified attributes having the specified types
     Record {
         /// Attributes and their types
@@ -47,6 +62,11 @@ pub enum SchemaType {
         /// Entity type
         ty: EntityType,
     },
+    /// Entity of any one of several entity types
+    EntityUnion {
+        /// Entity types. There are always at least two of them.
+        tys: BTreeSet<EntityType>,
+    },
     /// Extension types
     Extension {
         /// Name of the extension type.
@@ -64,6 +84,253 @@ pub struct AttributeType {
     pub(crate) attr_type: SchemaType,
     /// Is the attribute required
     pub(crate) required: bool,
+    /// Value of the attribute when it is absent (only for optional attributes)
+    pub(crate) default: Option<RestrictedExpr>,
+    /// Restriction on the values the attribute may take, beyond its type
+    pub(crate) refinement: Option<Refinement>,
+}
+
+/// Restriction on the values of a `Long`, `String`, or extension-typed
+/// attribute, beyond its type
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub enum Refinement {
+    /// `Long` values in an inclusive range
+    Range {
+        /// Least allowed value, if any
+        min: Option<i64>,
+        /// Greatest allowed value, if any
+        max: Option<i64>,
+    },
+    /// `String` values which match a regular expression
+    Pattern(StringPattern),
+    /// Extension values for which the named extension method, which takes no
+    /// other arguments and returns a `Bool`, returns `true`; e.g., `ipaddr`
+    /// values for which `isIpv4()` is `true`
+    Satisfying(Name),
+}
+
+/// A regular expression which must match the whole of a `String` value
+#[derive(Debug, Clone)]
+pub struct StringPattern {
+    /// The pattern as written in the schema
+    source: SmolStr,
+    /// `source`, anchored at both ends
+    regex: Regex,
+}
+
+impl StringPattern {
+    /// Compile a pattern, using the syntax of the `regex` crate
+    pub fn new(source: impl Into<SmolStr>) -> Result<Self, regex::Error> {
+        let source = source.into();
+        let regex = Regex::new(&format!("^(?:{source})$"))?;
+        Ok(Self { source, regex })
+    }
+
+    /// The pattern as written in the schema
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Does the whole of `s` match the pattern
+    pub fn is_match(&self, s: &str) -> bool {
+        self.regex.is_match(s)
+    }
+}
+
+impl PartialEq for StringPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for StringPattern {}
+
+impl std::hash::Hash for StringPattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+/// An invariant over the attributes of an entity, declared on its entity type
+/// and checked when entities of that type are ingested.
+///
+/// A constraint is a Cedar expression which must evaluate to `true`. It refers
+/// to the entity's attributes by their bare names, e.g., `end_time >
+/// start_time`. The attributes are also bound to `context`, so attributes whose
+/// names are not identifiers can be written as `context["start time"]`.
+#[derive(Debug, Clone)]
+pub struct EntityConstraint {
+    /// The constraint as written in the schema
+    source: SmolStr,
+    /// `source`, with bare attribute names resolved against `context`
+    expr: Arc<Expr>,
+}
+
+impl EntityConstraint {
+    /// Parse a constraint
+    pub fn parse(source: impl Into<SmolStr>) -> Result<Self, ParseErrors> {
+        let source = source.into();
+        let mut text = source.to_string();
+        loop {
+            let errs = match crate::parser::parse_expr(&text) {
+                Ok(expr) => {
+                    return Ok(Self {
+                        source,
+                        expr: Arc::new(expr),
+                    })
+                }
+                Err(errs) => errs,
+            };
+            // The policy parser rejects bare names as invalid variables. Turn
+            // each of them into an access on `context` and try again. Each
+            // round removes at least one such name, so this terminates.
+            let mut bare_names = errs
+                .iter()
+                .filter_map(|err| match err {
+                    ParseError::ToAST(err) => match err.kind() {
+                        ToASTErrorKind::ArbitraryVariable(name) if !name.contains("::") => {
+                            err.source_loc().map(|loc| loc.start()..loc.end())
+                        }
+                        _ => None,
+                    },
+                    ParseError::ToCST(_) => None,
+                })
+                .collect_vec();
+            if bare_names.is_empty() {
+                return Err(errs);
+            }
+            bare_names.sort_unstable_by_key(|span| span.start);
+            bare_names.dedup();
+            for span in bare_names.into_iter().rev() {
+                text.insert_str(span.start, "context.");
+            }
+        }
+    }
+
+    /// The constraint as written in the schema
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The expression to evaluate, with the entity's attributes as `context`
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
+
+impl PartialEq for EntityConstraint {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for EntityConstraint {}
+
+impl std::hash::Hash for EntityConstraint {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+impl std::fmt::Display for EntityConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Refinement {
+    /// Does the value `expr` satisfy this refinement. Values which are not of
+    /// the type the refinement applies to (including unknowns) trivially
+    /// satisfy it, since the refinement is only checked after the type.
+    pub fn admits(&self, expr: BorrowedRestrictedExpr<'_>) -> bool {
+        match (expr.as_long(), expr.as_string(), expr.as_extn_fn_call()) {
+            (Some(i), _, _) => self.admits_long(i),
+            (_, Some(s), _) => self.admits_string(s),
+            (_, _, Some(_)) => match self {
+                Self::Satisfying(_) => {
+                    let extensions = Extensions::all_available();
+                    // Calls which fail to evaluate (e.g., because their
+                    // arguments are ill-formed) are reported when the value is
+                    // checked against the attribute's type
+                    RestrictedEvaluator::new(extensions)
+                        .interpret(expr)
+                        .is_ok_and(|value| self.admits_value(&value))
+                }
+                Self::Range { .. } | Self::Pattern(_) => true,
+            },
+            _ => true,
+        }
+    }
+
+    /// Does the value `value` satisfy this refinement. Like
+    /// [`Refinement::admits()`], values of other types trivially satisfy it.
+    pub fn admits_value(&self, value: &Value) -> bool {
+        match value.value_kind() {
+            ValueKind::Lit(Literal::Long(i)) => self.admits_long(*i),
+            ValueKind::Lit(Literal::String(s)) => self.admits_string(s),
+            ValueKind::ExtensionValue(_) => self.admits_extension_value(value),
+            _ => true,
+        }
+    }
+
+    /// Does the `Long` value `i` satisfy this refinement
+    pub fn admits_long(&self, i: i64) -> bool {
+        match self {
+            Self::Range { min, max } => {
+                min.is_none_or(|min| min <= i) && max.is_none_or(|max| i <= max)
+            }
+            Self::Pattern(_) | Self::Satisfying(_) => true,
+        }
+    }
+
+    /// Does the `String` value `s` satisfy this refinement
+    pub fn admits_string(&self, s: &str) -> bool {
+        match self {
+            Self::Range { .. } | Self::Satisfying(_) => true,
+            Self::Pattern(pattern) => pattern.is_match(s),
+        }
+    }
+
+    /// Does the extension value `value` satisfy this refinement. The method of
+    /// a [`Refinement::Satisfying`] is looked up in all available extensions;
+    /// values for which it does not return `true` (including values it cannot
+    /// be applied to) do not satisfy the refinement.
+    fn admits_extension_value(&self, value: &Value) -> bool {
+        match self {
+            Self::Range { .. } | Self::Pattern(_) => true,
+            Self::Satisfying(method) => {
+                Extensions::all_available().func(method).is_ok_and(|func| {
+                    matches!(
+                        func.call(std::slice::from_ref(value)),
+                        Ok(PartialValue::Value(Value {
+                            value: ValueKind::Lit(Literal::Bool(true)),
+                            ..
+                        }))
+                    )
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Refinement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Range { min, max } => {
+                write!(f, "[")?;
+                if let Some(min) = min {
+                    write!(f, "{min}")?;
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    write!(f, "{max}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Pattern(pattern) => write!(f, "matching \"{}\"", pattern.as_str().escape_debug()),
+            Self::Satisfying(method) => write!(f, "satisfying \"{method}\""),
+        }
+    }
 }
 
 impl SchemaType {
@@ -83,19 +350,49 @@ impl SchemaType {
         }
     }
 
+    /// Return the runtime `Type`s of the values which have this `SchemaType`.
+    ///
+    /// This is a single `Type` except for `EntityUnion`, whose values may have
+    /// any one of its entity types.
+    pub fn runtime_types(&self) -> NonEmpty<Type> {
+        match self {
+            Self::Bool => nonempty![Type::Bool],
+            Self::Long => nonempty![Type::Long],
+            Self::String => nonempty![Type::String],
+            Self::Set { .. } | Self::EmptySet => nonempty![Type::Set],
+            Self::Map { .. } | Self::Record { .. } => nonempty![Type::Record],
+            Self::Entity { ty } => nonempty![Type::Entity { ty: ty.clone() }],
+            #[expect(
+                clippy::unwrap_used,
+                reason = "`EntityUnion` always has at least two entity types"
+            )]
+            Self::EntityUnion { tys } => NonEmpty::from_vec(
+                tys.iter()
+                    .map(|ty| Type::Entity { ty: ty.clone() })
+                    .collect(),
+            )
+            .unwrap(),
+            Self::Extension { name } => nonempty![Type::Extension { name: name.clone() }],
+        }
+    }
+
     /// Iterate over all extension function types contained in this SchemaType
     pub fn contained_ext_types(&self) -> Box<dyn Iterator<Item = &Name> + '_> {
         match self {
             Self::Extension { name } => Box::new(std::iter::once(name)),
             Self::Set { element_ty } => element_ty.contained_ext_types(),
+            Self::Map { value_ty } => value_ty.contained_ext_types(),
             Self::Record { attrs, .. } => Box::new(
                 attrs
                     .values()
                     .flat_map(|ty| ty.attr_type.contained_ext_types()),
             ),
-            Self::Bool | Self::Long | Self::String | Self::EmptySet | Self::Entity { .. } => {
-                Box::new(std::iter::empty())
-            }
+            Self::Bool
+            | Self::Long
+            | Self::String
+            | Self::EmptySet
+            | Self::Entity { .. }
+            | Self::EntityUnion { .. } => Box::new(std::iter::empty()),
         }
     }
 }
@@ -106,6 +403,8 @@ impl AttributeType {
         Self {
             attr_type,
             required: true,
+            default: None,
+            refinement: None,
         }
     }
 
@@ -114,6 +413,8 @@ impl AttributeType {
         Self {
             attr_type,
             required: false,
+            default: None,
+            refinement: None,
         }
     }
 
@@ -126,19 +427,45 @@ impl AttributeType {
     pub fn schema_type(&self) -> &SchemaType {
         &self.attr_type
     }
+
+    /// Set the value of the attribute when it is absent
+    pub fn with_default(self, default: Option<RestrictedExpr>) -> Self {
+        Self { default, ..self }
+    }
+
+    /// Get the value of the attribute when it is absent, if it has one
+    pub fn default_value(&self) -> Option<&RestrictedExpr> {
+        self.default.as_ref()
+    }
+
+    /// Restrict the values the attribute may take
+    pub fn with_refinement(self, refinement: Option<Refinement>) -> Self {
+        Self { refinement, ..self }
+    }
+
+    /// Get the restriction on the values the attribute may take, if it has one
+    pub fn refinement(&self) -> Option<&Refinement> {
+        self.refinement.as_ref()
+    }
 }
 
-impl From<SchemaType> for Type {
-    fn from(ty: SchemaType) -> Self {
+/// Fails (returning the `SchemaType`) for an `EntityUnion`, since values of
+/// that type may have any of several runtime types
+impl TryFrom<SchemaType> for Type {
+    type Error = SchemaType;
+
+    fn try_from(ty: SchemaType) -> Result<Self, SchemaType> {
         match ty {
-            SchemaType::Bool => Type::Bool,
-            SchemaType::Long => Type::Long,
-            SchemaType::String => Type::String,
-            SchemaType::Set { .. } => Type::Set,
-            SchemaType::EmptySet => Type::Set,
-            SchemaType::Record { .. } => Type::Record,
-            SchemaType::Entity { ty } => Type::Entity { ty },
-            SchemaType::Extension { name } => Type::Extension { name },
+            SchemaType::Bool => Ok(Type::Bool),
+            SchemaType::Long => Ok(Type::Long),
+            SchemaType::String => Ok(Type::String),
+            SchemaType::Set { .. } => Ok(Type::Set),
+            SchemaType::EmptySet => Ok(Type::Set),
+            SchemaType::Record { .. } => Ok(Type::Record),
+            SchemaType::Map { .. } => Ok(Type::Record),
+            SchemaType::Entity { ty } => Ok(Type::Entity { ty }),
+            SchemaType::EntityUnion { .. } => Err(ty),
+            SchemaType::Extension { name } => Ok(Type::Extension { name }),
         }
     }
 }
@@ -151,6 +478,7 @@ impl std::fmt::Display for SchemaType {
             Self::String => write!(f, "string"),
             Self::Set { element_ty } => write!(f, "[{element_ty}]"),
             Self::EmptySet => write!(f, "[]"),
+            Self::Map { value_ty } => write!(f, "{{ string => {value_ty} }}"),
             Self::Record { attrs, open_attrs } => {
                 write!(f, "{{ ")?;
                 // sorting attributes ensures that there is a single, deterministic
@@ -173,6 +501,9 @@ impl std::fmt::Display for SchemaType {
                 Ok(())
             }
             Self::Entity { ty } => write!(f, "`{ty}`"),
+            Self::EntityUnion { tys } => {
+                write!(f, "{}", tys.iter().map(|ty| format!("`{ty}`")).join(" | "))
+            }
             Self::Extension { name } => write!(f, "{name}"),
         }
     }
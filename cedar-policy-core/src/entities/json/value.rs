@@ -737,7 +737,7 @@ impl<'e> ValueParser<'e> {
             // apply: for instance, the `__entity` escape can optionally be omitted.
             // What this means is that we parse the contents as `EntityUidJson`, and
             // then convert that into an entity reference `RestrictedExpr`
-            Some(SchemaType::Entity { .. }) => {
+            Some(SchemaType::Entity { .. } | SchemaType::EntityUnion { .. }) => {
                 let uidjson: EntityUidJson = serde_json::from_value(val)?;
                 Ok(RestrictedExpr::val(uidjson.into_euid(ctx)?))
             }
@@ -861,6 +861,49 @@ impl<'e> ValueParser<'e> {
                     }
                 }
             },
+            // The expected type is a map type. No special parsing rules apply,
+            // but we need to parse the values according to the expected value
+            // type
+            Some(expected_ty @ SchemaType::Map { value_ty }) => match val {
+                serde_json::Value::Object(actual_attrs) => {
+                    let rexpr_pairs = actual_attrs
+                        .into_iter()
+                        .map(|(k, v)| {
+                            self.val_into_restricted_expr(v, Some(value_ty), ctx)
+                                .map(|v| (k.into(), v))
+                        })
+                        .collect::<Result<Vec<(SmolStr, RestrictedExpr)>, JsonDeserializationError>>()?;
+                    RestrictedExpr::record(rexpr_pairs).map_err(|e| match e {
+                        ExpressionConstructionError::DuplicateKey(
+                            expression_construction_errors::DuplicateKeyError { key, .. },
+                        ) => JsonDeserializationError::duplicate_key(ctx(), key),
+                    })
+                }
+                val => {
+                    let actual_val = {
+                        let jvalue: CedarValueJson = serde_json::from_value(val)?;
+                        jvalue.into_expr(ctx)?
+                    };
+                    let err = TypeMismatchError::type_mismatch(
+                        expected_ty.clone(),
+                        actual_val.try_type_of(self.extensions),
+                        actual_val,
+                    );
+                    match ctx() {
+                        JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
+                            Err(JsonDeserializationError::EntitySchemaConformance(
+                                EntitySchemaConformanceError::type_mismatch(
+                                    uid,
+                                    attr,
+                                    crate::entities::conformance::err::AttrOrTag::Attr,
+                                    err,
+                                ),
+                            ))
+                        }
+                        ctx => Err(JsonDeserializationError::type_mismatch(ctx, err)),
+                    }
+                }
+            },
             // The expected type is a record type. No special parsing rules
             // apply, but we need to parse the attribute values according to
             // their expected element types
@@ -878,12 +921,34 @@ impl<'e> ValueParser<'e> {
                             match mut_actual_attrs.remove(k.as_str()) {
                                 Some(actual_attr) => {
                                     match self.val_into_restricted_expr(actual_attr, Some(expected_attr_ty.schema_type()), ctx) {
-                                        Ok(actual_attr) => Some(Ok((k.clone(), actual_attr))),
+                                        Ok(actual_attr) => match expected_attr_ty.refinement() {
+                                            Some(refinement) if !refinement.admits(actual_attr.as_borrowed()) => {
+                                                let err = TypeMismatchError::unsatisfied_refinement(
+                                                    expected_attr_ty.schema_type().clone(),
+                                                    refinement.clone(),
+                                                    actual_attr,
+                                                );
+                                                Some(Err(match ctx() {
+                                                    JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
+                                                        JsonDeserializationError::EntitySchemaConformance(
+                                                            EntitySchemaConformanceError::type_mismatch(
+                                                                uid,
+                                                                attr,
+                                                                crate::entities::conformance::err::AttrOrTag::Attr,
+                                                                err,
+                                                            ),
+                                                        )
+                                                    }
+                                                    ctx => JsonDeserializationError::type_mismatch(ctx, err),
+                                                }))
+                                            }
+                                            _ => Some(Ok((k.clone(), actual_attr))),
+                                        },
                                         Err(e) => Some(Err(e)),
                                     }
                                 }
                                 None if expected_attr_ty.is_required() => Some(Err(JsonDeserializationError::missing_required_record_attr(ctx(), k.clone()))),
-                                None => None,
+                                None => expected_attr_ty.default_value().map(|default| Ok((k.clone(), default.clone()))),
                             }
                         })
                         .collect::<Result<Vec<(SmolStr, RestrictedExpr)>, JsonDeserializationError>>()?;
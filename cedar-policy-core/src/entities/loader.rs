@@ -0,0 +1,30 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The (internal) definition of [`EntityLoader`], which the batched evaluator
+//! and entity slicing use to fetch entities on demand.
+
+use crate::ast::{Entity, EntityUID};
+use std::collections::{HashMap, HashSet};
+
+/// Loads entities based on their EntityUID.
+/// See the public version in `api.rs`
+pub trait EntityLoader {
+    /// Load all entities for the given set of entity UIDs.
+    /// Returns a map from [`EntityUID`] to `Option<Entity>`, where `None` indicates
+    /// the entity does not exist.
+    fn load_entities(&mut self, uids: &HashSet<EntityUID>) -> HashMap<EntityUID, Option<Entity>>;
+}
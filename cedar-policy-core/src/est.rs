@@ -31,11 +31,13 @@ use crate::ast::EntityUID;
 use crate::ast::{self, Annotation};
 use crate::entities::json::{err::JsonDeserializationError, EntityUidJson};
 use crate::expr_builder::ExprBuilder;
+use crate::extensions::{ExtStyles, Extensions};
 use crate::parser::cst;
 use crate::parser::err::{parse_errors, ParseErrors, ToASTError, ToASTErrorKind};
 use crate::parser::util::{flatten_tuple_2, flatten_tuple_4};
 #[cfg(feature = "tolerant-ast")]
 use crate::parser::Loc;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::collections::{BTreeMap, HashMap};
@@ -70,6 +72,18 @@ pub struct Policy {
     #[serde(default)]
     #[serde(skip_serializing_if = "Annotations::is_empty")]
     pub(crate) annotations: Annotations,
+    /// Declared types of the template's value slots
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(rename = "valueSlots")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Record<string, SlotType>"))]
+    pub(crate) value_slots: BTreeMap<ast::SlotId, ast::SlotType>,
+    /// Default values of the template's value slots
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(rename = "valueSlotDefaults")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Record<string, Expr>"))]
+    pub(crate) value_slot_defaults: BTreeMap<ast::SlotId, Expr>,
 }
 
 /// Serde JSON structure for a `when` or `unless` clause in the EST format
@@ -103,6 +117,8 @@ impl Policy {
                 .map(|clause| clause.link(vals))
                 .collect::<Result<Vec<_>, _>>()?,
             annotations: self.annotations,
+            value_slots: self.value_slots,
+            value_slot_defaults: self.value_slot_defaults,
         })
     }
 
@@ -122,12 +138,15 @@ impl Policy {
                 .map(|clause| clause.sub_entity_literals(mapping))
                 .collect::<Result<Vec<_>, _>>()?,
             annotations: self.annotations,
+            value_slots: self.value_slots,
+            value_slot_defaults: self.value_slot_defaults,
         })
     }
 
     /// Returns true if this policy is a template, i.e., it has at least one slot.
     pub fn is_template(&self) -> bool {
-        self.principal.has_slot()
+        !self.value_slots.is_empty()
+            || self.principal.has_slot()
             || self.action.has_slot()
             || self.resource.has_slot()
             || self.conditions.iter().any(|c| c.has_slot())
@@ -157,7 +176,8 @@ impl Clause {
 
     /// Returns true if this clause has a slot.
     pub fn has_slot(&self) -> bool {
-        // currently, slots are not allowed in clauses
+        // currently, only value slots are allowed in clauses, and those are
+        // declared at the policy level
         false
     }
 }
@@ -165,6 +185,16 @@ impl Clause {
 impl TryFrom<cst::Policy> for Policy {
     type Error = ParseErrors;
     fn try_from(policy: cst::Policy) -> Result<Policy, ParseErrors> {
+        Self::try_from_cst(policy, Extensions::all_available())
+    }
+}
+
+impl Policy {
+    /// Convert a CST policy which may call the functions of `extensions`
+    pub(crate) fn try_from_cst(
+        policy: cst::Policy,
+        extensions: &Extensions<'_>,
+    ) -> Result<Policy, ParseErrors> {
         let policy = match policy {
             cst::Policy::Policy(policy_impl) => policy_impl,
             #[cfg(feature = "tolerant-ast")]
@@ -177,26 +207,34 @@ impl TryFrom<cst::Policy> for Policy {
             }
         };
         let maybe_effect = policy.effect.to_effect();
-        let maybe_scope = policy.extract_scope();
+        let maybe_scope = policy.extract_scope(extensions);
         let maybe_annotations = policy.get_ast_annotations(|v, l| {
             Some(Annotation {
                 val: v?,
                 loc: l.cloned(),
             })
         });
+        let maybe_value_slots = policy.value_slot_declarations();
+        let maybe_defaults = policy.value_slot_defaults(|e| Expr::try_from_cst(e, extensions));
         let maybe_conditions = ParseErrors::transpose(policy.conds.into_iter().map(|node| {
             let (cond, loc) = node.into_inner();
             let cond = cond.ok_or_else(|| {
                 ParseErrors::singleton(ToASTError::new(ToASTErrorKind::EmptyClause(None), loc))
             })?;
-            cond.try_into()
+            Clause::try_from_cst(&cond, extensions)
         }));
 
-        let (effect, annotations, (principal, action, resource), conditions) = flatten_tuple_4(
-            maybe_effect,
-            maybe_annotations,
-            maybe_scope,
-            maybe_conditions,
+        let (
+            (effect, annotations, (principal, action, resource), conditions),
+            (value_slots, defaults),
+        ) = flatten_tuple_2(
+            flatten_tuple_4(
+                maybe_effect,
+                maybe_annotations,
+                maybe_scope,
+                maybe_conditions,
+            ),
+            flatten_tuple_2(maybe_value_slots, maybe_defaults),
         )?;
         Ok(Policy {
             effect,
@@ -205,6 +243,14 @@ impl TryFrom<cst::Policy> for Policy {
             resource: resource.into(),
             conditions,
             annotations: Annotations(annotations),
+            value_slots: value_slots
+                .into_iter()
+                .map(|(slot, ty)| (slot.id, ty))
+                .collect(),
+            value_slot_defaults: defaults
+                .into_iter()
+                .map(|(slot, default)| (slot.id, default))
+                .collect(),
         })
     }
 }
@@ -212,8 +258,15 @@ impl TryFrom<cst::Policy> for Policy {
 impl TryFrom<cst::Cond> for Clause {
     type Error = ParseErrors;
     fn try_from(cond: cst::Cond) -> Result<Clause, ParseErrors> {
+        Self::try_from_cst(&cond, Extensions::all_available())
+    }
+}
+
+impl Clause {
+    /// Convert a CST condition which may call the functions of `extensions`
+    fn try_from_cst(cond: &cst::Cond, extensions: &Extensions<'_>) -> Result<Clause, ParseErrors> {
         let maybe_is_when = cond.cond.to_cond_is_when();
-        match cond.expr {
+        match &cond.expr {
             None => {
                 let maybe_ident = maybe_is_when.map(|is_when| {
                     cst::Ident::Ident(if is_when { "when" } else { "unless" }.into())
@@ -223,8 +276,8 @@ impl TryFrom<cst::Cond> for Clause {
                     .to_ast_err(ToASTErrorKind::EmptyClause(maybe_ident.ok()))
                     .into())
             }
-            Some(ref e) => {
-                let maybe_expr = e.try_into();
+            Some(e) => {
+                let maybe_expr = Expr::try_from_cst(e, extensions);
                 let (is_when, expr) = flatten_tuple_2(maybe_is_when, maybe_expr)?;
                 Ok(if is_when {
                     Clause::When(expr)
@@ -240,12 +293,14 @@ impl Policy {
     /// Try to convert a [`Policy`] into a [`ast::Policy`].
     ///
     /// This process requires a policy ID. If not supplied, this method will
-    /// fill it in as "JSON policy".
+    /// fill it in as "JSON policy". The policy may call the functions of
+    /// `extensions`.
     pub fn try_into_ast_policy(
         self,
         id: Option<ast::PolicyID>,
+        extensions: &Extensions<'_>,
     ) -> Result<ast::Policy, FromJsonError> {
-        let template: ast::Template = self.try_into_ast_policy_or_template(id)?;
+        let template: ast::Template = self.try_into_ast_policy_or_template(id, extensions)?;
         ast::StaticPolicy::try_from(template)
             .map(Into::into)
             .map_err(Into::into)
@@ -255,12 +310,14 @@ impl Policy {
     /// if the input is a static policy.
     ///
     /// This process requires a policy ID. If not supplied, this method will
-    /// fill it in as "JSON policy".
+    /// fill it in as "JSON policy". The policy may call the functions of
+    /// `extensions`.
     pub fn try_into_ast_template(
         self,
         id: Option<ast::PolicyID>,
+        extensions: &Extensions<'_>,
     ) -> Result<ast::Template, FromJsonError> {
-        let template: ast::Template = self.try_into_ast_policy_or_template(id)?;
+        let template: ast::Template = self.try_into_ast_policy_or_template(id, extensions)?;
         if template.slots().count() == 0 {
             Err(FromJsonError::PolicyToTemplate(
                 parse_errors::ExpectedTemplate::new(),
@@ -274,18 +331,33 @@ impl Policy {
     /// represent a template or static policy (which is a template with zero slots).
     ///
     /// This process requires a policy ID. If not supplied, this method will
-    /// fill it in as "JSON policy".
+    /// fill it in as "JSON policy". The policy may call the functions of
+    /// `extensions`.
     pub fn try_into_ast_policy_or_template(
         self,
         id: Option<ast::PolicyID>,
+        extensions: &Extensions<'_>,
     ) -> Result<ast::Template, FromJsonError> {
         let id = id.unwrap_or_else(|| ast::PolicyID::from_string("JSON policy"));
+        let defaults = self
+            .value_slot_defaults
+            .into_iter()
+            .map(|(slot, default)| {
+                Ok((
+                    ast::Slot {
+                        id: slot,
+                        loc: None,
+                    },
+                    default.try_into_ast(&id, extensions)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, FromJsonError>>()?;
         // a right fold of conditions
         // e.g., [c1, c2, c3,] --> c1 && (c2 && c3)
         let mut conds_rev_iter = self
             .conditions
             .into_iter()
-            .map(|cond| cond.try_into_ast(&id))
+            .map(|cond| cond.try_into_ast(&id, extensions))
             .rev()
             .collect::<Result<Vec<_>, _>>()?
             .into_iter();
@@ -313,13 +385,19 @@ impl Policy {
             self.action.try_into()?,
             self.resource.try_into()?,
             conditions,
-        ))
+        )
+        .with_value_slot_declarations(
+            self.value_slots
+                .into_iter()
+                .map(|(id, ty)| (ast::Slot { id, loc: None }, ty)),
+        )?
+        .with_value_slot_defaults(defaults)?)
     }
 }
 
 impl Clause {
     fn filter_slots(e: ast::Expr, is_when: bool) -> Result<ast::Expr, FromJsonError> {
-        let first_slot = e.slots().next();
+        let first_slot = e.slots().find(|slot| !slot.id.is_value_slot());
         if let Some(slot) = first_slot {
             Err(parse_errors::SlotsInConditionClause {
                 slot,
@@ -331,11 +409,15 @@ impl Clause {
         }
     }
     /// `id` is the ID of the policy the clause belongs to, used only for reporting errors
-    fn try_into_ast(self, id: &ast::PolicyID) -> Result<ast::Expr, FromJsonError> {
+    fn try_into_ast(
+        self,
+        id: &ast::PolicyID,
+        extensions: &Extensions<'_>,
+    ) -> Result<ast::Expr, FromJsonError> {
         match self {
-            Clause::When(expr) => Self::filter_slots(expr.try_into_ast(id)?, true),
+            Clause::When(expr) => Self::filter_slots(expr.try_into_ast(id, extensions)?, true),
             Clause::Unless(expr) => {
-                Self::filter_slots(ast::Expr::not(expr.try_into_ast(id)?), false)
+                Self::filter_slots(ast::Expr::not(expr.try_into_ast(id, extensions)?), false)
             }
         }
     }
@@ -362,6 +444,9 @@ impl From<ast::Policy> for Policy {
                     .map(|(k, v)| (k.clone(), Some(v.clone())))
                     .collect(),
             ),
+            // the values of any value slots are already substituted in
+            value_slots: BTreeMap::new(),
+            value_slot_defaults: BTreeMap::new(),
         }
     }
 }
@@ -387,6 +472,19 @@ impl From<ast::Template> for Policy {
                     .map(|(k, v)| (k.clone(), Some(v.clone())))
                     .collect(),
             ),
+            value_slots: ast
+                .value_slot_types()
+                .map(|(slot, ty)| (slot.clone(), ty.clone()))
+                .collect(),
+            value_slot_defaults: ast
+                .value_slot_defaults()
+                .map(|(slot, default)| {
+                    (
+                        slot.clone(),
+                        ast::Expr::from(default.clone()).into_expr::<Builder>(),
+                    )
+                })
+                .collect(),
         }
     }
 }
@@ -399,6 +497,32 @@ impl<T: Clone> From<ast::Expr<T>> for Clause {
 
 impl std::fmt::Display for Policy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with_styles(f, Extensions::all_available().styles())
+    }
+}
+
+impl Policy {
+    /// Display this policy in the Cedar syntax, calling the extension functions
+    /// of `extensions` with their call styles. Functions `extensions` does not
+    /// know of are displayed as function-style calls.
+    pub fn display_with_extensions<'a>(
+        &'a self,
+        extensions: &'a Extensions<'_>,
+    ) -> impl std::fmt::Display + 'a {
+        struct Display<'a>(&'a Policy, &'a ExtStyles);
+        impl std::fmt::Display for Display<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_with_styles(f, self.1)
+            }
+        }
+        Display(self, extensions.styles())
+    }
+
+    fn fmt_with_styles(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        styles: &ExtStyles,
+    ) -> std::fmt::Result {
         for (k, v) in self.annotations.0.iter() {
             write!(f, "@{k}")?;
             if let Some(v) = v {
@@ -406,13 +530,27 @@ impl std::fmt::Display for Policy {
             }
             writeln!(f)?;
         }
+        if !self.value_slots.is_empty() {
+            writeln!(
+                f,
+                "template({}) =>",
+                self.value_slots
+                    .iter()
+                    .map(|(slot, ty)| match self.value_slot_defaults.get(slot) {
+                        Some(default) => format!("{slot}: {ty} = {}", WithStyles(default, styles)),
+                        None => format!("{slot}: {ty}"),
+                    })
+                    .join(", ")
+            )?;
+        }
         write!(
             f,
             "{}({}, {}, {})",
             self.effect, self.principal, self.action, self.resource
         )?;
         for condition in &self.conditions {
-            write!(f, " {condition}")?;
+            write!(f, " ")?;
+            condition.fmt_with_styles(f, styles)?;
         }
         write!(f, ";")
     }
@@ -420,9 +558,19 @@ impl std::fmt::Display for Policy {
 
 impl std::fmt::Display for Clause {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with_styles(f, Extensions::all_available().styles())
+    }
+}
+
+impl Clause {
+    fn fmt_with_styles(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        styles: &ExtStyles,
+    ) -> std::fmt::Result {
         match self {
-            Self::When(expr) => write!(f, "when {{ {expr} }}"),
-            Self::Unless(expr) => write!(f, "unless {{ {expr} }}"),
+            Self::When(expr) => write!(f, "when {{ {} }}", WithStyles(expr, styles)),
+            Self::Unless(expr) => write!(f, "unless {{ {} }}", WithStyles(expr, styles)),
         }
     }
 }
@@ -466,7 +614,7 @@ mod test {
     #[track_caller]
     fn ast_roundtrip(est: Policy) -> Policy {
         let ast = est
-            .try_into_ast_policy(None)
+            .try_into_ast_policy(None, Extensions::all_available())
             .expect("Failed to convert to AST");
         ast.into()
     }
@@ -476,7 +624,7 @@ mod test {
     #[track_caller]
     fn ast_roundtrip_template(est: Policy) -> Policy {
         let ast = est
-            .try_into_ast_policy_or_template(None)
+            .try_into_ast_policy_or_template(None, Extensions::all_available())
             .expect("Failed to convert to AST");
         ast.into()
     }
@@ -486,7 +634,7 @@ mod test {
     #[track_caller]
     fn circular_roundtrip(est: Policy) -> Policy {
         let ast = est
-            .try_into_ast_policy(None)
+            .try_into_ast_policy(None, Extensions::all_available())
             .expect("Failed to convert to AST");
         let text = ast.to_string();
         let cst = parser::text_to_cst::parse_policy(&text)
@@ -501,7 +649,7 @@ mod test {
     #[track_caller]
     fn circular_roundtrip_template(est: Policy) -> Policy {
         let ast = est
-            .try_into_ast_policy_or_template(None)
+            .try_into_ast_policy_or_template(None, Extensions::all_available())
             .expect("Failed to convert to AST");
         let text = ast.to_string();
         let cst = parser::text_to_cst::parse_policy(&text)
@@ -969,6 +1117,90 @@ mod test {
         );
     }
 
+    #[test]
+    fn value_slot_template() {
+        let template = r#"
+            template(?limit: Long, ?tags: Set<String>, ?opts: { level: Long }) =>
+            permit(principal, action, resource)
+            when { resource.size < ?limit && ?tags.contains(resource.tag) && ?opts.level > 0 };
+        "#;
+        let cst = parser::text_to_cst::parse_policy(template)
+            .unwrap()
+            .node
+            .unwrap();
+        let est: Policy = cst.try_into().unwrap();
+        assert!(est.is_template());
+        let json = serde_json::to_value(&est).unwrap();
+        assert_eq!(
+            json["valueSlots"],
+            json!({
+                "?limit": { "type": "Long" },
+                "?tags": { "type": "Set", "element": { "type": "String" } },
+                "?opts": {
+                    "type": "Record",
+                    "attributes": { "level": { "type": "Long" } },
+                },
+            })
+        );
+        let old_est = est.clone();
+        let roundtripped = est_roundtrip(est);
+        assert_eq!(&old_est, &roundtripped);
+        let est = text_roundtrip(&old_est);
+        assert_eq!(&old_est, &est);
+        let est = ast_roundtrip_template(est);
+        assert_eq!(old_est.value_slots, est.value_slots);
+
+        // an undeclared value slot is an error
+        let mut json = json;
+        json["valueSlots"].as_object_mut().unwrap().remove("?limit");
+        let est: Policy = serde_json::from_value(json).unwrap();
+        assert_matches!(
+            est.try_into_ast_policy_or_template(None, Extensions::all_available()),
+            Err(FromJsonError::ValueSlotDeclaration(
+                ast::ValueSlotDeclarationError::Undeclared(slot)
+            )) => {
+                assert_eq!(slot.id, "?limit".parse().unwrap());
+            }
+        );
+    }
+
+    #[test]
+    fn value_slot_defaults() {
+        let template = r#"
+            template(?limit: Long = 10, ?tags: Set<String>) =>
+            permit(principal, action, resource)
+            when { resource.size < ?limit && ?tags.contains(resource.tag) };
+        "#;
+        let cst = parser::text_to_cst::parse_policy(template)
+            .unwrap()
+            .node
+            .unwrap();
+        let est: Policy = cst.try_into().unwrap();
+        let json = serde_json::to_value(&est).unwrap();
+        assert_eq!(
+            json["valueSlotDefaults"],
+            json!({ "?limit": { "Value": 10 } })
+        );
+        let old_est = est.clone();
+        let roundtripped = est_roundtrip(est);
+        assert_eq!(&old_est, &roundtripped);
+        let est = text_roundtrip(&old_est);
+        assert_eq!(&old_est, &est);
+        let est = ast_roundtrip_template(est);
+        assert_eq!(old_est.value_slot_defaults, est.value_slot_defaults);
+
+        // a default of the wrong type is an error
+        let mut json = json;
+        json["valueSlotDefaults"]["?limit"] = json!({ "Value": "ten" });
+        let est: Policy = serde_json::from_value(json).unwrap();
+        assert_matches!(
+            est.try_into_ast_policy_or_template(None, Extensions::all_available()),
+            Err(FromJsonError::ValueSlotDeclaration(
+                ast::ValueSlotDeclarationError::DefaultTypeMismatch { .. }
+            ))
+        );
+    }
+
     #[test]
     fn rbac_template() {
         let template = r#"
@@ -2672,8 +2904,12 @@ mod test {
         );
         let est1: Policy = serde_json::from_value(expected_json).unwrap();
         let est2: Policy = serde_json::from_value(alternative_json).unwrap();
-        let ast1 = est1.try_into_ast_policy(None).unwrap();
-        let ast2 = est2.try_into_ast_policy(None).unwrap();
+        let ast1 = est1
+            .try_into_ast_policy(None, Extensions::all_available())
+            .unwrap();
+        let ast2 = est2
+            .try_into_ast_policy(None, Extensions::all_available())
+            .unwrap();
         assert_eq!(ast1, ast2);
     }
 
@@ -3293,7 +3529,10 @@ mod test {
 
         let cst = parser::text_to_cst::parse_policy(template).unwrap();
         let ast: ast::Template = cst
-            .to_policy_template(ast::PolicyID::from_string("test"))
+            .to_policy_template(
+                ast::PolicyID::from_string("test"),
+                Extensions::all_available(),
+            )
             .unwrap();
 
         let policy = ast::Template::link(
@@ -3371,7 +3610,10 @@ mod test {
 
         let cst = parser::text_to_cst::parse_policy(template).unwrap();
         let ast: ast::Template = cst
-            .to_policy_template(ast::PolicyID::from_string("test"))
+            .to_policy_template(
+                ast::PolicyID::from_string("test"),
+                Extensions::all_available(),
+            )
             .unwrap();
 
         let policy = ast::Template::link(
@@ -3651,7 +3893,8 @@ mod test {
             }
         );
         let est: Policy = serde_json::from_value(template).unwrap();
-        let ast: Result<ast::Policy, _> = est.try_into_ast_policy(None);
+        let ast: Result<ast::Policy, _> =
+            est.try_into_ast_policy(None, Extensions::all_available());
         assert_matches!(
             ast,
             Err(e) => {
@@ -4256,7 +4499,7 @@ mod test {
             assert_matches!(
                 serde_json::from_value::<Policy>(bad)
                     .unwrap()
-                    .try_into_ast_policy(None),
+                    .try_into_ast_policy(None, Extensions::all_available()),
                 Err(e) => {
                     expect_err(
                         "!",
@@ -4431,7 +4674,10 @@ mod test {
     mod reserved_names {
         use cool_asserts::assert_matches;
 
-        use crate::{entities::json::err::JsonDeserializationError, est::FromJsonError};
+        use crate::{
+            entities::json::err::JsonDeserializationError, est::FromJsonError,
+            extensions::Extensions,
+        };
 
         use super::Policy;
         #[test]
@@ -4454,7 +4700,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::InvalidEntityType(_))
             );
 
@@ -4483,7 +4729,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::InvalidEntityType(_))
             );
         }
@@ -4522,7 +4768,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::JsonDeserializationError(
                     JsonDeserializationError::ParseEscape(_)
                 ))
@@ -4546,7 +4792,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::JsonDeserializationError(
                     JsonDeserializationError::ParseEscape(_)
                 ))
@@ -4571,7 +4817,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::JsonDeserializationError(
                     JsonDeserializationError::ParseEscape(_)
                 ))
@@ -4596,7 +4842,7 @@ mod test {
             ))
             .unwrap();
             assert_matches!(
-                policy.try_into_ast_policy(None),
+                policy.try_into_ast_policy(None, Extensions::all_available()),
                 Err(FromJsonError::JsonDeserializationError(
                     JsonDeserializationError::ParseEscape(_)
                 ))
@@ -4799,7 +5045,10 @@ mod test {
         let est: Policy =
             serde_json::from_value(json).expect("Expected valid JSON to parse to EST");
         let ast = est
-            .try_into_ast_policy_or_template(Some(ast::PolicyID::from_string("id")))
+            .try_into_ast_policy_or_template(
+                Some(ast::PolicyID::from_string("id")),
+                Extensions::all_available(),
+            )
             .expect("Expected EST -> AST conversion to succeed");
         assert_eq!(
             ToString::to_string(&ast.non_scope_constraints().unwrap()),
@@ -4810,7 +5059,8 @@ mod test {
 
 #[cfg(test)]
 mod issue_891 {
-    use crate::est;
+    use crate::est::{FromJsonError, Policy};
+    use crate::extensions::Extensions;
     use cool_asserts::assert_matches;
     use serde_json::json;
 
@@ -4834,8 +5084,10 @@ mod issue_891 {
     #[test]
     fn invalid_extension_func() {
         let src = est_json_with_body(&json!( { "ow4": [ { "Var": "principal" } ] }));
-        assert_matches!(serde_json::from_value::<est::Policy>(src), Err(e) => {
-            assert!(e.to_string().starts_with("unknown variant `ow4`, expected one of `Value`, `Var`, "), "e was: {e}");
+        let est: Policy = serde_json::from_value(src).unwrap();
+        assert_matches!(est.try_into_ast_policy(None, Extensions::all_available()), Err(e) => {
+            assert_matches!(&e, FromJsonError::UnknownExtensionFunction(_));
+            assert_eq!(e.to_string(), "invalid extension function: `ow4`");
         });
 
         let src = est_json_with_body(&json!(
@@ -4851,8 +5103,10 @@ mod issue_891 {
                 }
             }
         ));
-        assert_matches!(serde_json::from_value::<est::Policy>(src), Err(e) => {
-            assert!(e.to_string().starts_with("unknown variant `ownerOrEqual`, expected one of `Value`, `Var`, "), "e was: {e}");
+        let est: Policy = serde_json::from_value(src).unwrap();
+        assert_matches!(est.try_into_ast_policy(None, Extensions::all_available()), Err(e) => {
+            assert_matches!(&e, FromJsonError::UnknownExtensionFunction(_));
+            assert_eq!(e.to_string(), "invalid extension function: `ownerOrEqual`");
         });
 
         let src = est_json_with_body(&json!(
@@ -4867,8 +5121,10 @@ mod issue_891 {
                 }
             }
         ));
-        assert_matches!(serde_json::from_value::<est::Policy>(src), Err(e) => {
-            assert!(e.to_string().starts_with("unknown variant `resorThanOrEqual`, expected one of `Value`, `Var`, "), "e was: {e}");
+        let est: Policy = serde_json::from_value(src).unwrap();
+        assert_matches!(est.try_into_ast_policy(None, Extensions::all_available()), Err(e) => {
+            assert_matches!(&e, FromJsonError::UnknownExtensionFunction(_));
+            assert_eq!(e.to_string(), "invalid extension function: `resorThanOrEqual`");
         });
     }
 }
@@ -4877,6 +5133,7 @@ mod issue_891 {
 mod issue_925 {
     use crate::{
         est,
+        extensions::Extensions,
         test_utils::{expect_err, ExpectedErrorMessageBuilder},
     };
     use cool_asserts::assert_matches;
@@ -4905,7 +5162,7 @@ mod issue_925 {
         );
         let est: est::Policy = serde_json::from_value(src.clone()).unwrap();
         assert_matches!(
-            est.try_into_ast_policy(None),
+            est.try_into_ast_policy(None, Extensions::all_available()),
             Err(e) => {
                 expect_err(
                     &src,
@@ -4938,7 +5195,7 @@ mod issue_925 {
         );
         let est: est::Policy = serde_json::from_value(src.clone()).unwrap();
         assert_matches!(
-            est.try_into_ast_policy(None),
+            est.try_into_ast_policy(None, Extensions::all_available()),
             Err(e) => {
                 expect_err(
                     &src,
@@ -4977,7 +5234,7 @@ mod issue_925 {
         );
         let est: est::Policy = serde_json::from_value(src.clone()).unwrap();
         assert_matches!(
-            est.try_into_ast_policy(None),
+            est.try_into_ast_policy(None, Extensions::all_available()),
             Err(e) => {
                 expect_err(
                     &src,
@@ -5083,7 +5340,7 @@ mod issue_994 {
 #[cfg(feature = "partial-eval")]
 #[cfg(test)]
 mod issue_1061 {
-    use crate::{est, parser};
+    use crate::{est, extensions::Extensions, parser};
     use serde_json::json;
 
     #[test]
@@ -5115,7 +5372,7 @@ mod issue_1061 {
         let est =
             serde_json::from_value::<est::Policy>(src).expect("Failed to deserialize policy JSON");
         let ast_from_est = est
-            .try_into_ast_policy(None)
+            .try_into_ast_policy(None, Extensions::all_available())
             .expect("Failed to convert EST to AST");
         let ast_from_cedar = parser::parse_policy_or_template(None, &ast_from_est.to_string())
             .expect("Failed to parse policy template");
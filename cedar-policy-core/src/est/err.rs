@@ -80,6 +80,11 @@ pub enum FromJsonError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidActionType(#[from] parse_errors::InvalidActionType),
+    /// Returned when the declared value slots of a template don't match the
+    /// value slots it uses
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ValueSlotDeclaration(#[from] ast::ValueSlotDeclarationError),
     /// Returned when we have an error node in an AST - this is not supported
     #[cfg(feature = "tolerant-ast")]
     #[error("AST error node")]
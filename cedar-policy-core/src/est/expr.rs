@@ -89,21 +89,20 @@ impl<'de> Deserialize<'de> for Expr {
                         return Err(serde::de::Error::custom(format!("JSON object representing an `Expr` should have only one key, but found two keys: `{k}` and `{k2}`")));
                     }
                 };
-                if ExtStyles::is_known_extension_func_str(&k) {
-                    // `k` is the name of an extension function or method. We assume that
-                    // no such keys are valid keys for `ExprNoExt`, so we must parse as an
-                    // `ExtFuncCall`.
-                    let obj = serde_json::json!({ k: v });
-                    let extfunccall =
-                        serde_json::from_value(obj).map_err(serde::de::Error::custom)?;
-                    Ok(Expr::ExtFuncCall(extfunccall))
-                } else {
-                    // not a valid extension function or method, so we expect it
-                    // to work for `ExprNoExt`.
-                    let obj = serde_json::json!({ k: v });
+                let is_expr_no_ext = is_expr_no_ext_key(&k);
+                let obj = serde_json::json!({ k: v });
+                if is_expr_no_ext {
                     let exprnoext =
                         serde_json::from_value(obj).map_err(serde::de::Error::custom)?;
                     Ok(Expr::ExprNoExt(exprnoext))
+                } else {
+                    // `k` is not a key of `ExprNoExt`, so it must be the name of an
+                    // extension function or method. Which functions exist depends on
+                    // the extensions the `Expr` is converted with, so unknown
+                    // functions are only reported by `Expr::try_into_ast()`.
+                    let extfunccall =
+                        serde_json::from_value(obj).map_err(serde::de::Error::custom)?;
+                    Ok(Expr::ExtFuncCall(extfunccall))
                 }
             }
         }
@@ -112,6 +111,49 @@ impl<'de> Deserialize<'de> for Expr {
     }
 }
 
+/// If `k` is the key of a variant of [`ExprNoExt`] in its JSON form. Keys of
+/// operators which are behind features are included even with the feature
+/// off, so that they are reported as unsupported rather than as unknown
+/// extension functions.
+fn is_expr_no_ext_key(k: &str) -> bool {
+    matches!(
+        k,
+        "Value"
+            | "Var"
+            | "Slot"
+            | "!"
+            | "neg"
+            | "=="
+            | "!="
+            | "in"
+            | "<"
+            | "<="
+            | ">"
+            | ">="
+            | "&&"
+            | "||"
+            | "+"
+            | "-"
+            | "*"
+            | "contains"
+            | "containsAll"
+            | "containsAny"
+            | "isEmpty"
+            | "getTag"
+            | "hasTag"
+            | "get"
+            | "containsKey"
+            | "."
+            | "has"
+            | "like"
+            | "is"
+            | "if-then-else"
+            | "Set"
+            | "Record"
+            | "Error"
+    )
+}
+
 /// Represent an element of a pattern literal
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
@@ -349,6 +391,24 @@ pub enum ExprNoExt {
         /// Right-hand argument (inside the `()`)
         right: Arc<Expr>,
     },
+    /// `get()`
+    #[cfg(feature = "maps")]
+    #[serde(rename = "get")]
+    GetKey {
+        /// Left-hand argument (receiver)
+        left: Arc<Expr>,
+        /// Right-hand argument (inside the `()`)
+        right: Arc<Expr>,
+    },
+    /// `containsKey()`
+    #[cfg(feature = "maps")]
+    #[serde(rename = "containsKey")]
+    ContainsKey {
+        /// Left-hand argument (receiver)
+        left: Arc<Expr>,
+        /// Right-hand argument (inside the `()`)
+        right: Arc<Expr>,
+    },
     /// Get-attribute
     #[serde(rename = ".")]
     GetAttr {
@@ -666,6 +726,24 @@ impl ExprBuilder for Builder {
         })
     }
 
+    /// `left.get(right)`
+    #[cfg(feature = "maps")]
+    fn get_key(self, expr: Expr, key: Expr) -> Expr {
+        Expr::ExprNoExt(ExprNoExt::GetKey {
+            left: Arc::new(expr),
+            right: Arc::new(key),
+        })
+    }
+
+    /// `left.containsKey(right)`
+    #[cfg(feature = "maps")]
+    fn contains_key(self, expr: Expr, key: Expr) -> Expr {
+        Expr::ExprNoExt(ExprNoExt::ContainsKey {
+            left: Arc::new(expr),
+            right: Arc::new(key),
+        })
+    }
+
     /// `left.attr`
     fn get_attr(self, expr: Expr, attr: SmolStr) -> Expr {
         Expr::ExprNoExt(ExprNoExt::GetAttr {
@@ -873,6 +951,18 @@ impl Expr {
                     left: Arc::new(Arc::unwrap_or_clone(left).sub_entity_literals(mapping)?),
                     right: Arc::new(Arc::unwrap_or_clone(right).sub_entity_literals(mapping)?),
                 })),
+                #[cfg(feature = "maps")]
+                ExprNoExt::GetKey { left, right } => Ok(Expr::ExprNoExt(ExprNoExt::GetKey {
+                    left: Arc::new(Arc::unwrap_or_clone(left).sub_entity_literals(mapping)?),
+                    right: Arc::new(Arc::unwrap_or_clone(right).sub_entity_literals(mapping)?),
+                })),
+                #[cfg(feature = "maps")]
+                ExprNoExt::ContainsKey { left, right } => {
+                    Ok(Expr::ExprNoExt(ExprNoExt::ContainsKey {
+                        left: Arc::new(Arc::unwrap_or_clone(left).sub_entity_literals(mapping)?),
+                        right: Arc::new(Arc::unwrap_or_clone(right).sub_entity_literals(mapping)?),
+                    }))
+                }
                 ExprNoExt::GetAttr { left, attr } => Ok(Expr::ExprNoExt(ExprNoExt::GetAttr {
                     left: Arc::new(Arc::unwrap_or_clone(left).sub_entity_literals(mapping)?),
                     attr,
@@ -1022,6 +1112,11 @@ impl Expr {
                         stack.push((left, child_depth));
                         stack.push((right, child_depth));
                     }
+                    #[cfg(feature = "maps")]
+                    ExprNoExt::GetKey { left, right } | ExprNoExt::ContainsKey { left, right } => {
+                        stack.push((left, child_depth));
+                        stack.push((right, child_depth));
+                    }
                     ExprNoExt::GetAttr { left, .. } => {
                         stack.push((left, child_depth));
                     }
@@ -1186,6 +1281,16 @@ impl Expr {
                 Arc::unwrap_or_clone(left).try_into_expr::<B>()?,
                 Arc::unwrap_or_clone(right).try_into_expr::<B>()?,
             )),
+            #[cfg(feature = "maps")]
+            Expr::ExprNoExt(ExprNoExt::GetKey { left, right }) => Ok(builder.get_key(
+                Arc::unwrap_or_clone(left).try_into_expr::<B>()?,
+                Arc::unwrap_or_clone(right).try_into_expr::<B>()?,
+            )),
+            #[cfg(feature = "maps")]
+            Expr::ExprNoExt(ExprNoExt::ContainsKey { left, right }) => Ok(builder.contains_key(
+                Arc::unwrap_or_clone(left).try_into_expr::<B>()?,
+                Arc::unwrap_or_clone(right).try_into_expr::<B>()?,
+            )),
             Expr::ExprNoExt(ExprNoExt::GetAttr { left, attr }) => {
                 Ok(builder.get_attr(Arc::unwrap_or_clone(left).try_into_expr::<B>()?, attr))
             }
@@ -1269,7 +1374,12 @@ impl Expr {
     /// Attempt to convert this `est::Expr` into an `ast::Expr`
     ///
     /// `id`: the ID of the policy this `Expr` belongs to, used only for reporting errors
-    pub fn try_into_ast(self, id: &ast::PolicyID) -> Result<ast::Expr, FromJsonError> {
+    /// `extensions`: the extensions whose functions the `Expr` may call
+    pub fn try_into_ast(
+        self,
+        id: &ast::PolicyID,
+        extensions: &Extensions<'_>,
+    ) -> Result<ast::Expr, FromJsonError> {
         match self {
             Expr::ExprNoExt(ExprNoExt::Value(jsonvalue)) => jsonvalue
                 .into_expr(&|| JsonDeserializationErrorContext::Policy { id: id.clone() })
@@ -1277,97 +1387,110 @@ impl Expr {
                 .map_err(Into::into),
             Expr::ExprNoExt(ExprNoExt::Var(var)) => Ok(ast::Expr::var(var)),
             Expr::ExprNoExt(ExprNoExt::Slot(slot)) => Ok(ast::Expr::slot(slot)),
-            Expr::ExprNoExt(ExprNoExt::Not { arg }) => {
-                Ok(ast::Expr::not(Arc::unwrap_or_clone(arg).try_into_ast(id)?))
-            }
-            Expr::ExprNoExt(ExprNoExt::Neg { arg }) => {
-                Ok(ast::Expr::neg(Arc::unwrap_or_clone(arg).try_into_ast(id)?))
-            }
+            Expr::ExprNoExt(ExprNoExt::Not { arg }) => Ok(ast::Expr::not(
+                Arc::unwrap_or_clone(arg).try_into_ast(id, extensions)?,
+            )),
+            Expr::ExprNoExt(ExprNoExt::Neg { arg }) => Ok(ast::Expr::neg(
+                Arc::unwrap_or_clone(arg).try_into_ast(id, extensions)?,
+            )),
             Expr::ExprNoExt(ExprNoExt::Eq { left, right }) => Ok(ast::Expr::is_eq(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::NotEq { left, right }) => Ok(ast::Expr::noteq(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::In { left, right }) => Ok(ast::Expr::is_in(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Less { left, right }) => Ok(ast::Expr::less(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::LessEq { left, right }) => Ok(ast::Expr::lesseq(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Greater { left, right }) => Ok(ast::Expr::greater(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::GreaterEq { left, right }) => Ok(ast::Expr::greatereq(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::And { left, right }) => Ok(ast::Expr::and(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Or { left, right }) => Ok(ast::Expr::or(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Add { left, right }) => Ok(ast::Expr::add(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Sub { left, right }) => Ok(ast::Expr::sub(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Mul { left, right }) => Ok(ast::Expr::mul(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Contains { left, right }) => Ok(ast::Expr::contains(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::ContainsAll { left, right }) => Ok(ast::Expr::contains_all(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::ContainsAny { left, right }) => Ok(ast::Expr::contains_any(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::IsEmpty { arg }) => Ok(ast::Expr::is_empty(
-                Arc::unwrap_or_clone(arg).try_into_ast(id)?,
+                Arc::unwrap_or_clone(arg).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::GetTag { left, right }) => Ok(ast::Expr::get_tag(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::HasTag { left, right }) => Ok(ast::Expr::has_tag(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
-                Arc::unwrap_or_clone(right).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
+            )),
+            #[cfg(feature = "maps")]
+            Expr::ExprNoExt(ExprNoExt::GetKey { left, right }) => Ok(ast::Expr::get_key(
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
+            )),
+            #[cfg(feature = "maps")]
+            Expr::ExprNoExt(ExprNoExt::ContainsKey { left, right }) => Ok(ast::Expr::contains_key(
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(right).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::GetAttr { left, attr }) => Ok(ast::Expr::get_attr(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
                 attr,
             )),
             Expr::ExprNoExt(ExprNoExt::HasAttr(repr)) => match repr {
                 HasAttrRepr::Simple { left, attr } => Ok(ast::Expr::has_attr(
-                    Arc::unwrap_or_clone(left).try_into_ast(id)?,
+                    Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
                     attr,
                 )),
                 HasAttrRepr::Extended { left, attr } => Ok(ast::ExprBuilder::new()
-                    .extended_has_attr(Arc::unwrap_or_clone(left).try_into_ast(id)?, attr)),
+                    .extended_has_attr(
+                        Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
+                        attr,
+                    )),
             },
             Expr::ExprNoExt(ExprNoExt::Like { left, pattern }) => Ok(ast::Expr::like(
-                Arc::unwrap_or_clone(left).try_into_ast(id)?,
+                Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?,
                 crate::ast::Pattern::from(pattern.as_slice()),
             )),
             Expr::ExprNoExt(ExprNoExt::Is {
@@ -1377,12 +1500,13 @@ impl Expr {
             }) => ast::EntityType::from_normalized_str(entity_type.as_str())
                 .map_err(FromJsonError::InvalidEntityType)
                 .and_then(|entity_type_name| {
-                    let left: ast::Expr = Arc::unwrap_or_clone(left).try_into_ast(id)?;
+                    let left: ast::Expr =
+                        Arc::unwrap_or_clone(left).try_into_ast(id, extensions)?;
                     match in_expr {
                         Some(in_expr) => Ok(ast::ExprBuilder::new().is_in_entity_type(
                             left,
                             entity_type_name,
-                            Arc::unwrap_or_clone(in_expr).try_into_ast(id)?,
+                            Arc::unwrap_or_clone(in_expr).try_into_ast(id, extensions)?,
                         )),
                         None => Ok(ast::ExprBuilder::new().is_entity_type(left, entity_type_name)),
                     }
@@ -1392,14 +1516,14 @@ impl Expr {
                 then_expr,
                 else_expr,
             }) => Ok(ast::Expr::ite(
-                Arc::unwrap_or_clone(cond_expr).try_into_ast(id)?,
-                Arc::unwrap_or_clone(then_expr).try_into_ast(id)?,
-                Arc::unwrap_or_clone(else_expr).try_into_ast(id)?,
+                Arc::unwrap_or_clone(cond_expr).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(then_expr).try_into_ast(id, extensions)?,
+                Arc::unwrap_or_clone(else_expr).try_into_ast(id, extensions)?,
             )),
             Expr::ExprNoExt(ExprNoExt::Set(elements)) => Ok(ast::Expr::set(
                 elements
                     .into_iter()
-                    .map(|el| el.try_into_ast(id))
+                    .map(|el| el.try_into_ast(id, extensions))
                     .collect::<Result<Vec<_>, FromJsonError>>()?,
             )),
             Expr::ExprNoExt(ExprNoExt::Record(map)) =>
@@ -1410,7 +1534,7 @@ impl Expr {
                 )]
                 Ok(ast::Expr::record(
                     map.into_iter()
-                        .map(|(k, v)| Ok((k, v.try_into_ast(id)?)))
+                        .map(|(k, v)| Ok((k, v.try_into_ast(id, extensions)?)))
                         .collect::<Result<HashMap<SmolStr, _>, FromJsonError>>()?,
                 )
                 .expect("can't have duplicate keys here because the input was already a HashMap"))
@@ -1420,11 +1544,11 @@ impl Expr {
                 let fn_name = Name::from_normalized_str(&fn_name).map_err(|errs| {
                     JsonDeserializationError::parse_escape(EscapeKind::Extension, fn_name, errs)
                 })?;
-                if ExtStyles::is_known_extension_func_name(&fn_name) {
+                if extensions.styles().is_known_extension_func_name(&fn_name) {
                     Ok(ast::Expr::call_extension_fn(
                         fn_name,
                         args.into_iter()
-                            .map(|arg| arg.try_into_ast(id))
+                            .map(|arg| arg.try_into_ast(id, extensions))
                             .collect::<Result<_, _>>()?,
                     ))
                 } else {
@@ -1458,7 +1582,17 @@ impl From<ast::SlotId> for Expr {
 impl TryFrom<&Node<Option<cst::Expr>>> for Expr {
     type Error = ParseErrors;
     fn try_from(e: &Node<Option<cst::Expr>>) -> Result<Expr, ParseErrors> {
-        e.to_expr::<Builder>()
+        Self::try_from_cst(e, Extensions::all_available())
+    }
+}
+
+impl Expr {
+    /// Convert a CST expression which may call the functions of `extensions`
+    pub(crate) fn try_from_cst(
+        e: &Node<Option<cst::Expr>>,
+        extensions: &Extensions<'_>,
+    ) -> Result<Expr, ParseErrors> {
+        e.to_expr::<Builder>(extensions)
     }
 }
 
@@ -1473,17 +1607,50 @@ impl std::fmt::Display for Expr {
 
 impl BoundedDisplay for Expr {
     fn fmt(&self, f: &mut impl std::fmt::Write, n: Option<usize>) -> std::fmt::Result {
+        self.fmt_with_styles(f, n, Extensions::all_available().styles())
+    }
+}
+
+impl Expr {
+    /// Like `BoundedDisplay::fmt()`, but calls extension functions with the
+    /// call styles in `styles`
+    fn fmt_with_styles(
+        &self,
+        f: &mut impl std::fmt::Write,
+        n: Option<usize>,
+        styles: &ExtStyles,
+    ) -> std::fmt::Result {
         match self {
-            Self::ExprNoExt(e) => BoundedDisplay::fmt(e, f, n),
-            Self::ExtFuncCall(e) => BoundedDisplay::fmt(e, f, n),
+            Self::ExprNoExt(e) => e.fmt_with_styles(f, n, styles),
+            Self::ExtFuncCall(e) => e.fmt_with_styles(f, n, styles),
         }
     }
+
+    /// Display this `Expr` in the Cedar syntax, calling the extension functions
+    /// of `extensions` with their call styles. Functions `extensions` does not
+    /// know of are displayed as function-style calls.
+    pub fn display_with_extensions<'a>(
+        &'a self,
+        extensions: &'a Extensions<'_>,
+    ) -> impl std::fmt::Display + 'a {
+        WithStyles(self, extensions.styles())
+    }
+}
+
+/// Displays an [`Expr`] with the given call styles
+pub(crate) struct WithStyles<'a>(pub(crate) &'a Expr, pub(crate) &'a ExtStyles);
+
+impl std::fmt::Display for WithStyles<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt_with_styles(f, None, self.1)
+    }
 }
 
 fn display_cedarvaluejson(
     f: &mut impl std::fmt::Write,
     v: &CedarValueJson,
     n: Option<usize>,
+    styles: &ExtStyles,
 ) -> std::fmt::Result {
     match v {
         // Add parentheses around negative numeric literals otherwise
@@ -1503,22 +1670,16 @@ fn display_cedarvaluejson(
             __extn: FnAndArgs::Single { ext_fn, arg },
         } => {
             // search for the name and callstyle
-            let style = Extensions::all_available().all_funcs().find_map(|f| {
-                if &f.name().to_smolstr() == ext_fn {
-                    Some(f.style())
-                } else {
-                    None
-                }
-            });
+            let style = styles.style(ext_fn);
             match style {
                 Some(ast::CallStyle::MethodStyle) => {
-                    display_cedarvaluejson(f, arg, n)?;
+                    display_cedarvaluejson(f, arg, n, styles)?;
                     write!(f, ".{ext_fn}()")?;
                     Ok(())
                 }
                 Some(ast::CallStyle::FunctionStyle) | None => {
                     write!(f, "{ext_fn}(")?;
-                    display_cedarvaluejson(f, arg, n)?;
+                    display_cedarvaluejson(f, arg, n, styles)?;
                     write!(f, ")")?;
                     Ok(())
                 }
@@ -1528,20 +1689,14 @@ fn display_cedarvaluejson(
             __extn: FnAndArgs::Multi { ext_fn, args },
         } => {
             // search for the name and callstyle
-            let style = Extensions::all_available().all_funcs().find_map(|f| {
-                if &f.name().to_smolstr() == ext_fn {
-                    Some(f.style())
-                } else {
-                    None
-                }
-            });
+            let style = styles.style(ext_fn);
             match style {
                 Some(ast::CallStyle::MethodStyle) => {
                     #[expect(
                         clippy::indexing_slicing,
                         reason = "method-style calls must have more than one argument"
                     )]
-                    display_cedarvaluejson(f, &args[0], n)?;
+                    display_cedarvaluejson(f, &args[0], n, styles)?;
                     write!(f, ".{ext_fn}(")?;
                     #[expect(
                         clippy::indexing_slicing,
@@ -1551,10 +1706,10 @@ fn display_cedarvaluejson(
                         [] => {}
                         [args @ .., last] => {
                             for arg in args {
-                                display_cedarvaluejson(f, arg, n)?;
+                                display_cedarvaluejson(f, arg, n, styles)?;
                                 write!(f, ", ")?;
                             }
-                            display_cedarvaluejson(f, last, n)?;
+                            display_cedarvaluejson(f, last, n, styles)?;
                         }
                     }
                     write!(f, ")")?;
@@ -1566,10 +1721,10 @@ fn display_cedarvaluejson(
                         [] => {}
                         [args @ .., last] => {
                             for arg in args {
-                                display_cedarvaluejson(f, arg, n)?;
+                                display_cedarvaluejson(f, arg, n, styles)?;
                                 write!(f, ", ")?;
                             }
-                            display_cedarvaluejson(f, last, n)?;
+                            display_cedarvaluejson(f, last, n, styles)?;
                         }
                     }
                     write!(f, ")")?;
@@ -1583,7 +1738,7 @@ fn display_cedarvaluejson(
                     // truncate to n elements
                     write!(f, "[")?;
                     for val in v.iter().take(n) {
-                        display_cedarvaluejson(f, val, Some(n))?;
+                        display_cedarvaluejson(f, val, Some(n), styles)?;
                         write!(f, ", ")?;
                     }
                     write!(f, "..]")?;
@@ -1593,7 +1748,7 @@ fn display_cedarvaluejson(
                     // no truncation
                     write!(f, "[")?;
                     for (i, val) in v.iter().enumerate() {
-                        display_cedarvaluejson(f, val, n)?;
+                        display_cedarvaluejson(f, val, n, styles)?;
                         if i < v.len() - 1 {
                             write!(f, ", ")?;
                         }
@@ -1610,7 +1765,7 @@ fn display_cedarvaluejson(
                     write!(f, "{{")?;
                     for (k, v) in r.iter().take(n) {
                         write!(f, "\"{}\": ", k.escape_debug())?;
-                        display_cedarvaluejson(f, v, Some(n))?;
+                        display_cedarvaluejson(f, v, Some(n), styles)?;
                         write!(f, ", ")?;
                     }
                     write!(f, "..}}")?;
@@ -1621,7 +1776,7 @@ fn display_cedarvaluejson(
                     write!(f, "{{")?;
                     for (i, (k, v)) in r.iter().enumerate() {
                         write!(f, "\"{}\": ", k.escape_debug())?;
-                        display_cedarvaluejson(f, v, n)?;
+                        display_cedarvaluejson(f, v, n, styles)?;
                         if i < r.len() - 1 {
                             write!(f, ", ")?;
                         }
@@ -1646,13 +1801,26 @@ impl std::fmt::Display for ExprNoExt {
 
 impl BoundedDisplay for ExprNoExt {
     fn fmt(&self, f: &mut impl std::fmt::Write, n: Option<usize>) -> std::fmt::Result {
+        self.fmt_with_styles(f, n, Extensions::all_available().styles())
+    }
+}
+
+impl ExprNoExt {
+    /// Like `BoundedDisplay::fmt()`, but calls extension functions with the
+    /// call styles in `styles`
+    fn fmt_with_styles(
+        &self,
+        f: &mut impl std::fmt::Write,
+        n: Option<usize>,
+        styles: &ExtStyles,
+    ) -> std::fmt::Result {
         match &self {
-            ExprNoExt::Value(v) => display_cedarvaluejson(f, v, n),
+            ExprNoExt::Value(v) => display_cedarvaluejson(f, v, n, styles),
             ExprNoExt::Var(v) => write!(f, "{v}"),
             ExprNoExt::Slot(id) => write!(f, "{id}"),
             ExprNoExt::Not { arg } => {
                 write!(f, "!")?;
-                maybe_with_parens(f, arg, n)
+                maybe_with_parens(f, arg, n, styles)
             }
             ExprNoExt::Neg { arg } => {
                 // Always add parentheses instead of calling
@@ -1660,94 +1828,104 @@ impl BoundedDisplay for ExprNoExt {
                 // This makes sure that we always get a negation operation back
                 // (as opposed to e.g., a negative number) when parsing the
                 // printed form, thus preserving the round-tripping property.
-                write!(f, "-({arg})")
+                write!(f, "-({})", WithStyles(arg, styles))
             }
             ExprNoExt::Eq { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " == ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::NotEq { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " != ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::In { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " in ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Less { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " < ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::LessEq { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " <= ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Greater { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " > ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::GreaterEq { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " >= ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::And { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " && ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Or { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " || ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Add { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " + ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Sub { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " - ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Mul { left, right } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " * ")?;
-                maybe_with_parens(f, right, n)
+                maybe_with_parens(f, right, n, styles)
             }
             ExprNoExt::Contains { left, right } => {
-                maybe_with_parens(f, left, n)?;
-                write!(f, ".contains({right})")
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".contains({})", WithStyles(right, styles))
             }
             ExprNoExt::ContainsAll { left, right } => {
-                maybe_with_parens(f, left, n)?;
-                write!(f, ".containsAll({right})")
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".containsAll({})", WithStyles(right, styles))
             }
             ExprNoExt::ContainsAny { left, right } => {
-                maybe_with_parens(f, left, n)?;
-                write!(f, ".containsAny({right})")
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".containsAny({})", WithStyles(right, styles))
             }
             ExprNoExt::IsEmpty { arg } => {
-                maybe_with_parens(f, arg, n)?;
+                maybe_with_parens(f, arg, n, styles)?;
                 write!(f, ".isEmpty()")
             }
             ExprNoExt::GetTag { left, right } => {
-                maybe_with_parens(f, left, n)?;
-                write!(f, ".getTag({right})")
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".getTag({})", WithStyles(right, styles))
             }
             ExprNoExt::HasTag { left, right } => {
-                maybe_with_parens(f, left, n)?;
-                write!(f, ".hasTag({right})")
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".hasTag({})", WithStyles(right, styles))
+            }
+            #[cfg(feature = "maps")]
+            ExprNoExt::GetKey { left, right } => {
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".get({})", WithStyles(right, styles))
+            }
+            #[cfg(feature = "maps")]
+            ExprNoExt::ContainsKey { left, right } => {
+                maybe_with_parens(f, left, n, styles)?;
+                write!(f, ".containsKey({})", WithStyles(right, styles))
             }
             ExprNoExt::GetAttr { left, attr } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 if is_normalized_ident(attr) {
                     write!(f, ".{}", attr)
                 } else {
@@ -1756,7 +1934,7 @@ impl BoundedDisplay for ExprNoExt {
             }
             ExprNoExt::HasAttr(repr) => match repr {
                 HasAttrRepr::Simple { left, attr } => {
-                    maybe_with_parens(f, left, n)?;
+                    maybe_with_parens(f, left, n, styles)?;
                     if is_normalized_ident(attr) {
                         write!(f, " has {}", attr)
                     } else {
@@ -1764,7 +1942,7 @@ impl BoundedDisplay for ExprNoExt {
                     }
                 }
                 HasAttrRepr::Extended { left, attr } => {
-                    maybe_with_parens(f, left, n)?;
+                    maybe_with_parens(f, left, n, styles)?;
                     if is_normalized_ident(&attr.head) {
                         write!(f, " has {}", attr.head)?;
                     } else {
@@ -1783,7 +1961,7 @@ impl BoundedDisplay for ExprNoExt {
                 }
             },
             ExprNoExt::Like { left, pattern } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(
                     f,
                     " like \"{}\"",
@@ -1795,12 +1973,12 @@ impl BoundedDisplay for ExprNoExt {
                 entity_type,
                 in_expr,
             } => {
-                maybe_with_parens(f, left, n)?;
+                maybe_with_parens(f, left, n, styles)?;
                 write!(f, " is {entity_type}")?;
                 match in_expr {
                     Some(in_expr) => {
                         write!(f, " in ")?;
-                        maybe_with_parens(f, in_expr, n)
+                        maybe_with_parens(f, in_expr, n, styles)
                     }
                     None => Ok(()),
                 }
@@ -1811,11 +1989,11 @@ impl BoundedDisplay for ExprNoExt {
                 else_expr,
             } => {
                 write!(f, "if ")?;
-                maybe_with_parens(f, cond_expr, n)?;
+                maybe_with_parens(f, cond_expr, n, styles)?;
                 write!(f, " then ")?;
-                maybe_with_parens(f, then_expr, n)?;
+                maybe_with_parens(f, then_expr, n, styles)?;
                 write!(f, " else ")?;
-                maybe_with_parens(f, else_expr, n)
+                maybe_with_parens(f, else_expr, n, styles)
             }
             ExprNoExt::Set(v) => {
                 match n {
@@ -1823,7 +2001,7 @@ impl BoundedDisplay for ExprNoExt {
                         // truncate to n elements
                         write!(f, "[")?;
                         for element in v.iter().take(n) {
-                            BoundedDisplay::fmt(element, f, Some(n))?;
+                            element.fmt_with_styles(f, Some(n), styles)?;
                             write!(f, ", ")?;
                         }
                         write!(f, "..]")?;
@@ -1833,7 +2011,7 @@ impl BoundedDisplay for ExprNoExt {
                         // no truncation
                         write!(f, "[")?;
                         for (i, element) in v.iter().enumerate() {
-                            BoundedDisplay::fmt(element, f, n)?;
+                            element.fmt_with_styles(f, n, styles)?;
                             if i < v.len() - 1 {
                                 write!(f, ", ")?;
                             }
@@ -1854,7 +2032,7 @@ impl BoundedDisplay for ExprNoExt {
                             } else {
                                 write!(f, "\"{}\": ", k.escape_debug())?;
                             }
-                            BoundedDisplay::fmt(v, f, Some(n))?;
+                            v.fmt_with_styles(f, Some(n), styles)?;
                             write!(f, ", ")?;
                         }
                         write!(f, "..}}")?;
@@ -1869,7 +2047,7 @@ impl BoundedDisplay for ExprNoExt {
                             } else {
                                 write!(f, "\"{}\": ", k.escape_debug())?;
                             }
-                            BoundedDisplay::fmt(v, f, n)?;
+                            v.fmt_with_styles(f, n, styles)?;
                             if i < m.len() - 1 {
                                 write!(f, ", ")?;
                             }
@@ -1896,23 +2074,40 @@ impl std::fmt::Display for ExtFuncCall {
 
 impl BoundedDisplay for ExtFuncCall {
     fn fmt(&self, f: &mut impl std::fmt::Write, n: Option<usize>) -> std::fmt::Result {
+        self.fmt_with_styles(f, n, Extensions::all_available().styles())
+    }
+}
+
+impl ExtFuncCall {
+    /// Like `BoundedDisplay::fmt()`, but calls extension functions with the
+    /// call styles in `styles`
+    fn fmt_with_styles(
+        &self,
+        f: &mut impl std::fmt::Write,
+        n: Option<usize>,
+        styles: &ExtStyles,
+    ) -> std::fmt::Result {
         #[expect(clippy::unwrap_used, reason = "safe due to INVARIANT on `ExtFuncCall`")]
         let (fn_name, args) = self.try_components().unwrap();
         // search for the name and callstyle
-        let style = Extensions::all_available().all_funcs().find_map(|ext_fn| {
-            if &ext_fn.name().to_smolstr() == fn_name {
-                Some(ext_fn.style())
-            } else {
-                None
-            }
-        });
+        let style = styles.style(fn_name);
         match (style, args) {
             (Some(ast::CallStyle::MethodStyle), [receiver, rest @ ..]) => {
-                maybe_with_parens(f, receiver, n)?;
-                write!(f, ".{}({})", fn_name, rest.iter().join(", "))
+                maybe_with_parens(f, receiver, n, styles)?;
+                write!(
+                    f,
+                    ".{}({})",
+                    fn_name,
+                    rest.iter().map(|arg| WithStyles(arg, styles)).join(", ")
+                )
             }
             (_, _) => {
-                write!(f, "{}({})", fn_name, args.iter().join(", "))
+                write!(
+                    f,
+                    "{}({})",
+                    fn_name,
+                    args.iter().map(|arg| WithStyles(arg, styles)).join(", ")
+                )
             }
         }
     }
@@ -1927,13 +2122,14 @@ fn maybe_with_parens(
     f: &mut impl std::fmt::Write,
     expr: &Expr,
     n: Option<usize>,
+    styles: &ExtStyles,
 ) -> std::fmt::Result {
     match expr {
         Expr::ExprNoExt(ExprNoExt::Set(_)) |
         Expr::ExprNoExt(ExprNoExt::Record(_)) |
         Expr::ExprNoExt(ExprNoExt::Value(_)) |
         Expr::ExprNoExt(ExprNoExt::Var(_)) |
-        Expr::ExprNoExt(ExprNoExt::Slot(_)) => BoundedDisplay::fmt(expr, f, n),
+        Expr::ExprNoExt(ExprNoExt::Slot(_)) => expr.fmt_with_styles(f, n, styles),
 
         // we want parens here because things like parse((!x).y)
         // would be printed into !x.y which has a different meaning
@@ -1966,14 +2162,21 @@ fn maybe_with_parens(
         Expr::ExprNoExt(ExprNoExt::If { .. }) |
         Expr::ExtFuncCall { .. } => {
             write!(f, "(")?;
-            BoundedDisplay::fmt(expr, f, n)?;
+            expr.fmt_with_styles(f, n, styles)?;
+            write!(f, ")")?;
+            Ok(())
+        },
+        #[cfg(feature = "maps")]
+        Expr::ExprNoExt(ExprNoExt::GetKey { .. } | ExprNoExt::ContainsKey { .. }) => {
+            write!(f, "(")?;
+            expr.fmt_with_styles(f, n, styles)?;
             write!(f, ")")?;
             Ok(())
         },
         #[cfg(feature = "tolerant-ast")]
         Expr::ExprNoExt(ExprNoExt::Error { .. }) => {
             write!(f, "(")?;
-            BoundedDisplay::fmt(expr, f, n)?;
+            expr.fmt_with_styles(f, n, styles)?;
             write!(f, ")")?;
             Ok(())
         }
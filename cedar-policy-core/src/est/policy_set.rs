@@ -16,10 +16,11 @@
 
 use super::Clause;
 use super::Policy;
-use super::PolicySetFromJsonError;
+use super::{FromJsonError, PolicySetFromJsonError};
 use crate::ast::{self, EntityUID, PolicyID, SlotId};
-use crate::entities::json::err::JsonDeserializationErrorContext;
-use crate::entities::json::EntityUidJson;
+use crate::entities::json::err::{JsonDeserializationError, JsonDeserializationErrorContext};
+use crate::entities::json::{CedarValueJson, EntityUidJson};
+use crate::extensions::Extensions;
 use crate::jsonvalue::deserialize_linked_hash_map_no_duplicates;
 use crate::parser::cst::Policies;
 use crate::parser::err::ParseErrors;
@@ -48,18 +49,56 @@ impl PolicySet {
     /// Get the static or template-linked policy with the given id.
     /// Returns an `Option` rather than a `Result` because it is expected to be
     /// used in cases where the policy set is guaranteed to be well-formed
-    /// (e.g., after successful conversion to an `ast::PolicySet`)
-    pub fn get_policy(&self, id: &PolicyID) -> Option<Policy> {
+    /// (e.g., after successful conversion to an `ast::PolicySet`, with the same
+    /// `extensions`)
+    pub fn get_policy(&self, id: &PolicyID, extensions: &Extensions<'_>) -> Option<Policy> {
         let maybe_static_policy = self.static_policies.get(id).cloned();
 
         let maybe_link = self
             .template_links
             .iter()
             .filter_map(|link| {
-                if &link.new_id == id {
+                let declares_value_slots = || {
+                    self.get_template(&link.template_id)
+                        .is_some_and(|t| !t.value_slots.is_empty())
+                };
+                if &link.new_id == id && (!link.value_slots.is_empty() || declares_value_slots()) {
+                    // The values of value slots (including defaults) are
+                    // substituted in the AST
+                    self.get_template(&link.template_id).and_then(|template| {
+                        let template = template
+                            .try_into_ast_policy_or_template(
+                                Some(link.template_id.clone()),
+                                extensions,
+                            )
+                            .ok()?;
+                        let value_slots = link
+                            .value_slots
+                            .iter()
+                            .map(|(k, v)| {
+                                let v = v
+                                    .clone()
+                                    .into_expr(&|| JsonDeserializationErrorContext::TemplateLink)
+                                    .ok()?;
+                                Some((k.clone(), v))
+                            })
+                            .collect::<Option<_>>()?;
+                        ast::Template::link_with_value_slots(
+                            std::sync::Arc::new(template),
+                            link.new_id.clone(),
+                            link.values.clone(),
+                            value_slots,
+                        )
+                        .ok()
+                        .map(Policy::from)
+                    })
+                } else if &link.new_id == id {
                     self.get_template(&link.template_id).and_then(|template| {
-                        let unwrapped_est_vals: HashMap<SlotId, EntityUidJson> =
-                            link.values.iter().map(|(k, v)| (*k, v.into())).collect();
+                        let unwrapped_est_vals: HashMap<SlotId, EntityUidJson> = link
+                            .values
+                            .iter()
+                            .map(|(k, v)| (k.clone(), v.into()))
+                            .collect();
                         template.link(&unwrapped_est_vals).ok()
                     })
                 } else {
@@ -107,6 +146,11 @@ pub struct TemplateLink {
     /// Mapping between slots and entity uids
     #[serde_as(as = "serde_with::MapPreventDuplicates<_,EntityUidJson<TemplateLinkContext>>")]
     pub values: HashMap<SlotId, EntityUID>,
+    /// Mapping between value slots and their values
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde_as(as = "serde_with::MapPreventDuplicates<_,_>")]
+    pub value_slots: HashMap<SlotId, CedarValueJson>,
 }
 
 /// Statically set the deserialization error context to be deserialization of a template link
@@ -122,15 +166,26 @@ impl TryFrom<PolicySet> for ast::PolicySet {
     type Error = PolicySetFromJsonError;
 
     fn try_from(value: PolicySet) -> Result<Self, Self::Error> {
+        value.try_into_ast_policy_set(Extensions::all_available())
+    }
+}
+
+impl PolicySet {
+    /// Try to convert this policy set into an [`ast::PolicySet`]. The policies
+    /// may call the functions of `extensions`.
+    pub fn try_into_ast_policy_set(
+        self,
+        extensions: &Extensions<'_>,
+    ) -> Result<ast::PolicySet, PolicySetFromJsonError> {
         let mut ast_pset = ast::PolicySet::default();
 
-        for (id, policy) in value.static_policies {
-            let ast = policy.try_into_ast_policy(Some(id))?;
+        for (id, policy) in self.static_policies {
+            let ast = policy.try_into_ast_policy(Some(id), extensions)?;
             ast_pset.add(ast)?;
         }
 
-        for (id, policy) in value.templates {
-            let ast = policy.try_into_ast_policy_or_template(Some(id))?;
+        for (id, policy) in self.templates {
+            let ast = policy.try_into_ast_policy_or_template(Some(id), extensions)?;
             ast_pset.add_template(ast)?;
         }
 
@@ -138,9 +193,18 @@ impl TryFrom<PolicySet> for ast::PolicySet {
             template_id,
             new_id,
             values,
-        } in value.template_links
+            value_slots,
+        } in self.template_links
         {
-            ast_pset.link(template_id, new_id, values)?;
+            let value_slots = value_slots
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = v.into_expr(&|| JsonDeserializationErrorContext::TemplateLink)?;
+                    Ok((k, v))
+                })
+                .collect::<Result<_, JsonDeserializationError>>()
+                .map_err(FromJsonError::from)?;
+            ast_pset.link_with_value_slots(template_id, new_id, values, value_slots)?;
         }
 
         Ok(ast_pset)
@@ -17,13 +17,15 @@
 //! This module contains the Cedar evaluator.
 
 use crate::ast::*;
-use crate::entities::{Dereference, Entities};
+use crate::entities::conformance::typecheck_value_against_schematype;
+use crate::entities::{Dereference, Entities, SchemaType};
 use crate::extensions::Extensions;
 use crate::parser::Loc;
-#[cfg(feature = "partial-eval")]
-use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
 
+pub mod bytecode;
 mod err;
 #[cfg(feature = "tolerant-ast")]
 use crate::evaluator::EvaluationError::ASTErrorExpr;
@@ -203,6 +205,15 @@ pub struct Evaluator<'e> {
     resource: EntityUIDEntry,
     /// `Context` for the current request; this will be a Record type
     context: PartialValue,
+    /// Attributes of the `Context` for the current request which may or may
+    /// not be present, and whose values are unknown
+    unknown_context_attrs: BTreeSet<SmolStr>,
+    /// Presence of attributes of unknowns which is implied by the residual
+    /// conditions enclosing the expression being evaluated, as (name of the
+    /// unknown, attribute, whether the attribute is present). E.g., when
+    /// evaluating `b` in `context has a && b` where `context` is unknown, the
+    /// attribute `a` of `context` is present.
+    has_attr_assumptions: RefCell<Vec<(SmolStr, SmolStr, bool)>>,
     /// Entities which we use to resolve entity references.
     ///
     /// This is a reference, because the `Evaluator` doesn't need ownership of
@@ -280,16 +291,20 @@ impl<'e> RestrictedEvaluator<'e> {
     fn partial_interpret_internal(&self, expr: BorrowedRestrictedExpr<'_>) -> Result<PartialValue> {
         match expr.as_ref().expr_kind() {
             ExprKind::Lit(lit) => Ok(lit.clone().into()),
-            ExprKind::Set(items) => {
-                let vals = items
-                    .iter()
-                    .map(|item| self.partial_interpret(BorrowedRestrictedExpr::new_unchecked(item))) // assuming the invariant holds for `e`, it will hold here
-                    .collect::<Result<Vec<_>>>()?;
-                match split(vals) {
-                    Either::Left(values) => Ok(Value::set(values, expr.source_loc().cloned()).into()),
-                    Either::Right(residuals) => Ok(Expr::set(residuals).into()),
+            ExprKind::Set(items) => match expr.as_ref().literal_set() {
+                // a set of literals is built along with the expression
+                Some(lits) => Ok(Value::new(ValueKind::Set(lits.set.clone()), expr.source_loc().cloned()).into()),
+                None => {
+                    let vals = items
+                        .iter()
+                        .map(|item| self.partial_interpret(BorrowedRestrictedExpr::new_unchecked(item))) // assuming the invariant holds for `e`, it will hold here
+                        .collect::<Result<Vec<_>>>()?;
+                    match split(vals) {
+                        Either::Left(values) => Ok(Value::set(values, expr.source_loc().cloned()).into()),
+                        Either::Right(residuals) => Ok(Expr::set(residuals).into()),
+                    }
                 }
-            }
+            },
             ExprKind::Unknown(u) => Ok(PartialValue::unknown(u.clone())),
             ExprKind::Record(map) => {
                 let map = map
@@ -310,10 +325,15 @@ impl<'e> RestrictedEvaluator<'e> {
                 }
             }
             ExprKind::ExtensionFunctionApp { fn_name, args } => {
-                let args = args
-                    .iter()
-                    .map(|arg| self.partial_interpret(BorrowedRestrictedExpr::new_unchecked(arg))) // assuming the invariant holds for `e`, it will hold here
-                    .collect::<Result<Vec<_>>>()?;
+                let args = match eval_extension_args(
+                    self.extensions.func(fn_name).ok(),
+                    args.iter(),
+                    |arg| self.partial_interpret(BorrowedRestrictedExpr::new_unchecked(arg)), // assuming the invariant holds for `e`, it will hold here
+                    known_value,
+                )? {
+                    Either::Left(args) => args,
+                    Either::Right(v) => return Ok(v.into()),
+                };
                 match split(args) {
                     Either::Left(values) => {
                         let values : Vec<_> = values.collect();
@@ -339,6 +359,61 @@ pub(crate) fn valid_comparison_op_types(extensions: &Extensions<'_>) -> nonempty
     expected_types
 }
 
+/// Evaluate the arguments of a call to the extension function `efunc` left to
+/// right, with `eval_arg`.
+///
+/// If `efunc` has lazy arguments (see
+/// [`ExtensionFunction::with_short_circuit()`]) and the values of the
+/// arguments evaluated so far determine the result of the call, the remaining
+/// arguments are skipped and the result is returned on the right. Otherwise,
+/// the evaluated arguments are returned on the left. `as_value` returns the
+/// value of an evaluated argument, if it is known.
+pub(crate) fn eval_extension_args<A, T>(
+    efunc: Option<&ExtensionFunction>,
+    args: impl IntoIterator<Item = A>,
+    mut eval_arg: impl FnMut(A) -> Result<T>,
+    as_value: impl Fn(&T) -> Option<Value>,
+) -> Result<Either<Vec<T>, Value>> {
+    let efunc = efunc.filter(|efunc| efunc.has_lazy_args());
+    let mut evaluated = Vec::new();
+    // the values of the arguments evaluated so far, while they are all known
+    let mut known = Some(Vec::new());
+    for arg in args {
+        if let (Some(efunc), Some(known)) = (efunc, &known) {
+            if let Some(res) = efunc.short_circuit(known) {
+                return res.map(Either::Right);
+            }
+        }
+        let arg = eval_arg(arg)?;
+        if efunc.is_some() {
+            known = known.and_then(|mut known| {
+                known.push(as_value(&arg)?);
+                Some(known)
+            });
+        }
+        evaluated.push(arg);
+    }
+    Ok(Either::Left(evaluated))
+}
+
+fn known_value(v: &PartialValue) -> Option<Value> {
+    match v {
+        PartialValue::Value(v) => Some(v.clone()),
+        PartialValue::Residual(_) => None,
+    }
+}
+
+/// Is `uid`, whose entity is `entity` (if it exists), equal to or a descendant
+/// of any of `uids`?
+pub(crate) fn is_in_entities(
+    uid: &EntityUID,
+    entity: Option<&Entity>,
+    uids: &HashSet<EntityUID>,
+) -> bool {
+    uids.contains(uid)
+        || entity.is_some_and(|entity| entity.ancestors().any(|ancestor| uids.contains(ancestor)))
+}
+
 impl<'e> Evaluator<'e> {
     /// Create a fresh `Evaluator` for the given `request`, which uses the given
     /// `Entities` to resolve entity references. Use the given `Extension`s when
@@ -350,10 +425,12 @@ impl<'e> Evaluator<'e> {
             resource: q.resource,
             context: {
                 match q.context {
-                    None => PartialValue::unknown(Unknown::new_untyped("context")),
+                    None => PartialValue::unknown(Unknown::new_untyped(CONTEXT_UNKNOWN_NAME)),
                     Some(ctx) => ctx.into(),
                 }
             },
+            unknown_context_attrs: q.unknown_context_attrs,
+            has_attr_assumptions: RefCell::new(Vec::new()),
             entities,
             extensions,
             #[cfg(feature = "partial-eval")]
@@ -369,6 +446,8 @@ impl<'e> Evaluator<'e> {
             action: self.action,
             resource: self.resource,
             context: self.context,
+            unknown_context_attrs: self.unknown_context_attrs,
+            has_attr_assumptions: self.has_attr_assumptions,
             entities: self.entities,
             extensions: self.extensions,
             unknowns_mapper,
@@ -455,12 +534,17 @@ impl<'e> Evaluator<'e> {
             ExprKind::Lit(lit) => Ok(lit.clone().into()),
             ExprKind::Slot(id) => slots
                 .get(id)
-                .ok_or_else(|| err::EvaluationError::unlinked_slot(*id, loc.cloned()))
+                .ok_or_else(|| err::EvaluationError::unlinked_slot(id.clone(), loc.cloned()))
                 .map(|euid| PartialValue::from(euid.clone())),
             ExprKind::Var(v) => match v {
                 Var::Principal => Ok(self.principal.evaluate(*v)),
                 Var::Action => Ok(self.action.evaluate(*v)),
                 Var::Resource => Ok(self.resource.evaluate(*v)),
+                // If some attributes of the context are unknown, the context
+                // as a whole is unknown
+                Var::Context if !self.unknown_context_attrs.is_empty() => Ok(
+                    PartialValue::unknown(Unknown::new_untyped(CONTEXT_UNKNOWN_NAME)),
+                ),
                 Var::Context => Ok(self.context.clone()),
             },
             ExprKind::Unknown(u) => self.unknown_to_partialvalue(u),
@@ -471,13 +555,16 @@ impl<'e> Evaluator<'e> {
             } => self.eval_if(test_expr, then_expr, else_expr, slots),
             ExprKind::And { left, right } => {
                 match self.partial_interpret(left, slots)? {
-                    // PE Case: Try to partial interpret right expression in best-effort.
+                    // PE Case: Try to partial interpret right expression in best-effort,
+                    // assuming the left expression is true.
                     // If it fails, fall back to original right expression.
-                    PartialValue::Residual(e) => Ok(PartialValue::Residual(Expr::and(
-                        e,
-                        self.partial_interpret(right, slots)
-                            .map_or_else(|_| right.as_ref().clone(), Into::into),
-                    ))),
+                    PartialValue::Residual(e) => {
+                        let right = self.with_has_attr_assumptions(&e, true, || {
+                            self.partial_interpret(right, slots)
+                                .map_or_else(|_| right.as_ref().clone(), Into::into)
+                        });
+                        Ok(PartialValue::Residual(Expr::and(e, right)))
+                    }
                     // Full eval case
                     PartialValue::Value(v) => {
                         if v.get_as_bool()? {
@@ -500,13 +587,16 @@ impl<'e> Evaluator<'e> {
             }
             ExprKind::Or { left, right } => {
                 match self.partial_interpret(left, slots)? {
-                    // PE Case: Try to partial interpret right expression in best-effort.
+                    // PE Case: Try to partial interpret right expression in best-effort,
+                    // assuming the left expression is false.
                     // If it fails, fall back to original right expression.
-                    PartialValue::Residual(r) => Ok(PartialValue::Residual(Expr::or(
-                        r,
-                        self.partial_interpret(right, slots)
-                            .map_or_else(|_| right.as_ref().clone(), Into::into),
-                    ))),
+                    PartialValue::Residual(r) => {
+                        let right = self.with_has_attr_assumptions(&r, false, || {
+                            self.partial_interpret(right, slots)
+                                .map_or_else(|_| right.as_ref().clone(), Into::into)
+                        });
+                        Ok(PartialValue::Residual(Expr::or(r, right)))
+                    }
                     // Full eval case
                     PartialValue::Value(lhs) => {
                         if lhs.get_as_bool()? {
@@ -534,6 +624,7 @@ impl<'e> Evaluator<'e> {
                 PartialValue::Residual(r) => Ok(PartialValue::Residual(Expr::unary_app(*op, r))),
             },
             ExprKind::BinaryApp { op, arg1, arg2 } => {
+                let literal_entities = arg2.literal_set().and_then(|lits| lits.entities.as_ref());
                 // NOTE: There are more precise partial eval opportunities here, esp w/ typed unknowns
                 // Current limitations:
                 //   Operators are not partially evaluated, except in a few 'simple' cases when comparing a concrete value with an unknown of known type
@@ -585,12 +676,22 @@ impl<'e> Evaluator<'e> {
                                 };
                                 e
                             })?;
-                        match self.entities.entity(uid1) {
-                            Dereference::Residual(r) => Ok(PartialValue::Residual(
-                                Expr::binary_app(BinaryOp::In, r, arg2.into()),
-                            )),
-                            Dereference::NoSuchEntity => self.eval_in(uid1, None, arg2),
-                            Dereference::Data(entity1) => self.eval_in(uid1, Some(entity1), arg2),
+                        let entity1 = match self.entities.entity(uid1) {
+                            Dereference::Residual(r) => {
+                                return Ok(PartialValue::Residual(Expr::binary_app(
+                                    BinaryOp::In,
+                                    r,
+                                    arg2.into(),
+                                )))
+                            }
+                            Dereference::NoSuchEntity => None,
+                            Dereference::Data(entity1) => Some(entity1),
+                        };
+                        match literal_entities {
+                            // a set literal of entities is hashed, so rather
+                            // than scanning it, look up `uid1` and its ancestors
+                            Some(uids) => Ok(is_in_entities(uid1, entity1, uids).into()),
+                            None => self.eval_in(uid1, entity1, arg2),
                         }
                     }
                     // contains, which works on Sets
@@ -664,13 +765,41 @@ impl<'e> Evaluator<'e> {
                             }
                         }
                     }
+                    // GetKey and ContainsKey, which require a map (represented
+                    // as a record) on the left and a String on the right
+                    #[cfg(feature = "maps")]
+                    BinaryOp::GetKey => {
+                        let map = arg1.get_as_record()?;
+                        let key = arg2.get_as_string()?;
+                        map.get(key)
+                            .ok_or_else(|| {
+                                EvaluationError::map_key_does_not_exist(
+                                    key.clone(),
+                                    map.keys(),
+                                    map.len(),
+                                    loc.cloned(),
+                                )
+                            })
+                            .map(|v| PartialValue::Value(v.clone()))
+                    }
+                    #[cfg(feature = "maps")]
+                    BinaryOp::ContainsKey => {
+                        let map = arg1.get_as_record()?;
+                        let key = arg2.get_as_string()?;
+                        Ok(map.contains_key(key).into())
+                    }
                 }
             }
             ExprKind::ExtensionFunctionApp { fn_name, args } => {
-                let args = args
-                    .iter()
-                    .map(|arg| self.partial_interpret(arg, slots))
-                    .collect::<Result<Vec<_>>>()?;
+                let args = match eval_extension_args(
+                    self.extensions.func(fn_name).ok(),
+                    args.iter(),
+                    |arg| self.partial_interpret(arg, slots),
+                    known_value,
+                )? {
+                    Either::Left(args) => args,
+                    Either::Right(v) => return Ok(v.into()),
+                };
                 match split(args) {
                     Either::Left(vals) => {
                         let vals: Vec<_> = vals.collect();
@@ -683,7 +812,14 @@ impl<'e> Evaluator<'e> {
                 }
             }
             ExprKind::GetAttr { expr, attr } => self.get_attr(expr.as_ref(), attr, slots, loc),
-            ExprKind::HasAttr { expr, attr } => match self.partial_interpret(expr, slots)? {
+            ExprKind::HasAttr { expr, attr } if self.is_unknown_context_attr(expr, attr) => {
+                let context = Unknown::new_untyped(CONTEXT_UNKNOWN_NAME);
+                Ok(match self.unknown_has_attr(&context, attr) {
+                    Some(present) => present.into(),
+                    None => Expr::has_attr(Expr::unknown(context), attr.clone()).into(),
+                })
+            }
+            ExprKind::HasAttr { expr, attr } => match self.partial_interpret_record(expr, slots)? {
                 PartialValue::Value(Value {
                     value: ValueKind::Record(record),
                     ..
@@ -706,11 +842,17 @@ impl<'e> Evaluator<'e> {
                     &val,
                 )),
                 PartialValue::Residual(r) => match r.expr_kind() {
+                    // If the enclosing conditions imply whether the attribute
+                    // is present, use that
                     // If the residual is a known record and is projectable (it’s guaranteed to never error on evaluation),
                     // then check for the attribute existency.
                     ExprKind::Record(rec) if r.is_projectable() => {
                         Ok(rec.contains_key(attr).into())
                     }
+                    ExprKind::Unknown(u) => Ok(match self.unknown_has_attr(u, attr) {
+                        Some(present) => present.into(),
+                        None => Expr::has_attr(r, attr.clone()).into(),
+                    }),
                     // Otherwise, leave the expression as is.
                     _ => Ok(Expr::has_attr(r, attr.clone()).into()),
                 },
@@ -731,30 +873,41 @@ impl<'e> Evaluator<'e> {
                         Ok((v.get_as_entity()?.entity_type() == entity_type).into())
                     }
                     PartialValue::Residual(r) => {
-                        if let ExprKind::Unknown(Unknown {
-                            type_annotation:
-                                Some(Type::Entity {
-                                    ty: type_of_unknown,
-                                }),
-                            ..
-                        }) = r.expr_kind()
-                        {
-                            return Ok((type_of_unknown == entity_type).into());
+                        // If the residual is an unknown which can only be an
+                        // entity, its possible entity types may decide the `is`
+                        if let Some(tys) = match r.expr_kind() {
+                            ExprKind::Unknown(u) => u.possible_types(),
+                            _ => None,
+                        } {
+                            if tys.iter().all(|ty| matches!(ty, Type::Entity { .. })) {
+                                let ty = Type::Entity {
+                                    ty: entity_type.clone(),
+                                };
+                                if !tys.contains(&ty) {
+                                    return Ok(false.into());
+                                } else if tys.len() == 1 {
+                                    return Ok(true.into());
+                                }
+                            }
                         }
                         Ok(Expr::is_entity_type(r, entity_type.clone()).into())
                     }
                 }
             }
-            ExprKind::Set(items) => {
-                let vals = items
-                    .iter()
-                    .map(|item| self.partial_interpret(item, slots))
-                    .collect::<Result<Vec<_>>>()?;
-                match split(vals) {
-                    Either::Left(vals) => Ok(Value::set(vals, loc.cloned()).into()),
-                    Either::Right(r) => Ok(Expr::set(r).into()),
+            ExprKind::Set(items) => match expr.literal_set() {
+                // a set of literals is built along with the expression
+                Some(lits) => Ok(Value::new(ValueKind::Set(lits.set.clone()), loc.cloned()).into()),
+                None => {
+                    let vals = items
+                        .iter()
+                        .map(|item| self.partial_interpret(item, slots))
+                        .collect::<Result<Vec<_>>>()?;
+                    match split(vals) {
+                        Either::Left(vals) => Ok(Value::set(vals, loc.cloned()).into()),
+                        Either::Right(r) => Ok(Expr::set(r).into()),
+                    }
                 }
-            }
+            },
             ExprKind::Record(map) => {
                 let map = map
                     .iter()
@@ -792,12 +945,31 @@ impl<'e> Evaluator<'e> {
     // Try resolving a named Unknown into a Value
     #[cfg(feature = "partial-eval")]
     fn unknown_to_partialvalue(&self, u: &Unknown) -> Result<PartialValue> {
-        match (self.unknowns_mapper.as_ref()(&u.name), &u.type_annotation) {
+        match (
+            self.unknowns_mapper.as_ref()(&u.name),
+            &u.schema_type,
+            &u.type_annotation,
+        ) {
             // The mapper might not recognize the unknown
-            (None, _) => Ok(PartialValue::Residual(Expr::unknown(u.clone()))),
+            (None, _, _) => Ok(PartialValue::Residual(Expr::unknown(u.clone()))),
+            // The value must conform to the schema type of the unknown, if any
+            (Some(v), Some(ty), _) => {
+                match typecheck_value_against_schematype(
+                    &PartialValue::Value(v.clone()),
+                    ty,
+                    self.extensions,
+                ) {
+                    Ok(()) => Ok(PartialValue::Value(v)),
+                    Err(err) => Err(EvaluationError::type_error_with_advice(
+                        ty.runtime_types(),
+                        &v,
+                        err.to_string(),
+                    )),
+                }
+            }
             // Replace the unknown value with the concrete one found
-            (Some(v), None) => Ok(PartialValue::Value(v)),
-            (Some(v), Some(t)) => {
+            (Some(v), None, None) => Ok(PartialValue::Value(v)),
+            (Some(v), None, Some(t)) => {
                 if v.type_of() == *t {
                     Ok(PartialValue::Value(v))
                 } else {
@@ -846,6 +1018,83 @@ impl<'e> Evaluator<'e> {
 
     /// Evaluation of conditionals
     /// Must be sure to respect short-circuiting semantics
+    /// Interpret `expr`, whose attribute is being accessed or checked for. The
+    /// variable `context` is interpreted as its known attributes, as the
+    /// callers handle its unknown attributes separately.
+    fn partial_interpret_record(&self, expr: &Expr, slots: &SlotEnv) -> Result<PartialValue> {
+        match expr.expr_kind() {
+            ExprKind::Var(Var::Context) => Ok(self.context.clone()),
+            _ => self.partial_interpret(expr, slots),
+        }
+    }
+
+    /// Is `expr` the variable `context`, and `attr` one of its attributes
+    /// which may or may not be present?
+    fn is_unknown_context_attr(&self, expr: &Expr, attr: &str) -> bool {
+        matches!(expr.expr_kind(), ExprKind::Var(Var::Context))
+            && self.unknown_context_attrs.contains(attr)
+    }
+
+    /// Whether the attribute `attr` of the unknown `u` is present, or `None`
+    /// if this can't be decided. It is decided by the residual conditions
+    /// enclosing the expression being evaluated, which may check for the
+    /// attribute, or by the declared type of `u`: a required attribute of a
+    /// record is always present, and an undeclared attribute of a closed
+    /// record never is.
+    fn unknown_has_attr(&self, u: &Unknown, attr: &str) -> Option<bool> {
+        let assumed = self
+            .has_attr_assumptions
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(name, a, _)| name == &u.name && a == attr)
+            .map(|(_, _, present)| *present);
+        match (assumed, &u.schema_type) {
+            (Some(present), _) => Some(present),
+            (None, Some(SchemaType::Record { attrs, open_attrs })) => match attrs.get(attr) {
+                Some(attr_ty) if attr_ty.is_required() => Some(true),
+                None if !open_attrs => Some(false),
+                _ => None,
+            },
+            (None, _) => None,
+        }
+    }
+
+    /// Evaluate `f` assuming that the residual `cond` evaluates to `holds`,
+    /// which implies the presence (or absence) of attributes of unknowns
+    /// checked with `has` in `cond`
+    fn with_has_attr_assumptions<T>(&self, cond: &Expr, holds: bool, f: impl FnOnce() -> T) -> T {
+        fn collect(cond: &Expr, holds: bool, assumptions: &mut Vec<(SmolStr, SmolStr, bool)>) {
+            match cond.expr_kind() {
+                ExprKind::HasAttr { expr, attr } => {
+                    if let ExprKind::Unknown(u) = expr.expr_kind() {
+                        assumptions.push((u.name.clone(), attr.clone(), holds));
+                    }
+                }
+                // `a && b` holding implies both hold, and `a || b` not holding
+                // implies neither holds
+                ExprKind::And { left, right } if holds => {
+                    collect(left, holds, assumptions);
+                    collect(right, holds, assumptions);
+                }
+                ExprKind::Or { left, right } if !holds => {
+                    collect(left, holds, assumptions);
+                    collect(right, holds, assumptions);
+                }
+                ExprKind::UnaryApp {
+                    op: UnaryOp::Not,
+                    arg,
+                } => collect(arg, !holds, assumptions),
+                _ => (),
+            }
+        }
+        let len = self.has_attr_assumptions.borrow().len();
+        collect(cond, holds, &mut self.has_attr_assumptions.borrow_mut());
+        let result = f();
+        self.has_attr_assumptions.borrow_mut().truncate(len);
+        result
+    }
+
     fn eval_if(
         &self,
         guard: &Expr,
@@ -864,14 +1113,16 @@ impl<'e> Evaluator<'e> {
             PartialValue::Residual(guard) => {
                 // Try to partial interpret both branches in best-effort:
                 // if a branch partial evaluation fails, then fallback to the original branch expression.
-                let consequent = self
-                    .partial_interpret(consequent, slots)
-                    .map(|r| Arc::new(r.into()))
-                    .unwrap_or_else(|_| consequent.clone());
-                let alternative = self
-                    .partial_interpret(alternative, slots)
-                    .map(|r| Arc::new(r.into()))
-                    .unwrap_or_else(|_| alternative.clone());
+                let consequent = self.with_has_attr_assumptions(&guard, true, || {
+                    self.partial_interpret(consequent, slots)
+                        .map(|r| Arc::new(r.into()))
+                        .unwrap_or_else(|_| consequent.clone())
+                });
+                let alternative = self.with_has_attr_assumptions(&guard, false, || {
+                    self.partial_interpret(alternative, slots)
+                        .map(|r| Arc::new(r.into()))
+                        .unwrap_or_else(|_| alternative.clone())
+                });
                 Ok(Expr::ite_arc(Arc::new(guard), consequent, alternative).into())
             }
         }
@@ -887,7 +1138,14 @@ impl<'e> Evaluator<'e> {
         slots: &SlotEnv,
         source_loc: Option<&Loc>,
     ) -> Result<PartialValue> {
-        match self.partial_interpret(expr, slots)? {
+        if self.is_unknown_context_attr(expr, attr) {
+            return Ok(Expr::get_attr(
+                Expr::unknown(Unknown::new_untyped(CONTEXT_UNKNOWN_NAME)),
+                attr.clone(),
+            )
+            .into());
+        }
+        match self.partial_interpret_record(expr, slots)? {
             // PE Cases
             PartialValue::Residual(res) => {
                 match res.expr_kind() {
@@ -1009,23 +1267,24 @@ impl<'e> Evaluator<'e> {
         e2: &Expr,
         op: BinaryOp,
     ) -> Option<PartialValue> {
-        match (op, v1.value_kind(), e2.expr_kind()) {
-            // We detect comparing a typed unknown entity id to a literal entity id, and short-circuit to false if the literal is not the same type
-            (
-                BinaryOp::Eq,
-                ValueKind::Lit(Literal::EntityUID(uid1)),
-                ExprKind::Unknown(Unknown {
-                    type_annotation:
-                        Some(Type::Entity {
-                            ty: type_of_unknown,
-                        }),
-                    ..
-                }),
-            ) => {
-                if uid1.entity_type() != type_of_unknown {
-                    Some(false.into())
-                } else {
+        match (op, e2.expr_kind()) {
+            // We detect comparing a typed unknown to a value which can't be
+            // substituted for it, and short-circuit to false
+            (BinaryOp::Eq, ExprKind::Unknown(u)) => {
+                let conforms = match (&u.schema_type, u.possible_types()) {
+                    (Some(ty), _) => typecheck_value_against_schematype(
+                        &PartialValue::Value(v1.clone()),
+                        ty,
+                        self.extensions,
+                    )
+                    .is_ok(),
+                    (None, Some(tys)) => tys.contains(&v1.type_of()),
+                    (None, None) => true,
+                };
+                if conforms {
                     None
+                } else {
+                    Some(false.into())
                 }
             }
             _ => None,
@@ -1039,22 +1298,14 @@ impl<'e> Evaluator<'e> {
         op: BinaryOp,
     ) -> Option<PartialValue> {
         match (op, e1.expr_kind(), e2.expr_kind()) {
-            // We detect comparing two typed unknown entities, and return false if they don't have the same type.
-            (
-                BinaryOp::Eq,
-                ExprKind::Unknown(Unknown {
-                    type_annotation: Some(Type::Entity { ty: t1 }),
-                    ..
-                }),
-                ExprKind::Unknown(Unknown {
-                    type_annotation: Some(Type::Entity { ty: t2 }),
-                    ..
-                }),
-            ) => {
-                if t1 != t2 {
-                    Some(false.into())
-                } else {
-                    None
+            // We detect comparing two typed unknowns, and return false if no
+            // value can be substituted for both of them.
+            (BinaryOp::Eq, ExprKind::Unknown(u1), ExprKind::Unknown(u2)) => {
+                match (u1.possible_types(), u2.possible_types()) {
+                    (Some(tys1), Some(tys2)) if !tys1.iter().any(|ty| tys2.contains(ty)) => {
+                        Some(false.into())
+                    }
+                    _ => None,
                 }
             }
             _ => None,
@@ -1137,8 +1388,8 @@ impl Value {
     }
 
     /// Convert the `Value` to a Record, or throw a type error if it's not a Record.
-    #[cfg(feature = "partial-eval")]
-    pub(crate) fn get_as_record(&self) -> Result<&Arc<BTreeMap<SmolStr, Value>>> {
+    #[cfg(any(feature = "maps", feature = "lookup", feature = "partial-eval"))]
+    pub(crate) fn get_as_record(&self) -> Result<&Arc<std::collections::BTreeMap<SmolStr, Value>>> {
         match &self.value {
             ValueKind::Record(rec) => Ok(rec),
             _ => Err(EvaluationError::type_error_single(Type::Record, self)),
@@ -1170,13 +1421,13 @@ pub(crate) fn stack_size_check() -> Result<()> {
 #[expect(clippy::panic, clippy::cognitive_complexity, reason = "Unit Test Code")]
 #[cfg(test)]
 pub(crate) mod test {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
     use std::str::FromStr;
 
     use super::*;
 
     use crate::{
-        entities::{EntityJsonParser, NoEntitiesSchema, TCComputation},
+        entities::{AttributeType, EntityJsonParser, NoEntitiesSchema, SchemaType, TCComputation},
         parser::{self, parse_expr, parse_policy_or_template, parse_policyset},
         test_utils::{expect_err, ExpectedErrorMessageBuilder},
     };
@@ -1456,6 +1707,136 @@ pub(crate) mod test {
         assert_eq!(r, Either::Right(expected_residual));
     }
 
+    #[test]
+    fn partial_eval_schema_typed_unknowns() {
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, Extensions::none());
+        let unknown = |ty| Expr::unknown(Unknown::new_with_schema_type("u", ty));
+        let record = |open_attrs| SchemaType::Record {
+            attrs: BTreeMap::from([
+                ("a".into(), AttributeType::required(SchemaType::Long)),
+                ("b".into(), AttributeType::optional(SchemaType::Long)),
+            ]),
+            open_attrs,
+        };
+        let union = SchemaType::EntityUnion {
+            tys: BTreeSet::from(["A".parse().unwrap(), "B".parse().unwrap()]),
+        };
+        let set_of_longs = SchemaType::Set {
+            element_ty: Box::new(SchemaType::Long),
+        };
+
+        // `has` is decided by the declared attributes of a record
+        for (open, attr, expected) in [
+            (false, "a", Some(true)),
+            (false, "b", None),
+            (false, "c", Some(false)),
+            (true, "a", Some(true)),
+            (true, "c", None),
+        ] {
+            let e = Expr::has_attr(unknown(record(open)), attr.into());
+            let r = eval.partial_eval_expr(&e).unwrap();
+            match expected {
+                Some(b) => assert_eq!(r, Either::Left(b.into()), "{e}"),
+                None => assert_eq!(r, Either::Right(e.clone()), "{e}"),
+            }
+        }
+
+        // `is` is decided by the declared entity types
+        let e = Expr::is_entity_type(unknown(union.clone()), "C".parse().unwrap());
+        assert_eq!(eval.partial_eval_expr(&e), Ok(Either::Left(false.into())));
+        let e = Expr::is_entity_type(unknown(union.clone()), "A".parse().unwrap());
+        assert_eq!(eval.partial_eval_expr(&e), Ok(Either::Right(e.clone())));
+
+        // `==` is false for values which don't conform to the declared type
+        let e = Expr::is_eq(unknown(set_of_longs.clone()), Expr::set([Expr::val("x")]));
+        assert_eq!(eval.partial_eval_expr(&e), Ok(Either::Left(false.into())));
+        let e = Expr::is_eq(
+            Expr::val(r#"C::"c""#.parse::<EntityUID>().unwrap()),
+            unknown(union),
+        );
+        assert_eq!(eval.partial_eval_expr(&e), Ok(Either::Left(false.into())));
+        let e = Expr::is_eq(unknown(set_of_longs), Expr::set([Expr::val(1)]));
+        assert_matches!(eval.partial_eval_expr(&e), Ok(Either::Right(_)));
+
+        // `==` is false for unknowns whose declared types are disjoint
+        let e = Expr::is_eq(
+            unknown(SchemaType::Long),
+            Expr::unknown(Unknown::new_with_schema_type("v", SchemaType::String)),
+        );
+        assert_eq!(eval.partial_eval_expr(&e), Ok(Either::Left(false.into())));
+    }
+
+    #[test]
+    fn partial_eval_unknown_context_attrs() {
+        let request = basic_request().with_unknown_context_attrs(["foo".into(), "cur_time".into()]);
+        assert_eq!(
+            request.unknown_context_attrs().collect::<Vec<_>>(),
+            vec!["foo"]
+        );
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, Extensions::none());
+        let context = Expr::unknown(Unknown::new_untyped(CONTEXT_UNKNOWN_NAME));
+        let has_foo = Expr::has_attr(context.clone(), "foo".into());
+        let foo_is_3 = Expr::is_eq(Expr::get_attr(context.clone(), "foo".into()), Expr::val(3));
+        let partial_eval = |src: &str| eval.partial_eval_expr(&parse_expr(src).unwrap()).unwrap();
+
+        // The known attributes of the context are evaluated as usual
+        assert_eq!(
+            partial_eval(r#"context has cur_time && context.cur_time == "03:22:11""#),
+            Either::Left(true.into())
+        );
+        assert_eq!(partial_eval("context has bar"), Either::Left(false.into()));
+
+        // The unknown attributes become residuals on the unknown context
+        assert_eq!(
+            partial_eval("context has foo && context.foo == 3"),
+            Either::Right(Expr::and(has_foo.clone(), foo_is_3.clone()))
+        );
+        assert_eq!(
+            partial_eval(r#"context == {}"#),
+            Either::Right(Expr::is_eq(context, Expr::record([]).unwrap()))
+        );
+
+        // Residual conditions decide whether the unknown attributes are present
+        assert_eq!(
+            partial_eval("context has foo && (context has foo && context.foo == 3)"),
+            Either::Right(Expr::and(
+                has_foo.clone(),
+                Expr::and(Expr::val(true), foo_is_3)
+            ))
+        );
+        assert_eq!(
+            partial_eval("!(context has foo) || context has foo"),
+            Either::Right(Expr::or(Expr::not(has_foo.clone()), Expr::val(true)))
+        );
+        assert_eq!(
+            partial_eval("if context has foo then context has foo else context has foo"),
+            Either::Right(Expr::ite(has_foo, Expr::val(true), Expr::val(false)))
+        );
+    }
+
+    #[test]
+    fn substitute_schema_typed_unknowns() {
+        let union = SchemaType::EntityUnion {
+            tys: BTreeSet::from(["A".parse().unwrap(), "B".parse().unwrap()]),
+        };
+        let u = Unknown::new_with_schema_type("u", union);
+        assert_eq!(u.type_annotation, None);
+        let e = Expr::unknown(u);
+        let b: EntityUID = r#"B::"b""#.parse().unwrap();
+        let c: EntityUID = r#"C::"c""#.parse().unwrap();
+        assert_matches!(
+            e.substitute_typed(&HashMap::from([("u".into(), Value::from(b.clone()))])),
+            Ok(e) => assert_eq!(e, Expr::val(b))
+        );
+        assert_matches!(
+            e.substitute_typed(&HashMap::from([("u".into(), Value::from(c))])),
+            Err(SubstitutionError::TypeError { .. })
+        );
+    }
+
     #[test]
     fn interpret_primitives() {
         let request = basic_request();
@@ -4106,6 +4487,63 @@ pub(crate) mod test {
         );
     }
 
+    #[test]
+    fn interpret_literal_set_membership() {
+        let request = basic_request();
+        let entities = rich_entities();
+        let eval = Evaluator::new(request, &entities, Extensions::none());
+        let others = (0..5000).map(|i| Expr::val(EntityUID::with_eid(&format!("other{i}"))));
+        let set = |uids: &[&str]| {
+            Expr::set(
+                others
+                    .clone()
+                    .chain(uids.iter().map(|uid| Expr::val(EntityUID::with_eid(uid)))),
+            )
+        };
+        // the set is hashed when the expression is built
+        assert_matches!(set(&[]).literal_set(), Some(lits) => {
+            assert_eq!(lits.entities.as_ref().map(HashSet::len), Some(5000));
+        });
+        for (uid, uids, expected) in [
+            ("child", vec!["parent"], true),
+            ("child", vec!["child"], true),
+            ("child", vec!["sibling", "unrelated"], false),
+            ("doesnotexist", vec!["doesnotexist"], true),
+            ("doesnotexist", vec!["parent"], false),
+        ] {
+            assert_eq!(
+                eval.interpret_inline_policy(&Expr::is_in(
+                    Expr::val(EntityUID::with_eid(uid)),
+                    set(&uids)
+                )),
+                Ok(Value::from(expected)),
+                "{uid} in {uids:?}"
+            );
+        }
+        // a set with anything but entities is still an error
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::is_in(
+                Expr::val(EntityUID::with_eid("child")),
+                Expr::set([Expr::val(EntityUID::with_eid("parent")), Expr::val(1)])
+            )),
+            Err(EvaluationError::TypeError(_))
+        );
+        // and evaluating the set reuses its hashed representation
+        let set = set(&["parent"]);
+        assert_matches!(eval.interpret_inline_policy(&set), Ok(Value { value: ValueKind::Set(evaluated), .. }) => {
+            assert_matches!((&evaluated.fast, &set.literal_set().unwrap().set.fast), (Some(a), Some(b)) => {
+                assert!(Arc::ptr_eq(a, b));
+            });
+        });
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::contains(
+                set,
+                Expr::val(EntityUID::with_eid("parent"))
+            )),
+            Ok(Value::from(true))
+        );
+    }
+
     #[test]
     fn interpret_hierarchy_membership_slice() {
         // User::"Alice" in Group::"Friends".
@@ -4507,6 +4945,88 @@ pub(crate) mod test {
         );
     }
 
+    #[cfg(feature = "maps")]
+    #[test]
+    fn interpret_map_operations() {
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, Extensions::none());
+        let headers = Expr::record([
+            ("host".into(), Expr::val("example.com")),
+            ("user-agent".into(), Expr::val("curl")),
+        ])
+        .unwrap();
+        // headers.containsKey("host")
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::contains_key(headers.clone(), Expr::val("host"))),
+            Ok(Value::from(true))
+        );
+        // headers.containsKey("accept")
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::contains_key(headers.clone(), Expr::val("accept"))),
+            Ok(Value::from(false))
+        );
+        // headers.get("user-agent")
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::get_key(headers.clone(), Expr::val("user-agent"))),
+            Ok(Value::from("curl"))
+        );
+        // headers.get("accept")
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::get_key(headers.clone(), Expr::val("accept"))),
+            Err(e) => {
+                expect_err(
+                    "",
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("map does not have the key `accept`")
+                        .help(r#"available keys: ["host", "user-agent"]"#)
+                        .build(),
+                );
+            }
+        );
+        // {}.get("accept")
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::get_key(
+                Expr::record([]).unwrap(),
+                Expr::val("accept")
+            )),
+            Err(e) => {
+                expect_err(
+                    "",
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("map does not have the key `accept`")
+                        .help("map does not have any keys")
+                        .build(),
+                );
+            }
+        );
+        // headers.containsKey(1)
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::contains_key(headers, Expr::val(1))),
+            Err(e) => {
+                expect_err(
+                    "",
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("type error: expected string, got long").build(),
+                );
+            }
+        );
+        // [1].get("host")
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::get_key(
+                Expr::set([Expr::val(1)]),
+                Expr::val("host")
+            )),
+            Err(e) => {
+                expect_err(
+                    "",
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("type error: expected record, got set").build(),
+                );
+            }
+        );
+    }
+
     #[test]
     fn interpret_contains_all_and_contains_any() {
         let request = basic_request();
@@ -6092,6 +6612,69 @@ pub(crate) mod test {
         assert_matches!(eval.partial_interpret(&e, &HashMap::new()), Err(_));
     }
 
+    /// An extension with a method `a.impliesThat(b)`, which doesn't evaluate
+    /// `b` when `a` is `false`
+    fn lazy_extension() -> Extension {
+        let name: Name = "impliesThat".parse().unwrap();
+        Extension::new(
+            name.clone(),
+            [ExtensionFunction::binary(
+                name,
+                CallStyle::MethodStyle,
+                Box::new(|a, b| Ok((!a.get_as_bool()? || b.get_as_bool()?).into())),
+                SchemaType::Bool,
+                (SchemaType::Bool, SchemaType::Bool),
+            )
+            .with_short_circuit(Box::new(|known| match known {
+                [a] if a == &Value::from(false) => Some(Ok(true.into())),
+                _ => None,
+            }))],
+            std::iter::empty(),
+        )
+    }
+
+    #[test]
+    fn lazy_ext_args() {
+        let ext_array = [lazy_extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let es = basic_entities();
+        let eval = Evaluator::new(basic_request(), &es, &exts);
+        let implies = |a, b| Expr::call_extension_fn("impliesThat".parse().unwrap(), vec![a, b]);
+        // evaluating this errors
+        let missing = Expr::get_attr(Expr::var(Var::Context), "missing".into());
+
+        assert_eq!(
+            eval.interpret_inline_policy(&implies(Expr::val(false), missing.clone())),
+            Ok(Value::from(true))
+        );
+        assert_matches!(
+            eval.interpret_inline_policy(&implies(Expr::val(true), missing.clone())),
+            Err(EvaluationError::RecordAttrDoesNotExist(_))
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(&implies(Expr::val(true), Expr::val(false))),
+            Ok(Value::from(false))
+        );
+        // arguments are still evaluated left to right
+        assert_matches!(
+            eval.interpret_inline_policy(&implies(missing, Expr::val(false))),
+            Err(EvaluationError::RecordAttrDoesNotExist(_))
+        );
+
+        // with an unknown first argument, there is nothing to short-circuit on
+        let e = implies(Expr::unknown(Unknown::new_untyped("a")), Expr::val(false));
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            PartialValue::Residual(e)
+        );
+        // but a known first argument short-circuits an unknown second
+        let e = implies(Expr::val(false), Expr::unknown(Unknown::new_untyped("b")));
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            PartialValue::Value(Value::from(true))
+        );
+    }
+
     #[test]
     fn partial_like() {
         let es = Entities::new();
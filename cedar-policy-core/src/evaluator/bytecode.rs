@@ -0,0 +1,595 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module lowers policy conditions into a flat array of instructions,
+//! executed by a small stack machine in the [`Evaluator`].
+//!
+//! The stack machine only handles the common case: concrete evaluation which
+//! succeeds. Whenever it encounters an error, an unknown, or a residual (e.g.,
+//! from a partial entity store), it gives up and the policy is evaluated by the
+//! tree-walking interpreter instead, so that results, errors, and residuals
+//! are exactly the same as [`Evaluator::partial_evaluate()`].
+
+use super::{binary_arith, binary_relation, is_in_entities, unary_app, Evaluator, Result};
+use crate::ast::{
+    BinaryOp, EntityType, EntityUID, Expr, ExprKind, Literal, Name, PartialValue, Pattern, Policy,
+    PolicyID, PolicySet, SlotEnv, SlotId, UnaryOp, Value, ValueKind, Var,
+};
+use crate::entities::Dereference;
+use crate::extensions::Extensions;
+use itertools::Either;
+use smol_str::SmolStr;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// One instruction of a [`Program`]. Instructions pop their operands from,
+/// and push their result onto, the value stack.
+#[derive(Debug, Clone)]
+enum Instr {
+    /// Push a constant
+    Push(Value),
+    /// Push the value of a variable
+    Var(Var),
+    /// Push the entity a slot is linked to
+    Slot(SlotId),
+    /// Pop a boolean. If it is `false`, push `false` and jump to the target.
+    AndJump(usize),
+    /// Pop a boolean. If it is `true`, push `true` and jump to the target.
+    OrJump(usize),
+    /// Pop a boolean and push it back, failing if it isn't a boolean
+    AssertBool,
+    /// Pop a boolean. If it is `false`, jump to the target.
+    JumpIfFalse(usize),
+    /// Jump to the target
+    Jump(usize),
+    Unary(UnaryOp),
+    /// Pop the second and then the first argument
+    Binary(BinaryOp),
+    /// Pop `arity` arguments and call the extension function
+    CallExtension {
+        fn_name: Name,
+        arity: usize,
+    },
+    GetAttr(SmolStr),
+    HasAttr(SmolStr),
+    Like(Pattern),
+    Is(EntityType),
+    /// Pop `n` values and push the set of them
+    Set(usize),
+    /// Pop an entity and push whether it is in (i.e., equal to or a
+    /// descendant of) any of the entities of a set literal, which is hashed
+    /// at compile time so that large sets don't need to be scanned
+    InSet(Arc<HashSet<EntityUID>>),
+    /// Pop one value per key, in order, and push the record of them
+    Record(Arc<[SmolStr]>),
+    /// Evaluate an expression with the tree-walking interpreter, for
+    /// expressions with no instructions of their own (e.g., unknowns, and
+    /// calls to extension functions with lazy arguments)
+    Interpret(Expr),
+}
+
+/// A policy condition lowered to a flat array of instructions, which the
+/// [`Evaluator`] executes without walking the expression tree.
+#[derive(Debug, Clone)]
+pub struct Program {
+    instrs: Vec<Instr>,
+}
+
+impl Program {
+    /// Compile `expr`. `extensions` must be the extensions the program will
+    /// be evaluated with.
+    pub fn compile(expr: &Expr, extensions: &Extensions<'_>) -> Self {
+        let mut program = Self { instrs: Vec::new() };
+        program.lower(expr, extensions);
+        program
+    }
+
+    /// The number of instructions in the program
+    pub fn len(&self) -> usize {
+        self.instrs.len()
+    }
+
+    /// Returns `true` if the program has no instructions
+    pub fn is_empty(&self) -> bool {
+        self.instrs.is_empty()
+    }
+
+    /// Emit a jump with a placeholder target, returning its index
+    fn emit_jump(&mut self, instr: fn(usize) -> Instr) -> usize {
+        self.instrs.push(instr(usize::MAX));
+        self.instrs.len() - 1
+    }
+
+    /// Set the target of the jump at `at` to the next instruction
+    fn patch_jump(&mut self, at: usize) {
+        let target = self.instrs.len();
+        if let Some(Instr::AndJump(t) | Instr::OrJump(t) | Instr::JumpIfFalse(t) | Instr::Jump(t)) =
+            self.instrs.get_mut(at)
+        {
+            *t = target;
+        }
+    }
+
+    fn lower(&mut self, expr: &Expr, extensions: &Extensions<'_>) {
+        match expr.expr_kind() {
+            ExprKind::Lit(lit) => self.instrs.push(Instr::Push(lit.clone().into())),
+            ExprKind::Var(v) => self.instrs.push(Instr::Var(*v)),
+            ExprKind::Slot(id) => self.instrs.push(Instr::Slot(id.clone())),
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => {
+                self.lower(test_expr, extensions);
+                let to_else = self.emit_jump(Instr::JumpIfFalse);
+                self.lower(then_expr, extensions);
+                let to_end = self.emit_jump(Instr::Jump);
+                self.patch_jump(to_else);
+                self.lower(else_expr, extensions);
+                self.patch_jump(to_end);
+            }
+            ExprKind::And { left, right } => {
+                self.lower(left, extensions);
+                let to_end = self.emit_jump(Instr::AndJump);
+                self.lower(right, extensions);
+                self.instrs.push(Instr::AssertBool);
+                self.patch_jump(to_end);
+            }
+            ExprKind::Or { left, right } => {
+                self.lower(left, extensions);
+                let to_end = self.emit_jump(Instr::OrJump);
+                self.lower(right, extensions);
+                self.instrs.push(Instr::AssertBool);
+                self.patch_jump(to_end);
+            }
+            ExprKind::UnaryApp { op, arg } => {
+                self.lower(arg, extensions);
+                self.instrs.push(Instr::Unary(*op));
+            }
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                self.lower(arg1, extensions);
+                match (
+                    op,
+                    arg2.literal_set().and_then(|lits| lits.entities.as_ref()),
+                ) {
+                    (BinaryOp::In, Some(uids)) => {
+                        self.instrs.push(Instr::InSet(Arc::new(uids.clone())))
+                    }
+                    _ => {
+                        self.lower(arg2, extensions);
+                        self.instrs.push(Instr::Binary(*op));
+                    }
+                }
+            }
+            ExprKind::ExtensionFunctionApp { fn_name, args }
+                if extensions
+                    .func(fn_name)
+                    .is_ok_and(|efunc| !efunc.has_lazy_args()) =>
+            {
+                for arg in args.iter() {
+                    self.lower(arg, extensions);
+                }
+                self.instrs.push(Instr::CallExtension {
+                    fn_name: fn_name.clone(),
+                    arity: args.len(),
+                });
+            }
+            ExprKind::GetAttr { expr, attr } => {
+                self.lower(expr, extensions);
+                self.instrs.push(Instr::GetAttr(attr.clone()));
+            }
+            ExprKind::HasAttr { expr, attr } => {
+                self.lower(expr, extensions);
+                self.instrs.push(Instr::HasAttr(attr.clone()));
+            }
+            ExprKind::Like { expr, pattern } => {
+                self.lower(expr, extensions);
+                self.instrs.push(Instr::Like(pattern.clone()));
+            }
+            ExprKind::Is { expr, entity_type } => {
+                self.lower(expr, extensions);
+                self.instrs.push(Instr::Is(entity_type.clone()));
+            }
+            // Sets of literals are built along with the expression, rather
+            // than on every evaluation. Their hashed representation makes
+            // `contains` O(1).
+            ExprKind::Set(items) => match expr.literal_set() {
+                Some(lits) => self.instrs.push(Instr::Push(Value::new(
+                    ValueKind::Set(lits.set.clone()),
+                    None,
+                ))),
+                None => {
+                    for item in items.iter() {
+                        self.lower(item, extensions);
+                    }
+                    self.instrs.push(Instr::Set(items.len()));
+                }
+            },
+            ExprKind::Record(map) => {
+                for v in map.values() {
+                    self.lower(v, extensions);
+                }
+                self.instrs
+                    .push(Instr::Record(map.keys().cloned().collect()));
+            }
+            ExprKind::Unknown(_) | ExprKind::ExtensionFunctionApp { .. } => {
+                self.instrs.push(Instr::Interpret(expr.clone()));
+            }
+            #[cfg(feature = "tolerant-ast")]
+            ExprKind::Error { .. } => self.instrs.push(Instr::Interpret(expr.clone())),
+        }
+    }
+}
+
+/// A [`PolicySet`] along with a [`Program`] for the condition of each of its
+/// policies, for [`crate::authorizer::Authorizer::is_authorized_compiled()`]
+#[derive(Debug, Clone)]
+pub struct CompiledPolicySet {
+    policies: PolicySet,
+    programs: HashMap<PolicyID, Program>,
+}
+
+impl CompiledPolicySet {
+    /// Compile every policy in `policies` (including disabled ones, so that
+    /// the result stays valid if they are re-enabled on the returned set)
+    pub fn new(policies: PolicySet, extensions: &Extensions<'_>) -> Self {
+        let programs = policies
+            .policies()
+            .map(|p| (p.id().clone(), Program::compile(&p.condition(), extensions)))
+            .collect();
+        Self { policies, programs }
+    }
+
+    /// The policies which were compiled
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    /// The program for the condition of the policy `id`, if it exists
+    pub fn program(&self, id: &PolicyID) -> Option<&Program> {
+        self.programs.get(id)
+    }
+}
+
+impl Evaluator<'_> {
+    /// Like [`Evaluator::partial_evaluate()`], but evaluates `program`, the
+    /// compiled condition of `p`, when possible
+    pub fn evaluate_compiled(&self, p: &Policy, program: &Program) -> Result<Either<bool, Expr>> {
+        match self.run(program, p.env()) {
+            Some(Value {
+                value: ValueKind::Lit(Literal::Bool(b)),
+                ..
+            }) => Ok(Either::Left(b)),
+            _ => self.partial_evaluate(p),
+        }
+    }
+
+    /// Run `program`, returning `None` if it doesn't evaluate to a concrete
+    /// value without error
+    fn run(&self, program: &Program, slots: &SlotEnv) -> Option<Value> {
+        // Any unknown in the request means evaluation might produce a residual
+        if !self.unknown_context_attrs.is_empty() {
+            return None;
+        }
+        let mut stack: Vec<Value> = Vec::new();
+        let mut pc = 0;
+        while let Some(instr) = program.instrs.get(pc) {
+            pc += 1;
+            match instr {
+                Instr::Push(v) => stack.push(v.clone()),
+                Instr::Var(v) => {
+                    let val = match v {
+                        Var::Principal => self.principal.evaluate(*v),
+                        Var::Action => self.action.evaluate(*v),
+                        Var::Resource => self.resource.evaluate(*v),
+                        Var::Context => self.context.clone(),
+                    };
+                    stack.push(concrete(val)?);
+                }
+                Instr::Slot(id) => stack.push(slots.get(id)?.clone().into()),
+                Instr::AndJump(target) => {
+                    if !stack.pop()?.get_as_bool().ok()? {
+                        stack.push(false.into());
+                        pc = *target;
+                    }
+                }
+                Instr::OrJump(target) => {
+                    if stack.pop()?.get_as_bool().ok()? {
+                        stack.push(true.into());
+                        pc = *target;
+                    }
+                }
+                Instr::AssertBool => {
+                    let b = stack.pop()?.get_as_bool().ok()?;
+                    stack.push(b.into());
+                }
+                Instr::JumpIfFalse(target) => {
+                    if !stack.pop()?.get_as_bool().ok()? {
+                        pc = *target;
+                    }
+                }
+                Instr::Jump(target) => pc = *target,
+                Instr::Unary(op) => {
+                    let arg = stack.pop()?;
+                    stack.push(unary_app(*op, arg, None).ok()?);
+                }
+                Instr::Binary(op) => {
+                    let arg2 = stack.pop()?;
+                    let arg1 = stack.pop()?;
+                    stack.push(self.run_binary(*op, arg1, arg2)?);
+                }
+                Instr::CallExtension { fn_name, arity } => {
+                    let args = stack.split_off(stack.len().checked_sub(*arity)?);
+                    let efunc = self.extensions.func(fn_name).ok()?;
+                    stack.push(concrete(efunc.call(&args).ok()?)?);
+                }
+                Instr::GetAttr(attr) => {
+                    let val = match stack.pop()?.value {
+                        ValueKind::Record(record) => record.get(attr)?.clone(),
+                        ValueKind::Lit(Literal::EntityUID(uid)) => match self.entities.entity(&uid)
+                        {
+                            Dereference::Data(entity) => concrete(entity.get(attr)?.clone())?,
+                            Dereference::NoSuchEntity | Dereference::Residual(_) => return None,
+                        },
+                        _ => return None,
+                    };
+                    stack.push(val);
+                }
+                Instr::HasAttr(attr) => {
+                    let has = match stack.pop()?.value {
+                        ValueKind::Record(record) => record.contains_key(attr),
+                        ValueKind::Lit(Literal::EntityUID(uid)) => match self.entities.entity(&uid)
+                        {
+                            Dereference::Data(entity) => entity.get(attr).is_some(),
+                            Dereference::NoSuchEntity => false,
+                            Dereference::Residual(_) => return None,
+                        },
+                        _ => return None,
+                    };
+                    stack.push(has.into());
+                }
+                Instr::Like(pattern) => {
+                    let matches = pattern.wildcard_match(stack.pop()?.get_as_string().ok()?);
+                    stack.push(matches.into());
+                }
+                Instr::Is(entity_type) => {
+                    let is = stack.pop()?.get_as_entity().ok()?.entity_type() == entity_type;
+                    stack.push(is.into());
+                }
+                Instr::Set(n) => {
+                    let vals = stack.split_off(stack.len().checked_sub(*n)?);
+                    stack.push(Value::set(vals, None));
+                }
+                Instr::InSet(uids) => {
+                    let uid = stack.pop()?.get_as_entity().ok()?.clone();
+                    let entity = match self.entities.entity(&uid) {
+                        Dereference::Data(entity) => Some(entity),
+                        Dereference::NoSuchEntity => None,
+                        Dereference::Residual(_) => return None,
+                    };
+                    stack.push(is_in_entities(&uid, entity, uids).into());
+                }
+                Instr::Record(keys) => {
+                    let vals = stack.split_off(stack.len().checked_sub(keys.len())?);
+                    stack.push(Value::record(keys.iter().cloned().zip(vals), None));
+                }
+                Instr::Interpret(expr) => {
+                    stack.push(concrete(self.partial_interpret(expr, slots).ok()?)?);
+                }
+            }
+        }
+        stack.pop()
+    }
+
+    /// Apply a binary operator to concrete values, as in
+    /// [`Evaluator::partial_interpret()`]
+    fn run_binary(&self, op: BinaryOp, arg1: Value, arg2: Value) -> Option<Value> {
+        match op {
+            BinaryOp::Eq | BinaryOp::Less | BinaryOp::LessEq => {
+                binary_relation(op, &arg1, &arg2, self.extensions).ok()
+            }
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                binary_arith(op, arg1, arg2, None).ok()
+            }
+            BinaryOp::In => {
+                let uid1 = arg1.get_as_entity().ok()?;
+                let entity1 = match self.entities.entity(uid1) {
+                    Dereference::Data(entity) => Some(entity),
+                    Dereference::NoSuchEntity => None,
+                    Dereference::Residual(_) => return None,
+                };
+                concrete(self.eval_in(uid1, entity1, arg2).ok()?)
+            }
+            BinaryOp::Contains => Some(arg1.get_as_set().ok()?.contains(&arg2).into()),
+            BinaryOp::ContainsAll => Some(
+                arg2.get_as_set()
+                    .ok()?
+                    .is_subset(arg1.get_as_set().ok()?)
+                    .into(),
+            ),
+            BinaryOp::ContainsAny => {
+                Some((!arg1.get_as_set().ok()?.is_disjoint(arg2.get_as_set().ok()?)).into())
+            }
+            BinaryOp::GetTag | BinaryOp::HasTag => {
+                let uid = arg1.get_as_entity().ok()?;
+                let tag = arg2.get_as_string().ok()?;
+                match (op, self.entities.entity(uid)) {
+                    (BinaryOp::GetTag, Dereference::Data(entity)) => {
+                        concrete(entity.get_tag(tag)?.clone())
+                    }
+                    (BinaryOp::HasTag, Dereference::Data(entity)) => {
+                        Some(entity.get_tag(tag).is_some().into())
+                    }
+                    (BinaryOp::HasTag, Dereference::NoSuchEntity) => Some(false.into()),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey => {
+                let map = arg1.get_as_record().ok()?;
+                map.get(arg2.get_as_string().ok()?).cloned()
+            }
+            #[cfg(feature = "maps")]
+            BinaryOp::ContainsKey => {
+                let map = arg1.get_as_record().ok()?;
+                Some(map.contains_key(arg2.get_as_string().ok()?).into())
+            }
+        }
+    }
+}
+
+/// The value of `pval`, if it is concrete
+fn concrete(pval: PartialValue) -> Option<Value> {
+    match pval {
+        PartialValue::Value(v) => Some(v),
+        PartialValue::Residual(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evaluator::test::{basic_request, rich_entities};
+    use crate::parser::parse_policyset;
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    /// Conditions which the stack machine evaluates without falling back to
+    /// the interpreter
+    const COMPILED: &[&str] = &[
+        "true && (false || 1 < 2)",
+        "false && 1 + true",
+        "true || 1 + true",
+        "if 1 > 2 then 3 else 4 == 4",
+        "!(2 - 3 * 4 >= -10)",
+        r#"[1, 2, 3].contains(2) && [1, 2].containsAll([1]) && [3].containsAny([3, 4])"#,
+        r#"{a: 1, b: {c: "x"}}.b.c == "x" && {a: 1} has a && !({a: 1} has b)"#,
+        r#""abc" like "a*c" && principal is test_entity_type"#,
+        r#"test_entity_type::"entity_with_attrs".address.town == "barmstadt""#,
+        r#"test_entity_type::"entity_with_attrs" has tags && test_entity_type::"entity_with_tags".hasTag("spoon")"#,
+        r#"test_entity_type::"entity_with_tags".getTag("spoon") == -121"#,
+        r#"test_entity_type::"child" in test_entity_type::"grandparent""#,
+        r#"test_entity_type::"child" in [test_entity_type::"unrelated", test_entity_type::"parent"]"#,
+        r#"test_entity_type::"child" in [test_entity_type::"grandparent"] && !(test_entity_type::"unrelated" in [test_entity_type::"parent"])"#,
+        r#"test_entity_type::"nonexistent" in [test_entity_type::"nonexistent"] && !(principal in [])"#,
+        r#"["a", "b"].contains("b") && !([1, 2].contains("1")) && [[1], 2].contains([1])"#,
+        r#"context.device_properties.os_name == "Windows" && context.cur_time == "03:22:11""#,
+        r#"ip("10.0.0.1").isInRange(ip("10.0.0.0/8")) && decimal("1.5").lessThan(decimal("2.0"))"#,
+    ];
+
+    /// Conditions which error, and so are evaluated by the interpreter
+    const FALLBACK: &[&str] = &[
+        "1 + true",
+        "if 1 then true else false",
+        "true && 3",
+        r#"test_entity_type::"entity_with_attrs".nonexistent"#,
+        r#"test_entity_type::"entity_with_tags".getTag("fork")"#,
+        "9223372036854775807 + 1",
+        "principal in [1, 2]",
+        r#"1 in [test_entity_type::"parent"]"#,
+        r#"decimal("not a decimal").lessThan(decimal("1.0"))"#,
+    ];
+
+    fn policies(conditions: &[&str]) -> PolicySet {
+        let text = conditions
+            .iter()
+            .map(|c| format!("permit(principal, action, resource) when {{ {c} }};"))
+            .join("\n");
+        parse_policyset(&text).expect("policies should parse")
+    }
+
+    #[track_caller]
+    fn assert_same(conditions: &[&str], expect_compiled: bool) {
+        let entities = rich_entities();
+        let eval = Evaluator::new(basic_request(), &entities, Extensions::all_available());
+        let compiled = CompiledPolicySet::new(policies(conditions), Extensions::all_available());
+        for (i, c) in conditions.iter().enumerate() {
+            let id = PolicyID::from_string(format!("policy{i}"));
+            let p = compiled.policies().get(&id).expect("policy should exist");
+            let program = compiled.program(&id).expect("program should exist");
+            assert_eq!(
+                format!("{:?}", eval.evaluate_compiled(p, program)),
+                format!("{:?}", eval.partial_evaluate(p)),
+                "for `{c}`"
+            );
+            assert_eq!(
+                eval.run(program, p.env()).is_some(),
+                expect_compiled,
+                "for `{c}`"
+            );
+        }
+    }
+
+    #[test]
+    fn compiled_matches_interpreter() {
+        assert_same(COMPILED, true);
+    }
+
+    #[test]
+    fn errors_fall_back_to_interpreter() {
+        assert_same(FALLBACK, false);
+    }
+
+    #[test]
+    fn linked_slots() {
+        let mut pset = parse_policyset(
+            r#"permit(principal == ?principal, action, resource in ?resource)
+               when { principal == test_entity_type::"test_principal" };"#,
+        )
+        .expect("template should parse");
+        pset.link(
+            PolicyID::from_string("policy0"),
+            PolicyID::from_string("link"),
+            HashMap::from([
+                (SlotId::principal(), EntityUID::with_eid("test_principal")),
+                (SlotId::resource(), EntityUID::with_eid("test_resource")),
+            ]),
+        )
+        .expect("linking should succeed");
+        let compiled = CompiledPolicySet::new(pset, Extensions::all_available());
+        let entities = rich_entities();
+        let eval = Evaluator::new(basic_request(), &entities, Extensions::all_available());
+        let id = PolicyID::from_string("link");
+        let p = compiled.policies().get(&id).expect("link should exist");
+        let program = compiled.program(&id).expect("program should exist");
+        assert_eq!(
+            eval.run(program, p.env()),
+            Some(Value::from(true)),
+            "{program:?}"
+        );
+        assert_matches!(eval.evaluate_compiled(p, program), Ok(Either::Left(true)));
+    }
+
+    #[test]
+    fn literal_sets_are_built_at_compile_time() {
+        let groups = (0..5000)
+            .map(|i| format!(r#"test_entity_type::"group{i}""#))
+            .join(", ");
+        let strings = (0..5000).map(|i| format!(r#""s{i}""#)).join(", ");
+        let conditions = [
+            format!("principal in [{groups}, test_entity_type::\"test_principal\"]"),
+            format!(r#"[{strings}].contains("s4999")"#),
+        ];
+        let conditions: Vec<&str> = conditions.iter().map(String::as_str).collect();
+        let compiled = CompiledPolicySet::new(policies(&conditions), Extensions::all_available());
+        for program in compiled.programs.values() {
+            // The scope constraints and condition, without an instruction
+            // per set element
+            assert!(program.len() < 20);
+        }
+        assert_same(&conditions, true);
+    }
+}
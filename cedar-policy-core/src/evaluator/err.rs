@@ -45,8 +45,8 @@ pub enum EvaluationError {
     #[diagnostic(transparent)]
     EntityAttrDoesNotExist(#[from] evaluation_errors::EntityAttrDoesNotExistError),
 
-    /// Tried to get an attribute of a (non-entity) record, but that record
-    /// didn't have that attribute
+    /// Tried to get an attribute of a (non-entity) record, or a key of a map,
+    /// but that record or map didn't have that attribute or key
     #[error(transparent)]
     #[diagnostic(transparent)]
     RecordAttrDoesNotExist(#[from] evaluation_errors::RecordAttrDoesNotExistError),
@@ -240,6 +240,7 @@ impl EvaluationError {
     ) -> Self {
         evaluation_errors::RecordAttrDoesNotExistError {
             attr,
+            was_key: false,
             available_attrs: available_attrs
                 .into_iter()
                 .take(TOO_MANY_ATTRS)
@@ -251,6 +252,28 @@ impl EvaluationError {
         .into()
     }
 
+    /// Construct an error for the case where a key of a map does not exist
+    #[cfg(feature = "maps")]
+    pub(crate) fn map_key_does_not_exist<'a>(
+        key: SmolStr,
+        available_keys: impl IntoIterator<Item = &'a SmolStr>,
+        total_keys: usize,
+        source_loc: Option<Loc>,
+    ) -> Self {
+        evaluation_errors::RecordAttrDoesNotExistError {
+            attr: key,
+            was_key: true,
+            available_attrs: available_keys
+                .into_iter()
+                .take(TOO_MANY_ATTRS)
+                .cloned()
+                .collect(),
+            total_attrs: total_keys,
+            source_loc,
+        }
+        .into()
+    }
+
     /// Construct a [`TypeError`] error
     pub(crate) fn type_error(expected: NonEmpty<Type>, actual: &Value) -> Self {
         evaluation_errors::TypeError {
@@ -313,12 +336,14 @@ impl EvaluationError {
     /// Construct a [`FailedExtensionFunctionApplication`] error
     pub(crate) fn failed_extension_function_application(
         extension_name: Name,
+        kind: evaluation_errors::ExtensionErrorKind,
         msg: String,
         source_loc: Option<Loc>,
         advice: Option<String>,
     ) -> Self {
         evaluation_errors::ExtensionFunctionExecutionError {
             extension_name,
+            kind,
             msg,
             advice,
             source_loc,
@@ -452,17 +477,20 @@ pub mod evaluation_errors {
         }
     }
 
-    /// Tried to get an attribute of a (non-entity) record, but that record didn't
-    /// have that attribute
+    /// Tried to get an attribute of a (non-entity) record, or a key of a map,
+    /// but that record or map didn't have that attribute or key
     //
     // CAUTION: this type is publicly exported in `cedar-policy`.
     // Don't make fields `pub`, don't make breaking changes, and use caution
     // when adding public methods.
     #[derive(Debug, PartialEq, Eq, Clone, Error)]
-    #[error("record does not have the attribute `{attr}`")]
+    #[error("{} does not have the {} `{attr}`", if *.was_key { "map" } else { "record" }, if *.was_key { "key" } else { "attribute" })]
     pub struct RecordAttrDoesNotExistError {
-        /// Name of the attribute we tried to access
+        /// Name of the attribute (or key) we tried to access
         pub(crate) attr: SmolStr,
+        /// Whether this was an attempted map key access (`true`) or record
+        /// attribute access (`false`)
+        pub(crate) was_key: bool,
         /// (First five) Available attributes on the record
         pub(crate) available_attrs: Vec<SmolStr>,
         /// The total number of attrs this record has
@@ -475,16 +503,20 @@ pub mod evaluation_errors {
         impl_diagnostic_from_source_loc_opt_field!(source_loc);
 
         fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+            let what = if self.was_key { "keys" } else { "attributes" };
             if self.available_attrs.is_empty() {
-                Some(Box::new("record does not have any attributes"))
+                Some(Box::new(format!(
+                    "{} does not have any {what}",
+                    if self.was_key { "map" } else { "record" }
+                )))
             } else if self.available_attrs.len() == self.total_attrs {
                 Some(Box::new(format!(
-                    "available attributes: {:?}",
+                    "available {what}: {:?}",
                     self.available_attrs
                 )))
             } else {
                 Some(Box::new(format!(
-                    "available attributes: [{}, ... ({} more attributes) ]",
+                    "available {what}: [{}, ... ({} more {what}) ]",
                     self.available_attrs.iter().join(","),
                     self.total_attrs - self.available_attrs.len()
                 )))
@@ -664,6 +696,8 @@ pub mod evaluation_errors {
     pub struct ExtensionFunctionExecutionError {
         /// Name of the extension throwing the error
         pub(crate) extension_name: Name,
+        /// Kind of the error
+        pub(crate) kind: ExtensionErrorKind,
         /// Error message from the extension
         pub(crate) msg: String,
         /// Optional advice for how to fix this error
@@ -685,6 +719,64 @@ pub mod evaluation_errors {
         pub fn extension_name(&self) -> String {
             self.extension_name.to_string()
         }
+
+        /// Get the kind of this error, e.g., to tell errors caused by
+        /// malformed data apart from errors in policies
+        pub fn kind(&self) -> ExtensionErrorKind {
+            self.kind
+        }
+    }
+
+    /// The kinds of [`ExtensionFunctionExecutionError`]. Each kind has a
+    /// `snake_case` name (e.g., `parse`), which is stable and suitable for
+    /// aggregating or routing errors.
+    ///
+    /// Calls to an extension function with the wrong number of arguments are
+    /// reported as [`WrongNumArgumentsError`]s instead, and calls to a builtin
+    /// extension function with arguments of the wrong type as [`TypeError`]s.
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    #[non_exhaustive]
+    pub enum ExtensionErrorKind {
+        /// A string couldn't be parsed as a value of an extension type, e.g.,
+        /// `ip("not an ip")`. This usually means that data is malformed, as the
+        /// validator checks literal constructor arguments.
+        Parse,
+        /// A value or result is outside the range an extension function
+        /// supports, e.g., an overflowing `decimal` or `datetime` operation
+        OutOfRange,
+        /// An argument is well-formed but not valid for the function, e.g.,
+        /// `.asLong()` on an empty `optional`
+        InvalidArgument,
+        /// An argument of a custom extension function doesn't have the type
+        /// the function declares
+        WrongArgumentType,
+        /// A custom extension function returned a value which doesn't have the
+        /// type it declares, which is a bug in the function
+        InvalidReturnValue,
+        /// A custom extension function returned an error
+        Custom,
+    }
+
+    impl ExtensionErrorKind {
+        /// The stable `snake_case` name of this kind
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                Self::Parse => "parse",
+                Self::OutOfRange => "out_of_range",
+                Self::InvalidArgument => "invalid_argument",
+                Self::WrongArgumentType => "wrong_argument_type",
+                Self::InvalidReturnValue => "invalid_return_value",
+                Self::Custom => "custom",
+            }
+        }
+    }
+
+    impl std::fmt::Display for ExtensionErrorKind {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.as_str())
+        }
     }
 
     /// This error is raised if an expression contains unknowns and cannot be
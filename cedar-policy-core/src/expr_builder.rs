@@ -190,6 +190,14 @@ pub trait ExprBuilder: Clone {
     /// Create a 'hasTag' expression.
     fn has_tag(self, expr: Self::Expr, tag: Self::Expr) -> Self::Expr;
 
+    /// Create a 'get' expression for a map.
+    #[cfg(feature = "maps")]
+    fn get_key(self, expr: Self::Expr, key: Self::Expr) -> Self::Expr;
+
+    /// Create a 'containsKey' expression for a map.
+    #[cfg(feature = "maps")]
+    fn contains_key(self, expr: Self::Expr, key: Self::Expr) -> Self::Expr;
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     fn set(self, exprs: impl IntoIterator<Item = Self::Expr>) -> Self::Expr;
 
@@ -358,6 +366,10 @@ pub trait ExprBuilder: Clone {
             BinaryOp::ContainsAny => self.contains_any(arg1, arg2),
             BinaryOp::GetTag => self.get_tag(arg1, arg2),
             BinaryOp::HasTag => self.has_tag(arg1, arg2),
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey => self.get_key(arg1, arg2),
+            #[cfg(feature = "maps")]
+            BinaryOp::ContainsKey => self.contains_key(arg1, arg2),
         }
     }
 
@@ -24,10 +24,34 @@ pub mod decimal;
 
 #[cfg(feature = "datetime")]
 pub mod datetime;
+
+#[cfg(feature = "strings")]
+pub mod strings;
+
+#[cfg(feature = "bytes")]
+pub mod bytes;
+
+#[cfg(feature = "lookup")]
+pub mod lookup;
+
+#[cfg(feature = "geo")]
+pub mod geo;
+
+#[cfg(feature = "identifiers")]
+pub mod identifiers;
+
+#[cfg(feature = "mediatype")]
+pub mod mediatype;
+
+#[cfg(feature = "prf")]
+pub mod prf;
+#[cfg(feature = "regions")]
+pub mod regions;
+
 pub mod partial_evaluation;
 
 use std::collections::{HashMap, HashSet};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
 
 use crate::ast::{CallStyle, Extension, ExtensionFunction, Name, UnreservedId};
 use crate::entities::SchemaType;
@@ -41,7 +65,8 @@ use thiserror::Error;
 use self::extension_function_lookup_errors::FuncDoesNotExistError;
 use self::extension_initialization_errors::FuncMultiplyDefinedError;
 
-static ALL_AVAILABLE_EXTENSION_OBJECTS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
+/// All builtin extensions enabled by crate features
+fn builtin_extensions() -> Vec<Extension> {
     vec![
         #[cfg(feature = "ipaddr")]
         ipaddr::extension(),
@@ -49,22 +74,44 @@ static ALL_AVAILABLE_EXTENSION_OBJECTS: LazyLock<Vec<Extension>> = LazyLock::new
         decimal::extension(),
         #[cfg(feature = "datetime")]
         datetime::extension(),
+        #[cfg(feature = "strings")]
+        strings::extension(),
+        #[cfg(feature = "bytes")]
+        bytes::extension(),
+        #[cfg(feature = "lookup")]
+        lookup::extension(),
+        #[cfg(feature = "geo")]
+        geo::extension(),
+        #[cfg(feature = "identifiers")]
+        identifiers::extension(),
+        #[cfg(feature = "mediatype")]
+        mediatype::extension(),
         #[cfg(feature = "partial-eval")]
         partial_evaluation::extension(),
     ]
-});
+}
+
+/// All builtin extensions, which every [`Extensions`] with custom extensions
+/// extends
+static BUILTIN_EXTENSION_OBJECTS: LazyLock<Vec<Extension>> = LazyLock::new(builtin_extensions);
 
-static ALL_AVAILABLE_EXTENSIONS: LazyLock<Extensions<'static>> =
-    LazyLock::new(Extensions::build_all_available);
+/// Custom extensions registered with [`register_custom_extension()`] which
+/// have not yet been moved into [`ALL_AVAILABLE_EXTENSIONS`]. This is `None`
+/// once that has happened, at which point no further extensions can be
+/// registered.
+static PENDING_CUSTOM_EXTENSIONS: Mutex<Option<Vec<Extension>>> = Mutex::new(Some(Vec::new()));
+
+static ALL_AVAILABLE_EXTENSIONS: LazyLock<Arc<Extensions<'static>>> =
+    LazyLock::new(|| Arc::new(Extensions::build_all_available()));
 
 static EXTENSIONS_NONE: LazyLock<Extensions<'static>> = LazyLock::new(|| Extensions {
     extensions: &[],
+    custom: Vec::new(),
     functions: HashMap::new(),
     single_arg_constructors: HashMap::new(),
+    styles: Arc::default(),
 });
 
-static EXTENSION_STYLES: LazyLock<ExtStyles<'static>> = LazyLock::new(ExtStyles::load);
-
 /// Holds data on all the Extensions which are active for a given evaluation.
 ///
 /// This structure is intentionally not `Clone` because we can use it entirely
@@ -73,6 +120,9 @@ static EXTENSION_STYLES: LazyLock<ExtStyles<'static>> = LazyLock::new(ExtStyles:
 pub struct Extensions<'a> {
     /// the actual extensions
     extensions: &'a [Extension],
+    /// Custom extensions owned by this object, in addition to `extensions`.
+    /// See [`Extensions::with_custom_extensions()`].
+    custom: Vec<Extension>,
     /// All extension functions, collected from every extension used to
     /// construct this object.  Built ahead of time so that we know during
     /// extension function lookup that at most one extension function exists
@@ -82,17 +132,34 @@ pub struct Extensions<'a> {
     /// return type. Built ahead of time so that we know each constructor has
     /// a unique return type.
     single_arg_constructors: HashMap<&'a SchemaType, &'a ExtensionFunction>,
+    /// Call styles of all extension functions in `extensions` and `custom`,
+    /// which the parser uses inside of [`Extensions::scope()`]
+    styles: Arc<ExtStyles>,
 }
 
 impl Extensions<'static> {
     /// Get a new `Extensions` containing data on all the available extensions.
     fn build_all_available() -> Extensions<'static> {
+        let custom = PENDING_CUSTOM_EXTENSIONS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
         #[expect(
             clippy::expect_used,
-            reason = "Builtin extensions define functions/constructors only once. Also tested by many different test cases."
+            reason = "Builtin extensions define functions/constructors only once. Also tested by many different test cases. Custom extensions are checked against these in `register_custom_extension()`."
         )]
-        Self::specific_extensions(&ALL_AVAILABLE_EXTENSION_OBJECTS)
-            .expect("Default extensions should never error on initialization")
+        let builtins = Self::specific_extensions(&BUILTIN_EXTENSION_OBJECTS)
+            .expect("Default extensions should never error on initialization");
+        Extensions {
+            styles: Arc::new(ExtStyles::new(
+                builtins
+                    .all_funcs()
+                    .chain(custom.iter().flat_map(|e| e.funcs())),
+            )),
+            custom,
+            ..builtins
+        }
     }
 
     /// An [`Extensions`] object with static lifetime contain all available extensions.
@@ -100,6 +167,48 @@ impl Extensions<'static> {
         &ALL_AVAILABLE_EXTENSIONS
     }
 
+    /// Like [`Extensions::all_available()`], but shared, for holding
+    /// alongside `Extensions` built with [`Extensions::with_custom_extensions()`]
+    pub fn all_available_shared() -> Arc<Extensions<'static>> {
+        Arc::clone(&ALL_AVAILABLE_EXTENSIONS)
+    }
+
+    /// Get a new `Extensions` with all builtin extensions and the given
+    /// custom extensions enabled, independently of any custom extensions
+    /// registered with [`register_custom_extension()`].
+    ///
+    /// Unlike registration, this can happen at any time, and different
+    /// `Extensions` can have different custom extensions, e.g., one per
+    /// tenant. The custom extensions must satisfy the same restrictions as
+    /// for [`register_custom_extension()`]. Policies calling the custom
+    /// functions are parsed inside of [`Extensions::scope()`]. Calling a
+    /// function missing from the `Extensions` a policy is evaluated or
+    /// validated with is an error, as usual.
+    pub fn with_custom_extensions(
+        custom: Vec<Extension>,
+    ) -> std::result::Result<Extensions<'static>, CustomExtensionError> {
+        let builtins: &'static [Extension] = &BUILTIN_EXTENSION_OBJECTS;
+        let builtin_ext_types: HashSet<&Name> =
+            builtins.iter().flat_map(|e| e.ext_types()).collect();
+        for func in custom.iter().flat_map(|e| e.funcs()) {
+            check_custom_func(func, &builtin_ext_types)?;
+        }
+        Extensions::index_funcs(builtins.iter().chain(custom.iter()))?;
+        let builtins = Self::specific_extensions(builtins)?;
+        // Custom functions are looked up separately in `func()`. They can't be
+        // single-argument constructors, as those construct builtin extension
+        // types, whose constructors they would conflict with.
+        Ok(Extensions {
+            styles: Arc::new(ExtStyles::new(
+                builtins
+                    .all_funcs()
+                    .chain(custom.iter().flat_map(|e| e.funcs())),
+            )),
+            custom,
+            ..builtins
+        })
+    }
+
     /// Get a new `Extensions` with no extensions enabled.
     pub fn none() -> &'static Extensions<'static> {
         &EXTENSIONS_NONE
@@ -109,18 +218,42 @@ impl Extensions<'static> {
 impl<'a> Extensions<'a> {
     /// Obtain the non-empty vector of types supporting operator overloading
     pub fn types_with_operator_overloading(&self) -> impl Iterator<Item = &Name> + '_ {
-        self.extensions
-            .iter()
+        self.extensions()
             .flat_map(|ext| ext.types_with_operator_overloading())
     }
     /// Get a new `Extensions` with these specific extensions enabled.
     pub fn specific_extensions(
         extensions: &'a [Extension],
     ) -> std::result::Result<Extensions<'a>, ExtensionInitializationError> {
+        let (functions, single_arg_constructors) = Self::index_funcs(extensions.iter())?;
+        Ok(Extensions {
+            extensions,
+            custom: Vec::new(),
+            functions,
+            single_arg_constructors,
+            styles: Arc::new(ExtStyles::new(extensions.iter().flat_map(|e| e.funcs()))),
+        })
+    }
+
+    /// Build the maps of functions and single-argument constructors used by
+    /// [`Extensions`], returning an error if any two functions conflict.
+    #[expect(
+        clippy::type_complexity,
+        reason = "the two maps are exactly the fields of `Extensions`"
+    )]
+    fn index_funcs(
+        extensions: impl Iterator<Item = &'a Extension> + Clone,
+    ) -> std::result::Result<
+        (
+            HashMap<&'a Name, &'a ExtensionFunction>,
+            HashMap<&'a SchemaType, &'a ExtensionFunction>,
+        ),
+        ExtensionInitializationError,
+    > {
         // Build functions map, ensuring that no functions share the same name.
         let functions = util::collect_no_duplicates(
             extensions
-                .iter()
+                .clone()
                 .flat_map(|e| e.funcs())
                 .map(|f| (f.name(), f)),
         )
@@ -129,7 +262,6 @@ impl<'a> Extensions<'a> {
         // Build the constructor map, ensuring that no constructors share a return type
         let single_arg_constructors = util::collect_no_duplicates(
             extensions
-                .iter()
                 .flat_map(|e| e.funcs())
                 .filter(|f| f.is_single_arg_constructor())
                 .filter_map(|f| f.return_type().map(|return_type| (return_type, f))),
@@ -138,16 +270,30 @@ impl<'a> Extensions<'a> {
             return_type: Box::new(return_type.clone()),
         })?;
 
-        Ok(Extensions {
-            extensions,
-            functions,
-            single_arg_constructors,
-        })
+        Ok((functions, single_arg_constructors))
+    }
+
+    /// Iterate over the active extensions.
+    pub fn extensions(&self) -> impl Iterator<Item = &Extension> {
+        self.extensions.iter().chain(&self.custom)
+    }
+
+    /// The custom extensions active in addition to the builtin ones, i.e.,
+    /// those added by [`Extensions::with_custom_extensions()`], or registered
+    /// with [`register_custom_extension()`] for [`Extensions::all_available()`]
+    pub fn custom_extensions(&self) -> &[Extension] {
+        &self.custom
+    }
+
+    /// The call styles of the functions of these extensions, which the
+    /// parser uses to decide which calls are valid
+    pub(crate) fn styles(&self) -> &ExtStyles {
+        &self.styles
     }
 
     /// Get the names of all active extensions.
     pub fn ext_names(&self) -> impl Iterator<Item = &Name> {
-        self.extensions.iter().map(|ext| ext.name())
+        self.extensions().map(|ext| ext.name())
     }
 
     /// Get all extension type names declared by active extensions.
@@ -155,7 +301,7 @@ impl<'a> Extensions<'a> {
     /// (More specifically, all extension type names such that any function in
     /// an active extension could produce a value of that extension type.)
     pub fn ext_types(&self) -> impl Iterator<Item = &Name> {
-        self.extensions.iter().flat_map(|ext| ext.ext_types())
+        self.extensions().flat_map(|ext| ext.ext_types())
     }
 
     /// Get the extension function with the given name, from these extensions.
@@ -165,20 +311,24 @@ impl<'a> Extensions<'a> {
         &self,
         name: &Name,
     ) -> std::result::Result<&ExtensionFunction, ExtensionFunctionLookupError> {
-        self.functions.get(name).copied().ok_or_else(|| {
-            FuncDoesNotExistError {
-                name: name.clone(),
-                source_loc: name.loc().cloned(),
-            }
-            .into()
-        })
+        self.functions
+            .get(name)
+            .copied()
+            .or_else(|| self.custom.iter().find_map(|ext| ext.get_func(name)))
+            .ok_or_else(|| {
+                FuncDoesNotExistError {
+                    name: name.clone(),
+                    source_loc: name.loc().cloned(),
+                }
+                .into()
+            })
     }
 
     /// Iterate over all extension functions defined by all of these extensions.
     ///
     /// No guarantee that this list won't have duplicates or repeated names.
-    pub(crate) fn all_funcs(&self) -> impl Iterator<Item = &'a ExtensionFunction> {
-        self.extensions.iter().flat_map(|ext| ext.funcs())
+    pub(crate) fn all_funcs(&self) -> impl Iterator<Item = &ExtensionFunction> {
+        self.extensions().flat_map(|ext| ext.funcs())
     }
 
     /// Lookup a single-argument constructor by its return type
@@ -190,9 +340,176 @@ impl<'a> Extensions<'a> {
     }
 }
 
-/// Errors occurring while initializing extensions. There are internal errors, so
-/// this enum should not become part of the public API unless we publicly expose
-/// user-defined extension function.
+/// Names which the parser handles itself, and which therefore can't be used
+/// for (unqualified) custom extension functions or methods
+const RESERVED_FUNC_NAMES: &[&str] = &[
+    "contains",
+    "containsAll",
+    "containsAny",
+    "isEmpty",
+    "getTag",
+    "hasTag",
+    "get",
+    "containsKey",
+    "principal",
+    "action",
+    "resource",
+    "context",
+];
+
+/// Register a custom extension, making its functions available to the parser,
+/// evaluator, and validator alongside the builtin extensions, through
+/// [`Extensions::all_available()`].
+///
+/// Registration is process-wide, because the parser resolves extension
+/// function names with [`Extensions::all_available()`] outside of an
+/// [`Extensions::scope()`]. It must happen before `all_available()` is first
+/// used, which includes the first time any policy or expression is parsed;
+/// after that this returns [`CustomExtensionError::AlreadyInitialized`].
+/// [`Extensions::with_custom_extensions()`] is the alternative for custom
+/// extensions which are only needed by some evaluators and validators, or are
+/// only known later.
+///
+/// The functions of `extension` should be constructed with
+/// [`ExtensionFunction::custom()`]. They must have a fixed number of arguments
+/// and may only take and return values of type `Bool`, `Long`, `String`, an
+/// entity type, a builtin extension type, or a set of these. Methods must take
+/// at least one argument (the receiver) and have an unqualified name. No
+/// function may share a name with a builtin function or a function in another
+/// custom extension.
+pub fn register_custom_extension(
+    extension: Extension,
+) -> std::result::Result<(), CustomExtensionError> {
+    let builtins: &[Extension] = &BUILTIN_EXTENSION_OBJECTS;
+    let builtin_ext_types: HashSet<&Name> = builtins.iter().flat_map(|e| e.ext_types()).collect();
+    for func in extension.funcs() {
+        check_custom_func(func, &builtin_ext_types)?;
+    }
+
+    let mut guard = PENDING_CUSTOM_EXTENSIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let Some(pending) = guard.as_mut() else {
+        return Err(CustomExtensionError::AlreadyInitialized);
+    };
+    Extensions::index_funcs(
+        builtins
+            .iter()
+            .chain(pending.iter())
+            .chain(std::iter::once(&extension)),
+    )?;
+    pending.push(extension);
+    drop(guard);
+    Ok(())
+}
+
+/// Check the restrictions on a single custom extension function documented on
+/// [`register_custom_extension()`]
+fn check_custom_func(
+    func: &ExtensionFunction,
+    builtin_ext_types: &HashSet<&Name>,
+) -> std::result::Result<(), CustomExtensionError> {
+    let name = func.name();
+    if name.is_unqualified() && RESERVED_FUNC_NAMES.contains(&name.basename().as_ref()) {
+        return Err(CustomExtensionError::ReservedName { name: name.clone() });
+    }
+    if func.style() == CallStyle::MethodStyle {
+        if !name.is_unqualified() {
+            return Err(CustomExtensionError::QualifiedMethod { name: name.clone() });
+        }
+        if func.arg_types().is_empty() {
+            return Err(CustomExtensionError::MethodWithoutReceiver { name: name.clone() });
+        }
+    }
+    let Some(return_type) = func.return_type() else {
+        return Err(CustomExtensionError::UnsupportedSignature { name: name.clone() });
+    };
+    if func.is_variadic() {
+        return Err(CustomExtensionError::UnsupportedSignature { name: name.clone() });
+    }
+    fn is_supported(ty: &SchemaType, builtin_ext_types: &HashSet<&Name>) -> bool {
+        match ty {
+            SchemaType::Bool
+            | SchemaType::Long
+            | SchemaType::String
+            | SchemaType::Entity { .. } => true,
+            SchemaType::Set { element_ty } => is_supported(element_ty, builtin_ext_types),
+            SchemaType::Extension { name } => builtin_ext_types.contains(name),
+            _ => false,
+        }
+    }
+    match func
+        .arg_types()
+        .iter()
+        .chain(std::iter::once(return_type))
+        .find(|ty| !is_supported(ty, builtin_ext_types))
+    {
+        Some(ty) => Err(CustomExtensionError::UnsupportedType {
+            name: name.clone(),
+            ty: ty.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Errors occurring when registering a custom extension with
+/// [`register_custom_extension()`]
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make breaking changes, and use caution when adding public methods.
+#[derive(Diagnostic, Debug, PartialEq, Eq, Clone, Error)]
+#[non_exhaustive]
+pub enum CustomExtensionError {
+    /// The extensions were already initialized, so no more can be registered
+    #[error("custom extensions must be registered before any extensions are used")]
+    #[diagnostic(help(
+        "register custom extensions when your program starts, before parsing, evaluating, or validating any policies"
+    ))]
+    AlreadyInitialized,
+    /// A function in the extension conflicts with an existing function
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Conflict(#[from] ExtensionInitializationError),
+    /// A function has a name which is reserved for builtin operations
+    #[error("`{name}` is reserved and cannot be the name of a custom extension function")]
+    ReservedName {
+        /// Name of the function
+        name: Name,
+    },
+    /// A method has a qualified name
+    #[error("custom extension method `{name}` must not be qualified by a namespace")]
+    QualifiedMethod {
+        /// Name of the method
+        name: Name,
+    },
+    /// A method takes no arguments, so there's nothing to call it on
+    #[error("custom extension method `{name}` must take at least one argument")]
+    #[diagnostic(help("the first argument is the value the method is called on"))]
+    MethodWithoutReceiver {
+        /// Name of the method
+        name: Name,
+    },
+    /// A function is variadic or has no return type
+    #[error("custom extension function `{name}` must take a fixed number of arguments and have a return type")]
+    UnsupportedSignature {
+        /// Name of the function
+        name: Name,
+    },
+    /// A function takes or returns a type which custom functions can't use
+    #[error("custom extension function `{name}` cannot take or return values of type {ty}")]
+    #[diagnostic(help(
+        "custom extension functions may only use `Bool`, `Long`, `String`, entity types, builtin extension types, and sets of these"
+    ))]
+    UnsupportedType {
+        /// Name of the function
+        name: Name,
+        /// The unsupported type
+        ty: SchemaType,
+    },
+}
+
+/// Errors occurring while initializing extensions, or registering a custom
+/// extension whose functions conflict with existing ones.
 #[derive(Diagnostic, Debug, PartialEq, Eq, Clone, Error)]
 pub enum ExtensionInitializationError {
     /// An extension function was defined by multiple extensions.
@@ -295,63 +612,61 @@ pub mod extension_function_lookup_errors {
 pub type Result<T> = std::result::Result<T, ExtensionFunctionLookupError>;
 
 /// Extension functions have different callstyles. This stores information about the expected
-/// callstyle for each function. Provided static methods can be used to check the expected syntax
+/// callstyle for each function. Provided methods can be used to check the expected syntax
 /// of a given function call.
-#[derive(Debug)]
-pub(crate) struct ExtStyles<'a> {
+#[derive(Debug, Default)]
+pub(crate) struct ExtStyles {
     /// All extension function names (just functions, not methods), as `Name`s
-    functions: HashSet<&'a Name>,
+    functions: HashSet<Name>,
     /// All extension function methods. `UnreservedId` is appropriate because methods cannot be namespaced.
     methods: HashSet<UnreservedId>,
-    /// All extension function and method names (both qualified and unqualified), in their string (`Display`) form
-    functions_and_methods_as_str: HashSet<SmolStr>,
+    /// All extension function and method names (both qualified and unqualified), in their string (`Display`) form,
+    /// with their call style
+    styles_by_str: HashMap<SmolStr, CallStyle>,
 }
 
-impl ExtStyles<'static> {
-    fn load() -> ExtStyles<'static> {
-        let mut functions = HashSet::new();
-        let mut methods = HashSet::new();
-        let mut functions_and_methods_as_str = HashSet::new();
-        for func in crate::extensions::Extensions::all_available().all_funcs() {
-            functions_and_methods_as_str.insert(func.name().to_smolstr());
-            match func.style() {
-                CallStyle::FunctionStyle => {
-                    functions.insert(func.name());
-                }
-                CallStyle::MethodStyle => {
-                    debug_assert!(func.name().is_unqualified());
-                    methods.insert(func.name().basename());
-                }
-            };
-        }
-        ExtStyles {
-            functions,
-            methods,
-            functions_and_methods_as_str,
+impl ExtStyles {
+    fn new<'a>(funcs: impl IntoIterator<Item = &'a ExtensionFunction>) -> ExtStyles {
+        let mut styles = ExtStyles::default();
+        for func in funcs {
+            styles.insert(func);
         }
+        styles
+    }
+
+    fn insert(&mut self, func: &ExtensionFunction) {
+        self.styles_by_str
+            .insert(func.name().to_smolstr(), func.style());
+        match func.style() {
+            CallStyle::FunctionStyle => {
+                self.functions.insert(func.name().clone());
+            }
+            CallStyle::MethodStyle => {
+                debug_assert!(func.name().is_unqualified());
+                self.methods.insert(func.name().basename());
+            }
+        };
     }
 
     /// If this [`UnreservedId`] is a known method name
-    pub(crate) fn is_method(id: &UnreservedId) -> bool {
-        EXTENSION_STYLES.methods.contains(id)
+    pub(crate) fn is_method(&self, id: &UnreservedId) -> bool {
+        self.methods.contains(id)
     }
 
     /// If this [`Name`] is a known function name
-    pub(crate) fn is_function(id: &Name) -> bool {
-        EXTENSION_STYLES.functions.contains(id)
+    pub(crate) fn is_function(&self, id: &Name) -> bool {
+        self.functions.contains(id)
     }
 
     /// If this [`Name`] is a known extension function/method name or not
-    pub(crate) fn is_known_extension_func_name(name: &Name) -> bool {
-        Self::is_function(name) || (name.0.path.is_empty() && Self::is_method(&name.basename()))
+    pub(crate) fn is_known_extension_func_name(&self, name: &Name) -> bool {
+        self.is_function(name) || (name.0.path.is_empty() && self.is_method(&name.basename()))
     }
 
-    /// If this [`SmolStr`] is a known extension function/method name or not. Works
-    /// with both qualified and unqualified `s`. (As of this writing, there are no
-    /// qualified extension function/method names, so qualified `s` always results
-    /// in `false`.)
-    pub(crate) fn is_known_extension_func_str(s: &SmolStr) -> bool {
-        EXTENSION_STYLES.functions_and_methods_as_str.contains(s)
+    /// The call style of the extension function/method with this name, in
+    /// its string (`Display`) form, if it is known
+    pub(crate) fn style(&self, s: &str) -> Option<CallStyle> {
+        self.styles_by_str.get(s).copied()
     }
 
     fn suggest<I, T>(key: &str, choices: I) -> Option<String>
@@ -360,7 +675,10 @@ impl ExtStyles<'static> {
         T: ToString,
     {
         const SUGGESTION_EXTENSION_MAX_DISTANCE: usize = 3;
-        let choice_strings: Vec<String> = choices.into_iter().map(|c| c.to_string()).collect();
+        // sorted so that ties between equally close names are broken the same
+        // way every time, rather than by hash set iteration order
+        let mut choice_strings: Vec<String> = choices.into_iter().map(|c| c.to_string()).collect();
+        choice_strings.sort_unstable();
         let suggestion = fuzzy_search_limited(
             key,
             choice_strings.as_slice(),
@@ -370,13 +688,13 @@ impl ExtStyles<'static> {
     }
 
     /// When a method call was expected, suggest a method name matching the provided name
-    pub(crate) fn suggest_method(name: &UnreservedId) -> Option<String> {
-        Self::suggest(name.as_ref(), &EXTENSION_STYLES.methods)
+    pub(crate) fn suggest_method(&self, name: &UnreservedId) -> Option<String> {
+        Self::suggest(name.as_ref(), &self.methods)
     }
 
     /// When a function call was expected, suggest a function name matching the provided name
-    pub(crate) fn suggest_function(name: &Name) -> Option<String> {
-        Self::suggest(&name.to_string(), &EXTENSION_STYLES.functions)
+    pub(crate) fn suggest_function(&self, name: &Name) -> Option<String> {
+        Self::suggest(&name.to_string(), &self.functions)
     }
 }
 
@@ -429,4 +747,15 @@ mod test {
         let dedup_names: HashSet<_> = all_names.iter().collect();
         assert_eq!(all_names.len(), dedup_names.len());
     }
+
+    #[test]
+    fn extension_functions_are_documented() {
+        // Functions without a return type are internal, like `unknown()`
+        for func in Extensions::all_available()
+            .all_funcs()
+            .filter(|f| f.return_type().is_some())
+        {
+            assert!(func.doc().is_some(), "`{}` has no doc", func.name());
+        }
+    }
 }
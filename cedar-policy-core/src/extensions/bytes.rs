@@ -0,0 +1,283 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'bytes' extension, which provides an
+//! extension type for opaque byte strings such as checksums and key
+//! fingerprints.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue, Literal, Name,
+    RepresentableExtensionValue, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::sync::Arc;
+
+/// Help text describing the valid format of `bytes` constructor arguments
+const VALID_BYTES_HELP: &str =
+    "byte strings are written in standard base64 with padding, e.g., `bytes(\"3q2+7w==\")`";
+
+/// Help message to display when a String was provided where a `bytes` value
+/// was expected
+const ADVICE_MSG: &str = "maybe you forgot to apply the `bytes` constructor?";
+
+/// An opaque byte string. Two `Bytes` are equal if they contain the same
+/// bytes, and byte strings are ordered lexicographically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Bytes(Vec<u8>);
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static BYTES_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("bytes").expect("should be a valid identifier")
+    });
+    pub static BYTE_LENGTH: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("byteLength").expect("should be a valid identifier")
+    });
+}
+
+impl Bytes {
+    /// The Cedar typename of `bytes` values
+    fn typename() -> Name {
+        constants::BYTES_FROM_STR_NAME.clone()
+    }
+
+    /// Decode a byte string from base64. Only the canonical encoding of each
+    /// byte string is accepted, so that equal byte strings are always written
+    /// the same way.
+    fn from_base64(s: &str) -> Option<Self> {
+        STANDARD.decode(s).ok().map(Self)
+    }
+}
+
+impl ExtensionValue for Bytes {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: Option<String>,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        advice,
+    )
+}
+
+/// Cedar function that constructs a `bytes` Cedar type from a base64 Cedar
+/// string
+fn bytes_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let bytes = Bytes::from_base64(s.as_str()).ok_or_else(|| {
+        extension_err(
+            &constants::BYTES_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a valid base64 encoding of a byte string"),
+            Some(VALID_BYTES_HELP.into()),
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(bytes),
+        constants::BYTES_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Check that `v` is a `bytes` type and, if it is, return the wrapped value
+fn as_bytes(v: &Value) -> Result<&Bytes, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == Bytes::typename() => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let b = ev
+                .value()
+                .as_any()
+                .downcast_ref::<Bytes>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(b)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension {
+                    name: Bytes::typename(),
+                },
+                v,
+                ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: Bytes::typename(),
+            },
+            v,
+        )),
+    }
+}
+
+/// Cedar function that returns the number of bytes in a `bytes` value
+fn byte_length(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let bytes = as_bytes(arg)?;
+    let len = i64::try_from(bytes.0.len()).map_err(|_| {
+        extension_err(
+            &constants::BYTE_LENGTH,
+            ExtensionErrorKind::OutOfRange,
+            "byte string length does not fit in a `Long`",
+            None,
+        )
+    })?;
+    Ok(Value::from(len).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let bytes_type = SchemaType::Extension {
+        name: Bytes::typename(),
+    };
+    Extension::new(
+        constants::BYTES_FROM_STR_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::BYTES_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(bytes_from_str),
+                bytes_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Decodes a standard base64 string such as `"3q2+7w=="` as a byte string."#),
+            ExtensionFunction::unary(
+                constants::BYTE_LENGTH.clone(),
+                CallStyle::MethodStyle,
+                Box::new(byte_length),
+                SchemaType::Long,
+                bytes_type,
+            )
+            .with_doc("Returns the number of bytes in the receiver."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[test]
+    fn bytes_methods() {
+        for (src, expected) in [
+            (r#"bytes("3q2+7w==").byteLength()"#, Value::from(4)),
+            (r#"bytes("").byteLength()"#, Value::from(0)),
+            (r#"bytes("AA==").byteLength()"#, Value::from(1)),
+            (
+                r#"bytes("3q2+7w==") == bytes("3q2+7w==")"#,
+                Value::from(true),
+            ),
+            (
+                r#"bytes("3q2+7w==") == bytes("3q2+7g==")"#,
+                Value::from(false),
+            ),
+            (r#"bytes("") == bytes("")"#, Value::from(true)),
+            (r#"bytes("AA==") == "AA==""#, Value::from(false)),
+            (
+                r#"[bytes("3q2+7w=="), bytes("AA==")].contains(bytes("AA=="))"#,
+                Value::from(true),
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(expected), "{src}");
+        }
+    }
+
+    #[test]
+    fn bytes_constructor_errors() {
+        for src in [
+            // missing padding
+            r#"bytes("3q2+7w")"#,
+            // non-zero trailing bits, so not the canonical encoding of any byte string
+            r#"bytes("3q2+7x==")"#,
+            // URL-safe alphabet
+            r#"bytes("3q2-7w==")"#,
+            r#"bytes("not base64!")"#,
+            r#"bytes(" 3q2+7w==")"#,
+        ] {
+            assert_matches!(
+                eval(src),
+                Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                    extension_name,
+                    advice,
+                    ..
+                })) => {
+                    assert_eq!(extension_name, *constants::BYTES_FROM_STR_NAME, "{src}");
+                    assert_eq!(advice.as_deref(), Some(VALID_BYTES_HELP), "{src}");
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn bytes_type_errors() {
+        assert_matches!(
+            eval(r#""3q2+7w==".byteLength()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, advice, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: Bytes::typename() }]);
+                assert_eq!(advice.as_deref(), Some(ADVICE_MSG));
+            }
+        );
+        assert_matches!(
+            eval(r#"bytes(1)"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::String]);
+            }
+        );
+        // `byteLength` is a method, not a function
+        parse_expr(r#"byteLength(bytes("AA=="))"#).expect_err("should fail");
+    }
+}
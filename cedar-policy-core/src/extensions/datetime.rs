@@ -28,7 +28,7 @@ use crate::{
         Name, RepresentableExtensionValue, RestrictedExpr, Type, Value, ValueKind,
     },
     entities::SchemaType,
-    evaluator::{self, EvaluationError},
+    evaluator::{self, evaluation_errors::ExtensionErrorKind, EvaluationError},
 };
 
 const DATETIME_EXTENSION_NAME: &str = "datetime";
@@ -70,6 +70,12 @@ pub(crate) mod constants {
     pub static TO_TIME_NAME: LazyLock<Name> = LazyLock::new(|| {
         Name::parse_unqualified_name("toTime").expect("should be a valid identifier")
     });
+    pub static DAY_OF_WEEK_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("dayOfWeek").expect("should be a valid identifier")
+    });
+    pub static HOUR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("hour").expect("should be a valid identifier")
+    });
     pub static TO_MILLISECONDS_NAME: LazyLock<Name> = LazyLock::new(|| {
         Name::parse_unqualified_name("toMilliseconds").expect("should be a valid identifier")
     });
@@ -99,7 +105,8 @@ pub(crate) mod constants {
         LazyLock::new(|| Regex::new(r"^(\.([0-9]{3}))?(Z|((\+|-)([0-9]{2})([0-9]{2})))$").unwrap());
 }
 
-// The `datetime` type, represented internally as an `i64`.
+/// The `datetime` type, represented internally as an `i64`.
+/// Datetimes are ordered chronologically.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 struct DateTime {
     // The number of non-leap milliseconds from the Unix epoch
@@ -109,10 +116,12 @@ struct DateTime {
 fn extension_err(
     msg: String,
     extension_name: &crate::ast::Name,
+    kind: ExtensionErrorKind,
     advice: Option<String>,
 ) -> evaluator::EvaluationError {
     evaluator::EvaluationError::failed_extension_function_application(
         extension_name.clone(),
+        kind,
         msg,
         None, // source loc will be added by the evaluator
         advice,
@@ -150,6 +159,7 @@ fn datetime_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
             extension_err(
                 err.to_string(),
                 &constants::DATETIME_CONSTRUCTOR_NAME,
+                ExtensionErrorKind::Parse,
                 Some(
                     err.help()
                         .map(|v| v.to_string())
@@ -213,6 +223,7 @@ fn offset(datetime: &Value, duration: &Value) -> evaluator::Result<ExtensionOutp
                 duration
             ),
             &constants::OFFSET_METHOD_NAME,
+            ExtensionErrorKind::OutOfRange,
             None,
         )
     })?;
@@ -234,6 +245,7 @@ fn duration_since(lhs: &Value, rhs: &Value) -> evaluator::Result<ExtensionOutput
                 RestrictedExpr::from(rhs)
             ),
             &constants::DURATION_SINCE_NAME,
+            ExtensionErrorKind::OutOfRange,
             None,
         )
     })?;
@@ -253,6 +265,7 @@ fn to_date(value: &Value) -> evaluator::Result<ExtensionOutputValue> {
                 RestrictedExpr::from(d),
             ),
             &constants::TO_DATE_NAME,
+            ExtensionErrorKind::OutOfRange,
             None,
         )
     })?;
@@ -273,6 +286,60 @@ fn to_time(value: &Value) -> evaluator::Result<ExtensionOutputValue> {
     .into())
 }
 
+/// Shared implementation of the calendar component methods (`dayOfWeek` and
+/// `hour`), which interpret a datetime in a fixed UTC offset.
+///
+/// Only fixed offsets strictly between `-1d` and `1d` are accepted. There is
+/// deliberately no support for named time zones or daylight saving time, so
+/// the result depends only on the two arguments and never on a time zone
+/// database that could differ between evaluators.
+fn calendar_component(
+    datetime: &Value,
+    offset: &Value,
+    name: &Name,
+    component: impl Fn(DateTime) -> i64,
+) -> evaluator::Result<ExtensionOutputValue> {
+    let d = as_datetime(datetime)?;
+    let o = as_duration(offset)?;
+    if o.ms.unsigned_abs() >= DateTime::DAY_IN_MILLISECONDS.unsigned_abs() {
+        return Err(extension_err(
+            format!("offset must be less than one day in magnitude, got {o}"),
+            name,
+            ExtensionErrorKind::InvalidArgument,
+            Some(
+                "use a fixed UTC offset such as `duration(\"-5h\")` or `duration(\"5h30m\")`"
+                    .into(),
+            ),
+        ));
+    }
+    let local = d.offset(o).ok_or_else(|| {
+        extension_err(
+            format!(
+                "overflows when adding an offset: {}+({})",
+                RestrictedExpr::from(d),
+                o
+            ),
+            name,
+            ExtensionErrorKind::OutOfRange,
+            None,
+        )
+    })?;
+    Ok(Value::from(component(local)).into())
+}
+
+fn day_of_week(datetime: &Value, offset: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    calendar_component(
+        datetime,
+        offset,
+        &constants::DAY_OF_WEEK_NAME,
+        DateTime::day_of_week,
+    )
+}
+
+fn hour(datetime: &Value, offset: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    calendar_component(datetime, offset, &constants::HOUR_NAME, DateTime::hour)
+}
+
 impl ExtensionValue for DateTime {
     fn typename(&self) -> crate::ast::Name {
         constants::DATETIME_CONSTRUCTOR_NAME.to_owned()
@@ -331,6 +398,17 @@ impl DateTime {
         }
     }
 
+    /// The ISO 8601 day of the week, from 1 (Monday) to 7 (Sunday)
+    fn day_of_week(self) -> i64 {
+        // The Unix epoch, 1970-01-01, was a Thursday
+        (self.epoch.div_euclid(Self::DAY_IN_MILLISECONDS) + 3).rem_euclid(7) + 1
+    }
+
+    /// The hour of the day, from 0 to 23
+    fn hour(self) -> i64 {
+        self.epoch.rem_euclid(Self::DAY_IN_MILLISECONDS) / (1000 * 3600)
+    }
+
     fn as_ext_func_call(self) -> (Name, Vec<RestrictedExpr>) {
         (
             constants::OFFSET_METHOD_NAME.clone(),
@@ -373,7 +451,7 @@ impl From<NaiveDateTime> for DateTime {
     }
 }
 
-// The `duration` type
+/// The `duration` type. Durations are ordered by length.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct Duration {
     // The number of milliseconds
@@ -427,6 +505,7 @@ fn duration_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
             extension_err(
                 err.to_string(),
                 &constants::DURATION_CONSTRUCTOR_NAME,
+                ExtensionErrorKind::Parse,
                 Some(
                     err.help()
                         .map(|v| v.to_string())
@@ -683,6 +762,16 @@ fn parse_datetime(s: &str) -> Result<NaiveDateTime, DateTimeParseError> {
     Ok(NaiveDateTime::new(date, time) + offset?)
 }
 
+/// A `datetime` value for `time`, truncated to milliseconds (and saturating
+/// at the bounds of `datetime`), as a restricted expression
+pub fn datetime_from_system_time(time: std::time::SystemTime) -> RestrictedExpr {
+    let epoch = match time.duration_since(std::time::SystemTime::UNIX_EPOCH) {
+        Ok(since) => i64::try_from(since.as_millis()).unwrap_or(i64::MAX),
+        Err(before) => i64::try_from(before.duration().as_millis()).map_or(i64::MIN, |ms| -ms),
+    };
+    DateTime { epoch }.into()
+}
+
 /// Construct the extension
 pub fn extension() -> Extension {
     let datetime_type = SchemaType::Extension {
@@ -700,77 +789,104 @@ pub fn extension() -> Extension {
                 Box::new(datetime_from_str),
                 datetime_type.clone(),
                 SchemaType::String,
-            ),
+            )
+            .with_doc(r#"Parses an ISO 8601 string such as `"2024-10-15T11:35:00Z"` as a datetime."#),
             ExtensionFunction::unary(
                 constants::DURATION_CONSTRUCTOR_NAME.clone(),
                 CallStyle::FunctionStyle,
                 Box::new(duration_from_str),
                 duration_type.clone(),
                 SchemaType::String,
-            ),
+            )
+            .with_doc(r#"Parses a string such as `"1d2h3m4s5ms"` as a duration."#),
             ExtensionFunction::binary(
                 constants::OFFSET_METHOD_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(offset),
                 datetime_type.clone(),
                 (datetime_type.clone(), duration_type.clone()),
-            ),
+            )
+            .with_doc("Adds a duration to the receiver, returning a datetime."),
             ExtensionFunction::binary(
                 constants::DURATION_SINCE_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(duration_since),
                 duration_type.clone(),
                 (datetime_type.clone(), duration_type.clone()),
-            ),
+            )
+            .with_doc("Returns the duration from the argument to the receiver."),
             ExtensionFunction::unary(
                 constants::TO_DATE_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(to_date),
                 datetime_type.clone(),
                 datetime_type.clone(),
-            ),
+            )
+            .with_doc("Truncates the receiver to midnight UTC of the same day."),
             ExtensionFunction::unary(
                 constants::TO_TIME_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(to_time),
                 duration_type.clone(),
-                datetime_type,
-            ),
+                datetime_type.clone(),
+            )
+            .with_doc("Returns the time since midnight UTC of the receiver as a duration."),
+            ExtensionFunction::binary(
+                constants::DAY_OF_WEEK_NAME.clone(),
+                CallStyle::MethodStyle,
+                Box::new(day_of_week),
+                SchemaType::Long,
+                (datetime_type.clone(), duration_type.clone()),
+            )
+            .with_doc("Returns the ISO 8601 day of the week of the receiver in the given UTC offset, from 1 (Monday) to 7 (Sunday)."),
+            ExtensionFunction::binary(
+                constants::HOUR_NAME.clone(),
+                CallStyle::MethodStyle,
+                Box::new(hour),
+                SchemaType::Long,
+                (datetime_type, duration_type.clone()),
+            )
+            .with_doc("Returns the hour of the day of the receiver in the given UTC offset, from 0 to 23."),
             ExtensionFunction::unary(
                 constants::TO_MILLISECONDS_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(|value| duration_method(value, Duration::to_milliseconds)),
                 SchemaType::Long,
                 duration_type.clone(),
-            ),
+            )
+            .with_doc("Returns the receiver as a whole number of milliseconds."),
             ExtensionFunction::unary(
                 constants::TO_SECONDS_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(|value| duration_method(value, Duration::to_seconds)),
                 SchemaType::Long,
                 duration_type.clone(),
-            ),
+            )
+            .with_doc("Returns the receiver as a whole number of seconds, rounded towards zero."),
             ExtensionFunction::unary(
                 constants::TO_MINUTES_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(|value| duration_method(value, Duration::to_minutes)),
                 SchemaType::Long,
                 duration_type.clone(),
-            ),
+            )
+            .with_doc("Returns the receiver as a whole number of minutes, rounded towards zero."),
             ExtensionFunction::unary(
                 constants::TO_HOURS_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(|value| duration_method(value, Duration::to_hours)),
                 SchemaType::Long,
                 duration_type.clone(),
-            ),
+            )
+            .with_doc("Returns the receiver as a whole number of hours, rounded towards zero."),
             ExtensionFunction::unary(
                 constants::TO_DAYS_NAME.clone(),
                 CallStyle::MethodStyle,
                 Box::new(|value| duration_method(value, Duration::to_days)),
                 SchemaType::Long,
                 duration_type,
-            ),
+            )
+            .with_doc("Returns the receiver as a whole number of days, rounded towards zero."),
         ],
         [
             constants::DATETIME_CONSTRUCTOR_NAME.clone(),
@@ -795,8 +911,9 @@ mod tests {
         extensions::{
             datetime::{
                 constants::{
-                    DURATION_CONSTRUCTOR_NAME, TO_DATE_NAME, TO_DAYS_NAME, TO_HOURS_NAME,
-                    TO_MILLISECONDS_NAME, TO_MINUTES_NAME, TO_SECONDS_NAME, TO_TIME_NAME,
+                    DURATION_CONSTRUCTOR_NAME, HOUR_NAME, TO_DATE_NAME, TO_DAYS_NAME,
+                    TO_HOURS_NAME, TO_MILLISECONDS_NAME, TO_MINUTES_NAME, TO_SECONDS_NAME,
+                    TO_TIME_NAME,
                 },
                 parse_datetime, parse_duration, DateTimeParseError, Duration,
             },
@@ -1222,6 +1339,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_calendar_components() {
+        let unix_epoch = DateTime { epoch: 0 };
+        // 1970-01-01 was a Thursday
+        assert_eq!(unix_epoch.day_of_week(), 4);
+        assert_eq!(unix_epoch.hour(), 0);
+
+        // 2024-10-28 was a Monday
+        let monday: DateTime = parse_datetime("2024-10-28T23:59:59.999Z").unwrap().into();
+        assert_eq!(monday.day_of_week(), 1);
+        assert_eq!(monday.hour(), 23);
+        let sunday: DateTime = parse_datetime("2024-10-27T00:00:00Z").unwrap().into();
+        assert_eq!(sunday.day_of_week(), 7);
+        assert_eq!(sunday.hour(), 0);
+
+        // Datetimes before the epoch count backwards
+        let before_epoch: DateTime = parse_datetime("1969-12-31T13:30:00Z").unwrap().into();
+        assert_eq!(before_epoch.day_of_week(), 3);
+        assert_eq!(before_epoch.hour(), 13);
+        let some_day_before_unix_epoch: DateTime = parse_datetime("1900-01-01").unwrap().into();
+        assert_eq!(some_day_before_unix_epoch.day_of_week(), 1);
+
+        for d in [DateTime { epoch: i64::MIN }, DateTime { epoch: i64::MAX }] {
+            assert!((1..=7).contains(&d.day_of_week()));
+            assert!((0..=23).contains(&d.hour()));
+        }
+    }
+
     fn dummy_entity() -> EntityUIDEntry {
         EntityUIDEntry::Known {
             euid: Arc::new(EntityUID::from_components(
@@ -1343,6 +1488,29 @@ mod tests {
             eval.interpret_inline_policy(&parse_expr(r#"duration("7h")"#).unwrap()).unwrap()
         );
 
+        // the result of `.durationSince` compares with duration literals
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"datetime("2024-10-28T10:12:13Z").durationSince(datetime("2024-10-28T10:00:00Z")) <= duration("15m")"#).unwrap()
+            )
+            .unwrap(),
+            Value::from(true),
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"datetime("2024-10-28T10:20:00Z").durationSince(datetime("2024-10-28T10:00:00Z")) <= duration("15m")"#).unwrap()
+            )
+            .unwrap(),
+            Value::from(false),
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"datetime("2024-10-28T09:50:00Z").durationSince(datetime("2024-10-28T10:00:00Z")) < duration("0ms")"#).unwrap()
+            )
+            .unwrap(),
+            Value::from(true),
+        );
+
         assert_matches!(
             eval.interpret_inline_policy(&parse_expr(r#"datetime("2024-10-28T10:12:13.456+0700").durationSince("7h")"#).unwrap()),
             Err(EvaluationError::TypeError(err)) => {
@@ -1435,6 +1603,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interpretation_calendar_components() {
+        let dummy_entity = dummy_entity();
+        let entities = Entities::default();
+        let eval = Evaluator::new(
+            Request::new_unchecked(
+                dummy_entity.clone(),
+                dummy_entity.clone(),
+                dummy_entity,
+                None,
+            ),
+            &entities,
+            Extensions::all_available(),
+        );
+
+        let interpret = |s: &str| eval.interpret_inline_policy(&parse_expr(s).unwrap());
+
+        // 2024-10-28T02:30:00Z is a Monday in UTC but still Sunday evening in UTC-5
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T02:30:00Z").dayOfWeek(duration("0ms"))"#).unwrap(),
+            Value::from(1),
+        );
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T02:30:00Z").hour(duration("0ms"))"#).unwrap(),
+            Value::from(2),
+        );
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T02:30:00Z").dayOfWeek(duration("-5h"))"#).unwrap(),
+            Value::from(7),
+        );
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T02:30:00Z").hour(duration("-5h"))"#).unwrap(),
+            Value::from(21),
+        );
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T20:00:00Z").hour(duration("5h30m"))"#).unwrap(),
+            Value::from(1),
+        );
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T20:00:00Z").dayOfWeek(duration("5h30m"))"#).unwrap(),
+            Value::from(2),
+        );
+        // The offset in the datetime string does not affect the result, only
+        // the operand does
+        assert_eq!(
+            interpret(r#"datetime("2024-10-28T10:00:00-0700").hour(duration("0ms"))"#).unwrap(),
+            Value::from(17),
+        );
+        // A business-hours check
+        assert_eq!(
+            interpret(
+                r#"datetime("2024-10-28T14:00:00Z").dayOfWeek(duration("-4h")) <= 5 &&
+                   datetime("2024-10-28T14:00:00Z").hour(duration("-4h")) >= 9 &&
+                   datetime("2024-10-28T14:00:00Z").hour(duration("-4h")) < 17"#
+            )
+            .unwrap(),
+            Value::from(true),
+        );
+
+        // Offsets of a day or more are rejected
+        for name in ["dayOfWeek", "hour"] {
+            for offset in ["1d", "-1d", "24h30m"] {
+                assert_matches!(
+                    interpret(&format!(r#"datetime("2024-10-28").{name}(duration("{offset}"))"#)),
+                    Err(EvaluationError::FailedExtensionFunctionExecution(err)) => {
+                        assert_eq!(err.extension_name.to_string(), name);
+                    }
+                );
+            }
+        }
+        // Overflow when applying the offset
+        assert_matches!(
+            interpret(
+                r#"datetime("1970-01-01").offset(duration("-106751991167d")).hour(duration("-23h"))"#
+            ),
+            Err(EvaluationError::FailedExtensionFunctionExecution(err)) => {
+                assert_eq!(err.extension_name, *HOUR_NAME);
+            }
+        );
+    }
+
     #[test]
     fn test_interpretation_duration() {
         let dummy_entity = dummy_entity();
@@ -22,6 +22,7 @@ use crate::ast::{
 };
 use crate::entities::SchemaType;
 use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
 
 use miette::Diagnostic;
 use std::str::FromStr;
@@ -36,6 +37,7 @@ const NUM_DIGITS: u32 = 4;
 
 /// Decimal value, represented internally as an integer.
 /// `Decimal{value}` represents `value / 10^NUM_DIGITS`.
+/// Decimals are ordered numerically.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 struct Decimal {
     value: i64,
@@ -67,6 +69,15 @@ pub(crate) mod constants {
     pub static GREATER_THAN_OR_EQUAL: LazyLock<Name> = LazyLock::new(|| {
         Name::parse_unqualified_name("greaterThanOrEqual").expect("should be a valid identifier")
     });
+    pub static ADD: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("add").expect("should be a valid identifier")
+    });
+    pub static SUBTRACT: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("subtract").expect("should be a valid identifier")
+    });
+    pub static MULTIPLY: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("multiply").expect("should be a valid identifier")
+    });
 
     // Global regex, initialized at first use
     pub static DECIMAL_REGEX: LazyLock<Regex> =
@@ -98,6 +109,30 @@ enum Error {
         "the value must be in range -922337203685477.5808 to 922337203685477.5807"
     ))]
     Overflow,
+
+    /// Overflow occurred during decimal arithmetic
+    #[error("overflow when computing `{0}`")]
+    #[diagnostic(help(
+        "the result must be in range -922337203685477.5808 to 922337203685477.5807"
+    ))]
+    ArithmeticOverflow(String),
+
+    /// The exact result of a multiplication has too many digits after the decimal point
+    #[error("the result of `{0}` cannot be represented exactly as a decimal")]
+    #[diagnostic(help("at most {NUM_DIGITS} digits after the decimal are supported"))]
+    PrecisionLoss(String),
+}
+
+impl Error {
+    /// The kind of evaluation error this error is reported as
+    fn kind(&self) -> ExtensionErrorKind {
+        match self {
+            Self::FailedParse(_) | Self::TooManyDigits(_) => ExtensionErrorKind::Parse,
+            Self::Overflow | Self::ArithmeticOverflow(_) | Self::PrecisionLoss(_) => {
+                ExtensionErrorKind::OutOfRange
+            }
+        }
+    }
 }
 
 /// Computes x * 10 ^ y while checking for overflows
@@ -159,6 +194,39 @@ impl Decimal {
         .map(|value| Self { value })
         .ok_or(Error::Overflow)
     }
+
+    /// Add two `Decimal` values, erroring on overflow
+    fn checked_add(&self, other: &Self) -> Result<Self, Error> {
+        self.value
+            .checked_add(other.value)
+            .map(|value| Self { value })
+            .ok_or_else(|| Error::ArithmeticOverflow(format!("{self} + {other}")))
+    }
+
+    /// Subtract `other` from `self`, erroring on overflow
+    fn checked_sub(&self, other: &Self) -> Result<Self, Error> {
+        self.value
+            .checked_sub(other.value)
+            .map(|value| Self { value })
+            .ok_or_else(|| Error::ArithmeticOverflow(format!("{self} - {other}")))
+    }
+
+    /// Multiply two `Decimal` values.
+    ///
+    /// The exact product is computed first; it is an error if it has more than
+    /// [`NUM_DIGITS`] digits after the decimal (no implicit rounding), or if it
+    /// is out of range.
+    fn checked_mul(&self, other: &Self) -> Result<Self, Error> {
+        // an `i64 * i64` product always fits in an `i128`
+        let product = i128::from(self.value) * i128::from(other.value);
+        let scale = i128::pow(10, NUM_DIGITS);
+        if product % scale != 0 {
+            return Err(Error::PrecisionLoss(format!("{self} * {other}")));
+        }
+        i64::try_from(product / scale)
+            .map(|value| Self { value })
+            .map_err(|_| Error::ArithmeticOverflow(format!("{self} * {other}")))
+    }
 }
 
 impl std::fmt::Display for Decimal {
@@ -192,9 +260,15 @@ impl ExtensionValue for Decimal {
 
 const EXTENSION_NAME: &str = "decimal";
 
-fn extension_err(msg: impl Into<String>, advice: Option<String>) -> evaluator::EvaluationError {
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: Option<String>,
+) -> evaluator::EvaluationError {
     evaluator::EvaluationError::failed_extension_function_application(
-        constants::DECIMAL_FROM_STR_NAME.clone(),
+        name.clone(),
+        kind,
         msg.into(),
         None,
         advice, // source loc will be added by the evaluator
@@ -205,8 +279,14 @@ fn extension_err(msg: impl Into<String>, advice: Option<String>) -> evaluator::E
 /// Cedar string
 fn decimal_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
     let str = arg.get_as_string()?;
-    let decimal = Decimal::from_str(str.as_str())
-        .map_err(|e| extension_err(e.to_string(), e.help().map(|h| h.to_string())))?;
+    let decimal = Decimal::from_str(str.as_str()).map_err(|e| {
+        extension_err(
+            &constants::DECIMAL_FROM_STR_NAME,
+            e.kind(),
+            e.to_string(),
+            e.help().map(|h| h.to_string()),
+        )
+    })?;
     let arg_source_loc = arg.source_loc();
     let e = RepresentableExtensionValue::new(
         Arc::new(decimal),
@@ -282,6 +362,53 @@ fn decimal_ge(left: &Value, right: &Value) -> evaluator::Result<ExtensionOutputV
     Ok(Value::from(left >= right).into())
 }
 
+/// Apply a checked arithmetic operation to two `decimal` Cedar values,
+/// returning a new `decimal`
+fn decimal_arith(
+    name: &Name,
+    left: &Value,
+    right: &Value,
+    op: impl FnOnce(&Decimal, &Decimal) -> Result<Decimal, Error>,
+) -> evaluator::Result<ExtensionOutputValue> {
+    let left = as_decimal(left)?;
+    let right = as_decimal(right)?;
+    let result = op(left, right).map_err(|e| {
+        extension_err(
+            name,
+            e.kind(),
+            e.to_string(),
+            e.help().map(|h| h.to_string()),
+        )
+    })?;
+    let arg = crate::ast::RestrictedExpr::val(result.to_string());
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(RepresentableExtensionValue::new(
+            Arc::new(result),
+            constants::DECIMAL_FROM_STR_NAME.clone(),
+            vec![arg],
+        ))),
+        loc: None,
+    }
+    .into())
+}
+
+/// Cedar function that adds two `decimal` Cedar types, returning a `decimal`
+fn decimal_add(left: &Value, right: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    decimal_arith(&constants::ADD, left, right, Decimal::checked_add)
+}
+
+/// Cedar function that subtracts the second `decimal` Cedar type from the
+/// first, returning a `decimal`
+fn decimal_sub(left: &Value, right: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    decimal_arith(&constants::SUBTRACT, left, right, Decimal::checked_sub)
+}
+
+/// Cedar function that multiplies two `decimal` Cedar types, returning a
+/// `decimal`
+fn decimal_mul(left: &Value, right: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    decimal_arith(&constants::MULTIPLY, left, right, Decimal::checked_mul)
+}
+
 /// Construct the extension
 pub fn extension() -> Extension {
     let decimal_type = SchemaType::Extension {
@@ -296,35 +423,64 @@ pub fn extension() -> Extension {
                 Box::new(decimal_from_str),
                 decimal_type.clone(),
                 SchemaType::String,
-            ),
+            )
+            .with_doc(r#"Parses a string such as `"12.34"` as a decimal, with up to four digits after the decimal point."#),
             ExtensionFunction::binary(
                 constants::LESS_THAN.clone(),
                 CallStyle::MethodStyle,
                 Box::new(decimal_lt),
                 SchemaType::Bool,
                 (decimal_type.clone(), decimal_type.clone()),
-            ),
+            )
+            .with_doc("Tests whether the receiver is numerically less than the argument."),
             ExtensionFunction::binary(
                 constants::LESS_THAN_OR_EQUAL.clone(),
                 CallStyle::MethodStyle,
                 Box::new(decimal_le),
                 SchemaType::Bool,
                 (decimal_type.clone(), decimal_type.clone()),
-            ),
+            )
+            .with_doc("Tests whether the receiver is numerically less than or equal to the argument."),
             ExtensionFunction::binary(
                 constants::GREATER_THAN.clone(),
                 CallStyle::MethodStyle,
                 Box::new(decimal_gt),
                 SchemaType::Bool,
                 (decimal_type.clone(), decimal_type.clone()),
-            ),
+            )
+            .with_doc("Tests whether the receiver is numerically greater than the argument."),
             ExtensionFunction::binary(
                 constants::GREATER_THAN_OR_EQUAL.clone(),
                 CallStyle::MethodStyle,
                 Box::new(decimal_ge),
                 SchemaType::Bool,
+                (decimal_type.clone(), decimal_type.clone()),
+            )
+            .with_doc("Tests whether the receiver is numerically greater than or equal to the argument."),
+            ExtensionFunction::binary(
+                constants::ADD.clone(),
+                CallStyle::MethodStyle,
+                Box::new(decimal_add),
+                decimal_type.clone(),
+                (decimal_type.clone(), decimal_type.clone()),
+            )
+            .with_doc("Adds the argument to the receiver. Overflow is an error."),
+            ExtensionFunction::binary(
+                constants::SUBTRACT.clone(),
+                CallStyle::MethodStyle,
+                Box::new(decimal_sub),
+                decimal_type.clone(),
+                (decimal_type.clone(), decimal_type.clone()),
+            )
+            .with_doc("Subtracts the argument from the receiver. Overflow is an error."),
+            ExtensionFunction::binary(
+                constants::MULTIPLY.clone(),
+                CallStyle::MethodStyle,
+                Box::new(decimal_mul),
+                decimal_type.clone(),
                 (decimal_type.clone(), decimal_type),
-            ),
+            )
+            .with_doc("Multiplies the receiver by the argument. Overflow, or a product needing more than four digits after the decimal point, is an error."),
         ],
         std::iter::empty(),
     )
@@ -701,6 +857,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decimal_arithmetic() {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        let eval_str =
+            |s: &str| eval.interpret_inline_policy(&parse_expr(s).expect("parsing error"));
+
+        for (expr, expected) in [
+            (r#"decimal("1.23").add(decimal("4.5"))"#, "5.73"),
+            (r#"decimal("1.23").add(decimal("-4.5"))"#, "-3.27"),
+            (r#"decimal("1.23").subtract(decimal("4.5"))"#, "-3.27"),
+            (r#"decimal("-0.0001").subtract(decimal("-0.0001"))"#, "0.0"),
+            (r#"decimal("19.99").multiply(decimal("3.0"))"#, "59.97"),
+            (r#"decimal("0.01").multiply(decimal("0.01"))"#, "0.0001"),
+            (r#"decimal("-2.5").multiply(decimal("-0.5"))"#, "1.25"),
+            (
+                r#"decimal("100.0").multiply(decimal("0.25")).add(decimal("0.75"))"#,
+                "25.75",
+            ),
+        ] {
+            assert_matches!(eval_str(expr), Ok(v) => {
+                assert_eq!(as_decimal(&v).unwrap(), &Decimal::from_str(expected).unwrap(), "{expr}");
+            });
+        }
+
+        // results compare equal to the corresponding literals
+        assert_eq!(
+            eval_str(r#"decimal("0.1").add(decimal("0.2")) == decimal("0.3")"#),
+            Ok(true.into())
+        );
+
+        for (expr, func, msg) in [
+            (
+                r#"decimal("922337203685477.5807").add(decimal("0.0001"))"#,
+                "add",
+                "overflow when computing `922337203685477.5807 + 0.0001`",
+            ),
+            (
+                r#"decimal("-922337203685477.5808").subtract(decimal("0.0001"))"#,
+                "subtract",
+                "overflow when computing `-922337203685477.5808 - 0.0001`",
+            ),
+            (
+                r#"decimal("100000000000.0").multiply(decimal("10000.0"))"#,
+                "multiply",
+                "overflow when computing `100000000000.0000 * 10000.0000`",
+            ),
+            (
+                r#"decimal("0.01").multiply(decimal("0.001"))"#,
+                "multiply",
+                "the result of `0.0100 * 0.0010` cannot be represented exactly as a decimal",
+            ),
+        ] {
+            assert_matches!(eval_str(expr), Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                extension_name,
+                kind,
+                msg: actual,
+                ..
+            })) => {
+                assert_eq!(extension_name, Name::parse_unqualified_name(func).unwrap());
+                assert_eq!(kind, evaluation_errors::ExtensionErrorKind::OutOfRange);
+                assert_eq!(actual, msg);
+            });
+        }
+
+        assert_matches!(
+            eval_str(r#"decimal("1.0").add("1.0")"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { advice, .. })) => {
+                assert_matches!(advice, Some(a) => assert_eq!(a, ADVICE_MSG));
+            }
+        );
+    }
+
     fn check_round_trip(s: &str) {
         let d = Decimal::from_str(s).expect("should be a valid decimal");
         assert_eq!(d, Decimal::from_str(d.to_string()).unwrap());
@@ -0,0 +1,728 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'geo' extension, which provides extension
+//! types for points and polygons on the Earth's surface.
+//!
+//! Evaluation is deterministic across platforms: coordinates are stored as
+//! integers, containment in polygons uses exact integer arithmetic, and
+//! distances only use the basic IEEE 754 operations (`+`, `-`, `*`, `/` and
+//! `sqrt`), which are correctly rounded, rather than the platform's
+//! trigonometric functions.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue, Literal, Name,
+    RepresentableExtensionValue, RestrictedExpr, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use std::sync::Arc;
+
+/// Help text describing the valid format of `geopoint` constructor arguments
+const VALID_POINT_HELP: &str = "points are written as a latitude and a longitude in decimal degrees with at most 7 fractional digits, e.g., `geopoint(\"47.6062,-122.3321\")`";
+
+/// Help text describing the valid format of `geopolygon` constructor arguments
+const VALID_POLYGON_HELP: &str = "polygons are written as at least three points separated by `;`, e.g., `geopolygon(\"47.0,-122.0; 47.0,-121.0; 48.0,-121.0\")`";
+
+/// Help message to display when a String was provided where a `geopoint` or
+/// `geopolygon` value was expected
+const ADVICE_MSG: &str = "maybe you forgot to apply the `geopoint` or `geopolygon` constructor?";
+
+/// Number of coordinate units in a degree
+const UNITS_PER_DEGREE: i64 = 10_000_000;
+
+/// Mean radius of the Earth, in meters
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A point, stored as a latitude and a longitude in units of 1e-7 degrees.
+/// Points are ordered by latitude, then by longitude.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct GeoPoint {
+    lat: i64,
+    lon: i64,
+}
+
+/// A polygon, given by its vertices in order. The edges are straight lines in
+/// latitude and longitude, from each vertex to the next and from the last
+/// vertex back to the first. Polygons are ordered by comparing their vertices
+/// lexicographically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct GeoPolygon(Vec<GeoPoint>);
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("geo").expect("should be a valid identifier")
+    });
+    pub static GEOPOINT_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("geopoint").expect("should be a valid identifier")
+    });
+    pub static GEOPOLYGON_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("geopolygon").expect("should be a valid identifier")
+    });
+    pub static DISTANCE_TO: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("distanceTo").expect("should be a valid identifier")
+    });
+    pub static IS_IN_POLYGON: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("isInPolygon").expect("should be a valid identifier")
+    });
+}
+
+/// Parse a coordinate in decimal degrees with at most 7 fractional digits and
+/// an absolute value of at most `max_degrees`
+fn parse_coordinate(s: &str, max_degrees: i64) -> Option<i64> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((i, f)) if !f.is_empty() => (i, f),
+        Some(_) => return None,
+        None => (unsigned, ""),
+    };
+    if int_part.is_empty()
+        || int_part.len() > 3
+        || frac_part.len() > 7
+        || !int_part
+            .chars()
+            .chain(frac_part.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let int: i64 = int_part.parse().ok()?;
+    let frac: i64 = format!("{frac_part:0<7}").parse().ok()?;
+    let units = int * UNITS_PER_DEGREE + frac;
+    if units > max_degrees * UNITS_PER_DEGREE {
+        return None;
+    }
+    Some(if negative { -units } else { units })
+}
+
+/// Format a coordinate in decimal degrees, with no trailing zeros after the
+/// first fractional digit
+fn fmt_coordinate(units: i64, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let sign = if units < 0 { "-" } else { "" };
+    let (int, frac) = (
+        units.unsigned_abs() / UNITS_PER_DEGREE.unsigned_abs(),
+        units.unsigned_abs() % UNITS_PER_DEGREE.unsigned_abs(),
+    );
+    let frac = format!("{frac:07}");
+    let frac = match frac.trim_end_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    write!(f, "{sign}{int}.{frac}")
+}
+
+impl std::fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_coordinate(self.lat, f)?;
+        write!(f, ",")?;
+        fmt_coordinate(self.lon, f)
+    }
+}
+
+impl std::fmt::Display for GeoPolygon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, vertex) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{vertex}")?;
+        }
+        Ok(())
+    }
+}
+
+impl GeoPoint {
+    /// The Cedar typename of `geopoint` values
+    fn typename() -> Name {
+        constants::GEOPOINT_FROM_STR_NAME.clone()
+    }
+
+    /// Parse a point written as `lat,lon`
+    fn parse(s: &str) -> Option<Self> {
+        let (lat, lon) = s.split_once(',')?;
+        Some(Self {
+            lat: parse_coordinate(lat.trim(), 90)?,
+            lon: parse_coordinate(lon.trim(), 180)?,
+        })
+    }
+
+    /// The point as a unit vector from the center of the Earth
+    fn unit_vector(self) -> [f64; 3] {
+        let (sin_lat, cos_lat) = sin_cos_units(self.lat);
+        let (sin_lon, cos_lon) = sin_cos_units(self.lon);
+        [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+    }
+
+    /// The great-circle distance to `other`, in meters, rounded down
+    #[expect(
+        clippy::suboptimal_flops,
+        reason = "`mul_add` is avoided to keep to the operations listed in the module docs"
+    )]
+    fn distance_to(self, other: Self) -> i64 {
+        let [x0, y0, z0] = self.unit_vector();
+        let [x1, y1, z1] = other.unit_vector();
+        let (dx, dy, dz) = (x0 - x1, y0 - y1, z0 - z1);
+        let chord = (dx * dx + dy * dy + dz * dz).sqrt();
+        // The central angle is `2 * asin(chord / 2)`. Rounding may make
+        // `chord / 2` slightly larger than 1 for antipodal points.
+        let angle = 2.0 * asin(f64::min(chord / 2.0, 1.0));
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "the distance is at most half the Earth's circumference, which fits in an i64"
+        )]
+        let meters = (EARTH_RADIUS_METERS * angle).floor() as i64;
+        meters
+    }
+}
+
+impl GeoPolygon {
+    /// The Cedar typename of `geopolygon` values
+    fn typename() -> Name {
+        constants::GEOPOLYGON_FROM_STR_NAME.clone()
+    }
+
+    /// Parse a polygon written as at least three points separated by `;`
+    fn parse(s: &str) -> Option<Self> {
+        let vertices = s
+            .split(';')
+            .map(GeoPoint::parse)
+            .collect::<Option<Vec<_>>>()?;
+        (vertices.len() >= 3).then_some(Self(vertices))
+    }
+
+    /// Test whether `p` is inside the polygon or on its boundary, treating
+    /// latitude and longitude as planar coordinates. Uses exact integer
+    /// arithmetic.
+    fn contains(&self, p: GeoPoint) -> bool {
+        let (px, py) = (i128::from(p.lon), i128::from(p.lat));
+        let mut inside = false;
+        for (a, b) in self.0.iter().zip(self.0.iter().cycle().skip(1)) {
+            let (ax, ay) = (i128::from(a.lon), i128::from(a.lat));
+            let (bx, by) = (i128::from(b.lon), i128::from(b.lat));
+            let cross = (bx - ax) * (py - ay) - (px - ax) * (by - ay);
+            if cross == 0
+                && ax.min(bx) <= px
+                && px <= ax.max(bx)
+                && ay.min(by) <= py
+                && py <= ay.max(by)
+            {
+                // on the edge from `a` to `b`
+                return true;
+            }
+            // Count the edges crossed by a ray from `p` in the direction of
+            // increasing longitude
+            if (ay > py) != (by > py) && (cross > 0) == (by > ay) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+/// The sine and cosine of an angle given in units of 1e-7 degrees, with
+/// `|units| <= 180` degrees. The angle is reduced to at most 45 degrees with
+/// exact integer arithmetic before evaluating Taylor series.
+fn sin_cos_units(units: i64) -> (f64, f64) {
+    const QUARTER: i64 = 90 * UNITS_PER_DEGREE;
+    let a = units.abs();
+    let (sin, cos) = if a > QUARTER {
+        let (s, c) = sin_cos_reduced(2 * QUARTER - a);
+        (s, -c)
+    } else {
+        sin_cos_reduced(a)
+    };
+    if units < 0 {
+        (-sin, cos)
+    } else {
+        (sin, cos)
+    }
+}
+
+/// Like [`sin_cos_units`], for `0 <= units <= 90` degrees
+fn sin_cos_reduced(units: i64) -> (f64, f64) {
+    const QUARTER: i64 = 90 * UNITS_PER_DEGREE;
+    const EIGHTH: i64 = 45 * UNITS_PER_DEGREE;
+    if units > EIGHTH {
+        let (s, c) = sin_cos_small(QUARTER - units);
+        (c, s)
+    } else {
+        sin_cos_small(units)
+    }
+}
+
+/// Like [`sin_cos_units`], for `0 <= units <= 45` degrees
+fn sin_cos_small(units: i64) -> (f64, f64) {
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "at most 45 degrees in units of 1e-7 degrees, which is exact in an f64"
+    )]
+    let x = units as f64 * (std::f64::consts::PI / (180.0 * UNITS_PER_DEGREE as f64));
+    let x2 = x * x;
+    let (mut sin, mut sin_term) = (x, x);
+    let (mut cos, mut cos_term) = (1.0, 1.0);
+    // For `x <= pi / 4`, 12 terms of each series are accurate to well below
+    // the precision of an f64
+    for k in 1..12_u32 {
+        sin_term *= -x2 / f64::from((2 * k) * (2 * k + 1));
+        sin += sin_term;
+        cos_term *= -x2 / f64::from((2 * k - 1) * (2 * k));
+        cos += cos_term;
+    }
+    (sin, cos)
+}
+
+/// The arcsine of `0 <= h <= 1`, using
+/// `asin(h) = 2 * atan(h / (1 + sqrt(1 - h^2)))`
+#[expect(
+    clippy::suboptimal_flops,
+    reason = "`mul_add` is avoided to keep to the operations listed in the module docs"
+)]
+fn asin(h: f64) -> f64 {
+    2.0 * atan(h / (1.0 + (1.0 - h * h).sqrt()))
+}
+
+/// The arctangent of `0 <= y <= 1`
+fn atan(y: f64) -> f64 {
+    // tan(pi / 8)
+    const TAN_EIGHTH: f64 = 0.414_213_562_373_095_03;
+    if y > TAN_EIGHTH {
+        // atan(y) = pi / 4 + atan((y - 1) / (y + 1)), and the argument of the
+        // latter is in [-tan(pi / 8), 0]
+        std::f64::consts::FRAC_PI_4 + atan_series((y - 1.0) / (y + 1.0))
+    } else {
+        atan_series(y)
+    }
+}
+
+/// The Taylor series of the arctangent, for `|y| <= tan(pi / 8)`
+fn atan_series(y: f64) -> f64 {
+    let y2 = y * y;
+    let mut power = y;
+    let mut sum = y;
+    // `y^2 <= 0.172`, so 24 terms are accurate to well below the precision of
+    // an f64
+    for k in 1..24 {
+        power *= -y2;
+        sum += power / f64::from(2 * k + 1);
+    }
+    sum
+}
+
+impl ExtensionValue for GeoPoint {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+
+    /// The canonical representation of a point writes both coordinates with
+    /// no trailing zeros and no whitespace, e.g., `geopoint("47.5,-122.0")`.
+    fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
+        Some((
+            constants::GEOPOINT_FROM_STR_NAME.clone(),
+            vec![RestrictedExpr::val(self.to_string())],
+        ))
+    }
+}
+
+impl ExtensionValue for GeoPolygon {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+
+    /// The canonical representation of a polygon writes each vertex in its
+    /// canonical form, separated by `; `.
+    fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
+        Some((
+            constants::GEOPOLYGON_FROM_STR_NAME.clone(),
+            vec![RestrictedExpr::val(self.to_string())],
+        ))
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: &str,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        Some(advice.into()),
+    )
+}
+
+/// Cedar function that constructs a `geopoint` Cedar type from a Cedar string
+fn geopoint_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let point = GeoPoint::parse(s).ok_or_else(|| {
+        extension_err(
+            &constants::GEOPOINT_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a well-formed point"),
+            VALID_POINT_HELP,
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(point),
+        constants::GEOPOINT_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Cedar function that constructs a `geopolygon` Cedar type from a Cedar
+/// string
+fn geopolygon_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let polygon = GeoPolygon::parse(s).ok_or_else(|| {
+        extension_err(
+            &constants::GEOPOLYGON_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a well-formed polygon"),
+            VALID_POLYGON_HELP,
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(polygon),
+        constants::GEOPOLYGON_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Check that `v` is of the extension type `T` and, if it is, return the
+/// wrapped value
+fn as_geo<T: 'static>(v: &Value, typename: Name) -> Result<&T, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == typename => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let t = ev
+                .value()
+                .as_any()
+                .downcast_ref::<T>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(t)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension { name: typename },
+                v,
+                ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension { name: typename },
+            v,
+        )),
+    }
+}
+
+/// Cedar function that returns the great-circle distance between two
+/// `geopoint`s in meters, rounded down
+fn distance_to(p: &Value, q: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let p = as_geo::<GeoPoint>(p, GeoPoint::typename())?;
+    let q = as_geo::<GeoPoint>(q, GeoPoint::typename())?;
+    Ok(Value::from(p.distance_to(*q)).into())
+}
+
+/// Cedar function that tests whether a `geopoint` is inside a `geopolygon` or
+/// on its boundary
+fn is_in_polygon(p: &Value, polygon: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let p = as_geo::<GeoPoint>(p, GeoPoint::typename())?;
+    let polygon = as_geo::<GeoPolygon>(polygon, GeoPolygon::typename())?;
+    Ok(Value::from(polygon.contains(*p)).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let point_type = SchemaType::Extension {
+        name: GeoPoint::typename(),
+    };
+    let polygon_type = SchemaType::Extension {
+        name: GeoPolygon::typename(),
+    };
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::GEOPOINT_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(geopoint_from_str),
+                point_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Parses a latitude and longitude in decimal degrees such as `"47.6062,-122.3321"` as a point."#),
+            ExtensionFunction::unary(
+                constants::GEOPOLYGON_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(geopolygon_from_str),
+                polygon_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Parses at least three points separated by `;`, such as `"47.0,-122.0; 47.0,-121.0; 48.0,-121.0"`, as a polygon."#),
+            ExtensionFunction::binary(
+                constants::DISTANCE_TO.clone(),
+                CallStyle::MethodStyle,
+                Box::new(distance_to),
+                SchemaType::Long,
+                (point_type.clone(), point_type.clone()),
+            )
+            .with_doc("Returns the great-circle distance from the receiver to the argument in meters, rounded down."),
+            ExtensionFunction::binary(
+                constants::IS_IN_POLYGON.clone(),
+                CallStyle::MethodStyle,
+                Box::new(is_in_polygon),
+                SchemaType::Bool,
+                (point_type, polygon_type),
+            )
+            .with_doc("Tests whether the receiver is inside the argument polygon or on its boundary."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[test]
+    fn parse_points() {
+        assert_eq!(
+            GeoPoint::parse("47.6062,-122.3321"),
+            Some(GeoPoint {
+                lat: 476_062_000,
+                lon: -1_223_321_000
+            })
+        );
+        assert_eq!(
+            GeoPoint::parse("-90, 180"),
+            Some(GeoPoint {
+                lat: -900_000_000,
+                lon: 1_800_000_000
+            })
+        );
+        assert_eq!(
+            GeoPoint::parse("0.0000001,0"),
+            Some(GeoPoint { lat: 1, lon: 0 })
+        );
+        for s in [
+            "",
+            "47.6",
+            "90.0000001,0",
+            "0,180.1",
+            "0,-181",
+            "0.00000001,0",
+            "1.,2",
+            ".5,2",
+            "+1,2",
+            "1e1,2",
+            "1,2,3",
+            "0x1,2",
+        ] {
+            assert_eq!(GeoPoint::parse(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn distances() {
+        for (src, expected) in [
+            (r#"geopoint("0,0").distanceTo(geopoint("0,0"))"#, 0),
+            // one degree of latitude is about 111.2 km
+            (r#"geopoint("0,0").distanceTo(geopoint("1,0"))"#, 111_195),
+            (r#"geopoint("0,0").distanceTo(geopoint("-1,0"))"#, 111_195),
+            (r#"geopoint("0,0").distanceTo(geopoint("0,1"))"#, 111_195),
+            // half of the circumference
+            (
+                r#"geopoint("0,0").distanceTo(geopoint("0,180"))"#,
+                20_015_114,
+            ),
+            (
+                r#"geopoint("90,0").distanceTo(geopoint("-90,0"))"#,
+                20_015_114,
+            ),
+            // across the antimeridian
+            (
+                r#"geopoint("0,179.5").distanceTo(geopoint("0,-179.5"))"#,
+                111_195,
+            ),
+            // Seattle to Portland
+            (
+                r#"geopoint("47.6062,-122.3321").distanceTo(geopoint("45.5152,-122.6784"))"#,
+                234_010,
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(Value::from(expected)), "{src}");
+        }
+        assert_eq!(
+            eval(
+                r#"geopoint("47.6062,-122.3321").distanceTo(geopoint("47.6072,-122.3321")) <= 112"#
+            ),
+            Ok(Value::from(true))
+        );
+    }
+
+    #[test]
+    fn sin_cos_matches_std() {
+        for degrees in [
+            -180, -135, -90, -60, -45, -1, 0, 1, 30, 45, 46, 89, 90, 91, 179, 180,
+        ] {
+            let (sin, cos) = sin_cos_units(degrees * UNITS_PER_DEGREE);
+            let x = f64::from(i32::try_from(degrees).unwrap()).to_radians();
+            assert!((sin - x.sin()).abs() < 1e-15, "sin {degrees}");
+            assert!((cos - x.cos()).abs() < 1e-15, "cos {degrees}");
+        }
+        for h in [0.0, 0.1, 0.4, 0.5, 0.9, 0.999, 1.0] {
+            assert!((asin(h) - f64::asin(h)).abs() < 1e-15, "asin {h}");
+        }
+    }
+
+    #[test]
+    fn polygons() {
+        let square = r#"geopolygon("0,0; 0,10; 10,10; 10,0")"#;
+        for (point, expected) in [
+            ("5,5", true),
+            ("0,5", true),
+            ("10,10", true),
+            ("0.0000001,0.0000001", true),
+            ("-0.0000001,5", false),
+            ("5,10.0000001", false),
+            ("20,5", false),
+        ] {
+            let src = format!(r#"geopoint("{point}").isInPolygon({square})"#);
+            assert_eq!(eval(&src), Ok(Value::from(expected)), "{src}");
+        }
+        // a non-convex polygon shaped like a `U`
+        let u = r#"geopolygon("0,0; 10,0; 10,3; 2,3; 2,7; 10,7; 10,10; 0,10")"#;
+        for (point, expected) in [("1,5", true), ("5,5", false), ("5,1", true), ("5,9", true)] {
+            let src = format!(r#"geopoint("{point}").isInPolygon({u})"#);
+            assert_eq!(eval(&src), Ok(Value::from(expected)), "{src}");
+        }
+        assert_eq!(
+            eval(r#"geopolygon("0,0;0,1;1,1") == geopolygon("0,0; 0,1; 1,1.0")"#),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            eval(r#"geopolygon("0,0;0,1;1,1") == geopolygon("0,1;1,1;0,0")"#),
+            Ok(Value::from(false))
+        );
+    }
+
+    #[test]
+    fn constructor_errors() {
+        for (src, name, help) in [
+            (
+                r#"geopoint("91,0")"#,
+                &*constants::GEOPOINT_FROM_STR_NAME,
+                VALID_POINT_HELP,
+            ),
+            (
+                r#"geopolygon("0,0; 0,1")"#,
+                &*constants::GEOPOLYGON_FROM_STR_NAME,
+                VALID_POLYGON_HELP,
+            ),
+            (
+                r#"geopolygon("0,0; 0,1; 1")"#,
+                &*constants::GEOPOLYGON_FROM_STR_NAME,
+                VALID_POLYGON_HELP,
+            ),
+        ] {
+            assert_matches!(
+                eval(src),
+                Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                    extension_name,
+                    advice,
+                    ..
+                })) => {
+                    assert_eq!(&extension_name, name, "{src}");
+                    assert_eq!(advice.as_deref(), Some(help), "{src}");
+                }
+            );
+        }
+        assert_matches!(
+            eval(r#"geopoint("0,0").distanceTo("1,1")"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, advice, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: GeoPoint::typename() }]);
+                assert_eq!(advice.as_deref(), Some(ADVICE_MSG));
+            }
+        );
+        assert_matches!(
+            eval(r#"geopoint("0,0").isInPolygon(geopoint("0,0"))"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: GeoPolygon::typename() }]);
+            }
+        );
+    }
+
+    #[test]
+    fn canonical_repr() {
+        for (src, canonical) in [
+            (r#"geopoint("47.50, -122")"#, r#"geopoint("47.5,-122.0")"#),
+            (
+                r#"geopoint("-0.0000001,0")"#,
+                r#"geopoint("-0.0000001,0.0")"#,
+            ),
+            (
+                r#"geopolygon("0,0;0,1.10;1,1")"#,
+                r#"geopolygon("0.0,0.0; 0.0,1.1; 1.0,1.0")"#,
+            ),
+        ] {
+            let v = eval(src).unwrap().normalize_ext_values();
+            assert_eq!(v.to_string(), canonical);
+            assert_eq!(eval(canonical).unwrap(), v);
+        }
+    }
+}
@@ -0,0 +1,559 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'identifiers' extension, which provides
+//! extension types for UUIDs and ULIDs.
+//!
+//! Both are parsed case-insensitively and compared by value, so
+//! `uuid("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11")` and
+//! `uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")` are equal. ULIDs also
+//! support the comparison operators `<`, `<=`, `>`, and `>=`, which order them
+//! by creation time first, as their string forms would be ordered.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue, Literal, Name,
+    RepresentableExtensionValue, RestrictedExpr, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use std::sync::Arc;
+
+/// Help text describing the valid format of `uuid` constructor arguments
+const VALID_UUID_HELP: &str = "UUIDs are written as 32 hexadecimal digits in groups of 8, 4, 4, 4, and 12 separated by `-`, e.g., `uuid(\"a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11\")`";
+
+/// Help text describing the valid format of `ulid` constructor arguments
+const VALID_ULID_HELP: &str = "ULIDs are written as 26 Crockford base32 digits, starting with a digit from 0 to 7, e.g., `ulid(\"01ARZ3NDEKTSV4RRFFQ69G5FAV\")`";
+
+/// Help message to display when a String was provided where a `uuid` or `ulid`
+/// value was expected
+const ADVICE_MSG: &str = "maybe you forgot to apply the `uuid` or `ulid` constructor?";
+
+/// The digits of Crockford's base32, in order
+const CROCKFORD_DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A UUID, stored as a 128-bit integer. UUIDs are ordered by that integer,
+/// which is also the order of their canonical string forms.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct Uuid(u128);
+
+/// A ULID, stored as a 128-bit integer whose top 48 bits are a timestamp in
+/// milliseconds since the Unix epoch. ULIDs are ordered by that integer, so by
+/// timestamp first, which is also the order of their canonical string forms.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+struct Ulid(u128);
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("identifiers").expect("should be a valid identifier")
+    });
+    pub static UUID_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("uuid").expect("should be a valid identifier")
+    });
+    pub static ULID_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("ulid").expect("should be a valid identifier")
+    });
+    pub static UUID_VERSION: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("uuidVersion").expect("should be a valid identifier")
+    });
+    pub static TIMESTAMP_MILLIS: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("timestampMillis").expect("should be a valid identifier")
+    });
+}
+
+impl Uuid {
+    /// The Cedar typename of `uuid` values
+    fn typename() -> Name {
+        constants::UUID_FROM_STR_NAME.clone()
+    }
+
+    /// Parse a UUID in the hyphenated form, in either case
+    fn parse(s: &str) -> Option<Self> {
+        let groups: Vec<&str> = s.split('-').collect();
+        if groups.iter().map(|g| g.len()).collect::<Vec<_>>() != [8, 4, 4, 4, 12] {
+            return None;
+        }
+        groups
+            .iter()
+            .flat_map(|g| g.chars())
+            .try_fold(0, |acc, c| Some((acc << 4) | u128::from(c.to_digit(16)?)))
+            .map(Self)
+    }
+
+    /// The version of the UUID, from its 13th hexadecimal digit
+    fn version(self) -> i64 {
+        i64::from(((self.0 >> 76) & 0xf) as u8)
+    }
+}
+
+impl std::fmt::Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            self.0 >> 96,
+            (self.0 >> 80) & 0xffff,
+            (self.0 >> 64) & 0xffff,
+            (self.0 >> 48) & 0xffff,
+            self.0 & 0xffff_ffff_ffff
+        )
+    }
+}
+
+impl Ulid {
+    /// The Cedar typename of `ulid` values
+    fn typename() -> Name {
+        constants::ULID_FROM_STR_NAME.clone()
+    }
+
+    /// Parse a ULID of 26 Crockford base32 digits, in either case. The first
+    /// digit can be at most 7, as a ULID is only 128 bits.
+    fn parse(s: &str) -> Option<Self> {
+        if s.len() != 26 || !matches!(s.as_bytes().first(), Some(b'0'..=b'7')) {
+            return None;
+        }
+        s.bytes()
+            .try_fold(0, |acc, b| {
+                let digit = CROCKFORD_DIGITS
+                    .iter()
+                    .position(|d| *d == b.to_ascii_uppercase())?;
+                Some((acc << 5) | digit as u128)
+            })
+            .map(Self)
+    }
+
+    /// The timestamp of the ULID, in milliseconds since the Unix epoch
+    fn timestamp_millis(self) -> i64 {
+        // the timestamp is 48 bits, which fits in an i64
+        (self.0 >> 80) as i64
+    }
+}
+
+impl std::fmt::Display for Ulid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in (0..26).rev() {
+            let digit = (self.0 >> (5 * i)) & 0x1f;
+            #[expect(clippy::indexing_slicing, reason = "a 5-bit digit is less than 32")]
+            let c = char::from(CROCKFORD_DIGITS[digit as usize]);
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ExtensionValue for Uuid {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+
+    /// The canonical representation of a UUID is the hyphenated form in
+    /// lowercase, e.g., `uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")`.
+    fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
+        Some((
+            constants::UUID_FROM_STR_NAME.clone(),
+            vec![RestrictedExpr::val(self.to_string())],
+        ))
+    }
+}
+
+impl ExtensionValue for Ulid {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        true
+    }
+
+    /// The canonical representation of a ULID is in uppercase, e.g.,
+    /// `ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")`.
+    fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
+        Some((
+            constants::ULID_FROM_STR_NAME.clone(),
+            vec![RestrictedExpr::val(self.to_string())],
+        ))
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: &str,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        Some(advice.into()),
+    )
+}
+
+/// Cedar function that constructs a `uuid` Cedar type from a Cedar string
+fn uuid_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let uuid = Uuid::parse(s).ok_or_else(|| {
+        extension_err(
+            &constants::UUID_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a well-formed UUID"),
+            VALID_UUID_HELP,
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(uuid),
+        constants::UUID_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Cedar function that constructs a `ulid` Cedar type from a Cedar string
+fn ulid_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let ulid = Ulid::parse(s).ok_or_else(|| {
+        extension_err(
+            &constants::ULID_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a well-formed ULID"),
+            VALID_ULID_HELP,
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(ulid),
+        constants::ULID_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Check that `v` is of the extension type `T` and, if it is, return the
+/// wrapped value
+fn as_identifier<T: 'static>(v: &Value, typename: Name) -> Result<&T, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == typename => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let t = ev
+                .value()
+                .as_any()
+                .downcast_ref::<T>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(t)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension { name: typename },
+                v,
+                ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension { name: typename },
+            v,
+        )),
+    }
+}
+
+/// Cedar function that returns the version of a `uuid`
+fn uuid_version(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let uuid = as_identifier::<Uuid>(arg, Uuid::typename())?;
+    Ok(Value::from(uuid.version()).into())
+}
+
+/// Cedar function that returns the timestamp of a `ulid` in milliseconds
+/// since the Unix epoch
+fn timestamp_millis(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let ulid = as_identifier::<Ulid>(arg, Ulid::typename())?;
+    Ok(Value::from(ulid.timestamp_millis()).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let uuid_type = SchemaType::Extension {
+        name: Uuid::typename(),
+    };
+    let ulid_type = SchemaType::Extension {
+        name: Ulid::typename(),
+    };
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::UUID_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(uuid_from_str),
+                uuid_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Parses a hyphenated UUID such as `"a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"`, in either case."#),
+            ExtensionFunction::unary(
+                constants::ULID_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(ulid_from_str),
+                ulid_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Parses a ULID such as `"01ARZ3NDEKTSV4RRFFQ69G5FAV"`, in either case."#),
+            ExtensionFunction::unary(
+                constants::UUID_VERSION.clone(),
+                CallStyle::MethodStyle,
+                Box::new(uuid_version),
+                SchemaType::Long,
+                uuid_type,
+            )
+            .with_doc("Returns the version of the receiver, from 0 to 15."),
+            ExtensionFunction::unary(
+                constants::TIMESTAMP_MILLIS.clone(),
+                CallStyle::MethodStyle,
+                Box::new(timestamp_millis),
+                SchemaType::Long,
+                ulid_type,
+            )
+            .with_doc("Returns the timestamp of the receiver, in milliseconds since the Unix epoch."),
+        ],
+        [constants::ULID_FROM_STR_NAME.clone()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[test]
+    fn parse_uuids() {
+        assert_eq!(
+            Uuid::parse("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+            Some(Uuid(0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11))
+        );
+        assert_eq!(
+            Uuid::parse("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11"),
+            Some(Uuid(0xa0eebc99_9c0b_4ef8_bb6d_6bb9bd380a11))
+        );
+        assert_eq!(
+            Uuid::parse("00000000-0000-0000-0000-000000000000"),
+            Some(Uuid(0))
+        );
+        for s in [
+            "",
+            "a0eebc999c0b4ef8bb6d6bb9bd380a11",
+            "{a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}",
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1",
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a111",
+            "a0eebc99-9c0b4-ef8-bb6d-6bb9bd380a11",
+            "g0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            "+0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            " a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+        ] {
+            assert_eq!(Uuid::parse(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn parse_ulids() {
+        let ulid = Ulid::parse("01ARZ3NDEKTSV4RRFFQ69G5FAV").unwrap();
+        assert_eq!(ulid.to_string(), "01ARZ3NDEKTSV4RRFFQ69G5FAV");
+        assert_eq!(ulid.timestamp_millis(), 1_469_922_850_259);
+        assert_eq!(Ulid::parse("01arz3ndektsv4rrffq69g5fav"), Some(ulid));
+        assert_eq!(
+            Ulid::parse("7ZZZZZZZZZZZZZZZZZZZZZZZZZ"),
+            Some(Ulid(u128::MAX))
+        );
+        assert_eq!(Ulid::parse("00000000000000000000000000"), Some(Ulid(0)));
+        for s in [
+            "",
+            "01ARZ3NDEKTSV4RRFFQ69G5FA",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAVV",
+            // too large for 128 bits
+            "80000000000000000000000000",
+            // `I`, `L`, `O`, and `U` are not Crockford digits
+            "01ARZ3NDEKTSV4RRFFQ69G5FAI",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAL",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAO",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU",
+            "01ARZ3NDEKTSV4RRFFQ69G5FA-",
+        ] {
+            assert_eq!(Ulid::parse(s), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn identifier_methods() {
+        for (src, expected) in [
+            (
+                r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").uuidVersion()"#,
+                Value::from(4),
+            ),
+            (
+                r#"uuid("00000000-0000-0000-0000-000000000000").uuidVersion()"#,
+                Value::from(0),
+            ),
+            (
+                r#"uuid("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11") == uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")"#,
+                Value::from(true),
+            ),
+            (
+                r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11") == uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a12")"#,
+                Value::from(false),
+            ),
+            (
+                r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11") == "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11""#,
+                Value::from(false),
+            ),
+            (
+                r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV").timestampMillis()"#,
+                Value::from(1_469_922_850_259),
+            ),
+            (
+                r#"ulid("01arz3ndektsv4rrffq69g5fav") == ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")"#,
+                Value::from(true),
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(expected), "{src}");
+        }
+    }
+
+    #[test]
+    fn ulid_comparisons() {
+        for (src, expected) in [
+            // same timestamp, ordered by the random part
+            (
+                r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV") < ulid("01ARZ3NDEKTSV4RRFFQ69G5FAW")"#,
+                true,
+            ),
+            // ordered by timestamp first
+            (
+                r#"ulid("01ARZ3NDEKZZZZZZZZZZZZZZZZ") < ulid("01ARZ3NDEM0000000000000000")"#,
+                true,
+            ),
+            (
+                r#"ulid("01arz3ndektsv4rrffq69g5fav") <= ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")"#,
+                true,
+            ),
+            (
+                r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV") > ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")"#,
+                false,
+            ),
+            (
+                r#"ulid("7ZZZZZZZZZZZZZZZZZZZZZZZZZ") >= ulid("00000000000000000000000000")"#,
+                true,
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(Value::from(expected)), "{src}");
+        }
+        // UUIDs are not ordered
+        assert_matches!(
+            eval(
+                r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11") < uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a12")"#
+            ),
+            Err(EvaluationError::TypeError(_))
+        );
+        assert_matches!(
+            eval(r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV") < "01ARZ3NDEKTSV4RRFFQ69G5FAW""#),
+            Err(EvaluationError::TypeError(_))
+        );
+    }
+
+    #[test]
+    fn constructor_errors() {
+        for (src, name, help) in [
+            (
+                r#"uuid("a0eebc999c0b4ef8bb6d6bb9bd380a11")"#,
+                &*constants::UUID_FROM_STR_NAME,
+                VALID_UUID_HELP,
+            ),
+            (
+                r#"ulid("80000000000000000000000000")"#,
+                &*constants::ULID_FROM_STR_NAME,
+                VALID_ULID_HELP,
+            ),
+        ] {
+            assert_matches!(
+                eval(src),
+                Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                    extension_name,
+                    advice,
+                    ..
+                })) => {
+                    assert_eq!(&extension_name, name, "{src}");
+                    assert_eq!(advice.as_deref(), Some(help), "{src}");
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn identifier_type_errors() {
+        assert_matches!(
+            eval(r#""01ARZ3NDEKTSV4RRFFQ69G5FAV".timestampMillis()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, advice, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: Ulid::typename() }]);
+                assert_eq!(advice.as_deref(), Some(ADVICE_MSG));
+            }
+        );
+        assert_matches!(
+            eval(r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").timestampMillis()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: Ulid::typename() }]);
+            }
+        );
+    }
+
+    #[test]
+    fn canonical_repr() {
+        let uuid = Uuid::parse("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11").unwrap();
+        assert_eq!(uuid.to_string(), "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11");
+        let ulid = Ulid::parse("01arz3ndektsv4rrffq69g5fav").unwrap();
+        assert_eq!(
+            ulid.canonical_repr(),
+            Some((
+                constants::ULID_FROM_STR_NAME.clone(),
+                vec![RestrictedExpr::val("01ARZ3NDEKTSV4RRFFQ69G5FAV")]
+            ))
+        );
+    }
+}
@@ -22,6 +22,7 @@ use crate::ast::{
 };
 use crate::entities::SchemaType;
 use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
 
 use std::sync::Arc;
 
@@ -50,12 +51,21 @@ pub(crate) mod names {
     pub static IS_IN_RANGE: LazyLock<Name> = LazyLock::new(|| {
         Name::parse_unqualified_name("isInRange").expect("should be a valid identifier")
     });
+    pub static IPSET_FROM_SET_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("ipset").expect("should be a valid identifier")
+    });
+    pub static IS_IN_SET: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("isInSet").expect("should be a valid identifier")
+    });
 }
 
 /// Help message to display when a String was provided where an IP value was expected.
 /// This error is likely due to confusion between "127.0.0.1" and ip("127.0.0.1").
 const ADVICE_MSG: &str = "maybe you forgot to apply the `ip` constructor?";
 
+/// Help message to display when a Set was provided where an IP set value was expected.
+const IPSET_ADVICE_MSG: &str = "maybe you forgot to apply the `ipset` constructor?";
+
 /// Help text describing the valid IP address format, used when an IP parse error occurs.
 const VALID_IP_HELP: &str = "valid IP strings are IPv4/IPv6 addresses or CIDR ranges like `127.0.0.1`, `127.0.0.1/24`, or `ffee::/64`";
 
@@ -73,6 +83,9 @@ const PREFIX_STR_MAX_LEN_V6: u8 = 3;
 /// len('ABCD:EF01:2345:6789:ABCD:EF01:2345:6789/128') = 43
 const IP_STR_REP_MAX_LEN: u8 = 43;
 
+/// An IP address or range. Addresses are ordered with all IPv4 addresses
+/// before all IPv6 addresses, then numerically by address, then by prefix
+/// length.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 struct IPAddr {
     /// the actual address, without prefix
@@ -130,38 +143,167 @@ impl IPAddr {
 
     /// Return true if this is contained in the given `IPAddr`
     fn is_in_range(&self, other: &Self) -> bool {
-        match (&self.addr, &other.addr) {
-            (std::net::IpAddr::V4(self_v4), std::net::IpAddr::V4(other_v4)) => {
-                let netmask = |prefix: u8| {
-                    u32::MAX
-                        .checked_shl((PREFIX_MAX_LEN_V4 - prefix).into())
-                        .unwrap_or(0)
-                };
-                let hostmask = |prefix: u8| u32::MAX.checked_shr(prefix.into()).unwrap_or(0);
+        match (self.range(), other.range()) {
+            (AddrRange::V4(first, last), AddrRange::V4(other_first, other_last)) => {
+                other_first <= first && last <= other_last
+            }
+            (AddrRange::V6(first, last), AddrRange::V6(other_first, other_last)) => {
+                other_first <= first && last <= other_last
+            }
+            (_, _) => false,
+        }
+    }
 
-                let self_network = u32::from(*self_v4) & netmask(self.prefix);
-                let other_network = u32::from(*other_v4) & netmask(other.prefix);
-                let self_broadcast = u32::from(*self_v4) | hostmask(self.prefix);
-                let other_broadcast = u32::from(*other_v4) | hostmask(other.prefix);
-                other_network <= self_network && self_broadcast <= other_broadcast
+    /// The first (network) and last (broadcast) address of this range
+    fn range(&self) -> AddrRange {
+        match self.addr {
+            std::net::IpAddr::V4(v4) => {
+                let netmask = u32::MAX
+                    .checked_shl((PREFIX_MAX_LEN_V4 - self.prefix).into())
+                    .unwrap_or(0);
+                let hostmask = u32::MAX.checked_shr(self.prefix.into()).unwrap_or(0);
+                AddrRange::V4(u32::from(v4) & netmask, u32::from(v4) | hostmask)
             }
-            (std::net::IpAddr::V6(self_v6), std::net::IpAddr::V6(other_v6)) => {
-                let netmask = |prefix: u8| {
-                    u128::MAX
-                        .checked_shl((PREFIX_MAX_LEN_V6 - prefix).into())
-                        .unwrap_or(0)
-                };
-                let hostmask = |prefix: u8| u128::MAX.checked_shr(prefix.into()).unwrap_or(0);
+            std::net::IpAddr::V6(v6) => {
+                let netmask = u128::MAX
+                    .checked_shl((PREFIX_MAX_LEN_V6 - self.prefix).into())
+                    .unwrap_or(0);
+                let hostmask = u128::MAX.checked_shr(self.prefix.into()).unwrap_or(0);
+                AddrRange::V6(u128::from(v6) & netmask, u128::from(v6) | hostmask)
+            }
+        }
+    }
+}
+
+/// The first and last address of an IP range, as integers
+#[derive(Debug, Clone, Copy)]
+enum AddrRange {
+    V4(u32, u32),
+    V6(u128, u128),
+}
+
+/// A set of IP ranges, aggregated into sorted ranges of addresses which
+/// neither overlap nor touch, so that testing whether an address or range is
+/// in the set is a binary search.
+///
+/// Two `IPSet`s are equal if they contain the same addresses, regardless of
+/// the ranges they were constructed from. Sets are ordered by their IPv4
+/// ranges, then by their IPv6 ranges, each compared lexicographically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct IPSet {
+    v4: Vec<(u32, u32)>,
+    v6: Vec<(u128, u128)>,
+}
+
+impl IPSet {
+    /// The Cedar typename of all ipset values
+    fn typename() -> Name {
+        names::IPSET_FROM_SET_NAME.clone()
+    }
 
-                let self_network = u128::from(*self_v6) & netmask(self.prefix);
-                let other_network = u128::from(*other_v6) & netmask(other.prefix);
-                let self_broadcast = u128::from(*self_v6) | hostmask(self.prefix);
-                let other_broadcast = u128::from(*other_v6) | hostmask(other.prefix);
-                other_network <= self_network && self_broadcast <= other_broadcast
+    /// Aggregate the given IP addresses and ranges into an `IPSet`
+    fn from_ranges<'a>(ranges: impl IntoIterator<Item = &'a IPAddr>) -> Self {
+        let (mut v4, mut v6) = (Vec::new(), Vec::new());
+        for ip in ranges {
+            match ip.range() {
+                AddrRange::V4(first, last) => v4.push((first, last)),
+                AddrRange::V6(first, last) => v6.push((first, last)),
             }
-            (_, _) => false,
+        }
+        Self {
+            v4: aggregate(v4, |a| a.checked_add(1)),
+            v6: aggregate(v6, |a| a.checked_add(1)),
+        }
+    }
+
+    /// Return true if every address in `ip` is in this set
+    fn contains(&self, ip: &IPAddr) -> bool {
+        match ip.range() {
+            AddrRange::V4(first, last) => covers(&self.v4, first, last),
+            AddrRange::V6(first, last) => covers(&self.v6, first, last),
+        }
+    }
+
+    /// The fewest CIDR ranges covering exactly the addresses in this set
+    fn to_cidrs(&self) -> impl Iterator<Item = IPAddr> + '_ {
+        let v4 = self.v4.iter().flat_map(|(first, last)| {
+            range_to_cidrs((*first).into(), (*last).into(), PREFIX_MAX_LEN_V4).map(
+                |(network, prefix)| {
+                    #[expect(
+                        clippy::cast_possible_truncation,
+                        reason = "the network address of an IPv4 range fits in 32 bits"
+                    )]
+                    let network = network as u32;
+                    IPAddr {
+                        addr: std::net::IpAddr::V4(network.into()),
+                        prefix,
+                    }
+                },
+            )
+        });
+        let v6 = self.v6.iter().flat_map(|(first, last)| {
+            range_to_cidrs(*first, *last, PREFIX_MAX_LEN_V6).map(|(network, prefix)| IPAddr {
+                addr: std::net::IpAddr::V6(network.into()),
+                prefix,
+            })
+        });
+        v4.chain(v6)
+    }
+}
+
+/// Sort `ranges` and merge the ones which overlap or touch, where `next`
+/// returns the address after the given one, if any
+fn aggregate<T: Ord + Copy>(mut ranges: Vec<(T, T)>, next: impl Fn(T) -> Option<T>) -> Vec<(T, T)> {
+    ranges.sort_unstable();
+    let mut aggregated: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+    for (first, last) in ranges {
+        match aggregated.last_mut() {
+            Some((_, prev_last)) if next(*prev_last).is_none_or(|after| first <= after) => {
+                *prev_last = last.max(*prev_last);
+            }
+            _ => aggregated.push((first, last)),
         }
     }
+    aggregated
+}
+
+/// Return true if the aggregated `ranges` include every address from `first`
+/// to `last`. Since aggregated ranges don't touch, this is the case exactly
+/// when the last range starting at or before `first` ends at or after `last`.
+fn covers<T: Ord + Copy>(ranges: &[(T, T)], first: T, last: T) -> bool {
+    let after = ranges.partition_point(|(start, _)| *start <= first);
+    after
+        .checked_sub(1)
+        .and_then(|i| ranges.get(i))
+        .is_some_and(|(_, end)| last <= *end)
+}
+
+/// Split the addresses from `first` to `last`, of an IP version with `bits`
+/// bits per address, into the fewest CIDR ranges, as `(network, prefix)` pairs
+fn range_to_cidrs(first: u128, last: u128, bits: u8) -> impl Iterator<Item = (u128, u8)> {
+    let mut next = Some(first);
+    std::iter::from_fn(move || {
+        let network = next?;
+        // The block starting at `network` can be no larger than the alignment
+        // of `network` allows, and must not go past `last`
+        let alignment = network.trailing_zeros().min(bits.into());
+        let fits = match (last - network).checked_add(1) {
+            Some(count) => count.ilog2(),
+            None => u128::BITS,
+        };
+        let host_bits = alignment.min(fits);
+        let broadcast = network + u128::MAX.checked_shr(u128::BITS - host_bits).unwrap_or(0);
+        next = if broadcast < last {
+            Some(broadcast + 1)
+        } else {
+            None
+        };
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "`host_bits` is at most `bits`, which is at most 128"
+        )]
+        Some((network, bits - host_bits as u8))
+    })
 }
 
 fn parse_prefix(s: &str, max: u8, max_len: u8) -> Result<u8, String> {
@@ -257,9 +399,31 @@ impl ExtensionValue for IPAddr {
     }
 }
 
+impl ExtensionValue for IPSet {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+
+    /// The canonical representation of an IP set is the `ipset` constructor
+    /// applied to the fewest CIDR ranges covering the set, in order.
+    fn canonical_repr(&self) -> Option<(Name, Vec<crate::ast::RestrictedExpr>)> {
+        Some((
+            names::IPSET_FROM_SET_NAME.clone(),
+            vec![crate::ast::RestrictedExpr::set(
+                self.to_cidrs()
+                    .map(|ip| crate::ast::RestrictedExpr::val(ip.to_string())),
+            )],
+        ))
+    }
+}
+
 fn extension_err(msg: impl Into<String>, advice: Option<String>) -> evaluator::EvaluationError {
     evaluator::EvaluationError::failed_extension_function_application(
         names::EXTENSION_NAME.clone(),
+        ExtensionErrorKind::Parse,
         msg.into(),
         None, // source loc will be added by the evaluator
         advice,
@@ -374,6 +538,56 @@ fn as_ipaddr(v: &Value) -> Result<&IPAddr, evaluator::EvaluationError> {
     }
 }
 
+/// Cedar function which constructs an `ipset` Cedar type from a Cedar set
+/// of strings, each an IP address or range
+fn ipset_from_set(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let ranges = arg
+        .get_as_set()?
+        .iter()
+        .map(|v| {
+            IPAddr::from_str(v.get_as_string()?.as_str())
+                .map_err(|e| extension_err(e, Some(VALID_IP_HELP.to_string())))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let ipset = RepresentableExtensionValue::new(
+        Arc::new(IPSet::from_ranges(&ranges)),
+        names::IPSET_FROM_SET_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(ipset)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+fn as_ipset(v: &Value) -> Result<&IPSet, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == IPSet::typename() => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let ipset = ev
+                .value()
+                .as_any()
+                .downcast_ref::<IPSet>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(ipset)
+        }
+        ValueKind::Set(_) => Err(evaluator::EvaluationError::type_error_with_advice_single(
+            Type::Extension {
+                name: IPSet::typename(),
+            },
+            v,
+            IPSET_ADVICE_MSG.into(),
+        )),
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: IPSet::typename(),
+            },
+            v,
+        )),
+    }
+}
+
 /// Cedar function which tests whether an `ipaddr` Cedar type is an IPv4
 /// address, returning a Cedar bool
 fn is_ipv4(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
@@ -416,11 +630,22 @@ fn is_in_range(child: &Value, parents: &[Value]) -> evaluator::Result<ExtensionO
     Ok(parent_ips.into())
 }
 
+/// Cedar function which tests whether an `ipaddr` Cedar type is in the IP
+/// ranges of an `ipset` Cedar type, returning a Cedar bool
+fn is_in_set(ip: &Value, set: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let ipaddr = as_ipaddr(ip)?;
+    let ipset = as_ipset(set)?;
+    Ok(ipset.contains(ipaddr).into())
+}
+
 /// Construct the extension
 pub fn extension() -> Extension {
     let ipaddr_type = SchemaType::Extension {
         name: IPAddr::typename(),
     };
+    let ipset_type = SchemaType::Extension {
+        name: IPSet::typename(),
+    };
     Extension::new(
         names::EXTENSION_NAME.clone(),
         vec![
@@ -430,42 +655,66 @@ pub fn extension() -> Extension {
                 Box::new(ip_from_str),
                 ipaddr_type.clone(),
                 SchemaType::String,
-            ),
+            )
+            .with_doc(r#"Parses a string such as `"10.0.0.1"` or `"10.0.0.0/24"` as an IP address or range."#),
             ExtensionFunction::unary(
                 names::IS_IPV4.clone(),
                 CallStyle::MethodStyle,
                 Box::new(is_ipv4),
                 SchemaType::Bool,
                 ipaddr_type.clone(),
-            ),
+            )
+            .with_doc("Tests whether the receiver is an IPv4 address or range."),
             ExtensionFunction::unary(
                 names::IS_IPV6.clone(),
                 CallStyle::MethodStyle,
                 Box::new(is_ipv6),
                 SchemaType::Bool,
                 ipaddr_type.clone(),
-            ),
+            )
+            .with_doc("Tests whether the receiver is an IPv6 address or range."),
             ExtensionFunction::unary(
                 names::IS_LOOPBACK.clone(),
                 CallStyle::MethodStyle,
                 Box::new(is_loopback),
                 SchemaType::Bool,
                 ipaddr_type.clone(),
-            ),
+            )
+            .with_doc("Tests whether the receiver is a loopback address or range."),
             ExtensionFunction::unary(
                 names::IS_MULTICAST.clone(),
                 CallStyle::MethodStyle,
                 Box::new(is_multicast),
                 SchemaType::Bool,
                 ipaddr_type.clone(),
-            ),
+            )
+            .with_doc("Tests whether the receiver is a multicast address or range."),
             ExtensionFunction::variadic(
                 names::IS_IN_RANGE.clone(),
                 CallStyle::MethodStyle,
                 Box::new(is_in_range),
                 SchemaType::Bool,
-                (ipaddr_type.clone(), ipaddr_type),
-            ),
+                (ipaddr_type.clone(), ipaddr_type.clone()),
+            )
+            .with_doc("Tests whether the receiver is contained in the argument range."),
+            ExtensionFunction::unary(
+                names::IPSET_FROM_SET_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(ipset_from_set),
+                ipset_type.clone(),
+                SchemaType::Set {
+                    element_ty: Box::new(SchemaType::String),
+                },
+            )
+            .with_doc("Parses a set of strings, each an IP address or range, as an ipset."),
+            ExtensionFunction::binary(
+                names::IS_IN_SET.clone(),
+                CallStyle::MethodStyle,
+                Box::new(is_in_set),
+                SchemaType::Bool,
+                (ipaddr_type, ipset_type),
+            )
+            .with_doc("Tests whether the receiver is contained in the ranges of the argument ipset."),
         ],
         std::iter::empty(),
     )
@@ -1404,6 +1653,131 @@ mod tests {
         )));
     }
 
+    /// Build an `IPSet` from the given strings
+    fn ipset(ranges: &[&str]) -> IPSet {
+        let ranges: Vec<IPAddr> = ranges.iter().map(|s| s.parse().unwrap()).collect();
+        IPSet::from_ranges(&ranges)
+    }
+
+    #[test]
+    fn ipset_aggregation() {
+        let cidrs = |set: &IPSet| set.to_cidrs().map(|ip| ip.to_string()).collect::<Vec<_>>();
+
+        // Overlapping and touching ranges are merged, and the result is
+        // split back into the fewest CIDR ranges
+        let set = ipset(&[
+            "10.0.1.0/24",
+            "10.0.0.0/24",
+            "10.0.0.128/25",
+            "10.0.2.0/23",
+            "192.168.0.1",
+        ]);
+        assert_eq!(cidrs(&set), ["10.0.0.0/22", "192.168.0.1/32"]);
+        assert_eq!(set, ipset(&["192.168.0.1", "10.0.0.0/22"]));
+
+        let set = ipset(&["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.4"]);
+        assert_eq!(cidrs(&set), ["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/32"]);
+
+        // The whole address space
+        let set = ipset(&["0.0.0.0/1", "128.0.0.0/1", "::/0", "ffff::1"]);
+        assert_eq!(cidrs(&set), ["0.0.0.0/0", "::/0"]);
+        assert!(set.contains(&"255.255.255.255".parse().unwrap()));
+        assert!(set.contains(&"ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()));
+
+        assert_eq!(cidrs(&ipset(&[])), Vec::<String>::new());
+    }
+
+    #[test]
+    fn ipset_contains() {
+        let set = ipset(&["10.0.0.0/24", "10.0.1.0/24", "192.168.0.0/16", "fe80::/10"]);
+        for (ip, expected) in [
+            ("10.0.0.0", true),
+            ("10.0.1.255", true),
+            ("10.0.2.0", false),
+            ("9.255.255.255", false),
+            // Ranges are in the set if all their addresses are, even if they
+            // span several of the ranges the set was constructed from
+            ("10.0.0.0/23", true),
+            ("10.0.0.0/22", false),
+            ("192.168.10.0/24", true),
+            ("192.0.0.0/8", false),
+            ("fe80::1", true),
+            ("fe80::/9", false),
+            ("::1", false),
+            // IPv4 addresses are never in IPv6 ranges and vice versa
+            ("::ffff:a00:1", false),
+        ] {
+            assert_eq!(set.contains(&ip.parse().unwrap()), expected, "{ip}");
+        }
+        assert!(!ipset(&[]).contains(&"10.0.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn ip_is_in_set() {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ip("10.1.2.3").isInSet(ipset(["192.168.0.0/16", "10.0.0.0/8"]))"#)
+                    .unwrap()
+            ),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ip("8.8.8.8").isInSet(ipset(["192.168.0.0/16", "10.0.0.0/8"]))"#)
+                    .unwrap()
+            ),
+            Ok(Value::from(false))
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ip("8.8.8.8").isInSet(ipset([]))"#).unwrap()
+            ),
+            Ok(Value::from(false))
+        );
+        // `ipset`s are equal if they contain the same addresses
+        assert_eq!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ipset(["10.0.0.0/25", "10.0.0.128/25"]) == ipset(["10.0.0.0/24"])"#)
+                    .unwrap()
+            ),
+            Ok(Value::from(true))
+        );
+
+        // Invalid ranges are an error
+        assert_ipaddr_err(eval.interpret_inline_policy(
+            &parse_expr(r#"ipset(["10.0.0.0/8", "10.0.0.0/33"])"#).unwrap(),
+        ));
+        assert_matches!(
+            eval.interpret_inline_policy(&parse_expr(r#"ipset(["10.0.0.0/8", 1])"#).unwrap()),
+            Err(EvaluationError::TypeError(_))
+        );
+        // Forgetting the `ipset` constructor
+        assert_matches!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ip("10.1.2.3").isInSet(["10.0.0.0/8"])"#).unwrap()
+            ),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, advice, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension {
+                    name: Name::parse_unqualified_name("ipset")
+                        .expect("should be a valid identifier")
+                }]);
+                assert_eq!(advice, Some(IPSET_ADVICE_MSG.into()));
+            }
+        );
+        assert_matches!(
+            eval.interpret_inline_policy(
+                &parse_expr(r#"ip("10.1.2.3").isInSet(ip("10.0.0.0/8"))"#).unwrap()
+            ),
+            Err(EvaluationError::TypeError(_))
+        );
+    }
+
     #[test]
     fn test_contains_at_least_two() {
         assert!(contains_at_least_two(":::", ':'));
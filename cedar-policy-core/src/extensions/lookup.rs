@@ -0,0 +1,386 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'lookup' extension, which looks up a value
+//! in nested records by a dotted path given as data, e.g., a path stored in an
+//! entity attribute.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue, Name,
+    RepresentableExtensionValue, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use smol_str::SmolStr;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Help text describing the valid format of paths
+const VALID_PATH_HELP: &str =
+    "a path is a sequence of attribute names separated by `.`, e.g., `\"limits.daily\"`";
+
+/// The result of looking up a path: the value found at the path, if any.
+/// An absent value is ordered before every present value.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Optional(Option<Value>);
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("lookup").expect("should be a valid identifier")
+    });
+    pub static OPTIONAL_TYPE_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("optional").expect("should be a valid identifier")
+    });
+    pub static LOOKUP: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("lookup").expect("should be a valid identifier")
+    });
+    pub static IS_PRESENT: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("isPresent").expect("should be a valid identifier")
+    });
+    pub static AS_STRING: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("asString").expect("should be a valid identifier")
+    });
+    pub static AS_LONG: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("asLong").expect("should be a valid identifier")
+    });
+    pub static AS_BOOL: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("asBool").expect("should be a valid identifier")
+    });
+}
+
+impl Optional {
+    /// The Cedar typename of `optional` values
+    fn typename() -> Name {
+        constants::OPTIONAL_TYPE_NAME.clone()
+    }
+}
+
+impl ExtensionValue for Optional {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: Option<String>,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        advice,
+    )
+}
+
+/// Follow `path` from `record`. Only records are traversed, so the result is
+/// `None` if any attribute on the path is missing or any value before the end
+/// of the path is not a record.
+fn follow_path<'a>(record: &'a BTreeMap<SmolStr, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (last, init) = path.split_last()?;
+    let mut current = record;
+    for attr in init {
+        match &current.get(*attr)?.value {
+            ValueKind::Record(r) => current = r,
+            _ => return None,
+        }
+    }
+    current.get(*last)
+}
+
+/// Cedar function that looks up a dotted path in a record, returning an
+/// `optional` value which is empty if there is nothing at the path
+fn lookup(record: &Value, path: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let rec = record.get_as_record()?;
+    let path_str = path.get_as_string()?;
+    let segments: Vec<&str> = path_str.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(extension_err(
+            &constants::LOOKUP,
+            ExtensionErrorKind::Parse,
+            format!("`{path_str}` is not a valid path"),
+            Some(VALID_PATH_HELP.into()),
+        ));
+    }
+    let found = follow_path(rec, &segments).cloned();
+    let e = RepresentableExtensionValue::new(
+        Arc::new(Optional(found)),
+        constants::LOOKUP.clone(),
+        vec![record.clone().into(), path.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: record.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Check that `v` is an `optional` type and, if it is, return the wrapped value
+fn as_optional(v: &Value) -> Result<&Optional, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == Optional::typename() => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let o = ev
+                .value()
+                .as_any()
+                .downcast_ref::<Optional>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(o)
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: Optional::typename(),
+            },
+            v,
+        )),
+    }
+}
+
+/// Return the value wrapped in an `optional`, or an error naming the function
+/// `fname` if there is none
+fn present_value<'a>(fname: &Name, v: &'a Value) -> evaluator::Result<&'a Value> {
+    as_optional(v)?.0.as_ref().ok_or_else(|| {
+        extension_err(
+            fname,
+            ExtensionErrorKind::InvalidArgument,
+            "no value was found at the path",
+            Some("guard the call with `.isPresent()`".into()),
+        )
+    })
+}
+
+/// Cedar function that tests whether an `optional` holds a value
+fn is_present(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    Ok(Value::from(as_optional(arg)?.0.is_some()).into())
+}
+
+/// Cedar function that returns the `String` held by an `optional`
+fn as_string(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = present_value(&constants::AS_STRING, arg)?.get_as_string()?;
+    Ok(Value::from(s.clone()).into())
+}
+
+/// Cedar function that returns the `Long` held by an `optional`
+fn as_long(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let i = present_value(&constants::AS_LONG, arg)?.get_as_long()?;
+    Ok(Value::from(i).into())
+}
+
+/// Cedar function that returns the `Bool` held by an `optional`
+fn as_bool(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let b = present_value(&constants::AS_BOOL, arg)?.get_as_bool()?;
+    Ok(Value::from(b).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let optional_type = SchemaType::Extension {
+        name: Optional::typename(),
+    };
+    let any_record = SchemaType::Record {
+        attrs: BTreeMap::new(),
+        open_attrs: true,
+    };
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::binary(
+                constants::LOOKUP.clone(),
+                CallStyle::MethodStyle,
+                Box::new(lookup),
+                optional_type.clone(),
+                (any_record, SchemaType::String),
+            )
+            .with_doc("Looks up a dotted path such as `\"limits.daily\"` in the receiver, a record, returning an `optional` which is empty if nothing is at the path."),
+            ExtensionFunction::unary(
+                constants::IS_PRESENT.clone(),
+                CallStyle::MethodStyle,
+                Box::new(is_present),
+                SchemaType::Bool,
+                optional_type.clone(),
+            )
+            .with_doc("Tests whether the receiver holds a value."),
+            ExtensionFunction::unary(
+                constants::AS_STRING.clone(),
+                CallStyle::MethodStyle,
+                Box::new(as_string),
+                SchemaType::String,
+                optional_type.clone(),
+            )
+            .with_doc("Returns the `String` held by the receiver. Errors if it is empty or holds another type."),
+            ExtensionFunction::unary(
+                constants::AS_LONG.clone(),
+                CallStyle::MethodStyle,
+                Box::new(as_long),
+                SchemaType::Long,
+                optional_type.clone(),
+            )
+            .with_doc("Returns the `Long` held by the receiver. Errors if it is empty or holds another type."),
+            ExtensionFunction::unary(
+                constants::AS_BOOL.clone(),
+                CallStyle::MethodStyle,
+                Box::new(as_bool),
+                SchemaType::Bool,
+                optional_type,
+            )
+            .with_doc("Returns the `Bool` held by the receiver. Errors if it is empty or holds another type."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    const CONFIG: &str =
+        r#"{ limits: { daily: 10, enabled: true, owner: "alice" }, region: "eu", tags: ["a"] }"#;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        let src = src.replace("CONFIG", CONFIG);
+        eval.interpret_inline_policy(&parse_expr(&src).expect("parsing error"))
+    }
+
+    #[test]
+    fn lookup_methods() {
+        for (src, expected) in [
+            (r#"CONFIG.lookup("limits.daily").asLong()"#, Value::from(10)),
+            (
+                r#"CONFIG.lookup("limits.enabled").asBool()"#,
+                Value::from(true),
+            ),
+            (
+                r#"CONFIG.lookup("limits.owner").asString()"#,
+                Value::from("alice"),
+            ),
+            (r#"CONFIG.lookup("region").asString()"#, Value::from("eu")),
+            (r#"CONFIG.lookup("limits").isPresent()"#, Value::from(true)),
+            (
+                r#"CONFIG.lookup("limits.weekly").isPresent()"#,
+                Value::from(false),
+            ),
+            (
+                r#"CONFIG.lookup("missing.daily").isPresent()"#,
+                Value::from(false),
+            ),
+            // only records are traversed
+            (
+                r#"CONFIG.lookup("region.length").isPresent()"#,
+                Value::from(false),
+            ),
+            (r#"CONFIG.lookup("tags.a").isPresent()"#, Value::from(false)),
+            // the path may be computed
+            (
+                r#"CONFIG.lookup({path: "limits.daily"}.path).isPresent()"#,
+                Value::from(true),
+            ),
+            (
+                r#"CONFIG.lookup("limits.weekly").isPresent() && CONFIG.lookup("limits.weekly").asLong() > 5"#,
+                Value::from(false),
+            ),
+            (
+                r#"CONFIG.lookup("limits.daily") == {limits: {daily: 10}}.lookup("limits.daily")"#,
+                Value::from(true),
+            ),
+            (
+                r#"CONFIG.lookup("limits.weekly") == {}.lookup("x")"#,
+                Value::from(true),
+            ),
+            (
+                r#"CONFIG.lookup("limits.daily") == CONFIG.lookup("limits.weekly")"#,
+                Value::from(false),
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(expected), "{src}");
+        }
+    }
+
+    #[test]
+    fn lookup_errors() {
+        for src in [
+            r#"CONFIG.lookup("")"#,
+            r#"CONFIG.lookup("limits..daily")"#,
+            r#"CONFIG.lookup(".region")"#,
+        ] {
+            assert_matches!(
+                eval(src),
+                Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                    extension_name,
+                    kind,
+                    advice,
+                    ..
+                })) => {
+                    assert_eq!(extension_name, *constants::LOOKUP, "{src}");
+                    assert_eq!(kind, evaluation_errors::ExtensionErrorKind::Parse, "{src}");
+                    assert_eq!(advice.as_deref(), Some(VALID_PATH_HELP), "{src}");
+                }
+            );
+        }
+        assert_matches!(
+            eval(r#"CONFIG.lookup("limits.weekly").asLong()"#),
+            Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                extension_name,
+                kind,
+                ..
+            })) => {
+                assert_eq!(extension_name, *constants::AS_LONG);
+                assert_eq!(kind, evaluation_errors::ExtensionErrorKind::InvalidArgument);
+            }
+        );
+        assert_matches!(
+            eval(r#"CONFIG.lookup("region").asLong()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::Long]);
+            }
+        );
+        assert_matches!(
+            eval(r#""limits".lookup("limits")"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::Record]);
+            }
+        );
+        assert_matches!(
+            eval(r#"CONFIG.asLong()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: Optional::typename() }]);
+            }
+        );
+    }
+}
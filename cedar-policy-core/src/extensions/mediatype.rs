@@ -0,0 +1,533 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'mediatype' extension, which provides an
+//! extension type for media (MIME) types such as `image/png`.
+//!
+//! `mediatype("Image/PNG")` parses a media type as defined by RFC 6838, with
+//! optional parameters such as `;charset=utf-8`. Types, subtypes, and
+//! parameter names are case-insensitive, as is the value of the `charset`
+//! parameter, so media types which differ only in case are equal.
+//!
+//! `m.matches("image/*")` tests a media type against a media range, which is
+//! `*/*`, `type/*`, or `type/subtype`, optionally with parameters that the
+//! media type must also have with the same values. Unlike matching strings
+//! with `like`, this doesn't need escaping and isn't fooled by differences in
+//! case or by parameters.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue, Literal, Name,
+    RepresentableExtensionValue, RestrictedExpr, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Help text describing the valid format of `mediatype` constructor arguments
+const VALID_MEDIATYPE_HELP: &str = "media types are written as a type and a subtype separated by `/`, optionally followed by `;name=value` parameters, e.g., `mediatype(\"text/plain;charset=utf-8\")`";
+
+/// Help text describing the valid format of `matches` arguments
+const VALID_RANGE_HELP: &str =
+    "media ranges are `*/*`, `type/*`, or `type/subtype`, optionally followed by `;name=value` parameters, e.g., `\"image/*\"`";
+
+/// Help message to display when a String was provided where a `mediatype`
+/// value was expected
+const ADVICE_MSG: &str = "maybe you forgot to apply the `mediatype` constructor?";
+
+/// The maximum length of a type or subtype, from RFC 6838
+const MAX_NAME_LEN: usize = 127;
+
+/// The wildcard type or subtype of a media range
+const WILDCARD: &str = "*";
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("mediatype").expect("should be a valid identifier")
+    });
+    pub static MEDIATYPE_FROM_STR_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("mediatype").expect("should be a valid identifier")
+    });
+    pub static MATCHES: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("matches").expect("should be a valid identifier")
+    });
+    pub static ESSENCE: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("essence").expect("should be a valid identifier")
+    });
+}
+
+/// A media type, or a media range when parsed with wildcards allowed. Types,
+/// subtypes, and parameter names are stored in lowercase, so media types are
+/// ordered by type, then subtype, then parameters.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct MediaType {
+    type_: String,
+    subtype: String,
+    params: BTreeMap<String, String>,
+}
+
+/// Is `c` allowed in a type, subtype, or parameter name (RFC 6838)
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c)
+}
+
+/// Is `c` allowed in an unquoted parameter value (an RFC 9110 token)
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Parse a type, subtype, or parameter name, in lowercase
+fn parse_name(s: &str) -> Option<String> {
+    let mut chars = s.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(is_name_char)
+        && s.len() <= MAX_NAME_LEN;
+    valid.then(|| s.to_ascii_lowercase())
+}
+
+/// Parse a parameter value, which is either a token or a quoted string.
+/// Returns the value and the rest of the input.
+fn parse_value(s: &str) -> Option<(String, &str)> {
+    match s.strip_prefix('"') {
+        Some(quoted) => {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => return Some((value, quoted.get(i + 1..)?)),
+                    '\\' => value.push(chars.next()?.1),
+                    c if c.is_control() => return None,
+                    c => value.push(c),
+                }
+            }
+            None
+        }
+        None => {
+            let (value, rest) = s.split_at(s.find(|c| !is_token_char(c)).unwrap_or(s.len()));
+            (!value.is_empty()).then(|| (value.to_string(), rest))
+        }
+    }
+}
+
+impl MediaType {
+    /// The Cedar typename of `mediatype` values
+    fn typename() -> Name {
+        constants::MEDIATYPE_FROM_STR_NAME.clone()
+    }
+
+    /// Parse a media type or, if `wildcards` is set, a media range
+    fn parse(s: &str, wildcards: bool) -> Option<Self> {
+        let (essence, mut rest) = s.split_at(s.find([';', ' ', '\t']).unwrap_or(s.len()));
+        let (type_, subtype) = essence.split_once('/')?;
+        let (type_, subtype) = match (type_, subtype) {
+            (WILDCARD, WILDCARD) if wildcards => (WILDCARD.to_string(), WILDCARD.to_string()),
+            (type_, WILDCARD) if wildcards => (parse_name(type_)?, WILDCARD.to_string()),
+            (type_, subtype) => (parse_name(type_)?, parse_name(subtype)?),
+        };
+        let mut params = BTreeMap::new();
+        while !rest.is_empty() {
+            rest = rest
+                .trim_start_matches([' ', '\t'])
+                .strip_prefix(';')?
+                .trim_start_matches([' ', '\t']);
+            let (name, after_name) = rest.split_once('=')?;
+            let name = parse_name(name)?;
+            let (mut value, after_value) = parse_value(after_name)?;
+            if name == "charset" {
+                value.make_ascii_lowercase();
+            }
+            if params.insert(name, value).is_some() {
+                return None;
+            }
+            rest = after_value;
+        }
+        Some(Self {
+            type_,
+            subtype,
+            params,
+        })
+    }
+
+    /// The type and subtype, without parameters, e.g., `text/plain`
+    fn essence(&self) -> String {
+        format!("{}/{}", self.type_, self.subtype)
+    }
+
+    /// Does this media type match `range`
+    fn matches(&self, range: &Self) -> bool {
+        (range.type_ == WILDCARD || range.type_ == self.type_)
+            && (range.subtype == WILDCARD || range.subtype == self.subtype)
+            && range
+                .params
+                .iter()
+                .all(|(name, value)| self.params.get(name) == Some(value))
+    }
+}
+
+impl std::fmt::Display for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.type_, self.subtype)?;
+        for (name, value) in &self.params {
+            if !value.is_empty() && value.chars().all(is_token_char) {
+                write!(f, ";{name}={value}")?;
+            } else {
+                write!(f, ";{name}=\"")?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                write!(f, "\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ExtensionValue for MediaType {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
+
+    /// The canonical representation of a media type is in lowercase, with
+    /// parameters sorted by name and values quoted only if they must be, e.g.,
+    /// `mediatype("text/plain;charset=utf-8;format=flowed")`.
+    fn canonical_repr(&self) -> Option<(Name, Vec<RestrictedExpr>)> {
+        Some((
+            constants::MEDIATYPE_FROM_STR_NAME.clone(),
+            vec![RestrictedExpr::val(self.to_string())],
+        ))
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: &str,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        Some(advice.into()),
+    )
+}
+
+/// Cedar function that constructs a `mediatype` Cedar type from a Cedar
+/// string
+fn mediatype_from_str(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let mediatype = MediaType::parse(s, false).ok_or_else(|| {
+        extension_err(
+            &constants::MEDIATYPE_FROM_STR_NAME,
+            ExtensionErrorKind::Parse,
+            format!("`{s}` is not a well-formed media type"),
+            VALID_MEDIATYPE_HELP,
+        )
+    })?;
+    let e = RepresentableExtensionValue::new(
+        Arc::new(mediatype),
+        constants::MEDIATYPE_FROM_STR_NAME.clone(),
+        vec![arg.clone().into()],
+    );
+    Ok(Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg.source_loc().cloned(),
+    }
+    .into())
+}
+
+/// Check that `v` is a `mediatype` and, if it is, return the wrapped value
+fn as_mediatype(v: &Value) -> Result<&MediaType, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == MediaType::typename() => {
+            #[expect(clippy::expect_used, reason = "Conditional above performs a typecheck")]
+            let m = ev
+                .value()
+                .as_any()
+                .downcast_ref::<MediaType>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(m)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension {
+                    name: MediaType::typename(),
+                },
+                v,
+                ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: MediaType::typename(),
+            },
+            v,
+        )),
+    }
+}
+
+/// Cedar function that tests whether a `mediatype` matches a media range
+/// given as a Cedar string
+fn matches(mediatype: &Value, range: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let mediatype = as_mediatype(mediatype)?;
+    let range_str = range.get_as_string()?;
+    let range = MediaType::parse(range_str, true).ok_or_else(|| {
+        extension_err(
+            &constants::MATCHES,
+            ExtensionErrorKind::Parse,
+            format!("`{range_str}` is not a well-formed media range"),
+            VALID_RANGE_HELP,
+        )
+    })?;
+    Ok(Value::from(mediatype.matches(&range)).into())
+}
+
+/// Cedar function that returns the type and subtype of a `mediatype`
+fn essence(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let mediatype = as_mediatype(arg)?;
+    Ok(Value::from(mediatype.essence()).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let mediatype_type = SchemaType::Extension {
+        name: MediaType::typename(),
+    };
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::MEDIATYPE_FROM_STR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(mediatype_from_str),
+                mediatype_type.clone(),
+                SchemaType::String,
+            )
+            .with_doc(r#"Parses a media type such as `"text/plain;charset=utf-8"`, in any case."#),
+            ExtensionFunction::binary(
+                constants::MATCHES.clone(),
+                CallStyle::MethodStyle,
+                Box::new(matches),
+                SchemaType::Bool,
+                (mediatype_type.clone(), SchemaType::String),
+            )
+            .with_doc(r#"Returns whether the receiver matches the media range given as the argument, such as `"image/*"`. Parameters of the range must also be parameters of the receiver."#),
+            ExtensionFunction::unary(
+                constants::ESSENCE.clone(),
+                CallStyle::MethodStyle,
+                Box::new(essence),
+                SchemaType::String,
+                mediatype_type,
+            )
+            .with_doc("Returns the type and subtype of the receiver in lowercase, without parameters, e.g., `\"text/plain\"`."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[test]
+    fn parse_mediatypes() {
+        assert_eq!(
+            MediaType::parse("Text/Plain; Charset=\"UTF-8\";format=flowed", false),
+            Some(MediaType {
+                type_: "text".into(),
+                subtype: "plain".into(),
+                params: BTreeMap::from([
+                    ("charset".into(), "utf-8".into()),
+                    ("format".into(), "flowed".into())
+                ]),
+            })
+        );
+        for s in [
+            "image/png",
+            "application/vnd.api+json",
+            "multipart/form-data; boundary=\"a b\\\"c\"",
+        ] {
+            assert!(MediaType::parse(s, false).is_some(), "{s}");
+        }
+        for s in [
+            "",
+            "image",
+            "image/",
+            "/png",
+            "image/png/x",
+            "image/*",
+            "*/*",
+            " image/png",
+            "image /png",
+            "image/png;",
+            "image/png;charset",
+            "image/png;charset=",
+            "image/png;a=1;A=2",
+            "image/png;a=\"unterminated",
+            "image/png;a=1 b",
+            "image/png;a=1x\"",
+            "-image/png",
+        ] {
+            assert_eq!(MediaType::parse(s, false), None, "{s}");
+        }
+        for s in ["*/*", "image/*", "*/*;a=1"] {
+            assert!(MediaType::parse(s, true).is_some(), "{s}");
+        }
+        for s in ["*/png", "image/*x", "**/*"] {
+            assert_eq!(MediaType::parse(s, true), None, "{s}");
+        }
+    }
+
+    #[test]
+    fn mediatype_equality() {
+        for (src, expected) in [
+            (r#"mediatype("image/png") == mediatype("IMAGE/PNG")"#, true),
+            (
+                r#"mediatype("text/plain;charset=UTF-8") == mediatype("text/plain; charset=\"utf-8\"")"#,
+                true,
+            ),
+            (
+                r#"mediatype("text/plain;a=1;b=2") == mediatype("text/plain;b=2;a=1")"#,
+                true,
+            ),
+            // only `charset` values are case-insensitive
+            (
+                r#"mediatype("text/plain;format=Flowed") == mediatype("text/plain;format=flowed")"#,
+                false,
+            ),
+            (
+                r#"mediatype("text/plain") == mediatype("text/plain;charset=utf-8")"#,
+                false,
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(Value::from(expected)), "{src}");
+        }
+    }
+
+    #[test]
+    fn mediatype_matches() {
+        for (mediatype, range, expected) in [
+            ("image/png", "image/*", true),
+            ("IMAGE/PNG", "image/png", true),
+            ("image/png", "Image/*", true),
+            ("image/png", "*/*", true),
+            ("image/png", "text/*", false),
+            ("image/png", "image/jpeg", false),
+            ("image/svg+xml", "image/svg", false),
+            ("text/plain;charset=utf-8", "text/*", true),
+            ("text/plain;charset=utf-8", "text/plain;charset=UTF-8", true),
+            ("text/plain;charset=utf-8", "text/*;charset=us-ascii", false),
+            ("text/plain", "text/plain;charset=utf-8", false),
+        ] {
+            let src = format!(r#"mediatype("{mediatype}").matches("{range}")"#);
+            assert_eq!(eval(&src), Ok(Value::from(expected)), "{src}");
+        }
+        assert_eq!(
+            eval(r#"mediatype("Text/Plain; charset=utf-8").essence()"#),
+            Ok(Value::from("text/plain"))
+        );
+    }
+
+    #[test]
+    fn mediatype_errors() {
+        for (src, name, help) in [
+            (
+                r#"mediatype("image")"#,
+                &*constants::MEDIATYPE_FROM_STR_NAME,
+                VALID_MEDIATYPE_HELP,
+            ),
+            (
+                r#"mediatype("image/*")"#,
+                &*constants::MEDIATYPE_FROM_STR_NAME,
+                VALID_MEDIATYPE_HELP,
+            ),
+            (
+                r#"mediatype("image/png").matches("*/png")"#,
+                &*constants::MATCHES,
+                VALID_RANGE_HELP,
+            ),
+        ] {
+            assert_matches!(
+                eval(src),
+                Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                    extension_name,
+                    kind,
+                    advice,
+                    ..
+                })) => {
+                    assert_eq!(&extension_name, name, "{src}");
+                    assert_eq!(kind, ExtensionErrorKind::Parse, "{src}");
+                    assert_eq!(advice.as_deref(), Some(help), "{src}");
+                }
+            );
+        }
+        assert_matches!(
+            eval(r#""image/png".matches("image/*")"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, advice, .. })) => {
+                assert_eq!(expected, nonempty![Type::Extension { name: MediaType::typename() }]);
+                assert_eq!(advice.as_deref(), Some(ADVICE_MSG));
+            }
+        );
+    }
+
+    #[test]
+    fn canonical_repr() {
+        let mediatype =
+            MediaType::parse("Multipart/Form-Data; Boundary=\"a b\";charset=UTF-8", false).unwrap();
+        assert_eq!(
+            mediatype.canonical_repr(),
+            Some((
+                constants::MEDIATYPE_FROM_STR_NAME.clone(),
+                vec![RestrictedExpr::val(
+                    "multipart/form-data;boundary=\"a b\";charset=utf-8"
+                )]
+            ))
+        );
+        assert_eq!(
+            MediaType::parse(&mediatype.to_string(), false),
+            Some(mediatype)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'prf' extension, which provides a keyed,
+//! deterministic hash function so that policies can match identifiers such as
+//! email addresses without containing them in plain text.
+//!
+//! `prf(s)` returns the HMAC-SHA256 of the UTF-8 bytes of `s` under the
+//! extension's key, as 64 lowercase hex digits. The key is given to
+//! [`extension()`] by the application and never appears in policy text, so a
+//! policy like
+//!
+//! ```cedar
+//! permit(principal, action, resource)
+//! when { prf(principal.email) == "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843" };
+//! ```
+//!
+//! can be shared or stored without revealing the email address it matches.
+//!
+//! Unlike the other extensions, this one needs a key and so is never part of
+//! [`super::Extensions::all_available()`]. Instead, it is given to
+//! [`super::Extensions::with_custom_extensions()`], so that each `Extensions`
+//! has its own key.
+//!
+//! Some guidance on its use:
+//! - The output depends only on the key and the input string, so it is the
+//!   same across runs, processes, and platforms. Hashes can be computed
+//!   offline when writing policies and will match at authorization time.
+//! - The key should be at least 32 random bytes and kept as secret as the
+//!   identifiers themselves. Anyone with the key can hash candidate inputs
+//!   and so recover low-entropy identifiers (names, phone numbers, ...) by
+//!   brute force.
+//! - Changing the key changes every output, so rotating it means rewriting
+//!   every hash appearing in policies.
+//! - Inputs are hashed exactly as given. Normalize identifiers (e.g.,
+//!   lowercase email addresses) consistently before hashing them for
+//!   policies and before putting them in entity data.
+
+use crate::ast::{CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, Name, Value};
+use crate::entities::SchemaType;
+use crate::evaluator;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fmt::Write;
+use std::sync::Arc;
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static PRF_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("prf").expect("should be a valid identifier")
+    });
+}
+
+/// Compute HMAC-SHA256 of `input` under `key`, as lowercase hex
+fn keyed_hash(key: &[u8], input: &str) -> String {
+    #[expect(clippy::expect_used, reason = "HMAC accepts keys of any length")]
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(input.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            // writing to a `String` can't fail
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Cedar function that hashes a Cedar string under `key`
+fn prf(key: &[u8], arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    Ok(Value::from(keyed_hash(key, s.as_str())).into())
+}
+
+/// Construct the extension, with the given secret `key`
+pub fn extension(key: impl Into<Arc<[u8]>>) -> Extension {
+    let key: Arc<[u8]> = key.into();
+    Extension::new(
+        constants::PRF_NAME.clone(),
+        vec![ExtensionFunction::unary(
+            constants::PRF_NAME.clone(),
+            CallStyle::FunctionStyle,
+            Box::new(move |arg| prf(&key, arg)),
+            SchemaType::String,
+            SchemaType::String,
+        )
+        .with_doc(
+            "Returns the HMAC-SHA256 of the argument under a key supplied by the application, as 64 lowercase hex digits.",
+        )],
+        std::iter::empty(),
+    )
+}
+
+/// Name of the `prf` function, for applications building expressions
+/// directly
+pub fn function_name() -> Name {
+    constants::PRF_NAME.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Expr;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use cool_asserts::assert_matches;
+
+    /// Evaluate `prf(arg)` with an extension keyed by `key`. The parser only
+    /// accepts `prf` in the [`Extensions::scope()`] of such an extension.
+    #[track_caller]
+    fn eval_prf(key: &[u8], arg: Expr) -> evaluator::Result<Value> {
+        let ext_array = [extension(key)];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&Expr::call_extension_fn(function_name(), vec![arg]))
+    }
+
+    #[test]
+    fn rfc_4231_vector() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            eval_prf(b"Jefe", Expr::val("what do ya want for nothing?")),
+            Ok(Value::from(
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+            ))
+        );
+    }
+
+    #[test]
+    fn deterministic() {
+        let key = b"0123456789abcdef0123456789abcdef";
+        let first = eval_prf(key, Expr::val("alice@example.com")).unwrap();
+        for _ in 0..3 {
+            assert_eq!(
+                eval_prf(key, Expr::val("alice@example.com")),
+                Ok(first.clone())
+            );
+        }
+        // and agrees with hashes computed outside of evaluation
+        assert_eq!(Value::from(keyed_hash(key, "alice@example.com")), first);
+        assert_ne!(
+            eval_prf(key, Expr::val("Alice@example.com")),
+            Ok(first.clone())
+        );
+        assert_ne!(
+            eval_prf(b"another key", Expr::val("alice@example.com")),
+            Ok(first)
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(
+            eval_prf(b"key", Expr::val("")),
+            Ok(Value::from(
+                "5d5d139563c95b5967b9bd9a8c9b233a9dedb45072794cd232dc1b74832607d0"
+            ))
+        );
+    }
+
+    #[test]
+    fn prf_type_errors() {
+        assert_matches!(
+            eval_prf(b"key", Expr::val(1)),
+            Err(EvaluationError::TypeError(_))
+        );
+    }
+}
@@ -0,0 +1,395 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'regions' extension, which provides methods
+//! on ISO 3166-1 alpha-2 country codes for data-residency policies.
+//!
+//! `code.inRegion(region)` tests whether the country `code` belongs to the
+//! region grouping named `region`, e.g., `resource.country.inRegion("EU")`,
+//! so that policies don't need to repeat large set literals of country codes.
+//! `code.isCountryCode()` tests whether a string is an assigned country code.
+//! Country codes are matched case-insensitively.
+//!
+//! The region groupings are given to [`extension()`] by the application, as
+//! [`Regions`], so this extension is never part of
+//! [`super::Extensions::all_available()`]. Instead, it is given to
+//! [`super::Extensions::with_custom_extensions()`] for groupings specific to
+//! that `Extensions`. [`Regions::standard()`] declares the `EU` and `EEA`
+//! groupings.
+
+use crate::ast::{CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, Name, Value};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+use smol_str::SmolStr;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("regions").expect("should be a valid identifier")
+    });
+    pub static IN_REGION: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("inRegion").expect("should be a valid identifier")
+    });
+    pub static IS_COUNTRY_CODE: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("isCountryCode").expect("should be a valid identifier")
+    });
+}
+
+/// Help text describing valid country codes
+const VALID_CODE_HELP: &str =
+    "country codes are ISO 3166-1 alpha-2 codes such as `\"FR\"`; use `isCountryCode()` to test a string first";
+
+/// The officially assigned ISO 3166-1 alpha-2 country codes, in order
+const COUNTRY_CODES: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// The member states of the European Union
+const EU: [&str; 27] = [
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU", "IE", "IT",
+    "LT", "LU", "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+/// The members of the European Economic Area which aren't in the European
+/// Union
+const EEA_NON_EU: [&str; 3] = ["IS", "LI", "NO"];
+
+/// Normalize `code` to uppercase if it is an assigned country code
+fn normalize_code(code: &str) -> Option<&'static str> {
+    let upper = code.to_ascii_uppercase();
+    COUNTRY_CODES
+        .binary_search(&upper.as_str())
+        .ok()
+        .and_then(|i| COUNTRY_CODES.get(i).copied())
+}
+
+/// Error when declaring a region with a string which isn't an assigned
+/// country code
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("`{code}` is not an ISO 3166-1 alpha-2 country code")]
+pub struct InvalidCountryCodeError {
+    code: String,
+}
+
+impl InvalidCountryCodeError {
+    /// The invalid country code
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+}
+
+/// Named groupings of country codes, for `inRegion`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Regions {
+    regions: BTreeMap<SmolStr, BTreeSet<&'static str>>,
+}
+
+impl Regions {
+    /// No regions
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The `EU` region, of the member states of the European Union, and the
+    /// `EEA` region, of the European Economic Area
+    pub fn standard() -> Self {
+        Self {
+            regions: BTreeMap::from([
+                ("EU".into(), EU.into_iter().collect()),
+                ("EEA".into(), EU.into_iter().chain(EEA_NON_EU).collect()),
+            ]),
+        }
+    }
+
+    /// Declare the region `name` with the given country codes, in either case,
+    /// replacing any existing region with that name. Region names are
+    /// case-sensitive.
+    pub fn with_region(
+        mut self,
+        name: impl Into<SmolStr>,
+        codes: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, InvalidCountryCodeError> {
+        let codes = codes
+            .into_iter()
+            .map(|code| {
+                normalize_code(code.as_ref()).ok_or_else(|| InvalidCountryCodeError {
+                    code: code.as_ref().to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        self.regions.insert(name.into(), codes);
+        Ok(self)
+    }
+
+    /// The names of the declared regions
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(SmolStr::as_str)
+    }
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+    advice: &str,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        Some(advice.into()),
+    )
+}
+
+/// Cedar function that tests whether a country code is in the named region.
+/// Strings which aren't country codes and undeclared regions are errors, so
+/// that typos aren't silently treated as being outside every region.
+fn in_region(
+    regions: &Regions,
+    code: &Value,
+    region: &Value,
+) -> evaluator::Result<ExtensionOutputValue> {
+    let code = code.get_as_string()?;
+    let region = region.get_as_string()?;
+    let normalized = normalize_code(code).ok_or_else(|| {
+        extension_err(
+            &constants::IN_REGION,
+            ExtensionErrorKind::InvalidArgument,
+            format!("`{code}` is not an ISO 3166-1 alpha-2 country code"),
+            VALID_CODE_HELP,
+        )
+    })?;
+    let members = regions.regions.get(region.as_str()).ok_or_else(|| {
+        extension_err(
+            &constants::IN_REGION,
+            ExtensionErrorKind::InvalidArgument,
+            format!("`{region}` is not a declared region"),
+            &format!(
+                "declared regions are: {}",
+                regions
+                    .names()
+                    .map(|name| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    })?;
+    Ok(Value::from(members.contains(normalized)).into())
+}
+
+/// Cedar function that tests whether a string is a country code
+fn is_country_code(code: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let code = code.get_as_string()?;
+    Ok(Value::from(normalize_code(code).is_some()).into())
+}
+
+/// Construct the extension, with the given region groupings
+pub fn extension(regions: Regions) -> Extension {
+    let regions = Arc::new(regions);
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::binary(
+                constants::IN_REGION.clone(),
+                CallStyle::MethodStyle,
+                Box::new(move |code, region| in_region(&regions, code, region)),
+                SchemaType::Bool,
+                (SchemaType::String, SchemaType::String),
+            )
+            .with_doc("Returns whether the receiver, a country code in either case, is in the region named by the argument. Errors if the receiver isn't a country code or the region isn't declared."),
+            ExtensionFunction::unary(
+                constants::IS_COUNTRY_CODE.clone(),
+                CallStyle::MethodStyle,
+                Box::new(is_country_code),
+                SchemaType::Bool,
+                SchemaType::String,
+            )
+            .with_doc("Returns whether the receiver is an ISO 3166-1 alpha-2 country code, in either case."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+/// Name of the `inRegion` function, for applications building expressions
+/// directly
+pub fn in_region_name() -> Name {
+    constants::IN_REGION.clone()
+}
+
+/// Name of the `isCountryCode` function, for applications building
+/// expressions directly
+pub fn is_country_code_name() -> Name {
+    constants::IS_COUNTRY_CODE.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Expr;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use cool_asserts::assert_matches;
+
+    /// Evaluate `func` applied to `args` with the extension for `regions`.
+    /// The parser only accepts the functions of `regions` in the
+    /// [`Extensions::scope()`] of such an extension.
+    #[track_caller]
+    fn eval(regions: Regions, func: Name, args: Vec<Expr>) -> evaluator::Result<Value> {
+        let ext_array = [extension(regions)];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&Expr::call_extension_fn(func, args))
+    }
+
+    #[track_caller]
+    fn in_region(regions: Regions, code: &str, region: &str) -> evaluator::Result<Value> {
+        eval(
+            regions,
+            in_region_name(),
+            vec![Expr::val(code), Expr::val(region)],
+        )
+    }
+
+    #[test]
+    fn country_codes_sorted() {
+        assert!(COUNTRY_CODES.is_sorted_by(|a, b| a < b));
+        assert!(EU
+            .iter()
+            .chain(EEA_NON_EU.iter())
+            .all(|code| COUNTRY_CODES.contains(code)));
+    }
+
+    #[test]
+    fn standard_regions() {
+        assert_eq!(
+            in_region(Regions::standard(), "FR", "EU"),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            in_region(Regions::standard(), "fr", "EEA"),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            in_region(Regions::standard(), "NO", "EU"),
+            Ok(Value::from(false))
+        );
+        assert_eq!(
+            in_region(Regions::standard(), "No", "EEA"),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            in_region(Regions::standard(), "GB", "EEA"),
+            Ok(Value::from(false))
+        );
+    }
+
+    #[test]
+    fn custom_regions() {
+        let regions = Regions::standard()
+            .with_region("DACH", ["de", "AT", "Ch"])
+            .unwrap()
+            .with_region("EU", ["FR"])
+            .unwrap();
+        assert_eq!(
+            in_region(regions.clone(), "CH", "DACH"),
+            Ok(Value::from(true))
+        );
+        assert_eq!(
+            in_region(regions.clone(), "FR", "DACH"),
+            Ok(Value::from(false))
+        );
+        // redeclaring a region replaces it
+        assert_eq!(
+            in_region(regions.clone(), "DE", "EU"),
+            Ok(Value::from(false))
+        );
+        assert_eq!(regions.names().collect::<Vec<_>>(), ["DACH", "EEA", "EU"]);
+        assert_eq!(
+            Regions::empty().with_region("X", ["FR", "XX"]),
+            Err(InvalidCountryCodeError { code: "XX".into() })
+        );
+    }
+
+    #[test]
+    fn in_region_errors() {
+        assert_matches!(
+            in_region(Regions::standard(), "France", "EU"),
+            Err(EvaluationError::FailedExtensionFunctionExecution(_))
+        );
+        // region names are case-sensitive
+        assert_matches!(
+            in_region(Regions::standard(), "FR", "eu"),
+            Err(EvaluationError::FailedExtensionFunctionExecution(_))
+        );
+        assert_matches!(
+            eval(
+                Regions::standard(),
+                in_region_name(),
+                vec![Expr::val(1), Expr::val("EU")]
+            ),
+            Err(EvaluationError::TypeError(_))
+        );
+    }
+
+    #[test]
+    fn is_country_code() {
+        for (code, expected) in [
+            ("US", true),
+            ("us", true),
+            ("AX", true),
+            ("XK", false),
+            ("USA", false),
+            ("", false),
+        ] {
+            assert_eq!(
+                eval(
+                    Regions::empty(),
+                    is_country_code_name(),
+                    vec![Expr::val(code)]
+                ),
+                Ok(Value::from(expected)),
+                "{code}"
+            );
+        }
+    }
+}
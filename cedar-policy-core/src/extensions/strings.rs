@@ -0,0 +1,264 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'strings' extension, which provides methods
+//! on the builtin `String` type.
+
+use crate::ast::{CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, Name, Value};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use crate::evaluator::evaluation_errors::ExtensionErrorKind;
+
+#[expect(clippy::expect_used, reason = "The `Name`s here are valid identifiers")]
+pub(crate) mod constants {
+    use crate::ast::Name;
+    use std::sync::LazyLock;
+
+    pub static EXTENSION_NAME: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("strings").expect("should be a valid identifier")
+    });
+    pub static TO_LOWER_CASE: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("toLowerCase").expect("should be a valid identifier")
+    });
+    pub static STARTS_WITH: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("startsWith").expect("should be a valid identifier")
+    });
+    pub static ENDS_WITH: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("endsWith").expect("should be a valid identifier")
+    });
+    pub static SPLIT: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("split").expect("should be a valid identifier")
+    });
+    pub static LENGTH: LazyLock<Name> = LazyLock::new(|| {
+        Name::parse_unqualified_name("length").expect("should be a valid identifier")
+    });
+}
+
+fn extension_err(
+    name: &Name,
+    kind: ExtensionErrorKind,
+    msg: impl Into<String>,
+) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        name.clone(),
+        kind,
+        msg.into(),
+        None, // source loc will be added by the evaluator
+        None,
+    )
+}
+
+/// Cedar function that converts a string to lower case, using the Unicode
+/// `Lowercase` mapping (so a single character may map to several)
+fn to_lower_case(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    Ok(Value::from(s.to_lowercase()).into())
+}
+
+/// Cedar function that tests whether the first string starts with the second
+fn starts_with(s: &Value, prefix: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = s.get_as_string()?;
+    let prefix = prefix.get_as_string()?;
+    Ok(Value::from(s.starts_with(prefix.as_str())).into())
+}
+
+/// Cedar function that tests whether the first string ends with the second
+fn ends_with(s: &Value, suffix: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = s.get_as_string()?;
+    let suffix = suffix.get_as_string()?;
+    Ok(Value::from(s.ends_with(suffix.as_str())).into())
+}
+
+/// Cedar function that splits the first string at each occurrence of the
+/// second, returning the set of pieces. Empty pieces are included, so
+/// `"a,,b".split(",")` is `["a", "", "b"]`. The separator must not be empty.
+fn split(s: &Value, sep: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = s.get_as_string()?;
+    let sep = sep.get_as_string()?;
+    if sep.is_empty() {
+        return Err(extension_err(
+            &constants::SPLIT,
+            ExtensionErrorKind::InvalidArgument,
+            "the separator passed to `split` must not be empty",
+        ));
+    }
+    Ok(Value::set(s.split(sep.as_str()).map(Value::from), None).into())
+}
+
+/// Cedar function that returns the number of characters (Unicode scalar
+/// values, not bytes) in a string
+fn length(arg: &Value) -> evaluator::Result<ExtensionOutputValue> {
+    let s = arg.get_as_string()?;
+    let len = i64::try_from(s.chars().count()).map_err(|_| {
+        extension_err(
+            &constants::LENGTH,
+            ExtensionErrorKind::OutOfRange,
+            "string length does not fit in a `Long`",
+        )
+    })?;
+    Ok(Value::from(len).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::TO_LOWER_CASE.clone(),
+                CallStyle::MethodStyle,
+                Box::new(to_lower_case),
+                SchemaType::String,
+                SchemaType::String,
+            )
+            .with_doc("Converts the receiver to lower case."),
+            ExtensionFunction::binary(
+                constants::STARTS_WITH.clone(),
+                CallStyle::MethodStyle,
+                Box::new(starts_with),
+                SchemaType::Bool,
+                (SchemaType::String, SchemaType::String),
+            )
+            .with_doc("Tests whether the receiver starts with the argument."),
+            ExtensionFunction::binary(
+                constants::ENDS_WITH.clone(),
+                CallStyle::MethodStyle,
+                Box::new(ends_with),
+                SchemaType::Bool,
+                (SchemaType::String, SchemaType::String),
+            )
+            .with_doc("Tests whether the receiver ends with the argument."),
+            ExtensionFunction::binary(
+                constants::SPLIT.clone(),
+                CallStyle::MethodStyle,
+                Box::new(split),
+                SchemaType::Set {
+                    element_ty: Box::new(SchemaType::String),
+                },
+                (SchemaType::String, SchemaType::String),
+            )
+            .with_doc("Splits the receiver at each occurrence of the (non-empty) argument, returning the set of pieces."),
+            ExtensionFunction::unary(
+                constants::LENGTH.clone(),
+                CallStyle::MethodStyle,
+                Box::new(length),
+                SchemaType::Long,
+                SchemaType::String,
+            )
+            .with_doc("Returns the number of characters in the receiver."),
+        ],
+        std::iter::empty(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Type;
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{evaluation_errors, EvaluationError, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array).unwrap();
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[test]
+    fn string_methods() {
+        for (src, expected) in [
+            (
+                r#""Photo-ABC.JPG".toLowerCase()"#,
+                Value::from("photo-abc.jpg"),
+            ),
+            (r#""ÀÉÎ ß".toLowerCase()"#, Value::from("àéî ß")),
+            (r#""".toLowerCase()"#, Value::from("")),
+            (
+                r#""photos/2024/a.jpg".startsWith("photos/")"#,
+                Value::from(true),
+            ),
+            (
+                r#""photos/2024/a.jpg".startsWith("2024")"#,
+                Value::from(false),
+            ),
+            (r#""abc".startsWith("")"#, Value::from(true)),
+            (r#""photos/2024/a.jpg".endsWith(".jpg")"#, Value::from(true)),
+            (
+                r#""photos/2024/a.jpg".endsWith(".JPG")"#,
+                Value::from(false),
+            ),
+            (r#""abc".length()"#, Value::from(3)),
+            (r#""".length()"#, Value::from(0)),
+            (r#""héllo👋".length()"#, Value::from(6)),
+            (
+                r#""a,b,,a".split(",")"#,
+                Value::set([Value::from("a"), Value::from("b"), Value::from("")], None),
+            ),
+            (
+                r#""abc".split("::")"#,
+                Value::set([Value::from("abc")], None),
+            ),
+            (
+                r#""Photo.JPG".toLowerCase().endsWith(".jpg")"#,
+                Value::from(true),
+            ),
+            (
+                r#""Admin,Viewer".toLowerCase().split(",").contains("admin")"#,
+                Value::from(true),
+            ),
+        ] {
+            assert_eq!(eval(src), Ok(expected), "{src}");
+        }
+    }
+
+    #[test]
+    fn string_method_errors() {
+        assert_matches!(
+            eval(r#""a,b".split("")"#),
+            Err(EvaluationError::FailedExtensionFunctionExecution(evaluation_errors::ExtensionFunctionExecutionError {
+                extension_name,
+                msg,
+                ..
+            })) => {
+                assert_eq!(extension_name, *constants::SPLIT);
+                assert_eq!(msg, "the separator passed to `split` must not be empty");
+            }
+        );
+        assert_matches!(
+            eval(r#""abc".startsWith(1)"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, actual, .. })) => {
+                assert_eq!(expected, nonempty![Type::String]);
+                assert_eq!(actual, Type::Long);
+            }
+        );
+        assert_matches!(
+            eval(r#"[1].length()"#),
+            Err(EvaluationError::TypeError(evaluation_errors::TypeError { expected, .. })) => {
+                assert_eq!(expected, nonempty![Type::String]);
+            }
+        );
+        // `toLowerCase` is a method, not a function
+        parse_expr(r#"toLowerCase("ABC")"#).expect_err("should fail");
+    }
+}
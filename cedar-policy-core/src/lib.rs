@@ -33,13 +33,41 @@
 //! - `ipaddr` — IP address extension functions (`ip`, `isIpv4`, `isIpv6`,
 //!   `isLoopback`, `isMulticast`, `isInRange`).
 //! - `decimal` — Decimal number extension functions (`decimal`, `lessThan`,
-//!   `lessThanOrEqual`, `greaterThan`, `greaterThanOrEqual`).
+//!   `lessThanOrEqual`, `greaterThan`, `greaterThanOrEqual`, `add`, `subtract`,
+//!   `multiply`).
 //! - `datetime` — Date and time extension functions (`datetime`, `duration`,
 //!   `offset`, `durationSince`, `toDate`, `toTime`). Enables the `chrono`
 //!   dependency.
 //!
 //! ## Optional features
 //!
+//! - `strings` — String extension methods (`toLowerCase`, `startsWith`,
+//!   `endsWith`, `split`, `length`) on the builtin `String` type. Not
+//!   enabled by default, because it adds methods which policies in other
+//!   Cedar implementations may not support.
+//! - `bytes` — The `bytes` extension type for opaque byte strings, constructed
+//!   from base64 (`bytes`, `byteLength`). Not enabled by default. Enables the
+//!   `base64` dependency.
+//! - `lookup` — The `lookup` extension, which looks up a dotted path given as
+//!   data in nested records (`lookup`), returning an `optional` value
+//!   (`isPresent`, `asString`, `asLong`, `asBool`). Not enabled by default.
+//! - `geo` — The `geopoint` and `geopolygon` extension types for locations
+//!   (`geopoint`, `geopolygon`, `distanceTo`, `isInPolygon`). Not enabled by
+//!   default.
+//! - `identifiers` — The `uuid` and `ulid` extension types for identifiers
+//!   (`uuid`, `ulid`, `uuidVersion`, `timestampMillis`), with ULIDs ordered by
+//!   `<`, `<=`, `>`, and `>=`. Not enabled by default.
+//! - `mediatype` — The `mediatype` extension type for media (MIME) types
+//!   (`mediatype`, `matches`, `essence`), matched against media ranges such as
+//!   `image/*`. Not enabled by default.
+//! - `prf` — The `prf` extension, a keyed hash function (`prf`) for matching
+//!   hashed identifiers in policies. Not enabled by default, and needs a key
+//!   from the application, so it is only available to extension sets built
+//!   with one. Enables the `hmac` and `sha2` dependencies.
+//! - `regions` — The `regions` extension, with methods on ISO 3166-1 country
+//!   codes (`inRegion`, `isCountryCode`) for data-residency policies. Not
+//!   enabled by default, and needs region groupings from the application, so
+//!   it is only available to extension sets built with them.
 //! - `arbitrary` — Enables [`Arbitrary`](https://docs.rs/arbitrary) implementations
 //!   for several types in this crate. Useful for fuzzing.
 //! - `test-util` — Exposes the [`test_utils`] module with helpers for testing.
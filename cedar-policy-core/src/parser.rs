@@ -44,12 +44,13 @@ use std::collections::HashMap;
 use crate::ast;
 use crate::ast::RestrictedExpressionParseError;
 use crate::est;
+use crate::extensions::Extensions;
 
 /// simple main function for parsing policies
 /// generates numbered ids
 pub fn parse_policyset(text: &str) -> Result<ast::PolicySet, err::ParseErrors> {
     let cst = text_to_cst::parse_policies(text)?;
-    cst.to_policyset()
+    cst.to_policyset(Extensions::all_available())
 }
 
 /// Like `parse_policyset()`, but also returns the (lossless) original text of
@@ -60,8 +61,18 @@ pub fn parse_policyset(text: &str) -> Result<ast::PolicySet, err::ParseErrors> {
 pub fn parse_policyset_and_also_return_policy_text(
     text: &str,
 ) -> Result<(HashMap<ast::PolicyID, Option<&str>>, ast::PolicySet), err::ParseErrors> {
+    parse_policyset_and_also_return_policy_text_with_extensions(text, Extensions::all_available())
+}
+
+/// Like `parse_policyset_and_also_return_policy_text()`, but the policies may
+/// call the functions of `extensions` rather than those of
+/// [`Extensions::all_available()`].
+pub fn parse_policyset_and_also_return_policy_text_with_extensions<'a>(
+    text: &'a str,
+    extensions: &Extensions<'_>,
+) -> Result<(HashMap<ast::PolicyID, Option<&'a str>>, ast::PolicySet), err::ParseErrors> {
     let cst = text_to_cst::parse_policies(text)?;
-    let pset = cst.to_policyset()?;
+    let pset = cst.to_policyset(extensions)?;
     #[expect(
         clippy::expect_used,
         reason = "Shouldn't be `none` since `parse_policies()` and `to_policyset()` didn't return `Err`"
@@ -96,7 +107,7 @@ pub fn parse_policyset_to_ests_and_pset(
     text: &str,
 ) -> Result<(HashMap<ast::PolicyID, est::Policy>, ast::PolicySet), err::ParseErrors> {
     let cst = text_to_cst::parse_policies(text)?;
-    let pset = cst.to_policyset()?;
+    let pset = cst.to_policyset(Extensions::all_available())?;
     #[expect(
         clippy::expect_used,
         reason = "Shouldn't be `None` since `parse_policies()` and `to_policyset()` didn't return `Err`"
@@ -122,7 +133,7 @@ pub fn parse_policy_or_template(
 ) -> Result<ast::Template, err::ParseErrors> {
     let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
     let cst = text_to_cst::parse_policy(text)?;
-    cst.to_template(id)
+    cst.to_template(id, Extensions::all_available())
 }
 
 /// Like `parse_policy_or_template()`, but also returns the (lossless) EST -- that
@@ -131,11 +142,22 @@ pub fn parse_policy_or_template(
 pub fn parse_policy_or_template_to_est_and_ast(
     id: Option<ast::PolicyID>,
     text: &str,
+) -> Result<(est::Policy, ast::Template), err::ParseErrors> {
+    parse_policy_or_template_to_est_and_ast_with_extensions(id, text, Extensions::all_available())
+}
+
+/// Like `parse_policy_or_template_to_est_and_ast()`, but the policy may call
+/// the functions of `extensions` rather than those of
+/// [`Extensions::all_available()`].
+pub fn parse_policy_or_template_to_est_and_ast_with_extensions(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    extensions: &Extensions<'_>,
 ) -> Result<(est::Policy, ast::Template), err::ParseErrors> {
     let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
     let cst = text_to_cst::parse_policy(text)?;
-    let ast = cst.to_template(id)?;
-    let est = cst.try_into_inner()?.try_into()?;
+    let ast = cst.to_template(id, extensions)?;
+    let est = est::Policy::try_from_cst(cst.try_into_inner()?, extensions)?;
     Ok((est, ast))
 }
 
@@ -146,10 +168,20 @@ pub fn parse_policy_or_template_to_est_and_ast(
 pub fn parse_template(
     id: Option<ast::PolicyID>,
     text: &str,
+) -> Result<ast::Template, err::ParseErrors> {
+    parse_template_with_extensions(id, text, Extensions::all_available())
+}
+
+/// Like `parse_template()`, but the template may call the functions of
+/// `extensions` rather than those of [`Extensions::all_available()`].
+pub fn parse_template_with_extensions(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    extensions: &Extensions<'_>,
 ) -> Result<ast::Template, err::ParseErrors> {
     let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
     let cst = text_to_cst::parse_policy(text)?;
-    let template = cst.to_template(id)?;
+    let template = cst.to_template(id, extensions)?;
     validate_template_has_slots(template, cst)
 }
 
@@ -160,10 +192,20 @@ pub fn parse_template(
 pub fn parse_policy(
     id: Option<ast::PolicyID>,
     text: &str,
+) -> Result<ast::StaticPolicy, err::ParseErrors> {
+    parse_policy_with_extensions(id, text, Extensions::all_available())
+}
+
+/// Like `parse_policy()`, but the policy may call the functions of
+/// `extensions` rather than those of [`Extensions::all_available()`].
+pub fn parse_policy_with_extensions(
+    id: Option<ast::PolicyID>,
+    text: &str,
+    extensions: &Extensions<'_>,
 ) -> Result<ast::StaticPolicy, err::ParseErrors> {
     let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
     let cst = text_to_cst::parse_policy(text)?;
-    cst.to_policy(id)
+    cst.to_policy(id, extensions)
 }
 
 /// Like `parse_policy()`, but also returns the (lossless) EST -- that is, the
@@ -175,18 +217,29 @@ pub fn parse_policy_to_est_and_ast(
 ) -> Result<(est::Policy, ast::StaticPolicy), err::ParseErrors> {
     let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
     let cst = text_to_cst::parse_policy(text)?;
-    let ast = cst.to_policy(id)?;
+    let ast = cst.to_policy(id, Extensions::all_available())?;
     let est = cst.try_into_inner()?.try_into()?;
     Ok((est, ast))
 }
 
 /// Parse a policy or template (either one works) to its EST representation
 pub fn parse_policy_or_template_to_est(text: &str) -> Result<est::Policy, err::ParseErrors> {
+    parse_policy_or_template_to_est_with_extensions(text, Extensions::all_available())
+}
+
+/// Like `parse_policy_or_template_to_est()`, but the policy may call the
+/// functions of `extensions` rather than those of
+/// [`Extensions::all_available()`].
+pub fn parse_policy_or_template_to_est_with_extensions(
+    text: &str,
+    extensions: &Extensions<'_>,
+) -> Result<est::Policy, err::ParseErrors> {
     // We parse to EST and AST even though we only want the EST because some
     // checks are applied by the CST-to-AST conversion and not CST-to-EST, and
     // we do not want to return any EST if the policy text would not parse
     // normally.
-    parse_policy_or_template_to_est_and_ast(None, text).map(|(est, _ast)| est)
+    parse_policy_or_template_to_est_and_ast_with_extensions(None, text, extensions)
+        .map(|(est, _ast)| est)
 }
 
 /// parse an Expr
@@ -195,7 +248,7 @@ pub fn parse_policy_or_template_to_est(text: &str) -> Result<est::Policy, err::P
 /// or its constructors
 pub(crate) fn parse_expr(ptext: &str) -> Result<ast::Expr, err::ParseErrors> {
     let cst = text_to_cst::parse_expr(ptext)?;
-    cst.to_expr::<ast::ExprBuilder<()>>()
+    cst.to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
 }
 
 /// parse a RestrictedExpr
@@ -233,7 +286,7 @@ pub(crate) fn parse_internal_name(name: &str) -> Result<ast::InternalName, err::
 /// or its constructors
 pub(crate) fn parse_literal(val: &str) -> Result<ast::Literal, err::LiteralParseError> {
     let cst = text_to_cst::parse_primary(val)?;
-    match cst.to_expr::<ast::ExprBuilder<()>>() {
+    match cst.to_expr::<ast::ExprBuilder<()>>(Extensions::all_available()) {
         Ok(ast) => match ast.expr_kind() {
             ast::ExprKind::Lit(v) => Ok(v.clone()),
             _ => Err(err::LiteralParseError::InvalidLiteral(ast)),
@@ -878,7 +931,7 @@ mod tests {
             };
             "#;
         let error = ExpectedErrorMessageBuilder::error("`?blah` is not a valid template slot")
-            .help("a template slot may only be `?principal` or `?resource`")
+            .help("a template slot may only be `?principal`, `?resource`, or a value slot declared in a `template(...) =>` header")
             .exactly_one_underline("?blah")
             .build();
         assert_matches!(parse_policy(None, src), Err(e) => {
@@ -986,7 +1039,7 @@ mod tests {
             };
             "#;
         let error = ExpectedErrorMessageBuilder::error("`?blah` is not a valid template slot")
-            .help("a template slot may only be `?principal` or `?resource`")
+            .help("a template slot may only be `?principal`, `?resource`, or a value slot declared in a `template(...) =>` header")
             .exactly_one_underline("?blah")
             .build();
         assert_matches!(parse_policy(None, src), Err(e) => {
@@ -1066,6 +1119,133 @@ mod tests {
         });
     }
 
+    #[test]
+    fn value_slot_declarations() {
+        let src = r#"
+            template(?limit: Long, ?opts: { level: Long, "display name": Set<String> }, ?ip: ipaddr) =>
+            permit(principal, action, resource) when {
+                resource.size < ?limit && ?opts.level > 0 && ?ip.isLoopback()
+            };
+            "#;
+        let t = parse_policy_or_template(None, src).expect("should parse");
+        assert_eq!(t.value_slot_types().count(), 3);
+        let reparsed = parse_policy_or_template(None, &t.to_string()).expect("should parse");
+        assert_eq!(
+            t.value_slot_types().collect::<Vec<_>>(),
+            reparsed.value_slot_types().collect::<Vec<_>>()
+        );
+        assert!(parse_policy(None, src).is_err());
+
+        let src = r#"
+            template(?limit: Long = 10, ?ip: ipaddr = ip("127.0.0.1"), ?tags: Set<String> = []) =>
+            permit(principal, action, resource) when {
+                resource.size < ?limit && ?ip.isLoopback() && ?tags.isEmpty()
+            };
+            "#;
+        let t = parse_policy_or_template(None, src).expect("should parse");
+        assert_eq!(t.value_slot_defaults().count(), 3);
+        let reparsed = parse_policy_or_template(None, &t.to_string()).expect("should parse");
+        assert_eq!(
+            t.value_slot_defaults().collect::<Vec<_>>(),
+            reparsed.value_slot_defaults().collect::<Vec<_>>()
+        );
+
+        let src = r#"
+            template(?limit: Long = "ten") =>
+            permit(principal, action, resource) when { resource.size < ?limit };
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error(
+                    r#"the default value `"ten"` of `?limit` does not have its declared type `Long`"#,
+                )
+                .exactly_one_underline("?limit")
+                .build(),
+            );
+        });
+
+        let src = r#"
+            template(?limit: Long = resource.size) =>
+            permit(principal, action, resource) when { resource.size < ?limit };
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error(
+                    "the default value of `?limit` is not a literal, extension function call, set, or record",
+                )
+                .exactly_one_underline("?limit")
+                .build(),
+            );
+        });
+
+        let src = r#"
+            template(?limit: Long, ?unused: String) =>
+            permit(principal, action, resource) when { resource.size < ?limit };
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error(
+                    "`?unused` is declared in the template header but not used in the template",
+                )
+                .exactly_one_underline("?unused")
+                .build(),
+            );
+        });
+
+        let src = r#"
+            template(?principal: User) =>
+            permit(principal == ?principal, action, resource);
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error(
+                    "`?principal` cannot be declared in the template header",
+                )
+                .help("`?principal` and `?resource` are always entity slots and are not declared")
+                .exactly_one_underline("?principal")
+                .build(),
+            );
+        });
+
+        let src = r#"
+            template(?limit: List<Long>) =>
+            permit(principal, action, resource) when { resource.size < ?limit };
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error("`List<Long>` is not a valid type for a value slot")
+                    .help("`Set` is the only type which takes a type argument, e.g., `Set<Long>`")
+                    .exactly_one_underline("List<Long>")
+                    .build(),
+            );
+        });
+
+        let src = r#"
+            templat(?limit: Long) =>
+            permit(principal, action, resource) when { resource.size < ?limit };
+            "#;
+        assert_matches!(parse_policy_or_template(None, src), Err(e) => {
+            expect_exactly_one_error(
+                src,
+                &e,
+                &ExpectedErrorMessageBuilder::error("invalid template header: templat")
+                    .help("a template header must start with `template`, e.g., `template(?threshold: Long) =>`")
+                    .exactly_one_underline("templat")
+                    .build(),
+            );
+        });
+    }
+
     #[test]
     fn record_literals() {
         // unquoted keys
@@ -1160,17 +1340,19 @@ mod tests {
         // AST actually requires `principal` (`action`, `resource`, resp.). In
         // the `principal` case we also claim to expect `)` because an empty scope
         // initially parses to a CST. The trailing comma rules this out in the others.
+        // Directly after the first `(` we also claim to expect a slot, because
+        // `permit(` could be the start of a `template(?slot: Type) =>` header.
         assert_labeled_span(
             "permit(",
             "unexpected end of input",
             "",
-            "expected `)` or identifier",
+            "expected `)`, identifier, `?principal`, or `?resource`",
         );
         assert_labeled_span(
             "permit(,,);",
             "unexpected token `,`",
             ",",
-            "expected `)` or identifier",
+            "expected `)`, identifier, `?principal`, or `?resource`",
         );
         assert_labeled_span(
             "permit(principal,",
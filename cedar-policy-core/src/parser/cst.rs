@@ -51,6 +51,8 @@ pub enum Str {
 pub struct PolicyImpl {
     /// Annotations
     pub annotations: Vec<Node<Annotation>>,
+    /// Header declaring the value slots of a template, if any
+    pub header: Option<Node<TemplateHeader>>,
     /// policy effect
     pub effect: Node<Ident>,
     /// Variables
@@ -59,6 +61,47 @@ pub struct PolicyImpl {
     pub conds: Vec<Node<Cond>>,
 }
 
+/// Header declaring the value slots of a template, e.g.,
+/// `template(?threshold: Long) =>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateHeader {
+    /// keyword, expected: `template`
+    pub keyword: Node<Ident>,
+    /// Declared slots. The parser ensures there is at least one.
+    pub slots: Vec<Node<SlotDecl>>,
+}
+
+/// Declaration of a value slot in a template header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotDecl {
+    /// The slot being declared
+    pub slot: Node<Slot>,
+    /// Its type
+    pub ty: Node<SlotType>,
+    /// Its default value, used when a link does not bind it
+    pub default: Option<Node<Expr>>,
+}
+
+/// Type of a value slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotType {
+    /// Primitive, entity, or extension type, e.g., `Long` or `User`
+    Name(Node<Name>),
+    /// Type with a parameter, e.g., `Set<String>`
+    Generic(Node<Name>, Box<Node<SlotType>>),
+    /// Record type, e.g., `{ a: Long }`
+    Record(Vec<Node<SlotTypeAttr>>),
+}
+
+/// Attribute of a record type in a template header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotTypeAttr {
+    /// Attribute name, written either as an identifier or a string
+    pub key: Node<Str>,
+    /// Attribute type
+    pub ty: Node<SlotType>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Policy statement - can be an Error node when 'tolerant-ast' feature is switched on
 pub enum Policy {
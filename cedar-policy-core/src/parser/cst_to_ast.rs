@@ -45,7 +45,7 @@ use crate::ast::{
     PrincipalOrResourceConstraint, ResourceConstraint, UnreservedId, UnwrapInfallible,
 };
 use crate::expr_builder::{ExprBuilder, ExprBuilderInfallibleBuild};
-use crate::extensions::ExtStyles;
+use crate::extensions::Extensions;
 use itertools::{Either, Itertools};
 use nonempty::nonempty;
 use nonempty::NonEmpty;
@@ -83,7 +83,7 @@ impl Node<Option<cst::Policies>> {
     }
 
     /// convert `cst::Policies` to `ast::PolicySet`
-    pub fn to_policyset(&self) -> Result<ast::PolicySet> {
+    pub fn to_policyset(&self, exts: &Extensions<'_>) -> Result<ast::PolicySet> {
         let mut pset = ast::PolicySet::new();
         let mut all_errs: Vec<ParseErrors> = vec![];
         // Caution: `parser::parse_policyset_and_also_return_policy_text()`
@@ -91,7 +91,7 @@ impl Node<Option<cst::Policies>> {
         // generated by `with_generated_policyids()` to maintain an invariant.
         for (policy_id, policy) in self.with_generated_policyids()? {
             // policy may have convert error
-            match policy.to_policy_or_template(policy_id) {
+            match policy.to_policy_or_template(policy_id, exts) {
                 Ok(Either::Right(template)) => {
                     if let Err(e) = pset.add_template(template) {
                         match e {
@@ -128,7 +128,7 @@ impl Node<Option<cst::Policies>> {
 
     /// convert `cst::Policies` to `ast::PolicySet`
     #[cfg(feature = "tolerant-ast")]
-    pub fn to_policyset_tolerant(&self) -> Result<ast::PolicySet> {
+    pub fn to_policyset_tolerant(&self, exts: &Extensions<'_>) -> Result<ast::PolicySet> {
         let mut pset = ast::PolicySet::new();
         let mut all_errs: Vec<ParseErrors> = vec![];
         // Caution: `parser::parse_policyset_and_also_return_policy_text()`
@@ -136,7 +136,7 @@ impl Node<Option<cst::Policies>> {
         // generated by `with_generated_policyids()` to maintain an invariant.
         for (policy_id, policy) in self.with_generated_policyids()? {
             // policy may have convert error
-            match policy.to_policy_or_template_tolerant(policy_id) {
+            match policy.to_policy_or_template_tolerant(policy_id, exts) {
                 Ok(Either::Right(template)) => {
                     if let Err(e) = pset.add_template(template) {
                         match e {
@@ -175,23 +175,28 @@ impl Node<Option<cst::Policies>> {
 impl Node<Option<cst::Policy>> {
     /// Convert `cst::Policy` to `ast::Template`. Works for static policies as
     /// well, which will become templates with 0 slots
-    pub fn to_template(&self, id: ast::PolicyID) -> Result<ast::Template> {
-        self.to_policy_template(id)
+    pub fn to_template(&self, id: ast::PolicyID, exts: &Extensions<'_>) -> Result<ast::Template> {
+        self.to_policy_template(id, exts)
     }
 
     /// Convert `cst::Policy` to `ast::Template`. Works for static policies as
     /// well, which will become templates with 0 slots
     #[cfg(feature = "tolerant-ast")]
-    pub fn to_template_tolerant(&self, id: ast::PolicyID) -> Result<ast::Template> {
-        self.to_policy_template_tolerant(id)
+    pub fn to_template_tolerant(
+        &self,
+        id: ast::PolicyID,
+        exts: &Extensions<'_>,
+    ) -> Result<ast::Template> {
+        self.to_policy_template_tolerant(id, exts)
     }
 
     /// Convert `cst::Policy` to an AST `StaticPolicy` or `Template`
     pub fn to_policy_or_template(
         &self,
         id: ast::PolicyID,
+        exts: &Extensions<'_>,
     ) -> Result<Either<ast::StaticPolicy, ast::Template>> {
-        let t = self.to_policy_template(id)?;
+        let t = self.to_policy_template(id, exts)?;
         if t.slots().count() == 0 {
             #[expect(clippy::expect_used, reason = "A `Template` with no slots will successfully convert to a `StaticPolicy`")]
             let p = ast::StaticPolicy::try_from(t).expect("internal invariant violation: a template with no slots should be a valid static policy");
@@ -206,8 +211,9 @@ impl Node<Option<cst::Policy>> {
     pub fn to_policy_or_template_tolerant(
         &self,
         id: ast::PolicyID,
+        exts: &Extensions<'_>,
     ) -> Result<Either<ast::StaticPolicy, ast::Template>> {
-        let t = self.to_policy_template_tolerant(id)?;
+        let t = self.to_policy_template_tolerant(id, exts)?;
         if t.slots().count() == 0 {
             #[expect(clippy::expect_used, reason = "A `Template` with no slots will successfully convert to a `StaticPolicy`")]
             let p = ast::StaticPolicy::try_from(t).expect("internal invariant violation: a template with no slots should be a valid static policy");
@@ -218,8 +224,8 @@ impl Node<Option<cst::Policy>> {
     }
 
     /// Convert `cst::Policy` to an AST `StaticPolicy`. (Will fail if the CST is for a template)
-    pub fn to_policy(&self, id: ast::PolicyID) -> Result<ast::StaticPolicy> {
-        let maybe_template = self.to_policy_template(id);
+    pub fn to_policy(&self, id: ast::PolicyID, exts: &Extensions<'_>) -> Result<ast::StaticPolicy> {
+        let maybe_template = self.to_policy_template(id, exts);
         let maybe_policy = maybe_template.map(ast::StaticPolicy::try_from);
         match maybe_policy {
             // Successfully parsed a static policy
@@ -254,7 +260,11 @@ impl Node<Option<cst::Policy>> {
 
     /// Convert `cst::Policy` to `ast::Template`. Works for static policies as
     /// well, which will become templates with 0 slots
-    pub fn to_policy_template(&self, id: ast::PolicyID) -> Result<ast::Template> {
+    pub fn to_policy_template(
+        &self,
+        id: ast::PolicyID,
+        exts: &Extensions<'_>,
+    ) -> Result<ast::Template> {
         let policy = self.try_as_inner()?;
         let policy = match policy {
             cst::Policy::Policy(policy_impl) => policy_impl,
@@ -278,40 +288,55 @@ impl Node<Option<cst::Policy>> {
         });
 
         // convert scope
-        let maybe_scope = policy.extract_scope();
+        let maybe_scope = policy.extract_scope(exts);
 
         // convert conditions
         let maybe_conds = ParseErrors::transpose(policy.conds.iter().map(|c| {
-            let (e, is_when) = c.to_expr::<ast::ExprBuilder<()>>()?;
-
-            let slot_errs = e.slots().map(|slot| {
-                ToASTError::new(
-                    ToASTErrorKind::slots_in_condition_clause(
-                        slot.clone(),
-                        if is_when { "when" } else { "unless" },
-                    ),
-                    slot.loc.or_else(|| c.loc.clone()),
-                )
-                .into()
-            });
+            let (e, is_when) = c.to_expr::<ast::ExprBuilder<()>>(exts)?;
+
+            let slot_errs = e
+                .slots()
+                .filter(|slot| !slot.id.is_value_slot())
+                .map(|slot| {
+                    ToASTError::new(
+                        ToASTErrorKind::slots_in_condition_clause(
+                            slot.clone(),
+                            if is_when { "when" } else { "unless" },
+                        ),
+                        slot.loc.or_else(|| c.loc.clone()),
+                    )
+                    .into()
+                });
             match ParseErrors::from_iter(slot_errs) {
                 Some(errs) => Err(errs),
                 None => Ok(e),
             }
         }));
 
-        let (effect, annotations, (principal, action, resource), conds) =
-            flatten_tuple_4(maybe_effect, maybe_annotations, maybe_scope, maybe_conds)?;
-        Ok(construct_template_policy(
-            id,
-            annotations.into(),
-            effect,
-            principal,
-            action,
-            resource,
-            conds,
-            self.loc(),
-        ))
+        // convert template header
+        let maybe_value_slots = policy.value_slot_declarations();
+        let maybe_defaults =
+            policy.value_slot_defaults(|e| e.to_expr::<ast::ExprBuilder<()>>(exts));
+
+        let ((effect, annotations, (principal, action, resource), conds), (value_slots, defaults)) =
+            flatten_tuple_2(
+                flatten_tuple_4(maybe_effect, maybe_annotations, maybe_scope, maybe_conds),
+                flatten_tuple_2(maybe_value_slots, maybe_defaults),
+            )?;
+        declare_value_slots(
+            construct_template_policy(
+                id,
+                annotations.into(),
+                effect,
+                principal,
+                action,
+                resource,
+                conds,
+                self.loc(),
+            ),
+            value_slots,
+            defaults,
+        )
     }
 
     /// Convert `cst::Policy` to an AST `StaticPolicy`. (Will fail if the CST is for a template)
@@ -319,8 +344,12 @@ impl Node<Option<cst::Policy>> {
     /// These cannot be evaluated
     /// Should ONLY be used to examine a partially constructed AST from invalid Cedar
     #[cfg(feature = "tolerant-ast")]
-    pub fn to_policy_tolerant(&self, id: ast::PolicyID) -> Result<ast::StaticPolicy> {
-        let maybe_template = self.to_policy_template_tolerant(id);
+    pub fn to_policy_tolerant(
+        &self,
+        id: ast::PolicyID,
+        exts: &Extensions<'_>,
+    ) -> Result<ast::StaticPolicy> {
+        let maybe_template = self.to_policy_template_tolerant(id, exts);
         let maybe_policy = maybe_template.map(ast::StaticPolicy::try_from);
         match maybe_policy {
             // Successfully parsed a static policy
@@ -359,7 +388,11 @@ impl Node<Option<cst::Policy>> {
     /// These cannot be evaluated
     /// Should ONLY be used to examine a partially constructed AST from invalid Cedar
     #[cfg(feature = "tolerant-ast")]
-    pub fn to_policy_template_tolerant(&self, id: ast::PolicyID) -> Result<ast::Template> {
+    pub fn to_policy_template_tolerant(
+        &self,
+        id: ast::PolicyID,
+        exts: &Extensions<'_>,
+    ) -> Result<ast::Template> {
         let policy = self.try_as_inner()?;
         let policy = match policy {
             cst::Policy::Policy(policy_impl) => policy_impl,
@@ -376,46 +409,111 @@ impl Node<Option<cst::Policy>> {
         });
 
         // convert scope
-        let maybe_scope = policy.extract_scope_tolerant_ast();
+        let maybe_scope = policy.extract_scope_tolerant_ast(exts);
 
         // convert conditions
         let maybe_conds = ParseErrors::transpose(policy.conds.iter().map(|c| {
-            let (e, is_when) = c.to_expr::<ExprWithErrsBuilder<()>>()?;
-            let slot_errs = e.slots().map(|slot| {
-                ToASTError::new(
-                    ToASTErrorKind::slots_in_condition_clause(
-                        slot.clone(),
-                        if is_when { "when" } else { "unless" },
-                    ),
-                    slot.loc.or_else(|| c.loc.clone()),
-                )
-                .into()
-            });
+            let (e, is_when) = c.to_expr::<ExprWithErrsBuilder<()>>(exts)?;
+            let slot_errs = e
+                .slots()
+                .filter(|slot| !slot.id.is_value_slot())
+                .map(|slot| {
+                    ToASTError::new(
+                        ToASTErrorKind::slots_in_condition_clause(
+                            slot.clone(),
+                            if is_when { "when" } else { "unless" },
+                        ),
+                        slot.loc.or_else(|| c.loc.clone()),
+                    )
+                    .into()
+                });
             match ParseErrors::from_iter(slot_errs) {
                 Some(errs) => Err(errs),
                 None => Ok(e),
             }
         }));
 
-        let (effect, annotations, (principal, action, resource), conds) =
-            flatten_tuple_4(maybe_effect, maybe_annotations, maybe_scope, maybe_conds)?;
-        Ok(construct_template_policy(
-            id,
-            annotations.into(),
-            effect,
-            principal,
-            action,
-            resource,
-            conds,
-            self.loc.as_ref(),
-        ))
+        // convert template header
+        let maybe_value_slots = policy.value_slot_declarations();
+        let maybe_defaults =
+            policy.value_slot_defaults(|e| e.to_expr::<ast::ExprBuilder<()>>(exts));
+
+        let ((effect, annotations, (principal, action, resource), conds), (value_slots, defaults)) =
+            flatten_tuple_2(
+                flatten_tuple_4(maybe_effect, maybe_annotations, maybe_scope, maybe_conds),
+                flatten_tuple_2(maybe_value_slots, maybe_defaults),
+            )?;
+        declare_value_slots(
+            construct_template_policy(
+                id,
+                annotations.into(),
+                effect,
+                principal,
+                action,
+                resource,
+                conds,
+                self.loc.as_ref(),
+            ),
+            value_slots,
+            defaults,
+        )
     }
 }
 
+/// Declare the value slots of a template and their default values, reporting
+/// a value slot which is used but not declared as an invalid slot
+fn declare_value_slots(
+    template: ast::Template,
+    value_slots: Vec<(ast::Slot, ast::SlotType)>,
+    defaults: Vec<(ast::Slot, ast::Expr)>,
+) -> Result<ast::Template> {
+    template
+        .with_value_slot_declarations(value_slots)
+        .and_then(|t| t.with_value_slot_defaults(defaults))
+        .map_err(|err| {
+            let loc = err.slot().loc.clone();
+            let kind = match err {
+                ast::ValueSlotDeclarationError::Undeclared(slot) => {
+                    ToASTErrorKind::InvalidSlot(slot.id.to_smolstr())
+                }
+                err => err.into(),
+            };
+            ToASTError::new(kind, loc).into()
+        })
+}
+
 impl cst::PolicyImpl {
+    /// Get the value slots declared in the template header of the
+    /// `cst::Policy`, with their types. This is empty if there is no header.
+    pub fn value_slot_declarations(&self) -> Result<Vec<(ast::Slot, ast::SlotType)>> {
+        self.header
+            .as_ref()
+            .map_or_else(|| Ok(Vec::new()), |h| h.to_value_slot_declarations())
+    }
+
+    /// Get the default values of the value slots declared in the template
+    /// header of the `cst::Policy`, converted with `convert`. Slots declared
+    /// without a default are omitted.
+    pub fn value_slot_defaults<T>(
+        &self,
+        convert: impl Fn(&Node<Option<cst::Expr>>) -> Result<T>,
+    ) -> Result<Vec<(ast::Slot, T)>> {
+        let Some(header) = &self.header else {
+            return Ok(Vec::new());
+        };
+        ParseErrors::transpose(header.try_as_inner()?.slots.iter().filter_map(|decl| {
+            let default = decl.as_inner()?.default.as_ref()?;
+            Some(flatten_tuple_2(
+                decl.to_value_slot_declaration().map(|(slot, _)| slot),
+                convert(default),
+            ))
+        }))
+    }
+
     /// Get the scope constraints from the `cst::Policy`
     pub fn extract_scope(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<(PrincipalConstraint, ActionConstraint, ResourceConstraint)> {
         // Tracks where the last variable in the scope ended. We'll point to
         // this position to indicate where to fill in vars if we're missing one.
@@ -424,7 +522,7 @@ impl cst::PolicyImpl {
         let mut vars = self.variables.iter();
         let maybe_principal = if let Some(scope1) = vars.next() {
             end_of_last_var = scope1.loc.as_ref().map(|loc| loc.end()).or(end_of_last_var);
-            scope1.to_principal_constraint(TolerantAstSetting::NotTolerant)
+            scope1.to_principal_constraint(TolerantAstSetting::NotTolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -439,7 +537,7 @@ impl cst::PolicyImpl {
         };
         let maybe_action = if let Some(scope2) = vars.next() {
             end_of_last_var = scope2.loc.as_ref().map(|loc| loc.end()).or(end_of_last_var);
-            scope2.to_action_constraint(TolerantAstSetting::NotTolerant)
+            scope2.to_action_constraint(TolerantAstSetting::NotTolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -453,7 +551,7 @@ impl cst::PolicyImpl {
             .into())
         };
         let maybe_resource = if let Some(scope3) = vars.next() {
-            scope3.to_resource_constraint(TolerantAstSetting::NotTolerant)
+            scope3.to_resource_constraint(TolerantAstSetting::NotTolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -497,6 +595,7 @@ impl cst::PolicyImpl {
     #[cfg(feature = "tolerant-ast")]
     pub fn extract_scope_tolerant_ast(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<(PrincipalConstraint, ActionConstraint, ResourceConstraint)> {
         // Tracks where the last variable in the scope ended. We'll point to
         // this position to indicate where to fill in vars if we're missing one.
@@ -505,7 +604,7 @@ impl cst::PolicyImpl {
         let mut vars = self.variables.iter();
         let maybe_principal = if let Some(scope1) = vars.next() {
             end_of_last_var = scope1.loc.as_ref().map(|loc| loc.end()).or(end_of_last_var);
-            scope1.to_principal_constraint(TolerantAstSetting::Tolerant)
+            scope1.to_principal_constraint(TolerantAstSetting::Tolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -520,7 +619,7 @@ impl cst::PolicyImpl {
         };
         let maybe_action = if let Some(scope2) = vars.next() {
             end_of_last_var = scope2.loc.as_ref().map(|loc| loc.end()).or(end_of_last_var);
-            scope2.to_action_constraint(TolerantAstSetting::Tolerant)
+            scope2.to_action_constraint(TolerantAstSetting::Tolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -534,7 +633,7 @@ impl cst::PolicyImpl {
             .into())
         };
         let maybe_resource = if let Some(scope3) = vars.next() {
-            scope3.to_resource_constraint(TolerantAstSetting::Tolerant)
+            scope3.to_resource_constraint(TolerantAstSetting::Tolerant, exts)
         } else {
             let effect_span = self
                 .effect
@@ -734,6 +833,7 @@ impl ast::UnreservedId {
         e: Build::Expr,
         args: Vec<Build::Expr>,
         loc: Option<&Loc>,
+        exts: &Extensions<'_>,
     ) -> Result<Build::Expr> {
         let builder = Build::new().with_maybe_source_loc(loc);
         match self.as_ref() {
@@ -751,8 +851,14 @@ impl ast::UnreservedId {
                 .map(|arg| builder.get_tag(e, arg)),
             "hasTag" => extract_single_argument(args.into_iter(), "hasTag", loc)
                 .map(|arg| builder.has_tag(e, arg)),
+            #[cfg(feature = "maps")]
+            "get" => extract_single_argument(args.into_iter(), "get", loc)
+                .map(|arg| builder.get_key(e, arg)),
+            #[cfg(feature = "maps")]
+            "containsKey" => extract_single_argument(args.into_iter(), "containsKey", loc)
+                .map(|arg| builder.contains_key(e, arg)),
             _ => {
-                if ExtStyles::is_method(self) {
+                if exts.styles().is_method(self) {
                     let args = NonEmpty {
                         head: e,
                         tail: args,
@@ -762,7 +868,7 @@ impl ast::UnreservedId {
                         .unwrap_infallible())
                 } else {
                     let unqual_name = ast::Name::unqualified_name(self.clone());
-                    if ExtStyles::is_function(&unqual_name) {
+                    if exts.styles().is_function(&unqual_name) {
                         Err(ToASTError::new(
                             ToASTErrorKind::MethodCallOnFunction(unqual_name.basename()),
                             loc.cloned(),
@@ -773,7 +879,7 @@ impl ast::UnreservedId {
                             ToASTError::new(
                                 ToASTErrorKind::UnknownMethod {
                                     id: self.clone(),
-                                    hint: ExtStyles::suggest_method(self),
+                                    hint: exts.styles().suggest_method(self),
                                 },
                                 loc.cloned(),
                             )
@@ -834,8 +940,13 @@ impl Node<Option<cst::VariableDef>> {
     fn to_principal_constraint(
         &self,
         tolerant_setting: TolerantAstSetting,
+        exts: &Extensions<'_>,
     ) -> Result<PrincipalConstraint> {
-        match self.to_principal_or_resource_constraint(ast::Var::Principal, tolerant_setting)? {
+        match self.to_principal_or_resource_constraint(
+            ast::Var::Principal,
+            tolerant_setting,
+            exts,
+        )? {
             PrincipalOrResource::Principal(p) => Ok(p),
             PrincipalOrResource::Resource(_) => Err(self
                 .to_ast_err(ToASTErrorKind::IncorrectVariable {
@@ -849,8 +960,13 @@ impl Node<Option<cst::VariableDef>> {
     fn to_resource_constraint(
         &self,
         tolerant_setting: TolerantAstSetting,
+        exts: &Extensions<'_>,
     ) -> Result<ResourceConstraint> {
-        match self.to_principal_or_resource_constraint(ast::Var::Resource, tolerant_setting)? {
+        match self.to_principal_or_resource_constraint(
+            ast::Var::Resource,
+            tolerant_setting,
+            exts,
+        )? {
             PrincipalOrResource::Principal(_) => Err(self
                 .to_ast_err(ToASTErrorKind::IncorrectVariable {
                     expected: ast::Var::Resource,
@@ -865,6 +981,7 @@ impl Node<Option<cst::VariableDef>> {
         &self,
         expected: ast::Var,
         tolerant_ast: TolerantAstSetting,
+        exts: &Extensions<'_>,
     ) -> Result<PrincipalOrResource> {
         let vardef = self.try_as_inner()?;
         let var = vardef.variable.to_var()?;
@@ -876,7 +993,7 @@ impl Node<Option<cst::VariableDef>> {
         let c = if let Some((op, rel_expr)) = &vardef.ineq {
             // special check for the syntax `_ in _ is _`
             if op == &cst::RelOp::In {
-                if let Ok(expr) = rel_expr.to_expr::<ast::ExprBuilder<()>>() {
+                if let Ok(expr) = rel_expr.to_expr::<ast::ExprBuilder<()>>(exts) {
                     if matches!(expr.expr_kind(), ast::ExprKind::Is { .. }) {
                         return Err(self.to_ast_err(ToASTErrorKind::InvertedIsIn).into());
                     }
@@ -893,7 +1010,7 @@ impl Node<Option<cst::VariableDef>> {
                 (cst::RelOp::In, None) => Ok(PrincipalOrResourceConstraint::In(eref)),
                 (cst::RelOp::In, Some(entity_type)) => {
                     match entity_type
-                        .to_expr_or_special::<ast::ExprBuilder<()>>()?
+                        .to_expr_or_special::<ast::ExprBuilder<()>>(exts)?
                         .into_entity_type()
                     {
                         Ok(et) => Ok(PrincipalOrResourceConstraint::IsIn(Arc::new(et), eref)),
@@ -914,7 +1031,7 @@ impl Node<Option<cst::VariableDef>> {
             }
         } else if let Some(entity_type) = &vardef.entity_type {
             match entity_type
-                .to_expr_or_special::<ast::ExprBuilder<()>>()?
+                .to_expr_or_special::<ast::ExprBuilder<()>>(exts)?
                 .into_entity_type()
             {
                 Ok(et) => Ok(PrincipalOrResourceConstraint::Is(Arc::new(et))),
@@ -942,6 +1059,7 @@ impl Node<Option<cst::VariableDef>> {
     fn to_action_constraint(
         &self,
         tolerant_setting: TolerantAstSetting,
+        exts: &Extensions<'_>,
     ) -> Result<ast::ActionConstraint> {
         let vardef = self.try_as_inner()?;
 
@@ -968,7 +1086,7 @@ impl Node<Option<cst::VariableDef>> {
             let action_constraint = match op {
                 cst::RelOp::In => {
                     // special check for the syntax `_ in _ is _`
-                    if let Ok(expr) = rel_expr.to_expr::<ast::ExprBuilder<()>>() {
+                    if let Ok(expr) = rel_expr.to_expr::<ast::ExprBuilder<()>>(exts) {
                         if matches!(expr.expr_kind(), ast::ExprKind::Is { .. }) {
                             return Err(self.to_ast_err(ToASTErrorKind::IsInActionScope).into());
                         }
@@ -1031,12 +1149,15 @@ impl Node<Option<cst::Cond>> {
     /// `true` if the cond is a `when` clause, `false` if it is an `unless`
     /// clause. (The returned `expr` is already adjusted for this, the `bool` is
     /// for information only.)
-    fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<(Build::Expr, bool)> {
+    fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<(Build::Expr, bool)> {
         let cond = self.try_as_inner()?;
         let is_when = cond.cond.to_cond_is_when()?;
 
         let maybe_expr = match &cond.expr {
-            Some(expr) => expr.to_expr::<Build>(),
+            Some(expr) => expr.to_expr::<Build>(exts),
             None => {
                 let ident = match cond.cond.as_inner() {
                     Some(ident) => ident.clone(),
@@ -1269,11 +1390,15 @@ where
 
 impl Node<Option<cst::Expr>> {
     /// convert `cst::Expr` to `ast::Expr`
-    pub fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    pub fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     pub(crate) fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let expr_opt = self.try_as_inner()?;
 
@@ -1290,11 +1415,11 @@ impl Node<Option<cst::Expr>> {
         };
 
         match &*expr.expr {
-            cst::ExprData::Or(or) => or.to_expr_or_special::<Build>(),
+            cst::ExprData::Or(or) => or.to_expr_or_special::<Build>(exts),
             cst::ExprData::If(i, t, e) => {
-                let maybe_guard = i.to_expr::<Build>();
-                let maybe_then = t.to_expr::<Build>();
-                let maybe_else = e.to_expr::<Build>();
+                let maybe_guard = i.to_expr::<Build>(exts);
+                let maybe_then = t.to_expr::<Build>(exts);
+                let maybe_else = e.to_expr::<Build>(exts);
 
                 let (i, t, e) = flatten_tuple_3(maybe_guard, maybe_then, maybe_else)?;
                 Ok(ExprOrSpecial::Expr {
@@ -1311,11 +1436,13 @@ impl Node<Option<cst::Expr>> {
 impl Node<Option<cst::Or>> {
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let or = self.try_as_inner()?;
 
-        let maybe_first = or.initial.to_expr_or_special::<Build>();
-        let maybe_rest = ParseErrors::transpose(or.extended.iter().map(|i| i.to_expr::<Build>()));
+        let maybe_first = or.initial.to_expr_or_special::<Build>(exts);
+        let maybe_rest =
+            ParseErrors::transpose(or.extended.iter().map(|i| i.to_expr::<Build>(exts)));
 
         let (first, rest) = flatten_tuple_2(maybe_first, maybe_rest)?;
         if rest.is_empty() {
@@ -1334,16 +1461,21 @@ impl Node<Option<cst::Or>> {
 }
 
 impl Node<Option<cst::And>> {
-    pub(crate) fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    pub(crate) fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let and = self.try_as_inner()?;
 
-        let maybe_first = and.initial.to_expr_or_special::<Build>();
-        let maybe_rest = ParseErrors::transpose(and.extended.iter().map(|i| i.to_expr::<Build>()));
+        let maybe_first = and.initial.to_expr_or_special::<Build>(exts);
+        let maybe_rest =
+            ParseErrors::transpose(and.extended.iter().map(|i| i.to_expr::<Build>(exts)));
 
         let (first, rest) = flatten_tuple_2(maybe_first, maybe_rest)?;
         if rest.is_empty() {
@@ -1362,21 +1494,25 @@ impl Node<Option<cst::And>> {
 }
 
 impl Node<Option<cst::Relation>> {
-    fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let rel = self.try_as_inner()?;
 
         match rel {
             cst::Relation::Common { initial, extended } => {
-                let maybe_first = initial.to_expr_or_special::<Build>();
+                let maybe_first = initial.to_expr_or_special::<Build>(exts);
                 let maybe_rest = ParseErrors::transpose(
                     extended
                         .iter()
-                        .map(|(op, i)| i.to_expr::<Build>().map(|e| (op, e))),
+                        .map(|(op, i)| i.to_expr::<Build>(exts).map(|e| (op, e))),
                 );
                 let maybe_extra_elmts = if extended.len() > 1 {
                     Err(self.to_ast_err(ToASTErrorKind::AmbiguousOperators).into())
@@ -1397,8 +1533,8 @@ impl Node<Option<cst::Relation>> {
                 }
             }
             cst::Relation::Has { target, field } => {
-                let maybe_target = target.to_expr::<Build>();
-                let maybe_fields = Ok(match field.to_has_rhs::<Build>()? {
+                let maybe_target = target.to_expr::<Build>(exts);
+                let maybe_fields = Ok(match field.to_has_rhs::<Build>(exts)? {
                     Either::Left(s) => nonempty![s],
                     Either::Right(ids) => ids.map(|id| id.into_smolstr()),
                 });
@@ -1411,8 +1547,8 @@ impl Node<Option<cst::Relation>> {
                 })
             }
             cst::Relation::Like { target, pattern } => {
-                let maybe_target = target.to_expr::<Build>();
-                let maybe_pattern = pattern.to_expr_or_special::<Build>()?.into_pattern();
+                let maybe_target = target.to_expr::<Build>(exts);
+                let maybe_pattern = pattern.to_expr_or_special::<Build>(exts)?.into_pattern();
                 let (target, pattern) = flatten_tuple_2(maybe_target, maybe_pattern)?;
                 Ok(ExprOrSpecial::Expr {
                     expr: Build::new()
@@ -1426,9 +1562,9 @@ impl Node<Option<cst::Relation>> {
                 entity_type,
                 in_entity,
             } => {
-                let maybe_target = target.to_expr::<Build>();
+                let maybe_target = target.to_expr::<Build>(exts);
                 let maybe_entity_type = entity_type
-                    .to_expr_or_special::<Build>()?
+                    .to_expr_or_special::<Build>(exts)?
                     .into_entity_type()
                     .map_err(|eos| {
                         eos.to_ast_err(ToASTErrorKind::InvalidIsType {
@@ -1447,7 +1583,7 @@ impl Node<Option<cst::Relation>> {
                 let (t, n) = flatten_tuple_2(maybe_target, maybe_entity_type)?;
                 match in_entity {
                     Some(in_entity) => {
-                        let in_expr = in_entity.to_expr::<Build>()?;
+                        let in_expr = in_entity.to_expr::<Build>(exts)?;
                         Ok(ExprOrSpecial::Expr {
                             expr: Build::new()
                                 .with_maybe_source_loc(self.loc.as_ref())
@@ -1468,8 +1604,11 @@ impl Node<Option<cst::Relation>> {
 }
 
 impl Node<Option<cst::Add>> {
-    fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
 
     // Peel the grammar onion until we see valid RHS
@@ -1483,6 +1622,7 @@ impl Node<Option<cst::Add>> {
     // despite producing deadcode.
     pub(crate) fn to_has_rhs<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<Either<SmolStr, NonEmpty<UnreservedId>>> {
         let inner @ cst::Add { initial, extended } = self.try_as_inner()?;
         let err = |loc| {
@@ -1527,7 +1667,7 @@ impl Node<Option<cst::Add>> {
                 | cst::Primary::Ref(_)
                 | cst::Primary::Slot(_) => Err(err(item.loc.clone())),
                 cst::Primary::Literal(_) | cst::Primary::Name(_) => {
-                    let item = item.to_expr_or_special::<Build>()?;
+                    let item = item.to_expr_or_special::<Build>(exts)?;
                     match (item, access.as_slice()) {
                         (ExprOrSpecial::StrLit { lit, loc }, []) => Ok(Either::Left(
                             to_unescaped_string(lit).map_err(|escape_errs| {
@@ -1579,14 +1719,15 @@ impl Node<Option<cst::Add>> {
 
     pub(crate) fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let add = self.try_as_inner()?;
 
-        let maybe_first = add.initial.to_expr_or_special::<Build>();
+        let maybe_first = add.initial.to_expr_or_special::<Build>(exts);
         let maybe_rest = ParseErrors::transpose(
             add.extended
                 .iter()
-                .map(|&(op, ref i)| i.to_expr::<Build>().map(|e| (op, e))),
+                .map(|&(op, ref i)| i.to_expr::<Build>(exts).map(|e| (op, e))),
         );
         let (first, rest) = flatten_tuple_2(maybe_first, maybe_rest)?;
         if !rest.is_empty() {
@@ -1605,17 +1746,21 @@ impl Node<Option<cst::Add>> {
 }
 
 impl Node<Option<cst::Mult>> {
-    fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let mult = self.try_as_inner()?;
 
-        let maybe_first = mult.initial.to_expr_or_special::<Build>();
+        let maybe_first = mult.initial.to_expr_or_special::<Build>(exts);
         let maybe_rest = ParseErrors::transpose(mult.extended.iter().map(|&(op, ref i)| {
-            i.to_expr::<Build>().and_then(|e| match op {
+            i.to_expr::<Build>(exts).and_then(|e| match op {
                 cst::MultOp::Times => Ok(e),
                 cst::MultOp::Divide => {
                     Err(self.to_ast_err(ToASTErrorKind::UnsupportedDivision).into())
@@ -1641,18 +1786,22 @@ impl Node<Option<cst::Mult>> {
 }
 
 impl Node<Option<cst::Unary>> {
-    fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let unary = self.try_as_inner()?;
 
         match unary.op {
-            None => unary.item.to_expr_or_special::<Build>(),
+            None => unary.item.to_expr_or_special::<Build>(exts),
             Some(cst::NegOp::Bang(n)) => {
-                (0..n).fold(unary.item.to_expr_or_special::<Build>(), |inner, _| {
+                (0..n).fold(unary.item.to_expr_or_special::<Build>(exts), |inner, _| {
                     inner
                         .and_then(|e| e.into_expr::<Build>())
                         .map(|expr| ExprOrSpecial::Expr {
@@ -1663,7 +1812,7 @@ impl Node<Option<cst::Unary>> {
                         })
                 })
             }
-            Some(cst::NegOp::Dash(0)) => unary.item.to_expr_or_special::<Build>(),
+            Some(cst::NegOp::Dash(0)) => unary.item.to_expr_or_special::<Build>(exts),
             Some(cst::NegOp::Dash(c)) => {
                 // Test if there is a negative numeric literal.
                 // A negative numeric literal should match regex pattern
@@ -1697,7 +1846,7 @@ impl Node<Option<cst::Unary>> {
                     (
                         unary
                             .item
-                            .to_expr_or_special::<Build>()
+                            .to_expr_or_special::<Build>(exts)
                             .and_then(|i| i.into_expr::<Build>()),
                         c,
                     )
@@ -1762,6 +1911,7 @@ impl Node<Option<cst::Member>> {
         head: Build::Expr,
         next: &mut AstAccessor<Build::Expr>,
         tail: &'a mut [AstAccessor<Build::Expr>],
+        exts: &Extensions<'_>,
     ) -> Result<(Build::Expr, &'a mut [AstAccessor<Build::Expr>])> {
         use AstAccessor::*;
         match (next, tail) {
@@ -1774,7 +1924,10 @@ impl Node<Option<cst::Member>> {
                 let args = std::mem::take(args);
                 // move the id out of the slice as well, to avoid cloning the internal string
                 let id = mem::replace(id, ast::UnreservedId::empty());
-                Ok((id.to_meth::<Build>(head, args, self.loc.as_ref())?, rest))
+                Ok((
+                    id.to_meth::<Build>(head, args, self.loc.as_ref(), exts)?,
+                    rest,
+                ))
             }
 
             // field of arbitrary expr like `(principal.foo).bar`
@@ -1803,12 +1956,13 @@ impl Node<Option<cst::Member>> {
 
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let mem = self.try_as_inner()?;
 
-        let maybe_prim = mem.item.to_expr_or_special::<Build>();
+        let maybe_prim = mem.item.to_expr_or_special::<Build>(exts);
         let maybe_accessors =
-            ParseErrors::transpose(mem.access.iter().map(|a| a.to_access::<Build>()));
+            ParseErrors::transpose(mem.access.iter().map(|a| a.to_access::<Build>(exts)));
 
         // Return errors in case parsing failed for any element
         let (prim, mut accessors) = flatten_tuple_2(maybe_prim, maybe_accessors)?;
@@ -1825,14 +1979,14 @@ impl Node<Option<cst::Member>> {
                 // accesses on this expression in the loop at the end of this
                 // function.
                 (prim @ (Expr { .. } | StrLit { .. } | BoolLit { .. }), [next, rest @ ..]) => {
-                    self.build_expr_accessor::<Build>(prim.into_expr::<Build>()?, next, rest)?
+                    self.build_expr_accessor::<Build>(prim.into_expr::<Build>()?, next, rest, exts)?
                 }
 
                 // function call
                 (Name { name, .. }, [Call(args), rest @ ..]) => {
                     // move the vec out of the slice, we won't use the slice after
                     let args = std::mem::take(args);
-                    (name.into_func::<Build>(args, self.loc.clone())?, rest)
+                    (name.into_func::<Build>(args, self.loc.clone(), exts)?, rest)
                 }
                 // variable function call - error
                 (Var { var, .. }, [Call(_), ..]) => {
@@ -1857,6 +2011,7 @@ impl Node<Option<cst::Member>> {
                                 .var(var),
                             args,
                             self.loc.as_ref(),
+                            exts,
                         )?,
                         rest,
                     )
@@ -1919,7 +2074,7 @@ impl Node<Option<cst::Member>> {
         // without need to consider the other cases until we've consumed the
         // list of accesses.
         while let [next, rest @ ..] = tail {
-            (head, tail) = self.build_expr_accessor::<Build>(head, next, rest)?;
+            (head, tail) = self.build_expr_accessor::<Build>(head, next, rest, exts)?;
         }
         Ok(ExprOrSpecial::Expr {
             expr: head,
@@ -1929,7 +2084,10 @@ impl Node<Option<cst::Member>> {
 }
 
 impl Node<Option<cst::MemAccess>> {
-    fn to_access<Build: ExprBuilderInfallibleBuild>(&self) -> Result<AstAccessor<Build::Expr>> {
+    fn to_access<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<AstAccessor<Build::Expr>> {
         let acc = self.try_as_inner()?;
 
         match acc {
@@ -1938,11 +2096,14 @@ impl Node<Option<cst::MemAccess>> {
                 maybe_ident.map(AstAccessor::Field)
             }
             cst::MemAccess::Call(args) => {
-                let maybe_args = ParseErrors::transpose(args.iter().map(|e| e.to_expr::<Build>()));
+                let maybe_args =
+                    ParseErrors::transpose(args.iter().map(|e| e.to_expr::<Build>(exts)));
                 maybe_args.map(AstAccessor::Call)
             }
             cst::MemAccess::Index(index) => {
-                let maybe_index = index.to_expr_or_special::<Build>()?.into_string_literal();
+                let maybe_index = index
+                    .to_expr_or_special::<Build>(exts)?
+                    .into_string_literal();
                 maybe_index.map(AstAccessor::Index)
             }
         }
@@ -1950,11 +2111,15 @@ impl Node<Option<cst::MemAccess>> {
 }
 
 impl Node<Option<cst::Primary>> {
-    pub(crate) fn to_expr<Build: ExprBuilderInfallibleBuild>(&self) -> Result<Build::Expr> {
-        self.to_expr_or_special::<Build>()?.into_expr::<Build>()
+    pub(crate) fn to_expr<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<Build::Expr> {
+        self.to_expr_or_special::<Build>(exts)?.into_expr::<Build>()
     }
     fn to_expr_or_special<Build: ExprBuilderInfallibleBuild>(
         &self,
+        exts: &Extensions<'_>,
     ) -> Result<ExprOrSpecial<'_, Build::Expr>> {
         let prim = self.try_as_inner()?;
 
@@ -1989,12 +2154,13 @@ impl Node<Option<cst::Primary>> {
                     })
                 }
             }
-            cst::Primary::Expr(e) => e.to_expr::<Build>().map(|expr| ExprOrSpecial::Expr {
+            cst::Primary::Expr(e) => e.to_expr::<Build>(exts).map(|expr| ExprOrSpecial::Expr {
                 expr,
                 loc: e.loc.clone(),
             }),
             cst::Primary::EList(es) => {
-                let maybe_list = ParseErrors::transpose(es.iter().map(|e| e.to_expr::<Build>()));
+                let maybe_list =
+                    ParseErrors::transpose(es.iter().map(|e| e.to_expr::<Build>(exts)));
                 maybe_list.map(|list| ExprOrSpecial::Expr {
                     expr: Build::new()
                         .with_maybe_source_loc(self.loc.as_ref())
@@ -2003,7 +2169,7 @@ impl Node<Option<cst::Primary>> {
                 })
             }
             cst::Primary::RInits(is) => {
-                let rec = ParseErrors::transpose(is.iter().map(|i| i.to_init::<Build>()))?;
+                let rec = ParseErrors::transpose(is.iter().map(|i| i.to_init::<Build>(exts)))?;
                 let expr = Build::new()
                     .with_maybe_source_loc(self.loc.as_ref())
                     .record(rec)
@@ -2049,16 +2215,152 @@ impl TryFrom<&cst::Slot> for ast::SlotId {
         match slot {
             cst::Slot::Principal => Ok(ast::SlotId::principal()),
             cst::Slot::Resource => Ok(ast::SlotId::resource()),
-            cst::Slot::Other(slot) => Err(ToASTErrorKind::InvalidSlot(slot.clone())),
+            cst::Slot::Other(slot) => match slot.strip_prefix('?') {
+                Some(name) => Ok(ast::SlotId::named(ast::Id::new_unchecked(name))),
+                None => Err(ToASTErrorKind::InvalidSlot(slot.clone())),
+            },
         }
     }
 }
 
+impl Node<Option<cst::TemplateHeader>> {
+    /// Get the value slots declared in a template header, with their types
+    fn to_value_slot_declarations(&self) -> Result<Vec<(ast::Slot, ast::SlotType)>> {
+        let header = self.try_as_inner()?;
+        let maybe_keyword = match header.keyword.try_as_inner()? {
+            cst::Ident::Ident(keyword) if keyword == "template" => Ok(()),
+            keyword => Err(header
+                .keyword
+                .to_ast_err(ToASTErrorKind::InvalidTemplateHeader(keyword.clone()))
+                .into()),
+        };
+        let maybe_decls = ParseErrors::transpose(
+            header
+                .slots
+                .iter()
+                .map(|decl| decl.to_value_slot_declaration()),
+        );
+        let ((), decls) = flatten_tuple_2(maybe_keyword, maybe_decls)?;
+        Ok(decls)
+    }
+}
+
+impl Node<Option<cst::SlotDecl>> {
+    fn to_value_slot_declaration(&self) -> Result<(ast::Slot, ast::SlotType)> {
+        let decl = self.try_as_inner()?;
+        let maybe_slot = decl
+            .slot
+            .try_as_inner()
+            .map_err(ParseErrors::from)
+            .and_then(|slot| match ast::SlotId::try_from(slot) {
+                Ok(id) => Ok(ast::Slot {
+                    id,
+                    loc: decl.slot.loc.clone(),
+                }),
+                Err(e) => Err(decl.slot.to_ast_err(e).into()),
+            });
+        let maybe_ty = decl.ty.to_slot_type();
+        flatten_tuple_2(maybe_slot, maybe_ty)
+    }
+}
+
+impl Node<Option<cst::SlotType>> {
+    fn to_slot_type(&self) -> Result<ast::SlotType> {
+        let ty = self.try_as_inner()?;
+        match ty {
+            cst::SlotType::Name(name) => name.to_slot_type(),
+            cst::SlotType::Generic(name, element) => {
+                let maybe_set = name.to_internal_name().and_then(|name| {
+                    if builtin_type_basename(&name) == Some("Set") {
+                        Ok(())
+                    } else {
+                        Err(self
+                            .to_ast_err(ToASTErrorKind::InvalidSlotType(ty.to_string()))
+                            .into())
+                    }
+                });
+                let maybe_element = element.to_slot_type();
+                let ((), element) = flatten_tuple_2(maybe_set, maybe_element)?;
+                Ok(ast::SlotType::Set {
+                    element: Box::new(element),
+                })
+            }
+            cst::SlotType::Record(attrs) => {
+                let attrs = ParseErrors::transpose(attrs.iter().map(|attr| {
+                    let inner = attr.try_as_inner()?;
+                    let maybe_key = inner.key.as_valid_string().and_then(|s| {
+                        to_unescaped_string(s).map_err(|escape_errs| {
+                            ParseErrors::new_from_nonempty(
+                                escape_errs.map(|e| inner.key.to_ast_err(e).into()),
+                            )
+                        })
+                    });
+                    let maybe_ty = inner.ty.to_slot_type();
+                    let (key, ty) = flatten_tuple_2(maybe_key, maybe_ty)?;
+                    Ok((attr, key, ty))
+                }))?;
+                let mut attributes = BTreeMap::new();
+                for (attr, key, ty) in attrs {
+                    if attributes.contains_key(&key) {
+                        return Err(attr
+                            .to_ast_err(ToASTErrorKind::DuplicateSlotTypeAttribute(key))
+                            .into());
+                    }
+                    attributes.insert(key, ty);
+                }
+                Ok(ast::SlotType::Record { attributes })
+            }
+        }
+    }
+}
+
+impl Node<Option<cst::Name>> {
+    /// Resolve the name of a value slot type: `Bool`, `Long`, and `String`
+    /// are primitive types, extension type names like `decimal` are extension
+    /// types, and anything else is an entity type
+    fn to_slot_type(&self) -> Result<ast::SlotType> {
+        let name = self.to_internal_name()?;
+        if let Some(basename) = builtin_type_basename(&name) {
+            match basename {
+                "Bool" => return Ok(ast::SlotType::Bool),
+                "Long" => return Ok(ast::SlotType::Long),
+                "String" => return Ok(ast::SlotType::String),
+                _ => (),
+            }
+            if let Some(ext_ty) = Extensions::all_available().ext_types().find(|ext_ty| {
+                ext_ty.is_unqualified() && ext_ty.basename_as_ref().as_ref() == basename
+            }) {
+                return Ok(ast::SlotType::Extension {
+                    name: ext_ty.clone(),
+                });
+            }
+        }
+        if builtin_type_basename(&name) == Some("Set") {
+            return Err(self
+                .to_ast_err(ToASTErrorKind::InvalidSlotType(name.to_string()))
+                .into());
+        }
+        let name: ast::Name = name.try_into().map_err(ParseErrors::singleton)?;
+        Ok(ast::SlotType::Entity { name: name.into() })
+    }
+}
+
+/// The basename of `name` if it may refer to a builtin type, i.e., if it is
+/// unqualified or in the `__cedar` namespace
+fn builtin_type_basename(name: &ast::InternalName) -> Option<&str> {
+    if name.is_unqualified() || name.namespace() == "__cedar" {
+        Some(name.basename().as_ref())
+    } else {
+        None
+    }
+}
+
 impl From<ast::SlotId> for cst::Slot {
     fn from(slot: ast::SlotId) -> cst::Slot {
         match slot {
             ast::SlotId(ast::ValidSlotId::Principal) => cst::Slot::Principal,
             ast::SlotId(ast::ValidSlotId::Resource) => cst::Slot::Resource,
+            ast::SlotId(ast::ValidSlotId::Value(id)) => cst::Slot::Other(format_smolstr!("?{id}")),
         }
     }
 }
@@ -2125,14 +2427,22 @@ impl ast::Name {
         self,
         args: Vec<Build::Expr>,
         loc: Option<Loc>,
+        exts: &Extensions<'_>,
     ) -> Result<Build::Expr> {
         // error on standard methods
         if self.0.path.is_empty() {
             let id = self.basename();
-            if ExtStyles::is_method(&id)
+            if exts.styles().is_method(&id)
                 || matches!(
                     id.as_ref(),
-                    "contains" | "containsAll" | "containsAny" | "isEmpty" | "getTag" | "hasTag"
+                    "contains"
+                        | "containsAll"
+                        | "containsAny"
+                        | "isEmpty"
+                        | "getTag"
+                        | "hasTag"
+                        | "get"
+                        | "containsKey"
                 )
             {
                 return Err(ToASTError::new(
@@ -2142,13 +2452,13 @@ impl ast::Name {
                 .into());
             }
         }
-        if ExtStyles::is_function(&self) {
+        if exts.styles().is_function(&self) {
             Ok(Build::new()
                 .with_maybe_source_loc(loc.as_ref())
                 .call_extension_fn(self, args)
                 .unwrap_infallible())
         } else {
-            let hint = ExtStyles::suggest_function(&self);
+            let hint = exts.styles().suggest_function(&self);
             Err(ToASTError::new(ToASTErrorKind::UnknownFunction { id: self, hint }, loc).into())
         }
     }
@@ -2227,11 +2537,14 @@ impl Node<Option<cst::Literal>> {
 }
 
 impl Node<Option<cst::RecInit>> {
-    fn to_init<Build: ExprBuilderInfallibleBuild>(&self) -> Result<(SmolStr, Build::Expr)> {
+    fn to_init<Build: ExprBuilderInfallibleBuild>(
+        &self,
+        exts: &Extensions<'_>,
+    ) -> Result<(SmolStr, Build::Expr)> {
         let lit = self.try_as_inner()?;
 
-        let maybe_attr = lit.0.to_expr_or_special::<Build>()?.into_valid_attr();
-        let maybe_value = lit.1.to_expr::<Build>();
+        let maybe_attr = lit.0.to_expr_or_special::<Build>(exts)?.into_valid_attr();
+        let maybe_value = lit.1.to_expr::<Build>(exts);
 
         flatten_tuple_2(maybe_attr, maybe_value)
     }
@@ -2336,7 +2649,7 @@ mod tests {
     fn assert_parse_expr_succeeds(text: &str) -> Expr {
         text_to_cst::parse_expr(text)
             .expect("failed parser")
-            .to_expr::<ast::ExprBuilder<()>>()
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
             .unwrap_or_else(|errs| {
                 panic!("failed conversion to AST:\n{:?}", miette::Report::new(errs))
             })
@@ -2346,7 +2659,7 @@ mod tests {
     fn assert_parse_expr_fails(text: &str) -> ParseErrors {
         let result = text_to_cst::parse_expr(text)
             .expect("failed parser")
-            .to_expr::<ast::ExprBuilder<()>>();
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available());
         match result {
             Ok(expr) => {
                 panic!("conversion to AST should have failed, but succeeded with:\n{expr}")
@@ -2359,7 +2672,10 @@ mod tests {
     fn assert_parse_policy_succeeds(text: &str) -> ast::StaticPolicy {
         text_to_cst::parse_policy(text)
             .expect("failed parser")
-            .to_policy(ast::PolicyID::from_string("id"))
+            .to_policy(
+                ast::PolicyID::from_string("id"),
+                Extensions::all_available(),
+            )
             .unwrap_or_else(|errs| {
                 panic!("failed conversion to AST:\n{:?}", miette::Report::new(errs))
             })
@@ -2369,7 +2685,10 @@ mod tests {
     fn assert_parse_policy_fails(text: &str) -> ParseErrors {
         let result = text_to_cst::parse_policy(text)
             .expect("failed parser")
-            .to_policy(ast::PolicyID::from_string("id"));
+            .to_policy(
+                ast::PolicyID::from_string("id"),
+                Extensions::all_available(),
+            );
         match result {
             Ok(policy) => {
                 panic!("conversion to AST should have failed, but succeeded with:\n{policy}")
@@ -2772,7 +3091,7 @@ mod tests {
         "#,
         )
         .expect("should parse")
-        .to_policyset()
+        .to_policyset(Extensions::all_available())
         .unwrap_or_else(|errs| panic!("failed convert to AST:\n{:?}", miette::Report::new(errs)));
         assert_matches!(
             policyset
@@ -2844,7 +3163,7 @@ mod tests {
             permit(principal, action, resource);
             "#,
         ).expect("should parse")
-        .to_policyset()
+        .to_policyset(Extensions::all_available())
         .unwrap_or_else(|errs| panic!("failed convert to AST:\n{:?}", miette::Report::new(errs)));
         let policy0 = policyset
             .get(&ast::PolicyID::from_string("policy0"))
@@ -3513,7 +3832,10 @@ mod tests {
         for src in CORRECT_TEMPLATES {
             text_to_cst::parse_policy(src)
                 .expect("parse_error")
-                .to_template(ast::PolicyID::from_string("i0"))
+                .to_template(
+                    ast::PolicyID::from_string("i0"),
+                    Extensions::all_available(),
+                )
                 .unwrap_or_else(|errs| {
                     panic!(
                         "Failed to create a policy template: {:?}",
@@ -4526,6 +4848,7 @@ mod tests {
                 "[].bar()",
                 ExpectedErrorMessageBuilder::error("`bar` is not a valid method")
                     .exactly_one_underline("[].bar()")
+                    .help("did you mean `add`?")
                     .build(),
             ),
             (
@@ -4580,6 +4903,19 @@ mod tests {
         }
     }
 
+    #[cfg(not(feature = "maps"))]
+    #[test]
+    fn map_operations_require_feature() {
+        for (src, method) in [
+            (r#"context.m.get("k")"#, "get"),
+            (r#"context.m.containsKey("k")"#, "containsKey"),
+        ] {
+            assert_matches!(parse_expr(src), Err(e) => {
+                assert_eq!(e.to_string(), format!("`{method}` is not a valid method"));
+            });
+        }
+    }
+
     #[test]
     fn invalid_slot() {
         let invalid_policies = [
@@ -4621,7 +4957,7 @@ mod tests {
                 ExpectedErrorMessageBuilder::error(
                     "`?foo` is not a valid template slot",
                 ).help(
-                    "a template slot may only be `?principal` or `?resource`",
+                    "a template slot may only be `?principal`, `?resource`, or a value slot declared in a `template(...) =>` header",
                 ).exactly_one_underline("?foo").build(),
             ),
 
@@ -5462,7 +5798,10 @@ mod tests {
     fn assert_parse_policy_allows_errors(text: &str) -> ast::StaticPolicy {
         text_to_cst::parse_policy_tolerant(text)
             .expect("failed parser")
-            .to_policy_tolerant(ast::PolicyID::from_string("id"))
+            .to_policy_tolerant(
+                ast::PolicyID::from_string("id"),
+                Extensions::all_available(),
+            )
             .unwrap_or_else(|errs| {
                 panic!("failed conversion to AST:\n{:?}", miette::Report::new(errs))
             })
@@ -5473,7 +5812,10 @@ mod tests {
     fn assert_parse_policy_allows_errors_fails(text: &str) -> ParseErrors {
         let result = text_to_cst::parse_policy_tolerant(text)
             .expect("failed parser")
-            .to_policy_tolerant(ast::PolicyID::from_string("id"));
+            .to_policy_tolerant(
+                ast::PolicyID::from_string("id"),
+                Extensions::all_available(),
+            );
         match result {
             Ok(policy) => {
                 panic!("conversion to AST should have failed, but succeeded with:\n{policy}")
@@ -5828,7 +6170,7 @@ mod tests {
         "#,
         )
         .expect("should parse")
-        .to_policyset_tolerant()
+        .to_policyset_tolerant(Extensions::all_available())
         .unwrap_or_else(|errs| panic!("failed convert to AST:\n{:?}", miette::Report::new(errs)));
         policyset
             .get(&ast::PolicyID::from_string("policy0"))
@@ -5930,7 +6272,7 @@ mod tests {
                 ExpectedErrorMessageBuilder::error(
                     "`?foo` is not a valid template slot",
                 ).help(
-                    "a template slot may only be `?principal` or `?resource`",
+                    "a template slot may only be `?principal`, `?resource`, or a value slot declared in a `template(...) =>` header",
                 ).exactly_one_underline("?foo").build(),
             ),
 
@@ -6219,6 +6561,6 @@ mod tests {
     ) -> Result<ast::Template> {
         let id = id.unwrap_or_else(|| ast::PolicyID::from_string("policy0"));
         let cst = text_to_cst::parse_policy_tolerant(text)?;
-        cst.to_template_tolerant(id)
+        cst.to_template_tolerant(id, Extensions::all_available())
     }
 }
@@ -393,10 +393,32 @@ pub enum ToASTErrorKind {
     #[error(transparent)]
     #[diagnostic(transparent)]
     WrongEntityArgument(#[from] parse_errors::WrongEntityArgument),
-    /// Returned when a policy contains a template slot other than `?principal` or `?resource`
+    /// Returned when a policy contains a template slot other than `?principal`,
+    /// `?resource`, or a declared value slot
     #[error("`{0}` is not a valid template slot")]
-    #[diagnostic(help("a template slot may only be `?principal` or `?resource`"))]
+    #[diagnostic(help(
+        "a template slot may only be `?principal`, `?resource`, or a value slot declared in a `template(...) =>` header"
+    ))]
     InvalidSlot(SmolStr),
+    /// Returned when a template header starts with a keyword other than `template`
+    #[error("invalid template header: {0}")]
+    #[diagnostic(help(
+        "a template header must start with `template`, e.g., `template(?threshold: Long) =>`"
+    ))]
+    InvalidTemplateHeader(cst::Ident),
+    /// Returned when the type of a value slot is not a valid type
+    #[error("`{0}` is not a valid type for a value slot")]
+    #[diagnostic(help("`Set` is the only type which takes a type argument, e.g., `Set<Long>`"))]
+    InvalidSlotType(String),
+    /// Returned when a record type in a template header has the same
+    /// attribute more than once
+    #[error("duplicate attribute `{0}` in record type")]
+    DuplicateSlotTypeAttribute(SmolStr),
+    /// Returned when the value slots declared in a template header don't match
+    /// the value slots used in the template
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ValueSlotDeclaration(#[from] ast::ValueSlotDeclarationError),
     /// Returned when an entity type contains a reserved namespace or typename (as of this writing, just `__cedar`)
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -75,6 +75,14 @@ impl fmt::Display for Policy {
                 write!(f, "{} ", View(anno))?;
             }
         }
+        // then the template header, if any
+        if let Some(header) = &policy.header {
+            if f.alternate() {
+                writeln!(f, "{:#}", View(header))?;
+            } else {
+                write!(f, "{} ", View(header))?;
+            }
+        }
         // main policy body
         if f.alternate() {
             write!(f, "{:#}(", View(&policy.effect))?;
@@ -129,6 +137,58 @@ impl fmt::Display for Annotation {
     }
 }
 
+impl fmt::Display for TemplateHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", View(&self.keyword))?;
+        let mut slots = self.slots.iter();
+        if let Some(s) = slots.next() {
+            write!(f, "{}", View(s))?;
+            for s in slots {
+                write!(f, ", {}", View(s))?;
+            }
+        }
+        write!(f, ") =>")
+    }
+}
+
+impl fmt::Display for SlotDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", View(&self.slot), View(&self.ty))?;
+        if let Some(default) = &self.default {
+            write!(f, " = {}", View(default))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SlotType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotType::Name(name) => write!(f, "{}", View(name)),
+            SlotType::Generic(name, ty) => write!(f, "{}<{}>", View(name), View(ty)),
+            SlotType::Record(attrs) => {
+                let mut attrs = attrs.iter();
+                match attrs.next() {
+                    None => write!(f, "{{}}"),
+                    Some(a) => {
+                        write!(f, "{{ {}", View(a))?;
+                        for a in attrs {
+                            write!(f, ", {}", View(a))?;
+                        }
+                        write!(f, " }}")
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for SlotTypeAttr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", View(&self.key), View(&self.ty))
+    }
+}
+
 impl fmt::Display for VariableDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", View(&self.variable))?;
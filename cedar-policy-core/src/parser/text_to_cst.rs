@@ -1142,7 +1142,7 @@ mod tests {
             &ExpectedErrorMessageBuilder::error("unexpected token `\"this should be invalid\"`")
                 .exactly_one_underline_with_label(
                     "\"this should be invalid\"",
-                    "expected `)` or identifier",
+                    "expected `)`, identifier, `?principal`, or `?resource`",
                 )
                 .build(),
         );
@@ -1210,7 +1210,10 @@ mod tests {
             src,
             &errs,
             &ExpectedErrorMessageBuilder::error("unexpected token `@`")
-                .exactly_one_underline_with_label("@", "expected `)` or identifier")
+                .exactly_one_underline_with_label(
+                    "@",
+                    "expected `)`, identifier, `?principal`, or `?resource`",
+                )
                 .build(),
         );
     }
@@ -153,6 +153,7 @@ mod test {
 
     use super::to_unescaped_string;
     use crate::ast;
+    use crate::extensions::Extensions;
     use crate::parser::err::{ParseError, ToASTErrorKind};
     use crate::parser::text_to_cst;
     use crate::parser::unescape::to_pattern;
@@ -236,7 +237,7 @@ skipped";
         assert!(
             matches!(text_to_cst::parse_expr(r#""aa" like "\t\r\n\\\0\x42\*""#)
             .expect("failed parsing")
-            .to_expr::<ast::ExprBuilder<()>>()
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
             .expect("failed conversion").expr_kind(),
             ast::ExprKind::Like {
                 expr: _,
@@ -250,7 +251,7 @@ skipped";
         // invalid ASCII escapes
         let errs = text_to_cst::parse_expr(r#""abc" like "abc\xFF\xFEdef""#)
             .expect("failed parsing")
-            .to_expr::<ast::ExprBuilder<()>>()
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
             .unwrap_err();
         assert_eq!(errs.len(), 2);
         assert_matches!(&errs[0], ParseError::ToAST(e) => assert_matches!(e.kind(), ToASTErrorKind::Unescape(_)));
@@ -260,7 +261,7 @@ skipped";
         assert!(
             matches!(text_to_cst::parse_expr(r#""aaa" like "👀👀\*🤞🤞\*🤝""#)
             .expect("failed parsing")
-            .to_expr::<ast::ExprBuilder<()>>()
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
             .expect("failed conversion").expr_kind(),
             ast::ExprKind::Like { expr: _, pattern} if pattern.to_string() == *r"👀👀\*🤞🤞\*🤝")
         );
@@ -268,7 +269,7 @@ skipped";
         // invalid escapes
         let errs = text_to_cst::parse_expr(r#""aaa" like "abc\d\bdef""#)
             .expect("failed parsing")
-            .to_expr::<ast::ExprBuilder<()>>()
+            .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
             .unwrap_err();
         assert_eq!(errs.len(), 2);
         assert_matches!(&errs[0], ParseError::ToAST(e) => assert_matches!(e.kind(), ToASTErrorKind::Unescape(_)));
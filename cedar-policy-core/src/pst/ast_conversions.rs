@@ -26,8 +26,8 @@ use crate::ast::{self, UnwrapInfallible};
 use crate::expr_builder;
 use crate::extensions;
 use crate::pst::err::error_body::{
-    InvalidAnnotationError, InvalidEntityTypeError, PolicyMissingLinkIdError, UnsupportedErrorNode,
-    WrongSlotPositionError,
+    InvalidAnnotationError, InvalidEntityTypeError, InvalidExpressionError,
+    PolicyMissingLinkIdError, UnsupportedErrorNode, WrongSlotPositionError,
 };
 use crate::pst::expr::{Id, PstBuilder};
 use std::collections::HashMap;
@@ -310,6 +310,12 @@ impl Expr {
                             vec![inner],
                         )
                         .unwrap_infallible(),
+                    UnaryOp::IpSet => builder
+                        .call_extension_fn(
+                            extensions::ipaddr::names::IPSET_FROM_SET_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
                     UnaryOp::IsIPv4 => builder
                         .call_extension_fn(extensions::ipaddr::names::IS_IPV4.clone(), vec![inner])
                         .unwrap_infallible(),
@@ -370,6 +376,118 @@ impl Expr {
                             vec![inner],
                         )
                         .unwrap_infallible(),
+                    #[cfg(feature = "strings")]
+                    UnaryOp::ToLowerCase => builder
+                        .call_extension_fn(
+                            extensions::strings::constants::TO_LOWER_CASE.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "strings")]
+                    UnaryOp::Length => builder
+                        .call_extension_fn(
+                            extensions::strings::constants::LENGTH.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "bytes")]
+                    UnaryOp::Bytes => builder
+                        .call_extension_fn(
+                            extensions::bytes::constants::BYTES_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "bytes")]
+                    UnaryOp::ByteLength => builder
+                        .call_extension_fn(
+                            extensions::bytes::constants::BYTE_LENGTH.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "lookup")]
+                    UnaryOp::IsPresent => builder
+                        .call_extension_fn(
+                            extensions::lookup::constants::IS_PRESENT.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "lookup")]
+                    UnaryOp::AsString => builder
+                        .call_extension_fn(
+                            extensions::lookup::constants::AS_STRING.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "lookup")]
+                    UnaryOp::AsLong => builder
+                        .call_extension_fn(
+                            extensions::lookup::constants::AS_LONG.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "lookup")]
+                    UnaryOp::AsBool => builder
+                        .call_extension_fn(
+                            extensions::lookup::constants::AS_BOOL.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "geo")]
+                    UnaryOp::Geopoint => builder
+                        .call_extension_fn(
+                            extensions::geo::constants::GEOPOINT_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "geo")]
+                    UnaryOp::Geopolygon => builder
+                        .call_extension_fn(
+                            extensions::geo::constants::GEOPOLYGON_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "identifiers")]
+                    UnaryOp::Uuid => builder
+                        .call_extension_fn(
+                            extensions::identifiers::constants::UUID_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "identifiers")]
+                    UnaryOp::Ulid => builder
+                        .call_extension_fn(
+                            extensions::identifiers::constants::ULID_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "identifiers")]
+                    UnaryOp::UuidVersion => builder
+                        .call_extension_fn(
+                            extensions::identifiers::constants::UUID_VERSION.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "identifiers")]
+                    UnaryOp::TimestampMillis => builder
+                        .call_extension_fn(
+                            extensions::identifiers::constants::TIMESTAMP_MILLIS.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "mediatype")]
+                    UnaryOp::Mediatype => builder
+                        .call_extension_fn(
+                            extensions::mediatype::constants::MEDIATYPE_FROM_STR_NAME.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "mediatype")]
+                    UnaryOp::Essence => builder
+                        .call_extension_fn(
+                            extensions::mediatype::constants::ESSENCE.clone(),
+                            vec![inner],
+                        )
+                        .unwrap_infallible(),
                 }
             }
             Expr::BinaryOp { op, left, right } => {
@@ -394,6 +512,10 @@ impl Expr {
                     BinaryOp::ContainsAny => builder.contains_any(left_ast, right_ast),
                     BinaryOp::GetTag => builder.get_tag(left_ast, right_ast),
                     BinaryOp::HasTag => builder.has_tag(left_ast, right_ast),
+                    #[cfg(feature = "maps")]
+                    BinaryOp::GetKey => builder.get_key(left_ast, right_ast),
+                    #[cfg(feature = "maps")]
+                    BinaryOp::ContainsKey => builder.contains_key(left_ast, right_ast),
                     // Extension-function variants: each maps directly to its
                     // extension name, mirroring BinaryOp::to_name()
                     BinaryOp::IsInRange => builder
@@ -402,6 +524,12 @@ impl Expr {
                             vec![left_ast, right_ast],
                         )
                         .unwrap_infallible(),
+                    BinaryOp::IsInSet => builder
+                        .call_extension_fn(
+                            extensions::ipaddr::names::IS_IN_SET.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
                     BinaryOp::Offset => builder
                         .call_extension_fn(
                             extensions::datetime::constants::OFFSET_METHOD_NAME.clone(),
@@ -414,6 +542,18 @@ impl Expr {
                             vec![left_ast, right_ast],
                         )
                         .unwrap_infallible(),
+                    BinaryOp::DayOfWeek => builder
+                        .call_extension_fn(
+                            extensions::datetime::constants::DAY_OF_WEEK_NAME.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    BinaryOp::Hour => builder
+                        .call_extension_fn(
+                            extensions::datetime::constants::HOUR_NAME.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
                     BinaryOp::DecimalLessThan => builder
                         .call_extension_fn(
                             extensions::decimal::constants::LESS_THAN.clone(),
@@ -438,6 +578,73 @@ impl Expr {
                             vec![left_ast, right_ast],
                         )
                         .unwrap_infallible(),
+                    BinaryOp::DecimalAdd => builder
+                        .call_extension_fn(
+                            extensions::decimal::constants::ADD.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    BinaryOp::DecimalSub => builder
+                        .call_extension_fn(
+                            extensions::decimal::constants::SUBTRACT.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    BinaryOp::DecimalMul => builder
+                        .call_extension_fn(
+                            extensions::decimal::constants::MULTIPLY.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "strings")]
+                    BinaryOp::StartsWith => builder
+                        .call_extension_fn(
+                            extensions::strings::constants::STARTS_WITH.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "strings")]
+                    BinaryOp::EndsWith => builder
+                        .call_extension_fn(
+                            extensions::strings::constants::ENDS_WITH.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "strings")]
+                    BinaryOp::Split => builder
+                        .call_extension_fn(
+                            extensions::strings::constants::SPLIT.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "lookup")]
+                    BinaryOp::Lookup => builder
+                        .call_extension_fn(
+                            extensions::lookup::constants::LOOKUP.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "geo")]
+                    BinaryOp::DistanceTo => builder
+                        .call_extension_fn(
+                            extensions::geo::constants::DISTANCE_TO.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "geo")]
+                    BinaryOp::IsInPolygon => builder
+                        .call_extension_fn(
+                            extensions::geo::constants::IS_IN_POLYGON.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
+                    #[cfg(feature = "mediatype")]
+                    BinaryOp::Matches => builder
+                        .call_extension_fn(
+                            extensions::mediatype::constants::MATCHES.clone(),
+                            vec![left_ast, right_ast],
+                        )
+                        .unwrap_infallible(),
                 }
             }
             Expr::Set(exprs) => {
@@ -497,10 +704,7 @@ impl Expr {
                     )
                     .unwrap()
             }
-            Expr::Unknown { name } => builder.unknown(ast::Unknown {
-                name,
-                type_annotation: None,
-            }),
+            Expr::Unknown { name } => builder.unknown(ast::Unknown::new_untyped(name)),
             #[cfg(feature = "tpe")]
             Expr::ResidualError => builder
                 .call_extension_fn(crate::tpe::residual::ERROR_NAME.clone(), std::iter::empty())
@@ -605,6 +809,7 @@ impl From<ast::SlotId> for SlotId {
         match slot.0 {
             ast::ValidSlotId::Principal => SlotId::Principal,
             ast::ValidSlotId::Resource => SlotId::Resource,
+            ast::ValidSlotId::Value(id) => SlotId::Value(id.into_smolstr()),
         }
     }
 }
@@ -615,6 +820,7 @@ impl From<SlotId> for ast::SlotId {
         match slot {
             SlotId::Principal => ast::SlotId::principal(),
             SlotId::Resource => ast::SlotId::resource(),
+            SlotId::Value(name) => ast::SlotId::named(ast::Id::new_unchecked(name)),
         }
     }
 }
@@ -751,6 +957,12 @@ impl TryFrom<ast::Template> for Template {
     type Error = PstConstructionError;
 
     fn try_from(template: ast::Template) -> Result<Self, PstConstructionError> {
+        if template.value_slot_types().next().is_some() {
+            return Err(InvalidExpressionError::new(
+                "templates with value slots cannot be represented in the PST".to_string(),
+            )
+            .into());
+        }
         let (
             id,
             annot,
@@ -791,6 +1003,13 @@ impl TryFrom<ast::Policy> for Policy {
     type Error = PstConstructionError;
 
     fn try_from(policy: ast::Policy) -> Result<Self, PstConstructionError> {
+        if !policy.value_env().is_empty() {
+            return Err(InvalidExpressionError::new(
+                "policies linked from templates with value slots cannot be represented in the PST"
+                    .to_string(),
+            )
+            .into());
+        }
         let (template, id, values) = policy.into_components();
         let pst_template: Template = Arc::unwrap_or_clone(template).try_into()?;
         if pst_template.is_static() {
@@ -114,7 +114,7 @@ impl PrincipalConstraint {
         match self {
             PrincipalConstraint::Eq(EntityOrSlot::Slot(s))
             | PrincipalConstraint::In(EntityOrSlot::Slot(s))
-            | PrincipalConstraint::IsIn(_, EntityOrSlot::Slot(s)) => Some(*s),
+            | PrincipalConstraint::IsIn(_, EntityOrSlot::Slot(s)) => Some(s.clone()),
             _ => None,
         }
     }
@@ -171,7 +171,7 @@ impl ResourceConstraint {
         match self {
             ResourceConstraint::Eq(EntityOrSlot::Slot(s))
             | ResourceConstraint::In(EntityOrSlot::Slot(s))
-            | ResourceConstraint::IsIn(_, EntityOrSlot::Slot(s)) => Some(*s),
+            | ResourceConstraint::IsIn(_, EntityOrSlot::Slot(s)) => Some(s.clone()),
             _ => None,
         }
     }
@@ -25,6 +25,7 @@ use crate::entities;
 use crate::est;
 use crate::pst::err::error_body;
 use itertools::Itertools;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -269,6 +270,9 @@ impl TryFrom<Template> for est::Policy {
                 .map(|c| c.try_into())
                 .collect::<Result<Vec<_>, _>>()?,
             annotations,
+            // the PST cannot declare value slots
+            value_slots: BTreeMap::new(),
+            value_slot_defaults: BTreeMap::new(),
         })
     }
 }
@@ -699,6 +703,10 @@ mod tests {
             ("containsAny", BinaryOp::ContainsAny),
             ("getTag", BinaryOp::GetTag),
             ("hasTag", BinaryOp::HasTag),
+            #[cfg(feature = "maps")]
+            ("get", BinaryOp::GetKey),
+            #[cfg(feature = "maps")]
+            ("containsKey", BinaryOp::ContainsKey),
         ];
 
         for (op_str, expected_op) in test_cases {
@@ -116,18 +116,21 @@ impl From<ast::Id> for Id {
 /// ```
 ///
 /// This enum is `#[non_exhaustive]`; match arms must include a wildcard.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 #[non_exhaustive]
 pub enum SlotId {
     /// `?principal` slot
     Principal,
     /// `?resource` slot
     Resource,
+    /// Typed value slot, e.g., `?threshold`. The name excludes the leading
+    /// `?`.
+    Value(SmolStr),
 }
 
 impl Display for SlotId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let b: ast::SlotId = (*self).into();
+        let b: ast::SlotId = self.clone().into();
         write!(f, "{}", b)
     }
 }
@@ -279,6 +282,7 @@ pub enum Var {
 /// // Extension constructors
 /// decimal("1.23")             // Decimal
 /// ip("10.0.0.1")              // Ip
+/// ipset(["10.0.0.0/8"])       // IpSet
 /// datetime("2024-01-01")      // Datetime
 /// duration("1h30m")           // Duration
 ///
@@ -296,6 +300,34 @@ pub enum Var {
 /// duration("1h30m").toMinutes()             // ToMinutes
 /// duration("1h30m").toHours()               // ToHours
 /// duration("30d").toDays()                  // ToDays
+///
+/// // Strings extension methods (`strings` feature)
+/// "Photo.JPG".toLowerCase()   // ToLowerCase
+/// "photo.jpg".length()        // Length
+///
+/// // Bytes extension (`bytes` feature)
+/// bytes("3q2+7w==")               // Bytes
+/// bytes("3q2+7w==").byteLength()  // ByteLength
+///
+/// // Lookup extension (`lookup` feature)
+/// context.config.lookup("limits.daily").isPresent() // IsPresent
+/// context.config.lookup("region").asString()        // AsString
+/// context.config.lookup("limits.daily").asLong()    // AsLong
+/// context.config.lookup("enabled").asBool()         // AsBool
+///
+/// // Geo extension (`geo` feature)
+/// geopoint("47.6062,-122.3321")              // Geopoint
+/// geopolygon("0,0; 0,10; 10,10; 10,0")       // Geopolygon
+///
+/// // Identifiers extension (`identifiers` feature)
+/// uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")   // Uuid
+/// ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV")             // Ulid
+/// resource.id.uuidVersion()                      // UuidVersion
+/// resource.id.timestampMillis()                  // TimestampMillis
+///
+/// // Mediatype extension (`mediatype` feature)
+/// mediatype("image/png")                         // Mediatype
+/// resource.contentType.essence()                 // Essence
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
@@ -314,6 +346,8 @@ pub enum UnaryOp {
     Duration,
     /// `ip("...")`
     Ip,
+    /// `ipset([...])`
+    IpSet,
     /// `expr.isIpv4()`
     IsIPv4,
     /// `expr.isIpv6()`
@@ -336,6 +370,54 @@ pub enum UnaryOp {
     ToHours,
     /// `expr.toDays()`
     ToDays,
+    /// `expr.toLowerCase()`
+    #[cfg(feature = "strings")]
+    ToLowerCase,
+    /// `expr.length()`
+    #[cfg(feature = "strings")]
+    Length,
+    /// `bytes("...")`
+    #[cfg(feature = "bytes")]
+    Bytes,
+    /// `expr.byteLength()`
+    #[cfg(feature = "bytes")]
+    ByteLength,
+    /// `expr.isPresent()`
+    #[cfg(feature = "lookup")]
+    IsPresent,
+    /// `expr.asString()`
+    #[cfg(feature = "lookup")]
+    AsString,
+    /// `expr.asLong()`
+    #[cfg(feature = "lookup")]
+    AsLong,
+    /// `expr.asBool()`
+    #[cfg(feature = "lookup")]
+    AsBool,
+    /// `geopoint("...")`
+    #[cfg(feature = "geo")]
+    Geopoint,
+    /// `geopolygon("...")`
+    #[cfg(feature = "geo")]
+    Geopolygon,
+    /// `uuid("...")`
+    #[cfg(feature = "identifiers")]
+    Uuid,
+    /// `ulid("...")`
+    #[cfg(feature = "identifiers")]
+    Ulid,
+    /// `expr.uuidVersion()`
+    #[cfg(feature = "identifiers")]
+    UuidVersion,
+    /// `expr.timestampMillis()`
+    #[cfg(feature = "identifiers")]
+    TimestampMillis,
+    /// `mediatype("...")`
+    #[cfg(feature = "mediatype")]
+    Mediatype,
+    /// `expr.essence()`
+    #[cfg(feature = "mediatype")]
+    Essence,
 }
 
 impl UnaryOp {
@@ -349,6 +431,7 @@ impl UnaryOp {
             UnaryOp::Decimal => Some(&extensions::decimal::constants::DECIMAL_FROM_STR_NAME),
             UnaryOp::Duration => Some(&extensions::datetime::constants::DURATION_CONSTRUCTOR_NAME),
             UnaryOp::Ip => Some(&extensions::ipaddr::names::IP_FROM_STR_NAME),
+            UnaryOp::IpSet => Some(&extensions::ipaddr::names::IPSET_FROM_SET_NAME),
             UnaryOp::IsIPv4 => Some(&extensions::ipaddr::names::IS_IPV4),
             UnaryOp::IsIPV6 => Some(&extensions::ipaddr::names::IS_IPV6),
             UnaryOp::IsLoopback => Some(&extensions::ipaddr::names::IS_LOOPBACK),
@@ -360,6 +443,38 @@ impl UnaryOp {
             UnaryOp::ToMinutes => Some(&extensions::datetime::constants::TO_MINUTES_NAME),
             UnaryOp::ToHours => Some(&extensions::datetime::constants::TO_HOURS_NAME),
             UnaryOp::ToDays => Some(&extensions::datetime::constants::TO_DAYS_NAME),
+            #[cfg(feature = "strings")]
+            UnaryOp::ToLowerCase => Some(&extensions::strings::constants::TO_LOWER_CASE),
+            #[cfg(feature = "strings")]
+            UnaryOp::Length => Some(&extensions::strings::constants::LENGTH),
+            #[cfg(feature = "bytes")]
+            UnaryOp::Bytes => Some(&extensions::bytes::constants::BYTES_FROM_STR_NAME),
+            #[cfg(feature = "bytes")]
+            UnaryOp::ByteLength => Some(&extensions::bytes::constants::BYTE_LENGTH),
+            #[cfg(feature = "lookup")]
+            UnaryOp::IsPresent => Some(&extensions::lookup::constants::IS_PRESENT),
+            #[cfg(feature = "lookup")]
+            UnaryOp::AsString => Some(&extensions::lookup::constants::AS_STRING),
+            #[cfg(feature = "lookup")]
+            UnaryOp::AsLong => Some(&extensions::lookup::constants::AS_LONG),
+            #[cfg(feature = "lookup")]
+            UnaryOp::AsBool => Some(&extensions::lookup::constants::AS_BOOL),
+            #[cfg(feature = "geo")]
+            UnaryOp::Geopoint => Some(&extensions::geo::constants::GEOPOINT_FROM_STR_NAME),
+            #[cfg(feature = "geo")]
+            UnaryOp::Geopolygon => Some(&extensions::geo::constants::GEOPOLYGON_FROM_STR_NAME),
+            #[cfg(feature = "identifiers")]
+            UnaryOp::Uuid => Some(&extensions::identifiers::constants::UUID_FROM_STR_NAME),
+            #[cfg(feature = "identifiers")]
+            UnaryOp::Ulid => Some(&extensions::identifiers::constants::ULID_FROM_STR_NAME),
+            #[cfg(feature = "identifiers")]
+            UnaryOp::UuidVersion => Some(&extensions::identifiers::constants::UUID_VERSION),
+            #[cfg(feature = "identifiers")]
+            UnaryOp::TimestampMillis => Some(&extensions::identifiers::constants::TIMESTAMP_MILLIS),
+            #[cfg(feature = "mediatype")]
+            UnaryOp::Mediatype => Some(&extensions::mediatype::constants::MEDIATYPE_FROM_STR_NAME),
+            #[cfg(feature = "mediatype")]
+            UnaryOp::Essence => Some(&extensions::mediatype::constants::ESSENCE),
         }
     }
 
@@ -370,6 +485,7 @@ impl UnaryOp {
             "datetime" => Some(UnaryOp::Datetime),
             "duration" => Some(UnaryOp::Duration),
             "ip" => Some(UnaryOp::Ip),
+            "ipset" => Some(UnaryOp::IpSet),
             "isIpv4" => Some(UnaryOp::IsIPv4),
             "isIpv6" => Some(UnaryOp::IsIPV6),
             "isLoopback" => Some(UnaryOp::IsLoopback),
@@ -381,6 +497,38 @@ impl UnaryOp {
             "toMinutes" => Some(UnaryOp::ToMinutes),
             "toHours" => Some(UnaryOp::ToHours),
             "toDays" => Some(UnaryOp::ToDays),
+            #[cfg(feature = "strings")]
+            "toLowerCase" => Some(UnaryOp::ToLowerCase),
+            #[cfg(feature = "strings")]
+            "length" => Some(UnaryOp::Length),
+            #[cfg(feature = "bytes")]
+            "bytes" => Some(UnaryOp::Bytes),
+            #[cfg(feature = "bytes")]
+            "byteLength" => Some(UnaryOp::ByteLength),
+            #[cfg(feature = "lookup")]
+            "isPresent" => Some(UnaryOp::IsPresent),
+            #[cfg(feature = "lookup")]
+            "asString" => Some(UnaryOp::AsString),
+            #[cfg(feature = "lookup")]
+            "asLong" => Some(UnaryOp::AsLong),
+            #[cfg(feature = "lookup")]
+            "asBool" => Some(UnaryOp::AsBool),
+            #[cfg(feature = "geo")]
+            "geopoint" => Some(UnaryOp::Geopoint),
+            #[cfg(feature = "geo")]
+            "geopolygon" => Some(UnaryOp::Geopolygon),
+            #[cfg(feature = "identifiers")]
+            "uuid" => Some(UnaryOp::Uuid),
+            #[cfg(feature = "identifiers")]
+            "ulid" => Some(UnaryOp::Ulid),
+            #[cfg(feature = "identifiers")]
+            "uuidVersion" => Some(UnaryOp::UuidVersion),
+            #[cfg(feature = "identifiers")]
+            "timestampMillis" => Some(UnaryOp::TimestampMillis),
+            #[cfg(feature = "mediatype")]
+            "mediatype" => Some(UnaryOp::Mediatype),
+            #[cfg(feature = "mediatype")]
+            "essence" => Some(UnaryOp::Essence),
             _ => None,
         }
     }
@@ -435,12 +583,39 @@ impl Display for UnaryOp {
 /// resource.hasTag("env")              // HasTag
 /// resource.getTag("env")              // GetTag
 ///
+/// // Maps
+/// context.headers.containsKey("host") // ContainsKey
+/// context.headers.get("host")         // GetKey
+///
 /// // IP extension
 /// ip("10.0.0.1").isInRange(ip("10.0.0.0/24"))  // IsInRange
+/// ip("10.0.0.1").isInSet(ipset(["10.0.0.0/24"])) // IsInSet
 ///
 /// // Datetime extension
 /// datetime("2024-01-01").offset(duration("1d")) // Offset
 /// datetime("2024-01-02").durationSince(datetime("2024-01-01")) // DurationSince
+/// datetime("2024-01-01").dayOfWeek(duration("-5h")) // DayOfWeek
+/// datetime("2024-01-01").hour(duration("-5h"))      // Hour
+///
+/// // Strings extension (`strings` feature)
+/// "photos/a.jpg".startsWith("photos/") // StartsWith
+/// "photos/a.jpg".endsWith(".jpg")      // EndsWith
+/// "a,b".split(",")                     // Split
+///
+/// // Lookup extension (`lookup` feature)
+/// context.config.lookup(principal.limitPath) // Lookup
+///
+/// // Geo extension (`geo` feature)
+/// context.location.distanceTo(geopoint("47.6,-122.3"))          // DistanceTo
+/// context.location.isInPolygon(geopolygon("0,0; 0,1; 1,1"))     // IsInPolygon
+///
+/// // Mediatype extension (`mediatype` feature)
+/// resource.contentType.matches("image/*") // Matches
+///
+/// // Decimal extension
+/// decimal("1.5").add(decimal("0.25"))      // DecimalAdd
+/// decimal("1.5").subtract(decimal("0.25")) // DecimalSub
+/// decimal("1.5").multiply(decimal("0.25")) // DecimalMul
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
@@ -479,12 +654,24 @@ pub enum BinaryOp {
     GetTag,
     /// `left.hasTag(right)`
     HasTag,
+    /// `left.get(right)`
+    #[cfg(feature = "maps")]
+    GetKey,
+    /// `left.containsKey(right)`
+    #[cfg(feature = "maps")]
+    ContainsKey,
     /// `left.isInRange(right)`
     IsInRange,
+    /// `left.isInSet(right)`
+    IsInSet,
     /// `left.offset(right)`
     Offset,
     /// `left.durationSince(right)`
     DurationSince,
+    /// `left.dayOfWeek(right)`
+    DayOfWeek,
+    /// `left.hour(right)`
+    Hour,
     /// `left.lessThan(right)` (decimal less than)
     DecimalLessThan,
     /// `left.lessThanOrEqual(right)` (decimal less than or equal)
@@ -493,6 +680,33 @@ pub enum BinaryOp {
     DecimalGreater,
     /// `left.greaterThanOrEqual(right)` (decimal greater than or equal)
     DecimalGreaterEq,
+    /// `left.add(right)` (decimal addition)
+    DecimalAdd,
+    /// `left.subtract(right)` (decimal subtraction)
+    DecimalSub,
+    /// `left.multiply(right)` (decimal multiplication)
+    DecimalMul,
+    /// `left.startsWith(right)`
+    #[cfg(feature = "strings")]
+    StartsWith,
+    /// `left.endsWith(right)`
+    #[cfg(feature = "strings")]
+    EndsWith,
+    /// `left.split(right)`
+    #[cfg(feature = "strings")]
+    Split,
+    /// `left.lookup(right)`
+    #[cfg(feature = "lookup")]
+    Lookup,
+    /// `left.distanceTo(right)`
+    #[cfg(feature = "geo")]
+    DistanceTo,
+    /// `left.isInPolygon(right)`
+    #[cfg(feature = "geo")]
+    IsInPolygon,
+    /// `left.matches(right)`
+    #[cfg(feature = "mediatype")]
+    Matches,
 }
 
 impl BinaryOp {
@@ -500,14 +714,34 @@ impl BinaryOp {
         use crate::extensions;
         match self {
             BinaryOp::IsInRange => Some(&extensions::ipaddr::names::IS_IN_RANGE),
+            BinaryOp::IsInSet => Some(&extensions::ipaddr::names::IS_IN_SET),
             BinaryOp::Offset => Some(&extensions::datetime::constants::OFFSET_METHOD_NAME),
             BinaryOp::DurationSince => Some(&extensions::datetime::constants::DURATION_SINCE_NAME),
+            BinaryOp::DayOfWeek => Some(&extensions::datetime::constants::DAY_OF_WEEK_NAME),
+            BinaryOp::Hour => Some(&extensions::datetime::constants::HOUR_NAME),
             BinaryOp::DecimalLessThan => Some(&extensions::decimal::constants::LESS_THAN),
             BinaryOp::DecimalLessEq => Some(&extensions::decimal::constants::LESS_THAN_OR_EQUAL),
             BinaryOp::DecimalGreater => Some(&extensions::decimal::constants::GREATER_THAN),
             BinaryOp::DecimalGreaterEq => {
                 Some(&extensions::decimal::constants::GREATER_THAN_OR_EQUAL)
             }
+            BinaryOp::DecimalAdd => Some(&extensions::decimal::constants::ADD),
+            BinaryOp::DecimalSub => Some(&extensions::decimal::constants::SUBTRACT),
+            BinaryOp::DecimalMul => Some(&extensions::decimal::constants::MULTIPLY),
+            #[cfg(feature = "strings")]
+            BinaryOp::StartsWith => Some(&extensions::strings::constants::STARTS_WITH),
+            #[cfg(feature = "strings")]
+            BinaryOp::EndsWith => Some(&extensions::strings::constants::ENDS_WITH),
+            #[cfg(feature = "strings")]
+            BinaryOp::Split => Some(&extensions::strings::constants::SPLIT),
+            #[cfg(feature = "lookup")]
+            BinaryOp::Lookup => Some(&extensions::lookup::constants::LOOKUP),
+            #[cfg(feature = "geo")]
+            BinaryOp::DistanceTo => Some(&extensions::geo::constants::DISTANCE_TO),
+            #[cfg(feature = "geo")]
+            BinaryOp::IsInPolygon => Some(&extensions::geo::constants::IS_IN_POLYGON),
+            #[cfg(feature = "mediatype")]
+            BinaryOp::Matches => Some(&extensions::mediatype::constants::MATCHES),
             // those are operators, not names
             BinaryOp::Eq
             | BinaryOp::NotEq
@@ -526,6 +760,8 @@ impl BinaryOp {
             | BinaryOp::ContainsAny
             | BinaryOp::GetTag
             | BinaryOp::HasTag => None,
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey | BinaryOp::ContainsKey => None,
         }
     }
 
@@ -536,9 +772,29 @@ impl BinaryOp {
             "lessThanOrEqual" => Some(BinaryOp::DecimalLessEq),
             "greaterThan" => Some(BinaryOp::DecimalGreater),
             "greaterThanOrEqual" => Some(BinaryOp::DecimalGreaterEq),
+            "add" => Some(BinaryOp::DecimalAdd),
+            "subtract" => Some(BinaryOp::DecimalSub),
+            "multiply" => Some(BinaryOp::DecimalMul),
+            #[cfg(feature = "strings")]
+            "startsWith" => Some(BinaryOp::StartsWith),
+            #[cfg(feature = "strings")]
+            "endsWith" => Some(BinaryOp::EndsWith),
+            #[cfg(feature = "strings")]
+            "split" => Some(BinaryOp::Split),
+            #[cfg(feature = "lookup")]
+            "lookup" => Some(BinaryOp::Lookup),
+            #[cfg(feature = "geo")]
+            "distanceTo" => Some(BinaryOp::DistanceTo),
+            #[cfg(feature = "geo")]
+            "isInPolygon" => Some(BinaryOp::IsInPolygon),
+            #[cfg(feature = "mediatype")]
+            "matches" => Some(BinaryOp::Matches),
             "isInRange" => Some(BinaryOp::IsInRange),
+            "isInSet" => Some(BinaryOp::IsInSet),
             "offset" => Some(BinaryOp::Offset),
             "durationSince" => Some(BinaryOp::DurationSince),
+            "dayOfWeek" => Some(BinaryOp::DayOfWeek),
+            "hour" => Some(BinaryOp::Hour),
             _ => None,
         }
     }
@@ -564,6 +820,10 @@ impl Display for BinaryOp {
             BinaryOp::ContainsAny => write!(f, "{}", ast::BinaryOp::ContainsAny),
             BinaryOp::GetTag => write!(f, "{}", ast::BinaryOp::GetTag),
             BinaryOp::HasTag => write!(f, "{}", ast::BinaryOp::HasTag),
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey => write!(f, "{}", ast::BinaryOp::GetKey),
+            #[cfg(feature = "maps")]
+            BinaryOp::ContainsKey => write!(f, "{}", ast::BinaryOp::ContainsKey),
             // Extension functions - use their name
             _ => match self.to_name() {
                 Some(name) => write!(f, "{}", name),
@@ -892,10 +1152,10 @@ impl Expr {
     pub fn slots(&self) -> HashSet<SlotId> {
         self.reduce::<HashSet<SlotId>>(
             &|e| match e {
-                Expr::Slot(id) => Some(HashSet::from([*id])),
+                Expr::Slot(id) => Some(HashSet::from([id.clone()])),
                 _ => None,
             },
-            &|a, b| a.union(&b).copied().collect(),
+            &|a, b| a.union(&b).cloned().collect(),
             HashSet::new(),
         )
     }
@@ -1129,6 +1389,24 @@ impl ExprBuilder for PstBuilder {
         }
     }
 
+    #[cfg(feature = "maps")]
+    fn get_key(self, expr: Expr, key: Expr) -> Expr {
+        Expr::BinaryOp {
+            op: BinaryOp::GetKey,
+            left: Arc::new(expr),
+            right: Arc::new(key),
+        }
+    }
+
+    #[cfg(feature = "maps")]
+    fn contains_key(self, expr: Expr, key: Expr) -> Expr {
+        Expr::BinaryOp {
+            op: BinaryOp::ContainsKey,
+            left: Arc::new(expr),
+            right: Arc::new(key),
+        }
+    }
+
     fn set(self, exprs: impl IntoIterator<Item = Expr>) -> Expr {
         Expr::Set(exprs.into_iter().map(Arc::new).collect())
     }
@@ -1726,6 +2004,17 @@ mod tests {
                     builder().get_tag(builder().var(ast::Var::Action), builder().val("tag")),
                     "action.getTag(\"tag\")",
                 ),
+                // Maps
+                #[cfg(feature = "maps")]
+                (
+                    builder().contains_key(builder().var(ast::Var::Context), builder().val("k")),
+                    "context.containsKey(\"k\")",
+                ),
+                #[cfg(feature = "maps")]
+                (
+                    builder().get_key(builder().var(ast::Var::Context), builder().val("k")),
+                    "context.get(\"k\")",
+                ),
                 // Like
                 (
                     builder().like(
@@ -1799,6 +2088,7 @@ mod tests {
                 UnaryOp::Decimal,
                 UnaryOp::Duration,
                 UnaryOp::Ip,
+                UnaryOp::IpSet,
                 UnaryOp::IsIPv4,
                 UnaryOp::IsIPV6,
                 UnaryOp::IsLoopback,
@@ -1810,6 +2100,38 @@ mod tests {
                 UnaryOp::ToMinutes,
                 UnaryOp::ToHours,
                 UnaryOp::ToDays,
+                #[cfg(feature = "strings")]
+                UnaryOp::ToLowerCase,
+                #[cfg(feature = "strings")]
+                UnaryOp::Length,
+                #[cfg(feature = "bytes")]
+                UnaryOp::Bytes,
+                #[cfg(feature = "bytes")]
+                UnaryOp::ByteLength,
+                #[cfg(feature = "lookup")]
+                UnaryOp::IsPresent,
+                #[cfg(feature = "lookup")]
+                UnaryOp::AsString,
+                #[cfg(feature = "lookup")]
+                UnaryOp::AsLong,
+                #[cfg(feature = "lookup")]
+                UnaryOp::AsBool,
+                #[cfg(feature = "geo")]
+                UnaryOp::Geopoint,
+                #[cfg(feature = "geo")]
+                UnaryOp::Geopolygon,
+                #[cfg(feature = "identifiers")]
+                UnaryOp::Uuid,
+                #[cfg(feature = "identifiers")]
+                UnaryOp::Ulid,
+                #[cfg(feature = "identifiers")]
+                UnaryOp::UuidVersion,
+                #[cfg(feature = "identifiers")]
+                UnaryOp::TimestampMillis,
+                #[cfg(feature = "mediatype")]
+                UnaryOp::Mediatype,
+                #[cfg(feature = "mediatype")]
+                UnaryOp::Essence,
             ];
 
             for op in ops {
@@ -1843,9 +2165,33 @@ mod tests {
                 BinaryOp::ContainsAny,
                 BinaryOp::GetTag,
                 BinaryOp::HasTag,
+                #[cfg(feature = "maps")]
+                BinaryOp::GetKey,
+                #[cfg(feature = "maps")]
+                BinaryOp::ContainsKey,
                 BinaryOp::IsInRange,
+                BinaryOp::IsInSet,
                 BinaryOp::Offset,
                 BinaryOp::DurationSince,
+                BinaryOp::DayOfWeek,
+                BinaryOp::Hour,
+                BinaryOp::DecimalAdd,
+                BinaryOp::DecimalSub,
+                BinaryOp::DecimalMul,
+                #[cfg(feature = "strings")]
+                BinaryOp::StartsWith,
+                #[cfg(feature = "strings")]
+                BinaryOp::EndsWith,
+                #[cfg(feature = "strings")]
+                BinaryOp::Split,
+                #[cfg(feature = "lookup")]
+                BinaryOp::Lookup,
+                #[cfg(feature = "geo")]
+                BinaryOp::DistanceTo,
+                #[cfg(feature = "geo")]
+                BinaryOp::IsInPolygon,
+                #[cfg(feature = "mediatype")]
+                BinaryOp::Matches,
             ];
 
             for op in ops {
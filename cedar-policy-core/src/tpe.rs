@@ -43,7 +43,7 @@ pub(crate) fn policy_residual_map<'a>(
     let mut residuals = HashMap::new();
     let tc = Typechecker::new(schema, crate::validator::ValidationMode::Strict);
     let env = request.find_request_env(schema)?;
-    for p in ps.policies() {
+    for p in ps.enabled_policies() {
         let t = p.template();
 
         let errs: Vec<_> = Validator::validate_entity_types_and_literals(schema, t).collect();
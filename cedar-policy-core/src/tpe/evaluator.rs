@@ -20,14 +20,15 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use crate::{
     ast::{self, BinaryOp, EntityUID, PartialValue, Set, Value, ValueKind, Var},
-    evaluator::stack_size_check,
+    evaluator::{eval_extension_args, stack_size_check},
     extensions::Extensions,
 };
+use itertools::Either;
 
 use crate::{
     tpe::entities::PartialEntities,
     tpe::request::PartialRequest,
-    tpe::residual::{Residual, ResidualKind},
+    tpe::residual::{fold_binary_app, Residual, ResidualKind},
 };
 
 /// The partial evaluator
@@ -61,7 +62,7 @@ impl Evaluator<'_> {
             ty: r.ty().clone(),
         };
         let mk_concrete = |v: Value| Residual::Concrete {
-            value: normalize_ext_value(v),
+            value: v.normalize_ext_values(),
             ty: r.ty().clone(),
         };
 
@@ -304,24 +305,6 @@ impl Evaluator<'_> {
                         Residual::Concrete { value: v1, .. },
                         Residual::Concrete { value: v2, .. },
                     ) => match op {
-                        BinaryOp::Eq | BinaryOp::Less | BinaryOp::LessEq => {
-                            if let Ok(v) =
-                                crate::evaluator::binary_relation(*op, v1, v2, self.extensions)
-                            {
-                                mk_concrete(v)
-                            } else {
-                                mk_error()
-                            }
-                        }
-                        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
-                            if let Ok(v) =
-                                crate::evaluator::binary_arith(*op, v1.clone(), v2.clone(), None)
-                            {
-                                mk_concrete(v)
-                            } else {
-                                mk_error()
-                            }
-                        }
                         BinaryOp::In => {
                             if let Ok(uid1) = v1.get_as_entity() {
                                 if let Ok(uid2) = v2.get_as_entity() {
@@ -407,22 +390,27 @@ impl Evaluator<'_> {
                                 mk_error()
                             }
                         }
-                        BinaryOp::Contains => match &v1.value {
-                            ValueKind::Set(s) => mk_concrete(s.contains(v2).into()),
-                            _ => mk_error(),
-                        },
-                        BinaryOp::ContainsAll => match (v1.get_as_set(), v2.get_as_set()) {
-                            (Ok(arg1_set), Ok(arg2_set)) => {
-                                mk_concrete(arg2_set.is_subset(arg1_set).into())
+                        BinaryOp::Eq
+                        | BinaryOp::Less
+                        | BinaryOp::LessEq
+                        | BinaryOp::Add
+                        | BinaryOp::Sub
+                        | BinaryOp::Mul
+                        | BinaryOp::Contains
+                        | BinaryOp::ContainsAll
+                        | BinaryOp::ContainsAny => {
+                            match fold_binary_app(*op, v1, v2, self.extensions) {
+                                Some(Ok(v)) => mk_concrete(v),
+                                _ => mk_error(),
                             }
-                            _ => mk_error(),
-                        },
-                        BinaryOp::ContainsAny => match (v1.get_as_set(), v2.get_as_set()) {
-                            (Ok(arg1_set), Ok(arg2_set)) => {
-                                mk_concrete((!arg1_set.is_disjoint(arg2_set)).into())
+                        }
+                        #[cfg(feature = "maps")]
+                        BinaryOp::GetKey | BinaryOp::ContainsKey => {
+                            match fold_binary_app(*op, v1, v2, self.extensions) {
+                                Some(Ok(v)) => mk_concrete(v),
+                                _ => mk_error(),
                             }
-                            _ => mk_error(),
-                        },
+                        }
                     },
                     (Residual::Error(_), _) => mk_error(),
                     (_, Residual::Error(_)) => mk_error(),
@@ -430,7 +418,16 @@ impl Evaluator<'_> {
                 }
             }
             ResidualKind::ExtensionFunctionApp { fn_name, args } => {
-                let args = args.iter().map(|a| self.interpret(a)).collect::<Vec<_>>();
+                let args = match eval_extension_args(
+                    self.extensions.func(fn_name).ok(),
+                    args.iter(),
+                    |a| Ok(self.interpret(a)),
+                    |r| Value::try_from(r.clone()).ok(),
+                ) {
+                    Ok(Either::Left(args)) => args,
+                    Ok(Either::Right(value)) => return mk_concrete(value.normalize_ext_values()),
+                    Err(_) => return mk_error(),
+                };
                 // If the arguments are all concrete values, we proceed to
                 // evaluate the function call
                 if let Ok(vals) = args
@@ -443,7 +440,7 @@ impl Evaluator<'_> {
                     // `Residual::Error` of appropriate types
                     if let Ok(ext_fn) = self.extensions.func(fn_name) {
                         if let Ok(PartialValue::Value(value)) = ext_fn.call(&vals) {
-                            return mk_concrete(normalize_ext_value(value));
+                            return mk_concrete(value.normalize_ext_values());
                         }
                     }
                     mk_error()
@@ -604,89 +601,6 @@ impl Evaluator<'_> {
     }
 }
 
-/// If the value is an extension value whose type provides a [`canonical_repr`],
-/// rebuild the [`RepresentableExtensionValue`] so that the stored `func`/`args`
-/// match the canonical form.  This ensures TPE residuals are deterministic
-/// regardless of which constructor originally created the value.
-fn normalize_ext_value(value: Value) -> Value {
-    normalize_ext_value_inner(&value).unwrap_or(value)
-}
-
-/// Returns `Some(normalized)` if the value needed normalization, `None` if it was already fine.
-fn normalize_ext_value_inner(value: &Value) -> Option<Value> {
-    match &value.value {
-        ValueKind::Lit(_) => None,
-        ValueKind::ExtensionValue(ev) => {
-            let (func, args) = ev.value().canonical_repr()?;
-            Some(Value {
-                value: ValueKind::ExtensionValue(Arc::new(ast::RepresentableExtensionValue::new(
-                    ev.value.clone(),
-                    func,
-                    args,
-                ))),
-                loc: value.loc.clone(),
-            })
-        }
-        ValueKind::Set(s) if s.fast.is_some() => {
-            // due to invariant on set, this means all elements are literals, hence nothing to norm
-            None
-        }
-
-        // The Set and Record normalization attempt to avoid cloning by scanning whether
-        // normalization is needed. Cloning to get the normalization only happens when it is
-        // actually required.
-        ValueKind::Set(s) => {
-            // Find the first element that needs normalization or return None.
-            let (idx, normalized) = s
-                .iter()
-                .enumerate()
-                .find_map(|(i, x)| normalize_ext_value_inner(x).map(|n| (i, n)))?;
-            // Clone elements before `idx` as-is, insert the normalized one,
-            // then normalize the rest.
-            let vals: Vec<Value> = s
-                .iter()
-                .take(idx)
-                .cloned()
-                .chain(std::iter::once(normalized))
-                .chain(
-                    s.iter()
-                        .skip(idx + 1)
-                        .map(|v| normalize_ext_value_inner(v).unwrap_or_else(|| v.clone())),
-                )
-                .collect();
-            Some(Value {
-                value: ValueKind::Set(Set::new(vals)),
-                loc: value.loc.clone(),
-            })
-        }
-        ValueKind::Record(r) => {
-            let mut iter = r.iter().enumerate();
-            let (idx, key, normalized) = loop {
-                let (i, (k, v)) = iter.next()?;
-                if let Some(n) = normalize_ext_value_inner(v) {
-                    break (i, k.clone(), n);
-                }
-            };
-            let map: BTreeMap<_, _> = r
-                .iter()
-                .take(idx)
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .chain(std::iter::once((key, normalized)))
-                .chain(r.iter().skip(idx + 1).map(|(k, v)| {
-                    (
-                        k.clone(),
-                        normalize_ext_value_inner(v).unwrap_or_else(|| v.clone()),
-                    )
-                }))
-                .collect();
-            Some(Value {
-                value: ValueKind::Record(Arc::new(map)),
-                loc: value.loc.clone(),
-            })
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::collections::{BTreeMap, HashSet};
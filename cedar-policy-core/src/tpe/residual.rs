@@ -20,9 +20,11 @@ use std::collections::HashSet;
 use std::{collections::BTreeMap, sync::Arc, sync::LazyLock};
 
 use crate::ast::{
-    Annotations, Effect, EntityUID, Literal, Policy, PolicyID, SlotEnv, UnwrapInfallible, ValueKind,
+    Annotations, Effect, EntityUID, Literal, PartialValue, Policy, PolicyID, SlotEnv,
+    UnwrapInfallible, ValueKind,
 };
-use crate::evaluator::evaluation_errors;
+use crate::evaluator::{eval_extension_args, evaluation_errors};
+use crate::extensions::Extensions;
 #[cfg(feature = "tolerant-ast")]
 use crate::tpe::err::ErrorNotSupportedError;
 use crate::tpe::err::{ExprToResidualError, MissingTypeAnnotationError, UnknownNotSupportedError};
@@ -31,6 +33,7 @@ use crate::{
     ast::{self, BinaryOp, EntityType, Expr, Name, Pattern, UnaryOp, Value, Var},
     expr_builder::ExprBuilder,
 };
+use itertools::Either;
 use smol_str::SmolStr;
 
 /// The [`Name`] used to represent error nodes in the AST when converting from
@@ -128,6 +131,10 @@ impl Residual {
                     // <entityUID>.getTag possibly errors during reauthorization if <entityUID> does not exist in the entity store
                     ast::BinaryOp::GetTag => true,
 
+                    // <map>.get errors if the map does not contain the key
+                    #[cfg(feature = "maps")]
+                    ast::BinaryOp::GetKey => true,
+
                     // Other binary operations follow the general rule. They are all enumerated here for clarity, although
                     // a _ case could be used.
                     ast::BinaryOp::Contains
@@ -141,6 +148,11 @@ impl Residual {
                         arg1.can_error_assuming_well_formed()
                             || arg2.can_error_assuming_well_formed()
                     }
+                    #[cfg(feature = "maps")]
+                    ast::BinaryOp::ContainsKey => {
+                        arg1.can_error_assuming_well_formed()
+                            || arg2.can_error_assuming_well_formed()
+                    }
                 },
 
                 // Extension function invocations can error at runtime.
@@ -273,7 +285,7 @@ impl Residual {
                 None => {
                     // Any slot not bound in the env is an error now rather than waiting for evaluation
                     return Err(evaluation_errors::UnlinkedSlotError {
-                        slot: *slot,
+                        slot: slot.clone(),
                         source_loc: expr.source_loc().cloned(),
                     }
                     .into());
@@ -478,6 +490,403 @@ impl Residual {
     }
 }
 
+impl Residual {
+    /// Simplify this residual without a request or entities, by folding
+    /// operations whose operands are all concrete (including extension
+    /// function calls) and removing trivial structure such as `true && x`,
+    /// `x || false`, `if true then a else b`, and `!!x`.
+    ///
+    /// The simplified residual evaluates to the same value as this residual
+    /// for every request and entity store, and errors whenever this residual
+    /// errors. Operations which need the entity store (`in`, tags, and
+    /// attributes of entities) are only folded when they don't look up an
+    /// entity, e.g., `User::"alice" in User::"alice"`.
+    pub fn simplify(&self, extensions: &Extensions<'_>) -> Residual {
+        let kind = match self {
+            Residual::Partial { kind, .. } => kind,
+            Residual::Concrete { .. } | Residual::Error(_) => return self.clone(),
+        };
+        let ty = self.ty();
+        let mk_concrete = |value: Value| Residual::Concrete {
+            value: value.normalize_ext_values(),
+            ty: ty.clone(),
+        };
+        let mk_error = || Residual::Error(ty.clone());
+        let mk_residual = |kind: ResidualKind| Residual::Partial {
+            kind,
+            ty: ty.clone(),
+        };
+        match kind {
+            ResidualKind::Var(_) => self.clone(),
+            ResidualKind::And { left, right } => {
+                let left = left.simplify(extensions);
+                match (&left, left.as_bool()) {
+                    (_, Some(true)) => right.simplify(extensions),
+                    (_, Some(false)) => mk_concrete(false.into()),
+                    (Residual::Partial { .. }, None) => {
+                        let right = right.simplify(extensions);
+                        if right.is_true() {
+                            left
+                        } else if right.is_false() && !left.can_error_assuming_well_formed() {
+                            mk_concrete(false.into())
+                        } else {
+                            mk_residual(ResidualKind::And {
+                                left: Arc::new(left),
+                                right: Arc::new(right),
+                            })
+                        }
+                    }
+                    (_, None) => mk_error(),
+                }
+            }
+            ResidualKind::Or { left, right } => {
+                let left = left.simplify(extensions);
+                match (&left, left.as_bool()) {
+                    (_, Some(true)) => mk_concrete(true.into()),
+                    (_, Some(false)) => right.simplify(extensions),
+                    (Residual::Partial { .. }, None) => {
+                        let right = right.simplify(extensions);
+                        if right.is_false() {
+                            left
+                        } else if right.is_true() && !left.can_error_assuming_well_formed() {
+                            mk_concrete(true.into())
+                        } else {
+                            mk_residual(ResidualKind::Or {
+                                left: Arc::new(left),
+                                right: Arc::new(right),
+                            })
+                        }
+                    }
+                    (_, None) => mk_error(),
+                }
+            }
+            ResidualKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => {
+                let test_expr = test_expr.simplify(extensions);
+                match (&test_expr, test_expr.as_bool()) {
+                    (_, Some(true)) => then_expr.simplify(extensions),
+                    (_, Some(false)) => else_expr.simplify(extensions),
+                    (Residual::Partial { .. }, None) => {
+                        let then_expr = then_expr.simplify(extensions);
+                        let else_expr = else_expr.simplify(extensions);
+                        if then_expr.is_true() && else_expr.is_false() {
+                            // if c then true else false == c
+                            test_expr
+                        } else if then_expr.is_false() && else_expr.is_true() {
+                            // if c then false else true == !c
+                            mk_residual(ResidualKind::UnaryApp {
+                                op: UnaryOp::Not,
+                                arg: Arc::new(test_expr),
+                            })
+                        } else {
+                            mk_residual(ResidualKind::If {
+                                test_expr: Arc::new(test_expr),
+                                then_expr: Arc::new(then_expr),
+                                else_expr: Arc::new(else_expr),
+                            })
+                        }
+                    }
+                    (_, None) => mk_error(),
+                }
+            }
+            ResidualKind::Is { expr, entity_type } => {
+                let expr = expr.simplify(extensions);
+                match &expr {
+                    Residual::Concrete { value, .. } => match value.get_as_entity() {
+                        Ok(uid) => mk_concrete((uid.entity_type() == entity_type).into()),
+                        Err(_) => mk_error(),
+                    },
+                    Residual::Partial { .. } => mk_residual(ResidualKind::Is {
+                        expr: Arc::new(expr),
+                        entity_type: entity_type.clone(),
+                    }),
+                    Residual::Error(_) => mk_error(),
+                }
+            }
+            ResidualKind::Like { expr, pattern } => {
+                let expr = expr.simplify(extensions);
+                match &expr {
+                    Residual::Concrete { value, .. } => match value.get_as_string() {
+                        Ok(s) => mk_concrete(pattern.wildcard_match(s).into()),
+                        Err(_) => mk_error(),
+                    },
+                    Residual::Partial { .. } => mk_residual(ResidualKind::Like {
+                        expr: Arc::new(expr),
+                        pattern: pattern.clone(),
+                    }),
+                    Residual::Error(_) => mk_error(),
+                }
+            }
+            ResidualKind::BinaryApp { op, arg1, arg2 } => {
+                let arg1 = arg1.simplify(extensions);
+                let arg2 = arg2.simplify(extensions);
+                if let (
+                    Residual::Concrete { value: v1, .. },
+                    Residual::Concrete { value: v2, .. },
+                ) = (&arg1, &arg2)
+                {
+                    match fold_binary_app(*op, v1, v2, extensions) {
+                        Some(Ok(v)) => return mk_concrete(v),
+                        Some(Err(())) => return mk_error(),
+                        None => {
+                            // `in` is `true` without looking up any entity if
+                            // the left operand is (one of) the right operand(s)
+                            if *op == BinaryOp::In {
+                                if let Ok(uid) = v1.get_as_entity() {
+                                    let reflexive = match v2.get_as_set() {
+                                        Ok(s) => s.iter().any(|v| v.get_as_entity() == Ok(uid)),
+                                        Err(_) => v2.get_as_entity() == Ok(uid),
+                                    };
+                                    if reflexive {
+                                        return mk_concrete(true.into());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                if arg1.is_error() || arg2.is_error() {
+                    mk_error()
+                } else {
+                    mk_residual(ResidualKind::BinaryApp {
+                        op: *op,
+                        arg1: Arc::new(arg1),
+                        arg2: Arc::new(arg2),
+                    })
+                }
+            }
+            ResidualKind::ExtensionFunctionApp { fn_name, args } => {
+                let args = match eval_extension_args(
+                    extensions.func(fn_name).ok(),
+                    args.iter(),
+                    |a| Ok(a.simplify(extensions)),
+                    |r| Value::try_from(r.clone()).ok(),
+                ) {
+                    Ok(Either::Left(args)) => args,
+                    Ok(Either::Right(value)) => return mk_concrete(value),
+                    Err(_) => return mk_error(),
+                };
+                if let Ok(vals) = args
+                    .iter()
+                    .map(|a| Value::try_from(a.clone()))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                {
+                    if let Ok(ext_fn) = extensions.func(fn_name) {
+                        if let Ok(PartialValue::Value(value)) = ext_fn.call(&vals) {
+                            return mk_concrete(value);
+                        }
+                    }
+                    mk_error()
+                } else if args.iter().any(Residual::is_error) {
+                    mk_error()
+                } else {
+                    mk_residual(ResidualKind::ExtensionFunctionApp {
+                        fn_name: fn_name.clone(),
+                        args: Arc::new(args),
+                    })
+                }
+            }
+            ResidualKind::GetAttr { expr, attr } => {
+                let expr = expr.simplify(extensions);
+                match &expr {
+                    Residual::Concrete {
+                        value:
+                            Value {
+                                value: ValueKind::Record(r),
+                                ..
+                            },
+                        ..
+                    } => match r.get(attr) {
+                        Some(val) => mk_concrete(val.clone()),
+                        None => mk_error(),
+                    },
+                    Residual::Concrete {
+                        value:
+                            Value {
+                                value: ValueKind::Lit(Literal::EntityUID(_)),
+                                ..
+                            },
+                        ..
+                    }
+                    | Residual::Partial { .. } => mk_residual(ResidualKind::GetAttr {
+                        expr: Arc::new(expr),
+                        attr: attr.clone(),
+                    }),
+                    Residual::Concrete { .. } | Residual::Error(_) => mk_error(),
+                }
+            }
+            ResidualKind::HasAttr { expr, attr } => {
+                let expr = expr.simplify(extensions);
+                match &expr {
+                    Residual::Concrete {
+                        value:
+                            Value {
+                                value: ValueKind::Record(r),
+                                ..
+                            },
+                        ..
+                    } => mk_concrete(r.contains_key(attr).into()),
+                    Residual::Concrete {
+                        value:
+                            Value {
+                                value: ValueKind::Lit(Literal::EntityUID(_)),
+                                ..
+                            },
+                        ..
+                    }
+                    | Residual::Partial { .. } => mk_residual(ResidualKind::HasAttr {
+                        expr: Arc::new(expr),
+                        attr: attr.clone(),
+                    }),
+                    Residual::Concrete { .. } | Residual::Error(_) => mk_error(),
+                }
+            }
+            ResidualKind::UnaryApp { op, arg } => {
+                let arg = arg.simplify(extensions);
+                match &arg {
+                    Residual::Concrete { value, .. } => {
+                        match crate::evaluator::unary_app(*op, value.clone(), None) {
+                            Ok(v) => mk_concrete(v),
+                            Err(_) => mk_error(),
+                        }
+                    }
+                    // !!x == x
+                    Residual::Partial {
+                        kind:
+                            ResidualKind::UnaryApp {
+                                op: UnaryOp::Not,
+                                arg: inner,
+                            },
+                        ..
+                    } if *op == UnaryOp::Not => inner.as_ref().clone(),
+                    Residual::Partial { .. } => mk_residual(ResidualKind::UnaryApp {
+                        op: *op,
+                        arg: Arc::new(arg),
+                    }),
+                    Residual::Error(_) => mk_error(),
+                }
+            }
+            ResidualKind::Set(es) => {
+                let es: Vec<_> = es.iter().map(|e| e.simplify(extensions)).collect();
+                if let Ok(vals) = es
+                    .iter()
+                    .map(|e| Value::try_from(e.clone()))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                {
+                    mk_concrete(Value::set(vals, None))
+                } else if es.iter().any(Residual::is_error) {
+                    mk_error()
+                } else {
+                    mk_residual(ResidualKind::Set(Arc::new(es)))
+                }
+            }
+            ResidualKind::Record(attrs) => {
+                let attrs: BTreeMap<_, _> = attrs
+                    .iter()
+                    .map(|(k, e)| (k.clone(), e.simplify(extensions)))
+                    .collect();
+                if let Ok(vals) = attrs
+                    .iter()
+                    .map(|(k, e)| Ok((k.clone(), Value::try_from(e.clone())?)))
+                    .collect::<std::result::Result<BTreeMap<_, _>, ()>>()
+                {
+                    mk_concrete(Value::record(vals, None))
+                } else if attrs.values().any(Residual::is_error) {
+                    mk_error()
+                } else {
+                    mk_residual(ResidualKind::Record(Arc::new(attrs)))
+                }
+            }
+        }
+    }
+
+    /// The number of nodes in this residual, counting each concrete value
+    /// and error as a single node
+    pub fn size(&self) -> usize {
+        match self {
+            Residual::Concrete { .. } | Residual::Error(_) => 1,
+            Residual::Partial { kind, .. } => {
+                1 + match kind {
+                    ResidualKind::Var(_) => 0,
+                    ResidualKind::If {
+                        test_expr,
+                        then_expr,
+                        else_expr,
+                    } => test_expr.size() + then_expr.size() + else_expr.size(),
+                    ResidualKind::And { left, right } | ResidualKind::Or { left, right } => {
+                        left.size() + right.size()
+                    }
+                    ResidualKind::BinaryApp { arg1, arg2, .. } => arg1.size() + arg2.size(),
+                    ResidualKind::UnaryApp { arg: expr, .. }
+                    | ResidualKind::GetAttr { expr, .. }
+                    | ResidualKind::HasAttr { expr, .. }
+                    | ResidualKind::Like { expr, .. }
+                    | ResidualKind::Is { expr, .. } => expr.size(),
+                    ResidualKind::ExtensionFunctionApp { args, .. } => {
+                        args.iter().map(Residual::size).sum()
+                    }
+                    ResidualKind::Set(es) => es.iter().map(Residual::size).sum(),
+                    ResidualKind::Record(attrs) => attrs.values().map(Residual::size).sum(),
+                }
+            }
+        }
+    }
+
+    /// The value of this residual, if it is a concrete boolean
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Residual::Concrete { value, .. } => value.get_as_bool().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Apply `op` to the concrete operands `v1` and `v2`, if that doesn't need
+/// the entity store. Returns `None` for `in`, `getTag`, and `hasTag`, and
+/// `Some(Err(()))` if the application errors.
+pub(crate) fn fold_binary_app(
+    op: BinaryOp,
+    v1: &Value,
+    v2: &Value,
+    extensions: &Extensions<'_>,
+) -> Option<Result<Value, ()>> {
+    let folded = match op {
+        BinaryOp::In | BinaryOp::GetTag | BinaryOp::HasTag => return None,
+        BinaryOp::Eq | BinaryOp::Less | BinaryOp::LessEq => {
+            crate::evaluator::binary_relation(op, v1, v2, extensions).map_err(|_| ())
+        }
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+            crate::evaluator::binary_arith(op, v1.clone(), v2.clone(), None).map_err(|_| ())
+        }
+        BinaryOp::Contains => match &v1.value {
+            ValueKind::Set(s) => Ok(s.contains(v2).into()),
+            _ => Err(()),
+        },
+        BinaryOp::ContainsAll => match (v1.get_as_set(), v2.get_as_set()) {
+            (Ok(arg1_set), Ok(arg2_set)) => Ok(arg2_set.is_subset(arg1_set).into()),
+            _ => Err(()),
+        },
+        BinaryOp::ContainsAny => match (v1.get_as_set(), v2.get_as_set()) {
+            (Ok(arg1_set), Ok(arg2_set)) => Ok((!arg1_set.is_disjoint(arg2_set)).into()),
+            _ => Err(()),
+        },
+        #[cfg(feature = "maps")]
+        BinaryOp::GetKey => match (v1.get_as_record(), v2.get_as_string()) {
+            (Ok(map), Ok(key)) => map.get(key).cloned().ok_or(()),
+            _ => Err(()),
+        },
+        #[cfg(feature = "maps")]
+        BinaryOp::ContainsKey => match (v1.get_as_record(), v2.get_as_string()) {
+            (Ok(map), Ok(key)) => Ok(map.contains_key(key).into()),
+            _ => Err(()),
+        },
+    };
+    Some(folded)
+}
+
 /// Conversion from `Residual` to `Expr` so that we can use the concrete evaluator for re-authorization
 impl From<Residual> for Expr {
     fn from(value: Residual) -> Expr {
@@ -874,6 +1283,94 @@ mod test {
         }
     }
 
+    #[track_caller]
+    fn assert_simplifies_to(expr_str: &str, simplified_str: &str) {
+        let residual = parse_residual(expr_str);
+        let simplified = residual.simplify(Extensions::all_available());
+        assert_eq!(
+            Expr::from(simplified.clone()),
+            parse_expr(simplified_str).unwrap()
+        );
+        assert!(
+            simplified.size() < residual.size(),
+            "expected {} to be smaller than {}",
+            simplified.size(),
+            residual.size()
+        );
+    }
+
+    #[test]
+    fn simplify() {
+        // `parse_residual` wraps the expression in `true && (true && (true && _))`
+        assert_simplifies_to("principal.foo", "principal.foo");
+        assert_eq!(parse_residual("principal.foo").size(), 8);
+        assert_eq!(
+            parse_residual("principal.foo")
+                .simplify(Extensions::all_available())
+                .size(),
+            2
+        );
+
+        assert_simplifies_to(
+            "if true then principal.foo else principal.str == \"a\"",
+            "principal.foo",
+        );
+        assert_simplifies_to("if principal.foo then true else false", "principal.foo");
+        assert_simplifies_to("if principal.foo then false else true", "!principal.foo");
+        assert_simplifies_to("!!principal.foo", "principal.foo");
+        assert_simplifies_to("principal.foo || false", "principal.foo");
+        assert_simplifies_to("false || principal.foo", "principal.foo");
+        assert_simplifies_to("principal.num < 1 + 2 * 3", "principal.num < 7");
+        assert_simplifies_to(
+            r#"principal.set.contains("a") && ["a", "b"].contains("a")"#,
+            r#"principal.set.contains("a")"#,
+        );
+        assert_simplifies_to(
+            r#"principal.foo && decimal("1.5").lessThan(decimal("2.0"))"#,
+            "principal.foo",
+        );
+        assert_simplifies_to(r#"{a: 1, b: "x"}.a == 1 && principal.foo"#, "principal.foo");
+        assert_simplifies_to(
+            r#"principal is User && principal in [User::"alice", principal]"#,
+            r#"principal is User && principal in [User::"alice", principal]"#,
+        );
+        assert_simplifies_to(
+            r#"User::"alice" in [User::"bob", User::"alice"] && principal.foo"#,
+            "principal.foo",
+        );
+        assert_simplifies_to(r#""abc" like "a*" && principal.foo"#, "principal.foo");
+        assert_simplifies_to(
+            r#"decimal("1.0") == decimal("1.00") && principal.foo"#,
+            "principal.foo",
+        );
+
+        // `principal is User` can't error, so `false` absorbs it
+        assert_simplifies_to("principal is User && false", "false");
+    }
+
+    #[test]
+    fn simplify_preserves_errors() {
+        // `principal.foo` may error (e.g., if the principal doesn't exist), so
+        // `principal.foo && false` must not be simplified to `false`
+        assert_simplifies_to("principal.foo && 1 == 2", "principal.foo && false");
+        assert_matches!(
+            parse_residual("principal.foo || 9223372036854775807 + 1 == 0")
+                .simplify(Extensions::all_available()),
+            Residual::Partial {
+                kind: ResidualKind::Or { right, .. },
+                ..
+            } => assert!(right.is_error())
+        );
+        assert_matches!(
+            parse_residual("principal.foo && {a: 1}.a + 9223372036854775807 == 0")
+                .simplify(Extensions::all_available()),
+            Residual::Partial {
+                kind: ResidualKind::And { right, .. },
+                ..
+            } => assert!(right.is_error())
+        );
+    }
+
     fn assert_eq_expr(expr_str: &str) {
         // The unconstrained
         let e: Expr = format!("true && (true && (true && ({})))", expr_str)
@@ -58,6 +58,15 @@ pub trait TCNode<K> {
 /// error, the result contains an error structure `Err<K>` which contains the
 /// keys (with type `K`) for the nodes in the graph which caused the error.
 /// If `enforce_dag` then also check that the hierarchy is a DAG
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "cedar.compute_tc",
+        level = "debug",
+        skip_all,
+        fields(node_count = nodes.len(), enforce_dag)
+    )
+)]
 pub fn compute_tc<K, V>(nodes: &mut HashMap<K, V>, enforce_dag: bool) -> Result<(), K>
 where
     K: Clone + Eq + Hash + Debug + Display,
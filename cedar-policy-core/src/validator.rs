@@ -33,8 +33,10 @@
     )
 )]
 
-use crate::ast::{Policy, PolicySet, Template};
-use std::collections::HashSet;
+use crate::ast::{AnyId, Policy, PolicyID, PolicySet, Template};
+use itertools::{Either, Itertools};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 mod level_validate;
 
 mod coreschema;
@@ -52,13 +54,19 @@ pub use schema::err::*;
 pub use schema::*;
 mod deprecated_schema_compat;
 pub mod json_schema;
+pub mod schema_doc;
+pub mod schema_inference;
+mod shadowing;
 mod str_checks;
 pub use str_checks::confusable_string_checks;
 pub mod cedar_schema;
 pub mod typecheck;
+pub use typecheck::typecheck_expr;
 use typecheck::Typechecker;
 mod partition_nonempty;
 pub mod types;
+mod unused_definitions;
+pub use unused_definitions::UnusedDefinitions;
 
 /// Used to select how a policy will be validated.
 #[derive(Default, Eq, PartialEq, Copy, Clone, Debug)]
@@ -96,17 +104,177 @@ impl ValidationMode {
     }
 }
 
+/// How a kind of [`ValidationWarning`] is reported
+#[derive(Default, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum WarningSeverity {
+    /// Report the warning as a [`ValidationError`], failing validation
+    Error,
+    /// Report the warning as a warning
+    #[default]
+    Warn,
+    /// Don't report the warning
+    Ignore,
+}
+
+/// The name of the policy annotation listing the kinds of warnings which are
+/// not reported for the policy, e.g.,
+/// `@allow_warning("impossible_policy, mixed_script_string")`
+pub const ALLOW_WARNING_ANNOTATION: &str = "allow_warning";
+
+/// The name of the policy annotation setting the maximum entity dereference
+/// level for the policy (see RFC 76), e.g., `@max_deref_level("2")`. This
+/// overrides the level configured for the [`Validator`].
+pub const MAX_DEREF_LEVEL_ANNOTATION: &str = "max_deref_level";
+
+/// The name of the schema annotation marking an attribute as deprecated, with
+/// an optional reason, e.g., `@deprecated("use `email` instead")`. The
+/// validator reports a [`ValidationWarning::DeprecatedAttribute`] for each
+/// access to a deprecated attribute.
+pub const DEPRECATED_ANNOTATION: &str = "deprecated";
+
+/// Configuration for a [`Validator`]
+#[derive(Default, Eq, PartialEq, Clone, Debug)]
+pub struct ValidatorConfig {
+    warning_severities: HashMap<ValidationWarningKind, WarningSeverity>,
+    max_deref_level: Option<u32>,
+    require_fully_specified_actions: bool,
+    shadowing_checks: bool,
+    incomplete_schema: bool,
+}
+
+impl ValidatorConfig {
+    /// Create a configuration in which every warning is reported as a warning
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report warnings of the given kind with the given severity
+    pub fn with_warning_severity(
+        mut self,
+        kind: ValidationWarningKind,
+        severity: WarningSeverity,
+    ) -> Self {
+        self.warning_severities.insert(kind, severity);
+        self
+    }
+
+    /// The severity with which warnings of the given kind are reported
+    pub fn warning_severity(&self, kind: ValidationWarningKind) -> WarningSeverity {
+        self.warning_severities
+            .get(&kind)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Run level validation (see RFC 76) on every policy, with
+    /// `max_deref_level` as the maximum level for policies without a
+    /// `@max_deref_level` annotation
+    pub fn with_max_deref_level(mut self, max_deref_level: u32) -> Self {
+        self.max_deref_level = Some(max_deref_level);
+        self
+    }
+
+    /// The maximum level for policies without a `@max_deref_level`
+    /// annotation, or `None` if only annotated policies are level validated
+    pub fn max_deref_level(&self) -> Option<u32> {
+        self.max_deref_level
+    }
+
+    /// Report an error for each policy whose action scope constraint includes
+    /// an action which doesn't declare at least one principal type and one
+    /// resource type it applies to. Use
+    /// [`ValidatorSchema::check_fully_specified_actions()`] to check the
+    /// action declarations themselves.
+    pub fn with_fully_specified_actions(mut self) -> Self {
+        self.require_fully_specified_actions = true;
+        self
+    }
+
+    /// Whether every action a policy applies to must declare at least one
+    /// principal type and one resource type
+    pub fn requires_fully_specified_actions(&self) -> bool {
+        self.require_fully_specified_actions
+    }
+
+    /// Compare the scopes of the policies in a policy set, reporting a
+    /// [`ValidationWarning::ShadowedPolicy`] for each policy which is
+    /// shadowed by a broader policy with the same effect, and a
+    /// [`ValidationWarning::NullifiedPermit`] for each permit policy which is
+    /// nullified by a broader forbid policy. This compares every pair of
+    /// policies, so it is not done by default.
+    pub fn with_shadowing_checks(mut self) -> Self {
+        self.shadowing_checks = true;
+        self
+    }
+
+    /// Whether policies are checked for shadowing by other policies
+    pub fn shadowing_checks(&self) -> bool {
+        self.shadowing_checks
+    }
+
+    /// Validate against a schema which may not declare every entity type and
+    /// attribute policies use, e.g., while adopting a schema for an existing
+    /// set of policies. Instead of failing validation, each use of an entity
+    /// type which is not declared in the schema is reported as a
+    /// [`ValidationWarning::AssumedEntityType`], and each access to an
+    /// attribute which is not declared in the schema is reported as a
+    /// [`ValidationWarning::AssumedAttribute`]. The rest of an expression
+    /// accessing an undeclared attribute is not validated.
+    pub fn with_incomplete_schema(mut self) -> Self {
+        self.incomplete_schema = true;
+        self
+    }
+
+    /// Whether entity types and attributes which are not declared in the
+    /// schema are assumed to exist
+    pub fn incomplete_schema(&self) -> bool {
+        self.incomplete_schema
+    }
+}
+
 /// Structure containing the context needed for policy validation. This is
 /// currently only the `EntityType`s and `ActionType`s from a single schema.
 #[derive(Debug, Clone)]
 pub struct Validator {
     schema: ValidatorSchema,
+    config: ValidatorConfig,
+    /// Types of the extension functions policies may call
+    extensions: Arc<extensions::ExtensionSchemas<'static>>,
 }
 
 impl Validator {
     /// Construct a new Validator from a schema file.
     pub fn new(schema: ValidatorSchema) -> Validator {
-        Self { schema }
+        Self::with_config(schema, ValidatorConfig::default())
+    }
+
+    /// Construct a new Validator from a schema file, reporting warnings as
+    /// configured in `config`
+    pub fn with_config(schema: ValidatorSchema, config: ValidatorConfig) -> Validator {
+        Self {
+            schema,
+            config,
+            extensions: extensions::ExtensionSchemas::all_available_shared(),
+        }
+    }
+
+    /// Typecheck calls to the functions of `extensions`, rather than of
+    /// [`crate::extensions::Extensions::all_available()`]
+    pub fn with_extensions(mut self, extensions: &crate::extensions::Extensions<'_>) -> Validator {
+        self.extensions = Arc::new(extensions::ExtensionSchemas::with_custom_extensions(
+            extensions.custom_extensions(),
+        ));
+        self
+    }
+
+    /// A typechecker for the schema and extensions of this `Validator`
+    pub(crate) fn typechecker(&self, mode: ValidationMode) -> Typechecker<'_> {
+        Typechecker::with_extensions(&self.schema, mode, &self.extensions)
+    }
+
+    /// Get the configuration this `Validator` is using.
+    pub fn config(&self) -> &ValidatorConfig {
+        &self.config
     }
 
     /// Get the `ValidatorSchema` this `Validator` is using.
@@ -115,28 +283,18 @@ impl Validator {
     }
 
     /// Validate all templates, links, and static policies in a policy set.
+    /// Policies with a `@max_deref_level` annotation, and all policies if the
+    /// configuration sets a maximum level, are also level validated (see
+    /// RFC 76).
     /// Return a `ValidationResult`.
     pub fn validate(&self, policies: &PolicySet, mode: ValidationMode) -> ValidationResult {
-        let validate_policy_results: (Vec<_>, Vec<_>) = policies
-            .all_templates()
-            .map(|p| self.validate_policy(p, mode))
-            .unzip();
-        let template_and_static_policy_errs = validate_policy_results.0.into_iter().flatten();
-        let template_and_static_policy_warnings = validate_policy_results.1.into_iter().flatten();
-        let link_errs = policies
-            .policies()
-            .filter_map(|p| self.validate_slots(p, mode))
-            .flatten();
-        ValidationResult::new(
-            template_and_static_policy_errs.chain(link_errs),
-            template_and_static_policy_warnings
-                .chain(confusable_string_checks(policies.all_templates())),
-        )
+        self.validate_policies(policies, mode, self.config.max_deref_level)
     }
 
     /// Validate all templates, links, and static policies in a policy set.
     /// If validation passes, also run level validation with `max_deref_level`
-    /// (see RFC 76).
+    /// (see RFC 76), or with the level given by the `@max_deref_level`
+    /// annotation for policies which have one.
     /// Return a `ValidationResult`.
     pub fn validate_with_level(
         &self,
@@ -144,23 +302,158 @@ impl Validator {
         mode: ValidationMode,
         max_deref_level: u32,
     ) -> ValidationResult {
-        let validate_policy_results: (Vec<_>, Vec<_>) = policies
-            .all_templates()
-            .map(|p| self.validate_policy_with_level(p, mode, max_deref_level))
-            .unzip();
-        let template_and_static_policy_errs = validate_policy_results.0.into_iter().flatten();
-        let template_and_static_policy_warnings = validate_policy_results.1.into_iter().flatten();
+        self.validate_policies(policies, mode, Some(max_deref_level))
+    }
+
+    /// Validate all templates, links, and static policies in a policy set,
+    /// level validating each template whose `@max_deref_level` annotation or
+    /// `default_level` gives it a maximum level
+    fn validate_policies(
+        &self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        default_level: Option<u32>,
+    ) -> ValidationResult {
+        let max_deref_level = AnyId::new_unchecked(MAX_DEREF_LEVEL_ANNOTATION);
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for p in policies.all_templates() {
+            let level = match p.annotation(&max_deref_level) {
+                Some(annotation) => match annotation.val.trim().parse() {
+                    Ok(level) => Some(level),
+                    Err(_) => {
+                        errors.push(ValidationError::invalid_level_annotation(
+                            annotation.loc.clone(),
+                            p.id().clone(),
+                            annotation.val.clone(),
+                        ));
+                        default_level
+                    }
+                },
+                None => default_level,
+            };
+            if self.config.require_fully_specified_actions {
+                errors.extend(self.validate_fully_specified_actions(p));
+            }
+            match level {
+                Some(level) => {
+                    let (errs, warns) = self.validate_policy_with_level(p, mode, level);
+                    errors.extend(errs);
+                    warnings.extend(warns);
+                }
+                None => {
+                    let (errs, warns) = self.validate_policy(p, mode);
+                    errors.extend(errs);
+                    warnings.extend(warns);
+                }
+            }
+        }
         let link_errs = policies
             .policies()
             .filter_map(|p| self.validate_slots(p, mode))
             .flatten();
-        ValidationResult::new(
-            template_and_static_policy_errs.chain(link_errs),
-            template_and_static_policy_warnings
+        if self.config.shadowing_checks {
+            warnings.extend(self.shadowing_checks(policies));
+        }
+        let (errors, assumed) = self.assume_unknowns(errors.into_iter().chain(link_errs));
+        self.result_with_configured_warnings(
+            policies,
+            errors,
+            assumed
+                .into_iter()
+                .chain(warnings)
                 .chain(confusable_string_checks(policies.all_templates())),
         )
     }
 
+    /// When validating against an incomplete schema, turn each error for an
+    /// entity type or attribute which is not declared in the schema into a
+    /// warning that it is assumed to exist. Policies using an undeclared
+    /// entity type in their scope may apply to actions whose declaration
+    /// doesn't list it, so invalid action application errors for them are
+    /// dropped. Other errors are kept as errors.
+    fn assume_unknowns(
+        &self,
+        errors: impl IntoIterator<Item = ValidationError>,
+    ) -> (Vec<ValidationError>, Vec<ValidationWarning>) {
+        if !self.config.incomplete_schema {
+            return (errors.into_iter().collect(), Vec::new());
+        }
+        let (errors, assumed): (Vec<_>, Vec<_>) =
+            errors.into_iter().partition_map(|error| match error {
+                ValidationError::UnrecognizedEntityType(e) => {
+                    Either::Right(ValidationWarning::assumed_entity_type(
+                        e.source_loc,
+                        e.policy_id,
+                        e.actual_entity_type,
+                    ))
+                }
+                ValidationError::UnsafeAttributeAccess(e) => {
+                    Either::Right(ValidationWarning::assumed_attribute(
+                        e.source_loc,
+                        e.policy_id,
+                        e.attribute_access,
+                    ))
+                }
+                e => Either::Left(e),
+            });
+        let with_assumed_types: HashSet<PolicyID> = assumed
+            .iter()
+            .filter(|w| w.kind() == ValidationWarningKind::AssumedEntityType)
+            .map(|w| w.policy_id().clone())
+            .collect();
+        let errors = errors
+            .into_iter()
+            .filter(|e| match e {
+                ValidationError::InvalidActionApplication(e) => {
+                    !with_assumed_types.contains(&e.policy_id)
+                }
+                _ => true,
+            })
+            .collect();
+        (errors, assumed)
+    }
+
+    /// Build the `ValidationResult` for `policies`, reporting each warning
+    /// with its configured severity, unless the policy where it occurred
+    /// allows it with an `@allow_warning` annotation.
+    fn result_with_configured_warnings(
+        &self,
+        policies: &PolicySet,
+        errors: impl IntoIterator<Item = ValidationError>,
+        warnings: impl IntoIterator<Item = ValidationWarning>,
+    ) -> ValidationResult {
+        let allow_warning = AnyId::new_unchecked(ALLOW_WARNING_ANNOTATION);
+        let allowed: HashMap<&PolicyID, HashSet<ValidationWarningKind>> = policies
+            .all_templates()
+            .filter_map(|t| {
+                let kinds = t
+                    .annotation(&allow_warning)?
+                    .val
+                    .split(',')
+                    .filter_map(|kind| kind.trim().parse().ok())
+                    .collect();
+                Some((t.id(), kinds))
+            })
+            .collect();
+        let mut errors: Vec<_> = errors.into_iter().collect();
+        let mut reported_warnings = Vec::new();
+        for warning in warnings {
+            if allowed
+                .get(warning.policy_id())
+                .is_some_and(|kinds| kinds.contains(&warning.kind()))
+            {
+                continue;
+            }
+            match self.config.warning_severity(warning.kind()) {
+                WarningSeverity::Error => errors.push(ValidationError::warning_as_error(warning)),
+                WarningSeverity::Warn => reported_warnings.push(warning),
+                WarningSeverity::Ignore => {}
+            }
+        }
+        ValidationResult::new(errors, reported_warnings)
+    }
+
     /// Run all validations against a single static policy or template (note
     /// that Core `Template` includes static policies as well), gathering all
     /// validation errors and warnings in the returned iterators.
@@ -248,7 +541,7 @@ impl Validator {
         impl Iterator<Item = ValidationError> + 'a,
         impl Iterator<Item = ValidationWarning> + 'a,
     ) {
-        let typecheck = Typechecker::new(&self.schema, mode);
+        let typecheck = self.typechecker(mode);
         let mut errors = HashSet::new();
         let mut warnings = HashSet::new();
         typecheck.typecheck_policy(t, &mut errors, &mut warnings);
@@ -288,6 +581,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -297,6 +591,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -824,3 +1119,448 @@ mod enumerated_entity_types {
         );
     }
 }
+
+#[cfg(test)]
+mod warning_config {
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{ast::PolicyID, extensions::Extensions, parser::parse_policyset};
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(
+            r#"entity User; action view appliesTo { principal: User, resource: User };"#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    /// Two impossible policies, one of which allows the warning
+    fn policies() -> PolicySet {
+        parse_policyset(
+            r#"
+            permit(principal, action, resource) when { false };
+            @allow_warning("mixed_script_string , impossible_policy")
+            permit(principal, action, resource) when { false };
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn annotation_suppresses_warning() {
+        let validator = Validator::new(schema());
+        let result = validator.validate(&policies(), ValidationMode::Strict);
+        assert!(result.validation_passed());
+        assert_matches!(result.validation_warnings().collect_vec().as_slice(), [w] => {
+            assert_eq!(w.kind(), ValidationWarningKind::ImpossiblePolicy);
+            assert_eq!(w.policy_id(), &PolicyID::from_string("policy0"));
+        });
+    }
+
+    #[test]
+    fn warning_as_error() {
+        let config = ValidatorConfig::new().with_warning_severity(
+            ValidationWarningKind::ImpossiblePolicy,
+            WarningSeverity::Error,
+        );
+        let validator = Validator::with_config(schema(), config);
+        let result = validator.validate_with_level(&policies(), ValidationMode::Strict, 1);
+        assert!(!result.validation_passed());
+        assert_eq!(result.validation_warnings().count(), 0);
+        assert_matches!(
+            result.validation_errors().collect_vec().as_slice(),
+            [ValidationError::WarningAsError(e)] => {
+                assert_eq!(e.policy_id, PolicyID::from_string("policy0"));
+                assert_eq!(e.warning.kind(), ValidationWarningKind::ImpossiblePolicy);
+                assert_eq!(miette::Diagnostic::severity(e), Some(miette::Severity::Error));
+            }
+        );
+    }
+
+    #[test]
+    fn ignored_warning() {
+        let config = ValidatorConfig::new().with_warning_severity(
+            ValidationWarningKind::ImpossiblePolicy,
+            WarningSeverity::Ignore,
+        );
+        let result =
+            Validator::with_config(schema(), config).validate(&policies(), ValidationMode::Strict);
+        assert!(result.validation_passed());
+        assert_eq!(result.validation_warnings().count(), 0);
+    }
+
+    #[test]
+    fn warning_kind_names() {
+        for kind in ValidationWarningKind::ALL {
+            assert_eq!(kind.as_str().parse::<ValidationWarningKind>(), Ok(kind));
+        }
+        assert!("impossible".parse::<ValidationWarningKind>().is_err());
+    }
+}
+
+#[cfg(test)]
+mod deprecated_attributes {
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{ast::PolicyID, extensions::Extensions, parser::parse_policyset};
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User {
+                email: String,
+                @deprecated("use `email` instead")
+                mail?: String,
+                profile: {
+                    @deprecated
+                    nickname?: String,
+                },
+            };
+            action view appliesTo {
+                principal: User,
+                resource: User,
+                context: { @deprecated("always true") authenticated: Bool },
+            };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    fn warnings(src: &str) -> Vec<ValidationWarning> {
+        let policies = parse_policyset(src).unwrap();
+        let result = Validator::new(schema()).validate(&policies, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        result.validation_warnings().cloned().collect_vec()
+    }
+
+    #[test]
+    fn entity_attribute() {
+        assert_matches!(
+            warnings(r#"permit(principal, action, resource) when { principal has mail && principal.mail == "a" };"#).as_slice(),
+            [w1, w2] => {
+                for w in [w1, w2] {
+                    assert_eq!(w.kind(), ValidationWarningKind::DeprecatedAttribute);
+                    assert_eq!(w.policy_id(), &PolicyID::from_string("policy0"));
+                    assert_matches!(w, ValidationWarning::DeprecatedAttribute(w) => {
+                        assert_eq!(w.attr, "mail");
+                        assert_eq!(w.reason.as_deref(), Some("use `email` instead"));
+                    });
+                }
+                assert_eq!(
+                    w1.to_string(),
+                    "for policy `policy0`, attribute `mail` is deprecated: use `email` instead"
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn record_attributes() {
+        assert_matches!(
+            warnings(r#"permit(principal, action, resource) when { context.authenticated || principal.profile has nickname };"#).as_slice(),
+            [w1, w2] => {
+                let mut attrs = [w1, w2].map(|w| assert_matches!(
+                    w,
+                    ValidationWarning::DeprecatedAttribute(w) => (w.attr.clone(), w.reason.clone())
+                ));
+                attrs.sort();
+                assert_eq!(
+                    attrs,
+                    [
+                        ("authenticated".to_string(), Some("always true".to_string())),
+                        ("nickname".to_string(), None),
+                    ]
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn no_warning_for_other_attributes() {
+        assert_eq!(
+            warnings(r#"permit(principal, action, resource) when { principal.email == "a" };"#),
+            []
+        );
+    }
+
+    #[test]
+    fn allowed_warning() {
+        assert_eq!(
+            warnings(
+                r#"@allow_warning("deprecated_attribute") permit(principal, action, resource) when { principal has mail };"#
+            ),
+            []
+        );
+    }
+}
+
+#[cfg(test)]
+mod constant_comparisons {
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{extensions::Extensions, parser::parse_policyset};
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity Server {
+                port: Long[0..65535],
+                name: String matching "[a-z]+",
+                addr: ipaddr satisfying "isIpv4",
+            };
+            action connect appliesTo {
+                principal: Server,
+                resource: Server,
+                context: { retries: Long[0..5] },
+            };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    fn warnings(src: &str) -> Vec<ValidationWarning> {
+        let policies = parse_policyset(src).unwrap();
+        let result = Validator::new(schema()).validate(&policies, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        result.validation_warnings().cloned().collect_vec()
+    }
+
+    #[test]
+    fn constant_comparisons() {
+        for cond in [
+            "resource.port == 70000",
+            "resource.port != -1",
+            "resource.port > 65535",
+            "resource.port >= 0",
+            "resource.port < 0",
+            "resource.port <= 65535",
+            "70000 < resource.port",
+            "context.retries > 5",
+            r#"resource.name == "Web""#,
+            r#"resource.addr == ip("::1")"#,
+            r#"ip("::1") == resource.addr"#,
+        ] {
+            assert_matches!(
+                warnings(&format!(
+                    "permit(principal, action, resource) when {{ {cond} }};"
+                ))
+                .as_slice(),
+                [ValidationWarning::ConstantComparison(_)],
+                "{cond}"
+            );
+        }
+    }
+
+    #[test]
+    fn warning_message() {
+        assert_matches!(
+            warnings("permit(principal, action, resource) when { resource.port > 65535 };").as_slice(),
+            [w] => {
+                assert_eq!(w.kind(), ValidationWarningKind::ConstantComparison);
+                assert_eq!(
+                    w.to_string(),
+                    "for policy `policy0`, comparing attribute `port` with `65535` always has the same outcome, because the attribute has the refinement `[0..65535]`"
+                );
+            }
+        );
+        assert_matches!(
+            warnings(r#"permit(principal, action, resource) when { resource.addr == ip("::1") };"#).as_slice(),
+            [w] => {
+                assert_eq!(
+                    w.to_string(),
+                    r#"for policy `policy0`, comparing attribute `addr` with `ip("::1")` always has the same outcome, because the attribute has the refinement `satisfying "isIpv4"`"#
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn no_warning_for_other_comparisons() {
+        for cond in [
+            "resource.port == 443",
+            "resource.port > 1024",
+            "resource.port <= 0",
+            "resource.port < principal.port",
+            r#"resource.name == "web""#,
+            r#"resource.name like "Web*""#,
+            r#"resource.addr == ip("10.0.0.1")"#,
+            r#"resource.addr.isInRange(ip("::1/64"))"#,
+        ] {
+            assert_eq!(
+                warnings(&format!(
+                    "permit(principal, action, resource) when {{ {cond} }};"
+                )),
+                [],
+                "{cond}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod fully_specified_actions {
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{ast::PolicyID, extensions::Extensions, parser::parse_policyset};
+
+    fn schema() -> ValidatorSchema {
+        // The Cedar schema syntax doesn't allow empty lists of principal or
+        // resource types, so `share` is declared in the JSON syntax
+        ValidatorSchema::from_json_value(
+            serde_json::json!({ "": {
+                "entityTypes": { "User": {}, "Photo": {} },
+                "actions": {
+                    "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] } },
+                    "audit": {},
+                    "share": { "appliesTo": { "principalTypes": [], "resourceTypes": ["Photo"] } },
+                    "photoActions": {
+                        "memberOf": [{ "id": "view" }],
+                        "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] }
+                    }
+                }
+            }}),
+            Extensions::all_available(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn unspecified_action_declarations() {
+        assert_matches!(
+            schema().check_fully_specified_actions(),
+            Err(SchemaError::UnspecifiedActions(e)) => {
+                assert_eq!(
+                    e.actions().map(ToString::to_string).collect_vec(),
+                    [r#"Action::"audit""#, r#"Action::"share""#]
+                );
+            }
+        );
+        let schema = ValidatorSchema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0;
+        assert_matches!(schema.check_fully_specified_actions(), Ok(()));
+    }
+
+    #[test]
+    fn policies_with_unspecified_actions() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            permit(principal, action in [Action::"view", Action::"share"], resource);
+            permit(principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let result = Validator::new(schema()).validate(&policies, ValidationMode::Strict);
+        assert!(result.validation_passed());
+
+        let config = ValidatorConfig::new().with_fully_specified_actions();
+        let result =
+            Validator::with_config(schema(), config).validate(&policies, ValidationMode::Strict);
+        assert_matches!(
+            result.validation_errors().collect_vec().as_slice(),
+            [
+                ValidationError::UnspecifiedActionApplication(e1),
+                ValidationError::UnspecifiedActionApplication(e2),
+            ] => {
+                assert_eq!(e1.policy_id, PolicyID::from_string("policy1"));
+                assert_eq!(e1.actions.iter().map(ToString::to_string).collect_vec(), [r#"Action::"share""#]);
+                assert_eq!(e2.policy_id, PolicyID::from_string("policy2"));
+                assert_eq!(
+                    e2.actions.iter().map(ToString::to_string).collect_vec(),
+                    [r#"Action::"audit""#, r#"Action::"share""#]
+                );
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod incomplete_schema {
+    use cool_asserts::assert_matches;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::{extensions::Extensions, parser::parse_policyset};
+
+    fn validate(config: ValidatorConfig) -> ValidationResult {
+        let schema = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User { name: String };
+            entity Photo;
+            action view appliesTo { principal: User, resource: Photo, context: {} };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0;
+        let policies = parse_policyset(
+            r#"
+            permit(principal in Group::"admins", action, resource)
+            when { principal.department == "eng" && context.ip == "10.0.0.1" };
+            permit(principal, action, resource) when { principal.name == 1 };
+            "#,
+        )
+        .unwrap();
+        Validator::with_config(schema, config).validate(&policies, ValidationMode::Strict)
+    }
+
+    #[test]
+    fn unknowns_are_errors_by_default() {
+        let result = validate(ValidatorConfig::new());
+        assert_eq!(
+            result
+                .validation_errors()
+                .map(|e| format!("{e}"))
+                .sorted()
+                .collect_vec(),
+            [
+                "for policy `policy0`, attribute `department` on entity type `User` not found",
+                r#"for policy `policy0`, attribute `ip` in context for Action::"view" not found"#,
+                "for policy `policy0`, unable to find an applicable action given the policy scope constraints",
+                "for policy `policy0`, unrecognized entity type `Group`",
+                "the types Long and String are not compatible",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknowns_are_assumed() {
+        let result = validate(ValidatorConfig::new().with_incomplete_schema());
+        // Other errors are still reported
+        assert_matches!(
+            result.validation_errors().collect_vec().as_slice(),
+            [ValidationError::IncompatibleTypes(e)] => {
+                assert_eq!(e.policy_id, PolicyID::from_string("policy1"));
+            }
+        );
+        assert_eq!(
+            result
+                .validation_warnings()
+                .map(|w| (w.kind(), w.policy_id().to_string()))
+                .sorted()
+                .collect_vec(),
+            [
+                (ValidationWarningKind::AssumedEntityType, "policy0".into()),
+                (ValidationWarningKind::AssumedAttribute, "policy0".into()),
+                (ValidationWarningKind::AssumedAttribute, "policy0".into()),
+            ]
+        );
+    }
+}
@@ -20,6 +20,7 @@ mod ast;
 pub use ast::Path;
 mod err;
 pub mod fmt;
+mod generics;
 pub mod parser;
 pub(crate) mod test;
 pub mod to_json_schema;
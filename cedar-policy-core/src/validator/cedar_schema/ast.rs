@@ -18,6 +18,7 @@ use std::{collections::BTreeMap, iter::once};
 
 use crate::{
     ast::{Annotation, Annotations, AnyId, Eid, Id, InternalName},
+    entities::CedarValueJson,
     parser::{Loc, Node},
 };
 use itertools::{Either, Itertools};
@@ -120,6 +121,21 @@ impl Path {
         self.0.loc.as_ref()
     }
 
+    /// The last element of the [`Path`]
+    pub fn basename(&self) -> &Id {
+        &self.0.node.basename
+    }
+
+    /// The (potentially empty) prefix of the [`Path`]. Most significant name first
+    pub fn namespace(&self) -> &[Id] {
+        &self.0.node.namespace
+    }
+
+    /// The single element of the [`Path`], if it is unqualified
+    pub fn unqualified_name(&self) -> Option<&Id> {
+        self.namespace().is_empty().then(|| self.basename())
+    }
+
     /// Consume the [`Path`] and get an owned iterator over the elements. Most significant name first
     #[expect(
         clippy::should_implement_trait,
@@ -251,6 +267,8 @@ pub enum Declaration {
 #[derive(Debug, Clone)]
 pub struct TypeDecl {
     pub name: Node<Id>,
+    /// Type parameters of this common type (empty if it is not parameterized)
+    pub params: Vec<Node<Id>>,
     pub def: Node<Type>,
 }
 
@@ -287,6 +305,8 @@ pub struct StandardEntityDecl {
     pub attrs: Node<Vec<Node<Annotated<AttrDecl>>>>,
     /// Tag type for this entity (`None` means no tags on this entity)
     pub tags: Option<Node<Type>>,
+    /// Invariants over the attributes of this entity
+    pub constraints: Vec<Node<SmolStr>>,
 }
 
 /// Declaration of an entity type
@@ -301,10 +321,70 @@ pub struct EnumEntityDecl {
 pub enum Type {
     /// A set of types
     Set(Box<Node<Type>>),
+    /// A map from strings to the given type
+    Map(Box<Node<Type>>),
     /// A [`Path`] that could either refer to a Common Type or an Entity Type
     Ident(Path),
     /// A Record
     Record(Vec<Node<Annotated<AttrDecl>>>),
+    /// A union of two or more types
+    Union(Vec<Node<Type>>),
+    /// An instantiation of a parameterized common type, e.g., `Paginated<User>`
+    Generic {
+        /// Name of the parameterized common type
+        name: Path,
+        /// Type arguments (nonempty)
+        args: Vec<Node<Type>>,
+    },
+}
+
+impl Type {
+    /// Construct the type `path<args>`, which is a `Set` or `Map` type if
+    /// `path` is `Set` or `Map`, or else an instantiation of a parameterized
+    /// common type
+    pub fn applied(
+        path: Path,
+        args: NonEmpty<Node<Type>>,
+        loc: Loc,
+    ) -> Result<Node<Self>, UserError> {
+        let ty = match path.unqualified_name().map(AsRef::as_ref) {
+            Some("Set") => match (args.head, args.tail.as_slice()) {
+                (element, []) => Type::Set(Box::new(element)),
+                _ => {
+                    return Err(UserError::InvalidSetType(Node::with_source_loc(
+                        "`Set` takes a single element type".into(),
+                        loc,
+                    )))
+                }
+            },
+            #[cfg(feature = "maps")]
+            Some("Map") => match (args.head, <[_; 1]>::try_from(args.tail)) {
+                (
+                    Node {
+                        node: Type::Ident(key),
+                        ..
+                    },
+                    Ok([value]),
+                ) if key
+                    .unqualified_name()
+                    .is_some_and(|key| key.as_ref() == "String") =>
+                {
+                    Type::Map(Box::new(value))
+                }
+                _ => {
+                    return Err(UserError::InvalidMapType(Node::with_source_loc(
+                        "`Map` takes a `String` key type and a value type".into(),
+                        loc,
+                    )))
+                }
+            },
+            _ => Type::Generic {
+                name: path,
+                args: args.into(),
+            },
+        };
+        Ok(Node::with_source_loc(ty, loc))
+    }
 }
 
 /// Primitive Type Definitions
@@ -338,6 +418,26 @@ pub struct AttrDecl {
     pub required: bool,
     /// The type of this attribute
     pub ty: Node<Type>,
+    /// Restriction on the values of this attribute beyond its type, if any
+    pub refinement: Option<Node<Refinement>>,
+    /// Value this attribute takes when it is absent, if any
+    pub default: Option<Node<CedarValueJson>>,
+}
+
+/// Restriction on the values of an attribute beyond its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refinement {
+    /// `[min..max]`, for `Long` attributes. Either bound may be omitted.
+    Range {
+        /// Least allowed value
+        min: Option<i64>,
+        /// Greatest allowed value
+        max: Option<i64>,
+    },
+    /// `matching "pattern"`, for `String` attributes
+    Matching(SmolStr),
+    /// `satisfying "method"`, for extension-typed attributes
+    Satisfying(SmolStr),
 }
 
 /// The target of a [`PRAppDecl`]
@@ -49,6 +49,14 @@ pub enum UserError {
     ReservedIdentifierUsed(Node<SmolStr>),
     #[error("duplicate annotations: `{}`", .0)]
     DuplicateAnnotations(AnyId, Node<()>, Node<()>),
+    #[error("invalid value: {0}")]
+    InvalidValue(Node<SmolStr>),
+    #[error("invalid refinement: {0}")]
+    InvalidRefinement(Node<SmolStr>),
+    #[error("invalid map type: {0}")]
+    InvalidMapType(Node<SmolStr>),
+    #[error("invalid set type: {0}")]
+    InvalidSetType(Node<SmolStr>),
 }
 
 impl UserError {
@@ -60,6 +68,10 @@ impl UserError {
             Self::ReservedIdentifierUsed(n) => n.loc.as_ref().map(|loc| loc.span),
             // use the first occurrence as the primary source span
             Self::DuplicateAnnotations(_, n, _) => n.loc.as_ref().map(|loc| loc.span),
+            Self::InvalidValue(n) => n.loc.as_ref().map(|loc| loc.span),
+            Self::InvalidRefinement(n) => n.loc.as_ref().map(|loc| loc.span),
+            Self::InvalidMapType(n) => n.loc.as_ref().map(|loc| loc.span),
+            Self::InvalidSetType(n) => n.loc.as_ref().map(|loc| loc.span),
         }
     }
 }
@@ -87,6 +99,8 @@ static SCHEMA_TOKEN_CONFIG: LazyLock<ExpectedTokenConfig> = LazyLock::new(|| Exp
         ("IDENTIFIER", "identifier"),
         ("TAGS", "`tags`"),
         ("ENUM", "`enum`"),
+        ("IMPORT", "`import`"),
+        ("CONSTRAINTS", "`constraints`"),
     ]),
     impossible_tokens: HashSet::new(),
     special_identifier_tokens: HashSet::from([
@@ -105,6 +119,8 @@ static SCHEMA_TOKEN_CONFIG: LazyLock<ExpectedTokenConfig> = LazyLock::new(|| Exp
         "STRING",
         "BOOL",
         "ENUM",
+        "IMPORT",
+        "CONSTRAINTS",
     ]),
     identifier_sentinel: "IDENTIFIER",
     first_set_identifier_tokens: HashSet::from(["SET"]),
@@ -491,6 +507,10 @@ pub enum ToJsonSchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ReservedSchemaKeyword(#[from] ReservedSchemaKeyword),
+    /// Error raised when a parameterized common type is used incorrectly
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidGenericType(#[from] InvalidGenericType),
 }
 
 impl ToJsonSchemaError {
@@ -613,6 +633,74 @@ impl ToJsonSchemaError {
             loc,
         })
     }
+
+    pub(crate) fn invalid_generic_type(
+        name: &impl ToSmolStr,
+        kind: InvalidGenericTypeKind,
+        loc: Option<Loc>,
+    ) -> Self {
+        Self::InvalidGenericType(InvalidGenericType {
+            name: name.to_smolstr(),
+            kind,
+            loc,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{}", match .kind {
+    InvalidGenericTypeKind::NotGeneric => format!("`{name}` is not a parameterized common type"),
+    InvalidGenericTypeKind::MissingArguments => format!("missing type arguments for the parameterized common type `{name}`"),
+    InvalidGenericTypeKind::WrongArity { expected, found } => format!("`{name}` takes {expected} type argument(s), but {found} were given"),
+    InvalidGenericTypeKind::Cyclic => format!("the parameterized common type `{name}` is defined in terms of itself"),
+    InvalidGenericTypeKind::DuplicateParameter(param) => format!("duplicate type parameter `{param}` in the parameterized common type `{name}`"),
+    InvalidGenericTypeKind::AmbiguousReference { reference, namespace } => format!("`{reference}` in the definition of `{name}` refers to a different type when `{name}` is used in namespace `{namespace}`"),
+})]
+pub struct InvalidGenericType {
+    name: SmolStr,
+    kind: InvalidGenericTypeKind,
+    loc: Option<Loc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum InvalidGenericTypeKind {
+    /// A type which is not a parameterized common type was given type arguments
+    NotGeneric,
+    /// A parameterized common type was used without type arguments
+    MissingArguments,
+    /// A parameterized common type was given the wrong number of type arguments
+    WrongArity { expected: usize, found: usize },
+    /// A parameterized common type is defined in terms of itself
+    Cyclic,
+    /// A parameterized common type declares the same type parameter twice
+    DuplicateParameter(SmolStr),
+    /// A reference in the definition of a parameterized common type, declared
+    /// in the empty namespace, is shadowed by a declaration in the namespace
+    /// where the parameterized common type is used
+    AmbiguousReference {
+        reference: SmolStr,
+        namespace: SmolStr,
+    },
+}
+
+impl Diagnostic for InvalidGenericType {
+    impl_diagnostic_from_source_loc_opt_field!(loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match &self.kind {
+            InvalidGenericTypeKind::NotGeneric => Some(Box::new(format!(
+                "declare `{}` as a parameterized common type, like `type {}<T> = ...;`. The built-in parameterized types are `Set<T>` and `Map<String, T>`",
+                self.name, self.name
+            ))),
+            InvalidGenericTypeKind::Cyclic => Some(Box::new(
+                "parameterized common types are expanded where they are used, so they cannot be recursive",
+            )),
+            InvalidGenericTypeKind::AmbiguousReference { reference, .. } => Some(Box::new(format!(
+                "rename `{reference}` or the declaration shadowing it, or refer to it with a fully-qualified name"
+            ))),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
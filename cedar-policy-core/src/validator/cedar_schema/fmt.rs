@@ -17,7 +17,10 @@
 //! `Display` implementations for formatting a [`json_schema::Fragment`] in the
 //! Cedar schema syntax
 
-use std::{collections::HashSet, fmt::Display};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Display,
+};
 
 use itertools::Itertools;
 use miette::Diagnostic;
@@ -25,7 +28,8 @@ use nonempty::NonEmpty;
 use smol_str::{format_smolstr, SmolStr};
 use thiserror::Error;
 
-use crate::ast::{is_normalized_ident, InternalName};
+use crate::ast::{is_normalized_ident, EntityUID, InternalName};
+use crate::entities::CedarValueJson;
 use crate::validator::{json_schema, RawName};
 
 /// Number of spaces of indentation per level in the Cedarschema file
@@ -112,7 +116,28 @@ impl<N: Display> IndentedDisplay for json_schema::NamespaceDefinition<N> {
     ) -> std::fmt::Result {
         let total_len = self.common_types.len() + self.entity_types.len() + self.actions.len();
 
-        for (i, (n, ty)) in self.common_types.iter().enumerate() {
+        let common_types = dependency_order(&self.common_types, |ty| {
+            let mut names = Vec::new();
+            referenced_type_names(&ty.ty, &mut names);
+            names
+        });
+        let entity_types = dependency_order(&self.entity_types, |ty| match &ty.kind {
+            json_schema::EntityTypeKind::Standard(ty) => ty
+                .member_of_types
+                .iter()
+                .map(|parent| format_smolstr!("{parent}"))
+                .collect(),
+            json_schema::EntityTypeKind::Enum { .. } => Vec::new(),
+        });
+        let actions = dependency_order(&self.actions, |a| {
+            a.member_of
+                .iter()
+                .flatten()
+                .map(|parent| parent.id.clone())
+                .collect()
+        });
+
+        for (i, (n, ty)) in common_types.into_iter().enumerate() {
             ty.annotations.fmt_indented(f, base_indentation.len())?;
             writeln!(
                 f,
@@ -125,7 +150,7 @@ impl<N: Display> IndentedDisplay for json_schema::NamespaceDefinition<N> {
                 writeln!(f)?
             }
         }
-        for (i, (n, ty)) in self.entity_types.iter().enumerate() {
+        for (i, (n, ty)) in entity_types.into_iter().enumerate() {
             ty.annotations.fmt_indented(f, base_indentation.len())?;
             writeln!(
                 f,
@@ -137,7 +162,7 @@ impl<N: Display> IndentedDisplay for json_schema::NamespaceDefinition<N> {
                 writeln!(f)?
             }
         }
-        for (i, (n, a)) in self.actions.iter().enumerate() {
+        for (i, (n, a)) in actions.into_iter().enumerate() {
             a.annotations.fmt_indented(f, base_indentation.len())?;
             writeln!(
                 f,
@@ -154,6 +179,108 @@ impl<N: Display> IndentedDisplay for json_schema::NamespaceDefinition<N> {
     }
 }
 
+/// Order the declarations in `decls` so that each one comes after the
+/// declarations it refers to, as given by `deps`, and otherwise in
+/// alphabetical order. This makes the output read top-down, e.g., with parent
+/// entity types and action groups before their members. References are matched
+/// by their last path segment, and cycles are broken arbitrarily; the order of
+/// declarations doesn't matter to the meaning of a schema.
+fn dependency_order<K: Ord + Display, V>(
+    decls: &BTreeMap<K, V>,
+    deps: impl Fn(&V) -> Vec<SmolStr>,
+) -> Vec<(&K, &V)> {
+    fn visit<'a, K: Ord + Display, V>(
+        key: &'a K,
+        value: &'a V,
+        decls: &'a BTreeMap<K, V>,
+        deps: &impl Fn(&V) -> Vec<SmolStr>,
+        visited: &mut BTreeSet<&'a K>,
+        order: &mut Vec<(&'a K, &'a V)>,
+    ) {
+        if !visited.insert(key) {
+            return;
+        }
+        for dep in deps(value) {
+            let basename = dep.rsplit("::").next().unwrap_or(&dep);
+            if let Some((dep_key, dep_value)) =
+                decls.iter().find(|(k, _)| k.to_string() == basename)
+            {
+                visit(dep_key, dep_value, decls, deps, visited, order);
+            }
+        }
+        order.push((key, value));
+    }
+
+    let mut visited = BTreeSet::new();
+    let mut order = Vec::with_capacity(decls.len());
+    for (key, value) in decls {
+        visit(key, value, decls, &deps, &mut visited, &mut order);
+    }
+    order
+}
+
+/// Collect the names of the common and entity types that `ty` refers to
+pub(crate) fn referenced_type_names<N: Display>(
+    ty: &json_schema::Type<N>,
+    names: &mut Vec<SmolStr>,
+) {
+    match ty {
+        json_schema::Type::CommonTypeRef { type_name, .. } => {
+            names.push(format_smolstr!("{type_name}"));
+        }
+        json_schema::Type::Type { ty, .. } => match ty {
+            json_schema::TypeVariant::Entity { name: type_name }
+            | json_schema::TypeVariant::EntityOrCommon { type_name } => {
+                names.push(format_smolstr!("{type_name}"));
+            }
+            json_schema::TypeVariant::Set { element }
+            | json_schema::TypeVariant::Map { element } => {
+                referenced_type_names(element, names);
+            }
+            json_schema::TypeVariant::Record(rty) => {
+                for attr in rty.attributes.values() {
+                    referenced_type_names(&attr.ty, names);
+                }
+            }
+            json_schema::TypeVariant::Union { members } => {
+                for member in members {
+                    referenced_type_names(member, names);
+                }
+            }
+            json_schema::TypeVariant::String
+            | json_schema::TypeVariant::Long
+            | json_schema::TypeVariant::Boolean
+            | json_schema::TypeVariant::Extension { .. } => {}
+        },
+    }
+}
+
+/// Does `ty` contain a record type with `additionalAttributes`, which can't be
+/// expressed in the Cedar schema syntax?
+fn has_open_record<N>(ty: &json_schema::Type<N>) -> bool {
+    match ty {
+        json_schema::Type::CommonTypeRef { .. } => false,
+        json_schema::Type::Type { ty, .. } => match ty {
+            json_schema::TypeVariant::Set { element }
+            | json_schema::TypeVariant::Map { element } => has_open_record(element),
+            json_schema::TypeVariant::Record(rty) => {
+                rty.additional_attributes
+                    || rty
+                        .attributes
+                        .values()
+                        .any(|attr| has_open_record(&attr.ty))
+            }
+            json_schema::TypeVariant::Union { members } => members.iter().any(has_open_record),
+            json_schema::TypeVariant::String
+            | json_schema::TypeVariant::Long
+            | json_schema::TypeVariant::Boolean
+            | json_schema::TypeVariant::Entity { .. }
+            | json_schema::TypeVariant::EntityOrCommon { .. }
+            | json_schema::TypeVariant::Extension { .. } => false,
+        },
+    }
+}
+
 impl<N: Display> Display for json_schema::Type<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.fmt_indented(f, &BaseIndentation::none())
@@ -179,7 +306,22 @@ impl<N: Display> IndentedDisplay for json_schema::Type<N> {
                 json_schema::TypeVariant::Set { element } => {
                     write!(f, "Set<{}>", Indented(element.as_ref(), base_indentation))
                 } // It is possible to do Set<{"foo": String}>
+                json_schema::TypeVariant::Map { element } => {
+                    write!(
+                        f,
+                        "Map<String, {}>",
+                        Indented(element.as_ref(), base_indentation)
+                    )
+                }
                 json_schema::TypeVariant::String => write!(f, "__cedar::String"),
+                json_schema::TypeVariant::Union { members } => write!(
+                    f,
+                    "{}",
+                    members
+                        .iter()
+                        .map(|member| Indented(member, base_indentation))
+                        .join(" | ")
+                ),
             },
             json_schema::Type::CommonTypeRef { type_name, .. } => write!(f, "{type_name}"),
         }
@@ -204,14 +346,30 @@ impl<N: Display> IndentedDisplay for json_schema::RecordType<N> {
             ty.annotations.fmt_indented(f, member_indentation.len())?;
             writeln!(
                 f,
-                "{member_indentation}{}{}: {}{}",
-                if is_normalized_ident(n) {
-                    SmolStr::clone(n)
-                } else {
-                    format_smolstr!("\"{}\"", n.escape_debug())
-                },
+                "{member_indentation}{}{}: {}{}{}{}{}{}",
+                fmt_attr_name(n),
                 if ty.required { "" } else { "?" },
                 Indented(&ty.ty, &member_indentation),
+                match &ty.range {
+                    Some(json_schema::LongRange { min, max }) => format!(
+                        "[{}..{}]",
+                        min.map(|min| min.to_string()).unwrap_or_default(),
+                        max.map(|max| max.to_string()).unwrap_or_default()
+                    ),
+                    None => String::new(),
+                },
+                match &ty.pattern {
+                    Some(pattern) => format!(" matching \"{}\"", pattern.escape_debug()),
+                    None => String::new(),
+                },
+                match &ty.satisfying {
+                    Some(method) => format!(" satisfying \"{}\"", method.escape_debug()),
+                    None => String::new(),
+                },
+                match &ty.default {
+                    Some(default) => format!(" = {}", CedarSyntaxValue(default)),
+                    None => String::new(),
+                },
                 // TODO: Always print trailing commas when
                 // https://github.com/cedar-policy/rfcs/blob/main/text/0071-trailing-commas.md
                 // has been implemented
@@ -227,6 +385,58 @@ impl<N: Display> IndentedDisplay for json_schema::RecordType<N> {
     }
 }
 
+fn fmt_attr_name(n: &SmolStr) -> SmolStr {
+    if is_normalized_ident(n) {
+        SmolStr::clone(n)
+    } else {
+        format_smolstr!("\"{}\"", n.escape_debug())
+    }
+}
+
+/// Formats a default attribute value in the Cedar schema syntax
+struct CedarSyntaxValue<'a>(&'a CedarValueJson);
+
+impl Display for CedarSyntaxValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            CedarValueJson::Bool(b) => write!(f, "{b}"),
+            CedarValueJson::Long(i) => write!(f, "{i}"),
+            CedarValueJson::String(s) => write!(f, "\"{}\"", s.escape_debug()),
+            CedarValueJson::Set(vs) => {
+                write!(f, "[{}]", vs.iter().map(CedarSyntaxValue).join(", "))
+            }
+            CedarValueJson::Record(attrs) => write!(
+                f,
+                "{{{}}}",
+                attrs
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", fmt_attr_name(k), CedarSyntaxValue(v)))
+                    .join(", ")
+            ),
+            CedarValueJson::ExtnEscape { __extn } => write!(
+                f,
+                "{}({})",
+                __extn.fn_str(),
+                __extn.args().iter().map(CedarSyntaxValue).join(", ")
+            ),
+            CedarValueJson::EntityEscape { __entity } => {
+                match EntityUID::try_from(__entity.clone()) {
+                    Ok(euid) => write!(f, "{euid}"),
+                    Err(_) => write_json(f, self.0),
+                }
+            }
+            // These values are never valid default values, and have no
+            // representation in the Cedar schema syntax
+            CedarValueJson::ExprEscape { .. } | CedarValueJson::Null => write_json(f, self.0),
+        }
+    }
+}
+
+fn write_json(f: &mut std::fmt::Formatter<'_>, v: &CedarValueJson) -> std::fmt::Result {
+    let json = serde_json::to_string(v).map_err(|_| std::fmt::Error)?;
+    f.write_str(&json)
+}
+
 fn fmt_non_empty_slice<T: Display>(
     f: &mut std::fmt::Formatter<'_>,
     (head, tail): (&T, &[T]),
@@ -291,6 +501,17 @@ impl<N: Display> IndentedDisplay for json_schema::StandardEntityType<N> {
             write!(f, " tags {tags}")?;
         }
 
+        if !self.constraints.is_empty() {
+            write!(
+                f,
+                " constraints [{}]",
+                self.constraints
+                    .iter()
+                    .map(|c| format!("\"{}\"", c.escape_debug()))
+                    .join(", ")
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -360,6 +581,11 @@ pub enum ToCedarSchemaSyntaxError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     UnconvertibleEntityTypeShape(#[from] UnconvertibleEntityTypeShapeError),
+    /// Declarations using features that the Cedar schema syntax can't express
+    /// prevented the conversion
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    UnrepresentableFeatures(#[from] UnrepresentableFeaturesError),
 }
 
 /// Duplicate names were found in the schema
@@ -408,8 +634,41 @@ impl UnconvertibleEntityTypeShapeError {
     }
 }
 
+/// Declarations using features that the Cedar schema syntax can't express were
+/// found in the schema
+//
+// This is NOT a publicly exported error type.
+#[derive(Debug, Error)]
+#[error("The following declarations use features that cannot be expressed in Cedar schema syntax: [{}]", .names.iter().join(", "))]
+pub struct UnrepresentableFeaturesError {
+    /// Names of the common types, entity types and actions using these
+    /// features
+    names: NonEmpty<SmolStr>,
+}
+
+impl Diagnostic for UnrepresentableFeaturesError {
+    fn help(&self) -> Option<Box<dyn Display + '_>> {
+        Some(Box::new("The Cedar schema syntax supports neither record types with `additionalAttributes` nor action `attributes`."))
+    }
+}
+
+impl UnrepresentableFeaturesError {
+    /// Get the names of the common types, entity types and actions using
+    /// features the Cedar schema syntax can't express
+    pub fn names(&self) -> impl Iterator<Item = &SmolStr> {
+        self.names.iter()
+    }
+}
+
 /// Convert a [`json_schema::Fragment`] to a string containing the Cedar schema syntax
 ///
+/// The conversion is lossless: parsing the result gives back `json_schema`,
+/// including its annotations. If `json_schema` uses features that the Cedar
+/// schema syntax can't express, this returns an error rather than dropping
+/// them. Declarations are printed in alphabetical order, except that
+/// declarations referred to by other declarations (e.g., parent entity types
+/// and action groups) are printed first.
+///
 /// As of this writing, this existing code throws an error if any
 /// fully-qualified name in a non-empty namespace is a valid common type and
 /// also a valid entity type.
@@ -483,6 +742,50 @@ pub fn json_schema_to_cedar_schema_str<N: Display>(
         .into());
     }
 
+    // Check for records with `additionalAttributes` and for action
+    // attributes, which the Cedar schema syntax has no way to express
+    let mut unrepresentable = vec![];
+    for (name, ns) in json_schema.0.iter() {
+        let qualify = |basename: &dyn Display| match name {
+            Some(name) => format_smolstr!("{name}::{basename}"),
+            None => format_smolstr!("{basename}"),
+        };
+        unrepresentable.extend(
+            ns.common_types
+                .iter()
+                .filter(|(_, ty)| has_open_record(&ty.ty))
+                .map(|(ty_name, _)| qualify(ty_name)),
+        );
+        unrepresentable.extend(
+            ns.entity_types
+                .iter()
+                .filter(|(_, ty)| match &ty.kind {
+                    json_schema::EntityTypeKind::Standard(ty) => {
+                        has_open_record(&ty.shape.0)
+                            || ty.tags.as_ref().is_some_and(has_open_record)
+                    }
+                    json_schema::EntityTypeKind::Enum { .. } => false,
+                })
+                .map(|(ty_name, _)| qualify(ty_name)),
+        );
+        unrepresentable.extend(
+            ns.actions
+                .iter()
+                .filter(|(_, a)| {
+                    a.attributes.as_ref().is_some_and(|attrs| !attrs.is_empty())
+                        || a.applies_to
+                            .as_ref()
+                            .is_some_and(|spec| has_open_record(&spec.context.0))
+                })
+                .map(|(action, _)| {
+                    qualify(&format_smolstr!("Action::\"{}\"", action.escape_debug()))
+                }),
+        );
+    }
+    if let Some(names) = NonEmpty::from_vec(unrepresentable) {
+        return Err(UnrepresentableFeaturesError { names }.into());
+    }
+
     Ok(json_schema.to_string())
 }
 
@@ -499,6 +802,7 @@ mod tests {
     use cool_asserts::assert_matches;
     use nonempty::NonEmpty;
     use similar_asserts::assert_eq;
+    use smol_str::SmolStr;
 
     #[track_caller]
     fn test_round_trip(src: &str) {
@@ -842,4 +1146,140 @@ namespace TinyTodo {
             assert_eq!(names, expected_names)
         });
     }
+
+    #[test]
+    fn declarations_are_ordered_by_dependencies() {
+        let schema_json = serde_json::json!(
+            {
+                "": {
+                    "commonTypes": {
+                        "A": {
+                            "type": "Record",
+                            "attributes": { "b": { "type": "B" } }
+                        },
+                        "B": { "type": "Long" }
+                    },
+                    "entityTypes": {
+                        "Alpha": {},
+                        "Doc": { "memberOfTypes": ["Folder"] },
+                        "Folder": { "memberOfTypes": ["Root"] },
+                        "Root": {}
+                    },
+                    "actions": {
+                        "write": { "memberOf": [{ "id": "writeAll" }] },
+                        "writeAll": {}
+                    }
+                }
+            }
+        );
+        let fragment: json_schema::Fragment<RawName> = serde_json::from_value(schema_json).unwrap();
+        assert_eq!(
+            fragment.to_cedarschema().unwrap(),
+            r#"type B = __cedar::Long;
+
+type A = {
+  b: B
+};
+
+entity Alpha;
+
+entity Root;
+
+entity Folder in [Root];
+
+entity Doc in [Folder];
+
+action "writeAll";
+
+action "write" in [Action::"writeAll"];
+"#
+        );
+    }
+
+    #[test]
+    fn json_schema_roundtrips_through_cedar_schema() {
+        let schema_json = serde_json::json!(
+            {
+                "App": {
+                    "annotations": { "doc": "the app" },
+                    "commonTypes": {
+                        "Address": {
+                            "type": "Record",
+                            "attributes": {
+                                "street": { "type": "EntityOrCommon", "name": "String" }
+                            },
+                            "annotations": { "doc": "an address" }
+                        }
+                    },
+                    "entityTypes": {
+                        "User": {
+                            "memberOfTypes": ["Group"],
+                            "shape": {
+                                "type": "Record",
+                                "attributes": {
+                                    "address": {
+                                        "type": "EntityOrCommon",
+                                        "name": "Address",
+                                        "required": false,
+                                        "annotations": { "deprecated": "" }
+                                    }
+                                }
+                            },
+                            "annotations": { "doc": "a user" }
+                        },
+                        "Group": {}
+                    },
+                    "actions": {
+                        "view": {
+                            "appliesTo": {
+                                "principalTypes": ["User"],
+                                "resourceTypes": ["Group"]
+                            },
+                            "annotations": { "doc": "view a group" }
+                        }
+                    }
+                }
+            }
+        );
+        let fragment: json_schema::Fragment<RawName> =
+            serde_json::from_value(schema_json.clone()).unwrap();
+        let cedar_schema = fragment.to_cedarschema().unwrap();
+        let (parsed, _) = parse_cedar_schema_fragment(&cedar_schema, Extensions::none()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), schema_json);
+    }
+
+    #[test]
+    fn unrepresentable_features_fail_conversion() {
+        let schema_json = serde_json::json!(
+            {
+                "NS": {
+                    "commonTypes": {
+                        "Open": {
+                            "type": "Record",
+                            "attributes": {},
+                            "additionalAttributes": true
+                        }
+                    },
+                    "entityTypes": {
+                        "User": {}
+                    },
+                    "actions": {
+                        "view": {
+                            "attributes": { "level": 1 }
+                        }
+                    }
+                }
+            }
+        );
+        let fragment: json_schema::Fragment<RawName> = serde_json::from_value(schema_json).unwrap();
+        assert_matches!(
+            fragment.to_cedarschema(),
+            Err(crate::validator::cedar_schema::fmt::ToCedarSchemaSyntaxError::UnrepresentableFeatures(err)) => {
+                assert_eq!(
+                    err.names().map(SmolStr::as_str).collect::<Vec<_>>(),
+                    vec!["NS::Open", r#"NS::Action::"view""#]
+                );
+            }
+        );
+    }
 }
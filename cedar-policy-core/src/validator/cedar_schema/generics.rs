@@ -0,0 +1,408 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Expansion of parameterized common types, e.g.,
+//! `type Paginated<T> = { items: Set<T>, next?: String };`.
+//!
+//! The JSON schema syntax has no parameterized types, so before a schema is
+//! converted to it, each instantiation of a parameterized common type, e.g.,
+//! `Paginated<User>`, is replaced by the definition of the parameterized type
+//! with the type arguments substituted for its parameters.
+
+use std::collections::{HashMap, HashSet};
+
+use itertools::Either;
+use nonempty::NonEmpty;
+use smol_str::ToSmolStr;
+
+use crate::{ast::Id, parser::Node};
+
+use super::{
+    ast::{Annotated, AppDecl, AttrDecl, Declaration, EntityDecl, Namespace, Path, Type, TypeDecl},
+    err::{InvalidGenericTypeKind, ToJsonSchemaError, ToJsonSchemaErrors},
+    to_json_schema::collect_all_errors,
+};
+
+/// The path of a namespace, which is empty for the empty namespace
+type NamespaceId = Vec<Id>;
+
+/// A parameterized common type, identified by the namespace it is declared
+/// in and its name
+type GenericId = (NamespaceId, Id);
+
+/// The declaration of a parameterized common type
+struct Generic {
+    params: Vec<Node<Id>>,
+    def: Node<Type>,
+}
+
+struct Expander {
+    /// The parameterized common types declared in the schema
+    generics: HashMap<GenericId, Generic>,
+    /// The names of the entity types and common types declared in each
+    /// namespace, including parameterized common types
+    declared: HashMap<NamespaceId, HashSet<Id>>,
+}
+
+/// Replace each instantiation of a parameterized common type in `namespaces`
+/// by its definition, and remove the declarations of parameterized common
+/// types, which have no equivalent in the JSON schema syntax.
+pub(super) fn expand_generic_types(
+    mut namespaces: Vec<Annotated<Namespace>>,
+) -> Result<Vec<Annotated<Namespace>>, ToJsonSchemaErrors> {
+    let mut expander = Expander {
+        generics: HashMap::new(),
+        declared: HashMap::new(),
+    };
+    let mut errs = Vec::new();
+    for ns in &mut namespaces {
+        let ns_id = namespace_id(&ns.data);
+        let declared = expander.declared.entry(ns_id.clone()).or_default();
+        for decl in &ns.data.decls {
+            match &decl.data.node {
+                Declaration::Entity(e) => declared.extend(e.names().map(|name| name.node.clone())),
+                Declaration::Type(t) => {
+                    declared.insert(t.name.node.clone());
+                }
+                Declaration::Action(_) => {}
+            }
+        }
+        let (generics, decls): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut ns.data.decls).into_iter().partition(
+                |decl| matches!(&decl.data.node, Declaration::Type(t) if !t.params.is_empty()),
+            );
+        ns.data.decls = decls;
+        for decl in generics {
+            if let Declaration::Type(TypeDecl { name, params, def }) = decl.data.node {
+                let mut seen = HashSet::new();
+                for param in &params {
+                    if !seen.insert(&param.node) {
+                        errs.push(ToJsonSchemaError::invalid_generic_type(
+                            &name.node,
+                            InvalidGenericTypeKind::DuplicateParameter(param.node.to_smolstr()),
+                            param.loc.clone(),
+                        ));
+                    }
+                }
+                expander
+                    .generics
+                    .insert((ns_id.clone(), name.node), Generic { params, def });
+            }
+        }
+    }
+    if let Some(errs) = NonEmpty::from_vec(errs) {
+        return Err(ToJsonSchemaErrors::new(errs));
+    }
+    let namespaces = collect_all_errors(
+        namespaces
+            .into_iter()
+            .map(|ns| expander.expand_namespace(ns)),
+    )?
+    .collect();
+    Ok(namespaces)
+}
+
+fn namespace_id(ns: &Namespace) -> NamespaceId {
+    ns.name
+        .as_ref()
+        .map(|name| name.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+impl Expander {
+    fn expand_namespace(
+        &self,
+        mut ns: Annotated<Namespace>,
+    ) -> Result<Annotated<Namespace>, ToJsonSchemaErrors> {
+        let ns_id = namespace_id(&ns.data);
+        ns.data.decls =
+            collect_all_errors(std::mem::take(&mut ns.data.decls).into_iter().map(|decl| {
+                self.expand_decl(decl, &ns_id)
+                    .map_err(ToJsonSchemaErrors::from)
+            }))?
+            .collect();
+        Ok(ns)
+    }
+
+    fn expand_decl(
+        &self,
+        mut decl: Annotated<Node<Declaration>>,
+        ns: &[Id],
+    ) -> Result<Annotated<Node<Declaration>>, ToJsonSchemaError> {
+        match &mut decl.data.node {
+            Declaration::Entity(EntityDecl::Standard(e)) => {
+                e.attrs.node = self.expand_attrs(std::mem::take(&mut e.attrs.node), ns)?;
+                e.tags = e
+                    .tags
+                    .take()
+                    .map(|tags| self.expand(tags, ns))
+                    .transpose()?;
+            }
+            Declaration::Entity(EntityDecl::Enum(_)) => {}
+            Declaration::Action(a) => {
+                for app_decl in a
+                    .app_decls
+                    .iter_mut()
+                    .flat_map(|decls| decls.node.iter_mut())
+                {
+                    match &mut app_decl.node {
+                        AppDecl::Context(Either::Left(path)) => self.check_not_generic(path, ns)?,
+                        AppDecl::Context(Either::Right(attrs)) => {
+                            attrs.node = self.expand_attrs(std::mem::take(&mut attrs.node), ns)?;
+                        }
+                        AppDecl::PR(_) => {}
+                    }
+                }
+            }
+            Declaration::Type(t) => {
+                let def = std::mem::replace(
+                    &mut t.def,
+                    Node::with_maybe_source_loc(Type::Record(vec![]), None),
+                );
+                t.def = self.expand(def, ns)?;
+            }
+        }
+        Ok(decl)
+    }
+
+    fn expand_attrs(
+        &self,
+        attrs: Vec<Node<Annotated<AttrDecl>>>,
+        ns: &[Id],
+    ) -> Result<Vec<Node<Annotated<AttrDecl>>>, ToJsonSchemaError> {
+        attrs
+            .into_iter()
+            .map(|mut attr| {
+                let ty = std::mem::replace(
+                    &mut attr.node.data.ty,
+                    Node::with_maybe_source_loc(Type::Record(vec![]), None),
+                );
+                attr.node.data.ty = self.expand(ty, ns)?;
+                Ok(attr)
+            })
+            .collect()
+    }
+
+    /// Expand the instantiations of parameterized common types in `ty`,
+    /// which appears in namespace `ns`
+    fn expand(&self, ty: Node<Type>, ns: &[Id]) -> Result<Node<Type>, ToJsonSchemaError> {
+        self.expand_with_stack(ty, ns, &mut Vec::new())
+    }
+
+    /// Like [`Expander::expand()`], where `stack` holds the parameterized
+    /// common types being expanded, to detect cyclic definitions
+    fn expand_with_stack(
+        &self,
+        ty: Node<Type>,
+        ns: &[Id],
+        stack: &mut Vec<GenericId>,
+    ) -> Result<Node<Type>, ToJsonSchemaError> {
+        let Node { node, loc } = ty;
+        let node = match node {
+            Type::Set(t) => Type::Set(Box::new(self.expand_with_stack(*t, ns, stack)?)),
+            Type::Map(t) => Type::Map(Box::new(self.expand_with_stack(*t, ns, stack)?)),
+            Type::Ident(path) => {
+                self.check_not_generic(&path, ns)?;
+                Type::Ident(path)
+            }
+            Type::Record(attrs) => Type::Record(
+                attrs
+                    .into_iter()
+                    .map(|mut attr| {
+                        let ty = std::mem::replace(
+                            &mut attr.node.data.ty,
+                            Node::with_maybe_source_loc(Type::Record(vec![]), None),
+                        );
+                        attr.node.data.ty = self.expand_with_stack(ty, ns, stack)?;
+                        Ok(attr)
+                    })
+                    .collect::<Result<_, ToJsonSchemaError>>()?,
+            ),
+            Type::Union(members) => Type::Union(
+                members
+                    .into_iter()
+                    .map(|member| self.expand_with_stack(member, ns, stack))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Type::Generic { name, args } => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.expand_with_stack(arg, ns, stack))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (id, generic) = self.resolve(&name, ns).ok_or_else(|| {
+                    ToJsonSchemaError::invalid_generic_type(
+                        &name,
+                        InvalidGenericTypeKind::NotGeneric,
+                        loc.clone(),
+                    )
+                })?;
+                if args.len() != generic.params.len() {
+                    return Err(ToJsonSchemaError::invalid_generic_type(
+                        &name,
+                        InvalidGenericTypeKind::WrongArity {
+                            expected: generic.params.len(),
+                            found: args.len(),
+                        },
+                        loc,
+                    ));
+                }
+                if stack.contains(&id) {
+                    return Err(ToJsonSchemaError::invalid_generic_type(
+                        &name,
+                        InvalidGenericTypeKind::Cyclic,
+                        loc,
+                    ));
+                }
+                let subst = generic
+                    .params
+                    .iter()
+                    .map(|param| &param.node)
+                    .zip(args)
+                    .collect();
+                let body = self.instantiate(generic.def.clone(), &id, &subst, ns)?;
+                stack.push(id);
+                let body = self.expand_with_stack(body, ns, stack)?;
+                stack.pop();
+                // Keep the location of the instantiation
+                body.node
+            }
+        };
+        Ok(Node { node, loc })
+    }
+
+    /// Get the parameterized common type `path` refers to in namespace `ns`,
+    /// if any. Like references to other common types, an unqualified name
+    /// refers to a declaration in `ns`, or else in the empty namespace.
+    fn resolve(&self, path: &Path, ns: &[Id]) -> Option<(GenericId, &Generic)> {
+        let basename = path.basename().clone();
+        let id = if !path.namespace().is_empty() {
+            (path.namespace().to_vec(), basename)
+        } else if self.is_declared(ns, &basename) {
+            (ns.to_vec(), basename)
+        } else {
+            (vec![], basename)
+        };
+        let generic = self.generics.get(&id)?;
+        Some((id, generic))
+    }
+
+    fn check_not_generic(&self, path: &Path, ns: &[Id]) -> Result<(), ToJsonSchemaError> {
+        match self.resolve(path, ns) {
+            Some(_) => Err(ToJsonSchemaError::invalid_generic_type(
+                path,
+                InvalidGenericTypeKind::MissingArguments,
+                path.loc().cloned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn is_declared(&self, ns: &[Id], name: &Id) -> bool {
+        self.declared
+            .get(ns)
+            .is_some_and(|names| names.contains(name))
+    }
+
+    /// Substitute the type arguments `subst` for the type parameters in the
+    /// definition `ty` of the parameterized common type `generic`, which is
+    /// instantiated in namespace `ns`. Other references to types declared in
+    /// the namespace of `generic` are qualified with that namespace, so that
+    /// they refer to the same types in `ns`.
+    fn instantiate(
+        &self,
+        ty: Node<Type>,
+        generic: &GenericId,
+        subst: &HashMap<&Id, Node<Type>>,
+        ns: &[Id],
+    ) -> Result<Node<Type>, ToJsonSchemaError> {
+        let Node { node, loc } = ty;
+        let node = match node {
+            Type::Ident(path) => match path.unqualified_name().and_then(|id| subst.get(id)) {
+                Some(arg) => return Ok(arg.clone()),
+                None => Type::Ident(self.qualify(path, generic, ns)?),
+            },
+            Type::Generic { name, args } => Type::Generic {
+                name: self.qualify(name, generic, ns)?,
+                args: args
+                    .into_iter()
+                    .map(|arg| self.instantiate(arg, generic, subst, ns))
+                    .collect::<Result<_, _>>()?,
+            },
+            Type::Set(t) => Type::Set(Box::new(self.instantiate(*t, generic, subst, ns)?)),
+            Type::Map(t) => Type::Map(Box::new(self.instantiate(*t, generic, subst, ns)?)),
+            Type::Record(attrs) => Type::Record(
+                attrs
+                    .into_iter()
+                    .map(|mut attr| {
+                        let ty = std::mem::replace(
+                            &mut attr.node.data.ty,
+                            Node::with_maybe_source_loc(Type::Record(vec![]), None),
+                        );
+                        attr.node.data.ty = self.instantiate(ty, generic, subst, ns)?;
+                        Ok(attr)
+                    })
+                    .collect::<Result<_, ToJsonSchemaError>>()?,
+            ),
+            Type::Union(members) => Type::Union(
+                members
+                    .into_iter()
+                    .map(|member| self.instantiate(member, generic, subst, ns))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(Node { node, loc })
+    }
+
+    /// Qualify the reference `path` in the definition of the parameterized
+    /// common type `generic`, so that it refers to the same type when the
+    /// definition is instantiated in namespace `ns`
+    fn qualify(
+        &self,
+        path: Path,
+        generic: &GenericId,
+        ns: &[Id],
+    ) -> Result<Path, ToJsonSchemaError> {
+        let (generic_ns, generic_name) = generic;
+        let Some(name) = path.unqualified_name() else {
+            return Ok(path);
+        };
+        if !generic_ns.is_empty() && self.is_declared(generic_ns, name) {
+            return Ok(Path::new(
+                name.clone(),
+                generic_ns.iter().cloned(),
+                path.loc().cloned(),
+            ));
+        }
+        // A type in the empty namespace, or a builtin type, can't be referred
+        // to from `ns` if `ns` declares a type with the same name
+        if generic_ns.as_slice() != ns && self.is_declared(ns, name) {
+            return Err(ToJsonSchemaError::invalid_generic_type(
+                generic_name,
+                InvalidGenericTypeKind::AmbiguousReference {
+                    reference: name.to_smolstr(),
+                    namespace: ns
+                        .iter()
+                        .map(ToSmolStr::to_smolstr)
+                        .collect::<Vec<_>>()
+                        .join("::")
+                        .into(),
+                },
+                path.loc().cloned(),
+            ));
+        }
+        Ok(path)
+    }
+}
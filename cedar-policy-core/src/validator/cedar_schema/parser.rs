@@ -28,7 +28,9 @@ use super::{
     to_json_schema::cedar_schema_to_json_schema,
 };
 use crate::extensions::Extensions;
+use crate::parser::Node;
 use crate::validator::json_schema;
+use smol_str::SmolStr;
 
 lalrpop_mod!(
     #[allow(warnings, unused, missing_docs, missing_debug_implementations)]
@@ -82,6 +84,8 @@ fn parse_collect_errors<'a, P, T>(
 
 // Thread-safe "global" parsers, initialized at first use
 static SCHEMA_PARSER: LazyLock<grammar::SchemaParser> = LazyLock::new(grammar::SchemaParser::new);
+static SCHEMA_WITH_IMPORTS_PARSER: LazyLock<grammar::SchemaWithImportsParser> =
+    LazyLock::new(grammar::SchemaWithImportsParser::new);
 
 /// Parse errors for parsing a schema in the Cedar syntax
 //
@@ -116,6 +120,30 @@ pub fn parse_cedar_schema_fragment<'a>(
     Ok(tuple)
 }
 
+/// Parse a schema fragment, in the Cedar syntax, which may begin with
+/// `import` declarations, into a [`json_schema::Fragment`], possibly
+/// generating warnings. Also returns the imported names, in order.
+#[expect(clippy::type_complexity, reason = "judged to be readable enough")]
+pub fn parse_cedar_schema_fragment_with_imports<'a>(
+    src: &str,
+    extensions: &Extensions<'a>,
+) -> Result<
+    (
+        Vec<Node<SmolStr>>,
+        json_schema::Fragment<crate::validator::RawName>,
+        impl Iterator<Item = SchemaWarning> + 'a,
+    ),
+    CedarSchemaParseErrors,
+> {
+    let (imports, ast): (Vec<Node<SmolStr>>, Schema) = parse_collect_errors(
+        &*SCHEMA_WITH_IMPORTS_PARSER,
+        grammar::SchemaWithImportsParser::parse,
+        src,
+    )?;
+    let (fragment, warnings) = cedar_schema_to_json_schema(ast, extensions)?;
+    Ok((imports, fragment, warnings))
+}
+
 /// Parse schema from text
 pub fn parse_schema(text: &str) -> Result<Schema, err::ParseErrors> {
     parse_collect_errors(&*SCHEMA_PARSER, grammar::SchemaParser::parse, text)
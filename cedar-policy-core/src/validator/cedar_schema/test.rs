@@ -504,6 +504,7 @@ namespace Baz {action "Foo" appliesTo {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -2828,6 +2829,564 @@ mod entity_tags {
     }
 }
 
+mod default_values {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::ast::{Context, EntityUID, RestrictedExpr};
+    use crate::entities::{ContextJsonParser, EntityJsonParser, TCComputation};
+    use crate::extensions::Extensions;
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{
+        context_schema_for_action, json_schema, CedarSchemaError, CoreSchema, SchemaError,
+        ValidatorSchema,
+    };
+
+    const SCHEMA: &str = r#"
+    entity User {
+        name: String,
+        level?: Long = -1,
+        active?: Bool = true,
+        groups?: Set<String> = ["staff"],
+        manager?: User = User::"root",
+        addr?: ipaddr = ip("10.0.0.1"),
+        prefs?: { theme?: String = "dark", size?: Long } = {},
+    };
+    entity Doc;
+    action view appliesTo {
+        principal: User,
+        resource: Doc,
+        context: { mfa?: Bool = false, reason?: String },
+    };
+    "#;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(SCHEMA, Extensions::all_available())
+            .expect("schema should parse")
+            .0
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        let attrs = &json[""]["entityTypes"]["User"]["shape"]["attributes"];
+        assert_eq!(attrs["name"].get("default"), None);
+        assert_eq!(attrs["level"]["default"], json!(-1));
+        assert_eq!(attrs["active"]["default"], json!(true));
+        assert_eq!(attrs["groups"]["default"], json!(["staff"]));
+        assert_eq!(
+            attrs["manager"]["default"],
+            json!({ "__entity": { "type": "User", "id": "root" } })
+        );
+        assert_eq!(
+            attrs["addr"]["default"],
+            json!({ "__extn": { "fn": "ip", "arg": "10.0.0.1" } })
+        );
+        assert_eq!(attrs["prefs"]["default"], json!({}));
+        assert_eq!(
+            attrs["prefs"]["attributes"]["theme"]["default"],
+            json!("dark")
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let src = frag.to_cedarschema().unwrap();
+        assert!(src.contains("level?: Long = -1"), "{src}");
+        assert!(src.contains(r#"manager?: User = User::"root""#), "{src}");
+        assert!(src.contains(r#"addr?: ipaddr = ip("10.0.0.1")"#), "{src}");
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&src, Extensions::all_available()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&frag).unwrap(),
+            serde_json::to_value(&roundtripped).unwrap()
+        );
+
+        let json = serde_json::to_value(&frag).unwrap();
+        let from_json = json_schema::Fragment::from_json_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&from_json).unwrap(), json);
+    }
+
+    #[test]
+    fn required_attribute_with_default() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str("entity E { a: Long = 1 };", Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "a");
+                assert_eq!(e.to_string(), "invalid default value for attribute `a`: only optional attributes can have a default value");
+            }
+        );
+    }
+
+    #[test]
+    fn default_of_wrong_type() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(r#"entity E { a?: Long = "one" };"#, Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "a");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(r#"type T = { b?: Bool = 0 }; entity E { a: T };"#, Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "b");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(r#"entity E { a?: ipaddr = ip("not an ip") };"#, Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "a");
+            }
+        );
+    }
+
+    #[test]
+    fn default_of_undeclared_enum_entity() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(r#"entity Color enum ["red"]; entity E { c?: Color = Color::"blue" };"#, Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "c");
+                assert!(e.to_string().contains(r#"entity `Color::"blue"` is of an enumerated entity type"#), "{e}");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(r#"entity Color enum ["red"]; entity E { c?: Set<Color> = [Color::"red", Color::"blue"] };"#, Extensions::all_available())),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "c");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                r#"entity Color enum ["red"]; entity E { c?: Color = Color::"red" };"#,
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn invalid_default_syntax() {
+        assert_matches!(
+            collect_warnings(json_schema::Fragment::from_cedarschema_str(
+                "entity E { a?: Bool = yes };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(_))
+        );
+        assert_matches!(
+            collect_warnings(json_schema::Fragment::from_cedarschema_str(
+                "entity E { a?: Long = 9223372036854775808 };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(_))
+        );
+        assert_matches!(
+            collect_warnings(json_schema::Fragment::from_cedarschema_str(
+                "entity E { a?: { b?: Long } = { b: 1, b: 2 } };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(_))
+        );
+        assert_matches!(
+            collect_warnings(json_schema::Fragment::from_cedarschema_str(
+                "entity E { a?: Long = -9223372036854775808 };",
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn entity_defaults() {
+        let schema = schema();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let entities = parser
+            .from_json_value(json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": { "name": "alice", "level": 3, "prefs": { "size": 2 } },
+                    "parents": []
+                }
+            ]))
+            .unwrap();
+        let alice = entities
+            .entity(&EntityUID::with_eid_and_type("User", "alice").unwrap())
+            .unwrap();
+        let attr = |attr: &str| alice.get(attr).map(ToString::to_string);
+        assert_eq!(attr("level").as_deref(), Some("3"));
+        assert_eq!(attr("active").as_deref(), Some("true"));
+        assert_eq!(attr("groups").as_deref(), Some(r#"["staff"]"#));
+        assert_eq!(attr("manager").as_deref(), Some(r#"User::"root""#));
+        assert_eq!(
+            attr("prefs").as_deref(),
+            Some(r#"{size: 2, theme: "dark"}"#)
+        );
+        assert_matches!(attr("addr"), Some(_));
+    }
+
+    #[test]
+    fn context_defaults() {
+        let schema = schema();
+        let action = EntityUID::with_eid_and_type("Action", "view").unwrap();
+        let context_schema = context_schema_for_action(&schema, &action).unwrap();
+        let parser = ContextJsonParser::new(Some(&context_schema), Extensions::all_available());
+        let context = parser
+            .from_json_value(json!({ "reason": "audit" }))
+            .unwrap();
+        let expected = Context::from_pairs(
+            [
+                ("mfa".into(), RestrictedExpr::val(false)),
+                ("reason".into(), RestrictedExpr::val("audit")),
+            ],
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(context, expected);
+    }
+}
+
+mod refinements {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::ast::EntityUID;
+    use crate::entities::{ContextJsonParser, EntityJsonParser, TCComputation};
+    use crate::extensions::Extensions;
+    use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{
+        context_schema_for_action, json_schema, CedarSchemaError, CoreSchema, SchemaError,
+        ValidatorSchema,
+    };
+
+    const SCHEMA: &str = r#"
+    type Port = Long;
+    entity Server {
+        port: Port[0..65535],
+        name: String matching "[a-z][a-z0-9-]*",
+        priority?: Long[1..] = 1,
+        limits?: { burst: Long[..100] },
+    };
+    action connect appliesTo {
+        principal: Server,
+        resource: Server,
+        context: { retries: Long[0..5] },
+    };
+    "#;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(SCHEMA, Extensions::all_available())
+            .expect("schema should parse")
+            .0
+    }
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        let attrs = &json[""]["entityTypes"]["Server"]["shape"]["attributes"];
+        assert_eq!(attrs["port"]["range"], json!({ "min": 0, "max": 65535 }));
+        assert_eq!(attrs["name"]["pattern"], json!("[a-z][a-z0-9-]*"));
+        assert_eq!(attrs["priority"]["range"], json!({ "min": 1 }));
+        assert_eq!(
+            attrs["limits"]["attributes"]["burst"]["range"],
+            json!({ "max": 100 })
+        );
+
+        let src = frag.to_cedarschema().unwrap();
+        assert!(src.contains("port: Port[0..65535]"), "{src}");
+        assert!(
+            src.contains(r#"name: String matching "[a-z][a-z0-9-]*""#),
+            "{src}"
+        );
+        assert!(src.contains("priority?: Long[1..] = 1"), "{src}");
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&src, Extensions::all_available()).unwrap();
+        assert_eq!(json, serde_json::to_value(&roundtripped).unwrap());
+
+        let from_json = json_schema::Fragment::from_json_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&from_json).unwrap(), json);
+        schema();
+    }
+
+    #[test]
+    fn invalid_refinements() {
+        let err = |src: &str| {
+            assert_matches!(
+                collect_warnings(ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())),
+                Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeRefinement(e))) => e
+            )
+        };
+        let e = err("entity E { a: String[0..1] };");
+        assert_eq!(e.attr(), "a");
+        assert_eq!(
+            e.to_string(),
+            "invalid refinement for attribute `a`: `[0..1]` only applies to attributes of type `Long`, but the attribute has type `String`"
+        );
+        let e = err(r#"type T = Long; entity E { a: T matching "x" };"#);
+        assert_eq!(e.attr(), "a");
+        let e = err("entity E { a: Long[5..1] };");
+        assert_eq!(
+            e.to_string(),
+            "invalid refinement for attribute `a`: the range `[5..1]` is empty"
+        );
+        let e = err(r#"entity E { a: String matching "(" };"#);
+        assert_eq!(e.attr(), "a");
+        assert_matches!(
+            ValidatorSchema::from_json_value(
+                json!({ "": {
+                    "entityTypes": { "E": { "shape": { "type": "Record", "attributes": {
+                        "a": { "type": "Long", "range": { "min": 0 }, "pattern": "x" }
+                    } } } },
+                    "actions": {}
+                } }),
+                Extensions::all_available()
+            ),
+            Err(SchemaError::InvalidAttributeRefinement(_))
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity E { a?: Long[0..10] = 11 };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(
+                SchemaError::InvalidAttributeDefault(_)
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_refinement_syntax() {
+        for src in [
+            r#"entity E { a: String match "x" };"#,
+            "entity E { a: Long[0..99999999999999999999] };",
+            "entity E { a: Long[0...1] };",
+        ] {
+            assert_matches!(
+                collect_warnings(json_schema::Fragment::from_cedarschema_str(
+                    src,
+                    Extensions::all_available()
+                )),
+                Err(CedarSchemaError::Parsing(_)),
+                "{src}"
+            );
+        }
+    }
+
+    #[test]
+    fn entity_refinements() {
+        let schema = schema();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let server = |attrs: serde_json::Value| json!([{ "uid": { "type": "Server", "id": "s" }, "attrs": attrs, "parents": [] }]);
+        assert_matches!(
+            parser.from_json_value(server(json!({ "port": 443, "name": "web-1" }))),
+            Ok(_)
+        );
+        for attrs in [
+            json!({ "port": 70000, "name": "web-1" }),
+            json!({ "port": 443, "name": "Web" }),
+            json!({ "port": 443, "name": "web-1", "priority": 0 }),
+            json!({ "port": 443, "name": "web-1", "limits": { "burst": 101 } }),
+        ] {
+            assert_matches!(
+                parser.from_json_value(server(attrs.clone())),
+                Err(_),
+                "{attrs}"
+            );
+        }
+        let err = parser
+            .from_json_value(server(json!({ "port": 70000, "name": "web-1" })))
+            .unwrap_err();
+        expect_err(
+            "",
+            &miette::Report::new(err),
+            &ExpectedErrorMessageBuilder::error("entity does not conform to the schema")
+                .source(r#"in attribute `port` on `Server::"s"`, type mismatch: value was expected to have type long, but it does not satisfy the refinement `[0..65535]`: `70000`"#)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn context_refinements() {
+        let schema = schema();
+        let action = EntityUID::with_eid_and_type("Action", "connect").unwrap();
+        let context_schema = context_schema_for_action(&schema, &action).unwrap();
+        let parser = ContextJsonParser::new(Some(&context_schema), Extensions::all_available());
+        assert_matches!(parser.from_json_value(json!({ "retries": 5 })), Ok(_));
+        assert_matches!(parser.from_json_value(json!({ "retries": 6 })), Err(_));
+    }
+}
+
+mod extension_refinements {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::ast::EntityUID;
+    use crate::entities::{ContextJsonParser, EntityJsonParser, TCComputation};
+    use crate::extensions::Extensions;
+    use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{
+        context_schema_for_action, json_schema, CedarSchemaError, CoreSchema, SchemaError,
+        ValidatorSchema,
+    };
+
+    const SCHEMA: &str = r#"
+    entity Host {
+        addr: ipaddr satisfying "isIpv4",
+        backup?: __cedar::ipaddr satisfying "isIpv6",
+    };
+    action connect appliesTo {
+        principal: Host,
+        resource: Host,
+        context: { source: ipaddr satisfying "isLoopback" },
+    };
+    "#;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(SCHEMA, Extensions::all_available())
+            .expect("schema should parse")
+            .0
+    }
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        let attrs = &json[""]["entityTypes"]["Host"]["shape"]["attributes"];
+        assert_eq!(attrs["addr"]["satisfying"], json!("isIpv4"));
+        assert_eq!(attrs["backup"]["satisfying"], json!("isIpv6"));
+
+        let src = frag.to_cedarschema().unwrap();
+        assert!(src.contains(r#"addr: ipaddr satisfying "isIpv4""#), "{src}");
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&src, Extensions::all_available()).unwrap();
+        assert_eq!(json, serde_json::to_value(&roundtripped).unwrap());
+        schema();
+    }
+
+    #[test]
+    fn invalid_refinements() {
+        let err = |src: &str| {
+            assert_matches!(
+                collect_warnings(ValidatorSchema::from_cedarschema_str(src, Extensions::all_available())),
+                Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeRefinement(e))) => e
+            )
+        };
+        let e = err(r#"entity E { a: decimal satisfying "isIpv4" };"#);
+        assert_eq!(e.attr(), "a");
+        assert_eq!(
+            e.to_string(),
+            r#"invalid refinement for attribute `a`: `satisfying "isIpv4"` only applies to attributes of type `ipaddr`, but the attribute has type `decimal`"#
+        );
+        let e = err(r#"entity E { a: ipaddr satisfying "isIPv4" };"#);
+        assert_eq!(
+            e.to_string(),
+            "invalid refinement for attribute `a`: `isIPv4` cannot be used with `satisfying`: there is no extension function with this name"
+        );
+        for method in ["isInRange", "ip", "lessThan", "not a name"] {
+            let e = err(&format!(
+                r#"entity E {{ a: ipaddr satisfying "{method}" }};"#
+            ));
+            assert_eq!(e.attr(), "a", "{method}");
+        }
+        assert_matches!(
+            ValidatorSchema::from_json_value(
+                json!({ "": {
+                    "entityTypes": { "E": { "shape": { "type": "Record", "attributes": {
+                        "a": { "type": "Long", "range": { "min": 0 }, "satisfying": "isIpv4" }
+                    } } } },
+                    "actions": {}
+                } }),
+                Extensions::all_available()
+            ),
+            Err(SchemaError::InvalidAttributeRefinement(_))
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                r#"entity E { a?: ipaddr satisfying "isIpv4" = ip("::1") };"#,
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(
+                SchemaError::InvalidAttributeDefault(_)
+            ))
+        );
+    }
+
+    #[test]
+    fn entity_refinements() {
+        let schema = schema();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let host = |attrs: serde_json::Value| json!([{ "uid": { "type": "Host", "id": "h" }, "attrs": attrs, "parents": [] }]);
+        assert_matches!(
+            parser.from_json_value(host(json!({ "addr": "10.0.0.1", "backup": "::1" }))),
+            Ok(_)
+        );
+        for attrs in [
+            json!({ "addr": "::1" }),
+            json!({ "addr": { "__extn": { "fn": "ip", "arg": "::1" } } }),
+            json!({ "addr": "10.0.0.1", "backup": "10.0.0.2" }),
+        ] {
+            assert_matches!(
+                parser.from_json_value(host(attrs.clone())),
+                Err(_),
+                "{attrs}"
+            );
+        }
+        let err = parser
+            .from_json_value(host(json!({ "addr": "::1" })))
+            .unwrap_err();
+        expect_err(
+            "",
+            &miette::Report::new(err),
+            &ExpectedErrorMessageBuilder::error("entity does not conform to the schema")
+                .source(r#"in attribute `addr` on `Host::"h"`, type mismatch: value was expected to have type ipaddr, but it does not satisfy the refinement `satisfying "isIpv4"`: `ip("::1")`"#)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn context_refinements() {
+        let schema = schema();
+        let action = EntityUID::with_eid_and_type("Action", "connect").unwrap();
+        let context_schema = context_schema_for_action(&schema, &action).unwrap();
+        let parser = ContextJsonParser::new(Some(&context_schema), Extensions::all_available());
+        assert_matches!(
+            parser.from_json_value(json!({ "source": "127.0.0.1" })),
+            Ok(_)
+        );
+        assert_matches!(
+            parser.from_json_value(json!({ "source": "10.0.0.1" })),
+            Err(_)
+        );
+    }
+}
+
 pub(crate) const SPECIAL_IDS: [&str; 18] = [
     "principal",
     "action",
@@ -2990,3 +3549,527 @@ namespace TinyTodo {
         );
     }
 }
+
+mod union_types {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{json_schema, CedarSchemaError, SchemaError, ValidatorSchema};
+
+    const SCHEMA: &str = r#"
+    entity User;
+    entity Photo { owner: User };
+    entity Album { owner: User };
+    type Target = Photo | Album;
+    entity Share {
+        target: Target,
+        meta: { kind: String, count: Long } | { kind: String },
+    };
+    "#;
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        assert_eq!(
+            json[""]["commonTypes"]["Target"],
+            json!({
+                "type": "Union",
+                "members": [
+                    { "type": "EntityOrCommon", "name": "Photo" },
+                    { "type": "EntityOrCommon", "name": "Album" },
+                ],
+            })
+        );
+        let src = frag.to_cedarschema().unwrap();
+        assert!(src.contains("type Target = Photo | Album;"), "{src}");
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&src, Extensions::all_available()).unwrap();
+        assert_eq!(json, serde_json::to_value(&roundtripped).unwrap());
+
+        let from_json = json_schema::Fragment::from_json_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&from_json).unwrap(), json);
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                SCHEMA,
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn union_without_members() {
+        assert_matches!(
+            json_schema::Fragment::<crate::validator::RawName>::from_json_value(json!({
+                "": {
+                    "entityTypes": {},
+                    "actions": {},
+                    "commonTypes": { "T": { "type": "Union" } },
+                }
+            })),
+            Err(_)
+        );
+        assert_matches!(
+            ValidatorSchema::from_json_value(
+                json!({
+                    "": {
+                        "entityTypes": {},
+                        "actions": {},
+                        "commonTypes": { "T": { "type": "Union", "members": [] } },
+                    }
+                }),
+                Extensions::all_available()
+            ),
+            Err(SchemaError::InvalidUnionType(e)) => {
+                assert_eq!(e.to_string(), "invalid union type: a union must have at least one member");
+            }
+        );
+    }
+
+    #[test]
+    fn unsupported_members() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity E { a: Long | String };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidUnionType(e))) => {
+                assert_eq!(e.to_string(), "invalid union type: cannot form a union of `Long` and `String`");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity U; entity E { a: U | { b: Long } };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidUnionType(_)))
+        );
+    }
+
+    #[test]
+    fn incompatible_attribute() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity E { a: { b: Long } | { b: String } };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidUnionType(e))) => {
+                assert_eq!(e.to_string(), "invalid union type: members declare attribute `b` with incompatible types `Long` and `String`");
+            }
+        );
+    }
+}
+
+#[cfg(feature = "maps")]
+mod map_types {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{json_schema, CedarSchemaError, ValidatorSchema};
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let src = r#"
+        entity User { roles: Map<String, Set<String>> };
+        type Headers = Map<String, String>;
+        "#;
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available()).unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        assert_eq!(
+            json[""]["commonTypes"]["Headers"],
+            json!({
+                "type": "Map",
+                "element": { "type": "EntityOrCommon", "name": "String" },
+            })
+        );
+        let printed = frag.to_cedarschema().unwrap();
+        assert!(
+            printed.contains("type Headers = Map<String, String>;"),
+            "{printed}"
+        );
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&printed, Extensions::all_available())
+                .unwrap();
+        assert_eq!(json, serde_json::to_value(&roundtripped).unwrap());
+
+        let from_json = json_schema::Fragment::from_json_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&from_json).unwrap(), json);
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                src,
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn map_without_element() {
+        assert_matches!(
+            json_schema::Fragment::<crate::validator::RawName>::from_json_value(json!({
+                "": {
+                    "entityTypes": {},
+                    "actions": {},
+                    "commonTypes": { "T": { "type": "Map" } },
+                }
+            })),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn invalid_map_types() {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity E { a: Dict<String, Long> };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(e)) => {
+                assert!(e.to_string().contains("`Dict` is not a parameterized common type"), "{e}");
+            }
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                "entity E { a: Map<Long, Long> };",
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(e)) => {
+                assert!(e.to_string().contains("invalid map type: `Map` takes a `String` key type and a value type"), "{e}");
+            }
+        );
+    }
+}
+
+mod generic_types {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::extensions::Extensions;
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{json_schema, CedarSchemaError, ValidatorSchema};
+
+    fn expect_err(src: &str, msg: &str) {
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                src,
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Parsing(e)) => {
+                assert!(e.to_string().contains(msg), "{e}");
+            }
+        );
+    }
+
+    #[test]
+    fn monomorphized() {
+        let src = r#"
+        type Paginated<T> = { items: Set<T>, next?: String };
+        type Pair<A, B> = { first: A, second: B };
+        entity User;
+        entity Group {
+            members: Paginated<User>,
+            admins: Paginated<Pair<User, Long>>,
+        };
+        action list appliesTo {
+            principal: User,
+            resource: Group,
+            context: { page: Paginated<String> },
+        };
+        "#;
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available()).unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        assert_eq!(json[""]["commonTypes"], serde_json::Value::Null);
+        assert_eq!(
+            json[""]["entityTypes"]["Group"]["shape"]["attributes"]["admins"],
+            json!({
+                "type": "Record",
+                "attributes": {
+                    "items": {
+                        "type": "Set",
+                        "element": {
+                            "type": "Record",
+                            "attributes": {
+                                "first": { "type": "EntityOrCommon", "name": "User" },
+                                "second": { "type": "EntityOrCommon", "name": "Long" },
+                            },
+                        },
+                    },
+                    "next": { "type": "EntityOrCommon", "name": "String", "required": false },
+                },
+            })
+        );
+        assert_eq!(
+            json[""]["actions"]["list"]["appliesTo"]["context"]["attributes"]["page"]["attributes"]
+                ["items"],
+            json!({
+                "type": "Set",
+                "element": { "type": "EntityOrCommon", "name": "String" },
+            })
+        );
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                src,
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn across_namespaces() {
+        let src = r#"
+        namespace Lib {
+            entity Cursor;
+            type Paginated<T> = { items: Set<T>, cursor: Cursor };
+        }
+        namespace App {
+            entity Cursor;
+            entity User;
+            type Users = Lib::Paginated<User>;
+        }
+        "#;
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(src, Extensions::all_available()).unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        assert_eq!(
+            json["App"]["commonTypes"]["Users"],
+            json!({
+                "type": "Record",
+                "attributes": {
+                    "items": {
+                        "type": "Set",
+                        "element": { "type": "EntityOrCommon", "name": "User" },
+                    },
+                    "cursor": { "type": "EntityOrCommon", "name": "Lib::Cursor" },
+                },
+            })
+        );
+        assert_eq!(json["Lib"]["commonTypes"], serde_json::Value::Null);
+        assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                src,
+                Extensions::all_available()
+            )),
+            Ok(_)
+        );
+
+        expect_err(
+            r#"
+            entity Cursor;
+            type Paginated<T> = { items: Set<T>, cursor: Cursor };
+            namespace App {
+                entity Cursor;
+                type Cursors = Paginated<Cursor>;
+            }
+            "#,
+            "`Cursor` in the definition of `Paginated` refers to a different type when `Paginated` is used in namespace `App`",
+        );
+    }
+
+    #[test]
+    fn invalid_uses() {
+        expect_err(
+            "type Box<T> = { value: T }; entity E { a: Box };",
+            "missing type arguments for the parameterized common type `Box`",
+        );
+        expect_err(
+            "type Box<T> = { value: T }; entity E { a: Box<Long, String> };",
+            "`Box` takes 1 type argument(s), but 2 were given",
+        );
+        expect_err(
+            "type Long2 = Long; entity E { a: Long2<Long> };",
+            "`Long2` is not a parameterized common type",
+        );
+        expect_err(
+            "type List<T> = { head: T, tail: List<T> }; entity E { a: List<Long> };",
+            "the parameterized common type `List` is defined in terms of itself",
+        );
+        expect_err(
+            "type Pair<T, T> = { first: T, second: T }; entity E;",
+            "duplicate type parameter `T` in the parameterized common type `Pair`",
+        );
+        expect_err(
+            "entity E { a: Set<Long, Long> };",
+            "invalid set type: `Set` takes a single element type",
+        );
+    }
+}
+
+mod entity_constraints {
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::entities::{EntityJsonParser, TCComputation};
+    use crate::extensions::Extensions;
+    use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
+    use crate::validator::schema::test::utils::collect_warnings;
+    use crate::validator::{
+        json_schema, CedarSchemaError, CoreSchema, SchemaError, ValidatorSchema,
+    };
+
+    const SCHEMA: &str = r#"
+    entity Meeting {
+        start_time: Long,
+        end_time: Long,
+        "room name"?: String,
+    } constraints [
+        "end_time > start_time",
+        "!(context has \"room name\") || context[\"room name\"] != \"\""
+    ];
+    "#;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(SCHEMA, Extensions::all_available())
+            .expect("schema should parse")
+            .0
+    }
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let (frag, _) =
+            json_schema::Fragment::from_cedarschema_str(SCHEMA, Extensions::all_available())
+                .unwrap();
+        let json = serde_json::to_value(&frag).unwrap();
+        assert_eq!(
+            json[""]["entityTypes"]["Meeting"]["constraints"],
+            json!([
+                "end_time > start_time",
+                r#"!(context has "room name") || context["room name"] != """#,
+            ])
+        );
+
+        let src = frag.to_cedarschema().unwrap();
+        assert!(
+            src.contains(r#"constraints ["end_time > start_time", "#),
+            "{src}"
+        );
+        let (roundtripped, _) =
+            json_schema::Fragment::from_cedarschema_str(&src, Extensions::all_available()).unwrap();
+        assert_eq!(json, serde_json::to_value(&roundtripped).unwrap());
+
+        let from_json = json_schema::Fragment::from_json_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&from_json).unwrap(), json);
+        schema();
+    }
+
+    #[test]
+    fn constraints_as_identifier() {
+        let (frag, _) = json_schema::Fragment::from_cedarschema_str(
+            "entity constraints { constraints: Long } constraints [\"constraints > 0\"];",
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&frag).unwrap()[""]["entityTypes"]["constraints"]["constraints"],
+            json!(["constraints > 0"])
+        );
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let e = assert_matches!(
+            collect_warnings(ValidatorSchema::from_cedarschema_str(
+                r#"entity E { a: Long } constraints ["a >"];"#,
+                Extensions::all_available()
+            )),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidEntityConstraint(e))) => e
+        );
+        assert_eq!(e.entity_type().to_string(), "E");
+        assert_eq!(e.to_string(), "invalid constraint `a >` on entity type `E`");
+        assert_matches!(
+            ValidatorSchema::from_json_value(
+                json!({ "": {
+                    "entityTypes": { "E": { "constraints": ["foo::bar"] } },
+                    "actions": {}
+                } }),
+                Extensions::all_available()
+            ),
+            Err(SchemaError::InvalidEntityConstraint(_))
+        );
+        assert_matches!(
+            json_schema::Fragment::from_json_value(json!({ "": {
+                "entityTypes": { "E": { "enum": ["a"], "constraints": ["true"] } },
+                "actions": {}
+            } })),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn entity_constraints() {
+        let schema = schema();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let meeting = |attrs: serde_json::Value| json!([{ "uid": { "type": "Meeting", "id": "m" }, "attrs": attrs, "parents": [] }]);
+        for attrs in [
+            json!({ "start_time": 9, "end_time": 10 }),
+            json!({ "start_time": 9, "end_time": 10, "room name": "Lobby" }),
+        ] {
+            assert_matches!(
+                parser.from_json_value(meeting(attrs.clone())),
+                Ok(_),
+                "{attrs}"
+            );
+        }
+        for attrs in [
+            json!({ "start_time": 10, "end_time": 10 }),
+            json!({ "start_time": 9, "end_time": 10, "room name": "" }),
+        ] {
+            assert_matches!(
+                parser.from_json_value(meeting(attrs.clone())),
+                Err(_),
+                "{attrs}"
+            );
+        }
+        let err = parser
+            .from_json_value(meeting(json!({ "start_time": 11, "end_time": 10 })))
+            .unwrap_err();
+        expect_err(
+            "",
+            &miette::Report::new(err),
+            &ExpectedErrorMessageBuilder::error("entity does not conform to the schema")
+                .source(r#"entity `Meeting::"m"` does not satisfy the constraint `end_time > start_time`"#)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn constraint_evaluation_error() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"entity E { a?: Long } constraints ["a > 0"];"#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let err = parser
+            .from_json_value(
+                json!([{ "uid": { "type": "E", "id": "e" }, "attrs": {}, "parents": [] }]),
+            )
+            .unwrap_err();
+        expect_err(
+            "",
+            &miette::Report::new(err),
+            &ExpectedErrorMessageBuilder::error("entity does not conform to the schema")
+                .source(r#"entity `E::"e"` does not satisfy the constraint `a > 0`: record does not have the attribute `a`"#)
+                .build(),
+        );
+    }
+}
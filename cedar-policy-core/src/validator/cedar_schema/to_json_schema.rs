@@ -31,9 +31,10 @@ use std::collections::hash_map::Entry;
 use super::{
     ast::{
         ActionDecl, Annotated, AppDecl, AttrDecl, Decl, Declaration, EntityDecl, Namespace,
-        PRAppDecl, Path, QualName, Schema, Type, TypeDecl, BUILTIN_TYPES, PR,
+        PRAppDecl, Path, QualName, Refinement, Schema, Type, TypeDecl, BUILTIN_TYPES, PR,
     },
     err::{schema_warnings, SchemaWarning, ToJsonSchemaError, ToJsonSchemaErrors},
+    generics::expand_generic_types,
 };
 use crate::validator::{
     cedar_schema,
@@ -81,6 +82,7 @@ pub fn cedar_schema_to_json_schema(
 
     let names = build_namespace_bindings(all_namespaces.iter().map(|ns| &ns.data))?;
     let warnings = compute_namespace_warnings(&names, extensions);
+    let all_namespaces = expand_generic_types(all_namespaces)?;
     let fragment = collect_all_errors(all_namespaces.into_iter().map(convert_namespace))?.collect();
     Ok((
         json_schema::Fragment(fragment),
@@ -102,6 +104,9 @@ pub fn cedar_type_to_json_type(ty: Node<Type>) -> json_schema::Type<RawName> {
         Type::Set(t) => json_schema::TypeVariant::Set {
             element: Box::new(cedar_type_to_json_type(*t)),
         },
+        Type::Map(t) => json_schema::TypeVariant::Map {
+            element: Box::new(cedar_type_to_json_type(*t)),
+        },
         Type::Ident(p) => json_schema::TypeVariant::EntityOrCommon {
             type_name: RawName::from(p),
         },
@@ -109,6 +114,15 @@ pub fn cedar_type_to_json_type(ty: Node<Type>) -> json_schema::Type<RawName> {
             attributes: fields.into_iter().map(convert_attr_decl).collect(),
             additional_attributes: false,
         }),
+        Type::Union(members) => json_schema::TypeVariant::Union {
+            members: members.into_iter().map(cedar_type_to_json_type).collect(),
+        },
+        // Instantiations of parameterized common types are expanded before
+        // conversion; an unexpanded one is converted to a reference to its
+        // name, which fails to resolve
+        Type::Generic { name, .. } => json_schema::TypeVariant::EntityOrCommon {
+            type_name: RawName::from(name),
+        },
     };
     json_schema::Type::Type {
         ty: variant,
@@ -402,6 +416,7 @@ fn convert_entity_decl(
                     member_of_types: d.member_of_types.into_iter().map(RawName::from).collect(),
                     shape: convert_attr_decls(d.attrs),
                     tags: d.tags.map(cedar_type_to_json_type),
+                    constraints: d.constraints.into_iter().map(|c| c.node).collect(),
                 })
             }
         },
@@ -460,6 +475,31 @@ fn convert_attr_decl(
         json_schema::TypeOfAttribute {
             ty: cedar_type_to_json_type(attr.node.data.ty),
             required: attr.node.data.required,
+            range: match &attr.node.data.refinement {
+                Some(Node {
+                    node: Refinement::Range { min, max },
+                    ..
+                }) => Some(json_schema::LongRange {
+                    min: *min,
+                    max: *max,
+                }),
+                _ => None,
+            },
+            pattern: match &attr.node.data.refinement {
+                Some(Node {
+                    node: Refinement::Matching(pattern),
+                    ..
+                }) => Some(pattern.clone()),
+                _ => None,
+            },
+            satisfying: match attr.node.data.refinement {
+                Some(Node {
+                    node: Refinement::Satisfying(method),
+                    ..
+                }) => Some(method),
+                _ => None,
+            },
+            default: attr.node.data.default.map(|default| default.node),
             annotations: attr.node.annotations.into(),
             #[cfg(feature = "extended-schema")]
             loc: attr.loc,
@@ -470,7 +510,7 @@ fn convert_attr_decl(
 /// Takes a collection of results returning multiple errors
 /// Behaves similarly to `::collect()` over results, except instead of failing
 /// on the first error, keeps going to ensure all of the errors are accumulated
-fn collect_all_errors<A, E>(
+pub(super) fn collect_all_errors<A, E>(
     iter: impl IntoIterator<Item = Result<A, E>>,
 ) -> Result<impl Iterator<Item = A>, ToJsonSchemaErrors>
 where
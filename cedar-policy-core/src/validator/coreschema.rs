@@ -20,7 +20,8 @@ use crate::entities::conformance::{
 };
 use crate::extensions::{ExtensionFunctionLookupError, Extensions};
 use crate::validator::{
-    ValidatorActionId, ValidatorEntityType, ValidatorEntityTypeKind, ValidatorSchema,
+    types::RequestEnv, ValidatorActionId, ValidatorEntityType, ValidatorEntityTypeKind,
+    ValidatorSchema,
 };
 use crate::{ast, entities};
 use miette::Diagnostic;
@@ -158,6 +159,25 @@ impl entities::EntityTypeDescription for EntityTypeDescription {
         )
     }
 
+    fn attr_defaults<'s>(
+        &'s self,
+    ) -> Box<dyn Iterator<Item = (SmolStr, ast::RestrictedExpr)> + 's> {
+        Box::new(
+            self.validator_type
+                .attributes()
+                .iter()
+                .filter_map(|(attr, ty)| Some((attr.clone(), ty.default.clone()?))),
+        )
+    }
+
+    fn attr_refinement(&self, attr: &str) -> Option<entities::Refinement> {
+        self.validator_type.attr(attr)?.refinement.clone()
+    }
+
+    fn constraints<'s>(&'s self) -> Box<dyn Iterator<Item = entities::EntityConstraint> + 's> {
+        Box::new(self.validator_type.constraints().iter().cloned())
+    }
+
     fn allowed_parent_types(&self) -> Arc<HashSet<ast::EntityType>> {
         Arc::clone(&self.allowed_parent_types)
     }
@@ -183,8 +203,8 @@ impl ast::RequestSchema for ValidatorSchema {
             request.resource().uid(),
         )?;
 
-        if let (Some(context), Some(action)) = (request.context(), action_uid) {
-            self.validate_context(context, action, extensions)?;
+        if let (Some(context), Some(action)) = (request.context_with_unknown_attrs(), action_uid) {
+            self.validate_context(&context, action, extensions)?;
         }
         Ok(())
     }
@@ -204,24 +224,14 @@ impl ast::RequestSchema for ValidatorSchema {
         })?;
 
         // Validate entity UIDs in the context
-        validate_euids_in_partial_value(&CoreSchema::new(self), &context.clone().into()).map_err(
-            |e| match e {
-                ValidateEuidError::InvalidEnumEntity(e) => {
-                    RequestValidationError::InvalidEnumEntity(e)
-                }
-                ValidateEuidError::UndeclaredAction(e) => {
-                    request_validation_errors::UndeclaredActionError {
-                        action: Arc::new(e.uid),
-                    }
-                    .into()
-                }
-            },
-        )?;
+        let context_value = context.clone().into();
+        validate_euids_in_partial_value(&CoreSchema::new(self), &context_value)
+            .map_err(RequestValidationError::from_validate_euid_error)?;
 
         // Typecheck the context against the expected context type
         let expected_context_ty = validator_action_id.context_type();
         if !expected_context_ty
-            .typecheck_partial_value(&context.clone().into(), extensions)
+            .typecheck_partial_value(&context_value, extensions)
             .map_err(RequestValidationError::TypeOfContext)?
         {
             return Err(request_validation_errors::InvalidContextError {
@@ -341,6 +351,131 @@ impl ValidatorActionId {
     }
 }
 
+impl ValidatorSchema {
+    /// Get the request environment for requests with a principal of type
+    /// `principal`, the action `action`, and a resource of type `resource`,
+    /// returning `Err` if `action` is not declared or does not apply to
+    /// principals and resources of these types.
+    pub fn request_env<'a>(
+        &'a self,
+        principal: &'a ast::EntityType,
+        action: &'a ast::EntityUID,
+        resource: &'a ast::EntityType,
+    ) -> std::result::Result<RequestEnv<'a>, RequestValidationError> {
+        let validator_action_id = self.get_action_id(action).ok_or_else(|| {
+            request_validation_errors::UndeclaredActionError {
+                action: Arc::new(action.clone()),
+            }
+        })?;
+        if self.get_entity_type(principal).is_none() {
+            return Err(request_validation_errors::UndeclaredPrincipalTypeError {
+                principal_ty: principal.clone(),
+            }
+            .into());
+        }
+        if self.get_entity_type(resource).is_none() {
+            return Err(request_validation_errors::UndeclaredResourceTypeError {
+                resource_ty: resource.clone(),
+            }
+            .into());
+        }
+        let action_arc = Arc::new(action.clone());
+        validator_action_id.check_principal_type(principal, &action_arc)?;
+        validator_action_id.check_resource_type(resource, &action_arc)?;
+        Ok(RequestEnv::DeclaredAction {
+            principal,
+            action,
+            resource,
+            context: validator_action_id.context_type(),
+            principal_slot: None,
+            resource_slot: None,
+        })
+    }
+
+    /// Validate the value of a single context attribute `attr` for the given
+    /// `action`, returning `Err` if `attr` is not declared in the context
+    /// type of `action`, or if `value` does not have the declared type.
+    ///
+    /// Unlike [`ast::RequestSchema::validate_context()`], this does not
+    /// check that all required context attributes are present.
+    pub fn validate_context_attr(
+        &self,
+        action: &EntityUID,
+        attr: &str,
+        value: &ast::PartialValue,
+        extensions: &Extensions<'_>,
+    ) -> std::result::Result<(), RequestValidationError> {
+        let attr_ty = self.context_attr_type(action, attr)?;
+        validate_euids_in_partial_value(&CoreSchema::new(self), value)
+            .map_err(RequestValidationError::from_validate_euid_error)?;
+        let satisfies_refinement = match (&attr_ty.refinement, value) {
+            (Some(refinement), ast::PartialValue::Value(value)) => {
+                refinement.admits(ast::RestrictedExpr::from(value.clone()).as_borrowed())
+            }
+            _ => true,
+        };
+        if !attr_ty
+            .attr_type
+            .typecheck_partial_value(value, extensions)
+            .map_err(RequestValidationError::TypeOfContext)?
+            || !satisfies_refinement
+        {
+            return Err(request_validation_errors::InvalidContextAttributeError {
+                attr: attr.into(),
+                action: Arc::new(action.clone()),
+                expected: attr_ty.attr_type.to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Get the context attributes which are required for the given `action`,
+    /// i.e., which must be present in every valid context for `action`.
+    pub fn required_context_attrs(
+        &self,
+        action: &EntityUID,
+    ) -> std::result::Result<Vec<SmolStr>, RequestValidationError> {
+        Ok(self
+            .context_record_attrs(action)?
+            .iter()
+            .filter(|(_, ty)| ty.is_required)
+            .map(|(attr, _)| attr.clone())
+            .collect())
+    }
+
+    fn context_attr_type(
+        &self,
+        action: &EntityUID,
+        attr: &str,
+    ) -> std::result::Result<&crate::validator::types::AttributeType, RequestValidationError> {
+        self.context_record_attrs(action)?
+            .get_attr(attr)
+            .ok_or_else(|| {
+                request_validation_errors::UndeclaredContextAttributeError {
+                    attr: attr.into(),
+                    action: Arc::new(action.clone()),
+                }
+                .into()
+            })
+    }
+
+    fn context_record_attrs(
+        &self,
+        action: &EntityUID,
+    ) -> std::result::Result<&crate::validator::types::Attributes, RequestValidationError> {
+        match self.context_type(action) {
+            Some(crate::validator::types::Type::Record { attrs, .. }) => Ok(attrs),
+            // `context_type()` always returns a record type, so this can only
+            // be reached if the action is not declared
+            _ => Err(request_validation_errors::UndeclaredActionError {
+                action: Arc::new(action.clone()),
+            }
+            .into()),
+        }
+    }
+}
+
 impl ast::RequestSchema for CoreSchema<'_> {
     type Error = RequestValidationError;
     fn validate_request(
@@ -414,6 +549,35 @@ pub enum RequestValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] InvalidEnumEntityError),
+    /// A context attribute is not declared in the context type of the request
+    /// action
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UndeclaredContextAttribute(#[from] request_validation_errors::UndeclaredContextAttributeError),
+    /// A context attribute does not have the type declared for it in the
+    /// context type of the request action
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidContextAttribute(#[from] request_validation_errors::InvalidContextAttributeError),
+    /// A context attribute which is required by the context type of the
+    /// request action is missing
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingContextAttribute(#[from] request_validation_errors::MissingContextAttributeError),
+}
+
+impl RequestValidationError {
+    fn from_validate_euid_error(e: ValidateEuidError) -> Self {
+        match e {
+            ValidateEuidError::InvalidEnumEntity(e) => Self::InvalidEnumEntity(e),
+            ValidateEuidError::UndeclaredAction(e) => {
+                request_validation_errors::UndeclaredActionError {
+                    action: Arc::new(e.uid),
+                }
+                .into()
+            }
+        }
+    }
 }
 
 /// Errors related to validation
@@ -421,6 +585,7 @@ pub mod request_validation_errors {
     use crate::ast;
     use itertools::Itertools;
     use miette::Diagnostic;
+    use smol_str::SmolStr;
     use std::sync::Arc;
     use thiserror::Error;
 
@@ -615,6 +780,88 @@ pub mod request_validation_errors {
 
     const BOUNDEDDISPLAY_BOUND_FOR_INVALID_CONTEXT_ERROR: usize = 5;
 
+    /// A context attribute is not declared in the context type of the request
+    /// action
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("context attribute `{attr}` is not declared for `{action}`")]
+    pub struct UndeclaredContextAttributeError {
+        /// Attribute which is not declared
+        pub(crate) attr: SmolStr,
+        /// Action whose context type does not declare it
+        pub(crate) action: Arc<ast::EntityUID>,
+    }
+
+    impl UndeclaredContextAttributeError {
+        /// The attribute which is not declared
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The action whose context type does not declare it
+        pub fn action(&self) -> &ast::EntityUID {
+            &self.action
+        }
+    }
+
+    /// A context attribute does not have the type declared for it in the
+    /// context type of the request action
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("context attribute `{attr}` is not valid for `{action}`")]
+    #[diagnostic(help("expected a value of type `{expected}`"))]
+    pub struct InvalidContextAttributeError {
+        /// Attribute whose value is not valid
+        pub(crate) attr: SmolStr,
+        /// Action it is not valid for
+        pub(crate) action: Arc<ast::EntityUID>,
+        /// The type declared for the attribute
+        pub(crate) expected: String,
+    }
+
+    impl InvalidContextAttributeError {
+        /// The attribute whose value is not valid
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The action it is not valid for
+        pub fn action(&self) -> &ast::EntityUID {
+            &self.action
+        }
+
+        /// The type declared for the attribute, as a string
+        pub fn expected(&self) -> &str {
+            &self.expected
+        }
+    }
+
+    /// A context attribute which is required by the context type of the
+    /// request action is missing
+    #[derive(Debug, Error, Diagnostic)]
+    #[error("context is missing required attribute `{attr}` for `{action}`")]
+    pub struct MissingContextAttributeError {
+        /// Attribute which is missing
+        pub(crate) attr: SmolStr,
+        /// Action which requires it
+        pub(crate) action: Arc<ast::EntityUID>,
+    }
+
+    impl MissingContextAttributeError {
+        /// Construct a new `MissingContextAttributeError`
+        pub fn new(attr: SmolStr, action: Arc<ast::EntityUID>) -> Self {
+            Self { attr, action }
+        }
+
+        /// The attribute which is missing
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+
+        /// The action which requires it
+        pub fn action(&self) -> &ast::EntityUID {
+            &self.action
+        }
+    }
+
     impl InvalidContextError {
         /// The context which is not valid
         pub fn context(&self) -> &ast::Context {
@@ -671,6 +918,7 @@ pub fn context_schema_for_action(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::entities::{EntityJsonParser, TCComputation};
     use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
     use ast::{Context, Value};
     use cool_asserts::assert_matches;
@@ -1167,6 +1415,76 @@ mod test {
         );
     }
 
+    /// request context with nested records, sets, extension values, and
+    /// refined attributes
+    #[test]
+    fn context_nested_values() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity User;
+            action view appliesTo {
+                principal: User,
+                resource: User,
+                context: {
+                    request: { ip: ipaddr, port?: Long[1..65535], tag: String matching "[a-z]+" },
+                    owners: Set<User>,
+                }
+            };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let view = ast::EntityUID::with_eid_and_type("Action", "view").unwrap();
+        let context = |port: i64, tag: &str, owner: &str| {
+            let ip = ast::RestrictedExpr::call_extension_fn(
+                "ip".parse().unwrap(),
+                [ast::RestrictedExpr::val("10.0.0.1")],
+            );
+            ast::Context::from_pairs(
+                [
+                    (
+                        "request".into(),
+                        ast::RestrictedExpr::record([
+                            ("ip".into(), ip),
+                            ("port".into(), ast::RestrictedExpr::val(port)),
+                            ("tag".into(), ast::RestrictedExpr::val(tag)),
+                        ])
+                        .unwrap(),
+                    ),
+                    (
+                        "owners".into(),
+                        ast::RestrictedExpr::set([ast::RestrictedExpr::val(
+                            ast::EntityUID::with_eid_and_type(owner, "alice").unwrap(),
+                        )]),
+                    ),
+                ],
+                Extensions::all_available(),
+            )
+            .unwrap()
+        };
+        let validate = |context: &ast::Context| {
+            ast::RequestSchema::validate_context(
+                &schema,
+                context,
+                &view,
+                Extensions::all_available(),
+            )
+        };
+        assert_matches!(validate(&context(443, "prod", "User")), Ok(()));
+        assert_matches!(
+            validate(&context(0, "prod", "User")),
+            Err(RequestValidationError::InvalidContext(_))
+        );
+        assert_matches!(
+            validate(&context(443, "Prod", "User")),
+            Err(RequestValidationError::InvalidContext(_))
+        );
+        assert_matches!(
+            validate(&context(443, "prod", "Group")),
+            Err(RequestValidationError::InvalidContext(_))
+        );
+    }
+
     #[test]
     fn enumerated_entity_type() {
         assert_matches!(
@@ -1226,4 +1544,37 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn enumerated_entity_type_in_entity_data() {
+        let schema = schema_with_enums();
+        let core_schema = CoreSchema::new(&schema);
+        let parser = EntityJsonParser::new(
+            Some(&core_schema),
+            Extensions::none(),
+            TCComputation::ComputeNow,
+        );
+        assert_matches!(
+            parser
+                .iter_from_json_value(
+                    json!([{ "uid": { "type": "Fruit", "id": "🍉" }, "attrs": {}, "parents": [] }])
+                )
+                .map(Iterator::count),
+            Ok(2)
+        );
+        let json = json!([{ "uid": { "type": "Fruit", "id": "🥝" }, "attrs": {}, "parents": [] }]);
+        assert_matches!(
+            parser.iter_from_json_value(json.clone()).map(Iterator::count),
+            Err(e) => {
+                expect_err(
+                    &json,
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("entity does not conform to the schema")
+                        .source(r#"entity `Fruit::"🥝"` is of an enumerated entity type, but `"🥝"` is not declared as a valid eid"#)
+                        .help(r#"valid entity eids: "🍉", "🍓", "🍒""#)
+                        .build(),
+                );
+            }
+        );
+    }
 }
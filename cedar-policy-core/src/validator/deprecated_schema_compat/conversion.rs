@@ -233,6 +233,7 @@ impl TryFrom<compat::EntityType> for EntityType<RawName> {
                 member_of_types: value.member_of_types,
                 shape: value.shape.try_into()?,
                 tags: None,
+                constraints: Vec::new(),
             }),
             annotations: Annotations::new(),
             loc: None,
@@ -322,6 +323,10 @@ impl TryFrom<compat::TypeOfAttribute> for TypeOfAttribute<RawName> {
             ty: value.ty.try_into()?,
             annotations: Annotations::new(),
             required: value.required,
+            default: None,
+            range: None,
+            pattern: None,
+            satisfying: None,
             #[cfg(feature = "extended-schema")]
             loc: None,
         })
@@ -20,12 +20,13 @@
 use crate::entities::conformance::err::InvalidEnumEntityError;
 use crate::parser::Loc;
 use miette::Diagnostic;
+use smol_str::SmolStr;
 use thiserror::Error;
 use validation_errors::UnrecognizedActionIdHelp;
 
 use std::collections::BTreeSet;
 
-use crate::ast::{EntityType, Expr, PolicyID};
+use crate::ast::{EntityType, EntityUID, Expr, PolicyID};
 
 use crate::validator::types::{EntityLUB, Type};
 
@@ -130,6 +131,10 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnsafeTagAccess(#[from] validation_errors::UnsafeTagAccess),
+    /// The typechecker could not conclude that an access to a map key was safe.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnsafeMapAccess(#[from] validation_errors::UnsafeMapAccess),
     /// `.getTag()` on an entity type which cannot have tags according to the schema.
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -171,6 +176,16 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     EntityDerefLevelViolation(#[from] validation_errors::EntityDerefLevelViolation),
+    /// A validation warning which was configured to be reported as an error
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    WarningAsError(#[from] validation_errors::WarningAsError),
+    /// The action scope constraint of a policy includes actions which don't
+    /// declare both the principal and resource types they apply to, and the
+    /// validator is configured to require fully specified actions
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedActionApplication(#[from] validation_errors::UnspecifiedActionApplication),
 }
 
 impl ValidationError {
@@ -302,6 +317,22 @@ impl ValidationError {
         .into()
     }
 
+    #[cfg(feature = "maps")]
+    pub(crate) fn unsafe_map_access(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        map: Expr<Option<Type>>,
+        key: Expr<Option<Type>>,
+    ) -> Self {
+        validation_errors::UnsafeMapAccess {
+            source_loc,
+            policy_id,
+            map,
+            key,
+        }
+        .into()
+    }
+
     pub(crate) fn no_tags_allowed(
         source_loc: Option<Loc>,
         policy_id: PolicyID,
@@ -403,6 +434,7 @@ impl ValidationError {
         policy_id: PolicyID,
         allowed_level: crate::validator::level_validate::EntityDerefLevel,
         actual_level: crate::validator::level_validate::EntityDerefLevel,
+        access_chain: SmolStr,
     ) -> Self {
         validation_errors::EntityDerefLevelViolation {
             source_loc,
@@ -410,11 +442,49 @@ impl ValidationError {
             violation_kind: validation_errors::EntityDerefViolationKind::MaximumLevelExceeded {
                 allowed_level,
                 actual_level,
+                access_chain,
             },
         }
         .into()
     }
 
+    pub(crate) fn invalid_level_annotation(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        value: SmolStr,
+    ) -> Self {
+        validation_errors::EntityDerefLevelViolation {
+            source_loc,
+            policy_id,
+            violation_kind: validation_errors::EntityDerefViolationKind::InvalidLevelAnnotation {
+                value,
+            },
+        }
+        .into()
+    }
+
+    pub(crate) fn warning_as_error(warning: ValidationWarning) -> Self {
+        validation_errors::WarningAsError {
+            source_loc: warning.source_loc().cloned(),
+            policy_id: warning.policy_id().clone(),
+            warning: Box::new(warning),
+        }
+        .into()
+    }
+
+    pub(crate) fn unspecified_action_application(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        actions: Vec<EntityUID>,
+    ) -> Self {
+        validation_errors::UnspecifiedActionApplication {
+            source_loc,
+            policy_id,
+            actions,
+        }
+        .into()
+    }
+
     pub(crate) fn literal_dereference_target(source_loc: Option<Loc>, policy_id: PolicyID) -> Self {
         validation_errors::EntityDerefLevelViolation {
             source_loc,
@@ -453,9 +523,92 @@ pub enum ValidationWarning {
     #[diagnostic(transparent)]
     #[error(transparent)]
     ImpossiblePolicy(#[from] validation_warnings::ImpossiblePolicy),
+    /// A policy accesses an attribute which is annotated as `@deprecated` in
+    /// the schema.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    DeprecatedAttribute(#[from] validation_warnings::DeprecatedAttribute),
+    /// A policy compares an attribute which has a refinement in the schema
+    /// with a value, and the comparison has the same outcome for every value
+    /// the refinement allows.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ConstantComparison(#[from] validation_warnings::ConstantComparison),
+    /// A policy is shadowed by a broader policy with the same effect.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ShadowedPolicy(#[from] validation_warnings::ShadowedPolicy),
+    /// A permit policy is nullified by a broader forbid policy.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    NullifiedPermit(#[from] validation_warnings::NullifiedPermit),
+    /// A policy uses an entity type which is not declared in the schema, and
+    /// validation against an incomplete schema assumed it exists.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AssumedEntityType(#[from] validation_warnings::AssumedEntityType),
+    /// A policy accesses an attribute which is not declared in the schema,
+    /// and validation against an incomplete schema assumed it exists.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AssumedAttribute(#[from] validation_warnings::AssumedAttribute),
 }
 
 impl ValidationWarning {
+    /// The kind of this warning
+    pub fn kind(&self) -> ValidationWarningKind {
+        match self {
+            Self::MixedScriptString(_) => ValidationWarningKind::MixedScriptString,
+            Self::BidiCharsInString(_) => ValidationWarningKind::BidiCharsInString,
+            Self::BidiCharsInIdentifier(_) => ValidationWarningKind::BidiCharsInIdentifier,
+            Self::MixedScriptIdentifier(_) => ValidationWarningKind::MixedScriptIdentifier,
+            Self::ConfusableIdentifier(_) => ValidationWarningKind::ConfusableIdentifier,
+            Self::ImpossiblePolicy(_) => ValidationWarningKind::ImpossiblePolicy,
+            Self::DeprecatedAttribute(_) => ValidationWarningKind::DeprecatedAttribute,
+            Self::ConstantComparison(_) => ValidationWarningKind::ConstantComparison,
+            Self::ShadowedPolicy(_) => ValidationWarningKind::ShadowedPolicy,
+            Self::NullifiedPermit(_) => ValidationWarningKind::NullifiedPermit,
+            Self::AssumedEntityType(_) => ValidationWarningKind::AssumedEntityType,
+            Self::AssumedAttribute(_) => ValidationWarningKind::AssumedAttribute,
+        }
+    }
+
+    /// The id of the policy where the warning occurred
+    pub fn policy_id(&self) -> &PolicyID {
+        match self {
+            Self::MixedScriptString(w) => &w.policy_id,
+            Self::BidiCharsInString(w) => &w.policy_id,
+            Self::BidiCharsInIdentifier(w) => &w.policy_id,
+            Self::MixedScriptIdentifier(w) => &w.policy_id,
+            Self::ConfusableIdentifier(w) => &w.policy_id,
+            Self::ImpossiblePolicy(w) => &w.policy_id,
+            Self::DeprecatedAttribute(w) => &w.policy_id,
+            Self::ConstantComparison(w) => &w.policy_id,
+            Self::ShadowedPolicy(w) => &w.policy_id,
+            Self::NullifiedPermit(w) => &w.policy_id,
+            Self::AssumedEntityType(w) => &w.policy_id,
+            Self::AssumedAttribute(w) => &w.policy_id,
+        }
+    }
+
+    /// The source location of the warning, if available
+    pub fn source_loc(&self) -> Option<&Loc> {
+        match self {
+            Self::MixedScriptString(w) => w.source_loc.as_ref(),
+            Self::BidiCharsInString(w) => w.source_loc.as_ref(),
+            Self::BidiCharsInIdentifier(w) => w.source_loc.as_ref(),
+            Self::MixedScriptIdentifier(w) => w.source_loc.as_ref(),
+            Self::ConfusableIdentifier(w) => w.source_loc.as_ref(),
+            Self::ImpossiblePolicy(w) => w.source_loc.as_ref(),
+            Self::DeprecatedAttribute(w) => w.source_loc.as_ref(),
+            Self::ConstantComparison(w) => w.source_loc.as_ref(),
+            Self::ShadowedPolicy(w) => w.source_loc.as_ref(),
+            Self::NullifiedPermit(w) => w.source_loc.as_ref(),
+            Self::AssumedEntityType(w) => w.source_loc.as_ref(),
+            Self::AssumedAttribute(w) => w.source_loc.as_ref(),
+        }
+    }
+
     pub(crate) fn mixed_script_string(
         source_loc: Option<Loc>,
         policy_id: PolicyID,
@@ -530,4 +683,183 @@ impl ValidationWarning {
         }
         .into()
     }
+
+    pub(crate) fn deprecated_attribute(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        attr: impl Into<String>,
+        reason: Option<impl Into<String>>,
+    ) -> Self {
+        validation_warnings::DeprecatedAttribute {
+            source_loc,
+            policy_id,
+            attr: attr.into(),
+            reason: reason.map(Into::into),
+        }
+        .into()
+    }
+
+    pub(crate) fn constant_comparison(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        attr: impl Into<String>,
+        value: impl Into<String>,
+        refinement: impl Into<String>,
+    ) -> Self {
+        validation_warnings::ConstantComparison {
+            source_loc,
+            policy_id,
+            attr: attr.into(),
+            value: value.into(),
+            refinement: refinement.into(),
+        }
+        .into()
+    }
+
+    pub(crate) fn shadowed_policy(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        shadowing_policy_id: PolicyID,
+        scope: String,
+    ) -> Self {
+        validation_warnings::ShadowedPolicy {
+            source_loc,
+            policy_id,
+            shadowing_policy_id,
+            scope,
+        }
+        .into()
+    }
+
+    pub(crate) fn nullified_permit(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        forbid_policy_id: PolicyID,
+        scope: String,
+    ) -> Self {
+        validation_warnings::NullifiedPermit {
+            source_loc,
+            policy_id,
+            forbid_policy_id,
+            scope,
+        }
+        .into()
+    }
+
+    pub(crate) fn assumed_entity_type(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        entity_type: String,
+    ) -> Self {
+        validation_warnings::AssumedEntityType {
+            source_loc,
+            policy_id,
+            entity_type,
+        }
+        .into()
+    }
+
+    pub(crate) fn assumed_attribute(
+        source_loc: Option<Loc>,
+        policy_id: PolicyID,
+        attribute_access: validation_errors::AttributeAccess,
+    ) -> Self {
+        validation_warnings::AssumedAttribute {
+            source_loc,
+            policy_id,
+            attribute_access,
+        }
+        .into()
+    }
+}
+
+/// The kinds of [`ValidationWarning`], without any warning-specific
+/// information.
+///
+/// Each kind has a `snake_case` name, which is used to refer to it in
+/// `@allow_warning` policy annotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ValidationWarningKind {
+    /// See [`ValidationWarning::MixedScriptString`]
+    MixedScriptString,
+    /// See [`ValidationWarning::BidiCharsInString`]
+    BidiCharsInString,
+    /// See [`ValidationWarning::BidiCharsInIdentifier`]
+    BidiCharsInIdentifier,
+    /// See [`ValidationWarning::MixedScriptIdentifier`]
+    MixedScriptIdentifier,
+    /// See [`ValidationWarning::ConfusableIdentifier`]
+    ConfusableIdentifier,
+    /// See [`ValidationWarning::ImpossiblePolicy`]
+    ImpossiblePolicy,
+    /// See [`ValidationWarning::DeprecatedAttribute`]
+    DeprecatedAttribute,
+    /// See [`ValidationWarning::ConstantComparison`]
+    ConstantComparison,
+    /// See [`ValidationWarning::ShadowedPolicy`]
+    ShadowedPolicy,
+    /// See [`ValidationWarning::NullifiedPermit`]
+    NullifiedPermit,
+    /// See [`ValidationWarning::AssumedEntityType`]
+    AssumedEntityType,
+    /// See [`ValidationWarning::AssumedAttribute`]
+    AssumedAttribute,
+}
+
+impl ValidationWarningKind {
+    /// All warning kinds
+    pub const ALL: [Self; 12] = [
+        Self::MixedScriptString,
+        Self::BidiCharsInString,
+        Self::BidiCharsInIdentifier,
+        Self::MixedScriptIdentifier,
+        Self::ConfusableIdentifier,
+        Self::ImpossiblePolicy,
+        Self::DeprecatedAttribute,
+        Self::ConstantComparison,
+        Self::ShadowedPolicy,
+        Self::NullifiedPermit,
+        Self::AssumedEntityType,
+        Self::AssumedAttribute,
+    ];
+
+    /// The `snake_case` name of this kind, e.g., `impossible_policy`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MixedScriptString => "mixed_script_string",
+            Self::BidiCharsInString => "bidi_chars_in_string",
+            Self::BidiCharsInIdentifier => "bidi_chars_in_identifier",
+            Self::MixedScriptIdentifier => "mixed_script_identifier",
+            Self::ConfusableIdentifier => "confusable_identifier",
+            Self::ImpossiblePolicy => "impossible_policy",
+            Self::DeprecatedAttribute => "deprecated_attribute",
+            Self::ConstantComparison => "constant_comparison",
+            Self::ShadowedPolicy => "shadowed_policy",
+            Self::NullifiedPermit => "nullified_permit",
+            Self::AssumedEntityType => "assumed_entity_type",
+            Self::AssumedAttribute => "assumed_attribute",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationWarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Error parsing a [`ValidationWarningKind`] from its name
+#[derive(Debug, Clone, PartialEq, Eq, Diagnostic, Error)]
+#[error("`{0}` is not the name of a validation warning")]
+pub struct UnknownWarningKindError(pub(crate) String);
+
+impl std::str::FromStr for ValidationWarningKind {
+    type Err = UnknownWarningKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| UnknownWarningKindError(s.to_string()))
+    }
 }
@@ -397,6 +397,31 @@ impl Diagnostic for UnsafeTagAccess {
     }
 }
 
+/// Structure containing details about an unsafe map access error.
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+#[error("for policy `{policy_id}`, unable to guarantee safety of access to map key `{key}`")]
+pub struct UnsafeMapAccess {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// Map which we tried to access
+    pub map: Expr<Option<Type>>,
+    /// Key which we tried to access. May be a nonconstant `Expr`.
+    pub key: Expr<Option<Type>>,
+}
+
+impl Diagnostic for UnsafeMapAccess {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "try testing for the key's presence with `{}.containsKey({}) && ..`",
+            &self.map, &self.key
+        )))
+    }
+}
+
 /// Structure containing details about a no-tags-allowed error.
 #[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
 #[error(
@@ -512,17 +537,28 @@ pub struct EntityDerefLevelViolation {
 pub enum EntityDerefViolationKind {
     /// The policy exceeded the maximum allowed level
     #[error(
-        "this policy requires level {actual_level}, which exceeds the maximum allowed level ({allowed_level})"
+        "this policy requires level {actual_level}, which exceeds the maximum allowed level ({allowed_level}), in `{access_chain}`"
     )]
     MaximumLevelExceeded {
-        /// The maximum level allowed by the schema
+        /// The maximum level allowed for the policy
         allowed_level: EntityDerefLevel,
         /// The actual level this policy uses
         actual_level: EntityDerefLevel,
+        /// The chain of entity dereferences which exceeds the allowed level,
+        /// e.g., `principal.manager.manager.name`
+        access_chain: SmolStr,
     },
     /// The policy dereferences an entity literal, which isn't allowed at any level
     #[error("entity literals cannot be dereferenced at any level")]
     LiteralDerefTarget,
+    /// The policy's `@max_deref_level` annotation is not a valid level
+    #[error(
+        "the `@max_deref_level` annotation must be a non-negative integer, but found `{value}`"
+    )]
+    InvalidLevelAnnotation {
+        /// The value of the annotation
+        value: SmolStr,
+    },
 }
 
 impl Diagnostic for EntityDerefLevelViolation {
@@ -543,6 +579,65 @@ impl Diagnostic for EmptySetForbidden {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
 }
 
+/// Structure containing details about a policy whose action scope constraint
+/// includes actions which don't declare both the principal and resource types
+/// they apply to
+#[derive(Debug, Clone, Error, Hash, Eq, PartialEq)]
+#[error("for policy `{policy_id}`, the action scope constraint includes {} which {} not fully specified: {}",
+    if .actions.len() > 1 { "actions" } else { "an action" },
+    if .actions.len() > 1 { "are" } else { "is" },
+    .actions.iter().join(", "))]
+pub struct UnspecifiedActionApplication {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the error occurred
+    pub policy_id: PolicyID,
+    /// The actions which are not fully specified, in sorted order
+    pub actions: Vec<EntityUID>,
+}
+
+impl Diagnostic for UnspecifiedActionApplication {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new("the validator is configured to require that every action declares at least one principal type and one resource type in its `appliesTo`"))
+    }
+}
+
+/// A validation warning which was configured to be reported as an error
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
+#[error("{warning}")]
+pub struct WarningAsError {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The warning
+    pub warning: Box<super::ValidationWarning>,
+}
+
+impl Diagnostic for WarningAsError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.warning.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.warning.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.warning.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.warning.labels()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Error)
+    }
+}
+
 /// The policy passes a non-literal to an extension constructor, which is
 /// forbidden in strict validation
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Error)]
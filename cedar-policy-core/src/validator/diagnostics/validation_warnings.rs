@@ -27,6 +27,7 @@ macro_rules! impl_diagnostic_warning {
     };
 }
 
+use crate::validator::validation_errors::AttributeAccess;
 use crate::{ast::PolicyID, parser::Loc};
 use miette::Diagnostic;
 use thiserror::Error;
@@ -135,3 +136,135 @@ impl Diagnostic for ImpossiblePolicy {
     impl_diagnostic_from_source_loc_opt_field!(source_loc);
     impl_diagnostic_warning!();
 }
+
+/// Warning for policies which access attributes annotated as `@deprecated` in
+/// the schema
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, attribute `{attr}` is deprecated{}", .reason.as_ref().map(|reason| format!(": {reason}")).unwrap_or_default())]
+pub struct DeprecatedAttribute {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// Name of the deprecated attribute
+    pub attr: String,
+    /// Reason given in the `@deprecated` annotation, if any
+    pub reason: Option<String>,
+}
+
+impl Diagnostic for DeprecatedAttribute {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+}
+
+/// Warning for policies which compare an attribute which has a refinement in
+/// the schema with a value, where the comparison has the same outcome for
+/// every value the refinement allows
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, comparing attribute `{attr}` with `{value}` always has the same outcome, because the attribute has the refinement `{refinement}`")]
+pub struct ConstantComparison {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// Name of the refined attribute
+    pub attr: String,
+    /// Value the attribute is compared with
+    pub value: String,
+    /// Refinement of the attribute, as written in the Cedar schema syntax
+    pub refinement: String,
+}
+
+impl Diagnostic for ConstantComparison {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+}
+
+/// Warning for policies which are shadowed by a broader policy with the same
+/// effect, so that removing them doesn't change any authorization decision
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, policy `{shadowing_policy_id}` has the same effect and applies to every request in the scope `{scope}`")]
+pub struct ShadowedPolicy {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// Policy ID of the broader policy
+    pub shadowing_policy_id: PolicyID,
+    /// Scope of the shadowed policy, which the policies share
+    pub scope: String,
+}
+
+impl Diagnostic for ShadowedPolicy {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "the policy is redundant unless policy `{}` is removed or narrowed",
+            self.shadowing_policy_id
+        )))
+    }
+}
+
+/// Warning for permit policies which are nullified by a broader forbid
+/// policy, so that they never grant access
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, forbid policy `{forbid_policy_id}` applies to every request in the scope `{scope}`, so the policy never grants access")]
+pub struct NullifiedPermit {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// Policy ID of the broader forbid policy
+    pub forbid_policy_id: PolicyID,
+    /// Scope of the permit policy, which the policies share
+    pub scope: String,
+}
+
+impl Diagnostic for NullifiedPermit {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+}
+
+/// Warning for entity types which are not declared in the schema, and which
+/// validation against an incomplete schema assumed to exist
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, entity type `{entity_type}` is not declared in the schema and is assumed to exist")]
+pub struct AssumedEntityType {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The entity type which is not declared in the schema
+    pub entity_type: String,
+}
+
+impl Diagnostic for AssumedEntityType {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+}
+
+/// Warning for attributes which are not declared in the schema, and which
+/// validation against an incomplete schema assumed to exist
+#[derive(Debug, Clone, PartialEq, Error, Eq, Hash)]
+#[error("for policy `{policy_id}`, attribute {attribute_access} is not declared in the schema and is assumed to exist")]
+pub struct AssumedAttribute {
+    /// Source location
+    pub source_loc: Option<Loc>,
+    /// Policy ID where the warning occurred
+    pub policy_id: PolicyID,
+    /// The attribute which is not declared in the schema
+    pub attribute_access: AttributeAccess,
+}
+
+impl Diagnostic for AssumedAttribute {
+    impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    impl_diagnostic_warning!();
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "the rest of the expression accessing the attribute is not validated",
+        ))
+    }
+}
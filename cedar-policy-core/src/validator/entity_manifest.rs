@@ -455,7 +455,7 @@ pub fn compute_entity_manifest(
 
     let mut manifest: HashMap<RequestType, RootAccessTrie> = HashMap::new();
 
-    let typechecker = Typechecker::new(validator.schema(), ValidationMode::Strict);
+    let typechecker = validator.typechecker(ValidationMode::Strict);
     // now, for each policy we add the data it requires to the manifest
     for policy in policies.policies() {
         // typecheck the policy and get all the request environments
@@ -623,6 +623,15 @@ fn entity_manifest_from_expr(
             feature: "entity tags".into(),
         }
         .into()),
+        #[cfg(feature = "maps")]
+        ExprKind::BinaryApp {
+            op: BinaryOp::GetKey | BinaryOp::ContainsKey,
+            arg1: _,
+            arg2: _,
+        } => Err(UnsupportedCedarFeatureError {
+            feature: "maps".into(),
+        }
+        .into()),
         ExprKind::ExtensionFunctionApp { fn_name: _, args } => {
             // WARNING: this code assumes that extension functions
             // all take primitives as inputs and produce
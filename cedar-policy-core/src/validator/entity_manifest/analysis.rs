@@ -270,7 +270,8 @@ fn type_to_access_trie(ty: &Type) -> AccessTrie {
         | Type::Bool(_)
         | Type::Long
         | Type::String
-        | Type::Set { .. } => AccessTrie::new(),
+        | Type::Set { .. }
+        | Type::Map { .. } => AccessTrie::new(),
         Type::Record { attrs, .. } => {
             let mut fields = HashMap::new();
             for (attr_name, attr_type) in attrs.iter() {
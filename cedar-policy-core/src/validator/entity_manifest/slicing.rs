@@ -134,6 +134,21 @@ impl EntityManifest {
         let mut slicer = EntitySlicer { entities };
         load_entities(self, request, &mut slicer)
     }
+
+    /// Use this entity manifest to find an entity slice, fetching the entities
+    /// it needs from `loader`. Each entity is fetched at most once, and the
+    /// entities needed at each level of the manifest are fetched in one batch.
+    pub fn slice_entities_with_loader(
+        &self,
+        loader: &mut dyn crate::entities::loader::EntityLoader,
+        request: &Request,
+    ) -> Result<Entities, EntitySliceError> {
+        let mut slicer = LoaderSlicer {
+            loader,
+            loaded: HashMap::new(),
+        };
+        load_entities(self, request, &mut slicer)
+    }
 }
 
 struct EntitySlicer<'a> {
@@ -162,29 +177,78 @@ impl EntityLoader for EntitySlicer<'_> {
         &mut self,
         entities: &[AncestorsRequest],
     ) -> Result<Vec<HashSet<EntityUID>>, EntitySliceError> {
-        let mut res = vec![];
-
-        for request in entities {
-            if let Dereference::Data(entity) = self.entities.entity(&request.entity_id) {
-                let mut ancestors = HashSet::new();
+        Ok(entities
+            .iter()
+            .map(|request| match self.entities.entity(&request.entity_id) {
+                Dereference::Data(entity) => required_ancestors(entity, request),
+                // if the entity isn't there, we don't need any ancestors
+                _ => HashSet::new(),
+            })
+            .collect())
+    }
+}
 
-                for required_ancestor in &request.ancestors {
-                    if entity.is_descendant_of(required_ancestor) {
-                        ancestors.insert(required_ancestor.clone());
-                    }
-                }
+/// Slices entities fetched by an entity loader, keeping the full entities so
+/// that their ancestors can be found without fetching them again
+struct LoaderSlicer<'a> {
+    loader: &'a mut dyn crate::entities::loader::EntityLoader,
+    loaded: HashMap<EntityUID, Option<Entity>>,
+}
 
-                res.push(ancestors);
-            } else {
-                // if the entity isn't there, we don't need any ancestors
-                res.push(HashSet::new());
+impl EntityLoader for LoaderSlicer<'_> {
+    fn load_entities(
+        &mut self,
+        to_load: &[EntityRequest],
+    ) -> Result<Vec<EntityAnswer>, EntitySliceError> {
+        let missing: HashSet<EntityUID> = to_load
+            .iter()
+            .map(|request| &request.entity_id)
+            .filter(|uid| !self.loaded.contains_key(uid))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            let mut loaded = self.loader.load_entities(&missing);
+            // a requested entity the loader didn't return doesn't exist
+            for uid in missing {
+                loaded.entry(uid).or_insert(None);
             }
+            self.loaded.extend(loaded);
         }
+        to_load
+            .iter()
+            .map(|request| match self.loaded.get(&request.entity_id) {
+                // filter down the entity fields to those requested
+                Some(Some(entity)) => Ok(Some(request.access_trie.slice_entity(entity)?)),
+                _ => Ok(None),
+            })
+            .collect()
+    }
 
-        Ok(res)
+    fn load_ancestors(
+        &mut self,
+        entities: &[AncestorsRequest],
+    ) -> Result<Vec<HashSet<EntityUID>>, EntitySliceError> {
+        Ok(entities
+            .iter()
+            .map(|request| match self.loaded.get(&request.entity_id) {
+                Some(Some(entity)) => required_ancestors(entity, request),
+                // if the entity isn't there, we don't need any ancestors
+                _ => HashSet::new(),
+            })
+            .collect())
     }
 }
 
+/// The ancestors of `entity` which `request` asks for
+fn required_ancestors(entity: &Entity, request: &AncestorsRequest) -> HashSet<EntityUID> {
+    request
+        .ancestors
+        .iter()
+        .filter(|required_ancestor| entity.is_descendant_of(required_ancestor))
+        .cloned()
+        .collect()
+}
+
 impl AccessTrie {
     /// Given an entities store, an entity id, and a resulting store
     /// Slice the entities and put them in the resulting store.
@@ -358,6 +422,46 @@ action Read appliesTo {
                 sliced_entities.to_json_value().unwrap()
             );
         }
+
+        // Slicing with an entity loader gives the same slice, fetching
+        // each entity at most once
+        let mut loader = CountingLoader {
+            entities: &original_entities,
+            fetched: HashSet::new(),
+        };
+        let loaded_entities = manifest
+            .slice_entities_with_loader(&mut loader, &request)
+            .unwrap();
+        if !loaded_entities.deep_eq(&expected_entities) {
+            panic!(
+                "Entities sliced with a loader differed from expected. Expected:\n{}\nGot:\n{}",
+                expected_entities.to_json_value().unwrap(),
+                loaded_entities.to_json_value().unwrap()
+            );
+        }
+    }
+
+    struct CountingLoader<'a> {
+        entities: &'a Entities,
+        fetched: HashSet<EntityUID>,
+    }
+
+    impl crate::entities::loader::EntityLoader for CountingLoader<'_> {
+        fn load_entities(
+            &mut self,
+            uids: &HashSet<EntityUID>,
+        ) -> HashMap<EntityUID, Option<Entity>> {
+            uids.iter()
+                .map(|uid| {
+                    assert!(self.fetched.insert(uid.clone()), "{uid} fetched twice");
+                    let entity = match self.entities.entity(uid) {
+                        Dereference::Data(entity) => Some(entity.clone()),
+                        _ => None,
+                    };
+                    (uid.clone(), entity)
+                })
+                .collect()
+        }
     }
 
     #[test]
@@ -124,6 +124,7 @@ impl AccessTrie {
             | Type::Long
             | Type::String
             | Type::Set { .. }
+            | Type::Map { .. }
             | Type::ExtensionType { .. } => {
                 assert!(self.children.is_empty());
                 return Ok(HashMap::default());
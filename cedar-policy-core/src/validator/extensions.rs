@@ -17,10 +17,10 @@
 //! This module contains type information for all of the standard Cedar extensions.
 
 use std::collections::{BTreeSet, HashMap};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 use crate::{
-    ast::{Name, RestrictedExpr, Value},
+    ast::{Extension, Name, RestrictedExpr, Value},
     evaluator::{EvaluationError, RestrictedEvaluator},
     extensions::{util, Extensions},
 };
@@ -41,25 +41,60 @@ pub mod decimal;
 #[cfg(feature = "datetime")]
 pub mod datetime;
 
+#[cfg(feature = "strings")]
+pub mod strings;
+
+#[cfg(feature = "bytes")]
+pub mod bytes;
+
+#[cfg(feature = "lookup")]
+pub mod lookup;
+
+#[cfg(feature = "geo")]
+pub mod geo;
+
+#[cfg(feature = "identifiers")]
+pub mod identifiers;
+
+#[cfg(feature = "mediatype")]
+pub mod mediatype;
+
 pub mod partial_evaluation;
 
-static ALL_AVAILABLE_EXTENSION_SCHEMA_OBJECTS: LazyLock<Vec<ExtensionSchema>> =
+pub mod custom;
+
+static BUILTIN_EXTENSION_SCHEMA_OBJECTS: LazyLock<Vec<ExtensionSchema>> = LazyLock::new(|| {
+    vec![
+        #[cfg(feature = "ipaddr")]
+        ipaddr::extension_schema(),
+        #[cfg(feature = "decimal")]
+        decimal::extension_schema(),
+        #[cfg(feature = "datetime")]
+        datetime::extension_schema(),
+        #[cfg(feature = "strings")]
+        strings::extension_schema(),
+        #[cfg(feature = "bytes")]
+        bytes::extension_schema(),
+        #[cfg(feature = "lookup")]
+        lookup::extension_schema(),
+        #[cfg(feature = "geo")]
+        geo::extension_schema(),
+        #[cfg(feature = "identifiers")]
+        identifiers::extension_schema(),
+        #[cfg(feature = "mediatype")]
+        mediatype::extension_schema(),
+        #[cfg(feature = "partial-eval")]
+        partial_evaluation::extension_schema(),
+    ]
+});
+
+static ALL_AVAILABLE_EXTENSION_SCHEMAS: LazyLock<Arc<ExtensionSchemas<'static>>> =
     LazyLock::new(|| {
-        vec![
-            #[cfg(feature = "ipaddr")]
-            ipaddr::extension_schema(),
-            #[cfg(feature = "decimal")]
-            decimal::extension_schema(),
-            #[cfg(feature = "datetime")]
-            datetime::extension_schema(),
-            #[cfg(feature = "partial-eval")]
-            partial_evaluation::extension_schema(),
-        ]
+        Arc::new(ExtensionSchemas::with_custom_extensions(
+            Extensions::all_available().custom_extensions(),
+        ))
     });
 
-static ALL_AVAILABLE_EXTENSION_SCHEMAS: LazyLock<ExtensionSchemas<'static>> =
-    LazyLock::new(ExtensionSchemas::build_all_available);
-
 /// Aggregate structure containing information such as function signatures for multiple [`ExtensionSchema`].
 /// Ensures that no function name is defined mode than once.
 /// Intentionally does not derive `Clone` to avoid clones of the `HashMap`. For the
@@ -72,25 +107,46 @@ pub struct ExtensionSchemas<'a> {
     /// extension function lookup that at most one extension functions exists
     /// for a name.
     function_types: HashMap<&'a Name, &'a ExtensionFunctionType>,
+    /// Schemas of custom extensions owned by this object, whose functions are
+    /// looked up after `function_types`
+    custom: Vec<ExtensionSchema>,
     /// Extension types that support operator overloading
     types_with_operator_overloading: BTreeSet<&'a Name>,
 }
 
-impl<'a> ExtensionSchemas<'a> {
-    fn build_all_available() -> ExtensionSchemas<'static> {
+impl ExtensionSchemas<'static> {
+    /// Get schemas for all the available extensions.
+    pub fn all_available() -> &'static ExtensionSchemas<'static> {
+        &ALL_AVAILABLE_EXTENSION_SCHEMAS
+    }
+
+    /// Like [`ExtensionSchemas::all_available()`], but shared, for holding
+    /// alongside schemas built with
+    /// [`ExtensionSchemas::with_custom_extensions()`]
+    pub fn all_available_shared() -> Arc<ExtensionSchemas<'static>> {
+        Arc::clone(&ALL_AVAILABLE_EXTENSION_SCHEMAS)
+    }
+
+    /// Get schemas for all builtin extensions and the given custom
+    /// extensions, e.g., those of an [`Extensions`] built with
+    /// [`Extensions::with_custom_extensions()`], which checks that their
+    /// functions don't conflict with the builtin ones.
+    pub fn with_custom_extensions(custom: &[Extension]) -> ExtensionSchemas<'static> {
         #[expect(
             clippy::expect_used,
             reason = "Builtin extension function definitions never conflict. Also tested by many different test cases."
         )]
-        ExtensionSchemas::specific_extension_schemas(&ALL_AVAILABLE_EXTENSION_SCHEMA_OBJECTS)
-            .expect("Default extension schemas should never error on initialization")
-    }
-
-    /// Get schemas for all the available extensions.
-    pub fn all_available() -> &'static ExtensionSchemas<'static> {
-        &ALL_AVAILABLE_EXTENSION_SCHEMAS
+        let builtins =
+            ExtensionSchemas::specific_extension_schemas(&BUILTIN_EXTENSION_SCHEMA_OBJECTS)
+                .expect("Default extension schemas should never error on initialization");
+        ExtensionSchemas {
+            custom: custom.iter().map(custom::extension_schema).collect(),
+            ..builtins
+        }
     }
+}
 
+impl<'a> ExtensionSchemas<'a> {
     /// Get a new `ExtensionsSchemas` with these specific extensions enabled. No
     /// two extensions may declare functions with the same name.
     pub fn specific_extension_schemas(
@@ -113,6 +169,7 @@ impl<'a> ExtensionSchemas<'a> {
 
         Ok(Self {
             function_types,
+            custom: Vec::new(),
             types_with_operator_overloading,
         })
     }
@@ -120,7 +177,12 @@ impl<'a> ExtensionSchemas<'a> {
     /// Get the [`ExtensionFunctionType`] for a function with this [`Name`].
     /// Return `None` if no such function exists.
     pub fn func_type(&self, name: &Name) -> Option<&ExtensionFunctionType> {
-        self.function_types.get(name).copied()
+        self.function_types.get(name).copied().or_else(|| {
+            self.custom
+                .iter()
+                .flat_map(|ext| ext.function_types())
+                .find(|f| f.name() == name)
+        })
     }
 
     /// Query if `ext_ty_name` supports operator overloading
@@ -0,0 +1,56 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains type information for custom extensions registered with
+//! [`crate::extensions::register_custom_extension()`]. Unlike the builtin
+//! extensions, these are derived from the declared `SchemaType`s of each
+//! function, and no further argument validation is performed.
+
+use std::sync::Arc;
+
+use crate::ast::Extension;
+use crate::entities::SchemaType;
+use crate::validator::extension_schema::{ExtensionFunctionType, ExtensionSchema};
+use crate::validator::types::Type;
+
+/// Convert the declared type of a custom function argument or return value.
+/// Registration only accepts the types handled here, so the fallback is never
+/// used in practice.
+fn to_validator_type(ty: &SchemaType) -> Type {
+    match ty {
+        SchemaType::Bool => Type::primitive_boolean(),
+        SchemaType::Long => Type::primitive_long(),
+        SchemaType::String => Type::primitive_string(),
+        SchemaType::Set { element_ty } => Type::set(Arc::new(to_validator_type(element_ty))),
+        SchemaType::Entity { ty } => Type::named_entity_reference(ty.clone()),
+        SchemaType::Extension { name } => Type::extension(name.clone()),
+        _ => Type::Never,
+    }
+}
+
+/// Construct the extension schema for a custom extension
+pub fn extension_schema(ext: &Extension) -> ExtensionSchema {
+    let fun_tys = ext.funcs().map(|f| {
+        ExtensionFunctionType::new(
+            f.name().clone(),
+            f.arg_types().iter().map(to_validator_type).collect(),
+            f.return_type().map_or(Type::Never, to_validator_type),
+            None,
+            false,
+        )
+    });
+    ExtensionSchema::new(ext.name().clone(), fun_tys, std::iter::empty())
+}
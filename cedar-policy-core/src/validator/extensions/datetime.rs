@@ -41,7 +41,7 @@ fn get_argument_types(fname: &Name, datetime_ty: &Type, duration_ty: &Type) -> V
     }
     match fname.basename().as_ref() {
         "datetime" | "duration" => vec![Type::primitive_string()],
-        "offset" => vec![datetime_ty.clone(), duration_ty.clone()],
+        "offset" | "dayOfWeek" | "hour" => vec![datetime_ty.clone(), duration_ty.clone()],
         "durationSince" => vec![datetime_ty.clone(), datetime_ty.clone()],
         "toDate" => vec![datetime_ty.clone()],
         "toTime" => vec![datetime_ty.clone()],
@@ -60,9 +60,8 @@ fn get_return_type(fname: &Name, datetime_ty: &Type, duration_ty: &Type) -> Type
     match fname.basename().as_ref() {
         "datetime" | "offset" | "toDate" => datetime_ty.clone(),
         "duration" | "durationSince" | "toTime" => duration_ty.clone(),
-        "toMilliseconds" | "toSeconds" | "toMinutes" | "toHours" | "toDays" => {
-            Type::primitive_long()
-        }
+        "toMilliseconds" | "toSeconds" | "toMinutes" | "toHours" | "toDays" | "dayOfWeek"
+        | "hour" => Type::primitive_long(),
         _ => panic!("unexpected datetime extension function name: {fname}"),
     }
 }
@@ -86,7 +85,7 @@ fn get_argument_check(fname: &Name) -> Option<ArgumentCheckFn> {
             }))
         }
         "offset" | "durationSince" | "toDate" | "toTime" | "toMilliseconds" | "toSeconds"
-        | "toMinutes" | "toHours" | "toDays" => None,
+        | "toMinutes" | "toHours" | "toDays" | "dayOfWeek" | "hour" => None,
         _ => panic!("unexpected datetime extension function name: {fname}"),
     }
 }
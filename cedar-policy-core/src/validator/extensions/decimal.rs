@@ -41,7 +41,8 @@ fn get_argument_types(fname: &Name, decimal_ty: &Type) -> Vec<types::Type> {
     }
     match fname.basename().as_ref() {
         "decimal" => vec![Type::primitive_string()],
-        "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" => {
+        "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" | "add"
+        | "subtract" | "multiply" => {
             vec![decimal_ty.clone(), decimal_ty.clone()]
         }
         _ => panic!("unexpected decimal extension function name: {fname}"),
@@ -54,7 +55,7 @@ fn get_return_type(fname: &Name, decimal_ty: &Type) -> Type {
         panic!("unexpected decimal extension function name: {fname}")
     }
     match fname.basename().as_ref() {
-        "decimal" => decimal_ty.clone(),
+        "decimal" | "add" | "subtract" | "multiply" => decimal_ty.clone(),
         "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" => {
             Type::primitive_boolean()
         }
@@ -74,7 +75,8 @@ fn get_argument_check(fname: &Name) -> Option<ArgumentCheckFn> {
                 validate_decimal_string(fname.clone(), args)
             }))
         }
-        "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" => None,
+        "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" | "add"
+        | "subtract" | "multiply" => None,
         _ => panic!("unexpected decimal extension function name: {fname}"),
     }
 }
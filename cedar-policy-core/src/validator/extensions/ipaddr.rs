@@ -35,7 +35,7 @@ use super::eval_extension_constructor;
 // out-of-date with the ipaddr extension definition in Core.
 
 #[expect(clippy::panic, reason = "see `Note on safety` above")]
-fn get_argument_types(fname: &Name, ipaddr_ty: &Type) -> Vec<types::Type> {
+fn get_argument_types(fname: &Name, ipaddr_ty: &Type, ipset_ty: &Type) -> Vec<types::Type> {
     if !fname.as_ref().is_unqualified() {
         panic!("unexpected ipaddr extension function name: {fname}")
     }
@@ -43,18 +43,21 @@ fn get_argument_types(fname: &Name, ipaddr_ty: &Type) -> Vec<types::Type> {
         "ip" => vec![Type::primitive_string()],
         "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" => vec![ipaddr_ty.clone()],
         "isInRange" => vec![ipaddr_ty.clone(), ipaddr_ty.clone()],
+        "ipset" => vec![Type::set(Type::primitive_string().into())],
+        "isInSet" => vec![ipaddr_ty.clone(), ipset_ty.clone()],
         _ => panic!("unexpected ipaddr extension function name: {fname}"),
     }
 }
 
 #[expect(clippy::panic, reason = "see `Note on safety` above")]
-fn get_return_type(fname: &Name, ipaddr_ty: &Type) -> Type {
+fn get_return_type(fname: &Name, ipaddr_ty: &Type, ipset_ty: &Type) -> Type {
     if !fname.as_ref().is_unqualified() {
         panic!("unexpected ipaddr extension function name: {fname}")
     }
     match fname.basename().as_ref() {
         "ip" => ipaddr_ty.clone(),
-        "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" | "isInRange" => {
+        "ipset" => ipset_ty.clone(),
+        "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" | "isInRange" | "isInSet" => {
             Type::primitive_boolean()
         }
         _ => panic!("unexpected ipaddr extension function name: {fname}"),
@@ -73,7 +76,11 @@ fn get_argument_check(fname: &Name) -> Option<ArgumentCheckFn> {
                 validate_ip_string(fname.clone(), args)
             }))
         }
-        "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" | "isInRange" => None,
+        // `ipset` is not checked here, since strict validation would then
+        // require its argument to be a literal, which a set is not
+        "ipset" | "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" | "isInRange" | "isInSet" => {
+            None
+        }
         _ => panic!("unexpected ipaddr extension function name: {fname}"),
     }
 }
@@ -82,9 +89,10 @@ fn get_argument_check(fname: &Name) -> Option<ArgumentCheckFn> {
 pub fn extension_schema() -> ExtensionSchema {
     let ipaddr_ext = ipaddr::extension();
     let ipaddr_ty = Type::extension(ipaddr_ext.name().clone());
+    let ipset_ty = Type::extension(ipaddr::names::IPSET_FROM_SET_NAME.clone());
 
     let fun_tys = ipaddr_ext.funcs().map(|f| {
-        let return_type = get_return_type(f.name(), &ipaddr_ty);
+        let return_type = get_return_type(f.name(), &ipaddr_ty, &ipset_ty);
         debug_assert!(f
             .return_type()
             .map(|ty| return_type.is_consistent_with(ty))
@@ -92,7 +100,7 @@ pub fn extension_schema() -> ExtensionSchema {
 
         ExtensionFunctionType::new(
             f.name().clone(),
-            get_argument_types(f.name(), &ipaddr_ty),
+            get_argument_types(f.name(), &ipaddr_ty, &ipset_ty),
             return_type,
             get_argument_check(f.name()),
             f.is_variadic(),
@@ -0,0 +1,90 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Note on panic safety
+//! If any of the panics in this file are triggered, that means that this file has become
+//! out-of-date with the lookup extension definition in Core.
+//! This is tested by the `extension_schema_correctness()` test
+
+use crate::ast::Name;
+use crate::extensions::lookup;
+use crate::validator::extension_schema::{ExtensionFunctionType, ExtensionSchema};
+use crate::validator::types::{self, Type};
+
+// Note on safety:
+// This module depends on the Cedar parser only constructing AST with valid extension calls
+// If any of the panics in this file are triggered, that means that this file has become
+// out-of-date with the lookup extension definition in Core.
+
+#[expect(clippy::panic, reason = "see `Note on safety` above")]
+fn get_argument_types(fname: &Name, optional_ty: &Type) -> Vec<types::Type> {
+    if !fname.as_ref().is_unqualified() {
+        panic!("unexpected lookup extension function name: {fname}")
+    }
+    match fname.basename().as_ref() {
+        // Any record may be traversed; the path is usually not a literal, so
+        // there is no argument check
+        "lookup" => vec![Type::any_record(), Type::primitive_string()],
+        "isPresent" | "asString" | "asLong" | "asBool" => vec![optional_ty.clone()],
+        _ => panic!("unexpected lookup extension function name: {fname}"),
+    }
+}
+
+#[expect(clippy::panic, reason = "see `Note on safety` above")]
+fn get_return_type(fname: &Name, optional_ty: &Type) -> Type {
+    if !fname.as_ref().is_unqualified() {
+        panic!("unexpected lookup extension function name: {fname}")
+    }
+    match fname.basename().as_ref() {
+        "lookup" => optional_ty.clone(),
+        "isPresent" | "asBool" => Type::primitive_boolean(),
+        "asString" => Type::primitive_string(),
+        "asLong" => Type::primitive_long(),
+        _ => panic!("unexpected lookup extension function name: {fname}"),
+    }
+}
+
+/// Construct the extension schema
+pub fn extension_schema() -> ExtensionSchema {
+    let lookup_ext = lookup::extension();
+    let optional_ty = Type::extension(lookup::constants::OPTIONAL_TYPE_NAME.clone());
+
+    let fun_tys = lookup_ext.funcs().map(|f| {
+        let return_type = get_return_type(f.name(), &optional_ty);
+        debug_assert!(f
+            .return_type()
+            .map(|ty| return_type.is_consistent_with(ty))
+            .unwrap_or_else(|| return_type == Type::Never));
+        ExtensionFunctionType::new(
+            f.name().clone(),
+            get_argument_types(f.name(), &optional_ty),
+            return_type,
+            None,
+            false,
+        )
+    });
+    ExtensionSchema::new(lookup_ext.name().clone(), fun_tys, std::iter::empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Ensures that `extension_schema()` does not panic
+    #[test]
+    fn extension_schema_correctness() {
+        let _ = extension_schema();
+    }
+}
@@ -678,6 +678,8 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Deserialize<'de> for EntityType<N
             #[serde(default)]
             tags: RealOption<Type<N>>,
             #[serde(default)]
+            constraints: RealOption<Vec<SmolStr>>,
+            #[serde(default)]
             #[serde(rename = "enum")]
             choices: RealOption<NonEmpty<Eid>>,
             #[serde(default)]
@@ -699,6 +701,9 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Deserialize<'de> for EntityType<N
             if Option::<Type<N>>::from(value.tags).is_some() {
                 unexpected_fields.push("tags");
             }
+            if Option::<Vec<SmolStr>>::from(value.constraints).is_some() {
+                unexpected_fields.push("constraints");
+            }
             if !unexpected_fields.is_empty() {
                 return Err(serde::de::Error::custom(format!(
                     "unexpected field: {}",
@@ -716,6 +721,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Deserialize<'de> for EntityType<N
                     member_of_types: Option::from(value.member_of_types).unwrap_or_default(),
                     shape: Option::from(value.shape).unwrap_or_default(),
                     tags: Option::from(value.tags),
+                    constraints: Option::from(value.constraints).unwrap_or_default(),
                 }),
                 annotations: value.annotations,
                 loc: None,
@@ -746,6 +752,12 @@ pub struct StandardEntityType<N> {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub tags: Option<Type<N>>,
+    /// Invariants over their attributes which entities of this
+    /// [`StandardEntityType`] must satisfy. Each is a Cedar expression which
+    /// refers to the attributes by their bare names, e.g., `end_time > start_time`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub constraints: Vec<SmolStr>,
 }
 
 #[cfg(test)]
@@ -787,6 +799,7 @@ impl EntityType<RawName> {
                     tags: ty
                         .tags
                         .map(|ty| ty.conditionally_qualify_type_references(ns)),
+                    constraints: ty.constraints,
                 }),
                 annotations,
                 loc,
@@ -829,6 +842,7 @@ impl EntityType<ConditionalName> {
                         .tags
                         .map(|ty| ty.fully_qualify_type_references(all_defs))
                         .transpose()?,
+                    constraints: ty.constraints,
                 }),
                 annotations,
                 loc,
@@ -1248,7 +1262,14 @@ impl ActionEntityUID<ConditionalName> {
                 }
             }
         }
-        Err(ActionNotDefinedError(nonempty!(self)))
+        // Actions declared in the empty namespace can't be referred to from
+        // another namespace, so they aren't suggested
+        let suggestions = all_defs
+            .actions_with_id(&self.id)
+            .filter(|euid| !euid.entity_type().name().is_unqualified())
+            .cloned()
+            .collect();
+        Err(ActionNotDefinedError(nonempty!(self), suggestions))
     }
 
     /// Get the possible fully-qualified [`ActionEntityUID<InternalName>`]s
@@ -1404,9 +1425,18 @@ impl<N> Type<N> {
                     Box::new(it.chain(tys))
                 }),
             Type::Type {
-                ty: TypeVariant::Set { element },
+                ty: TypeVariant::Set { element } | TypeVariant::Map { element },
                 ..
             } => element.common_type_references(),
+            Type::Type {
+                ty: TypeVariant::Union { members },
+                ..
+            } => members
+                .iter()
+                .map(|ty| ty.common_type_references())
+                .fold(Box::new(std::iter::empty()), |it, tys| {
+                    Box::new(it.chain(tys))
+                }),
             Type::Type {
                 ty: TypeVariant::EntityOrCommon { type_name },
                 ..
@@ -1428,7 +1458,7 @@ impl<N> Type<N> {
                 ..
             } => Some(true),
             Self::Type {
-                ty: TypeVariant::Set { element },
+                ty: TypeVariant::Set { element } | TypeVariant::Map { element },
                 ..
             } => element.is_extension(),
             Self::Type {
@@ -1441,6 +1471,16 @@ impl<N> Type<N> {
                     Some(false) => Some(a),
                     None => None,
                 }),
+            Self::Type {
+                ty: TypeVariant::Union { members },
+                ..
+            } => members
+                .iter()
+                .try_fold(false, |a, ty| match ty.is_extension() {
+                    Some(true) => Some(true),
+                    Some(false) => Some(a),
+                    None => None,
+                }),
             Self::Type { .. } => Some(false),
             Self::CommonTypeRef { .. } => None,
         }
@@ -1590,9 +1630,22 @@ impl TypeVariant<InternalName> {
                     element: Box::new(element.resolve_entity_or_common_type(all_defs)?),
                 }))
             }
+            TypeVariant::Map { element } => {
+                Ok(ResolvedTypeVariant::TypeVariant(TypeVariant::Map {
+                    element: Box::new(element.resolve_entity_or_common_type(all_defs)?),
+                }))
+            }
             TypeVariant::Record(record_type) => Ok(ResolvedTypeVariant::TypeVariant(
                 TypeVariant::Record(record_type.resolve_record_type_entity_or_common(all_defs)?),
             )),
+            TypeVariant::Union { members } => {
+                Ok(ResolvedTypeVariant::TypeVariant(TypeVariant::Union {
+                    members: members
+                        .into_iter()
+                        .map(|member| member.resolve_entity_or_common_type(all_defs))
+                        .collect::<std::result::Result<_, TypeNotDefinedError>>()?,
+                }))
+            }
             // Other variants don't contain EntityOrCommon types
             other => Ok(ResolvedTypeVariant::TypeVariant(other)),
         }
@@ -1625,6 +1678,10 @@ impl TypeOfAttribute<InternalName> {
         Ok(TypeOfAttribute {
             ty: self.ty.resolve_entity_or_common_type(all_defs)?,
             required: self.required,
+            default: self.default,
+            range: self.range,
+            pattern: self.pattern,
+            satisfying: self.satisfying,
             annotations: self.annotations,
             #[cfg(feature = "extended-schema")]
             loc: self.loc,
@@ -1650,6 +1707,7 @@ impl EntityType<InternalName> {
                             .tags
                             .map(|tags| tags.resolve_entity_or_common_type(all_defs))
                             .transpose()?,
+                        constraints: standard.constraints,
                     })
                 }
                 EntityTypeKind::Enum { choices } => EntityTypeKind::Enum { choices },
@@ -1726,6 +1784,7 @@ enum TypeFields {
     Attributes,
     AdditionalAttributes,
     Name,
+    Members,
 }
 
 // This macro is used to avoid duplicating the fields names when calling
@@ -1747,6 +1806,9 @@ macro_rules! type_field_name {
     (Name) => {
         "name"
     };
+    (Members) => {
+        "members"
+    };
 }
 
 impl TypeFields {
@@ -1757,6 +1819,7 @@ impl TypeFields {
             TypeFields::Attributes => type_field_name!(Attributes),
             TypeFields::AdditionalAttributes => type_field_name!(AdditionalAttributes),
             TypeFields::Name => type_field_name!(Name),
+            TypeFields::Members => type_field_name!(Members),
         }
     }
 }
@@ -1786,13 +1849,16 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Visitor<'de> for TypeVisitor<N> {
     where
         M: MapAccess<'de>,
     {
-        use TypeFields::{AdditionalAttributes, Attributes, Element, Name, Type as TypeField};
+        use TypeFields::{
+            AdditionalAttributes, Attributes, Element, Members, Name, Type as TypeField,
+        };
 
         let mut type_name: Option<SmolStr> = None;
         let mut element: Option<Type<N>> = None;
         let mut attributes: Option<AttributesTypeMap> = None;
         let mut additional_attributes: Option<bool> = None;
         let mut name: Option<SmolStr> = None;
+        let mut members: Option<Vec<Type<N>>> = None;
 
         // Gather all the fields in the object. Any fields that are not one of
         // the possible fields for some schema type will have been reported by
@@ -1831,6 +1897,12 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Visitor<'de> for TypeVisitor<N> {
                     }
                     name = Some(map.next_value()?);
                 }
+                Members => {
+                    if members.is_some() {
+                        return Err(serde::de::Error::duplicate_field(Members.as_str()));
+                    }
+                    members = Some(map.next_value()?);
+                }
             }
         }
 
@@ -1840,6 +1912,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> Visitor<'de> for TypeVisitor<N> {
             attributes,
             additional_attributes,
             name,
+            members,
         )
     }
 }
@@ -1855,11 +1928,14 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
         attributes: Option<AttributesTypeMap>,
         additional_attributes: Option<bool>,
         name: Option<SmolStr>,
+        members: Option<Vec<Type<N>>>,
     ) -> std::result::Result<Type<N>, M::Error>
     where
         M: MapAccess<'de>,
     {
-        use TypeFields::{AdditionalAttributes, Attributes, Element, Name, Type as TypeField};
+        use TypeFields::{
+            AdditionalAttributes, Attributes, Element, Members, Name, Type as TypeField,
+        };
         // Fields that remain to be parsed
         let mut remaining_fields = [
             (TypeField, type_name.is_some()),
@@ -1867,6 +1943,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
             (Attributes, attributes.is_some()),
             (AdditionalAttributes, additional_attributes.is_some()),
             (Name, name.is_some()),
+            (Members, members.is_some()),
         ]
         .into_iter()
         .filter(|(_, present)| *present)
@@ -1890,7 +1967,10 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                     Ok(())
                 };
                 let error_if_any_fields = || -> std::result::Result<(), M::Error> {
-                    error_if_fields(&[Element, Attributes, AdditionalAttributes, Name], &[])
+                    error_if_fields(
+                        &[Element, Attributes, AdditionalAttributes, Name, Members],
+                        &[],
+                    )
                 };
                 match s.as_str() {
                     "String" => {
@@ -1916,7 +1996,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                     }
                     "Set" => {
                         error_if_fields(
-                            &[Attributes, AdditionalAttributes, Name],
+                            &[Attributes, AdditionalAttributes, Name, Members],
                             &[type_field_name!(Element)],
                         )?;
 
@@ -1930,9 +2010,26 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                             None => Err(serde::de::Error::missing_field(Element.as_str())),
                         }
                     }
+                    #[cfg(feature = "maps")]
+                    "Map" => {
+                        error_if_fields(
+                            &[Attributes, AdditionalAttributes, Name, Members],
+                            &[type_field_name!(Element)],
+                        )?;
+
+                        match element {
+                            Some(element) => Ok(Type::Type {
+                                ty: TypeVariant::Map {
+                                    element: Box::new(element),
+                                },
+                                loc: None,
+                            }),
+                            None => Err(serde::de::Error::missing_field(Element.as_str())),
+                        }
+                    }
                     "Record" => {
                         error_if_fields(
-                            &[Element, Name],
+                            &[Element, Name, Members],
                             &[
                                 type_field_name!(Attributes),
                                 type_field_name!(AdditionalAttributes),
@@ -1953,6 +2050,10 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                                                 TypeOfAttribute {
                                                     ty,
                                                     required,
+                                                    default,
+                                                    range,
+                                                    pattern,
+                                                    satisfying,
                                                     annotations,
                                                     #[cfg(feature = "extended-schema")]
                                                     loc,
@@ -1963,6 +2064,10 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                                                     TypeOfAttribute {
                                                         ty: ty.into_n(),
                                                         required,
+                                                        default,
+                                                        range,
+                                                        pattern,
+                                                        satisfying,
                                                         annotations,
                                                         #[cfg(feature = "extended-schema")]
                                                         loc,
@@ -1981,7 +2086,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                     }
                     "Entity" => {
                         error_if_fields(
-                            &[Element, Attributes, AdditionalAttributes],
+                            &[Element, Attributes, AdditionalAttributes, Members],
                             &[type_field_name!(Name)],
                         )?;
                         match name {
@@ -2002,7 +2107,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                     }
                     "EntityOrCommon" => {
                         error_if_fields(
-                            &[Element, Attributes, AdditionalAttributes],
+                            &[Element, Attributes, AdditionalAttributes, Members],
                             &[type_field_name!(Name)],
                         )?;
                         match name {
@@ -2023,7 +2128,7 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                     }
                     "Extension" => {
                         error_if_fields(
-                            &[Element, Attributes, AdditionalAttributes],
+                            &[Element, Attributes, AdditionalAttributes, Members],
                             &[type_field_name!(Name)],
                         )?;
 
@@ -2043,6 +2148,19 @@ impl<'de, N: Deserialize<'de> + From<RawName>> TypeVisitor<N> {
                             None => Err(serde::de::Error::missing_field(Name.as_str())),
                         }
                     }
+                    "Union" => {
+                        error_if_fields(
+                            &[Element, Attributes, AdditionalAttributes, Name],
+                            &[type_field_name!(Members)],
+                        )?;
+                        match members {
+                            Some(members) => Ok(Type::Type {
+                                ty: TypeVariant::Union { members },
+                                loc: None,
+                            }),
+                            None => Err(serde::de::Error::missing_field(Members.as_str())),
+                        }
+                    }
                     type_name => {
                         error_if_any_fields()?;
                         Ok(Type::CommonTypeRef {
@@ -2169,6 +2287,11 @@ pub enum TypeVariant<N> {
         /// Element type
         element: Box<Type<N>>,
     },
+    /// Map from `String` keys to values of a single type
+    Map {
+        /// Type of the values of the map
+        element: Box<Type<N>>,
+    },
     /// Record
     Record(RecordType<N>),
     /// Entity
@@ -2209,6 +2332,13 @@ pub enum TypeVariant<N> {
         /// Name of the extension type
         name: UnreservedId,
     },
+    /// Union of several types: a value of this type is a value of any one of
+    /// the `members`. The members must either all be entity types or all be
+    /// record types.
+    Union {
+        /// Member types of the union
+        members: Vec<Type<N>>,
+    },
 }
 
 impl TypeVariant<RawName> {
@@ -2231,6 +2361,15 @@ impl TypeVariant<RawName> {
             Self::Set { element } => TypeVariant::Set {
                 element: Box::new(element.conditionally_qualify_type_references(ns)),
             },
+            Self::Map { element } => TypeVariant::Map {
+                element: Box::new(element.conditionally_qualify_type_references(ns)),
+            },
+            Self::Union { members } => TypeVariant::Union {
+                members: members
+                    .into_iter()
+                    .map(|member| member.conditionally_qualify_type_references(ns))
+                    .collect(),
+            },
             Self::Record(RecordType {
                 attributes,
                 additional_attributes,
@@ -2241,6 +2380,10 @@ impl TypeVariant<RawName> {
                         TypeOfAttribute {
                             ty,
                             required,
+                            default,
+                            range,
+                            pattern,
+                            satisfying,
                             annotations,
                             #[cfg(feature = "extended-schema")]
                             loc,
@@ -2251,6 +2394,10 @@ impl TypeVariant<RawName> {
                             TypeOfAttribute {
                                 ty: ty.conditionally_qualify_type_references(ns),
                                 required,
+                                default,
+                                range,
+                                pattern,
+                                satisfying,
                                 annotations,
                                 #[cfg(feature = "extended-schema")]
                                 loc,
@@ -2285,7 +2432,13 @@ impl TypeVariant<RawName> {
             Self::Set { element } => TypeVariant::Set {
                 element: Box::new(element.into_n()),
             },
+            Self::Map { element } => TypeVariant::Map {
+                element: Box::new(element.into_n()),
+            },
             Self::Extension { name } => TypeVariant::Extension { name },
+            Self::Union { members } => TypeVariant::Union {
+                members: members.into_iter().map(Type::into_n).collect(),
+            },
         }
     }
 }
@@ -2315,6 +2468,15 @@ impl TypeVariant<ConditionalName> {
             Self::Set { element } => Ok(TypeVariant::Set {
                 element: Box::new(element.fully_qualify_type_references(all_defs)?),
             }),
+            Self::Map { element } => Ok(TypeVariant::Map {
+                element: Box::new(element.fully_qualify_type_references(all_defs)?),
+            }),
+            Self::Union { members } => Ok(TypeVariant::Union {
+                members: members
+                    .into_iter()
+                    .map(|member| member.fully_qualify_type_references(all_defs))
+                    .collect::<std::result::Result<_, TypeNotDefinedError>>()?,
+            }),
             Self::Record(RecordType {
                 attributes,
                 additional_attributes,
@@ -2327,6 +2489,10 @@ impl TypeVariant<ConditionalName> {
                             TypeOfAttribute {
                                 ty,
                                 required,
+                                default,
+                                range,
+                                pattern,
+                                satisfying,
                                 annotations,
                                 #[cfg(feature = "extended-schema")]
                                 loc,
@@ -2337,6 +2503,10 @@ impl TypeVariant<ConditionalName> {
                                 TypeOfAttribute {
                                     ty: ty.fully_qualify_type_references(all_defs)?,
                                     required,
+                                    default,
+                                    range,
+                                    pattern,
+                                    satisfying,
                                     annotations,
                                     #[cfg(feature = "extended-schema")]
                                     loc,
@@ -2351,6 +2521,23 @@ impl TypeVariant<ConditionalName> {
     }
 }
 
+/// Inclusive range of the values of a `Long` attribute. Either bound may be
+/// omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct LongRange {
+    /// Least allowed value
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    /// Greatest allowed value
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+}
+
 // Only used for serialization
 #[expect(
     clippy::trivially_copy_pass_by_ref,
@@ -2367,7 +2554,7 @@ impl<'a> arbitrary::Arbitrary<'a> for Type<RawName> {
         use std::collections::BTreeSet;
 
         Ok(Type::Type {
-            ty: match u.int_in_range::<u8>(1..=8)? {
+            ty: match u.int_in_range::<u8>(1..=9)? {
                 1 => TypeVariant::String,
                 2 => TypeVariant::Long,
                 3 => TypeVariant::Boolean,
@@ -2400,6 +2587,9 @@ impl<'a> arbitrary::Arbitrary<'a> for Type<RawName> {
                     #[expect(clippy::unwrap_used, reason = "`decimal` is a valid `UnreservedId`")]
                     name: "decimal".parse().unwrap(),
                 },
+                9 => TypeVariant::Map {
+                    element: Box::new(u.arbitrary()?),
+                },
                 n => panic!("bad index: {n}"),
             },
             loc: None,
@@ -2443,6 +2633,27 @@ pub struct TypeOfAttribute<N> {
     #[serde(default = "record_attribute_required_default")]
     #[serde(skip_serializing_if = "is_record_attribute_required_default")]
     pub required: bool,
+    /// Value the attribute takes when it is absent from an entity or record.
+    /// Only optional attributes may have a default value.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[educe(PartialOrd(ignore))]
+    pub default: Option<CedarValueJson>,
+    /// Inclusive range which the values of a `Long` attribute must be in
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<LongRange>,
+    /// Regular expression which the whole of the values of a `String`
+    /// attribute must match
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<SmolStr>,
+    /// Extension method, taking no other arguments and returning a `Bool`,
+    /// which must return `true` for the values of an extension-typed
+    /// attribute; e.g., `isIpv4` for an `ipaddr` attribute
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub satisfying: Option<SmolStr>,
 
     /// Source location - if available
     #[cfg(feature = "extended-schema")]
@@ -2457,6 +2668,13 @@ impl TypeOfAttribute<RawName> {
             ty: self.ty.into_n(),
 
             required: self.required,
+
+            default: self.default,
+
+            range: self.range,
+
+            pattern: self.pattern,
+            satisfying: self.satisfying,
             annotations: self.annotations,
             #[cfg(feature = "extended-schema")]
             loc: self.loc,
@@ -2471,6 +2689,10 @@ impl TypeOfAttribute<RawName> {
         TypeOfAttribute {
             ty: self.ty.conditionally_qualify_type_references(ns),
             required: self.required,
+            default: self.default,
+            range: self.range,
+            pattern: self.pattern,
+            satisfying: self.satisfying,
             annotations: self.annotations,
             #[cfg(feature = "extended-schema")]
             loc: self.loc,
@@ -2492,6 +2714,10 @@ impl TypeOfAttribute<ConditionalName> {
         Ok(TypeOfAttribute {
             ty: self.ty.fully_qualify_type_references(all_defs)?,
             required: self.required,
+            default: self.default,
+            range: self.range,
+            pattern: self.pattern,
+            satisfying: self.satisfying,
             annotations: self.annotations,
             #[cfg(feature = "extended-schema")]
             loc: self.loc,
@@ -2505,6 +2731,10 @@ impl<'a> arbitrary::Arbitrary<'a> for TypeOfAttribute<RawName> {
         Ok(Self {
             ty: u.arbitrary::<Type<RawName>>()?,
             required: u.arbitrary()?,
+            default: None,
+            range: None,
+            pattern: None,
+            satisfying: None,
             annotations: u.arbitrary()?,
             #[cfg(feature = "extended-schema")]
             loc: None,
@@ -2876,7 +3106,7 @@ mod test {
             expect_err(
                 "",
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error(r#"unknown field `foo`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`"#).build()
+                &ExpectedErrorMessageBuilder::error(r#"unknown field `foo`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`"#).build()
             );
         });
     }
@@ -3500,7 +3730,7 @@ mod entity_tags {
             expect_err(
                 &json,
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error("unknown field `tags`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+                &ExpectedErrorMessageBuilder::error("unknown field `tags`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`")
                     .build(),
             );
         });
@@ -3552,6 +3782,7 @@ mod test_json_roundtrip {
                                 loc: None,
                             }),
                             tags: None,
+                            constraints: vec![],
                         }),
                         annotations: Annotations::new(),
                         loc: None,
@@ -3603,6 +3834,7 @@ mod test_json_roundtrip {
                                     loc: None,
                                 }),
                                 tags: None,
+                                constraints: vec![],
                             }),
                             annotations: Annotations::new(),
                             loc: None,
@@ -3995,11 +4227,11 @@ mod annotations {
     }
 
     const ENTITY_TYPE_EXPECTED_ATTRIBUTES: &str =
-        "`memberOfTypes`, `shape`, `tags`, `enum`, `annotations`";
+        "`memberOfTypes`, `shape`, `tags`, `constraints`, `enum`, `annotations`";
     const NAMESPACE_EXPECTED_ATTRIBUTES: &str =
         "`commonTypes`, `entityTypes`, `actions`, `annotations`";
     const ATTRIBUTE_TYPE_EXPECTED_ATTRIBUTES: &str =
-        "`type`, `element`, `attributes`, `additionalAttributes`, `name`";
+        "`type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`";
     const APPLIES_TO_EXPECTED_ATTRIBUTES: &str = "`resourceTypes`, `principalTypes`, `context`";
 
     #[test]
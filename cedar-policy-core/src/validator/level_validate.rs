@@ -17,7 +17,7 @@
 //! Implementation of level validation (RFC 76)
 
 use super::*;
-use crate::ast::{BinaryOp, Expr, ExprKind, Literal, PolicyID};
+use crate::ast::{is_normalized_ident, BinaryOp, Expr, ExprKind, Literal, PolicyID};
 use crate::validator::types::{EntityKind, RequestEnv, Type};
 use smol_str::SmolStr;
 use thiserror::Error;
@@ -44,6 +44,11 @@ impl<I: Into<u32>> From<I> for EntityDerefLevel {
 }
 
 impl EntityDerefLevel {
+    /// The number of entity dereferences this level allows
+    pub fn level(self) -> u32 {
+        self.level
+    }
+
     fn increment(self) -> Self {
         (self.level + 1).into()
     }
@@ -73,7 +78,7 @@ impl Validator {
         // some policies have an error. This allows us to report more errors.
         let (errors, warnings) = self.validate_policy(p, mode);
 
-        let typechecker = Typechecker::new(&self.schema, mode);
+        let typechecker = self.typechecker(mode);
         let type_annotated_asts = typechecker.typecheck_by_request_env(p);
         let mut level_checker = LevelChecker {
             policy_id: p.id(),
@@ -195,6 +200,17 @@ impl LevelChecker<'_> {
                 self.check_expr_level(arg2, env);
                 deref_target_level.increment()
             }
+            #[cfg(feature = "maps")]
+            ExprKind::BinaryApp {
+                op: BinaryOp::GetKey,
+                arg1,
+                arg2,
+            } => {
+                // Getting a value from a map does not dereference an entity,
+                // so the level is that of the map itself
+                self.check_expr_level(arg2, env);
+                self.check_entity_deref_target_level(arg1, access_path, env)
+            }
             ExprKind::Record(attrs) => {
                 match access_path
                     .pop()
@@ -271,6 +287,7 @@ impl LevelChecker<'_> {
                             self.policy_id.clone(),
                             self.max_level,
                             deref_target_lvl.increment(),
+                            access_chain(e),
                         ));
                 }
                 self.check_expr_level(arg2, env);
@@ -295,6 +312,7 @@ impl LevelChecker<'_> {
                                 self.policy_id.clone(),
                                 self.max_level,
                                 deref_target_lvl.increment(),
+                                access_chain(e),
                             ));
                     }
                 }
@@ -340,11 +358,34 @@ impl LevelChecker<'_> {
     }
 }
 
+/// Render the chain of attribute accesses in `e` as it would be written in a
+/// policy (e.g., `principal.manager.name`). Falls back to the expression's
+/// `Display` for anything other than a variable followed by attribute accesses.
+fn access_chain<T: Clone>(e: &Expr<T>) -> SmolStr {
+    fn render<T>(e: &Expr<T>) -> Option<String> {
+        match e.expr_kind() {
+            ExprKind::Var(v) => Some(v.to_string()),
+            ExprKind::GetAttr { expr, attr } => {
+                let base = render(expr)?;
+                if is_normalized_ident(attr) {
+                    Some(format!("{base}.{attr}"))
+                } else {
+                    Some(format!("{base}[\"{}\"]", attr.escape_debug()))
+                }
+            }
+            _ => None,
+        }
+    }
+    render(e).map_or_else(|| e.to_string().into(), Into::into)
+}
+
 #[cfg(test)]
 mod levels_validation_tests {
     use super::*;
     use crate::parser;
     use crate::test_utils::{expect_err, ExpectedErrorMessageBuilder};
+    use crate::validator::validation_errors::EntityDerefViolationKind;
+    use cool_asserts::assert_matches;
 
     fn get_schema() -> ValidatorSchema {
         json_schema::Fragment::from_json_value(serde_json::json!(
@@ -467,13 +508,13 @@ mod levels_validation_tests {
         );
 
         if underlines.len() == 1 {
-            let expected = ExpectedErrorMessageBuilder::error(&msg)
+            let expected = ExpectedErrorMessageBuilder::error_starts_with(&msg)
                 .exactly_one_underline(underlines[0])
                 .build();
             expect_err(src, &miette::Report::new(errs.remove(0)), &expected);
         } else {
             for ul in underlines {
-                let expected = ExpectedErrorMessageBuilder::error(&msg)
+                let expected = ExpectedErrorMessageBuilder::error_starts_with(&msg)
                     .exactly_one_underline(ul)
                     .build();
                 if !errs.iter().any(|e| expected.matches(e)) {
@@ -1036,4 +1077,86 @@ mod levels_validation_tests {
             0,
         );
     }
+
+    #[test]
+    fn message_names_access_chain() {
+        let src = r#"permit(principal, action, resource) when { principal.user.user.bool };"#;
+        let p = parser::parse_policy_or_template(None, src).unwrap();
+        let errs = Validator::new(get_schema())
+            .validate_policy_with_level(&p, ValidationMode::Strict, 1)
+            .0
+            .collect::<Vec<_>>();
+        let expected = ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, this policy requires level 3, which exceeds the maximum allowed level (1), in `principal.user.user.bool`",
+        )
+        .exactly_one_underline("principal.user.user.bool")
+        .build();
+        assert_matches!(errs.as_slice(), [e] => {
+            expect_err(src, &miette::Report::new(e.clone()), &expected);
+        });
+    }
+
+    #[track_caller]
+    fn validate_set(src: &str, config: ValidatorConfig) -> Vec<ValidationError> {
+        let policies = parser::parse_policyset(src).unwrap();
+        Validator::with_config(get_schema(), config)
+            .validate(&policies, ValidationMode::Strict)
+            .into_errors_and_warnings()
+            .0
+            .collect()
+    }
+
+    #[test]
+    fn annotation_sets_policy_level() {
+        let errs = validate_set(
+            r#"
+            @max_deref_level("1")
+            permit(principal, action, resource) when { principal.user.bool };
+            @max_deref_level("2")
+            permit(principal, action, resource) when { principal.user.bool };
+            permit(principal, action, resource) when { principal.user.user.bool };
+            "#,
+            ValidatorConfig::new(),
+        );
+        assert_matches!(errs.as_slice(), [ValidationError::EntityDerefLevelViolation(e)] => {
+            assert_eq!(e.policy_id, PolicyID::from_string("policy0"));
+            assert_matches!(
+                &e.violation_kind,
+                EntityDerefViolationKind::MaximumLevelExceeded { allowed_level, actual_level, access_chain } => {
+                    assert_eq!(allowed_level.level(), 1);
+                    assert_eq!(actual_level.level(), 2);
+                    assert_eq!(access_chain, "principal.user.bool");
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn annotation_overrides_configured_level() {
+        let errs = validate_set(
+            r#"
+            @max_deref_level("2")
+            permit(principal, action, resource) when { principal.user.bool };
+            permit(principal, action, resource) when { principal.user.bool };
+            "#,
+            ValidatorConfig::new().with_max_deref_level(1),
+        );
+        assert_matches!(errs.as_slice(), [ValidationError::EntityDerefLevelViolation(e)] => {
+            assert_eq!(e.policy_id, PolicyID::from_string("policy1"));
+        });
+    }
+
+    #[test]
+    fn invalid_annotation() {
+        let src = r#"@max_deref_level("two") permit(principal, action, resource) when { principal.bool };"#;
+        let errs = validate_set(src, ValidatorConfig::new());
+        let expected = ExpectedErrorMessageBuilder::error(
+            "for policy `policy0`, the `@max_deref_level` annotation must be a non-negative integer, but found `two`",
+        )
+        .exactly_one_underline(r#"@max_deref_level("two")"#)
+        .build();
+        assert_matches!(errs.as_slice(), [e] => {
+            expect_err(src, &miette::Report::new(e.clone()), &expected);
+        });
+    }
 }
@@ -26,6 +26,7 @@ use crate::{
     validator::validation_errors::get_suggested_entity_type,
 };
 
+use itertools::Itertools;
 use std::{collections::HashSet, sync::Arc};
 
 use crate::validator::{
@@ -292,6 +293,27 @@ impl Validator {
         .into_iter()
     }
 
+    /// Check that every action satisfying the action scope constraint of the
+    /// template declares at least one principal type and one resource type it
+    /// applies to.
+    pub(crate) fn validate_fully_specified_actions(&self, t: &Template) -> Option<ValidationError> {
+        let actions: Vec<EntityUID> = self
+            .get_actions_satisfying_constraint(t.action_constraint())
+            .filter_map(|action_id| self.schema.get_action_id(action_id))
+            .filter(|action| !action.is_fully_specified())
+            .map(|action| action.name().clone())
+            .sorted_by_key(ToString::to_string)
+            .dedup()
+            .collect();
+        (!actions.is_empty()).then(|| {
+            ValidationError::unspecified_action_application(
+                t.loc().cloned(),
+                t.id().clone(),
+                actions,
+            )
+        })
+    }
+
     /// Gather all `ApplySpec` objects for all actions in the schema.
     pub(crate) fn get_apply_specs_for_action<'a>(
         &'a self,
@@ -306,7 +328,7 @@ impl Validator {
 
     /// Get the set of actions (action entity id strings) that satisfy the
     /// action scope constraint of the policy.
-    fn get_actions_satisfying_constraint<'a>(
+    pub(crate) fn get_actions_satisfying_constraint<'a>(
         &'a self,
         action_constraint: &'a ActionConstraint,
     ) -> Box<dyn Iterator<Item = &'a EntityUID> + 'a> {
@@ -492,6 +514,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -530,6 +553,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -625,6 +649,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -651,6 +676,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -677,6 +703,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -1061,6 +1088,7 @@ mod test {
                     member_of_types: vec![],
                     shape: json_schema::AttributesOrContext::default(),
                     tags: None,
+                    constraints: vec![],
                 }
                 .into(),
             )],
@@ -1096,6 +1124,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -1105,6 +1134,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -1493,6 +1523,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -1502,6 +1533,7 @@ mod test {
                         member_of_types: vec![resource_parent_type.parse().unwrap()],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -1511,6 +1543,7 @@ mod test {
                         member_of_types: vec![resource_grandparent_type.parse().unwrap()],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
@@ -1520,6 +1553,7 @@ mod test {
                         member_of_types: vec![],
                         shape: json_schema::AttributesOrContext::default(),
                         tags: None,
+                        constraints: vec![],
                     }
                     .into(),
                 ),
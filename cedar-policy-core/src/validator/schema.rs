@@ -21,13 +21,20 @@
 //! computed to obtain a `descendants` relation.
 
 use crate::{
-    ast::{Entity, EntityType, EntityUID, InternalName, Name, UnreservedId},
-    entities::{err::EntitiesError, Entities, TCComputation},
+    ast::{Entity, EntityType, EntityUID, ExprKind, InternalName, Literal, Name, UnreservedId},
+    entities::{
+        conformance::{is_valid_enumerated_entity, typecheck_restricted_expr_against_schematype},
+        err::EntitiesError,
+        json::err::TypeMismatchError,
+        Entities, EntityConstraint, SchemaType as CoreSchemaType, TCComputation,
+    },
+    evaluator::RestrictedEvaluator,
     extensions::Extensions,
     parser::Loc,
     transitive_closure::compute_tc,
 };
 use educe::Educe;
+use itertools::Itertools;
 use namespace_def::EntityTypeFragment;
 use nonempty::NonEmpty;
 #[cfg(feature = "extended-schema")]
@@ -47,9 +54,17 @@ use crate::validator::{
 
 mod action;
 pub use action::ValidatorActionId;
+mod action_hierarchy;
+pub use action_hierarchy::ActionHierarchy;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
 pub(crate) use action::ValidatorApplySpec;
 mod entity_type;
 pub use entity_type::{ValidatorEntityType, ValidatorEntityTypeKind};
+mod imports;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use imports::FileSchemaLoader;
+pub use imports::SchemaSourceLoader;
 mod namespace_def;
 pub(crate) use namespace_def::try_jsonschema_type_into_validator_type;
 pub use namespace_def::ValidatorNamespaceDef;
@@ -474,6 +489,88 @@ impl ValidatorSchema {
         self.action_ids.keys()
     }
 
+    /// Returns an iterator over all the actions which are (transitively)
+    /// members of `action`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn action_descendants<'a>(
+        &'a self,
+        action: &EntityUID,
+    ) -> Option<impl Iterator<Item = &'a EntityUID> + 'a> {
+        self.action_ids
+            .get(action)
+            .map(ValidatorActionId::descendants)
+    }
+
+    /// Returns an iterator over all the action groups which `action` is
+    /// (transitively) a member of
+    ///
+    /// # Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn action_ancestors<'a>(
+        &'a self,
+        action: &'a EntityUID,
+    ) -> Option<impl Iterator<Item = &'a EntityUID> + 'a> {
+        if self.action_ids.contains_key(action) {
+            Some(self.action_ids.values().filter_map(|group| {
+                if group.descendants.contains(action) {
+                    Some(&group.name)
+                } else {
+                    None
+                }
+            }))
+        } else {
+            None
+        }
+    }
+
+    /// Expand the action group `action` to the concrete actions it contains,
+    /// i.e., the actions which are (transitively) members of `action` and are
+    /// not themselves action groups. If `action` is not an action group, this
+    /// is just `action` itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn expand_action_group<'a>(
+        &'a self,
+        action: &EntityUID,
+    ) -> Option<impl Iterator<Item = &'a EntityUID> + 'a> {
+        let action = self.action_ids.get(action)?;
+        let expanded: Box<dyn Iterator<Item = &'a EntityUID> + 'a> =
+            if action.descendants.is_empty() {
+                Box::new(std::iter::once(&action.name))
+            } else {
+                Box::new(action.descendants.iter().filter(|member| {
+                    self.action_ids
+                        .get(*member)
+                        .is_some_and(|member| member.descendants.is_empty())
+                }))
+            };
+        Some(expanded)
+    }
+
+    /// Returns `true` if `action` is `group` or is (transitively) a member of
+    /// `group`, i.e., if `action in group` evaluates to `true` given the
+    /// action hierarchy declared in this schema. Returns `false` if `group` is
+    /// not found in the schema.
+    pub fn is_action_in(&self, action: &EntityUID, group: &EntityUID) -> bool {
+        self.action_ids
+            .get(group)
+            .is_some_and(|group| &group.name == action || group.descendants.contains(action))
+    }
+
+    /// Get the action hierarchy of this schema as a graph, with an edge from
+    /// each action to each action group it is a direct member of. Action
+    /// groups are resolved to their fully-qualified names, so an action may
+    /// be a member of a group declared in another namespace.
+    pub fn action_hierarchy(&self) -> ActionHierarchy<'_> {
+        ActionHierarchy::new(self)
+    }
+
     /// Create a [`ValidatorSchema`] without any definitions (of entity types,
     /// common types, or actions).
     pub fn empty() -> ValidatorSchema {
@@ -541,6 +638,30 @@ impl ValidatorSchema {
         Ok(schema_and_warnings)
     }
 
+    /// Construct a [`ValidatorSchema`] from the source `root`, in the Cedar
+    /// schema syntax, and every source it (transitively) imports with
+    /// `import "<name>";` declarations at the start of the source. Sources
+    /// are loaded with `loader`, and each is loaded only once, even if it is
+    /// imported more than once.
+    ///
+    /// It is an error for sources to import each other cyclically, or for an
+    /// entity type, common type, or action to be defined in more than one
+    /// source.
+    pub fn from_cedarschema_with_imports(
+        root: &str,
+        loader: &mut impl SchemaSourceLoader,
+        extensions: &Extensions<'_>,
+    ) -> std::result::Result<(Self, impl Iterator<Item = SchemaWarning>), CedarSchemaError> {
+        let sources = imports::load_sources(root, loader, extensions)?;
+        let fragments = sources
+            .fragments
+            .into_iter()
+            .map(|(_, fragment)| ValidatorSchemaFragment::from_schema_fragment(fragment))
+            .collect::<Result<Vec<_>>>()?;
+        let schema = Self::from_schema_fragments(fragments, extensions)?;
+        Ok((schema, sources.warnings.into_iter()))
+    }
+
     /// Helper function to construct a [`ValidatorSchema`] from a single [`json_schema::Fragment`].
     pub(crate) fn from_schema_frag(
         schema_file: json_schema::Fragment<RawName>,
@@ -731,6 +852,7 @@ impl ValidatorSchema {
                         attributes,
                         parents: _,
                         tags,
+                        constraints,
                     } => {
                         let (attributes, open_attributes) = {
                             let attr_loc = attributes.0.loc().cloned();
@@ -756,6 +878,18 @@ impl ValidatorSchema {
                             .transpose()?
                             .map(|unresolved| unresolved.resolve_common_type_refs(&common_types))
                             .transpose()?;
+                        let constraints = constraints
+                            .into_iter()
+                            .map(|constraint| {
+                                EntityConstraint::parse(constraint.clone()).map_err(|err| {
+                                    InvalidEntityConstraintError {
+                                        entity_type: name.clone(),
+                                        constraint,
+                                        err,
+                                    }
+                                })
+                            })
+                            .collect::<std::result::Result<Vec<_>, _>>()?;
 
                         Ok((
                             name.with_loc(name.loc()),
@@ -765,6 +899,7 @@ impl ValidatorSchema {
                                 attributes,
                                 open_attributes,
                                 tags.map(|t| t.ty),
+                                constraints,
                                 name.loc().cloned(),
                             ),
                         ))
@@ -847,6 +982,10 @@ impl ValidatorSchema {
             action_children.into_keys(),
             common_types.into_values(),
         )?;
+        // Default values were only parsed when the schema fragments were
+        // constructed. Now that all types are resolved, check them against
+        // the types of their attributes.
+        Self::check_attribute_defaults(&entity_types, &action_ids, extensions)?;
         Ok(ValidatorSchema::new_from_maps(
             entity_types,
             action_ids,
@@ -967,6 +1106,93 @@ impl ValidatorSchema {
         }
     }
 
+    /// Check that the default value of every attribute of an entity type or
+    /// action context (including attributes of nested records) is a valid
+    /// value of the attribute's type, and only refers to declared entities of
+    /// enumerated entity types.
+    fn check_attribute_defaults(
+        entity_types: &HashMap<EntityType, ValidatorEntityType>,
+        action_ids: &HashMap<EntityUID, ValidatorActionId>,
+        extensions: &Extensions<'_>,
+    ) -> Result<()> {
+        for entity_type in entity_types.values() {
+            Self::check_defaults_in_attributes(entity_type.attributes(), entity_types, extensions)?;
+        }
+        for action in action_ids.values() {
+            Self::check_defaults_in_type(&action.context, entity_types, extensions)?;
+        }
+        Ok(())
+    }
+
+    fn check_defaults_in_attributes(
+        attrs: &Attributes,
+        entity_types: &HashMap<EntityType, ValidatorEntityType>,
+        extensions: &Extensions<'_>,
+    ) -> Result<()> {
+        for (attr, attr_ty) in attrs.iter() {
+            if let Some(default) = &attr_ty.default {
+                let ty = CoreSchemaType::try_from(attr_ty.attr_type.as_ref().clone())
+                    .map_err(|e| InvalidAttributeDefaultError::invalid_value(attr.clone(), e))?;
+                typecheck_restricted_expr_against_schematype(
+                    default.as_borrowed(),
+                    &ty,
+                    extensions,
+                )
+                .map_err(|e| InvalidAttributeDefaultError::invalid_value(attr.clone(), e))?;
+                if let Some(refinement) = &attr_ty.refinement {
+                    if !refinement.admits(default.as_borrowed()) {
+                        return Err(InvalidAttributeDefaultError::invalid_value(
+                            attr.clone(),
+                            TypeMismatchError::unsatisfied_refinement(
+                                ty,
+                                refinement.clone(),
+                                default.clone(),
+                            ),
+                        )
+                        .into());
+                    }
+                }
+                // Typechecking does not check the arguments of extension
+                // constructors, so evaluate the default to catch, e.g., an
+                // invalid IP address
+                RestrictedEvaluator::new(extensions)
+                    .interpret(default.as_borrowed())
+                    .map_err(|e| InvalidAttributeDefaultError::invalid_value(attr.clone(), e))?;
+                // Typechecking only checks the type of entity references, so
+                // check that references to enumerated entities are valid
+                for e in default.subexpressions() {
+                    if let ExprKind::Lit(Literal::EntityUID(uid)) = e.expr_kind() {
+                        if let Some(ValidatorEntityTypeKind::Enum(choices)) =
+                            entity_types.get(uid.entity_type()).map(|ety| &ety.kind)
+                        {
+                            is_valid_enumerated_entity(choices, uid).map_err(|e| {
+                                InvalidAttributeDefaultError::invalid_value(attr.clone(), e)
+                            })?;
+                        }
+                    }
+                }
+            }
+            Self::check_defaults_in_type(&attr_ty.attr_type, entity_types, extensions)?;
+        }
+        Ok(())
+    }
+
+    fn check_defaults_in_type(
+        ty: &Type,
+        entity_types: &HashMap<EntityType, ValidatorEntityType>,
+        extensions: &Extensions<'_>,
+    ) -> Result<()> {
+        match ty {
+            Type::Record { attrs, .. } => {
+                Self::check_defaults_in_attributes(attrs, entity_types, extensions)
+            }
+            Type::Set {
+                element_type: Some(element_type),
+            } => Self::check_defaults_in_type(element_type, entity_types, extensions),
+            _ => Ok(()),
+        }
+    }
+
     /// Lookup the [`ValidatorActionId`] object in the schema with the given name.
     pub fn get_action_id(&self, action_id: &EntityUID) -> Option<&ValidatorActionId> {
         self.action_ids.get(action_id)
@@ -995,6 +1221,29 @@ impl ValidatorSchema {
         self.action_ids.values()
     }
 
+    /// Check that every action in the schema declares at least one principal
+    /// type and one resource type it applies to. Actions with no `appliesTo`,
+    /// or with an empty list of principal or resource types, are valid in a
+    /// schema but apply to no request, so a policy set may choose to forbid
+    /// them.
+    pub fn check_fully_specified_actions(&self) -> Result<()> {
+        let mut unspecified = self
+            .action_ids()
+            .filter(|action| !action.is_fully_specified())
+            .sorted_by_key(|action| action.name().to_string());
+        match unspecified.next() {
+            None => Ok(()),
+            Some(first) => Err(UnspecifiedActionsError {
+                actions: NonEmpty {
+                    head: first.name().clone(),
+                    tail: unspecified.map(|action| action.name().clone()).collect(),
+                },
+                source_loc: first.loc().cloned(),
+            }
+            .into()),
+        }
+    }
+
     /// An iterator over the entity type names in the schema.
     pub fn entity_type_names(&self) -> impl Iterator<Item = &EntityType> {
         self.entity_types.keys()
@@ -1252,6 +1501,18 @@ impl AllDefs {
         self.action_defs.contains(euid)
     }
 
+    /// Iterate over the actions, in any namespace, with the ID `id`, in sorted
+    /// order
+    pub(crate) fn actions_with_id<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> impl Iterator<Item = &'a EntityUID> + 'a {
+        self.action_defs
+            .iter()
+            .filter(move |euid| euid.eid().as_ref() == id)
+            .sorted()
+    }
+
     /// Mark the given [`InternalName`] as defined as an entity type
     pub fn mark_as_defined_as_entity_type(&mut self, name: InternalName) {
         self.entity_defs.insert(name);
@@ -1503,6 +1764,15 @@ impl<'a> CommonTypeResolver<'a> {
                 },
                 loc,
             }),
+            json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Map { element },
+                loc,
+            } => Ok(json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Map {
+                    element: Box::new(Self::resolve_type(resolve_table, *element)?),
+                },
+                loc,
+            }),
             json_schema::Type::Type {
                 ty:
                     json_schema::TypeVariant::Record(json_schema::RecordType {
@@ -1520,6 +1790,10 @@ impl<'a> CommonTypeResolver<'a> {
                                     attr,
                                     json_schema::TypeOfAttribute {
                                         required: attr_ty.required,
+                                        default: attr_ty.default,
+                                        range: attr_ty.range,
+                                        pattern: attr_ty.pattern,
+                                        satisfying: attr_ty.satisfying,
                                         ty: Self::resolve_type(resolve_table, attr_ty.ty)?,
                                         annotations: attr_ty.annotations,
                                         #[cfg(feature = "extended-schema")]
@@ -1533,6 +1807,18 @@ impl<'a> CommonTypeResolver<'a> {
                 }),
                 loc,
             }),
+            json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Union { members },
+                loc,
+            } => Ok(json_schema::Type::Type {
+                ty: json_schema::TypeVariant::Union {
+                    members: members
+                        .into_iter()
+                        .map(|member| Self::resolve_type(resolve_table, member))
+                        .partition_nonempty::<Vec<_>>()?,
+                },
+                loc,
+            }),
             _ => Ok(ty),
         }
     }
@@ -3183,7 +3469,7 @@ pub(crate) mod test {
                 &src,
                 &miette::Report::new(e),
                 &ExpectedErrorMessageBuilder::error("unknown extension type `ip`")
-                    .help("did you mean `ipaddr`?")
+                    .help("did you mean `ipset`?")
                     .build());
         });
 
@@ -3231,7 +3517,7 @@ pub(crate) mod test {
                         "attributes": {
                             "a": {
                                 "type": "Extension",
-                                "name": "i",
+                                "name": "ipst",
                             }
                         }
                     }
@@ -3245,8 +3531,8 @@ pub(crate) mod test {
             expect_err(
                 &src,
                 &miette::Report::new(e),
-                &ExpectedErrorMessageBuilder::error("unknown extension type `i`")
-                    .help("did you mean `ipaddr`?")
+                &ExpectedErrorMessageBuilder::error("unknown extension type `ipst`")
+                    .help("did you mean `ipset`?")
                     .build());
         });
 
@@ -5658,6 +5944,70 @@ action CreateList in Create appliesTo {
             .collect::<HashSet<EntityUID>>();
         assert_eq!(actions, HashSet::new());
     }
+
+    fn action_uids(names: &[&str]) -> HashSet<EntityUID> {
+        names
+            .iter()
+            .map(|name| format!("Action::\"{name}\"").parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn action_hierarchy() {
+        let schema: ValidatorSchema = r#"
+        entity User;
+        action All;
+        action Read, Write in All;
+        action GetList, GetLists in Read appliesTo { principal: User, resource: User };
+        action DeleteList in [Write, Read] appliesTo { principal: User, resource: User };
+        "#
+        .parse()
+        .unwrap();
+        let all = action_uids(&["All"]).into_iter().next().unwrap();
+        let delete_list = action_uids(&["DeleteList"]).into_iter().next().unwrap();
+        let undeclared = action_uids(&["Undeclared"]).into_iter().next().unwrap();
+
+        assert_eq!(
+            schema
+                .action_descendants(&all)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            action_uids(&["Read", "Write", "GetList", "GetLists", "DeleteList"])
+        );
+        assert_eq!(
+            schema
+                .action_ancestors(&delete_list)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            action_uids(&["All", "Read", "Write"])
+        );
+        assert_eq!(
+            schema
+                .expand_action_group(&all)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            action_uids(&["GetList", "GetLists", "DeleteList"])
+        );
+        assert_eq!(
+            schema
+                .expand_action_group(&delete_list)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            action_uids(&["DeleteList"])
+        );
+        assert!(schema.action_descendants(&undeclared).is_none());
+        assert!(schema.action_ancestors(&undeclared).is_none());
+        assert!(schema.expand_action_group(&undeclared).is_none());
+
+        assert!(schema.is_action_in(&delete_list, &all));
+        assert!(schema.is_action_in(&delete_list, &delete_list));
+        assert!(!schema.is_action_in(&all, &delete_list));
+        assert!(!schema.is_action_in(&undeclared, &undeclared));
+    }
 }
 
 #[cfg(test)]
@@ -5901,3 +6251,112 @@ action CreateList in Create appliesTo {
         assert_eq!(actions, expected);
     }
 }
+
+#[cfg(test)]
+mod action_hierarchy_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use miette::Diagnostic;
+
+    #[track_caller]
+    fn euid(s: &str) -> EntityUID {
+        s.parse().unwrap()
+    }
+
+    #[track_caller]
+    fn euids<'a>(actions: impl Iterator<Item = &'a EntityUID>) -> Vec<String> {
+        actions.map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn qualified_parents_in_other_namespaces() {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            namespace Shared {
+                action read, write;
+                action readOnly in [read];
+            }
+            namespace Photos {
+                entity User;
+                action view in [Shared::Action::"readOnly", Shared::Action::"read"]
+                    appliesTo { principal: User, resource: User };
+                action edit in [Shared::Action::"write", view]
+                    appliesTo { principal: User, resource: User };
+            }
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert!(schema.is_action_in(
+            &euid(r#"Photos::Action::"view""#),
+            &euid(r#"Shared::Action::"read""#)
+        ));
+        let hierarchy = schema.action_hierarchy();
+        // `view` is also declared in `read`, but it is in `read` through
+        // `readOnly`
+        assert_eq!(
+            euids(
+                hierarchy
+                    .parents(&euid(r#"Photos::Action::"view""#))
+                    .unwrap()
+            ),
+            [r#"Shared::Action::"readOnly""#]
+        );
+        assert_eq!(
+            euids(
+                hierarchy
+                    .parents(&euid(r#"Photos::Action::"edit""#))
+                    .unwrap()
+            ),
+            [r#"Photos::Action::"view""#, r#"Shared::Action::"write""#]
+        );
+        assert_eq!(
+            euids(
+                hierarchy
+                    .children(&euid(r#"Shared::Action::"read""#))
+                    .unwrap()
+            ),
+            [r#"Shared::Action::"readOnly""#]
+        );
+        assert_eq!(
+            euids(hierarchy.roots()),
+            [r#"Shared::Action::"read""#, r#"Shared::Action::"write""#]
+        );
+        assert_eq!(hierarchy.edges().count(), 4);
+        assert!(hierarchy.parents(&euid(r#"Action::"view""#)).is_none());
+    }
+
+    #[test]
+    fn unqualified_parent_in_other_namespace() {
+        assert_matches!(
+            ValidatorSchema::from_cedarschema_str(
+                r#"
+                namespace Shared { action read; }
+                namespace Photos { action view in [read]; }
+                "#,
+                Extensions::all_available(),
+            )
+            .map(|(schema, _)| schema),
+            Err(CedarSchemaError::Schema(e)) => {
+                assert_eq!(e.to_string(), r#"undeclared action: Action::"read""#);
+                assert_eq!(
+                    e.help().unwrap().to_string(),
+                    r#"actions declared in another namespace must be referred to by their fully-qualified name; did you mean `Shared::Action::"read"`?"#
+                );
+            }
+        );
+        assert_matches!(
+            ValidatorSchema::from_cedarschema_str(
+                r#"namespace Photos { action view in [Shared::Action::"read"]; }"#,
+                Extensions::all_available(),
+            )
+            .map(|(schema, _)| schema),
+            Err(CedarSchemaError::Schema(e)) => {
+                assert_eq!(
+                    e.help().unwrap().to_string(),
+                    "any actions appearing as parents need to be declared as actions"
+                );
+            }
+        );
+    }
+}
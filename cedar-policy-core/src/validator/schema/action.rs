@@ -139,6 +139,14 @@ impl ValidatorActionId {
     pub fn is_applicable_resource_type(&self, ty: &ast::EntityType) -> bool {
         self.applies_to.is_applicable_resource_type(ty)
     }
+
+    /// Does this action declare at least one principal type and one resource
+    /// type it applies to? Actions which are not fully specified apply to no
+    /// request.
+    pub fn is_fully_specified(&self) -> bool {
+        !self.applies_to.principal_apply_spec.is_empty()
+            && !self.applies_to.resource_apply_spec.is_empty()
+    }
 }
 
 impl TCNode<EntityUID> for ValidatorActionId {
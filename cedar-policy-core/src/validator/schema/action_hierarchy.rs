@@ -0,0 +1,104 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the resolved action hierarchy of a schema.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::EntityUID;
+
+use super::ValidatorSchema;
+
+/// The action hierarchy of a [`ValidatorSchema`], with every action group
+/// resolved to its fully-qualified name, as a graph with an edge from each
+/// action to each action group it is a direct member of.
+///
+/// The edges are the transitive reduction of the declared memberships: if an
+/// action is declared as a member of a group, and is also a member of the
+/// group through another group, there is only an edge to the other group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionHierarchy<'a> {
+    parents: BTreeMap<&'a EntityUID, BTreeSet<&'a EntityUID>>,
+    children: BTreeMap<&'a EntityUID, BTreeSet<&'a EntityUID>>,
+}
+
+impl<'a> ActionHierarchy<'a> {
+    /// Build the action hierarchy of `schema`
+    pub(crate) fn new(schema: &'a ValidatorSchema) -> Self {
+        let mut parents: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        let mut children: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for action in schema.action_ids() {
+            parents.entry(action.name()).or_default();
+            children.entry(action.name()).or_default();
+        }
+        for action in schema.action_ids() {
+            let ancestors: Vec<_> = schema
+                .action_ids()
+                .filter(|group| group.descendants.contains(action.name()))
+                .collect();
+            // A group is a direct parent unless the action is a member of it
+            // through another group
+            for group in &ancestors {
+                if !ancestors
+                    .iter()
+                    .any(|other| group.descendants.contains(other.name()))
+                {
+                    parents
+                        .entry(action.name())
+                        .or_default()
+                        .insert(group.name());
+                    children
+                        .entry(group.name())
+                        .or_default()
+                        .insert(action.name());
+                }
+            }
+        }
+        Self { parents, children }
+    }
+
+    /// Iterate over the action groups `action` is a direct member of, or
+    /// return `None` if `action` is not declared in the schema
+    pub fn parents(&self, action: &EntityUID) -> Option<impl Iterator<Item = &'a EntityUID> + '_> {
+        self.parents
+            .get(action)
+            .map(|parents| parents.iter().copied())
+    }
+
+    /// Iterate over the direct members of the action group `action`, or
+    /// return `None` if `action` is not declared in the schema
+    pub fn children(&self, action: &EntityUID) -> Option<impl Iterator<Item = &'a EntityUID> + '_> {
+        self.children
+            .get(action)
+            .map(|children| children.iter().copied())
+    }
+
+    /// Iterate over the actions which are not a member of any action group
+    pub fn roots(&self) -> impl Iterator<Item = &'a EntityUID> + '_ {
+        self.parents
+            .iter()
+            .filter(|(_, parents)| parents.is_empty())
+            .map(|(action, _)| *action)
+    }
+
+    /// Iterate over the edges of the hierarchy, as pairs of an action and an
+    /// action group it is a direct member of
+    pub fn edges(&self) -> impl Iterator<Item = (&'a EntityUID, &'a EntityUID)> + '_ {
+        self.parents
+            .iter()
+            .flat_map(|(action, parents)| parents.iter().map(move |parent| (*action, *parent)))
+    }
+}
@@ -0,0 +1,508 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generation of random entities, contexts, and requests which conform to a
+//! [`ValidatorSchema`], for property testing and fuzzing.
+//!
+//! Generators draw their randomness from an [`Unstructured`], so they can be
+//! driven by a fuzzer directly, or by `proptest` (or any other source of
+//! random bytes) by wrapping a random byte vector in an [`Unstructured`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ::arbitrary::{Error, Result, Unstructured};
+use nonempty::NonEmpty;
+use smol_str::SmolStr;
+
+use super::{ValidatorEntityTypeKind, ValidatorSchema};
+use crate::ast::{Context, Eid, Entity, EntityType, EntityUID, Name, Request, RestrictedExpr};
+use crate::entities::{Entities, Refinement, TCComputation};
+use crate::extensions::Extensions;
+use crate::validator::coreschema::CoreSchema;
+use crate::validator::types::{Attributes, BoolType, EntityKind, Type};
+
+/// Options controlling the size of the data produced by a [`SchemaGenerator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    /// The number of distinct entities of each (non-enumerated) entity type
+    /// which may be generated or referred to. Entities of type `T` have the
+    /// UIDs `T::"0"`, `T::"1"`, and so on, so a small number makes it likely
+    /// that generated references refer to generated entities.
+    pub max_entities_per_type: usize,
+    /// The maximum number of elements in a generated set, and of tags on a
+    /// generated entity
+    pub max_set_len: usize,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            max_entities_per_type: 3,
+            max_set_len: 3,
+        }
+    }
+}
+
+/// Generates random entities, contexts, and requests which conform to a
+/// [`ValidatorSchema`].
+///
+/// All generators return [`Error::IncorrectFormat`] if the schema refers to a
+/// type for which no value can be generated (e.g., an extension type without
+/// an enabled extension), and [`Error::EmptyChoose`] if a choice must be made
+/// from nothing (e.g., a request for a schema without any applicable actions).
+#[derive(Debug, Clone)]
+pub struct SchemaGenerator<'s> {
+    schema: &'s ValidatorSchema,
+    config: GeneratorConfig,
+    extensions: &'s Extensions<'s>,
+    /// Entity types, ordered so that every type comes after each type which
+    /// may be its descendant (barring cycles in the schema's hierarchy)
+    ordered_types: Vec<&'s EntityType>,
+    /// For each entity type, the entity types which may be its parents
+    parent_types: HashMap<&'s EntityType, Vec<&'s EntityType>>,
+}
+
+impl<'s> SchemaGenerator<'s> {
+    /// Create a generator for `schema` with the default [`GeneratorConfig`]
+    pub fn new(schema: &'s ValidatorSchema, extensions: &'s Extensions<'s>) -> Self {
+        Self::with_config(schema, GeneratorConfig::default(), extensions)
+    }
+
+    /// Create a generator for `schema` with the given [`GeneratorConfig`]
+    pub fn with_config(
+        schema: &'s ValidatorSchema,
+        config: GeneratorConfig,
+        extensions: &'s Extensions<'s>,
+    ) -> Self {
+        let mut types: Vec<_> = schema.entity_types().collect();
+        // An entity type's descendants include all of its descendants'
+        // descendants, so a type always has more descendants than any of them
+        types.sort_by_cached_key(|ty| (ty.descendants.len(), ty.name().to_string()));
+        let mut parent_types: HashMap<&EntityType, Vec<&EntityType>> = HashMap::new();
+        for parent in &types {
+            for child in &parent.descendants {
+                if let Some(child) = schema.get_entity_type(child) {
+                    parent_types
+                        .entry(child.name())
+                        .or_default()
+                        .push(parent.name());
+                }
+            }
+        }
+        Self {
+            schema,
+            config,
+            extensions,
+            ordered_types: types.into_iter().map(|ty| ty.name()).collect(),
+            parent_types,
+        }
+    }
+
+    /// The UIDs of the entities of type `ty` which may be generated or
+    /// referred to
+    fn entity_pool(&self, ty: &EntityType) -> Vec<EntityUID> {
+        let eids: Vec<Eid> = match self.schema.get_entity_type(ty).map(|ety| &ety.kind) {
+            Some(ValidatorEntityTypeKind::Enum(eids)) => eids.iter().cloned().collect(),
+            Some(ValidatorEntityTypeKind::Standard(_)) => (0..self.config.max_entities_per_type)
+                .map(|i| Eid::new(i.to_string()))
+                .collect(),
+            None => {
+                return self
+                    .schema
+                    .action_ids()
+                    .map(|action| action.name())
+                    .filter(|uid| uid.entity_type() == ty)
+                    .cloned()
+                    .collect()
+            }
+        };
+        eids.into_iter()
+            .map(|eid| EntityUID::from_components(ty.clone(), eid, None))
+            .collect()
+    }
+
+    /// Generate the UID of an entity of type `ty`. For an action entity type,
+    /// this is one of the actions declared in the schema.
+    pub fn entity_uid(&self, u: &mut Unstructured<'_>, ty: &EntityType) -> Result<EntityUID> {
+        let pool = self.entity_pool(ty);
+        if pool.is_empty() {
+            // `max_entities_per_type` is 0, but a reference is still needed
+            return Ok(EntityUID::from_components(ty.clone(), Eid::new("0"), None));
+        }
+        u.choose(&pool).cloned()
+    }
+
+    /// Generate a value of type `ty`, as a restricted expression
+    pub fn value(&self, u: &mut Unstructured<'_>, ty: &Type) -> Result<RestrictedExpr> {
+        match ty {
+            Type::Never => Err(Error::IncorrectFormat),
+            Type::Bool(BoolType::True) => Ok(RestrictedExpr::val(true)),
+            Type::Bool(BoolType::False) => Ok(RestrictedExpr::val(false)),
+            Type::Bool(BoolType::AnyBool) => Ok(RestrictedExpr::val(u.arbitrary::<bool>()?)),
+            Type::Long => Ok(RestrictedExpr::val(u.arbitrary::<i64>()?)),
+            Type::String => Ok(RestrictedExpr::val(u.arbitrary::<String>()?)),
+            Type::Entity(EntityKind::Entity(lub)) => {
+                let tys: Vec<_> = lub.iter().collect();
+                let ty = *u.choose(&tys)?;
+                Ok(RestrictedExpr::val(self.entity_uid(u, ty)?))
+            }
+            Type::Entity(EntityKind::AnyEntity) => {
+                let ty = *u.choose(&self.ordered_types)?;
+                Ok(RestrictedExpr::val(self.entity_uid(u, ty)?))
+            }
+            Type::Set { element_type } => {
+                let mut elements = Vec::new();
+                if let Some(element_type) = element_type {
+                    for _ in 0..u.int_in_range(0..=self.config.max_set_len)? {
+                        elements.push(self.value(u, element_type)?);
+                    }
+                }
+                Ok(RestrictedExpr::set(elements))
+            }
+            Type::Map { value_type } => {
+                let mut pairs = BTreeMap::new();
+                if let Some(value_type) = value_type {
+                    for _ in 0..u.int_in_range(0..=self.config.max_set_len)? {
+                        pairs.insert(
+                            SmolStr::from(u.arbitrary::<String>()?),
+                            self.value(u, value_type)?,
+                        );
+                    }
+                }
+                RestrictedExpr::record(pairs).map_err(|_| Error::IncorrectFormat)
+            }
+            Type::Record { attrs, .. } => RestrictedExpr::record(self.record_pairs(u, attrs)?)
+                .map_err(|_| Error::IncorrectFormat),
+            Type::ExtensionType { name } => self.extension_value(u, name),
+        }
+    }
+
+    /// Generate the attributes of a record with attribute types `attrs`.
+    /// Optional attributes are included at random. Values of attributes with
+    /// a range are generated within it, but no values are generated for
+    /// attributes with a pattern: optional ones are omitted, and required ones
+    /// are an error. Values generated for attributes with a `satisfying`
+    /// method which do not satisfy it are handled in the same way.
+    fn record_pairs(
+        &self,
+        u: &mut Unstructured<'_>,
+        attrs: &Attributes,
+    ) -> Result<Vec<(SmolStr, RestrictedExpr)>> {
+        let mut pairs = Vec::new();
+        for (attr, attr_ty) in attrs.iter() {
+            let value = match &attr_ty.refinement {
+                Some(Refinement::Pattern(_)) if attr_ty.is_required => {
+                    return Err(Error::IncorrectFormat)
+                }
+                Some(Refinement::Pattern(_)) => continue,
+                _ if !attr_ty.is_required && !u.arbitrary::<bool>()? => continue,
+                Some(Refinement::Range { min, max }) => RestrictedExpr::val(
+                    u.int_in_range(min.unwrap_or(i64::MIN)..=max.unwrap_or(i64::MAX))?,
+                ),
+                // Generated extension values may not satisfy the method, so
+                // keep only the ones which do
+                Some(refinement @ Refinement::Satisfying(_)) => {
+                    let value = self.value(u, &attr_ty.attr_type)?;
+                    if refinement.admits(value.as_borrowed()) {
+                        value
+                    } else if attr_ty.is_required {
+                        return Err(Error::IncorrectFormat);
+                    } else {
+                        continue;
+                    }
+                }
+                None => self.value(u, &attr_ty.attr_type)?,
+            };
+            pairs.push((attr.clone(), value));
+        }
+        Ok(pairs)
+    }
+
+    /// Generate a call to the constructor of the extension type `name`
+    fn extension_value(&self, u: &mut Unstructured<'_>, name: &Name) -> Result<RestrictedExpr> {
+        let (constructor, arg) = match name.to_string().as_str() {
+            "decimal" => {
+                // Decimals are stored as an `i64` with four fractional digits
+                let val = u.arbitrary::<i64>()?;
+                let sign = if val < 0 { "-" } else { "" };
+                let val = val.unsigned_abs();
+                (
+                    "decimal",
+                    format!("{sign}{}.{:04}", val / 10_000, val % 10_000),
+                )
+            }
+            "ipaddr" => {
+                if u.arbitrary::<bool>()? {
+                    let addr = std::net::Ipv4Addr::from(u.arbitrary::<[u8; 4]>()?);
+                    let prefix = u.int_in_range(0..=32)?;
+                    ("ip", format!("{addr}/{prefix}"))
+                } else {
+                    let addr = std::net::Ipv6Addr::from(u.arbitrary::<[u8; 16]>()?);
+                    let prefix = u.int_in_range(0..=128)?;
+                    ("ip", format!("{addr}/{prefix}"))
+                }
+            }
+            "datetime" => {
+                let year = u.int_in_range(1970..=2100)?;
+                let month = u.int_in_range(1..=12)?;
+                let day = u.int_in_range(1..=28)?;
+                let secs = u.int_in_range(0..=86_399)?;
+                (
+                    "datetime",
+                    format!(
+                        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+                        secs / 3600,
+                        secs / 60 % 60,
+                        secs % 60
+                    ),
+                )
+            }
+            "duration" => ("duration", format!("{}ms", u.arbitrary::<i32>()?)),
+            _ => return Err(Error::IncorrectFormat),
+        };
+        let constructor =
+            Name::parse_unqualified_name(constructor).map_err(|_| Error::IncorrectFormat)?;
+        Ok(RestrictedExpr::call_extension_fn(
+            constructor,
+            [RestrictedExpr::val(arg)],
+        ))
+    }
+
+    /// Generate an entity with the UID `uid`. Its parents are chosen from
+    /// the entities which may be generated by [`SchemaGenerator::entities()`],
+    /// such that the generated entity hierarchy is acyclic.
+    pub fn entity(&self, u: &mut Unstructured<'_>, uid: EntityUID) -> Result<Entity> {
+        let ety = self
+            .schema
+            .get_entity_type(uid.entity_type())
+            .ok_or(Error::IncorrectFormat)?;
+        let attrs = self.record_pairs(u, ety.attributes())?;
+        let mut tags = Vec::new();
+        if let Some(tag_type) = ety.tag_type() {
+            for _ in 0..u.int_in_range(0..=self.config.max_set_len)? {
+                tags.push((u.arbitrary::<String>()?.into(), self.value(u, tag_type)?));
+            }
+        }
+        let mut parents = HashSet::new();
+        let rank = |uid: &EntityUID| {
+            let ty_rank = self
+                .ordered_types
+                .iter()
+                .position(|ty| *ty == uid.entity_type());
+            (ty_rank, uid.eid().clone())
+        };
+        let own_rank = rank(&uid);
+        for parent_ty in self
+            .parent_types
+            .get(uid.entity_type())
+            .into_iter()
+            .flatten()
+        {
+            for parent in self.entity_pool(parent_ty) {
+                if rank(&parent) > own_rank && u.arbitrary::<bool>()? {
+                    parents.insert(parent);
+                }
+            }
+        }
+        Entity::new(uid, attrs, HashSet::new(), parents, tags, self.extensions)
+            .map_err(|_| Error::IncorrectFormat)
+    }
+
+    /// Generate a set of entities conforming to the schema, including the
+    /// schema's action entities. For each entity type, a random subset of the
+    /// entities which may be referred to is generated.
+    pub fn entities(&self, u: &mut Unstructured<'_>) -> Result<Entities> {
+        let mut entities = Vec::new();
+        for ty in &self.ordered_types {
+            for uid in self.entity_pool(ty) {
+                if u.arbitrary::<bool>()? {
+                    entities.push(self.entity(u, uid)?);
+                }
+            }
+        }
+        Entities::from_entities(
+            entities,
+            Some(&CoreSchema::new(self.schema)),
+            TCComputation::ComputeNow,
+            self.extensions,
+        )
+        .map_err(|_| Error::IncorrectFormat)
+    }
+
+    /// Generate a context for the action `action`
+    pub fn context(&self, u: &mut Unstructured<'_>, action: &EntityUID) -> Result<Context> {
+        let action = self
+            .schema
+            .get_action_id(action)
+            .ok_or(Error::IncorrectFormat)?;
+        let pairs = match action.context_type() {
+            Type::Record { attrs, .. } => self.record_pairs(u, attrs)?,
+            _ => return Err(Error::IncorrectFormat),
+        };
+        Context::from_pairs(pairs, self.extensions).map_err(|_| Error::IncorrectFormat)
+    }
+
+    /// Generate a request for one of the actions in the schema, with a
+    /// principal and resource of types the action applies to, and a context
+    /// conforming to the action's context type
+    pub fn request(&self, u: &mut Unstructured<'_>) -> Result<Request> {
+        let actions: Vec<_> = self
+            .schema
+            .action_ids()
+            .filter_map(|action| {
+                let principals = NonEmpty::collect(action.applies_to_principals())?;
+                let resources = NonEmpty::collect(action.applies_to_resources())?;
+                Some((action.name(), principals, resources))
+            })
+            .collect();
+        let (action, principals, resources) = u.choose(&actions)?;
+        let principals: Vec<_> = principals.iter().collect();
+        let resources: Vec<_> = resources.iter().collect();
+        let principal_ty = *u.choose(&principals)?;
+        let principal = self.entity_uid(u, principal_ty)?;
+        let resource_ty = *u.choose(&resources)?;
+        let resource = self.entity_uid(u, resource_ty)?;
+        let context = self.context(u, action)?;
+        Request::new(
+            (principal, None),
+            ((*action).clone(), None),
+            (resource, None),
+            context,
+            Some(self.schema),
+            self.extensions,
+        )
+        .map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::arbitrary::Unstructured;
+
+    use super::{GeneratorConfig, SchemaGenerator};
+    use crate::ast::{EntityUID, RequestSchema};
+    use crate::entities::conformance::EntitySchemaConformanceChecker;
+    use crate::extensions::Extensions;
+    use crate::validator::coreschema::CoreSchema;
+    use crate::validator::ValidatorSchema;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_cedarschema_str(
+            r#"
+            type Address = { street: String, zip?: Long };
+            entity Org;
+            entity Group in [Group, Org] { name: String };
+            entity User in [Group] {
+                name: String,
+                age?: Long,
+                address: Address,
+                friends: Set<User>,
+                manager?: User,
+                score: decimal,
+                home: ipaddr,
+            } tags Set<String>;
+            entity Color enum ["red", "green"];
+            entity Doc { owner: User, color: Color, created: datetime, ttl: duration };
+            action read appliesTo {
+                principal: [User, Group],
+                resource: Doc,
+                context: { mfa: Bool, ip: ipaddr, tags: Set<String>, extra?: Address },
+            };
+            action audit;
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    /// Run `f` with many inputs, each a different sequence of bytes
+    fn for_inputs(mut f: impl FnMut(&mut Unstructured<'_>)) {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for len in 0..200 {
+            let bytes: Vec<u8> = (0..len * 16)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state.to_le_bytes()[0]
+                })
+                .collect();
+            f(&mut Unstructured::new(&bytes));
+        }
+    }
+
+    #[test]
+    fn entities_conform() {
+        let schema = schema();
+        let generator = SchemaGenerator::new(&schema, Extensions::all_available());
+        let core_schema = CoreSchema::new(&schema);
+        let checker =
+            EntitySchemaConformanceChecker::new(&core_schema, Extensions::all_available());
+        let mut generated = 0;
+        for_inputs(|u| {
+            let entities = generator.entities(u).expect("entities should be generated");
+            for entity in entities.iter() {
+                checker.validate_entity(entity).unwrap();
+                generated += 1;
+            }
+        });
+        assert!(generated > 0);
+    }
+
+    #[test]
+    fn requests_conform() {
+        let schema = schema();
+        let generator = SchemaGenerator::new(&schema, Extensions::all_available());
+        for_inputs(|u| {
+            let request = generator.request(u).expect("request should be generated");
+            // `audit` doesn't apply to any principals, so is never chosen
+            assert_eq!(
+                request.action().uid(),
+                Some(&EntityUID::with_eid_and_type("Action", "read").unwrap())
+            );
+            schema
+                .validate_request(&request, Extensions::all_available())
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn entity_pool_bounds_references() {
+        let schema = schema();
+        let generator = SchemaGenerator::with_config(
+            &schema,
+            GeneratorConfig {
+                max_entities_per_type: 1,
+                max_set_len: 5,
+            },
+            Extensions::all_available(),
+        );
+        for_inputs(|u| {
+            let entities = generator.entities(u).unwrap();
+            for entity in entities.iter() {
+                let ty = entity.uid().entity_type();
+                // Actions and enumerated entities have fixed ids
+                if !ty.is_action() && ty.to_string() != "Color" {
+                    assert_eq!(entity.uid().eid().escaped(), "0");
+                }
+                for parent in entity.ancestors() {
+                    assert!(["0", "read", "audit"].contains(&parent.eid().escaped().as_str()));
+                }
+            }
+        });
+    }
+}
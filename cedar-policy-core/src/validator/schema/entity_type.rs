@@ -21,6 +21,7 @@ use nonempty::NonEmpty;
 use std::collections::HashSet;
 
 use crate::ast::Eid;
+use crate::entities::EntityConstraint;
 use crate::{ast::EntityType, parser::Loc, transitive_closure::TCNode};
 
 use crate::validator::types::{AttributeType, Attributes, OpenTag, Type};
@@ -77,6 +78,10 @@ pub struct StandardValidatorEntityType {
     /// Tag type for this entity type. `None` indicates that entities of this
     /// type are not allowed to have tags.
     pub(crate) tags: Option<Type>,
+
+    /// Invariants over their attributes which entities of this type must
+    /// satisfy.
+    pub(crate) constraints: Vec<EntityConstraint>,
 }
 
 impl ValidatorEntityType {
@@ -90,6 +95,7 @@ impl ValidatorEntityType {
         attributes: Attributes,
         open_attributes: OpenTag,
         tags: Option<Type>,
+        constraints: Vec<EntityConstraint>,
         loc: Option<Loc>,
     ) -> Self {
         Self {
@@ -99,6 +105,7 @@ impl ValidatorEntityType {
             kind: ValidatorEntityTypeKind::Standard(StandardValidatorEntityType {
                 open_attributes,
                 tags,
+                constraints,
             }),
             loc,
         }
@@ -166,6 +173,17 @@ impl ValidatorEntityType {
             ValidatorEntityTypeKind::Standard(ty) => ty.tag_type(),
         }
     }
+
+    /// Get the invariants over their attributes which entities of this type
+    /// must satisfy.
+    ///
+    /// For enumerated entity types, this will always be empty.
+    pub fn constraints(&self) -> &[EntityConstraint] {
+        match &self.kind {
+            ValidatorEntityTypeKind::Enum(_) => &[],
+            ValidatorEntityTypeKind::Standard(ty) => &ty.constraints,
+        }
+    }
 }
 
 impl StandardValidatorEntityType {
@@ -39,6 +39,149 @@ pub enum CedarSchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Parsing(#[from] CedarSchemaParseError),
+    /// Error resolving the `import` declarations of a schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Import(#[from] SchemaImportError),
+}
+
+/// Error resolving the `import` declarations of a schema composed from
+/// several sources
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make breaking changes, and use caution when adding public methods.
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum SchemaImportError {
+    /// A source could not be loaded
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Load(#[from] schema_import_errors::LoadError),
+    /// A source could not be parsed
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Parse(#[from] schema_import_errors::ParseError),
+    /// Sources import each other cyclically
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Cycle(#[from] schema_import_errors::CycleError),
+    /// The same entity type, common type, or action is defined in more than
+    /// one source
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateDefinition(#[from] schema_import_errors::DuplicateDefinitionError),
+}
+
+/// Error subtypes for [`SchemaImportError`]
+pub mod schema_import_errors {
+    use super::CedarSchemaParseError;
+    use itertools::Itertools;
+    use miette::Diagnostic;
+    use smol_str::SmolStr;
+    use thiserror::Error;
+
+    /// Error loading a schema source
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("failed to load schema source `{import}`{}", .importer.as_ref().map(|i| format!(" imported by `{i}`")).unwrap_or_default())]
+    pub struct LoadError {
+        /// The name of the source, as imported
+        pub(crate) import: SmolStr,
+        /// The source which imported it, or `None` for the root source
+        pub(crate) importer: Option<SmolStr>,
+        /// The underlying error
+        #[source]
+        pub(crate) source: std::io::Error,
+    }
+
+    impl LoadError {
+        /// The name of the source which could not be loaded, as imported
+        pub fn import(&self) -> &str {
+            &self.import
+        }
+
+        /// The source which imported it, or `None` for the root source
+        pub fn importer(&self) -> Option<&str> {
+            self.importer.as_deref()
+        }
+    }
+
+    /// Error parsing a schema source
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("in schema source `{source_name}`: {err}")]
+    #[diagnostic(forward(err))]
+    pub struct ParseError {
+        /// The source which could not be parsed
+        pub(crate) source_name: SmolStr,
+        /// The underlying error
+        pub(crate) err: CedarSchemaParseError,
+    }
+
+    impl ParseError {
+        /// The name of the source which could not be parsed
+        pub fn source_name(&self) -> &str {
+            &self.source_name
+        }
+    }
+
+    /// Error for sources which import each other cyclically
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("schema sources import each other cyclically: {}", .cycle.iter().map(|s| format!("`{s}`")).join(" -> "))]
+    pub struct CycleError {
+        /// The sources in the cycle, in import order, starting and ending with
+        /// the same source
+        pub(crate) cycle: Vec<SmolStr>,
+    }
+
+    impl CycleError {
+        /// The sources in the cycle, in import order, starting and ending with
+        /// the same source
+        pub fn cycle(&self) -> impl Iterator<Item = &str> {
+            self.cycle.iter().map(SmolStr::as_str)
+        }
+    }
+
+    /// Error for an entity type, common type, or action defined in more than
+    /// one source
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("{kind} `{name}` is defined in both `{first}` and `{second}`")]
+    pub struct DuplicateDefinitionError {
+        /// The kind of definition, e.g., `entity type`
+        pub(crate) kind: &'static str,
+        /// The fully qualified name which is defined more than once
+        pub(crate) name: String,
+        /// The first source defining it
+        pub(crate) first: SmolStr,
+        /// The second source defining it
+        pub(crate) second: SmolStr,
+    }
+
+    impl DuplicateDefinitionError {
+        /// The fully qualified name which is defined more than once
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// The names of the two sources defining it
+        pub fn sources(&self) -> (&str, &str) {
+            (&self.first, &self.second)
+        }
+    }
 }
 
 /// Error parsing a Cedar-syntax schema
@@ -257,6 +400,30 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ActionInvariantViolation(#[from] schema_errors::ActionInvariantViolationError),
+    /// The default value declared for an attribute is invalid
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidAttributeDefault(#[from] schema_errors::InvalidAttributeDefaultError),
+    /// A union type is invalid: it has no members, or its members are not
+    /// all entity types or all record types
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidUnionType(#[from] schema_errors::InvalidUnionTypeError),
+    /// The refinement declared for an attribute is invalid
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidAttributeRefinement(#[from] schema_errors::InvalidAttributeRefinementError),
+    /// A constraint declared for an entity type is not a valid Cedar
+    /// expression
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEntityConstraint(#[from] schema_errors::InvalidEntityConstraintError),
+    /// Actions which don't declare both the principal and resource types they
+    /// apply to, reported by
+    /// [`crate::validator::ValidatorSchema::check_fully_specified_actions()`]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedActions(#[from] schema_errors::UnspecifiedActionsError),
 }
 
 impl From<transitive_closure::TcError<EntityUID>> for SchemaError {
@@ -348,8 +515,10 @@ pub mod schema_errors {
     use std::fmt::Display;
 
     use crate::ast::{EntityType, EntityUID, InternalName, Name};
-    use crate::parser::{join_with_conjunction, Loc};
+    use crate::entities::Refinement;
+    use crate::parser::{err::ParseErrors, join_with_conjunction, Loc};
     use crate::transitive_closure;
+    use crate::validator::types::Type;
     use itertools::Itertools;
     use miette::Diagnostic;
     use nonempty::NonEmpty;
@@ -467,21 +636,42 @@ pub mod schema_errors {
     // CAUTION: this type is publicly exported in `cedar-policy`.
     // Don't make fields `pub`, don't make breaking changes, and use caution
     // when adding public methods.
-    #[derive(Debug, Diagnostic, Error)]
-    #[diagnostic(help("any actions appearing as parents need to be declared as actions"))]
+    #[derive(Debug, Error)]
     pub struct ActionNotDefinedError(
         pub(crate)  NonEmpty<
             crate::validator::json_schema::ActionEntityUID<crate::validator::ConditionalName>,
         >,
+        /// Actions declared in other namespaces with the same ID as the first
+        /// undeclared action
+        pub(crate) Vec<EntityUID>,
     );
 
+    impl Diagnostic for ActionNotDefinedError {
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            // as for `TypeNotDefinedError`, we only give help for the first undeclared action
+            match self.1.as_slice() {
+                [] => Some(Box::new(
+                    "any actions appearing as parents need to be declared as actions",
+                )),
+                [action] => Some(Box::new(format!(
+                    "actions declared in another namespace must be referred to by their fully-qualified name; did you mean `{action}`?"
+                ))),
+                actions => Some(Box::new(format!(
+                    "actions declared in another namespace must be referred to by their fully-qualified name; did you mean one of {}?",
+                    actions.iter().map(|action| format!("`{action}`")).join(", ")
+                ))),
+            }
+        }
+    }
+
     impl ActionNotDefinedError {
         /// Combine all the errors into a single [`ActionNotDefinedError`].
         ///
         /// This cannot fail, because `NonEmpty` guarantees there is at least
         /// one error to join.
         pub(crate) fn join_nonempty(errs: NonEmpty<ActionNotDefinedError>) -> Self {
-            Self(errs.flat_map(|err| err.0))
+            let suggestions = errs.head.1.clone();
+            Self(errs.flat_map(|err| err.0), suggestions)
         }
     }
 
@@ -892,4 +1082,218 @@ pub mod schema_errors {
 
         impl_diagnostic_from_method_on_nonempty_field!(euids, loc);
     }
+
+    /// The default value declared for an attribute is invalid: either the
+    /// attribute is required, or the value is not a valid value of the
+    /// attribute's type
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("invalid default value for attribute `{attr}`: {reason}")]
+    pub struct InvalidAttributeDefaultError {
+        /// Attribute with the invalid default value
+        pub(crate) attr: SmolStr,
+        /// Why the default value is invalid
+        pub(crate) reason: String,
+    }
+
+    impl InvalidAttributeDefaultError {
+        pub(crate) fn required(attr: SmolStr) -> Self {
+            Self {
+                attr,
+                reason: "only optional attributes can have a default value".into(),
+            }
+        }
+
+        pub(crate) fn invalid_value(attr: SmolStr, err: impl Display) -> Self {
+            Self {
+                attr,
+                reason: err.to_string(),
+            }
+        }
+
+        /// The attribute with the invalid default value
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+    }
+
+    /// A union type is invalid: it has no members, its members are not all
+    /// entity types or all record types, or members which are record types
+    /// declare the same attribute with incompatible types
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("invalid union type: {reason}")]
+    #[diagnostic(help(
+        "the members of a union must either all be entity types or all be record types"
+    ))]
+    pub struct InvalidUnionTypeError {
+        /// Why the union type is invalid
+        pub(crate) reason: String,
+    }
+
+    impl InvalidUnionTypeError {
+        pub(crate) fn empty() -> Self {
+            Self {
+                reason: "a union must have at least one member".into(),
+            }
+        }
+
+        pub(crate) fn unsupported_members(ty0: &Type, ty1: &Type) -> Self {
+            Self {
+                reason: format!("cannot form a union of `{ty0}` and `{ty1}`"),
+            }
+        }
+
+        pub(crate) fn incompatible_attribute(attr: &str, ty0: &Type, ty1: &Type) -> Self {
+            Self {
+                reason: format!(
+                    "members declare attribute `{attr}` with incompatible types `{ty0}` and `{ty1}`"
+                ),
+            }
+        }
+    }
+
+    /// The refinement declared for an attribute is invalid: it is a range on
+    /// an attribute which is not a `Long`, a pattern on an attribute which is
+    /// not a `String`, a `satisfying` method on an attribute of another type
+    /// than the method applies to, or the range, pattern, or method itself is
+    /// invalid
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("invalid refinement for attribute `{attr}`: {reason}")]
+    pub struct InvalidAttributeRefinementError {
+        /// Attribute with the invalid refinement
+        pub(crate) attr: SmolStr,
+        /// Why the refinement is invalid
+        pub(crate) reason: String,
+    }
+
+    impl InvalidAttributeRefinementError {
+        pub(crate) fn range_and_pattern(attr: SmolStr) -> Self {
+            Self {
+                attr,
+                reason: "an attribute cannot have both a range and a pattern".into(),
+            }
+        }
+
+        pub(crate) fn multiple(attr: SmolStr) -> Self {
+            Self {
+                attr,
+                reason:
+                    "an attribute cannot have both a `satisfying` method and a range or pattern"
+                        .into(),
+            }
+        }
+
+        pub(crate) fn invalid_method(attr: SmolStr, method: &str, why: impl Display) -> Self {
+            Self {
+                attr,
+                reason: format!("`{method}` cannot be used with `satisfying`: {why}"),
+            }
+        }
+
+        pub(crate) fn empty_range(attr: SmolStr, min: i64, max: i64) -> Self {
+            Self {
+                attr,
+                reason: format!("the range `[{min}..{max}]` is empty"),
+            }
+        }
+
+        pub(crate) fn invalid_pattern(attr: SmolStr, err: impl Display) -> Self {
+            Self {
+                attr,
+                reason: format!("invalid pattern: {err}"),
+            }
+        }
+
+        pub(crate) fn wrong_type(
+            attr: SmolStr,
+            refinement: &Refinement,
+            expected_ext: Option<&Name>,
+            ty: &Type,
+        ) -> Self {
+            let expected = match (refinement, expected_ext) {
+                (Refinement::Range { .. }, _) => "Long".to_string(),
+                (Refinement::Pattern(_), _) => "String".to_string(),
+                (Refinement::Satisfying(_), Some(name)) => name.to_string(),
+                (Refinement::Satisfying(_), None) => "an extension type".to_string(),
+            };
+            Self {
+                attr,
+                reason: format!(
+                    "`{refinement}` only applies to attributes of type `{expected}`, but the attribute has type `{ty}`"
+                ),
+            }
+        }
+
+        /// The attribute with the invalid refinement
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+    }
+
+    /// A constraint declared for an entity type is not a valid Cedar
+    /// expression
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug, Diagnostic)]
+    #[error("invalid constraint `{constraint}` on entity type `{entity_type}`")]
+    pub struct InvalidEntityConstraintError {
+        /// Entity type with the invalid constraint
+        pub(crate) entity_type: EntityType,
+        /// The constraint as written in the schema
+        pub(crate) constraint: SmolStr,
+        /// Error parsing the constraint
+        #[source]
+        #[diagnostic_source]
+        pub(crate) err: ParseErrors,
+    }
+
+    impl InvalidEntityConstraintError {
+        /// The entity type with the invalid constraint
+        pub fn entity_type(&self) -> &EntityType {
+            &self.entity_type
+        }
+    }
+
+    /// Actions which don't declare both the principal and resource types they
+    /// apply to
+    //
+    // CAUTION: this type is publicly exported in `cedar-policy`.
+    // Don't make fields `pub`, don't make breaking changes, and use caution
+    // when adding public methods.
+    #[derive(Error, Debug)]
+    #[error("{} {} {} not fully specified", if .actions.len() > 1 { "actions" } else { "action" }, .actions.iter().join(", "), if .actions.len() > 1 { "are" } else { "is" })]
+    pub struct UnspecifiedActionsError {
+        /// The actions, in sorted order
+        pub(crate) actions: NonEmpty<EntityUID>,
+        /// Source location of the declaration of the first action
+        pub(crate) source_loc: Option<Loc>,
+    }
+
+    impl Diagnostic for UnspecifiedActionsError {
+        fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+            Some(Box::new("declare at least one principal type and one resource type in the `appliesTo` of each action"))
+        }
+
+        impl_diagnostic_from_source_loc_opt_field!(source_loc);
+    }
+
+    impl UnspecifiedActionsError {
+        /// The actions which are not fully specified
+        pub fn actions(&self) -> impl Iterator<Item = &EntityUID> {
+            self.actions.iter()
+        }
+    }
 }
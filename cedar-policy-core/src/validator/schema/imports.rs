@@ -0,0 +1,374 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Composition of a schema from several sources in the Cedar schema syntax,
+//! which refer to each other with `import` declarations.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::path::{Path, PathBuf};
+
+use smol_str::SmolStr;
+
+use super::err::{schema_import_errors, CedarSchemaParseError, SchemaImportError};
+use super::RawName;
+use crate::extensions::Extensions;
+use crate::validator::cedar_schema::parser::parse_cedar_schema_fragment_with_imports;
+use crate::validator::cedar_schema::SchemaWarning;
+use crate::validator::json_schema;
+
+/// Loads the schema sources referred to by `import` declarations
+pub trait SchemaSourceLoader {
+    /// Load the source imported as `import` by the source named `importer`,
+    /// or the root source if `importer` is `None`.
+    ///
+    /// Returns a name for the source along with its text. The name is used to
+    /// detect import cycles and sources imported more than once, and in
+    /// diagnostics, so it must be the same each time the same source is
+    /// loaded (e.g., a canonical file path).
+    fn load(&mut self, importer: Option<&str>, import: &str) -> std::io::Result<(SmolStr, String)>;
+}
+
+/// Sources held in memory, keyed by name. Imports are looked up by name,
+/// regardless of the importing source.
+impl<S: BuildHasher> SchemaSourceLoader for HashMap<String, String, S> {
+    fn load(
+        &mut self,
+        _importer: Option<&str>,
+        import: &str,
+    ) -> std::io::Result<(SmolStr, String)> {
+        match self.get(import) {
+            Some(src) => Ok((import.into(), src.clone())),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no schema source named `{import}`"),
+            )),
+        }
+    }
+}
+
+/// Loads sources from the filesystem. Imports are resolved relative to the
+/// directory containing the importing file, and the root source relative to
+/// the current directory.
+///
+/// Not available on `wasm32-unknown-unknown`, which has no filesystem.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug, Clone, Default)]
+pub struct FileSchemaLoader;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl SchemaSourceLoader for FileSchemaLoader {
+    fn load(&mut self, importer: Option<&str>, import: &str) -> std::io::Result<(SmolStr, String)> {
+        let path = match importer.and_then(|importer| Path::new(importer).parent()) {
+            Some(dir) => dir.join(import),
+            None => PathBuf::from(import),
+        };
+        let path = path.canonicalize()?;
+        let src = std::fs::read_to_string(&path)?;
+        Ok((path.to_string_lossy().into(), src))
+    }
+}
+
+/// The sources of a composed schema, in an order where each source comes
+/// after every source it imports
+#[derive(Debug)]
+pub(crate) struct ComposedSources {
+    pub(crate) fragments: Vec<(SmolStr, json_schema::Fragment<RawName>)>,
+    pub(crate) warnings: Vec<SchemaWarning>,
+}
+
+/// Load and parse the source `root` and, transitively, every source it
+/// imports. Each source is only loaded once, even if it is imported more than
+/// once.
+pub(crate) fn load_sources(
+    root: &str,
+    loader: &mut impl SchemaSourceLoader,
+    extensions: &Extensions<'_>,
+) -> Result<ComposedSources, SchemaImportError> {
+    let mut composer = Composer {
+        loader,
+        extensions,
+        loaded: HashSet::new(),
+        stack: Vec::new(),
+        sources: ComposedSources {
+            fragments: Vec::new(),
+            warnings: Vec::new(),
+        },
+    };
+    composer.visit(None, root)?;
+    check_duplicate_definitions(&composer.sources.fragments)?;
+    Ok(composer.sources)
+}
+
+struct Composer<'l, 'e, L> {
+    loader: &'l mut L,
+    extensions: &'e Extensions<'e>,
+    /// Sources which have been loaded
+    loaded: HashSet<SmolStr>,
+    /// Sources currently being visited, in import order
+    stack: Vec<SmolStr>,
+    sources: ComposedSources,
+}
+
+impl<L: SchemaSourceLoader> Composer<'_, '_, L> {
+    fn visit(&mut self, importer: Option<&SmolStr>, import: &str) -> Result<(), SchemaImportError> {
+        let (name, src) = self
+            .loader
+            .load(importer.map(SmolStr::as_str), import)
+            .map_err(|source| schema_import_errors::LoadError {
+                import: import.into(),
+                importer: importer.cloned(),
+                source,
+            })?;
+        if let Some(pos) = self.stack.iter().position(|visiting| visiting == &name) {
+            let mut cycle: Vec<SmolStr> = self.stack.iter().skip(pos).cloned().collect();
+            cycle.push(name);
+            return Err(schema_import_errors::CycleError { cycle }.into());
+        }
+        if !self.loaded.insert(name.clone()) {
+            return Ok(());
+        }
+        let (imports, fragment, warnings) =
+            parse_cedar_schema_fragment_with_imports(&src, self.extensions).map_err(|err| {
+                schema_import_errors::ParseError {
+                    source_name: name.clone(),
+                    err: CedarSchemaParseError::new(err, &src),
+                }
+            })?;
+        self.sources.warnings.extend(warnings);
+        self.stack.push(name.clone());
+        for import in imports {
+            self.visit(Some(&name), &import.node)?;
+        }
+        self.stack.pop();
+        self.sources.fragments.push((name, fragment));
+        Ok(())
+    }
+}
+
+/// Check that no entity type, common type, or action is defined in more than
+/// one source. Duplicates within a single source are reported when the
+/// schema is constructed.
+fn check_duplicate_definitions(
+    fragments: &[(SmolStr, json_schema::Fragment<RawName>)],
+) -> Result<(), SchemaImportError> {
+    let mut defined: BTreeMap<(&'static str, String), &SmolStr> = BTreeMap::new();
+    for (source, fragment) in fragments {
+        for (ns, def) in &fragment.0 {
+            let qualify = |name: String| match ns {
+                Some(ns) => format!("{ns}::{name}"),
+                None => name,
+            };
+            let names = def
+                .entity_types
+                .keys()
+                .map(|name| ("entity type", qualify(name.to_string())))
+                .chain(
+                    def.common_types
+                        .keys()
+                        .map(|name| ("common type", qualify(name.to_string()))),
+                )
+                .chain(def.actions.keys().map(|name| {
+                    (
+                        "action",
+                        qualify(format!("Action::\"{}\"", name.escape_debug())),
+                    )
+                }));
+            for key in names {
+                if let Some(first) = defined.get(&key) {
+                    return Err(schema_import_errors::DuplicateDefinitionError {
+                        kind: key.0,
+                        name: key.1,
+                        first: (*first).clone(),
+                        second: source.clone(),
+                    }
+                    .into());
+                }
+                defined.insert(key, source);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use cool_asserts::assert_matches;
+
+    use crate::extensions::Extensions;
+    use crate::validator::{CedarSchemaError, SchemaImportError, ValidatorSchema};
+
+    fn sources(srcs: &[(&str, &str)]) -> HashMap<String, String> {
+        srcs.iter()
+            .map(|(name, src)| (name.to_string(), src.to_string()))
+            .collect()
+    }
+
+    fn compose(root: &str, srcs: &[(&str, &str)]) -> Result<ValidatorSchema, CedarSchemaError> {
+        ValidatorSchema::from_cedarschema_with_imports(
+            root,
+            &mut sources(srcs),
+            Extensions::all_available(),
+        )
+        .map(|(schema, _)| schema)
+    }
+
+    #[test]
+    fn diamond() {
+        let schema = compose(
+            "app",
+            &[
+                (
+                    "app",
+                    r#"import "docs"; import "groups";
+                    action view appliesTo { principal: User, resource: Document };"#,
+                ),
+                (
+                    "docs",
+                    r#"import "users"; entity Document { owner: User };"#,
+                ),
+                ("groups", r#"import "users"; entity Group;"#),
+                ("users", "entity User in Group;"),
+            ],
+        )
+        .expect("schema should compose");
+        assert_eq!(schema.entity_types().count(), 3);
+        assert_eq!(schema.action_ids().count(), 1);
+    }
+
+    #[test]
+    fn namespaced() {
+        let schema = compose(
+            "app",
+            &[
+                (
+                    "app",
+                    r#"import "users";
+                    namespace App { entity Document; action view appliesTo { principal: Users::User, resource: Document }; }"#,
+                ),
+                ("users", "namespace Users { entity User; }"),
+            ],
+        )
+        .expect("schema should compose");
+        assert_eq!(schema.entity_types().count(), 2);
+    }
+
+    #[test]
+    #[expect(
+        clippy::infinite_iter,
+        reason = "`CycleError::cycle()` is finite, unlike `Iterator::cycle()`"
+    )]
+    fn cycle() {
+        let err = compose(
+            "a",
+            &[
+                ("a", r#"import "b"; entity A;"#),
+                ("b", r#"import "c"; entity B;"#),
+                ("c", r#"import "b"; entity C;"#),
+            ],
+        )
+        .unwrap_err();
+        assert_matches!(
+            err,
+            CedarSchemaError::Import(SchemaImportError::Cycle(ref e)) => {
+                assert_eq!(e.cycle().collect::<Vec<_>>(), ["b", "c", "b"]);
+                assert_eq!(e.to_string(), "schema sources import each other cyclically: `b` -> `c` -> `b`");
+            }
+        );
+    }
+
+    #[test]
+    fn self_import() {
+        let err = compose("a", &[("a", r#"import "a"; entity A;"#)]).unwrap_err();
+        assert_matches!(err, CedarSchemaError::Import(SchemaImportError::Cycle(_)));
+    }
+
+    #[test]
+    fn duplicate_across_sources() {
+        let err = compose(
+            "a",
+            &[
+                ("a", r#"import "b"; entity User; entity A;"#),
+                ("b", "entity User;"),
+            ],
+        )
+        .unwrap_err();
+        assert_matches!(
+            err,
+            CedarSchemaError::Import(SchemaImportError::DuplicateDefinition(ref e)) => {
+                assert_eq!(e.name(), "User");
+                assert_eq!(e.sources(), ("b", "a"));
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_action_across_sources() {
+        let err = compose(
+            "a",
+            &[
+                ("a", r#"import "b"; namespace N { action "view"; }"#),
+                ("b", r#"namespace N { action view; }"#),
+            ],
+        )
+        .unwrap_err();
+        assert_matches!(
+            err,
+            CedarSchemaError::Import(SchemaImportError::DuplicateDefinition(ref e)) => {
+                assert_eq!(e.name(), r#"N::Action::"view""#);
+            }
+        );
+    }
+
+    #[test]
+    fn missing_import() {
+        let err = compose("a", &[("a", r#"import "b"; entity A;"#)]).unwrap_err();
+        assert_matches!(
+            err,
+            CedarSchemaError::Import(SchemaImportError::Load(ref e)) => {
+                assert_eq!(e.import(), "b");
+                assert_eq!(e.importer(), Some("a"));
+            }
+        );
+    }
+
+    #[test]
+    fn parse_error_names_source() {
+        let err =
+            compose("a", &[("a", r#"import "b"; entity A;"#), ("b", "entity B")]).unwrap_err();
+        assert_matches!(
+            err,
+            CedarSchemaError::Import(SchemaImportError::Parse(ref e)) => {
+                assert_eq!(e.source_name(), "b");
+            }
+        );
+    }
+
+    #[test]
+    fn import_after_declaration_is_rejected() {
+        let err = compose("a", &[("a", r#"entity A; import "b";"#)]).unwrap_err();
+        assert_matches!(err, CedarSchemaError::Import(SchemaImportError::Parse(_)));
+    }
+
+    #[test]
+    fn import_is_not_reserved() {
+        let schema = compose("a", &[("a", "entity import;")]).expect("schema should compose");
+        assert_eq!(schema.entity_types().count(), 1);
+    }
+}
@@ -22,7 +22,11 @@ use std::collections::{hash_map::Entry, HashMap, HashSet};
 use crate::ast::Eid;
 use crate::parser::Loc;
 use crate::{
-    ast::{EntityType, EntityUID, InternalName, Name, UnreservedId},
+    ast::{AnyId, EntityType, EntityUID, InternalName, Name, UnreservedId},
+    entities::{
+        json::err::JsonDeserializationErrorContext, Refinement, SchemaType as CoreSchemaType,
+        StringPattern,
+    },
     extensions::Extensions,
     fuzzy_match::fuzzy_search,
 };
@@ -35,8 +39,8 @@ use crate::validator::{
     err::{schema_errors::*, SchemaError},
     json_schema::{self, CommonTypeId, EntityTypeKind},
     partition_nonempty::PartitionNonEmpty,
-    types::{AttributeType, Attributes, OpenTag, Type},
-    ConditionalName, RawName, ReferenceType,
+    types::{AttributeType, Attributes, EntityKind, OpenTag, Type},
+    ConditionalName, RawName, ReferenceType, ValidationMode, DEPRECATED_ANNOTATION,
 };
 
 /// A single namespace definition from the schema JSON or Cedar syntax,
@@ -476,6 +480,9 @@ pub enum EntityTypeFragment<N> {
         /// resolved/inlined (e.g., because they are not defined in this schema
         /// fragment).
         tags: Option<json_schema::Type<N>>,
+        /// Invariants over the attributes of this entity type, as written in
+        /// the schema
+        constraints: Vec<SmolStr>,
     },
     Enum(NonEmpty<Eid>),
 }
@@ -516,6 +523,7 @@ impl EntityTypeFragment<ConditionalName> {
                     tags: ty
                         .tags
                         .map(|tags| tags.conditionally_qualify_type_references(schema_namespace)),
+                    constraints: ty.constraints,
                 }
             }
         }
@@ -537,6 +545,7 @@ impl EntityTypeFragment<ConditionalName> {
                 attributes,
                 parents,
                 tags,
+                constraints,
             } => {
                 // Fully qualify typenames appearing in `attributes`
                 let fully_qual_attributes = attributes.fully_qualify_type_references(all_defs);
@@ -565,6 +574,7 @@ impl EntityTypeFragment<ConditionalName> {
                         attributes,
                         parents,
                         tags,
+                        constraints,
                     }),
                     (Ok(_), Ok(_), Some(undeclared_parents)) => Err(TypeNotDefinedError {
                         undefined_types: undeclared_parents,
@@ -923,6 +933,15 @@ pub(crate) fn try_jsonschema_type_into_validator_type(
                 LocatedType::new_with_loc(Type::set(vt_ty.into()), &vt_loc)
             }),
         ),
+        json_schema::Type::Type {
+            ty: json_schema::TypeVariant::Map { element },
+            ..
+        } => Ok(
+            try_jsonschema_type_into_validator_type(*element, extensions, loc)?.map(|vt| {
+                let (vt_ty, vt_loc) = vt.into_type_and_loc();
+                LocatedType::new_with_loc(Type::map(vt_ty), &vt_loc)
+            }),
+        ),
         json_schema::Type::Type {
             ty: json_schema::TypeVariant::Record(rty),
             ..
@@ -960,6 +979,30 @@ pub(crate) fn try_jsonschema_type_into_validator_type(
                 ))
             }
         }
+        json_schema::Type::Type {
+            ty: json_schema::TypeVariant::Union { members },
+            ..
+        } => {
+            let members = members
+                .into_iter()
+                .map(|member| try_jsonschema_type_into_validator_type(member, extensions, None))
+                .collect::<crate::validator::err::Result<Vec<_>>>()?;
+            let loc_clone = loc.clone();
+            Ok(WithUnresolvedCommonTypeRefs::new(
+                move |common_type_defs| {
+                    let members = members
+                        .into_iter()
+                        .map(|member| {
+                            member
+                                .resolve_common_type_refs(common_type_defs)
+                                .map(|ty| ty.into_type_and_loc().0)
+                        })
+                        .collect::<crate::validator::err::Result<Vec<_>>>()?;
+                    Ok(LocatedType::new_with_loc(union_type(members)?, &loc))
+                },
+                loc_clone,
+            ))
+        }
         json_schema::Type::CommonTypeRef { type_name, .. } => {
             Ok(WithUnresolvedCommonTypeRefs::new(
                 move |common_type_defs| {
@@ -1012,6 +1055,93 @@ pub(crate) fn try_jsonschema_type_into_validator_type(
     }
 }
 
+/// Compute the validator [`Type`] of a union with the given member types.
+///
+/// A union of entity types is the least upper bound of the entity types, so
+/// the typechecker can narrow it with `is`. A union of record types is a
+/// record type with every attribute of every member, where an attribute is
+/// only required if it is required in every member. Other unions are not
+/// supported.
+fn union_type(members: Vec<Type>) -> crate::validator::err::Result<Type> {
+    let mut members = members.into_iter();
+    let Some(first) = members.next() else {
+        return Err(InvalidUnionTypeError::empty().into());
+    };
+    members.try_fold(first, |acc, member| match (acc, member) {
+        (Type::Entity(EntityKind::Entity(lub0)), Type::Entity(EntityKind::Entity(lub1))) => Ok(
+            Type::Entity(EntityKind::Entity(lub0.least_upper_bound(&lub1))),
+        ),
+        (
+            Type::Record {
+                attrs: attrs0,
+                open_attributes: open0,
+            },
+            Type::Record {
+                attrs: attrs1,
+                open_attributes: open1,
+            },
+        ) => Ok(Type::Record {
+            attrs: union_attributes(&attrs0, &attrs1)?,
+            open_attributes: if open0.is_open() || open1.is_open() {
+                OpenTag::OpenAttributes
+            } else {
+                OpenTag::ClosedAttributes
+            },
+        }),
+        (acc, member) => Err(InvalidUnionTypeError::unsupported_members(&acc, &member).into()),
+    })
+}
+
+/// Merge the attributes of two record types which are members of a union
+fn union_attributes(
+    attrs0: &Attributes,
+    attrs1: &Attributes,
+) -> crate::validator::err::Result<Attributes> {
+    // An attribute which is only in some members is optional in the union.
+    // Default values and refinements only apply when every member agrees on
+    // them.
+    let only_in_one = |ty: &AttributeType| {
+        AttributeType::new(ty.attr_type.clone(), false)
+            .with_refinement(ty.refinement.clone())
+            .with_deprecation(ty.deprecation.clone())
+    };
+    let mut attrs = Vec::new();
+    for (attr, ty0) in attrs0.iter() {
+        let attr_ty = match attrs1.get_attr(attr) {
+            Some(ty1) => {
+                let ty = Type::least_upper_bound(
+                    &ty0.attr_type,
+                    &ty1.attr_type,
+                    ValidationMode::Permissive,
+                )
+                .map_err(|_| {
+                    InvalidUnionTypeError::incompatible_attribute(
+                        attr,
+                        &ty0.attr_type,
+                        &ty1.attr_type,
+                    )
+                })?;
+                AttributeType::new(ty.into(), ty0.is_required && ty1.is_required)
+                    .with_default(ty0.default.clone().filter(|_| ty0.default == ty1.default))
+                    .with_refinement(
+                        ty0.refinement
+                            .clone()
+                            .filter(|_| ty0.refinement == ty1.refinement),
+                    )
+                    .with_deprecation(ty0.deprecation.clone().or_else(|| ty1.deprecation.clone()))
+            }
+            None => only_in_one(ty0),
+        };
+        attrs.push((attr.clone(), attr_ty));
+    }
+    for (attr, ty1) in attrs1.iter() {
+        if attrs0.get_attr(attr).is_none() {
+            attrs.push((attr.clone(), only_in_one(ty1)));
+        }
+    }
+    Ok(Attributes::with_attributes(attrs))
+}
+
 /// Convert a [`json_schema::RecordType`] (with fully qualified names) into the
 /// [`Type`] type used by the validator.
 pub(crate) fn try_record_type_into_validator_type(
@@ -1060,11 +1190,74 @@ fn parse_record_attributes(
             let loc = ty.loc;
             #[cfg(not(feature = "extended-schema"))]
             let loc = None;
+            // Default values are checked against the attribute's type once
+            // common types are resolved (see `ValidatorSchema::check_attribute_defaults`)
+            let default = match ty.default {
+                Some(_) if ty.required => {
+                    return Err(InvalidAttributeDefaultError::required(attr).into());
+                }
+                Some(default) => Some(
+                    default
+                        .into_expr(&|| JsonDeserializationErrorContext::AttributeDefault {
+                            attr: attr.clone(),
+                        })
+                        .map_err(|err| {
+                            InvalidAttributeDefaultError::invalid_value(attr.clone(), err)
+                        })?,
+                ),
+                None => None,
+            };
+            // Refinements are checked against the attribute's type once common
+            // types are resolved, below. `refinement_ext_ty` is the extension
+            // type a `satisfying` method applies to.
+            let mut refinement_ext_ty = None;
+            let refinement = match (ty.range, ty.pattern.clone()) {
+                _ if ty.satisfying.is_some() && (ty.range.is_some() || ty.pattern.is_some()) => {
+                    return Err(InvalidAttributeRefinementError::multiple(attr).into());
+                }
+                (Some(_), Some(_)) => {
+                    return Err(InvalidAttributeRefinementError::range_and_pattern(attr).into());
+                }
+                (Some(json_schema::LongRange { min, max }), None) => match (min, max) {
+                    (Some(min), Some(max)) if min > max => {
+                        return Err(
+                            InvalidAttributeRefinementError::empty_range(attr, min, max).into()
+                        );
+                    }
+                    _ => Some(Refinement::Range { min, max }),
+                },
+                (None, Some(pattern)) => {
+                    Some(Refinement::Pattern(StringPattern::new(pattern).map_err(
+                        |err| InvalidAttributeRefinementError::invalid_pattern(attr.clone(), err),
+                    )?))
+                }
+                (None, None) => match &ty.satisfying {
+                    Some(method) => {
+                        let (method, ext_ty) = check_satisfying_method(&attr, method, extensions)?;
+                        refinement_ext_ty = Some(ext_ty);
+                        Some(Refinement::Satisfying(method))
+                    }
+                    None => None,
+                },
+            };
+            let deprecation = ty
+                .annotations
+                .0
+                .get(&AnyId::new_unchecked(DEPRECATED_ANNOTATION))
+                .map(|annotation| {
+                    annotation
+                        .as_ref()
+                        .map(|annotation| annotation.val.clone())
+                        .unwrap_or_default()
+                });
             Ok((
                 attr,
                 (
                     try_jsonschema_type_into_validator_type(ty.ty.clone(), extensions, loc)?,
                     ty.required,
+                    default,
+                    (refinement, refinement_ext_ty),
+                    deprecation,
                 ),
             ))
         })
@@ -1074,23 +1267,75 @@ fn parse_record_attributes(
         |common_type_defs| {
             attrs_with_common_type_refs
                 .into_iter()
-                .map(|(s, (attr_ty, is_req))| {
-                    #[cfg(feature = "extended-schema")]
-                    let loc = attr_ty.loc().cloned();
-                    attr_ty
-                        .resolve_common_type_refs(common_type_defs)
-                        .map(|ty| {
-                            #[cfg(feature = "extended-schema")]
-                            let ty_pair =
-                                (s, AttributeType::new_with_loc(ty.ty.into(), is_req, loc));
-                            #[cfg(not(feature = "extended-schema"))]
-                            let ty_pair = (s, AttributeType::new(ty.ty.into(), is_req));
-                            ty_pair
-                        })
-                })
+                .map(
+                    |(
+                        s,
+                        (attr_ty, is_req, default, (refinement, refinement_ext_ty), deprecation),
+                    )| {
+                        #[cfg(feature = "extended-schema")]
+                        let loc = attr_ty.loc().cloned();
+                        let ty = attr_ty.resolve_common_type_refs(common_type_defs)?;
+                        match (&refinement, &ty.ty) {
+                            (None, _)
+                            | (Some(Refinement::Range { .. }), Type::Long)
+                            | (Some(Refinement::Pattern(_)), Type::String) => (),
+                            (Some(Refinement::Satisfying(_)), Type::ExtensionType { name })
+                                if refinement_ext_ty.as_ref() == Some(name) => {}
+                            (Some(refinement), ty) => {
+                                return Err(InvalidAttributeRefinementError::wrong_type(
+                                    s,
+                                    refinement,
+                                    refinement_ext_ty.as_ref(),
+                                    ty,
+                                )
+                                .into());
+                            }
+                        }
+                        #[cfg(feature = "extended-schema")]
+                        let attr_ty = AttributeType::new_with_loc(ty.ty.into(), is_req, loc);
+                        #[cfg(not(feature = "extended-schema"))]
+                        let attr_ty = AttributeType::new(ty.ty.into(), is_req);
+                        Ok((
+                            s,
+                            attr_ty
+                                .with_default(default)
+                                .with_refinement(refinement)
+                                .with_deprecation(deprecation),
+                        ))
+                    },
+                )
                 .collect::<crate::validator::err::Result<Vec<_>>>()
                 .map(Attributes::with_attributes)
         },
         loc,
     ))
 }
+
+/// Check that `method` names an extension method which can be used in a
+/// `satisfying` refinement of attribute `attr`: it must take a single argument
+/// of an extension type and return a `Bool`. Returns the method's name and the
+/// extension type it applies to.
+fn check_satisfying_method(
+    attr: &SmolStr,
+    method: &str,
+    extensions: &Extensions<'_>,
+) -> crate::validator::err::Result<(Name, Name)> {
+    let invalid =
+        |why: &str| InvalidAttributeRefinementError::invalid_method(attr.clone(), method, why);
+    let name = Name::parse_unqualified_name(method)
+        .map_err(|_| invalid("it is not a valid extension function name"))?;
+    let func = extensions
+        .func(&name)
+        .map_err(|_| invalid("there is no extension function with this name"))?;
+    match (func.arg_types(), func.return_type()) {
+        ([CoreSchemaType::Extension { name: ext_ty }], Some(CoreSchemaType::Bool))
+            if !func.is_variadic() =>
+        {
+            Ok((name, ext_ty.clone()))
+        }
+        _ => Err(invalid(
+            "it must take a single argument of an extension type and return a `Bool`",
+        )
+        .into()),
+    }
+}
@@ -1953,7 +1953,7 @@ fn F2c() {
 fn F3a() {
     let expected =
         ExpectedErrorMessageBuilder::error("undeclared action: NS2::Action::\"ActionGroup\"")
-            .help("any actions appearing as parents need to be declared as actions")
+            .help("actions declared in another namespace must be referred to by their fully-qualified name; did you mean `NS1::Action::\"ActionGroup\"`?")
             .build();
     assert_parse_error_cedar(F3a_cedar(), &expected);
     assert_parse_error_json(&F3a_json(), &expected);
@@ -0,0 +1,766 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Structured documentation for a schema, and rendering of it as Markdown or
+//! HTML.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::json_schema;
+use crate::est::Annotations;
+
+/// The annotation whose value is used as the description of a declaration
+const DOC_ANNOTATION: &str = "doc";
+
+/// Documentation for a schema: the entity types, actions, and common types
+/// declared in each of its namespaces, with their annotations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDoc {
+    /// Namespaces in the schema, with the empty namespace (if any) first
+    pub namespaces: Vec<NamespaceDoc>,
+}
+
+/// Documentation for the declarations in one namespace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceDoc {
+    /// Name of the namespace, or `None` for the empty namespace
+    pub name: Option<String>,
+    /// Annotations on the namespace
+    pub annotations: BTreeMap<String, String>,
+    /// Entity types declared in the namespace
+    pub entity_types: Vec<EntityTypeDoc>,
+    /// Actions declared in the namespace
+    pub actions: Vec<ActionDoc>,
+    /// Common types declared in the namespace
+    pub common_types: Vec<CommonTypeDoc>,
+}
+
+/// Documentation for an entity type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityTypeDoc {
+    /// Name of the entity type, as declared in its namespace
+    pub name: String,
+    /// Annotations on the entity type
+    pub annotations: BTreeMap<String, String>,
+    /// Entity types which entities of this type may be members of
+    pub member_of_types: Vec<String>,
+    /// Attributes of the entity type. Empty for enumerated entity types, and
+    /// for entity types whose shape is a common type.
+    pub attributes: Vec<AttributeDoc>,
+    /// The shape of the entity type, when it is a common type rather than a
+    /// record
+    pub shape: Option<String>,
+    /// Type of the tags on entities of this type, if they may have tags
+    pub tags: Option<String>,
+    /// Valid entity ids, for enumerated entity types
+    pub enum_choices: Vec<String>,
+    /// Constraints over the attributes of entities of this type
+    pub constraints: Vec<String>,
+}
+
+/// Documentation for an attribute of an entity type, record, or context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeDoc {
+    /// Name of the attribute
+    pub name: String,
+    /// Type of the attribute in the Cedar schema syntax. For record types, this
+    /// is `Record` and the attributes of the record are in `attributes`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Whether the attribute is required
+    pub required: bool,
+    /// Value of the attribute when it is absent, if it has one
+    pub default: Option<String>,
+    /// Annotations on the attribute
+    pub annotations: BTreeMap<String, String>,
+    /// Attributes of the record, if the attribute has a record type
+    pub attributes: Vec<AttributeDoc>,
+}
+
+/// Documentation for an action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDoc {
+    /// Name of the action
+    pub name: String,
+    /// Annotations on the action
+    pub annotations: BTreeMap<String, String>,
+    /// Action groups which the action is a member of
+    pub member_of: Vec<String>,
+    /// Principal types the action applies to
+    pub principal_types: Vec<String>,
+    /// Resource types the action applies to
+    pub resource_types: Vec<String>,
+    /// Attributes of the context for the action. Empty when the context is a
+    /// common type.
+    pub context: Vec<AttributeDoc>,
+    /// The context of the action, when it is a common type rather than a
+    /// record
+    pub context_type: Option<String>,
+}
+
+/// Documentation for a common type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommonTypeDoc {
+    /// Name of the common type
+    pub name: String,
+    /// Annotations on the common type
+    pub annotations: BTreeMap<String, String>,
+    /// The type in the Cedar schema syntax. For record types, this is `Record`
+    /// and the attributes of the record are in `attributes`.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Attributes of the record, if the common type is a record type
+    pub attributes: Vec<AttributeDoc>,
+}
+
+impl<N: Display> From<&json_schema::Fragment<N>> for SchemaDoc {
+    fn from(fragment: &json_schema::Fragment<N>) -> Self {
+        Self {
+            namespaces: fragment
+                .0
+                .iter()
+                .map(|(name, ns)| NamespaceDoc::new(name.as_ref().map(ToString::to_string), ns))
+                .collect(),
+        }
+    }
+}
+
+impl NamespaceDoc {
+    fn new<N: Display>(name: Option<String>, ns: &json_schema::NamespaceDefinition<N>) -> Self {
+        Self {
+            name,
+            annotations: annotations(&ns.annotations),
+            entity_types: ns
+                .entity_types
+                .iter()
+                .map(|(name, ety)| EntityTypeDoc::new(name.to_string(), ety))
+                .collect(),
+            actions: ns
+                .actions
+                .iter()
+                .map(|(name, action)| ActionDoc::new(name.to_string(), action))
+                .collect(),
+            common_types: ns
+                .common_types
+                .iter()
+                .map(|(name, ty)| CommonTypeDoc::new(name.to_string(), ty))
+                .collect(),
+        }
+    }
+}
+
+impl EntityTypeDoc {
+    fn new<N: Display>(name: String, ety: &json_schema::EntityType<N>) -> Self {
+        let mut doc = Self {
+            name,
+            annotations: annotations(&ety.annotations),
+            member_of_types: Vec::new(),
+            attributes: Vec::new(),
+            shape: None,
+            tags: None,
+            enum_choices: Vec::new(),
+            constraints: Vec::new(),
+        };
+        match &ety.kind {
+            json_schema::EntityTypeKind::Standard(ty) => {
+                doc.member_of_types = ty.member_of_types.iter().map(ToString::to_string).collect();
+                match record_attributes(&ty.shape.0) {
+                    Some(attrs) => doc.attributes = attrs,
+                    None => doc.shape = Some(type_str(&ty.shape.0)),
+                }
+                doc.tags = ty.tags.as_ref().map(type_str);
+                doc.constraints = ty.constraints.iter().map(ToString::to_string).collect();
+            }
+            json_schema::EntityTypeKind::Enum { choices } => {
+                doc.enum_choices = choices.iter().map(|eid| eid.as_ref().to_string()).collect();
+            }
+        }
+        doc
+    }
+}
+
+impl ActionDoc {
+    fn new<N: Display>(name: String, action: &json_schema::ActionType<N>) -> Self {
+        let mut doc = Self {
+            name,
+            annotations: annotations(&action.annotations),
+            member_of: action
+                .member_of
+                .iter()
+                .flatten()
+                .map(ToString::to_string)
+                .collect(),
+            principal_types: Vec::new(),
+            resource_types: Vec::new(),
+            context: Vec::new(),
+            context_type: None,
+        };
+        if let Some(applies_to) = &action.applies_to {
+            doc.principal_types = applies_to
+                .principal_types
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            doc.resource_types = applies_to
+                .resource_types
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            match record_attributes(&applies_to.context.0) {
+                Some(attrs) => doc.context = attrs,
+                None => doc.context_type = Some(type_str(&applies_to.context.0)),
+            }
+        }
+        doc
+    }
+}
+
+impl CommonTypeDoc {
+    fn new<N: Display>(name: String, ty: &json_schema::CommonType<N>) -> Self {
+        let (ty_str, attributes) = match record_attributes(&ty.ty) {
+            Some(attrs) => ("Record".to_string(), attrs),
+            None => (type_str(&ty.ty), Vec::new()),
+        };
+        Self {
+            name,
+            annotations: annotations(&ty.annotations),
+            ty: ty_str,
+            attributes,
+        }
+    }
+}
+
+fn annotations(annotations: &Annotations) -> BTreeMap<String, String> {
+    annotations
+        .0
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                v.as_ref().map(|v| v.val.to_string()).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// The type in the Cedar schema syntax, on one line
+fn type_str<N: Display>(ty: &json_schema::Type<N>) -> String {
+    ty.to_string().split_whitespace().join(" ")
+}
+
+/// The attributes of `ty`, if it is a record type
+fn record_attributes<N: Display>(ty: &json_schema::Type<N>) -> Option<Vec<AttributeDoc>> {
+    match ty {
+        json_schema::Type::Type {
+            ty: json_schema::TypeVariant::Record(record),
+            ..
+        } => Some(
+            record
+                .attributes
+                .iter()
+                .map(|(name, attr)| {
+                    let (ty, attributes) = match record_attributes(&attr.ty) {
+                        Some(attrs) => ("Record".to_string(), attrs),
+                        None => (type_str(&attr.ty), Vec::new()),
+                    };
+                    AttributeDoc {
+                        name: name.to_string(),
+                        ty,
+                        required: attr.required,
+                        default: attr
+                            .default
+                            .as_ref()
+                            .and_then(|default| serde_json::to_string(default).ok()),
+                        annotations: annotations(&attr.annotations),
+                        attributes,
+                    }
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+impl SchemaDoc {
+    /// Render this documentation as Markdown
+    pub fn to_markdown(&self) -> String {
+        self.render(&Markdown)
+    }
+
+    /// Render this documentation as a standalone HTML document
+    pub fn to_html(&self) -> String {
+        self.render(&Html)
+    }
+
+    fn render(&self, fmt: &impl Format) -> String {
+        let mut out = String::new();
+        fmt.start(&mut out);
+        for ns in &self.namespaces {
+            let title = match &ns.name {
+                Some(name) => format!("Namespace {}", fmt.code(name)),
+                None => "Schema".to_string(),
+            };
+            fmt.heading(&mut out, 1, &title);
+            fmt.annotations(&mut out, &ns.annotations);
+            if !ns.entity_types.is_empty() {
+                fmt.heading(&mut out, 2, "Entity types");
+                for ety in &ns.entity_types {
+                    fmt.heading(&mut out, 3, &fmt.code(&ety.name));
+                    fmt.annotations(&mut out, &ety.annotations);
+                    let mut facts = Vec::new();
+                    if !ety.member_of_types.is_empty() {
+                        facts.push(format!(
+                            "Member of: {}",
+                            fmt.code_list(&ety.member_of_types)
+                        ));
+                    }
+                    if let Some(shape) = &ety.shape {
+                        facts.push(format!("Shape: {}", fmt.code(shape)));
+                    }
+                    if let Some(tags) = &ety.tags {
+                        facts.push(format!("Tags: {}", fmt.code(tags)));
+                    }
+                    if !ety.enum_choices.is_empty() {
+                        facts.push(format!(
+                            "Enumerated ids: {}",
+                            fmt.code_list(&ety.enum_choices)
+                        ));
+                    }
+                    if !ety.constraints.is_empty() {
+                        facts.push(format!("Constraints: {}", fmt.code_list(&ety.constraints)));
+                    }
+                    fmt.list(&mut out, &facts);
+                    fmt.attributes(&mut out, &ety.attributes);
+                }
+            }
+            if !ns.actions.is_empty() {
+                fmt.heading(&mut out, 2, "Actions");
+                for action in &ns.actions {
+                    fmt.heading(&mut out, 3, &fmt.code(&action.name));
+                    fmt.annotations(&mut out, &action.annotations);
+                    let mut facts = Vec::new();
+                    if !action.member_of.is_empty() {
+                        facts.push(format!("Member of: {}", fmt.code_list(&action.member_of)));
+                    }
+                    if !action.principal_types.is_empty() || !action.resource_types.is_empty() {
+                        facts.push(format!(
+                            "Principals: {}",
+                            fmt.code_list(&action.principal_types)
+                        ));
+                        facts.push(format!(
+                            "Resources: {}",
+                            fmt.code_list(&action.resource_types)
+                        ));
+                    }
+                    if let Some(context) = &action.context_type {
+                        facts.push(format!("Context: {}", fmt.code(context)));
+                    }
+                    fmt.list(&mut out, &facts);
+                    fmt.attributes(&mut out, &action.context);
+                }
+            }
+            if !ns.common_types.is_empty() {
+                fmt.heading(&mut out, 2, "Common types");
+                for ty in &ns.common_types {
+                    fmt.heading(&mut out, 3, &fmt.code(&ty.name));
+                    fmt.annotations(&mut out, &ty.annotations);
+                    if ty.attributes.is_empty() {
+                        fmt.list(&mut out, &[format!("Type: {}", fmt.code(&ty.ty))]);
+                    }
+                    fmt.attributes(&mut out, &ty.attributes);
+                }
+            }
+        }
+        fmt.end(&mut out);
+        out
+    }
+}
+
+/// An output format for [`SchemaDoc`]. `text` arguments are plain text, which
+/// the format escapes as needed; the output of `code()` and `code_list()` is
+/// already in the format.
+trait Format {
+    fn start(&self, _out: &mut String) {}
+    fn end(&self, _out: &mut String) {}
+    fn text(&self, text: &str) -> String;
+    fn code(&self, text: &str) -> String;
+    fn heading(&self, out: &mut String, level: usize, content: &str);
+    fn paragraph(&self, out: &mut String, text: &str);
+    fn list(&self, out: &mut String, items: &[String]);
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]);
+
+    fn code_list(&self, items: &[String]) -> String {
+        if items.is_empty() {
+            return self.text("none");
+        }
+        items.iter().map(|item| self.code(item)).join(", ")
+    }
+
+    /// The `doc` annotation as a paragraph, followed by any other annotations
+    fn annotations(&self, out: &mut String, annotations: &BTreeMap<String, String>) {
+        if let Some(doc) = annotations.get(DOC_ANNOTATION) {
+            self.paragraph(out, doc);
+        }
+        let others = annotations
+            .iter()
+            .filter(|(k, _)| k.as_str() != DOC_ANNOTATION)
+            .map(|(k, v)| self.code(&annotation_str(k, v)))
+            .collect_vec();
+        if !others.is_empty() {
+            self.list(out, &[format!("Annotations: {}", others.join(", "))]);
+        }
+    }
+
+    /// A table of attributes, with the attributes of records flattened into
+    /// dotted paths
+    fn attributes(&self, out: &mut String, attributes: &[AttributeDoc]) {
+        fn rows<F: Format + ?Sized>(
+            fmt: &F,
+            prefix: &str,
+            attributes: &[AttributeDoc],
+            acc: &mut Vec<Vec<String>>,
+        ) {
+            for attr in attributes {
+                let path = format!("{prefix}{}", attr.name);
+                acc.push(vec![
+                    fmt.code(&path),
+                    fmt.code(&attr.ty),
+                    fmt.text(if attr.required { "yes" } else { "no" }),
+                    attr.default
+                        .as_ref()
+                        .map(|default| fmt.code(default))
+                        .unwrap_or_default(),
+                    attr.annotations
+                        .iter()
+                        .map(|(k, v)| {
+                            if k == DOC_ANNOTATION {
+                                fmt.text(v)
+                            } else {
+                                fmt.code(&annotation_str(k, v))
+                            }
+                        })
+                        .join(" "),
+                ]);
+                rows(fmt, &format!("{path}."), &attr.attributes, acc);
+            }
+        }
+        if attributes.is_empty() {
+            return;
+        }
+        let mut acc = Vec::new();
+        rows(self, "", attributes, &mut acc);
+        self.table(
+            out,
+            &["Attribute", "Type", "Required", "Default", "Description"],
+            &acc,
+        );
+    }
+}
+
+fn annotation_str(key: &str, value: &str) -> String {
+    format!("@{key}(\"{}\")", value.escape_debug())
+}
+
+struct Markdown;
+
+impl Format for Markdown {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' | '\r' => escaped.push(' '),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn code(&self, text: &str) -> String {
+        let text = text.replace(['\n', '\r'], " ").replace('|', "\\|");
+        // Use a fence longer than any run of backticks in the text
+        let longest = text
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        let fence = "`".repeat(longest + 1);
+        if text.starts_with('`') || text.ends_with('`') {
+            format!("{fence} {text} {fence}")
+        } else {
+            format!("{fence}{text}{fence}")
+        }
+    }
+
+    fn heading(&self, out: &mut String, level: usize, content: &str) {
+        let _ = writeln!(out, "{} {content}\n", "#".repeat(level));
+    }
+
+    fn paragraph(&self, out: &mut String, text: &str) {
+        let _ = writeln!(out, "{}\n", self.text(text));
+    }
+
+    fn list(&self, out: &mut String, items: &[String]) {
+        if items.is_empty() {
+            return;
+        }
+        for item in items {
+            let _ = writeln!(out, "- {item}");
+        }
+        out.push('\n');
+    }
+
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+        let _ = writeln!(out, "| {} |", header.join(" | "));
+        let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+        for row in rows {
+            let _ = writeln!(out, "| {} |", row.join(" | "));
+        }
+        out.push('\n');
+    }
+}
+
+struct Html;
+
+impl Format for Html {
+    fn start(&self, out: &mut String) {
+        out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Schema</title>\n</head>\n<body>\n");
+    }
+
+    fn end(&self, out: &mut String) {
+        out.push_str("</body>\n</html>\n");
+    }
+
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn code(&self, text: &str) -> String {
+        format!("<code>{}</code>", self.text(text))
+    }
+
+    fn heading(&self, out: &mut String, level: usize, content: &str) {
+        let _ = writeln!(out, "<h{level}>{content}</h{level}>");
+    }
+
+    fn paragraph(&self, out: &mut String, text: &str) {
+        let _ = writeln!(out, "<p>{}</p>", self.text(text));
+    }
+
+    fn list(&self, out: &mut String, items: &[String]) {
+        if items.is_empty() {
+            return;
+        }
+        out.push_str("<ul>\n");
+        for item in items {
+            let _ = writeln!(out, "<li>{item}</li>");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+        out.push_str("<table>\n<tr>");
+        for h in header {
+            let _ = write!(out, "<th>{}</th>", self.text(h));
+        }
+        out.push_str("</tr>\n");
+        for row in rows {
+            out.push_str("<tr>");
+            for cell in row {
+                let _ = write!(out, "<td>{cell}</td>");
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::indexing_slicing, reason = "unit tests")]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::extensions::Extensions;
+    use crate::validator::RawName;
+
+    const SCHEMA: &str = r#"
+    @doc("Photo sharing")
+    namespace App {
+        @doc("A person using the app")
+        entity User in [Group] {
+            @doc("Display name")
+            name: String,
+            address?: { city: String, zip?: String },
+            level?: Long = 1,
+        } tags Set<String>;
+        entity Group;
+        entity Color enum ["red", "green"];
+        type Album = { title: String };
+        @doc("View a photo")
+        @audited
+        action view appliesTo {
+            principal: User,
+            resource: Group,
+            context: { mfa: Bool },
+        };
+        action share in [view];
+    }
+    "#;
+
+    fn doc() -> SchemaDoc {
+        let (frag, _) = json_schema::Fragment::<RawName>::from_cedarschema_str(
+            SCHEMA,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        SchemaDoc::from(&frag)
+    }
+
+    #[test]
+    fn json_model() {
+        let doc = serde_json::to_value(doc()).unwrap();
+        let ns = &doc["namespaces"][0];
+        assert_eq!(ns["name"], json!("App"));
+        assert_eq!(ns["annotations"], json!({ "doc": "Photo sharing" }));
+        assert_eq!(
+            ns["entityTypes"][2],
+            json!({
+                "name": "User",
+                "annotations": { "doc": "A person using the app" },
+                "memberOfTypes": ["Group"],
+                "attributes": [
+                    {
+                        "name": "address",
+                        "type": "Record",
+                        "required": false,
+                        "default": null,
+                        "annotations": {},
+                        "attributes": [
+                            { "name": "city", "type": "String", "required": true, "default": null, "annotations": {}, "attributes": [] },
+                            { "name": "zip", "type": "String", "required": false, "default": null, "annotations": {}, "attributes": [] },
+                        ],
+                    },
+                    { "name": "level", "type": "Long", "required": false, "default": "1", "annotations": {}, "attributes": [] },
+                    { "name": "name", "type": "String", "required": true, "default": null, "annotations": { "doc": "Display name" }, "attributes": [] },
+                ],
+                "shape": null,
+                "tags": "Set<String>",
+                "enumChoices": [],
+                "constraints": [],
+            })
+        );
+        assert_eq!(ns["entityTypes"][0]["enumChoices"], json!(["red", "green"]));
+        assert_eq!(
+            ns["actions"][1],
+            json!({
+                "name": "view",
+                "annotations": { "audited": "", "doc": "View a photo" },
+                "memberOf": [],
+                "principalTypes": ["User"],
+                "resourceTypes": ["Group"],
+                "context": [
+                    { "name": "mfa", "type": "Bool", "required": true, "default": null, "annotations": {}, "attributes": [] },
+                ],
+                "contextType": null,
+            })
+        );
+        assert_eq!(ns["actions"][0]["memberOf"], json!(["Action::\"view\""]));
+        assert_eq!(ns["commonTypes"][0]["name"], json!("Album"));
+        assert_eq!(ns["commonTypes"][0]["type"], json!("Record"));
+    }
+
+    #[test]
+    fn markdown() {
+        let md = doc().to_markdown();
+        assert!(
+            md.starts_with("# Namespace `App`\n\nPhoto sharing\n\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("### `User`\n\nA person using the app\n\n- Member of: `Group`\n- Tags: `Set<String>`\n\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("| `address.city` | `String` | yes |  |  |\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("| `name` | `String` | yes |  | Display name |\n"),
+            "{md}"
+        );
+        assert!(md.contains("- Enumerated ids: `red`, `green`\n"), "{md}");
+        assert!(
+            md.contains("View a photo\n\n- Annotations: `@audited(\"\")`\n\n- Principals: `User`\n- Resources: `Group`\n"),
+            "{md}"
+        );
+        assert!(md.contains("- Member of: `Action::\"view\"`\n"), "{md}");
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let (frag, _) = json_schema::Fragment::<RawName>::from_cedarschema_str(
+            r#"@doc("<script>alert('hi')</script>") entity E { "a&b": String };"#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let html = SchemaDoc::from(&frag).to_html();
+        assert!(
+            html.contains("<p>&lt;script&gt;alert(&#39;hi&#39;)&lt;/script&gt;</p>"),
+            "{html}"
+        );
+        assert!(html.contains("<td><code>a&amp;b</code></td>"), "{html}");
+        assert!(!html.contains("<script>"), "{html}");
+    }
+
+    #[test]
+    fn markdown_is_escaped() {
+        let (frag, _) = json_schema::Fragment::<RawName>::from_cedarschema_str(
+            r#"entity E { @doc("a | b *c*") "x`y": String };"#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let md = SchemaDoc::from(&frag).to_markdown();
+        assert!(
+            md.contains("| ``x`y`` | `String` | yes |  | a \\| b \\*c\\* |"),
+            "{md}"
+        );
+    }
+}
@@ -0,0 +1,1231 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Inference of a draft schema from the policies in a policy set, as a
+//! starting point for validating policies which were written without one
+
+use super::{json_schema, RawName};
+use crate::ast::{
+    ActionConstraint, BinaryOp, EntityReference, EntityType, EntityUID, Expr, ExprKind, Literal,
+    PolicyID, PolicySet, PrincipalOrResourceConstraint, UnaryOp, Var,
+};
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+use smol_str::{format_smolstr, SmolStr, ToSmolStr};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+/// How confident [`infer_schema()`] is in a declaration of the schema it
+/// inferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// The declaration is a guess, and most likely needs to be corrected
+    Low,
+    /// The declaration is consistent with the policies, but they don't
+    /// determine it, so it may be incomplete
+    Medium,
+    /// The declaration is determined by the policies
+    High,
+}
+
+impl Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// A note on a declaration in an [`InferredSchema`], explaining how it was
+/// inferred and how confident the inference is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferenceNote {
+    declaration: SmolStr,
+    confidence: Confidence,
+    reason: SmolStr,
+}
+
+impl InferenceNote {
+    /// The declaration this note is about, e.g., `User`, `User.name`, or
+    /// `Action::"view"`. Context attributes are written like
+    /// `Action::"view".context.ip`.
+    pub fn declaration(&self) -> &str {
+        &self.declaration
+    }
+
+    /// How confident the inference is in the declaration
+    pub fn confidence(&self) -> Confidence {
+        self.confidence
+    }
+
+    /// Why the declaration was inferred as it was
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for InferenceNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} confidence): {}",
+            self.declaration, self.confidence, self.reason
+        )
+    }
+}
+
+/// A draft schema inferred from a policy set by [`infer_schema()`], together
+/// with notes on how confident the inference is in each declaration
+#[derive(Debug, Clone)]
+pub struct InferredSchema {
+    fragment: json_schema::Fragment<RawName>,
+    notes: Vec<InferenceNote>,
+}
+
+impl InferredSchema {
+    /// The inferred schema
+    pub fn fragment(&self) -> &json_schema::Fragment<RawName> {
+        &self.fragment
+    }
+
+    /// The notes on the declarations of the inferred schema, in the order the
+    /// declarations appear in the schema, followed by notes on attribute
+    /// accesses which could not be attributed to any entity type
+    pub fn notes(&self) -> impl Iterator<Item = &InferenceNote> {
+        self.notes.iter()
+    }
+
+    /// Split into the inferred schema and the notes on its declarations
+    pub fn into_parts(self) -> (json_schema::Fragment<RawName>, Vec<InferenceNote>) {
+        (self.fragment, self.notes)
+    }
+}
+
+/// Infer a draft schema declaring the entity types, attributes, and actions
+/// the policies in `policies` refer to.
+///
+/// Entity types and actions are declared when a policy mentions them.
+/// The principal and resource types each action applies to are taken from the
+/// scopes of the policies for that action. Attributes, of entity types and of
+/// the context, are declared when a policy accesses them, with a type
+/// approximated from how the policies use them, e.g., an attribute compared
+/// with a string literal is a `String`. Attributes are optional if a policy
+/// checks for them with `has`, and required otherwise. Parents of entity types
+/// are inferred from `in` expressions.
+///
+/// The policies rarely determine the whole schema, so the result is a draft to
+/// be reviewed, guided by the returned [`InferenceNote`]s.
+pub fn infer_schema(policies: &PolicySet) -> InferredSchema {
+    let mut inference = Inference::default();
+    // Templates are analyzed with their slots unfilled. Template-linked
+    // policies contribute the types of the entities their slots are filled
+    // with.
+    let mut scopes = Vec::new();
+    for p in policies.policies() {
+        scopes.push(Scope::new(
+            &mut inference,
+            p.id(),
+            p.principal_constraint().as_inner(),
+            p.action_constraint(),
+            p.resource_constraint().as_inner(),
+            p.non_scope_constraints(),
+        ));
+    }
+    for t in policies.templates() {
+        scopes.push(Scope::new(
+            &mut inference,
+            t.id(),
+            t.principal_constraint().as_inner(),
+            t.action_constraint(),
+            t.resource_constraint().as_inner(),
+            t.non_scope_constraints(),
+        ));
+    }
+    inference.infer(scopes)
+}
+
+/// The approximate type of an attribute or expression
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum Approx {
+    /// Nothing is known about the type
+    #[default]
+    Unknown,
+    Bool,
+    Long,
+    String,
+    /// An entity of one of these types. This is empty if the entity type is
+    /// not known.
+    Entity(BTreeSet<EntityType>),
+    Set(Box<Self>),
+    Record(BTreeMap<SmolStr, AttrUse>),
+    Extension(&'static str),
+}
+
+impl Approx {
+    /// Refine this type with what `other` tells us about it, or return `other`
+    /// if the two are incompatible
+    fn merge(&mut self, other: Self) -> Result<(), Self> {
+        match (&mut *self, other) {
+            (_, Self::Unknown) => Ok(()),
+            (Self::Unknown, other) => {
+                *self = other;
+                Ok(())
+            }
+            (Self::Entity(types), Self::Entity(other)) => {
+                types.extend(other);
+                Ok(())
+            }
+            (Self::Set(element), Self::Set(other)) => element
+                .merge(*other)
+                .map_err(|other| Self::Set(Box::new(other))),
+            (Self::Record(attrs), Self::Record(other)) => {
+                for (attr, attr_use) in other {
+                    attrs.entry(attr).or_default().merge(attr_use);
+                }
+                Ok(())
+            }
+            (this, other) if *this == other => Ok(()),
+            (_, other) => Err(other),
+        }
+    }
+
+    /// A description of the type for notes on conflicting uses
+    fn describe(&self) -> SmolStr {
+        match self {
+            Self::Unknown => "an unknown type".into(),
+            Self::Bool => "`Bool`".into(),
+            Self::Long => "`Long`".into(),
+            Self::String => "`String`".into(),
+            Self::Entity(types) if types.is_empty() => "an entity".into(),
+            Self::Entity(types) => format_smolstr!("`{}`", types.iter().join(" | ")),
+            Self::Set(_) => "a set".into(),
+            Self::Record(_) => "a record".into(),
+            Self::Extension(name) => format_smolstr!("`{name}`"),
+        }
+    }
+
+    /// The JSON schema syntax for this type. Types which are not known are
+    /// declared as `String`.
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Bool => json!({ "type": "Boolean" }),
+            Self::Long => json!({ "type": "Long" }),
+            Self::Entity(types) if !types.is_empty() => match types.iter().exactly_one() {
+                Ok(ty) => json!({ "type": "Entity", "name": ty.to_string() }),
+                Err(types) => json!({
+                    "type": "Union",
+                    "members": types
+                        .map(|ty| json!({ "type": "Entity", "name": ty.to_string() }))
+                        .collect_vec(),
+                }),
+            },
+            Self::Set(element) => json!({ "type": "Set", "element": element.to_json() }),
+            Self::Record(attrs) => record_json(attrs),
+            Self::Extension(name) => json!({ "type": "Extension", "name": name }),
+            Self::Unknown | Self::String | Self::Entity(_) => json!({ "type": "String" }),
+        }
+    }
+}
+
+/// The JSON schema syntax for the entity types and actions of a namespace
+type NamespaceJson = (Map<String, Value>, Map<String, Value>);
+
+/// The JSON schema syntax for a record type with the attributes `attrs`
+fn record_json(attrs: &BTreeMap<SmolStr, AttrUse>) -> Value {
+    let attributes: Map<String, Value> = attrs
+        .iter()
+        .map(|(attr, attr_use)| {
+            let mut ty = attr_use.ty.to_json();
+            if let (true, Some(ty)) = (attr_use.optional, ty.as_object_mut()) {
+                ty.insert("required".into(), json!(false));
+            }
+            (attr.to_string(), ty)
+        })
+        .collect();
+    json!({ "type": "Record", "attributes": attributes })
+}
+
+/// What the policies tell us about an attribute
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AttrUse {
+    ty: Approx,
+    /// A policy checks for the attribute with `has`
+    optional: bool,
+    /// Descriptions of the types the attribute is used as, other than `ty`
+    conflicts: BTreeSet<SmolStr>,
+}
+
+impl AttrUse {
+    fn merge(&mut self, other: Self) {
+        self.optional |= other.optional;
+        self.conflicts.extend(other.conflicts);
+        if let Err(conflict) = self.ty.merge(other.ty) {
+            self.conflicts.insert(conflict.describe());
+        }
+    }
+
+    /// Record the use `attr_use` of the attribute at `path` in `attrs`. If
+    /// the path goes through an attribute which is an entity, the rest of the
+    /// path is returned together with the entity types, since it refers to
+    /// attributes of those types instead.
+    fn insert<'a>(
+        attrs: &mut BTreeMap<SmolStr, Self>,
+        path: &'a [SmolStr],
+        attr_use: Self,
+    ) -> Option<(BTreeSet<EntityType>, &'a [SmolStr])> {
+        let (attr, rest) = path.split_first()?;
+        let entry = attrs.entry(attr.clone()).or_default();
+        if rest.is_empty() {
+            entry.merge(attr_use);
+            return None;
+        }
+        match &mut entry.ty {
+            Approx::Entity(types) if !types.is_empty() => return Some((types.clone(), rest)),
+            ty @ Approx::Unknown => *ty = Approx::Record(BTreeMap::new()),
+            _ => {}
+        }
+        match &mut entry.ty {
+            Approx::Record(attrs) => Self::insert(attrs, rest, attr_use),
+            _ => {
+                entry.conflicts.insert("a record".into());
+                None
+            }
+        }
+    }
+}
+
+/// What the policies tell us about an entity type
+#[derive(Debug, Default)]
+struct EntityDecl {
+    attrs: BTreeMap<SmolStr, AttrUse>,
+    member_of: BTreeSet<EntityType>,
+    /// The first policy mentioning the entity type
+    mentioned_in: Option<PolicyID>,
+}
+
+/// What the policies tell us about an action
+#[derive(Debug, Default)]
+struct ActionDecl {
+    principals: BTreeSet<EntityType>,
+    resources: BTreeSet<EntityType>,
+    context: BTreeMap<SmolStr, AttrUse>,
+    /// Some of the principal or resource types come from policies which also
+    /// apply to other actions, so they may not apply to this one
+    shared: bool,
+}
+
+/// Where an attribute access starts
+#[derive(Debug, Clone)]
+enum Root {
+    Principal,
+    Resource,
+    Context,
+    Entity(EntityType),
+}
+
+impl Display for Root {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Principal => write!(f, "principal"),
+            Self::Resource => write!(f, "resource"),
+            Self::Context => write!(f, "context"),
+            Self::Entity(ty) => write!(f, "{ty}"),
+        }
+    }
+}
+
+/// Where an attribute is declared
+#[derive(Debug, Clone)]
+enum Target {
+    Entity(EntityType),
+    Context(EntityUID),
+}
+
+/// The use of the attribute at the end of `path` of something starting at
+/// `root`, found in a policy
+#[derive(Debug)]
+struct Hint {
+    policy: PolicyID,
+    root: Root,
+    targets: Vec<Target>,
+    path: Vec<SmolStr>,
+    attr_use: AttrUse,
+}
+
+/// What the scope of a policy tells us
+#[derive(Debug)]
+struct Scope<'a> {
+    id: &'a PolicyID,
+    principals: BTreeSet<EntityType>,
+    principal_ancestor: Option<EntityType>,
+    /// `None` if the policy applies to all actions
+    actions: Option<Vec<EntityUID>>,
+    resources: BTreeSet<EntityType>,
+    resource_ancestor: Option<EntityType>,
+    condition: Option<&'a Expr>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(
+        inference: &mut Inference,
+        id: &'a PolicyID,
+        principal: &PrincipalOrResourceConstraint,
+        action: &ActionConstraint,
+        resource: &PrincipalOrResourceConstraint,
+        condition: Option<&'a Expr>,
+    ) -> Self {
+        let (principals, principal_ancestor) = scope_types(principal);
+        let (resources, resource_ancestor) = scope_types(resource);
+        let actions = match action {
+            ActionConstraint::Any => None,
+            ActionConstraint::Eq(action) => Some(vec![action.as_ref().clone()]),
+            ActionConstraint::In(actions) => Some(
+                actions
+                    .iter()
+                    .map(|action| action.as_ref().clone())
+                    .collect(),
+            ),
+            #[cfg(feature = "tolerant-ast")]
+            ActionConstraint::ErrorConstraint => None,
+        };
+        for ty in principals
+            .iter()
+            .chain(&resources)
+            .chain(&principal_ancestor)
+            .chain(&resource_ancestor)
+        {
+            inference.mention_entity_type(ty, id);
+        }
+        for action in actions.iter().flatten() {
+            inference.actions.entry(action.clone()).or_default();
+        }
+        if let Some(condition) = condition {
+            for sub in condition.subexpressions() {
+                match sub.expr_kind() {
+                    ExprKind::Lit(Literal::EntityUID(uid)) if uid.is_action() => {
+                        inference.actions.entry(uid.as_ref().clone()).or_default();
+                    }
+                    ExprKind::Lit(Literal::EntityUID(uid)) => {
+                        inference.mention_entity_type(uid.entity_type(), id);
+                    }
+                    ExprKind::Is { entity_type, .. } => {
+                        inference.mention_entity_type(entity_type, id);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Self {
+            id,
+            principals,
+            principal_ancestor,
+            actions,
+            resources,
+            resource_ancestor,
+            condition,
+        }
+    }
+}
+
+/// The types a principal or resource scope constraint allows, and the type of
+/// the ancestor it requires, if any
+fn scope_types(
+    constraint: &PrincipalOrResourceConstraint,
+) -> (BTreeSet<EntityType>, Option<EntityType>) {
+    let euid_type = |reference: &EntityReference| match reference {
+        EntityReference::EUID(euid) => Some(euid.entity_type().clone()),
+        EntityReference::Slot(_) => None,
+    };
+    match constraint {
+        PrincipalOrResourceConstraint::Any => (BTreeSet::new(), None),
+        PrincipalOrResourceConstraint::Eq(reference) => {
+            (euid_type(reference).into_iter().collect(), None)
+        }
+        PrincipalOrResourceConstraint::In(reference) => (BTreeSet::new(), euid_type(reference)),
+        PrincipalOrResourceConstraint::Is(ty) => (BTreeSet::from([ty.as_ref().clone()]), None),
+        PrincipalOrResourceConstraint::IsIn(ty, reference) => {
+            (BTreeSet::from([ty.as_ref().clone()]), euid_type(reference))
+        }
+    }
+}
+
+/// The argument types of extension functions, by function name. These are
+/// extension type names, `String` for the methods of the `strings` extension,
+/// or `Record` for the record traversed by `lookup`.
+fn extension_arg_types(name: &str) -> Option<&'static [&'static str]> {
+    Some(match name {
+        "isIpv4" | "isIpv6" | "isLoopback" | "isMulticast" => &["ipaddr"],
+        "isInRange" => &["ipaddr", "ipaddr"],
+        "isInSet" => &["ipaddr", "ipset"],
+        "lessThan" | "lessThanOrEqual" | "greaterThan" | "greaterThanOrEqual" | "add"
+        | "subtract" | "multiply" => &["decimal", "decimal"],
+        "offset" | "dayOfWeek" | "hour" => &["datetime", "duration"],
+        "durationSince" => &["datetime", "datetime"],
+        "toDate" | "toTime" => &["datetime"],
+        "toDays" | "toHours" | "toMinutes" | "toSeconds" | "toMilliseconds" => &["duration"],
+        "toLowerCase" | "length" => &["String"],
+        "startsWith" | "endsWith" | "split" => &["String", "String"],
+        "byteLength" => &["bytes"],
+        "lookup" => &["Record", "String"],
+        "isPresent" | "asString" | "asLong" | "asBool" => &["optional"],
+        "distanceTo" => &["geopoint", "geopoint"],
+        "isInPolygon" => &["geopoint", "geopolygon"],
+        "uuidVersion" => &["uuid"],
+        "timestampMillis" => &["ulid"],
+        "matches" => &["mediatype", "String"],
+        "essence" => &["mediatype"],
+        _ => return None,
+    })
+}
+
+/// The type of the value returned by an extension function, by function name
+fn extension_return_type(name: &str) -> Approx {
+    match name {
+        "ip" => Approx::Extension("ipaddr"),
+        "ipset" => Approx::Extension("ipset"),
+        "bytes" => Approx::Extension("bytes"),
+        "lookup" => Approx::Extension("optional"),
+        "geopoint" => Approx::Extension("geopoint"),
+        "geopolygon" => Approx::Extension("geopolygon"),
+        "uuid" => Approx::Extension("uuid"),
+        "ulid" => Approx::Extension("ulid"),
+        "mediatype" => Approx::Extension("mediatype"),
+        "decimal" | "add" | "subtract" | "multiply" => Approx::Extension("decimal"),
+        "datetime" | "offset" | "toDate" => Approx::Extension("datetime"),
+        "duration" | "durationSince" | "toTime" => Approx::Extension("duration"),
+        "toDays" | "toHours" | "toMinutes" | "toSeconds" | "toMilliseconds" | "dayOfWeek"
+        | "hour" | "length" | "byteLength" | "asLong" | "distanceTo" | "uuidVersion"
+        | "timestampMillis" => Approx::Long,
+        "toLowerCase" | "asString" | "essence" => Approx::String,
+        "split" => Approx::Set(Box::new(Approx::String)),
+        _ => match extension_arg_types(name) {
+            Some(_) => Approx::Bool,
+            None => Approx::Unknown,
+        },
+    }
+}
+
+/// The root and attribute path of an attribute access like
+/// `principal.address.street`, or of a variable with an empty path
+fn access_path(e: &Expr) -> Option<(Root, Vec<SmolStr>)> {
+    match e.expr_kind() {
+        ExprKind::Var(Var::Principal) => Some((Root::Principal, Vec::new())),
+        ExprKind::Var(Var::Resource) => Some((Root::Resource, Vec::new())),
+        ExprKind::Var(Var::Context) => Some((Root::Context, Vec::new())),
+        ExprKind::Lit(Literal::EntityUID(uid)) if !uid.is_action() => {
+            Some((Root::Entity(uid.entity_type().clone()), Vec::new()))
+        }
+        ExprKind::GetAttr { expr, attr } => {
+            let (root, mut path) = access_path(expr)?;
+            path.push(attr.clone());
+            Some((root, path))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inference {
+    entities: BTreeMap<EntityType, EntityDecl>,
+    actions: BTreeMap<EntityUID, ActionDecl>,
+    hints: Vec<Hint>,
+    notes: Vec<InferenceNote>,
+}
+
+impl Inference {
+    fn mention_entity_type(&mut self, ty: &EntityType, policy: &PolicyID) {
+        if !ty.is_action() {
+            self.entities
+                .entry(ty.clone())
+                .or_default()
+                .mentioned_in
+                .get_or_insert_with(|| policy.clone());
+        }
+    }
+
+    fn infer(mut self, mut scopes: Vec<Scope<'_>>) -> InferredSchema {
+        // The actions each policy applies to determine their `appliesTo`
+        for scope in &scopes {
+            let from_unscoped = scope.actions.is_none();
+            let actions = match &scope.actions {
+                Some(actions) => actions.clone(),
+                None => self.actions.keys().cloned().collect(),
+            };
+            for action in actions {
+                let decl = self.actions.entry(action).or_default();
+                decl.principals.extend(scope.principals.iter().cloned());
+                decl.resources.extend(scope.resources.iter().cloned());
+                decl.shared |=
+                    from_unscoped && !(scope.principals.is_empty() && scope.resources.is_empty());
+            }
+        }
+        // and policies which don't constrain the principal or resource type
+        // apply to those of the actions they apply to, which in turn apply
+        // to those types if nothing else determines theirs
+        for scope in &mut scopes {
+            let mut actions = self
+                .actions
+                .iter_mut()
+                .filter(|(action, _)| {
+                    scope
+                        .actions
+                        .as_ref()
+                        .is_none_or(|actions| actions.contains(action))
+                })
+                .map(|(_, decl)| decl)
+                .collect_vec();
+            if scope.principals.is_empty() {
+                scope.principals = actions
+                    .iter()
+                    .flat_map(|decl| decl.principals.iter().cloned())
+                    .collect();
+            }
+            if scope.resources.is_empty() {
+                scope.resources = actions
+                    .iter()
+                    .flat_map(|decl| decl.resources.iter().cloned())
+                    .collect();
+            }
+            for decl in &mut actions {
+                if decl.principals.is_empty() && !scope.principals.is_empty() {
+                    decl.principals.clone_from(&scope.principals);
+                    decl.shared = true;
+                }
+                if decl.resources.is_empty() && !scope.resources.is_empty() {
+                    decl.resources.clone_from(&scope.resources);
+                    decl.shared = true;
+                }
+            }
+        }
+
+        for scope in &scopes {
+            for (types, ancestor) in [
+                (&scope.principals, &scope.principal_ancestor),
+                (&scope.resources, &scope.resource_ancestor),
+            ] {
+                if let Some(ancestor) = ancestor {
+                    self.add_parent(types, ancestor);
+                }
+            }
+            if let Some(condition) = scope.condition {
+                for sub in condition.subexpressions() {
+                    self.analyze(scope, sub);
+                }
+            }
+        }
+
+        // Apply the hints for shorter paths first, so that we know which
+        // attributes are entities before we see accesses through them
+        let mut hints = std::mem::take(&mut self.hints);
+        hints.sort_by_key(|hint| hint.path.len());
+        for hint in hints {
+            if hint.targets.is_empty() {
+                let note = InferenceNote {
+                    declaration: format_smolstr!("{}.{}", hint.root, hint.path.join(".")),
+                    confidence: Confidence::Low,
+                    reason: format_smolstr!(
+                        "accessed in policy `{}`, but the {} type can't be determined, so the attribute is not declared",
+                        hint.policy,
+                        hint.root,
+                    ),
+                };
+                if !self.notes.contains(&note) {
+                    self.notes.push(note);
+                }
+            }
+            for target in hint.targets {
+                self.record(target, &hint.path, &hint.attr_use);
+            }
+        }
+        self.into_schema()
+    }
+
+    /// Record that entities of the types `children` may have a parent of type
+    /// `parent`
+    fn add_parent(&mut self, children: &BTreeSet<EntityType>, parent: &EntityType) {
+        if parent.is_action() {
+            return;
+        }
+        for child in children {
+            if child != parent && !child.is_action() {
+                self.entities
+                    .entry(child.clone())
+                    .or_default()
+                    .member_of
+                    .insert(parent.clone());
+            }
+        }
+    }
+
+    fn record(&mut self, target: Target, path: &[SmolStr], attr_use: &AttrUse) {
+        let attrs = match target {
+            Target::Entity(ty) => &mut self.entities.entry(ty).or_default().attrs,
+            Target::Context(action) => &mut self.actions.entry(action).or_default().context,
+        };
+        if let Some((types, rest)) = AttrUse::insert(attrs, path, attr_use.clone()) {
+            for ty in types {
+                self.record(Target::Entity(ty), rest, attr_use);
+            }
+        }
+    }
+
+    /// The approximate type of `e` in a policy with scope `scope`
+    fn type_of(&self, scope: &Scope<'_>, e: &Expr) -> Approx {
+        match e.expr_kind() {
+            ExprKind::Lit(Literal::Bool(_)) => Approx::Bool,
+            ExprKind::Lit(Literal::Long(_)) => Approx::Long,
+            ExprKind::Lit(Literal::String(_)) => Approx::String,
+            ExprKind::Lit(Literal::EntityUID(uid)) if !uid.is_action() => {
+                Approx::Entity(BTreeSet::from([uid.entity_type().clone()]))
+            }
+            ExprKind::Var(Var::Principal) => Approx::Entity(scope.principals.clone()),
+            ExprKind::Var(Var::Resource) => Approx::Entity(scope.resources.clone()),
+            ExprKind::Set(elements) => {
+                let mut element = Approx::Unknown;
+                for e in elements.iter() {
+                    let _ = element.merge(self.type_of(scope, e));
+                }
+                Approx::Set(Box::new(element))
+            }
+            ExprKind::Record(attrs) => Approx::Record(
+                attrs
+                    .iter()
+                    .map(|(attr, e)| {
+                        (
+                            attr.clone(),
+                            AttrUse {
+                                ty: self.type_of(scope, e),
+                                ..AttrUse::default()
+                            },
+                        )
+                    })
+                    .collect(),
+            ),
+            ExprKind::ExtensionFunctionApp { fn_name, .. } => {
+                extension_return_type(&fn_name.to_smolstr())
+            }
+            ExprKind::UnaryApp { op, .. } => match op {
+                UnaryOp::Not | UnaryOp::IsEmpty => Approx::Bool,
+                UnaryOp::Neg => Approx::Long,
+            },
+            ExprKind::BinaryApp { op, .. } => match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => Approx::Long,
+                BinaryOp::GetTag => Approx::Unknown,
+                #[cfg(feature = "maps")]
+                BinaryOp::GetKey => Approx::Unknown,
+                BinaryOp::Eq
+                | BinaryOp::Less
+                | BinaryOp::LessEq
+                | BinaryOp::In
+                | BinaryOp::Contains
+                | BinaryOp::ContainsAll
+                | BinaryOp::ContainsAny
+                | BinaryOp::HasTag => Approx::Bool,
+                #[cfg(feature = "maps")]
+                BinaryOp::ContainsKey => Approx::Bool,
+            },
+            ExprKind::And { .. }
+            | ExprKind::Or { .. }
+            | ExprKind::HasAttr { .. }
+            | ExprKind::Like { .. }
+            | ExprKind::Is { .. } => Approx::Bool,
+            ExprKind::If {
+                then_expr,
+                else_expr,
+                ..
+            } => {
+                let mut ty = self.type_of(scope, then_expr);
+                let _ = ty.merge(self.type_of(scope, else_expr));
+                ty
+            }
+            _ => Approx::Unknown,
+        }
+    }
+
+    /// Record that `e` is used as a value of type `ty`, if it is an attribute
+    fn hint(&mut self, scope: &Scope<'_>, e: &Expr, ty: Approx) {
+        if let Some((root, path)) = access_path(e) {
+            self.hint_path(
+                scope,
+                root,
+                path,
+                AttrUse {
+                    ty,
+                    ..AttrUse::default()
+                },
+            );
+        }
+    }
+
+    /// Record the use `attr_use` of the attribute at the end of `path`,
+    /// starting from `root`
+    fn hint_path(&mut self, scope: &Scope<'_>, root: Root, path: Vec<SmolStr>, attr_use: AttrUse) {
+        if path.is_empty() {
+            return;
+        }
+        let targets = match &root {
+            Root::Principal => scope
+                .principals
+                .iter()
+                .cloned()
+                .map(Target::Entity)
+                .collect(),
+            Root::Resource => scope
+                .resources
+                .iter()
+                .cloned()
+                .map(Target::Entity)
+                .collect(),
+            Root::Entity(ty) => vec![Target::Entity(ty.clone())],
+            Root::Context => match &scope.actions {
+                Some(actions) => actions.iter().cloned().map(Target::Context).collect(),
+                None => self.actions.keys().cloned().map(Target::Context).collect(),
+            },
+        };
+        self.hints.push(Hint {
+            policy: scope.id.clone(),
+            root,
+            targets,
+            path,
+            attr_use,
+        });
+    }
+
+    /// Record what the expression `e` (but not its subexpressions) tells us
+    /// about the attributes it uses
+    fn analyze(&mut self, scope: &Scope<'_>, e: &Expr) {
+        match e.expr_kind() {
+            ExprKind::If { test_expr, .. } => self.hint(scope, test_expr, Approx::Bool),
+            ExprKind::And { left, right } | ExprKind::Or { left, right } => {
+                self.hint(scope, left, Approx::Bool);
+                self.hint(scope, right, Approx::Bool);
+            }
+            ExprKind::UnaryApp { op, arg } => {
+                let ty = match op {
+                    UnaryOp::Not => Approx::Bool,
+                    UnaryOp::Neg => Approx::Long,
+                    UnaryOp::IsEmpty => Approx::Set(Box::default()),
+                };
+                self.hint(scope, arg, ty);
+            }
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                self.analyze_binary_app(scope, *op, arg1, arg2);
+            }
+            ExprKind::ExtensionFunctionApp { fn_name, args } => {
+                if let Some(arg_types) = extension_arg_types(&fn_name.to_smolstr()) {
+                    for (arg, ty) in args.iter().zip(arg_types) {
+                        let ty = match *ty {
+                            "String" => Approx::String,
+                            "Record" => Approx::Record(BTreeMap::new()),
+                            ty => Approx::Extension(ty),
+                        };
+                        self.hint(scope, arg, ty);
+                    }
+                }
+            }
+            ExprKind::GetAttr { .. } => self.hint(scope, e, Approx::Unknown),
+            ExprKind::HasAttr { expr, attr } => {
+                if let Some((root, mut path)) = access_path(expr) {
+                    path.push(attr.clone());
+                    self.hint_path(
+                        scope,
+                        root,
+                        path,
+                        AttrUse {
+                            optional: true,
+                            ..AttrUse::default()
+                        },
+                    );
+                }
+            }
+            ExprKind::Like { expr, .. } => self.hint(scope, expr, Approx::String),
+            ExprKind::Is { expr, entity_type } => self.hint(
+                scope,
+                expr,
+                Approx::Entity(BTreeSet::from([entity_type.clone()])),
+            ),
+            _ => {}
+        }
+    }
+
+    fn analyze_binary_app(&mut self, scope: &Scope<'_>, op: BinaryOp, arg1: &Expr, arg2: &Expr) {
+        match op {
+            BinaryOp::Eq => {
+                self.hint(scope, arg1, self.type_of(scope, arg2));
+                self.hint(scope, arg2, self.type_of(scope, arg1));
+            }
+            BinaryOp::Less | BinaryOp::LessEq => {
+                // `datetime`s, `duration`s, and `ulid`s can also be compared
+                let mut ty = self.type_of(scope, arg1);
+                let _ = ty.merge(self.type_of(scope, arg2));
+                if ty == Approx::Unknown {
+                    ty = Approx::Long;
+                }
+                self.hint(scope, arg1, ty.clone());
+                self.hint(scope, arg2, ty);
+            }
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                self.hint(scope, arg1, Approx::Long);
+                self.hint(scope, arg2, Approx::Long);
+            }
+            BinaryOp::In => {
+                self.hint(scope, arg1, Approx::Entity(BTreeSet::new()));
+                let Approx::Entity(children) = self.type_of(scope, arg1) else {
+                    return;
+                };
+                let parents = match self.type_of(scope, arg2) {
+                    Approx::Entity(parents) => parents,
+                    Approx::Set(element) => match *element {
+                        Approx::Entity(parents) => parents,
+                        _ => BTreeSet::new(),
+                    },
+                    _ => BTreeSet::new(),
+                };
+                for parent in &parents {
+                    self.add_parent(&children, parent);
+                }
+            }
+            BinaryOp::Contains => {
+                self.hint(
+                    scope,
+                    arg1,
+                    Approx::Set(Box::new(self.type_of(scope, arg2))),
+                );
+            }
+            BinaryOp::ContainsAll | BinaryOp::ContainsAny => {
+                for (set, other) in [(arg1, arg2), (arg2, arg1)] {
+                    let ty = match self.type_of(scope, other) {
+                        ty @ Approx::Set(_) => ty,
+                        _ => Approx::Set(Box::default()),
+                    };
+                    self.hint(scope, set, ty);
+                }
+            }
+            BinaryOp::GetTag | BinaryOp::HasTag => {}
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey | BinaryOp::ContainsKey => {}
+        }
+    }
+
+    fn into_schema(mut self) -> InferredSchema {
+        let mut namespaces: BTreeMap<String, NamespaceJson> = BTreeMap::new();
+        let mut notes = Vec::new();
+
+        for (ty, decl) in &self.entities {
+            let mut json = Map::new();
+            if !decl.member_of.is_empty() {
+                json.insert(
+                    "memberOfTypes".into(),
+                    json!(decl.member_of.iter().map(ToString::to_string).collect_vec()),
+                );
+            }
+            if !decl.attrs.is_empty() {
+                json.insert("shape".into(), record_json(&decl.attrs));
+            }
+            namespaces
+                .entry(ty.name().as_ref().namespace())
+                .or_default()
+                .0
+                .insert(ty.name().basename().to_string(), Value::Object(json));
+
+            let mentioned = match &decl.mentioned_in {
+                Some(policy) => format_smolstr!("mentioned in policy `{policy}`"),
+                None => "the type of an attribute".into(),
+            };
+            notes.push(if decl.member_of.is_empty() {
+                InferenceNote {
+                    declaration: ty.to_smolstr(),
+                    confidence: Confidence::High,
+                    reason: mentioned,
+                }
+            } else {
+                InferenceNote {
+                    declaration: ty.to_smolstr(),
+                    confidence: Confidence::Medium,
+                    reason: format_smolstr!(
+                        "{mentioned}; its parent types are inferred from `in` expressions and may be incomplete"
+                    ),
+                }
+            });
+            attr_notes(&ty.to_smolstr(), &decl.attrs, &mut notes);
+        }
+
+        for (action, decl) in &self.actions {
+            let mut json = Map::new();
+            let declaration = action.to_smolstr();
+            if decl.principals.is_empty() || decl.resources.is_empty() {
+                notes.push(InferenceNote {
+                    declaration,
+                    confidence: Confidence::Low,
+                    reason: "no policy determines the principal and resource types it applies to, so it has no `appliesTo`".into(),
+                });
+            } else {
+                let mut applies_to = Map::new();
+                applies_to.insert(
+                    "principalTypes".into(),
+                    json!(decl
+                        .principals
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect_vec()),
+                );
+                applies_to.insert(
+                    "resourceTypes".into(),
+                    json!(decl.resources.iter().map(ToString::to_string).collect_vec()),
+                );
+                if !decl.context.is_empty() {
+                    applies_to.insert("context".into(), record_json(&decl.context));
+                }
+                json.insert("appliesTo".into(), Value::Object(applies_to));
+                notes.push(if decl.shared {
+                    InferenceNote {
+                        declaration: declaration.clone(),
+                        confidence: Confidence::Medium,
+                        reason: "some of the principal and resource types it applies to come from policies which also apply to other actions, so they may not apply to it".into(),
+                    }
+                } else {
+                    InferenceNote {
+                        declaration: declaration.clone(),
+                        confidence: Confidence::High,
+                        reason: "the principal and resource types it applies to come from the scopes of the policies for it".into(),
+                    }
+                });
+                attr_notes(
+                    &format_smolstr!("{declaration}.context"),
+                    &decl.context,
+                    &mut notes,
+                );
+            }
+            namespaces
+                .entry(action.entity_type().name().as_ref().namespace())
+                .or_default()
+                .1
+                .insert(action.eid().as_ref().to_string(), Value::Object(json));
+        }
+
+        let json: Map<String, Value> = namespaces
+            .into_iter()
+            .map(|(ns, (entity_types, actions))| {
+                (
+                    ns,
+                    json!({ "entityTypes": entity_types, "actions": actions }),
+                )
+            })
+            .collect();
+        notes.append(&mut self.notes);
+        #[expect(
+            clippy::expect_used,
+            reason = "the JSON is constructed from valid names, so it is a valid schema"
+        )]
+        let fragment = json_schema::Fragment::from_json_value(Value::Object(json))
+            .expect("inferred schema should be a valid JSON schema");
+        InferredSchema { fragment, notes }
+    }
+}
+
+/// Add the notes on the attributes `attrs` of `owner`, and their nested
+/// attributes, to `notes`
+fn attr_notes(owner: &str, attrs: &BTreeMap<SmolStr, AttrUse>, notes: &mut Vec<InferenceNote>) {
+    for (attr, attr_use) in attrs {
+        let declaration = format_smolstr!("{owner}.{attr}");
+        let presence = if attr_use.optional {
+            "optional because a policy checks for it with `has`"
+        } else {
+            "required because no policy checks for it with `has`"
+        };
+        let (confidence, reason) = if !attr_use.conflicts.is_empty() {
+            (
+                Confidence::Low,
+                format_smolstr!(
+                    "used as {} and as {}, so it is declared as the former; {presence}",
+                    attr_use.ty.describe(),
+                    attr_use.conflicts.iter().join(" and as "),
+                ),
+            )
+        } else {
+            match &attr_use.ty {
+                Approx::Unknown => (
+                    Confidence::Low,
+                    format_smolstr!("its type can't be determined from how policies use it, so it is declared as a `String`; {presence}"),
+                ),
+                Approx::Entity(types) if types.is_empty() => (
+                    Confidence::Low,
+                    format_smolstr!("it is an entity of a type that can't be determined, so it is declared as a `String`; {presence}"),
+                ),
+                Approx::Record(_) => (
+                    Confidence::Medium,
+                    format_smolstr!("a record with the attributes policies access, which may be incomplete; {presence}"),
+                ),
+                _ => (
+                    Confidence::Medium,
+                    format_smolstr!("its type is inferred from how policies use it; {presence}"),
+                ),
+            }
+        };
+        notes.push(InferenceNote {
+            declaration: declaration.clone(),
+            confidence,
+            reason,
+        });
+        if let Approx::Record(attrs) = &attr_use.ty {
+            attr_notes(&declaration, attrs, notes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse_policyset;
+    use similar_asserts::assert_eq;
+
+    fn infer(policies: &str) -> InferredSchema {
+        infer_schema(&parse_policyset(policies).expect("policies should parse"))
+    }
+
+    fn notes(inferred: &InferredSchema) -> Vec<String> {
+        inferred.notes().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn infers_declarations() {
+        let inferred = infer(
+            r#"
+            permit(principal == User::"alice", action == Action::"view", resource is Photo)
+            when { resource.owner == principal && !resource.private && context.ip.isInRange(ip("10.0.0.0/8")) };
+            permit(principal in Group::"admins", action in [Action::"view", Action::"delete"], resource)
+            when { resource has tags && resource.tags.contains("public") && principal.age >= 18 };
+            "#,
+        );
+        assert_eq!(
+            inferred
+                .fragment()
+                .to_cedarschema()
+                .expect("should convert"),
+            r#"entity Group;
+
+entity Photo = {
+  owner: User,
+  private: __cedar::Bool,
+  tags?: Set<__cedar::String>
+};
+
+entity User in [Group] = {
+  age: __cedar::Long
+};
+
+action "delete" appliesTo {
+  principal: [User],
+  resource: [Photo],
+  context: {}
+};
+
+action "view" appliesTo {
+  principal: [User],
+  resource: [Photo],
+  context: {
+    ip: __cedar::ipaddr
+  }
+};
+"#
+        );
+        assert_eq!(
+            notes(&inferred),
+            [
+                "Group (high confidence): mentioned in policy `policy1`",
+                "Photo (high confidence): mentioned in policy `policy0`",
+                "Photo.owner (medium confidence): its type is inferred from how policies use it; required because no policy checks for it with `has`",
+                "Photo.private (medium confidence): its type is inferred from how policies use it; required because no policy checks for it with `has`",
+                "Photo.tags (medium confidence): its type is inferred from how policies use it; optional because a policy checks for it with `has`",
+                "User (medium confidence): mentioned in policy `policy0`; its parent types are inferred from `in` expressions and may be incomplete",
+                "User.age (medium confidence): its type is inferred from how policies use it; required because no policy checks for it with `has`",
+                r#"Action::"delete" (medium confidence): some of the principal and resource types it applies to come from policies which also apply to other actions, so they may not apply to it"#,
+                r#"Action::"view" (high confidence): the principal and resource types it applies to come from the scopes of the policies for it"#,
+                r#"Action::"view".context.ip (medium confidence): its type is inferred from how policies use it; required because no policy checks for it with `has`"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes_of_entity_attributes() {
+        let inferred = infer(
+            r#"
+            permit(principal is App::User, action == App::Action::"edit", resource is App::Doc)
+            when {
+                resource.owner == principal &&
+                resource.owner.address.city == "Seattle" &&
+                resource.meta.size < 100
+            };
+            "#,
+        );
+        assert_eq!(
+            inferred
+                .fragment()
+                .to_cedarschema()
+                .expect("should convert"),
+            r#"namespace App {
+  entity Doc = {
+    meta: {
+      size: __cedar::Long
+    },
+    owner: App::User
+  };
+
+  entity User = {
+    address: {
+      city: __cedar::String
+    }
+  };
+
+  action "edit" appliesTo {
+    principal: [App::User],
+    resource: [App::Doc],
+    context: {}
+  };
+}
+"#
+        );
+    }
+
+    #[test]
+    fn low_confidence_notes() {
+        let inferred = infer(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when { principal.level > 2 };
+            permit(principal is User, action == Action::"edit", resource is Doc)
+            when { resource.size == 1 || resource.size == "big" };
+            permit(principal == ?principal, action == Action::"edit", resource)
+            when { principal.manager in resource.owners };
+            "#,
+        );
+        assert_eq!(
+            notes(&inferred),
+            [
+                "Doc (high confidence): mentioned in policy `policy1`",
+                "Doc.owners (low confidence): its type can't be determined from how policies use it, so it is declared as a `String`; required because no policy checks for it with `has`",
+                "Doc.size (low confidence): used as `String` and as `Long`, so it is declared as the former; required because no policy checks for it with `has`",
+                "User (high confidence): mentioned in policy `policy1`",
+                "User.manager (low confidence): it is an entity of a type that can't be determined, so it is declared as a `String`; required because no policy checks for it with `has`",
+                r#"Action::"edit" (high confidence): the principal and resource types it applies to come from the scopes of the policies for it"#,
+                r#"Action::"view" (low confidence): no policy determines the principal and resource types it applies to, so it has no `appliesTo`"#,
+                "principal.level (low confidence): accessed in policy `policy0`, but the principal type can't be determined, so the attribute is not declared",
+            ]
+        );
+    }
+}
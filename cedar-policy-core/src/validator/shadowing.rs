@@ -0,0 +1,303 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Contains the validation pass which finds policies shadowed by other
+//! policies in the same policy set.
+
+use std::collections::HashSet;
+
+use crate::ast::{
+    ActionConstraint, Effect, EntityReference, EntityUID, Expr, ExprKind, Literal, Policy,
+    PolicySet, PrincipalOrResourceConstraint,
+};
+use itertools::Itertools;
+
+use super::{ValidationWarning, Validator};
+
+/// A policy with its scope constraints, with any slots filled
+struct Scope<'a> {
+    policy: &'a Policy,
+    principal: PrincipalOrResourceConstraint,
+    actions: Option<HashSet<&'a EntityUID>>,
+    resource: PrincipalOrResourceConstraint,
+}
+
+impl Validator {
+    /// Find pairs of policies where one policy applies to every request the
+    /// other applies to: a policy shadowed by a broader policy with the same
+    /// effect, or a permit policy nullified by a broader forbid policy.
+    ///
+    /// This only compares the scopes of the policies. A broader policy with a
+    /// condition only covers a narrower policy with the same condition.
+    pub(crate) fn shadowing_checks(&self, policies: &PolicySet) -> Vec<ValidationWarning> {
+        let scopes: Vec<Scope<'_>> = policies
+            .policies()
+            .sorted_by(|p1, p2| p1.id().cmp(p2.id()))
+            .map(|policy| Scope {
+                policy,
+                principal: policy.principal_constraint().into_inner(),
+                actions: match policy.action_constraint() {
+                    ActionConstraint::Any => None,
+                    constraint => {
+                        Some(self.get_actions_satisfying_constraint(constraint).collect())
+                    }
+                },
+                resource: policy.resource_constraint().into_inner(),
+            })
+            .collect();
+        let mut warnings = Vec::new();
+        for narrow in &scopes {
+            for broad in &scopes {
+                if std::ptr::eq(narrow, broad)
+                    || !covers(broad, narrow)
+                    // Report each pair of equivalent policies with the same
+                    // effect once
+                    || (narrow.policy.effect() == broad.policy.effect()
+                        && covers(narrow, broad)
+                        && narrow.policy.id() < broad.policy.id())
+                {
+                    continue;
+                }
+                let scope = format!(
+                    "{}, {}, {}",
+                    narrow.policy.principal_constraint(),
+                    narrow.policy.action_constraint(),
+                    narrow.policy.resource_constraint()
+                );
+                match (broad.policy.effect(), narrow.policy.effect()) {
+                    (Effect::Forbid, Effect::Permit) => {
+                        warnings.push(ValidationWarning::nullified_permit(
+                            narrow.policy.loc().cloned(),
+                            narrow.policy.id().clone(),
+                            broad.policy.id().clone(),
+                            scope,
+                        ));
+                    }
+                    (broad_effect, narrow_effect) if broad_effect == narrow_effect => {
+                        warnings.push(ValidationWarning::shadowed_policy(
+                            narrow.policy.loc().cloned(),
+                            narrow.policy.id().clone(),
+                            broad.policy.id().clone(),
+                            scope,
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Does `broad` apply to every request `narrow` applies to?
+fn covers(broad: &Scope<'_>, narrow: &Scope<'_>) -> bool {
+    let actions_covered = match (&broad.actions, &narrow.actions) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        // A policy which applies to no declared action is reported by other
+        // validation passes
+        (Some(broad), Some(narrow)) => !narrow.is_empty() && narrow.is_subset(broad),
+    };
+    actions_covered
+        && constraint_covers(&broad.principal, &narrow.principal)
+        && constraint_covers(&broad.resource, &narrow.resource)
+        && condition_covers(
+            broad.policy.non_scope_constraints(),
+            narrow.policy.non_scope_constraints(),
+        )
+}
+
+/// Is every entity satisfying the principal or resource constraint `narrow`
+/// guaranteed to satisfy `broad`?
+fn constraint_covers(
+    broad: &PrincipalOrResourceConstraint,
+    narrow: &PrincipalOrResourceConstraint,
+) -> bool {
+    use PrincipalOrResourceConstraint::{Any, Eq, In, Is, IsIn};
+    let same_entity = |e1: &EntityReference, e2: &EntityReference| match (e1, e2) {
+        (EntityReference::EUID(e1), EntityReference::EUID(e2)) => e1 == e2,
+        _ => false,
+    };
+    let has_type = |e: &EntityReference, ty| match e {
+        EntityReference::EUID(e) => e.entity_type() == ty,
+        EntityReference::Slot(_) => false,
+    };
+    match (broad, narrow) {
+        (Any, _) => true,
+        (Eq(e1), Eq(e2)) => same_entity(e1, e2),
+        // An entity is in itself
+        (In(e1), Eq(e2) | In(e2) | IsIn(_, e2)) => same_entity(e1, e2),
+        (Is(ty), Eq(e)) => has_type(e, ty.as_ref()),
+        (Is(ty1), Is(ty2) | IsIn(ty2, _)) => ty1 == ty2,
+        (IsIn(ty, e1), Eq(e2)) => has_type(e2, ty.as_ref()) && same_entity(e1, e2),
+        (IsIn(ty1, e1), IsIn(ty2, e2)) => ty1 == ty2 && same_entity(e1, e2),
+        _ => false,
+    }
+}
+
+/// Is the condition `narrow` guaranteed to imply `broad`? This is only the
+/// case if `broad` is always true, or if the conditions are the same.
+fn condition_covers(broad: Option<&Expr>, narrow: Option<&Expr>) -> bool {
+    match (broad, narrow) {
+        (None, _) => true,
+        (Some(broad), _) if matches!(broad.expr_kind(), ExprKind::Lit(Literal::Bool(true))) => true,
+        (Some(broad), Some(narrow)) => broad.eq_shape(narrow),
+        (Some(_), None) => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        ast::PolicyID,
+        extensions::Extensions,
+        parser::parse_policyset,
+        validator::{ValidationMode, ValidatorConfig, ValidatorSchema},
+    };
+    use cool_asserts::assert_matches;
+
+    fn validator() -> Validator {
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            r#"
+            entity Group;
+            entity User in [Group] { level: Long };
+            entity Photo;
+            action view, edit appliesTo { principal: User, resource: Photo };
+            action manage appliesTo { principal: User, resource: Photo };
+            action "delete" in [manage] appliesTo { principal: User, resource: Photo };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        Validator::with_config(schema, ValidatorConfig::new().with_shadowing_checks())
+    }
+
+    #[track_caller]
+    fn warnings(src: &str) -> Vec<(String, String, String)> {
+        let policies = parse_policyset(src).unwrap();
+        validator()
+            .shadowing_checks(&policies)
+            .into_iter()
+            .map(|w| match w {
+                ValidationWarning::ShadowedPolicy(w) => (
+                    w.policy_id.to_string(),
+                    w.shadowing_policy_id.to_string(),
+                    w.scope,
+                ),
+                ValidationWarning::NullifiedPermit(w) => (
+                    w.policy_id.to_string(),
+                    format!("forbid {}", w.forbid_policy_id),
+                    w.scope,
+                ),
+                w => (w.to_string(), String::new(), String::new()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shadowed_by_broader_scope() {
+        assert_eq!(
+            warnings(
+                r#"
+                permit(principal is User, action in Action::"manage", resource);
+                permit(principal == User::"alice", action == Action::"delete", resource is Photo);
+                permit(principal is User, action == Action::"view", resource);
+                "#
+            ),
+            [(
+                "policy1".to_string(),
+                "policy0".to_string(),
+                r#"principal == User::"alice", action == Action::"delete", resource is Photo"#
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn nullified_permit() {
+        assert_eq!(
+            warnings(
+                r#"
+                forbid(principal is User, action, resource);
+                permit(principal == User::"alice", action == Action::"view", resource)
+                when { principal.level > 2 };
+                "#
+            ),
+            [(
+                "policy1".to_string(),
+                "forbid policy0".to_string(),
+                r#"principal == User::"alice", action == Action::"view", resource"#.to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn conditions_and_duplicates() {
+        // A broader policy with a condition only covers a policy with the
+        // same condition, and equivalent policies are reported once
+        assert_eq!(
+            warnings(
+                r#"
+                permit(principal, action, resource) when { principal.level > 2 };
+                permit(principal, action == Action::"view", resource) when { principal.level > 3 };
+                permit(principal, action == Action::"edit", resource) when { principal.level > 2 };
+                permit(principal, action == Action::"edit", resource) when { principal.level > 2 };
+                "#
+            ),
+            [
+                (
+                    "policy2".to_string(),
+                    "policy0".to_string(),
+                    r#"principal, action == Action::"edit", resource"#.to_string()
+                ),
+                (
+                    "policy3".to_string(),
+                    "policy0".to_string(),
+                    r#"principal, action == Action::"edit", resource"#.to_string()
+                ),
+                (
+                    "policy3".to_string(),
+                    "policy2".to_string(),
+                    r#"principal, action == Action::"edit", resource"#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_with_config() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action, resource);
+            permit(principal, action == Action::"view", resource);
+            "#,
+        )
+        .unwrap();
+        let result = validator().validate(&policies, ValidationMode::Strict);
+        assert_matches!(result.validation_warnings().collect::<Vec<_>>().as_slice(), [ValidationWarning::ShadowedPolicy(w)] => {
+            assert_eq!(w.policy_id, PolicyID::from_string("policy1"));
+            assert_eq!(w.shadowing_policy_id, PolicyID::from_string("policy0"));
+        });
+        let (schema, _) = ValidatorSchema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let result = Validator::new(schema).validate(&policies, ValidationMode::Strict);
+        assert_eq!(result.validation_warnings().count(), 0);
+    }
+}
@@ -26,7 +26,10 @@ use itertools::Itertools;
 pub(crate) use typecheck_answer::TypecheckAnswer;
 
 use std::sync::Arc;
-use std::{borrow::Cow, collections::HashSet};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+};
 
 use crate::ast::UnwrapInfallible;
 use crate::validator::types::{BoolType, EntityLUB};
@@ -42,10 +45,12 @@ use crate::validator::{
 use crate::fuzzy_match::fuzzy_search;
 use crate::{
     ast::{
-        BinaryOp, EntityType, EntityUID, Expr, ExprBuilder, ExprKind, Literal, Name, PolicyID,
-        PrincipalOrResourceConstraint, SlotId, Template, UnaryOp, Var,
+        BinaryOp, EntityType, EntityUID, Expr, ExprBuilder, ExprKind, Literal, Name, PartialValue,
+        PolicyID, PrincipalOrResourceConstraint, SlotId, SlotType, Template, UnaryOp, Value, Var,
     },
+    entities::Refinement,
     expr_builder::ExprBuilder as _,
+    extensions::Extensions,
 };
 
 const REQUIRED_STACK_SPACE: usize = 1024 * 100;
@@ -66,7 +71,7 @@ pub enum PolicyCheck {
 #[derive(Debug)]
 pub struct Typechecker<'a> {
     schema: &'a ValidatorSchema,
-    extensions: &'static ExtensionSchemas<'static>,
+    extensions: &'a ExtensionSchemas<'a>,
     mode: ValidationMode,
     /// List of valid (unlinked) `RequestEnv`s for this schema.
     /// Cached here so it can be computed once (during `Typechecker`
@@ -77,9 +82,19 @@ pub struct Typechecker<'a> {
 impl<'a> Typechecker<'a> {
     /// Construct a new typechecker. All extensions are enabled by default.
     pub fn new(schema: &'a ValidatorSchema, mode: ValidationMode) -> Typechecker<'a> {
+        Self::with_extensions(schema, mode, ExtensionSchemas::all_available())
+    }
+
+    /// Construct a new typechecker which types extension function calls with
+    /// `extensions`
+    pub(crate) fn with_extensions(
+        schema: &'a ValidatorSchema,
+        mode: ValidationMode,
+        extensions: &'a ExtensionSchemas<'a>,
+    ) -> Typechecker<'a> {
         Self {
             schema,
-            extensions: ExtensionSchemas::all_available(),
+            extensions,
             mode,
             unlinked_envs: schema.unlinked_request_envs(mode).collect(),
         }
@@ -106,8 +121,14 @@ impl<'a> Typechecker<'a> {
         let (all_false, all_succ) = typecheck_answers.into_iter().fold(
             (true, true),
             |(all_false, all_succ), (_, check)| match check {
-                PolicyCheck::Success(_) => (false, all_succ),
-                PolicyCheck::Irrelevant(err, _) => {
+                PolicyCheck::Success(e) => {
+                    self.warn_deprecated_attributes(t.id(), &e, warnings);
+                    self.warn_constant_comparisons(t.id(), &e, warnings);
+                    (false, all_succ)
+                }
+                PolicyCheck::Irrelevant(err, e) => {
+                    self.warn_deprecated_attributes(t.id(), &e, warnings);
+                    self.warn_constant_comparisons(t.id(), &e, warnings);
                     let no_err = err.is_empty();
                     type_errors.extend(err);
                     (all_false, all_succ && no_err)
@@ -131,6 +152,180 @@ impl<'a> Typechecker<'a> {
         all_succ
     }
 
+    /// Report a warning for each access to (or `has` check on) an attribute
+    /// which is annotated as `@deprecated` in the schema, found using the
+    /// types in the typechecked policy expression `e`
+    fn warn_deprecated_attributes(
+        &self,
+        policy_id: &PolicyID,
+        e: &Expr<Option<Type>>,
+        warnings: &mut HashSet<ValidationWarning>,
+    ) {
+        for sub in e.subexpressions() {
+            let (target, attr) = match sub.expr_kind() {
+                ExprKind::GetAttr { expr, attr } | ExprKind::HasAttr { expr, attr } => (expr, attr),
+                _ => continue,
+            };
+            let deprecation = match target.data() {
+                Some(Type::Record { attrs, .. }) => {
+                    attrs.get_attr(attr).and_then(|ty| ty.deprecation.clone())
+                }
+                Some(Type::Entity(EntityKind::Entity(lub))) => lub.iter().find_map(|name| {
+                    self.schema
+                        .get_entity_type(name)?
+                        .attr(attr)?
+                        .deprecation
+                        .clone()
+                }),
+                _ => None,
+            };
+            if let Some(reason) = deprecation {
+                warnings.insert(ValidationWarning::deprecated_attribute(
+                    sub.source_loc().cloned(),
+                    policy_id.clone(),
+                    attr.as_str(),
+                    Some(reason).filter(|reason| !reason.is_empty()),
+                ));
+            }
+        }
+    }
+
+    /// Report a warning for each comparison of an attribute which has a
+    /// refinement in the schema with a literal (including a call to an
+    /// extension constructor with a literal argument), where the comparison
+    /// has the same outcome for every value the refinement allows
+    fn warn_constant_comparisons(
+        &self,
+        policy_id: &PolicyID,
+        e: &Expr<Option<Type>>,
+        warnings: &mut HashSet<ValidationWarning>,
+    ) {
+        for sub in e.subexpressions() {
+            let ExprKind::BinaryApp { op, arg1, arg2 } = sub.expr_kind() else {
+                continue;
+            };
+            if *op == BinaryOp::Eq {
+                self.warn_constant_extension_comparison(policy_id, sub, arg1, arg2, warnings);
+            }
+            // `attr_on_left` records which side of the comparison the
+            // attribute is on, which matters for `<` and `<=`
+            let (access, lit, attr_on_left) = match (arg1.expr_kind(), arg2.expr_kind()) {
+                (ExprKind::GetAttr { .. }, ExprKind::Lit(lit)) => (arg1, lit, true),
+                (ExprKind::Lit(lit), ExprKind::GetAttr { .. }) => (arg2, lit, false),
+                _ => continue,
+            };
+            let ExprKind::GetAttr { expr: target, attr } = access.expr_kind() else {
+                continue;
+            };
+            let Some(refinement) = self.attr_refinement(target, attr) else {
+                continue;
+            };
+            let constant = match (op, lit, refinement) {
+                (BinaryOp::Eq, Literal::Long(i), _) => !refinement.admits_long(*i),
+                (BinaryOp::Eq, Literal::String(s), _) => !refinement.admits_string(s),
+                (
+                    BinaryOp::Less | BinaryOp::LessEq,
+                    Literal::Long(i),
+                    Refinement::Range { min, max },
+                ) => {
+                    let strict = *op == BinaryOp::Less;
+                    if attr_on_left {
+                        // `attr < i` is always false if `min >= i`, and
+                        // always true if `max < i`
+                        min.is_some_and(|min| if strict { min >= *i } else { min > *i })
+                            || max.is_some_and(|max| if strict { max < *i } else { max <= *i })
+                    } else {
+                        // `i < attr` is always false if `max <= i`, and
+                        // always true if `min > i`
+                        max.is_some_and(|max| if strict { max <= *i } else { max < *i })
+                            || min.is_some_and(|min| if strict { min > *i } else { min >= *i })
+                    }
+                }
+                _ => false,
+            };
+            if constant {
+                warnings.insert(ValidationWarning::constant_comparison(
+                    sub.source_loc().cloned(),
+                    policy_id.clone(),
+                    attr.as_str(),
+                    lit.to_string(),
+                    refinement.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Report a warning if `eq`, which is `arg1 == arg2`, compares an
+    /// extension-typed attribute which has a `satisfying` refinement with an
+    /// extension constructor call whose value does not satisfy it, so that
+    /// the comparison is always `false`
+    fn warn_constant_extension_comparison(
+        &self,
+        policy_id: &PolicyID,
+        eq: &Expr<Option<Type>>,
+        arg1: &Expr<Option<Type>>,
+        arg2: &Expr<Option<Type>>,
+        warnings: &mut HashSet<ValidationWarning>,
+    ) {
+        let (access, call) = match (arg1.expr_kind(), arg2.expr_kind()) {
+            (ExprKind::GetAttr { .. }, ExprKind::ExtensionFunctionApp { .. }) => (arg1, arg2),
+            (ExprKind::ExtensionFunctionApp { .. }, ExprKind::GetAttr { .. }) => (arg2, arg1),
+            _ => return,
+        };
+        let (
+            ExprKind::GetAttr { expr: target, attr },
+            ExprKind::ExtensionFunctionApp { fn_name, args },
+        ) = (access.expr_kind(), call.expr_kind())
+        else {
+            return;
+        };
+        let Some(refinement @ Refinement::Satisfying(_)) = self.attr_refinement(target, attr)
+        else {
+            return;
+        };
+        let [arg] = args.as_slice() else {
+            return;
+        };
+        let ExprKind::Lit(Literal::String(s)) = arg.expr_kind() else {
+            return;
+        };
+        let value = Extensions::all_available()
+            .func(fn_name)
+            .ok()
+            .and_then(|func| func.call(&[Value::from(s.clone())]).ok());
+        if let Some(PartialValue::Value(value)) = value {
+            if !refinement.admits_value(&value) {
+                warnings.insert(ValidationWarning::constant_comparison(
+                    eq.source_loc().cloned(),
+                    policy_id.clone(),
+                    attr.as_str(),
+                    call.to_string(),
+                    refinement.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Get the refinement of attribute `attr` of `target`, using the type of
+    /// `target` in the typechecked policy. Entity attributes are only
+    /// considered if `target` has a single entity type.
+    fn attr_refinement<'s>(
+        &'s self,
+        target: &'s Expr<Option<Type>>,
+        attr: &str,
+    ) -> Option<&'s Refinement> {
+        match target.data() {
+            Some(Type::Record { attrs, .. }) => attrs.get_attr(attr)?.refinement.as_ref(),
+            Some(Type::Entity(EntityKind::Entity(lub))) => self
+                .schema
+                .get_entity_type(lub.get_single_entity()?)?
+                .attr(attr)?
+                .refinement
+                .as_ref(),
+            _ => None,
+        }
+    }
+
     /// Secondary entry point for typechecking requests. This method takes a policy and
     /// typechecks it under every schema-defined request environment. The result contains
     /// these environments and the individual typechecking response for each, in no
@@ -144,7 +339,7 @@ impl<'a> Typechecker<'a> {
         t: &'b Template,
     ) -> Vec<(RequestEnv<'b>, PolicyCheck)> {
         self.apply_typecheck_fn_by_request_env(t, |request_env, policy_id, expr| {
-            self.single_env_typechecking(request_env, policy_id, expr)
+            self.single_env_typechecking(request_env, policy_id, t.value_slot_types_map(), expr)
         })
     }
 
@@ -152,6 +347,7 @@ impl<'a> Typechecker<'a> {
         &self,
         request_env: &RequestEnv<'_>,
         policy_id: &PolicyID,
+        value_slot_types: &BTreeMap<SlotId, SlotType>,
         expr: &Expr,
     ) -> PolicyCheck {
         let mut type_errors = Vec::new();
@@ -161,6 +357,7 @@ impl<'a> Typechecker<'a> {
             mode: self.mode,
             policy_id,
             request_env,
+            value_slot_types,
         };
         let empty_prior_capability = CapabilitySet::new();
         let ans = single_env_typechecker.expect_type(
@@ -196,7 +393,12 @@ impl<'a> Typechecker<'a> {
         t: &'b Template,
         request_env: &RequestEnv<'b>,
     ) -> PolicyCheck {
-        self.single_env_typechecking(request_env, t.id(), &t.condition())
+        self.single_env_typechecking(
+            request_env,
+            t.id(),
+            t.value_slot_types_map(),
+            &t.condition(),
+        )
     }
 
     /// Apply `typecheck_fn` to the given policy in every schema-defined request
@@ -243,14 +445,14 @@ impl<'a> Typechecker<'a> {
             } => Box::new(
                 self.possible_slot_links(
                     t,
-                    SlotId::principal(),
+                    &SlotId::principal(),
                     principal,
                     t.principal_constraint().as_inner(),
                 )
                 .flat_map(move |p_slot| {
                     self.possible_slot_links(
                         t,
-                        SlotId::resource(),
+                        &SlotId::resource(),
                         resource,
                         t.resource_constraint().as_inner(),
                     )
@@ -267,11 +469,11 @@ impl<'a> Typechecker<'a> {
     fn possible_slot_links(
         &self,
         t: &Template,
-        slot_id: SlotId,
+        slot_id: &SlotId,
         var: &'a EntityType,
         constraint: &PrincipalOrResourceConstraint,
     ) -> Box<dyn Iterator<Item = Option<EntityType>> + 'a> {
-        if t.slots().any(|t_slot| t_slot.id == slot_id) {
+        if t.slots().any(|t_slot| &t_slot.id == slot_id) {
             let all_entity_types = self.schema.entity_types();
             match constraint {
                 // The condition is `var = ?slot`, so the policy can only apply
@@ -307,6 +509,36 @@ impl<'a> Typechecker<'a> {
     }
 }
 
+/// Typecheck the standalone expression `expr` against `schema`, for requests in
+/// the environment `env`, e.g., a fragment of a policy condition. The
+/// expression is typechecked in strict mode, but unlike the condition of a
+/// policy, it may have any type.
+///
+/// Returns the type of `expr`, or the errors found typechecking it. As `expr`
+/// is not part of a policy, the errors are reported for the policy ID `expr`.
+pub fn typecheck_expr(
+    expr: &Expr,
+    schema: &ValidatorSchema,
+    env: &RequestEnv<'_>,
+) -> Result<Type, Vec<ValidationError>> {
+    let policy_id = PolicyID::from_string("expr");
+    let typechecker = SingleEnvTypechecker {
+        schema,
+        extensions: ExtensionSchemas::all_available(),
+        mode: ValidationMode::Strict,
+        policy_id: &policy_id,
+        request_env: env,
+        value_slot_types: &BTreeMap::new(),
+    };
+    let mut type_errors = Vec::new();
+    match typechecker.typecheck(&CapabilitySet::new(), expr, &mut type_errors) {
+        TypecheckAnswer::TypecheckSuccess { expr_type, .. } => {
+            expr_type.into_data().ok_or(type_errors)
+        }
+        _ => Err(type_errors),
+    }
+}
+
 /// Struct which implements typechecking for policies within a single request
 /// env.
 struct SingleEnvTypechecker<'a> {
@@ -318,6 +550,8 @@ struct SingleEnvTypechecker<'a> {
     policy_id: &'a PolicyID,
     /// The single env which we're performing typechecking for
     request_env: &'a RequestEnv<'a>,
+    /// Declared types of the value slots of the template we're typechecking
+    value_slot_types: &'a BTreeMap<SlotId, SlotType>,
 }
 
 impl<'a> SingleEnvTypechecker<'a> {
@@ -376,12 +610,22 @@ impl<'a> SingleEnvTypechecker<'a> {
                     .with_same_source_loc(e)
                     .var(Var::Context),
             ),
-            ExprKind::Unknown(u) => {
-                TypecheckAnswer::fail(ExprBuilder::with_data(None).unknown(u.clone()))
-            }
-            // Template Slots, always has to be an entity.
+            // Unknowns declared with a schema type have that type. Other
+            // unknowns may be substituted with a value of any type.
+            ExprKind::Unknown(u) => match &u.schema_type {
+                Some(ty) => TypecheckAnswer::success(
+                    ExprBuilder::with_data(Some(Type::from_core_schema_type(ty)))
+                        .with_same_source_loc(e)
+                        .unknown(u.clone()),
+                ),
+                None => TypecheckAnswer::fail(ExprBuilder::with_data(None).unknown(u.clone())),
+            },
+            // Template Slots. `?principal` and `?resource` always have to be
+            // an entity, while value slots have their declared type.
             ExprKind::Slot(slotid) => TypecheckAnswer::success(
-                ExprBuilder::with_data(Some(if slotid.is_principal() {
+                ExprBuilder::with_data(Some(if let Some(ty) = self.value_slot_types.get(slotid) {
+                    Type::from_slot_type(ty)
+                } else if slotid.is_principal() {
                     self.request_env
                         .principal_slot()
                         .clone()
@@ -397,7 +641,7 @@ impl<'a> SingleEnvTypechecker<'a> {
                     Type::any_entity_reference()
                 }))
                 .with_same_source_loc(e)
-                .slot(*slotid),
+                .slot(slotid.clone()),
             ),
 
             // Literal booleans get singleton type according to their value.
@@ -773,6 +1017,8 @@ impl<'a> SingleEnvTypechecker<'a> {
 
                 actual.then_typecheck(|typ_expr_actual, _| match typ_expr_actual.data() {
                     Some(typ_actual) => {
+                        let narrowed = Self::narrow_entity_type(prior_capability, expr, typ_actual);
+                        let typ_actual = narrowed.as_ref().unwrap_or(typ_actual);
                         let all_attrs = typ_actual.all_attributes(self.schema);
                         let attr_ty = Type::lookup_attribute_type(self.schema, typ_actual, attr);
                         let annot_expr = ExprBuilder::with_data(
@@ -866,6 +1112,8 @@ impl<'a> SingleEnvTypechecker<'a> {
                 );
                 actual.then_typecheck(|typ_expr_actual, _| match typ_expr_actual.data() {
                     Some(typ_actual) => {
+                        let narrowed = Self::narrow_entity_type(prior_capability, expr, typ_actual);
+                        let typ_actual = narrowed.as_ref().unwrap_or(typ_actual);
                         match Type::lookup_attribute_type(self.schema, typ_actual, attr) {
                             Some(AttributeType {
                                 is_required: true, ..
@@ -1003,10 +1251,17 @@ impl<'a> SingleEnvTypechecker<'a> {
                                 Type::primitive_boolean()
                             };
 
-                            TypecheckAnswer::success(
+                            // When the `is` is `true`, we know the expression
+                            // is an entity of the tested type, so attribute
+                            // accesses guarded by it can use that entity type.
+                            TypecheckAnswer::success_with_capability(
                                 ExprBuilder::with_data(Some(type_of_is))
                                     .with_same_source_loc(e)
                                     .is_entity_type(expr_ty, entity_type.clone()),
+                                CapabilitySet::singleton(Capability::new_entity_type(
+                                    expr,
+                                    entity_type,
+                                )),
                             )
                         }
                         // For `AnyEntity` we don't know anything about what
@@ -1130,6 +1385,24 @@ impl<'a> SingleEnvTypechecker<'a> {
         }
     }
 
+    /// If `ty`, the type of `expr`, is a least upper bound of several entity
+    /// types, and the prior capability shows that `expr` is an entity of one
+    /// of them (because the access is guarded by an `is` test), return the
+    /// type of that single entity type.
+    fn narrow_entity_type(
+        prior_capability: &CapabilitySet<'_>,
+        expr: &Expr,
+        ty: &Type,
+    ) -> Option<Type> {
+        match ty {
+            Type::Entity(EntityKind::Entity(lub)) if lub.get_single_entity().is_none() => lub
+                .iter()
+                .find(|ety| prior_capability.contains(&Capability::new_entity_type(expr, ety)))
+                .map(|ety| Type::named_entity_reference(ety.clone())),
+            _ => None,
+        }
+    }
+
     // Return if `ty` is a valid comparison operator type
     // Currently, only primitive long and certain extension types are valid
     fn is_valid_comparison_op_type(&self, ty: &Type) -> bool {
@@ -1626,6 +1899,95 @@ impl<'a> SingleEnvTypechecker<'a> {
                     })
                 })
             }
+
+            #[cfg(feature = "maps")]
+            BinaryOp::ContainsKey => self
+                .expect_type(
+                    prior_capability,
+                    arg1,
+                    Type::any_map(),
+                    type_errors,
+                    |actual| match actual {
+                        Type::Record { .. } => Some(UnexpectedTypeHelp::TryUsingHas),
+                        _ => None,
+                    },
+                )
+                .then_typecheck(|expr_ty_arg1, _| {
+                    self.expect_type(
+                        prior_capability,
+                        arg2,
+                        Type::primitive_string(),
+                        type_errors,
+                        |_| None,
+                    )
+                    .then_typecheck(|expr_ty_arg2, _| {
+                        let type_of_contains_key = if prior_capability
+                            .contains(&Capability::new_borrowed_key(arg1, arg2))
+                        {
+                            // Prior capability tells us that we already checked for the key
+                            Type::singleton_boolean(true)
+                        } else {
+                            Type::primitive_boolean()
+                        };
+                        TypecheckAnswer::success_with_capability(
+                            ExprBuilder::with_data(Some(type_of_contains_key))
+                                .with_same_source_loc(bin_expr)
+                                .contains_key(expr_ty_arg1, expr_ty_arg2),
+                            CapabilitySet::singleton(Capability::new_borrowed_key(arg1, arg2)),
+                        )
+                    })
+                }),
+
+            #[cfg(feature = "maps")]
+            BinaryOp::GetKey => self
+                .expect_type(
+                    prior_capability,
+                    arg1,
+                    Type::any_map(),
+                    type_errors,
+                    |actual| match actual {
+                        Type::Record { .. } => Some(UnexpectedTypeHelp::TryUsingHas),
+                        _ => None,
+                    },
+                )
+                .then_typecheck(|expr_ty_arg1, _| {
+                    self.expect_type(
+                        prior_capability,
+                        arg2,
+                        Type::primitive_string(),
+                        type_errors,
+                        |_| None,
+                    )
+                    .then_typecheck(|expr_ty_arg2, _| {
+                        if prior_capability.contains(&Capability::new_borrowed_key(arg1, arg2)) {
+                            let value_type = match expr_ty_arg1.data() {
+                                Some(Type::Map {
+                                    value_type: Some(value_type),
+                                }) => value_type.as_ref().clone(),
+                                // `Never`, or an arbitrary map, which can't
+                                // be written in a schema
+                                _ => Type::Never,
+                            };
+                            TypecheckAnswer::success(
+                                ExprBuilder::with_data(Some(value_type))
+                                    .with_same_source_loc(bin_expr)
+                                    .get_key(expr_ty_arg1, expr_ty_arg2),
+                            )
+                        } else {
+                            type_errors.push(ValidationError::unsafe_map_access(
+                                bin_expr_loc.clone(),
+                                self.policy_id.clone(),
+                                expr_ty_arg1.clone(),
+                                expr_ty_arg2.clone(),
+                            ));
+                            TypecheckAnswer::fail(
+                                ExprBuilder::new()
+                                    .with_same_source_loc(bin_expr)
+                                    .get_key(expr_ty_arg1, expr_ty_arg2),
+                            )
+                        }
+                    })
+                }),
         }
     }
 
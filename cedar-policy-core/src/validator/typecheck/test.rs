@@ -26,12 +26,16 @@ pub(crate) mod test_utils;
 
 mod expr;
 mod extensions;
+#[cfg(feature = "maps")]
+mod maps;
 mod namespace;
 mod optional_attributes;
 #[cfg(feature = "partial-validate")]
 mod partial;
 mod policy;
+mod standalone;
 mod strict;
 mod tags;
 mod type_annotation;
+mod union_types;
 mod unspecified_entity;
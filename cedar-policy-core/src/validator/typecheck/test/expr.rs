@@ -17,10 +17,11 @@
 //! Contains tests for typechecking Cedar expressions outside of a larger
 //! policy and without a schema.
 
-use std::{str::FromStr, vec};
+use std::{collections::BTreeMap, str::FromStr, vec};
 
 use crate::{
-    ast::{BinaryOp, EntityUID, Expr, Pattern, PatternElem, SlotId, Var},
+    ast::{BinaryOp, EntityUID, Expr, Pattern, PatternElem, SlotId, Unknown, Var},
+    entities::{AttributeType, SchemaType},
     extensions::Extensions,
     validator::types::BoolType,
 };
@@ -51,6 +52,29 @@ fn primitives_typecheck() {
     assert_typechecks_empty_schema(&Expr::val("foo"), &Type::primitive_string());
 }
 
+#[test]
+fn schema_typed_unknown_typechecks() {
+    let record = SchemaType::Record {
+        attrs: BTreeMap::from([("a".into(), AttributeType::required(SchemaType::Long))]),
+        open_attrs: false,
+    };
+    assert_typechecks_empty_schema(
+        &Expr::get_attr(
+            Expr::unknown(Unknown::new_with_schema_type("u", record)),
+            "a".into(),
+        ),
+        &Type::primitive_long(),
+    );
+    assert_typechecks_empty_schema(
+        &Expr::add(
+            Expr::unknown(Unknown::new_with_schema_type("u", SchemaType::Long)),
+            Expr::val(1),
+        ),
+        &Type::primitive_long(),
+    );
+    assert_typecheck_fails_empty_schema_without_type(&Expr::unknown(Unknown::new_untyped("u")));
+}
+
 #[test]
 fn slot_typechecks() {
     assert_typechecks_empty_schema(
@@ -70,6 +94,7 @@ fn slot_in_typechecks() {
         member_of_types: vec![],
         shape: json_schema::AttributesOrContext::default(),
         tags: None,
+        constraints: vec![],
     }
     .into();
     let schema = json_schema::NamespaceDefinition::new([("typename".parse().unwrap(), etype)], []);
@@ -101,6 +126,7 @@ fn slot_equals_typechecks() {
         member_of_types: vec![],
         shape: json_schema::AttributesOrContext::default(),
         tags: None,
+        constraints: vec![],
     }
     .into();
     // These don't typecheck in strict mode because the test_util expression
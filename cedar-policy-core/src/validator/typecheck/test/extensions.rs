@@ -101,6 +101,255 @@ fn ip_extension_typecheck_fails() {
     );
 }
 
+#[test]
+#[cfg(feature = "ipaddr")]
+fn ipset_extension_typechecks() {
+    use crate::ast::Name;
+    let ipset_name = Name::parse_unqualified_name("ipset").expect("should be a valid identifier");
+
+    let expr = Expr::from_str(r#"ipset(["10.0.0.0/8", "192.168.0.0/16"])"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::extension(ipset_name.clone()));
+    let expr = Expr::from_str(r#"ip("10.0.0.1").isInSet(ipset(["10.0.0.0/8"]))"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+
+    let src = r#"ip("10.0.0.1").isInSet(["10.0.0.0/8"])"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(&expr, &Type::primitive_boolean());
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, r#"["10.0.0.0/8"]"#),
+            expr_id_placeholder(),
+            Type::extension(ipset_name),
+            Type::set(Type::primitive_string().into()),
+            None,
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "strings")]
+fn strings_extension_typechecks() {
+    let expr = Expr::from_str(r#""Photo.JPG".toLowerCase().endsWith(".jpg")"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr = Expr::from_str(r#""a,b".split(",").contains("a") && "abc".length() > 2"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+
+    let src = r#""abc".startsWith(1)"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(&expr, &Type::primitive_boolean());
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, "1"),
+            expr_id_placeholder(),
+            Type::primitive_string(),
+            Type::primitive_long(),
+            None,
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn bytes_extension_typechecks() {
+    use crate::ast::Name;
+    let bytes_name = Name::parse_unqualified_name("bytes").expect("should be a valid identifier");
+
+    let expr = Expr::from_str(r#"bytes("3q2+7w==") == bytes("3q2+7w==")"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr = Expr::from_str(r#"bytes("3q2+7w==").byteLength()"#).expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_long());
+
+    let src = r#""3q2+7w==".byteLength()"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(&expr, &Type::primitive_long());
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, r#""3q2+7w==""#),
+            expr_id_placeholder(),
+            Type::extension(bytes_name.clone()),
+            Type::primitive_string(),
+            None,
+        )
+    );
+
+    let src = r#"bytes("3q2+7w")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(&expr, &Type::extension(bytes_name));
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::function_argument_validation(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            "failed to parse as a base64 byte string: `\"3q2+7w\"`".into(),
+            Some(
+                "byte strings are written in standard base64 with padding, e.g., `bytes(\"3q2+7w==\")`".into(),
+            ),
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "lookup")]
+fn lookup_extension_typechecks() {
+    let expr = Expr::from_str(
+        r#"{limits: {daily: 10}}.lookup("limits.daily").isPresent() && {limits: {daily: 10}}.lookup("limits.daily").asLong() > 5"#,
+    )
+    .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr = Expr::from_str(r#"{}.lookup({path: "region"}.path).asString()"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_string());
+
+    let src = r#""limits".lookup("daily")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(
+        &expr,
+        &Type::extension("optional".parse().expect("should be a valid identifier")),
+    );
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, r#""limits""#),
+            expr_id_placeholder(),
+            Type::any_record(),
+            Type::primitive_string(),
+            None,
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "geo")]
+fn geo_extension_typechecks() {
+    let expr = Expr::from_str(
+        r#"geopoint("47.6062,-122.3321").distanceTo(geopoint("45.5152,-122.6784")) < 250000"#,
+    )
+    .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr =
+        Expr::from_str(r#"geopoint("5,5").isInPolygon(geopolygon("0,0; 0,10; 10,10; 10,0"))"#)
+            .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+
+    let src = r#"geopoint("5,5").isInPolygon(geopoint("0,0"))"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(&expr, &Type::primitive_boolean());
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::expected_type(
+            get_loc(src, r#"geopoint("0,0")"#),
+            expr_id_placeholder(),
+            Type::extension("geopolygon".parse().expect("should be a valid identifier")),
+            Type::extension("geopoint".parse().expect("should be a valid identifier")),
+            None,
+        )
+    );
+
+    let src = r#"geopolygon("0,0; 0,10")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(
+        &expr,
+        &Type::extension("geopolygon".parse().expect("should be a valid identifier")),
+    );
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::function_argument_validation(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            "failed to parse as a polygon: `\"0,0; 0,10\"`".into(),
+            Some(
+                "polygons are written as at least three points separated by `;`, e.g., `geopolygon(\"47.0,-122.0; 47.0,-121.0; 48.0,-121.0\")`".into(),
+            ),
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "identifiers")]
+fn identifiers_extension_typechecks() {
+    let expr = Expr::from_str(
+        r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").uuidVersion() == 4 && ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV") < ulid("01ARZ3NDEKTSV4RRFFQ69G5FAW")"#,
+    )
+    .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr = Expr::from_str(r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV").timestampMillis()"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_long());
+
+    // UUIDs are not ordered
+    let src = r#"uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11") < uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a12")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(&expr, &Type::primitive_boolean());
+
+    let src = r#"ulid("01ARZ3NDEKTSV4RRFFQ69G5FA")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(
+        &expr,
+        &Type::extension("ulid".parse().expect("should be a valid identifier")),
+    );
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::function_argument_validation(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            "failed to parse as a ULID: `\"01ARZ3NDEKTSV4RRFFQ69G5FA\"`".into(),
+            Some(
+                "ULIDs are written as 26 Crockford base32 digits, starting with a digit from 0 to 7, e.g., `ulid(\"01ARZ3NDEKTSV4RRFFQ69G5FAV\")`".into(),
+            ),
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "mediatype")]
+fn mediatype_extension_typechecks() {
+    let expr = Expr::from_str(r#"mediatype("image/png").matches("image/*")"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    let expr = Expr::from_str(r#"mediatype("text/plain;charset=utf-8").essence()"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_string());
+
+    // the receiver of `matches` must be a `mediatype`, not a string
+    let expr = Expr::from_str(r#""image/png".matches("image/*")"#).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(&expr, &Type::primitive_boolean());
+
+    let src = r#"mediatype("image/*")"#;
+    let expr = Expr::from_str(src).expect("parsing should succeed");
+    let errors = assert_typecheck_fails_empty_schema(
+        &expr,
+        &Type::extension("mediatype".parse().expect("should be a valid identifier")),
+    );
+    let type_error = assert_exactly_one_diagnostic(errors);
+    assert_eq!(
+        type_error,
+        ValidationError::function_argument_validation(
+            get_loc(src, src),
+            expr_id_placeholder(),
+            "failed to parse as a media type: `\"image/*\"`".into(),
+            Some(
+                "media types are written as a type and a subtype separated by `/`, optionally followed by `;name=value` parameters, e.g., `mediatype(\"text/plain;charset=utf-8\")`".into(),
+            ),
+        )
+    );
+}
+
 #[test]
 #[cfg(not(feature = "variadic-is-in-range"))]
 fn ip_extension_not_variadic() {
@@ -160,6 +409,13 @@ fn decimal_extension_typechecks() {
     let expr = Expr::from_str("decimal(\"1.23\").greaterThanOrEqual(decimal(\"1.24\"))")
         .expect("parsing should succeed");
     assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    for method in ["add", "subtract", "multiply"] {
+        let expr = Expr::from_str(&format!(
+            "decimal(\"1.23\").{method}(decimal(\"1.24\")).lessThan(decimal(\"2.0\"))"
+        ))
+        .expect("parsing should succeed");
+        assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
+    }
 }
 
 #[test]
@@ -246,6 +502,12 @@ fn datetime_extension_typechecks() {
     let expr =
         Expr::from_str(r#"datetime("2024-10-28").toTime()"#).expect("parsing should succeed");
     assert_typechecks_empty_schema(&expr, &Type::extension(duration_name.clone()));
+    let expr = Expr::from_str(r#"datetime("2024-10-28").dayOfWeek(duration("-5h"))"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_long());
+    let expr = Expr::from_str(r#"datetime("2024-10-28").hour(duration("-5h"))"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(&expr, &Type::primitive_long());
     let expr = Expr::from_str(r#"datetime("2024-10-28") < datetime("2024-10-28")"#)
         .expect("parsing should succeed");
     assert_typechecks_empty_schema(&expr, &Type::primitive_boolean());
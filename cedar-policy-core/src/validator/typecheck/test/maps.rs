@@ -0,0 +1,149 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Contains tests for attributes declared with map types, and the `get` and
+//! `containsKey` operations on them.
+
+use super::test_utils::{
+    assert_entities_do_not_validate, assert_entities_validate, assert_exactly_one_diagnostic,
+    assert_policy_typecheck_fails, assert_policy_typechecks,
+};
+use crate::{
+    ast::PolicyID,
+    parser::parse_policy,
+    test_utils::{expect_err, ExpectedErrorMessageBuilder},
+    validator::ValidationError,
+};
+use cool_asserts::assert_matches;
+use serde_json::json;
+
+fn schema_with_maps() -> &'static str {
+    r#"
+        entity User { roles: Map<String, Set<String>> };
+        entity Document;
+        action view appliesTo {
+            principal: [User],
+            resource: [Document],
+            context: { headers: Map<String, String>, record: { host: String } },
+        };
+    "#
+}
+
+#[test]
+fn get_guarded_by_contains_key() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            context.headers.containsKey("host") && context.headers.get("host") like "*.example.com"
+        };"#,
+    )
+    .unwrap();
+    assert_policy_typechecks(schema_with_maps(), policy);
+
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            if principal.roles.containsKey("docs") then principal.roles.get("docs").contains("admin") else false
+        };"#,
+    )
+    .unwrap();
+    assert_policy_typechecks(schema_with_maps(), policy);
+}
+
+#[test]
+fn unguarded_get() {
+    let src = r#"permit(principal, action, resource) when { context.headers.get("host") == "example.com" };"#;
+    let policy = parse_policy(Some(PolicyID::from_string("0")), src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema_with_maps(), policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_matches!(error, ValidationError::UnsafeMapAccess(_));
+    expect_err(
+        src,
+        &miette::Report::new(error),
+        &ExpectedErrorMessageBuilder::error(
+            r#"for policy `0`, unable to guarantee safety of access to map key `"host"`"#,
+        )
+        .help(r#"try testing for the key's presence with `context.headers.containsKey("host") && ..`"#)
+        .exactly_one_underline(r#"context.headers.get("host")"#)
+        .build(),
+    );
+}
+
+#[test]
+fn guard_on_different_key() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            context.headers.containsKey("host") && context.headers.get("accept") == "*"
+        };"#,
+    )
+    .unwrap();
+    let errors = assert_policy_typecheck_fails(schema_with_maps(), policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_matches!(error, ValidationError::UnsafeMapAccess(_));
+}
+
+#[test]
+fn map_operations_on_records() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when { context.record.containsKey("host") };"#,
+    )
+    .unwrap();
+    let errors = assert_policy_typecheck_fails(schema_with_maps(), policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_matches!(error, ValidationError::UnexpectedType(_));
+}
+
+#[test]
+fn value_type_mismatch() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            context.headers.containsKey("n") && context.headers.get("n") > 1
+        };"#,
+    )
+    .unwrap();
+    let errors = assert_policy_typecheck_fails(schema_with_maps(), policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_matches!(error, ValidationError::UnexpectedType(_));
+}
+
+#[test]
+fn entity_data_with_map_attribute() {
+    assert_entities_validate(
+        json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "roles": { "docs": ["admin"], "wiki": [] } }, "parents": [] },
+            { "uid": { "type": "User", "id": "bob" }, "attrs": { "roles": {} }, "parents": [] },
+        ]),
+        schema_with_maps(),
+    );
+    let err = assert_entities_do_not_validate(
+        json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "roles": { "docs": "admin" } }, "parents": [] },
+        ]),
+        schema_with_maps(),
+    );
+    expect_err(
+        "",
+        &miette::Report::new(err),
+        &ExpectedErrorMessageBuilder::error("error during entity deserialization")
+            .source(
+                r#"in attribute `roles` on `User::"alice"`, type mismatch: value was expected to have type [string], but it actually has type string: `"admin"`"#,
+            )
+            .build(),
+    );
+}
@@ -1303,4 +1303,39 @@ mod templates {
             )
         );
     }
+
+    #[test]
+    fn value_slots_safe_body() {
+        assert_policy_typechecks(
+            simple_schema_file(),
+            parse_policy_or_template(
+                None,
+                r#"template(?limit: Long, ?names: Set<String>, ?owner: User) => permit(principal is User, action, resource) when { principal.age < ?limit && ?names.contains(principal.name) && principal == ?owner };"#,
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn value_slot_error_body() {
+        let src =
+            r#"template(?limit: String) => permit(principal, action, resource) when { ?limit };"#;
+        let errors = assert_policy_typecheck_fails(
+            simple_schema_file(),
+            parse_policy_or_template(None, src).unwrap(),
+        );
+        let error = assert_exactly_one_diagnostic(errors);
+        // the second occurrence of `?limit`, in the condition
+        let start = src.rfind("?limit").unwrap();
+        assert_eq!(
+            error,
+            ValidationError::expected_type(
+                Some(crate::parser::Loc::new(start..start + 6, src.into())),
+                PolicyID::from_string("policy0"),
+                Type::primitive_boolean(),
+                Type::primitive_string(),
+                None,
+            )
+        );
+    }
 }
@@ -0,0 +1,113 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Contains tests for typechecking standalone expressions with
+//! [`typecheck_expr`]
+
+use cool_asserts::assert_matches;
+
+use crate::{
+    ast::{EntityType, EntityUID, Expr},
+    extensions::Extensions,
+    validator::{
+        typecheck::typecheck_expr,
+        types::{BoolType, Type},
+        RequestValidationError, ValidationError, ValidatorSchema,
+    },
+};
+
+fn schema() -> ValidatorSchema {
+    ValidatorSchema::from_cedarschema_str(
+        r#"
+        entity User { age: Long, nickname?: String };
+        entity Photo { tags: Set<String> };
+        action view appliesTo { principal: User, resource: Photo, context: { ip: ipaddr } };
+        "#,
+        Extensions::all_available(),
+    )
+    .expect("schema should be valid")
+    .0
+}
+
+#[track_caller]
+fn typecheck(src: &str) -> Result<Type, Vec<ValidationError>> {
+    let schema = schema();
+    let principal: EntityType = "User".parse().unwrap();
+    let action: EntityUID = r#"Action::"view""#.parse().unwrap();
+    let resource: EntityType = "Photo".parse().unwrap();
+    let env = schema
+        .request_env(&principal, &action, &resource)
+        .expect("request env should be valid");
+    let expr: Expr = src.parse().unwrap();
+    typecheck_expr(&expr, &schema, &env)
+}
+
+#[test]
+fn standalone_expr_types() {
+    assert_matches!(typecheck("principal.age + 1"), Ok(Type::Long));
+    assert_matches!(typecheck("resource.tags"), Ok(Type::Set { element_type: Some(ty) }) => {
+        assert_eq!(*ty, Type::String);
+    });
+    assert_matches!(
+        typecheck(r#"resource.tags.contains("beach")"#),
+        Ok(Type::Bool(BoolType::AnyBool))
+    );
+    assert_matches!(typecheck("principal"), Ok(ty) => {
+        assert_eq!(ty, Type::named_entity_reference("User".parse().unwrap()));
+    });
+    assert_matches!(
+        typecheck(r#"context.ip.isInRange(ip("10.0.0.0/8"))"#),
+        Ok(Type::Bool(_))
+    );
+}
+
+#[test]
+fn standalone_expr_errors() {
+    assert_matches!(typecheck("principal.nickname"), Err(errs) => {
+        assert_matches!(&errs[..], [ValidationError::UnsafeOptionalAttributeAccess(_)]);
+    });
+    assert_matches!(typecheck(r#"principal.age + "one""#), Err(errs) => {
+        assert_matches!(&errs[..], [ValidationError::UnexpectedType(_)]);
+    });
+    assert_matches!(typecheck("resource.owner"), Err(errs) => {
+        assert_matches!(&errs[..], [ValidationError::UnsafeAttributeAccess(_)]);
+    });
+}
+
+#[test]
+fn invalid_request_env() {
+    let schema = schema();
+    let user: EntityType = "User".parse().unwrap();
+    let photo: EntityType = "Photo".parse().unwrap();
+    let view: EntityUID = r#"Action::"view""#.parse().unwrap();
+    let edit: EntityUID = r#"Action::"edit""#.parse().unwrap();
+    assert_matches!(
+        schema.request_env(&photo, &view, &photo),
+        Err(RequestValidationError::InvalidPrincipalType(_))
+    );
+    assert_matches!(
+        schema.request_env(&user, &view, &user),
+        Err(RequestValidationError::InvalidResourceType(_))
+    );
+    assert_matches!(
+        schema.request_env(&user, &edit, &photo),
+        Err(RequestValidationError::UndeclaredAction(_))
+    );
+    assert_matches!(
+        schema.request_env(&"Album".parse().unwrap(), &view, &photo),
+        Err(RequestValidationError::UndeclaredPrincipalType(_))
+    );
+}
@@ -18,6 +18,7 @@
 
 use cool_asserts::assert_matches;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -54,6 +55,7 @@ fn assert_typechecks_strict(
         mode: ValidationMode::Strict,
         policy_id: &expr_id_placeholder(),
         request_env,
+        value_slot_types: &BTreeMap::new(),
     };
     let mut errs = Vec::new();
     let answer =
@@ -81,6 +83,7 @@ fn assert_strict_type_error(
         mode: ValidationMode::Strict,
         policy_id: &expr_id_placeholder(),
         request_env,
+        value_slot_types: &BTreeMap::new(),
     };
     let mut errs = Vec::new();
     let answer =
@@ -174,6 +177,7 @@ fn strict_typecheck_catches_regular_type_error() {
             mode: ValidationMode::Strict,
             policy_id: &expr_id_placeholder(),
             request_env: &q,
+            value_slot_types: &BTreeMap::new(),
         };
         let mut errs = Vec::new();
         typechecker.expect_type(
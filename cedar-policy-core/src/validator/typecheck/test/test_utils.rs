@@ -19,7 +19,11 @@
 
 use cool_asserts::assert_matches;
 use itertools::Itertools;
-use std::{collections::HashSet, hash::Hash, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
 
 use crate::ast::{Context, EntityUID, Expr, PolicyID, Request, Template, ACTION_ENTITY_TYPE};
 use crate::entities::{err::EntitiesError, Entities, EntityJsonParser, TCComputation};
@@ -114,6 +118,7 @@ impl Typechecker<'_> {
             mode: self.mode,
             policy_id,
             request_env: &request_env,
+            value_slot_types: &BTreeMap::new(),
         };
         let mut type_errors = Vec::new();
         let ans = typechecker.typecheck(&CapabilitySet::new(), e, &mut type_errors);
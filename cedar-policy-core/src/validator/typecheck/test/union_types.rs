@@ -0,0 +1,139 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Contains tests for attributes declared with union types, and narrowing
+//! them with `is`.
+
+use super::test_utils::{
+    assert_entities_do_not_validate, assert_entities_validate, assert_exactly_one_diagnostic,
+    assert_policy_typecheck_fails, assert_policy_typechecks,
+};
+use crate::{
+    ast::PolicyID,
+    parser::parse_policy,
+    test_utils::{expect_err, ExpectedErrorMessageBuilder},
+    validator::ValidationError,
+};
+use cool_asserts::assert_matches;
+use serde_json::json;
+
+fn schema_with_unions() -> &'static str {
+    r#"
+        entity User;
+        entity Photo { owner: User, width: Long };
+        entity Album { owner: User, size: Long };
+        type Target = Photo | Album;
+        entity Share { target: Target };
+        action view appliesTo {
+            principal: [User],
+            resource: [Share],
+            context: { payload: { kind: String, count: Long } | { kind: String, label: String } },
+        };
+    "#
+}
+
+#[test]
+fn common_attribute_without_narrowing() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when { resource.target.owner == principal };"#,
+    )
+    .unwrap();
+    assert_policy_typechecks(schema_with_unions(), policy);
+}
+
+#[test]
+fn narrowing_with_is() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            (resource.target is Photo && resource.target.width > 100) ||
+            (if resource.target is Album then resource.target.size > 10 else false)
+        };"#,
+    )
+    .unwrap();
+    assert_policy_typechecks(schema_with_unions(), policy);
+}
+
+#[test]
+fn no_narrowing_across_or() {
+    let src = r#"permit(principal, action, resource) when {
+        (resource.target is Photo || resource.target is Album) && resource.target.width > 100
+    };"#;
+    let policy = parse_policy(Some(PolicyID::from_string("0")), src).unwrap();
+    let errors = assert_policy_typecheck_fails(schema_with_unions(), policy);
+    let error = assert_exactly_one_diagnostic(errors);
+    assert_matches!(error, ValidationError::UnsafeAttributeAccess(_));
+}
+
+#[test]
+fn narrowing_to_wrong_type() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            resource.target is Album && resource.target.width > 100
+        };"#,
+    )
+    .unwrap();
+    assert_policy_typecheck_fails(schema_with_unions(), policy);
+}
+
+#[test]
+fn record_union_attributes() {
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when {
+            context.payload.kind == "counter" && context.payload has count && context.payload.count > 0
+        };"#,
+    )
+    .unwrap();
+    assert_policy_typechecks(schema_with_unions(), policy);
+
+    let policy = parse_policy(
+        Some(PolicyID::from_string("0")),
+        r#"permit(principal, action, resource) when { context.payload.label == "a" };"#,
+    )
+    .unwrap();
+    assert_policy_typecheck_fails(schema_with_unions(), policy);
+}
+
+#[test]
+fn entity_data_with_union_attribute() {
+    assert_entities_validate(
+        json!([
+            { "uid": { "type": "Share", "id": "a" }, "attrs": { "target": { "type": "Album", "id": "vacation" } }, "parents": [] },
+            { "uid": { "type": "Share", "id": "p" }, "attrs": { "target": { "type": "Photo", "id": "beach" } }, "parents": [] },
+        ]),
+        schema_with_unions(),
+    );
+    let err = assert_entities_do_not_validate(
+        json!([
+            { "uid": { "type": "Share", "id": "u" }, "attrs": { "target": { "type": "User", "id": "alice" } }, "parents": [] },
+        ]),
+        schema_with_unions(),
+    );
+    expect_err(
+        "",
+        &miette::Report::new(err),
+        &ExpectedErrorMessageBuilder::error(
+            "entity does not conform to the schema",
+        )
+        .source(
+            r#"in attribute `target` on `Share::"u"`, type mismatch: value was expected to have type `Album` | `Photo`, but it actually has type (entity of type `User`): `User::"alice"`"#,
+        )
+        .build(),
+    );
+}
@@ -33,11 +33,12 @@ use std::{
 
 use crate::{
     ast::{
-        BorrowedRestrictedExpr, EntityType, EntityUID, Name, PartialValue, RestrictedExpr, Value,
+        BorrowedRestrictedExpr, EntityType, EntityUID, Literal, Name, PartialValue, RestrictedExpr,
+        SlotType, Value, ValueKind,
     },
     entities::{
         conformance::typecheck_restricted_expr_against_schematype,
-        AttributeType as CoreAttributeType, SchemaType as CoreSchemaType,
+        AttributeType as CoreAttributeType, Refinement, SchemaType as CoreSchemaType,
     },
     extensions::{ExtensionFunctionLookupError, Extensions},
     validator::{validation_errors::LubHelp, ValidationMode},
@@ -87,6 +88,15 @@ pub enum Type {
         element_type: Option<Arc<Type>>,
     },
 
+    /// The type of maps from `String` keys to values of some type. At runtime,
+    /// maps are records whose attributes are the keys.
+    Map {
+        /// The type of the values in the map, or None if it represents an
+        /// arbitrary map type. As for `Set`, this should only be None when the
+        /// type is being used in a subtype comparison or for error reporting.
+        value_type: Option<Arc<Type>>,
+    },
+
     /// A record type
     Record {
         /// The attributes that we know must exist (or may exist in the case of
@@ -154,6 +164,17 @@ impl Type {
         }
     }
 
+    pub(crate) fn any_map() -> Type {
+        Type::Map { value_type: None }
+    }
+
+    /// The Map type, with `String` keys and the value type `vty`
+    pub fn map(vty: Type) -> Type {
+        Type::Map {
+            value_type: Some(Arc::new(vty)),
+        }
+    }
+
     pub(crate) fn any_record() -> Type {
         // OpenAttributes <: ClosedAttributes, so this makes `any_record` a
         // super type of all records.
@@ -206,6 +227,61 @@ impl Type {
         Type::ExtensionType { name }
     }
 
+    /// The type of values which may be provided for a template value slot
+    /// declared with type `ty`
+    pub(crate) fn from_slot_type(ty: &SlotType) -> Type {
+        match ty {
+            SlotType::Bool => Type::primitive_boolean(),
+            SlotType::Long => Type::primitive_long(),
+            SlotType::String => Type::primitive_string(),
+            SlotType::Set { element } => Type::set(Arc::new(Type::from_slot_type(element))),
+            SlotType::Record { attributes } => Type::record_with_required_attributes(
+                attributes
+                    .iter()
+                    .map(|(k, ty)| (k.clone(), Arc::new(Type::from_slot_type(ty)))),
+                OpenTag::ClosedAttributes,
+            ),
+            SlotType::Entity { name } => Type::named_entity_reference(name.clone()),
+            SlotType::Extension { name } => Type::extension(name.clone()),
+        }
+    }
+
+    /// The type of values which conform to the Core `SchemaType` `ty`, e.g.,
+    /// the values which may be substituted for an unknown declared with `ty`
+    pub(crate) fn from_core_schema_type(ty: &CoreSchemaType) -> Type {
+        match ty {
+            CoreSchemaType::Bool => Type::primitive_boolean(),
+            CoreSchemaType::Long => Type::primitive_long(),
+            CoreSchemaType::String => Type::primitive_string(),
+            CoreSchemaType::Set { element_ty } => {
+                Type::set(Arc::new(Type::from_core_schema_type(element_ty)))
+            }
+            CoreSchemaType::EmptySet => Type::any_set(),
+            CoreSchemaType::Map { value_ty } => Type::map(Type::from_core_schema_type(value_ty)),
+            CoreSchemaType::Record { attrs, open_attrs } => Type::record_with_attributes(
+                attrs.iter().map(|(k, attr)| {
+                    (
+                        k.clone(),
+                        AttributeType::new(
+                            Arc::new(Type::from_core_schema_type(attr.schema_type())),
+                            attr.is_required(),
+                        ),
+                    )
+                }),
+                if *open_attrs {
+                    OpenTag::OpenAttributes
+                } else {
+                    OpenTag::ClosedAttributes
+                },
+            ),
+            CoreSchemaType::Entity { ty } => Type::named_entity_reference(ty.clone()),
+            CoreSchemaType::EntityUnion { tys } => Type::Entity(EntityKind::Entity(EntityLUB {
+                lub_elements: tys.clone(),
+            })),
+            CoreSchemaType::Extension { name } => Type::extension(name.clone()),
+        }
+    }
+
     /// Implements a subtype relation for the type structure.
     pub(crate) fn is_subtype(ty0: &Type, ty1: &Type, mode: ValidationMode) -> bool {
         match (ty0, ty1) {
@@ -233,6 +309,15 @@ impl Type {
                 (None, None) => true,
             },
 
+            // Likewise for maps, whose values cannot be modified
+            (Type::Map { value_type: v_ty0 }, Type::Map { value_type: v_ty1 }) => {
+                match (v_ty0, v_ty1) {
+                    (Some(v_ty0), Some(v_ty1)) => Type::is_subtype(v_ty0, v_ty1, mode),
+                    (_, None) => true,
+                    (None, Some(_)) => false,
+                }
+            }
+
             (
                 Type::Record {
                     attrs: attrs0,
@@ -305,6 +390,19 @@ impl Type {
                 },
             ) => Ok(Type::set(Type::least_upper_bound(te0, te1, mode)?.into())),
 
+            // As for sets, the `None` cases are impossible due to the subtype
+            // checks above
+            (Type::Map { value_type: None }, Type::Map { .. })
+            | (Type::Map { .. }, Type::Map { value_type: None }) => Ok(Type::any_map()),
+            (
+                Type::Map {
+                    value_type: Some(tv0),
+                },
+                Type::Map {
+                    value_type: Some(tv1),
+                },
+            ) => Ok(Type::map(Type::least_upper_bound(tv0, tv1, mode)?)),
+
             (
                 Type::Record {
                     attrs: attrs0,
@@ -480,6 +578,9 @@ impl Type {
                 // that could have the EmptySet CoreSchemaType and that validator Set type.
                 matches!(self, Type::Set { .. })
             }
+            CoreSchemaType::Map { value_ty } => {
+                matches!(self, Type::Map { value_type: Some(value_type) } if value_type.is_consistent_with(value_ty))
+            }
             CoreSchemaType::Record {
                 attrs: core_attrs,
                 open_attrs: core_open,
@@ -533,6 +634,13 @@ impl Type {
                 },
                 _ => false,
             },
+            CoreSchemaType::EntityUnion { tys } => match self {
+                Type::Entity(kind) => match kind {
+                    EntityKind::Entity(lub) => !lub.lub_elements.is_disjoint(tys),
+                    EntityKind::AnyEntity => true,
+                },
+                _ => false,
+            },
             CoreSchemaType::Extension { name } => {
                 matches!(self, Type::ExtensionType { name: n } if name == n)
             }
@@ -564,15 +672,91 @@ impl Type {
     }
 
     /// Does the given `Value` have this validator type?
+    ///
+    /// This is checked on the `Value` directly, since it is on the path of
+    /// every request validation, except for extension values, which are
+    /// checked by converting them to a `RestrictedExpr`.
     pub(crate) fn typecheck_value(
         &self,
         value: &Value,
         extensions: &Extensions<'_>,
     ) -> Result<bool, ExtensionFunctionLookupError> {
-        // we accept the overhead of cloning the `Value` and converting to
-        // `RestrictedExpr` in order to improve code reuse and maintainability
-        let rexpr = RestrictedExpr::from(value.clone());
-        self.typecheck_restricted_expr(rexpr.as_borrowed(), extensions)
+        match (self, value.value_kind()) {
+            (Type::Bool(BoolType::AnyBool), ValueKind::Lit(Literal::Bool(_))) => Ok(true),
+            (Type::Bool(BoolType::True), ValueKind::Lit(Literal::Bool(b))) => Ok(*b),
+            (Type::Bool(BoolType::False), ValueKind::Lit(Literal::Bool(b))) => Ok(!*b),
+            (Type::Long, ValueKind::Lit(Literal::Long(_))) => Ok(true),
+            (Type::String, ValueKind::Lit(Literal::String(_))) => Ok(true),
+            (Type::Set { element_type: None }, ValueKind::Set(_)) => Ok(true),
+            (
+                Type::Set {
+                    element_type: Some(el_type),
+                },
+                ValueKind::Set(set),
+            ) => {
+                for elt in set.iter() {
+                    if !el_type.typecheck_value(elt, extensions)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            (Type::Map { value_type: None }, ValueKind::Record(_)) => Ok(true),
+            (
+                Type::Map {
+                    value_type: Some(v_type),
+                },
+                ValueKind::Record(record),
+            ) => {
+                for v in record.values() {
+                    if !v_type.typecheck_value(v, extensions)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            (Type::Entity(EntityKind::Entity(lub)), ValueKind::Lit(Literal::EntityUID(euid))) => {
+                Ok(lub.contains(euid.entity_type()))
+            }
+            (Type::Entity(EntityKind::AnyEntity), ValueKind::Lit(Literal::EntityUID(_))) => {
+                Ok(true)
+            }
+            (
+                Type::Record {
+                    attrs,
+                    open_attributes,
+                },
+                ValueKind::Record(record),
+            ) => {
+                for (k, attr_val) in record.iter() {
+                    match attrs.get_attr(k) {
+                        Some(attr_ty) => {
+                            if !attr_ty.attr_type.typecheck_value(attr_val, extensions)?
+                                || !attr_ty
+                                    .refinement
+                                    .as_ref()
+                                    .is_none_or(|refinement| refinement.admits_value(attr_val))
+                            {
+                                return Ok(false);
+                            }
+                        }
+                        None => {
+                            if open_attributes != &OpenTag::OpenAttributes {
+                                return Ok(false);
+                            }
+                        }
+                    }
+                }
+                Ok(attrs
+                    .iter()
+                    .all(|(k, attr_ty)| !attr_ty.is_required || record.contains_key(k)))
+            }
+            (Type::ExtensionType { .. }, ValueKind::ExtensionValue(_)) => {
+                let rexpr = RestrictedExpr::from(value.clone());
+                self.typecheck_restricted_expr(rexpr.as_borrowed(), extensions)
+            }
+            _ => Ok(false),
+        }
     }
 
     /// Does the given `BorrowedRestrictedExpr` have this validator type?
@@ -605,6 +789,20 @@ impl Type {
                 }
                 None => Ok(false),
             },
+            Type::Map { value_type: None } => Ok(restricted_expr.as_record_pairs().is_some()),
+            Type::Map {
+                value_type: Some(v_type),
+            } => match restricted_expr.as_record_pairs() {
+                Some(pairs) => {
+                    for (_, v) in pairs {
+                        if !v_type.typecheck_restricted_expr(v, extensions)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                None => Ok(false),
+            },
             Type::Entity(EntityKind::Entity(lub)) => match restricted_expr.as_euid() {
                 Some(euid) => Ok(lub.contains(euid.entity_type())),
                 None => Ok(false),
@@ -622,6 +820,10 @@ impl Type {
                                 if !attr_ty
                                     .attr_type
                                     .typecheck_restricted_expr(attr_val.to_owned(), extensions)?
+                                    || !attr_ty
+                                        .refinement
+                                        .as_ref()
+                                        .is_none_or(|refinement| refinement.admits(*attr_val))
                                 {
                                     return Ok(false);
                                 }
@@ -706,6 +908,10 @@ impl Display for Type {
                 Some(element_type) => write!(f, "Set<{element_type}>"),
                 None => write!(f, "Set<__cedar::internal::Any>"),
             },
+            Type::Map { value_type } => match value_type {
+                Some(value_type) => write!(f, "Map<String, {value_type}>"),
+                None => write!(f, "Map<String, __cedar::internal::Any>"),
+            },
             Type::Entity(EntityKind::AnyEntity) => {
                 write!(f, "__cedar::internal::AnyEntity")
             }
@@ -755,6 +961,14 @@ impl TryFrom<Type> for CoreSchemaType {
                 element_ty: Box::new(CoreSchemaType::try_from(element_type.as_ref().clone())?),
             }),
             Type::Set { element_type: None } => Ok(CoreSchemaType::EmptySet),
+            Type::Map {
+                value_type: Some(value_type),
+            } => Ok(CoreSchemaType::Map {
+                value_ty: Box::new(CoreSchemaType::try_from(value_type.as_ref().clone())?),
+            }),
+            Type::Map { value_type: None } => {
+                Err("map type without a value type is not representable in core::SchemaType".into())
+            }
             Type::Entity(kind @ EntityKind::AnyEntity) => Err(format!(
                 "any-entity type is not representable in core::SchemaType: {kind:?}"
             )),
@@ -772,18 +986,20 @@ impl TryFrom<Type> for CoreSchemaType {
                                 match v.is_required {
                                     true => CoreAttributeType::required(schema_type),
                                     false => CoreAttributeType::optional(schema_type),
-                                },
+                                }
+                                .with_default(v.default)
+                                .with_refinement(v.refinement),
                             ))
                         })
                         .collect::<Result<_, String>>()?
                 },
                 open_attrs: open_attributes.is_open(),
             }),
-            Type::Entity(EntityKind::Entity(lub)) => match lub.into_single_entity() {
-                Some(name) => Ok(CoreSchemaType::Entity { ty: name }),
-                None => Err(
-                    "non-singleton LUB type is not representable in core::SchemaType".to_string(),
-                ),
+            Type::Entity(EntityKind::Entity(lub)) => match lub.get_single_entity() {
+                Some(name) => Ok(CoreSchemaType::Entity { ty: name.clone() }),
+                None => Ok(CoreSchemaType::EntityUnion {
+                    tys: lub.lub_elements,
+                }),
             },
             Type::ExtensionType { name } => Ok(CoreSchemaType::Extension { name }),
         }
@@ -897,7 +1113,7 @@ impl EntityLUB {
     /// Generate the least upper bound of this [`EntityLUB`] and another. This
     /// returns an [`EntityLUB`] for the union of the entity types in both argument
     /// LUBs. The attributes of the LUB are not computed.
-    pub(crate) fn least_upper_bound(&self, other: &EntityLUB) -> EntityLUB {
+    pub fn least_upper_bound(&self, other: &EntityLUB) -> EntityLUB {
         EntityLUB {
             lub_elements: self
                 .lub_elements
@@ -921,7 +1137,7 @@ impl EntityLUB {
 
     /// An iterator over the entity type [`Name`]s in the set of entity types
     /// comprising this [`EntityLUB`].
-    pub(crate) fn iter(&self) -> impl Iterator<Item = &EntityType> {
+    pub fn iter(&self) -> impl Iterator<Item = &EntityType> {
         self.lub_elements.iter()
     }
 
@@ -1212,8 +1428,8 @@ impl EntityKind {
 }
 
 /// Contains the type of a record attribute and if the attribute is required.
-#[derive(Ord, PartialOrd, Educe, Debug, Clone)]
-#[educe(Eq, PartialEq, Hash)]
+#[derive(Educe, Debug, Clone)]
+#[educe(Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct AttributeType {
     /// The type of the attribute.
     pub attr_type: Arc<Type>,
@@ -1221,6 +1437,24 @@ pub struct AttributeType {
     /// True when the attribute must be present. False if it is optional, and so
     /// may not be present in a record or entity.
     pub is_required: bool,
+
+    /// Value an optional attribute takes when it is absent from an entity or
+    /// record. Default values don't affect typechecking, so they are ignored
+    /// when comparing attribute types.
+    #[educe(PartialEq(ignore), Hash(ignore), PartialOrd(ignore))]
+    pub default: Option<RestrictedExpr>,
+
+    /// `Some` if the attribute is annotated as `@deprecated` in the schema,
+    /// containing the annotation value (which may be empty). Like default
+    /// values, this is ignored when comparing attribute types.
+    #[educe(PartialEq(ignore), Hash(ignore), PartialOrd(ignore))]
+    pub deprecation: Option<SmolStr>,
+
+    /// Restriction on the values of a `Long` or `String` attribute beyond its
+    /// type. Like default values, this is ignored when comparing attribute
+    /// types.
+    #[educe(PartialEq(ignore), Hash(ignore), PartialOrd(ignore))]
+    pub refinement: Option<Refinement>,
     ///  Source location - if available
     #[cfg(feature = "extended-schema")]
     #[educe(Eq(ignore))]
@@ -1234,6 +1468,9 @@ impl AttributeType {
         Self {
             attr_type,
             is_required,
+            default: None,
+            deprecation: None,
+            refinement: None,
             #[cfg(feature = "extended-schema")]
             loc: None,
         }
@@ -1246,6 +1483,9 @@ impl AttributeType {
         Self {
             attr_type,
             is_required,
+            default: None,
+            deprecation: None,
+            refinement: None,
             loc,
         }
     }
@@ -1265,6 +1505,25 @@ impl AttributeType {
         self.is_required
     }
 
+    /// Set the value the attribute takes when it is absent
+    pub fn with_default(self, default: Option<RestrictedExpr>) -> Self {
+        Self { default, ..self }
+    }
+
+    /// Mark the attribute as deprecated (or not), with the value of its
+    /// `@deprecated` annotation
+    pub fn with_deprecation(self, deprecation: Option<SmolStr>) -> Self {
+        Self {
+            deprecation,
+            ..self
+        }
+    }
+
+    /// Restrict the values the attribute may take
+    pub fn with_refinement(self, refinement: Option<Refinement>) -> Self {
+        Self { refinement, ..self }
+    }
+
     /// Display just the type portion of the [`AttributeType`], ignoring the
     /// `is_required` flag
     fn display_type(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
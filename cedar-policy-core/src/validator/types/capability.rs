@@ -14,10 +14,10 @@
  * limitations under the License.
  */
 
-use smol_str::SmolStr;
+use smol_str::{SmolStr, ToSmolStr};
 use std::collections::BTreeSet;
 
-use crate::ast::{Expr, ExprShapeOnly};
+use crate::ast::{EntityType, Expr, ExprShapeOnly};
 
 /// A set of capabilities. Used to represent knowledge about attribute existence
 /// before and after evaluating an expression.
@@ -54,7 +54,8 @@ impl<'a> CapabilitySet<'a> {
 }
 
 /// Represent a single capability, which is an expression and some attribute that is
-/// known to exist for that expression.
+/// known to exist for that expression, or some entity type that the expression is
+/// known to have.
 #[derive(Eq, PartialEq, Debug, Clone, PartialOrd, Ord)]
 pub struct Capability<'a> {
     /// For this expression
@@ -63,8 +64,9 @@ pub struct Capability<'a> {
     ///
     /// This expression represents the attribute or tag name. It should have type string.
     /// Often this is a string constant, but in the case of tags it can be an expression.
+    /// For an entity type capability, this is the entity type as a string constant.
     attribute_or_tag: ExprShapeOnly<'a, ()>,
-    /// Is `attribute_or_tag` an attribute name or a tag name
+    /// Is `attribute_or_tag` an attribute name, a tag name, or an entity type
     kind: CapabilityKind,
 }
 
@@ -74,6 +76,11 @@ enum CapabilityKind {
     Attribute,
     /// This capability is for accessing tags
     Tag,
+    /// This capability is for accessing map keys
+    Key,
+    /// This capability is for treating the expression as an entity of a
+    /// specific entity type, after an `is` test
+    EntityType,
 }
 
 impl<'a> Capability<'a> {
@@ -106,4 +113,24 @@ impl<'a> Capability<'a> {
             kind: CapabilityKind::Tag,
         }
     }
+
+    /// Construct a new [`Capability`] stating that the map `on_expr` is known
+    /// to contain the key `key`
+    pub fn new_borrowed_key(on_expr: &'a Expr<()>, key: &'a Expr<()>) -> Self {
+        Self {
+            on_expr: ExprShapeOnly::new_from_borrowed(on_expr),
+            attribute_or_tag: ExprShapeOnly::new_from_borrowed(key),
+            kind: CapabilityKind::Key,
+        }
+    }
+
+    /// Construct a new [`Capability`] stating that the expression `on_expr` is
+    /// known to be an entity of type `entity_type`
+    pub fn new_entity_type(on_expr: &'a Expr<()>, entity_type: &EntityType) -> Self {
+        Self {
+            on_expr: ExprShapeOnly::new_from_borrowed(on_expr),
+            attribute_or_tag: ExprShapeOnly::new_from_owned(Expr::val(entity_type.to_smolstr())),
+            kind: CapabilityKind::EntityType,
+        }
+    }
 }
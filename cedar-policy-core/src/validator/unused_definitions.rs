@@ -0,0 +1,402 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Detection of schema declarations which are never used by a policy set
+
+use super::cedar_schema::fmt::referenced_type_names;
+use super::typecheck::PolicyCheck;
+use super::types::{EntityKind, RequestEnv, Type};
+use super::{json_schema, RawName, ValidationMode, Validator};
+use crate::ast::{EntityType, EntityUID, Expr, ExprKind, Literal, PolicySet};
+use smol_str::{format_smolstr, SmolStr, ToSmolStr};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// The entity types, actions, and entity attributes declared in a schema which
+/// are never used by the policies in a policy set, as computed by
+/// [`Validator::unused_definitions()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnusedDefinitions {
+    entity_types: BTreeSet<EntityType>,
+    actions: BTreeSet<EntityUID>,
+    attributes: BTreeMap<EntityType, BTreeSet<SmolStr>>,
+}
+
+impl UnusedDefinitions {
+    /// The entity types which no policy refers to, and which are not the type
+    /// of any expression in a policy
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityType> {
+        self.entity_types.iter()
+    }
+
+    /// The actions which no policy refers to or applies to
+    pub fn actions(&self) -> impl Iterator<Item = &EntityUID> {
+        self.actions.iter()
+    }
+
+    /// The attributes which no policy accesses (or checks for with `has`),
+    /// together with the entity type declaring them. Attributes of the unused
+    /// entity types are not included.
+    pub fn attributes(&self) -> impl Iterator<Item = (&EntityType, &SmolStr)> {
+        self.attributes
+            .iter()
+            .flat_map(|(ety, attrs)| attrs.iter().map(move |attr| (ety, attr)))
+    }
+
+    /// Is every entity type, action, and attribute used?
+    pub fn is_empty(&self) -> bool {
+        self.entity_types.is_empty() && self.actions.is_empty() && self.attributes.is_empty()
+    }
+}
+
+/// The declarations which are used by a policy set
+#[derive(Debug, Default)]
+struct Usage {
+    entity_types: HashSet<EntityType>,
+    actions: HashSet<EntityUID>,
+    attributes: HashSet<(EntityType, SmolStr)>,
+}
+
+impl Usage {
+    /// Record the entity types and actions mentioned by name in `e`
+    fn mention_literals<T>(&mut self, e: &Expr<T>) {
+        for sub in e.subexpressions() {
+            match sub.expr_kind() {
+                ExprKind::Lit(Literal::EntityUID(uid)) => {
+                    self.entity_types.insert(uid.entity_type().clone());
+                    if uid.is_action() {
+                        self.actions.insert(uid.as_ref().clone());
+                    }
+                }
+                ExprKind::Is { entity_type, .. } => {
+                    self.entity_types.insert(entity_type.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record the entity types appearing in `ty`. The attributes of record
+    /// types are not included, since accessing them produces expressions of
+    /// their own.
+    fn use_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Entity(EntityKind::Entity(lub)) => {
+                self.entity_types.extend(lub.iter().cloned());
+            }
+            Type::Set {
+                element_type: Some(element_type),
+            }
+            | Type::Map {
+                value_type: Some(element_type),
+            } => self.use_type(element_type),
+            _ => {}
+        }
+    }
+
+    /// Record the types of the expressions in the typechecked policy `e`, and
+    /// the entity attributes it accesses
+    fn use_typed_expr(&mut self, e: &Expr<Option<Type>>) {
+        for sub in e.subexpressions() {
+            if let Some(ty) = sub.data() {
+                self.use_type(ty);
+            }
+            if let ExprKind::GetAttr { expr, attr } | ExprKind::HasAttr { expr, attr } =
+                sub.expr_kind()
+            {
+                if let Some(Type::Entity(EntityKind::Entity(lub))) = expr.data() {
+                    self.attributes
+                        .extend(lub.iter().map(|ety| (ety.clone(), attr.clone())));
+                }
+            }
+        }
+    }
+}
+
+impl Validator {
+    /// Find the entity types, actions, and entity attributes declared in the
+    /// schema which are never used by the policies in `policies`.
+    ///
+    /// An entity type is used if a policy mentions it (in an entity literal or
+    /// an `is` test), or if a policy applies to requests where an expression
+    /// has that type, e.g., the principal types of the actions the policy
+    /// applies to. An action is used if a policy mentions it or applies to
+    /// requests for it. An attribute is used if a policy accesses it, or
+    /// checks for it with `has`, on an entity of the declaring type. Policies
+    /// which fail to typecheck for a request environment use nothing in that
+    /// environment other than what they mention.
+    pub fn unused_definitions(&self, policies: &PolicySet) -> UnusedDefinitions {
+        let typechecker = self.typechecker(ValidationMode::Permissive);
+        let mut usage = Usage::default();
+        for t in policies.all_templates() {
+            usage.mention_literals(&t.condition());
+            for (env, check) in typechecker.typecheck_by_request_env(t) {
+                let PolicyCheck::Success(e) = check else {
+                    continue;
+                };
+                if let RequestEnv::DeclaredAction {
+                    principal,
+                    action,
+                    resource,
+                    ..
+                } = env
+                {
+                    usage.entity_types.insert(principal.clone());
+                    usage.entity_types.insert(resource.clone());
+                    usage.actions.insert(action.clone());
+                }
+                usage.use_typed_expr(&e);
+            }
+        }
+        for p in policies.policies() {
+            usage
+                .entity_types
+                .extend(p.env().values().map(|uid| uid.entity_type().clone()));
+        }
+
+        let entity_types = self
+            .schema
+            .entity_type_names()
+            .filter(|ety| !usage.entity_types.contains(*ety))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let actions = self
+            .schema
+            .actions()
+            .filter(|action| !usage.actions.contains(*action))
+            .cloned()
+            .collect();
+        let attributes = self
+            .schema
+            .entity_types()
+            .filter(|ety| !entity_types.contains(ety.name()))
+            .filter_map(|ety| {
+                let unused = ety
+                    .attributes()
+                    .iter()
+                    .map(|(attr, _)| attr)
+                    .filter(|attr| {
+                        !usage
+                            .attributes
+                            .contains(&(ety.name().clone(), (*attr).clone()))
+                    })
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                (!unused.is_empty()).then(|| (ety.name().clone(), unused))
+            })
+            .collect();
+        UnusedDefinitions {
+            entity_types,
+            actions,
+            attributes,
+        }
+    }
+}
+
+impl json_schema::Fragment<RawName> {
+    /// Find the common types declared in this schema fragment which are never
+    /// used, directly or through other common types, by the declaration of an
+    /// entity type or action context. Policies never refer to common types, so
+    /// unlike [`Validator::unused_definitions()`] this only needs the schema.
+    ///
+    /// Returns the fully qualified names of the unused common types.
+    pub fn unused_common_types(&self) -> BTreeSet<SmolStr> {
+        let qualify = |ns: Option<&crate::ast::Name>, name: &str| match ns {
+            Some(ns) => format_smolstr!("{ns}::{name}"),
+            None => SmolStr::new(name),
+        };
+        // A reference which isn't fully qualified may refer to a common type in
+        // its own namespace or in the empty namespace, so we consider both used
+        let resolve = |ns: Option<&crate::ast::Name>, reference: &SmolStr| {
+            if reference.contains("::") {
+                vec![reference.clone()]
+            } else {
+                vec![qualify(ns, reference), reference.clone()]
+            }
+        };
+
+        let mut pending = Vec::new();
+        for (ns, def) in &self.0 {
+            let mut references = Vec::new();
+            for ety in def.entity_types.values() {
+                if let json_schema::EntityTypeKind::Standard(ety) = &ety.kind {
+                    referenced_type_names(&ety.shape.0, &mut references);
+                    if let Some(tags) = &ety.tags {
+                        referenced_type_names(tags, &mut references);
+                    }
+                }
+            }
+            for action in def.actions.values() {
+                if let Some(applies_to) = &action.applies_to {
+                    referenced_type_names(&applies_to.context.0, &mut references);
+                }
+            }
+            pending.extend(
+                references
+                    .iter()
+                    .flat_map(|reference| resolve(ns.as_ref(), reference)),
+            );
+        }
+
+        let common_types = self
+            .0
+            .iter()
+            .flat_map(|(ns, def)| {
+                def.common_types
+                    .iter()
+                    .map(move |(name, ty)| (qualify(ns.as_ref(), &name.to_smolstr()), (ns, ty)))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut used = BTreeSet::new();
+        while let Some(name) = pending.pop() {
+            let Some((ns, ty)) = common_types.get(&name) else {
+                continue;
+            };
+            if used.insert(name) {
+                let mut references = Vec::new();
+                referenced_type_names(&ty.ty, &mut references);
+                pending.extend(
+                    references
+                        .iter()
+                        .flat_map(|reference| resolve(ns.as_ref(), reference)),
+                );
+            }
+        }
+        common_types
+            .into_keys()
+            .filter(|name| !used.contains(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extensions::Extensions;
+    use crate::parser::parse_policyset;
+    use crate::validator::ValidatorSchema;
+
+    fn unused(schema: &str, policies: &str) -> UnusedDefinitions {
+        let (schema, _) =
+            ValidatorSchema::from_cedarschema_str(schema, Extensions::all_available())
+                .expect("schema should parse");
+        let policies = parse_policyset(policies).expect("policies should parse");
+        Validator::new(schema).unused_definitions(&policies)
+    }
+
+    const SCHEMA: &str = r#"
+        entity Group;
+        entity User in [Group] { name: String, email: String, legacyId: Long };
+        entity Photo { owner: User, private: Bool };
+        entity Album;
+        entity Legacy;
+        action view appliesTo { principal: User, resource: Photo };
+        action share appliesTo { principal: User, resource: Photo };
+        action archive appliesTo { principal: User, resource: Album };
+    "#;
+
+    #[test]
+    fn reports_unused_definitions() {
+        let unused = unused(
+            SCHEMA,
+            r#"permit(principal, action == Action::"view", resource)
+               when { resource.owner == principal && principal.name != "" };"#,
+        );
+        assert_eq!(
+            unused
+                .entity_types()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["Album", "Group", "Legacy"]
+        );
+        assert_eq!(
+            unused
+                .actions()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [r#"Action::"archive""#, r#"Action::"share""#]
+        );
+        assert_eq!(
+            unused
+                .attributes()
+                .map(|(ety, attr)| format!("{ety}.{attr}"))
+                .collect::<Vec<_>>(),
+            ["Photo.private", "User.email", "User.legacyId"]
+        );
+    }
+
+    #[test]
+    fn unconstrained_policies_use_everything_they_apply_to() {
+        let unused = unused(
+            SCHEMA,
+            r#"permit(principal in Group::"admins", action, resource)
+               when { resource has private || principal has email };
+               forbid(principal, action, resource is Album)
+               when { principal.name like "*" && principal.legacyId > 0 };
+               permit(principal, action, resource)
+               when { resource has owner && resource.owner has name };
+               forbid(principal in Legacy::"l", action, resource);"#,
+        );
+        assert_eq!(unused, UnusedDefinitions::default());
+        assert!(unused.is_empty());
+    }
+
+    #[test]
+    fn unused_common_types() {
+        let fragment: json_schema::Fragment<RawName> = serde_json::from_value(serde_json::json!({
+            "": {
+                "commonTypes": {
+                    "Name": { "type": "String" },
+                    "Stale": { "type": "Long" }
+                },
+                "entityTypes": {},
+                "actions": {}
+            },
+            "App": {
+                "commonTypes": {
+                    "Address": {
+                        "type": "Record",
+                        "attributes": { "street": { "type": "Street" } }
+                    },
+                    "Street": { "type": "String" },
+                    "Orphan": {
+                        "type": "Record",
+                        "attributes": { "street": { "type": "Street" } }
+                    }
+                },
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "address": { "type": "Address" },
+                                "name": { "type": "Name" }
+                            }
+                        }
+                    }
+                },
+                "actions": {}
+            }
+        }))
+        .expect("fragment should parse");
+        assert_eq!(
+            fragment
+                .unused_common_types()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["App::Orphan", "Stale"]
+        );
+    }
+}
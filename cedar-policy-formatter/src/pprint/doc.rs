@@ -860,6 +860,154 @@ impl Doc for Node<Option<Annotation>> {
     }
 }
 
+impl Doc for Node<Option<TemplateHeader>> {
+    fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
+        let header = self.as_inner()?;
+        let keyword_doc = header.keyword.to_doc(context)?;
+        let lp_doc = add_comment(
+            RcDoc::text("("),
+            get_comment_after_end(
+                header.keyword.loc.as_ref().map(|loc| loc.span),
+                &mut context.tokens,
+            )?,
+            RcDoc::nil(),
+        );
+        let mut slots_doc = RcDoc::nil();
+        for (i, slot) in header.slots.iter().enumerate() {
+            let slot_doc = slot.to_doc(context)?;
+            let sep = if i + 1 == header.slots.len() {
+                ")"
+            } else {
+                ","
+            };
+            slots_doc = slots_doc.append(slot_doc).append(add_comment(
+                RcDoc::text(sep),
+                get_comment_after_end(slot.loc.as_ref().map(|loc| loc.span), &mut context.tokens)?,
+                if sep == "," {
+                    RcDoc::line()
+                } else {
+                    RcDoc::nil()
+                },
+            ));
+        }
+        Some(
+            keyword_doc
+                .append(lp_doc)
+                .append(slots_doc.nest(context.config.indent_width).group())
+                .append(RcDoc::space())
+                .append(add_comment(
+                    RcDoc::text("=>"),
+                    get_comment_at_end(self.loc.as_ref().map(|loc| loc.span), &mut context.tokens)?,
+                    RcDoc::hardline(),
+                )),
+        )
+    }
+}
+
+impl Doc for Node<Option<SlotDecl>> {
+    fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
+        let decl = self.as_inner()?;
+        Some(
+            decl.slot
+                .to_doc(context)?
+                .append(add_comment(
+                    RcDoc::text(":"),
+                    get_comment_after_end(
+                        decl.slot.loc.as_ref().map(|loc| loc.span),
+                        &mut context.tokens,
+                    )?,
+                    RcDoc::space(),
+                ))
+                .append(decl.ty.to_doc(context)?),
+        )
+    }
+}
+
+impl Doc for Node<Option<SlotType>> {
+    fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
+        match self.as_inner()? {
+            SlotType::Name(name) => name.to_doc(context),
+            SlotType::Generic(name, arg) => Some(
+                name.to_doc(context)?
+                    .append(add_comment(
+                        RcDoc::text("<"),
+                        get_comment_after_end(
+                            name.loc.as_ref().map(|loc| loc.span),
+                            &mut context.tokens,
+                        )?,
+                        RcDoc::nil(),
+                    ))
+                    .append(arg.to_doc(context)?)
+                    .append(add_comment(
+                        RcDoc::text(">"),
+                        get_comment_at_end(
+                            self.loc.as_ref().map(|loc| loc.span),
+                            &mut context.tokens,
+                        )?,
+                        RcDoc::nil(),
+                    )),
+            ),
+            SlotType::Record(attrs) => {
+                let lb_doc = add_comment(
+                    RcDoc::text("{"),
+                    get_comment_at_start(
+                        self.loc.as_ref().map(|loc| loc.span),
+                        &mut context.tokens,
+                    )?,
+                    RcDoc::nil(),
+                );
+                let mut attrs_doc = RcDoc::nil();
+                for (i, attr) in attrs.iter().enumerate() {
+                    attrs_doc = attrs_doc.append(attr.to_doc(context)?);
+                    if i + 1 < attrs.len() {
+                        attrs_doc = attrs_doc.append(add_comment(
+                            RcDoc::text(","),
+                            get_comment_after_end(
+                                attr.loc.as_ref().map(|loc| loc.span),
+                                &mut context.tokens,
+                            )?,
+                            RcDoc::space(),
+                        ));
+                    }
+                }
+                let rb_doc = add_comment(
+                    RcDoc::text("}"),
+                    get_comment_at_end(self.loc.as_ref().map(|loc| loc.span), &mut context.tokens)?,
+                    RcDoc::nil(),
+                );
+                Some(if attrs.is_empty() {
+                    lb_doc.append(rb_doc)
+                } else {
+                    lb_doc
+                        .append(RcDoc::space())
+                        .append(attrs_doc)
+                        .append(RcDoc::space())
+                        .append(rb_doc)
+                })
+            }
+        }
+    }
+}
+
+impl Doc for Node<Option<SlotTypeAttr>> {
+    fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
+        let attr = self.as_inner()?;
+        Some(
+            attr.key
+                .to_doc(context)?
+                .append(add_comment(
+                    RcDoc::text(":"),
+                    get_comment_after_end(
+                        attr.key.loc.as_ref().map(|loc| loc.span),
+                        &mut context.tokens,
+                    )?,
+                    RcDoc::space(),
+                ))
+                .append(attr.ty.to_doc(context)?),
+        )
+    }
+}
+
 impl Doc for Node<Option<Ident>> {
     fn to_doc<'src>(&self, context: &mut Context<'_, 'src>) -> Option<RcDoc<'src>> {
         Some(add_comment(
@@ -883,6 +1031,10 @@ impl Doc for Node<Option<Policy>> {
             policy.annotations.iter().map(|a| a.to_doc(context)),
             RcDoc::nil(),
         );
+        let header_doc = match &policy.header {
+            Some(header) => header.to_doc(context)?,
+            None => RcDoc::nil(),
+        };
         let eff_leading_comment = get_leading_comment_at_start(
             policy.effect.loc.as_ref().map(|loc| loc.span),
             &mut context.tokens,
@@ -951,6 +1103,7 @@ impl Doc for Node<Option<Policy>> {
             RcDoc::intersperse(conds.iter().map(|c| c.to_doc(context)), RcDoc::hardline());
         Some(
             anno_doc
+                .append(header_doc)
                 .append(
                     get_leading_comment_doc_from_str(&eff_leading_comment).append(
                         eff_doc
@@ -19,6 +19,7 @@ use std::collections::BTreeMap;
 use miette::{miette, Result, WrapErr};
 
 use cedar_policy_core::ast::PolicySet;
+use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::parser::parse_policyset;
 use cedar_policy_core::parser::text_to_cst::parse_policies;
 use smol_str::ToSmolStr;
@@ -109,7 +110,9 @@ fn soundness_check(ps: &str, ast: &PolicySet) -> Result<()> {
 
 pub fn policies_str_to_pretty(ps: &str, config: &Config) -> Result<String> {
     let cst = parse_policies(ps).wrap_err("cannot parse input policies")?;
-    let ast = cst.to_policyset().wrap_err("cannot parse input policies")?;
+    let ast = cst
+        .to_policyset(Extensions::all_available())
+        .wrap_err("cannot parse input policies")?;
     let (tokens, end_of_file_comment) =
         get_token_stream(ps).ok_or_else(|| miette!("cannot get token stream"))?;
     let mut context = config::Context { config, tokens };
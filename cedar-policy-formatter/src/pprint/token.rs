@@ -153,6 +153,9 @@ pub enum Token {
     #[token("?resource")]
     ResourceSlot,
 
+    #[regex(r"\?[_a-zA-Z][_a-zA-Z0-9]*", |lex| SmolStr::new(lex.slice()))]
+    ValueSlot(SmolStr),
+
     #[regex(r"[_a-zA-Z][_a-zA-Z0-9]*", |lex| SmolStr::new(lex.slice()))]
     Identifier(SmolStr),
 
@@ -216,6 +219,9 @@ pub enum Token {
     #[token(">=")]
     Ge,
 
+    #[token("=>")]
+    Arrow,
+
     #[token("||")]
     Or,
 
@@ -247,6 +253,7 @@ impl fmt::Display for Token {
             Self::Action => write!(f, "action"),
             Self::Add => write!(f, "+"),
             Self::And => write!(f, "&&"),
+            Self::Arrow => write!(f, "=>"),
             Self::At => write!(f, "@"),
             Self::Colon => write!(f, ":"),
             Self::Comma => write!(f, ","),
@@ -296,6 +303,7 @@ impl fmt::Display for Token {
             Self::Then => write!(f, "then"),
             Self::True => write!(f, "true"),
             Self::Unless => write!(f, "unless"),
+            Self::ValueSlot(s) => write!(f, "{s}"),
             Self::When => write!(f, "when"),
             #[expect(
                 clippy::unreachable,
@@ -0,0 +1,439 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of `#[derive(CedarEntity)]` and `#[derive(CedarContext)]`.
+//! The generated code uses the same helpers from `cedar_policy::codegen` as
+//! the code generated from schemas.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Type};
+
+/// A field of a derived struct, with its `#[cedar(...)]` options
+struct CedarField {
+    ident: Ident,
+    ty: Type,
+    /// The name of the Cedar attribute
+    attr: String,
+    role: Role,
+    /// The Cedar schema type given with `#[cedar(ty = "...")]`
+    schema_type: Option<String>,
+}
+
+/// What a field becomes in Cedar
+#[derive(PartialEq, Eq)]
+enum Role {
+    Attribute,
+    /// The id of the entity's uid
+    Id,
+    /// The parents of the entity
+    Parents,
+    /// Not converted
+    Skip,
+}
+
+impl CedarField {
+    fn new(field: &Field) -> syn::Result<Self> {
+        let Some(ident) = field.ident.clone() else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "only structs with named fields are supported",
+            ));
+        };
+        let mut this = Self {
+            attr: ident.to_string().trim_start_matches("r#").to_string(),
+            ident,
+            ty: field.ty.clone(),
+            role: Role::Attribute,
+            schema_type: None,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("cedar")) {
+            attr.parse_nested_meta(|meta| {
+                let role = if meta.path.is_ident("rename") {
+                    this.attr = meta.value()?.parse::<LitStr>()?.value();
+                    return Ok(());
+                } else if meta.path.is_ident("ty") {
+                    this.schema_type = Some(meta.value()?.parse::<LitStr>()?.value());
+                    return Ok(());
+                } else if meta.path.is_ident("id") {
+                    Role::Id
+                } else if meta.path.is_ident("parents") {
+                    Role::Parents
+                } else if meta.path.is_ident("skip") {
+                    Role::Skip
+                } else {
+                    return Err(meta.error(
+                        "expected `rename = \"...\"`, `ty = \"...\"`, `id`, `parents`, or `skip`",
+                    ));
+                };
+                if this.role != Role::Attribute {
+                    return Err(
+                        meta.error("a field can have only one of `id`, `parents`, and `skip`")
+                    );
+                }
+                this.role = role;
+                Ok(())
+            })?;
+        }
+        Ok(this)
+    }
+
+    /// The entry for this attribute in the list passed to
+    /// `cedar_policy::codegen::{entity, context, record}`
+    fn to_field_entry(&self) -> TokenStream {
+        let ident = &self.ident;
+        let attr = &self.attr;
+        if option_inner(&self.ty).is_some() {
+            quote! {
+                (#attr, #ident.map(::cedar_policy::codegen::CedarValue::into_restricted_expression))
+            }
+        } else {
+            quote! {
+                (#attr, ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(#ident)))
+            }
+        }
+    }
+
+    /// The declaration of this attribute in a Cedar schema
+    fn schema_decl(&self) -> syn::Result<String> {
+        let (ty, optional) = match option_inner(&self.ty) {
+            Some(inner) => (inner, "?"),
+            None => (&self.ty, ""),
+        };
+        let schema_type = match &self.schema_type {
+            Some(schema_type) => schema_type.clone(),
+            None => infer_schema_type(ty).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    ty,
+                    "can't infer the Cedar type of this field; declare it with `#[cedar(ty = \"...\")]`",
+                )
+            })?,
+        };
+        Ok(format!("{:?}{optional}: {schema_type}", self.attr))
+    }
+}
+
+/// The fields of a struct, or an error if `input` isn't a struct with named
+/// fields
+fn fields(input: &DeriveInput) -> syn::Result<Vec<CedarField>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(CedarField::new).collect(),
+            Fields::Unit => Ok(Vec::new()),
+            Fields::Unnamed(_) => Err(syn::Error::new_spanned(
+                input,
+                "only structs with named fields are supported",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(input, "only structs are supported")),
+    }
+}
+
+/// The value of the struct-level option `#[cedar(key = "...")]`, if present
+fn struct_option(input: &DeriveInput, key: &str) -> syn::Result<Option<String>> {
+    let mut value = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("cedar")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) {
+                value = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error(format!("expected `{key} = \"...\"`")))
+            }
+        })?;
+    }
+    Ok(value)
+}
+
+/// `let Self { a, b, .. } = self;`, binding every field which isn't skipped
+fn destructure(fields: &[CedarField]) -> TokenStream {
+    let idents = fields
+        .iter()
+        .filter(|f| f.role != Role::Skip)
+        .map(|f| &f.ident);
+    quote! { let Self { #(#idents,)* .. } = self; }
+}
+
+/// The schema declaration `decl` of the unqualified `name`, in a namespace
+/// block if `name` is in a namespace
+fn in_namespace(name: &str, decl: impl FnOnce(&str) -> String) -> String {
+    match name.rsplit_once("::") {
+        Some((namespace, basename)) => format!("namespace {namespace} {{ {} }}", decl(basename)),
+        None => decl(name),
+    }
+}
+
+pub fn derive_entity(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields(input)?;
+    let entity_type =
+        struct_option(input, "entity_type")?.unwrap_or_else(|| input.ident.to_string());
+    let mut ids = fields.iter().filter(|f| f.role == Role::Id);
+    let Some(id) = ids.next() else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "an entity needs a field marked `#[cedar(id)]`",
+        ));
+    };
+    if let Some(extra) = ids.next() {
+        return Err(syn::Error::new_spanned(
+            &extra.ident,
+            "only one field can be marked `#[cedar(id)]`",
+        ));
+    }
+    let mut parents = fields.iter().filter(|f| f.role == Role::Parents);
+    let parents_field = parents.next();
+    if let Some(extra) = parents.next() {
+        return Err(syn::Error::new_spanned(
+            &extra.ident,
+            "only one field can be marked `#[cedar(parents)]`",
+        ));
+    }
+
+    let attrs = fields.iter().filter(|f| f.role == Role::Attribute);
+    let entries = attrs.clone().map(CedarField::to_field_entry);
+    let decls = attrs
+        .map(CedarField::schema_decl)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let parent_types = parents_field.and_then(|f| f.schema_type.as_deref());
+    let schema = in_namespace(&entity_type, |basename| {
+        let parents = parent_types.map_or_else(String::new, |ty| format!(" in [{ty}]"));
+        format!("entity {basename}{parents} {{ {} }};", decls.join(", "))
+    });
+
+    let id_ident = &id.ident;
+    let parents_expr = match parents_field {
+        Some(f) => {
+            let ident = &f.ident;
+            quote! { ::std::iter::IntoIterator::into_iter(#ident).map(::std::convert::Into::into) }
+        }
+        None => quote! { ::std::iter::empty() },
+    };
+    let destructure = destructure(&fields);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Name of the entity type
+            pub const ENTITY_TYPE: &'static str = #entity_type;
+
+            /// Declaration of the entity type in the Cedar schema format
+            pub const CEDAR_SCHEMA: &'static str = #schema;
+
+            /// Uid of the entity of this type with id `id`
+            pub fn uid(id: &str) -> ::cedar_policy::EntityUid {
+                ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+            }
+
+            /// Create the entity with these attributes
+            pub fn into_entity(self) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {
+                #destructure
+                ::cedar_policy::codegen::entity(
+                    Self::ENTITY_TYPE,
+                    ::std::convert::AsRef::<str>::as_ref(&#id_ident),
+                    ::std::vec![#(#entries),*],
+                    #parents_expr,
+                )
+            }
+        }
+
+        impl #impl_generics ::std::convert::TryFrom<#name #ty_generics> for ::cedar_policy::Entity #where_clause {
+            type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+            fn try_from(value: #name #ty_generics) -> ::std::result::Result<Self, Self::Error> {
+                value.into_entity()
+            }
+        }
+    })
+}
+
+pub fn derive_context(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields(input)?;
+    if let Some(f) = fields
+        .iter()
+        .find(|f| matches!(f.role, Role::Id | Role::Parents))
+    {
+        return Err(syn::Error::new_spanned(
+            &f.ident,
+            "`id` and `parents` are only supported on entities",
+        ));
+    }
+    let type_name = struct_option(input, "type_name")?.unwrap_or_else(|| input.ident.to_string());
+    let attrs = fields.iter().filter(|f| f.role == Role::Attribute);
+    let entries = attrs
+        .clone()
+        .map(CedarField::to_field_entry)
+        .collect::<Vec<_>>();
+    let decls = attrs
+        .clone()
+        .map(CedarField::schema_decl)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let schema = in_namespace(&type_name, |basename| {
+        format!("type {basename} = {{ {} }};", decls.join(", "))
+    });
+    let getters = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let attr = &f.attr;
+        match f.role {
+            Role::Skip => quote! { #ident: ::std::default::Default::default() },
+            _ if option_inner(&f.ty).is_some() => {
+                quote! { #ident: ::cedar_policy::codegen::optional_attr(record, #attr)? }
+            }
+            _ => quote! { #ident: ::cedar_policy::codegen::attr(record, #attr)? },
+        }
+    });
+    let expect_record = if fields.iter().all(|f| f.role == Role::Skip) {
+        quote! { ::cedar_policy::codegen::expect_record(value)?; }
+    } else {
+        quote! { let record = ::cedar_policy::codegen::expect_record(value)?; }
+    };
+    let destructure = destructure(&fields);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Declaration of this record type in the Cedar schema format
+            pub const CEDAR_SCHEMA: &'static str = #schema;
+
+            /// Create a request context with these attributes
+            pub fn into_context(self) -> ::std::result::Result<::cedar_policy::Context, ::cedar_policy::ContextCreationError> {
+                #destructure
+                ::cedar_policy::codegen::context(::std::vec![#(#entries),*])
+            }
+        }
+
+        impl #impl_generics ::cedar_policy::codegen::CedarValue for #name #ty_generics #where_clause {
+            fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+                #destructure
+                ::cedar_policy::codegen::record(::std::vec![#(#entries),*])
+            }
+
+            fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+                #expect_record
+                ::std::result::Result::Ok(Self { #(#getters),* })
+            }
+        }
+
+        impl #impl_generics ::std::convert::TryFrom<#name #ty_generics> for ::cedar_policy::Context #where_clause {
+            type Error = ::cedar_policy::ContextCreationError;
+
+            fn try_from(value: #name #ty_generics) -> ::std::result::Result<Self, Self::Error> {
+                value.into_context()
+            }
+        }
+    })
+}
+
+/// The `T` of `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let (name, inner) = single_generic(ty)?;
+    (name == "Option").then_some(inner)
+}
+
+/// The name and generic argument of a type with exactly one generic type
+/// argument, like `Vec<T>`
+fn single_generic(ty: &Type) -> Option<(String, &Type)> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => {
+            Some((segment.ident.to_string(), inner))
+        }
+        _ => None,
+    }
+}
+
+/// The Cedar schema type of the Rust types `cedar_policy::codegen` converts,
+/// by their name
+fn infer_schema_type(ty: &Type) -> Option<String> {
+    if let Some((name, inner)) = single_generic(ty) {
+        return match name.as_str() {
+            "Vec" => Some(format!("Set<{}>", infer_schema_type(inner)?)),
+            _ => None,
+        };
+    }
+    let Type::Path(path) = ty else { return None };
+    let name = path.path.segments.last()?.ident.to_string();
+    let schema_type = match name.as_str() {
+        "bool" => "Bool",
+        "i64" => "Long",
+        "String" => "String",
+        "IpAddr" => "ipaddr",
+        "Decimal" => "decimal",
+        "Datetime" => "datetime",
+        "Duration" => "duration",
+        _ => return None,
+    };
+    Some(schema_type.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schema() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[cedar(entity_type = "App::User")]
+            struct User {
+                #[cedar(id)]
+                name: String,
+                #[cedar(parents, ty = "Group")]
+                groups: Vec<EntityUid>,
+                age: Option<i64>,
+                #[cedar(rename = "ip")]
+                addr: std::net::IpAddr,
+                tags: Vec<String>,
+                #[cedar(ty = "User")]
+                manager: EntityUid,
+                #[cedar(skip)]
+                cache: (),
+            }
+        };
+        let tokens = derive_entity(&input).unwrap().to_string();
+        assert!(
+            tokens.contains(
+                r#""namespace App { entity User in [Group] { \"age\"?: Long, \"ip\": ipaddr, \"tags\": Set<String>, \"manager\": User }; }""#
+            ),
+            "{tokens}"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |input: DeriveInput| derive_entity(&input).unwrap_err().to_string();
+        assert_eq!(
+            err(syn::parse_quote! { struct A { x: String } }),
+            "an entity needs a field marked `#[cedar(id)]`"
+        );
+        assert_eq!(
+            err(syn::parse_quote! { struct A { #[cedar(id)] x: String, y: EntityUid } }),
+            "can't infer the Cedar type of this field; declare it with `#[cedar(ty = \"...\")]`"
+        );
+        assert_eq!(
+            err(syn::parse_quote! { struct A { #[cedar(id, skip)] x: String } }),
+            "a field can have only one of `id`, `parents`, and `skip`"
+        );
+        assert_eq!(
+            derive_context(&syn::parse_quote! { struct A { #[cedar(id)] x: String } })
+                .unwrap_err()
+                .to_string(),
+            "`id` and `parents` are only supported on entities"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Macros for embedding Cedar policies in Rust programs, and for converting
+//! Rust types into Cedar entities and contexts.
+//!
+//! [`include_policyset!`] parses, and optionally validates, policy files when
+//! the program is compiled, so that invalid bundled policies fail the build
+//! rather than startup.
+//!
+//! [`CedarEntity`](derive@CedarEntity) and [`CedarContext`](derive@CedarContext)
+//! derive conversions from Rust structs into `cedar_policy::Entity` and
+//! `cedar_policy::Context`.
+
+mod derive;
+
+use cedar_policy::{PolicySet, Schema, ValidationMode, Validator};
+use miette::{Diagnostic, NarratableReportHandler, Report};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, DeriveInput, Ident, LitStr, Token};
+
+/// Embed the policies in the files matching a glob pattern as a
+/// `cedar_policy::PolicySet`.
+///
+/// ```ignore
+/// let policies: cedar_policy::PolicySet =
+///     cedar_policy_macros::include_policyset!("policies/*.cedar");
+/// ```
+///
+/// The pattern is relative to the directory of the crate's `Cargo.toml`, and
+/// matching files are concatenated in lexicographic order of their paths.
+/// It is a compile-time error if no file matches or if any file fails to
+/// parse. Policies are assigned ids as by `PolicySet::from_str()`.
+///
+/// With a `schema` argument, which is the path of a schema in the Cedar
+/// schema format or, if it ends with `.json`, the JSON schema format, the
+/// policies are also validated in strict mode, and it is a compile-time error
+/// if validation fails:
+///
+/// ```ignore
+/// let policies = cedar_policy_macros::include_policyset!(
+///     "policies/*.cedar",
+///     schema = "policies/schema.cedarschema",
+/// );
+/// ```
+///
+/// The files are embedded with `include_str!()`, so the program is rebuilt
+/// when they change. (New files matching the pattern are not picked up until
+/// the program is rebuilt for another reason.) The policies are parsed again
+/// when the expression is evaluated, which can't fail; store the result, e.g.,
+/// in a `std::sync::LazyLock`, to parse them only once.
+#[proc_macro]
+pub fn include_policyset(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as Args);
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    expand(&args, &manifest_dir)
+        .unwrap_or_else(|err| syn::Error::new(Span::call_site(), err).to_compile_error())
+        .into()
+}
+
+/// Derive conversions of a struct into a `cedar_policy::Entity`.
+///
+/// The generated code uses `cedar_policy::codegen`, so it requires the
+/// `codegen` feature of `cedar-policy`. Each field becomes an attribute of the
+/// same name, except for the fields marked with:
+///
+/// - `#[cedar(id)]`: the id of the entity's uid. This field is required, and
+///   its type must implement `AsRef<str>`.
+/// - `#[cedar(parents)]`: the parents of the entity, as a collection of values
+///   which convert into `cedar_policy::EntityUid`.
+/// - `#[cedar(skip)]`: not converted.
+///
+/// Attributes are converted with `cedar_policy::codegen::CedarValue`, which is
+/// implemented for `bool`, `i64`, `String`, `EntityUid`, `Vec`s of them,
+/// `std::net::IpAddr`, the extension types of `cedar_policy::codegen` (e.g.,
+/// `Decimal` and `Datetime`), and structs deriving
+/// [`CedarContext`](derive@CedarContext). An `Option` field is left out of the
+/// entity when it is `None`. `#[cedar(rename = "...")]` names the attribute
+/// differently from the field.
+///
+/// ```ignore
+/// #[derive(CedarEntity)]
+/// #[cedar(entity_type = "PhotoApp::User")]
+/// struct User {
+///     #[cedar(id)]
+///     name: String,
+///     #[cedar(parents, ty = "UserGroup")]
+///     groups: Vec<cedar_policy::EntityUid>,
+///     department: String,
+///     #[cedar(ty = "User")]
+///     manager: Option<cedar_policy::EntityUid>,
+/// }
+///
+/// let entity: cedar_policy::Entity = user.try_into()?;
+/// ```
+///
+/// The entity type is the name of the struct unless given with
+/// `#[cedar(entity_type = "...")]`. The struct gets an `into_entity()` method,
+/// which `TryFrom` calls, a `uid()` function returning the uid of the entity
+/// of this type with a given id, and constants with the name of the entity
+/// type, `ENTITY_TYPE`, and its declaration in the Cedar schema format,
+/// `CEDAR_SCHEMA`. The Cedar type of each attribute in the declaration is
+/// inferred from the field's type where possible, and otherwise must be given
+/// with `#[cedar(ty = "...")]` (e.g., for entity references). The `ty` of the
+/// parents field is the list of parent entity types. Build a schema from such
+/// declarations by parsing each as a `cedar_policy::SchemaFragment`.
+#[proc_macro_derive(CedarEntity, attributes(cedar))]
+pub fn derive_cedar_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive::derive_entity(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive conversions of a struct into a `cedar_policy::Context`, and to and
+/// from Cedar record values.
+///
+/// Fields are converted as for [`CedarEntity`](derive@CedarEntity), except that
+/// there are no `id` or `parents` fields. Skipped fields must implement
+/// `Default`, which provides their values when converting from a Cedar value.
+///
+/// ```ignore
+/// #[derive(CedarContext)]
+/// struct ViewContext {
+///     authenticated: bool,
+///     source_ip: std::net::IpAddr,
+/// }
+///
+/// let context = ViewContext { authenticated: true, source_ip }.into_context()?;
+/// ```
+///
+/// The struct gets an `into_context()` method, which `TryFrom` calls, an
+/// implementation of `cedar_policy::codegen::CedarValue`, so that it can be
+/// the type of a field of another derived struct, and a `CEDAR_SCHEMA`
+/// constant declaring its record type in the Cedar schema format, under its
+/// name or the one given with `#[cedar(type_name = "...")]`.
+#[proc_macro_derive(CedarContext, attributes(cedar))]
+pub fn derive_cedar_context(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive::derive_context(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The arguments of [`include_policyset!`]
+#[derive(Debug)]
+struct Args {
+    pattern: String,
+    schema: Option<String>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let pattern = input.parse::<LitStr>()?.value();
+        let mut schema = None;
+        while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            if key != "schema" || schema.is_some() {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("unexpected argument `{key}`; expected `schema = \"...\"`"),
+                ));
+            }
+            schema = Some(value.value());
+        }
+        if !input.is_empty() {
+            return Err(input.error("expected `,`"));
+        }
+        Ok(Self { pattern, schema })
+    }
+}
+
+/// Check the policies (and schema) named by `args`, with paths relative to
+/// `manifest_dir`, and return the expression embedding them, or the error
+/// message to report
+fn expand(args: &Args, manifest_dir: &Path) -> Result<TokenStream2, String> {
+    let pattern = manifest_dir.join(&args.pattern);
+    let mut paths = glob::glob(&pattern.to_string_lossy())
+        .map_err(|err| format!("invalid pattern `{}`: {err}", args.pattern))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("failed to read `{}`: {err}", err.path().display()))?;
+    paths.sort();
+    if paths.is_empty() {
+        return Err(format!("no policy files match `{}`", args.pattern));
+    }
+
+    let schema_path = args.schema.as_ref().map(|schema| manifest_dir.join(schema));
+    let validator = match &schema_path {
+        Some(path) => Some(Validator::new(parse_schema(path)?)),
+        None => None,
+    };
+
+    for path in &paths {
+        let text = read(path)?;
+        let policies = PolicySet::from_str(&text).map_err(|err| render(err, path, &text))?;
+        if let Some(validator) = &validator {
+            let result = validator.validate(&policies, ValidationMode::Strict);
+            if !result.validation_passed() {
+                return Err(render(result, path, &text));
+            }
+        }
+    }
+
+    let schema_tracker = schema_path.map(|path| {
+        let path = path.to_string_lossy();
+        quote! { const _: &::core::primitive::str = ::core::include_str!(#path); }
+    });
+    let includes = paths.iter().map(|path| {
+        let path = path.to_string_lossy();
+        quote! { ::core::include_str!(#path), "\n" }
+    });
+    Ok(quote! {
+        {
+            #schema_tracker
+            <::cedar_policy::PolicySet as ::core::str::FromStr>::from_str(
+                ::core::concat!(#(#includes),*)
+            )
+            .unwrap_or_else(|_| {
+                ::core::unreachable!("embedded policies were parsed when the program was compiled")
+            })
+        }
+    })
+}
+
+/// Parse the schema at `path`, in the JSON schema format if it ends with
+/// `.json` and the Cedar schema format otherwise
+fn parse_schema(path: &Path) -> Result<Schema, String> {
+    let text = read(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        Schema::from_json_str(&text).map_err(|err| render(err, path, &text))
+    } else {
+        Schema::from_cedarschema_str(&text)
+            .map(|(schema, _)| schema)
+            .map_err(|err| render(err, path, &text))
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read `{}`: {err}", path.display()))
+}
+
+/// Render `err`, which occurred in the file at `path` with contents `text`,
+/// including the locations it refers to
+fn render(err: impl Diagnostic + Send + Sync + 'static, path: &Path, text: &str) -> String {
+    let report = Report::new(err).with_source_code(text.to_string());
+    let mut rendered = format!("in `{}`: ", path.display());
+    if NarratableReportHandler::new()
+        .render_report(&mut rendered, report.as_ref())
+        .is_err()
+    {
+        rendered.push_str(&report.to_string());
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand_err(pattern: &str, schema: Option<&str>) -> String {
+        let args = Args {
+            pattern: pattern.to_string(),
+            schema: schema.map(str::to_string),
+        };
+        expand(&args, Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap_err()
+    }
+
+    #[test]
+    fn parses_args() {
+        let args: Args = syn::parse_str(r#""*.cedar", schema = "schema.json","#).unwrap();
+        assert_eq!(args.pattern, "*.cedar");
+        assert_eq!(args.schema.as_deref(), Some("schema.json"));
+        let err = syn::parse_str::<Args>(r#""*.cedar", entities = "e.json""#).unwrap_err();
+        assert!(err.to_string().contains("unexpected argument `entities`"));
+    }
+
+    #[test]
+    fn no_matching_files() {
+        assert_eq!(
+            expand_err("tests/nonexistent/*.cedar", None),
+            "no policy files match `tests/nonexistent/*.cedar`"
+        );
+    }
+
+    #[test]
+    fn parse_error_names_file() {
+        let err = expand_err("tests/invalid/syntax.cedar", None);
+        assert!(err.contains("syntax.cedar"), "{err}");
+        assert!(err.contains("unexpected token `}`"), "{err}");
+    }
+
+    #[test]
+    fn validation_error_names_file() {
+        let err = expand_err(
+            "tests/invalid/unknown_attr.cedar",
+            Some("tests/policies/schema.cedarschema"),
+        );
+        assert!(err.contains("unknown_attr.cedar"), "{err}");
+        assert!(err.contains("owner"), "{err}");
+        // Without a schema, the policy is fine
+        let args = Args {
+            pattern: "tests/invalid/unknown_attr.cedar".to_string(),
+            schema: None,
+        };
+        assert!(expand(&args, Path::new(env!("CARGO_MANIFEST_DIR"))).is_ok());
+    }
+}
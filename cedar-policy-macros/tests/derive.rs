@@ -0,0 +1,156 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![allow(clippy::unwrap_used, reason = "testing")]
+
+use cedar_policy::codegen::{CedarValue, Decimal};
+use cedar_policy::{Context, Entity, EntityUid, RestrictedExpression, Schema, SchemaFragment};
+use cedar_policy_macros::{CedarContext, CedarEntity};
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, CedarContext)]
+#[cedar(type_name = "App::Address")]
+struct Address {
+    street: String,
+    zip: Option<String>,
+}
+
+#[derive(CedarEntity)]
+#[cedar(entity_type = "App::User")]
+struct User {
+    #[cedar(id)]
+    name: String,
+    #[cedar(parents, ty = "Group")]
+    groups: Vec<EntityUid>,
+    age: Option<i64>,
+    #[cedar(rename = "ip")]
+    addr: IpAddr,
+    balance: Decimal,
+    #[cedar(ty = "Address")]
+    address: Address,
+    #[cedar(skip)]
+    #[expect(dead_code, reason = "testing that skipped fields are ignored")]
+    session: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, CedarContext)]
+struct RequestContext {
+    #[cedar(rename = "sourceIp")]
+    source_ip: IpAddr,
+    authenticated: bool,
+    #[cedar(skip)]
+    attempts: u32,
+}
+
+fn user() -> User {
+    User {
+        name: "alice".into(),
+        groups: vec![EntityUid::from_str(r#"App::Group::"admins""#).unwrap()],
+        age: None,
+        addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        balance: Decimal("1.50".into()),
+        address: Address {
+            street: "Main St".into(),
+            zip: Some("12345".into()),
+        },
+        session: 7,
+    }
+}
+
+#[test]
+fn entity() {
+    assert_eq!(User::ENTITY_TYPE, "App::User");
+    let entity: Entity = user().try_into().unwrap();
+    let expected = Entity::from_json_value(
+        serde_json::json!({
+            "uid": { "type": "App::User", "id": "alice" },
+            "attrs": {
+                "ip": { "__extn": { "fn": "ip", "arg": "10.0.0.1" } },
+                "balance": { "__extn": { "fn": "decimal", "arg": "1.50" } },
+                "address": { "street": "Main St", "zip": "12345" },
+            },
+            "parents": [{ "type": "App::Group", "id": "admins" }],
+        }),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        entity.to_json_value().unwrap(),
+        expected.to_json_value().unwrap()
+    );
+    assert_eq!(User::uid("alice"), entity.uid());
+}
+
+#[test]
+fn context() {
+    let context: Context = RequestContext {
+        source_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        authenticated: true,
+        attempts: 3,
+    }
+    .try_into()
+    .unwrap();
+    assert_eq!(
+        context.to_json_value().unwrap(),
+        serde_json::json!({
+            "sourceIp": { "__extn": { "fn": "ip", "arg": "127.0.0.1" } },
+            "authenticated": true,
+        })
+    );
+}
+
+#[test]
+fn record_round_trip() {
+    let address = Address {
+        street: "Main St".into(),
+        zip: None,
+    };
+    let expr: RestrictedExpression = address.clone().into_restricted_expression();
+    let context = Context::from_pairs([("address".to_string(), expr)]).unwrap();
+    let request = cedar_policy::Request::new(
+        EntityUid::from_str(r#"P::"p""#).unwrap(),
+        EntityUid::from_str(r#"A::"a""#).unwrap(),
+        EntityUid::from_str(r#"R::"r""#).unwrap(),
+        context,
+        None,
+    )
+    .unwrap();
+    let value = cedar_policy::eval_expression(
+        &request,
+        &cedar_policy::Entities::empty(),
+        &cedar_policy::Expression::from_str("context.address").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Address::from_eval_result(&value).unwrap(), address);
+}
+
+#[test]
+fn schema() {
+    // Each declaration is a separate schema fragment, since a namespace can
+    // only be declared once per fragment
+    let fragments = [
+        User::CEDAR_SCHEMA,
+        Address::CEDAR_SCHEMA,
+        RequestContext::CEDAR_SCHEMA,
+        r#"namespace App {
+            entity Group;
+            action view appliesTo { principal: User, resource: User, context: RequestContext };
+        }"#,
+    ]
+    .map(|text| SchemaFragment::from_cedarschema_str(text).unwrap().0);
+    Schema::from_schema_fragments(fragments).unwrap();
+}
@@ -0,0 +1,58 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![allow(clippy::unwrap_used, reason = "testing")]
+
+use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, PolicyId, Request};
+use cedar_policy_macros::include_policyset;
+use std::str::FromStr;
+
+#[test]
+fn embeds_matching_files_in_order() {
+    let policies = include_policyset!("tests/policies/*.cedar");
+    assert_eq!(policies.num_of_policies(), 2);
+    // `a.cedar` comes first
+    assert!(policies
+        .policy(&PolicyId::new("policy0"))
+        .unwrap()
+        .to_string()
+        .contains("alice"));
+}
+
+#[test]
+fn validates_against_schema() {
+    let policies = include_policyset!(
+        "tests/policies/*.cedar",
+        schema = "tests/policies/schema.cedarschema",
+    );
+    let entities = Entities::from_json_value(
+        serde_json::json!([
+            { "uid": { "type": "Photo", "id": "p" }, "attrs": { "private": false }, "parents": [] },
+        ]),
+        None,
+    )
+    .unwrap();
+    let request = Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+        Context::empty(),
+        None,
+    )
+    .unwrap();
+    let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+    assert_eq!(response.decision(), Decision::Allow);
+}
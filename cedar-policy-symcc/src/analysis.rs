@@ -0,0 +1,356 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Analyses relating two policies over every request environment of a schema.
+//!
+//! The checks on [`CedarSymCompiler`] answer questions about a single
+//! `RequestEnv` at a time. [`PolicyAnalyzer`] runs them for each request
+//! environment of a schema in turn, so that questions like "can this policy
+//! ever apply?", "does this policy permit everything that one permits?" or
+//! "can these two policies ever both apply?" are answered for every well-formed request, along with a witness
+//! when the answer isn't the one hoped for. [`PolicyAnalyzer::minimize`]
+//! builds on these to find the policies of a policy set which can be removed
+//! without changing any authorization decision.
+
+use std::collections::HashMap;
+
+use cedar_policy::{Effect, Policy, PolicyId, PolicySet, RequestEnv, Schema};
+
+use crate::err::Result;
+use crate::solver::Solver;
+use crate::{CedarSymCompiler, CompiledPolicy, CompiledPolicySet, Env};
+
+/// Why a policy removed by [`PolicyAnalyzer::minimize`] is redundant. Each
+/// reason holds for every well-formed input allowed by the schema, with
+/// respect to the policies remaining after the removal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Redundancy {
+    /// The policy never matches
+    NeverMatches,
+    /// A remaining policy with the same effect matches every input this
+    /// policy matches
+    Subsumed {
+        /// The policy subsuming the removed one
+        by: PolicyId,
+    },
+    /// This is a `permit` policy, and a remaining `forbid` policy matches every
+    /// input this policy matches
+    Overridden {
+        /// The `forbid` policy overriding the removed one
+        by: PolicyId,
+    },
+    /// The remaining policies authorize every input the same with or without
+    /// this policy, though no single one of them accounts for it on its own
+    Covered,
+}
+
+/// A policy removed by [`PolicyAnalyzer::minimize`], and why it is redundant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedundantPolicy {
+    /// Id of the removed policy
+    pub id: PolicyId,
+    /// Why removing the policy doesn't change any authorization decision
+    pub redundancy: Redundancy,
+}
+
+/// The result of [`PolicyAnalyzer::minimize`]
+#[derive(Debug, Clone)]
+pub struct Minimization {
+    /// The policy set without its redundant policies. It makes the same
+    /// authorization decision as the original policy set for every
+    /// well-formed input.
+    pub policies: PolicySet,
+    /// The removed policies, in the order they were removed
+    pub removed: Vec<RedundantPolicy>,
+}
+
+/// Checks properties relating two policies for every well-formed input
+/// allowed by a [`Schema`], using the [`Solver`] `S`.
+///
+/// Each check returns `None` if the property holds, or else a counterexample
+/// (a synthesized request and entity store) demonstrating that it doesn't.
+#[derive(Debug)]
+pub struct PolicyAnalyzer<'a, S: Solver> {
+    compiler: CedarSymCompiler<S>,
+    schema: &'a Schema,
+}
+
+impl<'a, S: Solver> PolicyAnalyzer<'a, S> {
+    /// Constructs a new [`PolicyAnalyzer`] for the given schema, which uses
+    /// the given [`Solver`] instance.
+    pub fn new(solver: S, schema: &'a Schema) -> Result<Self> {
+        Ok(Self {
+            compiler: CedarSymCompiler::new(solver)?,
+            schema,
+        })
+    }
+
+    /// Returns a mutable reference to the underlying [`CedarSymCompiler`],
+    /// for checks on a single `RequestEnv`.
+    pub fn compiler_mut(&mut self) -> &mut CedarSymCompiler<S> {
+        &mut self.compiler
+    }
+
+    /// Checks that `policy` never matches. Returns an input which `policy`
+    /// matches, if it exists.
+    ///
+    /// For a policy the validator warns is impossible, `None` confirms that no
+    /// well-formed input is ever authorized by it.
+    pub async fn check_never_matches(&mut self, policy: &Policy) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let policy = CompiledPolicy::compile(policy, &req_env, self.schema)?;
+            let cex = self
+                .compiler
+                .check_never_matches_with_counterexample_opt(&policy)
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that `policy` matches every input. Returns an input which
+    /// `policy` doesn't match, if it exists.
+    pub async fn check_always_matches(&mut self, policy: &Policy) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let policy = CompiledPolicy::compile(policy, &req_env, self.schema)?;
+            let cex = self
+                .compiler
+                .check_always_matches_with_counterexample_opt(&policy)
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that evaluating `policy` never errors. Returns an input on
+    /// which `policy` errors, e.g., by overflowing an arithmetic operation,
+    /// if it exists.
+    pub async fn check_never_errors(&mut self, policy: &Policy) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let policy = CompiledPolicy::compile(policy, &req_env, self.schema)?;
+            let cex = self
+                .compiler
+                .check_never_errors_with_counterexample_opt(&policy)
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that `policy2` permits every input that `policy1` permits.
+    /// Returns a counterexample which is allowed by `policy1` but not by
+    /// `policy2`, if it exists.
+    ///
+    /// This compares the authorization decisions of the two policies on their
+    /// own, so a `forbid` policy permits nothing: it is implied by any policy,
+    /// and implies a `permit` policy only if that policy never matches. Use
+    /// [`Self::check_matches_implies`] to compare when policies match instead.
+    pub async fn check_implies(
+        &mut self,
+        policy1: &Policy,
+        policy2: &Policy,
+    ) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let (policy1, policy2) = self.compile(policy1, policy2, &req_env)?;
+            let cex = self
+                .compiler
+                .check_implies_with_counterexample_opt(
+                    &policy1.into_compiled_policyset(),
+                    &policy2.into_compiled_policyset(),
+                )
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that `policy2` matches every input that `policy1` matches,
+    /// regardless of their effects. For instance, this checks that a `forbid`
+    /// guardrail `policy2` covers everything another `forbid` policy does.
+    /// Returns a counterexample which is matched by `policy1` but not by
+    /// `policy2`, if it exists.
+    pub async fn check_matches_implies(
+        &mut self,
+        policy1: &Policy,
+        policy2: &Policy,
+    ) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let (policy1, policy2) = self.compile(policy1, policy2, &req_env)?;
+            let cex = self
+                .compiler
+                .check_matches_implies_with_counterexample_opt(&policy1, &policy2)
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that `policy1` and `policy2` never both apply to the same input,
+    /// regardless of their effects. Returns an input which is matched by both
+    /// policies, if it exists.
+    pub async fn check_matches_disjoint(
+        &mut self,
+        policy1: &Policy,
+        policy2: &Policy,
+    ) -> Result<Option<Env>> {
+        for req_env in self.schema.request_envs() {
+            let (policy1, policy2) = self.compile(policy1, policy2, &req_env)?;
+            let cex = self
+                .compiler
+                .check_matches_disjoint_with_counterexample_opt(&policy1, &policy2)
+                .await?;
+            if cex.is_some() {
+                return Ok(cex);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Removes policies from `pset` which don't affect any authorization
+    /// decision for well-formed inputs, along with the reason each one is
+    /// redundant.
+    ///
+    /// Policies are considered one at a time, from last to first, and each
+    /// one is removed if the policy set without it still makes the same
+    /// decision for every input. So of two duplicate policies, the first is
+    /// kept. Only static policies are considered for removal; template-linked
+    /// policies are kept as-is.
+    pub async fn minimize(&mut self, pset: &PolicySet) -> Result<Minimization> {
+        let mut compiled = Vec::new();
+        for req_env in self.schema.request_envs() {
+            let policies = pset
+                .policies()
+                .filter(|p| p.is_static())
+                .map(|p| {
+                    Ok((
+                        p.id().clone(),
+                        CompiledPolicy::compile(p, &req_env, self.schema)?,
+                    ))
+                })
+                .collect::<Result<HashMap<_, _>>>()?;
+            compiled.push((req_env, policies));
+        }
+
+        let mut kept = pset.clone();
+        let mut removed = Vec::new();
+        let candidates: Vec<Policy> = pset.policies().filter(|p| p.is_static()).cloned().collect();
+        for policy in candidates.iter().rev() {
+            let mut without = kept.clone();
+            #[expect(
+                clippy::unwrap_used,
+                reason = "`policy` is a static policy of `kept`, as it hasn't been removed yet"
+            )]
+            without.remove_static(policy.id().clone()).unwrap();
+            if let Some(redundancy) = self.redundancy(policy, &kept, &without, &compiled).await? {
+                removed.push(RedundantPolicy {
+                    id: policy.id().clone(),
+                    redundancy,
+                });
+                kept = without;
+            }
+        }
+        Ok(Minimization {
+            policies: kept,
+            removed,
+        })
+    }
+
+    /// Why `policy` is redundant in `kept`, if it is, where `without` is
+    /// `kept` without `policy` and `compiled` has the static policies of
+    /// `kept` compiled for each request environment.
+    async fn redundancy(
+        &mut self,
+        policy: &Policy,
+        kept: &PolicySet,
+        without: &PolicySet,
+        compiled: &[(RequestEnv, HashMap<PolicyId, CompiledPolicy>)],
+    ) -> Result<Option<Redundancy>> {
+        let mut never_matches = true;
+        for (_, policies) in compiled {
+            never_matches &= self
+                .compiler
+                .check_never_matches_opt(&policies[policy.id()])
+                .await?;
+            if !never_matches {
+                break;
+            }
+        }
+        if never_matches {
+            return Ok(Some(Redundancy::NeverMatches));
+        }
+
+        for other in without.policies().filter(|p| p.is_static()) {
+            let redundancy = match (policy.effect(), other.effect()) {
+                (Effect::Permit, Effect::Forbid) => Redundancy::Overridden {
+                    by: other.id().clone(),
+                },
+                (effect, other_effect) if effect == other_effect => Redundancy::Subsumed {
+                    by: other.id().clone(),
+                },
+                _ => continue,
+            };
+            let mut implies = true;
+            for (_, policies) in compiled {
+                implies &= self
+                    .compiler
+                    .check_matches_implies_opt(&policies[policy.id()], &policies[other.id()])
+                    .await?;
+                if !implies {
+                    break;
+                }
+            }
+            if implies {
+                return Ok(Some(redundancy));
+            }
+        }
+
+        for (req_env, _) in compiled {
+            let equivalent = self
+                .compiler
+                .check_equivalent_opt(
+                    &CompiledPolicySet::compile(kept, req_env, self.schema)?,
+                    &CompiledPolicySet::compile(without, req_env, self.schema)?,
+                )
+                .await?;
+            if !equivalent {
+                return Ok(None);
+            }
+        }
+        Ok(Some(Redundancy::Covered))
+    }
+
+    fn compile(
+        &self,
+        policy1: &Policy,
+        policy2: &Policy,
+        req_env: &RequestEnv,
+    ) -> Result<(CompiledPolicy, CompiledPolicy)> {
+        Ok((
+            CompiledPolicy::compile(policy1, req_env, self.schema)?,
+            CompiledPolicy::compile(policy2, req_env, self.schema)?,
+        ))
+    }
+}
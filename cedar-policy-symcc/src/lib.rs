@@ -17,6 +17,8 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
 pub mod err;
 mod symcc;
 mod symccopt;
@@ -285,6 +285,10 @@ pub fn compile_app2(op2: BinaryOp, t1: Term, t2: Term, es: &SymEntities) -> Resu
         }
         (HasTag, Entity { ety }, String) => compile_has_tag(t1, t2, es.tags(&ety), &ety),
         (GetTag, Entity { ety }, String) => compile_get_tag(t1, t2, es.tags(&ety), &ety),
+        #[cfg(feature = "maps")]
+        (GetKey | ContainsKey, _, _) => Err(CompileError::UnsupportedFeature(
+            "map operations are unsupported".into(),
+        )),
         (_, _, _) => Err(CompileError::TypeError),
     }
 }
@@ -651,6 +655,20 @@ pub fn compile_call(xfn: &cedar_policy_core::ast::Name, ts: Vec<Term>) -> Result
             let t1 = extract_first(ts);
             compile_call1(ExtType::DateTime, extfun::to_time, t1)
         }
+        ("dayOfWeek", 2) => {
+            let (t1, t2) = extract_first2(ts);
+            compile_call2_error(
+                ExtType::DateTime,
+                ExtType::Duration,
+                extfun::day_of_week,
+                t1,
+                t2,
+            )
+        }
+        ("hour", 2) => {
+            let (t1, t2) = extract_first2(ts);
+            compile_call2_error(ExtType::DateTime, ExtType::Duration, extfun::hour, t1, t2)
+        }
         ("toMilliseconds", 1) => {
             let t1 = extract_first(ts);
             compile_call1(ExtType::Duration, extfun::to_milliseconds, t1)
@@ -1327,6 +1345,59 @@ mod datetime_tests {
         );
     }
 
+    // Test that the str compiles and simplifies to a Long literal matching rep,
+    // or to none if rep is None
+    fn test_long_simpl_expr(str: &str, rep: Option<i64>) {
+        let expected = match rep {
+            Some(rep) => Term::Some(Arc::new(rep.into())),
+            None => Term::None(TermType::Bitvec { n: SIXTY_FOUR }),
+        };
+        assert_eq!(
+            compile(&parse_expr(str), &datetime_sym_env()).unwrap(),
+            expected,
+            "{str}"
+        )
+    }
+
+    #[test]
+    fn test_calendar_components_simpl_expr() {
+        // 2024-10-28T02:30:00Z is a Monday in UTC but still Sunday evening in UTC-5
+        let monday = r#"datetime("2024-10-28T02:30:00Z")"#;
+        test_long_simpl_expr(&format!(r#"{monday}.dayOfWeek(duration("0ms"))"#), Some(1));
+        test_long_simpl_expr(&format!(r#"{monday}.hour(duration("0ms"))"#), Some(2));
+        test_long_simpl_expr(&format!(r#"{monday}.dayOfWeek(duration("-5h"))"#), Some(7));
+        test_long_simpl_expr(&format!(r#"{monday}.hour(duration("-5h"))"#), Some(21));
+        test_long_simpl_expr(&format!(r#"{monday}.hour(duration("5h30m"))"#), Some(8));
+        // 1970-01-01 was a Thursday, so the day before was a Wednesday
+        test_long_simpl_expr(
+            r#"datetime("1970-01-01").dayOfWeek(duration("0ms"))"#,
+            Some(4),
+        );
+        test_long_simpl_expr(
+            r#"datetime("1969-12-31T13:30:00Z").dayOfWeek(duration("0ms"))"#,
+            Some(3),
+        );
+        test_long_simpl_expr(
+            r#"datetime("1969-12-31T13:30:00Z").hour(duration("0ms"))"#,
+            Some(13),
+        );
+        test_long_simpl_expr(
+            r#"datetime("1969-12-31").dayOfWeek(duration("0ms"))"#,
+            Some(3),
+        );
+        test_long_simpl_expr(
+            r#"datetime("1900-01-01").dayOfWeek(duration("0ms"))"#,
+            Some(1),
+        );
+        // offsets must be less than a day in magnitude, and must not overflow
+        test_long_simpl_expr(&format!(r#"{monday}.hour(duration("1d"))"#), None);
+        test_long_simpl_expr(&format!(r#"{monday}.dayOfWeek(duration("-1d"))"#), None);
+        test_long_simpl_expr(
+            r#"datetime("1970-01-01").offset(duration("9223372036854775807ms")).hour(duration("1ms"))"#,
+            None,
+        );
+    }
+
     #[test]
     fn test_datetime_simpl_comp_expr() {
         test_valid_bool_simpl_expr(r#"datetime("2025-01-01") == datetime("2025-01-01")"#, true);
@@ -228,3 +228,48 @@ pub fn to_time(dt: Term) -> Term {
         ),
     ))
 }
+
+/// `dt` interpreted in the UTC offset `dur`, as by `dayOfWeek` and `hour`, along
+/// with whether that errors: the offset must be less than a day in magnitude,
+/// and adding it must not overflow
+fn local_datetime_val(dt: Term, dur: Term) -> (Term, Term) {
+    let ms_per_day: i64 = 86400000;
+    let dt_val = ext_datetime_val(dt);
+    let dur_val = ext_duration_val(dur);
+    let error = or(
+        or(
+            bvsle(dur_val.clone(), (-ms_per_day).into()),
+            bvsle(ms_per_day.into(), dur_val.clone()),
+        ),
+        bvsaddo(dt_val.clone(), dur_val.clone()),
+    );
+    (error, bvadd(dt_val, dur_val))
+}
+
+pub fn day_of_week(dt: Term, dur: Term) -> Term {
+    let ms_per_day: Term = 86400000.into();
+    let zero: Term = 0.into();
+    let (error, local) = local_datetime_val(dt, dur);
+    // the number of days since the epoch, rounded down; `bvsdiv` rounds towards zero
+    let days = ite(
+        or(
+            bvsle(zero.clone(), local.clone()),
+            eq(bvsrem(local.clone(), ms_per_day.clone()), zero),
+        ),
+        bvsdiv(local.clone(), ms_per_day.clone()),
+        bvsub(bvsdiv(local, ms_per_day), 1.into()),
+    );
+    // the epoch, 1970-01-01, was a Thursday, which is day 4 of the ISO week
+    if_false(
+        error,
+        bvadd(bvsmod(bvadd(days, 3.into()), 7.into()), 1.into()),
+    )
+}
+
+pub fn hour(dt: Term, dur: Term) -> Term {
+    let (error, local) = local_datetime_val(dt, dur);
+    if_false(
+        error,
+        bvsdiv(bvsmod(local, 86400000.into()), 3600000.into()),
+    )
+}
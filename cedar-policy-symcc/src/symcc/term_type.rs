@@ -166,6 +166,9 @@ impl TermType {
                     ))
                 }
             }
+            Type::Map { .. } => Err(CompileError::UnsupportedFeature(
+                "map types are unsupported".into(),
+            )),
             Type::Never => Err(CompileError::UnsupportedFeature(
                 "never type is not supported".into(),
             )),
@@ -483,6 +483,10 @@ fn compile_app2(
                 term,
             })
         }
+        #[cfg(feature = "maps")]
+        (GetKey | ContainsKey, _, _) => Err(CompileError::UnsupportedFeature(
+            "map operations are unsupported".into(),
+        )),
         (_, _, _) => Err(CompileError::TypeError),
     }
 }
@@ -927,6 +931,20 @@ pub fn compile_call(
             let t1 = extract_first(args);
             compile_call1(ExtType::DateTime, extfun::to_time, t1)
         }
+        ("dayOfWeek", 2) => {
+            let (t1, t2) = extract_first2(args);
+            compile_call2_error(
+                ExtType::DateTime,
+                ExtType::Duration,
+                extfun::day_of_week,
+                t1,
+                t2,
+            )
+        }
+        ("hour", 2) => {
+            let (t1, t2) = extract_first2(args);
+            compile_call2_error(ExtType::DateTime, ExtType::Duration, extfun::hour, t1, t2)
+        }
         ("toMilliseconds", 1) => {
             let t1 = extract_first(args);
             compile_call1(ExtType::Duration, extfun::to_milliseconds, t1)
@@ -0,0 +1,286 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![cfg(feature = "analysis")]
+
+use cedar_policy::{Authorizer, Decision, Policy, PolicyId, PolicySet, Schema, Validator};
+use cedar_policy_symcc::{
+    analysis::{PolicyAnalyzer, Redundancy, RedundantPolicy},
+    solver::LocalSolver,
+    Env,
+};
+
+mod utils;
+
+fn sample_schema() -> Schema {
+    utils::schema_from_cedarstr(
+        r#"
+        entity User { level: Long };
+        entity Document { owner: User };
+        action view, edit appliesTo {
+            principal: [User],
+            resource: [Document]
+        };
+        "#,
+    )
+}
+
+fn decision(policy: &Policy, cex: &Env) -> Decision {
+    let pset = PolicySet::from_policies([policy.clone()]).unwrap();
+    Authorizer::new()
+        .is_authorized(&cex.request, &pset, &cex.entities)
+        .decision()
+}
+
+#[tokio::test]
+async fn implies() {
+    let schema = sample_schema();
+    let validator = Validator::new(schema.clone());
+    let owner_views = utils::policy_from_text(
+        "owner_views",
+        r#"permit(principal, action == Action::"view", resource) when { resource.owner == principal };"#,
+        &validator,
+    );
+    let anyone_views = utils::policy_from_text(
+        "anyone_views",
+        r#"permit(principal, action == Action::"view", resource);"#,
+        &validator,
+    );
+    let mut analyzer = PolicyAnalyzer::new(LocalSolver::cvc5().unwrap(), &schema).unwrap();
+
+    assert!(analyzer
+        .check_implies(&owner_views, &anyone_views)
+        .await
+        .unwrap()
+        .is_none());
+    let cex = analyzer
+        .check_implies(&anyone_views, &owner_views)
+        .await
+        .unwrap()
+        .expect("`anyone_views` permits more than `owner_views`");
+    assert_eq!(decision(&anyone_views, &cex), Decision::Allow);
+    assert_eq!(decision(&owner_views, &cex), Decision::Deny);
+}
+
+#[tokio::test]
+async fn matches_implies() {
+    let schema = sample_schema();
+    let validator = Validator::new(schema.clone());
+    let guardrail = utils::policy_from_text(
+        "guardrail",
+        "forbid(principal, action, resource) when { principal.level < 3 };",
+        &validator,
+    );
+    let narrower = utils::policy_from_text(
+        "narrower",
+        "forbid(principal, action, resource) when { principal.level < 1 };",
+        &validator,
+    );
+    let mut analyzer = PolicyAnalyzer::new(LocalSolver::cvc5().unwrap(), &schema).unwrap();
+
+    // As decisions, forbid policies trivially imply each other
+    assert!(analyzer
+        .check_implies(&guardrail, &narrower)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(analyzer
+        .check_matches_implies(&narrower, &guardrail)
+        .await
+        .unwrap()
+        .is_none());
+    let cex = analyzer
+        .check_matches_implies(&guardrail, &narrower)
+        .await
+        .unwrap()
+        .expect("`guardrail` matches more than `narrower`");
+    assert_eq!(decision(&guardrail, &cex), Decision::Deny);
+    let permit_all = PolicySet::from_policies([
+        narrower,
+        Policy::parse(None, "permit(principal, action, resource);").unwrap(),
+    ])
+    .unwrap();
+    assert_eq!(
+        Authorizer::new()
+            .is_authorized(&cex.request, &permit_all, &cex.entities)
+            .decision(),
+        Decision::Allow
+    );
+}
+
+#[tokio::test]
+async fn matches_disjoint() {
+    let schema = sample_schema();
+    let validator = Validator::new(schema.clone());
+    let anyone_views = utils::policy_from_text(
+        "anyone_views",
+        r#"permit(principal, action == Action::"view", resource);"#,
+        &validator,
+    );
+    let anyone_edits = utils::policy_from_text(
+        "anyone_edits",
+        r#"permit(principal, action == Action::"edit", resource);"#,
+        &validator,
+    );
+    let guardrail = utils::policy_from_text(
+        "guardrail",
+        "forbid(principal, action, resource) when { principal.level < 3 };",
+        &validator,
+    );
+    let mut analyzer = PolicyAnalyzer::new(LocalSolver::cvc5().unwrap(), &schema).unwrap();
+
+    assert!(analyzer
+        .check_matches_disjoint(&anyone_views, &anyone_edits)
+        .await
+        .unwrap()
+        .is_none());
+    let cex = analyzer
+        .check_matches_disjoint(&anyone_views, &guardrail)
+        .await
+        .unwrap()
+        .expect("both policies apply to low-level users viewing documents");
+    assert_eq!(decision(&anyone_views, &cex), Decision::Allow);
+    assert_eq!(decision(&guardrail, &cex), Decision::Deny);
+}
+
+#[tokio::test]
+async fn single_policy() {
+    let schema = sample_schema();
+    let validator = Validator::new(schema.clone());
+    let impossible = utils::policy_from_text(
+        "impossible",
+        "permit(principal, action, resource) when { principal.level < 0 && principal.level > 0 };",
+        &validator,
+    );
+    let low_level = utils::policy_from_text(
+        "low_level",
+        "permit(principal, action, resource) when { principal.level < 3 };",
+        &validator,
+    );
+    let overflow = utils::policy_from_text(
+        "overflow",
+        "permit(principal, action, resource) when { principal.level + 1 > 3 };",
+        &validator,
+    );
+    let mut analyzer = PolicyAnalyzer::new(LocalSolver::cvc5().unwrap(), &schema).unwrap();
+
+    assert!(analyzer
+        .check_never_matches(&impossible)
+        .await
+        .unwrap()
+        .is_none());
+    let cex = analyzer
+        .check_never_matches(&low_level)
+        .await
+        .unwrap()
+        .expect("`low_level` applies to low-level users");
+    assert_eq!(decision(&low_level, &cex), Decision::Allow);
+    let cex = analyzer
+        .check_always_matches(&low_level)
+        .await
+        .unwrap()
+        .expect("`low_level` doesn't apply to high-level users");
+    assert_eq!(decision(&low_level, &cex), Decision::Deny);
+
+    assert!(analyzer
+        .check_never_errors(&low_level)
+        .await
+        .unwrap()
+        .is_none());
+    let cex = analyzer
+        .check_never_errors(&overflow)
+        .await
+        .unwrap()
+        .expect("`overflow` errors for the largest level");
+    let pset = PolicySet::from_policies([overflow]).unwrap();
+    let response = Authorizer::new().is_authorized(&cex.request, &pset, &cex.entities);
+    assert_eq!(response.diagnostics().errors().count(), 1);
+}
+
+#[tokio::test]
+async fn minimize() {
+    let schema = sample_schema();
+    let pset = PolicySet::from_policies(
+        [
+            (
+                "owner_views",
+                r#"permit(principal, action == Action::"view", resource) when { resource.owner == principal };"#,
+            ),
+            (
+                "anyone_views",
+                r#"permit(principal, action == Action::"view", resource);"#,
+            ),
+            (
+                "anyone_views_again",
+                r#"permit(principal, action == Action::"view", resource);"#,
+            ),
+            (
+                "guardrail",
+                "forbid(principal, action, resource) when { principal.level < 3 };",
+            ),
+            (
+                "low_edits",
+                r#"permit(principal, action == Action::"edit", resource) when { principal.level < 2 };"#,
+            ),
+            (
+                "mid_edits",
+                r#"permit(principal, action == Action::"edit", resource) when { principal.level >= 2 };"#,
+            ),
+            (
+                "never",
+                "permit(principal, action, resource) when { principal.level < 0 && principal.level > 0 };",
+            ),
+        ]
+        .map(|(id, text)| Policy::parse(Some(PolicyId::new(id)), text).unwrap()),
+    )
+    .unwrap();
+    let mut analyzer = PolicyAnalyzer::new(LocalSolver::cvc5().unwrap(), &schema).unwrap();
+
+    let minimization = analyzer.minimize(&pset).await.unwrap();
+    assert_eq!(
+        minimization.removed,
+        vec![
+            RedundantPolicy {
+                id: PolicyId::new("never"),
+                redundancy: Redundancy::NeverMatches,
+            },
+            RedundantPolicy {
+                id: PolicyId::new("low_edits"),
+                redundancy: Redundancy::Overridden {
+                    by: PolicyId::new("guardrail"),
+                },
+            },
+            RedundantPolicy {
+                id: PolicyId::new("anyone_views_again"),
+                redundancy: Redundancy::Subsumed {
+                    by: PolicyId::new("anyone_views"),
+                },
+            },
+            RedundantPolicy {
+                id: PolicyId::new("owner_views"),
+                redundancy: Redundancy::Subsumed {
+                    by: PolicyId::new("anyone_views"),
+                },
+            },
+        ]
+    );
+    let kept: Vec<String> = minimization
+        .policies
+        .policies()
+        .map(|p| p.id().to_string())
+        .collect();
+    assert_eq!(kept, vec!["anyone_views", "guardrail", "mid_edits"]);
+}
@@ -0,0 +1,99 @@
+#![allow(clippy::unwrap_used, reason = "benchmarking")]
+
+use std::{hint::black_box, str::FromStr};
+
+use cedar_policy::{Authorizer, Context, Entities, EntityUid, PolicySet, Request};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+
+/// A policy set of `n` policies with non-trivial conditions, most of which
+/// don't apply to the benchmarked request
+fn policies(n: usize) -> PolicySet {
+    let text = (0..n)
+        .map(|i| {
+            format!(
+                r#"permit(principal, action == Action::"view", resource)
+                when {{
+                    context.level > {i} && resource.owner == principal &&
+                    ["a", "b", "c"].contains(context.tag) &&
+                    (principal.dept like "eng*" || resource has secret)
+                }};"#
+            )
+        })
+        .join("\n");
+    PolicySet::from_str(&text).unwrap()
+}
+
+fn compiled_vs_interpreted(c: &mut Criterion) {
+    let entities = Entities::from_json_value(
+        serde_json::json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "dept": "engineering" }, "parents": [] },
+            { "uid": { "type": "Photo", "id": "p" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } }, "parents": [] },
+        ]),
+        None,
+    )
+    .unwrap();
+    let request = Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+        Context::from_json_value(serde_json::json!({ "level": 50, "tag": "b" }), None).unwrap(),
+        None,
+    )
+    .unwrap();
+    let authorizer = Authorizer::new();
+
+    let mut group = c.benchmark_group("compiled vs interpreted");
+    for n in [1, 10, 100] {
+        let policies = policies(n);
+        let compiled = policies.compile(&authorizer);
+        group.bench_with_input(BenchmarkId::new("interpreted", n), &policies, |b, p| {
+            b.iter(|| authorizer.is_authorized(black_box(&request), p, &entities));
+        });
+        group.bench_with_input(BenchmarkId::new("compiled", n), &compiled, |b, p| {
+            b.iter(|| authorizer.is_authorized_compiled(black_box(&request), p, &entities));
+        });
+    }
+    group.finish();
+}
+
+/// Membership in a large set literal, which is hashed when it is parsed
+fn large_literal_sets(c: &mut Criterion) {
+    let groups = (0..5000).map(|i| format!(r#"Group::"g{i}""#)).join(", ");
+    let ids = (0..5000).map(|i| format!(r#""u{i}""#)).join(", ");
+    let policies = PolicySet::from_str(&format!(
+        r#"permit(principal, action, resource) when {{ principal in [{groups}] }};
+        permit(principal, action, resource) when {{ [{ids}].contains(principal.id) }};"#
+    ))
+    .unwrap();
+    let entities = Entities::from_json_value(
+        serde_json::json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": { "id": "u4999" }, "parents": [{ "type": "Group", "id": "g4999" }] },
+        ]),
+        None,
+    )
+    .unwrap();
+    let request = Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+        Context::empty(),
+        None,
+    )
+    .unwrap();
+    let authorizer = Authorizer::new();
+    let compiled = policies.compile(&authorizer);
+
+    let mut group = c.benchmark_group("large literal sets");
+    group.bench_function("interpreted", |b| {
+        b.iter(|| authorizer.is_authorized(black_box(&request), &policies, &entities));
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| authorizer.is_authorized_compiled(black_box(&request), &compiled, &entities));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compiled_vs_interpreted, large_literal_sets);
+criterion_main!(benches);
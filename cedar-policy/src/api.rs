@@ -42,6 +42,68 @@ mod deprecated_schema_compat;
 mod err;
 pub use err::*;
 
+mod snapshot;
+pub use snapshot::*;
+
+mod persist;
+pub use persist::POLICY_SET_FORMAT_VERSION;
+mod metadata;
+pub use metadata::PolicyMetadata;
+mod diff;
+pub use diff::{MergeConflictResolution, PolicySetDiff};
+mod coverage;
+pub use coverage::{ClauseCoverage, ClauseKind, CoverageReport, PolicyCoverage};
+mod explanation;
+pub use explanation::{
+    ClauseExplanation, DecisionReason, Explanation, OperandValue, PolicyExplanation, PolicyOutcome,
+};
+mod collision;
+pub use collision::IdCollisionBehavior;
+mod layers;
+pub use layers::{LayerMode, LayeredPolicySet, PolicyLayer};
+mod audit;
+pub use audit::{AuditRecord, AuditSink, AuditedError, JsonLinesAuditSink};
+mod bytecode;
+pub use bytecode::CompiledPolicySet;
+mod entity_loader;
+pub use entity_loader::{AsyncEntityLoader, CachingEntityLoader, EntityLoader, TestEntityLoader};
+mod references;
+pub use references::{PolicyReference, ReferenceTarget};
+mod signing;
+mod upgrade;
+pub use signing::{PolicySetSignature, SignatureVerifier};
+mod environments;
+pub use environments::{EnvironmentConfig, UntaggedPolicies, DEFAULT_ENVIRONMENT_ANNOTATION};
+#[cfg(feature = "partial-eval")]
+mod specialization;
+#[cfg(feature = "partial-eval")]
+pub use specialization::{SpecializationCache, SpecializedPolicySet, SpecializedVariable};
+
+mod filter;
+pub use filter::{
+    CompareOp, FilterBackend, FilterTranslation, FilterTranslator, Quantifier, SqlFilter,
+    SqlPredicate, UntranslatableExpr, UntranslatableReason,
+};
+
+mod introspection;
+pub use introspection::*;
+mod request_space;
+pub use request_space::{DecisionTable, DecisionTableRow, RequestSpace};
+mod unused_definitions;
+pub use unused_definitions::*;
+mod schema_inference;
+pub use schema_inference::*;
+mod schema_migration;
+pub use schema_migration::*;
+mod custom_functions;
+mod schema_annotations;
+pub use custom_functions::*;
+
+#[cfg(feature = "arbitrary")]
+mod generator;
+#[cfg(feature = "arbitrary")]
+pub use generator::*;
+
 #[cfg(feature = "tpe")]
 mod tpe;
 #[cfg(feature = "tpe")]
@@ -49,6 +111,7 @@ pub use tpe::*;
 
 pub use ast::Effect;
 pub use authorizer::Decision;
+pub use authorizer::{Clock, EvalEnvironment, FixedClock, SystemClock};
 #[cfg(feature = "partial-eval")]
 use cedar_policy_core::ast::BorrowedRestrictedExpr;
 use cedar_policy_core::ast::{self, RequestSchema, RestrictedExpr};
@@ -56,7 +119,6 @@ use cedar_policy_core::authorizer::{self};
 use cedar_policy_core::entities::{ContextSchema, Dereference};
 use cedar_policy_core::est::{self, TemplateLink};
 use cedar_policy_core::evaluator::Evaluator;
-#[cfg(feature = "partial-eval")]
 use cedar_policy_core::evaluator::RestrictedEvaluator;
 use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::parser;
@@ -211,6 +273,20 @@ impl Entity {
         self.0.deep_eq(&other.0)
     }
 
+    /// Attach provenance metadata (where the entity's data came from) to this
+    /// entity, replacing any previously attached. Provenance has no effect on
+    /// authorization or on entity equality.
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: EntityProvenance) -> Self {
+        self.0.set_provenance(Arc::new(provenance));
+        self
+    }
+
+    /// Get the provenance metadata attached to this entity, if any
+    pub fn provenance(&self) -> Option<&EntityProvenance> {
+        self.0.provenance()
+    }
+
     /// Get the Uid of this entity
     /// ```
     /// # use cedar_policy::{Entity, EntityId, EntityTypeName, EntityUid};
@@ -493,6 +569,15 @@ impl Entities {
     /// - [`EntitiesError::Duplicate`] if there are any duplicate entities in `entities`
     /// - [`EntitiesError::InvalidEntity`] if `schema` is not none and any entities do not conform
     ///   to the schema
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.entities",
+            level = "debug",
+            skip_all,
+            fields(entity_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_entities(
         entities: impl IntoIterator<Item = Entity>,
         schema: Option<&Schema>,
@@ -505,7 +590,7 @@ impl Entities {
             cedar_policy_core::entities::TCComputation::ComputeNow,
             Extensions::all_available(),
         )
-        .map(Entities)
+        .map(|entities| Self(entities).record_len())
     }
 
     /// Add all of the [`Entity`]s in the collection to this [`Entities`]
@@ -759,6 +844,15 @@ impl Entities {
     /// # let ip = entity.attr("ip_addr").unwrap().unwrap();
     /// # assert_eq!(ip, EvalResult::ExtensionValue("ip(\"10.0.1.101\")".to_string()));
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.entities",
+            level = "debug",
+            skip_all,
+            fields(entity_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_str(json: &str, schema: Option<&Schema>) -> Result<Self, EntitiesError> {
         let schema = schema.map(|s| cedar_policy_core::validator::CoreSchema::new(&s.0));
         let eparser = cedar_policy_core::entities::EntityJsonParser::new(
@@ -766,7 +860,10 @@ impl Entities {
             Extensions::all_available(),
             cedar_policy_core::entities::TCComputation::ComputeNow,
         );
-        eparser.from_json_str(json).map(Entities)
+        eparser
+            .from_json_str(json)
+            .map(Entities)
+            .map(Self::record_len)
     }
 
     /// Parse an entities JSON file (in `serde_json::Value` form) into an
@@ -814,6 +911,15 @@ impl Entities {
     /// );
     /// let entities = Entities::from_json_value(data, None).unwrap();
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.entities",
+            level = "debug",
+            skip_all,
+            fields(entity_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_value(
         json: serde_json::Value,
         schema: Option<&Schema>,
@@ -824,7 +930,10 @@ impl Entities {
             Extensions::all_available(),
             cedar_policy_core::entities::TCComputation::ComputeNow,
         );
-        eparser.from_json_value(json).map(Entities)
+        eparser
+            .from_json_value(json)
+            .map(Entities)
+            .map(Self::record_len)
     }
 
     /// Parse an entities JSON file (in `std::io::Read` form) into an `Entities`
@@ -850,6 +959,15 @@ impl Entities {
     /// - [`EntitiesError::InvalidEntity`] if `schema` is not none and any entities do not conform
     ///   to the schema
     /// - [`EntitiesError::Deserialization`] if there are errors while parsing the json
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.entities",
+            level = "debug",
+            skip_all,
+            fields(entity_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_file(
         json: impl std::io::Read,
         schema: Option<&Schema>,
@@ -860,7 +978,121 @@ impl Entities {
             Extensions::all_available(),
             cedar_policy_core::entities::TCComputation::ComputeNow,
         );
-        eparser.from_json_file(json).map(Entities)
+        eparser
+            .from_json_file(json)
+            .map(Entities)
+            .map(Self::record_len)
+    }
+
+    /// Parse an entities JSON file (in `&str` form) into an `Entities` object,
+    /// treating attributes which are not declared in the `schema` according
+    /// to `handling` instead of always returning an error.
+    ///
+    /// This otherwise behaves like [`Entities::from_json_str()`] with a
+    /// schema. The returned [`UndeclaredAttrs`] lists the attributes which
+    /// were dropped or preserved.
+    ///
+    /// ## Errors
+    /// - [`EntitiesError::Duplicate`] if there are any duplicate entities in `entities`
+    /// - [`EntitiesError::InvalidEntity`] if any entities do not conform to
+    ///   the schema (other than by having undeclared attributes, unless
+    ///   `handling` is [`UndeclaredAttrHandling::Error`])
+    /// - [`EntitiesError::Deserialization`] if there are errors while parsing the json
+    ///
+    /// ```
+    /// # use cedar_policy::{Entities, EntityUid, Schema, UndeclaredAttrHandling};
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str("entity User { name: String };").unwrap();
+    /// let data = r#"[
+    ///     { "uid": {"type": "User", "id": "alice"}, "attrs": {"name": "Alice", "team": "blue"}, "parents": [] }
+    /// ]"#;
+    /// let (entities, undeclared) = Entities::from_json_str_with_undeclared_attrs(
+    ///     data,
+    ///     &schema,
+    ///     UndeclaredAttrHandling::Preserve,
+    /// ).unwrap();
+    /// let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    /// assert!(entities.get(&alice).unwrap().attr("team").is_none());
+    /// assert_eq!(undeclared.get(&alice, "team"), Some(&serde_json::json!("blue")));
+    /// ```
+    pub fn from_json_str_with_undeclared_attrs(
+        json: &str,
+        schema: &Schema,
+        handling: UndeclaredAttrHandling,
+    ) -> Result<(Self, UndeclaredAttrs), EntitiesError> {
+        let schema = cedar_policy_core::validator::CoreSchema::new(&schema.0);
+        let eparser = cedar_policy_core::entities::EntityJsonParser::new(
+            Some(&schema),
+            Extensions::all_available(),
+            cedar_policy_core::entities::TCComputation::ComputeNow,
+        )
+        .with_undeclared_attr_handling(handling);
+        eparser
+            .from_json_str_with_undeclared_attrs(json)
+            .map(|(entities, undeclared)| (Self(entities), UndeclaredAttrs(undeclared)))
+    }
+
+    /// Parse an entities JSON file (in `serde_json::Value` form) into an
+    /// `Entities` object, treating attributes which are not declared in the
+    /// `schema` according to `handling` instead of always returning an error.
+    ///
+    /// This otherwise behaves like [`Entities::from_json_value()`] with a
+    /// schema. The returned [`UndeclaredAttrs`] lists the attributes which
+    /// were dropped or preserved.
+    ///
+    /// ## Errors
+    /// - [`EntitiesError::Duplicate`] if there are any duplicate entities in `entities`
+    /// - [`EntitiesError::InvalidEntity`] if any entities do not conform to
+    ///   the schema (other than by having undeclared attributes, unless
+    ///   `handling` is [`UndeclaredAttrHandling::Error`])
+    /// - [`EntitiesError::Deserialization`] if there are errors while parsing the json
+    pub fn from_json_value_with_undeclared_attrs(
+        json: serde_json::Value,
+        schema: &Schema,
+        handling: UndeclaredAttrHandling,
+    ) -> Result<(Self, UndeclaredAttrs), EntitiesError> {
+        let schema = cedar_policy_core::validator::CoreSchema::new(&schema.0);
+        let eparser = cedar_policy_core::entities::EntityJsonParser::new(
+            Some(&schema),
+            Extensions::all_available(),
+            cedar_policy_core::entities::TCComputation::ComputeNow,
+        )
+        .with_undeclared_attr_handling(handling);
+        eparser
+            .from_json_value_with_undeclared_attrs(json)
+            .map(|(entities, undeclared)| (Self(entities), UndeclaredAttrs(undeclared)))
+    }
+
+    /// Attach provenance metadata (where the entities' data came from) to
+    /// every entity currently in this `Entities`, replacing any provenance
+    /// they already had.
+    ///
+    /// This is typically called on the entities loaded from one source,
+    /// before merging them with entities from other sources. Provenance is
+    /// attached to the individual entities, so it is preserved by
+    /// [`Entities::add_entities()`] and similar methods. When an entity is
+    /// added which is structurally equal to one already present, the existing
+    /// entity and its provenance are kept.
+    ///
+    /// ```
+    /// # use cedar_policy::{Entities, EntityProvenance, EntityUid};
+    /// # use std::str::FromStr;
+    /// let entities = Entities::from_json_str(r#"[
+    ///     { "uid": {"type": "User", "id": "alice"}, "attrs": {}, "parents": [] }
+    /// ]"#, None).unwrap()
+    /// .with_provenance(EntityProvenance::new().with_source("directory").with_version("42"));
+    /// let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    /// assert_eq!(entities.provenance(&alice).unwrap().source(), Some("directory"));
+    /// ```
+    #[must_use]
+    pub fn with_provenance(self, provenance: EntityProvenance) -> Self {
+        Self(self.0.with_provenance(provenance))
+    }
+
+    /// Get the provenance metadata attached to the entity with the given UID,
+    /// if the entity exists and has any
+    pub fn provenance(&self, uid: &EntityUid) -> Option<&EntityProvenance> {
+        self.0.provenance(uid.as_ref())
     }
 
     /// Is entity `a` an ancestor of entity `b`?
@@ -890,6 +1122,13 @@ impl Entities {
         self.0.len()
     }
 
+    /// Record the number of entities on the current `cedar.entities` span
+    fn record_len(self) -> Self {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("entity_count", self.len());
+        self
+    }
+
     /// Returns true if the `Entities` contains no `Entity`s
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
@@ -917,6 +1156,77 @@ impl Entities {
         self.0.to_json_value()
     }
 
+    /// Dump an `Entities` object into a canonical JSON string, suitable for
+    /// diffing and content hashing.
+    ///
+    /// Equal `Entities` always produce the same string: entities are sorted by
+    /// UID, parents are sorted, object keys are sorted, and extension values
+    /// use a canonical encoding (e.g., `decimal("1.0")` and `decimal("1.00")`
+    /// are encoded the same way). The output is compact, and is suitable for
+    /// parsing in via `from_json_*` even with no `Schema`.
+    ///
+    /// ```
+    /// # use cedar_policy::Entities;
+    /// let json = |parents: &str| format!(r#"[{{
+    ///     "uid": {{"type": "User", "id": "alice"}},
+    ///     "attrs": {{}},
+    ///     "parents": {parents}
+    /// }}]"#);
+    /// let a = Entities::from_json_str(&json(r#"[{"type": "Group", "id": "b"}, {"type": "Group", "id": "a"}]"#), None).unwrap();
+    /// let b = Entities::from_json_str(&json(r#"[{"type": "Group", "id": "a"}, {"type": "Group", "id": "b"}]"#), None).unwrap();
+    /// assert_eq!(a.to_json_canonical().unwrap(), b.to_json_canonical().unwrap());
+    /// ```
+    pub fn to_json_canonical(&self) -> Result<String, EntitiesError> {
+        self.0.to_json_canonical()
+    }
+
+    /// Find every reference from an entity in this `Entities` (in its parents,
+    /// attributes, or tags) to an entity that is not in this `Entities`.
+    ///
+    /// References to any entity in `allowed_dangling` are considered
+    /// intentionally dangling and are not reported. The result is sorted by
+    /// referencing entity.
+    ///
+    /// ```
+    /// # use cedar_policy::{Entities, EntityUid};
+    /// # use std::str::FromStr;
+    /// let entities = Entities::from_json_str(r#"[
+    ///     { "uid": {"type": "User", "id": "alice"}, "attrs": {}, "parents": [{"type": "Group", "id": "admins"}] }
+    /// ]"#, None).unwrap();
+    /// let refs = entities.dangling_references([]);
+    /// assert_eq!(refs.len(), 1);
+    /// assert_eq!(refs[0].target(), &EntityUid::from_str(r#"Group::"admins""#).unwrap());
+    /// ```
+    pub fn dangling_references(
+        &self,
+        allowed_dangling: impl IntoIterator<Item = EntityUid>,
+    ) -> Vec<DanglingReference> {
+        let allowed_dangling = allowed_dangling.into_iter().map(|euid| euid.0).collect();
+        self.0
+            .dangling_references(&allowed_dangling)
+            .into_iter()
+            .map(DanglingReference)
+            .collect()
+    }
+
+    /// Check that every entity referenced by an entity in this `Entities` (in
+    /// its parents, attributes, or tags) is also in this `Entities`, except for
+    /// the entities in `allowed_dangling`.
+    ///
+    /// Dangling references are not an error during authorization (`in` and
+    /// attribute access simply behave as if the entity had no ancestors or
+    /// attributes), so this check is opt-in.
+    /// ## Errors
+    /// - [`entities_errors::DanglingReferencesError`] listing every dangling
+    ///   reference, if there are any
+    pub fn check_referential_integrity(
+        &self,
+        allowed_dangling: impl IntoIterator<Item = EntityUid>,
+    ) -> Result<(), entities_errors::DanglingReferencesError> {
+        let allowed_dangling = allowed_dangling.into_iter().map(|euid| euid.0).collect();
+        Ok(self.0.check_referential_integrity(&allowed_dangling)?)
+    }
+
     #[doc = include_str!("../experimental_warning.md")]
     /// Visualize an `Entities` object in the graphviz `dot`
     /// format. Entity visualization is best-effort and not well tested.
@@ -927,6 +1237,177 @@ impl Entities {
         self.0.to_dot_str(&mut dot_str).unwrap();
         dot_str
     }
+
+    /// Export the entity hierarchy as a typed graph, with an edge from each
+    /// entity to each of its direct parents. Parents which are not in this
+    /// `Entities` are included as nodes, marked as not in the slice.
+    pub fn to_graph(&self) -> EntityGraph {
+        EntityGraph(self.0.to_graph())
+    }
+
+    /// Render the entity hierarchy in the graphviz `dot` format, optionally
+    /// with a summary of each entity's attributes. Entities are clustered by
+    /// type, and parents which are not in this `Entities` are drawn dashed.
+    ///
+    /// Unlike [`Entities::to_dot_str()`], the output is deterministic.
+    ///
+    /// ```
+    /// # use cedar_policy::{DotOptions, Entities};
+    /// let entities = Entities::from_json_str(r#"[
+    ///     { "uid": {"type": "User", "id": "alice"}, "attrs": {"age": 42}, "parents": [{"type": "Group", "id": "admins"}] }
+    /// ]"#, None).unwrap();
+    /// let dot = entities.to_dot(&DotOptions { include_attrs: true, ..DotOptions::default() });
+    /// assert!(dot.contains(r#""User::\"alice\"" -> "Group::\"admins\"""#));
+    /// assert!(dot.contains(r#"[label="alice\nage: 42"]"#));
+    /// ```
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let mut dot_str = String::new();
+        #[expect(clippy::unwrap_used, reason = "writing to a String cannot fail")]
+        self.0.to_dot(&mut dot_str, options).unwrap();
+        dot_str
+    }
+}
+
+pub use cedar_policy_core::ast::EntityProvenance;
+pub use cedar_policy_core::entities::graph::DotOptions;
+
+/// The entity hierarchy of an [`Entities`], as a graph whose nodes are
+/// entities and whose edges go from each entity to each of its direct parents.
+///
+/// Nodes and edges are sorted, so the graph is the same for equal `Entities`.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+pub struct EntityGraph(cedar_policy_core::entities::graph::EntityGraph);
+
+impl EntityGraph {
+    /// The nodes of the graph, sorted by entity UID
+    pub fn nodes(&self) -> impl Iterator<Item = &EntityGraphNode> {
+        self.0.nodes().map(EntityGraphNode::ref_cast)
+    }
+
+    /// The edges of the graph, as `(child, parent)` pairs, sorted
+    pub fn edges(&self) -> impl Iterator<Item = (&EntityUid, &EntityUid)> {
+        self.0
+            .edges()
+            .map(|(child, parent)| (EntityUid::ref_cast(child), EntityUid::ref_cast(parent)))
+    }
+
+    /// Render the graph in the graphviz `dot` format. See
+    /// [`Entities::to_dot()`].
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let mut dot_str = String::new();
+        #[expect(clippy::unwrap_used, reason = "writing to a String cannot fail")]
+        self.0.write_dot(&mut dot_str, options).unwrap();
+        dot_str
+    }
+}
+
+/// A node in an [`EntityGraph`]
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+pub struct EntityGraphNode(cedar_policy_core::entities::graph::EntityGraphNode);
+
+impl EntityGraphNode {
+    /// The entity this node represents
+    pub fn uid(&self) -> &EntityUid {
+        EntityUid::ref_cast(self.0.uid())
+    }
+
+    /// Iterate over the entity's attributes, sorted by name, as (name, value)
+    /// pairs. Empty if the entity is not in the slice.
+    ///
+    /// The value for any individual attribute may be `Err` if the attribute is
+    /// not a value (i.e., is unknown due to partial evaluation).
+    pub fn attrs(
+        &self,
+    ) -> impl Iterator<Item = (&str, Result<EvalResult, PartialValueToValueError>)> {
+        self.0.attrs().map(|(k, v)| {
+            (
+                k.as_ref(),
+                ast::Value::try_from(v.clone()).map(EvalResult::from),
+            )
+        })
+    }
+
+    /// Whether the entity is in the [`Entities`] the graph was exported from.
+    /// This is `false` for nodes which only exist because they are the parent
+    /// of an entity which is.
+    pub fn in_slice(&self) -> bool {
+        self.0.in_slice()
+    }
+}
+
+pub use cedar_policy_core::entities::UndeclaredAttrHandling;
+
+/// Attributes which were present in entity JSON but not declared in the
+/// schema, and were dropped or preserved rather than causing an error.
+/// See [`UndeclaredAttrHandling`].
+#[repr(transparent)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, RefCast)]
+pub struct UndeclaredAttrs(cedar_policy_core::entities::UndeclaredAttrs);
+
+impl UndeclaredAttrs {
+    /// Returns `true` if no undeclared attributes were encountered
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the (entity, attribute) pairs for every undeclared
+    /// attribute that was encountered, whether dropped or preserved
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityUid, &str)> {
+        self.0
+            .iter()
+            .map(|(uid, attr)| (EntityUid::ref_cast(uid), attr.as_str()))
+    }
+
+    /// Get the preserved JSON value of the undeclared attribute `attr` of
+    /// entity `uid`. Returns `None` if there is no such attribute, or if it
+    /// was dropped rather than preserved.
+    pub fn get(&self, uid: &EntityUid, attr: &str) -> Option<&serde_json::Value> {
+        self.0.get(uid.as_ref(), attr)
+    }
+
+    /// Iterate over the undeclared attributes of entity `uid`, with their
+    /// JSON values if they were preserved
+    pub fn attrs_of(
+        &self,
+        uid: &EntityUid,
+    ) -> impl Iterator<Item = (&str, Option<&serde_json::Value>)> {
+        self.0
+            .attrs_of(uid.as_ref())
+            .map(|(attr, val)| (attr.as_str(), val))
+    }
+}
+
+pub use cedar_policy_core::entities::integrity::ReferenceSite;
+
+/// A reference from an entity to an entity that does not exist in the
+/// [`Entities`] it belongs to
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, RefCast)]
+pub struct DanglingReference(cedar_policy_core::entities::integrity::DanglingReference);
+
+impl DanglingReference {
+    /// The entity containing the reference
+    pub fn referrer(&self) -> &EntityUid {
+        EntityUid::ref_cast(self.0.referrer())
+    }
+
+    /// The referenced entity, which does not exist
+    pub fn target(&self) -> &EntityUid {
+        EntityUid::ref_cast(self.0.target())
+    }
+
+    /// Where in the referencing entity the reference was found
+    pub fn site(&self) -> &ReferenceSite {
+        self.0.site()
+    }
+}
+
+impl std::fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 /// Validates scope variables against the provided schema
@@ -1060,6 +1541,49 @@ impl Authorizer {
         Self(authorizer::Authorizer::new())
     }
 
+    /// Evaluate requests to this `Authorizer` in `environment`, e.g., with a
+    /// [`FixedClock`] so that tests and replays of past requests don't depend
+    /// on the current time.
+    ///
+    /// ```
+    /// # use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, EvalEnvironment, FixedClock, PolicySet, Request};
+    /// # use std::str::FromStr;
+    /// # use std::time::{Duration, SystemTime};
+    /// let policies = PolicySet::from_str(r#"
+    ///     permit(principal, action, resource) when { context.now < datetime("2025-01-01") };
+    /// "#).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"a""#).unwrap(),
+    ///     Context::empty(),
+    ///     None,
+    /// ).unwrap();
+    /// // 2024-01-01T00:00:00Z
+    /// let clock = FixedClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200));
+    /// let authorizer = Authorizer::new()
+    ///     .with_environment(EvalEnvironment::new(clock).with_current_time_attr("now"));
+    /// let response = authorizer.is_authorized(&request, &policies, &Entities::empty());
+    /// assert_eq!(response.decision(), Decision::Allow);
+    /// ```
+    #[must_use]
+    pub fn with_environment(self, environment: EvalEnvironment) -> Self {
+        Self(self.0.with_environment(environment))
+    }
+
+    /// Evaluate requests to this `Authorizer` with the builtin and custom
+    /// extensions of `extensions`, rather than [`ExtensionSet::all_available()`]
+    #[must_use]
+    pub fn with_extensions(self, extensions: &ExtensionSet) -> Self {
+        Self(self.0.with_extensions(extensions.shared()))
+    }
+
+    /// The environment requests to this `Authorizer` are evaluated in. See
+    /// [`Authorizer::with_environment()`].
+    pub fn environment(&self) -> &EvalEnvironment {
+        self.0.environment()
+    }
+
     /// Returns an authorization response for `r` with respect to the given
     /// `PolicySet` and `Entities`.
     ///
@@ -1113,8 +1637,37 @@ impl Authorizer {
     /// let response = authorizer.is_authorized(&request, &policy, &entities);
     /// assert_eq!(response.decision(), Decision::Allow);
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.authorize",
+            level = "debug",
+            skip_all,
+            fields(
+                policy_count = p.num_of_policies(),
+                entity_count = e.len(),
+                decision = tracing::field::Empty
+            )
+        )
+    )]
     pub fn is_authorized(&self, r: &Request, p: &PolicySet, e: &Entities) -> Response {
-        self.0.is_authorized(r.0.clone(), &p.ast, &e.0).into()
+        let response: Response = self.0.is_authorized(r.0.clone(), &p.ast, &e.0).into();
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("decision", tracing::field::debug(response.decision()));
+        response
+    }
+
+    /// Like [`Authorizer::is_authorized()`], but also evaluates the policies
+    /// disabled with [`PolicySet::set_enabled()`]. The disabled policies that
+    /// the request would have satisfied are reported in
+    /// [`Diagnostics::would_have_applied()`]; they do not affect the decision.
+    pub fn is_authorized_dry_run(&self, r: &Request, p: &PolicySet, e: &Entities) -> Response {
+        let (response, would_have_applied) =
+            self.0.is_authorized_dry_run(r.0.clone(), &p.ast, &e.0);
+        let mut response = Response::from(response);
+        response.diagnostics.would_have_applied =
+            would_have_applied.into_iter().map(PolicyId::new).collect();
+        response
     }
 
     /// A partially evaluated authorization request.
@@ -1134,6 +1687,34 @@ impl Authorizer {
             .is_authorized_core(query.0.clone(), &policy_set.ast, &entities.0);
         PartialResponse(response)
     }
+
+    /// Resume evaluation of a partial response when more data becomes
+    /// available, re-evaluating only its residuals rather than the whole
+    /// policy set.
+    ///
+    /// `additional_entities` should contain the entities which were missing
+    /// from the (partial) entity store, as well as any other entities the
+    /// residuals still refer to. Entities which are still missing stay
+    /// unknown if it is a partial store (see [`Entities::partial()`]).
+    ///
+    /// If the context of the request was unknown, `additional_context` becomes
+    /// the context, unless it is empty. Otherwise, it provides some of the
+    /// attributes declared with [`RequestBuilder::unknown_context_attributes()`],
+    /// and the others stay unknown. Providing any other attribute is an error.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn resume(
+        &self,
+        residual_response: &PartialResponse,
+        additional_entities: &Entities,
+        additional_context: &Context,
+    ) -> Result<PartialResponse, ReauthorizationError> {
+        let response =
+            residual_response
+                .0
+                .resume(&self.0, &additional_entities.0, &additional_context.0)?;
+        Ok(PartialResponse(response))
+    }
 }
 
 /// Authorization response returned from the `Authorizer`
@@ -1227,6 +1808,18 @@ impl PartialResponse {
         entity_uids
     }
 
+    /// Returns the attributes of the context which residuals access or check
+    /// for, and which were declared unknown with
+    /// [`RequestBuilder::unknown_context_attributes()`] (or are attributes of
+    /// an unknown context). Uses of the context as a whole aren't included.
+    pub fn unknown_context_attributes(&self) -> HashSet<String> {
+        self.0
+            .all_residuals()
+            .flat_map(|policy| policy.unknown_context_attrs())
+            .map(Into::into)
+            .collect()
+    }
+
     /// Return the residual for a given [`PolicyId`], if it exists in the response
     pub fn get(&self, id: &PolicyId) -> Option<Policy> {
         self.0.get(id.as_ref()).map(Policy::from_ast)
@@ -1255,8 +1848,7 @@ impl PartialResponse {
         auth: &Authorizer,
         es: &Entities,
     ) -> Result<Self, ReauthorizationError> {
-        let exts = Extensions::all_available();
-        let evaluator = RestrictedEvaluator::new(exts);
+        let evaluator = RestrictedEvaluator::new(auth.0.extensions());
         let mapping = mapping
             .into_iter()
             .map(|(name, expr)| {
@@ -1287,6 +1879,15 @@ pub struct Diagnostics {
     /// Errors that occurred during authorization. The errors should be
     /// treated as unordered, since policies may be evaluated in any order.
     errors: Vec<AuthorizationError>,
+    /// Version of the [`PolicySetSnapshot`] the request was authorized
+    /// against, if any
+    policy_set_version: Option<u64>,
+    /// `PolicyId`s of the disabled policies that the request would have
+    /// satisfied. Only computed by [`Authorizer::is_authorized_dry_run()`].
+    would_have_applied: HashSet<PolicyId>,
+    /// Name of the layer which denied the request. Only computed by
+    /// [`Authorizer::is_authorized_layered()`].
+    deciding_layer: Option<String>,
 }
 
 #[doc(hidden)]
@@ -1295,6 +1896,9 @@ impl From<authorizer::Diagnostics> for Diagnostics {
         Self {
             reason: diagnostics.reason.into_iter().map(PolicyId::new).collect(),
             errors: diagnostics.errors.into_iter().map(Into::into).collect(),
+            policy_set_version: None,
+            would_have_applied: HashSet::new(),
+            deciding_layer: None,
         }
     }
 }
@@ -1419,6 +2023,27 @@ impl Diagnostics {
         self.errors.iter()
     }
 
+    /// Get the version of the [`PolicySetSnapshot`] that the request was
+    /// authorized against. This is `None` unless the request was authorized
+    /// with [`PolicySetSnapshot::is_authorized()`].
+    pub fn policy_set_version(&self) -> Option<u64> {
+        self.policy_set_version
+    }
+
+    /// Get the name of the layer which denied the request. This is `None`
+    /// unless the request was authorized with
+    /// [`Authorizer::is_authorized_layered()`] and denied by one of the layers.
+    pub fn deciding_layer(&self) -> Option<&str> {
+        self.deciding_layer.as_deref()
+    }
+
+    /// Get the `PolicyId`s of the disabled policies that the request would
+    /// have satisfied had they been enabled. This is empty unless the request
+    /// was authorized with [`Authorizer::is_authorized_dry_run()`].
+    pub fn would_have_applied(&self) -> impl Iterator<Item = &PolicyId> {
+        self.would_have_applied.iter()
+    }
+
     /// Consume the `Diagnostics`, producing owned versions of `reason()` and `errors()`
     pub(crate) fn into_components(
         self,
@@ -1439,7 +2064,13 @@ impl Response {
     ) -> Self {
         Self {
             decision,
-            diagnostics: Diagnostics { reason, errors },
+            diagnostics: Diagnostics {
+                reason,
+                errors,
+                policy_set_version: None,
+                would_have_applied: HashSet::new(),
+                deciding_layer: None,
+            },
         }
     }
 
@@ -1498,6 +2129,8 @@ impl From<ValidationMode> for cedar_policy_core::validator::ValidationMode {
     }
 }
 
+pub use cedar_policy_core::validator::{ValidatorConfig, WarningSeverity};
+
 /// Validator object, which provides policy validation and typechecking.
 #[repr(transparent)]
 #[derive(Debug, Clone, RefCast)]
@@ -1517,6 +2150,46 @@ impl Validator {
         Self(cedar_policy_core::validator::Validator::new(schema.0))
     }
 
+    /// Construct a new `Validator` to validate policies using the given
+    /// `Schema`, reporting validation warnings as configured in `config`.
+    ///
+    /// Independently of `config`, a policy can allow kinds of warnings, which
+    /// are then never reported for it, by listing their names in an
+    /// `@allow_warning` annotation.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicySet, Schema, ValidationMode, ValidationWarningKind, Validator, ValidatorConfig, WarningSeverity};
+    /// # use std::str::FromStr;
+    /// let (schema, _) = Schema::from_cedarschema_str(
+    ///     "entity User; action view appliesTo { principal: User, resource: User };",
+    /// )
+    /// .unwrap();
+    /// let config = ValidatorConfig::new()
+    ///     .with_warning_severity(ValidationWarningKind::ImpossiblePolicy, WarningSeverity::Error);
+    /// let validator = Validator::with_config(schema, config);
+    ///
+    /// let pset = PolicySet::from_str("permit(principal, action, resource) when { false };").unwrap();
+    /// assert!(!validator.validate(&pset, ValidationMode::Strict).validation_passed());
+    ///
+    /// let pset = PolicySet::from_str(r#"
+    ///     @allow_warning("impossible_policy")
+    ///     permit(principal, action, resource) when { false };
+    /// "#).unwrap();
+    /// assert!(validator.validate(&pset, ValidationMode::Strict).validation_passed_without_warnings());
+    /// ```
+    pub fn with_config(schema: Schema, config: ValidatorConfig) -> Self {
+        Self(cedar_policy_core::validator::Validator::with_config(
+            schema.0, config,
+        ))
+    }
+
+    /// Typecheck calls to the functions of `extensions`, rather than of
+    /// [`ExtensionSet::all_available()`]
+    #[must_use]
+    pub fn with_extensions(self, extensions: &ExtensionSet) -> Self {
+        Self(self.0.with_extensions(extensions.as_core()))
+    }
+
     /// Get the `Schema` this `Validator` is using.
     pub fn schema(&self) -> &Schema {
         RefCast::ref_cast(self.0.schema())
@@ -1529,6 +2202,20 @@ impl Validator {
     /// that policy passed the validator. If the function `validation_passed`
     /// returns true, then there were no validation errors found, so all
     /// policies in the policy set have passed the validator.
+    ///
+    /// Policies with a `@max_deref_level("N")` annotation, and all policies
+    /// if the [`ValidatorConfig`] sets a maximum level with
+    /// [`ValidatorConfig::with_max_deref_level()`], are also level validated
+    /// (RFC 76).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.validate",
+            level = "debug",
+            skip_all,
+            fields(policy_count = pset.num_of_policies(), ?mode)
+        )
+    )]
     pub fn validate(&self, pset: &PolicySet, mode: ValidationMode) -> ValidationResult {
         ValidationResult::from(self.0.validate(&pset.ast, mode.into()))
     }
@@ -1540,6 +2227,34 @@ impl Validator {
     /// appear in the output iterator, then that policy passed the validator. If the function
     /// `validation_passed` returns true, then there were no validation errors found, so
     /// all policies in the policy set have passed the validator.
+    ///
+    /// A policy with a `@max_deref_level("N")` annotation is validated with
+    /// level `N` instead of `max_deref_level`. Each
+    /// [`ValidationError::EntityDerefLevelViolation`] names the chain of
+    /// entity dereferences which exceeds the policy's level.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicySet, Schema, ValidationError, ValidationMode, Validator};
+    /// # use std::str::FromStr;
+    /// let (schema, _) = Schema::from_cedarschema_str(
+    ///     "entity User { manager: User, name: String }; action view appliesTo { principal: User, resource: User };",
+    /// )
+    /// .unwrap();
+    /// let pset = PolicySet::from_str(
+    ///     r#"
+    ///     permit(principal, action, resource) when { principal.manager.name == "alice" };
+    ///     @max_deref_level("2")
+    ///     permit(principal, action, resource) when { principal.manager.name == "bob" };
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// let result = Validator::new(schema).validate_with_level(&pset, ValidationMode::Strict, 1);
+    /// let errors: Vec<_> = result.validation_errors().collect();
+    /// assert_eq!(errors.len(), 1);
+    /// let ValidationError::EntityDerefLevelViolation(e) = errors[0] else { panic!() };
+    /// assert_eq!(e.access_chain(), Some("principal.manager.name"));
+    /// assert_eq!(e.actual_level(), Some(2));
+    /// ```
     pub fn validate_with_level(
         &self,
         pset: &PolicySet,
@@ -1733,6 +2448,83 @@ impl SchemaFragment {
         get_annotation_by_key(&ns_def.entity_types.get(&ty)?.annotations, annotation_key)
     }
 
+    /// Get annotations of an attribute declared in the shape of an entity
+    /// type, e.g., `@doc` or `@deprecated`
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `ty` is not a valid entity type name
+    /// or `ty` is not found in the corresponding namespace definition
+    /// or `ty` does not declare an attribute `attr`
+    pub fn entity_attribute_annotations(
+        &self,
+        namespace: Option<EntityNamespace>,
+        ty: &str,
+        attr: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.entity_attribute(namespace, ty, attr)
+            .map(|attr| annotations_to_pairs(&attr.annotations))
+    }
+
+    /// Get annotation value of an attribute declared in the shape of an
+    /// entity type by annotation key `annotation_key`
+    ///
+    /// ```
+    /// # use cedar_policy::SchemaFragment;
+    /// let (fragment, _) = SchemaFragment::from_cedarschema_str(r#"
+    ///     entity User {
+    ///         @doc("Primary email address")
+    ///         email: String,
+    ///         @deprecated("use `email` instead")
+    ///         mail?: String,
+    ///     };
+    /// "#).unwrap();
+    /// assert_eq!(fragment.entity_attribute_annotation(None, "User", "email", "doc"), Some("Primary email address"));
+    /// assert_eq!(fragment.entity_attribute_annotation(None, "User", "mail", "deprecated"), Some("use `email` instead"));
+    /// assert_eq!(fragment.entity_attribute_annotation(None, "User", "email", "deprecated"), None);
+    /// ```
+    ///
+    /// Returns `None` if `namespace` is not found in the [`SchemaFragment`]
+    /// or `ty` is not a valid entity type name
+    /// or `ty` is not found in the corresponding namespace definition
+    /// or `ty` does not declare an attribute `attr`
+    /// or `annotation_key` is not a valid annotation key
+    /// or it does not exist
+    pub fn entity_attribute_annotation(
+        &self,
+        namespace: Option<EntityNamespace>,
+        ty: &str,
+        attr: &str,
+        annotation_key: impl AsRef<str>,
+    ) -> Option<&str> {
+        get_annotation_by_key(
+            &self.entity_attribute(namespace, ty, attr)?.annotations,
+            annotation_key,
+        )
+    }
+
+    /// Get the declaration of attribute `attr` of entity type `ty`, if `ty`
+    /// is declared with a record shape (rather than a common type)
+    fn entity_attribute(
+        &self,
+        namespace: Option<EntityNamespace>,
+        ty: &str,
+        attr: &str,
+    ) -> Option<&json_schema::TypeOfAttribute<cedar_policy_core::validator::RawName>> {
+        let ns_def = self.lossless.0.get(&namespace.map(|n| n.0))?;
+        let ty = ast::UnreservedId::from_normalized_str(ty).ok()?;
+        match &ns_def.entity_types.get(&ty)?.kind {
+            json_schema::EntityTypeKind::Standard(json_schema::StandardEntityType {
+                shape:
+                    json_schema::AttributesOrContext(json_schema::Type::Type {
+                        ty: json_schema::TypeVariant::Record(record),
+                        ..
+                    }),
+                ..
+            }) => record.attributes.get(attr),
+            _ => None,
+        }
+    }
+
     /// Get annotations of an action declaration
     ///
     /// Returns `None` if `namespace` is not found in the [`SchemaFragment`] or
@@ -1872,6 +2664,32 @@ impl SchemaFragment {
         let str = self.lossless.to_cedarschema()?;
         Ok(str)
     }
+
+    /// Generate documentation for the entity types, actions, and common types
+    /// declared in this [`SchemaFragment`], including their annotations. The
+    /// documentation can be serialized as JSON, or rendered with
+    /// [`SchemaDoc::to_markdown()`] or [`SchemaDoc::to_html()`].
+    ///
+    /// The `@doc` annotation on a declaration is rendered as its description.
+    /// Documentation is generated from a [`SchemaFragment`] rather than a
+    /// [`Schema`], because a [`Schema`] does not keep annotations or common
+    /// type declarations.
+    ///
+    /// ```
+    /// # use cedar_policy::SchemaFragment;
+    /// let (fragment, _) = SchemaFragment::from_cedarschema_str(r#"
+    ///     @doc("A person using the app")
+    ///     entity User { name: String };
+    ///     entity Photo;
+    ///     action view appliesTo { principal: User, resource: Photo };
+    /// "#).unwrap();
+    /// let doc = fragment.to_doc();
+    /// assert_eq!(doc.namespaces[0].entity_types[0].name, "Photo");
+    /// assert!(doc.to_markdown().contains("A person using the app"));
+    /// ```
+    pub fn to_doc(&self) -> SchemaDoc {
+        SchemaDoc::from(&self.lossless)
+    }
 }
 
 impl TryInto<Schema> for SchemaFragment {
@@ -1902,6 +2720,13 @@ impl FromStr for SchemaFragment {
     }
 }
 
+pub use cedar_policy_core::validator::schema_doc::{
+    ActionDoc, AttributeDoc, CommonTypeDoc, EntityTypeDoc, NamespaceDoc, SchemaDoc,
+};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use cedar_policy_core::validator::FileSchemaLoader;
+pub use cedar_policy_core::validator::SchemaSourceLoader;
+
 /// Object containing schema information used by the validator.
 #[repr(transparent)]
 #[derive(Debug, Clone, RefCast)]
@@ -1953,6 +2778,10 @@ impl Schema {
 
     /// Create a [`Schema`] from a JSON value (which should be an object of the
     /// shape required for the JSON schema format).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cedar.parse_schema", level = "debug", skip_all)
+    )]
     pub fn from_json_value(json: serde_json::Value) -> Result<Self, SchemaError> {
         Ok(Self(
             cedar_policy_core::validator::ValidatorSchema::from_json_value(
@@ -1964,6 +2793,10 @@ impl Schema {
 
     /// Create a [`Schema`] from a string containing JSON in the appropriate
     /// shape.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cedar.parse_schema", level = "debug", skip_all)
+    )]
     pub fn from_json_str(json: &str) -> Result<Self, SchemaError> {
         Ok(Self(
             cedar_policy_core::validator::ValidatorSchema::from_json_str(
@@ -1975,6 +2808,10 @@ impl Schema {
 
     /// Create a [`Schema`] directly from a file containing JSON in the
     /// appropriate shape.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cedar.parse_schema", level = "debug", skip_all)
+    )]
     pub fn from_json_file(file: impl std::io::Read) -> Result<Self, SchemaError> {
         Ok(Self(
             cedar_policy_core::validator::ValidatorSchema::from_json_file(
@@ -1985,6 +2822,10 @@ impl Schema {
     }
 
     /// Parse the schema from a reader, in the Cedar schema format.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cedar.parse_schema", level = "debug", skip_all)
+    )]
     pub fn from_cedarschema_file(
         file: impl std::io::Read,
     ) -> Result<(Self, impl Iterator<Item = SchemaWarning> + 'static), CedarSchemaError> {
@@ -1997,6 +2838,10 @@ impl Schema {
     }
 
     /// Parse the schema from a string, in the Cedar schema format.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cedar.parse_schema", level = "debug", skip_all)
+    )]
     pub fn from_cedarschema_str(
         src: &str,
     ) -> Result<(Self, impl Iterator<Item = SchemaWarning>), CedarSchemaError> {
@@ -2008,12 +2853,80 @@ impl Schema {
         Ok((Self(schema), warnings))
     }
 
+    /// Parse the schema from the source `root`, in the Cedar schema format,
+    /// along with every source it (transitively) imports. A source imports
+    /// other sources with `import "<name>";` declarations before any
+    /// namespace or declaration, and sources are loaded with `loader` (e.g.,
+    /// a [`FileSchemaLoader`], or a `HashMap` from names to sources).
+    ///
+    /// Each source is loaded only once, even if it is imported more than
+    /// once. It is an error for sources to import each other cyclically, or
+    /// for an entity type, common type, or action to be defined in more than
+    /// one source.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityTypeName, Schema};
+    /// # use std::collections::HashMap;
+    /// # use std::str::FromStr;
+    /// let mut sources = HashMap::from([
+    ///     (
+    ///         "app.cedarschema".to_string(),
+    ///         r#"import "users.cedarschema";
+    ///         entity Document;
+    ///         action view appliesTo { principal: User, resource: Document };"#
+    ///             .to_string(),
+    ///     ),
+    ///     ("users.cedarschema".to_string(), "entity User;".to_string()),
+    /// ]);
+    /// let (schema, _) = Schema::from_cedarschema_with_imports("app.cedarschema", &mut sources).unwrap();
+    /// let user = EntityTypeName::from_str("User").unwrap();
+    /// assert!(schema.entity_types().any(|ty| ty == &user));
+    /// ```
+    pub fn from_cedarschema_with_imports(
+        root: &str,
+        loader: &mut impl SchemaSourceLoader,
+    ) -> Result<(Self, impl Iterator<Item = SchemaWarning>), CedarSchemaError> {
+        let (schema, warnings) =
+            cedar_policy_core::validator::ValidatorSchema::from_cedarschema_with_imports(
+                root,
+                loader,
+                Extensions::all_available(),
+            )?;
+        Ok((Self(schema), warnings))
+    }
+
     /// Extract from the schema an [`Entities`] containing the action entities
     /// declared in the schema.
     pub fn action_entities(&self) -> Result<Entities, EntitiesError> {
         Ok(Entities(self.0.action_entities()?))
     }
 
+    /// Check that every action declared in the schema applies to at least one
+    /// principal type and one resource type. Actions with no `appliesTo`, or
+    /// with an empty list of principal or resource types, are allowed in a
+    /// schema but apply to no request. To also reject policies whose action
+    /// scope constraint includes such actions, validate them with
+    /// [`ValidatorConfig::with_fully_specified_actions()`].
+    ///
+    /// ```
+    /// # use cedar_policy::Schema;
+    /// let (schema, _) = Schema::from_cedarschema_str(r#"
+    ///     entity User;
+    ///     action view appliesTo { principal: User, resource: User };
+    ///     action audit;
+    /// "#).unwrap();
+    /// let err = schema.check_fully_specified_actions().unwrap_err();
+    /// assert_eq!(err.to_string(), r#"action Action::"audit" is not fully specified"#);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`SchemaError::UnspecifiedActions`] error listing the actions
+    /// which are not fully specified.
+    pub fn check_fully_specified_actions(&self) -> Result<(), SchemaError> {
+        self.0.check_fully_specified_actions()
+    }
+
     /// Returns an iterator over every entity type that can be a principal for any action in this schema
     ///
     /// Note: this iterator may contain duplicates.
@@ -2109,44 +3022,214 @@ impl Schema {
     ///
     /// ## Errors
     ///
-    /// Returns [`None`] if the `ty` is not found in the schema
-    pub fn ancestors<'a>(
+    /// Returns [`None`] if the `ty` is not found in the schema
+    pub fn ancestors<'a>(
+        &'a self,
+        ty: &'a EntityTypeName,
+    ) -> Option<impl Iterator<Item = &'a EntityTypeName> + 'a> {
+        self.0
+            .ancestors(&ty.0)
+            .map(|iter| iter.map(RefCast::ref_cast))
+    }
+
+    /// Returns an iterator over all the action groups defined in this schema
+    pub fn action_groups(&self) -> impl Iterator<Item = &EntityUid> {
+        self.0.action_groups().map(RefCast::ref_cast)
+    }
+
+    /// Returns an iterator over all entity types defined in this schema
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityTypeName> {
+        self.0
+            .entity_types()
+            .map(|ety| RefCast::ref_cast(ety.name()))
+    }
+
+    /// Returns an iterator over all actions defined in this schema
+    pub fn actions(&self) -> impl Iterator<Item = &EntityUid> {
+        self.0.actions().map(RefCast::ref_cast)
+    }
+
+    /// Returns an iterator over the actions that apply to this principal and
+    /// resource type, as specified by the `appliesTo` block for the action in
+    /// this schema.
+    pub fn actions_for_principal_and_resource<'a: 'b, 'b>(
+        &'a self,
+        principal_type: &'b EntityTypeName,
+        resource_type: &'b EntityTypeName,
+    ) -> impl Iterator<Item = &'a EntityUid> + 'b {
+        self.0
+            .actions_for_principal_and_resource(&principal_type.0, &resource_type.0)
+            .map(RefCast::ref_cast)
+    }
+
+    /// Returns an iterator over all the actions which are (transitively)
+    /// members of the action group `action`
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn action_descendants<'a>(
+        &'a self,
+        action: &EntityUid,
+    ) -> Option<impl Iterator<Item = &'a EntityUid> + 'a> {
+        self.0
+            .action_descendants(&action.0)
+            .map(|iter| iter.map(RefCast::ref_cast))
+    }
+
+    /// Returns an iterator over all the action groups which `action` is
+    /// (transitively) a member of
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn action_ancestors<'a>(
+        &'a self,
+        action: &'a EntityUid,
+    ) -> Option<impl Iterator<Item = &'a EntityUid> + 'a> {
+        self.0
+            .action_ancestors(&action.0)
+            .map(|iter| iter.map(RefCast::ref_cast))
+    }
+
+    /// Expand the action group `action` to the concrete actions it contains,
+    /// i.e., the actions which are (transitively) members of `action` and are
+    /// not themselves action groups. If `action` is not an action group, this
+    /// is just `action` itself.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityUid, Schema};
+    /// # use std::collections::HashSet;
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity User;
+    ///     action Read, Write;
+    ///     action ListAll in Read;
+    ///     action GetList, GetLists in ListAll appliesTo { principal: User, resource: User };
+    /// "#).unwrap();
+    /// let read = EntityUid::from_str(r#"Action::"Read""#).unwrap();
+    /// let actions: HashSet<String> = schema
+    ///     .expand_action_group(&read)
+    ///     .unwrap()
+    ///     .map(ToString::to_string)
+    ///     .collect();
+    /// assert_eq!(
+    ///     actions,
+    ///     HashSet::from([r#"Action::"GetList""#.into(), r#"Action::"GetLists""#.into()])
+    /// );
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn expand_action_group<'a>(
         &'a self,
-        ty: &'a EntityTypeName,
-    ) -> Option<impl Iterator<Item = &'a EntityTypeName> + 'a> {
+        action: &EntityUid,
+    ) -> Option<impl Iterator<Item = &'a EntityUid> + 'a> {
         self.0
-            .ancestors(&ty.0)
+            .expand_action_group(&action.0)
             .map(|iter| iter.map(RefCast::ref_cast))
     }
 
-    /// Returns an iterator over all the action groups defined in this schema
-    pub fn action_groups(&self) -> impl Iterator<Item = &EntityUid> {
-        self.0.action_groups().map(RefCast::ref_cast)
+    /// Returns `true` if `action` is `group` or is (transitively) a member of
+    /// `group`, i.e., if `action in group` evaluates to `true` given the
+    /// action hierarchy declared in this schema. Returns `false` if `group` is
+    /// not found in the schema.
+    pub fn is_action_in(&self, action: &EntityUid, group: &EntityUid) -> bool {
+        self.0.is_action_in(&action.0, &group.0)
     }
 
-    /// Returns an iterator over all entity types defined in this schema
-    pub fn entity_types(&self) -> impl Iterator<Item = &EntityTypeName> {
+    /// Validate `context` against the context type this schema declares for
+    /// `action` (see [`Schema::context_type()`]), e.g., to reject a malformed
+    /// context before building the rest of a request.
+    ///
+    /// This only borrows the schema and does no setup of its own, so it is
+    /// cheap to call for every request. Constructing a [`Request`] with a
+    /// schema already performs this check.
+    ///
+    /// ```
+    /// # use cedar_policy::{Context, EntityUid, Schema};
+    /// # use std::str::FromStr;
+    /// let (schema, _) = Schema::from_cedarschema_str(r#"
+    ///     entity User;
+    ///     action view appliesTo { principal: User, resource: User, context: { ip: ipaddr } };
+    /// "#).unwrap();
+    /// let view = EntityUid::from_str(r#"Action::"view""#).unwrap();
+    /// let context = Context::from_json_str(r#"{ "ip": { "__extn": { "fn": "ip", "arg": "10.0.0.1" } } }"#, None).unwrap();
+    /// assert!(schema.validate_context(&context, &view).is_ok());
+    /// assert!(schema.validate_context(&Context::empty(), &view).is_err());
+    /// ```
+    pub fn validate_context(
+        &self,
+        context: &Context,
+        action: &EntityUid,
+    ) -> std::result::Result<(), RequestValidationError> {
+        Ok(RequestSchema::validate_context(
+            &self.0,
+            &context.0,
+            action.as_ref(),
+            Extensions::all_available(),
+        )?)
+    }
+
+    /// Get the action hierarchy declared in this schema, with every action
+    /// group resolved to its fully-qualified name, including action groups
+    /// declared in other namespaces.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityUid, Schema};
+    /// # use std::str::FromStr;
+    /// let (schema, _) = Schema::from_cedarschema_str(r#"
+    ///     namespace Shared { action read; }
+    ///     namespace Photos { action view in [Shared::Action::"read"]; }
+    /// "#).unwrap();
+    /// let view = EntityUid::from_str(r#"Photos::Action::"view""#).unwrap();
+    /// let read = EntityUid::from_str(r#"Shared::Action::"read""#).unwrap();
+    /// let hierarchy = schema.action_hierarchy();
+    /// assert_eq!(hierarchy.parents(&view).unwrap().collect::<Vec<_>>(), [&read]);
+    /// assert_eq!(hierarchy.roots().collect::<Vec<_>>(), [&read]);
+    /// ```
+    pub fn action_hierarchy(&self) -> ActionHierarchy<'_> {
+        ActionHierarchy(self.0.action_hierarchy())
+    }
+}
+
+/// The action hierarchy of a [`Schema`], as a graph with an edge from each
+/// action to each action group it is a direct member of.
+///
+/// If an action is declared as a member of a group, and is also a member of
+/// that group through another group, there is only an edge to the other group.
+/// Actions are sorted, so the graph is the same for equal schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionHierarchy<'a>(cedar_policy_core::validator::ActionHierarchy<'a>);
+
+impl<'a> ActionHierarchy<'a> {
+    /// The action groups `action` is a direct member of, or `None` if `action`
+    /// is not declared in the schema
+    pub fn parents(&self, action: &EntityUid) -> Option<impl Iterator<Item = &'a EntityUid> + '_> {
         self.0
-            .entity_types()
-            .map(|ety| RefCast::ref_cast(ety.name()))
+            .parents(&action.0)
+            .map(|parents| parents.map(EntityUid::ref_cast))
     }
 
-    /// Returns an iterator over all actions defined in this schema
-    pub fn actions(&self) -> impl Iterator<Item = &EntityUid> {
-        self.0.actions().map(RefCast::ref_cast)
+    /// The direct members of the action group `action`, or `None` if `action`
+    /// is not declared in the schema
+    pub fn children(&self, action: &EntityUid) -> Option<impl Iterator<Item = &'a EntityUid> + '_> {
+        self.0
+            .children(&action.0)
+            .map(|children| children.map(EntityUid::ref_cast))
     }
 
-    /// Returns an iterator over the actions that apply to this principal and
-    /// resource type, as specified by the `appliesTo` block for the action in
-    /// this schema.
-    pub fn actions_for_principal_and_resource<'a: 'b, 'b>(
-        &'a self,
-        principal_type: &'b EntityTypeName,
-        resource_type: &'b EntityTypeName,
-    ) -> impl Iterator<Item = &'a EntityUid> + 'b {
+    /// The actions which are not a member of any action group
+    pub fn roots(&self) -> impl Iterator<Item = &'a EntityUid> + '_ {
+        self.0.roots().map(EntityUid::ref_cast)
+    }
+
+    /// The edges of the graph, as `(action, group)` pairs, sorted
+    pub fn edges(&self) -> impl Iterator<Item = (&'a EntityUid, &'a EntityUid)> + '_ {
         self.0
-            .actions_for_principal_and_resource(&principal_type.0, &resource_type.0)
-            .map(RefCast::ref_cast)
+            .edges()
+            .map(|(action, group)| (EntityUid::ref_cast(action), EntityUid::ref_cast(group)))
     }
 }
 
@@ -2245,6 +3328,12 @@ mod test_schema_str_to_json_with_resolved_types {
                     io_error
                 );
             }
+            Err(CedarSchemaError::Import(import_error)) => {
+                panic!(
+                    "Expected TypeNotDefined error, but got import error: {:?}",
+                    import_error
+                );
+            }
         }
     }
 
@@ -2316,6 +3405,24 @@ impl ValidationResult {
         self.validation_warnings.iter()
     }
 
+    /// Summarize the entity types and attributes which validation with
+    /// [`ValidatorConfig::with_incomplete_schema()`] assumed to exist
+    pub fn assumed_unknowns(&self) -> AssumedUnknowns {
+        let mut assumed = AssumedUnknowns::default();
+        for warning in &self.validation_warnings {
+            match warning {
+                ValidationWarning::AssumedEntityType(w) => {
+                    assumed.entity_types.insert(w.entity_type().to_string());
+                }
+                ValidationWarning::AssumedAttribute(w) => {
+                    assumed.attributes.insert(w.attribute());
+                }
+                _ => {}
+            }
+        }
+        assumed
+    }
+
     fn first_error_or_warning(&self) -> Option<&dyn Diagnostic> {
         self.validation_errors
             .first()
@@ -2340,6 +3447,35 @@ impl ValidationResult {
     }
 }
 
+/// Entity types and attributes which validation assumed to exist.
+///
+/// These are not declared in the schema, and validation with
+/// [`ValidatorConfig::with_incomplete_schema()`] assumed they exist. See
+/// [`ValidationResult::assumed_unknowns()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssumedUnknowns {
+    entity_types: BTreeSet<String>,
+    attributes: BTreeSet<String>,
+}
+
+impl AssumedUnknowns {
+    /// The entity types which were assumed to exist, in sorted order
+    pub fn entity_types(&self) -> impl Iterator<Item = &str> {
+        self.entity_types.iter().map(String::as_str)
+    }
+
+    /// The attributes which were assumed to exist, each with the entity type
+    /// or action context it was accessed on, in sorted order
+    pub fn attributes(&self) -> impl Iterator<Item = &str> {
+        self.attributes.iter().map(String::as_str)
+    }
+
+    /// True if no entity types or attributes were assumed to exist
+    pub fn is_empty(&self) -> bool {
+        self.entity_types.is_empty() && self.attributes.is_empty()
+    }
+}
+
 #[doc(hidden)]
 impl From<cedar_policy_core::validator::ValidationResult> for ValidationResult {
     fn from(r: cedar_policy_core::validator::ValidationResult) -> Self {
@@ -2499,6 +3635,58 @@ pub(crate) struct StringifiedPolicySet {
     pub policy_templates: Vec<String>,
 }
 
+impl StringifiedPolicySet {
+    /// Join the static policies and templates into a single string
+    fn join(self) -> String {
+        self.policies
+            .into_iter()
+            .chain(self.policy_templates)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Values for the slots of a template, used with [`PolicySet::link_many`].
+///
+/// Entity slots (`?principal` and `?resource`) are bound to entities, and value
+/// slots declared in a `template(...) =>` header are bound to values of their
+/// declared types.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlotBindings {
+    entities: HashMap<SlotId, EntityUid>,
+    values: HashMap<SlotId, RestrictedExpression>,
+}
+
+impl SlotBindings {
+    /// Create empty bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind the entity slot `slot` (`?principal` or `?resource`) to `euid`
+    #[must_use]
+    pub fn with_entity(mut self, slot: SlotId, euid: EntityUid) -> Self {
+        self.entities.insert(slot, euid);
+        self
+    }
+
+    /// Bind the value slot `slot` to `value`
+    #[must_use]
+    pub fn with_value(mut self, slot: SlotId, value: RestrictedExpression) -> Self {
+        self.values.insert(slot, value);
+        self
+    }
+}
+
+impl From<HashMap<SlotId, EntityUid>> for SlotBindings {
+    fn from(entities: HashMap<SlotId, EntityUid>) -> Self {
+        Self {
+            entities,
+            values: HashMap::new(),
+        }
+    }
+}
+
 /// Represents a set of `Policy`s
 #[derive(Debug, Clone, Default)]
 pub struct PolicySet {
@@ -2509,6 +3697,9 @@ pub struct PolicySet {
     policies: LinkedHashMap<PolicyId, Policy>,
     /// Templates in the set
     templates: LinkedHashMap<PolicyId, Template>,
+    /// Metadata of policies and templates in the set. Every key is a key of
+    /// `policies` or `templates`.
+    metadata: HashMap<PolicyId, PolicyMetadata>,
 }
 
 impl PartialEq for PolicySet {
@@ -2544,7 +3735,30 @@ impl FromStr for PolicySet {
     ///
     /// See [`Policy`] for more.
     fn from_str(policies: &str) -> Result<Self, Self::Err> {
-        let (texts, pset) = parser::parse_policyset_and_also_return_policy_text(policies)?;
+        Self::from_str_with_extensions(policies, &ExtensionSet::all_available())
+    }
+}
+
+impl PolicySet {
+    /// Like [`PolicySet::from_str()`], but the policies may call the functions
+    /// of `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.parse_policies",
+            level = "debug",
+            skip_all,
+            fields(policy_count = tracing::field::Empty)
+        )
+    )]
+    pub fn from_str_with_extensions(
+        policies: &str,
+        extensions: &ExtensionSet,
+    ) -> Result<Self, ParseErrors> {
+        let (texts, pset) = parser::parse_policyset_and_also_return_policy_text_with_extensions(
+            policies,
+            extensions.as_core(),
+        )?;
         #[expect(clippy::expect_used, reason = "By the invariant on `parse_policyset_and_also_return_policy_text(policies)`, every `PolicyId` in `pset.policies()` occurs as a key in `text`.")]
         let policies = pset.policies().map(|p|
             (
@@ -2574,14 +3788,14 @@ impl FromStr for PolicySet {
             ast: pset,
             policies,
             templates,
-        })
+            metadata: HashMap::new(),
+        }
+        .record_len())
     }
-}
 
-impl PolicySet {
     /// Build the policy set AST from the EST
-    fn from_est(est: &est::PolicySet) -> Result<Self, PolicySetError> {
-        let ast: ast::PolicySet = est.clone().try_into()?;
+    fn from_est(est: &est::PolicySet, extensions: &Extensions<'_>) -> Result<Self, PolicySetError> {
+        let ast = est.clone().try_into_ast_policy_set(extensions)?;
         #[expect(
             clippy::expect_used,
             reason = "Since conversion from EST to AST succeeded, every `PolicyId` in `ast.policies()` occurs in `est`"
@@ -2592,7 +3806,7 @@ impl PolicySet {
                 (
                     PolicyId::new(p.id().clone()),
                     Policy {
-                        lossless: LosslessPolicy::Est(est.get_policy(p.id()).expect(
+                        lossless: LosslessPolicy::Est(est.get_policy(p.id(), extensions).expect(
                             "internal invariant violation: policy id exists in asts but not ests",
                         )),
                         ast: p.clone(),
@@ -2622,6 +3836,7 @@ impl PolicySet {
             ast,
             policies,
             templates,
+            metadata: HashMap::new(),
         })
     }
 
@@ -2641,6 +3856,7 @@ impl PolicySet {
             ast,
             policies,
             templates,
+            metadata: HashMap::new(),
         })
     }
 
@@ -2690,29 +3906,84 @@ impl PolicySet {
     }
 
     /// Deserialize the [`PolicySet`] from a JSON string
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.parse_policies",
+            level = "debug",
+            skip_all,
+            fields(policy_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_str(src: impl AsRef<str>) -> Result<Self, PolicySetError> {
         let est: est::PolicySet = serde_json::from_str(src.as_ref())
             .map_err(|e| policy_set_errors::JsonPolicySetError { inner: e })?;
-        Self::from_est(&est)
+        Self::from_est(&est, Extensions::all_available()).map(Self::record_len)
     }
 
     /// Deserialize the [`PolicySet`] from a JSON value
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.parse_policies",
+            level = "debug",
+            skip_all,
+            fields(policy_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_value(src: serde_json::Value) -> Result<Self, PolicySetError> {
+        Self::from_json_value_with_extensions(src, &ExtensionSet::all_available())
+    }
+
+    /// Like [`PolicySet::from_json_value()`], but the policies may call the
+    /// functions of `extensions`, rather than those of
+    /// [`ExtensionSet::all_available()`]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.parse_policies",
+            level = "debug",
+            skip_all,
+            fields(policy_count = tracing::field::Empty)
+        )
+    )]
+    pub fn from_json_value_with_extensions(
+        src: serde_json::Value,
+        extensions: &ExtensionSet,
+    ) -> Result<Self, PolicySetError> {
         let est: est::PolicySet = serde_json::from_value(src)
             .map_err(|e| policy_set_errors::JsonPolicySetError { inner: e })?;
-        Self::from_est(&est)
+        Self::from_est(&est, extensions.as_core()).map(Self::record_len)
     }
 
     /// Deserialize the [`PolicySet`] from a JSON reader
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.parse_policies",
+            level = "debug",
+            skip_all,
+            fields(policy_count = tracing::field::Empty)
+        )
+    )]
     pub fn from_json_file(r: impl std::io::Read) -> Result<Self, PolicySetError> {
         let est: est::PolicySet = serde_json::from_reader(r)
             .map_err(|e| policy_set_errors::JsonPolicySetError { inner: e })?;
-        Self::from_est(&est)
+        Self::from_est(&est, Extensions::all_available()).map(Self::record_len)
     }
 
     /// Serialize the [`PolicySet`] as a JSON value
     pub fn to_json(self) -> Result<serde_json::Value, PolicySetError> {
-        let est = self.est()?;
+        self.to_json_with_extensions(&ExtensionSet::all_available())
+    }
+
+    /// Like [`PolicySet::to_json()`], for policy sets calling the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn to_json_with_extensions(
+        self,
+        extensions: &ExtensionSet,
+    ) -> Result<serde_json::Value, PolicySetError> {
+        let est = self.est(extensions.as_core())?;
         let value = serde_json::to_value(est)
             .map_err(|e| policy_set_errors::JsonPolicySetError { inner: e })?;
         Ok(value)
@@ -2748,7 +4019,7 @@ impl PolicySet {
                         .ast
                         .env()
                         .iter()
-                        .map(|(k, v)| ((*k).into(), v.clone().into()))
+                        .map(|(k, v)| (k.clone().into(), v.clone().into()))
                         .collect(),
                 });
             }
@@ -2785,7 +4056,7 @@ impl PolicySet {
                         .ast
                         .env()
                         .iter()
-                        .map(|(k, v)| ((*k).into(), v.clone().into()))
+                        .map(|(k, v)| (k.clone().into(), v.clone().into()))
                         .collect(),
                 });
             }
@@ -2798,9 +4069,9 @@ impl PolicySet {
     }
 
     /// Get the EST representation of the [`PolicySet`]
-    fn est(self) -> Result<est::PolicySet, PolicyToJsonError> {
+    fn est(self, extensions: &Extensions<'_>) -> Result<est::PolicySet, PolicyToJsonError> {
         let (static_policies, template_links): (Vec<_>, Vec<_>) =
-            fold_partition(self.policies, is_static_or_link)?;
+            fold_partition(self.policies, |p| is_static_or_link(p, extensions))?;
         let static_policies = static_policies.into_iter().collect::<LinkedHashMap<_, _>>();
         let templates = self
             .templates
@@ -2808,7 +4079,7 @@ impl PolicySet {
             .map(|(id, template)| {
                 template
                     .lossless
-                    .est(|| template.ast.clone().into())
+                    .est(|| template.ast.clone().into(), extensions)
                     .map(|est| (id.into(), est))
             })
             .collect::<Result<LinkedHashMap<_, _>, _>>()?;
@@ -2840,19 +4111,16 @@ impl PolicySet {
     /// rules.  Policy formatting can be done through the Cedar policy CLI or
     /// the `cedar-policy-formatter` crate.
     pub fn to_cedar(&self) -> Option<String> {
-        match self.stringify() {
-            Some(StringifiedPolicySet {
-                policies,
-                policy_templates,
-            }) => {
-                let policies_as_vec = policies
-                    .into_iter()
-                    .chain(policy_templates)
-                    .collect::<Vec<_>>();
-                Some(policies_as_vec.join("\n\n"))
-            }
-            None => None,
-        }
+        self.stringify().map(StringifiedPolicySet::join)
+    }
+
+    /// Like [`PolicySet::to_cedar()`], for policy sets calling the functions
+    /// of `extensions`, rather than those of [`ExtensionSet::all_available()`].
+    /// Policies which weren't parsed from the human-readable syntax are
+    /// rendered with the call styles of `extensions`.
+    pub fn to_cedar_with_extensions(&self, extensions: &ExtensionSet) -> Option<String> {
+        self.stringify_with_extensions(extensions)
+            .map(StringifiedPolicySet::join)
     }
 
     /// Get the human-readable Cedar syntax representation of this policy set,
@@ -2872,6 +4140,21 @@ impl PolicySet {
     /// rules.  Policy formatting can be done through the Cedar policy CLI or
     /// the `cedar-policy-formatter` crate.
     pub(crate) fn stringify(&self) -> Option<StringifiedPolicySet> {
+        self.stringify_by(Policy::to_cedar, Template::to_cedar)
+    }
+
+    fn stringify_with_extensions(&self, extensions: &ExtensionSet) -> Option<StringifiedPolicySet> {
+        self.stringify_by(
+            |p| p.to_cedar_with_extensions(extensions),
+            |t| t.to_cedar_with_extensions(extensions),
+        )
+    }
+
+    fn stringify_by(
+        &self,
+        policy_to_cedar: impl Fn(&Policy) -> Option<String>,
+        template_to_cedar: impl Fn(&Template) -> String,
+    ) -> Option<StringifiedPolicySet> {
         let policies = self
             .policies
             .values()
@@ -2879,13 +4162,13 @@ impl PolicySet {
             // before printing, hoping that the size of policy sets is fairly
             // small.
             .sorted_by_key(|p| AsRef::<str>::as_ref(p.id()))
-            .map(Policy::to_cedar)
+            .map(policy_to_cedar)
             .collect::<Option<Vec<_>>>()?;
         let policy_templates = self
             .templates
             .values()
             .sorted_by_key(|t| AsRef::<str>::as_ref(t.id()))
-            .map(Template::to_cedar)
+            .map(template_to_cedar)
             .collect_vec();
 
         Some(StringifiedPolicySet {
@@ -2900,6 +4183,7 @@ impl PolicySet {
             ast: ast::PolicySet::new(),
             policies: LinkedHashMap::new(),
             templates: LinkedHashMap::new(),
+            metadata: HashMap::new(),
         }
     }
 
@@ -2980,6 +4264,12 @@ impl PolicySet {
                         self.templates.insert(pid.clone(), new_t);
                     }
                 }
+                for (old_pid, metadata) in &other.metadata {
+                    let pid = renaming.get(old_pid).unwrap_or(old_pid);
+                    self.metadata
+                        .entry(pid.clone())
+                        .or_insert_with(|| metadata.clone());
+                }
 
                 Ok(renaming)
             }
@@ -3021,6 +4311,7 @@ impl PolicySet {
             .remove_static(&ast::PolicyID::from_string(&policy_id))
             .is_ok()
         {
+            self.metadata.remove(&policy_id);
             Ok(policy)
         } else {
             //Restore self.policies
@@ -3055,7 +4346,10 @@ impl PolicySet {
             .ast
             .remove_template(&ast::PolicyID::from_string(&template_id))
         {
-            Ok(_) => Ok(template),
+            Ok(_) => {
+                self.metadata.remove(&template_id);
+                Ok(template)
+            }
             Err(ast::PolicySetTemplateRemovalError::RemoveTemplateWithLinksError(_)) => {
                 self.templates.insert(template_id.clone(), template);
                 Err(PolicySetError::RemoveTemplateWithActiveLinks(
@@ -3152,6 +4446,13 @@ impl PolicySet {
         self.policies.len()
     }
 
+    /// Record the number of policies on the current `cedar.parse_policies` span
+    fn record_len(self) -> Self {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("policy_count", self.num_of_policies());
+        self
+    }
+
     /// Returns the number of `Template`s in the `PolicySet`.
     pub fn num_of_templates(&self) -> usize {
         self.templates.len()
@@ -3171,11 +4472,57 @@ impl PolicySet {
         new_id: PolicyId,
         vals: HashMap<SlotId, EntityUid>,
     ) -> Result<(), PolicySetError> {
-        let unwrapped_vals: HashMap<ast::SlotId, ast::EntityUID> = vals
+        self.link_many(template_id, [(new_id, vals.into())])?
             .into_iter()
-            .map(|(key, value)| (key.into(), value.into()))
-            .collect();
+            .next()
+            .map_or(Ok(()), |(_, err)| Err(err))
+    }
 
+    /// Attempt to link a template which declares value slots in a
+    /// `template(...) =>` header, and add the new template-linked policy to
+    /// the policy set. `vals` provides the values for `?principal` and
+    /// `?resource`, while `value_vals` provides a value for each declared
+    /// value slot, which must have the type declared for that slot.
+    /// If link fails, the `PolicySet` is not modified.
+    ///
+    /// The linked policy has the values substituted into its conditions, so
+    /// its text and JSON representations are generated from the linked
+    /// policy rather than from the text or JSON of the template.
+    pub fn link_with_values(
+        &mut self,
+        template_id: PolicyId,
+        new_id: PolicyId,
+        vals: HashMap<SlotId, EntityUid>,
+        value_vals: HashMap<SlotId, RestrictedExpression>,
+    ) -> Result<(), PolicySetError> {
+        let bindings = value_vals
+            .into_iter()
+            .fold(SlotBindings::from(vals), |bindings, (slot, value)| {
+                bindings.with_value(slot, value)
+            });
+        self.link_many(template_id, [(new_id, bindings)])?
+            .into_iter()
+            .next()
+            .map_or(Ok(()), |(_, err)| Err(err))
+    }
+
+    /// Link the template `template_id` once for each of the given ids and
+    /// slot bindings, adding the new template-linked policies to the policy
+    /// set. This is equivalent to, but faster than, calling [`PolicySet::link`]
+    /// (or [`PolicySet::link_with_values`]) once per link, because the
+    /// template is only looked up and checked once.
+    ///
+    /// Returns an error, without modifying the `PolicySet`, if `template_id`
+    /// does not correspond to a template. Otherwise, links which fail (e.g.,
+    /// because their bindings don't match the slots of the template, or their
+    /// id conflicts with an existing policy or an earlier link in the batch)
+    /// don't prevent the other links from being added, and are returned with
+    /// their errors, in the order in which they were given.
+    pub fn link_many(
+        &mut self,
+        template_id: PolicyId,
+        links: impl IntoIterator<Item = (PolicyId, SlotBindings)>,
+    ) -> Result<Vec<(PolicyId, PolicySetError)>, PolicySetError> {
         // Try to get the template with the id we're linking from.  We do this
         // _before_ calling `self.ast.link` because `link` mutates the policy
         // set by creating a new link entry in a hashmap. This happens even when
@@ -3192,37 +4539,66 @@ impl PolicySet {
                 .into()
             });
         };
+        let template_id: ast::PolicyID = template_id.into();
 
-        let linked_ast = self.ast.link(
-            template_id.into(),
-            new_id.clone().into(),
-            unwrapped_vals.clone(),
-        )?;
-
-        #[expect(
-            clippy::expect_used,
-            reason = "`lossless.link()` will not fail after `ast.link()` succeeds"
-        )]
-        let linked_lossless = template
-            .lossless
-            .clone()
-            .link(
+        let mut errors = Vec::new();
+        for (new_id, bindings) in links {
+            let unwrapped_vals: HashMap<ast::SlotId, ast::EntityUID> = bindings
+                .entities
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect();
+            let value_slots: ast::ValueSlotEnv = bindings
+                .values
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.0))
+                .collect();
+            let linked_ast = match self.ast.link_with_value_slots(
+                template_id.clone(),
                 new_id.clone().into(),
-                unwrapped_vals.iter().map(|(k, v)| (*k, v)),
-            )
-            // The only error case for `lossless.link()` is a template with
-            // slots which are not filled by the provided values. `ast.link()`
-            // will have already errored if there are any unfilled slots in the
-            // template.
-            .expect("ast.link() didn't fail above, so this shouldn't fail");
-        self.policies.insert(
-            new_id,
-            Policy {
-                ast: linked_ast.clone(),
-                lossless: linked_lossless,
-            },
-        );
-        Ok(())
+                unwrapped_vals.clone(),
+                value_slots,
+            ) {
+                Ok(linked_ast) => linked_ast.clone(),
+                Err(err) => {
+                    errors.push((new_id, err.into()));
+                    continue;
+                }
+            };
+
+            // Links which bind value slots (explicitly or by default) have
+            // the values substituted into their conditions, so the lossless
+            // representation of the template no longer applies to them
+            let has_value_slots = !linked_ast.value_env().is_empty();
+            let linked_lossless = if has_value_slots {
+                LosslessPolicy::Empty
+            } else {
+                #[expect(
+                    clippy::expect_used,
+                    reason = "`lossless.link()` will not fail after `ast.link()` succeeds"
+                )]
+                template
+                    .lossless
+                    .clone()
+                    .link(
+                        new_id.clone().into(),
+                        unwrapped_vals.iter().map(|(k, v)| (k.clone(), v)),
+                    )
+                    // The only error case for `lossless.link()` is a template with
+                    // slots which are not filled by the provided values. `ast.link()`
+                    // will have already errored if there are any unfilled slots in the
+                    // template.
+                    .expect("ast.link() didn't fail above, so this shouldn't fail")
+            };
+            self.policies.insert(
+                new_id,
+                Policy {
+                    ast: linked_ast,
+                    lossless: linked_lossless,
+                },
+            );
+        }
+        Ok(errors)
     }
 
     /// Get all the unknown entities from the policy set
@@ -3236,6 +4612,17 @@ impl PolicySet {
         entity_uids
     }
 
+    /// Get the attributes of the unknown context which the policies in the
+    /// policy set access or check for
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn unknown_context_attributes(&self) -> HashSet<String> {
+        self.policies
+            .values()
+            .flat_map(Policy::unknown_context_attributes)
+            .collect()
+    }
+
     /// Unlink a template-linked policy from the policy set.
     /// Returns the policy that was unlinked.
     pub fn unlink(&mut self, policy_id: PolicyId) -> Result<Policy, PolicySetError> {
@@ -3247,7 +4634,10 @@ impl PolicySet {
         // If self.policies and self.ast disagree, authorization cannot be trusted.
         #[expect(clippy::panic, reason = "We just found the policy in self.policies")]
         match self.ast.unlink(&ast::PolicyID::from_string(&policy_id)) {
-            Ok(_) => Ok(policy),
+            Ok(_) => {
+                self.metadata.remove(&policy_id);
+                Ok(policy)
+            }
             Err(ast::PolicySetUnlinkError::NotLinkError(_)) => {
                 //Restore self.policies
                 self.policies.insert(policy_id.clone(), policy);
@@ -3260,6 +4650,29 @@ impl PolicySet {
             }
         }
     }
+
+    /// Enable or disable the policy or template with id `id`, returning
+    /// whether it was previously enabled. Errors if there is no such policy or
+    /// template.
+    ///
+    /// A disabled policy is kept in the policy set (and is still validated,
+    /// stored, and so on), but is skipped during authorization. Disabling a
+    /// template disables all of its links. Use
+    /// [`Authorizer::is_authorized_dry_run()`] to find the disabled policies
+    /// that would have applied to a request.
+    pub fn set_enabled(&mut self, id: PolicyId, enabled: bool) -> Result<bool, PolicySetError> {
+        self.ast
+            .set_enabled(id.as_ref(), enabled)
+            .ok_or_else(|| policy_set_errors::NoSuchPolicyError { policy_id: id }.into())
+    }
+
+    /// Returns `false` if the policy or template with id `id` has been
+    /// disabled with [`PolicySet::set_enabled()`]. Note that a link to a
+    /// disabled template is skipped during authorization even if the link
+    /// itself is enabled.
+    pub fn is_enabled(&self, id: &PolicyId) -> bool {
+        self.ast.is_enabled(id.as_ref())
+    }
 }
 
 impl std::fmt::Display for PolicySet {
@@ -3267,7 +4680,9 @@ impl std::fmt::Display for PolicySet {
         // prefer to display the lossless format
         let mut policies = self.policies().peekable();
         while let Some(policy) = policies.next() {
-            policy.lossless.fmt(|| policy.ast.clone().into(), f)?;
+            policy
+                .lossless
+                .fmt(|| policy.ast.clone().into(), Extensions::all_available(), f)?;
             if policies.peek().is_some() {
                 writeln!(f)?;
             }
@@ -3280,6 +4695,7 @@ impl std::fmt::Display for PolicySet {
 /// link
 fn is_static_or_link(
     (id, policy): (PolicyId, Policy),
+    extensions: &Extensions<'_>,
 ) -> Result<Either<(ast::PolicyID, est::Policy), TemplateLink>, PolicyToJsonError> {
     match policy.template_id() {
         Some(template_id) => {
@@ -3287,17 +4703,31 @@ fn is_static_or_link(
                 .ast
                 .env()
                 .iter()
-                .map(|(id, euid)| (*id, euid.clone()))
+                .map(|(id, euid)| (id.clone(), euid.clone()))
                 .collect();
+            let value_slots = policy
+                .ast
+                .value_env()
+                .iter()
+                .map(|(id, value)| {
+                    Ok((
+                        id.clone(),
+                        cedar_policy_core::entities::json::CedarValueJson::from_expr(
+                            value.as_borrowed(),
+                        )?,
+                    ))
+                })
+                .collect::<Result<_, entities_json_errors::JsonSerializationError>>()?;
             Ok(Either::Right(TemplateLink {
                 new_id: id.into(),
                 template_id: template_id.clone().into(),
                 values,
+                value_slots,
             }))
         }
         None => policy
             .lossless
-            .est(|| policy.ast.clone().into())
+            .est(|| policy.ast.clone().into(), extensions)
             .map(|est| Either::Left((id.into(), est))),
     }
 }
@@ -3521,7 +4951,21 @@ impl Template {
     /// If the `id` is None, the parser will use the default "policy0".
     /// The behavior around None may change in the future.
     pub fn parse(id: Option<PolicyId>, src: impl AsRef<str>) -> Result<Self, ParseErrors> {
-        let ast = parser::parse_template(id.map(Into::into), src.as_ref())?;
+        Self::parse_with_extensions(id, src, &ExtensionSet::all_available())
+    }
+
+    /// Like [`Template::parse()`], but the template may call the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn parse_with_extensions(
+        id: Option<PolicyId>,
+        src: impl AsRef<str>,
+        extensions: &ExtensionSet,
+    ) -> Result<Self, ParseErrors> {
+        let ast = parser::parse_template_with_extensions(
+            id.map(Into::into),
+            src.as_ref(),
+            extensions.as_core(),
+        )?;
         Ok(Self {
             ast,
             lossless: LosslessTemplate::from_text(Some(src.as_ref())),
@@ -3663,16 +5107,32 @@ impl Template {
     pub fn from_json(
         id: Option<PolicyId>,
         json: serde_json::Value,
+    ) -> Result<Self, PolicyFromJsonError> {
+        Self::from_json_with_extensions(id, json, &ExtensionSet::all_available())
+    }
+
+    /// Like [`Template::from_json()`], but the template may call the functions
+    /// of `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn from_json_with_extensions(
+        id: Option<PolicyId>,
+        json: serde_json::Value,
+        extensions: &ExtensionSet,
     ) -> Result<Self, PolicyFromJsonError> {
         let est: est::Policy = serde_json::from_value(json)
             .map_err(|e| entities_json_errors::JsonDeserializationError::Serde(e.into()))
             .map_err(cedar_policy_core::est::FromJsonError::from)?;
-        Self::from_est(id, est)
+        Self::from_est(id, est, extensions.as_core())
     }
 
-    fn from_est(id: Option<PolicyId>, est: est::Policy) -> Result<Self, PolicyFromJsonError> {
+    fn from_est(
+        id: Option<PolicyId>,
+        est: est::Policy,
+        extensions: &Extensions<'_>,
+    ) -> Result<Self, PolicyFromJsonError> {
         Ok(Self {
-            ast: est.clone().try_into_ast_template(id.map(PolicyId::into))?,
+            ast: est
+                .clone()
+                .try_into_ast_template(id.map(PolicyId::into), extensions)?,
             lossless: LosslessTemplate::Est(est),
         })
     }
@@ -3686,7 +5146,18 @@ impl Template {
 
     /// Get the JSON representation of this `Template`.
     pub fn to_json(&self) -> Result<serde_json::Value, PolicyToJsonError> {
-        let est = self.lossless.est(|| self.ast.clone().into())?;
+        self.to_json_with_extensions(&ExtensionSet::all_available())
+    }
+
+    /// Like [`Template::to_json()`], for templates calling the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn to_json_with_extensions(
+        &self,
+        extensions: &ExtensionSet,
+    ) -> Result<serde_json::Value, PolicyToJsonError> {
+        let est = self
+            .lossless
+            .est(|| self.ast.clone().into(), extensions.as_core())?;
         serde_json::to_value(est).map_err(Into::into)
     }
 
@@ -3707,6 +5178,24 @@ impl Template {
         }
     }
 
+    /// Like [`Template::to_cedar()`], but a template which wasn't parsed from
+    /// the human-readable syntax is rendered with the call styles of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn to_cedar_with_extensions(&self, extensions: &ExtensionSet) -> String {
+        match &self.lossless {
+            LosslessTemplate::Text(text) => text.clone(),
+            lossless => lossless
+                .est(|| self.ast.clone().into(), extensions.as_core())
+                .map_or_else(
+                    |_| self.ast.to_string(),
+                    |est| {
+                        est.display_with_extensions(extensions.as_core())
+                            .to_string()
+                    },
+                ),
+        }
+    }
+
     /// Get the valid [`RequestEnv`]s for this template, according to the schema.
     ///
     /// That is, all the [`RequestEnv`]s in the schema for which this template is
@@ -3719,7 +5208,8 @@ impl Template {
 impl std::fmt::Display for Template {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // prefer to display the lossless format
-        self.lossless.fmt(|| self.ast.clone().into(), f)
+        self.lossless
+            .fmt(|| self.ast.clone().into(), Extensions::all_available(), f)
     }
 }
 
@@ -3905,7 +5395,7 @@ impl Policy {
                 .ast
                 .env()
                 .iter()
-                .map(|(key, value)| ((*key).into(), value.clone().into()))
+                .map(|(key, value)| (key.clone().into(), value.clone().into()))
                 .collect();
             Some(wrapped_vals)
         }
@@ -3965,10 +5455,10 @@ impl Policy {
         match self.ast.template().principal_constraint().as_inner() {
             ast::PrincipalOrResourceConstraint::Any => PrincipalConstraint::Any,
             ast::PrincipalOrResourceConstraint::In(eref) => {
-                PrincipalConstraint::In(self.convert_entity_reference(eref, slot_id).clone())
+                PrincipalConstraint::In(self.convert_entity_reference(eref, &slot_id).clone())
             }
             ast::PrincipalOrResourceConstraint::Eq(eref) => {
-                PrincipalConstraint::Eq(self.convert_entity_reference(eref, slot_id).clone())
+                PrincipalConstraint::Eq(self.convert_entity_reference(eref, &slot_id).clone())
             }
             ast::PrincipalOrResourceConstraint::Is(entity_type) => {
                 PrincipalConstraint::Is(entity_type.as_ref().clone().into())
@@ -3976,7 +5466,7 @@ impl Policy {
             ast::PrincipalOrResourceConstraint::IsIn(entity_type, eref) => {
                 PrincipalConstraint::IsIn(
                     entity_type.as_ref().clone().into(),
-                    self.convert_entity_reference(eref, slot_id).clone(),
+                    self.convert_entity_reference(eref, &slot_id).clone(),
                 )
             }
         }
@@ -4008,10 +5498,10 @@ impl Policy {
         match self.ast.template().resource_constraint().as_inner() {
             ast::PrincipalOrResourceConstraint::Any => ResourceConstraint::Any,
             ast::PrincipalOrResourceConstraint::In(eref) => {
-                ResourceConstraint::In(self.convert_entity_reference(eref, slot_id).clone())
+                ResourceConstraint::In(self.convert_entity_reference(eref, &slot_id).clone())
             }
             ast::PrincipalOrResourceConstraint::Eq(eref) => {
-                ResourceConstraint::Eq(self.convert_entity_reference(eref, slot_id).clone())
+                ResourceConstraint::Eq(self.convert_entity_reference(eref, &slot_id).clone())
             }
             ast::PrincipalOrResourceConstraint::Is(entity_type) => {
                 ResourceConstraint::Is(entity_type.as_ref().clone().into())
@@ -4019,7 +5509,7 @@ impl Policy {
             ast::PrincipalOrResourceConstraint::IsIn(entity_type, eref) => {
                 ResourceConstraint::IsIn(
                     entity_type.as_ref().clone().into(),
-                    self.convert_entity_reference(eref, slot_id).clone(),
+                    self.convert_entity_reference(eref, &slot_id).clone(),
                 )
             }
         }
@@ -4034,7 +5524,7 @@ impl Policy {
     fn convert_entity_reference<'a>(
         &'a self,
         r: &'a ast::EntityReference,
-        slot: ast::SlotId,
+        slot: &ast::SlotId,
     ) -> &'a EntityUid {
         match r {
             ast::EntityReference::EUID(euid) => EntityUid::ref_cast(euid),
@@ -4042,9 +5532,7 @@ impl Policy {
                 clippy::unwrap_used,
                 reason = "This `unwrap` here is safe due the invariant (values total map) on policies"
             )]
-            ast::EntityReference::Slot(_) => {
-                EntityUid::ref_cast(self.ast.env().get(&slot).unwrap())
-            }
+            ast::EntityReference::Slot(_) => EntityUid::ref_cast(self.ast.env().get(slot).unwrap()),
         }
     }
 
@@ -4057,7 +5545,21 @@ impl Policy {
     /// It can also fail if a template was passed in, as this function only accepts static
     /// policies
     pub fn parse(id: Option<PolicyId>, policy_src: impl AsRef<str>) -> Result<Self, ParseErrors> {
-        let inline_ast = parser::parse_policy(id.map(Into::into), policy_src.as_ref())?;
+        Self::parse_with_extensions(id, policy_src, &ExtensionSet::all_available())
+    }
+
+    /// Like [`Policy::parse()`], but the policy may call the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn parse_with_extensions(
+        id: Option<PolicyId>,
+        policy_src: impl AsRef<str>,
+        extensions: &ExtensionSet,
+    ) -> Result<Self, ParseErrors> {
+        let inline_ast = parser::parse_policy_with_extensions(
+            id.map(Into::into),
+            policy_src.as_ref(),
+            extensions.as_core(),
+        )?;
         let (_, ast) = ast::Template::link_static_policy(inline_ast);
         Ok(Self {
             ast,
@@ -4133,11 +5635,21 @@ impl Policy {
     pub fn from_json(
         id: Option<PolicyId>,
         json: serde_json::Value,
+    ) -> Result<Self, PolicyFromJsonError> {
+        Self::from_json_with_extensions(id, json, &ExtensionSet::all_available())
+    }
+
+    /// Like [`Policy::from_json()`], but the policy may call the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn from_json_with_extensions(
+        id: Option<PolicyId>,
+        json: serde_json::Value,
+        extensions: &ExtensionSet,
     ) -> Result<Self, PolicyFromJsonError> {
         let est: est::Policy = serde_json::from_value(json)
             .map_err(|e| entities_json_errors::JsonDeserializationError::Serde(e.into()))
             .map_err(cedar_policy_core::est::FromJsonError::from)?;
-        Self::from_est(id, est)
+        Self::from_est(id, est, extensions.as_core())
     }
 
     /// Get the valid [`RequestEnv`]s for this policy, according to the schema.
@@ -4174,7 +5686,7 @@ impl Policy {
         )]
         let cloned_est = self
             .lossless
-            .est(|| self.ast.clone().into())
+            .est(|| self.ast.clone().into(), Extensions::all_available())
             .expect("Internal error, failed to construct est.");
 
         let mapping = mapping.into_iter().map(|(k, v)| (k.0, v.0)).collect();
@@ -4189,7 +5701,7 @@ impl Policy {
 
         let ast = est
             .clone()
-            .try_into_ast_policy(Some(self.ast.id().clone()))?;
+            .try_into_ast_policy(Some(self.ast.id().clone()), Extensions::all_available())?;
 
         Ok(Self {
             ast,
@@ -4197,9 +5709,103 @@ impl Policy {
         })
     }
 
-    fn from_est(id: Option<PolicyId>, est: est::Policy) -> Result<Self, PolicyFromJsonError> {
+    /// Convert this template-linked policy into an equivalent static policy
+    /// with the same id, e.g., to export it to a system which doesn't support
+    /// templates. The slots of the template are replaced by the values they
+    /// are linked to, and the annotations of the template are kept, so
+    /// [`Policy::to_cedar()`] of the result is re-parseable Cedar text.
+    ///
+    /// Returns a clone of this policy if it is already static.
+    /// ```
+    /// # use cedar_policy::{EntityUid, PolicyId, PolicySet, SlotId};
+    /// # use std::collections::HashMap;
+    /// # use std::str::FromStr;
+    /// let mut policies = PolicySet::from_str(
+    ///     r#"@team("payments") permit(principal == ?principal, action, resource);"#,
+    /// ).unwrap();
+    /// policies.link(
+    ///     PolicyId::new("policy0"),
+    ///     PolicyId::new("alice"),
+    ///     HashMap::from([(SlotId::principal(), EntityUid::from_str(r#"User::"alice""#).unwrap())]),
+    /// ).unwrap();
+    /// let inlined = policies.policy(&PolicyId::new("alice")).unwrap().inline_template();
+    /// assert!(inlined.is_static());
+    /// assert_eq!(inlined.annotation("team"), Some("payments"));
+    /// assert!(inlined.to_cedar().unwrap().contains(r#"principal == User::"alice""#));
+    /// ```
+    #[must_use]
+    pub fn inline_template(&self) -> Self {
+        if self.is_static() {
+            return self.clone();
+        }
+        // Converting a linked policy to the EST substitutes the values of its
+        // slots, so the EST is a static policy
+        let est = est::Policy::from(self.ast.clone());
+        #[expect(
+            clippy::expect_used,
+            reason = "The EST of a linked policy has no slots, so it can be converted to a static policy"
+        )]
+        let ast = est
+            .clone()
+            .try_into_ast_policy(Some(self.ast.id().clone()), Extensions::all_available())
+            .expect("Internal error, failed to convert linked policy to a static policy.");
+        Self {
+            ast,
+            lossless: LosslessPolicy::Est(est),
+        }
+    }
+
+    /// Check only the scope of this policy (its `principal`, `action`, and
+    /// `resource` constraints) against `request`, without evaluating its
+    /// `when` and `unless` conditions, e.g., to pre-filter the policies which
+    /// may be relevant to a request or to explain why a policy doesn't apply.
+    /// Hierarchy constraints (`in`) are checked against `entities`.
+    ///
+    /// Returns `false` only if some scope constraint is not satisfied by
+    /// `request`. If the scope is satisfied, or can't be checked (e.g.,
+    /// because the principal of `request` is unknown), returns `true`: the
+    /// policy could apply, depending on its conditions.
+    /// ```
+    /// # use cedar_policy::{Context, Entities, EntityUid, Policy, Request};
+    /// # use std::str::FromStr;
+    /// let policy = Policy::from_str(r#"
+    ///     permit(principal, action == Action::"view", resource in Album::"trip")
+    ///     when { context.authenticated };
+    /// "#).unwrap();
+    /// let entities = Entities::from_json_str(r#"[
+    ///     { "uid": { "type": "Photo", "id": "beach" }, "attrs": {}, "parents": [{ "type": "Album", "id": "trip" }] }
+    /// ]"#, None).unwrap();
+    /// let request = |action: &str| Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(action).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"beach""#).unwrap(),
+    ///     Context::empty(),
+    ///     None,
+    /// ).unwrap();
+    /// assert!(policy.could_apply_to(&request(r#"Action::"view""#), &entities));
+    /// assert!(!policy.could_apply_to(&request(r#"Action::"edit""#), &entities));
+    /// ```
+    pub fn could_apply_to(&self, request: &Request, entities: &Entities) -> bool {
+        let eval = Evaluator::new(request.0.clone(), &entities.0, Extensions::all_available());
+        let scope = [
+            self.ast.principal_constraint().as_expr(),
+            self.ast.action_constraint().as_expr(),
+            self.ast.resource_constraint().as_expr(),
+        ];
+        scope.iter().all(|constraint| {
+            !matches!(eval.interpret(constraint, self.ast.env()), Ok(v) if v == ast::Value::from(false))
+        })
+    }
+
+    fn from_est(
+        id: Option<PolicyId>,
+        est: est::Policy,
+        extensions: &Extensions<'_>,
+    ) -> Result<Self, PolicyFromJsonError> {
         Ok(Self {
-            ast: est.clone().try_into_ast_policy(id.map(PolicyId::into))?,
+            ast: est
+                .clone()
+                .try_into_ast_policy(id.map(PolicyId::into), extensions)?,
             lossless: LosslessPolicy::Est(est),
         })
     }
@@ -4223,7 +5829,18 @@ impl Policy {
     /// assert_eq!(json, Policy::from_json(None, json.clone()).unwrap().to_json().unwrap());
     /// ```
     pub fn to_json(&self) -> Result<serde_json::Value, PolicyToJsonError> {
-        let est = self.lossless.est(|| self.ast.clone().into())?;
+        self.to_json_with_extensions(&ExtensionSet::all_available())
+    }
+
+    /// Like [`Policy::to_json()`], for policies calling the functions of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn to_json_with_extensions(
+        &self,
+        extensions: &ExtensionSet,
+    ) -> Result<serde_json::Value, PolicyToJsonError> {
+        let est = self
+            .lossless
+            .est(|| self.ast.clone().into(), extensions.as_core())?;
         serde_json::to_value(est).map_err(Into::into)
     }
 
@@ -4256,6 +5873,26 @@ impl Policy {
         }
     }
 
+    /// Like [`Policy::to_cedar()`], but a policy which wasn't parsed from the
+    /// human-readable syntax is rendered with the call styles of
+    /// `extensions`, rather than those of [`ExtensionSet::all_available()`]
+    pub fn to_cedar_with_extensions(&self, extensions: &ExtensionSet) -> Option<String> {
+        if matches!(self.lossless, LosslessPolicy::Text { .. }) || !self.is_static() {
+            return self.to_cedar();
+        }
+        let displayed = self
+            .lossless
+            .est(|| self.ast.clone().into(), extensions.as_core())
+            .map_or_else(
+                |_| self.ast.to_string(),
+                |est| {
+                    est.display_with_extensions(extensions.as_core())
+                        .to_string()
+                },
+            );
+        Some(displayed)
+    }
+
     /// Get the PST representation of this policy.
     pub fn to_pst(&self) -> Result<pst::Policy, pst::PstConstructionError> {
         self.lossless
@@ -4281,6 +5918,18 @@ impl Policy {
             .collect()
     }
 
+    /// Get the attributes of the unknown context which the policy accesses or
+    /// checks for. Uses of the context as a whole aren't included.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn unknown_context_attributes(&self) -> HashSet<String> {
+        self.ast
+            .unknown_context_attrs()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
     /// Create a `Policy` from its AST representation only. The `LosslessPolicy`
     /// will reflect the AST structure. When possible, don't use this method and
     /// create the `Policy` from the policy text, CST, or EST instead, as the
@@ -4312,7 +5961,8 @@ impl Policy {
 impl std::fmt::Display for Policy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // prefer to display the lossless format
-        self.lossless.fmt(|| self.ast.clone().into(), f)
+        self.lossless
+            .fmt(|| self.ast.clone().into(), Extensions::all_available(), f)
     }
 }
 
@@ -4367,14 +6017,16 @@ impl LosslessTemplate {
     fn est(
         &self,
         fallback_est: impl FnOnce() -> est::Policy,
+        extensions: &Extensions<'_>,
     ) -> Result<est::Policy, PolicyToJsonError> {
         match self {
             Self::Empty => Ok(fallback_est()),
             Self::Est(est) => Ok(est.clone()),
             Self::Pst(pst) => Ok(pst.clone().try_into()?),
-            Self::Text(text) => {
-                Ok(parser::parse_policy_or_template_to_est(text).map_err(ParseErrors::from)?)
-            }
+            Self::Text(text) => Ok(parser::parse_policy_or_template_to_est_with_extensions(
+                text, extensions,
+            )
+            .map_err(ParseErrors::from)?),
         }
     }
 
@@ -4438,15 +6090,16 @@ impl LosslessTemplate {
     fn fmt(
         &self,
         fallback_est: impl FnOnce() -> est::Policy,
+        extensions: &Extensions<'_>,
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
         match self {
-            Self::Empty => match self.est(fallback_est) {
-                Ok(est) => write!(f, "{est}"),
+            Self::Empty => match self.est(fallback_est, extensions) {
+                Ok(est) => write!(f, "{}", est.display_with_extensions(extensions)),
                 Err(e) => write!(f, "<invalid policy: {e}>"),
             },
             Self::Pst(pst) => write!(f, "{pst}"), // PST -> EST conversion in display
-            Self::Est(est) => write!(f, "{est}"),
+            Self::Est(est) => write!(f, "{}", est.display_with_extensions(extensions)),
             Self::Text(text) => write!(f, "{text}"),
         }
     }
@@ -4494,6 +6147,7 @@ impl LosslessPolicy {
     fn est(
         &self,
         fallback_est: impl FnOnce() -> est::Policy,
+        extensions: &Extensions<'_>,
     ) -> Result<est::Policy, PolicyToJsonError> {
         match self {
             Self::Empty => Ok(fallback_est()),
@@ -4510,12 +6164,12 @@ impl LosslessPolicy {
                 }
             }
             Self::Text { text, slots } => {
-                let est =
-                    parser::parse_policy_or_template_to_est(text).map_err(ParseErrors::from)?;
+                let est = parser::parse_policy_or_template_to_est_with_extensions(text, extensions)
+                    .map_err(ParseErrors::from)?;
                 if slots.is_empty() {
                     Ok(est)
                 } else {
-                    let unwrapped_vals = slots.iter().map(|(k, v)| (*k, v.into())).collect();
+                    let unwrapped_vals = slots.iter().map(|(k, v)| (k.clone(), v.into())).collect();
                     Ok(est.link(&unwrapped_vals)?)
                 }
             }
@@ -4542,7 +6196,7 @@ impl LosslessPolicy {
                 } else {
                     let pst_vals: HashMap<pst::SlotId, pst::EntityUID> = slots
                         .iter()
-                        .map(|(k, v)| ((*k).into(), v.clone().into()))
+                        .map(|(k, v)| (k.clone().into(), v.clone().into()))
                         .collect();
                     let static_policy = template.link(&pst_vals)?;
                     Ok(pst::Policy::Static(static_policy))
@@ -4583,21 +6237,22 @@ impl LosslessPolicy {
     fn fmt(
         &self,
         fallback_est: impl FnOnce() -> est::Policy,
+        extensions: &Extensions<'_>,
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
         match self {
-            Self::Empty => match self.est(fallback_est) {
-                Ok(est) => write!(f, "{est}"),
+            Self::Empty => match self.est(fallback_est, extensions) {
+                Ok(est) => write!(f, "{}", est.display_with_extensions(extensions)),
                 Err(e) => write!(f, "<invalid policy: {e}>"),
             },
             Self::Pst(pst) => write!(f, "{pst}"), // Does PST -> EST
-            Self::Est(est) => write!(f, "{est}"),
+            Self::Est(est) => write!(f, "{}", est.display_with_extensions(extensions)),
             Self::Text { text, slots } => {
                 if slots.is_empty() {
                     write!(f, "{text}")
                 } else {
-                    match self.est(fallback_est) {
-                        Ok(est) => write!(f, "{est}"),
+                    match self.est(fallback_est, extensions) {
+                        Ok(est) => write!(f, "{}", est.display_with_extensions(extensions)),
                         Err(e) => write!(f, "<invalid linked policy: {e}>"),
                     }
                 }
@@ -4668,6 +6323,17 @@ impl Expression {
         ))
     }
 
+    /// Create an expression representing a set of ip address ranges.
+    /// This function does not perform error checking on the source strings,
+    /// it creates an expression that calls the `ipset` constructor.
+    pub fn new_ipset(srcs: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let srcs_expr = ast::Expr::set(srcs.into_iter().map(|src| ast::Expr::val(src.as_ref())));
+        Self(ast::Expr::call_extension_fn(
+            ipset_extension_name(),
+            vec![srcs_expr],
+        ))
+    }
+
     /// Create an expression representing a fixed precision decimal number.
     /// This function does not perform error checking on the source string,
     /// it creates an expression that calls the `decimal` constructor.
@@ -4809,6 +6475,20 @@ impl RestrictedExpression {
         ))
     }
 
+    /// Create an expression representing a set of ip address ranges.
+    /// This function does not perform error checking on the source strings,
+    /// it creates an expression that calls the `ipset` constructor.
+    pub fn new_ipset(srcs: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let srcs_expr = ast::RestrictedExpr::set(
+            srcs.into_iter()
+                .map(|src| ast::RestrictedExpr::val(src.as_ref())),
+        );
+        Self(ast::RestrictedExpr::call_extension_fn(
+            ipset_extension_name(),
+            [srcs_expr],
+        ))
+    }
+
     /// Create an expression representing a fixed precision decimal number.
     /// This function does not perform error checking on the source string,
     /// it creates an expression that calls the `decimal` constructor.
@@ -4851,6 +6531,73 @@ impl RestrictedExpression {
     }
 }
 
+/// An unknown for partial evaluation, optionally declared with a type taken
+/// from a [`Schema`].
+///
+/// Partial evaluation uses the declared type to simplify the residuals: e.g.,
+/// `u has attr` is `true` when `u` is declared as a record with the required
+/// attribute `attr`, and `u == 1` is `false` when `u` is declared as a
+/// `String`. The validator also accepts residuals containing typed unknowns.
+/// Substituting a value which doesn't have the declared type is an error.
+///
+/// ```
+/// # use cedar_policy::{RestrictedExpression, Schema, Unknown};
+/// let schema: Schema = "entity User { age: Long };".parse().unwrap();
+/// let user = schema.entity_type(&"User".parse().unwrap()).unwrap();
+/// let age = Unknown::of_type("age", user.attribute("age").unwrap().ty().clone());
+/// let expr = RestrictedExpression::from(age);
+/// ```
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unknown {
+    name: SmolStr,
+    ty: Option<DeclaredType>,
+}
+
+#[cfg(feature = "partial-eval")]
+impl Unknown {
+    /// Create an unknown which may take a value of any type
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self {
+            name: name.as_ref().into(),
+            ty: None,
+        }
+    }
+
+    /// Create an unknown which may only take values of the type `ty`
+    pub fn of_type(name: impl AsRef<str>, ty: DeclaredType) -> Self {
+        Self {
+            name: name.as_ref().into(),
+            ty: Some(ty),
+        }
+    }
+
+    /// The name of the unknown
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The declared type of the unknown, if any
+    pub fn declared_type(&self) -> Option<&DeclaredType> {
+        self.ty.as_ref()
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+impl From<Unknown> for RestrictedExpression {
+    fn from(u: Unknown) -> Self {
+        // A type which can't be represented in Core (i.e., one naming an
+        // extension type which can't exist) is dropped, leaving the unknown
+        // untyped
+        let unknown = match u.ty.as_ref().and_then(DeclaredType::to_core_schema_type) {
+            Some(ty) => ast::Unknown::new_with_schema_type(u.name, ty),
+            None => ast::Unknown::new_untyped(u.name),
+        };
+        Self(ast::RestrictedExpr::unknown(unknown))
+    }
+}
+
 #[cfg(test)]
 impl RestrictedExpression {
     /// Deconstruct an [`RestrictedExpression`] to get the internal type.
@@ -4876,6 +6623,14 @@ fn ip_extension_name() -> ast::Name {
     ast::Name::unqualified_name("ip".parse().unwrap())
 }
 
+fn ipset_extension_name() -> ast::Name {
+    #[expect(
+        clippy::unwrap_used,
+        reason = "This is a constant and is known to be safe, verified by a test"
+    )]
+    ast::Name::unqualified_name("ipset".parse().unwrap())
+}
+
 fn datetime_extension_name() -> ast::Name {
     #[expect(
         clippy::unwrap_used,
@@ -4916,6 +6671,9 @@ pub struct RequestBuilder<S> {
     resource: ast::EntityUIDEntry,
     /// Here, `None` means unknown
     context: Option<ast::Context>,
+    /// Attributes of `context` which may or may not be present, and whose
+    /// values are unknown
+    unknown_context_attrs: BTreeSet<SmolStr>,
     schema: S,
 }
 
@@ -4933,6 +6691,7 @@ impl Default for RequestBuilder<UnsetSchema> {
             action: ast::EntityUIDEntry::unknown(),
             resource: ast::EntityUIDEntry::unknown(),
             context: None,
+            unknown_context_attrs: BTreeSet::new(),
             schema: UnsetSchema,
         }
     }
@@ -5006,6 +6765,26 @@ impl<S> RequestBuilder<S> {
             ..self
         }
     }
+
+    /// Declare attributes of the context which may or may not be present, and
+    /// whose values are unknown. The rest of the context is as set with
+    /// [`RequestBuilder::context()`], so this has no effect unless the context
+    /// is set. Attributes which the context already has are ignored.
+    ///
+    /// Partial evaluation leaves residuals such as `unknown("context") has foo`
+    /// and `unknown("context").foo` for these attributes, which
+    /// [`PartialResponse::unknown_context_attributes()`] lists. The enclosing
+    /// conditions are taken into account, so
+    /// `context has foo && context.foo > 3` only checks for `foo` once.
+    #[must_use]
+    pub fn unknown_context_attributes(
+        mut self,
+        attrs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Self {
+        self.unknown_context_attrs
+            .extend(attrs.into_iter().map(|attr| attr.as_ref().into()));
+        self
+    }
 }
 
 #[cfg(feature = "partial-eval")]
@@ -5018,18 +6797,17 @@ impl RequestBuilder<UnsetSchema> {
             action: self.action,
             resource: self.resource,
             context: self.context,
+            unknown_context_attrs: self.unknown_context_attrs,
             schema,
         }
     }
 
     /// Create the [`Request`]
     pub fn build(self) -> Request {
-        Request(ast::Request::new_unchecked(
-            self.principal,
-            self.action,
-            self.resource,
-            self.context,
-        ))
+        Request(
+            ast::Request::new_unchecked(self.principal, self.action, self.resource, self.context)
+                .with_unknown_context_attrs(self.unknown_context_attrs),
+        )
     }
 }
 
@@ -5037,14 +6815,187 @@ impl RequestBuilder<UnsetSchema> {
 impl RequestBuilder<&Schema> {
     /// Create the [`Request`]
     pub fn build(self) -> Result<Request, RequestValidationError> {
-        Ok(Request(ast::Request::new_with_unknowns(
-            self.principal,
-            self.action,
-            self.resource,
-            self.context,
-            Some(&self.schema.0),
+        let request =
+            ast::Request::new_unchecked(self.principal, self.action, self.resource, self.context)
+                .with_unknown_context_attrs(self.unknown_context_attrs);
+        ast::RequestSchema::validate_request(
+            &self.schema.0,
+            &request,
             Extensions::all_available(),
-        )?))
+        )?;
+        Ok(Request(request))
+    }
+}
+
+/// Builder for a [`Request`] which validates each field against a [`Schema`]
+/// as it is set, rather than only once the whole request is built.
+///
+/// Each setter returns `Err` as soon as the value being set is inconsistent
+/// with the schema or with the fields set so far, pointing at the offending
+/// field (e.g., a single context attribute) rather than the whole request.
+/// Fields which depend on the action (the principal and resource types, and
+/// the context attributes) are checked again when the action is set.
+///
+/// ```
+/// # use cedar_policy::{EntityUid, Request, RestrictedExpression, Schema};
+/// # use std::str::FromStr;
+/// let schema = Schema::from_str(r#"
+///     entity User;
+///     entity Photo;
+///     action view appliesTo { principal: User, resource: Photo, context: { mfa: Bool } };
+/// "#).unwrap();
+/// let builder = Request::schema_builder(&schema)
+///     .action(EntityUid::from_str(r#"Action::"view""#).unwrap()).unwrap()
+///     .principal(EntityUid::from_str(r#"User::"alice""#).unwrap()).unwrap()
+///     .resource(EntityUid::from_str(r#"Photo::"vacation""#).unwrap()).unwrap();
+/// // the error points at the `mfa` attribute
+/// assert!(builder.clone().context_attr("mfa", RestrictedExpression::new_long(1)).is_err());
+/// let request = builder
+///     .context_attr("mfa", RestrictedExpression::new_bool(true)).unwrap()
+///     .build()
+///     .unwrap();
+/// assert_eq!(request.action().unwrap().to_string(), r#"Action::"view""#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaRequestBuilder<'s> {
+    schema: &'s Schema,
+    principal: Option<EntityUid>,
+    action: Option<EntityUid>,
+    resource: Option<EntityUid>,
+    /// Context attributes set so far, with their evaluated values
+    context: BTreeMap<SmolStr, (RestrictedExpr, ast::PartialValue)>,
+}
+
+impl<'s> SchemaRequestBuilder<'s> {
+    /// Create a builder for requests conforming to `schema`
+    pub fn new(schema: &'s Schema) -> Self {
+        Self {
+            schema,
+            principal: None,
+            action: None,
+            resource: None,
+            context: BTreeMap::new(),
+        }
+    }
+
+    /// Set the principal, checking that its type is declared in the schema
+    /// and, if the action has been set, that it is valid for the action
+    pub fn principal(self, principal: EntityUid) -> Result<Self, RequestBuilderError> {
+        self.schema.0.validate_scope_variables(
+            Some(principal.as_ref()),
+            self.action.as_ref().map(AsRef::as_ref),
+            None,
+        )?;
+        Ok(Self {
+            principal: Some(principal),
+            ..self
+        })
+    }
+
+    /// Set the action, checking that it is declared in the schema, that the
+    /// principal and resource (if set) are valid for it, and that the context
+    /// attributes set so far are declared for it and have the declared types
+    pub fn action(self, action: EntityUid) -> Result<Self, RequestBuilderError> {
+        self.schema.0.validate_scope_variables(
+            self.principal.as_ref().map(AsRef::as_ref),
+            Some(action.as_ref()),
+            self.resource.as_ref().map(AsRef::as_ref),
+        )?;
+        for (attr, (_, value)) in &self.context {
+            self.schema.0.validate_context_attr(
+                action.as_ref(),
+                attr,
+                value,
+                Extensions::all_available(),
+            )?;
+        }
+        Ok(Self {
+            action: Some(action),
+            ..self
+        })
+    }
+
+    /// Set the resource, checking that its type is declared in the schema
+    /// and, if the action has been set, that it is valid for the action
+    pub fn resource(self, resource: EntityUid) -> Result<Self, RequestBuilderError> {
+        self.schema.0.validate_scope_variables(
+            None,
+            self.action.as_ref().map(AsRef::as_ref),
+            Some(resource.as_ref()),
+        )?;
+        Ok(Self {
+            resource: Some(resource),
+            ..self
+        })
+    }
+
+    /// Set a single context attribute, replacing any previous value for it.
+    ///
+    /// If the action has been set, checks that `attr` is declared in the
+    /// action's context type and that `value` has the declared type.
+    pub fn context_attr(
+        mut self,
+        attr: impl Into<SmolStr>,
+        value: RestrictedExpression,
+    ) -> Result<Self, RequestBuilderError> {
+        let attr = attr.into();
+        let evaluated = RestrictedEvaluator::new(Extensions::all_available())
+            .partial_interpret(value.0.as_borrowed())?;
+        if let Some(action) = &self.action {
+            self.schema.0.validate_context_attr(
+                action.as_ref(),
+                &attr,
+                &evaluated,
+                Extensions::all_available(),
+            )?;
+        }
+        self.context.insert(attr, (value.0, evaluated));
+        Ok(self)
+    }
+
+    /// Set every attribute in `context`, as if by calling
+    /// [`SchemaRequestBuilder::context_attr()`] for each of them.
+    /// Attributes set previously which are not in `context` are kept.
+    pub fn context(self, context: Context) -> Result<Self, RequestBuilderError> {
+        context
+            .0
+            .into_iter()
+            .try_fold(self, |builder, (attr, value)| {
+                builder.context_attr(attr, RestrictedExpression(value))
+            })
+    }
+
+    /// Create the [`Request`], checking that the principal, action, and
+    /// resource have been set, and that every context attribute required for
+    /// the action has been set.
+    pub fn build(self) -> Result<Request, RequestBuilderError> {
+        use cedar_policy_core::validator::request_validation_errors::MissingContextAttributeError;
+        let missing = |component| request_builder_errors::MissingComponentError { component };
+        let principal = self.principal.ok_or_else(|| missing("principal"))?;
+        let action = self.action.ok_or_else(|| missing("action"))?;
+        let resource = self.resource.ok_or_else(|| missing("resource"))?;
+        for attr in self.schema.0.required_context_attrs(action.as_ref())? {
+            if !self.context.contains_key(&attr) {
+                return Err(cedar_policy_core::validator::RequestValidationError::from(
+                    MissingContextAttributeError::new(attr, Arc::new(action.0.clone())),
+                )
+                .into());
+            }
+        }
+        let context = ast::Context::from_pairs(
+            self.context
+                .into_iter()
+                .map(|(attr, (value, _))| (attr, value)),
+            Extensions::all_available(),
+        )
+        .map_err(ContextCreationError::from)?;
+        Ok(Request::new(
+            principal,
+            action,
+            resource,
+            Context(context),
+            Some(self.schema),
+        )?)
     }
 }
 
@@ -5091,6 +7042,12 @@ impl Request {
         RequestBuilder::default()
     }
 
+    /// Create a [`SchemaRequestBuilder`], which validates each component of
+    /// the request against `schema` as it is set
+    pub fn schema_builder(schema: &Schema) -> SchemaRequestBuilder<'_> {
+        SchemaRequestBuilder::new(schema)
+    }
+
     /// Create a Request.
     ///
     /// Note that you can create the `EntityUid`s using `.parse()` on any
@@ -5157,7 +7114,7 @@ impl Request {
 /// the Context object for an authorization request
 #[repr(transparent)]
 #[derive(Debug, Clone, PartialEq, Eq, RefCast)]
-pub struct Context(ast::Context);
+pub struct Context(pub(crate) ast::Context);
 
 #[doc(hidden)] // because this converts to a private/internal type
 impl AsRef<ast::Context> for Context {
@@ -5340,6 +7297,51 @@ impl Context {
         Ok(Self(context))
     }
 
+    /// Create a `Context` from loosely-typed JSON, coercing values into the
+    /// types the schema declares for the context of `action` before parsing.
+    ///
+    /// This is intended for JSON produced by systems which, e.g., encode
+    /// numbers as strings. Where the schema expects a `Long`, strings
+    /// containing an integer are coerced to integers; where it expects a
+    /// `decimal`, numbers are coerced to decimals (if no precision is lost);
+    /// and where it expects a `datetime`, ISO 8601 date-times with an explicit
+    /// offset are coerced to the format the `datetime` constructor accepts.
+    /// Values which cannot be coerced are parsed as by
+    /// [`Context::from_json_value()`]. Returns the coercions which were
+    /// performed along with the `Context`.
+    ///
+    /// ```
+    /// # use cedar_policy::{Context, EntityUid, EvalResult, Schema};
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity User;
+    ///     action view appliesTo { principal: User, resource: User, context: { age: Long } };
+    /// "#).unwrap();
+    /// let action = EntityUid::from_str(r#"Action::"view""#).unwrap();
+    /// let (context, coercions) = Context::from_json_value_coerced(
+    ///     serde_json::json!({ "age": "42" }),
+    ///     (&schema, &action),
+    /// ).unwrap();
+    /// assert_eq!(context.get("age"), Some(EvalResult::Long(42)));
+    /// assert_eq!(coercions.len(), 1);
+    /// assert_eq!(coercions[0].path().collect::<Vec<_>>(), ["age"]);
+    /// ```
+    pub fn from_json_value_coerced(
+        json: serde_json::Value,
+        schema: (&Schema, &EntityUid),
+    ) -> Result<(Self, Vec<JsonCoercion>), ContextJsonError> {
+        let schema = Self::get_context_schema(schema.0, schema.1)?;
+        let (context, coercions) = cedar_policy_core::entities::ContextJsonParser::new(
+            Some(&schema),
+            Extensions::all_available(),
+        )
+        .from_json_value_coerced(json)?;
+        Ok((
+            Self(context),
+            coercions.into_iter().map(JsonCoercion).collect(),
+        ))
+    }
+
     /// Create a `Context` from a JSON file.  The JSON file must contain a JSON
     /// object, not any other JSON type, or you will get an error here.
     /// JSON here must use the `__entity` and `__extn` escapes for entity
@@ -5393,6 +7395,27 @@ impl Context {
         self.0.to_json_value()
     }
 
+    /// Convert this `Context` into a canonical JSON string, suitable for
+    /// content hashing.
+    ///
+    /// Equal contexts always produce the same string: object keys are sorted,
+    /// set elements are sorted (extension values by type, then by value), and
+    /// extension values use a canonical encoding (e.g., `decimal("1.0")` and
+    /// `decimal("1.00")` are encoded the same way). The output is compact, and
+    /// is suitable for parsing in via `from_json_*` even with no `Schema`.
+    ///
+    /// ```
+    /// # use cedar_policy::Context;
+    /// let a = Context::from_json_str(r#"{"limits": [{"__extn": {"fn": "decimal", "arg": "1.0"}}, {"__extn": {"fn": "decimal", "arg": "0.5"}}]}"#, None).unwrap();
+    /// let b = Context::from_json_str(r#"{"limits": [{"__extn": {"fn": "decimal", "arg": "0.50"}}, {"__extn": {"fn": "decimal", "arg": "1.00"}}]}"#, None).unwrap();
+    /// assert_eq!(a.to_json_canonical().unwrap(), b.to_json_canonical().unwrap());
+    /// ```
+    pub fn to_json_canonical(
+        &self,
+    ) -> Result<String, entities_json_errors::JsonSerializationError> {
+        self.0.to_json_canonical()
+    }
+
     /// Internal helper function to convert `(&Schema, &EntityUid)` to `impl ContextSchema`
     fn get_context_schema(
         schema: &Schema,
@@ -5412,24 +7435,82 @@ impl Context {
         Self::from_pairs(self.into_iter().chain(other_context))
     }
 
+    /// Create a [`Context`] by layering the given contexts on top of each
+    /// other, in order, e.g., a static service context, then a per-request
+    /// context, then a computed context. When an attribute appears in more
+    /// than one layer, the value from the last layer containing it is used.
+    ///
+    /// Layers are merged shallowly: a record-valued attribute in a later layer
+    /// replaces the whole record from earlier layers. Unlike
+    /// [`Context::merge()`], overlapping attributes are allowed; however, it
+    /// is an error for an attribute to have values of different types in
+    /// different layers.
+    ///
+    /// ```
+    /// # use cedar_policy::{Context, EvalResult, RestrictedExpression};
+    /// let service = Context::from_pairs([
+    ///     ("service".into(), RestrictedExpression::new_string("photos".into())),
+    ///     ("region".into(), RestrictedExpression::new_string("us-east-1".into())),
+    /// ]).unwrap();
+    /// let request = Context::from_pairs([
+    ///     ("region".into(), RestrictedExpression::new_string("eu-west-1".into())),
+    /// ]).unwrap();
+    /// let context = Context::layered([service, request]).unwrap();
+    /// assert_eq!(context.get("region"), Some(EvalResult::String("eu-west-1".into())));
+    /// assert_eq!(context.get("service"), Some(EvalResult::String("photos".into())));
+    ///
+    /// let conflicting = Context::from_pairs([
+    ///     ("region".into(), RestrictedExpression::new_long(1)),
+    /// ]).unwrap();
+    /// assert!(Context::layered([context, conflicting]).is_err());
+    /// ```
+    pub fn layered(layers: impl IntoIterator<Item = Self>) -> Result<Self, ContextCreationError> {
+        Ok(Self(ast::Context::layered(
+            layers.into_iter().map(|layer| layer.0),
+            Extensions::all_available(),
+        )?))
+    }
+
     /// Validates this context against the provided schema
     ///
     /// Returns Ok(()) if the context is valid according to the schema, or an error otherwise
     ///
     /// This validation is already handled by `Request::new`, so there is no need to separately call
-    /// if you are validating the whole request
+    /// if you are validating the whole request. See also [`Schema::validate_context()`].
     pub fn validate(
         &self,
         schema: &crate::Schema,
         action: &EntityUid,
     ) -> std::result::Result<(), RequestValidationError> {
-        // Call the validate_context function from coreschema.rs
-        Ok(RequestSchema::validate_context(
-            &schema.0,
-            &self.0,
-            action.as_ref(),
-            Extensions::all_available(),
-        )?)
+        schema.validate_context(self, action)
+    }
+}
+
+/// A coercion performed by [`Context::from_json_value_coerced()`]
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+pub struct JsonCoercion(cedar_policy_core::entities::JsonCoercion);
+
+impl JsonCoercion {
+    /// Path to the coerced value, from the root of the context. Record
+    /// attributes are identified by name, and set elements by their index.
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.0.path().iter().map(SmolStr::as_str)
+    }
+
+    /// The value before coercion
+    pub fn original(&self) -> &serde_json::Value {
+        self.0.original()
+    }
+
+    /// The value after coercion
+    pub fn coerced(&self) -> &serde_json::Value {
+        self.0.coerced()
+    }
+
+    /// The type the schema declares for the value, as a string
+    pub fn expected_type(&self) -> String {
+        self.0.expected().to_string()
     }
 }
 
@@ -5676,6 +7757,52 @@ pub fn eval_expression(
     ))
 }
 
+/// Typechecks a standalone expression, such as a fragment of a policy
+/// condition, against the schema for requests in the environment `env`.
+///
+/// The expression is typechecked as in strict validation, except that it may
+/// have any type, not only `Bool`. Returns the type of the expression, or the
+/// errors found typechecking it.
+///
+/// ```
+/// # use cedar_policy::{typecheck_expression, Expression, RequestEnv, Schema};
+/// # use std::str::FromStr;
+/// let schema = Schema::from_str(r#"
+///     entity User { age: Long };
+///     entity Photo { tags: Set<String> };
+///     action view appliesTo { principal: User, resource: Photo };
+/// "#).unwrap();
+/// let env = RequestEnv::new(
+///     "User".parse().unwrap(),
+///     r#"Action::"view""#.parse().unwrap(),
+///     "Photo".parse().unwrap(),
+/// );
+/// let expr = Expression::from_str("resource.tags").unwrap();
+/// let ty = typecheck_expression(&expr, &schema, &env).unwrap();
+/// assert_eq!(ty.to_string(), "Set<String>");
+/// let expr = Expression::from_str(r#"principal.age > "18""#).unwrap();
+/// assert!(typecheck_expression(&expr, &schema, &env).is_err());
+/// ```
+pub fn typecheck_expression(
+    expr: &Expression,
+    schema: &Schema,
+    env: &RequestEnv,
+) -> Result<DeclaredType, TypecheckExpressionError> {
+    let core_env = schema
+        .0
+        .request_env(&env.principal.0, &env.action.0, &env.resource.0)?
+        .link(
+            env.principal_slot.as_ref().map(|ety| ety.0.clone()),
+            env.resource_slot.as_ref().map(|ety| ety.0.clone()),
+        );
+    let ty = cedar_policy_core::validator::typecheck_expr(&expr.0, &schema.0, &core_env).map_err(
+        |errors| typecheck_expression_errors::TypeErrors {
+            errors: errors.into_iter().map(ValidationError::from).collect(),
+        },
+    )?;
+    Ok(DeclaredType::from_typechecked_type(&ty))
+}
+
 // These are the same tests in validator, just ensuring all the plumbing is done correctly
 #[cfg(test)]
 mod test_access {
@@ -5980,6 +8107,169 @@ action CreateList in Create appliesTo {
             .collect::<HashSet<EntityTypeName>>();
         assert_eq!(entities, expected);
     }
+
+    #[test]
+    fn action_hierarchy() {
+        let schema = schema();
+        let read: EntityUid = r#"Action::"Read""#.parse().unwrap();
+        let get_list: EntityUid = r#"Action::"GetList""#.parse().unwrap();
+        let expected = ["GetList", "GetLists"]
+            .into_iter()
+            .map(|ty| format!("Action::\"{ty}\"").parse().unwrap())
+            .collect::<HashSet<EntityUid>>();
+        assert_eq!(
+            schema
+                .action_descendants(&read)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            expected
+        );
+        assert_eq!(
+            schema
+                .expand_action_group(&read)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            expected
+        );
+        assert_eq!(
+            schema
+                .action_ancestors(&get_list)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![read.clone()]
+        );
+        assert!(schema.is_action_in(&get_list, &read));
+        assert!(!schema.is_action_in(&read, &get_list));
+        let hierarchy = schema.action_hierarchy();
+        assert_eq!(
+            hierarchy.parents(&get_list).unwrap().collect::<Vec<_>>(),
+            [&read]
+        );
+        assert_eq!(
+            hierarchy
+                .children(&read)
+                .unwrap()
+                .cloned()
+                .collect::<HashSet<_>>(),
+            expected
+        );
+        assert!(hierarchy.roots().any(|action| action == &read));
+        assert!(hierarchy.edges().any(|edge| edge == (&get_list, &read)));
+        let undeclared: EntityUid = r#"Action::"Undeclared""#.parse().unwrap();
+        assert!(schema.action_descendants(&undeclared).is_none());
+        assert!(schema.action_ancestors(&undeclared).is_none());
+        assert!(schema.expand_action_group(&undeclared).is_none());
+        assert!(hierarchy.parents(&undeclared).is_none());
+    }
+
+    #[test]
+    fn entity_type_info() {
+        let schema = schema();
+        let list = schema
+            .entity_type(&"List".parse().unwrap())
+            .expect("List should be declared");
+        assert_eq!(list.name(), &"List".parse::<EntityTypeName>().unwrap());
+        let attrs = list
+            .attributes()
+            .map(|(name, attr)| (name.to_string(), attr.ty().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attrs,
+            vec![
+                ("editors".to_string(), "Team".to_string()),
+                ("name".to_string(), "String".to_string()),
+                ("owner".to_string(), "User".to_string()),
+                ("readers".to_string(), "Team".to_string()),
+                (
+                    "tasks".to_string(),
+                    r#"Set<{"id": Long, "name": String, "state": String}>"#.to_string()
+                ),
+            ]
+        );
+        let owner = list.attribute("owner").unwrap();
+        assert!(owner.is_required());
+        assert_eq!(owner.ty(), &DeclaredType::Entity("User".parse().unwrap()));
+        assert!(list.attribute("undeclared").is_none());
+        assert!(list.tag_type().is_none());
+        assert!(list.enum_choices().is_none());
+        assert!(schema.entity_type(&"Undeclared".parse().unwrap()).is_none());
+
+        let get_list: EntityUid = r#"Action::"GetList""#.parse().unwrap();
+        assert_eq!(
+            schema.context_type(&get_list),
+            Some(DeclaredType::Record {
+                attributes: BTreeMap::new(),
+                open: false
+            })
+        );
+        let undeclared: EntityUid = r#"Action::"Undeclared""#.parse().unwrap();
+        assert!(schema.context_type(&undeclared).is_none());
+    }
+
+    #[test]
+    fn entity_type_info_tags_and_enums() {
+        let schema = Schema::from_str(
+            r#"
+            entity Color enum ["red", "green"];
+            entity Doc {
+                color?: Color,
+                labels: Set<Long>,
+                owner: Color | Doc,
+            } tags Set<String>;
+            action view appliesTo {
+                principal: Doc,
+                resource: Doc,
+                context: { ip: ipaddr, reason?: String },
+            };
+            "#,
+        )
+        .unwrap();
+        let color = schema.entity_type(&"Color".parse().unwrap()).unwrap();
+        assert_eq!(
+            color
+                .enum_choices()
+                .unwrap()
+                .map(AsRef::as_ref)
+                .collect::<Vec<&str>>(),
+            vec!["red", "green"]
+        );
+        assert_eq!(color.attributes().count(), 0);
+
+        let doc = schema.entity_type(&"Doc".parse().unwrap()).unwrap();
+        assert_eq!(
+            doc.tag_type(),
+            Some(DeclaredType::Set(Box::new(DeclaredType::String)))
+        );
+        assert!(!doc.attribute("color").unwrap().is_required());
+        assert_eq!(
+            doc.attribute("labels").unwrap().ty().to_string(),
+            "Set<Long>"
+        );
+        assert_eq!(
+            doc.attribute("owner").unwrap().ty().to_string(),
+            "Color | Doc"
+        );
+
+        let view: EntityUid = r#"Action::"view""#.parse().unwrap();
+        assert_eq!(
+            schema.context_type(&view).unwrap().to_string(),
+            r#"{"ip": ipaddr, "reason"?: String}"#
+        );
+    }
+
+    #[cfg(feature = "maps")]
+    #[test]
+    fn entity_type_info_maps() {
+        let schema = Schema::from_str("entity Doc { labels: Map<String, Long> };").unwrap();
+        let doc = schema.entity_type(&"Doc".parse().unwrap()).unwrap();
+        assert_eq!(
+            doc.attribute("labels").unwrap().ty().to_string(),
+            "Map<String, Long>"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -6262,7 +8552,7 @@ action CreateList in Create appliesTo {
 
 #[cfg(test)]
 mod test_lossless_empty {
-    use super::{LosslessPolicy, LosslessTemplate, Policy, PolicyId, Template};
+    use super::{Extensions, LosslessPolicy, LosslessTemplate, Policy, PolicyId, Template};
     use cedar_policy_core::pst;
     use cool_asserts::assert_matches;
 
@@ -6285,7 +8575,7 @@ mod test_lossless_empty {
         // The EST representation is obtained from the AST
         let lossy_policy0_est = lossy_policy0
             .lossless
-            .est(|| policy0.ast.clone().into())
+            .est(|| policy0.ast.clone().into(), Extensions::all_available())
             .unwrap();
         assert_eq!(lossy_policy0_est, policy0.ast.into());
     }
@@ -6307,7 +8597,7 @@ mod test_lossless_empty {
         // The EST representation is obtained from the AST
         let lossy_template0_est = lossy_template0
             .lossless
-            .est(|| template0.ast.clone().into())
+            .est(|| template0.ast.clone().into(), Extensions::all_available())
             .unwrap();
         assert_eq!(lossy_template0_est, template0.ast.into());
     }
@@ -6376,3 +8666,21 @@ pub fn compute_entity_manifest(
 ) -> Result<EntityManifest, EntityManifestError> {
     entity_manifest::compute_entity_manifest(&validator.0, &pset.ast).map_err(Into::into)
 }
+
+/// Use an entity manifest to find the entity slice needed to answer
+/// `request`, fetching the entities from `loader`.
+///
+/// Each entity is fetched at most once, and the entities needed at each level
+/// of the manifest are fetched in one call to `loader`.
+#[doc = include_str!("../experimental_warning.md")]
+#[deprecated = "The `entity-manifest` experimental feature and all associated functions are deprecated. Migrate to `PolicySet::is_authorized_batch` for efficient authorization with on-demand entity loading."]
+#[cfg(feature = "entity-manifest")]
+pub fn slice_entities_with_loader(
+    manifest: &EntityManifest,
+    request: &Request,
+    loader: &mut dyn EntityLoader,
+) -> Result<Entities, EntitySliceError> {
+    manifest
+        .slice_entities_with_loader(&mut entity_loader::EntityLoaderWrapper(loader), &request.0)
+        .map(Entities)
+}
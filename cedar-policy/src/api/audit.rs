@@ -0,0 +1,278 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`AuditRecord`], a normalized record of one
+//! authorization decision, the [`AuditSink`] trait for emitting them, and
+//! [`JsonLinesAuditSink`], which writes each record as a line of JSON.
+
+use super::{AuthorizationError, Decision, PolicyId, Request, Response};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use super::{Authorizer, Entities, PolicySet};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+/// A normalized record of one authorization decision, as emitted to an
+/// [`AuditSink`] by [`crate::Authorizer::is_authorized_audited()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch at which authorization started
+    timestamp_millis: u64,
+    /// How long authorization took, in microseconds
+    duration_micros: u64,
+    /// The principal, or `None` if it is unknown
+    principal: Option<String>,
+    /// The action, or `None` if it is unknown
+    action: Option<String>,
+    /// The resource, or `None` if it is unknown
+    resource: Option<String>,
+    /// The context in the JSON entities format, or `None` if it is unknown
+    context: Option<serde_json::Value>,
+    decision: Decision,
+    /// Sorted ids of the policies that determined the decision
+    determining_policies: Vec<PolicyId>,
+    errors: Vec<AuditedError>,
+    /// See [`crate::Diagnostics::policy_set_version()`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    policy_set_version: Option<u64>,
+}
+
+/// An error which occurred in a policy during an audited authorization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditedError {
+    policy_id: PolicyId,
+    message: String,
+}
+
+impl AuditedError {
+    /// The id of the policy which errored
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// The error message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl AuditRecord {
+    /// Record the authorization of `request`, which started at `started` and
+    /// took `duration` to produce `response`. This is useful to audit
+    /// decisions made other than by
+    /// [`crate::Authorizer::is_authorized_audited()`], e.g., by
+    /// [`crate::PolicySetSnapshot::is_authorized()`].
+    pub fn new(
+        request: &Request,
+        response: &Response,
+        started: SystemTime,
+        duration: Duration,
+    ) -> Self {
+        let mut determining_policies: Vec<PolicyId> =
+            response.diagnostics().reason().cloned().collect();
+        determining_policies.sort();
+        let mut errors: Vec<AuditedError> = response
+            .diagnostics()
+            .errors()
+            .map(|err| match err {
+                AuthorizationError::PolicyEvaluationError(err) => AuditedError {
+                    policy_id: err.policy_id().clone(),
+                    message: err.inner().to_string(),
+                },
+            })
+            .collect();
+        errors.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+        Self {
+            timestamp_millis: started
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |since_epoch| saturating_u64(since_epoch.as_millis())),
+            duration_micros: saturating_u64(duration.as_micros()),
+            principal: request.principal().map(ToString::to_string),
+            action: request.action().map(ToString::to_string),
+            resource: request.resource().map(ToString::to_string),
+            context: request
+                .context()
+                .and_then(|context| context.to_json_value().ok()),
+            decision: response.decision(),
+            determining_policies,
+            errors,
+            policy_set_version: response.diagnostics().policy_set_version(),
+        }
+    }
+
+    /// Milliseconds since the Unix epoch at which authorization started
+    pub fn timestamp_millis(&self) -> u64 {
+        self.timestamp_millis
+    }
+
+    /// How long authorization took
+    pub fn duration(&self) -> Duration {
+        Duration::from_micros(self.duration_micros)
+    }
+
+    /// The principal, formatted as in Cedar policies (e.g., `User::"alice"`),
+    /// or `None` if it is unknown
+    pub fn principal(&self) -> Option<&str> {
+        self.principal.as_deref()
+    }
+
+    /// The action, formatted as in Cedar policies, or `None` if it is unknown
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+
+    /// The resource, formatted as in Cedar policies, or `None` if it is
+    /// unknown
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    /// The context in the JSON entities format, or `None` if it is unknown
+    pub fn context(&self) -> Option<&serde_json::Value> {
+        self.context.as_ref()
+    }
+
+    /// The authorization decision
+    pub fn decision(&self) -> Decision {
+        self.decision
+    }
+
+    /// The ids of the policies that determined the decision, sorted
+    pub fn determining_policies(&self) -> impl Iterator<Item = &PolicyId> {
+        self.determining_policies.iter()
+    }
+
+    /// The errors which occurred during authorization, sorted by policy id
+    pub fn errors(&self) -> impl Iterator<Item = &AuditedError> {
+        self.errors.iter()
+    }
+
+    /// The version of the policy set the request was authorized against, if
+    /// known. See [`crate::Diagnostics::policy_set_version()`].
+    pub fn policy_set_version(&self) -> Option<u64> {
+        self.policy_set_version
+    }
+}
+
+// `Instant::now()` panics on this target
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl Authorizer {
+    /// Like [`Authorizer::is_authorized()`], but also emits an [`AuditRecord`]
+    /// of the decision, including how long authorization took, to `sink`.
+    /// The record's timestamp is read from the clock of the authorizer's
+    /// [`crate::EvalEnvironment`].
+    ///
+    /// Not available on `wasm32-unknown-unknown`, which has no system clock;
+    /// build records with [`AuditRecord::new()`] there instead.
+    pub fn is_authorized_audited(
+        &self,
+        r: &Request,
+        p: &PolicySet,
+        e: &Entities,
+        sink: &dyn AuditSink,
+    ) -> Response {
+        let started = self.environment().now();
+        let timer = std::time::Instant::now();
+        let response = self.is_authorized(r, p, e);
+        sink.record(&AuditRecord::new(r, &response, started, timer.elapsed()));
+        response
+    }
+}
+
+fn saturating_u64(n: u128) -> u64 {
+    u64::try_from(n).unwrap_or(u64::MAX)
+}
+
+/// A destination for [`AuditRecord`]s, which is invoked once per audited
+/// authorization.
+///
+/// Sinks take `&self` so that one sink can be shared between threads;
+/// implementations which buffer or write records need interior mutability.
+/// Any closure taking an `&AuditRecord` is a sink.
+pub trait AuditSink {
+    /// Emit `record`. This should not fail: authorization has already
+    /// happened, so a sink which cannot emit a record should retain the
+    /// failure for later inspection rather than panic.
+    fn record(&self, record: &AuditRecord);
+}
+
+impl<F: Fn(&AuditRecord)> AuditSink for F {
+    fn record(&self, record: &AuditRecord) {
+        self(record);
+    }
+}
+
+/// An [`AuditSink`] which writes each record as one line of JSON (the
+/// "JSON lines" format) to a writer, such as a file or `std::io::stdout()`.
+///
+/// If writing a record fails, the error is retained and can be retrieved with
+/// [`JsonLinesAuditSink::take_error()`]; later records are still attempted.
+#[derive(Debug)]
+pub struct JsonLinesAuditSink<W: Write> {
+    state: Mutex<JsonLinesState<W>>,
+}
+
+#[derive(Debug)]
+struct JsonLinesState<W> {
+    writer: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> JsonLinesAuditSink<W> {
+    /// Create a sink writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: Mutex::new(JsonLinesState {
+                writer,
+                error: None,
+            }),
+        }
+    }
+
+    /// Take the first error which occurred writing a record since the last
+    /// call to this method, if any
+    pub fn take_error(&self) -> Option<std::io::Error> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .error
+            .take()
+    }
+
+    /// Consume the sink, returning the writer
+    pub fn into_inner(self) -> W {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .writer
+    }
+}
+
+impl<W: Write> AuditSink for JsonLinesAuditSink<W> {
+    fn record(&self, record: &AuditRecord) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let result = serde_json::to_writer(&mut state.writer, record)
+            .map_err(std::io::Error::from)
+            .and_then(|()| state.writer.write_all(b"\n"))
+            .and_then(|()| state.writer.flush());
+        if let Err(err) = result {
+            state.error.get_or_insert(err);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`CompiledPolicySet`], a [`PolicySet`] whose policies
+//! are compiled to a compact bytecode for faster repeated evaluation by
+//! [`Authorizer::is_authorized_compiled()`].
+
+use super::{Authorizer, Entities, PolicySet, Request, Response};
+use cedar_policy_core::evaluator::bytecode;
+
+/// A [`PolicySet`] whose policies are compiled to a flat array of
+/// instructions, which [`Authorizer::is_authorized_compiled()`] executes
+/// without walking the policies' expression trees.
+///
+/// Compiling is worthwhile for policy sets which are evaluated many times.
+/// Responses are the same as those of [`Authorizer::is_authorized()`]: when a
+/// policy can't be evaluated by its compiled form (e.g., because it errors, or
+/// because the request or entities contain unknowns), it is evaluated as
+/// usual.
+///
+/// The compiled set is a snapshot: changes to the [`PolicySet`] it was
+/// compiled from are not reflected in it.
+#[derive(Debug, Clone)]
+pub struct CompiledPolicySet {
+    policies: PolicySet,
+    compiled: bytecode::CompiledPolicySet,
+}
+
+impl CompiledPolicySet {
+    /// The policies which were compiled
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+}
+
+impl PolicySet {
+    /// Compile the policies of this set for faster repeated evaluation by
+    /// `authorizer`, whose extensions the policies' extension function calls
+    /// are resolved with. See [`CompiledPolicySet`].
+    pub fn compile(&self, authorizer: &Authorizer) -> CompiledPolicySet {
+        CompiledPolicySet {
+            policies: self.clone(),
+            compiled: bytecode::CompiledPolicySet::new(self.ast.clone(), authorizer.0.extensions()),
+        }
+    }
+}
+
+impl Authorizer {
+    /// Like [`Authorizer::is_authorized()`], but evaluates the compiled
+    /// policies of `p`, which is faster when the same policies are evaluated
+    /// many times. The response is the same as for [`CompiledPolicySet::policies()`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cedar.authorize",
+            level = "debug",
+            skip_all,
+            fields(
+                policy_count = p.policies.num_of_policies(),
+                entity_count = e.len(),
+                compiled = true
+            )
+        )
+    )]
+    pub fn is_authorized_compiled(
+        &self,
+        r: &Request,
+        p: &CompiledPolicySet,
+        e: &Entities,
+    ) -> Response {
+        self.0
+            .is_authorized_compiled(r.0.clone(), &p.compiled, &e.0)
+            .into()
+    }
+}
@@ -0,0 +1,135 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::add_with()`] and
+//! [`PolicySet::add_template_with()`], which add a policy or template whose id
+//! may already be in use, and [`PolicySet::unique_id()`], which generates ids
+//! that are not.
+
+use super::{MergeConflictResolution, Policy, PolicyId, PolicySet, PolicySetError, Template};
+
+/// How [`PolicySet::add_with()`] and [`PolicySet::add_template_with()`] handle
+/// an id which is already used by a policy or template in the policy set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdCollisionBehavior {
+    /// Return an error and leave the policy set unchanged, like
+    /// [`PolicySet::add()`]
+    Error,
+    /// Replace the existing policy or template. Existing links to a replaced
+    /// template are relinked against the new one.
+    Replace,
+    /// Add the policy or template under a new id, chosen by
+    /// [`PolicySet::unique_id()`]
+    Version,
+}
+
+impl PolicySet {
+    /// Returns `true` if `id` is used by a policy or template in this policy set
+    fn contains_id(&self, id: &PolicyId) -> bool {
+        self.policies.contains_key(id) || self.templates.contains_key(id)
+    }
+
+    /// Get an id based on `id` which is not used by any policy or template in
+    /// this policy set: `id` itself if it is unused, and otherwise `id#2`,
+    /// `id#3`, etc., whichever is unused first.
+    /// ```
+    /// # use cedar_policy::{PolicyId, PolicySet};
+    /// # use std::str::FromStr;
+    /// let policies = PolicySet::from_str("permit(principal, action, resource);").unwrap();
+    /// assert_eq!(policies.unique_id(&PolicyId::new("policy0")), PolicyId::new("policy0#2"));
+    /// assert_eq!(policies.unique_id(&PolicyId::new("policy1")), PolicyId::new("policy1"));
+    /// ```
+    pub fn unique_id(&self, id: &PolicyId) -> PolicyId {
+        if !self.contains_id(id) {
+            return id.clone();
+        }
+        let mut version: u64 = 2;
+        loop {
+            let candidate = PolicyId::new(format!("{id}#{version}"));
+            if !self.contains_id(&candidate) {
+                return candidate;
+            }
+            version += 1;
+        }
+    }
+
+    /// Add a static policy to the `PolicySet`, handling a collision with the
+    /// id of an existing policy or template according to `on_collision`.
+    /// Returns the id under which the policy was added.
+    ///
+    /// With [`IdCollisionBehavior::Replace`], the policy replaces the existing
+    /// definition as in [`PolicySet::merge_by_id()`] with
+    /// [`MergeConflictResolution::TakeIncoming`], so it is enabled and has no
+    /// metadata. If an error is returned, the `PolicySet` is unchanged.
+    pub fn add_with(
+        &mut self,
+        policy: Policy,
+        on_collision: IdCollisionBehavior,
+    ) -> Result<PolicyId, PolicySetError> {
+        let id = policy.id().clone();
+        match on_collision {
+            IdCollisionBehavior::Replace if self.contains_id(&id) => {
+                let mut incoming = Self::new();
+                incoming.add(policy)?;
+                self.merge_by_id(&incoming, MergeConflictResolution::TakeIncoming)?;
+                Ok(id)
+            }
+            IdCollisionBehavior::Version if self.contains_id(&id) => {
+                let id = self.unique_id(&id);
+                self.add(policy.new_id(id.clone()))?;
+                Ok(id)
+            }
+            IdCollisionBehavior::Error
+            | IdCollisionBehavior::Replace
+            | IdCollisionBehavior::Version => {
+                self.add(policy)?;
+                Ok(id)
+            }
+        }
+    }
+
+    /// Add a `Template` to the `PolicySet`, handling a collision with the id
+    /// of an existing policy or template according to `on_collision`.
+    /// Returns the id under which the template was added.
+    ///
+    /// See [`PolicySet::add_with()`].
+    pub fn add_template_with(
+        &mut self,
+        template: Template,
+        on_collision: IdCollisionBehavior,
+    ) -> Result<PolicyId, PolicySetError> {
+        let id = template.id().clone();
+        match on_collision {
+            IdCollisionBehavior::Replace if self.contains_id(&id) => {
+                let mut incoming = Self::new();
+                incoming.add_template(template)?;
+                self.merge_by_id(&incoming, MergeConflictResolution::TakeIncoming)?;
+                Ok(id)
+            }
+            IdCollisionBehavior::Version if self.contains_id(&id) => {
+                let id = self.unique_id(&id);
+                self.add_template(template.new_id(id.clone()))?;
+                Ok(id)
+            }
+            IdCollisionBehavior::Error
+            | IdCollisionBehavior::Replace
+            | IdCollisionBehavior::Version => {
+                self.add_template(template)?;
+                Ok(id)
+            }
+        }
+    }
+}
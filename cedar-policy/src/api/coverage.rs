@@ -0,0 +1,297 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::coverage()`], which reports how often each
+//! policy, and each clause of each policy, is satisfied by a corpus of
+//! requests, e.g., to find dead policies before deleting them.
+
+use super::{Entities, PolicyId, PolicySet, Request};
+use cedar_policy_core::ast::{
+    self, ActionConstraint, Expr, ExprKind, Literal, PrincipalOrResourceConstraint, ValueKind,
+};
+use cedar_policy_core::authorizer;
+use cedar_policy_core::evaluator::Evaluator;
+use cedar_policy_core::extensions::Extensions;
+
+/// The part of a policy that a [`ClauseCoverage`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClauseKind {
+    /// The principal constraint of the policy's scope
+    Principal,
+    /// The action constraint of the policy's scope
+    Action,
+    /// The resource constraint of the policy's scope
+    Resource,
+    /// A condition of the policy: a top-level conjunct of its `when` and
+    /// `unless` clauses
+    Condition,
+}
+
+/// How often a clause of a policy was satisfied. Returned by
+/// [`PolicyCoverage::clauses()`].
+///
+/// Clauses are evaluated in order, as they are during authorization, so a
+/// clause is not evaluated for a request if an earlier clause of the same
+/// policy was not satisfied.
+#[derive(Debug, Clone)]
+pub struct ClauseCoverage {
+    kind: ClauseKind,
+    expr: Expr,
+    satisfied: u64,
+    unsatisfied: u64,
+    errors: u64,
+}
+
+impl ClauseCoverage {
+    fn new(kind: ClauseKind, expr: Expr) -> Self {
+        Self {
+            kind,
+            expr,
+            satisfied: 0,
+            unsatisfied: 0,
+            errors: 0,
+        }
+    }
+
+    /// The part of the policy this clause is
+    pub fn kind(&self) -> ClauseKind {
+        self.kind
+    }
+
+    /// The clause, as a Cedar expression
+    pub fn expr(&self) -> String {
+        self.expr.to_string()
+    }
+
+    /// The number of requests for which this clause evaluated to `true`
+    pub fn satisfied(&self) -> u64 {
+        self.satisfied
+    }
+
+    /// The number of requests for which this clause evaluated to `false`
+    pub fn unsatisfied(&self) -> u64 {
+        self.unsatisfied
+    }
+
+    /// The number of requests for which evaluating this clause errored
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns `true` if this clause evaluated to both `true` and `false`
+    pub fn is_covered(&self) -> bool {
+        self.satisfied > 0 && self.unsatisfied > 0
+    }
+}
+
+/// How often a policy was satisfied. Returned by [`CoverageReport::policy()`]
+/// and [`CoverageReport::policies()`].
+#[derive(Debug, Clone)]
+pub struct PolicyCoverage {
+    id: PolicyId,
+    satisfied: u64,
+    determining: u64,
+    errors: u64,
+    clauses: Vec<ClauseCoverage>,
+}
+
+impl PolicyCoverage {
+    fn new(policy: &ast::Policy) -> Self {
+        let clauses = clauses(policy)
+            .into_iter()
+            .map(|(kind, expr)| ClauseCoverage::new(kind, expr))
+            .collect();
+        Self {
+            id: PolicyId::new(policy.id().clone()),
+            satisfied: 0,
+            determining: 0,
+            errors: 0,
+            clauses,
+        }
+    }
+
+    /// Evaluate each clause of `policy` in order, stopping at the first which
+    /// is not satisfied
+    fn record(&mut self, eval: &Evaluator<'_>, policy: &ast::Policy) {
+        for clause in &mut self.clauses {
+            let outcome = eval
+                .interpret(&clause.expr, policy.env())
+                .ok()
+                .and_then(|v| match v.value_kind() {
+                    ValueKind::Lit(Literal::Bool(b)) => Some(*b),
+                    _ => None,
+                });
+            match outcome {
+                Some(true) => clause.satisfied += 1,
+                Some(false) => {
+                    clause.unsatisfied += 1;
+                    return;
+                }
+                None => {
+                    clause.errors += 1;
+                    self.errors += 1;
+                    return;
+                }
+            }
+        }
+        self.satisfied += 1;
+    }
+
+    /// The id of the policy
+    pub fn id(&self) -> &PolicyId {
+        &self.id
+    }
+
+    /// The number of requests which satisfied the policy
+    pub fn satisfied(&self) -> u64 {
+        self.satisfied
+    }
+
+    /// The number of requests for which the policy determined the
+    /// authorization decision, i.e., was in [`crate::Diagnostics::reason()`]
+    pub fn determining(&self) -> u64 {
+        self.determining
+    }
+
+    /// The number of requests for which evaluating the policy errored
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// The clauses of the policy: its scope constraints (other than
+    /// unconstrained `principal`, `action`, or `resource`), followed by its
+    /// conditions
+    pub fn clauses(&self) -> impl Iterator<Item = &ClauseCoverage> {
+        self.clauses.iter()
+    }
+}
+
+/// Per-policy and per-clause statistics for a corpus of requests. Returned by
+/// [`PolicySet::coverage()`].
+#[derive(Debug, Clone)]
+pub struct CoverageReport {
+    requests: u64,
+    policies: Vec<PolicyCoverage>,
+}
+
+impl CoverageReport {
+    /// The number of requests in the corpus
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Iterate over the statistics for each static and template-linked
+    /// policy, in the order of the policy set
+    pub fn policies(&self) -> impl Iterator<Item = &PolicyCoverage> {
+        self.policies.iter()
+    }
+
+    /// Get the statistics for the policy with id `id`
+    pub fn policy(&self, id: &PolicyId) -> Option<&PolicyCoverage> {
+        self.policies.iter().find(|p| &p.id == id)
+    }
+
+    /// Iterate over the ids of the policies which no request satisfied
+    pub fn never_satisfied(&self) -> impl Iterator<Item = &PolicyId> {
+        self.policies
+            .iter()
+            .filter(|p| p.satisfied == 0)
+            .map(|p| &p.id)
+    }
+}
+
+impl PolicySet {
+    /// Evaluate every policy in this policy set, including disabled policies,
+    /// for each of `requests` with respect to `entities`, and report how often
+    /// each policy and each of its clauses was satisfied.
+    ///
+    /// ```
+    /// # use cedar_policy::{Context, Entities, EntityUid, PolicyId, PolicySet, Request};
+    /// # use std::str::FromStr;
+    /// let policies = PolicySet::from_str(r#"
+    ///     permit(principal, action == Action::"view", resource);
+    ///     permit(principal, action == Action::"edit", resource) when { context.owner };
+    /// "#).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"a""#).unwrap(),
+    ///     Context::empty(),
+    ///     None,
+    /// ).unwrap();
+    /// let report = policies.coverage([&request], &Entities::empty());
+    /// assert_eq!(report.policy(&PolicyId::new("policy0")).unwrap().satisfied(), 1);
+    /// assert_eq!(report.never_satisfied().collect::<Vec<_>>(), [&PolicyId::new("policy1")]);
+    /// ```
+    pub fn coverage<'a>(
+        &self,
+        requests: impl IntoIterator<Item = &'a Request>,
+        entities: &Entities,
+    ) -> CoverageReport {
+        let authorizer = authorizer::Authorizer::new();
+        let mut report = CoverageReport {
+            requests: 0,
+            policies: self.ast.policies().map(PolicyCoverage::new).collect(),
+        };
+        for request in requests {
+            report.requests += 1;
+            let eval = Evaluator::new(request.0.clone(), &entities.0, Extensions::all_available());
+            for (policy, coverage) in self.ast.policies().zip(&mut report.policies) {
+                coverage.record(&eval, policy);
+            }
+            let response = authorizer.is_authorized(request.0.clone(), &self.ast, &entities.0);
+            for coverage in &mut report.policies {
+                if response.diagnostics.reason.contains(coverage.id.as_ref()) {
+                    coverage.determining += 1;
+                }
+            }
+        }
+        report
+    }
+}
+
+/// The clauses of `policy` in evaluation order: its scope constraints (other
+/// than unconstrained `principal`, `action`, or `resource`), followed by the
+/// top-level conjuncts of its conditions
+pub(super) fn clauses(policy: &ast::Policy) -> Vec<(ClauseKind, Expr)> {
+    let mut clauses = Vec::new();
+    let principal = policy.principal_constraint();
+    if principal.as_inner() != &PrincipalOrResourceConstraint::Any {
+        clauses.push((ClauseKind::Principal, principal.as_expr()));
+    }
+    if policy.action_constraint() != &ActionConstraint::Any {
+        clauses.push((ClauseKind::Action, policy.action_constraint().as_expr()));
+    }
+    let resource = policy.resource_constraint();
+    if resource.as_inner() != &PrincipalOrResourceConstraint::Any {
+        clauses.push((ClauseKind::Resource, resource.as_expr()));
+    }
+    if let Some(conditions) = policy.non_scope_constraints() {
+        push_conjuncts(conditions, &mut clauses);
+    }
+    clauses
+}
+
+/// Push the top-level conjuncts of `expr` onto `clauses`, in evaluation order
+fn push_conjuncts(expr: &Expr, clauses: &mut Vec<(ClauseKind, Expr)>) {
+    match expr.expr_kind() {
+        ExprKind::And { left, right } => {
+            push_conjuncts(left, clauses);
+            push_conjuncts(right, clauses);
+        }
+        _ => clauses.push((ClauseKind::Condition, expr.clone())),
+    }
+}
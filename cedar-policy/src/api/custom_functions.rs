@@ -0,0 +1,678 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Custom extension functions, implemented in Rust and called from policies
+//! like the builtin extension functions
+
+use super::{custom_extension_errors, CustomExtensionError, DeclaredType, EvalResult};
+use cedar_policy_core::ast::{self, CallStyle, RestrictedExpr};
+use cedar_policy_core::entities::SchemaType;
+use cedar_policy_core::evaluator::RestrictedEvaluator;
+use cedar_policy_core::extensions::{self, Extensions};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The Rust implementation of a [`CustomFunction`]
+type Callback = Box<dyn Fn(&[EvalResult]) -> Result<EvalResult, String> + Send + Sync + 'static>;
+
+/// The Rust implementation of [`CustomFunction::with_short_circuit()`]
+type ShortCircuit =
+    Box<dyn Fn(&[EvalResult]) -> Option<Result<EvalResult, String>> + Send + Sync + 'static>;
+
+/// A function in a [`CustomExtension`].
+///
+/// The function is called with arguments of the declared types, and must
+/// return a value of the declared return type, or an error message. Cedar
+/// checks both, so a function returning a value of the wrong type causes an
+/// evaluation error, as does a function returning `Err`. Extension values are
+/// passed and returned as [`EvalResult::ExtensionValue`], e.g., as
+/// `decimal("1.5")`.
+pub struct CustomFunction {
+    name: String,
+    style: CallStyle,
+    arg_types: Vec<DeclaredType>,
+    return_type: DeclaredType,
+    func: Callback,
+    doc: Option<String>,
+    short_circuit: Option<ShortCircuit>,
+}
+
+impl std::fmt::Debug for CustomFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomFunction")
+            .field("name", &self.name)
+            .field("style", &self.style)
+            .field("arg_types", &self.arg_types)
+            .field("return_type", &self.return_type)
+            .field("doc", &self.doc)
+            .field("has_lazy_args", &self.short_circuit.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CustomFunction {
+    /// A function called like `name(arg1, arg2)`. The name may be qualified
+    /// by a namespace, e.g., `geo::distance`.
+    pub fn function(
+        name: impl Into<String>,
+        arg_types: Vec<DeclaredType>,
+        return_type: DeclaredType,
+        func: impl Fn(&[EvalResult]) -> Result<EvalResult, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            style: CallStyle::FunctionStyle,
+            arg_types,
+            return_type,
+            func: Box::new(func),
+            doc: None,
+            short_circuit: None,
+        }
+    }
+
+    /// A method called like `arg1.name(arg2)`. The first argument is the value
+    /// the method is called on, so `arg_types` must not be empty, and the name
+    /// must not be qualified by a namespace.
+    pub fn method(
+        name: impl Into<String>,
+        arg_types: Vec<DeclaredType>,
+        return_type: DeclaredType,
+        func: impl Fn(&[EvalResult]) -> Result<EvalResult, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            style: CallStyle::MethodStyle,
+            ..Self::function(name, arg_types, return_type, func)
+        }
+    }
+
+    /// Attach a short description of the function, which is returned by
+    /// [`crate::extension_functions()`] for editors and other tools
+    #[must_use]
+    pub fn with_doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Evaluate the arguments of the function lazily, so that it can
+    /// short-circuit like `&&` and `||`.
+    ///
+    /// Arguments are evaluated left to right, and before each argument after
+    /// the first, `short_circuit` is called with the arguments evaluated so
+    /// far. If it returns `Some`, that is the result of the call, and the
+    /// remaining arguments are never evaluated, so they cost nothing and
+    /// can't cause errors. `short_circuit` must agree with the function:
+    /// whenever it returns a result, the function must return the same result
+    /// for any remaining arguments. Like the function, `short_circuit` is
+    /// called with arguments of the declared types, and its result is checked
+    /// against the declared return type.
+    ///
+    /// ```
+    /// # use cedar_policy::{CustomFunction, DeclaredType, EvalResult};
+    /// // `a.impliesThat(b)` doesn't evaluate `b` when `a` is `false`
+    /// CustomFunction::method(
+    ///     "impliesThat",
+    ///     vec![DeclaredType::Bool, DeclaredType::Bool],
+    ///     DeclaredType::Bool,
+    ///     |args| match args {
+    ///         [EvalResult::Bool(a), EvalResult::Bool(b)] => Ok(EvalResult::Bool(!a || *b)),
+    ///         _ => Err("expected two booleans".into()),
+    ///     },
+    /// )
+    /// .with_short_circuit(|known| match known {
+    ///     [EvalResult::Bool(false)] => Some(Ok(EvalResult::Bool(true))),
+    ///     _ => None,
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_short_circuit(
+        mut self,
+        short_circuit: impl Fn(&[EvalResult]) -> Option<Result<EvalResult, String>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.short_circuit = Some(Box::new(short_circuit));
+        self
+    }
+
+    /// The name of the function
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn into_extension_function(self) -> Result<ast::ExtensionFunction, CustomExtensionError> {
+        let name = parse_name(&self.name)?;
+        let unsupported = |ty: &DeclaredType| custom_extension_errors::UnsupportedTypeError {
+            function: self.name.clone(),
+            ty: ty.clone(),
+        };
+        let arg_types = self
+            .arg_types
+            .iter()
+            .map(|ty| to_schema_type(ty).ok_or_else(|| unsupported(ty)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let return_type =
+            to_schema_type(&self.return_type).ok_or_else(|| unsupported(&self.return_type))?;
+        let func = self.func;
+        let ext_func = ast::ExtensionFunction::custom(
+            name,
+            self.style,
+            Box::new(move |args: &[ast::Value]| {
+                let args: Vec<EvalResult> = args.iter().cloned().map(EvalResult::from).collect();
+                to_value(func(&args)?)
+            }),
+            return_type,
+            arg_types,
+        );
+        let ext_func = match self.short_circuit {
+            Some(short_circuit) => {
+                ext_func.with_custom_short_circuit(Box::new(move |known: &[ast::Value]| {
+                    let known: Vec<EvalResult> =
+                        known.iter().cloned().map(EvalResult::from).collect();
+                    short_circuit(&known).map(|res| to_value(res?))
+                }))
+            }
+            None => ext_func,
+        };
+        Ok(match self.doc {
+            Some(doc) => ext_func.with_doc(doc),
+            None => ext_func,
+        })
+    }
+}
+
+/// A custom extension: a named group of [`CustomFunction`]s which policies can
+/// call like builtin extension functions.
+///
+/// Custom extensions are registered process-wide with
+/// [`CustomExtension::register()`], and are then available to every policy
+/// parsed, evaluated, or validated afterwards. Registration must happen before
+/// any policy, expression, or schema is parsed, e.g., at the start of `main`.
+/// Alternatively, custom extensions can be added to an [`ExtensionSet`],
+/// which is then only used by the authorizers and validators given it.
+///
+/// Custom functions must behave like builtin extension functions, which
+/// Cedar relies on to be:
+/// - deterministic: given the same arguments, a function must always return
+///   the same result, without reading the clock, randomness, files, the
+///   network, or any other outside state. Authorization decisions must not
+///   depend on when or where they are made.
+/// - pure: a function must not have observable side effects, as Cedar may
+///   call it any number of times, or not at all, when evaluating a policy.
+/// - total: a function should return `Err` rather than panic.
+/// - thread-safe: a function may be called from several threads at once.
+///
+/// Policies calling custom functions can't be converted to the policy
+/// syntax tree ([`crate::pst`]) or analyzed symbolically.
+///
+/// ```
+/// # use cedar_policy::{CustomExtension, CustomFunction, DeclaredType, EvalResult};
+/// CustomExtension::new("geo")
+///     .with_function(CustomFunction::method(
+///         "isInRegion",
+///         vec![DeclaredType::String, DeclaredType::String],
+///         DeclaredType::Bool,
+///         |args| match args {
+///             [EvalResult::String(country), EvalResult::String(region)] => {
+///                 Ok(EvalResult::Bool(region == "eu" && ["DE", "FR"].contains(&country.as_str())))
+///             }
+///             _ => Err("expected two strings".into()),
+///         },
+///     ))
+///     .register()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CustomExtension {
+    name: String,
+    functions: Vec<CustomFunction>,
+}
+
+impl CustomExtension {
+    /// Create an extension with the given name and no functions
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// Add a function to the extension
+    #[must_use]
+    pub fn with_function(mut self, function: CustomFunction) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// Register the extension, making its functions available to the parser,
+    /// evaluator, and validator.
+    ///
+    /// Returns an error if Cedar has already parsed, evaluated, or validated
+    /// anything, or if a function conflicts with a builtin function or a
+    /// function in another registered extension.
+    pub fn register(self) -> Result<(), CustomExtensionError> {
+        extensions::register_custom_extension(self.into_extension()?)
+            .map_err(|err| custom_extension_errors::RegistrationError::from(err).into())
+    }
+
+    fn into_extension(self) -> Result<ast::Extension, CustomExtensionError> {
+        let name = parse_name(&self.name)?;
+        let mut names = HashSet::new();
+        if let Some(dup) = self.functions.iter().find(|f| !names.insert(f.name())) {
+            return Err(custom_extension_errors::DuplicateFunctionError {
+                name: dup.name.clone(),
+            }
+            .into());
+        }
+        let functions = self
+            .functions
+            .into_iter()
+            .map(CustomFunction::into_extension_function)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ast::Extension::new(name, functions, std::iter::empty()))
+    }
+}
+
+/// The extensions used by an [`crate::Authorizer`] or [`crate::Validator`]:
+/// all builtin extensions, and a set of custom extensions.
+///
+/// Unlike registering custom extensions with [`CustomExtension::register()`],
+/// building an `ExtensionSet` can happen at any time, and different
+/// authorizers and validators can use different custom extensions, e.g., one
+/// set per tenant. Policies calling the custom functions of a set are parsed
+/// with it, e.g., with [`crate::PolicySet::from_str_with_extensions()`], and
+/// converted to and from JSON with the `_with_extensions` variants of the
+/// conversion functions. Calling a function which isn't in the
+/// `ExtensionSet` of an authorizer is an evaluation error, and of a
+/// validator, a validation error.
+///
+/// ```
+/// # use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, EvalResult, CustomExtension, CustomFunction, DeclaredType, ExtensionSet, PolicySet, Request};
+/// # use std::str::FromStr;
+/// let tenant_region = |region: &'static str| {
+///     CustomExtension::new("tenant").with_function(CustomFunction::function(
+///         "tenant::region",
+///         vec![],
+///         DeclaredType::String,
+///         move |_| Ok(EvalResult::String(region.into())),
+///     ))
+/// };
+/// let eu = ExtensionSet::builder()
+///     .with_extension(tenant_region("eu"))
+///     .build()
+///     .unwrap();
+/// let us = ExtensionSet::builder()
+///     .with_extension(tenant_region("us"))
+///     .build()
+///     .unwrap();
+///
+/// let policies = PolicySet::from_str_with_extensions(r#"
+///     permit(principal, action, resource) when { tenant::region() == "eu" };
+/// "#, &eu).unwrap();
+/// let request = Request::new(
+///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+///     EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+///     Context::empty(),
+///     None,
+/// )
+/// .unwrap();
+/// let decide = |extensions: &ExtensionSet| {
+///     Authorizer::new()
+///         .with_extensions(extensions)
+///         .is_authorized(&request, &policies, &Entities::empty())
+///         .decision()
+/// };
+/// assert_eq!(decide(&eu), Decision::Allow);
+/// assert_eq!(decide(&us), Decision::Deny);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtensionSet(Arc<Extensions<'static>>);
+
+impl ExtensionSet {
+    /// The builtin extensions and the custom extensions registered with
+    /// [`CustomExtension::register()`], which authorizers and validators use
+    /// unless given another `ExtensionSet`
+    pub fn all_available() -> Self {
+        Self(Extensions::all_available_shared())
+    }
+
+    /// Start building an `ExtensionSet` with the builtin extensions and the
+    /// custom extensions added to the [`ExtensionSetBuilder`]. Custom
+    /// extensions registered with [`CustomExtension::register()`] are not
+    /// included.
+    pub fn builder() -> ExtensionSetBuilder {
+        ExtensionSetBuilder {
+            extensions: Vec::new(),
+        }
+    }
+
+    pub(crate) fn shared(&self) -> Arc<Extensions<'static>> {
+        Arc::clone(&self.0)
+    }
+
+    pub(crate) fn as_core(&self) -> &Extensions<'static> {
+        &self.0
+    }
+}
+
+/// Builder for an [`ExtensionSet`], created with [`ExtensionSet::builder()`]
+#[derive(Debug)]
+pub struct ExtensionSetBuilder {
+    extensions: Vec<PendingExtension>,
+}
+
+/// An extension added to an [`ExtensionSetBuilder`], which is checked when
+/// the set is built
+#[derive(Debug)]
+enum PendingExtension {
+    Custom(CustomExtension),
+    #[cfg(feature = "prf")]
+    Prf(Arc<[u8]>),
+    #[cfg(feature = "regions")]
+    Regions(RegionsExtension),
+}
+
+impl PendingExtension {
+    fn into_extension(self) -> Result<ast::Extension, CustomExtensionError> {
+        match self {
+            Self::Custom(extension) => extension.into_extension(),
+            #[cfg(feature = "prf")]
+            Self::Prf(key) => Ok(extensions::prf::extension(key)),
+            #[cfg(feature = "regions")]
+            Self::Regions(regions) => regions.into_extension(),
+        }
+    }
+}
+
+impl ExtensionSetBuilder {
+    /// Add a custom extension
+    #[must_use]
+    pub fn with_extension(mut self, extension: CustomExtension) -> Self {
+        self.extensions.push(PendingExtension::Custom(extension));
+        self
+    }
+
+    /// Add the `prf` extension with the given secret `key`, making the keyed
+    /// hash function `prf` available to policies evaluated and validated with
+    /// this set.
+    ///
+    /// `prf(s)` returns the HMAC-SHA256 of `s` under `key` as 64 lowercase hex
+    /// digits, so a policy can compare a hashed identifier, such as
+    /// `prf(principal.email) == "5bdc..."`, without containing the identifier
+    /// itself. The output depends only on `key` and `s`, so hashes can be
+    /// computed ahead of time when writing policies. `key` should be at least
+    /// 32 random bytes and kept secret: with it, low-entropy identifiers can
+    /// be recovered by hashing guesses.
+    ///
+    /// Each `ExtensionSet` has its own key, so tenants can have different
+    /// keys, and a key can be rotated by authorizing with a set with the new
+    /// key once policies have been rewritten for it.
+    ///
+    /// ```
+    /// # use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, ExtensionSet, PolicySet, Request};
+    /// # use std::str::FromStr;
+    /// let tenant_a = ExtensionSet::builder().with_prf(b"Jefe".as_slice()).build().unwrap();
+    /// let tenant_b = ExtensionSet::builder().with_prf(b"another key".as_slice()).build().unwrap();
+    /// let policies = PolicySet::from_str_with_extensions(r#"
+    ///     permit(principal, action, resource)
+    ///     when { prf("what do ya want for nothing?") == "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843" };
+    /// "#, &tenant_a).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+    ///     Context::empty(),
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let decide = |extensions: &ExtensionSet| {
+    ///     Authorizer::new()
+    ///         .with_extensions(extensions)
+    ///         .is_authorized(&request, &policies, &Entities::empty())
+    ///         .decision()
+    /// };
+    /// assert_eq!(decide(&tenant_a), Decision::Allow);
+    /// assert_eq!(decide(&tenant_b), Decision::Deny);
+    /// ```
+    #[cfg(feature = "prf")]
+    #[must_use]
+    pub fn with_prf(mut self, key: impl Into<Arc<[u8]>>) -> Self {
+        self.extensions.push(PendingExtension::Prf(key.into()));
+        self
+    }
+
+    /// Add the `regions` extension with the region groupings of `regions`.
+    /// See [`RegionsExtension`] for how `regions` works.
+    ///
+    /// Each `ExtensionSet` has its own groupings, so, e.g., tenants can
+    /// declare their own regions.
+    ///
+    /// ```
+    /// # use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, ExtensionSet, PolicySet, RegionsExtension, Request};
+    /// # use std::str::FromStr;
+    /// let tenant_a = ExtensionSet::builder()
+    ///     .with_regions(RegionsExtension::empty().with_region("HOME", ["DE", "AT"]))
+    ///     .build()
+    ///     .unwrap();
+    /// let tenant_b = ExtensionSet::builder()
+    ///     .with_regions(RegionsExtension::empty().with_region("HOME", ["US"]))
+    ///     .build()
+    ///     .unwrap();
+    /// let policies = PolicySet::from_str_with_extensions(r#"
+    ///     permit(principal, action, resource) when { "de".inRegion("HOME") };
+    /// "#, &tenant_a).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+    ///     Context::empty(),
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let decide = |extensions: &ExtensionSet| {
+    ///     Authorizer::new()
+    ///         .with_extensions(extensions)
+    ///         .is_authorized(&request, &policies, &Entities::empty())
+    ///         .decision()
+    /// };
+    /// assert_eq!(decide(&tenant_a), Decision::Allow);
+    /// assert_eq!(decide(&tenant_b), Decision::Deny);
+    /// ```
+    #[cfg(feature = "regions")]
+    #[must_use]
+    pub fn with_regions(mut self, regions: RegionsExtension) -> Self {
+        self.extensions.push(PendingExtension::Regions(regions));
+        self
+    }
+
+    /// Build the `ExtensionSet`.
+    ///
+    /// Returns an error if a custom extension is invalid, e.g., a region of
+    /// the `regions` extension contains a string which isn't a country code,
+    /// or a function conflicts with a builtin function or a function in
+    /// another custom extension of the set. Also returns an error if a function has the name
+    /// of a method in another `ExtensionSet`, or vice versa.
+    pub fn build(self) -> Result<ExtensionSet, CustomExtensionError> {
+        let custom = self
+            .extensions
+            .into_iter()
+            .map(PendingExtension::into_extension)
+            .collect::<Result<Vec<_>, _>>()?;
+        Extensions::with_custom_extensions(custom)
+            .map(|extensions| ExtensionSet(Arc::new(extensions)))
+            .map_err(|err| custom_extension_errors::RegistrationError::from(err).into())
+    }
+}
+
+fn parse_name(name: &str) -> Result<ast::Name, custom_extension_errors::InvalidNameError> {
+    ast::Name::from_str(name).map_err(|_| custom_extension_errors::InvalidNameError {
+        name: name.to_owned(),
+    })
+}
+
+/// Convert a declared type to the type used by the evaluator, returning `None`
+/// for types that custom functions can't use
+fn to_schema_type(ty: &DeclaredType) -> Option<SchemaType> {
+    Some(match ty {
+        DeclaredType::Bool => SchemaType::Bool,
+        DeclaredType::Long => SchemaType::Long,
+        DeclaredType::String => SchemaType::String,
+        DeclaredType::Set(element_ty) => SchemaType::Set {
+            element_ty: Box::new(to_schema_type(element_ty)?),
+        },
+        DeclaredType::Entity(ty) => SchemaType::Entity {
+            ty: ty.as_ref().clone(),
+        },
+        DeclaredType::Extension(name) => SchemaType::Extension {
+            name: ast::Name::from_str(name).ok()?,
+        },
+        DeclaredType::Map(_) | DeclaredType::Record { .. } | DeclaredType::EntityUnion(_) => {
+            return None
+        }
+    })
+}
+
+/// Convert the result of a custom function back to a value
+fn to_value(res: EvalResult) -> Result<ast::Value, String> {
+    Ok(match res {
+        EvalResult::Bool(b) => b.into(),
+        EvalResult::Long(i) => i.into(),
+        EvalResult::String(s) => s.into(),
+        EvalResult::EntityUid(uid) => ast::EntityUID::from(uid).into(),
+        EvalResult::Set(set) => ast::Value::set(
+            set.0
+                .into_iter()
+                .map(to_value)
+                .collect::<Result<Vec<_>, _>>()?,
+            None,
+        ),
+        EvalResult::Record(record) => ast::Value::record(
+            record
+                .0
+                .into_iter()
+                .map(|(k, v)| Ok((k, to_value(v)?)))
+                .collect::<Result<Vec<_>, String>>()?,
+            None,
+        ),
+        EvalResult::ExtensionValue(s) => {
+            let expr = RestrictedExpr::from_str(&s).map_err(|err| err.to_string())?;
+            RestrictedEvaluator::new(Extensions::all_available())
+                .interpret(expr.as_borrowed())
+                .map_err(|err| err.to_string())?
+        }
+    })
+}
+
+/// The `regions` extension, with methods on ISO 3166-1 alpha-2 country codes
+/// for data-residency policies.
+///
+/// `code.inRegion(region)` tests whether the country `code` is in the region
+/// grouping named `region`, e.g., `resource.country.inRegion("EU")`, and
+/// `code.isCountryCode()` tests whether a string is an assigned country code.
+/// Country codes are matched case-insensitively, while region names are
+/// case-sensitive. `inRegion` errors if its receiver isn't a country code or
+/// its argument isn't a declared region.
+///
+/// The groupings of regions are declared by the application and given to
+/// individual authorizers and validators with
+/// [`ExtensionSetBuilder::with_regions()`].
+///
+/// ```
+/// # use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, ExtensionSet, PolicySet, RegionsExtension, Request};
+/// # use std::str::FromStr;
+/// let extensions = ExtensionSet::builder()
+///     .with_regions(RegionsExtension::standard().with_region("DACH", ["DE", "AT", "CH"]))
+///     .build()
+///     .unwrap();
+/// let policies = PolicySet::from_str_with_extensions(r#"
+///     permit(principal, action, resource)
+///     when { "fr".inRegion("EU") && !"NO".inRegion("EU") && "NO".inRegion("EEA") && "ch".inRegion("DACH") };
+/// "#, &extensions).unwrap();
+/// let request = Request::new(
+///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+///     EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+///     Context::empty(),
+///     None,
+/// )
+/// .unwrap();
+/// let response = Authorizer::new()
+///     .with_extensions(&extensions)
+///     .is_authorized(&request, &policies, &Entities::empty());
+/// assert_eq!(response.decision(), Decision::Allow);
+/// ```
+#[cfg(feature = "regions")]
+#[derive(Debug, Clone)]
+pub struct RegionsExtension {
+    standard: bool,
+    regions: Vec<(String, Vec<String>)>,
+}
+
+#[cfg(feature = "regions")]
+impl RegionsExtension {
+    /// The extension with no regions declared
+    pub fn empty() -> Self {
+        Self {
+            standard: false,
+            regions: Vec::new(),
+        }
+    }
+
+    /// The extension with the `EU` region, of the member states of the
+    /// European Union, and the `EEA` region, of the European Economic Area
+    pub fn standard() -> Self {
+        Self {
+            standard: true,
+            regions: Vec::new(),
+        }
+    }
+
+    /// Declare the region `name` with the given country codes, in either
+    /// case, replacing any region already declared with that name
+    #[must_use]
+    pub fn with_region(
+        mut self,
+        name: impl Into<String>,
+        codes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.regions
+            .push((name.into(), codes.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    fn into_extension(self) -> Result<ast::Extension, CustomExtensionError> {
+        use extensions::regions::Regions;
+        let base = if self.standard {
+            Regions::standard()
+        } else {
+            Regions::empty()
+        };
+        let regions = self
+            .regions
+            .into_iter()
+            .try_fold(base, |regions, (name, codes)| {
+                regions.with_region(name.as_str(), &codes).map_err(|err| {
+                    custom_extension_errors::InvalidCountryCodeError {
+                        region: name,
+                        country_code: err.code().to_string(),
+                    }
+                })
+            })?;
+        Ok(extensions::regions::extension(regions))
+    }
+}
@@ -272,7 +272,7 @@ mod extra_fields_allowed {
                 "type": "Long",
                 "bogus": "bogus",
             }),
-            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`",
         );
         assert_type_json_ok_deprecated_and_err_standard(
             &json!({
@@ -315,7 +315,7 @@ mod extra_fields_allowed {
                 "type": "Boolean",
                 "bogus": "bogus",
             }),
-            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`",
         );
         assert_type_json_ok_deprecated_and_err_standard(
             &json!({
@@ -358,7 +358,7 @@ mod extra_fields_allowed {
                 "type": "String",
                 "bogus": "bogus",
             }),
-            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`",
         );
         assert_type_json_ok_deprecated_and_err_standard(
             &json!({
@@ -401,7 +401,7 @@ mod extra_fields_allowed {
                 "type": "ty",
                 "bogus": "bogus",
             }),
-            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`",
+            "unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`",
         );
         assert_type_json_ok_deprecated_and_err_standard(
             &json!({
@@ -475,7 +475,7 @@ mod extra_fields_forbidden {
                 "element": {"type": "Long"},
                 "bogus": "bogus",
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`")
                 .build(),
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Set")
                 .help("neither `ns::Set` nor `Set` refers to anything that has been declared as a common type")
@@ -532,7 +532,7 @@ mod extra_fields_forbidden {
                 "name": "User",
                 "bogus": "bogus",
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`")
                 .build(),
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Entity")
                 .help("neither `ns::Entity` nor `Entity` refers to anything that has been declared as a common type")
@@ -588,7 +588,7 @@ mod extra_fields_forbidden {
                 "name": "ip",
                 "bogus": "bogus"
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`")
                 .build(),
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Extension")
                 .help("neither `ns::Extension` nor `Extension` refers to anything that has been declared as a common type")
@@ -644,7 +644,7 @@ mod extra_fields_forbidden {
                 "attributes": {},
                 "bogus": "bogus"
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`")
+            &ExpectedErrorMessageBuilder::error("unknown field `bogus`, expected one of `type`, `element`, `attributes`, `additionalAttributes`, `name`, `members`")
                 .build(),
             &ExpectedErrorMessageBuilder::error("failed to resolve type: Record")
                 .help("neither `ns::Record` nor `Record` refers to anything that has been declared as a common type")
@@ -708,7 +708,7 @@ mod extra_fields_forbidden {
                     "actions": {},
                 }
             }),
-            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `memberOfTypes`, `shape`, `tags`, `enum`, `annotations`").build(),
+            &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected one of `memberOfTypes`, `shape`, `tags`, `constraints`, `enum`, `annotations`").build(),
             &ExpectedErrorMessageBuilder::error("unknown field `foo`, expected `memberOfTypes` or `shape`").build(),
         );
     }
@@ -0,0 +1,241 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::diff()`] and [`PolicySet::merge_by_id()`],
+//! which compare and combine policy sets by policy and template id, e.g., to
+//! promote policies from one environment to another.
+
+use super::{
+    policy_set_errors::MergeConflictError, PolicyId, PolicySet, PolicySetError,
+    RestrictedExpression, SlotBindings,
+};
+use cedar_policy_core::ast;
+use ref_cast::RefCast;
+
+/// The differences between two policy sets, by policy and template id.
+/// Returned by [`PolicySet::diff()`] and [`PolicySet::merge_by_id()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicySetDiff {
+    added: Vec<PolicyId>,
+    removed: Vec<PolicyId>,
+    modified: Vec<PolicyId>,
+}
+
+impl PolicySetDiff {
+    /// Ids of the policies and templates which are only in the new policy set
+    pub fn added(&self) -> impl Iterator<Item = &PolicyId> {
+        self.added.iter()
+    }
+
+    /// Ids of the policies and templates which are only in the old policy set
+    pub fn removed(&self) -> impl Iterator<Item = &PolicyId> {
+        self.removed.iter()
+    }
+
+    /// Ids of the policies and templates which are in both policy sets, but
+    /// are defined differently
+    pub fn modified(&self) -> impl Iterator<Item = &PolicyId> {
+        self.modified.iter()
+    }
+
+    /// Returns `true` if the two policy sets define the same policies and
+    /// templates
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// How [`PolicySet::merge_by_id()`] handles a policy or template id which is
+/// defined differently in the two policy sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeConflictResolution {
+    /// Return an error and leave the policy set unchanged
+    Fail,
+    /// Keep the existing definition
+    KeepExisting,
+    /// Replace the existing definition with the incoming one
+    TakeIncoming,
+}
+
+/// The definition of a policy or template id in a policy set
+#[derive(PartialEq)]
+enum Definition<'a> {
+    Policy(&'a ast::Policy),
+    Template(&'a ast::Template),
+}
+
+impl PolicySet {
+    fn definition(&self, id: &PolicyId) -> Option<Definition<'_>> {
+        self.policies
+            .get(id)
+            .map(|p| Definition::Policy(&p.ast))
+            .or_else(|| self.templates.get(id).map(|t| Definition::Template(&t.ast)))
+    }
+
+    fn ids(&self) -> impl Iterator<Item = &PolicyId> {
+        self.templates.keys().chain(self.policies.keys())
+    }
+
+    /// Compare this policy set to `other`, by policy and template id.
+    ///
+    /// A policy or template is modified if its definition in `other` differs
+    /// from its definition in `self`. Definitions are compared by their
+    /// abstract syntax, so changes to formatting or comments are not
+    /// modifications, but changes to annotations are. A link is modified if
+    /// its template or slot bindings are.
+    pub fn diff(&self, other: &Self) -> PolicySetDiff {
+        let mut diff = PolicySetDiff::default();
+        for id in self.ids() {
+            match other.definition(id) {
+                None => diff.removed.push(id.clone()),
+                Some(theirs) => {
+                    if self.definition(id) != Some(theirs) {
+                        diff.modified.push(id.clone());
+                    }
+                }
+            }
+        }
+        diff.added = other
+            .ids()
+            .filter(|id| self.definition(id).is_none())
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Add the policies, templates, and links of `other` to this policy set,
+    /// matching them by id. Unlike [`PolicySet::merge()`], an id which is
+    /// defined identically in both policy sets (in the sense of
+    /// [`PolicySet::diff()`]) is not a conflict; an id which is defined
+    /// differently is handled according to `resolution`.
+    ///
+    /// Incoming links are linked against the template with the same id in the
+    /// merged policy set. When a template is replaced, the existing links to
+    /// it are relinked against the incoming template. Metadata and whether
+    /// the policy or template is enabled are taken along with it.
+    ///
+    /// Returns the changes made to this policy set. If an error is returned,
+    /// this policy set is unchanged.
+    pub fn merge_by_id(
+        &mut self,
+        other: &Self,
+        resolution: MergeConflictResolution,
+    ) -> Result<PolicySetDiff, PolicySetError> {
+        let diff = self.diff(other);
+        let replaced = match resolution {
+            MergeConflictResolution::Fail if !diff.modified.is_empty() => {
+                return Err(MergeConflictError {
+                    policy_ids: diff.modified,
+                }
+                .into());
+            }
+            MergeConflictResolution::Fail | MergeConflictResolution::KeepExisting => &[][..],
+            MergeConflictResolution::TakeIncoming => &diff.modified[..],
+        };
+        let incoming = |id: &PolicyId| diff.added.contains(id) || replaced.contains(id);
+
+        let mut merged = self.clone();
+        // Existing links to replaced templates are relinked below
+        let relinked: Vec<_> = self
+            .policies
+            .values()
+            .filter(|p| {
+                p.template_id()
+                    .is_some_and(|t| replaced.contains(t) && !replaced.contains(p.id()))
+            })
+            .collect();
+        for p in &relinked {
+            merged.unlink(p.id().clone())?;
+        }
+        // Remove links before the templates they are linked to
+        for id in replaced {
+            match merged.policies.get(id).map(super::Policy::is_static) {
+                Some(true) => {
+                    merged.remove_static(id.clone())?;
+                }
+                Some(false) => {
+                    merged.unlink(id.clone())?;
+                }
+                None => (),
+            }
+        }
+        for id in replaced {
+            if merged.templates.contains_key(id) {
+                merged.remove_template(id.clone())?;
+            }
+        }
+
+        for t in other.templates.values().filter(|t| incoming(t.id())) {
+            merged.add_template(t.clone())?;
+        }
+        let mut links = Vec::new();
+        for p in other.policies.values().filter(|p| incoming(p.id())) {
+            if p.is_static() {
+                merged.add(p.clone())?;
+            } else {
+                links.push(p);
+            }
+        }
+        for p in relinked.iter().chain(&links) {
+            merged.relink(p.id().clone(), &p.ast)?;
+        }
+
+        for id in other.ids().filter(|id| incoming(id)) {
+            match other.metadata.get(id) {
+                Some(metadata) => merged.metadata.insert(id.clone(), metadata.clone()),
+                None => merged.metadata.remove(id),
+            };
+            merged.ast.set_enabled(id.as_ref(), other.is_enabled(id));
+        }
+        for p in &relinked {
+            if let Some(metadata) = self.metadata.get(p.id()) {
+                merged.metadata.insert(p.id().clone(), metadata.clone());
+            }
+            merged
+                .ast
+                .set_enabled(p.id().as_ref(), self.is_enabled(p.id()));
+        }
+
+        let applied = self.diff(&merged);
+        *self = merged;
+        Ok(applied)
+    }
+
+    /// Link `id` with the same template id and slot bindings as `link`
+    pub(super) fn relink(
+        &mut self,
+        id: PolicyId,
+        link: &ast::Policy,
+    ) -> Result<(), PolicySetError> {
+        let bindings = SlotBindings {
+            entities: link
+                .env()
+                .iter()
+                .map(|(slot, euid)| (slot.clone().into(), euid.clone().into()))
+                .collect(),
+            values: link
+                .value_env()
+                .iter()
+                .map(|(slot, value)| (slot.clone().into(), RestrictedExpression(value.clone())))
+                .collect(),
+        };
+        let template_id = PolicyId::ref_cast(link.template().id()).clone();
+        match self.link_many(template_id, [(id, bindings)])?.pop() {
+            Some((_, err)) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`EntityLoader`] and [`AsyncEntityLoader`], which
+//! integrators implement to fetch entities on demand, and
+//! [`CachingEntityLoader`], which adds caching to either.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+
+#[cfg(any(feature = "tpe", feature = "entity-manifest"))]
+use {
+    cedar_policy_core::ast,
+    cedar_policy_core::entities::loader::EntityLoader as EntityLoaderInternal, ref_cast::RefCast,
+};
+
+use super::{Entities, Entity, EntityUid};
+
+/// Entity loader trait for batched evaluation.
+///
+/// Loads entities on demand, returning `None` for missing entities.
+/// The `load_entities` function must load all requested entities,
+/// and must compute and include all ancestors of the requested entities.
+/// Loading more entities than requested is allowed.
+///
+/// Wrap a loader in a [`CachingEntityLoader`] to avoid fetching the same
+/// entities repeatedly.
+#[doc = include_str!("../../experimental_warning.md")]
+pub trait EntityLoader {
+    /// Load all entities for the given set of entity UIDs.
+    /// Returns a map from [`EntityUid`] to [`Option<Entity>`], where `None` indicates
+    /// the entity does not exist.
+    fn load_entities(&mut self, uids: &HashSet<EntityUid>) -> HashMap<EntityUid, Option<Entity>>;
+}
+
+/// Asynchronous version of [`EntityLoader`], for loaders which fetch entities
+/// from a remote store.
+///
+/// The same requirements as for [`EntityLoader`] apply to the entities it
+/// loads.
+#[doc = include_str!("../../experimental_warning.md")]
+pub trait AsyncEntityLoader {
+    /// Load all entities for the given set of entity UIDs.
+    /// Resolves to a map from [`EntityUid`] to [`Option<Entity>`], where
+    /// `None` indicates the entity does not exist.
+    fn load_entities(
+        &mut self,
+        uids: &HashSet<EntityUid>,
+    ) -> impl Future<Output = HashMap<EntityUid, Option<Entity>>>;
+}
+
+#[cfg(any(feature = "tpe", feature = "entity-manifest"))]
+/// Wrapper struct used to convert an [`EntityLoader`] to an `EntityLoaderInternal`
+pub(crate) struct EntityLoaderWrapper<'a>(pub(crate) &'a mut dyn EntityLoader);
+
+#[cfg(any(feature = "tpe", feature = "entity-manifest"))]
+impl EntityLoaderInternal for EntityLoaderWrapper<'_> {
+    fn load_entities(
+        &mut self,
+        uids: &HashSet<ast::EntityUID>,
+    ) -> HashMap<ast::EntityUID, Option<ast::Entity>> {
+        from_loaded(self.0.load_entities(&to_uids(uids)))
+    }
+}
+
+#[cfg(any(feature = "tpe", feature = "entity-manifest"))]
+/// Convert internal entity UIDs to the ones passed to an [`EntityLoader`]
+pub(crate) fn to_uids(uids: &HashSet<ast::EntityUID>) -> HashSet<EntityUid> {
+    uids.iter()
+        .map(|id| EntityUid::ref_cast(id).clone())
+        .collect()
+}
+
+#[cfg(any(feature = "tpe", feature = "entity-manifest"))]
+/// Convert entities returned by an [`EntityLoader`] to internal entities
+pub(crate) fn from_loaded(
+    loaded: HashMap<EntityUid, Option<Entity>>,
+) -> HashMap<ast::EntityUID, Option<ast::Entity>> {
+    loaded
+        .into_iter()
+        .map(|(uid, entity)| (uid.0, entity.map(|e| e.0)))
+        .collect()
+}
+
+/// Simple entity loader implementation that loads from a pre-existing Entities store
+#[doc = include_str!("../../experimental_warning.md")]
+#[derive(Debug)]
+pub struct TestEntityLoader<'a> {
+    entities: &'a Entities,
+}
+
+impl<'a> TestEntityLoader<'a> {
+    /// Create a new [`TestEntityLoader`] from an existing Entities store
+    pub fn new(entities: &'a Entities) -> Self {
+        Self { entities }
+    }
+}
+
+impl EntityLoader for TestEntityLoader<'_> {
+    fn load_entities(&mut self, uids: &HashSet<EntityUid>) -> HashMap<EntityUid, Option<Entity>> {
+        uids.iter()
+            .map(|uid| {
+                let entity = self.entities.get(uid).cloned();
+                (uid.clone(), entity)
+            })
+            .collect()
+    }
+}
+
+/// An [`EntityLoader`] or [`AsyncEntityLoader`] which caches the entities
+/// loaded by another, so that each entity is fetched from the inner loader
+/// only once while it stays in the cache.
+///
+/// Each call fetches all the requested entities which aren't cached in a
+/// single call to the inner loader. The cache holds up to `capacity`
+/// entities, evicting the least recently used ones first. By default, it also
+/// remembers which entities don't exist (see
+/// [`CachingEntityLoader::with_negative_caching()`]).
+///
+/// Cached entities are never refreshed: call
+/// [`CachingEntityLoader::invalidate()`] or [`CachingEntityLoader::clear()`]
+/// when the underlying store changes.
+#[doc = include_str!("../../experimental_warning.md")]
+#[derive(Debug)]
+pub struct CachingEntityLoader<L> {
+    inner: L,
+    capacity: usize,
+    negative_caching: bool,
+    /// Cached entities, with the tick at which each was last used
+    cache: HashMap<EntityUid, (u64, Option<Entity>)>,
+    /// Cached entity UIDs by the tick at which they were last used, least
+    /// recently used first
+    recency: BTreeMap<u64, EntityUid>,
+    tick: u64,
+}
+
+impl<L> CachingEntityLoader<L> {
+    /// Cache up to `capacity` of the entities loaded by `inner`
+    pub fn new(inner: L, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            negative_caching: true,
+            cache: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Set whether to cache that an entity doesn't exist (the default), so
+    /// that later requests for it don't reach the inner loader
+    #[must_use]
+    pub fn with_negative_caching(mut self, negative_caching: bool) -> Self {
+        self.negative_caching = negative_caching;
+        self
+    }
+
+    /// Remove `uid` from the cache, so that it is fetched again the next time
+    /// it is requested
+    pub fn invalidate(&mut self, uid: &EntityUid) {
+        if let Some((tick, _)) = self.cache.remove(uid) {
+            self.recency.remove(&tick);
+        }
+    }
+
+    /// Remove all entities from the cache
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.recency.clear();
+    }
+
+    /// The number of entities (including non-existent ones) in the cache
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// The inner loader
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Consume the cache, returning the inner loader
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    /// Look up `uids` in the cache, returning the cached entities and the
+    /// UIDs which must be fetched
+    fn lookup(
+        &mut self,
+        uids: &HashSet<EntityUid>,
+    ) -> (HashMap<EntityUid, Option<Entity>>, HashSet<EntityUid>) {
+        let mut found = HashMap::new();
+        let mut missing = HashSet::new();
+        for uid in uids {
+            let tick = self.next_tick();
+            match self.cache.get_mut(uid) {
+                Some((last_used, entity)) => {
+                    self.recency.remove(last_used);
+                    self.recency.insert(tick, uid.clone());
+                    *last_used = tick;
+                    found.insert(uid.clone(), entity.clone());
+                }
+                None => {
+                    missing.insert(uid.clone());
+                }
+            }
+        }
+        (found, missing)
+    }
+
+    /// Cache the entities fetched for `missing`, adding them to `found`
+    fn store(
+        &mut self,
+        missing: &HashSet<EntityUid>,
+        fetched: HashMap<EntityUid, Option<Entity>>,
+        found: &mut HashMap<EntityUid, Option<Entity>>,
+    ) {
+        // A requested entity which the inner loader didn't return doesn't exist
+        let not_returned = missing
+            .iter()
+            .filter(|uid| !fetched.contains_key(*uid))
+            .map(|uid| (uid.clone(), None))
+            .collect::<Vec<_>>();
+        for (uid, entity) in fetched.into_iter().chain(not_returned) {
+            if entity.is_some() || self.negative_caching {
+                self.insert(uid.clone(), entity.clone());
+            }
+            found.insert(uid, entity);
+        }
+    }
+
+    fn insert(&mut self, uid: EntityUid, entity: Option<Entity>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.invalidate(&uid);
+        while self.cache.len() >= self.capacity {
+            match self.recency.pop_first() {
+                Some((_, lru)) => {
+                    self.cache.remove(&lru);
+                }
+                None => break,
+            }
+        }
+        let tick = self.next_tick();
+        self.recency.insert(tick, uid.clone());
+        self.cache.insert(uid, (tick, entity));
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl<L: EntityLoader> EntityLoader for CachingEntityLoader<L> {
+    fn load_entities(&mut self, uids: &HashSet<EntityUid>) -> HashMap<EntityUid, Option<Entity>> {
+        let (mut found, missing) = self.lookup(uids);
+        if !missing.is_empty() {
+            let fetched = self.inner.load_entities(&missing);
+            self.store(&missing, fetched, &mut found);
+        }
+        found
+    }
+}
+
+impl<L: AsyncEntityLoader> AsyncEntityLoader for CachingEntityLoader<L> {
+    async fn load_entities(
+        &mut self,
+        uids: &HashSet<EntityUid>,
+    ) -> HashMap<EntityUid, Option<Entity>> {
+        let (mut found, missing) = self.lookup(uids);
+        if !missing.is_empty() {
+            let fetched = self.inner.load_entities(&missing).await;
+            self.store(&missing, fetched, &mut found);
+        }
+        found
+    }
+}
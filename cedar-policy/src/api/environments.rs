@@ -0,0 +1,216 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::for_environment()`], which selects the
+//! policies and templates of a policy set that are tagged for an environment
+//! (e.g., `prod` or `staging`) with an annotation such as
+//! `@env("prod, staging")`.
+
+use super::{policy_set_errors::EnvironmentTagError, PolicyId, PolicySet, PolicySetError};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The annotation key used by [`EnvironmentConfig::new()`]
+pub const DEFAULT_ENVIRONMENT_ANNOTATION: &str = "env";
+
+/// How [`PolicySet::for_environment()`] treats policies and templates which
+/// are not tagged with any environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UntaggedPolicies {
+    /// Untagged policies and templates are in every environment
+    #[default]
+    Include,
+    /// Untagged policies and templates are an error
+    Reject,
+}
+
+/// The environments that the policies of a policy set may be tagged with,
+/// and how they are tagged. Used by [`PolicySet::for_environment()`].
+///
+/// A policy or template is tagged with an annotation whose value is a
+/// comma-separated list of environment names, e.g., `@env("prod, staging")`.
+/// Template-linked policies are in the environments of their template.
+#[derive(Debug, Clone)]
+pub struct EnvironmentConfig {
+    environments: BTreeSet<String>,
+    annotation: String,
+    untagged: UntaggedPolicies,
+}
+
+impl EnvironmentConfig {
+    /// Create a configuration for the given environment names, tagged with
+    /// the `@env` annotation, and which includes untagged policies in every
+    /// environment
+    pub fn new(environments: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            environments: environments.into_iter().map(Into::into).collect(),
+            annotation: DEFAULT_ENVIRONMENT_ANNOTATION.into(),
+            untagged: UntaggedPolicies::default(),
+        }
+    }
+
+    /// Set the annotation key that policies are tagged with
+    #[must_use]
+    pub fn with_annotation(mut self, key: impl Into<String>) -> Self {
+        self.annotation = key.into();
+        self
+    }
+
+    /// Set how policies and templates without the annotation are treated
+    #[must_use]
+    pub fn with_untagged(mut self, untagged: UntaggedPolicies) -> Self {
+        self.untagged = untagged;
+        self
+    }
+
+    /// Iterate over the environment names, in sorted order
+    pub fn environments(&self) -> impl Iterator<Item = &str> {
+        self.environments.iter().map(String::as_str)
+    }
+
+    /// The annotation key that policies are tagged with
+    pub fn annotation(&self) -> &str {
+        &self.annotation
+    }
+
+    /// Check the tag `tag` of the policy or template `id`, and return the
+    /// environments it is in, where `None` means every environment
+    fn environments_of<'a>(
+        &self,
+        id: &PolicyId,
+        tag: Option<&'a str>,
+    ) -> Result<Option<BTreeSet<&'a str>>, EnvironmentTagError> {
+        let Some(tag) = tag else {
+            return match self.untagged {
+                UntaggedPolicies::Include => Ok(None),
+                UntaggedPolicies::Reject => Err(EnvironmentTagError::Untagged {
+                    policy_id: id.clone(),
+                    annotation: self.annotation.clone(),
+                }),
+            };
+        };
+        let environments: BTreeSet<_> = tag
+            .split(',')
+            .map(str::trim)
+            .filter(|env| !env.is_empty())
+            .collect();
+        if environments.is_empty() {
+            return Err(EnvironmentTagError::EmptyTag {
+                policy_id: id.clone(),
+            });
+        }
+        if let Some(env) = environments
+            .iter()
+            .find(|env| !self.environments.contains(**env))
+        {
+            return Err(EnvironmentTagError::UnknownTag {
+                policy_id: id.clone(),
+                environment: (*env).into(),
+            });
+        }
+        Ok(Some(environments))
+    }
+}
+
+impl PolicySet {
+    /// Get the policy set for `environment`: the policies and templates of
+    /// this policy set which are tagged with `environment` (or untagged, if
+    /// untagged policies are included), and the links to those templates.
+    /// Metadata, and whether each policy and template is enabled, are kept.
+    ///
+    /// So that no policy is silently dropped, every policy and template is
+    /// checked, even those which are not in `environment`: it is an error for
+    /// one to be tagged with an environment that is not in `config`, tagged
+    /// with no environments, or, if `config` rejects untagged policies, not
+    /// tagged at all.
+    ///
+    /// ```
+    /// # use cedar_policy::{EnvironmentConfig, PolicyId, PolicySet};
+    /// # use std::str::FromStr;
+    /// let policies = PolicySet::from_str(r#"
+    ///     @env("prod, staging") permit(principal, action == Action::"view", resource);
+    ///     @env("staging") permit(principal, action == Action::"debug", resource);
+    ///     forbid(principal, action, resource) when { context.blocked };
+    /// "#).unwrap();
+    /// let config = EnvironmentConfig::new(["prod", "staging"]);
+    /// let prod = policies.for_environment("prod", &config).unwrap();
+    /// assert!(prod.policy(&PolicyId::new("policy0")).is_some());
+    /// assert!(prod.policy(&PolicyId::new("policy1")).is_none());
+    /// assert!(prod.policy(&PolicyId::new("policy2")).is_some());
+    /// ```
+    pub fn for_environment(
+        &self,
+        environment: &str,
+        config: &EnvironmentConfig,
+    ) -> Result<Self, PolicySetError> {
+        if !config.environments.contains(environment) {
+            return Err(EnvironmentTagError::UnknownEnvironment {
+                environment: environment.into(),
+            }
+            .into());
+        }
+        let in_environment = |id: &PolicyId, tag: Option<&str>| {
+            config
+                .environments_of(id, tag)
+                .map(|envs| envs.is_none_or(|envs| envs.contains(environment)))
+        };
+        let mut excluded_templates = Vec::new();
+        for t in self.templates.values() {
+            if !in_environment(t.id(), t.annotation(&config.annotation))? {
+                excluded_templates.push(t.id().clone());
+            }
+        }
+        let mut excluded_links = Vec::new();
+        let mut excluded_policies = Vec::new();
+        for p in self.policies.values() {
+            match p.template_id() {
+                Some(template_id) => {
+                    if excluded_templates.contains(template_id) {
+                        excluded_links.push(p.id().clone());
+                    }
+                }
+                None => {
+                    if !in_environment(p.id(), p.annotation(&config.annotation))? {
+                        excluded_policies.push(p.id().clone());
+                    }
+                }
+            }
+        }
+
+        let mut pset = self.clone();
+        for id in excluded_links {
+            pset.unlink(id)?;
+        }
+        for id in excluded_policies {
+            pset.remove_static(id)?;
+        }
+        for id in excluded_templates {
+            pset.remove_template(id)?;
+        }
+        Ok(pset)
+    }
+
+    /// Get the policy set for each environment in `config`, as
+    /// [`PolicySet::for_environment()`] does, keyed by environment name
+    pub fn for_environments(
+        &self,
+        config: &EnvironmentConfig,
+    ) -> Result<BTreeMap<String, Self>, PolicySetError> {
+        config
+            .environments()
+            .map(|env| Ok((env.to_string(), self.for_environment(env, config)?)))
+            .collect()
+    }
+}
@@ -37,6 +37,7 @@ use cedar_policy_core::validator::entity_manifest::{
     self, PartialExpressionError, PartialRequestError, UnsupportedCedarFeatureError,
 };
 pub use cedar_policy_core::validator::{schema_errors, SchemaError};
+pub use cedar_policy_core::validator::{schema_import_errors, SchemaImportError};
 use cedar_policy_core::{ast, authorizer, est, pst};
 use miette::Diagnostic;
 use ref_cast::RefCast;
@@ -45,6 +46,7 @@ use smol_str::SmolStr;
 use thiserror::Error;
 use to_cedar_syntax_errors::NameCollisionsError;
 use to_cedar_syntax_errors::UnconvertibleEntityTypeShapeError;
+use to_cedar_syntax_errors::UnrepresentableFeaturesError;
 
 #[cfg(feature = "entity-manifest")]
 use super::ValidationResult;
@@ -54,7 +56,28 @@ pub use cedar_policy_core::tpe::err as tpe_err;
 
 /// Errors related to [`crate::Entities`]
 pub mod entities_errors {
+    use crate::DanglingReference;
+    use miette::Diagnostic;
+    use ref_cast::RefCast;
+    use thiserror::Error;
+
     pub use cedar_policy_core::entities::err::{Duplicate, EntitiesError, TransitiveClosureError};
+
+    /// An entity slice contains references to entities that do not exist in
+    /// the slice
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct DanglingReferencesError(
+        #[from] cedar_policy_core::entities::err::DanglingReferencesError,
+    );
+
+    impl DanglingReferencesError {
+        /// All of the dangling references that were found
+        pub fn references(&self) -> impl Iterator<Item = &DanglingReference> {
+            self.0.references().map(DanglingReference::ref_cast)
+        }
+    }
 }
 
 /// Errors related to serializing/deserializing entities or contexts to/from JSON
@@ -73,6 +96,7 @@ pub mod conformance_errors {
         ActionDeclarationMismatch, EntitySchemaConformanceError, ExtensionFunctionLookup,
         InvalidAncestorType, MissingRequiredEntityAttr, TypeMismatch, UndeclaredAction,
         UnexpectedEntityAttr, UnexpectedEntityTag, UnexpectedEntityTypeError,
+        UnsatisfiedConstraint,
     };
 }
 
@@ -189,6 +213,10 @@ pub enum ToCedarSchemaError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     UnconvertibleEntityTypeShape(#[from] to_cedar_syntax_errors::UnconvertibleEntityTypeShapeError),
+    /// Declarations using features that the Cedar schema syntax can't express were found in the schema
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    UnrepresentableFeatures(#[from] to_cedar_syntax_errors::UnrepresentableFeaturesError),
 }
 
 /// Error subtypes for [`ToCedarSchemaError`]
@@ -234,6 +262,23 @@ pub mod to_cedar_syntax_errors {
                 .map(std::string::String::as_str)
         }
     }
+
+    /// Declarations using features that the Cedar schema syntax can't express were found in the schema
+    #[derive(Debug, Error, Diagnostic)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct UnrepresentableFeaturesError {
+        pub(super) err:
+            cedar_policy_core::validator::cedar_schema::fmt::UnrepresentableFeaturesError,
+    }
+
+    impl UnrepresentableFeaturesError {
+        /// Get the names of the common types, entity types and actions using
+        /// features the Cedar schema syntax can't express
+        pub fn names(&self) -> impl Iterator<Item = &str> {
+            self.err.names().map(smol_str::SmolStr::as_str)
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -261,6 +306,7 @@ impl From<cedar_policy_core::validator::cedar_schema::fmt::ToCedarSchemaSyntaxEr
                     .collect(),
                 err,
             }.into(),
+            cedar_policy_core::validator::cedar_schema::fmt::ToCedarSchemaSyntaxError::UnrepresentableFeatures(err) => UnrepresentableFeaturesError { err }.into(),
         }
     }
 }
@@ -294,6 +340,10 @@ pub enum CedarSchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Schema(#[from] SchemaError),
+    /// Error loading or composing the sources imported by a Cedar schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Import(#[from] SchemaImportError),
 }
 
 #[doc(hidden)]
@@ -305,6 +355,7 @@ impl From<cedar_policy_core::validator::CedarSchemaError> for CedarSchemaError {
                 cedar_schema_errors::IoError(e).into()
             }
             cedar_policy_core::validator::CedarSchemaError::Parsing(e) => e.into(),
+            cedar_policy_core::validator::CedarSchemaError::Import(e) => e.into(),
         }
     }
 }
@@ -371,6 +422,11 @@ pub enum ContextCreationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ExpressionConstruction(#[from] ExpressionConstructionError),
+    /// An attribute has values of different types in different layers.
+    /// Only returned by `Context::layered()`
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    LayerTypeConflict(context_creation_errors::LayerTypeConflict),
 }
 
 #[doc(hidden)]
@@ -382,13 +438,14 @@ impl From<ast::ContextCreationError> for ContextCreationError {
             ast::ContextCreationError::ExpressionConstruction(ece) => {
                 Self::ExpressionConstruction(ece)
             }
+            ast::ContextCreationError::LayerTypeConflict(e) => Self::LayerTypeConflict(e),
         }
     }
 }
 
 /// Error subtypes for [`ContextCreationError`]
 mod context_creation_errors {
-    pub use cedar_policy_core::ast::context_creation_errors::NotARecord;
+    pub use cedar_policy_core::ast::context_creation_errors::{LayerTypeConflict, NotARecord};
 }
 
 /// Error subtypes for [`ValidationError`].
@@ -438,6 +495,10 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnsafeTagAccess(#[from] validation_errors::UnsafeTagAccess),
+    /// The typechecker could not conclude that an access to a map key was safe.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnsafeMapAccess(#[from] validation_errors::UnsafeMapAccess),
     /// `.getTag()` on an entity type which cannot have tags according to the schema.
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -481,6 +542,17 @@ pub enum ValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] validation_errors::InvalidEnumEntity),
+    /// A validation warning which the [`crate::ValidatorConfig`] reports as
+    /// an error
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    WarningAsError(#[from] validation_errors::WarningAsError),
+    /// The action scope constraint of a policy includes actions which don't
+    /// declare at least one principal type and one resource type they apply
+    /// to, and the [`crate::ValidatorConfig`] requires fully specified actions
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnspecifiedActionApplication(#[from] validation_errors::UnspecifiedActionApplication),
 }
 
 impl ValidationError {
@@ -495,6 +567,7 @@ impl ValidationError {
             Self::UnsafeAttributeAccess(e) => e.policy_id(),
             Self::UnsafeOptionalAttributeAccess(e) => e.policy_id(),
             Self::UnsafeTagAccess(e) => e.policy_id(),
+            Self::UnsafeMapAccess(e) => e.policy_id(),
             Self::NoTagsAllowed(e) => e.policy_id(),
             Self::UndefinedFunction(e) => e.policy_id(),
             Self::WrongNumberArguments(e) => e.policy_id(),
@@ -505,10 +578,184 @@ impl ValidationError {
             Self::InternalInvariantViolation(e) => e.policy_id(),
             Self::EntityDerefLevelViolation(e) => e.policy_id(),
             Self::InvalidEnumEntity(e) => e.policy_id(),
+            Self::WarningAsError(e) => e.policy_id(),
+            Self::UnspecifiedActionApplication(e) => e.policy_id(),
+        }
+    }
+
+    /// The kind of this error
+    pub fn kind(&self) -> ValidationErrorKind {
+        match self {
+            Self::UnrecognizedEntityType(_) => ValidationErrorKind::UnrecognizedEntityType,
+            Self::UnrecognizedActionId(_) => ValidationErrorKind::UnrecognizedActionId,
+            Self::InvalidActionApplication(_) => ValidationErrorKind::InvalidActionApplication,
+            Self::UnexpectedType(_) => ValidationErrorKind::UnexpectedType,
+            Self::IncompatibleTypes(_) => ValidationErrorKind::IncompatibleTypes,
+            Self::UnsafeAttributeAccess(_) => ValidationErrorKind::UnsafeAttributeAccess,
+            Self::UnsafeOptionalAttributeAccess(_) => {
+                ValidationErrorKind::UnsafeOptionalAttributeAccess
+            }
+            Self::UnsafeTagAccess(_) => ValidationErrorKind::UnsafeTagAccess,
+            Self::UnsafeMapAccess(_) => ValidationErrorKind::UnsafeMapAccess,
+            Self::NoTagsAllowed(_) => ValidationErrorKind::NoTagsAllowed,
+            Self::UndefinedFunction(_) => ValidationErrorKind::UndefinedFunction,
+            Self::WrongNumberArguments(_) => ValidationErrorKind::WrongNumberArguments,
+            Self::FunctionArgumentValidation(_) => ValidationErrorKind::FunctionArgumentValidation,
+            Self::EmptySetForbidden(_) => ValidationErrorKind::EmptySetForbidden,
+            Self::NonLitExtConstructor(_) => ValidationErrorKind::NonLitExtConstructor,
+            Self::HierarchyNotRespected(_) => ValidationErrorKind::HierarchyNotRespected,
+            Self::InternalInvariantViolation(_) => ValidationErrorKind::InternalInvariantViolation,
+            Self::EntityDerefLevelViolation(_) => ValidationErrorKind::EntityDerefLevelViolation,
+            Self::InvalidEnumEntity(_) => ValidationErrorKind::InvalidEnumEntity,
+            Self::WarningAsError(_) => ValidationErrorKind::WarningAsError,
+            Self::UnspecifiedActionApplication(_) => {
+                ValidationErrorKind::UnspecifiedActionApplication
+            }
+        }
+    }
+
+    /// The location in the policy source of the expression where the
+    /// validator found the issue, if available
+    pub fn source_span(&self) -> Option<miette::SourceSpan> {
+        match self {
+            Self::UnrecognizedEntityType(e) => e.source_span(),
+            Self::UnrecognizedActionId(e) => e.source_span(),
+            Self::InvalidActionApplication(e) => e.source_span(),
+            Self::UnexpectedType(e) => e.source_span(),
+            Self::IncompatibleTypes(e) => e.source_span(),
+            Self::UnsafeAttributeAccess(e) => e.source_span(),
+            Self::UnsafeOptionalAttributeAccess(e) => e.source_span(),
+            Self::UnsafeTagAccess(e) => e.source_span(),
+            Self::UnsafeMapAccess(e) => e.source_span(),
+            Self::NoTagsAllowed(e) => e.source_span(),
+            Self::UndefinedFunction(e) => e.source_span(),
+            Self::WrongNumberArguments(e) => e.source_span(),
+            Self::FunctionArgumentValidation(e) => e.source_span(),
+            Self::EmptySetForbidden(e) => e.source_span(),
+            Self::NonLitExtConstructor(e) => e.source_span(),
+            Self::HierarchyNotRespected(e) => e.source_span(),
+            Self::InternalInvariantViolation(e) => e.source_span(),
+            Self::EntityDerefLevelViolation(e) => e.source_span(),
+            Self::InvalidEnumEntity(e) => e.source_span(),
+            Self::WarningAsError(e) => e.source_span(),
+            Self::UnspecifiedActionApplication(e) => e.source_span(),
+        }
+    }
+
+    /// The source text of the expression where the validator found the
+    /// issue, if available
+    pub fn snippet(&self) -> Option<&str> {
+        match self {
+            Self::UnrecognizedEntityType(e) => e.snippet(),
+            Self::UnrecognizedActionId(e) => e.snippet(),
+            Self::InvalidActionApplication(e) => e.snippet(),
+            Self::UnexpectedType(e) => e.snippet(),
+            Self::IncompatibleTypes(e) => e.snippet(),
+            Self::UnsafeAttributeAccess(e) => e.snippet(),
+            Self::UnsafeOptionalAttributeAccess(e) => e.snippet(),
+            Self::UnsafeTagAccess(e) => e.snippet(),
+            Self::UnsafeMapAccess(e) => e.snippet(),
+            Self::NoTagsAllowed(e) => e.snippet(),
+            Self::UndefinedFunction(e) => e.snippet(),
+            Self::WrongNumberArguments(e) => e.snippet(),
+            Self::FunctionArgumentValidation(e) => e.snippet(),
+            Self::EmptySetForbidden(e) => e.snippet(),
+            Self::NonLitExtConstructor(e) => e.snippet(),
+            Self::HierarchyNotRespected(e) => e.snippet(),
+            Self::InternalInvariantViolation(e) => e.snippet(),
+            Self::EntityDerefLevelViolation(e) => e.snippet(),
+            Self::InvalidEnumEntity(e) => e.snippet(),
+            Self::WarningAsError(e) => e.snippet(),
+            Self::UnspecifiedActionApplication(e) => e.snippet(),
         }
     }
 }
 
+/// The kinds of [`ValidationError`], without any error-specific information.
+/// Each kind has a `snake_case` name (e.g., `unexpected_type`), which is
+/// stable and suitable for aggregating or routing errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// See [`ValidationError::UnrecognizedEntityType`]
+    UnrecognizedEntityType,
+    /// See [`ValidationError::UnrecognizedActionId`]
+    UnrecognizedActionId,
+    /// See [`ValidationError::InvalidActionApplication`]
+    InvalidActionApplication,
+    /// See [`ValidationError::UnexpectedType`]
+    UnexpectedType,
+    /// See [`ValidationError::IncompatibleTypes`]
+    IncompatibleTypes,
+    /// See [`ValidationError::UnsafeAttributeAccess`]
+    UnsafeAttributeAccess,
+    /// See [`ValidationError::UnsafeOptionalAttributeAccess`]
+    UnsafeOptionalAttributeAccess,
+    /// See [`ValidationError::UnsafeTagAccess`]
+    UnsafeTagAccess,
+    /// See [`ValidationError::UnsafeMapAccess`]
+    UnsafeMapAccess,
+    /// See [`ValidationError::NoTagsAllowed`]
+    NoTagsAllowed,
+    /// See [`ValidationError::UndefinedFunction`]
+    UndefinedFunction,
+    /// See [`ValidationError::WrongNumberArguments`]
+    WrongNumberArguments,
+    /// See [`ValidationError::FunctionArgumentValidation`]
+    FunctionArgumentValidation,
+    /// See [`ValidationError::EmptySetForbidden`]
+    EmptySetForbidden,
+    /// See [`ValidationError::NonLitExtConstructor`]
+    NonLitExtConstructor,
+    /// See [`ValidationError::HierarchyNotRespected`]
+    HierarchyNotRespected,
+    /// See [`ValidationError::InternalInvariantViolation`]
+    InternalInvariantViolation,
+    /// See [`ValidationError::EntityDerefLevelViolation`]
+    EntityDerefLevelViolation,
+    /// See [`ValidationError::InvalidEnumEntity`]
+    InvalidEnumEntity,
+    /// See [`ValidationError::WarningAsError`]
+    WarningAsError,
+    /// See [`ValidationError::UnspecifiedActionApplication`]
+    UnspecifiedActionApplication,
+}
+
+impl ValidationErrorKind {
+    /// The `snake_case` name of this kind, e.g., `unexpected_type`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UnrecognizedEntityType => "unrecognized_entity_type",
+            Self::UnrecognizedActionId => "unrecognized_action_id",
+            Self::InvalidActionApplication => "invalid_action_application",
+            Self::UnexpectedType => "unexpected_type",
+            Self::IncompatibleTypes => "incompatible_types",
+            Self::UnsafeAttributeAccess => "unsafe_attribute_access",
+            Self::UnsafeOptionalAttributeAccess => "unsafe_optional_attribute_access",
+            Self::UnsafeTagAccess => "unsafe_tag_access",
+            Self::UnsafeMapAccess => "unsafe_map_access",
+            Self::NoTagsAllowed => "no_tags_allowed",
+            Self::UndefinedFunction => "undefined_function",
+            Self::WrongNumberArguments => "wrong_number_arguments",
+            Self::FunctionArgumentValidation => "function_argument_validation",
+            Self::EmptySetForbidden => "empty_set_forbidden",
+            Self::NonLitExtConstructor => "non_lit_ext_constructor",
+            Self::HierarchyNotRespected => "hierarchy_not_respected",
+            Self::InternalInvariantViolation => "internal_invariant_violation",
+            Self::EntityDerefLevelViolation => "entity_deref_level_violation",
+            Self::InvalidEnumEntity => "invalid_enum_entity",
+            Self::WarningAsError => "warning_as_error",
+            Self::UnspecifiedActionApplication => "unspecified_action_application",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[doc(hidden)]
 impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
     fn from(error: cedar_policy_core::validator::ValidationError) -> Self {
@@ -537,6 +784,9 @@ impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
             cedar_policy_core::validator::ValidationError::UnsafeTagAccess(e) => {
                 Self::UnsafeTagAccess(e.into())
             }
+            cedar_policy_core::validator::ValidationError::UnsafeMapAccess(e) => {
+                Self::UnsafeMapAccess(e.into())
+            }
             cedar_policy_core::validator::ValidationError::NoTagsAllowed(e) => {
                 Self::NoTagsAllowed(e.into())
             }
@@ -564,6 +814,12 @@ impl From<cedar_policy_core::validator::ValidationError> for ValidationError {
             cedar_policy_core::validator::ValidationError::EntityDerefLevelViolation(e) => {
                 Self::EntityDerefLevelViolation(e.into())
             }
+            cedar_policy_core::validator::ValidationError::WarningAsError(e) => {
+                Self::WarningAsError(e.into())
+            }
+            cedar_policy_core::validator::ValidationError::UnspecifiedActionApplication(e) => {
+                Self::UnspecifiedActionApplication(e.into())
+            }
         }
     }
 }
@@ -612,6 +868,44 @@ pub enum ValidationWarning {
     #[diagnostic(transparent)]
     #[error(transparent)]
     ImpossiblePolicy(#[from] validation_warnings::ImpossiblePolicy),
+    /// A policy accesses an attribute which is annotated as `@deprecated` in
+    /// the schema, e.g., `@deprecated("use `email` instead")`.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    DeprecatedAttribute(#[from] validation_warnings::DeprecatedAttribute),
+    /// A policy compares an attribute which has a refinement in the schema
+    /// with a value, and the comparison has the same outcome for every value
+    /// the refinement allows, e.g., `resource.port > 70000` where `port` has
+    /// type `Long[0..65535]`.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ConstantComparison(#[from] validation_warnings::ConstantComparison),
+    /// A policy is shadowed by a broader policy with the same effect, so
+    /// removing it doesn't change any authorization decision. Only reported
+    /// if the [`crate::ValidatorConfig`] enables shadowing checks.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ShadowedPolicy(#[from] validation_warnings::ShadowedPolicy),
+    /// A permit policy is nullified by a broader forbid policy, so it never
+    /// grants access. Only reported if the [`crate::ValidatorConfig`] enables
+    /// shadowing checks.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    NullifiedPermit(#[from] validation_warnings::NullifiedPermit),
+    /// A policy uses an entity type which is not declared in the schema.
+    /// Only reported if the [`crate::ValidatorConfig`] allows an incomplete
+    /// schema, instead of the [`ValidationError::UnrecognizedEntityType`]
+    /// error.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AssumedEntityType(#[from] validation_warnings::AssumedEntityType),
+    /// A policy accesses an attribute which is not declared in the schema.
+    /// Only reported if the [`crate::ValidatorConfig`] allows an incomplete
+    /// schema, instead of the [`ValidationError::UnsafeAttributeAccess`]
+    /// error.
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    AssumedAttribute(#[from] validation_warnings::AssumedAttribute),
 }
 
 impl ValidationWarning {
@@ -624,10 +918,76 @@ impl ValidationWarning {
             Self::MixedScriptIdentifier(w) => w.policy_id(),
             Self::ConfusableIdentifier(w) => w.policy_id(),
             Self::ImpossiblePolicy(w) => w.policy_id(),
+            Self::DeprecatedAttribute(w) => w.policy_id(),
+            Self::ConstantComparison(w) => w.policy_id(),
+            Self::ShadowedPolicy(w) => w.policy_id(),
+            Self::NullifiedPermit(w) => w.policy_id(),
+            Self::AssumedEntityType(w) => w.policy_id(),
+            Self::AssumedAttribute(w) => w.policy_id(),
+        }
+    }
+
+    /// The location in the policy source of the expression where the
+    /// validator found the issue, if available
+    pub fn source_span(&self) -> Option<miette::SourceSpan> {
+        match self {
+            Self::MixedScriptString(w) => w.source_span(),
+            Self::BidiCharsInString(w) => w.source_span(),
+            Self::BidiCharsInIdentifier(w) => w.source_span(),
+            Self::MixedScriptIdentifier(w) => w.source_span(),
+            Self::ConfusableIdentifier(w) => w.source_span(),
+            Self::ImpossiblePolicy(w) => w.source_span(),
+            Self::DeprecatedAttribute(w) => w.source_span(),
+            Self::ConstantComparison(w) => w.source_span(),
+            Self::ShadowedPolicy(w) => w.source_span(),
+            Self::NullifiedPermit(w) => w.source_span(),
+            Self::AssumedEntityType(w) => w.source_span(),
+            Self::AssumedAttribute(w) => w.source_span(),
+        }
+    }
+
+    /// The source text of the expression where the validator found the
+    /// issue, if available
+    pub fn snippet(&self) -> Option<&str> {
+        match self {
+            Self::MixedScriptString(w) => w.snippet(),
+            Self::BidiCharsInString(w) => w.snippet(),
+            Self::BidiCharsInIdentifier(w) => w.snippet(),
+            Self::MixedScriptIdentifier(w) => w.snippet(),
+            Self::ConfusableIdentifier(w) => w.snippet(),
+            Self::ImpossiblePolicy(w) => w.snippet(),
+            Self::DeprecatedAttribute(w) => w.snippet(),
+            Self::ConstantComparison(w) => w.snippet(),
+            Self::ShadowedPolicy(w) => w.snippet(),
+            Self::NullifiedPermit(w) => w.snippet(),
+            Self::AssumedEntityType(w) => w.snippet(),
+            Self::AssumedAttribute(w) => w.snippet(),
+        }
+    }
+
+    /// The kind of this warning, which can be used to configure how it is
+    /// reported with a [`crate::ValidatorConfig`], or to allow it for a policy
+    /// with an `@allow_warning` annotation
+    pub fn kind(&self) -> ValidationWarningKind {
+        match self {
+            Self::MixedScriptString(_) => ValidationWarningKind::MixedScriptString,
+            Self::BidiCharsInString(_) => ValidationWarningKind::BidiCharsInString,
+            Self::BidiCharsInIdentifier(_) => ValidationWarningKind::BidiCharsInIdentifier,
+            Self::MixedScriptIdentifier(_) => ValidationWarningKind::MixedScriptIdentifier,
+            Self::ConfusableIdentifier(_) => ValidationWarningKind::ConfusableIdentifier,
+            Self::ImpossiblePolicy(_) => ValidationWarningKind::ImpossiblePolicy,
+            Self::DeprecatedAttribute(_) => ValidationWarningKind::DeprecatedAttribute,
+            Self::ConstantComparison(_) => ValidationWarningKind::ConstantComparison,
+            Self::ShadowedPolicy(_) => ValidationWarningKind::ShadowedPolicy,
+            Self::NullifiedPermit(_) => ValidationWarningKind::NullifiedPermit,
+            Self::AssumedEntityType(_) => ValidationWarningKind::AssumedEntityType,
+            Self::AssumedAttribute(_) => ValidationWarningKind::AssumedAttribute,
         }
     }
 }
 
+pub use cedar_policy_core::validator::{UnknownWarningKindError, ValidationWarningKind};
+
 #[doc(hidden)]
 impl From<cedar_policy_core::validator::ValidationWarning> for ValidationWarning {
     fn from(warning: cedar_policy_core::validator::ValidationWarning) -> Self {
@@ -650,6 +1010,24 @@ impl From<cedar_policy_core::validator::ValidationWarning> for ValidationWarning
             cedar_policy_core::validator::ValidationWarning::ImpossiblePolicy(w) => {
                 Self::ImpossiblePolicy(w.into())
             }
+            cedar_policy_core::validator::ValidationWarning::DeprecatedAttribute(w) => {
+                Self::DeprecatedAttribute(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::ConstantComparison(w) => {
+                Self::ConstantComparison(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::ShadowedPolicy(w) => {
+                Self::ShadowedPolicy(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::NullifiedPermit(w) => {
+                Self::NullifiedPermit(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::AssumedEntityType(w) => {
+                Self::AssumedEntityType(w.into())
+            }
+            cedar_policy_core::validator::ValidationWarning::AssumedAttribute(w) => {
+                Self::AssumedAttribute(w.into())
+            }
         }
     }
 }
@@ -789,6 +1167,129 @@ pub mod policy_set_errors {
         }
     }
 
+    /// Error when merging policy sets which define the same policy or template
+    /// id differently
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("policy sets conflict on {}", .policy_ids.iter().map(|id| format!("`{id}`")).collect::<Vec<_>>().join(", "))]
+    pub struct MergeConflictError {
+        pub(crate) policy_ids: Vec<PolicyId>,
+    }
+
+    impl MergeConflictError {
+        /// Get the ids of the policies and templates which are defined
+        /// differently in the two policy sets
+        pub fn policy_ids(&self) -> impl Iterator<Item = &PolicyId> {
+            self.policy_ids.iter()
+        }
+    }
+
+    /// Error when upgrading a template with [`crate::PolicySet::upgrade_template()`]
+    /// would make some of its existing links invalid
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("cannot upgrade template `{template_id}`: links {} are no longer valid", .invalid_links.iter().map(|(id, _)| format!("`{id}`")).collect::<Vec<_>>().join(", "))]
+    pub struct TemplateUpgradeError {
+        pub(crate) template_id: PolicyId,
+        pub(crate) invalid_links: Vec<(PolicyId, super::PolicySetError)>,
+    }
+
+    impl TemplateUpgradeError {
+        /// Get the id of the template which could not be upgraded
+        pub fn template_id(&self) -> &PolicyId {
+            &self.template_id
+        }
+
+        /// Get the links which are not valid for the new template, with the
+        /// reason each could not be relinked
+        pub fn invalid_links(&self) -> impl Iterator<Item = (&PolicyId, &super::PolicySetError)> {
+            self.invalid_links.iter().map(|(id, err)| (id, err))
+        }
+    }
+
+    /// Error when none of the detached signatures of a policy set can be
+    /// verified
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("none of the {signatures} signatures of the policy set could be verified")]
+    #[diagnostic(help("the policy set may have been modified since it was signed, or signed with an untrusted key"))]
+    pub struct SignatureVerificationError {
+        pub(crate) signatures: usize,
+    }
+
+    impl SignatureVerificationError {
+        /// Get the number of signatures which were checked
+        pub fn signatures(&self) -> usize {
+            self.signatures
+        }
+    }
+
+    /// Error when selecting the policies of a policy set for an environment
+    /// with [`crate::PolicySet::for_environment()`]
+    #[derive(Debug, Diagnostic, Error)]
+    #[non_exhaustive]
+    pub enum EnvironmentTagError {
+        /// The requested environment is not one of the configured environments
+        #[error("unknown environment `{environment}`")]
+        UnknownEnvironment {
+            /// The requested environment
+            environment: String,
+        },
+        /// A policy or template is tagged with an environment that is not one
+        /// of the configured environments
+        #[error("`{policy_id}` is tagged with unknown environment `{environment}`")]
+        UnknownTag {
+            /// The id of the policy or template
+            policy_id: PolicyId,
+            /// The unknown environment
+            environment: String,
+        },
+        /// A policy or template is tagged with no environments
+        #[error("`{policy_id}` is tagged with no environments")]
+        EmptyTag {
+            /// The id of the policy or template
+            policy_id: PolicyId,
+        },
+        /// A policy or template is not tagged, and untagged policies are
+        /// rejected
+        #[error("`{policy_id}` has no `@{annotation}` annotation")]
+        #[diagnostic(help(
+            "every policy and template must be tagged with the environments it belongs to"
+        ))]
+        Untagged {
+            /// The id of the policy or template
+            policy_id: PolicyId,
+            /// The annotation key that policies are tagged with
+            annotation: String,
+        },
+    }
+
+    /// Error when adding a layer to a [`crate::LayeredPolicySet`] which
+    /// already has a layer with the same name
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("duplicate policy layer `{name}`")]
+    pub struct DuplicateLayerError {
+        pub(crate) name: String,
+    }
+
+    impl DuplicateLayerError {
+        /// Get the name of the duplicate layer
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    /// Error when referring to a policy or template that doesn't exist
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("no policy or template with id `{policy_id}`")]
+    pub struct NoSuchPolicyError {
+        pub(crate) policy_id: PolicyId,
+    }
+
+    impl NoSuchPolicyError {
+        /// Get the [`PolicyId`] which does not exist
+        pub fn policy_id(&self) -> &PolicyId {
+            &self.policy_id
+        }
+    }
+
     /// Error when removing a link that is not a link
     #[derive(Debug, Diagnostic, Error)]
     #[error("unable to unlink `{policy_id}` because it is not a link")]
@@ -810,6 +1311,62 @@ pub mod policy_set_errors {
         #[from]
         pub(crate) inner: serde_json::Error,
     }
+
+    /// Error when loading a policy set stored with
+    /// [`crate::PolicySet::store_json()`] or [`crate::PolicySet::store_binary()`]
+    #[derive(Debug, Diagnostic, Error)]
+    #[non_exhaustive]
+    pub enum PersistedFormatError {
+        /// The data was stored in a format version that this version of Cedar
+        /// cannot load
+        #[error("unsupported policy set format version {version}")]
+        #[diagnostic(help(
+            "this version of Cedar loads policy sets stored in format version {supported}"
+        ))]
+        UnsupportedVersion {
+            /// The version the data was stored in
+            version: u64,
+            /// The version this version of Cedar loads
+            supported: u32,
+        },
+        /// The JSON data does not have a `formatVersion`
+        #[error("stored policy set is missing its `formatVersion`")]
+        MissingVersion,
+        /// The binary data does not start with the policy set header
+        #[error("binary data is not a stored policy set")]
+        BadHeader,
+        /// The length of the binary payload does not match the header
+        #[error("stored policy set payload has {actual} bytes, but its header says {expected}")]
+        LengthMismatch {
+            /// The payload length recorded in the header
+            expected: u64,
+            /// The actual payload length
+            actual: u64,
+        },
+    }
+}
+
+/// Errors when updating a [`crate::PolicyStore`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum PolicyStoreError {
+    /// A compare-and-swap update expected a different current version
+    #[error(
+        "expected the policy store to be at version {expected}, but it is at version {actual}"
+    )]
+    #[diagnostic(help("another update was published first; retry against the latest snapshot"))]
+    VersionMismatch {
+        /// The version the update expected
+        expected: u64,
+        /// The current version
+        actual: u64,
+    },
+    /// The requested version is not retained by the store
+    #[error("policy store does not retain version {version}")]
+    NotRetained {
+        /// The requested version
+        version: u64,
+    },
 }
 
 /// Potential errors when adding to a `PolicySet`.
@@ -873,6 +1430,34 @@ pub enum PolicySetError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     PstConversion(#[from] pst::PstConstructionError),
+    /// Error when referring to a policy or template that doesn't exist
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NoSuchPolicy(#[from] policy_set_errors::NoSuchPolicyError),
+    /// Error when merging policy sets which define the same id differently
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MergeConflict(#[from] policy_set_errors::MergeConflictError),
+    /// Error when upgrading a template would invalidate some of its links
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TemplateUpgrade(#[from] policy_set_errors::TemplateUpgradeError),
+    /// Error when verifying the signatures of a policy set
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SignatureVerification(#[from] policy_set_errors::SignatureVerificationError),
+    /// Error when selecting the policies of a policy set for an environment
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EnvironmentTag(#[from] policy_set_errors::EnvironmentTagError),
+    /// Error when adding a layer whose name is already used
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateLayer(#[from] policy_set_errors::DuplicateLayerError),
+    /// Error when loading a stored policy set
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PersistedFormat(#[from] policy_set_errors::PersistedFormatError),
 }
 
 #[doc(hidden)]
@@ -980,6 +1565,13 @@ impl From<pst::PstConstructionError> for PolicyToJsonError {
     }
 }
 
+#[doc(hidden)]
+impl From<entities_json_errors::JsonSerializationError> for PolicyToJsonError {
+    fn from(e: entities_json_errors::JsonSerializationError) -> Self {
+        Self::JsonSerialization(serde_json::Error::custom(e.to_string()).into())
+    }
+}
+
 /// Error subtypes for [`PolicyToJsonError`]
 pub mod policy_to_json_errors {
     use cedar_policy_core::est;
@@ -1141,6 +1733,21 @@ pub enum RequestValidationError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     InvalidEnumEntity(#[from] request_validation_errors::InvalidEnumEntityError),
+    /// A context attribute is not declared in the context type of the request
+    /// action
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UndeclaredContextAttribute(#[from] request_validation_errors::UndeclaredContextAttributeError),
+    /// A context attribute does not have the type declared for it in the
+    /// context type of the request action
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidContextAttribute(#[from] request_validation_errors::InvalidContextAttributeError),
+    /// A context attribute which is required by the context type of the
+    /// request action is missing
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingContextAttribute(#[from] request_validation_errors::MissingContextAttributeError),
 }
 
 #[doc(hidden)]
@@ -1171,6 +1778,15 @@ impl From<cedar_policy_core::validator::RequestValidationError> for RequestValid
             cedar_policy_core::validator::RequestValidationError::InvalidEnumEntity(e) => {
                 Self::InvalidEnumEntity(e.into())
             }
+            cedar_policy_core::validator::RequestValidationError::UndeclaredContextAttribute(e) => {
+                Self::UndeclaredContextAttribute(e.into())
+            }
+            cedar_policy_core::validator::RequestValidationError::InvalidContextAttribute(e) => {
+                Self::InvalidContextAttribute(e.into())
+            }
+            cedar_policy_core::validator::RequestValidationError::MissingContextAttribute(e) => {
+                Self::MissingContextAttribute(e.into())
+            }
         }
     }
 }
@@ -1307,6 +1923,499 @@ pub mod request_validation_errors {
     pub struct InvalidEnumEntityError(
         #[from] cedar_policy_core::entities::conformance::err::InvalidEnumEntityError,
     );
+
+    /// A context attribute is not declared in the context type of the request
+    /// action
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct UndeclaredContextAttributeError(
+        #[from]
+        cedar_policy_core::validator::request_validation_errors::UndeclaredContextAttributeError,
+    );
+
+    impl UndeclaredContextAttributeError {
+        /// The attribute which is not declared
+        pub fn attr(&self) -> &str {
+            self.0.attr()
+        }
+
+        /// The action whose context type does not declare it
+        pub fn action(&self) -> &EntityUid {
+            RefCast::ref_cast(self.0.action())
+        }
+    }
+
+    /// A context attribute does not have the type declared for it in the
+    /// context type of the request action
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct InvalidContextAttributeError(
+        #[from]
+        cedar_policy_core::validator::request_validation_errors::InvalidContextAttributeError,
+    );
+
+    impl InvalidContextAttributeError {
+        /// The attribute whose value is not valid
+        pub fn attr(&self) -> &str {
+            self.0.attr()
+        }
+
+        /// The action it is not valid for
+        pub fn action(&self) -> &EntityUid {
+            RefCast::ref_cast(self.0.action())
+        }
+
+        /// The type declared for the attribute, as a string
+        pub fn expected(&self) -> &str {
+            self.0.expected()
+        }
+    }
+
+    /// A context attribute which is required by the context type of the
+    /// request action is missing
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct MissingContextAttributeError(
+        #[from]
+        cedar_policy_core::validator::request_validation_errors::MissingContextAttributeError,
+    );
+
+    impl MissingContextAttributeError {
+        /// The attribute which is missing
+        pub fn attr(&self) -> &str {
+            self.0.attr()
+        }
+
+        /// The action which requires it
+        pub fn action(&self) -> &EntityUid {
+            RefCast::ref_cast(self.0.action())
+        }
+    }
+}
+
+/// Error when building a [`crate::Request`] with a
+/// [`crate::SchemaRequestBuilder`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum RequestBuilderError {
+    /// The request, or the field being set, does not conform to the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Validation(#[from] RequestValidationError),
+    /// Error evaluating the value given for a context attribute
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Evaluation(#[from] EvaluationError),
+    /// The principal, action, or resource was not set before building the
+    /// request
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingComponent(#[from] request_builder_errors::MissingComponentError),
+    /// Error constructing the request's context
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ContextCreation(#[from] ContextCreationError),
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::RequestValidationError> for RequestBuilderError {
+    fn from(e: cedar_policy_core::validator::RequestValidationError) -> Self {
+        Self::Validation(e.into())
+    }
+}
+
+/// Error when typechecking an expression with
+/// [`crate::typecheck_expression()`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum TypecheckExpressionError {
+    /// The request environment does not conform to the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RequestEnv(#[from] RequestValidationError),
+    /// The expression does not typecheck
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TypeErrors(#[from] typecheck_expression_errors::TypeErrors),
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_core::validator::RequestValidationError> for TypecheckExpressionError {
+    fn from(e: cedar_policy_core::validator::RequestValidationError) -> Self {
+        Self::RequestEnv(e.into())
+    }
+}
+
+/// Error subtypes for [`TypecheckExpressionError`]
+pub mod typecheck_expression_errors {
+    use super::ValidationError;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// The expression does not typecheck
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("expression does not typecheck")]
+    pub struct TypeErrors {
+        #[related]
+        pub(crate) errors: Vec<ValidationError>,
+    }
+
+    impl TypeErrors {
+        /// The errors found typechecking the expression. These are reported
+        /// for the policy ID `expr`.
+        pub fn errors(&self) -> impl Iterator<Item = &ValidationError> {
+            self.errors.iter()
+        }
+    }
+}
+
+/// Error when reading the version a schema fragment declares with a
+/// `@version` annotation
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum SchemaVersionError {
+    /// A version is not of the form `MAJOR.MINOR.PATCH`
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidVersion(#[from] schema_version_errors::InvalidVersionError),
+    /// Namespaces declare different versions
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ConflictingVersions(#[from] schema_version_errors::ConflictingVersionsError),
+}
+
+/// Error subtypes for [`SchemaVersionError`]
+pub mod schema_version_errors {
+    use crate::SchemaVersion;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// A version is not of the form `MAJOR.MINOR.PATCH`
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("invalid schema version `{version}`")]
+    #[diagnostic(help(
+        "versions have the form `MAJOR.MINOR.PATCH`, where each part is a non-negative integer, e.g., `3.2.0`"
+    ))]
+    pub struct InvalidVersionError {
+        pub(crate) version: String,
+    }
+
+    impl InvalidVersionError {
+        /// The invalid version
+        pub fn version(&self) -> &str {
+            &self.version
+        }
+    }
+
+    /// Namespaces declare different versions
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("namespace `{}` declares version `{}`, but namespace `{}` declares version `{}`", .namespaces.0, .versions.0, .namespaces.1, .versions.1)]
+    pub struct ConflictingVersionsError {
+        pub(crate) namespaces: (String, String),
+        pub(crate) versions: (SchemaVersion, SchemaVersion),
+    }
+
+    impl ConflictingVersionsError {
+        /// The two namespaces which declare different versions
+        pub fn namespaces(&self) -> (&str, &str) {
+            (&self.namespaces.0, &self.namespaces.1)
+        }
+
+        /// The versions they declare
+        pub fn versions(&self) -> (SchemaVersion, SchemaVersion) {
+            self.versions
+        }
+    }
+}
+
+/// Error when residuals can't be translated into a filter by a
+/// [`crate::FilterTranslator`]
+#[derive(Debug, Diagnostic, Error)]
+#[error("cannot translate {} residual expression(s) into a filter", .untranslatable.len())]
+#[diagnostic(help("{}", .untranslatable.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")))]
+pub struct FilterTranslationError {
+    pub(crate) untranslatable: Vec<crate::UntranslatableExpr>,
+}
+
+impl FilterTranslationError {
+    /// The residual expressions which could not be translated
+    pub fn untranslatable(&self) -> impl Iterator<Item = &crate::UntranslatableExpr> {
+        self.untranslatable.iter()
+    }
+}
+
+/// Error when enumerating the requests of a [`crate::RequestSpace`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum RequestSpaceError {
+    /// The context type of an action has infinitely many values
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnboundedContext(#[from] request_space_errors::UnboundedContextError),
+    /// A context given for an action does not conform to the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidContext(#[from] RequestValidationError),
+}
+
+/// Error subtypes for [`RequestSpaceError`]
+pub mod request_space_errors {
+    use crate::{DeclaredType, EntityUid};
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// The context type of an action has an attribute with infinitely many
+    /// values, so its contexts can't be enumerated
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("cannot enumerate the contexts of `{action}`: attribute `{}` has type `{ty}`, which has infinitely many values", .path.join("."))]
+    #[diagnostic(help("provide the contexts to consider with `RequestSpace::with_contexts()`"))]
+    pub struct UnboundedContextError {
+        pub(crate) action: EntityUid,
+        pub(crate) path: Vec<String>,
+        pub(crate) ty: DeclaredType,
+    }
+
+    impl UnboundedContextError {
+        /// The action whose contexts can't be enumerated
+        pub fn action(&self) -> &EntityUid {
+            &self.action
+        }
+
+        /// The path to the attribute, from the root of the context
+        pub fn path(&self) -> impl Iterator<Item = &str> {
+            self.path.iter().map(String::as_str)
+        }
+
+        /// The type of the attribute
+        pub fn ty(&self) -> &DeclaredType {
+            &self.ty
+        }
+    }
+}
+
+/// Error when migrating entity data with a [`crate::SchemaMigration`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum SchemaMigrationError {
+    /// The entity data is not in the Cedar JSON entity format
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEntity(#[from] schema_migration_errors::InvalidEntityError),
+    /// Converting the value of an attribute failed
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Conversion(#[from] schema_migration_errors::ConversionError),
+    /// There is no migration to upgrade entity data from a version
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingMigration(#[from] schema_migration_errors::MissingMigrationError),
+}
+
+/// Error subtypes for [`SchemaMigrationError`]
+pub mod schema_migration_errors {
+    use crate::{EntityUid, SchemaVersion};
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// The entity data is not in the Cedar JSON entity format
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("invalid entity data: {reason}")]
+    pub struct InvalidEntityError {
+        pub(crate) reason: String,
+    }
+
+    /// Converting the value of an attribute failed
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("failed to convert attribute `{attr}` of `{uid}`: {reason}")]
+    pub struct ConversionError {
+        pub(crate) uid: EntityUid,
+        pub(crate) attr: String,
+        pub(crate) reason: String,
+    }
+
+    impl ConversionError {
+        /// The entity whose attribute could not be converted
+        pub fn uid(&self) -> &EntityUid {
+            &self.uid
+        }
+
+        /// The attribute which could not be converted
+        pub fn attr(&self) -> &str {
+            &self.attr
+        }
+    }
+
+    /// There is no migration to upgrade entity data from a version
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("no migration upgrades entity data from schema version `{from}` towards `{to}`")]
+    pub struct MissingMigrationError {
+        pub(crate) from: SchemaVersion,
+        pub(crate) to: SchemaVersion,
+    }
+
+    impl MissingMigrationError {
+        /// The version with no migration from it
+        pub fn source_version(&self) -> SchemaVersion {
+            self.from
+        }
+
+        /// The version the entity data was being upgraded to
+        pub fn target_version(&self) -> SchemaVersion {
+            self.to
+        }
+    }
+}
+
+/// Error when registering a [`crate::CustomExtension`]
+#[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
+pub enum CustomExtensionError {
+    /// The name of the extension or one of its functions is not a valid
+    /// Cedar name
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidName(#[from] custom_extension_errors::InvalidNameError),
+    /// Two functions in the extension have the same name
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DuplicateFunction(#[from] custom_extension_errors::DuplicateFunctionError),
+    /// A function takes or returns a type that custom extension functions
+    /// can't use
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnsupportedType(#[from] custom_extension_errors::UnsupportedTypeError),
+    /// The extension conflicts with existing extensions, or was registered
+    /// too late
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Registration(#[from] custom_extension_errors::RegistrationError),
+    /// A region of the `regions` extension was declared with a string which
+    /// isn't a country code
+    #[cfg(feature = "regions")]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidCountryCode(#[from] custom_extension_errors::InvalidCountryCodeError),
+}
+
+/// Error subtypes for [`CustomExtensionError`]
+pub mod custom_extension_errors {
+    use crate::DeclaredType;
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// The name of the extension or one of its functions is not a valid Cedar
+    /// name
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("`{name}` is not a valid name for a custom extension or function")]
+    #[diagnostic(help(
+        "names are identifiers, optionally qualified by a namespace, e.g., `geo::distance`"
+    ))]
+    pub struct InvalidNameError {
+        pub(crate) name: String,
+    }
+
+    impl InvalidNameError {
+        /// The invalid name
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    /// Two functions in the extension have the same name
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("custom extension function `{name}` is defined multiple times")]
+    pub struct DuplicateFunctionError {
+        pub(crate) name: String,
+    }
+
+    impl DuplicateFunctionError {
+        /// The name of the function
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    /// A function takes or returns a type that custom extension functions
+    /// can't use
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("custom extension function `{function}` cannot take or return values of type `{ty}`")]
+    #[diagnostic(help(
+        "custom extension functions may only use `Bool`, `Long`, `String`, entity types, builtin extension types, and sets of these"
+    ))]
+    pub struct UnsupportedTypeError {
+        pub(crate) function: String,
+        pub(crate) ty: DeclaredType,
+    }
+
+    impl UnsupportedTypeError {
+        /// The name of the function
+        pub fn function(&self) -> &str {
+            &self.function
+        }
+
+        /// The unsupported type
+        pub fn ty(&self) -> &DeclaredType {
+            &self.ty
+        }
+    }
+
+    /// The extension conflicts with existing extensions, or was registered
+    /// after extensions were first used
+    #[derive(Debug, Diagnostic, Error)]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    pub struct RegistrationError(
+        #[from] pub(crate) cedar_policy_core::extensions::CustomExtensionError,
+    );
+
+    /// A region of the `regions` extension was declared with a string which
+    /// isn't a country code
+    #[cfg(feature = "regions")]
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("region `{region}` contains `{country_code}`, which is not an ISO 3166-1 alpha-2 country code")]
+    #[diagnostic(help("country codes are two letters, e.g., `FR`"))]
+    pub struct InvalidCountryCodeError {
+        pub(crate) region: String,
+        pub(crate) country_code: String,
+    }
+
+    #[cfg(feature = "regions")]
+    impl InvalidCountryCodeError {
+        /// The name of the region
+        pub fn region(&self) -> &str {
+            &self.region
+        }
+
+        /// The invalid country code
+        pub fn country_code(&self) -> &str {
+            &self.country_code
+        }
+    }
+}
+
+/// Error subtypes for [`RequestBuilderError`]
+pub mod request_builder_errors {
+    use miette::Diagnostic;
+    use thiserror::Error;
+
+    /// The principal, action, or resource was not set before building the
+    /// request
+    #[derive(Debug, Diagnostic, Error)]
+    #[error("the request {component} was not set")]
+    pub struct MissingComponentError {
+        pub(crate) component: &'static str,
+    }
+
+    impl MissingComponentError {
+        /// The component which was not set: `principal`, `action`, or
+        /// `resource`
+        pub fn component(&self) -> &str {
+            self.component
+        }
+    }
 }
 
 /// An error generated by entity slicing.
@@ -19,6 +19,7 @@ use ref_cast::RefCast;
 use thiserror::Error;
 
 use crate::PolicyId;
+use cedar_policy_core::validator::validation_errors::EntityDerefViolationKind;
 
 // Required for doc link to `ValidationError` without qualifying it with
 // `crate`, but not used otherwise, so non-doc builds warned about unused
@@ -45,6 +46,18 @@ macro_rules! wrap_core_error {
             pub fn policy_id(&self) -> &PolicyId {
                 PolicyId::ref_cast(&self.0.policy_id)
             }
+
+            /// The location in the policy source of the expression where this
+            /// error was found, if available
+            pub fn source_span(&self) -> Option<miette::SourceSpan> {
+                self.0.source_loc.as_ref().map(|loc| loc.span)
+            }
+
+            /// The source text of the expression where this error was found,
+            /// if available
+            pub fn snippet(&self) -> Option<&str> {
+                self.0.source_loc.as_ref().and_then(|loc| loc.snippet())
+            }
         }
 
         #[doc(hidden)]
@@ -64,6 +77,7 @@ wrap_core_error!(IncompatibleTypes);
 wrap_core_error!(UnsafeAttributeAccess);
 wrap_core_error!(UnsafeOptionalAttributeAccess);
 wrap_core_error!(UnsafeTagAccess);
+wrap_core_error!(UnsafeMapAccess);
 wrap_core_error!(NoTagsAllowed);
 wrap_core_error!(UndefinedFunction);
 wrap_core_error!(WrongNumberArguments);
@@ -74,3 +88,74 @@ wrap_core_error!(EmptySetForbidden);
 wrap_core_error!(NonLitExtConstructor);
 wrap_core_error!(InternalInvariantViolation);
 wrap_core_error!(InvalidEnumEntity);
+wrap_core_error!(WarningAsError);
+wrap_core_error!(UnspecifiedActionApplication);
+
+impl UnexpectedType {
+    /// The types which were expected, one of which the expression should
+    /// have had
+    pub fn expected_types(&self) -> impl Iterator<Item = String> + '_ {
+        self.0.expected.iter().map(ToString::to_string)
+    }
+
+    /// The type which the expression had
+    pub fn actual_type(&self) -> String {
+        self.0.actual.to_string()
+    }
+}
+
+impl IncompatibleTypes {
+    /// The types which are incompatible with each other
+    pub fn types(&self) -> impl Iterator<Item = String> + '_ {
+        self.0.types.iter().map(ToString::to_string)
+    }
+}
+
+impl EntityDerefLevelViolation {
+    /// The maximum level allowed for the policy, if the policy exceeded it
+    pub fn allowed_level(&self) -> Option<u32> {
+        match &self.0.violation_kind {
+            EntityDerefViolationKind::MaximumLevelExceeded { allowed_level, .. } => {
+                Some(allowed_level.level())
+            }
+            _ => None,
+        }
+    }
+
+    /// The level required by the access chain, if the policy exceeded the
+    /// maximum allowed level
+    pub fn actual_level(&self) -> Option<u32> {
+        match &self.0.violation_kind {
+            EntityDerefViolationKind::MaximumLevelExceeded { actual_level, .. } => {
+                Some(actual_level.level())
+            }
+            _ => None,
+        }
+    }
+
+    /// The chain of entity dereferences which exceeds the maximum allowed
+    /// level (e.g., `principal.manager.manager.name`), if the policy exceeded it
+    pub fn access_chain(&self) -> Option<&str> {
+        match &self.0.violation_kind {
+            EntityDerefViolationKind::MaximumLevelExceeded { access_chain, .. } => {
+                Some(access_chain.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl WarningAsError {
+    /// The warning which was reported as an error
+    pub fn warning(&self) -> crate::ValidationWarning {
+        (*self.0.warning).clone().into()
+    }
+}
+
+impl UnspecifiedActionApplication {
+    /// The actions in the scope of the policy which are not fully specified,
+    /// in sorted order
+    pub fn actions(&self) -> impl Iterator<Item = &crate::EntityUid> {
+        self.0.actions.iter().map(crate::EntityUid::ref_cast)
+    }
+}
@@ -45,6 +45,18 @@ macro_rules! wrap_core_warning {
             pub fn policy_id(&self) -> &PolicyId {
                 PolicyId::ref_cast(&self.0.policy_id)
             }
+
+            /// The location in the policy source of the expression where this
+            /// warning was found, if available
+            pub fn source_span(&self) -> Option<miette::SourceSpan> {
+                self.0.source_loc.as_ref().map(|loc| loc.span)
+            }
+
+            /// The source text of the expression where this warning was found,
+            /// if available
+            pub fn snippet(&self) -> Option<&str> {
+                self.0.source_loc.as_ref().and_then(|loc| loc.snippet())
+            }
         }
 
         #[doc(hidden)]
@@ -62,3 +74,76 @@ wrap_core_warning!(BidiCharsInIdentifier);
 wrap_core_warning!(MixedScriptIdentifier);
 wrap_core_warning!(ConfusableIdentifier);
 wrap_core_warning!(ImpossiblePolicy);
+wrap_core_warning!(DeprecatedAttribute);
+wrap_core_warning!(ConstantComparison);
+wrap_core_warning!(ShadowedPolicy);
+wrap_core_warning!(NullifiedPermit);
+wrap_core_warning!(AssumedEntityType);
+wrap_core_warning!(AssumedAttribute);
+
+impl DeprecatedAttribute {
+    /// The name of the deprecated attribute
+    pub fn attr(&self) -> &str {
+        &self.0.attr
+    }
+
+    /// The reason given in the attribute's `@deprecated` annotation, if any
+    pub fn reason(&self) -> Option<&str> {
+        self.0.reason.as_deref()
+    }
+}
+
+impl ConstantComparison {
+    /// The name of the refined attribute
+    pub fn attr(&self) -> &str {
+        &self.0.attr
+    }
+
+    /// The refinement of the attribute, as written in the Cedar schema syntax,
+    /// e.g., `[0..65535]`
+    pub fn refinement(&self) -> &str {
+        &self.0.refinement
+    }
+}
+
+impl ShadowedPolicy {
+    /// The broader policy with the same effect which shadows this policy
+    pub fn shadowing_policy_id(&self) -> &PolicyId {
+        PolicyId::ref_cast(&self.0.shadowing_policy_id)
+    }
+
+    /// The scope of the shadowed policy, which the policies share, e.g.,
+    /// `principal == User::"alice", action == Action::"view", resource`
+    pub fn scope(&self) -> &str {
+        &self.0.scope
+    }
+}
+
+impl NullifiedPermit {
+    /// The broader forbid policy which nullifies this permit policy
+    pub fn forbid_policy_id(&self) -> &PolicyId {
+        PolicyId::ref_cast(&self.0.forbid_policy_id)
+    }
+
+    /// The scope of the permit policy, which the policies share, e.g.,
+    /// `principal == User::"alice", action == Action::"view", resource`
+    pub fn scope(&self) -> &str {
+        &self.0.scope
+    }
+}
+
+impl AssumedEntityType {
+    /// The entity type which is not declared in the schema
+    pub fn entity_type(&self) -> &str {
+        &self.0.entity_type
+    }
+}
+
+impl AssumedAttribute {
+    /// The attribute which is not declared in the schema, and the entity
+    /// type or action context it was accessed on, e.g.,
+    /// `` `department` on entity type `User` ``
+    pub fn attribute(&self) -> String {
+        self.0.attribute_access.to_string()
+    }
+}
@@ -0,0 +1,411 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`Authorizer::explain()`], which explains an
+//! authorization decision as a structured [`Explanation`]: which policies
+//! determined the decision, and, for every other policy, which scope
+//! constraint or condition it failed and the values that made it fail. It is
+//! meant for rendering human-readable explanations, e.g., "access was denied
+//! because `principal.age >= 18` was false (`principal.age` is 16)".
+
+use super::coverage::clauses;
+use super::{Authorizer, ClauseKind, Decision, Effect, Entities, EvalResult, PolicyId};
+use super::{PolicySet, Request};
+use cedar_policy_core::ast::{self, BinaryOp, Expr, ExprKind, Value};
+use cedar_policy_core::evaluator::Evaluator;
+use cedar_policy_core::parser::Loc;
+use itertools::Itertools;
+use std::fmt::{self, Display};
+
+/// Why an authorization decision was reached. Returned by
+/// [`Explanation::reason()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionReason {
+    /// The request was allowed, because these `permit` policies were
+    /// satisfied and no `forbid` policy was
+    Permitted {
+        /// The satisfied `permit` policies
+        permits: Vec<PolicyId>,
+    },
+    /// The request was denied, because these `forbid` policies were
+    /// satisfied. They override any satisfied `permit` policies.
+    Forbidden {
+        /// The satisfied `forbid` policies
+        forbids: Vec<PolicyId>,
+        /// The satisfied `permit` policies which were overridden
+        overridden_permits: Vec<PolicyId>,
+    },
+    /// The request was denied, because no `permit` policy was satisfied
+    NoPermit,
+}
+
+/// A value which a clause of a policy depended on: a variable, or an attribute
+/// or tag access, and what it evaluated to for the request. Returned by
+/// [`ClauseExplanation::operands()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperandValue {
+    expr: String,
+    value: EvalResult,
+}
+
+impl OperandValue {
+    /// The operand, as written in the policy if it was parsed from Cedar
+    /// syntax, e.g., `principal.age`
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// The value of the operand
+    pub fn value(&self) -> &EvalResult {
+        &self.value
+    }
+}
+
+/// A clause of a policy which was not satisfied by a request, or whose
+/// evaluation errored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClauseExplanation {
+    kind: ClauseKind,
+    expr: String,
+    operands: Vec<OperandValue>,
+}
+
+impl ClauseExplanation {
+    fn new(kind: ClauseKind, expr: &Expr, eval: &Evaluator<'_>, policy: &ast::Policy) -> Self {
+        Self {
+            kind,
+            expr: source_text(expr),
+            operands: operands(expr)
+                .into_iter()
+                .filter_map(|operand| {
+                    let value = eval.interpret(operand, policy.env()).ok()?;
+                    Some(OperandValue {
+                        expr: source_text(operand),
+                        value: value.into(),
+                    })
+                })
+                .unique_by(|operand| operand.expr.clone())
+                .collect(),
+        }
+    }
+
+    /// The part of the policy this clause is
+    pub fn kind(&self) -> ClauseKind {
+        self.kind
+    }
+
+    /// The clause, as written in the policy if it was parsed from Cedar
+    /// syntax (e.g., `unless { context.mfa }`), or otherwise as a Cedar
+    /// expression
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// The values of the variables, attributes, and tags the clause refers to,
+    /// in the order they appear in the clause. Operands which could not be
+    /// evaluated (e.g., missing attributes) are omitted.
+    pub fn operands(&self) -> impl Iterator<Item = &OperandValue> {
+        self.operands.iter()
+    }
+}
+
+impl Display for ClauseExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ClauseKind::Principal => "principal constraint",
+            ClauseKind::Action => "action constraint",
+            ClauseKind::Resource => "resource constraint",
+            ClauseKind::Condition => "condition",
+        };
+        write!(f, "{kind} `{}`", self.expr)?;
+        if !self.operands.is_empty() {
+            let operands = self
+                .operands
+                .iter()
+                .map(|operand| format!("`{}` is {}", operand.expr, operand.value))
+                .join(", ");
+            write!(f, " ({operands})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of evaluating one policy for a request. Returned by
+/// [`PolicyExplanation::outcome()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyOutcome {
+    /// The policy was satisfied
+    Satisfied,
+    /// A scope constraint of the policy was not satisfied
+    ScopeNotSatisfied(ClauseExplanation),
+    /// The scope of the policy was satisfied, but one of its conditions was
+    /// not
+    ConditionNotSatisfied(ClauseExplanation),
+    /// Evaluating a clause of the policy errored
+    Error {
+        /// The clause whose evaluation errored
+        clause: ClauseExplanation,
+        /// The error message
+        message: String,
+    },
+    /// The policy is disabled (see [`PolicySet::set_enabled()`]), so it was
+    /// not evaluated
+    Disabled,
+}
+
+/// How one policy contributed to an authorization decision. Returned by
+/// [`Explanation::policies()`] and [`Explanation::policy()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyExplanation {
+    id: PolicyId,
+    effect: Effect,
+    outcome: PolicyOutcome,
+}
+
+impl PolicyExplanation {
+    /// Evaluate the clauses of `policy` in order, as during authorization,
+    /// stopping at the first which is not satisfied
+    fn new(eval: &Evaluator<'_>, pset: &ast::PolicySet, policy: &ast::Policy) -> Self {
+        let outcome = if pset.is_enabled(policy.id()) && pset.is_enabled(policy.template().id()) {
+            clauses(policy)
+                .into_iter()
+                .find_map(|(kind, expr)| {
+                    let clause = || ClauseExplanation::new(kind, &expr, eval, policy);
+                    match eval.interpret(&expr, policy.env()) {
+                        Ok(v) if v == Value::from(true) => None,
+                        Ok(_) if kind == ClauseKind::Condition => {
+                            Some(PolicyOutcome::ConditionNotSatisfied(clause()))
+                        }
+                        Ok(_) => Some(PolicyOutcome::ScopeNotSatisfied(clause())),
+                        Err(e) => Some(PolicyOutcome::Error {
+                            clause: clause(),
+                            message: e.to_string(),
+                        }),
+                    }
+                })
+                .unwrap_or(PolicyOutcome::Satisfied)
+        } else {
+            PolicyOutcome::Disabled
+        };
+        Self {
+            id: PolicyId::new(policy.id().clone()),
+            effect: policy.effect(),
+            outcome,
+        }
+    }
+
+    /// The id of the policy
+    pub fn id(&self) -> &PolicyId {
+        &self.id
+    }
+
+    /// The effect of the policy
+    pub fn effect(&self) -> Effect {
+        self.effect
+    }
+
+    /// The outcome of evaluating the policy
+    pub fn outcome(&self) -> &PolicyOutcome {
+        &self.outcome
+    }
+}
+
+impl Display for PolicyExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): ", self.id, self.effect)?;
+        match &self.outcome {
+            PolicyOutcome::Satisfied => write!(f, "satisfied"),
+            PolicyOutcome::ScopeNotSatisfied(clause)
+            | PolicyOutcome::ConditionNotSatisfied(clause) => {
+                write!(f, "{clause} was not satisfied")
+            }
+            PolicyOutcome::Error { clause, message } => write!(f, "{clause} errored: {message}"),
+            PolicyOutcome::Disabled => write!(f, "disabled"),
+        }
+    }
+}
+
+/// A structured explanation of an authorization decision. Returned by
+/// [`Authorizer::explain()`].
+///
+/// The [`Display`] implementation renders the explanation as English text,
+/// with a summary line followed by one line for each policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    decision: Decision,
+    reason: DecisionReason,
+    policies: Vec<PolicyExplanation>,
+}
+
+impl Explanation {
+    /// The authorization decision, which is the same as
+    /// [`Authorizer::is_authorized()`] returns
+    pub fn decision(&self) -> Decision {
+        self.decision
+    }
+
+    /// Why the decision was reached
+    pub fn reason(&self) -> &DecisionReason {
+        &self.reason
+    }
+
+    /// Iterate over the explanations of each static and template-linked
+    /// policy, in the order of the policy set
+    pub fn policies(&self) -> impl Iterator<Item = &PolicyExplanation> {
+        self.policies.iter()
+    }
+
+    /// Get the explanation of the policy with id `id`
+    pub fn policy(&self, id: &PolicyId) -> Option<&PolicyExplanation> {
+        self.policies.iter().find(|p| &p.id == id)
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            DecisionReason::Permitted { permits } => {
+                writeln!(f, "Allow: permitted by {}", permits.iter().join(", "))?;
+            }
+            DecisionReason::Forbidden {
+                forbids,
+                overridden_permits,
+            } => {
+                write!(f, "Deny: forbidden by {}", forbids.iter().join(", "))?;
+                if !overridden_permits.is_empty() {
+                    write!(f, ", overriding {}", overridden_permits.iter().join(", "))?;
+                }
+                writeln!(f)?;
+            }
+            DecisionReason::NoPermit => writeln!(f, "Deny: no permit policy was satisfied")?,
+        }
+        for policy in &self.policies {
+            writeln!(f, "  {policy}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Authorizer {
+    /// Authorize `request` like [`Authorizer::is_authorized()`], and explain
+    /// the decision: which policies determined it (including the `permit`
+    /// policies overridden by `forbid` policies), and for each static and
+    /// template-linked policy, whether it was satisfied, and if not, the first
+    /// scope constraint or condition (a top-level conjunct of its `when` and
+    /// `unless` clauses) it failed, with the values of the variables and
+    /// attributes that clause refers to.
+    ///
+    /// ```
+    /// # use cedar_policy::{Authorizer, Context, Entities, EntityUid, PolicyId, PolicyOutcome, PolicySet, Request, RestrictedExpression};
+    /// # use std::str::FromStr;
+    /// let policies = PolicySet::from_str(r#"
+    ///     permit(principal, action == Action::"view", resource) when { context.age >= 18 };
+    /// "#).unwrap();
+    /// let request = Request::new(
+    ///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+    ///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+    ///     EntityUid::from_str(r#"Photo::"a""#).unwrap(),
+    ///     Context::from_pairs([("age".into(), RestrictedExpression::new_long(16))]).unwrap(),
+    ///     None,
+    /// ).unwrap();
+    /// let explanation = Authorizer::new().explain(&request, &policies, &Entities::empty());
+    /// let policy = explanation.policy(&PolicyId::new("policy0")).unwrap();
+    /// let PolicyOutcome::ConditionNotSatisfied(clause) = policy.outcome() else { panic!() };
+    /// assert_eq!(clause.to_string(), "condition `context.age >= 18` (`context.age` is 16)");
+    /// ```
+    pub fn explain(
+        &self,
+        request: &Request,
+        policies: &PolicySet,
+        entities: &Entities,
+    ) -> Explanation {
+        let response = self.is_authorized(request, policies, entities);
+        let eval = Evaluator::new(
+            self.0.environment().prepare_request(request.0.clone()),
+            &entities.0,
+            self.0.extensions(),
+        );
+        let explanations: Vec<_> = policies
+            .ast
+            .policies()
+            .map(|policy| PolicyExplanation::new(&eval, &policies.ast, policy))
+            .collect();
+        let determining: Vec<_> = explanations
+            .iter()
+            .filter(|p| response.diagnostics().reason().contains(&p.id))
+            .map(|p| p.id.clone())
+            .collect();
+        let reason = match response.decision() {
+            Decision::Allow => DecisionReason::Permitted {
+                permits: determining,
+            },
+            Decision::Deny if determining.is_empty() => DecisionReason::NoPermit,
+            Decision::Deny => DecisionReason::Forbidden {
+                forbids: determining,
+                overridden_permits: explanations
+                    .iter()
+                    .filter(|p| p.effect == Effect::Permit && p.outcome == PolicyOutcome::Satisfied)
+                    .map(|p| p.id.clone())
+                    .collect(),
+            },
+        };
+        Explanation {
+            decision: response.decision(),
+            reason,
+            policies: explanations,
+        }
+    }
+}
+
+/// The source text of `expr`, or its rendering as a Cedar expression if it
+/// has no source location
+fn source_text(expr: &Expr) -> String {
+    expr.source_loc()
+        .and_then(Loc::snippet)
+        .map_or_else(|| expr.to_string(), str::to_owned)
+}
+
+/// The variables, slots, and attribute and tag accesses in `expr`, except
+/// those which are only the object of another attribute or tag access (so
+/// `principal.manager.name` has only itself as an operand)
+fn operands(expr: &Expr) -> Vec<&Expr> {
+    let accessed: Vec<&Expr> = expr
+        .subexpressions()
+        .filter_map(|e| match e.expr_kind() {
+            ExprKind::GetAttr { expr, .. }
+            | ExprKind::BinaryApp {
+                op: BinaryOp::GetTag,
+                arg1: expr,
+                ..
+            } => Some(expr.as_ref()),
+            _ => None,
+        })
+        .collect();
+    expr.subexpressions()
+        .filter(|e| {
+            matches!(
+                e.expr_kind(),
+                ExprKind::Var(_)
+                    | ExprKind::Slot(_)
+                    | ExprKind::GetAttr { .. }
+                    | ExprKind::BinaryApp {
+                        op: BinaryOp::GetTag,
+                        ..
+                    }
+            ) && !accessed.iter().any(|a| std::ptr::eq(*a, *e))
+        })
+        .collect()
+}
@@ -0,0 +1,902 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`FilterTranslator`], which translates the residual
+//! policies of partial evaluation over one unknown request variable (e.g.,
+//! `resource`) into a filter predicate for an external data store, through a
+//! [`FilterBackend`]. [`SqlFilter`] is a reference backend producing SQL
+//! `WHERE` clauses.
+
+use super::{pst, Effect, FilterTranslationError, Policy};
+use pst::{BinaryOp, EntityType, EntityUID, Expr, Literal, PatternElem, UnaryOp, Var};
+use smol_str::SmolStr;
+use std::collections::HashMap;
+
+/// A comparison between an attribute and a literal, used by
+/// [`FilterBackend::compare()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    /// `==`
+    Eq,
+    /// `!=`
+    NotEq,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEq,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEq,
+}
+
+impl CompareOp {
+    fn from_pst(op: BinaryOp) -> Option<Self> {
+        match op {
+            BinaryOp::Eq => Some(Self::Eq),
+            BinaryOp::NotEq => Some(Self::NotEq),
+            BinaryOp::Less => Some(Self::Less),
+            BinaryOp::LessEq => Some(Self::LessEq),
+            BinaryOp::Greater => Some(Self::Greater),
+            BinaryOp::GreaterEq => Some(Self::GreaterEq),
+            _ => None,
+        }
+    }
+
+    /// The operator `op` such that `a self b` is `b op a`
+    fn flip(self) -> Self {
+        match self {
+            Self::Eq | Self::NotEq => self,
+            Self::Less => Self::Greater,
+            Self::LessEq => Self::GreaterEq,
+            Self::Greater => Self::Less,
+            Self::GreaterEq => Self::LessEq,
+        }
+    }
+}
+
+/// Whether a set attribute must contain all or any of some values, used by
+/// [`FilterBackend::contains()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quantifier {
+    /// The set contains every value (`containsAll`, or `contains` with a
+    /// single value)
+    All,
+    /// The set contains at least one of the values (`containsAny`)
+    Any,
+}
+
+/// Builds filter predicates for a data store, e.g., SQL `WHERE` clauses or
+/// document store queries. Used by [`FilterTranslator`].
+///
+/// Predicates are about the entity that the translator's target variable
+/// refers to, and the attributes of that entity. An attribute is given as a
+/// path from that entity: the empty path is the entity itself, `["owner"]` is
+/// `resource.owner`, and `["owner", "name"]` is `resource.owner.name`.
+///
+/// The methods for predicates on attributes return `None` if the backend
+/// cannot express the predicate, which [`FilterTranslator`] reports as an
+/// [`UntranslatableExpr`]. By default, they all return `None`.
+pub trait FilterBackend {
+    /// The type of predicates
+    type Output;
+
+    /// A predicate which is always `value`
+    fn constant(&mut self, value: bool) -> Self::Output;
+
+    /// The conjunction of two or more predicates
+    fn and(&mut self, operands: Vec<Self::Output>) -> Self::Output;
+
+    /// The disjunction of two or more predicates
+    fn or(&mut self, operands: Vec<Self::Output>) -> Self::Output;
+
+    /// The negation of a predicate
+    fn not(&mut self, operand: Self::Output) -> Self::Output;
+
+    /// The attribute at `path` compares to `value` by `op`
+    fn compare(
+        &mut self,
+        path: &[SmolStr],
+        op: CompareOp,
+        value: &Literal,
+    ) -> Option<Self::Output> {
+        let _ = (path, op, value);
+        None
+    }
+
+    /// The attribute at `path` exists
+    fn has_attr(&mut self, path: &[SmolStr]) -> Option<Self::Output> {
+        let _ = path;
+        None
+    }
+
+    /// The string attribute at `path` matches the `like` pattern `pattern`
+    fn like(&mut self, path: &[SmolStr], pattern: &[PatternElem]) -> Option<Self::Output> {
+        let _ = (path, pattern);
+        None
+    }
+
+    /// The entity at `path` has type `entity_type`
+    fn is_type(&mut self, path: &[SmolStr], entity_type: &EntityType) -> Option<Self::Output> {
+        let _ = (path, entity_type);
+        None
+    }
+
+    /// The entity at `path` is one of `ancestors`, or a descendant of one of
+    /// them
+    fn is_in(&mut self, path: &[SmolStr], ancestors: &[EntityUID]) -> Option<Self::Output> {
+        let _ = (path, ancestors);
+        None
+    }
+
+    /// The attribute at `path` is equal to one of `values`
+    fn one_of(&mut self, path: &[SmolStr], values: &[Literal]) -> Option<Self::Output> {
+        let _ = (path, values);
+        None
+    }
+
+    /// The set attribute at `path` contains all or any of `values`
+    fn contains(
+        &mut self,
+        path: &[SmolStr],
+        values: &[Literal],
+        quantifier: Quantifier,
+    ) -> Option<Self::Output> {
+        let _ = (path, values, quantifier);
+        None
+    }
+}
+
+/// Why a residual expression could not be translated. Returned by
+/// [`UntranslatableExpr::reason()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UntranslatableReason {
+    /// The expression is not a predicate that [`FilterTranslator`] can
+    /// translate, e.g., because it uses arithmetic, an extension function, or
+    /// a request variable other than the target
+    Unrecognized,
+    /// The expression is a predicate that the [`FilterBackend`] cannot
+    /// express
+    Unsupported,
+    /// Partial evaluation determined that the expression errors
+    Error,
+}
+
+/// A residual expression which could not be translated into a filter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UntranslatableExpr {
+    expr: String,
+    reason: UntranslatableReason,
+}
+
+impl UntranslatableExpr {
+    /// The expression, as Cedar syntax
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Why the expression could not be translated
+    pub fn reason(&self) -> UntranslatableReason {
+        self.reason
+    }
+}
+
+impl std::fmt::Display for UntranslatableExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.reason {
+            UntranslatableReason::Unrecognized => write!(f, "`{}` is not a filter", self.expr),
+            UntranslatableReason::Unsupported => {
+                write!(f, "`{}` is not supported by the backend", self.expr)
+            }
+            UntranslatableReason::Error => write!(f, "`{}` always errors", self.expr),
+        }
+    }
+}
+
+/// The result of [`FilterTranslator::translate_residuals()`] and
+/// [`FilterTranslator::translate_expr()`]
+#[derive(Debug, Clone)]
+pub struct FilterTranslation<T> {
+    filter: T,
+    approximated: Vec<UntranslatableExpr>,
+}
+
+impl<T> FilterTranslation<T> {
+    /// The filter predicate
+    pub fn filter(&self) -> &T {
+        &self.filter
+    }
+
+    /// Consume the translation, returning the filter predicate
+    pub fn into_filter(self) -> T {
+        self.filter
+    }
+
+    /// The expressions which could not be translated, and were approximated
+    /// so that the filter matches more entities than the residuals allow
+    pub fn approximated(&self) -> impl Iterator<Item = &UntranslatableExpr> {
+        self.approximated.iter()
+    }
+
+    /// Returns `true` if the filter matches exactly the entities which the
+    /// residuals allow
+    pub fn is_exact(&self) -> bool {
+        self.approximated.is_empty()
+    }
+}
+
+/// A predicate on the target with no boolean structure, which corresponds to
+/// a [`FilterBackend`] method
+enum Predicate<'e> {
+    Compare(Vec<SmolStr>, CompareOp, Literal),
+    HasAttr(Vec<SmolStr>),
+    Like(Vec<SmolStr>, &'e [PatternElem]),
+    Is(Vec<SmolStr>, &'e EntityType, Option<Vec<EntityUID>>),
+    In(Vec<SmolStr>, Vec<EntityUID>),
+    OneOf(Vec<SmolStr>, Vec<Literal>),
+    Contains(Vec<SmolStr>, Vec<Literal>, Quantifier),
+}
+
+impl Predicate<'_> {
+    /// Build this predicate with `backend`, if it can express it
+    fn build<B: FilterBackend>(&self, backend: &mut B) -> Option<B::Output> {
+        match self {
+            Self::Compare(path, op, value) => backend.compare(path, *op, value),
+            Self::HasAttr(path) => backend.has_attr(path),
+            Self::Like(path, pattern) => backend.like(path, pattern),
+            Self::Is(path, entity_type, ancestors) => {
+                let is_type = backend.is_type(path, entity_type)?;
+                match ancestors {
+                    Some(ancestors) => {
+                        let is_in = backend.is_in(path, ancestors)?;
+                        Some(backend.and(vec![is_type, is_in]))
+                    }
+                    None => Some(is_type),
+                }
+            }
+            Self::In(path, ancestors) => backend.is_in(path, ancestors),
+            Self::OneOf(path, values) => backend.one_of(path, values),
+            Self::Contains(path, values, quantifier) => backend.contains(path, values, *quantifier),
+        }
+    }
+}
+
+/// A translated predicate, or a constant which has not been passed to the
+/// backend yet, so that it can be folded away
+enum Emitted<T> {
+    Const(bool),
+    Filter(T),
+}
+
+/// Translates residuals of partial evaluation over a target request variable
+/// into filter predicates on that variable, using a [`FilterBackend`].
+///
+/// A residual over the target is typically the result of
+/// [`crate::PolicySet::tpe()`] with the target unknown, or of partial
+/// evaluation with the target as an unknown. The translator recognizes
+/// boolean structure (`&&`, `||`, `!`, and `if`) over comparisons between an
+/// attribute of the target and a literal, `has`, `like`, `is`, `in`, and the
+/// set operations `contains`, `containsAll`, and `containsAny` with literal
+/// operands. The translation assumes that the residuals don't error, e.g.,
+/// because they were validated against a schema.
+///
+/// By default, translation fails if any residual expression can't be
+/// translated; the error lists all of them. With
+/// [`FilterTranslator::with_approximation()`], such expressions are instead
+/// replaced by `true` or `false` so that the filter matches a superset of the
+/// allowed entities, which the application must then re-authorize.
+///
+/// ```
+/// # use cedar_policy::{pst, FilterTranslator, PolicySet, SqlFilter};
+/// # use std::str::FromStr;
+/// let policies = PolicySet::from_str(r#"
+///     permit(principal, action, resource) when { resource.owner == "alice" && resource.size < 100 };
+///     forbid(principal, action, resource) when { resource.archived };
+/// "#).unwrap();
+/// let translator = FilterTranslator::new(pst::Var::Resource);
+/// let mut sql = SqlFilter::new("id").with_column("owner", "owner_name");
+/// let translation = translator.translate_residuals(policies.policies(), &mut sql).unwrap();
+/// let filter = translation.filter();
+/// assert_eq!(
+///     filter.sql(),
+///     r#"(("owner_name" = ? AND "size" < ?) AND NOT ("archived" = ?))"#
+/// );
+/// assert_eq!(filter.params().len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilterTranslator {
+    target: Var,
+    approximate: bool,
+}
+
+impl FilterTranslator {
+    /// Create a translator for residuals over `target`
+    pub fn new(target: Var) -> Self {
+        Self {
+            target,
+            approximate: false,
+        }
+    }
+
+    /// Set whether expressions which can't be translated are approximated
+    /// (see [`FilterTranslator`]) rather than failing the translation
+    #[must_use]
+    pub fn with_approximation(mut self, approximate: bool) -> Self {
+        self.approximate = approximate;
+        self
+    }
+
+    /// Translate residual policies into a filter matching the entities for
+    /// which some permit policy is satisfied and no forbid policy is
+    pub fn translate_residuals<'a, B: FilterBackend>(
+        &self,
+        residuals: impl IntoIterator<Item = &'a Policy>,
+        backend: &mut B,
+    ) -> Result<FilterTranslation<B::Output>, FilterTranslationError> {
+        let mut untranslatable = Vec::new();
+        let mut permits = Vec::new();
+        let mut forbids = Vec::new();
+        for policy in residuals {
+            let condition = policy.ast.condition();
+            // Forbid policies are negated in the filter
+            let positive = policy.effect() == Effect::Permit;
+            let emitted = if let Ok(expr) = Expr::try_from(condition.clone()) {
+                self.emit(&expr, positive, backend, &mut untranslatable)
+            } else {
+                push_unique(
+                    &mut untranslatable,
+                    condition.to_string(),
+                    UntranslatableReason::Unrecognized,
+                );
+                Emitted::Const(positive)
+            };
+            if positive {
+                permits.push(emitted);
+            } else {
+                forbids.push(emitted);
+            }
+        }
+        let permitted = emit_or(backend, permits);
+        let forbidden = emit_or(backend, forbids);
+        let forbidden = emit_not(backend, forbidden);
+        let filter = emit_and(backend, vec![permitted, forbidden]);
+        self.finish(backend, filter, untranslatable)
+    }
+
+    /// Translate a single boolean residual expression into a filter
+    pub fn translate_expr<B: FilterBackend>(
+        &self,
+        expr: &Expr,
+        backend: &mut B,
+    ) -> Result<FilterTranslation<B::Output>, FilterTranslationError> {
+        let mut untranslatable = Vec::new();
+        let filter = self.emit(expr, true, backend, &mut untranslatable);
+        self.finish(backend, filter, untranslatable)
+    }
+
+    fn finish<B: FilterBackend>(
+        &self,
+        backend: &mut B,
+        filter: Emitted<B::Output>,
+        untranslatable: Vec<UntranslatableExpr>,
+    ) -> Result<FilterTranslation<B::Output>, FilterTranslationError> {
+        if !self.approximate && !untranslatable.is_empty() {
+            return Err(FilterTranslationError { untranslatable });
+        }
+        let filter = match filter {
+            Emitted::Const(value) => backend.constant(value),
+            Emitted::Filter(filter) => filter,
+        };
+        Ok(FilterTranslation {
+            filter,
+            approximated: untranslatable,
+        })
+    }
+
+    /// Translate `expr`, which occurs under an even number of negations if
+    /// `positive`. Expressions which can't be translated are recorded in
+    /// `untranslatable` and replaced by `positive`.
+    fn emit<B: FilterBackend>(
+        &self,
+        expr: &Expr,
+        positive: bool,
+        backend: &mut B,
+        untranslatable: &mut Vec<UntranslatableExpr>,
+    ) -> Emitted<B::Output> {
+        match expr {
+            Expr::Literal(Literal::Bool(b)) => return Emitted::Const(*b),
+            Expr::BinaryOp {
+                op: op @ (BinaryOp::And | BinaryOp::Or),
+                ..
+            } => {
+                let mut operands = Vec::new();
+                collect_operands(expr, *op, &mut operands);
+                let operands = operands
+                    .into_iter()
+                    .map(|e| self.emit(e, positive, backend, untranslatable))
+                    .collect();
+                return if *op == BinaryOp::And {
+                    emit_and(backend, operands)
+                } else {
+                    emit_or(backend, operands)
+                };
+            }
+            Expr::UnaryOp {
+                op: UnaryOp::Not,
+                expr,
+            } => {
+                let operand = self.emit(expr, !positive, backend, untranslatable);
+                return emit_not(backend, operand);
+            }
+            Expr::IfThenElse {
+                cond,
+                then_expr,
+                else_expr,
+            } => {
+                // if c then a else b == (c && a) || (!c && b)
+                let c = self.emit(cond, positive, backend, untranslatable);
+                let a = self.emit(then_expr, positive, backend, untranslatable);
+                let then_branch = emit_and(backend, vec![c, a]);
+                let not_c = self.emit(cond, !positive, backend, untranslatable);
+                let not_c = emit_not(backend, not_c);
+                let b = self.emit(else_expr, positive, backend, untranslatable);
+                let else_branch = emit_and(backend, vec![not_c, b]);
+                return emit_or(backend, vec![then_branch, else_branch]);
+            }
+            _ => (),
+        }
+        let reason = match self.predicate(expr) {
+            Some(predicate) => match predicate.build(backend) {
+                Some(filter) => return Emitted::Filter(filter),
+                None => UntranslatableReason::Unsupported,
+            },
+            #[cfg(feature = "tpe")]
+            None if expr.has_error() => UntranslatableReason::Error,
+            None => UntranslatableReason::Unrecognized,
+        };
+        push_unique(untranslatable, expr.to_string(), reason);
+        Emitted::Const(positive)
+    }
+
+    /// Recognize a predicate on the target which has no boolean structure
+    fn predicate<'e>(&self, expr: &'e Expr) -> Option<Predicate<'e>> {
+        match expr {
+            Expr::GetAttr { .. } => Some(Predicate::Compare(
+                self.path(expr)?,
+                CompareOp::Eq,
+                Literal::Bool(true),
+            )),
+            Expr::HasAttr { expr, attrs } => {
+                let mut path = self.path(expr)?;
+                path.extend(attrs.iter().cloned());
+                Some(Predicate::HasAttr(path))
+            }
+            Expr::Like { expr, pattern } => Some(Predicate::Like(self.path(expr)?, pattern)),
+            Expr::Is {
+                expr,
+                entity_type,
+                in_expr,
+            } => {
+                let ancestors = match in_expr {
+                    Some(in_expr) => Some(entity_literals(in_expr)?),
+                    None => None,
+                };
+                Some(Predicate::Is(self.path(expr)?, entity_type, ancestors))
+            }
+            Expr::BinaryOp { op, left, right } => {
+                if let Some(cmp) = CompareOp::from_pst(*op) {
+                    if let (Some(path), Expr::Literal(value)) = (self.path(left), right.as_ref()) {
+                        return Some(Predicate::Compare(path, cmp, value.clone()));
+                    }
+                    if let (Expr::Literal(value), Some(path)) = (left.as_ref(), self.path(right)) {
+                        return Some(Predicate::Compare(path, cmp.flip(), value.clone()));
+                    }
+                    return None;
+                }
+                match op {
+                    BinaryOp::In => Some(Predicate::In(self.path(left)?, entity_literals(right)?)),
+                    BinaryOp::Contains => {
+                        if let (Some(path), Expr::Literal(value)) =
+                            (self.path(left), right.as_ref())
+                        {
+                            return Some(Predicate::Contains(
+                                path,
+                                vec![value.clone()],
+                                Quantifier::All,
+                            ));
+                        }
+                        Some(Predicate::OneOf(self.path(right)?, set_literals(left)?))
+                    }
+                    BinaryOp::ContainsAll | BinaryOp::ContainsAny => {
+                        let quantifier = if *op == BinaryOp::ContainsAll {
+                            Quantifier::All
+                        } else {
+                            Quantifier::Any
+                        };
+                        Some(Predicate::Contains(
+                            self.path(left)?,
+                            set_literals(right)?,
+                            quantifier,
+                        ))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The attribute path of `expr` from the target, if `expr` is the target
+    /// or an attribute of it
+    fn path(&self, expr: &Expr) -> Option<Vec<SmolStr>> {
+        match expr {
+            Expr::Var(var) if *var == self.target => Some(Vec::new()),
+            // Partial evaluation represents unknown variables by their name
+            Expr::Unknown { name } if name == var_name(&self.target) => Some(Vec::new()),
+            Expr::GetAttr { expr, attr } => {
+                let mut path = self.path(expr)?;
+                path.push(attr.clone());
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn var_name(var: &Var) -> &'static str {
+    match var {
+        Var::Principal => "principal",
+        Var::Action => "action",
+        Var::Resource => "resource",
+        Var::Context => "context",
+    }
+}
+
+/// Push the operands of nested applications of `op` (`&&` or `||`) onto
+/// `operands`, in order
+fn collect_operands<'e>(expr: &'e Expr, op: BinaryOp, operands: &mut Vec<&'e Expr>) {
+    match expr {
+        Expr::BinaryOp {
+            op: inner,
+            left,
+            right,
+        } if *inner == op => {
+            collect_operands(left, op, operands);
+            collect_operands(right, op, operands);
+        }
+        _ => operands.push(expr),
+    }
+}
+
+/// The literals of `expr`, if it is a set of literals
+fn set_literals(expr: &Expr) -> Option<Vec<Literal>> {
+    match expr {
+        Expr::Set(items) => items
+            .iter()
+            .map(|item| match item.as_ref() {
+                Expr::Literal(lit) => Some(lit.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// The entities of `expr`, if it is an entity literal or a set of them
+fn entity_literals(expr: &Expr) -> Option<Vec<EntityUID>> {
+    match expr {
+        Expr::Literal(Literal::EntityUID(uid)) => Some(vec![uid.clone()]),
+        _ => set_literals(expr)?
+            .into_iter()
+            .map(|lit| match lit {
+                Literal::EntityUID(uid) => Some(uid),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+fn push_unique(
+    untranslatable: &mut Vec<UntranslatableExpr>,
+    expr: String,
+    reason: UntranslatableReason,
+) {
+    let entry = UntranslatableExpr { expr, reason };
+    if !untranslatable.contains(&entry) {
+        untranslatable.push(entry);
+    }
+}
+
+fn emit_and<B: FilterBackend>(
+    backend: &mut B,
+    operands: Vec<Emitted<B::Output>>,
+) -> Emitted<B::Output> {
+    emit_junction(backend, operands, false)
+}
+
+fn emit_or<B: FilterBackend>(
+    backend: &mut B,
+    operands: Vec<Emitted<B::Output>>,
+) -> Emitted<B::Output> {
+    emit_junction(backend, operands, true)
+}
+
+/// Combine `operands` with `||` if `is_or`, and `&&` otherwise, folding
+/// constant operands
+fn emit_junction<B: FilterBackend>(
+    backend: &mut B,
+    operands: Vec<Emitted<B::Output>>,
+    is_or: bool,
+) -> Emitted<B::Output> {
+    let mut filters = Vec::new();
+    for operand in operands {
+        match operand {
+            // `true` absorbs a disjunction, and `false` a conjunction
+            Emitted::Const(value) if value == is_or => return Emitted::Const(is_or),
+            Emitted::Const(_) => (),
+            Emitted::Filter(filter) => filters.push(filter),
+        }
+    }
+    if filters.len() > 1 {
+        Emitted::Filter(if is_or {
+            backend.or(filters)
+        } else {
+            backend.and(filters)
+        })
+    } else {
+        filters
+            .pop()
+            .map_or(Emitted::Const(!is_or), Emitted::Filter)
+    }
+}
+
+fn emit_not<B: FilterBackend>(backend: &mut B, operand: Emitted<B::Output>) -> Emitted<B::Output> {
+    match operand {
+        Emitted::Const(value) => Emitted::Const(!value),
+        Emitted::Filter(filter) => Emitted::Filter(backend.not(filter)),
+    }
+}
+
+/// A SQL boolean expression with `?` placeholders, and the values to bind to
+/// them in order. Produced by [`SqlFilter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlPredicate {
+    sql: String,
+    params: Vec<Literal>,
+}
+
+impl SqlPredicate {
+    /// The SQL expression, for use in a `WHERE` clause
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The values of the placeholders in [`SqlPredicate::sql()`], in order.
+    /// Entity values (from comparisons with the target, or with entity
+    /// attributes) are left to the caller to encode as the table does.
+    pub fn params(&self) -> &[Literal] {
+        &self.params
+    }
+}
+
+/// A reference [`FilterBackend`] producing SQL [`SqlPredicate`]s over a table
+/// with one row per entity of the target type.
+///
+/// The target entity itself is stored in the id column, and each attribute in
+/// a column which defaults to its path joined with `_` (e.g., `owner_name` for
+/// `resource.owner.name`). Missing attributes are `NULL`. Set attributes are
+/// not supported. `is` is only supported with a type column, and `in` only
+/// with an ancestors table.
+#[derive(Debug, Clone)]
+pub struct SqlFilter {
+    id_column: String,
+    type_column: Option<String>,
+    columns: HashMap<String, String>,
+    ancestors: Option<(String, String, String)>,
+}
+
+impl SqlFilter {
+    /// Create a backend for a table whose `id_column` stores the entity
+    pub fn new(id_column: impl Into<String>) -> Self {
+        Self {
+            id_column: id_column.into(),
+            type_column: None,
+            columns: HashMap::new(),
+            ancestors: None,
+        }
+    }
+
+    /// Store the attribute at `path` (e.g., `owner.name`) in `column`
+    #[must_use]
+    pub fn with_column(mut self, path: impl Into<String>, column: impl Into<String>) -> Self {
+        self.columns.insert(path.into(), column.into());
+        self
+    }
+
+    /// Store the entity type name of each entity in `column`, to support `is`
+    #[must_use]
+    pub fn with_type_column(mut self, column: impl Into<String>) -> Self {
+        self.type_column = Some(column.into());
+        self
+    }
+
+    /// Look up ancestors in `table`, which has a row for each entity
+    /// (`entity_column`) and each of its ancestors (`ancestor_column`), to
+    /// support `in`
+    #[must_use]
+    pub fn with_ancestors_table(
+        mut self,
+        table: impl Into<String>,
+        entity_column: impl Into<String>,
+        ancestor_column: impl Into<String>,
+    ) -> Self {
+        self.ancestors = Some((table.into(), entity_column.into(), ancestor_column.into()));
+        self
+    }
+
+    fn column(&self, path: &[SmolStr]) -> String {
+        if path.is_empty() {
+            return quote(&self.id_column);
+        }
+        self.columns
+            .get(&path.join("."))
+            .map_or_else(|| quote(&path.join("_")), |column| quote(column))
+    }
+
+    fn junction(operands: Vec<SqlPredicate>, op: &str) -> SqlPredicate {
+        let mut sql = String::from("(");
+        let mut params = Vec::new();
+        for (i, operand) in operands.into_iter().enumerate() {
+            if i > 0 {
+                sql.push_str(op);
+            }
+            sql.push_str(&operand.sql);
+            params.extend(operand.params);
+        }
+        sql.push(')');
+        SqlPredicate { sql, params }
+    }
+}
+
+/// Quote a SQL identifier
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// `count` comma-separated placeholders
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+impl FilterBackend for SqlFilter {
+    type Output = SqlPredicate;
+
+    fn constant(&mut self, value: bool) -> SqlPredicate {
+        SqlPredicate {
+            sql: if value { "TRUE" } else { "FALSE" }.into(),
+            params: Vec::new(),
+        }
+    }
+
+    fn and(&mut self, operands: Vec<SqlPredicate>) -> SqlPredicate {
+        Self::junction(operands, " AND ")
+    }
+
+    fn or(&mut self, operands: Vec<SqlPredicate>) -> SqlPredicate {
+        Self::junction(operands, " OR ")
+    }
+
+    fn not(&mut self, operand: SqlPredicate) -> SqlPredicate {
+        let sql = if operand.sql.starts_with('(') {
+            format!("NOT {}", operand.sql)
+        } else {
+            format!("NOT ({})", operand.sql)
+        };
+        SqlPredicate {
+            sql,
+            params: operand.params,
+        }
+    }
+
+    fn compare(
+        &mut self,
+        path: &[SmolStr],
+        op: CompareOp,
+        value: &Literal,
+    ) -> Option<SqlPredicate> {
+        let op = match op {
+            CompareOp::Eq => "=",
+            CompareOp::NotEq => "<>",
+            CompareOp::Less => "<",
+            CompareOp::LessEq => "<=",
+            CompareOp::Greater => ">",
+            CompareOp::GreaterEq => ">=",
+        };
+        Some(SqlPredicate {
+            sql: format!("{} {op} ?", self.column(path)),
+            params: vec![value.clone()],
+        })
+    }
+
+    fn has_attr(&mut self, path: &[SmolStr]) -> Option<SqlPredicate> {
+        Some(SqlPredicate {
+            sql: format!("{} IS NOT NULL", self.column(path)),
+            params: Vec::new(),
+        })
+    }
+
+    fn like(&mut self, path: &[SmolStr], pattern: &[PatternElem]) -> Option<SqlPredicate> {
+        let mut sql_pattern = String::new();
+        for elem in pattern {
+            match elem {
+                PatternElem::Wildcard => sql_pattern.push('%'),
+                PatternElem::Char(c @ ('%' | '_' | '\\')) => {
+                    sql_pattern.push('\\');
+                    sql_pattern.push(*c);
+                }
+                PatternElem::Char(c) => sql_pattern.push(*c),
+            }
+        }
+        Some(SqlPredicate {
+            sql: format!("{} LIKE ? ESCAPE '\\'", self.column(path)),
+            params: vec![Literal::String(sql_pattern.into())],
+        })
+    }
+
+    fn is_type(&mut self, path: &[SmolStr], entity_type: &EntityType) -> Option<SqlPredicate> {
+        if !path.is_empty() {
+            return None;
+        }
+        Some(SqlPredicate {
+            sql: format!("{} = ?", quote(self.type_column.as_ref()?)),
+            params: vec![Literal::String(entity_type.to_string().into())],
+        })
+    }
+
+    fn is_in(&mut self, path: &[SmolStr], ancestors: &[EntityUID]) -> Option<SqlPredicate> {
+        let (table, entity_column, ancestor_column) = self.ancestors.as_ref()?;
+        if ancestors.is_empty() {
+            return Some(self.constant(false));
+        }
+        let column = self.column(path);
+        let sql = format!(
+            "({column} IN ({list}) OR {column} IN (SELECT {} FROM {} WHERE {} IN ({list})))",
+            quote(entity_column),
+            quote(table),
+            quote(ancestor_column),
+            list = placeholders(ancestors.len()),
+        );
+        let params: Vec<_> = ancestors
+            .iter()
+            .map(|uid| Literal::EntityUID(uid.clone()))
+            .collect();
+        Some(SqlPredicate {
+            sql,
+            params: [params.clone(), params].concat(),
+        })
+    }
+
+    fn one_of(&mut self, path: &[SmolStr], values: &[Literal]) -> Option<SqlPredicate> {
+        if values.is_empty() {
+            return Some(self.constant(false));
+        }
+        Some(SqlPredicate {
+            sql: format!("{} IN ({})", self.column(path), placeholders(values.len())),
+            params: values.to_vec(),
+        })
+    }
+}
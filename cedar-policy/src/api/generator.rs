@@ -0,0 +1,100 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`SchemaGenerator`], which generates random entities,
+//! contexts, and requests conforming to a [`Schema`], for property testing
+//! and fuzzing.
+
+use super::{Context, Entities, EntityTypeName, EntityUid, Request, Schema};
+use arbitrary::{Result, Unstructured};
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_core::validator::arbitrary as core_arbitrary;
+
+pub use core_arbitrary::GeneratorConfig;
+
+/// Generates random entities, contexts, and requests which conform to a
+/// [`Schema`].
+///
+/// Randomness is drawn from an [`Unstructured`], so a generator can be driven
+/// directly by a fuzzer (e.g., `cargo fuzz`). To use it with `proptest`,
+/// generate a random `Vec<u8>` and wrap it in an [`Unstructured`].
+///
+/// Generators return [`arbitrary::Error::EmptyChoose`] when asked to make a
+/// choice from nothing (e.g., a request for a schema with no action that
+/// applies to any principal and resource), and
+/// [`arbitrary::Error::IncorrectFormat`] when the schema refers to a type for
+/// which no value can be generated (e.g., an extension type whose extension
+/// is disabled, or a required attribute whose values must match a pattern).
+///
+/// ```
+/// # use cedar_policy::{Schema, SchemaGenerator};
+/// # use arbitrary::Unstructured;
+/// let (schema, _) = Schema::from_cedarschema_str(
+///     "entity User { name: String }; entity Doc; action read appliesTo { principal: User, resource: Doc };",
+/// )
+/// .unwrap();
+/// let generator = SchemaGenerator::new(&schema);
+/// let mut u = Unstructured::new(&[7, 42, 3, 9, 18, 250, 11, 0, 96]);
+/// let request = generator.request(&mut u).unwrap();
+/// assert_eq!(request.action().unwrap().to_string(), r#"Action::"read""#);
+/// let entities = generator.entities(&mut u).unwrap();
+/// assert!(entities
+///     .iter()
+///     .filter(|e| e.uid().type_name().to_string() == "User")
+///     .all(|e| e.attr("name").is_some()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaGenerator<'s>(core_arbitrary::SchemaGenerator<'s>);
+
+impl<'s> SchemaGenerator<'s> {
+    /// Create a generator for `schema` with the default [`GeneratorConfig`]
+    pub fn new(schema: &'s Schema) -> Self {
+        Self::with_config(schema, GeneratorConfig::default())
+    }
+
+    /// Create a generator for `schema` with the given [`GeneratorConfig`]
+    pub fn with_config(schema: &'s Schema, config: GeneratorConfig) -> Self {
+        Self(core_arbitrary::SchemaGenerator::with_config(
+            &schema.0,
+            config,
+            Extensions::all_available(),
+        ))
+    }
+
+    /// Generate the UID of an entity of type `ty`
+    pub fn entity_uid(&self, u: &mut Unstructured<'_>, ty: &EntityTypeName) -> Result<EntityUid> {
+        self.0.entity_uid(u, &ty.0).map(EntityUid)
+    }
+
+    /// Generate a set of entities conforming to the schema, including the
+    /// schema's action entities. The generated entity hierarchy is acyclic.
+    pub fn entities(&self, u: &mut Unstructured<'_>) -> Result<Entities> {
+        self.0.entities(u).map(Entities)
+    }
+
+    /// Generate a context conforming to the context type of `action`.
+    /// Returns [`arbitrary::Error::IncorrectFormat`] if `action` is not
+    /// declared in the schema.
+    pub fn context(&self, u: &mut Unstructured<'_>, action: &EntityUid) -> Result<Context> {
+        self.0.context(u, &action.0).map(Context)
+    }
+
+    /// Generate a request for one of the schema's actions, with a principal,
+    /// resource, and context conforming to the action's declaration
+    pub fn request(&self, u: &mut Unstructured<'_>) -> Result<Request> {
+        self.0.request(u).map(Request)
+    }
+}
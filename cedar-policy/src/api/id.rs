@@ -367,11 +367,65 @@ impl AsRef<ast::PolicyID> for PolicyId {
     }
 }
 
+/// Separates the namespace components of a [`PolicyId`] from each other and
+/// from its name
+const POLICY_ID_NAMESPACE_SEPARATOR: &str = "::";
+
 impl PolicyId {
     /// Construct a [`PolicyId`] from a source string
     pub fn new(id: impl AsRef<str>) -> Self {
         Self(ast::PolicyID::from_string(id.as_ref()))
     }
+
+    /// Construct a [`PolicyId`] with the given namespace components, e.g., to
+    /// keep the policies of different teams apart in one policy set. The
+    /// components and name are joined with `::`.
+    /// ```
+    /// # use cedar_policy::PolicyId;
+    /// let id = PolicyId::namespaced(["payments", "prod"], "policy0");
+    /// assert_eq!(id, PolicyId::new("payments::prod::policy0"));
+    /// assert_eq!(id.namespace().collect::<Vec<_>>(), ["payments", "prod"]);
+    /// assert_eq!(id.name(), "policy0");
+    /// ```
+    pub fn namespaced<S: AsRef<str>>(
+        namespace: impl IntoIterator<Item = S>,
+        name: impl AsRef<str>,
+    ) -> Self {
+        let mut id = String::new();
+        for component in namespace {
+            id.push_str(component.as_ref());
+            id.push_str(POLICY_ID_NAMESPACE_SEPARATOR);
+        }
+        id.push_str(name.as_ref());
+        Self::new(id)
+    }
+
+    /// The namespace components of this id: everything before its last `::`,
+    /// split on `::`. Empty if the id has no namespace.
+    pub fn namespace(&self) -> impl Iterator<Item = &str> {
+        let id: &str = self.as_ref();
+        id.rsplit_once(POLICY_ID_NAMESPACE_SEPARATOR)
+            .map(|(namespace, _)| namespace.split(POLICY_ID_NAMESPACE_SEPARATOR))
+            .into_iter()
+            .flatten()
+    }
+
+    /// The name of this id, without its namespace: everything after its
+    /// last `::`
+    pub fn name(&self) -> &str {
+        let id: &str = self.as_ref();
+        id.rsplit_once(POLICY_ID_NAMESPACE_SEPARATOR)
+            .map_or(id, |(_, name)| name)
+    }
+
+    /// Returns `true` if this id is in `namespace` or one of its
+    /// sub-namespaces
+    pub fn is_in_namespace<S: AsRef<str>>(&self, namespace: impl IntoIterator<Item = S>) -> bool {
+        let mut own = self.namespace();
+        namespace
+            .into_iter()
+            .all(|component| own.next() == Some(component.as_ref()))
+    }
 }
 
 impl FromStr for PolicyId {
@@ -438,6 +492,21 @@ impl SlotId {
     pub fn resource() -> Self {
         Self(ast::SlotId::resource())
     }
+
+    /// Check if this is a value slot, i.e., a slot other than `?principal`
+    /// and `?resource` which is declared in a `template(...) =>` header
+    pub fn is_value_slot(&self) -> bool {
+        self.0.is_value_slot()
+    }
+}
+
+impl FromStr for SlotId {
+    type Err = ParseErrors;
+
+    /// Parse a [`SlotId`], e.g., `?principal` or `?limit`
+    fn from_str(slot: &str) -> Result<Self, Self::Err> {
+        ast::SlotId::from_str(slot).map(Self).map_err(Into::into)
+    }
 }
 
 impl std::fmt::Display for SlotId {
@@ -0,0 +1,520 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`EntityTypeInfo`], [`AttributeInfo`] and
+//! [`DeclaredType`], which describe the entity types declared in a
+//! [`Schema`] independently of the format the schema was written in, and
+//! [`ExtensionFunctionInfo`], which describes the available extension
+//! functions.
+
+use super::{EntityId, EntityTypeName, EntityUid, Schema};
+use cedar_policy_core::ast::CallStyle;
+use cedar_policy_core::entities::SchemaType;
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_core::validator::types::{EntityKind, OpenTag, Type};
+use cedar_policy_core::validator::{ValidatorEntityType, ValidatorEntityTypeKind};
+use itertools::Itertools;
+use ref_cast::RefCast;
+use std::collections::BTreeMap;
+
+/// The type of an attribute, tag, or context, as declared in a [`Schema`].
+///
+/// Common types are resolved, so this never refers to a common type by name.
+/// The [`std::fmt::Display`] implementation prints the type in Cedar schema
+/// syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeclaredType {
+    /// Boolean
+    Bool,
+    /// Signed integer
+    Long,
+    /// String
+    String,
+    /// Set with elements of the given type
+    Set(Box<Self>),
+    /// Map from `String` keys to values of the given type
+    Map(Box<Self>),
+    /// Record with the given attributes
+    Record {
+        /// Attributes of the record and their types
+        attributes: BTreeMap<String, AttributeInfo>,
+        /// Whether a record of this type may have attributes other than
+        /// `attributes`
+        open: bool,
+    },
+    /// Entity of the given type
+    Entity(EntityTypeName),
+    /// Entity of any one of the given types. There are always at least two.
+    EntityUnion(Vec<EntityTypeName>),
+    /// Extension type with the given name, e.g., `ipaddr` or `decimal`
+    Extension(String),
+}
+
+impl DeclaredType {
+    /// Convert a type taken from a [`Schema`]. Returns `None` for the types
+    /// that only arise during typechecking, which can't be declared in a schema.
+    fn from_validator_type(ty: &Type) -> Option<Self> {
+        Some(match ty {
+            Type::Bool(_) => Self::Bool,
+            Type::Long => Self::Long,
+            Type::String => Self::String,
+            Type::Set {
+                element_type: Some(element_type),
+            } => Self::Set(Box::new(Self::from_validator_type(element_type)?)),
+            Type::Map {
+                value_type: Some(value_type),
+            } => Self::Map(Box::new(Self::from_validator_type(value_type)?)),
+            Type::Record {
+                attrs,
+                open_attributes,
+            } => Self::Record {
+                attributes: attrs
+                    .iter()
+                    .map(|(name, attr)| {
+                        Some((
+                            name.to_string(),
+                            AttributeInfo {
+                                ty: Self::from_validator_type(&attr.attr_type)?,
+                                required: attr.is_required,
+                            },
+                        ))
+                    })
+                    .collect::<Option<_>>()?,
+                open: *open_attributes == OpenTag::OpenAttributes,
+            },
+            Type::Entity(EntityKind::Entity(lub)) => lub.get_single_entity().map_or_else(
+                || Self::EntityUnion(lub.iter().cloned().map(EntityTypeName).collect()),
+                |ety| Self::Entity(EntityTypeName(ety.clone())),
+            ),
+            Type::ExtensionType { name } => Self::Extension(name.to_string()),
+            Type::Never
+            | Type::Set { element_type: None }
+            | Type::Map { value_type: None }
+            | Type::Entity(EntityKind::AnyEntity) => return None,
+        })
+    }
+
+    /// Convert the type of an extension function argument or return value.
+    /// Returns `None` for the type of the empty set.
+    fn from_core_schema_type(ty: &SchemaType) -> Option<Self> {
+        Some(match ty {
+            SchemaType::Bool => Self::Bool,
+            SchemaType::Long => Self::Long,
+            SchemaType::String => Self::String,
+            SchemaType::Set { element_ty } => {
+                Self::Set(Box::new(Self::from_core_schema_type(element_ty)?))
+            }
+            SchemaType::EmptySet => return None,
+            SchemaType::Map { value_ty } => {
+                Self::Map(Box::new(Self::from_core_schema_type(value_ty)?))
+            }
+            SchemaType::Record { attrs, open_attrs } => Self::Record {
+                attributes: attrs
+                    .iter()
+                    .map(|(name, attr)| {
+                        Some((
+                            name.to_string(),
+                            AttributeInfo {
+                                ty: Self::from_core_schema_type(attr.schema_type())?,
+                                required: attr.is_required(),
+                            },
+                        ))
+                    })
+                    .collect::<Option<_>>()?,
+                open: *open_attrs,
+            },
+            SchemaType::Entity { ty } => Self::Entity(EntityTypeName::ref_cast(ty).clone()),
+            SchemaType::EntityUnion { tys } => Self::EntityUnion(
+                tys.iter()
+                    .map(|ty| EntityTypeName::ref_cast(ty).clone())
+                    .collect(),
+            ),
+            SchemaType::Extension { name } => Self::Extension(name.to_string()),
+        })
+    }
+
+    /// Convert to the Core `SchemaType`. Returns `None` if this contains an
+    /// extension type whose name isn't a valid name, or an `EntityUnion` of no
+    /// entity types.
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn to_core_schema_type(&self) -> Option<SchemaType> {
+        use cedar_policy_core::entities::AttributeType;
+        use std::collections::BTreeSet;
+
+        Some(match self {
+            Self::Bool => SchemaType::Bool,
+            Self::Long => SchemaType::Long,
+            Self::String => SchemaType::String,
+            Self::Set(element_ty) => SchemaType::Set {
+                element_ty: Box::new(element_ty.to_core_schema_type()?),
+            },
+            Self::Map(value_ty) => SchemaType::Map {
+                value_ty: Box::new(value_ty.to_core_schema_type()?),
+            },
+            Self::Record { attributes, open } => SchemaType::Record {
+                attrs: attributes
+                    .iter()
+                    .map(|(name, attr)| {
+                        let ty = attr.ty.to_core_schema_type()?;
+                        Some((
+                            name.into(),
+                            if attr.required {
+                                AttributeType::required(ty)
+                            } else {
+                                AttributeType::optional(ty)
+                            },
+                        ))
+                    })
+                    .collect::<Option<_>>()?,
+                open_attrs: *open,
+            },
+            Self::Entity(ty) => SchemaType::Entity { ty: ty.0.clone() },
+            Self::EntityUnion(tys) => {
+                let mut tys: BTreeSet<_> = tys.iter().map(|ty| ty.0.clone()).collect();
+                if tys.len() > 1 {
+                    SchemaType::EntityUnion { tys }
+                } else {
+                    SchemaType::Entity {
+                        ty: tys.pop_first()?,
+                    }
+                }
+            }
+            Self::Extension(name) => SchemaType::Extension {
+                name: name.parse().ok()?,
+            },
+        })
+    }
+
+    /// Convert a type that was declared in a [`Schema`]
+    #[expect(
+        clippy::expect_used,
+        reason = "`ty` is taken from a `ValidatorSchema`, which only contains types that can be declared in a schema"
+    )]
+    pub(crate) fn from_schema_type(ty: &Type) -> Self {
+        Self::from_validator_type(ty).expect("schema type is not representable as a `DeclaredType`")
+    }
+
+    /// Convert the type of an expression which typechecked in strict mode
+    #[expect(
+        clippy::expect_used,
+        reason = "strict typechecking rejects the expressions with types that can't be declared in a schema, e.g., empty sets"
+    )]
+    pub(crate) fn from_typechecked_type(ty: &Type) -> Self {
+        Self::from_validator_type(ty)
+            .expect("strictly typechecked type is not representable as a `DeclaredType`")
+    }
+}
+
+impl std::fmt::Display for DeclaredType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool => write!(f, "Bool"),
+            Self::Long => write!(f, "Long"),
+            Self::String => write!(f, "String"),
+            Self::Set(element) => write!(f, "Set<{element}>"),
+            Self::Map(value) => write!(f, "Map<String, {value}>"),
+            Self::Record { attributes, .. } => write!(
+                f,
+                "{{{}}}",
+                attributes
+                    .iter()
+                    .map(|(name, attr)| format!(
+                        "{name:?}{}: {}",
+                        if attr.required { "" } else { "?" },
+                        attr.ty
+                    ))
+                    .join(", ")
+            ),
+            Self::Entity(ety) => write!(f, "{ety}"),
+            Self::EntityUnion(etys) => write!(f, "{}", etys.iter().join(" | ")),
+            Self::Extension(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// The declaration of an attribute of an entity type or record type in a
+/// [`Schema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeInfo {
+    ty: DeclaredType,
+    required: bool,
+}
+
+impl AttributeInfo {
+    /// The declared type of the attribute
+    pub fn ty(&self) -> &DeclaredType {
+        &self.ty
+    }
+
+    /// Whether the attribute must be present. Returns `false` for optional
+    /// attributes.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+}
+
+/// The declaration of an entity type in a [`Schema`], as returned by
+/// [`Schema::entity_type()`]
+#[derive(Debug, Clone, Copy)]
+pub struct EntityTypeInfo<'a>(&'a ValidatorEntityType);
+
+impl<'a> EntityTypeInfo<'a> {
+    /// The name of the entity type
+    pub fn name(&self) -> &'a EntityTypeName {
+        EntityTypeName::ref_cast(self.0.name())
+    }
+
+    /// Iterate over the attributes declared for entities of this type, ordered
+    /// by name. Enumerated entity types never have attributes.
+    pub fn attributes(&self) -> impl Iterator<Item = (&'a str, AttributeInfo)> {
+        self.0.attributes().iter().map(|(name, attr)| {
+            (
+                name.as_str(),
+                AttributeInfo {
+                    ty: DeclaredType::from_schema_type(&attr.attr_type),
+                    required: attr.is_required,
+                },
+            )
+        })
+    }
+
+    /// Get the declaration of the attribute `attr`, or `None` if entities of
+    /// this type don't have that attribute
+    pub fn attribute(&self, attr: &str) -> Option<AttributeInfo> {
+        self.0.attr(attr).map(|attr| AttributeInfo {
+            ty: DeclaredType::from_schema_type(&attr.attr_type),
+            required: attr.is_required,
+        })
+    }
+
+    /// Get the type of the tags of entities of this type, or `None` if
+    /// entities of this type can't have tags
+    pub fn tag_type(&self) -> Option<DeclaredType> {
+        self.0.tag_type().map(DeclaredType::from_schema_type)
+    }
+
+    /// Iterate over the entity ids this entity type allows, if it is an
+    /// enumerated entity type. Returns `None` for entity types which are not
+    /// enumerated.
+    pub fn enum_choices(&self) -> Option<impl Iterator<Item = &'a EntityId>> {
+        match &self.0.kind {
+            ValidatorEntityTypeKind::Enum(choices) => Some(choices.iter().map(EntityId::ref_cast)),
+            ValidatorEntityTypeKind::Standard(_) => None,
+        }
+    }
+}
+
+impl Schema {
+    /// Get the declaration of the entity type `ty`, which can be used to
+    /// query its attributes, tag type and, for an enumerated entity type, the
+    /// entity ids it allows.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityTypeName, Schema};
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity User {
+    ///         name: String,
+    ///         manager?: User,
+    ///     } tags Set<String>;
+    ///     entity Color enum ["red", "green"];
+    /// "#).unwrap();
+    /// let user = schema.entity_type(&"User".parse().unwrap()).unwrap();
+    /// let attrs: Vec<String> = user
+    ///     .attributes()
+    ///     .map(|(name, attr)| format!("{name}: {} (required: {})", attr.ty(), attr.is_required()))
+    ///     .collect();
+    /// assert_eq!(attrs, ["manager: User (required: false)", "name: String (required: true)"]);
+    /// assert_eq!(user.tag_type().unwrap().to_string(), "Set<String>");
+    ///
+    /// let color = schema.entity_type(&"Color".parse().unwrap()).unwrap();
+    /// let choices: Vec<&str> = color.enum_choices().unwrap().map(AsRef::as_ref).collect();
+    /// assert_eq!(choices, ["red", "green"]);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if `ty` is not found in the schema
+    pub fn entity_type(&self, ty: &EntityTypeName) -> Option<EntityTypeInfo<'_>> {
+        self.0.get_entity_type(&ty.0).map(EntityTypeInfo)
+    }
+
+    /// Iterate over the entities of the enumerated entity types declared in
+    /// the schema, ordered by entity type name and then as declared. These
+    /// are the only entities of those types which entity data, requests, and
+    /// policies may refer to when validated against the schema.
+    ///
+    /// ```
+    /// # use cedar_policy::Schema;
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity User;
+    ///     entity Status enum ["draft", "published"];
+    ///     entity Color enum ["red"];
+    /// "#).unwrap();
+    /// let uids: Vec<String> = schema.enum_entities().map(|uid| uid.to_string()).collect();
+    /// assert_eq!(uids, [r#"Color::"red""#, r#"Status::"draft""#, r#"Status::"published""#]);
+    /// ```
+    pub fn enum_entities(&self) -> impl Iterator<Item = EntityUid> + '_ {
+        self.0
+            .entity_types()
+            .filter_map(|ety| match &ety.kind {
+                ValidatorEntityTypeKind::Enum(choices) => Some((ety.name(), choices)),
+                ValidatorEntityTypeKind::Standard(_) => None,
+            })
+            .sorted_by_cached_key(|(name, _)| name.to_string())
+            .flat_map(|(name, choices)| {
+                choices.iter().map(|eid| {
+                    EntityUid::from_type_name_and_id(
+                        EntityTypeName::ref_cast(name).clone(),
+                        EntityId::ref_cast(eid).clone(),
+                    )
+                })
+            })
+    }
+
+    /// Get the type of the context for requests with the action `action`.
+    /// This is always a [`DeclaredType::Record`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns [`None`] if `action` is not found in the schema
+    pub fn context_type(&self, action: &EntityUid) -> Option<DeclaredType> {
+        self.0
+            .context_type(&action.0)
+            .map(DeclaredType::from_schema_type)
+    }
+}
+
+/// An extension function available to policies, such as `ip()` or
+/// `.isInRange()`, as returned by [`extension_functions()`]
+///
+/// The [`std::fmt::Display`] implementation prints the signature of the
+/// function, e.g., `ip(String) -> ipaddr` or `ipaddr.isInRange(ipaddr) -> Bool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionFunctionInfo {
+    name: String,
+    extension: String,
+    is_method: bool,
+    arg_types: Vec<DeclaredType>,
+    is_variadic: bool,
+    return_type: DeclaredType,
+    doc: Option<String>,
+}
+
+impl ExtensionFunctionInfo {
+    /// The name of the function, e.g., `ip` or `isInRange`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the extension defining the function, e.g., `ipaddr`
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    /// Whether the function is called as a method, e.g., `a.isInRange(b)`,
+    /// rather than as a function, e.g., `ip(a)`. The receiver of a method is
+    /// its first argument.
+    pub fn is_method(&self) -> bool {
+        self.is_method
+    }
+
+    /// The types of the arguments of the function, including the receiver
+    /// of a method
+    pub fn arg_types(&self) -> &[DeclaredType] {
+        &self.arg_types
+    }
+
+    /// Whether the last argument may be repeated any number of times
+    pub fn is_variadic(&self) -> bool {
+        self.is_variadic
+    }
+
+    /// The type of the value returned by the function
+    pub fn return_type(&self) -> &DeclaredType {
+        &self.return_type
+    }
+
+    /// A short description of the function, if it has one
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+}
+
+impl std::fmt::Display for ExtensionFunctionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut args = self.arg_types.iter().map(ToString::to_string).collect_vec();
+        if self.is_variadic {
+            if let Some(last) = args.last_mut() {
+                last.push_str("...");
+            }
+        }
+        let (receiver, args) = match args.split_first() {
+            Some((receiver, args)) if self.is_method => (format!("{receiver}."), args),
+            _ => (String::new(), args.as_slice()),
+        };
+        write!(
+            f,
+            "{receiver}{}({}) -> {}",
+            self.name,
+            args.join(", "),
+            self.return_type
+        )
+    }
+}
+
+/// Iterate over the extension functions available to policies, ordered by
+/// name.
+///
+/// This includes any custom functions registered with
+/// [`crate::CustomExtension::register()`]. Which builtin functions are
+/// available depends on the enabled crate features.
+///
+/// ```
+/// # use cedar_policy::extension_functions;
+/// let is_in_set = extension_functions()
+///     .find(|f| f.name() == "isInSet")
+///     .unwrap();
+/// assert_eq!(is_in_set.extension(), "ipaddr");
+/// assert!(is_in_set.is_method());
+/// assert_eq!(is_in_set.to_string(), "ipaddr.isInSet(ipset) -> Bool");
+/// assert!(is_in_set.doc().is_some());
+/// ```
+pub fn extension_functions() -> impl Iterator<Item = ExtensionFunctionInfo> {
+    Extensions::all_available()
+        .extensions()
+        .flat_map(|ext| ext.funcs().map(move |func| (ext, func)))
+        .filter_map(|(ext, func)| {
+            // Functions without a return type, like the partial evaluation
+            // `unknown()`, are internal and not listed
+            Some(ExtensionFunctionInfo {
+                name: func.name().to_string(),
+                extension: ext.name().to_string(),
+                is_method: func.style() == CallStyle::MethodStyle,
+                arg_types: func
+                    .arg_types()
+                    .iter()
+                    .map(DeclaredType::from_core_schema_type)
+                    .collect::<Option<_>>()?,
+                is_variadic: func.is_variadic(),
+                return_type: DeclaredType::from_core_schema_type(func.return_type()?)?,
+                doc: func.doc().map(ToString::to_string),
+            })
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+}
@@ -0,0 +1,217 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`LayeredPolicySet`], an ordered stack of policy sets
+//! (e.g., organization-wide guardrails, then team policies, then
+//! resource-owner policies) which are combined with an explicit precedence by
+//! [`Authorizer::is_authorized_layered()`].
+
+use super::{
+    policy_set_errors::{DuplicateLayerError, JsonPolicySetError},
+    Authorizer, Decision, Entities, PolicyId, PolicySet, PolicySetError, Request, Response,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// How a [`PolicyLayer`] contributes to the decision of
+/// [`Authorizer::is_authorized_layered()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LayerMode {
+    /// A `forbid` policy of the layer which is satisfied denies the request,
+    /// regardless of the other layers. `permit` policies of the layer have no
+    /// effect.
+    Guardrail,
+    /// The layer must allow the request, following the usual Cedar
+    /// semantics: some `permit` policy of the layer is satisfied and no
+    /// `forbid` policy is. Layers below it can only narrow what it allows.
+    Narrowing,
+}
+
+/// A named policy set in a [`LayeredPolicySet`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyLayer {
+    name: String,
+    mode: LayerMode,
+    policies: PolicySet,
+}
+
+impl PolicyLayer {
+    /// Create a layer named `name` containing `policies`
+    pub fn new(name: impl Into<String>, mode: LayerMode, policies: PolicySet) -> Self {
+        Self {
+            name: name.into(),
+            mode,
+            policies,
+        }
+    }
+
+    /// The name of the layer
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the layer contributes to the decision
+    pub fn mode(&self) -> LayerMode {
+        self.mode
+    }
+
+    /// The policies of the layer
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    /// Qualify the ids of this layer's policies with the layer name, so they
+    /// are unambiguous across layers
+    fn qualify(&self, ids: HashSet<PolicyId>) -> impl Iterator<Item = PolicyId> + '_ {
+        ids.into_iter()
+            .map(|id| PolicyId::namespaced([&self.name], id))
+    }
+}
+
+/// An ordered stack of [`PolicyLayer`]s, from highest to lowest precedence.
+///
+/// A request is allowed by [`Authorizer::is_authorized_layered()`] if no
+/// [`LayerMode::Guardrail`] layer forbids it, every [`LayerMode::Narrowing`]
+/// layer allows it, and there is at least one narrowing layer. Policy ids only
+/// need to be unique within a layer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayeredPolicySet {
+    layers: Vec<PolicyLayer>,
+}
+
+/// The serialized form of a [`PolicyLayer`], whose policies are stored with
+/// [`PolicySet::store_json()`]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredLayer {
+    name: String,
+    mode: LayerMode,
+    policies: serde_json::Value,
+}
+
+impl LayeredPolicySet {
+    /// Create a `LayeredPolicySet` with no layers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `layer` below the existing layers, i.e., with lower precedence.
+    /// Errors if there is already a layer with the same name.
+    pub fn push_layer(&mut self, layer: PolicyLayer) -> Result<(), PolicySetError> {
+        if self.layer(layer.name()).is_some() {
+            return Err(DuplicateLayerError { name: layer.name }.into());
+        }
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    /// Iterate over the layers, from highest to lowest precedence
+    pub fn layers(&self) -> impl Iterator<Item = &PolicyLayer> {
+        self.layers.iter()
+    }
+
+    /// Get the layer named `name`
+    pub fn layer(&self, name: &str) -> Option<&PolicyLayer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// Serialize all the layers, with their names, modes, and policies, as
+    /// one JSON value. Load it again with [`LayeredPolicySet::from_json()`].
+    pub fn to_json(&self) -> Result<serde_json::Value, PolicySetError> {
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                Ok(StoredLayer {
+                    name: layer.name.clone(),
+                    mode: layer.mode,
+                    policies: layer.policies.store_json()?,
+                })
+            })
+            .collect::<Result<Vec<_>, PolicySetError>>()?;
+        serde_json::to_value(layers).map_err(|inner| JsonPolicySetError { inner }.into())
+    }
+
+    /// Load layers serialized with [`LayeredPolicySet::to_json()`]
+    pub fn from_json(value: serde_json::Value) -> Result<Self, PolicySetError> {
+        let stored: Vec<StoredLayer> =
+            serde_json::from_value(value).map_err(|inner| JsonPolicySetError { inner })?;
+        let mut layers = Self::new();
+        for layer in stored {
+            let policies = PolicySet::load_json(layer.policies)?;
+            layers.push_layer(PolicyLayer::new(layer.name, layer.mode, policies))?;
+        }
+        Ok(layers)
+    }
+}
+
+impl Authorizer {
+    /// Authorize `r` against a stack of policy layers. The layers are
+    /// evaluated from highest to lowest precedence, and the first layer which
+    /// denies the request determines the response: its name is reported by
+    /// [`crate::Diagnostics::deciding_layer()`], and its determining policies
+    /// by [`crate::Diagnostics::reason()`]. If the request is allowed, the
+    /// reason is the determining policies of all the narrowing layers. Errors
+    /// from every evaluated layer are reported.
+    ///
+    /// Policy ids in the reason are namespaced by the name of their layer
+    /// (see [`PolicyId::namespaced()`]), e.g., `org::policy0`.
+    ///
+    /// See [`LayeredPolicySet`] for when a request is allowed.
+    pub fn is_authorized_layered(
+        &self,
+        r: &Request,
+        layers: &LayeredPolicySet,
+        e: &Entities,
+    ) -> Response {
+        let mut allowed = Response::new(Decision::Deny, HashSet::new(), Vec::new());
+        let mut narrowed = false;
+        for layer in &layers.layers {
+            let response = self.is_authorized(r, &layer.policies, e);
+            allowed
+                .diagnostics
+                .errors
+                .extend(response.diagnostics.errors);
+            let denies = match layer.mode {
+                LayerMode::Guardrail => {
+                    // Only satisfied `forbid` policies are reasons for a denial
+                    response.decision == Decision::Deny && !response.diagnostics.reason.is_empty()
+                }
+                LayerMode::Narrowing => {
+                    narrowed = true;
+                    response.decision == Decision::Deny
+                }
+            };
+            if denies {
+                allowed.diagnostics.reason = layer.qualify(response.diagnostics.reason).collect();
+                allowed.diagnostics.deciding_layer = Some(layer.name.clone());
+                return allowed;
+            }
+            if layer.mode == LayerMode::Narrowing {
+                allowed
+                    .diagnostics
+                    .reason
+                    .extend(layer.qualify(response.diagnostics.reason));
+            }
+        }
+        // Without a narrowing layer, no layer permits the request
+        if narrowed {
+            allowed.decision = Decision::Allow;
+        }
+        allowed
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicyMetadata`], structured information about a
+//! policy or template that is kept by a [`PolicySet`] alongside (but separately
+//! from) the policy's annotations.
+
+use super::{policy_set_errors, Policy, PolicyId, PolicySet, PolicySetError, Template};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+/// Metadata about a policy or template: when it was created and last modified,
+/// and arbitrary key-value tags (e.g., `team` = `payments`).
+///
+/// Unlike annotations, metadata is not part of the policy text and has no
+/// effect on authorization or validation. It is set with
+/// [`PolicySet::set_policy_metadata()`] and is preserved by
+/// [`PolicySet::store_json()`] and [`PolicySet::store_binary()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<SystemTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<SystemTime>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
+}
+
+impl PolicyMetadata {
+    /// Create empty metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time the policy was created
+    #[must_use]
+    pub fn with_created(mut self, time: SystemTime) -> Self {
+        self.created = Some(time);
+        self
+    }
+
+    /// Set the time the policy was last modified
+    #[must_use]
+    pub fn with_modified(mut self, time: SystemTime) -> Self {
+        self.modified = Some(time);
+        self
+    }
+
+    /// Set the tag `key` to `value`, replacing any previous value
+    #[must_use]
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// The time the policy was created, if known
+    pub fn created(&self) -> Option<SystemTime> {
+        self.created
+    }
+
+    /// The time the policy was last modified, if known
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Get the value of the tag `key`
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(String::as_str)
+    }
+
+    /// Iterate over the tags as `(key, value)` pairs, ordered by key
+    pub fn tags(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tags.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns `true` if this metadata matches `tag`, which is either
+    /// `key:value` (the tag `key` is set to `value`) or just `key` (the tag
+    /// `key` is set to any value).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        match tag.split_once(':') {
+            Some((key, value)) => self.tag(key) == Some(value),
+            None => self.tags.contains_key(tag),
+        }
+    }
+}
+
+impl PolicySet {
+    /// Get the metadata of the policy or template with id `id`, if any has
+    /// been set
+    pub fn policy_metadata(&self, id: &PolicyId) -> Option<&PolicyMetadata> {
+        self.metadata.get(id)
+    }
+
+    /// Set the metadata of the policy or template with id `id`, returning its
+    /// previous metadata. Errors if there is no such policy or template.
+    ///
+    /// Metadata is removed along with its policy or template.
+    pub fn set_policy_metadata(
+        &mut self,
+        id: PolicyId,
+        metadata: PolicyMetadata,
+    ) -> Result<Option<PolicyMetadata>, PolicySetError> {
+        if self.policies.contains_key(&id) || self.templates.contains_key(&id) {
+            Ok(self.metadata.insert(id, metadata))
+        } else {
+            Err(policy_set_errors::NoSuchPolicyError { policy_id: id }.into())
+        }
+    }
+
+    /// Iterate over the static and template-linked policies whose metadata
+    /// matches `tag`, in the sense of [`PolicyMetadata::has_tag()`]. For
+    /// example, `policies_with_tag("team:payments")` returns the policies
+    /// whose `team` tag is `payments`.
+    pub fn policies_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Policy> {
+        self.policies()
+            .filter(move |p| self.metadata_has_tag(p.id(), tag))
+    }
+
+    /// Iterate over the templates whose metadata matches `tag`, in the sense
+    /// of [`PolicyMetadata::has_tag()`]
+    pub fn templates_with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a Template> {
+        self.templates()
+            .filter(move |t| self.metadata_has_tag(t.id(), tag))
+    }
+
+    fn metadata_has_tag(&self, id: &PolicyId, tag: &str) -> bool {
+        self.metadata.get(id).is_some_and(|m| m.has_tag(tag))
+    }
+}
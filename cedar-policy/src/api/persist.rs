@@ -0,0 +1,174 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines the versioned storage format for a whole [`PolicySet`],
+//! including its static policies, templates, and template links.
+//!
+//! A stored policy set has two equivalent encodings:
+//!
+//! * JSON: an object `{ "formatVersion": 1, "policySet": ..., "metadata": ... }`,
+//!   where `policySet` is the JSON policy set format (see
+//!   [`PolicySet::to_json()`]) and the optional `metadata` maps policy and
+//!   template ids to their [`PolicyMetadata`].
+//! * Binary: the 8-byte header `CEDARPS\0`, the format version as a big-endian
+//!   `u32`, the payload length as a big-endian `u64`, and then the payload,
+//!   which is the compact JSON form without its `formatVersion`.
+//!
+//! Policy and template ids, annotations, metadata, disabled policies, and the
+//! slot bindings of every link (including value slots) are preserved, so loading a stored
+//! policy set produces a policy set equal to the one that was stored.
+
+use super::{policy_set_errors, PolicyId, PolicyMetadata, PolicySet, PolicySetError};
+use cedar_policy_core::{est, extensions::Extensions};
+use policy_set_errors::{JsonPolicySetError, NoSuchPolicyError, PersistedFormatError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The storage format version written by [`PolicySet::store_json()`] and
+/// [`PolicySet::store_binary()`]
+pub const POLICY_SET_FORMAT_VERSION: u32 = 1;
+
+const FORMAT_VERSION_KEY: &str = "formatVersion";
+const BINARY_MAGIC: &[u8; 8] = b"CEDARPS\0";
+const BINARY_HEADER_LEN: usize = BINARY_MAGIC.len() + 4 + 8;
+
+/// The contents of a stored policy set, other than its format version
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredPolicySet {
+    policy_set: est::PolicySet,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    metadata: HashMap<PolicyId, PolicyMetadata>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    disabled: HashSet<PolicyId>,
+}
+
+impl StoredPolicySet {
+    fn new(pset: &PolicySet) -> Result<Self, PolicySetError> {
+        Ok(Self {
+            policy_set: pset.clone().est(Extensions::all_available())?,
+            metadata: pset.metadata.clone(),
+            disabled: pset
+                .policies
+                .keys()
+                .chain(pset.templates.keys())
+                .filter(|id| !pset.is_enabled(id))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn load(self) -> Result<PolicySet, PolicySetError> {
+        let mut pset = PolicySet::from_est(&self.policy_set, Extensions::all_available())?;
+        for (policy_id, metadata) in self.metadata {
+            if pset.policy(&policy_id).is_none() && pset.template(&policy_id).is_none() {
+                return Err(NoSuchPolicyError { policy_id }.into());
+            }
+            pset.metadata.insert(policy_id, metadata);
+        }
+        for policy_id in self.disabled {
+            pset.set_enabled(policy_id, false)?;
+        }
+        Ok(pset)
+    }
+}
+
+impl PolicySet {
+    /// Store this policy set, with all of its templates and links, in the
+    /// versioned JSON format. Load it again with [`PolicySet::load_json()`].
+    pub fn store_json(&self) -> Result<serde_json::Value, PolicySetError> {
+        let mut value = serde_json::to_value(StoredPolicySet::new(self)?)
+            .map_err(|inner| JsonPolicySetError { inner })?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert(FORMAT_VERSION_KEY.into(), POLICY_SET_FORMAT_VERSION.into());
+        }
+        Ok(value)
+    }
+
+    /// Store this policy set in the versioned JSON format, as a string
+    pub fn store_json_string(&self) -> Result<String, PolicySetError> {
+        serde_json::to_string(&self.store_json()?)
+            .map_err(|inner| JsonPolicySetError { inner }.into())
+    }
+
+    /// Load a policy set stored with [`PolicySet::store_json()`]
+    pub fn load_json(value: serde_json::Value) -> Result<Self, PolicySetError> {
+        let version = value
+            .get(FORMAT_VERSION_KEY)
+            .and_then(serde_json::Value::as_u64)
+            .ok_or(PersistedFormatError::MissingVersion)?;
+        check_version(version)?;
+        let stored: StoredPolicySet =
+            serde_json::from_value(value).map_err(|inner| JsonPolicySetError { inner })?;
+        stored.load()
+    }
+
+    /// Load a policy set stored with [`PolicySet::store_json_string()`]
+    pub fn load_json_str(src: impl AsRef<str>) -> Result<Self, PolicySetError> {
+        let value =
+            serde_json::from_str(src.as_ref()).map_err(|inner| JsonPolicySetError { inner })?;
+        Self::load_json(value)
+    }
+
+    /// Store this policy set, with all of its templates and links, in the
+    /// versioned binary format. Load it again with [`PolicySet::load_binary()`].
+    pub fn store_binary(&self) -> Result<Vec<u8>, PolicySetError> {
+        let payload = serde_json::to_vec(&StoredPolicySet::new(self)?)
+            .map_err(|inner| JsonPolicySetError { inner })?;
+        let mut bytes = Vec::with_capacity(BINARY_HEADER_LEN + payload.len());
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&POLICY_SET_FORMAT_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Load a policy set stored with [`PolicySet::store_binary()`]
+    pub fn load_binary(bytes: &[u8]) -> Result<Self, PolicySetError> {
+        let (magic, rest) = bytes
+            .split_first_chunk::<8>()
+            .ok_or(PersistedFormatError::BadHeader)?;
+        if magic != BINARY_MAGIC {
+            return Err(PersistedFormatError::BadHeader.into());
+        }
+        let (version, rest) = rest
+            .split_first_chunk::<4>()
+            .ok_or(PersistedFormatError::BadHeader)?;
+        check_version(u32::from_be_bytes(*version).into())?;
+        let (len, payload) = rest
+            .split_first_chunk::<8>()
+            .ok_or(PersistedFormatError::BadHeader)?;
+        let expected = u64::from_be_bytes(*len);
+        let actual = payload.len() as u64;
+        if expected != actual {
+            return Err(PersistedFormatError::LengthMismatch { expected, actual }.into());
+        }
+        let stored: StoredPolicySet =
+            serde_json::from_slice(payload).map_err(|inner| JsonPolicySetError { inner })?;
+        stored.load()
+    }
+}
+
+fn check_version(version: u64) -> Result<(), PersistedFormatError> {
+    if version == u64::from(POLICY_SET_FORMAT_VERSION) {
+        Ok(())
+    } else {
+        Err(PersistedFormatError::UnsupportedVersion {
+            version,
+            supported: POLICY_SET_FORMAT_VERSION,
+        })
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::find_references()`], which finds where the
+//! policies and templates of a policy set refer to an entity, entity type,
+//! attribute, or action, e.g., to assess the impact of deleting a group or
+//! renaming an attribute.
+
+use super::{EntityTypeName, EntityUid, PolicyId, PolicySet};
+use cedar_policy_core::ast::{self, Expr, ExprKind, Literal, SlotEnv};
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_core::parser::{cst, text_to_cst, Loc};
+use miette::SourceSpan;
+
+/// What [`PolicySet::find_references()`] looks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceTarget {
+    /// An entity, referenced by an entity literal, or by a slot which a link
+    /// binds to it
+    Entity(EntityUid),
+    /// An entity type, referenced by an `is` constraint or by an entity
+    /// literal of that type
+    EntityType(EntityTypeName),
+    /// An attribute, referenced by `.attr`, `["attr"]`, or `has attr`
+    Attribute(String),
+    /// An action, referenced in the same way as an [`ReferenceTarget::Entity`]
+    Action(EntityUid),
+}
+
+impl ReferenceTarget {
+    /// Returns `true` if `euid` is (or has) this target
+    fn matches_euid(&self, euid: &ast::EntityUID) -> bool {
+        match self {
+            Self::Entity(target) | Self::Action(target) => &target.0 == euid,
+            Self::EntityType(target) => &target.0 == euid.entity_type(),
+            Self::Attribute(_) => false,
+        }
+    }
+
+    /// Returns `true` if `expr` itself (not its subexpressions) refers to
+    /// this target. Slots refer to the entity `env` binds them to.
+    fn matches(&self, expr: &Expr, env: &SlotEnv) -> bool {
+        match (expr.expr_kind(), self) {
+            (ExprKind::Lit(Literal::EntityUID(euid)), _) => self.matches_euid(euid),
+            (ExprKind::Slot(slot), _) => env.get(slot).is_some_and(|euid| self.matches_euid(euid)),
+            (ExprKind::Is { entity_type, .. }, Self::EntityType(target)) => {
+                &target.0 == entity_type
+            }
+            (
+                ExprKind::GetAttr { attr, .. } | ExprKind::HasAttr { attr, .. },
+                Self::Attribute(target),
+            ) => attr == target,
+            _ => false,
+        }
+    }
+}
+
+/// A reference to a [`ReferenceTarget`] in a policy or template. Returned by
+/// [`PolicySet::find_references()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyReference {
+    policy_id: PolicyId,
+    span: Option<SourceSpan>,
+}
+
+impl PolicyReference {
+    /// The id of the policy or template with the reference
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// The location of the reference in the source the policy or template was
+    /// parsed from, if it was parsed from Cedar text. For a link, this is a
+    /// location in the text of its template (e.g., the slot `?principal`).
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+}
+
+impl PolicySet {
+    /// Find every reference to `target` in the templates, static policies,
+    /// and template-linked policies of this policy set, in that order. Within
+    /// a policy or template, references are ordered as they appear in its
+    /// scope and then its conditions.
+    ///
+    /// A policy or template which refers to `target` more than once has one
+    /// [`PolicyReference`] for each reference.
+    /// ```
+    /// # use cedar_policy::{EntityUid, PolicyId, PolicySet, ReferenceTarget};
+    /// # use std::str::FromStr;
+    /// let src = r#"permit(principal in Group::"admins", action, resource);"#;
+    /// let policies = PolicySet::from_str(src).unwrap();
+    /// let admins = EntityUid::from_str(r#"Group::"admins""#).unwrap();
+    /// let refs = policies.find_references(&ReferenceTarget::Entity(admins));
+    /// assert_eq!(refs.len(), 1);
+    /// assert_eq!(refs[0].policy_id(), &PolicyId::new("policy0"));
+    /// let span = refs[0].span().unwrap();
+    /// assert_eq!(&src[span.offset()..span.offset() + span.len()], r#"Group::"admins""#);
+    /// ```
+    pub fn find_references(&self, target: &ReferenceTarget) -> Vec<PolicyReference> {
+        let no_env = SlotEnv::new();
+        let templates = self.ast.templates().map(|t| (t.id(), t, &no_env));
+        let policies = self.ast.policies().map(|p| (p.id(), p.template(), p.env()));
+        templates
+            .chain(policies)
+            .flat_map(|(id, template, env)| {
+                template_references(template, env, target)
+                    .into_iter()
+                    .map(move |span| PolicyReference {
+                        policy_id: PolicyId::new(id.clone()),
+                        span,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// The references to `target` in `template`, with its slots bound by `env`
+fn template_references(
+    template: &ast::Template,
+    env: &SlotEnv,
+    target: &ReferenceTarget,
+) -> Vec<Option<SourceSpan>> {
+    // The AST doesn't keep the locations of scope constraints, so they are
+    // found in the source text when it is available
+    let mut found = template
+        .loc()
+        .and_then(|loc| scope_references_in_source(loc, env, target))
+        .unwrap_or_else(|| {
+            let mut found = Vec::new();
+            for scope in [
+                template.principal_constraint().as_expr(),
+                template.action_constraint().as_expr(),
+                template.resource_constraint().as_expr(),
+            ] {
+                expr_references(&scope, env, target, &mut found);
+            }
+            found
+        });
+    if let Some(conditions) = template.non_scope_constraints() {
+        expr_references(conditions, env, target, &mut found);
+    }
+    found
+}
+
+/// Push the location of each reference to `target` in `expr` onto `found`,
+/// in source order
+fn expr_references(
+    expr: &Expr,
+    env: &SlotEnv,
+    target: &ReferenceTarget,
+    found: &mut Vec<Option<SourceSpan>>,
+) {
+    let mut spans: Vec<_> = expr
+        .subexpressions()
+        .filter(|e| target.matches(e, env))
+        .map(|e| e.source_loc().map(|loc| loc.span))
+        .collect();
+    spans.sort_by_key(|span| span.map(|span| span.offset()));
+    found.extend(spans);
+}
+
+/// Find the references to `target` in the scope of the policy whose source
+/// is at `loc`. Returns `None` if the source can't be parsed.
+fn scope_references_in_source(
+    loc: &Loc,
+    env: &SlotEnv,
+    target: &ReferenceTarget,
+) -> Option<Vec<Option<SourceSpan>>> {
+    let cst = text_to_cst::parse_policy(loc.snippet()?).ok()?;
+    let policy = match cst.node? {
+        cst::Policy::Policy(policy) => policy,
+        #[cfg(feature = "tolerant-ast")]
+        cst::Policy::PolicyError => return None,
+    };
+    // Locations in `cst` are relative to the start of the policy
+    let shift = |span: SourceSpan| SourceSpan::from((span.offset() + loc.start(), span.len()));
+    let mut found = Vec::new();
+    for var in &policy.variables {
+        let var = var.as_inner()?;
+        if let (ReferenceTarget::EntityType(ty), Some(entity_type)) = (target, &var.entity_type) {
+            let entity_type_loc = entity_type.loc.as_ref()?;
+            if entity_type_loc
+                .snippet()?
+                .trim()
+                .parse::<EntityTypeName>()
+                .ok()?
+                == *ty
+            {
+                found.push(Some(shift(entity_type_loc.span)));
+            }
+        }
+        if let Some((_, expr)) = &var.ineq {
+            let expr = expr
+                .to_expr::<ast::ExprBuilder<()>>(Extensions::all_available())
+                .ok()?;
+            let mut spans = Vec::new();
+            expr_references(&expr, env, target, &mut spans);
+            found.extend(spans.into_iter().map(|span| span.map(shift)));
+        }
+    }
+    Some(found)
+}
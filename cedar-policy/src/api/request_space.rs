@@ -0,0 +1,398 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`RequestSpace`], which enumerates every request a
+//! schema allows when its entities and contexts are finite, e.g., to check a
+//! small policy set exhaustively with a [`DecisionTable`].
+
+use super::{
+    request_space_errors, AttributeInfo, Authorizer, Context, Decision, DeclaredType, Entities,
+    Entity, EntityTypeName, EntityUid, PolicySet, Request, RequestSpaceError, Response,
+    RestrictedExpression, Schema,
+};
+use cedar_policy_core::ast;
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+
+/// The requests allowed by a [`Schema`], with principals and resources drawn
+/// from a finite set of entities.
+///
+/// Principals and resources of an enumerated entity type range over the
+/// entities the schema declares for it. Those of other entity types range over
+/// the entities of that type in the [`Entities`] given to
+/// [`RequestSpace::new()`].
+///
+/// Contexts range over every value of the context type of the action, which
+/// must then be finite: its attributes must be booleans, entities, or records
+/// of those, and optional attributes are either absent or take any of these
+/// values. For actions whose context type has other attributes, such as
+/// strings or numbers, provide the contexts to consider with
+/// [`RequestSpace::with_contexts()`].
+#[derive(Debug, Clone)]
+pub struct RequestSpace<'a> {
+    schema: &'a Schema,
+    entities: &'a Entities,
+    contexts: HashMap<EntityUid, Vec<Context>>,
+}
+
+/// The path to a context attribute whose type has infinitely many values, and
+/// its type
+type Unbounded = (Vec<String>, DeclaredType);
+
+/// The requests of a [`RequestSpace`] for one request environment
+#[derive(Debug)]
+struct Section {
+    principals: Vec<EntityUid>,
+    action: EntityUid,
+    resources: Vec<EntityUid>,
+    contexts: Vec<Context>,
+}
+
+impl Section {
+    fn len(&self) -> usize {
+        self.principals.len() * self.resources.len() * self.contexts.len()
+    }
+
+    fn requests(self) -> impl Iterator<Item = Request> {
+        let Self {
+            principals,
+            action,
+            resources,
+            contexts,
+        } = self;
+        principals
+            .into_iter()
+            .cartesian_product(resources)
+            .cartesian_product(contexts)
+            .map(move |((principal, resource), context)| {
+                Request(ast::Request::new_unchecked(
+                    ast::EntityUIDEntry::known(principal.into(), None),
+                    ast::EntityUIDEntry::known(action.clone().into(), None),
+                    ast::EntityUIDEntry::known(resource.into(), None),
+                    Some(context.0),
+                ))
+            })
+    }
+}
+
+impl<'a> RequestSpace<'a> {
+    /// The requests allowed by `schema`, with principals and resources of
+    /// entity types which are not enumerated drawn from `entities`
+    pub fn new(schema: &'a Schema, entities: &'a Entities) -> Self {
+        Self {
+            schema,
+            entities,
+            contexts: HashMap::new(),
+        }
+    }
+
+    /// Consider the given contexts for requests with the action `action`,
+    /// instead of enumerating its context type. They are validated against
+    /// the schema when the requests are enumerated.
+    #[must_use]
+    pub fn with_contexts(
+        mut self,
+        action: EntityUid,
+        contexts: impl IntoIterator<Item = Context>,
+    ) -> Self {
+        self.contexts.entry(action).or_default().extend(contexts);
+        self
+    }
+
+    /// The number of requests in this request space, without enumerating them
+    ///
+    /// ## Errors
+    ///
+    /// See [`RequestSpace::requests()`]
+    pub fn size(&self) -> Result<usize, RequestSpaceError> {
+        Ok(self.sections()?.iter().map(Section::len).sum())
+    }
+
+    /// Enumerate the requests in this request space, ordered by action as
+    /// declared by [`Schema::request_envs()`], and then by principal, resource
+    /// and context.
+    ///
+    /// ```
+    /// # use cedar_policy::{Entities, RequestSpace, Schema};
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity Role enum ["admin", "viewer"];
+    ///     entity Doc enum ["plan", "budget"];
+    ///     action read appliesTo {
+    ///         principal: Role,
+    ///         resource: Doc,
+    ///         context: { internal: Bool },
+    ///     };
+    /// "#).unwrap();
+    /// let entities = Entities::empty();
+    /// let space = RequestSpace::new(&schema, &entities);
+    /// assert_eq!(space.size().unwrap(), 8);
+    /// assert_eq!(space.requests().unwrap().count(), 8);
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the context type of an action without contexts
+    /// from [`RequestSpace::with_contexts()`] has infinitely many values, or
+    /// if any of those contexts doesn't conform to the schema.
+    pub fn requests(&self) -> Result<impl Iterator<Item = Request>, RequestSpaceError> {
+        Ok(self.sections()?.into_iter().flat_map(Section::requests))
+    }
+
+    /// Authorize every request in this request space against `policies`,
+    /// using the entities given to [`RequestSpace::new()`].
+    ///
+    /// ## Errors
+    ///
+    /// See [`RequestSpace::requests()`]
+    pub fn decision_table(&self, policies: &PolicySet) -> Result<DecisionTable, RequestSpaceError> {
+        let authorizer = Authorizer::new();
+        let rows = self
+            .requests()?
+            .map(|request| {
+                let response = authorizer.is_authorized(&request, policies, self.entities);
+                DecisionTableRow { request, response }
+            })
+            .collect();
+        Ok(DecisionTable { rows })
+    }
+
+    fn sections(&self) -> Result<Vec<Section>, RequestSpaceError> {
+        let mut candidates = HashMap::new();
+        let mut sections = Vec::new();
+        for req_env in self.schema.request_envs() {
+            let action = req_env.action().clone();
+            let contexts = match self.contexts.get(&action) {
+                Some(contexts) => {
+                    for context in contexts {
+                        context.validate(self.schema, &action)?;
+                    }
+                    contexts.clone()
+                }
+                None => self.enumerate_contexts(&action, &mut candidates)?,
+            };
+            sections.push(Section {
+                principals: self
+                    .entities_of_type(req_env.principal(), &mut candidates)
+                    .to_vec(),
+                action,
+                resources: self
+                    .entities_of_type(req_env.resource(), &mut candidates)
+                    .to_vec(),
+                contexts,
+            });
+        }
+        Ok(sections)
+    }
+
+    /// The entities of type `ty` which requests may refer to, memoized in
+    /// `candidates`
+    fn entities_of_type<'c>(
+        &self,
+        ty: &EntityTypeName,
+        candidates: &'c mut HashMap<EntityTypeName, Vec<EntityUid>>,
+    ) -> &'c [EntityUid] {
+        candidates.entry(ty.clone()).or_insert_with(|| {
+            self.schema
+                .entity_type(ty)
+                .and_then(|info| info.enum_choices())
+                .map_or_else(
+                    || {
+                        self.entities
+                            .iter()
+                            .map(Entity::uid)
+                            .filter(|uid| uid.type_name() == ty)
+                            .sorted_by_cached_key(ToString::to_string)
+                            .collect()
+                    },
+                    |choices| {
+                        choices
+                            .map(|eid| EntityUid::from_type_name_and_id(ty.clone(), eid.clone()))
+                            .collect()
+                    },
+                )
+        })
+    }
+
+    fn enumerate_contexts(
+        &self,
+        action: &EntityUid,
+        candidates: &mut HashMap<EntityTypeName, Vec<EntityUid>>,
+    ) -> Result<Vec<Context>, RequestSpaceError> {
+        let Some(DeclaredType::Record { attributes, .. }) = self.schema.context_type(action) else {
+            return Ok(vec![Context::empty()]);
+        };
+        let mut path = Vec::new();
+        let records = self
+            .enumerate_record(&attributes, &mut path, candidates)
+            .map_err(|(path, ty)| request_space_errors::UnboundedContextError {
+                action: action.clone(),
+                path,
+                ty,
+            })?;
+        Ok(records
+            .into_iter()
+            .map(|attrs| {
+                #[expect(
+                    clippy::expect_used,
+                    reason = "the attributes of a record type are distinct"
+                )]
+                Context::from_pairs(attrs).expect("the attributes of a record type are distinct")
+            })
+            .collect())
+    }
+
+    /// Every value of the record type with the given `attributes`, as lists of
+    /// its attributes. On failure, returns the path to an attribute whose type
+    /// has infinitely many values, and its type.
+    fn enumerate_record(
+        &self,
+        attributes: &BTreeMap<String, AttributeInfo>,
+        path: &mut Vec<String>,
+        candidates: &mut HashMap<EntityTypeName, Vec<EntityUid>>,
+    ) -> Result<Vec<Vec<(String, RestrictedExpression)>>, Unbounded> {
+        let mut choices = Vec::with_capacity(attributes.len());
+        for (name, info) in attributes {
+            path.push(name.clone());
+            let mut values: Vec<Option<RestrictedExpression>> = self
+                .enumerate_values(info.ty(), path, candidates)?
+                .into_iter()
+                .map(Some)
+                .collect();
+            path.pop();
+            if !info.is_required() {
+                values.insert(0, None);
+            }
+            choices.push(values.into_iter().map(move |v| (name.clone(), v)));
+        }
+        Ok(choices
+            .into_iter()
+            .multi_cartesian_product()
+            .map(|attrs| {
+                attrs
+                    .into_iter()
+                    .filter_map(|(name, v)| Some((name, v?)))
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn enumerate_values(
+        &self,
+        ty: &DeclaredType,
+        path: &mut Vec<String>,
+        candidates: &mut HashMap<EntityTypeName, Vec<EntityUid>>,
+    ) -> Result<Vec<RestrictedExpression>, Unbounded> {
+        match ty {
+            DeclaredType::Bool => Ok(vec![
+                RestrictedExpression::new_bool(false),
+                RestrictedExpression::new_bool(true),
+            ]),
+            DeclaredType::Entity(ty) => Ok(self
+                .entities_of_type(ty, candidates)
+                .iter()
+                .cloned()
+                .map(RestrictedExpression::new_entity_uid)
+                .collect()),
+            DeclaredType::EntityUnion(tys) => Ok(tys
+                .iter()
+                .flat_map(|ty| self.entities_of_type(ty, candidates).to_vec())
+                .map(RestrictedExpression::new_entity_uid)
+                .collect()),
+            DeclaredType::Record { attributes, .. } => Ok(self
+                .enumerate_record(attributes, path, candidates)?
+                .into_iter()
+                .map(|attrs| {
+                    #[expect(
+                        clippy::expect_used,
+                        reason = "the attributes of a record type are distinct"
+                    )]
+                    RestrictedExpression::new_record(attrs)
+                        .expect("the attributes of a record type are distinct")
+                })
+                .collect()),
+            DeclaredType::Long
+            | DeclaredType::String
+            | DeclaredType::Set(_)
+            | DeclaredType::Map(_)
+            | DeclaredType::Extension(_) => Err((path.clone(), ty.clone())),
+        }
+    }
+}
+
+/// The decisions of a policy set for every request of a [`RequestSpace`], as
+/// returned by [`RequestSpace::decision_table()`]
+#[derive(Debug, Clone)]
+pub struct DecisionTable {
+    rows: Vec<DecisionTableRow>,
+}
+
+impl DecisionTable {
+    /// Iterate over the rows of the table, one per request, in the order of
+    /// [`RequestSpace::requests()`]
+    pub fn rows(&self) -> impl Iterator<Item = &DecisionTableRow> {
+        self.rows.iter()
+    }
+
+    /// The number of rows of the table
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the table has no rows, because the request space is empty
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Iterate over the requests with the decision `decision`
+    pub fn requests_with_decision(&self, decision: Decision) -> impl Iterator<Item = &Request> {
+        self.rows
+            .iter()
+            .filter(move |row| row.decision() == decision)
+            .map(|row| &row.request)
+    }
+
+    /// Iterate over the rows for which authorization encountered errors
+    pub fn rows_with_errors(&self) -> impl Iterator<Item = &DecisionTableRow> {
+        self.rows
+            .iter()
+            .filter(|row| row.response.diagnostics().errors().next().is_some())
+    }
+}
+
+/// A request of a [`RequestSpace`] and the response of a policy set to it
+#[derive(Debug, Clone)]
+pub struct DecisionTableRow {
+    request: Request,
+    response: Response,
+}
+
+impl DecisionTableRow {
+    /// The request
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// The response to the request
+    pub fn response(&self) -> &Response {
+        &self.response
+    }
+
+    /// The decision for the request
+    pub fn decision(&self) -> Decision {
+        self.response.decision()
+    }
+}
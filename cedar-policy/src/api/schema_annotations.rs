@@ -0,0 +1,270 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines lookups of the annotations on any declaration in a
+//! [`SchemaFragment`] by its path, e.g., `App::User.address.street`.
+
+use super::{annotations_to_pairs, get_annotation_by_key, SchemaFragment};
+use cedar_policy_core::ast;
+use cedar_policy_core::est::Annotations;
+use cedar_policy_core::parser::unescape::to_unescaped_string;
+use cedar_policy_core::validator::json_schema::{
+    self, EntityTypeKind, NamespaceDefinition, RecordType, TypeVariant,
+};
+use cedar_policy_core::validator::RawName;
+use cedar_policy_core::FromNormalizedStr;
+use smol_str::SmolStr;
+
+type Namespace = NamespaceDefinition<RawName>;
+
+impl SchemaFragment {
+    /// Get the annotations of the declaration at `path` in this fragment.
+    ///
+    /// A path is written the way the declaration is referred to in policies
+    /// and schemas, optionally followed by a `.`-separated list of attributes:
+    ///
+    /// - `App` is the namespace `App`
+    /// - `App::User` is the entity type or common type `User` declared in
+    ///   namespace `App`, and `User` is one declared in the empty namespace
+    /// - `App::Action::"view"` is the action `view` declared in namespace `App`
+    /// - `App::User.address.street` is the attribute `street` of the record
+    ///   attribute `address` of `App::User`. Attributes of actions are
+    ///   attributes of their context. Attribute names which are not valid
+    ///   identifiers are quoted, e.g., `App::User."first name"`.
+    ///
+    /// Attributes are looked up through references to common types, e.g., if
+    /// `address` has the common type `Address`, `App::User.address.street` is
+    /// the attribute `street` of `Address`.
+    ///
+    /// ```
+    /// # use cedar_policy::SchemaFragment;
+    /// let (fragment, _) = SchemaFragment::from_cedarschema_str(r#"
+    ///     @route("eu")
+    ///     namespace App {
+    ///         type Address = { @pii street: String };
+    ///         entity User { @doc("Home address") address: Address };
+    ///         @route("audit-log")
+    ///         action view appliesTo { principal: User, resource: User, context: { @pii ip: ipaddr } };
+    ///     }
+    /// "#).unwrap();
+    /// assert_eq!(fragment.annotation_at("App", "route"), Some("eu"));
+    /// assert_eq!(fragment.annotation_at("App::User.address", "doc"), Some("Home address"));
+    /// assert_eq!(fragment.annotation_at("App::User.address.street", "pii"), Some(""));
+    /// assert_eq!(fragment.annotation_at(r#"App::Action::"view""#, "route"), Some("audit-log"));
+    /// assert_eq!(fragment.annotation_at(r#"App::Action::"view".ip"#, "pii"), Some(""));
+    /// assert!(fragment.annotations_at("App::Photo").is_none());
+    /// ```
+    ///
+    /// Returns `None` if `path` is not a valid path or there is no
+    /// declaration at `path` in this fragment.
+    pub fn annotations_at(&self, path: &str) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.annotations_by_path(path).map(annotations_to_pairs)
+    }
+
+    /// Get the value of the annotation `annotation_key` on the declaration at
+    /// `path` in this fragment. See [`SchemaFragment::annotations_at()`] for
+    /// the format of paths.
+    ///
+    /// Returns `None` if `path` is not a valid path, or there is no
+    /// declaration at `path` in this fragment, or `annotation_key` is not a
+    /// valid annotation key, or the declaration has no such annotation.
+    pub fn annotation_at(&self, path: &str, annotation_key: impl AsRef<str>) -> Option<&str> {
+        get_annotation_by_key(self.annotations_by_path(path)?, annotation_key)
+    }
+
+    fn annotations_by_path(&self, path: &str) -> Option<&Annotations> {
+        let (decl, attrs) = split_attributes(path)?;
+        if let Some(quote) = decl.find('"') {
+            let (action_type, id) = decl.split_at(quote);
+            let namespace = match action_type.strip_suffix("Action::")? {
+                "" => "",
+                namespace => namespace.strip_suffix("::")?,
+            };
+            let (ns, ns_def) = self.namespace(namespace)?;
+            let action = ns_def.actions.get(&parse_quoted(id)?)?;
+            return match attrs.split_first() {
+                None => Some(&action.annotations),
+                Some((attr, rest)) => {
+                    let context = &action.applies_to.as_ref()?.context;
+                    self.attribute(ns, &context.0, attr, rest)
+                }
+            };
+        }
+        if attrs.is_empty() {
+            if let Some((_, ns_def)) = self.namespace(decl) {
+                // Namespaces, other than the empty namespace, take precedence
+                // over declarations in the empty namespace with the same name
+                if !decl.is_empty() {
+                    return Some(&ns_def.annotations);
+                }
+            }
+        }
+        let (namespace, name) = decl.rsplit_once("::").unwrap_or(("", decl));
+        let (ns, ns_def) = self.namespace(namespace)?;
+        let name = ast::UnreservedId::from_normalized_str(name).ok()?;
+        if let Some(entity_type) = ns_def.entity_types.get(&name) {
+            return match attrs.split_first() {
+                None => Some(&entity_type.annotations),
+                Some((attr, rest)) => match &entity_type.kind {
+                    EntityTypeKind::Standard(ty) => self.attribute(ns, &ty.shape.0, attr, rest),
+                    EntityTypeKind::Enum { .. } => None,
+                },
+            };
+        }
+        let common_type = ns_def
+            .common_types
+            .get(&json_schema::CommonTypeId::new(name).ok()?)?;
+        match attrs.split_first() {
+            None => Some(&common_type.annotations),
+            Some((attr, rest)) => self.attribute(ns, &common_type.ty, attr, rest),
+        }
+    }
+
+    /// Get the namespace named `namespace`, where `""` is the empty namespace,
+    /// together with its name
+    fn namespace(&self, namespace: &str) -> Option<(Option<&ast::Name>, &Namespace)> {
+        let name = if namespace.is_empty() {
+            None
+        } else {
+            Some(namespace.parse::<ast::Name>().ok()?)
+        };
+        self.lossless
+            .0
+            .get_key_value(&name)
+            .map(|(name, ns_def)| (name.as_ref(), ns_def))
+    }
+
+    /// Get the annotations of the attribute `attr`, followed by the nested
+    /// attributes `rest`, of the record type `ty` declared in namespace `ns`
+    fn attribute<'a>(
+        &'a self,
+        ns: Option<&'a ast::Name>,
+        ty: &'a json_schema::Type<RawName>,
+        attr: &str,
+        rest: &[String],
+    ) -> Option<&'a Annotations> {
+        let (ns, record) = self.record(ns, ty)?;
+        let attr = record.attributes.get(attr)?;
+        match rest.split_first() {
+            None => Some(&attr.annotations),
+            Some((next, rest)) => self.attribute(ns, &attr.ty, next, rest),
+        }
+    }
+
+    /// Get the record type `ty` declared in namespace `ns`, following a
+    /// reference to a common type, together with the namespace the record
+    /// type is declared in
+    fn record<'a>(
+        &'a self,
+        ns: Option<&'a ast::Name>,
+        ty: &'a json_schema::Type<RawName>,
+    ) -> Option<(Option<&'a ast::Name>, &'a RecordType<RawName>)> {
+        let type_name = match ty {
+            json_schema::Type::Type {
+                ty: TypeVariant::Record(record),
+                ..
+            } => return Some((ns, record)),
+            json_schema::Type::Type {
+                ty: TypeVariant::EntityOrCommon { type_name },
+                ..
+            }
+            | json_schema::Type::CommonTypeRef { type_name, .. } => type_name,
+            json_schema::Type::Type { .. } => return None,
+        };
+        // An unqualified reference is to a common type declared in the same
+        // namespace, or else in the empty namespace
+        let type_name = type_name.to_string();
+        let candidates = match type_name.rsplit_once("::") {
+            Some((namespace, name)) => vec![(namespace.to_string(), name)],
+            None => vec![
+                (
+                    ns.map(ToString::to_string).unwrap_or_default(),
+                    type_name.as_str(),
+                ),
+                (String::new(), type_name.as_str()),
+            ],
+        };
+        candidates.into_iter().find_map(|(namespace, name)| {
+            let (ns, ns_def) = self.namespace(&namespace)?;
+            let id =
+                json_schema::CommonTypeId::new(ast::UnreservedId::from_normalized_str(name).ok()?)
+                    .ok()?;
+            self.record(ns, &ns_def.common_types.get(&id)?.ty)
+        })
+    }
+}
+
+/// Split a path into the declaration and the list of attributes following it
+fn split_attributes(path: &str) -> Option<(&str, Vec<String>)> {
+    // The declaration ends at the first `.` outside of a quoted action ID
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut end = path.len();
+    for (i, c) in path.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '.' if !in_quotes => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let (decl, mut rest) = path.split_at(end);
+    let mut attrs = Vec::new();
+    while let Some(attr) = rest.strip_prefix('.') {
+        let (name, remaining) = if attr.starts_with('"') {
+            let len = quoted_len(attr)?;
+            let (quoted, remaining) = attr.split_at(len);
+            (parse_quoted(quoted)?.to_string(), remaining)
+        } else {
+            let len = attr.find('.').unwrap_or(attr.len());
+            let (name, remaining) = attr.split_at(len);
+            (
+                ast::Id::from_normalized_str(name).ok()?.to_string(),
+                remaining,
+            )
+        };
+        attrs.push(name);
+        rest = remaining;
+    }
+    rest.is_empty().then_some((decl, attrs))
+}
+
+/// The length of the quoted string at the start of `s`, including the quotes
+fn quoted_len(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    s.char_indices().skip(1).find_map(|(i, c)| match c {
+        _ if escaped => {
+            escaped = false;
+            None
+        }
+        '\\' => {
+            escaped = true;
+            None
+        }
+        '"' => Some(Some(i + 1)),
+        _ => None,
+    })?
+}
+
+/// Parse a quoted string, with the escapes allowed in Cedar string literals
+fn parse_quoted(s: &str) -> Option<SmolStr> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    to_unescaped_string(inner).ok()
+}
@@ -0,0 +1,95 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`InferredSchema`], a draft schema inferred from the
+//! policies in a [`PolicySet`].
+
+use super::{PolicySet, SchemaFragment};
+use cedar_policy_core::validator::schema_inference;
+pub use cedar_policy_core::validator::schema_inference::{Confidence, InferenceNote};
+
+/// A draft schema inferred from a policy set by
+/// [`PolicySet::infer_schema()`], together with notes on how confident the
+/// inference is in each declaration
+#[derive(Debug, Clone)]
+pub struct InferredSchema {
+    fragment: SchemaFragment,
+    notes: Vec<InferenceNote>,
+}
+
+impl InferredSchema {
+    /// The inferred schema
+    pub fn fragment(&self) -> &SchemaFragment {
+        &self.fragment
+    }
+
+    /// Take the inferred schema, discarding the notes
+    pub fn into_fragment(self) -> SchemaFragment {
+        self.fragment
+    }
+
+    /// Iterate over the notes on the declarations of the inferred schema,
+    /// in the order the declarations appear in the schema, followed by notes
+    /// on attribute accesses which could not be attributed to any entity type
+    pub fn notes(&self) -> impl Iterator<Item = &InferenceNote> {
+        self.notes.iter()
+    }
+}
+
+impl PolicySet {
+    /// Infer a draft schema declaring the entity types, attributes, and
+    /// actions the policies in this policy set refer to, as a starting point
+    /// for validating policies which were written without a schema.
+    ///
+    /// The principal and resource types each action applies to are taken from
+    /// the scopes of the policies for that action. The types of attributes,
+    /// of entities and of the context, are approximated from how the policies
+    /// use them, e.g., an attribute compared with a string literal is a
+    /// `String`. Attributes are optional if a policy checks for them with
+    /// `has`, and required otherwise. The policies rarely determine the whole
+    /// schema, so each declaration comes with an [`InferenceNote`] giving the
+    /// [`Confidence`] of the inference.
+    ///
+    /// ```
+    /// # use cedar_policy::{Confidence, PolicySet};
+    /// # use std::str::FromStr;
+    /// let pset = PolicySet::from_str(r#"
+    ///     permit(principal is User, action == Action::"view", resource is Photo)
+    ///     when { resource.owner == principal || resource.public };
+    ///     permit(principal, action == Action::"delete", resource)
+    ///     when { principal.level > 2 };
+    /// "#).unwrap();
+    /// let inferred = pset.infer_schema();
+    /// let schema = inferred.fragment().to_cedarschema().unwrap();
+    /// assert!(schema.contains("owner: User"));
+    /// let doubtful: Vec<_> = inferred
+    ///     .notes()
+    ///     .filter(|note| note.confidence() == Confidence::Low)
+    ///     .map(|note| note.declaration())
+    ///     .collect();
+    /// assert_eq!(doubtful, [r#"Action::"delete""#, "principal.level"]);
+    /// ```
+    pub fn infer_schema(&self) -> InferredSchema {
+        let (fragment, notes) = schema_inference::infer_schema(&self.ast).into_parts();
+        #[expect(
+            clippy::expect_used,
+            reason = "inferred schemas only refer to the entity types they declare"
+        )]
+        let fragment =
+            SchemaFragment::try_from(fragment).expect("inferred schema should be a valid fragment");
+        InferredSchema { fragment, notes }
+    }
+}
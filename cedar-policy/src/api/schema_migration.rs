@@ -0,0 +1,509 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`SchemaVersion`], the version a schema declares with
+//! a `@version` annotation, and [`SchemaMigration`], which upgrades stored
+//! entity data from one version of a schema to the next.
+
+use super::{
+    schema_migration_errors, schema_version_errors, EntityTypeName, EntityUid, SchemaFragment,
+    SchemaMigrationError, SchemaVersionError,
+};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// The version of a schema, declared with a `@version("MAJOR.MINOR.PATCH")`
+/// annotation on its namespaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl SchemaVersion {
+    /// Construct the version `major.minor.patch`
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The major version
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    /// The minor version
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    /// The patch version
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+}
+
+impl Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = schema_version_errors::InvalidVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || schema_version_errors::InvalidVersionError {
+            version: s.to_string(),
+        };
+        let mut parts = s.split('.').map(|part| {
+            // Reject signs and leading zeros, which `u64::from_str` allows
+            if part.starts_with(|c: char| c.is_ascii_digit())
+                && (part == "0" || !part.starts_with('0'))
+            {
+                part.parse::<u64>().map_err(|_| invalid())
+            } else {
+                Err(invalid())
+            }
+        });
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(major), Some(minor), Some(patch), None) => Ok(Self::new(major?, minor?, patch?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl SchemaFragment {
+    /// Get the version this schema fragment declares with a
+    /// `@version("MAJOR.MINOR.PATCH")` annotation, or `None` if it declares
+    /// no version.
+    ///
+    /// Annotations are not allowed on the empty namespace, so the version is
+    /// declared on the named namespaces of the fragment. If several of them
+    /// declare a version, they must all declare the same one.
+    ///
+    /// ```
+    /// # use cedar_policy::{SchemaFragment, SchemaVersion};
+    /// # use std::str::FromStr;
+    /// let fragment = SchemaFragment::from_str(r#"
+    ///     @version("3.2.0")
+    ///     namespace App {
+    ///         entity User;
+    ///     }
+    /// "#).unwrap();
+    /// assert_eq!(fragment.version().unwrap(), Some(SchemaVersion::new(3, 2, 0)));
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if a version is not of the form `MAJOR.MINOR.PATCH`,
+    /// or if namespaces declare different versions.
+    pub fn version(&self) -> Result<Option<SchemaVersion>, SchemaVersionError> {
+        let mut version: Option<(SchemaVersion, String)> = None;
+        for (ns, def) in &self.lossless.0 {
+            let Some(declared) = super::get_annotation_by_key(&def.annotations, "version") else {
+                continue;
+            };
+            let declared: SchemaVersion = declared.parse()?;
+            let ns = ns.as_ref().map(ToString::to_string).unwrap_or_default();
+            match &version {
+                Some((first, first_ns)) if *first != declared => {
+                    return Err(schema_version_errors::ConflictingVersionsError {
+                        namespaces: (first_ns.clone(), ns),
+                        versions: (*first, declared),
+                    }
+                    .into());
+                }
+                Some(_) => {}
+                None => version = Some((declared, ns)),
+            }
+        }
+        Ok(version.map(|(version, _)| version))
+    }
+}
+
+/// A migration of stored entity data from one version of a schema to the
+/// next, e.g., to rename an attribute.
+///
+/// Migrations operate on entities in the Cedar JSON entity format, as entity
+/// data which conforms to one version of a schema usually can't be parsed
+/// with the next. [`EntityMigration`] implements the common migrations, and
+/// [`SchemaMigrations`] applies a sequence of migrations to upgrade entity
+/// data across several versions.
+pub trait SchemaMigration {
+    /// The version of the schema this migration upgrades entity data from
+    fn source_version(&self) -> SchemaVersion;
+
+    /// The version of the schema this migration upgrades entity data to
+    fn target_version(&self) -> SchemaVersion;
+
+    /// Upgrade a single entity, in the Cedar JSON entity format, in place
+    fn migrate_entity(&self, entity: &mut serde_json::Value) -> Result<(), SchemaMigrationError>;
+
+    /// Upgrade a list of entities, in the Cedar JSON entities format, in
+    /// place, by upgrading each entity with [`SchemaMigration::migrate_entity()`]
+    fn migrate_entities(
+        &self,
+        entities: &mut serde_json::Value,
+    ) -> Result<(), SchemaMigrationError> {
+        let entities =
+            entities
+                .as_array_mut()
+                .ok_or_else(|| schema_migration_errors::InvalidEntityError {
+                    reason: "expected a list of entities".into(),
+                })?;
+        entities
+            .iter_mut()
+            .try_for_each(|entity| self.migrate_entity(entity))
+    }
+}
+
+/// Converter used by [`EntityMigration::change_attribute_type()`]
+type Converter = Box<dyn Fn(serde_json::Value) -> Result<serde_json::Value, String>>;
+
+/// A step of an [`EntityMigration`]
+enum MigrationStep {
+    RenameAttribute {
+        entity_type: EntityTypeName,
+        from: String,
+        to: String,
+    },
+    ChangeAttributeType {
+        entity_type: EntityTypeName,
+        attr: String,
+        converter: Converter,
+    },
+}
+
+/// A [`SchemaMigration`] built from common changes to the attributes of
+/// entity types, which are applied to each entity in the order they were
+/// added
+///
+/// ```
+/// # use cedar_policy::{EntityMigration, SchemaMigration, SchemaVersion};
+/// # use serde_json::json;
+/// let migration = EntityMigration::new(SchemaVersion::new(1, 0, 0), SchemaVersion::new(2, 0, 0))
+///     .rename_attribute("User".parse().unwrap(), "name", "fullName")
+///     .change_attribute_type("User".parse().unwrap(), "age", |age| match age.as_str() {
+///         Some(age) => age.parse::<i64>().map(Into::into).map_err(|err| err.to_string()),
+///         None => Err("expected a string".into()),
+///     });
+/// let mut entities = json!([
+///     { "uid": { "type": "User", "id": "alice" }, "attrs": { "name": "Alice", "age": "42" }, "parents": [] },
+/// ]);
+/// migration.migrate_entities(&mut entities).unwrap();
+/// assert_eq!(entities[0]["attrs"], json!({ "fullName": "Alice", "age": 42 }));
+/// ```
+pub struct EntityMigration {
+    from: SchemaVersion,
+    to: SchemaVersion,
+    steps: Vec<MigrationStep>,
+}
+
+impl std::fmt::Debug for EntityMigration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityMigration")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("steps", &self.steps.len())
+            .finish()
+    }
+}
+
+impl EntityMigration {
+    /// Construct a migration from schema version `from` to `to`, which
+    /// doesn't change any entities until steps are added to it
+    pub fn new(from: SchemaVersion, to: SchemaVersion) -> Self {
+        Self {
+            from,
+            to,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Rename the attribute `from` of entities of type `entity_type` to `to`.
+    /// Entities which don't have the attribute are unchanged.
+    #[must_use]
+    pub fn rename_attribute(
+        mut self,
+        entity_type: EntityTypeName,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        self.steps.push(MigrationStep::RenameAttribute {
+            entity_type,
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// Change the type of the attribute `attr` of entities of type
+    /// `entity_type`, converting its value, in the Cedar JSON value format,
+    /// with `converter`. Entities which don't have the attribute are
+    /// unchanged. If `converter` returns an error message, the migration fails
+    /// with a [`SchemaMigrationError::Conversion`] error.
+    #[must_use]
+    pub fn change_attribute_type(
+        mut self,
+        entity_type: EntityTypeName,
+        attr: impl Into<String>,
+        converter: impl Fn(serde_json::Value) -> Result<serde_json::Value, String> + 'static,
+    ) -> Self {
+        self.steps.push(MigrationStep::ChangeAttributeType {
+            entity_type,
+            attr: attr.into(),
+            converter: Box::new(converter),
+        });
+        self
+    }
+}
+
+impl SchemaMigration for EntityMigration {
+    fn source_version(&self) -> SchemaVersion {
+        self.from
+    }
+
+    fn target_version(&self) -> SchemaVersion {
+        self.to
+    }
+
+    fn migrate_entity(&self, entity: &mut serde_json::Value) -> Result<(), SchemaMigrationError> {
+        let invalid = |reason: &str| schema_migration_errors::InvalidEntityError {
+            reason: reason.into(),
+        };
+        let entity = entity
+            .as_object_mut()
+            .ok_or_else(|| invalid("expected an entity to be a JSON object"))?;
+        let uid = entity
+            .get("uid")
+            .cloned()
+            .ok_or_else(|| invalid("expected an entity to have a `uid`"))?;
+        let uid = EntityUid::from_json(uid).map_err(|err| invalid(&err.to_string()))?;
+        let Some(attrs) = entity.get_mut("attrs") else {
+            return Ok(());
+        };
+        let attrs = attrs
+            .as_object_mut()
+            .ok_or_else(|| invalid("expected the `attrs` of an entity to be a JSON object"))?;
+        for step in &self.steps {
+            match step {
+                MigrationStep::RenameAttribute {
+                    entity_type,
+                    from,
+                    to,
+                } if entity_type == uid.type_name() => {
+                    if let Some(value) = attrs.remove(from) {
+                        attrs.insert(to.clone(), value);
+                    }
+                }
+                MigrationStep::ChangeAttributeType {
+                    entity_type,
+                    attr,
+                    converter,
+                } if entity_type == uid.type_name() => {
+                    if let Some(value) = attrs.get_mut(attr) {
+                        *value = converter(value.take()).map_err(|reason| {
+                            schema_migration_errors::ConversionError {
+                                uid: uid.clone(),
+                                attr: attr.clone(),
+                                reason,
+                            }
+                        })?;
+                    }
+                }
+                MigrationStep::RenameAttribute { .. }
+                | MigrationStep::ChangeAttributeType { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of [`SchemaMigration`]s, which upgrades entity data across
+/// several versions of a schema by applying the migration from each version
+/// to the next
+///
+/// ```
+/// # use cedar_policy::{EntityMigration, SchemaMigrations, SchemaVersion};
+/// # use serde_json::json;
+/// let (v1, v2, v3) = (SchemaVersion::new(1, 0, 0), SchemaVersion::new(1, 1, 0), SchemaVersion::new(2, 0, 0));
+/// let migrations = SchemaMigrations::new()
+///     .with(EntityMigration::new(v1, v2).rename_attribute("User".parse().unwrap(), "name", "fullName"))
+///     .with(EntityMigration::new(v2, v3).rename_attribute("User".parse().unwrap(), "fullName", "displayName"));
+/// let mut entities = json!([
+///     { "uid": { "type": "User", "id": "alice" }, "attrs": { "name": "Alice" }, "parents": [] },
+/// ]);
+/// migrations.migrate_entities(&mut entities, v1, v3).unwrap();
+/// assert_eq!(entities[0]["attrs"], json!({ "displayName": "Alice" }));
+/// ```
+#[derive(Default)]
+pub struct SchemaMigrations {
+    migrations: Vec<Box<dyn SchemaMigration>>,
+}
+
+impl std::fmt::Debug for SchemaMigrations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(
+                self.migrations
+                    .iter()
+                    .map(|m| format!("{} -> {}", m.source_version(), m.target_version())),
+            )
+            .finish()
+    }
+}
+
+impl SchemaMigrations {
+    /// Construct an empty sequence of migrations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a migration to the sequence
+    #[must_use]
+    pub fn with(mut self, migration: impl SchemaMigration + 'static) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// Upgrade a list of entities, in the Cedar JSON entities format, in place
+    /// from schema version `from` to `to`, by applying the migration from
+    /// `from` to the next version, then the migration from that version, and
+    /// so on until reaching `to`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`SchemaMigrationError::MissingMigration`] error if there is
+    /// no migration from one of the versions to a later version which is not
+    /// after `to`. If any migration fails, `entities` may be partially
+    /// migrated.
+    pub fn migrate_entities(
+        &self,
+        entities: &mut serde_json::Value,
+        from: SchemaVersion,
+        to: SchemaVersion,
+    ) -> Result<(), SchemaMigrationError> {
+        let mut version = from;
+        while version != to {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| {
+                    m.source_version() == version
+                        && m.target_version() > version
+                        && m.target_version() <= to
+                })
+                .ok_or(schema_migration_errors::MissingMigrationError { from: version, to })?;
+            migration.migrate_entities(entities)?;
+            version = migration.target_version();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SchemaFragment;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(
+            "3.2.0".parse::<SchemaVersion>().unwrap(),
+            SchemaVersion::new(3, 2, 0)
+        );
+        assert_eq!(SchemaVersion::new(10, 0, 1).to_string(), "10.0.1");
+        for invalid in ["3.2", "3.2.0.1", "3.2.x", "3.02.0", "+3.2.0", "", "3..0"] {
+            assert_matches!(invalid.parse::<SchemaVersion>(), Err(_), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn fragment_versions() {
+        let fragment = SchemaFragment::from_str("entity User;").unwrap();
+        assert_matches!(fragment.version(), Ok(None));
+        let fragment = SchemaFragment::from_str(
+            r#"
+            @version("1.0.0") namespace A { entity User; }
+            @version("1.0.0") namespace B { entity User; }
+            namespace C { entity User; }
+            "#,
+        )
+        .unwrap();
+        assert_matches!(fragment.version(), Ok(Some(v)) => assert_eq!(v, SchemaVersion::new(1, 0, 0)));
+        let fragment = SchemaFragment::from_str(
+            r#"
+            @version("1.0.0") namespace A { entity User; }
+            @version("2.0.0") namespace B { entity User; }
+            "#,
+        )
+        .unwrap();
+        assert_matches!(fragment.version(), Err(SchemaVersionError::ConflictingVersions(e)) => {
+            assert_eq!(e.namespaces(), ("A", "B"));
+        });
+        let fragment =
+            SchemaFragment::from_str(r#"@version("one") namespace A { entity User; }"#).unwrap();
+        assert_matches!(fragment.version(), Err(SchemaVersionError::InvalidVersion(e)) => {
+            assert_eq!(e.version(), "one");
+        });
+    }
+
+    #[test]
+    fn migration_errors() {
+        let v1 = SchemaVersion::new(1, 0, 0);
+        let v2 = SchemaVersion::new(2, 0, 0);
+        let v3 = SchemaVersion::new(3, 0, 0);
+        let migrations =
+            SchemaMigrations::new().with(EntityMigration::new(v1, v2).change_attribute_type(
+                "User".parse().unwrap(),
+                "age",
+                |age| {
+                    age.as_str()
+                        .map(Into::into)
+                        .ok_or_else(|| "not a string".into())
+                },
+            ));
+        let mut entities = json!([
+            { "uid": { "__entity": { "type": "User", "id": "alice" } }, "attrs": { "age": "42" }, "parents": [] },
+            { "uid": { "type": "Photo", "id": "beach" }, "attrs": { "age": 3 }, "parents": [] },
+        ]);
+        migrations.migrate_entities(&mut entities, v1, v2).unwrap();
+        assert_matches!(
+            migrations.migrate_entities(&mut entities, v2, v3),
+            Err(SchemaMigrationError::MissingMigration(e)) => {
+                assert_eq!(e.source_version(), v2);
+            }
+        );
+        assert_matches!(
+            migrations.migrate_entities(&mut json!([{ "uid": { "type": "User", "id": "bob" }, "attrs": { "age": 42 } }]), v1, v2),
+            Err(SchemaMigrationError::Conversion(e)) => {
+                assert_eq!(e.uid().to_string(), r#"User::"bob""#);
+                assert_eq!(e.attr(), "age");
+            }
+        );
+        assert_matches!(
+            migrations.migrate_entities(&mut json!({}), v1, v2),
+            Err(SchemaMigrationError::InvalidEntity(_))
+        );
+    }
+}
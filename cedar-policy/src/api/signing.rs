@@ -0,0 +1,167 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines detached signatures for stored policy sets.
+//!
+//! Cedar does not implement any signature scheme itself. Instead, the bytes to
+//! sign are given by [`PolicySet::canonical_bytes()`], the signature produced
+//! by the caller's signing key is kept as a [`PolicySetSignature`] alongside
+//! the stored policy set, and signatures are checked with a caller-provided
+//! [`SignatureVerifier`] when the policy set is loaded.
+
+use super::{
+    policy_set_errors::{JsonPolicySetError, SignatureVerificationError},
+    PolicySet, PolicySetError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A detached signature over the [`PolicySet::canonical_bytes()`] of a policy
+/// set, made with the key identified by `key_id`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySetSignature {
+    key_id: String,
+    signature: Vec<u8>,
+}
+
+impl PolicySetSignature {
+    /// Create a signature made with the key identified by `key_id`
+    pub fn new(key_id: impl Into<String>, signature: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            signature: signature.into(),
+        }
+    }
+
+    /// The id of the key the signature was made with
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// The signature bytes
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+}
+
+/// Checks [`PolicySetSignature`]s, e.g., against a set of trusted public keys
+///
+/// This is implemented for closures taking the signed bytes and the signature.
+pub trait SignatureVerifier {
+    /// Returns `true` if `signature` is a valid signature of `message` made
+    /// with a trusted key. Should return `false` for signatures made with
+    /// unknown keys.
+    fn verify(&self, message: &[u8], signature: &PolicySetSignature) -> bool;
+}
+
+impl<F: Fn(&[u8], &PolicySetSignature) -> bool> SignatureVerifier for F {
+    fn verify(&self, message: &[u8], signature: &PolicySetSignature) -> bool {
+        self(message, signature)
+    }
+}
+
+impl PolicySet {
+    /// The canonical byte representation of this policy set, which is what
+    /// a [`PolicySetSignature`] signs.
+    ///
+    /// This is the compact form of [`PolicySet::store_json()`] with object
+    /// keys, template links, and disabled ids sorted, so policy sets which
+    /// store the same policies, templates, links, metadata, and disabled
+    /// policies have the same canonical bytes regardless of the order in
+    /// which they were built, and a stored policy set has the same canonical
+    /// bytes after it is loaded again.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, PolicySetError> {
+        let mut value = self.store_json()?;
+        // Links are sorted by their id, and disabled ids by themselves
+        let sort_key = |item: &Value| {
+            item.get("newId")
+                .unwrap_or(item)
+                .as_str()
+                .map(str::to_owned)
+        };
+        for array in ["/policySet/templateLinks", "/disabled"] {
+            if let Some(Value::Array(items)) = value.pointer_mut(array) {
+                items.sort_by_key(sort_key);
+            }
+        }
+        serde_json::to_vec(&sort_keys(value)).map_err(|inner| JsonPolicySetError { inner }.into())
+    }
+
+    /// Check that at least one of `signatures` is a valid signature of the
+    /// [`PolicySet::canonical_bytes()`] of this policy set according to
+    /// `verifier`, and return the first such signature
+    pub fn verify_signatures<'a>(
+        &self,
+        signatures: &'a [PolicySetSignature],
+        verifier: &impl SignatureVerifier,
+    ) -> Result<&'a PolicySetSignature, PolicySetError> {
+        let message = self.canonical_bytes()?;
+        signatures
+            .iter()
+            .find(|signature| verifier.verify(&message, signature))
+            .ok_or_else(|| {
+                SignatureVerificationError {
+                    signatures: signatures.len(),
+                }
+                .into()
+            })
+    }
+
+    /// Load a policy set stored with [`PolicySet::store_json()`], as
+    /// [`PolicySet::load_json()`] does, and then check it against its
+    /// detached `signatures` with [`PolicySet::verify_signatures()`]
+    pub fn load_json_verified(
+        value: Value,
+        signatures: &[PolicySetSignature],
+        verifier: &impl SignatureVerifier,
+    ) -> Result<Self, PolicySetError> {
+        let pset = Self::load_json(value)?;
+        pset.verify_signatures(signatures, verifier)?;
+        Ok(pset)
+    }
+
+    /// Load a policy set stored with [`PolicySet::store_binary()`], as
+    /// [`PolicySet::load_binary()`] does, and then check it against its
+    /// detached `signatures` with [`PolicySet::verify_signatures()`]
+    pub fn load_binary_verified(
+        bytes: &[u8],
+        signatures: &[PolicySetSignature],
+        verifier: &impl SignatureVerifier,
+    ) -> Result<Self, PolicySetError> {
+        let pset = Self::load_binary(bytes)?;
+        pset.verify_signatures(signatures, verifier)?;
+        Ok(pset)
+    }
+}
+
+/// Recursively sort the keys of every object in `value`
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
@@ -0,0 +1,405 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`EntitiesSnapshot`] and [`EntitiesWriter`], which
+//! support a read-mostly concurrency pattern for [`Entities`]: readers hold
+//! cheap, immutable snapshots while a writer publishes new ones.
+//! [`PolicySetSnapshot`] and [`PolicyStore`] support the same pattern for
+//! [`PolicySet`], with compare-and-swap updates and rollback.
+
+use super::{Authorizer, Entities, PolicySet, PolicyStoreError, Request, Response};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// An immutable, cheaply clonable snapshot of an [`Entities`].
+///
+/// Cloning a snapshot only clones an [`Arc`]. Snapshots are obtained from an
+/// [`EntitiesWriter`], or created directly with [`EntitiesSnapshot::new()`],
+/// and dereference to [`Entities`], so they can be passed anywhere an
+/// `&Entities` is expected (e.g., to [`crate::Authorizer::is_authorized()`]).
+#[derive(Debug, Clone, Default)]
+pub struct EntitiesSnapshot {
+    entities: Arc<Entities>,
+    version: u64,
+}
+
+impl EntitiesSnapshot {
+    /// Create a snapshot of `entities`, with version 0
+    pub fn new(entities: Entities) -> Self {
+        Self {
+            entities: Arc::new(entities),
+            version: 0,
+        }
+    }
+
+    /// The [`Entities`] in this snapshot
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// The version of this snapshot. Each snapshot published by an
+    /// [`EntitiesWriter`] has a version one greater than the previous one.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` if `self` and `other` are the same snapshot, i.e., share
+    /// the same underlying [`Entities`]. This is cheaper than comparing the
+    /// entities for equality.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.entities, &other.entities)
+    }
+}
+
+impl std::ops::Deref for EntitiesSnapshot {
+    type Target = Entities;
+
+    fn deref(&self) -> &Entities {
+        &self.entities
+    }
+}
+
+impl AsRef<Entities> for EntitiesSnapshot {
+    fn as_ref(&self) -> &Entities {
+        &self.entities
+    }
+}
+
+impl From<Entities> for EntitiesSnapshot {
+    fn from(entities: Entities) -> Self {
+        Self::new(entities)
+    }
+}
+
+/// Publishes new [`EntitiesSnapshot`]s for concurrent readers.
+///
+/// An `EntitiesWriter` can be shared (e.g., in an [`Arc`]) between readers,
+/// which call [`EntitiesWriter::snapshot()`] to obtain the latest snapshot,
+/// and writers, which call [`EntitiesWriter::update()`] to publish a new one.
+/// Readers are never blocked while an update is being computed, and existing
+/// snapshots are unaffected by updates. Updates are serialized, so no update
+/// is lost.
+///
+/// ```
+/// # use cedar_policy::{Entities, EntitiesWriter, Entity, EntityUid};
+/// # use std::str::FromStr;
+/// let writer = EntitiesWriter::new(Entities::empty());
+/// let before = writer.snapshot();
+/// let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+/// let after = writer
+///     .update(|entities| entities.add_entities([Entity::with_uid(alice)], None))
+///     .unwrap();
+/// assert_eq!(before.len(), 0);
+/// assert_eq!(after.len(), 1);
+/// assert_eq!(after.version(), before.version() + 1);
+/// assert!(writer.snapshot().ptr_eq(&after));
+/// ```
+#[derive(Debug, Default)]
+pub struct EntitiesWriter {
+    /// The latest published snapshot. This lock is only held long enough to
+    /// clone or replace the snapshot.
+    current: RwLock<EntitiesSnapshot>,
+    /// Held for the duration of an update, so that concurrent updates are
+    /// applied one after another
+    update_lock: Mutex<()>,
+}
+
+impl EntitiesWriter {
+    /// Create a writer whose initial snapshot contains `entities`
+    pub fn new(entities: Entities) -> Self {
+        Self::from_snapshot(EntitiesSnapshot::new(entities))
+    }
+
+    /// Create a writer whose initial snapshot is `snapshot`
+    pub fn from_snapshot(snapshot: EntitiesSnapshot) -> Self {
+        Self {
+            current: RwLock::new(snapshot),
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Get the latest published snapshot
+    pub fn snapshot(&self) -> EntitiesSnapshot {
+        // A panic while the lock is held cannot leave the snapshot in an
+        // inconsistent state, so it is safe to ignore poisoning
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Compute a new [`Entities`] from (a copy of) the latest snapshot's
+    /// entities using `f`, and publish it as the latest snapshot.
+    ///
+    /// Copying an [`Entities`] does not copy the individual entities, which
+    /// are shared between snapshots. Readers continue to see the previous
+    /// snapshot while `f` runs. If `f` returns an error, nothing is published.
+    pub fn update<E>(
+        &self,
+        f: impl FnOnce(Entities) -> Result<Entities, E>,
+    ) -> Result<EntitiesSnapshot, E> {
+        let _guard = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let previous = self.snapshot();
+        let snapshot = EntitiesSnapshot {
+            entities: Arc::new(f(Entities::clone(&previous))?),
+            version: previous.version + 1,
+        };
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = snapshot.clone();
+        Ok(snapshot)
+    }
+
+    /// Publish `entities` as the latest snapshot, replacing the previous one
+    pub fn replace(&self, entities: Entities) -> EntitiesSnapshot {
+        match self.update(|_| Ok::<_, std::convert::Infallible>(entities)) {
+            Ok(snapshot) => snapshot,
+            Err(infallible) => match infallible {},
+        }
+    }
+}
+
+/// An immutable, cheaply clonable snapshot of a [`PolicySet`], with a version.
+///
+/// Snapshots are obtained from a [`PolicyStore`], or created directly with
+/// [`PolicySetSnapshot::new()`], and dereference to [`PolicySet`].
+#[derive(Debug, Clone, Default)]
+pub struct PolicySetSnapshot {
+    policies: Arc<PolicySet>,
+    version: u64,
+}
+
+impl PolicySetSnapshot {
+    /// Create a snapshot of `policies`, with version 0
+    pub fn new(policies: PolicySet) -> Self {
+        Self {
+            policies: Arc::new(policies),
+            version: 0,
+        }
+    }
+
+    /// The [`PolicySet`] in this snapshot
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    /// The version of this snapshot. Each snapshot published by a
+    /// [`PolicyStore`] has a version one greater than the previous one.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` if `self` and `other` are the same snapshot, i.e., share
+    /// the same underlying [`PolicySet`]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.policies, &other.policies)
+    }
+
+    /// Authorize `request` against the policies in this snapshot. The
+    /// snapshot's version is reported by
+    /// [`crate::Diagnostics::policy_set_version()`], so that decisions can be
+    /// traced back to the policies that made them.
+    pub fn is_authorized(
+        &self,
+        authorizer: &Authorizer,
+        request: &Request,
+        entities: &Entities,
+    ) -> Response {
+        let mut response = authorizer.is_authorized(request, &self.policies, entities);
+        response.diagnostics.policy_set_version = Some(self.version);
+        response
+    }
+}
+
+impl std::ops::Deref for PolicySetSnapshot {
+    type Target = PolicySet;
+
+    fn deref(&self) -> &PolicySet {
+        &self.policies
+    }
+}
+
+impl AsRef<PolicySet> for PolicySetSnapshot {
+    fn as_ref(&self) -> &PolicySet {
+        &self.policies
+    }
+}
+
+impl From<PolicySet> for PolicySetSnapshot {
+    fn from(policies: PolicySet) -> Self {
+        Self::new(policies)
+    }
+}
+
+/// Publishes new [`PolicySetSnapshot`]s for concurrent readers, and retains
+/// recent snapshots for rollback.
+///
+/// Like [`EntitiesWriter`], readers call [`PolicyStore::snapshot()`] and are
+/// never blocked while an update is being computed, and updates are applied
+/// one after another. Every published snapshot has a version one greater than
+/// the previous one. [`PolicyStore::compare_and_swap()`] only publishes if the
+/// store is still at the version the caller expects, and
+/// [`PolicyStore::rollback()`] republishes the policies of a retained snapshot.
+///
+/// ```
+/// # use cedar_policy::{PolicySet, PolicyStore};
+/// # use std::str::FromStr;
+/// let store = PolicyStore::new(PolicySet::new());
+/// let v0 = store.snapshot();
+/// let permit_all = PolicySet::from_str("permit(principal, action, resource);").unwrap();
+/// let v1 = store.compare_and_swap(v0.version(), permit_all.clone()).unwrap();
+/// assert_eq!(v1.version(), 1);
+/// // A stale update is rejected
+/// assert!(store.compare_and_swap(v0.version(), permit_all).is_err());
+/// // Rolling back publishes the old policies as a new version
+/// let v2 = store.rollback(v0.version()).unwrap();
+/// assert_eq!(v2.version(), 2);
+/// assert!(v2.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct PolicyStore {
+    /// The latest published snapshot. This lock is only held long enough to
+    /// clone or replace the snapshot.
+    current: RwLock<PolicySetSnapshot>,
+    /// Held for the duration of an update. Guards the previously published
+    /// snapshots that are retained for rollback, oldest first.
+    history: Mutex<VecDeque<PolicySetSnapshot>>,
+    /// The maximum number of previous snapshots to retain
+    retain: usize,
+}
+
+impl Default for PolicyStore {
+    fn default() -> Self {
+        Self::new(PolicySet::new())
+    }
+}
+
+impl PolicyStore {
+    /// The number of previous snapshots retained by [`PolicyStore::new()`]
+    pub const DEFAULT_RETAINED_SNAPSHOTS: usize = 16;
+
+    /// Create a store whose initial snapshot contains `policies`, retaining
+    /// [`PolicyStore::DEFAULT_RETAINED_SNAPSHOTS`] previous snapshots
+    pub fn new(policies: PolicySet) -> Self {
+        Self::with_retention(policies, Self::DEFAULT_RETAINED_SNAPSHOTS)
+    }
+
+    /// Create a store whose initial snapshot contains `policies`, retaining up
+    /// to `retain` previous snapshots for rollback
+    pub fn with_retention(policies: PolicySet, retain: usize) -> Self {
+        Self {
+            current: RwLock::new(PolicySetSnapshot::new(policies)),
+            history: Mutex::new(VecDeque::new()),
+            retain,
+        }
+    }
+
+    /// Get the latest published snapshot
+    pub fn snapshot(&self) -> PolicySetSnapshot {
+        // A panic while the lock is held cannot leave the snapshot in an
+        // inconsistent state, so it is safe to ignore poisoning
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The previous snapshots that are retained for rollback, oldest first
+    pub fn history(&self) -> Vec<PolicySetSnapshot> {
+        self.lock_history().iter().cloned().collect()
+    }
+
+    /// Compute a new [`PolicySet`] from (a copy of) the latest snapshot's
+    /// policies using `f`, and publish it as the latest snapshot. If `f`
+    /// returns an error, nothing is published.
+    pub fn update<E>(
+        &self,
+        f: impl FnOnce(PolicySet) -> Result<PolicySet, E>,
+    ) -> Result<PolicySetSnapshot, E> {
+        let mut history = self.lock_history();
+        let previous = self.snapshot();
+        let policies = f(PolicySet::clone(&previous))?;
+        Ok(self.publish(&mut history, previous, Arc::new(policies)))
+    }
+
+    /// Publish `policies` as the latest snapshot, but only if the latest
+    /// snapshot has version `expected_version`
+    pub fn compare_and_swap(
+        &self,
+        expected_version: u64,
+        policies: PolicySet,
+    ) -> Result<PolicySetSnapshot, PolicyStoreError> {
+        let mut history = self.lock_history();
+        let previous = self.snapshot();
+        if previous.version != expected_version {
+            return Err(PolicyStoreError::VersionMismatch {
+                expected: expected_version,
+                actual: previous.version,
+            });
+        }
+        Ok(self.publish(&mut history, previous, Arc::new(policies)))
+    }
+
+    /// Publish `policies` as the latest snapshot, replacing the previous one
+    pub fn replace(&self, policies: PolicySet) -> PolicySetSnapshot {
+        let mut history = self.lock_history();
+        let previous = self.snapshot();
+        self.publish(&mut history, previous, Arc::new(policies))
+    }
+
+    /// Publish the policies of the retained snapshot with version `version`
+    /// as the latest snapshot. The published snapshot has a new version, so
+    /// versions keep increasing across rollbacks.
+    pub fn rollback(&self, version: u64) -> Result<PolicySetSnapshot, PolicyStoreError> {
+        let mut history = self.lock_history();
+        let previous = self.snapshot();
+        let policies = if previous.version == version {
+            Arc::clone(&previous.policies)
+        } else {
+            history
+                .iter()
+                .find(|snapshot| snapshot.version == version)
+                .map(|snapshot| Arc::clone(&snapshot.policies))
+                .ok_or(PolicyStoreError::NotRetained { version })?
+        };
+        Ok(self.publish(&mut history, previous, policies))
+    }
+
+    fn lock_history(&self) -> std::sync::MutexGuard<'_, VecDeque<PolicySetSnapshot>> {
+        self.history.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Publish `policies` as the successor of `previous`, which must be the
+    /// latest snapshot. Must be called with the `history` lock held.
+    fn publish(
+        &self,
+        history: &mut VecDeque<PolicySetSnapshot>,
+        previous: PolicySetSnapshot,
+        policies: Arc<PolicySet>,
+    ) -> PolicySetSnapshot {
+        let snapshot = PolicySetSnapshot {
+            policies,
+            version: previous.version + 1,
+        };
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = snapshot.clone();
+        history.push_back(previous);
+        while history.len() > self.retain {
+            history.pop_front();
+        }
+        snapshot
+    }
+}
@@ -0,0 +1,301 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`SpecializationCache`], which partially evaluates a
+//! policy set once for each principal (or resource) it sees, and reuses the
+//! residual policies to authorize later requests for the same principal (or
+//! resource).
+
+use super::{
+    Authorizer, EntitiesSnapshot, EntityUid, PartialResponse, PolicySetSnapshot, Request,
+    RequestBuilder, Response,
+};
+use cedar_policy_core::ast;
+use ref_cast::RefCast;
+use smol_str::SmolStr;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The request variable that a [`SpecializedPolicySet`] fixes
+#[doc = include_str!("../../experimental_warning.md")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecializedVariable {
+    /// The principal is fixed, and the action, resource, and context are
+    /// unknown
+    Principal,
+    /// The resource is fixed, and the principal, action, and context are
+    /// unknown
+    Resource,
+}
+
+impl SpecializedVariable {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Principal => "principal",
+            Self::Resource => "resource",
+        }
+    }
+
+    /// The value of this variable in `request`, if it is known
+    fn of(self, request: &Request) -> Option<&ast::EntityUID> {
+        match self {
+            Self::Principal => request.0.principal().uid(),
+            Self::Resource => request.0.resource().uid(),
+        }
+    }
+}
+
+/// A policy set partially evaluated with one request variable fixed.
+/// Returned by [`SpecializationCache::specialize()`].
+#[doc = include_str!("../../experimental_warning.md")]
+#[derive(Debug, Clone)]
+pub struct SpecializedPolicySet {
+    variable: SpecializedVariable,
+    uid: EntityUid,
+    residuals: PartialResponse,
+}
+
+impl SpecializedPolicySet {
+    /// Partially evaluate `policies` with `variable` fixed to `uid`
+    fn new(
+        variable: SpecializedVariable,
+        uid: EntityUid,
+        policies: &PolicySetSnapshot,
+        entities: &EntitiesSnapshot,
+    ) -> Self {
+        let builder = RequestBuilder::default();
+        let request = match variable {
+            SpecializedVariable::Principal => builder.principal(uid.clone()),
+            SpecializedVariable::Resource => builder.resource(uid.clone()),
+        }
+        .build();
+        let residuals = Authorizer::new().is_authorized_partial(&request, policies, entities);
+        Self {
+            variable,
+            uid,
+            residuals,
+        }
+    }
+
+    /// The request variable which is fixed
+    pub fn variable(&self) -> SpecializedVariable {
+        self.variable
+    }
+
+    /// The value that the variable is fixed to
+    pub fn uid(&self) -> &EntityUid {
+        &self.uid
+    }
+
+    /// The result of partially evaluating the policy set, whose residuals are
+    /// the specialized policies
+    pub fn residuals(&self) -> &PartialResponse {
+        &self.residuals
+    }
+
+    /// Authorize `request` against the residual policies, with respect to
+    /// `entities`, which should be the entities the policy set was
+    /// specialized for. Returns `None` if `request` doesn't fix the same
+    /// variable to the same value, or has any unknowns.
+    pub fn is_authorized(
+        &self,
+        request: &Request,
+        entities: &EntitiesSnapshot,
+    ) -> Option<Response> {
+        if self.variable.of(request)? != &self.uid.0 {
+            return None;
+        }
+        let mut mapping: HashMap<SmolStr, ast::Value> = HashMap::new();
+        for (name, uid) in [
+            ("principal", request.0.principal().uid()),
+            ("action", request.0.action().uid()),
+            ("resource", request.0.resource().uid()),
+        ] {
+            if name != self.variable.name() {
+                mapping.insert(name.into(), uid?.clone().into());
+            }
+        }
+        let context = match request.0.context()? {
+            ast::Context::Value(attrs) => ast::Value::record_arc(Arc::clone(attrs), None),
+            ast::Context::RestrictedResidual(_) => return None,
+        };
+        mapping.insert("context".into(), context);
+
+        let authorizer = Authorizer::new();
+        let mut response = self
+            .residuals
+            .0
+            .reauthorize(&mapping, &authorizer.0, &entities.0)
+            .ok()?;
+        // Policies which errored during specialization are `false` in the
+        // residuals, so their errors are only recorded here
+        response
+            .errors
+            .extend(self.residuals.0.errors.iter().cloned());
+        Some(response.concretize().into())
+    }
+}
+
+/// The specializations computed for one policy set and entities snapshot
+#[derive(Debug, Default)]
+struct CacheEntries {
+    policies: Option<PolicySetSnapshot>,
+    entities: Option<EntitiesSnapshot>,
+    specialized: HashMap<EntityUid, Arc<SpecializedPolicySet>>,
+}
+
+impl CacheEntries {
+    /// Drop every specialization if `policies` or `entities` is not the
+    /// snapshot they were computed for
+    fn invalidate_unless(&mut self, policies: &PolicySetSnapshot, entities: &EntitiesSnapshot) {
+        let current = self.policies.as_ref().is_some_and(|p| p.ptr_eq(policies))
+            && self.entities.as_ref().is_some_and(|e| e.ptr_eq(entities));
+        if !current {
+            self.policies = Some(policies.clone());
+            self.entities = Some(entities.clone());
+            self.specialized.clear();
+        }
+    }
+}
+
+/// Caches a [`SpecializedPolicySet`] for each principal (or resource).
+///
+/// A policy set is partially evaluated once per principal that requests are
+/// authorized for, rather than fully evaluated for every request.
+///
+/// The cache is keyed on the [`PolicySetSnapshot`] and [`EntitiesSnapshot`]
+/// it is given: when it is given a different snapshot of either (e.g., after
+/// a [`crate::PolicyStore`] update), every cached specialization is dropped.
+/// The cache can be shared between threads.
+///
+/// ```
+/// # use cedar_policy::{Context, EntitiesSnapshot, EntityUid, PolicySet, PolicySetSnapshot, Request, RestrictedExpression, SpecializationCache, SpecializedVariable};
+/// # use std::str::FromStr;
+/// let policies = PolicySetSnapshot::new(PolicySet::from_str(r#"
+///     permit(principal == User::"alice", action, resource) when { context.mfa };
+/// "#).unwrap());
+/// let entities = EntitiesSnapshot::default();
+/// let cache = SpecializationCache::new(SpecializedVariable::Principal);
+/// let request = Request::new(
+///     EntityUid::from_str(r#"User::"alice""#).unwrap(),
+///     EntityUid::from_str(r#"Action::"view""#).unwrap(),
+///     EntityUid::from_str(r#"Photo::"beach""#).unwrap(),
+///     Context::from_pairs([("mfa".into(), RestrictedExpression::new_bool(true))]).unwrap(),
+///     None,
+/// ).unwrap();
+/// let response = cache.is_authorized(&request, &policies, &entities);
+/// assert_eq!(response.decision(), cedar_policy::Decision::Allow);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[doc = include_str!("../../experimental_warning.md")]
+#[derive(Debug)]
+pub struct SpecializationCache {
+    variable: SpecializedVariable,
+    entries: Mutex<CacheEntries>,
+}
+
+impl SpecializationCache {
+    /// Create an empty cache of policy sets specialized on `variable`
+    pub fn new(variable: SpecializedVariable) -> Self {
+        Self {
+            variable,
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// The request variable which the cached policy sets are specialized on
+    pub fn variable(&self) -> SpecializedVariable {
+        self.variable
+    }
+
+    /// Get the specialization of `policies` for `uid` with respect to
+    /// `entities`, computing and caching it if it isn't cached
+    pub fn specialize(
+        &self,
+        uid: &EntityUid,
+        policies: &PolicySetSnapshot,
+        entities: &EntitiesSnapshot,
+    ) -> Arc<SpecializedPolicySet> {
+        {
+            let mut entries = self.lock();
+            entries.invalidate_unless(policies, entities);
+            if let Some(specialized) = entries.specialized.get(uid) {
+                return Arc::clone(specialized);
+            }
+        }
+        // Partially evaluate without holding the lock, so that other
+        // principals can be looked up in the meantime
+        let specialized = Arc::new(SpecializedPolicySet::new(
+            self.variable,
+            uid.clone(),
+            policies,
+            entities,
+        ));
+        let mut entries = self.lock();
+        entries.invalidate_unless(policies, entities);
+        Arc::clone(
+            entries
+                .specialized
+                .entry(uid.clone())
+                .or_insert(specialized),
+        )
+    }
+
+    /// Authorize `request` against `policies` with respect to `entities`,
+    /// using the cached specialization for the principal (or resource) of
+    /// `request`. The response is the same as
+    /// [`PolicySetSnapshot::is_authorized()`] would return, which is used
+    /// instead if `request` has any unknowns.
+    pub fn is_authorized(
+        &self,
+        request: &Request,
+        policies: &PolicySetSnapshot,
+        entities: &EntitiesSnapshot,
+    ) -> Response {
+        let response = self.variable.of(request).and_then(|uid| {
+            self.specialize(EntityUid::ref_cast(uid), policies, entities)
+                .is_authorized(request, entities)
+        });
+        match response {
+            Some(mut response) => {
+                response.diagnostics.policy_set_version = Some(policies.version());
+                response
+            }
+            None => policies.is_authorized(&Authorizer::new(), request, entities),
+        }
+    }
+
+    /// The number of cached specializations
+    pub fn len(&self) -> usize {
+        self.lock().specialized.len()
+    }
+
+    /// Returns `true` if no specializations are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached specialization
+    pub fn clear(&self) {
+        *self.lock() = CacheEntries::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        // A panic while the lock is held cannot leave the entries in an
+        // inconsistent state, so it is safe to ignore poisoning
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -14,15 +14,13 @@
  * limitations under the License.
  */
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
 use cedar_policy_core::ast::{self, Value};
 use cedar_policy_core::authorizer::Decision;
-use cedar_policy_core::batched_evaluator::is_authorized_batched;
-use cedar_policy_core::batched_evaluator::{
-    err::BatchedEvalError, EntityLoader as EntityLoaderInternal,
-};
+use cedar_policy_core::batched_evaluator::err::BatchedEvalError;
+use cedar_policy_core::batched_evaluator::{is_authorized_batched, BatchedEvaluation};
 use cedar_policy_core::evaluator::{EvaluationError, RestrictedEvaluator};
 use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::tpe;
@@ -30,10 +28,12 @@ use itertools::Itertools;
 use ref_cast::RefCast;
 use smol_str::SmolStr;
 
+use super::entity_loader::{from_loaded, to_uids, EntityLoaderWrapper};
 use crate::{
-    api, tpe_err, Authorizer, Context, Entities, Entity, EntityId, EntityTypeName, EntityUid,
-    PartialEntityError, PartialRequestCreationError, PermissionQueryError, Policy, PolicySet,
-    Request, RequestValidationError, RestrictedExpression, Schema, TpeReauthorizationError,
+    api, tpe_err, AsyncEntityLoader, Authorizer, Context, Entities, EntityId, EntityLoader,
+    EntityTypeName, EntityUid, PartialEntityError, PartialRequestCreationError,
+    PermissionQueryError, Policy, PolicySet, Request, RequestValidationError, RestrictedExpression,
+    Schema, TpeReauthorizationError,
 };
 
 /// A partial [`EntityUid`].
@@ -444,66 +444,6 @@ impl TpeResponse<'_> {
     }
 }
 
-/// Entity loader trait for batched evaluation.
-///
-/// Loads entities on demand, returning `None` for missing entities.
-/// The `load_entities` function must load all requested entities,
-/// and must compute and include all ancestors of the requested entities.
-/// Loading more entities than requested is allowed.
-#[doc = include_str!("../../experimental_warning.md")]
-pub trait EntityLoader {
-    /// Load all entities for the given set of entity UIDs.
-    /// Returns a map from [`EntityUid`] to [`Option<Entity>`], where `None` indicates
-    /// the entity does not exist.
-    fn load_entities(&mut self, uids: &HashSet<EntityUid>) -> HashMap<EntityUid, Option<Entity>>;
-}
-
-/// Wrapper struct used to convert an [`EntityLoader`] to an `EntityLoaderInternal`
-struct EntityLoaderWrapper<'a>(&'a mut dyn EntityLoader);
-
-impl EntityLoaderInternal for EntityLoaderWrapper<'_> {
-    fn load_entities(
-        &mut self,
-        uids: &HashSet<ast::EntityUID>,
-    ) -> HashMap<ast::EntityUID, Option<ast::Entity>> {
-        let ids = uids
-            .iter()
-            .map(|id| EntityUid::ref_cast(id).clone())
-            .collect();
-        self.0
-            .load_entities(&ids)
-            .into_iter()
-            .map(|(uid, entity)| (uid.0, entity.map(|e| e.0)))
-            .collect()
-    }
-}
-
-/// Simple entity loader implementation that loads from a pre-existing Entities store
-#[doc = include_str!("../../experimental_warning.md")]
-#[derive(Debug)]
-
-pub struct TestEntityLoader<'a> {
-    entities: &'a Entities,
-}
-
-impl<'a> TestEntityLoader<'a> {
-    /// Create a new [`TestEntityLoader`] from an existing Entities store
-    pub fn new(entities: &'a Entities) -> Self {
-        Self { entities }
-    }
-}
-
-impl EntityLoader for TestEntityLoader<'_> {
-    fn load_entities(&mut self, uids: &HashSet<EntityUid>) -> HashMap<EntityUid, Option<Entity>> {
-        uids.iter()
-            .map(|uid| {
-                let entity = self.entities.get(uid).cloned();
-                (uid.clone(), entity)
-            })
-            .collect()
-    }
-}
-
 impl PolicySet {
     /// Perform type-aware partial evaluation on this [`PolicySet`].
     ///
@@ -552,6 +492,34 @@ impl PolicySet {
         )
     }
 
+    /// Like [`PolicySet::is_authorized_batched`] but uses an
+    /// [`AsyncEntityLoader`] to load entities on demand.
+    ///
+    /// Awaits `loader` at most `max_iters` times, returning
+    /// early if an authorization result is reached.
+    /// Otherwise, it iterates `max_iters` times and returns
+    /// a partial result.
+    ///
+    #[doc = include_str!("../../experimental_warning.md")]
+    pub async fn is_authorized_batched_async(
+        &self,
+        query: &Request,
+        schema: &Schema,
+        loader: &mut impl AsyncEntityLoader,
+        max_iters: u32,
+    ) -> Result<Decision, BatchedEvalError> {
+        let mut evaluation = BatchedEvaluation::new(&query.0, &self.ast, &schema.0)?;
+        for _i in 0..max_iters {
+            let to_load = to_uids(&evaluation.entities_to_load());
+            let loaded_entities = loader.load_entities(&to_load).await;
+            evaluation.add_entities(from_loaded(loaded_entities))?;
+            if evaluation.is_done() {
+                break;
+            }
+        }
+        evaluation.decision()
+    }
+
     /// Perform a permission query on the resource
     #[doc = include_str!("../../experimental_warning.md")]
     pub fn query_resource(
@@ -2108,6 +2076,62 @@ when { principal in resource.admins };
 
             assert_matches!(result, Err(BatchedEvalError::InsufficientIterations(_)));
         }
+
+        #[test]
+        fn test_is_authorized_batched_async() {
+            use crate::{AsyncEntityLoader, CachingEntityLoader, Entity, EntityLoader};
+            use std::collections::{HashMap, HashSet};
+            use std::future::Future;
+            use std::task::{self, Poll, Waker};
+
+            struct AsyncLoader<'a>(TestEntityLoader<'a>);
+
+            impl AsyncEntityLoader for AsyncLoader<'_> {
+                async fn load_entities(
+                    &mut self,
+                    uids: &HashSet<EntityUid>,
+                ) -> HashMap<EntityUid, Option<Entity>> {
+                    EntityLoader::load_entities(&mut self.0, uids)
+                }
+            }
+
+            let schema = schema();
+            let policies = policy_set();
+            let entities = entities();
+            let mut loader =
+                CachingEntityLoader::new(AsyncLoader(TestEntityLoader::new(&entities)), 100);
+
+            for (principal, action) in [("alice", "push"), ("jane", "pull"), ("bob", "push")] {
+                let request = Request::new(
+                    format!(r#"User::"{principal}""#).parse().unwrap(),
+                    format!(r#"Action::"{action}""#).parse().unwrap(),
+                    r#"Repository::"common_knowledge""#.parse().unwrap(),
+                    Context::empty(),
+                    Some(&schema),
+                )
+                .unwrap();
+                let batched_decision = policies
+                    .is_authorized_batched(
+                        &request,
+                        &schema,
+                        &mut TestEntityLoader::new(&entities),
+                        u32::MAX,
+                    )
+                    .unwrap();
+                // The loader never waits, so the future is ready on its first poll
+                let future = std::pin::pin!(policies.is_authorized_batched_async(
+                    &request,
+                    &schema,
+                    &mut loader,
+                    u32::MAX
+                ));
+                assert_matches!(
+                    future.poll(&mut task::Context::from_waker(Waker::noop())),
+                    Poll::Ready(Ok(async_decision)) => assert_eq!(batched_decision, async_decision)
+                );
+            }
+            assert!(!loader.is_empty());
+        }
     }
 
     mod trivial {
@@ -2814,6 +2838,65 @@ when { principal in resource.admins };
         );
     }
 
+    #[test]
+    fn residuals_to_sql_filter() {
+        use crate::{
+            pst, Context, FilterTranslator, PartialEntities, PartialEntityUid, PartialRequest,
+            PolicySet, SqlFilter,
+        };
+        use std::str::FromStr;
+
+        let schema = Schema::from_str(
+            r"
+            entity User { level: Long };
+            entity Photo { owner: User, public: Bool, size: Long };
+            action view appliesTo { principal: User, resource: Photo, context: { limit: Long } };
+        ",
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r"
+            permit(principal, action, resource) when { resource.owner == principal || resource.public };
+            forbid(principal, action, resource) when { resource.size > context.limit };
+        ",
+        )
+        .unwrap();
+        let request = PartialRequest::new(
+            PartialEntityUid::from_concrete(r#"User::"alice""#.parse().unwrap()),
+            r#"Action::"view""#.parse().unwrap(),
+            PartialEntityUid::new("Photo".parse().unwrap(), None),
+            Some(
+                Context::from_pairs([("limit".into(), RestrictedExpression::new_long(100))])
+                    .unwrap(),
+            ),
+            &schema,
+        )
+        .unwrap();
+        let entities = PartialEntities::empty();
+        let response = policies.tpe(&request, &entities, &schema).unwrap();
+        let residuals: Vec<_> = response.nontrivial_residual_policies().collect();
+        let mut backend = SqlFilter::new("id").with_column("owner", "owner_id");
+        let filter = FilterTranslator::new(pst::Var::Resource)
+            .translate_residuals(&residuals, &mut backend)
+            .unwrap()
+            .into_filter();
+        assert_eq!(
+            filter.sql(),
+            r#"(("owner_id" = ? OR "public" = ?) AND NOT (NOT ("size" <= ?)))"#
+        );
+        assert_eq!(
+            filter.params(),
+            [
+                pst::Literal::EntityUID(pst::EntityUID {
+                    ty: pst::EntityType::from_name(pst::Name::unqualified("User").unwrap()),
+                    eid: "alice".into(),
+                }),
+                pst::Literal::Bool(true),
+                pst::Literal::Long(100),
+            ]
+        );
+    }
+
     mod template_links {
         use std::{collections::HashMap, str::FromStr};
 
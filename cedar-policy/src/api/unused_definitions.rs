@@ -0,0 +1,124 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`UnusedDefinitions`], which reports the declarations
+//! in a [`Schema`](super::Schema) that a [`PolicySet`] never uses.
+
+use super::{EntityTypeName, EntityUid, PolicySet, SchemaFragment, Validator};
+use ref_cast::RefCast;
+
+/// The entity types, actions, and entity attributes declared in a schema
+/// which are never used by the policies in a policy set, as returned by
+/// [`Validator::unused_definitions()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedDefinitions(cedar_policy_core::validator::UnusedDefinitions);
+
+impl UnusedDefinitions {
+    /// Iterate over the entity types which no policy refers to, and which are
+    /// not the type of any expression in a policy, ordered by name
+    pub fn entity_types(&self) -> impl Iterator<Item = &EntityTypeName> {
+        self.0.entity_types().map(EntityTypeName::ref_cast)
+    }
+
+    /// Iterate over the actions which no policy refers to or applies to,
+    /// ordered by name
+    pub fn actions(&self) -> impl Iterator<Item = &EntityUid> {
+        self.0.actions().map(EntityUid::ref_cast)
+    }
+
+    /// Iterate over the attributes which no policy accesses (or checks for with
+    /// `has`), together with the entity type declaring them. Attributes of the
+    /// entity types returned by [`UnusedDefinitions::entity_types()`] are not
+    /// included.
+    pub fn attributes(&self) -> impl Iterator<Item = (&EntityTypeName, &str)> {
+        self.0
+            .attributes()
+            .map(|(ety, attr)| (EntityTypeName::ref_cast(ety), attr.as_str()))
+    }
+
+    /// Returns `true` if every entity type, action, and attribute is used
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Validator {
+    /// Find the entity types, actions, and entity attributes declared in the
+    /// schema which are never used by the policies in `pset`, e.g., to prune a
+    /// schema.
+    ///
+    /// An entity type is used if a policy mentions it (in an entity literal or
+    /// an `is` test), or if a policy applies to requests where an expression
+    /// has that type, e.g., the principal types of the actions the policy
+    /// applies to. An action is used if a policy mentions it or applies to
+    /// requests for it. An attribute is used if a policy accesses it, or
+    /// checks for it with `has`, on an entity of the declaring type.
+    ///
+    /// Common types are never referred to by policies. See
+    /// [`SchemaFragment::unused_common_types()`] to find those which are not
+    /// used in the schema itself.
+    ///
+    /// ```
+    /// # use cedar_policy::{PolicySet, Schema, Validator};
+    /// # use std::str::FromStr;
+    /// let schema = Schema::from_str(r#"
+    ///     entity User { name: String, legacyId: Long };
+    ///     entity Photo;
+    ///     entity Fax;
+    ///     action view appliesTo { principal: User, resource: Photo };
+    ///     action fax appliesTo { principal: User, resource: Fax };
+    /// "#).unwrap();
+    /// let pset = PolicySet::from_str(r#"
+    ///     permit(principal, action == Action::"view", resource) when { principal.name == "alice" };
+    /// "#).unwrap();
+    /// let unused = Validator::new(schema).unused_definitions(&pset);
+    /// assert_eq!(unused.entity_types().map(ToString::to_string).collect::<Vec<_>>(), ["Fax"]);
+    /// assert_eq!(unused.actions().map(ToString::to_string).collect::<Vec<_>>(), [r#"Action::"fax""#]);
+    /// assert_eq!(
+    ///     unused.attributes().map(|(ety, attr)| format!("{ety}.{attr}")).collect::<Vec<_>>(),
+    ///     ["User.legacyId"],
+    /// );
+    /// ```
+    pub fn unused_definitions(&self, pset: &PolicySet) -> UnusedDefinitions {
+        UnusedDefinitions(self.0.unused_definitions(&pset.ast))
+    }
+}
+
+impl SchemaFragment {
+    /// Iterate over the fully qualified names of the common types declared in
+    /// this fragment which are never used, directly or through other common
+    /// types, by the declaration of an entity type or action context
+    ///
+    /// ```
+    /// # use cedar_policy::SchemaFragment;
+    /// # use std::str::FromStr;
+    /// let fragment = SchemaFragment::from_str(r#"
+    ///     namespace App {
+    ///         type Street = String;
+    ///         type Address = { street: Street };
+    ///         type LegacyAddress = { street: Street, box: Long };
+    ///         entity User { address: Address };
+    ///     }
+    /// "#).unwrap();
+    /// assert_eq!(fragment.unused_common_types().collect::<Vec<_>>(), ["App::LegacyAddress"]);
+    /// ```
+    pub fn unused_common_types(&self) -> impl Iterator<Item = String> {
+        self.lossless
+            .unused_common_types()
+            .into_iter()
+            .map(|name| name.to_string())
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module defines [`PolicySet::upgrade_template()`], which replaces the
+//! body of a template and relinks all of its existing links against the new
+//! body in one step.
+
+use super::{
+    policy_set_errors::TemplateUpgradeError, PolicyId, PolicySet, PolicySetError, Template,
+};
+
+impl PolicySet {
+    /// Replace the template with the same id as `template` by `template`, and
+    /// relink every existing link to it against the new template, with the
+    /// same link ids and slot bindings (including value slots).
+    ///
+    /// Each link is checked as it would be by [`PolicySet::link_many()`], so
+    /// a link is no longer valid if, e.g., the new template removes a slot it
+    /// binds, adds a slot it doesn't bind, or declares a value slot with a
+    /// different type. If any link is no longer valid, a
+    /// [`TemplateUpgradeError`] listing every invalid link is returned and
+    /// this policy set is unchanged. Otherwise, returns the ids of the
+    /// relinked policies. The metadata of the template and its links, and
+    /// whether they are enabled, are kept.
+    ///
+    /// ```
+    /// # use cedar_policy::{EntityUid, PolicyId, PolicySet, SlotId, Template};
+    /// # use std::{collections::HashMap, str::FromStr};
+    /// let mut policies = PolicySet::new();
+    /// let template = Template::parse(
+    ///     Some(PolicyId::new("t")),
+    ///     "permit(principal == ?principal, action, resource);",
+    /// ).unwrap();
+    /// policies.add_template(template).unwrap();
+    /// let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    /// policies.link(
+    ///     PolicyId::new("t"),
+    ///     PolicyId::new("alice"),
+    ///     HashMap::from([(SlotId::principal(), alice)]),
+    /// ).unwrap();
+    ///
+    /// let template = Template::parse(
+    ///     Some(PolicyId::new("t")),
+    ///     r#"permit(principal == ?principal, action == Action::"view", resource);"#,
+    /// ).unwrap();
+    /// let relinked = policies.upgrade_template(template).unwrap();
+    /// assert_eq!(relinked, [PolicyId::new("alice")]);
+    /// ```
+    pub fn upgrade_template(
+        &mut self,
+        template: Template,
+    ) -> Result<Vec<PolicyId>, PolicySetError> {
+        let template_id = template.id().clone();
+        let links: Vec<_> = self
+            .policies
+            .values()
+            .filter(|p| p.template_id() == Some(&template_id))
+            .collect();
+
+        let mut upgraded = self.clone();
+        for p in &links {
+            upgraded.unlink(p.id().clone())?;
+        }
+        upgraded.remove_template(template_id.clone())?;
+        upgraded.add_template(template)?;
+        let invalid_links: Vec<_> = links
+            .iter()
+            .filter_map(|p| {
+                upgraded
+                    .relink(p.id().clone(), &p.ast)
+                    .err()
+                    .map(|err| (p.id().clone(), err))
+            })
+            .collect();
+        if !invalid_links.is_empty() {
+            return Err(TemplateUpgradeError {
+                template_id,
+                invalid_links,
+            }
+            .into());
+        }
+
+        let ids: Vec<_> = links.iter().map(|p| p.id().clone()).collect();
+        for id in ids.iter().chain([&template_id]) {
+            if let Some(metadata) = self.metadata.get(id) {
+                upgraded.metadata.insert(id.clone(), metadata.clone());
+            }
+            upgraded.ast.set_enabled(id.as_ref(), self.is_enabled(id));
+        }
+        *self = upgraded;
+        Ok(ids)
+    }
+}
@@ -0,0 +1,1043 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generation of Rust types from a [`Schema`].
+//!
+//! [`generate()`] produces Rust source code declaring a type for every entity
+//! type declared in a schema, and for the context of every action that
+//! declares a non-empty context. Attributes of standard entity types and
+//! contexts become struct fields, optional attributes become [`Option`]s, and
+//! enumerated entity types become Rust enums. Every generated type converts
+//! into a [`RestrictedExpression`] and from an [`EvalResult`], so application
+//! code constructing entities and contexts is type-checked at compile time.
+//!
+//! The generated code depends only on the items of this module. It is meant
+//! to be generated from a build script:
+//!
+//! ```no_run
+//! // build.rs
+//! # use cedar_policy::{codegen, Schema};
+//! # use std::str::FromStr;
+//! let src = std::fs::read_to_string("schema.cedarschema").unwrap();
+//! let schema = Schema::from_str(&src).unwrap();
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! std::fs::write(format!("{out_dir}/schema.rs"), codegen::generate(&schema)).unwrap();
+//! ```
+//!
+//! and included with `include!(concat!(env!("OUT_DIR"), "/schema.rs"));`.
+//!
+//! Types are generated for entity types, not entity references: an attribute
+//! whose type is a standard entity type becomes an [`EntityUid`], while an
+//! attribute whose type is an enumerated entity type becomes the generated
+//! enum. Entity types in a namespace are generated in a module named after the
+//! namespace, in `snake_case`.
+
+use crate::{
+    AttributeInfo, Context, ContextCreationError, DeclaredType, Entity, EntityAttrEvaluationError,
+    EntityId, EntityTypeInfo, EntityTypeName, EntityUid, EvalResult, Record, RestrictedExpression,
+    Schema,
+};
+use itertools::Itertools;
+use miette::Diagnostic;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A Rust type that corresponds to a Cedar type. Implemented by all the types
+/// [`generate()`] uses for attributes, and by the generated types themselves.
+pub trait CedarValue: Sized {
+    /// Convert this value into a Cedar expression
+    fn into_restricted_expression(self) -> RestrictedExpression;
+
+    /// Convert a Cedar value into this type
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` doesn't have the Cedar type corresponding
+    /// to this type.
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError>;
+}
+
+/// Error when a Cedar value can't be converted into one of the types produced
+/// by [`generate()`]
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make fields `pub`, don't make breaking changes, and use caution
+// when adding public methods.
+#[derive(Debug, Clone, PartialEq, Eq, Diagnostic, Error)]
+#[error("{}{kind}", fmt_path(.path))]
+pub struct ValueConversionError {
+    kind: ValueConversionErrorKind,
+    /// Path of attributes leading to the value which couldn't be converted,
+    /// innermost last
+    path: Vec<String>,
+}
+
+fn fmt_path(path: &[String]) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("in attribute `{}`: ", path.join("."))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+enum ValueConversionErrorKind {
+    #[error("expected {expected}, found `{found}`")]
+    UnexpectedValue { expected: String, found: EvalResult },
+    #[error("missing required attribute `{0}`")]
+    MissingAttribute(String),
+}
+
+impl ValueConversionError {
+    fn unexpected_value(expected: impl Into<String>, found: &EvalResult) -> Self {
+        Self {
+            kind: ValueConversionErrorKind::UnexpectedValue {
+                expected: expected.into(),
+                found: found.clone(),
+            },
+            path: Vec::new(),
+        }
+    }
+
+    fn missing_attribute(attr: &str) -> Self {
+        Self {
+            kind: ValueConversionErrorKind::MissingAttribute(attr.into()),
+            path: Vec::new(),
+        }
+    }
+
+    fn in_attribute(mut self, attr: &str) -> Self {
+        self.path.insert(0, attr.into());
+        self
+    }
+
+    /// Path of record attributes leading to the value which couldn't be
+    /// converted, outermost first. Empty if the error is about the converted
+    /// value itself.
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.path.iter().map(String::as_str)
+    }
+}
+
+impl CedarValue for bool {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_bool(self)
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        match value {
+            EvalResult::Bool(b) => Ok(*b),
+            _ => Err(ValueConversionError::unexpected_value("a boolean", value)),
+        }
+    }
+}
+
+impl CedarValue for i64 {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_long(self)
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        match value {
+            EvalResult::Long(l) => Ok(*l),
+            _ => Err(ValueConversionError::unexpected_value("a long", value)),
+        }
+    }
+}
+
+impl CedarValue for String {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_string(self)
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        match value {
+            EvalResult::String(s) => Ok(s.clone()),
+            _ => Err(ValueConversionError::unexpected_value("a string", value)),
+        }
+    }
+}
+
+impl CedarValue for EntityUid {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_entity_uid(self)
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        match value {
+            EvalResult::EntityUid(uid) => Ok(uid.clone()),
+            _ => Err(ValueConversionError::unexpected_value("an entity", value)),
+        }
+    }
+}
+
+impl<T: CedarValue> CedarValue for Vec<T> {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_set(self.into_iter().map(T::into_restricted_expression))
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        match value {
+            EvalResult::Set(set) => set.iter().map(T::from_eval_result).collect(),
+            _ => Err(ValueConversionError::unexpected_value("a set", value)),
+        }
+    }
+}
+
+impl<T: CedarValue> CedarValue for BTreeMap<String, T> {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        record(
+            self.into_iter()
+                .map(|(k, v)| (k, Some(v.into_restricted_expression()))),
+        )
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        expect_record(value)?
+            .iter()
+            .map(|(k, v)| {
+                T::from_eval_result(v)
+                    .map(|v| (k.clone(), v))
+                    .map_err(|e| e.in_attribute(k))
+            })
+            .collect()
+    }
+}
+
+/// Any Cedar value, used by [`generate()`] for types it has no more specific
+/// Rust type for
+impl CedarValue for RestrictedExpression {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        self
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        Self::from_str(&value.to_string())
+            .map_err(|_| ValueConversionError::unexpected_value("a value", value))
+    }
+}
+
+macro_rules! extension_type {
+    ($(#[$doc:meta])* $name:ident, $type_name:literal, $constructor:ident, $fn_name:literal) => {
+        $(#[$doc])*
+        ///
+        /// Holds the string passed to the
+        #[doc = concat!("`", $fn_name, "()`")]
+        /// constructor, which is only checked once the value is used in a
+        /// Cedar expression.
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub String);
+
+        impl CedarValue for $name {
+            fn into_restricted_expression(self) -> RestrictedExpression {
+                RestrictedExpression::$constructor(self.0)
+            }
+
+            fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+                extension_arg(value, $fn_name).map(Self).ok_or_else(|| {
+                    ValueConversionError::unexpected_value(
+                        concat!("a value of type `", $type_name, "`"),
+                        value,
+                    )
+                })
+            }
+        }
+    };
+}
+
+extension_type!(
+    /// A value of the `ipaddr` extension type
+    IpAddr,
+    "ipaddr",
+    new_ip,
+    "ip"
+);
+extension_type!(
+    /// A value of the `decimal` extension type
+    Decimal,
+    "decimal",
+    new_decimal,
+    "decimal"
+);
+extension_type!(
+    /// A value of the `datetime` extension type
+    Datetime,
+    "datetime",
+    new_datetime,
+    "datetime"
+);
+extension_type!(
+    /// A value of the `duration` extension type
+    Duration,
+    "duration",
+    new_duration,
+    "duration"
+);
+
+/// An IP address, as a value of the `ipaddr` extension type. Unlike
+/// [`IpAddr`], this can't hold a range.
+impl CedarValue for std::net::IpAddr {
+    fn into_restricted_expression(self) -> RestrictedExpression {
+        RestrictedExpression::new_ip(self.to_string())
+    }
+
+    fn from_eval_result(value: &EvalResult) -> Result<Self, ValueConversionError> {
+        extension_arg(value, "ip")
+            .and_then(|addr| addr.parse().ok())
+            .ok_or_else(|| ValueConversionError::unexpected_value("an IP address", value))
+    }
+}
+
+/// Get the argument of the extension constructor call `fn_name(arg)` that
+/// `value` is displayed as
+fn extension_arg(value: &EvalResult, fn_name: &str) -> Option<String> {
+    let EvalResult::ExtensionValue(src) = value else {
+        return None;
+    };
+    let expr = cedar_policy_core::ast::RestrictedExpr::from_str(src).ok()?;
+    let (name, args) = expr.as_extn_fn_call()?;
+    if name.to_string() != fn_name {
+        return None;
+    }
+    args.exactly_one()
+        .ok()?
+        .as_string()
+        .map(ToString::to_string)
+}
+
+// The functions below are used by generated code, and are not meant to be
+// called directly.
+
+/// Build a record expression from its attributes, leaving out the absent
+/// optional attributes
+#[doc(hidden)]
+pub fn record(
+    fields: impl IntoIterator<Item = (impl Into<String>, Option<RestrictedExpression>)>,
+) -> RestrictedExpression {
+    let fields: BTreeMap<String, RestrictedExpression> = fields
+        .into_iter()
+        .filter_map(|(k, v)| Some((k.into(), v?)))
+        .collect();
+    #[expect(
+        clippy::expect_used,
+        reason = "attributes come from a map, so they are distinct"
+    )]
+    RestrictedExpression::new_record(fields).expect("record attributes should be distinct")
+}
+
+/// Build a context from its attributes, leaving out the absent optional
+/// attributes
+#[doc(hidden)]
+pub fn context(
+    fields: impl IntoIterator<Item = (&'static str, Option<RestrictedExpression>)>,
+) -> Result<Context, ContextCreationError> {
+    Context::from_pairs(
+        fields
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v?))),
+    )
+}
+
+/// Build an entity from its type, id, attributes, and parents, leaving out the
+/// absent optional attributes
+#[doc(hidden)]
+pub fn entity(
+    entity_type: &str,
+    id: &str,
+    fields: impl IntoIterator<Item = (&'static str, Option<RestrictedExpression>)>,
+    parents: impl IntoIterator<Item = EntityUid>,
+) -> Result<Entity, EntityAttrEvaluationError> {
+    Entity::new(
+        uid(entity_type, id),
+        fields
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v?)))
+            .collect(),
+        parents.into_iter().collect(),
+    )
+}
+
+/// Build the uid of the entity of type `entity_type` with id `id`
+#[doc(hidden)]
+pub fn uid(entity_type: &str, id: &str) -> EntityUid {
+    #[expect(
+        clippy::expect_used,
+        reason = "generated code only passes names of entity types declared in a schema"
+    )]
+    let entity_type =
+        EntityTypeName::from_str(entity_type).expect("entity type name should be valid");
+    EntityUid::from_type_name_and_id(entity_type, EntityId::new(id))
+}
+
+/// Expect `value` to be a record
+#[doc(hidden)]
+pub fn expect_record(value: &EvalResult) -> Result<&Record, ValueConversionError> {
+    match value {
+        EvalResult::Record(record) => Ok(record),
+        _ => Err(ValueConversionError::unexpected_value("a record", value)),
+    }
+}
+
+/// Get the required attribute `attr` of `record`
+#[doc(hidden)]
+pub fn attr<T: CedarValue>(record: &Record, attr: &str) -> Result<T, ValueConversionError> {
+    optional_attr(record, attr)?.ok_or_else(|| ValueConversionError::missing_attribute(attr))
+}
+
+/// Get the optional attribute `attr` of `record`
+#[doc(hidden)]
+pub fn optional_attr<T: CedarValue>(
+    record: &Record,
+    attr: &str,
+) -> Result<Option<T>, ValueConversionError> {
+    record
+        .get(attr)
+        .map(|v| T::from_eval_result(v).map_err(|e| e.in_attribute(attr)))
+        .transpose()
+}
+
+/// Convert `value`, an entity of the enumerated entity type `entity_type`,
+/// into the corresponding one of `choices`
+#[doc(hidden)]
+pub fn enum_choice<T: Copy>(
+    value: &EvalResult,
+    entity_type: &str,
+    choices: &[(&str, T)],
+) -> Result<T, ValueConversionError> {
+    if let EvalResult::EntityUid(uid) = value {
+        if uid.type_name().to_string() == entity_type {
+            if let Some((_, choice)) = choices.iter().find(|(id, _)| *id == uid.id().unescaped()) {
+                return Ok(*choice);
+            }
+        }
+    }
+    Err(ValueConversionError::unexpected_value(
+        format!("an entity of type `{entity_type}`"),
+        value,
+    ))
+}
+
+/// Generate Rust source code declaring types for the entity types and action
+/// contexts declared in `schema`. See the [module documentation](self).
+pub fn generate(schema: &Schema) -> String {
+    let mut generator = Generator::new(schema);
+    generator.run();
+    generator.finish()
+}
+
+/// Names of the Rust types generated for the entity types and contexts of one
+/// namespace, and the source code of those types
+#[derive(Debug, Default)]
+struct Module {
+    used_names: HashSet<String>,
+    items: Vec<String>,
+}
+
+impl Module {
+    /// Reserve a type name derived from `name`, distinct from the type names
+    /// reserved so far
+    fn reserve(&mut self, name: &str) -> String {
+        let base = pascal_case(name);
+        let mut candidate = base.clone();
+        let mut i = 2;
+        while !self.used_names.insert(candidate.clone()) {
+            candidate = format!("{base}{i}");
+            i += 1;
+        }
+        candidate
+    }
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    /// Generated modules, by namespace
+    modules: BTreeMap<String, Module>,
+    /// Rust type names of the enumerated entity types
+    enums: BTreeMap<EntityTypeName, String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(schema: &'a Schema) -> Self {
+        Self {
+            schema,
+            modules: BTreeMap::new(),
+            enums: BTreeMap::new(),
+        }
+    }
+
+    fn run(&mut self) {
+        let schema = self.schema;
+        let entity_types = schema
+            .entity_types()
+            .filter_map(|ety| schema.entity_type(ety))
+            .sorted_by_key(|info| info.name().to_string())
+            .collect_vec();
+        let actions = schema
+            .actions()
+            .filter_map(|action| {
+                let context = schema.context_type(action)?;
+                match context {
+                    DeclaredType::Record { attributes, .. } if !attributes.is_empty() => {
+                        Some((action, attributes))
+                    }
+                    _ => None,
+                }
+            })
+            .sorted_by_key(|(action, _)| action.to_string())
+            .collect_vec();
+
+        // Reserve the names of the top-level types first, so they keep their
+        // natural names even if a nested record type would also want it
+        let mut entity_names = Vec::new();
+        for info in &entity_types {
+            let name = info.name();
+            let module = self.modules.entry(name.namespace()).or_default();
+            let rust_name = module.reserve(name.basename());
+            if info.enum_choices().is_some() {
+                self.enums.insert(name.clone(), rust_name.clone());
+            }
+            entity_names.push(rust_name);
+        }
+        let mut context_names = Vec::new();
+        for (action, _) in &actions {
+            let module = self
+                .modules
+                .entry(action.type_name().namespace())
+                .or_default();
+            context_names
+                .push(module.reserve(&format!("{}Context", pascal_case(action.id().unescaped()))));
+        }
+
+        for (info, rust_name) in entity_types.iter().zip(entity_names) {
+            let name = info.name();
+            let namespace = name.namespace();
+            let item = info.enum_choices().map_or_else(
+                || self.standard_entity(&namespace, *info, &rust_name),
+                |choices| enum_entity(name, &rust_name, choices.map(AsRef::as_ref)),
+            );
+            self.module(&namespace).items.push(item);
+        }
+
+        for ((action, attributes), rust_name) in actions.into_iter().zip(context_names) {
+            let namespace = action.type_name().namespace();
+            let mut item = self.record_struct(
+                &namespace,
+                &rust_name,
+                &format!("Context of a request for the action `{action}`"),
+                &attributes,
+            );
+            let _ = write!(
+                item,
+                r"
+impl {rust_name} {{
+    /// Create a request context with these attributes
+    pub fn into_context(self) -> ::std::result::Result<::cedar_policy::Context, ::cedar_policy::ContextCreationError> {{
+        ::cedar_policy::codegen::context(self.into_fields())
+    }}
+}}
+"
+            );
+            self.module(&namespace).items.push(item);
+        }
+    }
+
+    /// Generate a struct for the standard entity type `info`, and its
+    /// conversions
+    fn standard_entity(
+        &mut self,
+        namespace: &str,
+        info: EntityTypeInfo<'_>,
+        rust_name: &str,
+    ) -> String {
+        let name = info.name();
+        let attributes = info.attributes().map(|(k, v)| (k.to_string(), v)).collect();
+        let mut item = self.record_struct(
+            namespace,
+            rust_name,
+            &format!("Attributes of an entity of type `{name}`"),
+            &attributes,
+        );
+        let _ = write!(
+            item,
+            r#"
+impl {rust_name} {{
+    /// Name of the entity type
+    pub const ENTITY_TYPE: &'static str = "{ety}";
+
+    /// Uid of the entity of this type with id `id`
+    pub fn uid(id: &str) -> ::cedar_policy::EntityUid {{
+        ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+    }}
+
+    /// Create the entity of this type with id `id`, these attributes, and
+    /// the given parents
+    pub fn into_entity(
+        self,
+        id: &str,
+        parents: impl ::std::iter::IntoIterator<Item = ::cedar_policy::EntityUid>,
+    ) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {{
+        ::cedar_policy::codegen::entity(Self::ENTITY_TYPE, id, self.into_fields(), parents)
+    }}
+}}
+"#,
+            ety = name.to_string().escape_debug(),
+        );
+        item
+    }
+
+    fn module(&mut self, namespace: &str) -> &mut Module {
+        self.modules.entry(namespace.to_string()).or_default()
+    }
+
+    /// Generate a struct named `rust_name` with one field per attribute, and
+    /// the conversions of that struct. Record types nested in attributes are
+    /// generated as separate structs.
+    fn record_struct(
+        &mut self,
+        namespace: &str,
+        rust_name: &str,
+        doc: &str,
+        attributes: &BTreeMap<String, AttributeInfo>,
+    ) -> String {
+        let mut used_fields = HashSet::new();
+        let fields = attributes
+            .iter()
+            .map(|(attr, info)| {
+                let base = snake_case(attr);
+                let mut field = base.clone();
+                let mut i = 2;
+                while !used_fields.insert(field.clone()) {
+                    field = format!("{base}_{i}");
+                    i += 1;
+                }
+                let ty = self.rust_type(namespace, rust_name, attr, info.ty());
+                (attr, raw_ident(&field), ty, info.is_required())
+            })
+            .collect_vec();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "/// {doc}");
+        let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq, Eq)]");
+        if fields.is_empty() {
+            let _ = writeln!(out, "pub struct {rust_name} {{}}");
+        } else {
+            let _ = writeln!(out, "pub struct {rust_name} {{");
+            for (attr, field, ty, required) in &fields {
+                let _ = writeln!(out, "    /// The `{}` attribute", attr.escape_debug());
+                if *required {
+                    let _ = writeln!(out, "    pub {field}: {ty},");
+                } else {
+                    let _ = writeln!(out, "    pub {field}: ::std::option::Option<{ty}>,");
+                }
+            }
+            let _ = writeln!(out, "}}");
+        }
+
+        let _ = writeln!(out, "\nimpl {rust_name} {{");
+        let _ = writeln!(
+            out,
+            "    fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {{"
+        );
+        if fields.is_empty() {
+            let _ = writeln!(out, "        ::std::vec::Vec::new()");
+        } else {
+            let _ = writeln!(out, "        ::std::vec![");
+            for (attr, field, _, required) in &fields {
+                if *required {
+                    let _ = writeln!(
+                        out,
+                        "            (\"{}\", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.{field}))),",
+                        attr.escape_debug()
+                    );
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "            (\"{}\", self.{field}.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),",
+                        attr.escape_debug()
+                    );
+                }
+            }
+            let _ = writeln!(out, "        ]");
+        }
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}}");
+
+        out.push_str(&cedar_value_impl(rust_name, &fields));
+        out.push_str(&conversions(rust_name));
+        out
+    }
+
+    /// The Rust type for `ty`, the type of the attribute `attr` of the
+    /// generated type `owner` in the module for `namespace`. Record types are
+    /// generated as structs named after `owner` and `attr`.
+    fn rust_type(&mut self, namespace: &str, owner: &str, attr: &str, ty: &DeclaredType) -> String {
+        match ty {
+            DeclaredType::Bool => "bool".into(),
+            DeclaredType::Long => "i64".into(),
+            DeclaredType::String => "::std::string::String".into(),
+            DeclaredType::Set(element) => format!(
+                "::std::vec::Vec<{}>",
+                self.rust_type(namespace, owner, attr, element)
+            ),
+            DeclaredType::Map(value) => format!(
+                "::std::collections::BTreeMap<::std::string::String, {}>",
+                self.rust_type(namespace, owner, attr, value)
+            ),
+            DeclaredType::Record { attributes, .. } => {
+                let rust_name = self
+                    .module(namespace)
+                    .reserve(&format!("{owner}{}", pascal_case(attr)));
+                let item = self.record_struct(
+                    namespace,
+                    &rust_name,
+                    &format!(
+                        "Type of the `{}` attribute of [`{owner}`]",
+                        attr.escape_debug()
+                    ),
+                    attributes,
+                );
+                self.module(namespace).items.push(item);
+                rust_name
+            }
+            DeclaredType::Entity(ety) => self.enums.get(ety).map_or_else(
+                || "::cedar_policy::EntityUid".into(),
+                |rust_name| type_path(namespace, &ety.namespace(), rust_name),
+            ),
+            DeclaredType::EntityUnion(_) => "::cedar_policy::EntityUid".into(),
+            DeclaredType::Extension(name) => match name.as_str() {
+                "ipaddr" => "::cedar_policy::codegen::IpAddr".into(),
+                "decimal" => "::cedar_policy::codegen::Decimal".into(),
+                "datetime" => "::cedar_policy::codegen::Datetime".into(),
+                "duration" => "::cedar_policy::codegen::Duration".into(),
+                _ => "::cedar_policy::RestrictedExpression".into(),
+            },
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::from(
+            "// @generated by `cedar_policy::codegen::generate()`. Do not edit by hand.\n",
+        );
+        for (namespace, module) in self.modules {
+            if module.items.is_empty() {
+                continue;
+            }
+            let items = module.items.join("\n");
+            if namespace.is_empty() {
+                let _ = write!(out, "\n{items}");
+            } else {
+                let _ = writeln!(out, "\n/// Types for the `{namespace}` namespace");
+                let _ = writeln!(out, "pub mod {} {{", module_name(&namespace));
+                for line in items.lines() {
+                    if line.is_empty() {
+                        out.push('\n');
+                    } else {
+                        let _ = writeln!(out, "    {line}");
+                    }
+                }
+                let _ = writeln!(out, "}}");
+            }
+        }
+        out
+    }
+}
+
+/// Generate an enum for the enumerated entity type `name`, and its conversions
+fn enum_entity<'a>(
+    name: &EntityTypeName,
+    rust_name: &str,
+    choices: impl Iterator<Item = &'a str>,
+) -> String {
+    let mut used = HashSet::new();
+    let variants = choices
+        .map(|choice| {
+            let base = pascal_case(choice);
+            let mut variant = base.clone();
+            let mut i = 2;
+            while !used.insert(variant.clone()) {
+                variant = format!("{base}{i}");
+                i += 1;
+            }
+            (choice.escape_debug().to_string(), variant)
+        })
+        .collect_vec();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "/// An entity of the enumerated entity type `{name}`");
+    let _ = writeln!(
+        out,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]"
+    );
+    let _ = writeln!(out, "pub enum {rust_name} {{");
+    for (choice, variant) in &variants {
+        let _ = writeln!(out, "    /// The entity with id `{choice}`");
+        let _ = writeln!(out, "    {variant},");
+    }
+    let _ = writeln!(out, "}}");
+    let _ = write!(
+        out,
+        r#"
+impl {rust_name} {{
+    /// Name of the entity type
+    pub const ENTITY_TYPE: &'static str = "{ety}";
+
+    /// All the entities of this type, in the order they are declared
+    pub const ALL: &'static [Self] = &[{all}];
+
+    /// Id of this entity
+    pub fn id(self) -> &'static str {{
+        match self {{
+{ids}        }}
+    }}
+
+    /// Uid of this entity
+    pub fn uid(self) -> ::cedar_policy::EntityUid {{
+        ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, self.id())
+    }}
+}}
+
+impl ::cedar_policy::codegen::CedarValue for {rust_name} {{
+    fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {{
+        ::cedar_policy::RestrictedExpression::new_entity_uid(self.uid())
+    }}
+
+    fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {{
+        ::cedar_policy::codegen::enum_choice(value, Self::ENTITY_TYPE, &[{pairs}])
+    }}
+}}
+
+impl ::std::convert::From<{rust_name}> for ::cedar_policy::EntityUid {{
+    fn from(value: {rust_name}) -> Self {{
+        value.uid()
+    }}
+}}
+"#,
+        ety = name.to_string().escape_debug(),
+        all = variants
+            .iter()
+            .map(|(_, variant)| format!("Self::{variant}"))
+            .join(", "),
+        ids = variants
+            .iter()
+            .map(|(choice, variant)| format!("            Self::{variant} => \"{choice}\",\n"))
+            .join(""),
+        pairs = variants
+            .iter()
+            .map(|(choice, variant)| format!("(\"{choice}\", Self::{variant})"))
+            .join(", "),
+    );
+    out.push_str(&conversions(rust_name));
+    out
+}
+
+/// `CedarValue` implementation for the generated struct `rust_name` with the
+/// given fields
+fn cedar_value_impl(rust_name: &str, fields: &[(&String, String, String, bool)]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "\nimpl ::cedar_policy::codegen::CedarValue for {rust_name} {{"
+    );
+    let _ = writeln!(
+        out,
+        "    fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {{"
+    );
+    let _ = writeln!(
+        out,
+        "        ::cedar_policy::codegen::record(self.into_fields())"
+    );
+    let _ = writeln!(out, "    }}\n");
+    let _ = writeln!(
+        out,
+        "    fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {{"
+    );
+    if fields.is_empty() {
+        let _ = writeln!(
+            out,
+            "        ::cedar_policy::codegen::expect_record(value)?;"
+        );
+        let _ = writeln!(out, "        ::std::result::Result::Ok(Self {{}})");
+    } else {
+        let _ = writeln!(
+            out,
+            "        let record = ::cedar_policy::codegen::expect_record(value)?;"
+        );
+        let _ = writeln!(out, "        ::std::result::Result::Ok(Self {{");
+        for (attr, field, _, required) in fields {
+            let getter = if *required { "attr" } else { "optional_attr" };
+            let _ = writeln!(
+                out,
+                "            {field}: ::cedar_policy::codegen::{getter}(record, \"{}\")?,",
+                attr.escape_debug()
+            );
+        }
+        let _ = writeln!(out, "        }})");
+    }
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+/// `From` and `TryFrom` implementations for a generated type, in terms of its
+/// `CedarValue` implementation
+fn conversions(rust_name: &str) -> String {
+    format!(
+        r"
+impl ::std::convert::From<{rust_name}> for ::cedar_policy::RestrictedExpression {{
+    fn from(value: {rust_name}) -> Self {{
+        ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+    }}
+}}
+
+impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for {rust_name} {{
+    type Error = ::cedar_policy::codegen::ValueConversionError;
+
+    fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {{
+        ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+    }}
+}}
+"
+    )
+}
+
+/// Path to the type `rust_name` generated for namespace `target`, from the
+/// module generated for namespace `from`
+fn type_path(from: &str, target: &str, rust_name: &str) -> String {
+    match (from.is_empty(), target.is_empty()) {
+        _ if from == target => rust_name.to_string(),
+        (true, false) => format!("{}::{rust_name}", module_name(target)),
+        (false, true) => format!("super::{rust_name}"),
+        (false, false) => format!("super::{}::{rust_name}", module_name(target)),
+        (true, true) => rust_name.to_string(),
+    }
+}
+
+/// Name of the module generated for `namespace`, e.g., `acme_photos` for
+/// `Acme::Photos`
+fn module_name(namespace: &str) -> String {
+    raw_ident(&namespace.split("::").map(snake_case).join("_"))
+}
+
+/// Split `s` into words at non-alphanumeric characters and at lowercase to
+/// uppercase transitions
+fn words(s: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if !c.is_ascii_alphanumeric() {
+            words.push(String::new());
+            prev_lower = false;
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if !(prev_lower && c.is_ascii_uppercase()) => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    words.retain(|w| !w.is_empty());
+    words
+}
+
+/// Convert `s` into a valid `PascalCase` Rust identifier
+fn pascal_case(s: &str) -> String {
+    let ident: String = words(s)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident
+    } else {
+        format!("T{ident}")
+    }
+}
+
+/// Convert `s` into a `snake_case` Rust identifier, which may be a keyword
+fn snake_case(s: &str) -> String {
+    let ident = words(s).iter().map(|w| w.to_ascii_lowercase()).join("_");
+    if ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident
+    } else {
+        format!("_{ident}")
+    }
+}
+
+/// Make `ident` usable as a field or module name, escaping Rust keywords
+fn raw_ident(ident: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    match ident {
+        // These can't be raw identifiers
+        "self" | "super" | "crate" | "_" => format!("{ident}_"),
+        _ if KEYWORDS.contains(&ident) => format!("r#{ident}"),
+        _ => ident.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(pascal_case("view photo"), "ViewPhoto");
+        assert_eq!(pascal_case("readOnly"), "ReadOnly");
+        assert_eq!(pascal_case("light-blue"), "LightBlue");
+        assert_eq!(pascal_case("1st"), "T1st");
+        assert_eq!(snake_case("zipCode"), "zip_code");
+        assert_eq!(snake_case("zip-code"), "zip_code");
+        assert_eq!(snake_case("2fa"), "_2fa");
+        assert_eq!(raw_ident("type"), "r#type");
+        assert_eq!(raw_ident("self"), "self_");
+        assert_eq!(module_name("Acme::PhotoApp"), "acme_photo_app");
+    }
+
+    #[test]
+    fn name_collisions() {
+        let schema = Schema::from_str(
+            r#"
+            entity Foo { bar: { x: Long }, "a-b": String, a_b: Long };
+            entity FooBar;
+            action "foo bar" appliesTo {
+                principal: Foo,
+                resource: Foo,
+                context: { foo: Bool },
+            };
+            "#,
+        )
+        .unwrap();
+        let code = generate(&schema);
+        assert!(code.contains("pub struct Foo {"), "{code}");
+        assert!(code.contains("pub struct FooBar {}"), "{code}");
+        assert!(code.contains("pub struct FooBar2 {"), "{code}");
+        assert!(code.contains("pub bar: FooBar2,"), "{code}");
+        assert!(code.contains("pub a_b: ::std::string::String,"), "{code}");
+        assert!(code.contains("pub a_b_2: i64,"), "{code}");
+        assert!(code.contains("pub struct FooBarContext {"), "{code}");
+    }
+}
@@ -662,6 +662,86 @@ fn build_error<T>(
     }
 }
 
+impl AuthorizationCall {
+    /// Construct an `AuthorizationCall` with no schema. The Rust types of the
+    /// public API can be converted into each argument with `TryFrom`, e.g.,
+    /// `EntityUid::try_from(&euid)` or `PolicySet::try_from(&pset)`.
+    pub fn new(
+        principal: EntityUid,
+        action: EntityUid,
+        resource: EntityUid,
+        context: Context,
+        policies: PolicySet,
+        entities: Entities,
+    ) -> Self {
+        Self {
+            principal,
+            action,
+            resource,
+            context,
+            schema: None,
+            validate_request: true,
+            policies,
+            entities,
+        }
+    }
+
+    /// Use `schema` to parse `context` and `entities` and, unless disabled
+    /// with [`AuthorizationCall::with_validate_request`], to validate the request
+    #[must_use]
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Set whether the request is validated against the schema, if one is provided
+    #[must_use]
+    pub fn with_validate_request(mut self, validate_request: bool) -> Self {
+        self.validate_request = validate_request;
+        self
+    }
+
+    /// Get the principal taking action
+    pub fn principal(&self) -> &EntityUid {
+        &self.principal
+    }
+
+    /// Get the action the principal is taking
+    pub fn action(&self) -> &EntityUid {
+        &self.action
+    }
+
+    /// Get the resource being acted on by the principal
+    pub fn resource(&self) -> &EntityUid {
+        &self.resource
+    }
+
+    /// Get the context of the request
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Get the schema, if any
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// Whether the request is validated against the schema, if one is provided
+    pub fn validate_request(&self) -> bool {
+        self.validate_request
+    }
+
+    /// Get the set of policies to use during authorization
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    /// Get the set of entities to use during authorization
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+}
+
 impl AuthorizationCall {
     fn parse(
         self,
@@ -1438,6 +1518,80 @@ mod test {
         assert_is_authorized_json(call);
     }
 
+    #[test]
+    fn test_authorization_call_from_rust_types() {
+        use std::str::FromStr;
+
+        let mut pset = crate::PolicySet::from_policies([crate::Policy::parse(
+            Some(PolicyId::new("static")),
+            r#"permit(principal == User::"alice", action, resource) when { context.ok };"#,
+        )
+        .unwrap()])
+        .unwrap();
+        pset.add_template(
+            crate::Template::parse(
+                Some(PolicyId::new("template")),
+                "permit(principal == ?principal, action, resource);",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(
+                crate::SlotId::principal(),
+                crate::EntityUid::from_str(r#"User::"bob""#).unwrap(),
+            )]),
+        )
+        .unwrap();
+        let euid = |s: &str| EntityUid::try_from(&crate::EntityUid::from_str(s).unwrap()).unwrap();
+        let call = AuthorizationCall::new(
+            euid(r#"User::"alice""#),
+            euid(r#"Action::"view""#),
+            euid(r#"Photo::"door""#),
+            Context::try_from(
+                &crate::Context::from_pairs([(
+                    "ok".into(),
+                    crate::RestrictedExpression::new_bool(true),
+                )])
+                .unwrap(),
+            )
+            .unwrap(),
+            PolicySet::try_from(&pset).unwrap(),
+            Entities::try_from(&crate::Entities::empty()).unwrap(),
+        );
+
+        // The call serializes to the documented JSON format
+        let json = serde_json::to_value(&call).unwrap();
+        assert_eq!(
+            json["principal"],
+            json!({ "__entity": { "type": "User", "id": "alice" } })
+        );
+        assert_eq!(json["context"], json!({ "ok": true }));
+        assert_eq!(json["validateRequest"], json!(true));
+        assert_eq!(
+            json["policies"]["templateLinks"],
+            json!([{
+                "templateId": "template",
+                "newId": "linked",
+                "values": { "?principal": { "__entity": { "type": "User", "id": "bob" } } }
+            }])
+        );
+        assert!(json["policies"]["staticPolicies"]["static"].is_string());
+        assert!(json["policies"]["templates"]["template"].is_string());
+        let roundtripped: AuthorizationCall = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(roundtripped, call);
+
+        assert_is_authorized_json(json);
+        assert_matches!(is_authorized(call.clone().with_validate_request(false)), AuthorizationAnswer::Success { response, .. } => {
+            assert_eq!(response.decision(), Decision::Allow);
+            assert_eq!(response.diagnostics().reason().collect::<Vec<_>>(), vec![&PolicyId::new("static")]);
+        });
+        assert_eq!(call.principal(), &euid(r#"User::"alice""#));
+        assert!(call.schema().is_none());
+    }
+
     #[test]
     fn test_authorized_fails_on_policy_collision_with_template() {
         let call = json!({
@@ -14,6 +14,28 @@
  * limitations under the License.
  */
 
+//! Functions for calling Cedar from other languages, which take and return
+//! JSON values.
+//!
+//! Each operation is available in three forms: one over `serde`-compatible
+//! Rust types (e.g., [`is_authorized()`](crate::ffi::is_authorized)), one over [`serde_json::Value`]s
+//! (e.g., [`is_authorized_json()`](crate::ffi::is_authorized_json)), and one over JSON strings (e.g.,
+//! [`is_authorized_json_str()`](crate::ffi::is_authorized_json_str)). The JSON-string forms are the most
+//! convenient to wrap in a C ABI for non-Rust policy enforcement points: they
+//! borrow their input, return an owned `String` that the wrapper is
+//! responsible for handing back to the caller and freeing, and report errors
+//! in the answer itself rather than by panicking. The only error they return
+//! is for input which isn't valid JSON for the operation.
+//!
+//! To parse a policy set or schema once and authorize against it many times,
+//! cache it with [`preparse_policy_set()`](crate::ffi::preparse_policy_set) or [`preparse_schema()`](crate::ffi::preparse_schema) and then
+//! call [`stateful_is_authorized()`](crate::ffi::stateful_is_authorized). The cache is thread-local, so these calls
+//! must be made from the same thread.
+//!
+//! This crate doesn't export a C ABI itself, since doing so requires `unsafe`
+//! code, which this crate forbids. The `cedar-ffi` crate provides one, built
+//! on the types in this module.
+
 mod is_authorized;
 pub use is_authorized::*;
 mod utils;
@@ -632,6 +632,43 @@ impl PolicySet {
     }
 }
 
+impl TryFrom<&crate::PolicySet> for PolicySet {
+    type Error = miette::Report;
+
+    /// Static policies and templates keep their ids; template-linked policies
+    /// are represented as [`TemplateLink`]s against their templates.
+    fn try_from(pset: &crate::PolicySet) -> Result<Self, miette::Report> {
+        let mut static_policies = HashMap::new();
+        let mut template_links = Vec::new();
+        for policy in pset.policies() {
+            match (policy.template_id(), policy.template_links()) {
+                (Some(template_id), Some(values)) => {
+                    let values = values
+                        .into_iter()
+                        .map(|(slot, euid)| EntityUid::try_from(&euid).map(|euid| (slot, euid)))
+                        .collect::<Result<_, _>>()?;
+                    template_links.push(TemplateLink {
+                        template_id: template_id.clone(),
+                        new_id: policy.id().clone(),
+                        values,
+                    });
+                }
+                _ => {
+                    static_policies.insert(policy.id().clone(), Policy::from(policy));
+                }
+            }
+        }
+        Ok(Self {
+            static_policies: StaticPolicySet::Map(static_policies),
+            templates: pset
+                .templates()
+                .map(|template| (template.id().clone(), Template::from(template)))
+                .collect(),
+            template_links,
+        })
+    }
+}
+
 #[cfg(test)]
 impl PolicySet {
     /// Create an empty [`PolicySet`]
@@ -29,17 +29,52 @@
 //! extension functions:
 //!
 //! - `ipaddr` — IP address extension functions (`ip`, `isIpv4`, `isIpv6`,
-//!   `isLoopback`, `isMulticast`, `isInRange`).
+//!   `isLoopback`, `isMulticast`, `isInRange`, `ipset`, `isInSet`).
 //! - `decimal` — Decimal number extension functions (`decimal`, `lessThan`,
-//!   `lessThanOrEqual`, `greaterThan`, `greaterThanOrEqual`).
+//!   `lessThanOrEqual`, `greaterThan`, `greaterThanOrEqual`, `add`, `subtract`,
+//!   `multiply`).
 //! - `datetime` — Date and time extension functions (`datetime`, `duration`,
-//!   `offset`, `durationSince`, `toDate`, `toTime`). Enables the `chrono`
-//!   dependency.
+//!   `offset`, `durationSince`, `toDate`, `toTime`, `dayOfWeek`, `hour`).
+//!   Enables the `chrono` dependency.
 //!
 //! ## Optional features
 //!
+//! - `strings` — String extension methods (`toLowerCase`, `startsWith`,
+//!   `endsWith`, `split`, `length`) on the builtin `String` type. Not
+//!   enabled by default, because it adds methods which policies in other
+//!   Cedar implementations may not support.
+//! - `bytes` — The `bytes` extension type for opaque byte strings, constructed
+//!   from base64 (`bytes`, `byteLength`). Not enabled by default. Enables the
+//!   `base64` dependency.
+//! - `lookup` — The `lookup` extension, which looks up a dotted path given as
+//!   data in nested records (`lookup`), returning an `optional` value
+//!   (`isPresent`, `asString`, `asLong`, `asBool`). Not enabled by default.
+//! - `geo` — The `geopoint` and `geopolygon` extension types for locations
+//!   (`geopoint`, `geopolygon`, `distanceTo`, `isInPolygon`). Not enabled by
+//!   default.
+//! - `identifiers` — The `uuid` and `ulid` extension types for identifiers
+//!   (`uuid`, `ulid`, `uuidVersion`, `timestampMillis`), with ULIDs ordered by
+//!   `<`, `<=`, `>`, and `>=`. Not enabled by default.
+//! - `mediatype` — The `mediatype` extension type for media (MIME) types
+//!   (`mediatype`, `matches`, `essence`), matched against media ranges such as
+//!   `image/*`. Not enabled by default.
+//! - `prf` — The `prf` extension, a keyed hash function (`prf`) for matching
+//!   hashed identifiers in policies. Not enabled by default, and needs a key
+//!   from the application, so it is only available to extension sets built
+//!   with one. Enables the `hmac` and `sha2` dependencies.
+//! - `regions` — The `regions` extension, with methods on ISO 3166-1 country
+//!   codes (`inRegion`, `isCountryCode`) for data-residency policies. Not
+//!   enabled by default, and needs region groupings from the application, so
+//!   it is only available to extension sets built with them.
 //! - `heap-profiling` — Enables heap profiling via `dhat`.
 //! - `corpus-timing` — Enables corpus timing instrumentation.
+//! - `tracing` — Emits [`tracing`](https://docs.rs/tracing) spans at `DEBUG`
+//!   level around schema and policy parsing (`cedar.parse_schema`,
+//!   `cedar.parse_policies`), validation (`cedar.validate`), entity
+//!   construction (`cedar.entities`), transitive closure computation
+//!   (`cedar.compute_tc`), and authorization (`cedar.authorize`), with policy
+//!   and entity counts as span fields. Enables the `tracing` dependency.
+//! - `yaml` — Enables reading [`testing`] suites from YAML files.
 //! - `wasm` — Enables WebAssembly bindings via `wasm-bindgen` and `tsify`.
 //!
 //! ## Experimental features
@@ -50,6 +85,9 @@
 //!
 //! - `experimental` — Enables all experimental features listed below.
 //! - `variadic-is-in-range` — Variadic overload for the `isInRange` function.
+//! - `maps` — `Map<String, T>` schema types, and the `.get()` and
+//!   `.containsKey()` operations on maps. Symbolic analysis doesn't support
+//!   maps yet.
 //! - `tpe` — Type-aware partial evaluation / batched authorization.
 //! - `partial-eval` — Partial evaluation of Cedar policies. You should prefer `tpe` above.
 //! - `partial-validate` — Partial validation of Cedar policies.
@@ -97,8 +135,13 @@ mod api;
 pub use api::version::{get_lang_version, get_sdk_version};
 pub use api::*;
 
+#[cfg(feature = "codegen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
+pub mod codegen;
 /// FFI utilities, see comments in the module itself
 pub mod ffi;
+pub mod lint;
+pub mod testing;
 
 /// Protobuf models of cedar-policy types
 #[cfg(feature = "protobufs")]
@@ -0,0 +1,800 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Linting of Cedar policies.
+//!
+//! A [`Linter`] runs a list of [`LintRule`]s over the static policies and
+//! templates of a [`PolicySet`], optionally with a [`Schema`], and returns the
+//! [`LintFinding`]s they produce. Unlike validation errors, findings point out
+//! policies which are valid but probably don't do what their author intended.
+//!
+//! [`Linter::with_default_rules()`] runs the rules defined in this module:
+//!
+//! - [`AlwaysTrueCondition`]: a `when` or `unless` condition which can never
+//!   rule out a request, e.g., `when { true }`
+//! - [`RedundantHas`]: a `has` test for an attribute the schema declares as
+//!   required
+//! - [`EntityTypeMismatch`]: `principal`, `action` or `resource` compared to
+//!   an entity literal of a type it can never have
+//! - [`BroadPrincipalScope`]: a `permit` policy which doesn't constrain the
+//!   `principal` at all
+//!
+//! Other rules, e.g., organization-specific conventions, are added with
+//! [`Linter::with_rule()`] by implementing [`LintRule`]:
+//!
+//! ```
+//! # use cedar_policy::lint::{LintFinding, LintRule, Linter};
+//! # use cedar_policy::{Policy, PolicySet, Schema, Template};
+//! struct RequireOwner;
+//!
+//! impl LintRule for RequireOwner {
+//!     fn name(&self) -> &str {
+//!         "require-owner"
+//!     }
+//!
+//!     fn check_policy(&self, policy: &Policy, _schema: Option<&Schema>) -> Vec<LintFinding> {
+//!         match policy.annotation("owner") {
+//!             Some(_) => vec![],
+//!             None => vec![LintFinding::new(
+//!                 self.name(),
+//!                 policy.id().clone(),
+//!                 "policy has no `@owner` annotation",
+//!             )],
+//!         }
+//!     }
+//! }
+//!
+//! let policies: PolicySet = r#"
+//!     @owner("payments")
+//!     permit(principal == User::"alice", action, resource) when { true };
+//! "#.parse().unwrap();
+//! let findings = Linter::with_default_rules()
+//!     .with_rule(RequireOwner)
+//!     .lint(&policies, None);
+//! assert_eq!(findings.len(), 1);
+//! assert_eq!(findings[0].rule(), "always-true-condition");
+//! ```
+
+use crate::{
+    AttributeInfo, DeclaredType, EntityTypeName, EntityUid, Policy, PolicyId, PolicySet,
+    RequestEnv, Schema, Template,
+};
+use cedar_policy_core::ast::{
+    self, BinaryOp, Effect, ExprKind, Literal, PrincipalOrResourceConstraint, UnaryOp, Var,
+};
+use cedar_policy_core::parser::Loc;
+use cedar_policy_core::validator::typecheck::Typechecker;
+use cedar_policy_core::validator::ValidationMode;
+use miette::{Diagnostic, LabeledSpan, Severity, SourceSpan};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A check run by a [`Linter`] on every static policy and template.
+pub trait LintRule {
+    /// Name identifying this rule in the findings it produces, e.g.,
+    /// `always-true-condition`
+    fn name(&self) -> &str;
+
+    /// Check the static policy `policy`. `schema` is the schema the policies
+    /// are linted against, if any.
+    fn check_policy(&self, policy: &Policy, schema: Option<&Schema>) -> Vec<LintFinding>;
+
+    /// Check the template `template`. `schema` is the schema the policies are
+    /// linted against, if any. By default, templates are not checked.
+    fn check_template(&self, template: &Template, schema: Option<&Schema>) -> Vec<LintFinding> {
+        let _ = (template, schema);
+        Vec::new()
+    }
+}
+
+/// A problem a [`LintRule`] found in a policy or template
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make fields `pub`, don't make breaking changes, and use caution
+// when adding public methods.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("for policy `{policy_id}`, {message}")]
+pub struct LintFinding {
+    rule: String,
+    policy_id: PolicyId,
+    message: String,
+    help: Option<String>,
+    span: Option<SourceSpan>,
+    /// Source the span indexes into, if known
+    src: Option<Arc<str>>,
+}
+
+impl LintFinding {
+    /// Construct a finding of the rule named `rule` in the policy or template
+    /// `policy_id`
+    pub fn new(rule: impl Into<String>, policy_id: PolicyId, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.into(),
+            policy_id,
+            message: message.into(),
+            help: None,
+            span: None,
+            src: None,
+        }
+    }
+
+    /// Attach a suggestion for fixing the problem
+    #[must_use]
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Attach the location of the problem in the policy source
+    #[must_use]
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Attach the location of the problem, with the source it indexes into
+    fn with_loc(mut self, loc: Option<&Loc>) -> Self {
+        if let Some(loc) = loc {
+            self.span = Some(loc.span);
+            self.src = Some(Arc::clone(&loc.src));
+        }
+        self
+    }
+
+    /// Name of the rule which produced this finding
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// Id of the policy or template this finding is about
+    pub fn policy_id(&self) -> &PolicyId {
+        &self.policy_id
+    }
+
+    /// Description of the problem
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Location of the problem in the policy source, if known
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+}
+
+impl Diagnostic for LintFinding {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(&self.rule))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn Display + 'a>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.src.as_ref().map(|src| src as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.span
+            .map(|span| Box::new(std::iter::once(LabeledSpan::underline(span))) as _)
+    }
+}
+
+/// Runs a list of [`LintRule`]s over policies
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl std::fmt::Debug for Linter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Linter")
+            .field("rules", &self.rule_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Linter {
+    /// Construct a [`Linter`] without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a [`Linter`] with all the rules defined in this module
+    pub fn with_default_rules() -> Self {
+        Self::new()
+            .with_rule(AlwaysTrueCondition)
+            .with_rule(RedundantHas)
+            .with_rule(EntityTypeMismatch)
+            .with_rule(BroadPrincipalScope)
+    }
+
+    /// Add `rule` to the rules this [`Linter`] runs
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Iterate over the names of the rules this [`Linter`] runs
+    pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.name())
+    }
+
+    /// Run every rule on every static policy and template in `policies`.
+    /// Template-linked policies are not checked separately from their
+    /// templates. Rules which need a schema produce no findings if `schema`
+    /// is `None`.
+    pub fn lint(&self, policies: &PolicySet, schema: Option<&Schema>) -> Vec<LintFinding> {
+        let static_findings = policies
+            .policies()
+            .filter(|policy| policy.is_static())
+            .flat_map(|policy| {
+                self.rules
+                    .iter()
+                    .flat_map(move |rule| rule.check_policy(policy, schema))
+            });
+        let template_findings = policies.templates().flat_map(|template| {
+            self.rules
+                .iter()
+                .flat_map(move |rule| rule.check_template(template, schema))
+        });
+        static_findings.chain(template_findings).collect()
+    }
+}
+
+/// Implement [`LintRule`] for a rule defined by a `check` function on the
+/// AST of policies and templates
+macro_rules! ast_lint_rule {
+    ($rule:ident, $name:literal) => {
+        impl LintRule for $rule {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn check_policy(&self, policy: &Policy, schema: Option<&Schema>) -> Vec<LintFinding> {
+                self.check(policy.ast.template(), schema)
+            }
+
+            fn check_template(
+                &self,
+                template: &Template,
+                schema: Option<&Schema>,
+            ) -> Vec<LintFinding> {
+                self.check(&template.ast, schema)
+            }
+        }
+    };
+}
+
+/// Start a finding of `rule` in `template`
+fn finding(rule: &impl LintRule, template: &ast::Template, message: String) -> LintFinding {
+    LintFinding::new(rule.name(), PolicyId::new(template.id()), message)
+}
+
+/// Order `findings` by their position in the policy source, since
+/// subexpressions are not visited in that order
+fn sort_by_position(findings: &mut [LintFinding]) {
+    findings.sort_by_key(|finding| finding.span.map(|span| span.offset()));
+}
+
+/// The request environments in `schema` which match the scope of `template`,
+/// whether or not the template's conditions can be satisfied in them
+fn request_envs(template: &ast::Template, schema: &Schema) -> BTreeSet<RequestEnv> {
+    let typechecker = Typechecker::new(&schema.0, ValidationMode::default());
+    typechecker
+        .typecheck_by_request_env(template)
+        .into_iter()
+        .map(|(env, _)| env.into())
+        .collect()
+}
+
+/// Finds `when` and `unless` conditions which are always true.
+///
+/// For example, `when { true }` or `unless { principal != principal }`. Such
+/// a condition doesn't change when the policy applies, which usually means it
+/// is unfinished or was meant to test something else.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysTrueCondition;
+
+ast_lint_rule!(AlwaysTrueCondition, "always-true-condition");
+
+impl AlwaysTrueCondition {
+    fn check(self, template: &ast::Template, _schema: Option<&Schema>) -> Vec<LintFinding> {
+        let mut conjuncts = Vec::new();
+        if let Some(condition) = template.non_scope_constraints() {
+            collect_conjuncts(condition, &mut conjuncts);
+        }
+        conjuncts
+            .into_iter()
+            .filter(|expr| is_always(expr, true))
+            .map(|expr| {
+                finding(&self, template, "this condition is always true".into())
+                    .with_help("remove the condition, or correct it to test what was intended")
+                    .with_loc(expr.source_loc())
+            })
+            .collect()
+    }
+}
+
+/// Collect the expressions `expr` is a conjunction of, which include the
+/// individual `when` and `unless` conditions of a policy
+fn collect_conjuncts<'a>(expr: &'a ast::Expr, conjuncts: &mut Vec<&'a ast::Expr>) {
+    match expr.expr_kind() {
+        ExprKind::And { left, right } => {
+            collect_conjuncts(left, conjuncts);
+            collect_conjuncts(right, conjuncts);
+        }
+        _ => conjuncts.push(expr),
+    }
+}
+
+/// Does `expr` always evaluate to `value` (if it doesn't error)?
+fn is_always(expr: &ast::Expr, value: bool) -> bool {
+    match expr.expr_kind() {
+        ExprKind::Lit(Literal::Bool(b)) => *b == value,
+        ExprKind::UnaryApp {
+            op: UnaryOp::Not,
+            arg,
+        } => is_always(arg, !value),
+        ExprKind::And { left, right } if value => is_always(left, true) && is_always(right, true),
+        ExprKind::And { left, right } => is_always(left, false) || is_always(right, false),
+        ExprKind::Or { left, right } if value => is_always(left, true) || is_always(right, true),
+        ExprKind::Or { left, right } => is_always(left, false) && is_always(right, false),
+        // Comparing a variable or literal to itself
+        ExprKind::BinaryApp {
+            op: BinaryOp::Eq,
+            arg1,
+            arg2,
+        } => {
+            value
+                && matches!(arg1.expr_kind(), ExprKind::Var(_) | ExprKind::Lit(_))
+                && arg1.eq_shape(arg2)
+        }
+        _ => false,
+    }
+}
+
+/// Finds `has` tests for attributes which the schema declares as required.
+///
+/// For example, `principal has name` where every `principal` the policy
+/// applies to has a required `name` attribute. Such a test is always true.
+/// Requires a schema.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedundantHas;
+
+ast_lint_rule!(RedundantHas, "redundant-has");
+
+impl RedundantHas {
+    fn check(self, template: &ast::Template, schema: Option<&Schema>) -> Vec<LintFinding> {
+        let Some(schema) = schema else {
+            return Vec::new();
+        };
+        let Some(condition) = template.non_scope_constraints() else {
+            return Vec::new();
+        };
+        let envs = request_envs(template, schema);
+        if envs.is_empty() {
+            return Vec::new();
+        }
+        let mut findings: Vec<_> = condition
+            .subexpressions()
+            .filter_map(|expr| match expr.expr_kind() {
+                ExprKind::HasAttr { expr: record, attr }
+                    if envs
+                        .iter()
+                        .all(|env| is_required_attr(record, attr, env, schema)) =>
+                {
+                    Some(
+                        finding(
+                            &self,
+                            template,
+                            format!(
+                                "`{attr}` is a required attribute, so this `has` test is always true"
+                            ),
+                        )
+                        .with_help(format!("remove the `has {attr}` test"))
+                        .with_loc(expr.source_loc()),
+                    )
+                }
+                _ => None,
+            })
+            .collect();
+        sort_by_position(&mut findings);
+        findings
+    }
+}
+
+/// Is `attr` a required attribute of `expr` in the request environment `env`?
+fn is_required_attr(expr: &ast::Expr, attr: &str, env: &RequestEnv, schema: &Schema) -> bool {
+    match declared_type(expr, env, schema) {
+        Some(DeclaredType::Record { attributes, .. }) => {
+            attributes.get(attr).is_some_and(AttributeInfo::is_required)
+        }
+        Some(DeclaredType::Entity(ty)) => schema
+            .entity_type(&ty)
+            .and_then(|info| info.attribute(attr))
+            .is_some_and(|attr| attr.is_required()),
+        _ => false,
+    }
+}
+
+/// The type `schema` declares for `expr` in the request environment `env`,
+/// for the variables `principal`, `resource` and `context`, and the
+/// attributes of expressions with a declared type
+fn declared_type(expr: &ast::Expr, env: &RequestEnv, schema: &Schema) -> Option<DeclaredType> {
+    match expr.expr_kind() {
+        ExprKind::Var(Var::Principal) => Some(DeclaredType::Entity(env.principal().clone())),
+        ExprKind::Var(Var::Resource) => Some(DeclaredType::Entity(env.resource().clone())),
+        ExprKind::Var(Var::Context) => schema.context_type(env.action()),
+        ExprKind::GetAttr { expr, attr } => match declared_type(expr, env, schema)? {
+            DeclaredType::Record { attributes, .. } => {
+                attributes.get(attr.as_str()).map(|attr| attr.ty().clone())
+            }
+            DeclaredType::Entity(ty) => schema
+                .entity_type(&ty)?
+                .attribute(attr)
+                .map(|attr| attr.ty().clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Finds comparisons to entity literals of a type the compared variable can
+/// never have.
+///
+/// For example, `principal == Group::"admins"` in a policy which only applies
+/// to `User` principals. Such a comparison is always false. The possible types
+/// of `principal`, `action` and `resource` are taken from the schema, if any,
+/// or else from the policy scope.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EntityTypeMismatch;
+
+ast_lint_rule!(EntityTypeMismatch, "entity-type-mismatch");
+
+impl EntityTypeMismatch {
+    fn check(self, template: &ast::Template, schema: Option<&Schema>) -> Vec<LintFinding> {
+        let Some(condition) = template.non_scope_constraints() else {
+            return Vec::new();
+        };
+        let envs = schema.map(|schema| request_envs(template, schema));
+        let mut findings: Vec<_> = condition
+            .subexpressions()
+            .filter_map(|expr| {
+                let ExprKind::BinaryApp {
+                    op: BinaryOp::Eq,
+                    arg1,
+                    arg2,
+                } = expr.expr_kind()
+                else {
+                    return None;
+                };
+                let ((ExprKind::Var(var), ExprKind::Lit(Literal::EntityUID(euid)))
+                | (ExprKind::Lit(Literal::EntityUID(euid)), ExprKind::Var(var))) =
+                    (arg1.expr_kind(), arg2.expr_kind())
+                else {
+                    return None;
+                };
+                let possible = match &envs {
+                    Some(envs) => possible_types_in_envs(*var, envs)?,
+                    None => possible_types_in_scope(*var, template)?,
+                };
+                let ty = EntityTypeName::from(euid.entity_type().clone());
+                if possible.contains(&ty) {
+                    return None;
+                }
+                Some(
+                    finding(
+                        &self,
+                        template,
+                        format!("`{var}` is never a `{ty}`, so this comparison is always false"),
+                    )
+                    .with_help(format!(
+                        "`{var}` is a {} in this policy",
+                        possible
+                            .iter()
+                            .map(|ty| format!("`{ty}`"))
+                            .collect::<Vec<_>>()
+                            .join(" or ")
+                    ))
+                    .with_loc(expr.source_loc()),
+                )
+            })
+            .collect();
+        sort_by_position(&mut findings);
+        findings
+    }
+}
+
+/// The entity types `var` has in the request environments `envs`, or `None`
+/// if it is not `principal`, `action` or `resource`, or there are no
+/// environments
+fn possible_types_in_envs(
+    var: Var,
+    envs: &BTreeSet<RequestEnv>,
+) -> Option<BTreeSet<EntityTypeName>> {
+    let types: BTreeSet<_> = envs
+        .iter()
+        .filter_map(|env| match var {
+            Var::Principal => Some(env.principal().clone()),
+            Var::Action => Some(env.action().type_name().clone()),
+            Var::Resource => Some(env.resource().clone()),
+            Var::Context => None,
+        })
+        .collect();
+    (!types.is_empty()).then_some(types)
+}
+
+/// The entity type the scope of `template` constrains `var` to, or `None` if
+/// the scope doesn't determine it
+fn possible_types_in_scope(var: Var, template: &ast::Template) -> Option<BTreeSet<EntityTypeName>> {
+    let constraint = match var {
+        Var::Principal => template.principal_constraint().as_inner().clone(),
+        Var::Resource => template.resource_constraint().as_inner().clone(),
+        Var::Action => {
+            return match template.action_constraint() {
+                ast::ActionConstraint::Eq(euid) => {
+                    Some(BTreeSet::from([EntityUid::from(euid.as_ref().clone())
+                        .type_name()
+                        .clone()]))
+                }
+                _ => None,
+            }
+        }
+        Var::Context => return None,
+    };
+    let ty = match constraint {
+        PrincipalOrResourceConstraint::Eq(ast::EntityReference::EUID(euid)) => {
+            euid.entity_type().clone()
+        }
+        PrincipalOrResourceConstraint::Is(ty) | PrincipalOrResourceConstraint::IsIn(ty, _) => {
+            ty.as_ref().clone()
+        }
+        _ => return None,
+    };
+    Some(BTreeSet::from([EntityTypeName::from(ty)]))
+}
+
+/// Finds `permit` policies which grant access to every principal.
+///
+/// These are the policies which neither constrain the `principal` in their
+/// scope nor mention it in their conditions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BroadPrincipalScope;
+
+ast_lint_rule!(BroadPrincipalScope, "broad-principal-scope");
+
+impl BroadPrincipalScope {
+    fn check(self, template: &ast::Template, _schema: Option<&Schema>) -> Vec<LintFinding> {
+        let unconstrained = template.effect() == Effect::Permit
+            && matches!(
+                template.principal_constraint().as_inner(),
+                PrincipalOrResourceConstraint::Any
+            )
+            && !template.non_scope_constraints().is_some_and(|condition| {
+                condition
+                    .subexpressions()
+                    .any(|expr| matches!(expr.expr_kind(), ExprKind::Var(Var::Principal)))
+            });
+        if unconstrained {
+            vec![
+                finding(&self, template, "this policy permits every principal".into())
+                    .with_help("constrain the `principal` in the policy scope, e.g., `principal in Group::\"admins\"`, or in a `when` condition")
+                    .with_loc(template.loc()),
+            ]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn lint(rule: impl LintRule + 'static, src: &str, schema: Option<&Schema>) -> Vec<LintFinding> {
+        let policies = PolicySet::from_str(src).unwrap();
+        Linter::new().with_rule(rule).lint(&policies, schema)
+    }
+
+    /// The source text each finding points to
+    fn snippets<'a>(findings: &[LintFinding], src: &'a str) -> Vec<&'a str> {
+        findings
+            .iter()
+            .map(|finding| {
+                let span = finding.span().unwrap();
+                &src[span.offset()..span.offset() + span.len()]
+            })
+            .collect()
+    }
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+            entity Group;
+            entity User in Group { name: String, nick?: String };
+            entity Photo { owner: User };
+            action view appliesTo {
+                principal: User,
+                resource: Photo,
+                context: { ip: ipaddr, mfa?: Bool },
+            };
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn always_true_condition() {
+        let src = r#"
+            permit(principal == User::"a", action, resource) when { true } unless { false };
+            permit(principal == User::"a", action, resource) when { principal == principal };
+            permit(principal == User::"a", action, resource) when { context.mfa || true };
+            permit(principal == User::"a", action, resource) when { context.mfa } unless { context.a && false };
+        "#;
+        let findings = lint(AlwaysTrueCondition, src, None);
+        assert_eq!(
+            snippets(&findings, src),
+            vec![
+                "true",
+                "unless { false }",
+                "principal == principal",
+                "context.mfa || true",
+                "unless { context.a && false }",
+            ]
+        );
+        assert_eq!(findings[0].rule(), "always-true-condition");
+        assert_eq!(findings[0].policy_id(), &PolicyId::new("policy0"));
+        assert_eq!(
+            findings[0].to_string(),
+            "for policy `policy0`, this condition is always true"
+        );
+    }
+
+    #[test]
+    fn redundant_has() {
+        let src = r#"
+            permit(principal, action == Action::"view", resource)
+            when { principal has name && principal has nick && resource.owner has name }
+            unless { context has ip || context has mfa };
+        "#;
+        let schema = schema();
+        let findings = lint(RedundantHas, src, Some(&schema));
+        assert_eq!(
+            snippets(&findings, src),
+            vec![
+                "principal has name",
+                "resource.owner has name",
+                "context has ip"
+            ]
+        );
+        assert!(lint(RedundantHas, src, None).is_empty());
+    }
+
+    #[test]
+    fn entity_type_mismatch() {
+        let src = r#"
+            permit(principal is User, action, resource) when { principal == Group::"g" || principal == User::"u" };
+            permit(principal, action, resource) when { principal == Group::"g" };
+            permit(principal, action == Action::"view", resource) when { Photo::"p" == resource && resource == User::"u" };
+        "#;
+        let findings = lint(EntityTypeMismatch, src, None);
+        assert_eq!(snippets(&findings, src), vec![r#"principal == Group::"g""#]);
+
+        let schema = schema();
+        let findings = lint(EntityTypeMismatch, src, Some(&schema));
+        assert_eq!(
+            snippets(&findings, src),
+            vec![
+                r#"principal == Group::"g""#,
+                r#"principal == Group::"g""#,
+                r#"resource == User::"u""#,
+            ]
+        );
+        assert_eq!(
+            findings[2].to_string(),
+            "for policy `policy2`, `resource` is never a `User`, so this comparison is always false"
+        );
+    }
+
+    #[test]
+    fn broad_principal_scope() {
+        let src = r#"
+            permit(principal, action, resource);
+            permit(principal, action, resource) when { principal.name == "a" };
+            forbid(principal, action, resource);
+            permit(principal in Group::"g", action, resource);
+            permit(principal, action, resource == ?resource);
+        "#;
+        let findings = lint(BroadPrincipalScope, src, None);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|finding| finding.policy_id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["policy0", "policy4"]
+        );
+        assert_eq!(
+            snippets(&findings[..1], src),
+            vec!["permit(principal, action, resource);"]
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        struct NoTemplates;
+
+        impl LintRule for NoTemplates {
+            fn name(&self) -> &str {
+                "no-templates"
+            }
+
+            fn check_policy(&self, _: &Policy, _: Option<&Schema>) -> Vec<LintFinding> {
+                Vec::new()
+            }
+
+            fn check_template(&self, template: &Template, _: Option<&Schema>) -> Vec<LintFinding> {
+                vec![LintFinding::new(
+                    self.name(),
+                    template.id().clone(),
+                    "templates are not allowed",
+                )
+                .with_help("use a static policy")]
+            }
+        }
+
+        let linter = Linter::with_default_rules().with_rule(NoTemplates);
+        assert_eq!(
+            linter.rule_names().collect::<Vec<_>>(),
+            vec![
+                "always-true-condition",
+                "redundant-has",
+                "entity-type-mismatch",
+                "broad-principal-scope",
+                "no-templates",
+            ]
+        );
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal == User::"a", action, resource);
+            permit(principal == ?principal, action, resource) when { true };
+            "#,
+        )
+        .unwrap();
+        let findings = linter.lint(&policies, None);
+        assert_eq!(
+            findings.iter().map(LintFinding::rule).collect::<Vec<_>>(),
+            vec!["always-true-condition", "no-templates"]
+        );
+        assert_eq!(findings[1].policy_id(), &PolicyId::new("policy1"));
+        assert_eq!(findings[1].span(), None);
+        assert_eq!(
+            findings[1].help().map(|help| help.to_string()).as_deref(),
+            Some("use a static policy")
+        );
+    }
+}
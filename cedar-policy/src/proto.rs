@@ -56,3 +56,5 @@ mod api;
 
 /// `Protobuf` trait and associated utilities
 pub mod traits;
+
+pub mod definitions;
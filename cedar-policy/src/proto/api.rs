@@ -48,6 +48,7 @@ fallible_conversions!(api::EntityTypeName, api::EntityTypeName, models::Name);
 fallible_conversions!(api::EntityNamespace, api::EntityNamespace, models::Name);
 fallible_conversions!(api::Expression, api::Expression, models::Expr);
 fallible_conversions!(api::Request, api::Request, models::Request);
+fallible_conversions!(api::Context, api::Context, models::Context);
 
 // nonstandard conversions
 
@@ -110,6 +111,7 @@ standard_protobuf_impl!(api::EntityNamespace, models::Name);
 standard_protobuf_impl!(api::Template, models::TemplateBody);
 standard_protobuf_impl!(api::Expression, models::Expr);
 standard_protobuf_impl!(api::Request, models::Request);
+standard_protobuf_impl!(api::Context, models::Context);
 
 // nonstandard implementations of `traits::Protobuf`
 
@@ -328,6 +330,50 @@ mod test {
         );
     }
 
+    #[test]
+    fn roundtrip_context() {
+        use crate::proto::traits::Protobuf;
+
+        let context = crate::Context::from_json_str(
+            r#"{
+                "authenticated": true,
+                "level": 3,
+                "src_ip": { "__extn": { "fn": "ip", "arg": "10.0.0.1" } },
+                "owner": { "__entity": { "type": "User", "id": "alice" } },
+                "tags": ["a", "b"],
+                "nested": { "x": "y" }
+            }"#,
+            None,
+        )
+        .expect("Failed to parse context");
+        let roundtripped =
+            crate::Context::decode(&context.encode()[..]).expect("Failed to decode context");
+        similar_asserts::assert_eq!(context, roundtripped);
+    }
+
+    #[test]
+    fn roundtrip_request_context() {
+        use crate::proto::traits::Protobuf;
+
+        let context = crate::Context::from_json_str(r#"{ "authenticated": true }"#, None)
+            .expect("Failed to parse context");
+        let request = crate::Request::new(
+            crate::EntityUid::from_strs("User", "alice"),
+            crate::EntityUid::from_strs("Action", "view"),
+            crate::EntityUid::from_strs("Photo", "vacation"),
+            context.clone(),
+            None,
+        )
+        .expect("Failed to build request");
+        let roundtripped =
+            crate::Request::decode(&request.encode()[..]).expect("Failed to decode request");
+        assert_eq!(roundtripped.context(), Some(&context));
+        assert_eq!(
+            roundtripped.principal(),
+            Some(&crate::EntityUid::from_strs("User", "alice"))
+        );
+    }
+
     /// Decoding arbitrary bytes must never panic — it should return `Err`.
     #[test]
     fn decode_random_bytes_does_not_panic() {
@@ -357,6 +403,7 @@ mod test {
             let _ = crate::Template::decode(*input);
             let _ = crate::Expression::decode(*input);
             let _ = crate::Request::decode(*input);
+            let _ = crate::Context::decode(*input);
             let _ = crate::PolicySet::decode(*input);
         }
     }
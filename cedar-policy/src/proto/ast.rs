@@ -335,7 +335,7 @@ impl TryFrom<models::Expr> for ast::Expr {
                     .right
                     .ok_or_else(|| ProtobufConversionError::missing("right"))?;
                 Ok(ast::Expr::binary_app(
-                    ast::BinaryOp::from(pbop),
+                    ast::BinaryOp::try_from(pbop)?,
                     ast::Expr::try_from(left)?,
                     ast::Expr::try_from(right)?,
                 ))
@@ -643,9 +643,10 @@ impl From<&ast::UnaryOp> for models::expr::unary_app::Op {
     }
 }
 
-impl From<models::expr::binary_app::Op> for ast::BinaryOp {
-    fn from(v: models::expr::binary_app::Op) -> Self {
-        match v {
+impl TryFrom<models::expr::binary_app::Op> for ast::BinaryOp {
+    type Error = ProtobufConversionError;
+    fn try_from(v: models::expr::binary_app::Op) -> Result<Self, Self::Error> {
+        Ok(match v {
             models::expr::binary_app::Op::Eq => ast::BinaryOp::Eq,
             models::expr::binary_app::Op::Less => ast::BinaryOp::Less,
             models::expr::binary_app::Op::LessEq => ast::BinaryOp::LessEq,
@@ -658,7 +659,18 @@ impl From<models::expr::binary_app::Op> for ast::BinaryOp {
             models::expr::binary_app::Op::ContainsAny => ast::BinaryOp::ContainsAny,
             models::expr::binary_app::Op::GetTag => ast::BinaryOp::GetTag,
             models::expr::binary_app::Op::HasTag => ast::BinaryOp::HasTag,
-        }
+            #[cfg(feature = "maps")]
+            models::expr::binary_app::Op::GetKey => ast::BinaryOp::GetKey,
+            #[cfg(feature = "maps")]
+            models::expr::binary_app::Op::ContainsKey => ast::BinaryOp::ContainsKey,
+            #[cfg(not(feature = "maps"))]
+            models::expr::binary_app::Op::GetKey | models::expr::binary_app::Op::ContainsKey => {
+                return Err(ProtobufConversionError::InvalidValue(format!(
+                    "binary op `{}` requires the `maps` feature",
+                    v.as_str_name()
+                )));
+            }
+        })
     }
 }
 
@@ -677,6 +689,10 @@ impl From<&ast::BinaryOp> for models::expr::binary_app::Op {
             ast::BinaryOp::ContainsAny => models::expr::binary_app::Op::ContainsAny,
             ast::BinaryOp::GetTag => models::expr::binary_app::Op::GetTag,
             ast::BinaryOp::HasTag => models::expr::binary_app::Op::HasTag,
+            #[cfg(feature = "maps")]
+            ast::BinaryOp::GetKey => models::expr::binary_app::Op::GetKey,
+            #[cfg(feature = "maps")]
+            ast::BinaryOp::ContainsKey => models::expr::binary_app::Op::ContainsKey,
         }
     }
 }
@@ -739,26 +755,9 @@ impl TryFrom<models::Request> for ast::Request {
                 v.resource
                     .ok_or_else(|| ProtobufConversionError::missing("resource"))?,
             )?,
-            Some(
-                ast::Context::from_pairs(
-                    v.context
-                        .into_iter()
-                        .map(|(k, v)| {
-                            let expr = ast::Expr::try_from(v)?;
-                            let restricted = ast::RestrictedExpr::new(expr).map_err(|e| {
-                                ProtobufConversionError::InvalidValue(format!(
-                                    "invalid restricted expr in context key `{k}`: {e}"
-                                ))
-                            })?;
-                            Ok((k.to_smolstr(), restricted))
-                        })
-                        .collect::<Result<Vec<_>, ProtobufConversionError>>()?,
-                    Extensions::all_available(),
-                )
-                .map_err(|e| {
-                    ProtobufConversionError::InvalidValue(format!("invalid context: {e}"))
-                })?,
-            ),
+            Some(ast::Context::try_from(models::Context {
+                pairs: v.context,
+            })?),
         ))
     }
 }
@@ -770,20 +769,50 @@ impl From<&ast::Request> for models::Request {
             principal: Some(models::EntityUid::from(v.principal())),
             action: Some(models::EntityUid::from(v.action())),
             resource: Some(models::EntityUid::from(v.resource())),
-            context: {
-                let ctx = v.context().expect(
+            context: models::Context::from(
+                v.context().expect(
                     "Requests with unknown context currently cannot be modeled in protobuf",
-                );
-                match ctx {
-                    ast::Context::Value(map) => map
-                        .iter()
-                        .map(|(k, v)| (k.to_string(), models::Expr::from(v)))
-                        .collect(),
-                    ast::Context::RestrictedResidual(map) => map
-                        .iter()
-                        .map(|(k, v)| (k.to_string(), models::Expr::from(v)))
-                        .collect(),
-                }
+                ),
+            )
+            .pairs,
+        }
+    }
+}
+
+impl TryFrom<models::Context> for ast::Context {
+    type Error = ProtobufConversionError;
+    fn try_from(v: models::Context) -> Result<Self, Self::Error> {
+        ast::Context::from_pairs(
+            v.pairs
+                .into_iter()
+                .map(|(k, v)| {
+                    let expr = ast::Expr::try_from(v)?;
+                    let restricted = ast::RestrictedExpr::new(expr).map_err(|e| {
+                        ProtobufConversionError::InvalidValue(format!(
+                            "invalid restricted expr in context key `{k}`: {e}"
+                        ))
+                    })?;
+                    Ok((k.to_smolstr(), restricted))
+                })
+                .collect::<Result<Vec<_>, ProtobufConversionError>>()?,
+            Extensions::all_available(),
+        )
+        .map_err(|e| ProtobufConversionError::InvalidValue(format!("invalid context: {e}")))
+    }
+}
+
+impl From<&ast::Context> for models::Context {
+    fn from(v: &ast::Context) -> Self {
+        Self {
+            pairs: match v {
+                ast::Context::Value(map) => map
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), models::Expr::from(v)))
+                    .collect(),
+                ast::Context::RestrictedResidual(map) => map
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), models::Expr::from(v)))
+                    .collect(),
             },
         }
     }
@@ -976,6 +1005,30 @@ mod test {
         assert_eq!(e, ast::Expr::try_from(models::Expr::from(&e)).unwrap());
     }
 
+    #[test]
+    #[cfg(feature = "maps")]
+    fn map_expr_roundtrip() {
+        let e = ast::Expr::get_key(
+            ast::Expr::get_attr(ast::Expr::var(ast::Var::Principal), "scores".into()),
+            ast::Expr::val("math"),
+        );
+        assert_eq!(e, ast::Expr::try_from(models::Expr::from(&e)).unwrap());
+        let e = ast::Expr::contains_key(
+            ast::Expr::record([
+                ("a".into(), ast::Expr::val(1)),
+                ("b".into(), ast::Expr::val(2)),
+            ])
+            .unwrap(),
+            ast::Expr::get_attr(ast::Expr::var(ast::Var::Context), "key".into()),
+        );
+        assert_eq!(e, ast::Expr::try_from(models::Expr::from(&e)).unwrap());
+        let e: ast::Expr =
+            r#"principal.scores.containsKey("math") && principal.scores.get("math") > 3"#
+                .parse()
+                .unwrap();
+        assert_eq!(e, ast::Expr::try_from(models::Expr::from(&e)).unwrap());
+    }
+
     #[test]
     fn literal_roundtrip() {
         let bool_literal_f = ast::Literal::from(false);
@@ -0,0 +1,473 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generation of `.proto` definitions from a [`Schema`].
+//!
+//! [`generate()`] produces a proto3 file declaring a message for every
+//! standard entity type declared in a schema, holding the attributes (and
+//! tags) of entities of that type, and a message for the context of every
+//! action that declares a non-empty context. Optional attributes become
+//! `optional` fields, sets become `repeated` fields, and record types become
+//! nested messages. Entity references are `cedar_policy_core.EntityUid`s from
+//! `core.proto`, and values of extension types are the string passed to their
+//! constructor, e.g., `"10.0.0.1"` for an `ipaddr`.
+//!
+//! Fields are numbered in the order of the attribute names, so adding an
+//! attribute to the schema may renumber the existing fields of a message.
+
+use crate::{AttributeInfo, DeclaredType, Schema};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+/// Generate a proto3 file in the package `package`, declaring messages for
+/// the entity types and action contexts declared in `schema`. See the
+/// [module documentation](self).
+pub fn generate(schema: &Schema, package: &str) -> String {
+    let entity_types = schema
+        .entity_types()
+        .filter_map(|ety| schema.entity_type(ety))
+        .filter(|info| info.enum_choices().is_none())
+        .sorted_by_key(|info| info.name().to_string())
+        .collect_vec();
+    let actions = schema
+        .actions()
+        .filter_map(|action| match schema.context_type(action)? {
+            DeclaredType::Record { attributes, .. } if !attributes.is_empty() => {
+                Some((action, attributes))
+            }
+            _ => None,
+        })
+        .sorted_by_key(|(action, _)| action.to_string())
+        .collect_vec();
+
+    // Reserve the names of the entity messages first, so they keep their
+    // natural names even if a context message would also want it
+    let mut names = Names::default();
+    let entity_names = entity_types
+        .iter()
+        .map(|info| names.reserve(&message_name(&info.name().to_string())))
+        .collect_vec();
+    let context_names = actions
+        .iter()
+        .map(|(action, _)| {
+            let path = format!(
+                "{}::{}",
+                action.type_name().namespace(),
+                action.id().unescaped()
+            );
+            names.reserve(&format!("{}Context", message_name(&path)))
+        })
+        .collect_vec();
+
+    let mut uses_core = false;
+    let mut messages = Vec::new();
+    for (info, name) in entity_types.iter().zip(entity_names) {
+        let attributes = info.attributes().map(|(k, v)| (k.to_string(), v)).collect();
+        messages.push(message(
+            &mut uses_core,
+            name,
+            format!("Attributes of an entity of type `{}`", info.name()),
+            &attributes,
+            info.tag_type().as_ref(),
+        ));
+    }
+    for ((action, attributes), name) in actions.iter().zip(context_names) {
+        messages.push(message(
+            &mut uses_core,
+            name,
+            format!("Context of a request for the action `{action}`"),
+            attributes,
+            None,
+        ));
+    }
+
+    let mut out = String::from(
+        "// @generated by `cedar_policy::proto::definitions::generate()`. Do not edit by hand.\n",
+    );
+    let _ = writeln!(out, "syntax = \"proto3\";");
+    let _ = writeln!(out, "package {package};");
+    if uses_core {
+        let _ = writeln!(out, "\nimport \"core.proto\";");
+    }
+    for message in messages {
+        out.push('\n');
+        message.write(&mut out, 0);
+    }
+    out
+}
+
+/// Message names, or field names, already used in one scope
+#[derive(Debug, Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    /// Reserve a name derived from `name`, distinct from the names reserved
+    /// so far
+    fn reserve(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut i = 2;
+        while !self.0.insert(candidate.clone()) {
+            candidate = format!("{name}{i}");
+            i += 1;
+        }
+        candidate
+    }
+}
+
+/// A message declaration, with the messages nested in it
+#[derive(Debug)]
+struct Message {
+    name: String,
+    doc: String,
+    /// Field declarations, e.g., `optional string name = 1;`, each with the
+    /// comment preceding it
+    fields: Vec<(String, String)>,
+    nested: Vec<Message>,
+    /// Names used by the messages nested in this one
+    nested_names: Names,
+}
+
+impl Message {
+    fn new(name: String, doc: String) -> Self {
+        let mut nested_names = Names::default();
+        // A nested message named like its parent would shadow it
+        nested_names.reserve(&name);
+        Self {
+            name,
+            doc,
+            fields: Vec::new(),
+            nested: Vec::new(),
+            nested_names,
+        }
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(out, "{indent}// {}", self.doc);
+        if self.fields.is_empty() && self.nested.is_empty() {
+            let _ = writeln!(out, "{indent}message {} {{}}", self.name);
+            return;
+        }
+        let _ = writeln!(out, "{indent}message {} {{", self.name);
+        for message in &self.nested {
+            message.write(out, depth + 1);
+            out.push('\n');
+        }
+        for (comment, field) in &self.fields {
+            let _ = writeln!(out, "{indent}    // {comment}");
+            let _ = writeln!(out, "{indent}    {field}");
+        }
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+/// Generate a message named `name` with one field per attribute, and a
+/// `tags` field if `tags` is the type of entity tags
+fn message(
+    uses_core: &mut bool,
+    name: String,
+    doc: String,
+    attributes: &BTreeMap<String, AttributeInfo>,
+    tags: Option<&DeclaredType>,
+) -> Message {
+    let mut message = Message::new(name, doc);
+    let mut field_names = Names::default();
+    let mut number = 0;
+    for (attr, info) in attributes {
+        number += 1;
+        let field = field_names.reserve(&field_name(attr));
+        let ty = field_type(uses_core, &mut message, attr, info.ty(), info.is_required());
+        message.fields.push((
+            format!("The `{}` attribute", attr.escape_debug()),
+            format!("{ty} {field} = {number};"),
+        ));
+    }
+    if let Some(tags) = tags {
+        number += 1;
+        let field = field_names.reserve("tags");
+        let value = element_type(uses_core, &mut message, "tag", tags);
+        message.fields.push((
+            "The tags of the entity".into(),
+            format!("map<string, {value}> {field} = {number};"),
+        ));
+    }
+    message
+}
+
+/// The type of the field for the attribute `attr` of `owner`, including its
+/// label
+fn field_type(
+    uses_core: &mut bool,
+    owner: &mut Message,
+    attr: &str,
+    ty: &DeclaredType,
+    required: bool,
+) -> String {
+    match ty {
+        // `repeated` and `map` fields can't be `optional`, so optional sets
+        // and maps are wrapped in a message instead
+        DeclaredType::Set(element) if required => {
+            format!("repeated {}", element_type(uses_core, owner, attr, element))
+        }
+        DeclaredType::Map(value) if required => {
+            format!(
+                "map<string, {}>",
+                element_type(uses_core, owner, attr, value)
+            )
+        }
+        _ => {
+            let ty = element_type(uses_core, owner, attr, ty);
+            if required {
+                ty
+            } else {
+                format!("optional {ty}")
+            }
+        }
+    }
+}
+
+/// The type of a single value of type `ty`, held by the attribute `attr` of
+/// `owner`. Messages for records, and wrappers for sets and maps, are nested
+/// in `owner`.
+fn element_type(
+    uses_core: &mut bool,
+    owner: &mut Message,
+    attr: &str,
+    ty: &DeclaredType,
+) -> String {
+    match ty {
+        DeclaredType::Bool => "bool".into(),
+        DeclaredType::Long => "int64".into(),
+        DeclaredType::String | DeclaredType::Extension(_) => "string".into(),
+        DeclaredType::Entity(_) | DeclaredType::EntityUnion(_) => {
+            *uses_core = true;
+            "cedar_policy_core.EntityUid".into()
+        }
+        DeclaredType::Record { attributes, .. } => {
+            let name = owner.nested_names.reserve(&message_name(attr));
+            let nested = message(
+                uses_core,
+                name.clone(),
+                format!("Type of the `{}` attribute", attr.escape_debug()),
+                attributes,
+                None,
+            );
+            owner.nested.push(nested);
+            name
+        }
+        DeclaredType::Set(_) | DeclaredType::Map(_) => {
+            let name = owner
+                .nested_names
+                .reserve(&format!("{}Value", message_name(attr)));
+            let mut wrapper = Message::new(
+                name.clone(),
+                format!("Value of the `{}` attribute", attr.escape_debug()),
+            );
+            let field = field_type(uses_core, &mut wrapper, "values", ty, true);
+            wrapper
+                .fields
+                .push(("The values".into(), format!("{field} values = 1;")));
+            owner.nested.push(wrapper);
+            name
+        }
+    }
+}
+
+/// Split `s` into words at non-alphanumeric characters and at lowercase to
+/// uppercase transitions
+fn words(s: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if !c.is_ascii_alphanumeric() {
+            words.push(String::new());
+            prev_lower = false;
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if !(prev_lower && c.is_ascii_uppercase()) => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    words.retain(|w| !w.is_empty());
+    words
+}
+
+/// Convert `s`, e.g., an entity type name, into a `PascalCase` message name
+fn message_name(s: &str) -> String {
+    let name: String = words(s)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("T{name}")
+    }
+}
+
+/// Convert the attribute name `s` into a `snake_case` field name
+fn field_name(s: &str) -> String {
+    let name = words(s).iter().map(|w| w.to_ascii_lowercase()).join("_");
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("f_{name}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(message_name("Photos::User"), "PhotosUser");
+        assert_eq!(message_name("view photo"), "ViewPhoto");
+        assert_eq!(message_name("1st"), "T1st");
+        assert_eq!(field_name("zipCode"), "zip_code");
+        assert_eq!(field_name("2fa"), "f_2fa");
+        assert_eq!(field_name(""), "f_");
+    }
+
+    #[test]
+    fn photos() {
+        let schema = Schema::from_str(
+            r#"
+            namespace Photos {
+                entity User {
+                    name: String,
+                    age?: Long,
+                    address?: { street: String, zipCode?: String },
+                    roles: Set<String>,
+                    friends?: Set<User>,
+                    ip: ipaddr,
+                };
+                entity Photo {
+                    matrix: Set<Set<Long>>,
+                } tags Set<String>;
+                entity Color enum ["red", "blue"];
+                action view appliesTo {
+                    principal: User,
+                    resource: Photo,
+                    context: { authenticated: Bool },
+                };
+                action delete appliesTo { principal: User, resource: Photo };
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            generate(&schema, "acme.photos"),
+            r#"// @generated by `cedar_policy::proto::definitions::generate()`. Do not edit by hand.
+syntax = "proto3";
+package acme.photos;
+
+import "core.proto";
+
+// Attributes of an entity of type `Photos::Photo`
+message PhotosPhoto {
+    // Value of the `matrix` attribute
+    message MatrixValue {
+        // The values
+        repeated int64 values = 1;
+    }
+
+    // Value of the `tag` attribute
+    message TagValue {
+        // The values
+        repeated string values = 1;
+    }
+
+    // The `matrix` attribute
+    repeated MatrixValue matrix = 1;
+    // The tags of the entity
+    map<string, TagValue> tags = 2;
+}
+
+// Attributes of an entity of type `Photos::User`
+message PhotosUser {
+    // Type of the `address` attribute
+    message Address {
+        // The `street` attribute
+        string street = 1;
+        // The `zipCode` attribute
+        optional string zip_code = 2;
+    }
+
+    // Value of the `friends` attribute
+    message FriendsValue {
+        // The values
+        repeated cedar_policy_core.EntityUid values = 1;
+    }
+
+    // The `address` attribute
+    optional Address address = 1;
+    // The `age` attribute
+    optional int64 age = 2;
+    // The `friends` attribute
+    optional FriendsValue friends = 3;
+    // The `ip` attribute
+    string ip = 4;
+    // The `name` attribute
+    string name = 5;
+    // The `roles` attribute
+    repeated string roles = 6;
+}
+
+// Context of a request for the action `Photos::Action::"view"`
+message PhotosViewContext {
+    // The `authenticated` attribute
+    bool authenticated = 1;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn name_collisions() {
+        let schema = Schema::from_str(
+            r#"
+            entity Foo { "a-b": String, a_b: Long, tags: Bool, foo: { x: Long } } tags Long;
+            entity ViewContext;
+            action view appliesTo {
+                principal: Foo,
+                resource: Foo,
+                context: { x: Long },
+            };
+            "#,
+        )
+        .unwrap();
+        let proto = generate(&schema, "test");
+        assert!(!proto.contains("import"), "{proto}");
+        assert!(proto.contains("string a_b = 1;"), "{proto}");
+        assert!(proto.contains("int64 a_b2 = 2;"), "{proto}");
+        assert!(proto.contains("message Foo2 {"), "{proto}");
+        assert!(proto.contains("Foo2 foo = 3;"), "{proto}");
+        assert!(proto.contains("bool tags = 4;"), "{proto}");
+        assert!(proto.contains("map<string, int64> tags2 = 5;"), "{proto}");
+        assert!(proto.contains("message ViewContext {}"), "{proto}");
+        assert!(proto.contains("message ViewContext2 {"), "{proto}");
+    }
+}
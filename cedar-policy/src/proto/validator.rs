@@ -19,6 +19,7 @@
 use super::ast::ProtobufConversionError;
 use super::models;
 use cedar_policy_core::ast::{self, Eid};
+use cedar_policy_core::entities::{EntityConstraint, Refinement, StringPattern};
 use cedar_policy_core::validator::types;
 use nonempty::NonEmpty;
 use smol_str::SmolStr;
@@ -150,6 +151,11 @@ impl From<&cedar_policy_core::validator::ValidatorEntityType> for models::Entity
                 attributes,
                 tags,
                 enum_choices: vec![],
+                constraints: v
+                    .constraints()
+                    .iter()
+                    .map(|c| c.as_str().to_string())
+                    .collect(),
             },
             cedar_policy_core::validator::ValidatorEntityTypeKind::Enum(enum_choices) => Self {
                 name,
@@ -160,6 +166,7 @@ impl From<&cedar_policy_core::validator::ValidatorEntityType> for models::Entity
                     .into_iter()
                     .map(|eid| eid.as_ref().to_string())
                     .collect(),
+                constraints: vec![],
             },
         }
     }
@@ -185,6 +192,16 @@ impl TryFrom<models::EntityDecl> for cedar_policy_core::validator::ValidatorEnti
                 model_to_attributes(v.attributes)?,
                 types::OpenTag::default(),
                 v.tags.map(types::Type::try_from).transpose()?,
+                v.constraints
+                    .into_iter()
+                    .map(|c| {
+                        EntityConstraint::parse(c).map_err(|e| {
+                            ProtobufConversionError::InvalidValue(format!(
+                                "invalid entity constraint: {e}"
+                            ))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
                 None,
             )),
             Some(enum_choices) => {
@@ -222,6 +239,9 @@ impl TryFrom<models::Type> for types::Type {
             models::r#type::Data::SetElem(elty) => Ok(types::Type::Set {
                 element_type: Some(Arc::new(types::Type::try_from(*elty)?)),
             }),
+            models::r#type::Data::MapValue(vty) => {
+                Ok(types::Type::map(types::Type::try_from(*vty)?))
+            }
             models::r#type::Data::Entity(e) => Ok(types::Type::Entity(types::EntityKind::Entity(
                 types::EntityLUB::single_entity(ast::EntityType::try_from(e)?),
             ))),
@@ -232,6 +252,16 @@ impl TryFrom<models::Type> for types::Type {
             models::r#type::Data::Ext(name) => Ok(types::Type::ExtensionType {
                 name: ast::Name::try_from(name)?,
             }),
+            models::r#type::Data::EntityUnion(union) => union
+                .members
+                .into_iter()
+                .map(ast::EntityType::try_from)
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .map(types::EntityLUB::single_entity)
+                .reduce(|lub0, lub1| lub0.least_upper_bound(&lub1))
+                .map(|lub| types::Type::Entity(types::EntityKind::Entity(lub)))
+                .ok_or_else(|| ProtobufConversionError::missing("members")),
         }
     }
 }
@@ -260,8 +290,23 @@ impl From<&types::Type> for models::Type {
                         .as_ref(),
                 )))),
             },
-            types::Type::Entity(types::EntityKind::Entity(lub)) => Self {
-                data: Some(models::r#type::Data::Entity(models::Name::from(lub.get_single_entity().expect("can't encode non-singleton LUB in protobuf; non-singleton LUB types should never appear in a Schema").as_ref()))),
+            types::Type::Map { value_type } => Self {
+                data: Some(models::r#type::Data::MapValue(Box::new(models::Type::from(
+                    value_type
+                        .as_ref()
+                        .expect("can't encode Map without value type in protobuf; Map-without-value-type should never appear in a Schema")
+                        .as_ref(),
+                )))),
+            },
+            types::Type::Entity(types::EntityKind::Entity(lub)) => match lub.get_single_entity() {
+                Some(ty) => Self {
+                    data: Some(models::r#type::Data::Entity(models::Name::from(ty.as_ref()))),
+                },
+                None => Self {
+                    data: Some(models::r#type::Data::EntityUnion(models::r#type::EntityUnion {
+                        members: lub.iter().map(|ty| models::Name::from(ty.as_ref())).collect(),
+                    })),
+                },
             },
             types::Type::Record{ attrs, open_attributes } => {
                 assert_eq!(open_attributes, &types::OpenTag::ClosedAttributes, "can't encode open record in protobuf");
@@ -303,6 +348,50 @@ impl TryFrom<models::AttributeType> for types::AttributeType {
             )?
             .into(),
             is_required: v.is_required,
+            default: v
+                .default_value
+                .map(|e| {
+                    ast::RestrictedExpr::new(ast::Expr::try_from(e)?).map_err(|e| {
+                        ProtobufConversionError::InvalidValue(format!(
+                            "invalid attribute default value: {e}"
+                        ))
+                    })
+                })
+                .transpose()?,
+            deprecation: v.deprecation.map(Into::into),
+            refinement: match (v.range, v.pattern, v.satisfying) {
+                (Some(range), None, None) => Some(Refinement::Range {
+                    min: range.min,
+                    max: range.max,
+                }),
+                (None, Some(pattern), None) => Some(Refinement::Pattern(
+                    StringPattern::new(pattern).map_err(|e| {
+                        ProtobufConversionError::InvalidValue(format!(
+                            "invalid attribute pattern: {e}"
+                        ))
+                    })?,
+                )),
+                (None, None, Some(method)) => Some(
+                    ast::Name::parse_unqualified_name(&method)
+                        .map(Refinement::Satisfying)
+                        .map_err(|e| {
+                            ProtobufConversionError::InvalidValue(format!(
+                                "invalid attribute `satisfying` method: {e}"
+                            ))
+                        })?,
+                ),
+                (None, None, None) => None,
+                (Some(_), Some(_), _) => {
+                    return Err(ProtobufConversionError::InvalidValue(
+                        "attribute has both a range and a pattern".into(),
+                    ))
+                }
+                (_, _, Some(_)) => {
+                    return Err(ProtobufConversionError::InvalidValue(
+                        "attribute has both a `satisfying` method and a range or pattern".into(),
+                    ))
+                }
+            },
             #[cfg(feature = "extended-schema")]
             loc: None,
         })
@@ -314,6 +403,23 @@ impl From<&types::AttributeType> for models::AttributeType {
         Self {
             attr_type: Some(models::Type::from(v.attr_type.as_ref())),
             is_required: v.is_required,
+            default_value: v.default.as_ref().map(|e| models::Expr::from(e.as_ref())),
+            deprecation: v.deprecation.as_ref().map(ToString::to_string),
+            range: match &v.refinement {
+                Some(Refinement::Range { min, max }) => Some(models::attribute_type::Range {
+                    min: *min,
+                    max: *max,
+                }),
+                _ => None,
+            },
+            pattern: match &v.refinement {
+                Some(Refinement::Pattern(pattern)) => Some(pattern.as_str().to_string()),
+                _ => None,
+            },
+            satisfying: match &v.refinement {
+                Some(Refinement::Satisfying(method)) => Some(method.to_string()),
+                _ => None,
+            },
         }
     }
 }
@@ -324,6 +430,8 @@ mod test {
 
     use super::models;
     use super::ProtobufConversionError;
+    use cedar_policy_core::ast;
+    use cedar_policy_core::entities::{Refinement, StringPattern};
     use cedar_policy_core::validator::types::{
         AttributeType, BoolType, EntityKind, EntityLUB, OpenTag, Type,
     };
@@ -344,6 +452,10 @@ mod test {
         assert_type_roundtrip(Type::Entity(EntityKind::Entity(EntityLUB::single_entity(
             "User".parse().unwrap(),
         ))));
+        assert_type_roundtrip(Type::Entity(EntityKind::Entity(
+            EntityLUB::single_entity("Photo".parse().unwrap())
+                .least_upper_bound(&EntityLUB::single_entity("Album".parse().unwrap())),
+        )));
         assert_type_roundtrip(Type::set(Arc::new(Type::Long)));
         assert_type_roundtrip(Type::record_with_attributes(
             None,
@@ -413,6 +525,9 @@ mod test {
         assert_schema_roundtrip("entity Group; entity User in [Group];");
         assert_schema_roundtrip("entity User { foo : Long };");
         assert_schema_roundtrip("entity User tags String;");
+        assert_schema_roundtrip(
+            r#"entity Meeting { start: Long, end: Long } constraints ["end > start"];"#,
+        );
         assert_schema_roundtrip(r#"entity User enum ["0"];"#);
         assert_schema_roundtrip(r#"entity User enum ["", "\0", "🐈"];"#);
     }
@@ -497,6 +612,7 @@ mod test {
             attributes: Default::default(),
             tags: None,
             enum_choices: vec![],
+            constraints: vec![],
         };
         assert_matches!(
             cedar_policy_core::validator::ValidatorEntityType::try_from(bad),
@@ -518,6 +634,11 @@ mod test {
         let bad = models::AttributeType {
             attr_type: None,
             is_required: true,
+            default_value: None,
+            deprecation: None,
+            range: None,
+            pattern: None,
+            satisfying: None,
         };
         assert_matches!(
             cedar_policy_core::validator::types::AttributeType::try_from(bad),
@@ -525,6 +646,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn attribute_refinement_roundtrip() {
+        let refinements = [
+            Refinement::Range {
+                min: Some(0),
+                max: Some(65535),
+            },
+            Refinement::Range {
+                min: None,
+                max: Some(-1),
+            },
+            Refinement::Pattern(StringPattern::new("[a-z]+").unwrap()),
+            Refinement::Satisfying(ast::Name::parse_unqualified_name("isIpv4").unwrap()),
+        ];
+        for refinement in refinements {
+            let attr_ty =
+                AttributeType::new(Arc::new(Type::Long), true).with_refinement(Some(refinement));
+            let roundtripped =
+                AttributeType::try_from(models::AttributeType::from(&attr_ty)).unwrap();
+            assert_eq!(attr_ty.refinement, roundtripped.refinement);
+        }
+    }
+
     #[test]
     fn schema_try_from_invalid_entity_decl() {
         let bad = models::Schema {
@@ -534,6 +678,7 @@ mod test {
                 attributes: Default::default(),
                 tags: None,
                 enum_choices: vec![],
+                constraints: vec![],
             }],
             action_decls: vec![],
         };
@@ -899,6 +899,184 @@ mod policy_set_tests {
             .contains(&"Test::\"test\"".parse().unwrap()));
     }
 
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn partial_response_typed_unknowns() {
+        let schema = Schema::from_str(
+            "
+            entity User, Photo;
+            action view appliesTo {
+                principal: User,
+                resource: Photo,
+                context: { info: { mfa: Bool, note?: String } },
+            };
+        ",
+        )
+        .unwrap();
+        let action = EntityUid::from_strs("Action", "view");
+        let Some(DeclaredType::Record { attributes, .. }) = schema.context_type(&action) else {
+            panic!("context should be a record");
+        };
+        let info_ty = attributes["info"].ty().clone();
+
+        let pset = PolicySet::from_str(
+            "
+            permit(principal, action, resource) when { context.info has mfa };
+            permit(principal, action, resource) when { context.info has other };
+            permit(principal, action, resource) when { context.info.mfa };
+        ",
+        )
+        .unwrap();
+        let authorize = |info: Unknown| {
+            let request = Request::new(
+                EntityUid::from_strs("User", "alice"),
+                action.clone(),
+                EntityUid::from_strs("Photo", "beach"),
+                Context::from_pairs([("info".into(), info.into())]).unwrap(),
+                None,
+            )
+            .unwrap();
+            Authorizer::new().is_authorized_partial(&request, &pset, &Entities::empty())
+        };
+
+        // Without a type, none of the policies can be decided
+        let response = authorize(Unknown::new("info"));
+        assert_eq!(response.decision(), None);
+        assert_eq!(response.nontrivial_residuals().count(), 3);
+
+        // With a type, the `has` checks are decided, and the remaining
+        // residual validates against the schema
+        let response = authorize(Unknown::of_type("info", info_ty));
+        assert_eq!(response.decision(), Some(Decision::Allow));
+        let residuals: Vec<_> = response.nontrivial_residuals().collect();
+        assert_eq!(residuals.len(), 1);
+        assert_eq!(residuals[0].id(), &PolicyId::new("policy2"));
+        let residuals = PolicySet::from_policies(residuals).unwrap();
+        let result = Validator::new(schema).validate(&residuals, ValidationMode::Strict);
+        assert!(result.validation_passed(), "{result:?}");
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn partial_response_unknown_context_attributes() {
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { context has level && context.level > 3 };
+            permit(principal, action, resource) when { context.region == "eu" };
+            forbid(principal, action, resource) when { context has banned };
+        "#,
+        )
+        .unwrap();
+        assert_eq!(pset.unknown_context_attributes(), HashSet::new());
+        let request = Request::builder()
+            .principal(EntityUid::from_strs("User", "alice"))
+            .action(EntityUid::from_strs("Action", "view"))
+            .resource(EntityUid::from_strs("Photo", "beach"))
+            .context(
+                Context::from_pairs([(
+                    "region".into(),
+                    RestrictedExpression::new_string("us".into()),
+                )])
+                .unwrap(),
+            )
+            .unknown_context_attributes(["level", "region", "banned"])
+            .build();
+        let authorizer = Authorizer::new();
+        let response = authorizer.is_authorized_partial(&request, &pset, &Entities::empty());
+
+        // `region` is known, so only `level` and `banned` remain
+        assert_eq!(response.decision(), None);
+        assert_eq!(
+            response.unknown_context_attributes(),
+            HashSet::from(["level".to_string(), "banned".to_string()])
+        );
+        let residual = response
+            .nontrivial_residuals()
+            .find(|p| p.id() == &PolicyId::new("policy0"))
+            .unwrap();
+        assert_eq!(
+            residual.unknown_context_attributes(),
+            HashSet::from(["level".to_string()])
+        );
+        assert_eq!(residual.unknown_entities(), HashSet::new());
+
+        let context = RestrictedExpression::new_record([(
+            "level".to_string(),
+            RestrictedExpression::new_long(5),
+        )])
+        .unwrap();
+        let response = response
+            .reauthorize_with_bindings([("context", &context)], &authorizer, &Entities::empty())
+            .unwrap();
+        assert_eq!(response.decision(), Some(Decision::Allow));
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn authorizer_resume() {
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when {
+                principal.department == "eng" && context has mfa && context.mfa
+            };
+            forbid(principal, action, resource) when { context.risk > 5 };
+        "#,
+        )
+        .unwrap();
+        let request = Request::builder()
+            .principal(EntityUid::from_strs("User", "alice"))
+            .action(EntityUid::from_strs("Action", "view"))
+            .resource(EntityUid::from_strs("Photo", "beach"))
+            .context(Context::empty())
+            .unknown_context_attributes(["mfa", "risk"])
+            .build();
+        let authorizer = Authorizer::new();
+        let response =
+            authorizer.is_authorized_partial(&request, &pset, &Entities::empty().partial());
+        assert_eq!(response.decision(), None);
+
+        // The principal arrives first
+        let entities = Entities::from_json_str(
+            r#"[{"uid": {"type": "User", "id": "alice"}, "attrs": {"department": "eng"}, "parents": []}]"#,
+            None,
+        )
+        .unwrap()
+        .partial();
+        let response = authorizer
+            .resume(&response, &entities, &Context::empty())
+            .unwrap();
+        assert_eq!(response.decision(), None);
+        assert!(response.unknown_entities().is_empty());
+        assert_eq!(
+            response.unknown_context_attributes(),
+            HashSet::from(["mfa".to_string(), "risk".to_string()])
+        );
+
+        // Then the context attributes, one source at a time
+        let mfa =
+            Context::from_pairs([("mfa".into(), RestrictedExpression::new_bool(true))]).unwrap();
+        let response = authorizer
+            .resume(&response, &Entities::empty(), &mfa)
+            .unwrap();
+        assert_eq!(response.decision(), None);
+        assert_eq!(
+            response.unknown_context_attributes(),
+            HashSet::from(["risk".to_string()])
+        );
+        let risk =
+            Context::from_pairs([("risk".into(), RestrictedExpression::new_long(2))]).unwrap();
+        let response = authorizer
+            .resume(&response, &Entities::empty(), &risk)
+            .unwrap();
+        assert_eq!(response.decision(), Some(Decision::Allow));
+
+        // Attributes which are already known can't be provided again
+        assert_matches!(
+            authorizer.resume(&response, &Entities::empty(), &risk),
+            Err(ReauthorizationError::Concretization(_))
+        );
+    }
+
     #[test]
     fn unlink_linked_policy() {
         let template = Template::parse(
@@ -1896,182 +2074,1494 @@ forbid(principal,action,resource == ?resource);//lossless keeps comments"
             )
         );
     }
-}
-
-mod schema_tests {
-    use super::*;
-    use cool_asserts::assert_matches;
-    use serde_json::json;
 
-    /// A minimal test that a valid Schema parses
     #[test]
-    fn valid_schema() {
-        Schema::from_json_value(json!(
-        { "": {
-            "entityTypes": {
-                "Photo": {
-                    "memberOfTypes": [ "Album" ],
-                    "shape": {
-                        "type": "Record",
-                        "attributes": {
-                            "foo": {
-                                "type": "Boolean",
-                                "required": false
-                            }
-                        }
-                    }
-                },
-                "Album": {
-                    "memberOfTypes": [ ],
-                    "shape": {
-                        "type": "Record",
-                        "attributes": {
-                            "foo": {
-                                "type": "Boolean",
-                                "required": false
-                            }
-                        }
-                    }
-                }
-            },
-            "actions": {
-                "view": {
-                    "appliesTo": {
-                        "principalTypes": ["Photo", "Album"],
-                        "resourceTypes": ["Photo"]
-                    }
-                }
-            }
-        }}))
-        .expect("schema should be valid");
-    }
+    fn link_many() {
+        let template = Template::parse(
+            Some(PolicyId::new("template")),
+            "permit(principal == ?principal, action, resource in ?resource);",
+        )
+        .expect("Template Parse Failure");
+        let mut pset = PolicySet::new();
+        pset.add_template(template).unwrap();
 
-    /// Test that an invalid schema returns the appropriate error
-    #[test]
-    fn invalid_schema() {
-        assert_matches!(
-            Schema::from_json_str(
-                // Written as a string because duplicate entity types are detected
-                // by the serde-json string parser.
-                r#"{"": {
-                "entityTypes": {
-                    "Photo": {
-                        "memberOfTypes": [ "Album" ],
-                        "shape": {
-                            "type": "Record",
-                            "attributes": {
-                                "foo": {
-                                    "type": "Boolean",
-                                    "required": false
-                                }
-                            }
-                        }
-                    },
-                    "Album": {
-                        "memberOfTypes": [ ],
-                        "shape": {
-                            "type": "Record",
-                            "attributes": {
-                                "foo": {
-                                    "type": "Boolean",
-                                    "required": false
-                                }
-                            }
-                        }
-                    },
-                    "Photo": {
-                        "memberOfTypes": [ "Album" ],
-                        "shape": {
-                            "type": "Record",
-                            "attributes": {
-                                "foo": {
-                                    "type": "Boolean",
-                                    "required": false
-                                }
-                            }
-                        }
-                    }
-                },
-                "actions": {
-                    "view": {
-                        "appliesTo": {
-                            "principalTypes": ["Photo", "Album"],
-                            "resourceTypes": ["Photo"]
-                        }
-                    }
-                }
-            }}"#
-            ),
-            Err(e) =>
-                expect_err(
-                    "",
-                    &Report::new(e),
-                    &ExpectedErrorMessageBuilder::error("invalid entry: found duplicate key at line 39 column 17")
-                        .build(),
+        let bindings = |i: usize| {
+            SlotBindings::new()
+                .with_entity(
+                    SlotId::principal(),
+                    EntityUid::from_strs("User", &format!("user{i}")),
                 )
+                .with_entity(SlotId::resource(), EntityUid::from_strs("Folder", "f"))
+        };
+        let errors = pset
+            .link_many(
+                PolicyId::new("template"),
+                (0..100)
+                    .map(|i| (PolicyId::new(format!("link{i}")), bindings(i)))
+                    .chain([
+                        // conflicts with an earlier link in the batch
+                        (PolicyId::new("link3"), bindings(3)),
+                        // doesn't bind `?resource`
+                        (
+                            PolicyId::new("unbound"),
+                            SlotBindings::new().with_entity(
+                                SlotId::principal(),
+                                EntityUid::from_strs("User", "alice"),
+                            ),
+                        ),
+                    ])
+                    .chain([(PolicyId::new("link100"), bindings(100))]),
+            )
+            .unwrap();
+        assert_matches!(errors.as_slice(), [(conflict, PolicySetError::Linking(_)), (unbound, PolicySetError::Linking(_))] => {
+            assert_eq!(conflict, &PolicyId::new("link3"));
+            assert_eq!(unbound, &PolicyId::new("unbound"));
+        });
+        assert_eq!(pset.num_of_policies(), 101);
+        assert_eq!(
+            pset.policy(&PolicyId::new("link3"))
+                .unwrap()
+                .principal_constraint(),
+            PrincipalConstraint::Eq(EntityUid::from_strs("User", "user3"))
         );
-    }
+        assert!(pset.policy(&PolicyId::new("link100")).is_some());
 
-    #[test]
-    fn unconvertible_json_schema_fails_conversion() {
-        let json_string = json!(
-            {
-                "": {
-                    "commonTypes": {
-                        "Task": {
-                            "type": "Record",
-                            "attributes": {}
-                        }
-                    },
-                    "entityTypes": {
-                        "User": {
-                            "shape": {
-                                "type": "Task"
-                            }
-                        }
-                    },
-                    "actions": {}
-                }
-            }
+        let before_link = pset.clone();
+        assert_matches!(
+            pset.link_many(PolicyId::new("link0"), [(PolicyId::new("x"), bindings(0))]),
+            Err(PolicySetError::ExpectedTemplate(_))
         );
-        let schema =
-            SchemaFragment::from_json_value(json_string.clone()).expect("schema should be valid");
-        let result = schema.to_cedarschema();
         assert_matches!(
-            result,
-            Err(e @ ToCedarSchemaError::UnconvertibleEntityTypeShape(..)) => {
-                expect_err(
-                    &json_string,
-                    &miette::Report::new(e),
-                    &ExpectedErrorMessageBuilder::error(
-                        "The following entities have shapes that cannot be converted to Cedar schema syntax: [User]",
-                    )
-                    .help("Entity shapes may only be record types. In the Cedar schema syntax, they additionally may not reference common type definitions.")
-                    .build(),
-                );
-            }
+            pset.link_many(
+                PolicyId::new("nonexistent"),
+                [(PolicyId::new("x"), bindings(0))]
+            ),
+            Err(PolicySetError::Linking(_))
+        );
+        assert_eq!(
+            pset, before_link,
+            "A failed link shouldn't mutate the policy set"
         );
     }
-}
-
-mod ancestors_tests {
-    use super::*;
 
     #[test]
-    fn test_ancestors() {
-        let a_euid: EntityUid = EntityUid::from_strs("test", "A");
-        let b_euid: EntityUid = EntityUid::from_strs("test", "b");
-        let c_euid: EntityUid = EntityUid::from_strs("test", "C");
-        let a = Entity::new_no_attrs(a_euid.clone(), HashSet::new());
-        let b = Entity::new_no_attrs(b_euid.clone(), HashSet::from([a_euid.clone()]));
-        let c = Entity::new_no_attrs(c_euid.clone(), HashSet::from([b_euid.clone()]));
-        let es = Entities::from_entities([a, b, c], None).unwrap();
-        assert_eq!(es.len(), 3);
-        assert!(!es.is_empty());
-        let ans = es.ancestors(&c_euid).unwrap().collect::<HashSet<_>>();
-        assert_eq!(ans.len(), 2);
-        assert!(ans.contains(&b_euid));
-        assert!(ans.contains(&a_euid));
+    fn store_and_load() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            @reason("owners")
+            permit(principal == ?principal, action, resource in ?resource);
+            template(?limit: Long) =>
+            permit(principal, action, resource) when { context.size < ?limit };
+            "#,
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("policy1"),
+            PolicyId::new("link"),
+            HashMap::from([
+                (SlotId::principal(), EntityUid::from_strs("User", "alice")),
+                (SlotId::resource(), EntityUid::from_strs("Folder", "home")),
+            ]),
+        )
+        .unwrap();
+        pset.link_with_values(
+            PolicyId::new("policy2"),
+            PolicyId::new("valued_link"),
+            HashMap::new(),
+            HashMap::from([(
+                "?limit".parse().unwrap(),
+                RestrictedExpression::new_long(10),
+            )]),
+        )
+        .unwrap();
+
+        let json = pset.store_json().unwrap();
+        assert_eq!(json["formatVersion"], POLICY_SET_FORMAT_VERSION);
+        let loaded = PolicySet::load_json(json).unwrap();
+        assert_eq!(loaded, pset);
+        assert_eq!(
+            loaded
+                .policy(&PolicyId::new("link"))
+                .unwrap()
+                .annotation("reason"),
+            Some("owners")
+        );
+        let loaded = PolicySet::load_json_str(pset.store_json_string().unwrap()).unwrap();
+        assert_eq!(loaded, pset);
+
+        let bytes = pset.store_binary().unwrap();
+        assert!(bytes.starts_with(b"CEDARPS\0"));
+        let loaded = PolicySet::load_binary(&bytes).unwrap();
+        assert_eq!(loaded, pset);
+
+        assert_matches!(
+            PolicySet::load_binary(&bytes[..bytes.len() - 1]),
+            Err(PolicySetError::PersistedFormat(
+                policy_set_errors::PersistedFormatError::LengthMismatch { .. }
+            ))
+        );
+        assert_matches!(
+            PolicySet::load_binary(b"not a policy set"),
+            Err(PolicySetError::PersistedFormat(
+                policy_set_errors::PersistedFormatError::BadHeader
+            ))
+        );
+        let mut newer = bytes;
+        newer[8..12].copy_from_slice(&2_u32.to_be_bytes());
+        assert_matches!(
+            PolicySet::load_binary(&newer),
+            Err(PolicySetError::PersistedFormat(
+                policy_set_errors::PersistedFormatError::UnsupportedVersion { version: 2, .. }
+            ))
+        );
+        assert_matches!(
+            PolicySet::load_json(
+                serde_json::json!({ "policySet": pset.clone().to_json().unwrap() })
+            ),
+            Err(PolicySetError::PersistedFormat(
+                policy_set_errors::PersistedFormatError::MissingVersion
+            ))
+        );
+        assert_matches!(
+            PolicySet::load_json(serde_json::json!({ "formatVersion": 1, "policySet": [] })),
+            Err(PolicySetError::JsonPolicySet(_))
+        );
+    }
+
+    #[test]
+    fn policy_metadata() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"pay", resource);
+            permit(principal, action == Action::"view", resource);
+            permit(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("policy2"),
+            PolicyId::new("link"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", "alice"))]),
+        )
+        .unwrap();
+        let created = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_123);
+        let payments = PolicyMetadata::new()
+            .with_created(created)
+            .with_modified(created + std::time::Duration::from_secs(60))
+            .with_tag("team", "payments")
+            .with_tag("ticket", "PAY-12");
+        assert_eq!(
+            pset.set_policy_metadata(PolicyId::new("policy0"), payments.clone())
+                .unwrap(),
+            None
+        );
+        pset.set_policy_metadata(
+            PolicyId::new("link"),
+            PolicyMetadata::new().with_tag("team", "payments"),
+        )
+        .unwrap();
+        pset.set_policy_metadata(
+            PolicyId::new("policy1"),
+            PolicyMetadata::new().with_tag("team", "search"),
+        )
+        .unwrap();
+        pset.set_policy_metadata(
+            PolicyId::new("policy2"),
+            PolicyMetadata::new().with_tag("team", "payments"),
+        )
+        .unwrap();
+        assert_matches!(
+            pset.set_policy_metadata(PolicyId::new("nope"), PolicyMetadata::new()),
+            Err(PolicySetError::NoSuchPolicy(_))
+        );
+
+        let ids = |policies: Vec<&Policy>| {
+            policies
+                .into_iter()
+                .map(|p| p.id().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(pset.policies_with_tag("team:payments").collect()),
+            ["policy0", "link"]
+        );
+        assert_eq!(ids(pset.policies_with_tag("ticket").collect()), ["policy0"]);
+        assert_eq!(pset.policies_with_tag("team:billing").count(), 0);
+        assert_eq!(pset.templates_with_tag("team:payments").count(), 1);
+        let metadata = pset.policy_metadata(&PolicyId::new("policy0")).unwrap();
+        assert_eq!(metadata, &payments);
+        assert_eq!(metadata.created(), Some(created));
+        assert_eq!(metadata.tag("ticket"), Some("PAY-12"));
+
+        // Metadata is preserved when storing and loading the policy set
+        let loaded = PolicySet::load_json(pset.store_json().unwrap()).unwrap();
+        assert_eq!(
+            loaded.policy_metadata(&PolicyId::new("policy0")),
+            Some(&payments)
+        );
+        let loaded = PolicySet::load_binary(&pset.store_binary().unwrap()).unwrap();
+        assert_eq!(
+            ids(loaded.policies_with_tag("team:payments").collect()),
+            ["policy0", "link"]
+        );
+
+        // Metadata is removed along with its policy
+        pset.unlink(PolicyId::new("link")).unwrap();
+        assert_eq!(pset.policy_metadata(&PolicyId::new("link")), None);
+        pset.link(
+            PolicyId::new("policy2"),
+            PolicyId::new("link"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", "bob"))]),
+        )
+        .unwrap();
+        assert_eq!(
+            ids(pset.policies_with_tag("team:payments").collect()),
+            ["policy0"]
+        );
+    }
+
+    #[test]
+    fn diff_and_merge_by_id() {
+        let link_alice = |pset: &mut PolicySet, id: &str, user: &str| {
+            pset.link(
+                PolicyId::new("policy2"),
+                PolicyId::new(id),
+                HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", user))]),
+            )
+            .unwrap();
+        };
+        let mut prod = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            forbid(principal, action, resource) when { context.blocked };
+            permit(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        link_alice(&mut prod, "alice", "alice");
+        link_alice(&mut prod, "bob", "bob");
+        let mut staging = PolicySet::from_str(
+            r#"
+            // reformatted, but not modified
+            permit(
+                principal,
+                action == Action::"view",
+                resource
+            );
+            forbid(principal, action, resource) when { context.blocked || context.suspended };
+            permit(principal == ?principal, action, resource) when { context.mfa };
+            permit(principal, action == Action::"edit", resource);
+            "#,
+        )
+        .unwrap();
+        link_alice(&mut staging, "alice", "alice");
+        staging
+            .set_policy_metadata(
+                PolicyId::new("policy3"),
+                PolicyMetadata::new().with_tag("team", "docs"),
+            )
+            .unwrap();
+
+        let ids =
+            |ids: Vec<&PolicyId>| ids.into_iter().map(ToString::to_string).collect::<Vec<_>>();
+        let diff = prod.diff(&staging);
+        assert_eq!(ids(diff.added().collect()), ["policy3"]);
+        assert_eq!(ids(diff.removed().collect()), ["bob"]);
+        assert_eq!(
+            ids(diff.modified().collect()),
+            ["policy2", "policy1", "alice"]
+        );
+        assert!(prod.diff(&prod).is_empty());
+
+        // Conflicting definitions are an error by default
+        let before = prod.clone();
+        let err = prod
+            .merge_by_id(&staging, MergeConflictResolution::Fail)
+            .unwrap_err();
+        assert_matches!(&err, PolicySetError::MergeConflict(e) => {
+            assert_eq!(ids(e.policy_ids().collect()), ["policy2", "policy1", "alice"]);
+        });
+        assert!(prod.diff(&before).is_empty());
+
+        // Keeping the existing definitions only adds the new policy
+        let mut kept = prod.clone();
+        let applied = kept
+            .merge_by_id(&staging, MergeConflictResolution::KeepExisting)
+            .unwrap();
+        assert_eq!(ids(applied.added().collect()), ["policy3"]);
+        assert_eq!(applied.modified().count(), 0);
+        assert_eq!(
+            kept.policy(&PolicyId::new("policy1")),
+            prod.policy(&PolicyId::new("policy1"))
+        );
+        assert_eq!(
+            kept.policy_metadata(&PolicyId::new("policy3"))
+                .and_then(|m| m.tag("team")),
+            Some("docs")
+        );
+
+        // Taking the incoming definitions relinks existing links to the new
+        // template, and keeps policies only in the existing set
+        let applied = prod
+            .merge_by_id(&staging, MergeConflictResolution::TakeIncoming)
+            .unwrap();
+        assert_eq!(ids(applied.added().collect()), ["policy3"]);
+        assert_eq!(applied.removed().count(), 0);
+        assert_eq!(
+            ids(applied.modified().collect()),
+            ["policy2", "policy1", "alice", "bob"]
+        );
+        let remaining = prod.diff(&staging);
+        assert_eq!(ids(remaining.removed().collect()), ["bob"]);
+        assert_eq!(remaining.added().count() + remaining.modified().count(), 0);
+        assert!(prod
+            .policy(&PolicyId::new("bob"))
+            .unwrap()
+            .to_string()
+            .contains("context.mfa"));
+    }
+
+    #[test]
+    fn disabled_policies() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            forbid(principal == User::"alice", action, resource);
+            "#,
+        )
+        .unwrap();
+        let request = Request::new(
+            EntityUid::from_strs("User", "alice"),
+            EntityUid::from_strs("Action", "view"),
+            EntityUid::from_strs("Photo", "a"),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new();
+        let forbid = PolicyId::new("policy1");
+        assert_eq!(
+            authorizer
+                .is_authorized(&request, &pset, &Entities::empty())
+                .decision(),
+            Decision::Deny
+        );
+
+        assert!(pset.set_enabled(forbid.clone(), false).unwrap());
+        assert!(!pset.is_enabled(&forbid));
+        assert_matches!(
+            pset.set_enabled(PolicyId::new("nope"), false),
+            Err(PolicySetError::NoSuchPolicy(_))
+        );
+        assert_eq!(pset.num_of_policies(), 2);
+        let response = authorizer.is_authorized(&request, &pset, &Entities::empty());
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(response.diagnostics().would_have_applied().count(), 0);
+
+        // A dry run reports the disabled policy without changing the decision
+        let response = authorizer.is_authorized_dry_run(&request, &pset, &Entities::empty());
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(
+            response
+                .diagnostics()
+                .would_have_applied()
+                .collect::<Vec<_>>(),
+            [&forbid]
+        );
+
+        // Disabled policies are preserved when storing and loading
+        let loaded = PolicySet::load_json(pset.store_json().unwrap()).unwrap();
+        assert!(!loaded.is_enabled(&forbid));
+        assert_eq!(loaded, pset);
+
+        assert!(!pset.set_enabled(forbid, true).unwrap());
+        assert_eq!(
+            authorizer
+                .is_authorized(&request, &pset, &Entities::empty())
+                .decision(),
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            permit(principal, action == Action::"edit", resource)
+            when { context.owner && context.level > 2 };
+            forbid(principal == User::"mallory", action, resource);
+            forbid(principal is Admin, action, resource);
+            "#,
+        )
+        .unwrap();
+        let request = |principal: &str, action: &str, context: serde_json::Value| {
+            Request::new(
+                EntityUid::from_strs("User", principal),
+                EntityUid::from_strs("Action", action),
+                EntityUid::from_strs("Photo", "a"),
+                Context::from_json_value(context, None).unwrap(),
+                None,
+            )
+            .unwrap()
+        };
+        let requests = [
+            request("alice", "view", serde_json::json!({})),
+            request(
+                "alice",
+                "edit",
+                serde_json::json!({"owner": true, "level": 3}),
+            ),
+            request(
+                "bob",
+                "edit",
+                serde_json::json!({"owner": false, "level": 3}),
+            ),
+            request("carol", "edit", serde_json::json!({})),
+            request("mallory", "view", serde_json::json!({})),
+        ];
+        let report = pset.coverage(&requests, &Entities::empty());
+        assert_eq!(report.requests(), 5);
+        let policy = |id: &str| report.policy(&PolicyId::new(id)).unwrap();
+
+        // `policy0` is satisfied for mallory, but is overridden by `policy2`
+        assert_eq!(policy("policy0").satisfied(), 2);
+        assert_eq!(policy("policy0").determining(), 1);
+        assert_eq!(policy("policy2").determining(), 1);
+
+        let edit = policy("policy1");
+        assert_eq!((edit.satisfied(), edit.errors()), (1, 1));
+        let clauses = edit.clauses().collect::<Vec<_>>();
+        assert_eq!(
+            clauses.iter().map(|c| c.kind()).collect::<Vec<_>>(),
+            [
+                ClauseKind::Action,
+                ClauseKind::Condition,
+                ClauseKind::Condition
+            ]
+        );
+        assert_eq!(
+            clauses
+                .iter()
+                .map(|c| (c.satisfied(), c.unsatisfied(), c.errors()))
+                .collect::<Vec<_>>(),
+            [(3, 2, 0), (1, 1, 1), (1, 0, 0)]
+        );
+        assert!(clauses[1].is_covered());
+        assert!(!clauses[2].is_covered());
+
+        assert_eq!(
+            report.never_satisfied().collect::<Vec<_>>(),
+            [&PolicyId::new("policy3")]
+        );
+        let dead = policy("policy3").clauses().next().unwrap();
+        assert_eq!(dead.kind(), ClauseKind::Principal);
+        assert_eq!(dead.unsatisfied(), 5);
+    }
+
+    #[test]
+    fn add_with_id_collisions() {
+        let mut pset = PolicySet::from_str(
+            "permit(principal, action, resource);
+            permit(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("policy1"),
+            PolicyId::new("link"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", "alice"))]),
+        )
+        .unwrap();
+        let forbid = Policy::parse(
+            Some(PolicyId::new("policy0")),
+            "forbid(principal, action, resource);",
+        )
+        .unwrap();
+
+        let before = pset.clone();
+        assert_matches!(
+            pset.add_with(forbid.clone(), IdCollisionBehavior::Error),
+            Err(PolicySetError::AlreadyDefined(_))
+        );
+        assert_eq!(pset, before);
+
+        let id = pset
+            .add_with(forbid.clone(), IdCollisionBehavior::Version)
+            .unwrap();
+        assert_eq!(id, PolicyId::new("policy0#2"));
+        assert_eq!(
+            pset.add_with(forbid.clone(), IdCollisionBehavior::Version)
+                .unwrap(),
+            PolicyId::new("policy0#3")
+        );
+        assert_eq!(pset.policy(&id).unwrap().effect(), Effect::Forbid);
+        assert_eq!(
+            pset.policy(&PolicyId::new("policy0")).unwrap().effect(),
+            Effect::Permit
+        );
+
+        let id = pset.add_with(forbid, IdCollisionBehavior::Replace).unwrap();
+        assert_eq!(id, PolicyId::new("policy0"));
+        assert_eq!(pset.policy(&id).unwrap().effect(), Effect::Forbid);
+
+        // replacing a template relinks its links
+        let template = Template::parse(
+            Some(PolicyId::new("policy1")),
+            "forbid(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        pset.add_template_with(template.clone(), IdCollisionBehavior::Replace)
+            .unwrap();
+        assert_eq!(
+            pset.policy(&PolicyId::new("link")).unwrap().effect(),
+            Effect::Forbid
+        );
+        assert_eq!(
+            pset.add_template_with(template, IdCollisionBehavior::Version)
+                .unwrap(),
+            PolicyId::new("policy1#2")
+        );
+
+        // namespaced ids don't collide across namespaces
+        let payments = PolicyId::namespaced(["payments"], "policy0");
+        let id = pset
+            .add_with(
+                Policy::parse(
+                    Some(payments.clone()),
+                    "permit(principal, action, resource);",
+                )
+                .unwrap(),
+                IdCollisionBehavior::Error,
+            )
+            .unwrap();
+        assert_eq!(id, payments);
+        assert_eq!(id.name(), "policy0");
+        assert!(id.is_in_namespace(["payments"]));
+        assert!(!PolicyId::new("policy0").is_in_namespace(["payments"]));
+        assert_eq!(PolicyId::new("policy0").namespace().count(), 0);
+    }
+
+    #[test]
+    fn inline_template() {
+        let mut pset = PolicySet::from_str(
+            r#"@id("limit")
+            template(?limit: Long) =>
+            permit(principal in ?principal, action, resource == ?resource)
+            when { context.size < ?limit };"#,
+        )
+        .unwrap();
+        pset.link_with_values(
+            PolicyId::new("policy0"),
+            PolicyId::new("link"),
+            HashMap::from([
+                (SlotId::principal(), EntityUid::from_strs("Team", "admins")),
+                (SlotId::resource(), EntityUid::from_strs("Photo", "p")),
+            ]),
+            HashMap::from([(
+                "?limit".parse().unwrap(),
+                RestrictedExpression::new_long(10),
+            )]),
+        )
+        .unwrap();
+        let link = pset.policy(&PolicyId::new("link")).unwrap();
+        let inlined = link.inline_template();
+        assert!(inlined.is_static());
+        assert_eq!(inlined.id(), link.id());
+        assert_eq!(inlined.template_id(), None);
+        assert_eq!(inlined.annotation("id"), Some("limit"));
+
+        let reparsed = Policy::parse(Some(PolicyId::new("link")), inlined.to_cedar().unwrap())
+            .expect("inlined policy should be re-parseable");
+        assert_eq!(reparsed.to_json().unwrap(), inlined.to_json().unwrap());
+        assert_eq!(
+            reparsed.principal_constraint(),
+            PrincipalConstraint::In(EntityUid::from_strs("Team", "admins"))
+        );
+
+        // a static policy is returned unchanged
+        assert_eq!(inlined.inline_template(), inlined);
+    }
+
+    #[test]
+    fn layered_authorization() {
+        let layer = |name: &str, mode: LayerMode, src: &str| {
+            PolicyLayer::new(name, mode, PolicySet::from_str(src).unwrap())
+        };
+        let mut layers = LayeredPolicySet::new();
+        layers
+            .push_layer(layer(
+                "org",
+                LayerMode::Guardrail,
+                "forbid(principal, action, resource) when { context.sensitive };",
+            ))
+            .unwrap();
+        layers
+            .push_layer(layer(
+                "team",
+                LayerMode::Narrowing,
+                "permit(principal, action, resource);",
+            ))
+            .unwrap();
+        layers
+            .push_layer(layer(
+                "owner",
+                LayerMode::Narrowing,
+                r#"permit(principal, action == Action::"view", resource);"#,
+            ))
+            .unwrap();
+        assert_matches!(
+            layers.push_layer(layer("team", LayerMode::Narrowing, "")),
+            Err(PolicySetError::DuplicateLayer(e)) => assert_eq!(e.name(), "team")
+        );
+
+        let authorize = |layers: &LayeredPolicySet, action: &str, sensitive: bool| {
+            let request = Request::new(
+                EntityUid::from_strs("User", "alice"),
+                EntityUid::from_strs("Action", action),
+                EntityUid::from_strs("Photo", "p"),
+                Context::from_pairs([(
+                    "sensitive".into(),
+                    RestrictedExpression::new_bool(sensitive),
+                )])
+                .unwrap(),
+                None,
+            )
+            .unwrap();
+            Authorizer::new().is_authorized_layered(&request, layers, &Entities::empty())
+        };
+
+        let response = authorize(&layers, "view", false);
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(response.diagnostics().deciding_layer(), None);
+        assert_eq!(response.diagnostics().reason().count(), 2);
+
+        // guardrail forbids win
+        let response = authorize(&layers, "view", true);
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().deciding_layer(), Some("org"));
+        assert_eq!(
+            response.diagnostics().reason().collect::<Vec<_>>(),
+            [&PolicyId::new("org::policy0")]
+        );
+
+        // lower layers narrow what higher layers allow
+        let response = authorize(&layers, "edit", false);
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().deciding_layer(), Some("owner"));
+
+        // the layers are serialized as one artifact
+        let roundtripped = LayeredPolicySet::from_json(layers.to_json().unwrap()).unwrap();
+        assert_eq!(roundtripped, layers);
+        assert_eq!(
+            roundtripped
+                .layers()
+                .map(PolicyLayer::name)
+                .collect::<Vec<_>>(),
+            ["org", "team", "owner"]
+        );
+        assert_eq!(
+            authorize(&roundtripped, "view", true)
+                .diagnostics()
+                .deciding_layer(),
+            Some("org")
+        );
+
+        // guardrails alone permit nothing
+        let mut guardrails = LayeredPolicySet::new();
+        guardrails
+            .push_layer(layer(
+                "org",
+                LayerMode::Guardrail,
+                "permit(principal, action, resource);",
+            ))
+            .unwrap();
+        let response = authorize(&guardrails, "view", false);
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().deciding_layer(), None);
+    }
+
+    #[test]
+    fn find_references() {
+        let src = r#"
+            permit(principal is User in Group::"admins", action in [Action::"view", Action::"edit"], resource)
+            when { resource.owner == principal && principal has owner };
+            permit(principal == ?principal, action == Action::"view", resource is Photo);
+        "#;
+        let mut pset = PolicySet::from_str(src).unwrap();
+        pset.link(
+            PolicyId::new("policy1"),
+            PolicyId::new("link"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Group", "admins"))]),
+        )
+        .unwrap();
+        pset.add(
+            Policy::from_json(
+                Some(PolicyId::new("json")),
+                serde_json::json!({
+                    "effect": "forbid",
+                    "principal": { "op": "in", "entity": { "type": "Group", "id": "admins" } },
+                    "action": { "op": "All" },
+                    "resource": { "op": "All" },
+                    "conditions": [],
+                }),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let snippets = |target: ReferenceTarget| {
+            pset.find_references(&target)
+                .into_iter()
+                .map(|r| {
+                    let snippet = r
+                        .span()
+                        .and_then(|span| src.get(span.offset()..span.offset() + span.len()));
+                    (r.policy_id().to_string(), snippet)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            snippets(ReferenceTarget::Entity(EntityUid::from_strs(
+                "Group", "admins"
+            ))),
+            [
+                ("policy0".into(), Some(r#"Group::"admins""#)),
+                ("link".into(), Some("?principal")),
+                ("json".into(), None),
+            ]
+        );
+        assert_eq!(
+            snippets(ReferenceTarget::EntityType("User".parse().unwrap())),
+            [("policy0".into(), Some("User"))]
+        );
+        assert_eq!(
+            snippets(ReferenceTarget::EntityType("Photo".parse().unwrap())),
+            [
+                ("policy1".into(), Some("Photo")),
+                ("link".into(), Some("Photo"))
+            ]
+        );
+        assert_eq!(
+            snippets(ReferenceTarget::Action(EntityUid::from_strs(
+                "Action", "view"
+            ))),
+            [
+                ("policy1".into(), Some(r#"Action::"view""#)),
+                ("policy0".into(), Some(r#"Action::"view""#)),
+                ("link".into(), Some(r#"Action::"view""#)),
+            ]
+        );
+        assert_eq!(
+            snippets(ReferenceTarget::Attribute("owner".into())),
+            [
+                ("policy0".into(), Some("resource.owner")),
+                ("policy0".into(), Some("principal has owner")),
+            ]
+        );
+        assert!(snippets(ReferenceTarget::Attribute("other".into())).is_empty());
+    }
+
+    #[test]
+    fn upgrade_template() {
+        let mut pset = PolicySet::new();
+        let template = Template::parse(
+            Some(PolicyId::new("t")),
+            "permit(principal == ?principal, action, resource in ?resource);",
+        )
+        .unwrap();
+        pset.add_template(template).unwrap();
+        for (id, user) in [("alice", "alice"), ("bob", "bob")] {
+            pset.link(
+                PolicyId::new("t"),
+                PolicyId::new(id),
+                HashMap::from([
+                    (SlotId::principal(), EntityUid::from_strs("User", user)),
+                    (SlotId::resource(), EntityUid::from_strs("Album", "a")),
+                ]),
+            )
+            .unwrap();
+        }
+        pset.set_enabled(PolicyId::new("bob"), false).unwrap();
+        pset.set_policy_metadata(
+            PolicyId::new("bob"),
+            PolicyMetadata::new().with_tag("team", "photos"),
+        )
+        .unwrap();
+
+        let template = Template::parse(
+            Some(PolicyId::new("t")),
+            r#"permit(principal == ?principal, action == Action::"view", resource in ?resource);"#,
+        )
+        .unwrap();
+        let mut relinked = pset.upgrade_template(template).unwrap();
+        relinked.sort();
+        assert_eq!(relinked, [PolicyId::new("alice"), PolicyId::new("bob")]);
+        let bob = pset.policy(&PolicyId::new("bob")).unwrap();
+        assert_eq!(
+            bob.action_constraint(),
+            ActionConstraint::Eq(EntityUid::from_strs("Action", "view"))
+        );
+        assert_eq!(bob.template_id(), Some(&PolicyId::new("t")));
+        assert!(!pset.is_enabled(&PolicyId::new("bob")));
+        assert_eq!(
+            pset.policy_metadata(&PolicyId::new("bob"))
+                .and_then(|m| m.tag("team")),
+            Some("photos")
+        );
+
+        // Removing `?resource` invalidates every link, so nothing changes
+        let before = pset.clone();
+        let template = Template::parse(
+            Some(PolicyId::new("t")),
+            "permit(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        assert_matches!(
+            pset.upgrade_template(template),
+            Err(PolicySetError::TemplateUpgrade(err)) => {
+                assert_eq!(err.template_id(), &PolicyId::new("t"));
+                let mut invalid: Vec<_> = err.invalid_links().map(|(id, _)| id.clone()).collect();
+                invalid.sort();
+                assert_eq!(invalid, [PolicyId::new("alice"), PolicyId::new("bob")]);
+            }
+        );
+        assert!(pset.diff(&before).is_empty());
+        assert_eq!(
+            pset.template(&PolicyId::new("t"))
+                .unwrap()
+                .action_constraint(),
+            ActionConstraint::Eq(EntityUid::from_strs("Action", "view"))
+        );
+
+        let template = Template::parse(
+            Some(PolicyId::new("missing")),
+            "permit(principal == ?principal, action, resource);",
+        )
+        .unwrap();
+        assert_matches!(
+            pset.upgrade_template(template),
+            Err(PolicySetError::TemplateNonexistent(_))
+        );
+    }
+
+    #[test]
+    fn signed_policy_sets() {
+        // A toy signature scheme: the signature is the key id followed by
+        // the message, and only `trusted` is a trusted key
+        let sign = |pset: &PolicySet, key_id: &str| {
+            let mut signature = key_id.as_bytes().to_vec();
+            signature.extend(pset.canonical_bytes().unwrap());
+            PolicySetSignature::new(key_id, signature)
+        };
+        let verifier = |message: &[u8], signature: &PolicySetSignature| {
+            signature.key_id() == "trusted"
+                && signature.signature().strip_prefix(b"trusted".as_slice()) == Some(message)
+        };
+
+        let build = |order: &[usize]| {
+            let policies = [
+                r#"permit(principal == User::"alice", action, resource);"#,
+                "forbid(principal, action, resource) when { context.blocked };",
+                "permit(principal == ?principal, action, resource);",
+            ];
+            let mut pset = PolicySet::new();
+            for &i in order {
+                let id = PolicyId::new(format!("p{i}"));
+                if i == 2 {
+                    pset.add_template(Template::parse(Some(id), policies[i]).unwrap())
+                        .unwrap();
+                } else {
+                    pset.add(Policy::parse(Some(id), policies[i]).unwrap())
+                        .unwrap();
+                }
+            }
+            for &i in order {
+                pset.link(
+                    PolicyId::new("p2"),
+                    PolicyId::new(format!("link{i}")),
+                    HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", "bob"))]),
+                )
+                .unwrap();
+                pset.set_enabled(PolicyId::new(format!("link{i}")), false)
+                    .unwrap();
+            }
+            pset
+        };
+        let pset = build(&[0, 1, 2]);
+        assert_eq!(
+            pset.canonical_bytes().unwrap(),
+            build(&[2, 1, 0]).canonical_bytes().unwrap()
+        );
+
+        let signatures = [sign(&pset, "untrusted"), sign(&pset, "trusted")];
+        assert_eq!(
+            pset.verify_signatures(&signatures, &verifier)
+                .unwrap()
+                .key_id(),
+            "trusted"
+        );
+        let loaded =
+            PolicySet::load_json_verified(pset.store_json().unwrap(), &signatures, &verifier)
+                .unwrap();
+        assert!(pset.diff(&loaded).is_empty());
+        PolicySet::load_binary_verified(&pset.store_binary().unwrap(), &signatures, &verifier)
+            .unwrap();
+
+        assert_matches!(
+            pset.verify_signatures(&signatures[..1], &verifier),
+            Err(PolicySetError::SignatureVerification(err)) => assert_eq!(err.signatures(), 1)
+        );
+        assert_matches!(
+            pset.verify_signatures(&[], &verifier),
+            Err(PolicySetError::SignatureVerification(_))
+        );
+        let mut tampered = pset.store_json().unwrap();
+        tampered["policySet"]["staticPolicies"]["p1"]["effect"] = "permit".into();
+        assert_matches!(
+            PolicySet::load_json_verified(tampered, &signatures, &verifier),
+            Err(PolicySetError::SignatureVerification(_))
+        );
+    }
+
+    #[test]
+    fn for_environment() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            @env("prod, staging") permit(principal, action == Action::"view", resource);
+            @env("staging") permit(principal, action == Action::"debug", resource);
+            forbid(principal, action, resource) when { context.blocked };
+            @env("staging") permit(principal == ?principal, action, resource);
+        "#,
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("policy3"),
+            PolicyId::new("link"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("User", "alice"))]),
+        )
+        .unwrap();
+        pset.set_enabled(PolicyId::new("policy0"), false).unwrap();
+
+        let config = EnvironmentConfig::new(["prod", "staging", "dev"]);
+        let by_env = pset.for_environments(&config).unwrap();
+        let ids = |env: &str| {
+            let pset = &by_env[env];
+            let mut ids: Vec<_> = pset
+                .policies()
+                .map(|p| p.id().to_string())
+                .chain(pset.templates().map(|t| t.id().to_string()))
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("prod"), ["policy0", "policy2"]);
+        assert_eq!(
+            ids("staging"),
+            ["link", "policy0", "policy1", "policy2", "policy3"]
+        );
+        assert_eq!(ids("dev"), ["policy2"]);
+        assert!(!by_env["prod"].is_enabled(&PolicyId::new("policy0")));
+
+        assert_matches!(
+            pset.for_environment("test", &config),
+            Err(PolicySetError::EnvironmentTag(policy_set_errors::EnvironmentTagError::UnknownEnvironment { environment })) => {
+                assert_eq!(environment, "test");
+            }
+        );
+        let strict = config.clone().with_untagged(UntaggedPolicies::Reject);
+        assert_matches!(
+            pset.for_environment("prod", &strict),
+            Err(PolicySetError::EnvironmentTag(policy_set_errors::EnvironmentTagError::Untagged { policy_id, .. })) => {
+                assert_eq!(policy_id, PolicyId::new("policy2"));
+            }
+        );
+        // A typo in a tag is an error in every environment, not just the one
+        // the policy was meant for
+        let typo = Policy::parse(
+            Some(PolicyId::new("typo")),
+            r#"@env("prdo") permit(principal, action, resource);"#,
+        )
+        .unwrap();
+        pset.add(typo).unwrap();
+        assert_matches!(
+            pset.for_environment("dev", &config),
+            Err(PolicySetError::EnvironmentTag(policy_set_errors::EnvironmentTagError::UnknownTag { policy_id, environment })) => {
+                assert_eq!(policy_id, PolicyId::new("typo"));
+                assert_eq!(environment, "prdo");
+            }
+        );
+        let other_key = config.with_annotation("deploy");
+        assert_eq!(
+            pset.for_environment("dev", &other_key)
+                .unwrap()
+                .policies()
+                .count(),
+            5
+        );
+        let empty =
+            PolicySet::from_str(r#"@env(" , ") permit(principal, action, resource);"#).unwrap();
+        assert_matches!(
+            empty.for_environment("dev", &other_key.with_annotation("env")),
+            Err(PolicySetError::EnvironmentTag(
+                policy_set_errors::EnvironmentTagError::EmptyTag { .. }
+            ))
+        );
+    }
+
+    #[test]
+    fn could_apply_to() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal is User in Group::"admins", action in [Action::"view", Action::"edit"], resource)
+            when { false };
+            permit(principal == ?principal, action, resource in ?resource);
+        "#,
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("policy1"),
+            PolicyId::new("link"),
+            HashMap::from([
+                (SlotId::principal(), EntityUid::from_strs("User", "alice")),
+                (SlotId::resource(), EntityUid::from_strs("Album", "trip")),
+            ]),
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "admins" }] },
+                { "uid": { "type": "Photo", "id": "beach" }, "attrs": {}, "parents": [{ "type": "Album", "id": "trip" }] },
+            ]),
+            None,
+        )
+        .unwrap();
+        let request = |principal: EntityUid, action: &str, resource: &str| {
+            Request::new(
+                principal,
+                EntityUid::from_strs("Action", action),
+                EntityUid::from_strs("Photo", resource),
+                Context::empty(),
+                None,
+            )
+            .unwrap()
+        };
+        let alice = || EntityUid::from_strs("User", "alice");
+        let bob = || EntityUid::from_strs("User", "bob");
+        let admin_service = || EntityUid::from_strs("Service", "admins");
+
+        // The condition of `policy0` is never satisfied, but isn't evaluated
+        let policy0 = pset.policy(&PolicyId::new("policy0")).unwrap();
+        assert!(policy0.could_apply_to(&request(alice(), "view", "beach"), &entities));
+        assert!(!policy0.could_apply_to(&request(alice(), "delete", "beach"), &entities));
+        assert!(!policy0.could_apply_to(&request(bob(), "view", "beach"), &entities));
+        assert!(!policy0.could_apply_to(&request(admin_service(), "view", "beach"), &entities));
+
+        let link = pset.policy(&PolicyId::new("link")).unwrap();
+        assert!(link.could_apply_to(&request(alice(), "delete", "beach"), &entities));
+        assert!(!link.could_apply_to(&request(alice(), "view", "mountain"), &entities));
+        assert!(!link.could_apply_to(&request(bob(), "view", "beach"), &entities));
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn specialization_cache() {
+        let store = PolicyStore::new(
+            PolicySet::from_str(
+                r#"
+                permit(principal in Group::"admins", action, resource);
+                permit(principal, action == Action::"view", resource in Album::"trip")
+                when { context.mfa };
+                forbid(principal, action == Action::"delete", resource)
+                unless { principal.level > 3 };
+            "#,
+            )
+            .unwrap(),
+        );
+        let entities = EntitiesSnapshot::new(
+            Entities::from_json_value(
+                serde_json::json!([
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": { "level": 5 }, "parents": [{ "type": "Group", "id": "admins" }] },
+                    { "uid": { "type": "User", "id": "bob" }, "attrs": {}, "parents": [] },
+                    { "uid": { "type": "Photo", "id": "beach" }, "attrs": {}, "parents": [{ "type": "Album", "id": "trip" }] },
+                ]),
+                None,
+            )
+            .unwrap(),
+        );
+        let request = |principal: &str, action: &str, mfa: bool| {
+            Request::new(
+                EntityUid::from_strs("User", principal),
+                EntityUid::from_strs("Action", action),
+                EntityUid::from_strs("Photo", "beach"),
+                Context::from_pairs([("mfa".into(), RestrictedExpression::new_bool(mfa))]).unwrap(),
+                None,
+            )
+            .unwrap()
+        };
+        let summary = |response: &Response| {
+            let mut reasons: Vec<_> = response.diagnostics().reason().cloned().collect();
+            reasons.sort();
+            (
+                response.decision(),
+                reasons,
+                response.diagnostics().errors().count(),
+            )
+        };
+
+        let cache = SpecializationCache::new(SpecializedVariable::Principal);
+        let policies = store.snapshot();
+        for principal in ["alice", "bob"] {
+            for action in ["view", "delete"] {
+                for mfa in [true, false] {
+                    let request = request(principal, action, mfa);
+                    let expected = policies.is_authorized(&Authorizer::new(), &request, &entities);
+                    let actual = cache.is_authorized(&request, &policies, &entities);
+                    assert_eq!(summary(&actual), summary(&expected), "{request}");
+                    assert_eq!(actual.diagnostics().policy_set_version(), Some(0));
+                }
+            }
+        }
+        assert_eq!(cache.len(), 2);
+        // Bob has no `level`, so the `forbid` errors for him, as it does
+        // during full evaluation
+        assert_eq!(
+            summary(&cache.is_authorized(&request("bob", "delete", true), &policies, &entities)).2,
+            1
+        );
+        let bob = EntityUid::from_strs("User", "bob");
+        let specialized = cache.specialize(&bob, &policies, &entities);
+        assert_eq!(specialized.uid(), &bob);
+        assert!(specialized
+            .is_authorized(&request("alice", "view", true), &entities)
+            .is_none());
+
+        // A new policy set snapshot invalidates every specialization
+        let policies = store
+            .update(|mut pset| {
+                pset.remove_static(PolicyId::new("policy0"))?;
+                Ok::<_, PolicySetError>(pset)
+            })
+            .unwrap();
+        let response =
+            cache.is_authorized(&request("alice", "delete", false), &policies, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().policy_set_version(), Some(1));
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+
+        let by_resource = SpecializationCache::new(SpecializedVariable::Resource);
+        let response =
+            by_resource.is_authorized(&request("bob", "view", true), &policies, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(by_resource.len(), 1);
+    }
+
+    #[test]
+    fn link_with_values() {
+        let template = Template::parse(
+            Some(PolicyId::new("template")),
+            "template(?limit: Long, ?actions: Set<Action>) =>
+            permit(principal == ?principal, action, resource)
+            when { context.size < ?limit && ?actions.contains(action) };",
+        )
+        .expect("Template Parse Failure");
+        let mut pset = PolicySet::new();
+        pset.add_template(template).unwrap();
+
+        let alice = EntityUid::from_strs("User", "alice");
+        let view = EntityUid::from_strs("Action", "view");
+        let before_link = pset.clone();
+        let result = pset.link_with_values(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(SlotId::principal(), alice.clone())]),
+            HashMap::from([(
+                "?limit".parse().unwrap(),
+                RestrictedExpression::new_string("ten".into()),
+            )]),
+        );
+        assert_matches!(result, Err(PolicySetError::Linking(_)));
+        assert_eq!(
+            pset, before_link,
+            "A failed link shouldn't mutate the policy set"
+        );
+
+        pset.link_with_values(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(SlotId::principal(), alice.clone())]),
+            HashMap::from([
+                (
+                    "?limit".parse().unwrap(),
+                    RestrictedExpression::new_long(10),
+                ),
+                (
+                    "?actions".parse().unwrap(),
+                    RestrictedExpression::new_set([RestrictedExpression::new_entity_uid(
+                        view.clone(),
+                    )]),
+                ),
+            ]),
+        )
+        .unwrap();
+
+        let authorize = |pset: &PolicySet, action: &EntityUid, size: i64| {
+            let request = Request::new(
+                alice.clone(),
+                action.clone(),
+                EntityUid::from_strs("Photo", "p"),
+                Context::from_pairs([("size".into(), RestrictedExpression::new_long(size))])
+                    .unwrap(),
+                None,
+            )
+            .unwrap();
+            Authorizer::new()
+                .is_authorized(&request, pset, &Entities::empty())
+                .decision()
+        };
+        let edit = EntityUid::from_strs("Action", "edit");
+        assert_eq!(authorize(&pset, &view, 5), Decision::Allow);
+        assert_eq!(authorize(&pset, &view, 50), Decision::Deny);
+        assert_eq!(authorize(&pset, &edit, 5), Decision::Deny);
+
+        // The link survives a roundtrip through JSON
+        let json = pset.clone().to_json().unwrap();
+        assert_eq!(
+            json["templateLinks"][0]["valueSlots"],
+            serde_json::json!({
+                "?limit": 10,
+                "?actions": [{ "__entity": { "type": "Action", "id": "view" } }],
+            })
+        );
+        let roundtripped = PolicySet::from_json_value(json).unwrap();
+        assert_eq!(authorize(&roundtripped, &view, 5), Decision::Allow);
+        assert_eq!(authorize(&roundtripped, &view, 50), Decision::Deny);
+    }
+
+    #[test]
+    fn link_with_default_values() {
+        let template = Template::parse(
+            Some(PolicyId::new("template")),
+            "template(?limit: Long = 10) =>
+            permit(principal == ?principal, action, resource)
+            when { context.size < ?limit };",
+        )
+        .expect("Template Parse Failure");
+        let mut pset = PolicySet::new();
+        pset.add_template(template).unwrap();
+
+        let alice = EntityUid::from_strs("User", "alice");
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("defaulted"),
+            HashMap::from([(SlotId::principal(), alice.clone())]),
+        )
+        .unwrap();
+        let defaulted = pset.policy(&PolicyId::new("defaulted")).unwrap();
+        assert!(defaulted.to_string().contains("< 10"));
+
+        let authorize = |pset: &PolicySet, size: i64| {
+            let request = Request::new(
+                alice.clone(),
+                EntityUid::from_strs("Action", "view"),
+                EntityUid::from_strs("Photo", "p"),
+                Context::from_pairs([("size".into(), RestrictedExpression::new_long(size))])
+                    .unwrap(),
+                None,
+            )
+            .unwrap();
+            Authorizer::new()
+                .is_authorized(&request, pset, &Entities::empty())
+                .decision()
+        };
+        assert_eq!(authorize(&pset, 5), Decision::Allow);
+        assert_eq!(authorize(&pset, 50), Decision::Deny);
+
+        // The default survives a roundtrip through JSON
+        let json = pset.clone().to_json().unwrap();
+        let roundtripped = PolicySet::from_json_value(json).unwrap();
+        assert_eq!(authorize(&roundtripped, 5), Decision::Allow);
+        assert_eq!(authorize(&roundtripped, 50), Decision::Deny);
+    }
+}
+
+mod schema_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    /// A minimal test that a valid Schema parses
+    #[test]
+    fn valid_schema() {
+        Schema::from_json_value(json!(
+        { "": {
+            "entityTypes": {
+                "Photo": {
+                    "memberOfTypes": [ "Album" ],
+                    "shape": {
+                        "type": "Record",
+                        "attributes": {
+                            "foo": {
+                                "type": "Boolean",
+                                "required": false
+                            }
+                        }
+                    }
+                },
+                "Album": {
+                    "memberOfTypes": [ ],
+                    "shape": {
+                        "type": "Record",
+                        "attributes": {
+                            "foo": {
+                                "type": "Boolean",
+                                "required": false
+                            }
+                        }
+                    }
+                }
+            },
+            "actions": {
+                "view": {
+                    "appliesTo": {
+                        "principalTypes": ["Photo", "Album"],
+                        "resourceTypes": ["Photo"]
+                    }
+                }
+            }
+        }}))
+        .expect("schema should be valid");
+    }
+
+    /// Test that an invalid schema returns the appropriate error
+    #[test]
+    fn invalid_schema() {
+        assert_matches!(
+            Schema::from_json_str(
+                // Written as a string because duplicate entity types are detected
+                // by the serde-json string parser.
+                r#"{"": {
+                "entityTypes": {
+                    "Photo": {
+                        "memberOfTypes": [ "Album" ],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "foo": {
+                                    "type": "Boolean",
+                                    "required": false
+                                }
+                            }
+                        }
+                    },
+                    "Album": {
+                        "memberOfTypes": [ ],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "foo": {
+                                    "type": "Boolean",
+                                    "required": false
+                                }
+                            }
+                        }
+                    },
+                    "Photo": {
+                        "memberOfTypes": [ "Album" ],
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "foo": {
+                                    "type": "Boolean",
+                                    "required": false
+                                }
+                            }
+                        }
+                    }
+                },
+                "actions": {
+                    "view": {
+                        "appliesTo": {
+                            "principalTypes": ["Photo", "Album"],
+                            "resourceTypes": ["Photo"]
+                        }
+                    }
+                }
+            }}"#
+            ),
+            Err(e) =>
+                expect_err(
+                    "",
+                    &Report::new(e),
+                    &ExpectedErrorMessageBuilder::error("invalid entry: found duplicate key at line 39 column 17")
+                        .build(),
+                )
+        );
+    }
+
+    #[test]
+    fn unconvertible_json_schema_fails_conversion() {
+        let json_string = json!(
+            {
+                "": {
+                    "commonTypes": {
+                        "Task": {
+                            "type": "Record",
+                            "attributes": {}
+                        }
+                    },
+                    "entityTypes": {
+                        "User": {
+                            "shape": {
+                                "type": "Task"
+                            }
+                        }
+                    },
+                    "actions": {}
+                }
+            }
+        );
+        let schema =
+            SchemaFragment::from_json_value(json_string.clone()).expect("schema should be valid");
+        let result = schema.to_cedarschema();
+        assert_matches!(
+            result,
+            Err(e @ ToCedarSchemaError::UnconvertibleEntityTypeShape(..)) => {
+                expect_err(
+                    &json_string,
+                    &miette::Report::new(e),
+                    &ExpectedErrorMessageBuilder::error(
+                        "The following entities have shapes that cannot be converted to Cedar schema syntax: [User]",
+                    )
+                    .help("Entity shapes may only be record types. In the Cedar schema syntax, they additionally may not reference common type definitions.")
+                    .build(),
+                );
+            }
+        );
+    }
+}
+
+mod ancestors_tests {
+    use super::*;
+
+    #[test]
+    fn test_ancestors() {
+        let a_euid: EntityUid = EntityUid::from_strs("test", "A");
+        let b_euid: EntityUid = EntityUid::from_strs("test", "b");
+        let c_euid: EntityUid = EntityUid::from_strs("test", "C");
+        let a = Entity::new_no_attrs(a_euid.clone(), HashSet::new());
+        let b = Entity::new_no_attrs(b_euid.clone(), HashSet::from([a_euid.clone()]));
+        let c = Entity::new_no_attrs(c_euid.clone(), HashSet::from([b_euid.clone()]));
+        let es = Entities::from_entities([a, b, c], None).unwrap();
+        assert_eq!(es.len(), 3);
+        assert!(!es.is_empty());
+        let ans = es.ancestors(&c_euid).unwrap().collect::<HashSet<_>>();
+        assert_eq!(ans.len(), 2);
+        assert!(ans.contains(&b_euid));
+        assert!(ans.contains(&a_euid));
     }
 }
 
@@ -5506,7 +6996,7 @@ mod level_validation_tests {
             src,
             &miette::Report::new(result),
             &ExpectedErrorMessageBuilder::error(
-                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1)",
+                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1), in `resource.foo.profile_pic`",
             )
             .exactly_one_underline("resource.foo.profile_pic")
             .build(),
@@ -5539,7 +7029,7 @@ mod level_validation_tests {
             src,
             &miette::Report::new(result),
             &ExpectedErrorMessageBuilder::error(
-                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1)",
+                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1), in `resource.foo.profile_pic`",
             )
             .exactly_one_underline("resource.foo.profile_pic")
             .build(),
@@ -5636,7 +7126,7 @@ mod level_validation_tests {
             src,
             &miette::Report::new(result),
             &ExpectedErrorMessageBuilder::error(
-                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1)",
+                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1), in `resource.foo.profile_pic`",
             )
             .exactly_one_underline("resource.foo.profile_pic")
             .build(),
@@ -5687,7 +7177,7 @@ mod level_validation_tests {
             src,
             &miette::Report::new(result),
             &ExpectedErrorMessageBuilder::error(
-                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1)",
+                "for policy `policy0`, this policy requires level 2, which exceeds the maximum allowed level (1), in `resource.foo.is_admin`",
             )
             .exactly_one_underline("resource.foo.is_admin")
             .build(),
@@ -6617,6 +8107,106 @@ mod decimal_ip_constructors {
         );
     }
 
+    #[test]
+    fn expr_ipset() {
+        let ipset = Expression::new_ipset(["192.168.0.0/16", "10.0.0.0/8"]);
+        assert_matches!(evaluate_empty(&ipset),
+            Ok(EvalResult::ExtensionValue(o)) => assert_eq!(&o, r#"ipset(["10.0.0.0/8", "192.168.0.0/16"])"#)
+        );
+    }
+
+    #[test]
+    fn expr_bad_ipset() {
+        let ipset = Expression::new_ipset(["10.0.0.0/8", "foobar"]);
+        assert_matches!(evaluate_empty(&ipset),
+            Err(EvaluationError::FailedExtensionFunctionExecution(e)) => {
+                assert_eq!(e.extension_name(), "ipaddr");
+            }
+        );
+    }
+
+    #[test]
+    fn rexpr_ipset_constructor() {
+        let ipset = RestrictedExpression::new_ipset(["10.0.0.0/8"]);
+        assert_matches!(ipset.into_inner().expr_kind(),
+            ast::ExprKind::ExtensionFunctionApp { fn_name, args} => {
+                assert_eq!(fn_name, &("ipset".parse().unwrap()));
+                assert_eq!(args.as_ref().len(), 1);
+                let arg = args.first().unwrap();
+                assert_matches!(arg.expr_kind(), ast::ExprKind::Set(elems) => {
+                    assert_matches!(
+                        elems.first().unwrap().expr_kind(),
+                        ast::ExprKind::Lit(ast::Literal::String(s)) => {
+                            assert_eq!(s.as_str(), "10.0.0.0/8");
+                        },
+                    );
+                });
+            }
+        );
+    }
+
+    #[test]
+    fn ipset_in_entity_data() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            r#"
+            entity User;
+            entity Network = { ranges: ipset };
+            action connect appliesTo {
+                principal: User,
+                resource: Network,
+                context: { source: ipaddr },
+            };
+            "#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "Network", "id": "corp" },
+                    "attrs": {
+                        "ranges": { "__extn": { "fn": "ipset", "arg": ["10.0.0.0/8", "192.168.0.0/16"] } }
+                    },
+                    "parents": []
+                }
+            ]),
+            Some(&schema),
+        )
+        .unwrap();
+        let policies: PolicySet =
+            "permit(principal, action, resource) when { context.source.isInSet(resource.ranges) };"
+                .parse()
+                .unwrap();
+        assert!(Validator::new(schema.clone())
+            .validate(&policies, ValidationMode::Strict)
+            .validation_passed());
+
+        let request = |source: &str| {
+            Request::new(
+                r#"User::"alice""#.parse().unwrap(),
+                r#"Action::"connect""#.parse().unwrap(),
+                r#"Network::"corp""#.parse().unwrap(),
+                Context::from_pairs([("source".into(), RestrictedExpression::new_ip(source))])
+                    .unwrap(),
+                Some(&schema),
+            )
+            .unwrap()
+        };
+        let authorizer = Authorizer::new();
+        for (source, decision) in [
+            ("10.20.30.40", Decision::Allow),
+            ("192.168.1.1", Decision::Allow),
+            ("172.16.0.1", Decision::Deny),
+        ] {
+            assert_eq!(
+                authorizer
+                    .is_authorized(&request(source), &policies, &entities)
+                    .decision(),
+                decision,
+                "{source}"
+            );
+        }
+    }
+
     fn evaluate_empty(expr: &Expression) -> Result<EvalResult, EvaluationError> {
         let euid: EntityUid = r#"Placeholder::"entity""#.parse().unwrap();
         let r = Request::new(euid.clone(), euid.clone(), euid, Context::empty(), None).unwrap();
@@ -7855,6 +9445,141 @@ mod request_validation_tests {
     }
 }
 
+mod schema_request_builder_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+            entity User;
+            entity Photo;
+            action view appliesTo {
+                principal: User,
+                resource: Photo,
+                context: { mfa: Bool, ip?: ipaddr }
+            };
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn view() -> EntityUid {
+        EntityUid::from_strs("Action", "view")
+    }
+
+    #[test]
+    fn builds_valid_request() {
+        let schema = schema();
+        let request = Request::schema_builder(&schema)
+            .principal(EntityUid::from_strs("User", "alice"))
+            .unwrap()
+            .resource(EntityUid::from_strs("Photo", "vacation"))
+            .unwrap()
+            .context_attr("mfa", RestrictedExpression::new_bool(true))
+            .unwrap()
+            .action(view())
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.principal(),
+            Some(&EntityUid::from_strs("User", "alice"))
+        );
+        assert_eq!(
+            request.context().unwrap().get("mfa"),
+            Some(EvalResult::Bool(true))
+        );
+    }
+
+    #[test]
+    fn invalid_attr_reported_when_set() {
+        let schema = schema();
+        let builder = Request::schema_builder(&schema).action(view()).unwrap();
+        assert_matches!(
+            builder.clone().context_attr("mfa", RestrictedExpression::new_long(1)),
+            Err(RequestBuilderError::Validation(RequestValidationError::InvalidContextAttribute(e))) => {
+                assert_eq!(e.attr(), "mfa");
+                assert_eq!(e.action(), &view());
+            }
+        );
+        assert_matches!(
+            builder.context_attr("otp", RestrictedExpression::new_long(1)),
+            Err(RequestBuilderError::Validation(RequestValidationError::UndeclaredContextAttribute(e))) => {
+                assert_eq!(e.attr(), "otp");
+            }
+        );
+    }
+
+    #[test]
+    fn attrs_revalidated_when_action_set() {
+        let schema = schema();
+        let builder = Request::schema_builder(&schema)
+            .context_attr("mfa", RestrictedExpression::new_string("yes".into()))
+            .unwrap();
+        assert_matches!(
+            builder.action(view()),
+            Err(RequestBuilderError::Validation(RequestValidationError::InvalidContextAttribute(e))) => {
+                assert_eq!(e.attr(), "mfa");
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_scope_variables() {
+        let schema = schema();
+        assert_matches!(
+            Request::schema_builder(&schema).action(EntityUid::from_strs("Action", "edit")),
+            Err(RequestBuilderError::Validation(
+                RequestValidationError::UndeclaredAction(_)
+            ))
+        );
+        assert_matches!(
+            Request::schema_builder(&schema)
+                .action(view())
+                .unwrap()
+                .resource(EntityUid::from_strs("User", "bob")),
+            Err(RequestBuilderError::Validation(
+                RequestValidationError::InvalidResourceType(_)
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_components() {
+        let schema = schema();
+        let builder = Request::schema_builder(&schema)
+            .action(view())
+            .unwrap()
+            .principal(EntityUid::from_strs("User", "alice"))
+            .unwrap();
+        assert_matches!(
+            builder.clone().build(),
+            Err(RequestBuilderError::MissingComponent(e)) => {
+                assert_eq!(e.component(), "resource");
+            }
+        );
+        let builder = builder
+            .resource(EntityUid::from_strs("Photo", "vacation"))
+            .unwrap();
+        assert_matches!(
+            builder.clone().build(),
+            Err(RequestBuilderError::Validation(RequestValidationError::MissingContextAttribute(e))) => {
+                assert_eq!(e.attr(), "mfa");
+            }
+        );
+        // `ip` is optional
+        builder
+            .context(
+                Context::from_pairs([("mfa".into(), RestrictedExpression::new_bool(false))])
+                    .unwrap(),
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+    }
+}
+
 mod context_tests {
     use cool_asserts::assert_matches;
     use serde_json::json;
@@ -8058,6 +9783,62 @@ mod context_tests {
             &ExpectedErrorMessageBuilder::error("duplicate key `key2` in context").build(),
         );
     }
+
+    #[test]
+    #[cfg(all(feature = "decimal", feature = "datetime"))]
+    fn coerced_context() {
+        let schema = Schema::from_str(
+            r#"
+            entity User;
+            action pay appliesTo {
+                principal: User,
+                resource: User,
+                context: { amount: decimal, at: datetime, attempts: Set<Long> }
+            };
+            "#,
+        )
+        .unwrap();
+        let action = EntityUid::from_strs("Action", "pay");
+        let (context, coercions) = Context::from_json_value_coerced(
+            json!({
+                "amount": 12.5,
+                "at": "2024-06-01T12:30:00+02:00",
+                "attempts": ["1", 2],
+            }),
+            (&schema, &action),
+        )
+        .unwrap();
+        let expected = Context::from_json_value(
+            json!({
+                "amount": "12.5",
+                "at": "2024-06-01T12:30:00+0200",
+                "attempts": [1, 2],
+            }),
+            Some((&schema, &action)),
+        )
+        .unwrap();
+        assert_eq!(context, expected);
+        let mut coerced: Vec<_> = coercions
+            .iter()
+            .map(|c| (c.path().collect::<Vec<_>>().join("."), c.expected_type()))
+            .collect();
+        coerced.sort();
+        assert_eq!(
+            coerced,
+            [
+                ("amount".to_string(), "decimal".to_string()),
+                ("at".to_string(), "datetime".to_string()),
+                ("attempts.0".to_string(), "long".to_string()),
+            ]
+        );
+
+        // values which can't be coerced are reported as usual
+        assert!(Context::from_json_value_coerced(
+            json!({ "amount": 1.23456, "at": "2024-06-01", "attempts": [] }),
+            (&schema, &action),
+        )
+        .is_err());
+    }
 }
 
 mod policy_manipulation_functions_tests {
@@ -9116,6 +10897,134 @@ mod schema_annotations {
             None
         );
     }
+
+    #[track_caller]
+    fn nested_schema() -> SchemaFragment {
+        SchemaFragment::from_cedarschema_str(
+            r#"
+        @route("shared")
+        type Address = { @pii street: String, "zip code": String };
+
+        @route("eu")
+        namespace App {
+          @doc("a user")
+          entity User {
+            @pii
+            name: String,
+            @doc("home")
+            home: Address,
+            @doc("work")
+            work: { @doc("office") office: { @pii desk: Long } },
+          };
+          @route("audit-log")
+          action "view photo" appliesTo {
+            principal: User,
+            resource: User,
+            context: { @pii ip: ipaddr, @doc("where") origin: Address },
+          };
+        }
+        "#,
+        )
+        .expect("should be a valid schema fragment")
+        .0
+    }
+
+    #[test]
+    fn annotations_by_path() {
+        let schema = nested_schema();
+        for (path, key, value) in [
+            ("App", "route", "eu"),
+            ("Address", "route", "shared"),
+            ("Address.street", "pii", ""),
+            ("App::User", "doc", "a user"),
+            ("App::User.name", "pii", ""),
+            ("App::User.home", "doc", "home"),
+            ("App::User.home.street", "pii", ""),
+            ("App::User.work.office", "doc", "office"),
+            ("App::User.work.office.desk", "pii", ""),
+            (r#"App::Action::"view photo""#, "route", "audit-log"),
+            (r#"App::Action::"view photo".ip"#, "pii", ""),
+            (r#"App::Action::"view photo".origin"#, "doc", "where"),
+            (r#"App::Action::"view photo".origin.street"#, "pii", ""),
+        ] {
+            assert_eq!(schema.annotation_at(path, key), Some(value), "{path}");
+        }
+        assert_eq!(
+            schema
+                .annotations_at("App::User.work.office.desk")
+                .expect("should get annotations")
+                .collect::<Vec<_>>(),
+            vec![("pii", "")]
+        );
+        assert_eq!(
+            schema
+                .annotations_at(r#"Address."zip code""#)
+                .expect("should get annotations")
+                .count(),
+            0
+        );
+        for path in [
+            "App::Photo",
+            "App::User.age",
+            "App::User.name.first",
+            r#"App::Action::"view""#,
+            r#"App::Action::"view photo".time"#,
+            "Other",
+            "App::User.",
+            "App::User.work office",
+            r#"App::User."work"#,
+        ] {
+            assert!(schema.annotations_at(path).is_none(), "{path}");
+        }
+        assert_matches!(schema.annotation_at("App::User.name", "doc"), None);
+    }
+
+    /// Annotations on every kind of declaration survive converting a schema
+    /// from the Cedar format to the JSON format and back
+    #[test]
+    fn annotations_round_trip() {
+        let schema = nested_schema();
+        let json = SchemaFragment::from_json_str(
+            &schema
+                .to_json_string()
+                .expect("should convert to JSON schema"),
+        )
+        .expect("should parse JSON schema");
+        let cedar = SchemaFragment::from_cedarschema_str(
+            &json
+                .to_cedarschema()
+                .expect("should convert to Cedar schema"),
+        )
+        .expect("should parse Cedar schema")
+        .0;
+        for fragment in [&json, &cedar] {
+            for path in [
+                "App",
+                "Address",
+                "Address.street",
+                "App::User",
+                "App::User.name",
+                "App::User.home",
+                "App::User.work.office",
+                "App::User.work.office.desk",
+                r#"App::Action::"view photo""#,
+                r#"App::Action::"view photo".ip"#,
+                r#"App::Action::"view photo".origin"#,
+            ] {
+                assert_eq!(
+                    fragment
+                        .annotations_at(path)
+                        .expect("should get annotations")
+                        .collect::<Vec<_>>(),
+                    schema
+                        .annotations_at(path)
+                        .expect("should get annotations")
+                        .collect::<Vec<_>>(),
+                    "{path}"
+                );
+            }
+        }
+    }
 }
 
 mod to_cedar {
@@ -9366,9 +11275,620 @@ mod test_entities_api {
         assert_eq!(entities.len(), 2);
         assert!(!entities.is_ancestor_of(&e2_uid, &e1_uid));
 
-        entities = entities.upsert_entities(vec![e1_updated], None).unwrap();
-        assert_eq!(entities.len(), 2);
-        assert!(entities.is_ancestor_of(&e2_uid, &e1_uid));
+        entities = entities.upsert_entities(vec![e1_updated], None).unwrap();
+        assert_eq!(entities.len(), 2);
+        assert!(entities.is_ancestor_of(&e2_uid, &e1_uid));
+    }
+}
+
+mod entities_snapshot_tests {
+    use super::{Entities, EntitiesSnapshot, EntitiesWriter, Entity, EntityUid};
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    fn user(id: &str) -> Entity {
+        Entity::new_no_attrs(EntityUid::from_strs("User", id), HashSet::new())
+    }
+
+    #[test]
+    fn snapshots_are_unaffected_by_updates() {
+        let writer = EntitiesWriter::new(Entities::empty());
+        let first = writer
+            .update(|es| es.add_entities([user("alice")], None))
+            .unwrap();
+        let second = writer
+            .update(|es| es.upsert_entities([user("bob")], None))
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 2);
+        assert_eq!(first.version(), 1);
+        assert_eq!(second.version(), 2);
+        assert!(writer.snapshot().ptr_eq(&second));
+        assert!(!first.ptr_eq(&second));
+
+        let replaced = writer.replace(Entities::empty());
+        assert_eq!(replaced.version(), 3);
+        assert!(replaced.is_empty());
+        assert_eq!(second.len(), 2);
+    }
+
+    #[test]
+    fn failed_update_publishes_nothing() {
+        let writer = EntitiesWriter::new(Entities::empty());
+        let before = writer.snapshot();
+        // adding two different entities with the same UID is an error
+        let alice_with_parent = Entity::new_no_attrs(
+            EntityUid::from_strs("User", "alice"),
+            HashSet::from([EntityUid::from_strs("Group", "admins")]),
+        );
+        writer
+            .update(|es| es.add_entities([user("alice"), alice_with_parent], None))
+            .unwrap_err();
+        assert!(writer.snapshot().ptr_eq(&before));
+        assert_eq!(writer.snapshot().version(), 0);
+    }
+
+    #[test]
+    fn clones_share_entities() {
+        let snapshot = EntitiesSnapshot::from(
+            Entities::empty()
+                .add_entities([user("alice")], None)
+                .unwrap(),
+        );
+        let clone = snapshot.clone();
+        assert!(snapshot.ptr_eq(&clone));
+        assert_eq!(snapshot.entities(), clone.entities());
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let writer = Arc::new(EntitiesWriter::new(Entities::empty()));
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let writer = Arc::clone(&writer);
+                std::thread::spawn(move || {
+                    for j in 0..10 {
+                        let reader = writer.snapshot();
+                        writer
+                            .update(|es| es.add_entities([user(&format!("{i}-{j}"))], None))
+                            .unwrap();
+                        assert!(writer.snapshot().version() > reader.version());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let latest = writer.snapshot();
+        assert_eq!(latest.len(), 80);
+        assert_eq!(latest.version(), 80);
+    }
+}
+
+mod policy_store_tests {
+    use super::{
+        Authorizer, Context, Decision, Entities, EntityUid, PolicyId, PolicySet, PolicySetSnapshot,
+        PolicyStore, PolicyStoreError, Request,
+    };
+    use cool_asserts::assert_matches;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn permit_all() -> PolicySet {
+        PolicySet::from_str("permit(principal, action, resource);").unwrap()
+    }
+
+    #[test]
+    fn compare_and_swap() {
+        let store = PolicyStore::new(PolicySet::new());
+        let v0 = store.snapshot();
+        let v1 = store.compare_and_swap(0, permit_all()).unwrap();
+        assert_eq!(v1.version(), 1);
+        assert_eq!(v1.num_of_policies(), 1);
+        assert_matches!(
+            store.compare_and_swap(0, PolicySet::new()),
+            Err(PolicyStoreError::VersionMismatch {
+                expected: 0,
+                actual: 1
+            })
+        );
+        assert!(store.snapshot().ptr_eq(&v1));
+        assert!(v0.is_empty());
+
+        let v2 = store
+            .update(|mut pset| {
+                pset.remove_static(PolicyId::new("policy0"))?;
+                Ok::<_, crate::PolicySetError>(pset)
+            })
+            .unwrap();
+        assert_eq!(v2.version(), 2);
+        assert!(v2.is_empty());
+        assert_eq!(store.replace(permit_all()).version(), 3);
+    }
+
+    #[test]
+    fn rollback() {
+        let store = PolicyStore::with_retention(PolicySet::new(), 2);
+        let v1 = store.replace(permit_all());
+        store.replace(PolicySet::new());
+        store.replace(PolicySet::new());
+        assert_eq!(
+            store
+                .history()
+                .iter()
+                .map(PolicySetSnapshot::version)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        let v4 = store.rollback(1).unwrap();
+        assert_eq!(v4.version(), 4);
+        assert!(v4.ptr_eq(&v1));
+        assert_matches!(
+            store.rollback(0),
+            Err(PolicyStoreError::NotRetained { version: 0 })
+        );
+        assert_eq!(store.snapshot().version(), 4);
+    }
+
+    #[test]
+    fn version_in_diagnostics() {
+        let store = PolicyStore::new(PolicySet::new());
+        store.replace(permit_all());
+        let request = Request::new(
+            EntityUid::from_strs("User", "alice"),
+            EntityUid::from_strs("Action", "view"),
+            EntityUid::from_strs("Photo", "a"),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new();
+        let snapshot = store.snapshot();
+        let response = snapshot.is_authorized(&authorizer, &request, &Entities::empty());
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(response.diagnostics().policy_set_version(), Some(1));
+        let response = authorizer.is_authorized(&request, &snapshot, &Entities::empty());
+        assert_eq!(response.diagnostics().policy_set_version(), None);
+    }
+
+    #[test]
+    fn concurrent_compare_and_swap() {
+        let store = Arc::new(PolicyStore::new(PolicySet::new()));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                std::thread::spawn(move || {
+                    let mut successes = 0;
+                    for _ in 0..10 {
+                        let snapshot = store.snapshot();
+                        if store
+                            .compare_and_swap(snapshot.version(), permit_all())
+                            .is_ok()
+                        {
+                            successes += 1;
+                        }
+                    }
+                    successes
+                })
+            })
+            .collect();
+        let mut successes = 0;
+        for handle in handles {
+            successes += handle.join().unwrap();
+        }
+        assert_eq!(store.snapshot().version(), successes);
+    }
+}
+
+mod audit_tests {
+    use super::{
+        AuditRecord, AuditSink, Authorizer, Context, Decision, Entities, EntityUid,
+        JsonLinesAuditSink, PolicyId, PolicySet, PolicySetSnapshot, Request,
+    };
+    use std::cell::RefCell;
+    use std::str::FromStr;
+    use std::time::{Duration, SystemTime};
+
+    fn request() -> Request {
+        Request::new(
+            EntityUid::from_str(r#"User::"alice""#).unwrap(),
+            EntityUid::from_str(r#"Action::"view""#).unwrap(),
+            EntityUid::from_str(r#"Photo::"vacation""#).unwrap(),
+            Context::from_json_value(serde_json::json!({ "mfa": true }), None).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+
+    fn policies() -> PolicySet {
+        PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.mfa };
+            permit(principal == User::"alice", action, resource);
+            forbid(principal, action, resource) when { principal.banned };"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn is_authorized_audited() {
+        let records = RefCell::new(Vec::new());
+        let sink = |record: &AuditRecord| records.borrow_mut().push(record.clone());
+        let response = Authorizer::new().is_authorized_audited(
+            &request(),
+            &policies(),
+            &Entities::empty(),
+            &sink,
+        );
+        assert_eq!(response.decision(), Decision::Allow);
+
+        let records = records.into_inner();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.principal(), Some(r#"User::"alice""#));
+        assert_eq!(record.action(), Some(r#"Action::"view""#));
+        assert_eq!(record.resource(), Some(r#"Photo::"vacation""#));
+        assert_eq!(record.context(), Some(&serde_json::json!({ "mfa": true })));
+        assert_eq!(record.decision(), Decision::Allow);
+        assert_eq!(
+            record.determining_policies().collect::<Vec<_>>(),
+            [&PolicyId::new("policy0"), &PolicyId::new("policy1")]
+        );
+        let errors: Vec<_> = record.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].policy_id(), &PolicyId::new("policy2"));
+        assert!(record.timestamp_millis() > 0);
+        assert_eq!(record.policy_set_version(), None);
+    }
+
+    #[test]
+    fn json_lines() {
+        let sink = JsonLinesAuditSink::new(Vec::new());
+        let snapshot = PolicySetSnapshot::new(policies());
+        let started = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for _ in 0..2 {
+            let response =
+                snapshot.is_authorized(&Authorizer::new(), &request(), &Entities::empty());
+            sink.record(&AuditRecord::new(
+                &request(),
+                &response,
+                started,
+                Duration::from_micros(42),
+            ));
+        }
+        assert!(sink.take_error().is_none());
+
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestampMillis": 1_700_000_000_000_u64,
+                "durationMicros": 42,
+                "principal": r#"User::"alice""#,
+                "action": r#"Action::"view""#,
+                "resource": r#"Photo::"vacation""#,
+                "context": { "mfa": true },
+                "decision": "allow",
+                "determiningPolicies": ["policy0", "policy1"],
+                "errors": [{
+                    "policyId": "policy2",
+                    "message": json["errors"][0]["message"],
+                }],
+                "policySetVersion": 0,
+            })
+        );
+        let record: AuditRecord = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(record.duration(), Duration::from_micros(42));
+        assert_eq!(record.policy_set_version(), Some(0));
+    }
+}
+
+mod filter_tests {
+    use super::{
+        pst, CompareOp, FilterBackend, FilterTranslator, PolicySet, SqlFilter, UntranslatableReason,
+    };
+    use cool_asserts::assert_matches;
+    use similar_asserts::assert_eq;
+    use smol_str::SmolStr;
+    use std::str::FromStr;
+
+    fn sql(policies: &str) -> (String, usize) {
+        let policies = PolicySet::from_str(policies).unwrap();
+        let mut backend = SqlFilter::new("id")
+            .with_type_column("type")
+            .with_ancestors_table("ancestors", "entity", "ancestor");
+        let filter = FilterTranslator::new(pst::Var::Resource)
+            .translate_residuals(policies.policies(), &mut backend)
+            .unwrap()
+            .into_filter();
+        (filter.sql().to_string(), filter.params().len())
+    }
+
+    #[test]
+    fn sql_filter() {
+        assert_eq!(
+            sql(
+                r"permit(principal, action, resource) when { 100 > resource.size && resource has owner.name };"
+            ),
+            (
+                r#"(NOT ("size" >= ?) AND "owner" IS NOT NULL AND "owner_name" IS NOT NULL)"#
+                    .to_string(),
+                1
+            )
+        );
+        assert_eq!(
+            sql(
+                r#"permit(principal, action, resource) when { resource.name like "*_1.jpg" || ["a", "b"].contains(resource.owner) };"#
+            ),
+            (
+                r#"("name" LIKE ? ESCAPE '\' OR "owner" IN (?, ?))"#.to_string(),
+                3
+            )
+        );
+        assert_eq!(
+            sql(r#"permit(principal, action, resource is Photo in Folder::"f");"#),
+            (
+                r#"("type" = ? AND ("id" IN (?) OR "id" IN (SELECT "entity" FROM "ancestors" WHERE "ancestor" IN (?))))"#
+                    .to_string(),
+                3
+            )
+        );
+        assert_eq!(
+            sql(
+                r#"permit(principal, action, resource) when { if resource.public then true else resource.owner == "alice" };"#
+            ),
+            (
+                r#"("public" = ? OR (NOT ("public" = ?) AND "owner" = ?))"#.to_string(),
+                3
+            )
+        );
+        // Policies which are trivially true or false are folded away
+        assert_eq!(
+            sql(r"
+                permit(principal, action, resource) when { resource.public };
+                permit(principal, action, resource) when { false };
+                forbid(principal, action, resource) when { !resource.visible };
+            "),
+            (
+                r#"("public" = ? AND NOT (NOT ("visible" = ?)))"#.to_string(),
+                2
+            )
+        );
+        assert_eq!(
+            sql(r"permit(principal, action, resource) when { false };"),
+            ("FALSE".to_string(), 0)
+        );
+        assert_eq!(sql(""), ("FALSE".to_string(), 0));
+    }
+
+    #[test]
+    fn untranslatable() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { resource.size + 1 < 3 && resource.public };
+            permit(principal, action, resource) when { resource.tags.contains("x") };
+            forbid(principal == User::"mallory", action, resource);
+        "#,
+        )
+        .unwrap();
+        let translator = FilterTranslator::new(pst::Var::Resource);
+        let err = translator
+            .translate_residuals(policies.policies(), &mut SqlFilter::new("id"))
+            .unwrap_err();
+        let untranslatable: Vec<_> = err
+            .untranslatable()
+            .map(|e| (e.expr().to_string(), e.reason()))
+            .collect();
+        assert_eq!(
+            untranslatable,
+            vec![
+                (
+                    "((resource.size) + 1) < 3".to_string(),
+                    UntranslatableReason::Unrecognized
+                ),
+                (
+                    r#"(resource.tags).contains("x")"#.to_string(),
+                    UntranslatableReason::Unsupported
+                ),
+                (
+                    r#"principal == User::"mallory""#.to_string(),
+                    UntranslatableReason::Unrecognized
+                ),
+            ]
+        );
+
+        // With approximation, the untranslatable permit condition is `true`
+        // and the untranslatable forbid condition is `false`
+        let translation = translator
+            .with_approximation(true)
+            .translate_residuals(policies.policies(), &mut SqlFilter::new("id"))
+            .unwrap();
+        assert!(!translation.is_exact());
+        assert_eq!(translation.approximated().count(), 3);
+        assert_eq!(translation.filter().sql(), "TRUE");
+    }
+
+    /// A backend which only supports equality, producing MongoDB-style filters
+    struct MongoFilter;
+
+    impl FilterBackend for MongoFilter {
+        type Output = serde_json::Value;
+
+        fn constant(&mut self, value: bool) -> serde_json::Value {
+            if value {
+                serde_json::json!({})
+            } else {
+                serde_json::json!({ "_id": { "$exists": false } })
+            }
+        }
+
+        fn and(&mut self, operands: Vec<serde_json::Value>) -> serde_json::Value {
+            serde_json::json!({ "$and": operands })
+        }
+
+        fn or(&mut self, operands: Vec<serde_json::Value>) -> serde_json::Value {
+            serde_json::json!({ "$or": operands })
+        }
+
+        fn not(&mut self, operand: serde_json::Value) -> serde_json::Value {
+            serde_json::json!({ "$nor": [operand] })
+        }
+
+        fn compare(
+            &mut self,
+            path: &[SmolStr],
+            op: CompareOp,
+            value: &pst::Literal,
+        ) -> Option<serde_json::Value> {
+            let value = match value {
+                pst::Literal::Bool(b) => serde_json::json!(b),
+                pst::Literal::Long(i) => serde_json::json!(i),
+                pst::Literal::String(s) => serde_json::json!(s),
+                _ => return None,
+            };
+            (op == CompareOp::Eq).then(|| serde_json::json!({ path.join("."): value }))
+        }
+    }
+
+    #[test]
+    fn custom_backend() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { resource.owner.name == "alice" || resource.public };
+            forbid(principal, action, resource) when { resource.size > 10 };
+        "#,
+        )
+        .unwrap();
+        let translator = FilterTranslator::new(pst::Var::Resource);
+        assert_matches!(
+            translator.translate_residuals(policies.policies(), &mut MongoFilter),
+            Err(err) => {
+                assert_eq!(err.untranslatable().next().unwrap().reason(), UntranslatableReason::Unsupported);
+            }
+        );
+        let translation = translator
+            .translate_residuals(policies.policies().take(1), &mut MongoFilter)
+            .unwrap();
+        assert_eq!(
+            translation.into_filter(),
+            serde_json::json!({ "$or": [{ "owner.name": "alice" }, { "public": true }] })
+        );
+    }
+}
+
+mod request_space_tests {
+    use super::{
+        Context, Decision, Entities, EntityUid, EvalResult, PolicySet, RequestSpace,
+        RequestSpaceError, RestrictedExpression, Schema,
+    };
+    use cool_asserts::assert_matches;
+    use std::str::FromStr;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+            entity Role enum ["admin", "viewer"];
+            entity User { role: Role };
+            entity Doc;
+            action read appliesTo {
+                principal: User,
+                resource: Doc,
+                context: { internal: Bool, approver?: User },
+            };
+            action delete appliesTo {
+                principal: User,
+                resource: Doc,
+                context: { reason: String },
+            };
+        "#,
+        )
+        .unwrap()
+    }
+
+    fn entities(schema: &Schema) -> Entities {
+        Entities::from_json_str(
+            r#"[
+                {"uid": {"type": "User", "id": "alice"}, "attrs": {"role": {"__entity": {"type": "Role", "id": "admin"}}}, "parents": []},
+                {"uid": {"type": "User", "id": "bob"}, "attrs": {"role": {"__entity": {"type": "Role", "id": "viewer"}}}, "parents": []},
+                {"uid": {"type": "Doc", "id": "plan"}, "attrs": {}, "parents": []}
+            ]"#,
+            Some(schema),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn unbounded_context() {
+        let schema = schema();
+        let entities = entities(&schema);
+        let space = RequestSpace::new(&schema, &entities);
+        assert_matches!(
+            space.size(),
+            Err(RequestSpaceError::UnboundedContext(err)) => {
+                assert_eq!(err.action(), &EntityUid::from_strs("Action", "delete"));
+                assert_eq!(err.path().collect::<Vec<_>>(), ["reason"]);
+                assert_eq!(err.ty().to_string(), "String");
+            }
+        );
+
+        let invalid =
+            Context::from_pairs([("reason".into(), RestrictedExpression::new_long(1))]).unwrap();
+        let space = space.with_contexts(EntityUid::from_strs("Action", "delete"), [invalid]);
+        assert_matches!(space.size(), Err(RequestSpaceError::InvalidContext(_)));
+    }
+
+    #[test]
+    fn decision_table() {
+        let schema = schema();
+        let entities = entities(&schema);
+        let reasons = ["cleanup", "mistake"].map(|reason| {
+            Context::from_pairs([(
+                "reason".into(),
+                RestrictedExpression::new_string(reason.into()),
+            )])
+            .unwrap()
+        });
+        let space = RequestSpace::new(&schema, &entities)
+            .with_contexts(EntityUid::from_strs("Action", "delete"), reasons);
+        // `read` has 2 principals, 1 resource and 2 * (1 + 2) contexts, and
+        // `delete` has 2 principals, 1 resource and 2 contexts
+        assert_eq!(space.size().unwrap(), 16);
+
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"read", resource)
+            when { context.internal || context has approver };
+            permit(principal, action == Action::"delete", resource)
+            when { principal.role == Role::"admin" && context.reason == "cleanup" };
+            forbid(principal, action, resource)
+            when { context has approver && context.approver == principal };
+        "#,
+        )
+        .unwrap();
+        let table = space.decision_table(&policies).unwrap();
+        assert_eq!(table.len(), 16);
+        assert_eq!(table.rows_with_errors().count(), 0);
+        // For `read`, per principal: 1 context is internal without an
+        // approver, and 2 have an approver other than the principal. For
+        // `delete`: alice with the reason "cleanup".
+        let allowed: Vec<_> = table.requests_with_decision(Decision::Allow).collect();
+        assert_eq!(allowed.len(), 2 * (1 + 2) + 1);
+        assert!(allowed.iter().all(|request| {
+            request
+                .context()
+                .unwrap()
+                .get("approver")
+                .is_none_or(|approver| {
+                    approver != EvalResult::EntityUid(request.principal().unwrap().clone())
+                })
+        }));
+        assert_eq!(
+            table.requests_with_decision(Decision::Deny).count(),
+            16 - allowed.len()
+        );
     }
 }
 
@@ -11150,473 +13670,818 @@ mod policy_set_pst_tests {
         pset.remove_static(PolicyId::new("id")).unwrap();
         assert!(pset.is_empty());
 
-        let response = authorizer.is_authorized(&request, &pset, &entities);
-        assert_eq!(response.decision(), Decision::Deny);
+        let response = authorizer.is_authorized(&request, &pset, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+
+        let t = Template::from_pst(principal_slot_template("t")).unwrap();
+        pset.add_template(t).unwrap();
+        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+        pset.link(PolicyId::new("t"), PolicyId::new("linked"), env)
+            .unwrap();
+
+        let response = authorizer.is_authorized(&request, &pset, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+
+        assert_matches!(
+            pset.remove_static(PolicyId::new("t")),
+            Err(PolicySetError::PolicyNonexistent(_))
+        );
+
+        pset.unlink(PolicyId::new("linked")).unwrap();
+        assert_matches!(
+            pset.remove_static(PolicyId::new("t")),
+            Err(PolicySetError::PolicyNonexistent(_))
+        );
+
+        let response = authorizer.is_authorized(&request, &pset, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+
+        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+        pset.link(PolicyId::new("t"), PolicyId::new("linked"), env)
+            .unwrap();
+
+        let response = authorizer.is_authorized(&request, &pset, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+
+        assert_matches!(
+            pset.remove_template(PolicyId::new("t")),
+            Err(PolicySetError::RemoveTemplateWithActiveLinks(_))
+        );
+
+        pset.unlink(PolicyId::new("linked")).unwrap();
+        pset.remove_template(PolicyId::new("t")).unwrap();
+        assert!(pset.is_empty());
+
+        let response = authorizer.is_authorized(&request, &pset, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+    }
+
+    #[test]
+    fn pset_removal_prop_test_1() {
+        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
+        let mut pset = PolicySet::new();
+        pset.add_template(t).unwrap();
+        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+        pset.link(PolicyId::new("policy0"), PolicyId::new("policy3"), env)
+            .unwrap();
+
+        let t2 = Template::from_pst(principal_slot_template("policy3")).unwrap();
+        assert_matches!(
+            pset.add_template(t2),
+            Err(PolicySetError::AlreadyDefined(_))
+        );
+        assert_matches!(
+            pset.remove_static(PolicyId::new("policy3")),
+            Err(PolicySetError::PolicyNonexistent(_))
+        );
+        assert_matches!(
+            pset.remove_template(PolicyId::new("policy3")),
+            Err(PolicySetError::TemplateNonexistent(_))
+        );
+    }
+
+    #[test]
+    fn pset_requests() {
+        let t = Template::from_pst(principal_slot_template("template")).unwrap();
+        let sp = Policy::from_pst(permit_all("static").into()).unwrap();
+        let pid_static = PolicyId::new("static");
+        let pid_linked = PolicyId::new("linked");
+        let pid_linked2 = PolicyId::new("linked2");
+        let id_template = PolicyId::new("template");
+        // Policy set: 1 template, 1 static policy, two links
+        let mut pset = PolicySet::new();
+        pset.add_template(t).unwrap();
+        pset.add(sp).unwrap();
+        pset.link(
+            id_template.clone(),
+            pid_linked.clone(),
+            HashMap::from([(
+                SlotId::principal(),
+                EntityUid::from_strs("Concierge", "test"),
+            )]),
+        )
+        .unwrap();
+        pset.link(
+            id_template.clone(),
+            pid_linked2.clone(),
+            HashMap::from([(
+                SlotId::principal(),
+                EntityUid::from_strs("Concierge", "test2"),
+            )]),
+        )
+        .unwrap();
+
+        assert_eq!(pset.num_of_templates(), 1);
+        assert_eq!(pset.num_of_policies(), 3);
+        assert_eq!(pset.policies().filter(|p| p.is_static()).count(), 1);
+        assert_eq!(
+            pset.template(&id_template).unwrap().id(),
+            &"template".parse().unwrap()
+        );
+        for id in [&pid_static, &pid_linked, &pid_linked2] {
+            assert_eq!(pset.policy(id).unwrap().id(), id);
+        }
+    }
+
+    #[test]
+    fn link_static_policy_errors_expected_template() {
+        let mut pset = PolicySet::new();
+        pset.add(Policy::from_pst(permit_all("static").into()).unwrap())
+            .unwrap();
+
+        let before_link = pset.clone();
+        let result = pset.link(
+            PolicyId::new("static"),
+            PolicyId::new("linked"),
+            HashMap::new(),
+        );
+        assert_matches!(result, Err(PolicySetError::ExpectedTemplate(_)));
+        assert_eq!(pset, before_link);
+    }
+
+    #[test]
+    fn link_linked_policy_errors_expected_template() {
+        let t = Template::from_pst(principal_slot_template("template")).unwrap();
+        let mut pset = PolicySet::new();
+        let id_linked = PolicyId::new("linked");
+        let id_linked2 = PolicyId::new("linked2");
+        let id_template = PolicyId::new("template");
+        pset.add_template(t).unwrap();
+        pset.link(
+            id_template,
+            id_linked.clone(),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        )
+        .unwrap();
+
+        let before_link = pset.clone();
+        let result = pset.link(id_linked, id_linked2, HashMap::new());
+        assert_matches!(result, Err(PolicySetError::ExpectedTemplate(_)));
+        assert_eq!(pset, before_link);
+    }
+
+    #[test]
+    fn unlink_linked_policy() {
+        let t = Template::from_pst(principal_slot_template("template")).unwrap();
+        let mut pset = PolicySet::new();
+        pset.add_template(t).unwrap();
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        )
+        .unwrap();
+
+        let authorizer = Authorizer::new();
+        let request = test_request();
+        let entities = entities_for_test();
+
+        assert_eq!(
+            authorizer
+                .is_authorized(&request, &pset, &entities)
+                .decision(),
+            Decision::Allow
+        );
+
+        pset.unlink(PolicyId::new("linked")).unwrap();
+        assert_eq!(
+            authorizer
+                .is_authorized(&request, &pset, &entities)
+                .decision(),
+            Decision::Deny
+        );
+
+        assert_matches!(
+            pset.unlink(PolicyId::new("linked")),
+            Err(PolicySetError::LinkNonexistent(_))
+        );
+    }
+
+    #[test]
+    fn get_linked_policy() {
+        let t = Template::from_pst(principal_slot_template("template")).unwrap();
+        let mut pset = PolicySet::new();
+        pset.add_template(t).unwrap();
+
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        )
+        .unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            1
+        );
+
+        pset.unlink(PolicyId::new("linked")).unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            0
+        );
+        assert_matches!(
+            pset.unlink(PolicyId::new("linked")),
+            Err(PolicySetError::LinkNonexistent(_))
+        );
+
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("linked"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        )
+        .unwrap();
+        pset.link(
+            PolicyId::new("template"),
+            PolicyId::new("linked2"),
+            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        )
+        .unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            2
+        );
+
+        // Can't re-add template
+        let t2 = Template::from_pst(principal_slot_template("template")).unwrap();
+        assert_matches!(
+            pset.add_template(t2),
+            Err(PolicySetError::AlreadyDefined(_))
+        );
+
+        // Add another template
+        let t3 = Template::from_pst(principal_slot_template("template2")).unwrap();
+        pset.add_template(t3).unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template2"))
+                .unwrap()
+                .count(),
+            0
+        );
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            2
+        );
 
-        let t = Template::from_pst(principal_slot_template("t")).unwrap();
-        pset.add_template(t).unwrap();
-        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
-        pset.link(PolicyId::new("t"), PolicyId::new("linked"), env)
-            .unwrap();
+        // Can't remove template with active links
+        assert_matches!(
+            pset.remove_template(PolicyId::new("template")),
+            Err(PolicySetError::RemoveTemplateWithActiveLinks(_))
+        );
 
-        let response = authorizer.is_authorized(&request, &pset, &entities);
-        assert_eq!(response.decision(), Decision::Allow);
+        // Can't add policy named "template" or "linked"
+        let p = Policy::from_pst(permit_all("template").into()).unwrap();
+        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
+        let p = Policy::from_pst(permit_all("linked").into()).unwrap();
+        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
+
+        // Can add and remove "policy"
+        let p = Policy::from_pst(permit_all("policy").into()).unwrap();
+        pset.add(p).unwrap();
+        pset.remove_static(PolicyId::new("policy")).unwrap();
 
+        // Cannot remove linked or template as static
         assert_matches!(
-            pset.remove_static(PolicyId::new("t")),
+            pset.remove_static(PolicyId::new("linked")),
             Err(PolicySetError::PolicyNonexistent(_))
         );
-
-        pset.unlink(PolicyId::new("linked")).unwrap();
         assert_matches!(
-            pset.remove_static(PolicyId::new("t")),
+            pset.remove_static(PolicyId::new("template")),
             Err(PolicySetError::PolicyNonexistent(_))
         );
 
-        let response = authorizer.is_authorized(&request, &pset, &entities);
-        assert_eq!(response.decision(), Decision::Deny);
-
-        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
-        pset.link(PolicyId::new("t"), PolicyId::new("linked"), env)
-            .unwrap();
-
-        let response = authorizer.is_authorized(&request, &pset, &entities);
-        assert_eq!(response.decision(), Decision::Allow);
-
+        // Unlink, remove
+        pset.unlink(PolicyId::new("linked")).unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            1
+        );
+        pset.remove_template(PolicyId::new("template2")).unwrap();
         assert_matches!(
-            pset.remove_template(PolicyId::new("t")),
+            pset.remove_template(PolicyId::new("template")),
             Err(PolicySetError::RemoveTemplateWithActiveLinks(_))
         );
+        pset.unlink(PolicyId::new("linked2")).unwrap();
+        assert_eq!(
+            pset.get_linked_policies(PolicyId::new("template"))
+                .unwrap()
+                .count(),
+            0
+        );
+        pset.remove_template(PolicyId::new("template")).unwrap();
+        assert!(pset.get_linked_policies(PolicyId::new("template")).is_err());
+    }
 
-        pset.unlink(PolicyId::new("linked")).unwrap();
-        pset.remove_template(PolicyId::new("t")).unwrap();
-        assert!(pset.is_empty());
+    #[test]
+    fn pset_add_conflict() {
+        let mut pset = PolicySet::new();
+        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
+        pset.add_template(t).unwrap();
+        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+        pset.link(PolicyId::new("policy0"), PolicyId::new("policy1"), env)
+            .unwrap();
 
-        let response = authorizer.is_authorized(&request, &pset, &entities);
-        assert_eq!(response.decision(), Decision::Deny);
+        // template id; static
+        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
+        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
+        // link id; static
+        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
+        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
+        // static; static
+        let p = Policy::from_pst(permit_all("policy2").into()).unwrap();
+        pset.add(p.clone()).unwrap();
+        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
     }
 
     #[test]
-    fn pset_removal_prop_test_1() {
-        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
+    fn pset_add_template_conflict() {
         let mut pset = PolicySet::new();
+        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
         pset.add_template(t).unwrap();
         let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
         pset.link(PolicyId::new("policy0"), PolicyId::new("policy3"), env)
             .unwrap();
 
-        let t2 = Template::from_pst(principal_slot_template("policy3")).unwrap();
+        // link; template
+        let t = Template::from_pst(principal_slot_template("policy3")).unwrap();
+        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
+        // template; template
+        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
+        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
+        // static; template
+        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
+        pset.add(p).unwrap();
+        let t = Template::from_pst(principal_slot_template("policy1")).unwrap();
+        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
+    }
+
+    #[test]
+    fn pset_link_conflict() {
+        let mut pset = PolicySet::new();
+        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
+        pset.add_template(t).unwrap();
+        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+
+        // link; link
+        pset.link(
+            PolicyId::new("policy0"),
+            PolicyId::new("policy3"),
+            env.clone(),
+        )
+        .unwrap();
         assert_matches!(
-            pset.add_template(t2),
-            Err(PolicySetError::AlreadyDefined(_))
+            pset.link(
+                PolicyId::new("policy0"),
+                PolicyId::new("policy3"),
+                env.clone()
+            ),
+            Err(PolicySetError::Linking(_))
         );
+        // template; link
         assert_matches!(
-            pset.remove_static(PolicyId::new("policy3")),
-            Err(PolicySetError::PolicyNonexistent(_))
+            pset.link(
+                PolicyId::new("policy0"),
+                PolicyId::new("policy0"),
+                env.clone()
+            ),
+            Err(PolicySetError::Linking(_))
         );
+        // static; link
+        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
+        pset.add(p).unwrap();
         assert_matches!(
-            pset.remove_template(PolicyId::new("policy3")),
-            Err(PolicySetError::TemplateNonexistent(_))
+            pset.link(PolicyId::new("policy0"), PolicyId::new("policy1"), env),
+            Err(PolicySetError::Linking(_))
         );
     }
 
+    // --- Merge tests ---
+
     #[test]
-    fn pset_requests() {
-        let t = Template::from_pst(principal_slot_template("template")).unwrap();
-        let sp = Policy::from_pst(permit_all("static").into()).unwrap();
-        let pid_static = PolicyId::new("static");
-        let pid_linked = PolicyId::new("linked");
-        let pid_linked2 = PolicyId::new("linked2");
-        let id_template = PolicyId::new("template");
-        // Policy set: 1 template, 1 static policy, two links
-        let mut pset = PolicySet::new();
-        pset.add_template(t).unwrap();
-        pset.add(sp).unwrap();
-        pset.link(
-            id_template.clone(),
-            pid_linked.clone(),
-            HashMap::from([(
-                SlotId::principal(),
-                EntityUid::from_strs("Concierge", "test"),
-            )]),
-        )
-        .unwrap();
-        pset.link(
-            id_template.clone(),
-            pid_linked2.clone(),
-            HashMap::from([(
-                SlotId::principal(),
-                EntityUid::from_strs("Concierge", "test2"),
-            )]),
-        )
-        .unwrap();
+    fn merge_empty_into_empty() {
+        let empty = || pst::PolicySet {
+            templates: LinkedHashMap::new(),
+            policies: LinkedHashMap::new(),
+            template_links: vec![],
+        };
+        let mut ps0 = PolicySet::from_pst(empty()).unwrap();
+        let ps1 = PolicySet::from_pst(empty()).unwrap();
+        let names = ps0.merge(&ps1, false).unwrap();
+        assert_eq!(names, HashMap::new());
+        assert!(ps0.is_empty());
+    }
 
-        assert_eq!(pset.num_of_templates(), 1);
-        assert_eq!(pset.num_of_policies(), 3);
-        assert_eq!(pset.policies().filter(|p| p.is_static()).count(), 1);
-        assert_eq!(
-            pset.template(&id_template).unwrap().id(),
-            &"template".parse().unwrap()
-        );
-        for id in [&pid_static, &pid_linked, &pid_linked2] {
-            assert_eq!(pset.policy(id).unwrap().id(), id);
-        }
+    #[test]
+    fn merge_policy_into_empty() {
+        let empty = pst::PolicySet {
+            templates: LinkedHashMap::new(),
+            policies: LinkedHashMap::new(),
+            template_links: vec![],
+        };
+        let mut ps0 = PolicySet::from_pst(empty).unwrap();
+        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
+        let ps1 = PolicySet::from_policies([p.clone()]).unwrap();
+        let names = ps0.merge(&ps1, false).unwrap();
+        assert_eq!(names, HashMap::new());
+        assert!(!ps0.is_empty());
+        assert_eq!(ps0, ps1);
     }
 
     #[test]
-    fn link_static_policy_errors_expected_template() {
-        let mut pset = PolicySet::new();
-        pset.add(Policy::from_pst(permit_all("static").into()).unwrap())
-            .unwrap();
+    fn merge_empty_into_policy() {
+        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
+        let mut ps0 = PolicySet::from_policies([p]).unwrap();
+        let ps0_copy = ps0.clone();
+        let ps1 = PolicySet::new();
+        let names = ps0.merge(&ps1, false).unwrap();
+        assert_eq!(names, HashMap::new());
+        assert_eq!(ps0, ps0_copy);
+    }
 
-        let before_link = pset.clone();
-        let result = pset.link(
-            PolicyId::new("static"),
-            PolicyId::new("linked"),
-            HashMap::new(),
+    #[test]
+    fn merge_policies_disjoint() {
+        let pid0 = PolicyId::new("0");
+        let pid1 = PolicyId::new("1");
+        let p0 = Policy::from_pst(permit_all(pid0).into()).unwrap();
+        let forbid_tmpl = pst::Template::new(
+            pid1,
+            pst::Effect::Forbid,
+            pst::PrincipalConstraint::Any,
+            pst::ActionConstraint::Any,
+            pst::ResourceConstraint::Any,
         );
-        assert_matches!(result, Err(PolicySetError::ExpectedTemplate(_)));
-        assert_eq!(pset, before_link);
+        let p1 =
+            Policy::from_pst(pst::StaticPolicy::try_from(forbid_tmpl).unwrap().into()).unwrap();
+
+        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
+        let ps1 = PolicySet::from_policies([p1.clone()]).unwrap();
+        let names = ps0.merge(&ps1, false).unwrap();
+        assert_eq!(names, HashMap::new());
+        let expected = PolicySet::from_policies([p0, p1]).unwrap();
+        assert_eq!(ps0, expected);
     }
 
     #[test]
-    fn link_linked_policy_errors_expected_template() {
-        let t = Template::from_pst(principal_slot_template("template")).unwrap();
-        let mut pset = PolicySet::new();
-        let id_linked = PolicyId::new("linked");
-        let id_linked2 = PolicyId::new("linked2");
-        let id_template = PolicyId::new("template");
-        pset.add_template(t).unwrap();
-        pset.link(
-            id_template,
-            id_linked.clone(),
-            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+    fn merge_policies_collision_error() {
+        let colliding_pid = PolicyId::new("0");
+        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
+        let p1 = Policy::from_pst(
+            pst::StaticPolicy::try_from(pst::Template::new(
+                colliding_pid.clone(),
+                pst::Effect::Forbid,
+                pst::PrincipalConstraint::Any,
+                pst::ActionConstraint::Any,
+                pst::ResourceConstraint::Any,
+            ))
+            .unwrap()
+            .into(),
         )
         .unwrap();
 
-        let before_link = pset.clone();
-        let result = pset.link(id_linked, id_linked2, HashMap::new());
-        assert_matches!(result, Err(PolicySetError::ExpectedTemplate(_)));
-        assert_eq!(pset, before_link);
+        let mut ps0 = PolicySet::from_policies([p0]).unwrap();
+        let ps1 = PolicySet::from_policies([p1]).unwrap();
+        assert_matches!(
+            ps0.merge(&ps1, false),
+            Err(PolicySetError::AlreadyDefined(e)) => {
+                assert_eq!(e.duplicate_id(), &colliding_pid)
+            }
+        );
     }
 
     #[test]
-    fn unlink_linked_policy() {
-        let t = Template::from_pst(principal_slot_template("template")).unwrap();
-        let mut pset = PolicySet::new();
-        pset.add_template(t).unwrap();
-        pset.link(
-            PolicyId::new("template"),
-            PolicyId::new("linked"),
-            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+    fn merge_policies_collision_rename() {
+        let colliding_pid = PolicyId::new("0");
+        let name_after_merge = PolicyId::new("policy0");
+        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
+        let p1 = Policy::from_pst(
+            pst::StaticPolicy::try_from(pst::Template::new(
+                colliding_pid.clone(),
+                pst::Effect::Forbid,
+                pst::PrincipalConstraint::Any,
+                pst::ActionConstraint::Any,
+                pst::ResourceConstraint::Any,
+            ))
+            .unwrap()
+            .into(),
         )
         .unwrap();
 
-        let authorizer = Authorizer::new();
-        let request = test_request();
-        let entities = entities_for_test();
-
-        assert_eq!(
-            authorizer
-                .is_authorized(&request, &pset, &entities)
-                .decision(),
-            Decision::Allow
-        );
-
-        pset.unlink(PolicyId::new("linked")).unwrap();
+        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
+        let ps1 = PolicySet::from_policies([p1.clone()]).unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(
-            authorizer
-                .is_authorized(&request, &pset, &entities)
-                .decision(),
-            Decision::Deny
+            names,
+            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
         );
+        assert_eq!(ps0.policy(&colliding_pid), Some(&p0));
+        let p1_rename = p1.new_id(name_after_merge.clone());
+        assert_eq!(ps0.policy(&name_after_merge), Some(&p1_rename));
+    }
 
-        assert_matches!(
-            pset.unlink(PolicyId::new("linked")),
-            Err(PolicySetError::LinkNonexistent(_))
-        );
+    #[test]
+    fn merge_policies_collision_eq_policies() {
+        let colliding_pid = PolicyId::new("0");
+        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
+        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
+        let ps1 = PolicySet::from_policies([p0.clone()]).unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(names, HashMap::new());
+        let expected = PolicySet::from_policies([p0]).unwrap();
+        assert_eq!(ps0, expected);
     }
 
     #[test]
-    fn get_linked_policy() {
-        let t = Template::from_pst(principal_slot_template("template")).unwrap();
-        let mut pset = PolicySet::new();
-        pset.add_template(t).unwrap();
+    fn merge_policies_templates_no_collision() {
+        let template0_pid = PolicyId::new("0");
+        let template1_pid = PolicyId::new("2");
+        let link_pid = PolicyId::new("1");
+        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
+        let t1 = Template::from_pst(resource_slot_template(template1_pid.clone())).unwrap();
 
-        pset.link(
-            PolicyId::new("template"),
-            PolicyId::new("linked"),
-            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        ps1.link(
+            template1_pid,
+            link_pid.clone(),
+            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
         )
         .unwrap();
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            1
-        );
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(names, HashMap::new());
 
-        pset.unlink(PolicyId::new("linked")).unwrap();
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            0
-        );
-        assert_matches!(
-            pset.unlink(PolicyId::new("linked")),
-            Err(PolicySetError::LinkNonexistent(_))
-        );
+        let original_linked = ps1.policy(&link_pid).unwrap();
+        let merged_linked = ps0.policy(&link_pid).unwrap();
+        assert_eq!(original_linked, merged_linked);
+    }
 
-        pset.link(
-            PolicyId::new("template"),
-            PolicyId::new("linked"),
-            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
-        )
-        .unwrap();
-        pset.link(
-            PolicyId::new("template"),
-            PolicyId::new("linked2"),
-            HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]),
+    #[test]
+    fn merge_policies_templates_collision() {
+        let colliding_pid = PolicyId::new("0");
+        let link_pid = PolicyId::new("1");
+        let name_after_merge = PolicyId::new("policy0");
+        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+        let t1 = Template::from_pst(resource_slot_template(colliding_pid.clone())).unwrap();
+
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        ps1.link(
+            colliding_pid.clone(),
+            link_pid.clone(),
+            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
         )
         .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            2
-        );
-
-        // Can't re-add template
-        let t2 = Template::from_pst(principal_slot_template("template")).unwrap();
-        assert_matches!(
-            pset.add_template(t2),
-            Err(PolicySetError::AlreadyDefined(_))
-        );
-
-        // Add another template
-        let t3 = Template::from_pst(principal_slot_template("template2")).unwrap();
-        pset.add_template(t3).unwrap();
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template2"))
-                .unwrap()
-                .count(),
-            0
-        );
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            2
-        );
-
-        // Can't remove template with active links
-        assert_matches!(
-            pset.remove_template(PolicyId::new("template")),
-            Err(PolicySetError::RemoveTemplateWithActiveLinks(_))
-        );
-
-        // Can't add policy named "template" or "linked"
-        let p = Policy::from_pst(permit_all("template").into()).unwrap();
-        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
-        let p = Policy::from_pst(permit_all("linked").into()).unwrap();
-        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
-
-        // Can add and remove "policy"
-        let p = Policy::from_pst(permit_all("policy").into()).unwrap();
-        pset.add(p).unwrap();
-        pset.remove_static(PolicyId::new("policy")).unwrap();
-
-        // Cannot remove linked or template as static
-        assert_matches!(
-            pset.remove_static(PolicyId::new("linked")),
-            Err(PolicySetError::PolicyNonexistent(_))
-        );
-        assert_matches!(
-            pset.remove_static(PolicyId::new("template")),
-            Err(PolicySetError::PolicyNonexistent(_))
+            names,
+            HashMap::from([(colliding_pid, name_after_merge.clone())])
         );
 
-        // Unlink, remove
-        pset.unlink(PolicyId::new("linked")).unwrap();
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            1
-        );
-        pset.remove_template(PolicyId::new("template2")).unwrap();
-        assert_matches!(
-            pset.remove_template(PolicyId::new("template")),
-            Err(PolicySetError::RemoveTemplateWithActiveLinks(_))
-        );
-        pset.unlink(PolicyId::new("linked2")).unwrap();
-        assert_eq!(
-            pset.get_linked_policies(PolicyId::new("template"))
-                .unwrap()
-                .count(),
-            0
-        );
-        pset.remove_template(PolicyId::new("template")).unwrap();
-        assert!(pset.get_linked_policies(PolicyId::new("template")).is_err());
+        let merged_link = ps0.policy(&link_pid).unwrap();
+        assert_eq!(merged_link.template_id(), Some(&name_after_merge));
     }
 
     #[test]
-    fn pset_add_conflict() {
-        let mut pset = PolicySet::new();
-        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
-        pset.add_template(t).unwrap();
-        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
-        pset.link(PolicyId::new("policy0"), PolicyId::new("policy1"), env)
-            .unwrap();
+    fn merge_policies_policy_template_collision() {
+        let colliding_pid = PolicyId::new("0");
+        let name_after_merge = PolicyId::new("policy0");
+        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
+        let t1 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
 
-        // template id; static
-        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
-        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
-        // link id; static
-        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
-        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
-        // static; static
-        let p = Policy::from_pst(permit_all("policy2").into()).unwrap();
-        pset.add(p.clone()).unwrap();
-        assert_matches!(pset.add(p), Err(PolicySetError::AlreadyDefined(_)));
+        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
+        );
+        assert_eq!(ps0.policy(&colliding_pid), Some(&p0));
+        assert!(ps0.template(&name_after_merge).is_some());
     }
 
     #[test]
-    fn pset_add_template_conflict() {
-        let mut pset = PolicySet::new();
-        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
-        pset.add_template(t).unwrap();
-        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
-        pset.link(PolicyId::new("policy0"), PolicyId::new("policy3"), env)
-            .unwrap();
+    fn merge_policies_link_collision() {
+        let template0_pid = PolicyId::new("0");
+        let template1_pid = PolicyId::new("1");
+        let colliding_link_pid = PolicyId::new("2");
+        let name_after_merge = PolicyId::new("policy0");
+        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
+        let t1 = Template::from_pst(resource_slot_template(template1_pid.clone())).unwrap();
 
-        // link; template
-        let t = Template::from_pst(principal_slot_template("policy3")).unwrap();
-        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
-        // template; template
-        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
-        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
-        // static; template
-        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
-        pset.add(p).unwrap();
-        let t = Template::from_pst(principal_slot_template("policy1")).unwrap();
-        assert_matches!(pset.add_template(t), Err(PolicySetError::AlreadyDefined(_)));
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        ps0.link(
+            template0_pid,
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        ps1.link(
+            template1_pid.clone(),
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([(colliding_link_pid, name_after_merge.clone())])
+        );
+
+        let merged_linked = ps0.policy(&name_after_merge).unwrap();
+        assert_eq!(merged_linked.template_id(), Some(&template1_pid));
     }
 
     #[test]
-    fn pset_link_conflict() {
-        let mut pset = PolicySet::new();
-        let t = Template::from_pst(principal_slot_template("policy0")).unwrap();
-        pset.add_template(t).unwrap();
-        let env = HashMap::from([(SlotId::principal(), EntityUid::from_strs("Test", "test"))]);
+    fn merge_policies_template_and_link_collision() {
+        let colliding_template_pid = PolicyId::new("0");
+        let colliding_link_pid = PolicyId::new("2");
+        let template_name_after_merge = PolicyId::new("policy0");
+        let link_name_after_merge = PolicyId::new("policy1");
+        let t0 =
+            Template::from_pst(principal_slot_template(colliding_template_pid.clone())).unwrap();
+        let t1 = Template::from_pst(pst::Template::new(
+            colliding_template_pid.clone(),
+            pst::Effect::Forbid,
+            pst::PrincipalConstraint::Eq(pst::EntityOrSlot::Slot(pst::SlotId::Principal)),
+            pst::ActionConstraint::Any,
+            pst::ResourceConstraint::Any,
+        ))
+        .unwrap();
 
-        // link; link
-        pset.link(
-            PolicyId::new("policy0"),
-            PolicyId::new("policy3"),
-            env.clone(),
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        ps0.link(
+            colliding_template_pid.clone(),
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
         )
         .unwrap();
-        assert_matches!(
-            pset.link(
-                PolicyId::new("policy0"),
-                PolicyId::new("policy3"),
-                env.clone()
-            ),
-            Err(PolicySetError::Linking(_))
-        );
-        // template; link
-        assert_matches!(
-            pset.link(
-                PolicyId::new("policy0"),
-                PolicyId::new("policy0"),
-                env.clone()
-            ),
-            Err(PolicySetError::Linking(_))
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        ps1.link(
+            colliding_template_pid.clone(),
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([
+                (colliding_template_pid, template_name_after_merge.clone()),
+                (colliding_link_pid, link_name_after_merge.clone()),
+            ])
         );
-        // static; link
-        let p = Policy::from_pst(permit_all("policy1").into()).unwrap();
-        pset.add(p).unwrap();
-        assert_matches!(
-            pset.link(PolicyId::new("policy0"), PolicyId::new("policy1"), env),
-            Err(PolicySetError::Linking(_))
+
+        let merged_linked = ps0.policy(&link_name_after_merge).unwrap();
+        assert_eq!(
+            merged_linked.template_id(),
+            Some(&template_name_after_merge)
         );
     }
 
-    // --- Merge tests ---
-
     #[test]
-    fn merge_empty_into_empty() {
-        let empty = || pst::PolicySet {
-            templates: LinkedHashMap::new(),
-            policies: LinkedHashMap::new(),
-            template_links: vec![],
-        };
-        let mut ps0 = PolicySet::from_pst(empty()).unwrap();
-        let ps1 = PolicySet::from_pst(empty()).unwrap();
-        let names = ps0.merge(&ps1, false).unwrap();
+    fn merge_policies_eq_templates_different_links() {
+        let shared_template_pid = PolicyId::new("0");
+        let link0_pid = PolicyId::new("1");
+        let link1_pid = PolicyId::new("2");
+        let t0 = Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
+        let t0_dup =
+            Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
+
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        ps0.link(
+            shared_template_pid.clone(),
+            link0_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t0_dup).unwrap();
+        ps1.link(
+            shared_template_pid,
+            link1_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"alice""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(names, HashMap::new());
-        assert!(ps0.is_empty());
+        assert!(ps0.policy(&link0_pid).is_some());
+        assert!(ps0.policy(&link1_pid).is_some());
     }
 
     #[test]
-    fn merge_policy_into_empty() {
-        let empty = pst::PolicySet {
-            templates: LinkedHashMap::new(),
-            policies: LinkedHashMap::new(),
-            template_links: vec![],
-        };
-        let mut ps0 = PolicySet::from_pst(empty).unwrap();
-        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
-        let ps1 = PolicySet::from_policies([p.clone()]).unwrap();
-        let names = ps0.merge(&ps1, false).unwrap();
-        assert_eq!(names, HashMap::new());
-        assert!(!ps0.is_empty());
-        assert_eq!(ps0, ps1);
+    fn merge_policies_eq_templates_link_collision() {
+        let shared_template_pid = PolicyId::new("0");
+        let colliding_link_pid = PolicyId::new("1");
+        let name_after_merge = PolicyId::new("policy0");
+        let t0 = Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
+        let t0_dup =
+            Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
+
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        ps0.link(
+            shared_template_pid.clone(),
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t0_dup).unwrap();
+        ps1.link(
+            shared_template_pid,
+            colliding_link_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"alice""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([(colliding_link_pid, name_after_merge)])
+        );
+    }
+
+    #[test]
+    fn merge_policies_template_collides_with_link() {
+        let template0_pid = PolicyId::new("0");
+        let colliding_pid = PolicyId::new("1");
+        let name_after_merge = PolicyId::new("policy0");
+        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
+        let t1 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        ps0.link(
+            template0_pid,
+            colliding_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([(colliding_pid, name_after_merge.clone())])
+        );
+        assert!(ps0.template(&name_after_merge).is_some());
     }
 
     #[test]
-    fn merge_empty_into_policy() {
-        let p = Policy::from_pst(permit_all("policy0").into()).unwrap();
-        let mut ps0 = PolicySet::from_policies([p]).unwrap();
-        let ps0_copy = ps0.clone();
-        let ps1 = PolicySet::new();
-        let names = ps0.merge(&ps1, false).unwrap();
-        assert_eq!(names, HashMap::new());
-        assert_eq!(ps0, ps0_copy);
-    }
+    fn merge_policies_link_collides_with_template() {
+        let original_pid = PolicyId::new("0");
+        let colliding_pid = PolicyId::new("1");
+        let name_after_merge = PolicyId::new("policy0");
+        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+        let t1 = Template::from_pst(principal_slot_template(original_pid.clone())).unwrap();
 
-    #[test]
-    fn merge_policies_disjoint() {
-        let pid0 = PolicyId::new("0");
-        let pid1 = PolicyId::new("1");
-        let p0 = Policy::from_pst(permit_all(pid0).into()).unwrap();
-        let forbid_tmpl = pst::Template::new(
-            pid1,
-            pst::Effect::Forbid,
-            pst::PrincipalConstraint::Any,
-            pst::ActionConstraint::Any,
-            pst::ResourceConstraint::Any,
+        let mut ps0 = PolicySet::new();
+        ps0.add_template(t0).unwrap();
+        let mut ps1 = PolicySet::new();
+        ps1.add_template(t1).unwrap();
+        ps1.link(
+            original_pid.clone(),
+            colliding_pid.clone(),
+            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        )
+        .unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(
+            names,
+            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
         );
-        let p1 =
-            Policy::from_pst(pst::StaticPolicy::try_from(forbid_tmpl).unwrap().into()).unwrap();
-
-        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
-        let ps1 = PolicySet::from_policies([p1.clone()]).unwrap();
-        let names = ps0.merge(&ps1, false).unwrap();
-        assert_eq!(names, HashMap::new());
-        let expected = PolicySet::from_policies([p0, p1]).unwrap();
-        assert_eq!(ps0, expected);
+        assert!(ps0.policy(&name_after_merge).is_some());
     }
 
+    /// The generated fresh name "policy0" is already taken by an existing
+    /// policy in ps0, so the renaming must skip to "policy1".
     #[test]
-    fn merge_policies_collision_error() {
+    fn merge_policies_fresh_id_skips_existing() {
         let colliding_pid = PolicyId::new("0");
-        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let p1 = Policy::from_pst(
+        let blocker_pid = PolicyId::new("policy0");
+        let expected_fresh_pid = PolicyId::new("policy1");
+        let p_colliding = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
+        let p_blocker = Policy::from_pst(
             pst::StaticPolicy::try_from(pst::Template::new(
-                colliding_pid.clone(),
+                blocker_pid.clone(),
                 pst::Effect::Forbid,
                 pst::PrincipalConstraint::Any,
                 pst::ActionConstraint::Any,
@@ -11626,23 +14491,7 @@ mod policy_set_pst_tests {
             .into(),
         )
         .unwrap();
-
-        let mut ps0 = PolicySet::from_policies([p0]).unwrap();
-        let ps1 = PolicySet::from_policies([p1]).unwrap();
-        assert_matches!(
-            ps0.merge(&ps1, false),
-            Err(PolicySetError::AlreadyDefined(e)) => {
-                assert_eq!(e.duplicate_id(), &colliding_pid)
-            }
-        );
-    }
-
-    #[test]
-    fn merge_policies_collision_rename() {
-        let colliding_pid = PolicyId::new("0");
-        let name_after_merge = PolicyId::new("policy0");
-        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let p1 = Policy::from_pst(
+        let p_other = Policy::from_pst(
             pst::StaticPolicy::try_from(pst::Template::new(
                 colliding_pid.clone(),
                 pst::Effect::Forbid,
@@ -11655,144 +14504,188 @@ mod policy_set_pst_tests {
         )
         .unwrap();
 
-        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
-        let ps1 = PolicySet::from_policies([p1.clone()]).unwrap();
+        let mut ps0 = PolicySet::from_policies([p_colliding.clone(), p_blocker.clone()]).unwrap();
+        let ps1 = PolicySet::from_policies([p_other]).unwrap();
         let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(
             names,
-            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
+            HashMap::from([(colliding_pid, expected_fresh_pid.clone())])
         );
-        assert_eq!(ps0.policy(&colliding_pid), Some(&p0));
-        let p1_rename = p1.new_id(name_after_merge.clone());
-        assert_eq!(ps0.policy(&name_after_merge), Some(&p1_rename));
-    }
-
-    #[test]
-    fn merge_policies_collision_eq_policies() {
-        let colliding_pid = PolicyId::new("0");
-        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
-        let ps1 = PolicySet::from_policies([p0.clone()]).unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(names, HashMap::new());
-        let expected = PolicySet::from_policies([p0]).unwrap();
-        assert_eq!(ps0, expected);
+        assert!(ps0.policy(&expected_fresh_pid).is_some());
+        assert!(ps0.policy(&blocker_pid).is_some());
     }
 
+    /// Both sets have the same template and the same link (same slot values).
+    /// Everything should be deduped with no renames.
     #[test]
-    fn merge_policies_templates_no_collision() {
-        let template0_pid = PolicyId::new("0");
-        let template1_pid = PolicyId::new("2");
+    fn merge_policies_eq_templates_eq_links() {
+        let template_pid = PolicyId::new("0");
         let link_pid = PolicyId::new("1");
-        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
-        let t1 = Template::from_pst(resource_slot_template(template1_pid.clone())).unwrap();
+        let t0 = Template::from_pst(principal_slot_template(template_pid.clone())).unwrap();
+        let t0_dup = Template::from_pst(principal_slot_template(template_pid.clone())).unwrap();
+        let env = HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]);
 
         let mut ps0 = PolicySet::new();
         ps0.add_template(t0).unwrap();
+        ps0.link(template_pid.clone(), link_pid.clone(), env.clone())
+            .unwrap();
         let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        ps1.link(
-            template1_pid,
-            link_pid.clone(),
-            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
-        )
-        .unwrap();
+        ps1.add_template(t0_dup).unwrap();
+        ps1.link(template_pid, link_pid.clone(), env).unwrap();
+
         let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(names, HashMap::new());
+        assert!(ps0.policy(&link_pid).is_some());
+    }
 
-        let original_linked = ps1.policy(&link_pid).unwrap();
-        let merged_linked = ps0.policy(&link_pid).unwrap();
-        assert_eq!(original_linked, merged_linked);
+    /// Three collisions at once force the fresh-ID generator to produce
+    /// policy0, policy1, and policy2.
+    #[test]
+    fn merge_policies_triple_collision() {
+        let pid_a = PolicyId::new("a");
+        let pid_b = PolicyId::new("b");
+        let pid_c = PolicyId::new("c");
+        let p0a = Policy::from_pst(permit_all(pid_a.clone()).into()).unwrap();
+        let p0b = Policy::from_pst(permit_all(pid_b.clone()).into()).unwrap();
+        let p0c = Policy::from_pst(permit_all(pid_c.clone()).into()).unwrap();
+        // Build ps1 with the same IDs but different effects so they collide
+        let make_forbid = |id: PolicyId| {
+            Policy::from_pst(
+                pst::StaticPolicy::try_from(pst::Template::new(
+                    id,
+                    pst::Effect::Forbid,
+                    pst::PrincipalConstraint::Any,
+                    pst::ActionConstraint::Any,
+                    pst::ResourceConstraint::Any,
+                ))
+                .unwrap()
+                .into(),
+            )
+            .unwrap()
+        };
+        let p1a = make_forbid(pid_a.clone());
+        let p1b = make_forbid(pid_b.clone());
+        let p1c = make_forbid(pid_c.clone());
+
+        let mut ps0 = PolicySet::from_policies([p0a, p0b, p0c]).unwrap();
+        let ps1 = PolicySet::from_policies([p1a, p1b, p1c]).unwrap();
+        let names = ps0.merge(&ps1, true).unwrap();
+        assert_eq!(names.len(), 3);
+        // All three original IDs were renamed
+        assert!(names.contains_key(&pid_a));
+        assert!(names.contains_key(&pid_b));
+        assert!(names.contains_key(&pid_c));
+        // The fresh names are all distinct
+        let fresh: HashSet<_> = names.values().collect();
+        assert_eq!(fresh.len(), 3);
     }
 
+    /// After merging a renamed template, we can still link against it
+    /// using its new name.
     #[test]
-    fn merge_policies_templates_collision() {
+    fn merge_policies_link_against_renamed_template() {
         let colliding_pid = PolicyId::new("0");
-        let link_pid = PolicyId::new("1");
         let name_after_merge = PolicyId::new("policy0");
+        // ps0 has a principal-slot template "0"
         let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+        // ps1 has a different resource-slot template also named "0"
         let t1 = Template::from_pst(resource_slot_template(colliding_pid.clone())).unwrap();
 
         let mut ps0 = PolicySet::new();
         ps0.add_template(t0).unwrap();
         let mut ps1 = PolicySet::new();
         ps1.add_template(t1).unwrap();
-        ps1.link(
-            colliding_pid.clone(),
-            link_pid.clone(),
-            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
-        )
-        .unwrap();
+
         let names = ps0.merge(&ps1, true).unwrap();
         assert_eq!(
             names,
             HashMap::from([(colliding_pid, name_after_merge.clone())])
         );
 
-        let merged_link = ps0.policy(&link_pid).unwrap();
-        assert_eq!(merged_link.template_id(), Some(&name_after_merge));
+        // The renamed template should be linkable
+        let link_pid = PolicyId::new("new_link");
+        ps0.link(
+            name_after_merge,
+            link_pid.clone(),
+            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
+        )
+        .unwrap();
+        assert!(ps0.policy(&link_pid).is_some());
     }
 
+    /// After merging with a rename, `to_pst()` should produce a PST where
+    /// the renamed static policy carries its new ID, not the old one.
+    /// This is a PST-specific concern because the lossless representation
+    /// is cloned from `other` (which still has the old ID).
     #[test]
-    fn merge_policies_policy_template_collision() {
+    fn merge_pst_roundtrip_renamed_static_policy() {
         let colliding_pid = PolicyId::new("0");
         let name_after_merge = PolicyId::new("policy0");
         let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let t1 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+        let p1 = Policy::from_pst(
+            pst::StaticPolicy::try_from(pst::Template::new(
+                colliding_pid.clone(),
+                pst::Effect::Forbid,
+                pst::PrincipalConstraint::Any,
+                pst::ActionConstraint::Any,
+                pst::ResourceConstraint::Any,
+            ))
+            .unwrap()
+            .into(),
+        )
+        .unwrap();
 
-        let mut ps0 = PolicySet::from_policies([p0.clone()]).unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
-        );
-        assert_eq!(ps0.policy(&colliding_pid), Some(&p0));
-        assert!(ps0.template(&name_after_merge).is_some());
+        let mut ps0 = PolicySet::from_policies([p0]).unwrap();
+        let ps1 = PolicySet::from_policies([p1]).unwrap();
+        ps0.merge(&ps1, true).unwrap();
+
+        let pst_set = ps0.to_pst().unwrap();
+        // The renamed policy should appear under its new ID in the PST
+        assert!(pst_set
+            .policies
+            .contains_key(&name_after_merge.clone().into()));
+        // And the PST's internal ID should also be the new one
+        let renamed_pst = &pst_set.policies[&name_after_merge.clone().into()];
+        assert_eq!(PolicyId::from(renamed_pst.id().clone()), name_after_merge);
     }
 
+    /// After merging with a rename, `to_pst()` should produce a PST where
+    /// the renamed template carries its new ID. Verify the full round-trip:
+    /// `from_pst(to_pst())` should yield an equivalent PolicySet.
     #[test]
-    fn merge_policies_link_collision() {
-        let template0_pid = PolicyId::new("0");
-        let template1_pid = PolicyId::new("1");
-        let colliding_link_pid = PolicyId::new("2");
+    fn merge_pst_roundtrip_renamed_template() {
+        let colliding_pid = PolicyId::new("0");
         let name_after_merge = PolicyId::new("policy0");
-        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
-        let t1 = Template::from_pst(resource_slot_template(template1_pid.clone())).unwrap();
+        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+        let t1 = Template::from_pst(resource_slot_template(colliding_pid.clone())).unwrap();
 
         let mut ps0 = PolicySet::new();
         ps0.add_template(t0).unwrap();
-        ps0.link(
-            template0_pid,
-            colliding_link_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
-        )
-        .unwrap();
         let mut ps1 = PolicySet::new();
         ps1.add_template(t1).unwrap();
-        ps1.link(
-            template1_pid.clone(),
-            colliding_link_pid.clone(),
-            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
-        )
-        .unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_link_pid, name_after_merge.clone())])
-        );
+        ps0.merge(&ps1, true).unwrap();
 
-        let merged_linked = ps0.policy(&name_after_merge).unwrap();
-        assert_eq!(merged_linked.template_id(), Some(&template1_pid));
+        let pst_set = ps0.to_pst().unwrap();
+        // The renamed template should appear under its new ID
+        assert!(pst_set
+            .templates
+            .contains_key(&name_after_merge.clone().into()));
+        // And its internal ID should match
+        let renamed_tmpl = &pst_set.templates[&name_after_merge.clone().into()];
+        assert_eq!(PolicyId::from(renamed_tmpl.id.clone()), name_after_merge);
+
+        // Full round-trip: from_pst(to_pst()) should be equivalent
+        let roundtripped = PolicySet::from_pst(pst_set).unwrap();
+        assert_eq!(ps0, roundtripped);
     }
 
+    /// After merging a template with a link that both get renamed,
+    /// the PST round-trip should preserve the link's reference to
+    /// the renamed template.
     #[test]
-    fn merge_policies_template_and_link_collision() {
+    fn merge_pst_roundtrip_renamed_template_and_link() {
         let colliding_template_pid = PolicyId::new("0");
         let colliding_link_pid = PolicyId::new("2");
-        let template_name_after_merge = PolicyId::new("policy0");
-        let link_name_after_merge = PolicyId::new("policy1");
         let t0 =
             Template::from_pst(principal_slot_template(colliding_template_pid.clone())).unwrap();
         let t1 = Template::from_pst(pst::Template::new(
@@ -11815,431 +14708,1173 @@ mod policy_set_pst_tests {
         let mut ps1 = PolicySet::new();
         ps1.add_template(t1).unwrap();
         ps1.link(
-            colliding_template_pid.clone(),
-            colliding_link_pid.clone(),
+            colliding_template_pid,
+            colliding_link_pid,
             HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
         )
         .unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
+        ps0.merge(&ps1, true).unwrap();
+
+        let pst_set = ps0.to_pst().unwrap();
+        // Round-trip should produce an equivalent PolicySet
+        let roundtripped = PolicySet::from_pst(pst_set).unwrap();
+        assert_eq!(ps0, roundtripped);
+    }
+}
+
+#[cfg(feature = "tolerant-ast")]
+mod tolerant_ast_tests {
+    use super::*;
+    use cedar_policy_core::ast;
+
+    /// Helper: build a TemplateBody with ActionConstraint::ErrorConstraint
+    fn template_body_with_error_action() -> ast::TemplateBody {
+        let pc = ast::PrincipalConstraint::new(ast::PrincipalOrResourceConstraint::any());
+        let rc = ast::ResourceConstraint::new(ast::PrincipalOrResourceConstraint::any());
+        ast::TemplateBody::new(
+            ast::PolicyID::from_string("error_action_test"),
+            None,
+            ast::Annotations::default(),
+            ast::Effect::Permit,
+            pc,
+            ast::ActionConstraint::ErrorConstraint,
+            rc,
+            None,
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "internal ErrorConstraint cannot be represented in the public API")]
+    fn template_action_constraint_error_panics() {
+        let tb = template_body_with_error_action();
+        let ast_template = ast::Template::from(tb);
+        let template = Template::from_ast(ast_template);
+        let _ = template.action_constraint();
+    }
+
+    #[test]
+    #[should_panic(expected = "internal ErrorConstraint cannot be represented in the public API")]
+    fn policy_action_constraint_error_panics() {
+        let tb = template_body_with_error_action();
+        let ast_template = ast::Template::from(tb);
+        let static_policy = ast::StaticPolicy::try_from(ast_template).unwrap();
+        let ast_policy = ast::Policy::from(static_policy);
+        let policy = Policy::from_ast(ast_policy);
+        let _ = policy.action_constraint();
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    /// Span name and the recorded fields, starting with the span id
+    type SpanFields = (String, Vec<(String, String)>);
+
+    /// Spans in the order they were created, with their recorded fields
+    #[derive(Default, Clone)]
+    struct Spans(Arc<Mutex<Vec<SpanFields>>>);
+
+    struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl<S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>> Layer<S>
+        for Spans
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: LayerContext<'_, S>) {
+            let mut fields = vec![("id".to_string(), format!("{}", id.into_u64()))];
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.0
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name().to_string(), fields));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: LayerContext<'_, S>) {
+            let id = format!("{}", id.into_u64());
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, fields)) = spans
+                .iter_mut()
+                .rev()
+                .find(|(_, fields)| fields.first().is_some_and(|(_, v)| *v == id))
+            {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+    }
+
+    impl Spans {
+        fn field(&self, span: &str, field: &str) -> Option<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(name, _)| name == span)
+                .flat_map(|(_, fields)| fields.iter())
+                .filter(|(name, _)| name == field)
+                .map(|(_, value)| value.clone())
+                .next_back()
+        }
+
+        fn names(&self) -> Vec<String> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn phases_are_instrumented() {
+        let spans = Spans::default();
+        let subscriber = tracing_subscriber::registry().with(spans.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let (schema, _) = Schema::from_cedarschema_str(
+                "entity User in [Group]; entity Group; action view appliesTo { principal: User, resource: Group };",
+            )
+            .unwrap();
+            let pset = PolicySet::from_str(
+                r#"permit(principal, action, resource in Group::"admins");
+                forbid(principal, action, resource) when { false };"#,
+            )
+            .unwrap();
+            assert!(Validator::new(schema.clone())
+                .validate(&pset, ValidationMode::Strict)
+                .validation_passed());
+            let entities = Entities::from_json_value(
+                serde_json::json!([
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Group", "id": "admins" }] },
+                    { "uid": { "type": "Group", "id": "admins" }, "attrs": {}, "parents": [] }
+                ]),
+                Some(&schema),
+            )
+            .unwrap();
+            let request = Request::new(
+                EntityUid::from_str(r#"User::"alice""#).unwrap(),
+                EntityUid::from_str(r#"Action::"view""#).unwrap(),
+                EntityUid::from_str(r#"Group::"admins""#).unwrap(),
+                Context::empty(),
+                Some(&schema),
+            )
+            .unwrap();
+            let response = Authorizer::new().is_authorized(&request, &pset, &entities);
+            assert_eq!(response.decision(), Decision::Allow);
+        });
+
+        let names = spans.names();
+        for name in [
+            "cedar.parse_schema",
+            "cedar.parse_policies",
+            "cedar.validate",
+            "cedar.entities",
+            "cedar.compute_tc",
+            "cedar.authorize",
+        ] {
+            assert!(
+                names.iter().any(|n| n == name),
+                "missing span {name}: {names:?}"
+            );
+        }
         assert_eq!(
-            names,
-            HashMap::from([
-                (colliding_template_pid, template_name_after_merge.clone()),
-                (colliding_link_pid, link_name_after_merge.clone()),
-            ])
+            spans
+                .field("cedar.parse_policies", "policy_count")
+                .as_deref(),
+            Some("2")
+        );
+        assert_eq!(
+            spans.field("cedar.validate", "policy_count").as_deref(),
+            Some("2")
+        );
+        // The schema's action entity is added to the two entities in the JSON
+        assert_eq!(
+            spans.field("cedar.entities", "entity_count").as_deref(),
+            Some("3")
+        );
+        assert_eq!(
+            spans.field("cedar.authorize", "entity_count").as_deref(),
+            Some("3")
+        );
+        assert_eq!(
+            spans.field("cedar.authorize", "decision").as_deref(),
+            Some("Allow")
         );
+    }
+}
 
-        let merged_linked = ps0.policy(&link_name_after_merge).unwrap();
+mod schema_imports_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    #[test]
+    fn compose_from_map() {
+        let mut sources = HashMap::from([
+            (
+                "app".to_string(),
+                r#"import "users";
+                namespace App {
+                    entity Document;
+                    action view appliesTo { principal: Users::User, resource: Document };
+                }"#
+                .to_string(),
+            ),
+            (
+                "users".to_string(),
+                "namespace Users { entity User; }".to_string(),
+            ),
+        ]);
+        let (schema, _) = Schema::from_cedarschema_with_imports("app", &mut sources).unwrap();
+        let mut types: Vec<_> = schema.entity_types().map(ToString::to_string).collect();
+        types.sort();
+        assert_eq!(types, ["App::Document", "Users::User"]);
+    }
+
+    #[test]
+    fn compose_errors() {
+        let mut sources = HashMap::from([
+            ("a".to_string(), r#"import "b"; entity A;"#.to_string()),
+            ("b".to_string(), r#"import "a"; entity A;"#.to_string()),
+        ]);
+        assert_matches!(
+            Schema::from_cedarschema_with_imports("a", &mut sources).map(|(schema, _)| schema),
+            Err(CedarSchemaError::Import(SchemaImportError::Cycle(_)))
+        );
+
+        let mut sources = HashMap::from([
+            ("a".to_string(), r#"import "b"; entity A;"#.to_string()),
+            ("b".to_string(), "entity A;".to_string()),
+        ]);
+        let Err(err) = Schema::from_cedarschema_with_imports("a", &mut sources) else {
+            panic!("expected duplicate definition error");
+        };
+        expect_err(
+            "",
+            &Report::new(err),
+            &ExpectedErrorMessageBuilder::error("entity type `A` is defined in both `b` and `a`")
+                .build(),
+        );
+    }
+
+    #[test]
+    fn compose_from_files() {
+        let dir = std::env::temp_dir().join(format!("cedar-schema-imports-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("common")).unwrap();
+        std::fs::write(
+            dir.join("app.cedarschema"),
+            r#"import "common/users.cedarschema";
+            entity Document;
+            action view appliesTo { principal: User, resource: Document };"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common").join("users.cedarschema"),
+            r#"import "groups.cedarschema"; entity User in Group;"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common").join("groups.cedarschema"),
+            "entity Group;",
+        )
+        .unwrap();
+
+        let root = dir.join("app.cedarschema");
+        let result =
+            Schema::from_cedarschema_with_imports(&root.to_string_lossy(), &mut FileSchemaLoader);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (schema, _) = result.unwrap();
+        assert_eq!(schema.entity_types().count(), 3);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod schema_generator_tests {
+    use super::*;
+    use arbitrary::Unstructured;
+
+    #[test]
+    fn generated_data_validates() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            r#"
+            entity Team;
+            entity User in [Team] { name: String, teams: Set<Team>, level?: Long };
+            entity Doc { owner: User };
+            action edit appliesTo {
+                principal: User,
+                resource: Doc,
+                context: { reason: String },
+            };
+            "#,
+        )
+        .unwrap();
+        let generator = SchemaGenerator::with_config(
+            &schema,
+            GeneratorConfig {
+                max_entities_per_type: 2,
+                max_set_len: 4,
+            },
+        );
+        for seed in 0..64_u8 {
+            let bytes: Vec<u8> = (0..=u8::MAX)
+                .cycle()
+                .take(512)
+                .map(|i| i.wrapping_mul(seed).wrapping_add(seed))
+                .collect();
+            let mut u = Unstructured::new(&bytes);
+            let entities = generator.entities(&mut u).unwrap();
+            Entities::from_entities(entities.iter().cloned(), Some(&schema)).unwrap();
+            let request = generator.request(&mut u).unwrap();
+            Request::new(
+                request.principal().unwrap().clone(),
+                request.action().unwrap().clone(),
+                request.resource().unwrap().clone(),
+                request.context().unwrap().clone(),
+                Some(&schema),
+            )
+            .unwrap();
+        }
+    }
+}
+
+mod validator_config_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    #[test]
+    fn incomplete_schema() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            r#"
+            entity User { name: String };
+            entity Photo;
+            action view appliesTo { principal: User, resource: Photo, context: {} };
+            "#,
+        )
+        .unwrap();
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal in Group::"admins", action, resource)
+            when { principal.department == "eng" && context.ip == "10.0.0.1" };
+            permit(principal, action, resource) when { resource in Album::"trips" };
+            "#,
+        )
+        .unwrap();
+        let result = Validator::new(schema.clone()).validate(&pset, ValidationMode::Strict);
+        assert!(!result.validation_passed());
+        assert!(result.assumed_unknowns().is_empty());
+
+        let config = ValidatorConfig::new().with_incomplete_schema();
+        let result = Validator::with_config(schema, config).validate(&pset, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        let assumed = result.assumed_unknowns();
         assert_eq!(
-            merged_linked.template_id(),
-            Some(&template_name_after_merge)
+            assumed.entity_types().collect::<Vec<_>>(),
+            ["Album", "Group"]
+        );
+        assert_eq!(
+            assumed.attributes().collect::<Vec<_>>(),
+            [
+                "`department` on entity type `User`",
+                r#"`ip` in context for Action::"view""#,
+            ]
         );
     }
 
     #[test]
-    fn merge_policies_eq_templates_different_links() {
-        let shared_template_pid = PolicyId::new("0");
-        let link0_pid = PolicyId::new("1");
-        let link1_pid = PolicyId::new("2");
-        let t0 = Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
-        let t0_dup =
-            Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
+    fn warnings_as_errors() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
+        )
+        .unwrap();
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { false };
+            @allow_warning("impossible_policy")
+            permit(principal, action, resource) when { false };
+            "#,
+        )
+        .unwrap();
 
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        ps0.link(
-            shared_template_pid.clone(),
-            link0_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        let result = Validator::new(schema.clone()).validate(&pset, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        assert_matches!(result.validation_warnings().collect::<Vec<_>>().as_slice(), [w] => {
+            assert_eq!(w.kind(), ValidationWarningKind::ImpossiblePolicy);
+            assert_eq!(w.policy_id(), &PolicyId::new("policy0"));
+        });
+
+        let config = ValidatorConfig::new().with_warning_severity(
+            ValidationWarningKind::ImpossiblePolicy,
+            WarningSeverity::Error,
+        );
+        let result = Validator::with_config(schema, config).validate(&pset, ValidationMode::Strict);
+        assert_eq!(result.validation_warnings().count(), 0);
+        assert_matches!(
+            result.validation_errors().collect::<Vec<_>>().as_slice(),
+            [ValidationError::WarningAsError(e)] => {
+                assert_eq!(e.policy_id(), &PolicyId::new("policy0"));
+                assert_matches!(e.warning(), ValidationWarning::ImpossiblePolicy(_));
+            }
+        );
+    }
+
+    #[test]
+    fn fully_specified_actions() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User }; action audit;",
         )
         .unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t0_dup).unwrap();
-        ps1.link(
-            shared_template_pid,
-            link1_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"alice""#.parse().unwrap())]),
+        assert_matches!(
+            schema.check_fully_specified_actions(),
+            Err(SchemaError::UnspecifiedActions(e)) => {
+                assert_eq!(e.actions().map(ToString::to_string).collect::<Vec<_>>(), [r#"Action::"audit""#]);
+            }
+        );
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource);
+            permit(principal, action, resource);
+            "#,
         )
         .unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(names, HashMap::new());
-        assert!(ps0.policy(&link0_pid).is_some());
-        assert!(ps0.policy(&link1_pid).is_some());
+
+        let config = ValidatorConfig::new().with_fully_specified_actions();
+        let result = Validator::with_config(schema, config).validate(&pset, ValidationMode::Strict);
+        assert_matches!(
+            result.validation_errors().collect::<Vec<_>>().as_slice(),
+            [ValidationError::UnspecifiedActionApplication(e)] => {
+                assert_eq!(e.policy_id(), &PolicyId::new("policy1"));
+                assert_eq!(e.actions().map(ToString::to_string).collect::<Vec<_>>(), [r#"Action::"audit""#]);
+                assert_eq!(
+                    e.to_string(),
+                    r#"for policy `policy1`, the action scope constraint includes an action which is not fully specified: Action::"audit""#
+                );
+            }
+        );
     }
 
     #[test]
-    fn merge_policies_eq_templates_link_collision() {
-        let shared_template_pid = PolicyId::new("0");
-        let colliding_link_pid = PolicyId::new("1");
-        let name_after_merge = PolicyId::new("policy0");
-        let t0 = Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
-        let t0_dup =
-            Template::from_pst(principal_slot_template(shared_template_pid.clone())).unwrap();
-
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        ps0.link(
-            shared_template_pid.clone(),
-            colliding_link_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+    fn shadowing_checks() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action view, edit appliesTo { principal: User, resource: User };",
         )
         .unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t0_dup).unwrap();
-        ps1.link(
-            shared_template_pid,
-            colliding_link_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"alice""#.parse().unwrap())]),
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal is User, action, resource);
+            permit(principal == User::"alice", action == Action::"view", resource);
+            forbid(principal, action == Action::"edit", resource);
+            "#,
         )
         .unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_link_pid, name_after_merge)])
+
+        let result = Validator::new(schema.clone()).validate(&pset, ValidationMode::Strict);
+        assert_eq!(result.validation_warnings().count(), 0);
+
+        let config = ValidatorConfig::new().with_shadowing_checks();
+        let result = Validator::with_config(schema, config).validate(&pset, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        assert_matches!(
+            result.validation_warnings().collect::<Vec<_>>().as_slice(),
+            [ValidationWarning::ShadowedPolicy(w)] => {
+                assert_eq!(w.policy_id(), &PolicyId::new("policy1"));
+                assert_eq!(w.shadowing_policy_id(), &PolicyId::new("policy0"));
+                assert_eq!(w.scope(), r#"principal == User::"alice", action == Action::"view", resource"#);
+            }
         );
     }
+}
+
+mod structured_validation_results {
+    use super::*;
+    use cool_asserts::assert_matches;
 
     #[test]
-    fn merge_policies_template_collides_with_link() {
-        let template0_pid = PolicyId::new("0");
-        let colliding_pid = PolicyId::new("1");
-        let name_after_merge = PolicyId::new("policy0");
-        let t0 = Template::from_pst(principal_slot_template(template0_pid.clone())).unwrap();
-        let t1 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
+    fn error_details() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User { age: Long }; action view appliesTo { principal: User, resource: User };",
+        )
+        .unwrap();
+        let src = r#"permit(principal, action, resource) when { principal.age };"#;
+        let pset = PolicySet::from_str(src).unwrap();
+        let result = Validator::new(schema).validate(&pset, ValidationMode::Strict);
+        let errors: Vec<_> = result.validation_errors().collect();
+        assert_matches!(errors.as_slice(), [e] => {
+            assert_eq!(e.kind(), ValidationErrorKind::UnexpectedType);
+            assert_eq!(e.kind().to_string(), "unexpected_type");
+            assert_eq!(e.policy_id(), &PolicyId::new("policy0"));
+            assert_eq!(e.snippet(), Some("principal.age"));
+            let span = e.source_span().unwrap();
+            assert_eq!(&src[span.offset()..span.offset() + span.len()], "principal.age");
+            assert_matches!(e, ValidationError::UnexpectedType(e) => {
+                assert_eq!(e.expected_types().collect::<Vec<_>>(), ["Bool"]);
+                assert_eq!(e.actual_type(), "Long");
+            });
+        });
+    }
 
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        ps0.link(
-            template0_pid,
-            colliding_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+    #[test]
+    fn warning_details() {
+        let (schema, _) = Schema::from_cedarschema_str(
+            "entity User; action view appliesTo { principal: User, resource: User };",
         )
         .unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_pid, name_after_merge.clone())])
-        );
-        assert!(ps0.template(&name_after_merge).is_some());
+        let src = r#"permit(principal, action, resource) when { "аdmin" == "admin" };"#;
+        let pset = PolicySet::from_str(src).unwrap();
+        let result = Validator::new(schema).validate(&pset, ValidationMode::Strict);
+        let warning = result
+            .validation_warnings()
+            .find(|w| w.kind() == ValidationWarningKind::MixedScriptString)
+            .unwrap();
+        assert_eq!(warning.snippet(), Some(r#""аdmin""#));
     }
+}
 
-    #[test]
-    fn merge_policies_link_collides_with_template() {
-        let original_pid = PolicyId::new("0");
-        let colliding_pid = PolicyId::new("1");
-        let name_after_merge = PolicyId::new("policy0");
-        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
-        let t1 = Template::from_pst(principal_slot_template(original_pid.clone())).unwrap();
+mod level_validation_config_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
 
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        ps1.link(
-            original_pid.clone(),
-            colliding_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+    fn schema() -> Schema {
+        Schema::from_cedarschema_str(
+            "entity User { manager: User, name: String }; action view appliesTo { principal: User, resource: User };",
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn configured_level_and_annotation() {
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { principal.manager.manager.name == "alice" };
+            @max_deref_level("3")
+            permit(principal, action, resource) when { principal.manager.manager.name == "bob" };
+            "#,
         )
         .unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_pid.clone(), name_after_merge.clone())])
+
+        // Without a configured level, only annotated policies are level validated
+        let result = Validator::new(schema()).validate(&pset, ValidationMode::Strict);
+        assert!(result.validation_passed());
+
+        let config = ValidatorConfig::new().with_max_deref_level(1);
+        let result =
+            Validator::with_config(schema(), config).validate(&pset, ValidationMode::Strict);
+        assert_matches!(
+            result.validation_errors().collect::<Vec<_>>().as_slice(),
+            [ValidationError::EntityDerefLevelViolation(e)] => {
+                assert_eq!(e.policy_id(), &PolicyId::new("policy0"));
+                assert_eq!(e.allowed_level(), Some(1));
+                assert_eq!(e.actual_level(), Some(3));
+                assert_eq!(e.access_chain(), Some("principal.manager.manager.name"));
+            }
         );
-        assert!(ps0.policy(&name_after_merge).is_some());
     }
 
-    /// The generated fresh name "policy0" is already taken by an existing
-    /// policy in ps0, so the renaming must skip to "policy1".
     #[test]
-    fn merge_policies_fresh_id_skips_existing() {
-        let colliding_pid = PolicyId::new("0");
-        let blocker_pid = PolicyId::new("policy0");
-        let expected_fresh_pid = PolicyId::new("policy1");
-        let p_colliding = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let p_blocker = Policy::from_pst(
-            pst::StaticPolicy::try_from(pst::Template::new(
-                blocker_pid.clone(),
-                pst::Effect::Forbid,
-                pst::PrincipalConstraint::Any,
-                pst::ActionConstraint::Any,
-                pst::ResourceConstraint::Any,
-            ))
-            .unwrap()
-            .into(),
+    fn literal_deref_has_no_access_chain() {
+        let pset = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { User::"alice".name == "alice" };"#,
         )
         .unwrap();
-        let p_other = Policy::from_pst(
-            pst::StaticPolicy::try_from(pst::Template::new(
-                colliding_pid.clone(),
-                pst::Effect::Forbid,
-                pst::PrincipalConstraint::Any,
-                pst::ActionConstraint::Any,
-                pst::ResourceConstraint::Any,
-            ))
-            .unwrap()
-            .into(),
+        let result = Validator::new(schema()).validate_with_level(&pset, ValidationMode::Strict, 2);
+        assert_matches!(
+            result.validation_errors().collect::<Vec<_>>().as_slice(),
+            [ValidationError::EntityDerefLevelViolation(e)] => {
+                assert_eq!(e.access_chain(), None);
+                assert_eq!(e.allowed_level(), None);
+            }
+        );
+    }
+}
+
+mod attribute_default_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        Schema::from_cedarschema_str(
+            r#"
+            entity User { name: String, level?: Long = 1, tags?: Set<String> = [] };
+            entity Doc;
+            action view appliesTo {
+                principal: User,
+                resource: Doc,
+                context: { mfa?: Bool = false },
+            };
+            "#,
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn entities_take_defaults() {
+        let entities = Entities::from_json_value(
+            json!([
+                { "uid": { "type": "User", "id": "alice" }, "attrs": { "name": "alice" }, "parents": [] },
+                { "uid": { "type": "User", "id": "bob" }, "attrs": { "name": "bob", "level": 5 }, "parents": [] }
+            ]),
+            Some(&schema()),
         )
         .unwrap();
+        let level = |id: &str| {
+            entities
+                .get(&EntityUid::from_strs("User", id))
+                .and_then(|e| e.attr("level"))
+                .and_then(Result::ok)
+        };
+        assert_matches!(level("alice"), Some(EvalResult::Long(1)));
+        assert_matches!(level("bob"), Some(EvalResult::Long(5)));
+    }
 
-        let mut ps0 = PolicySet::from_policies([p_colliding.clone(), p_blocker.clone()]).unwrap();
-        let ps1 = PolicySet::from_policies([p_other]).unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(
-            names,
-            HashMap::from([(colliding_pid, expected_fresh_pid.clone())])
-        );
-        assert!(ps0.policy(&expected_fresh_pid).is_some());
-        assert!(ps0.policy(&blocker_pid).is_some());
+    #[test]
+    fn context_takes_defaults() {
+        let action = EntityUid::from_strs("Action", "view");
+        let context = Context::from_json_value(json!({}), Some((&schema(), &action))).unwrap();
+        assert_matches!(context.get("mfa"), Some(EvalResult::Bool(false)));
     }
 
-    /// Both sets have the same template and the same link (same slot values).
-    /// Everything should be deduped with no renames.
     #[test]
-    fn merge_policies_eq_templates_eq_links() {
-        let template_pid = PolicyId::new("0");
-        let link_pid = PolicyId::new("1");
-        let t0 = Template::from_pst(principal_slot_template(template_pid.clone())).unwrap();
-        let t0_dup = Template::from_pst(principal_slot_template(template_pid.clone())).unwrap();
-        let env = HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]);
+    fn required_attribute_default_is_rejected() {
+        assert_matches!(
+            Schema::from_cedarschema_str("entity User { name: String = \"\" };")
+                .map(|(schema, _)| schema),
+            Err(CedarSchemaError::Schema(SchemaError::InvalidAttributeDefault(e))) => {
+                assert_eq!(e.attr(), "name");
+            }
+        );
+    }
+}
 
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        ps0.link(template_pid.clone(), link_pid.clone(), env.clone())
-            .unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t0_dup).unwrap();
-        ps1.link(template_pid, link_pid.clone(), env).unwrap();
+mod attribute_annotation_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
 
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(names, HashMap::new());
-        assert!(ps0.policy(&link_pid).is_some());
-    }
+    const SCHEMA: &str = r#"
+        entity User {
+            @doc("Primary email address")
+            email: String,
+            @doc("Legacy email address")
+            @deprecated("use `email` instead")
+            mail?: String,
+        };
+        action view appliesTo { principal: User, resource: User };
+    "#;
 
-    /// Three collisions at once force the fresh-ID generator to produce
-    /// policy0, policy1, and policy2.
     #[test]
-    fn merge_policies_triple_collision() {
-        let pid_a = PolicyId::new("a");
-        let pid_b = PolicyId::new("b");
-        let pid_c = PolicyId::new("c");
-        let p0a = Policy::from_pst(permit_all(pid_a.clone()).into()).unwrap();
-        let p0b = Policy::from_pst(permit_all(pid_b.clone()).into()).unwrap();
-        let p0c = Policy::from_pst(permit_all(pid_c.clone()).into()).unwrap();
-        // Build ps1 with the same IDs but different effects so they collide
-        let make_forbid = |id: PolicyId| {
-            Policy::from_pst(
-                pst::StaticPolicy::try_from(pst::Template::new(
-                    id,
-                    pst::Effect::Forbid,
-                    pst::PrincipalConstraint::Any,
-                    pst::ActionConstraint::Any,
-                    pst::ResourceConstraint::Any,
-                ))
+    fn query_attribute_annotations() {
+        let (fragment, _) = SchemaFragment::from_cedarschema_str(SCHEMA).unwrap();
+        assert_eq!(
+            fragment
+                .entity_attribute_annotations(None, "User", "mail")
                 .unwrap()
+                .collect::<Vec<_>>(),
+            [
+                ("deprecated", "use `email` instead"),
+                ("doc", "Legacy email address")
+            ]
+        );
+        assert_eq!(
+            fragment.entity_attribute_annotation(None, "User", "email", "doc"),
+            Some("Primary email address")
+        );
+        assert!(fragment
+            .entity_attribute_annotations(None, "User", "name")
+            .is_none());
+        assert!(fragment
+            .entity_attribute_annotations(None, "Group", "email")
+            .is_none());
+    }
+
+    #[test]
+    fn deprecated_attribute_warning() {
+        let (schema, _) = Schema::from_cedarschema_str(SCHEMA).unwrap();
+        let pset = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { principal has mail && principal.mail == "alice@example.com" };"#,
+        )
+        .unwrap();
+        let result = Validator::new(schema).validate(&pset, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        // Both the `has` check and the access are reported
+        let mut snippets = result
+            .validation_warnings()
+            .map(|w| {
+                assert_matches!(w, ValidationWarning::DeprecatedAttribute(w) => {
+                    assert_eq!(w.policy_id(), &PolicyId::new("policy0"));
+                    assert_eq!(w.attr(), "mail");
+                    assert_eq!(w.reason(), Some("use `email` instead"));
+                    w.snippet()
+                })
+            })
+            .collect::<Vec<_>>();
+        snippets.sort();
+        assert_eq!(
+            snippets,
+            [Some("principal has mail"), Some("principal.mail")]
+        );
+    }
+}
+
+mod compiled_tests {
+    use super::{Authorizer, Context, Entities, EntityUid, PolicyId, PolicySet, Request};
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn is_authorized_compiled() {
+        let mut policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.level > 2 && resource.public };
+            forbid(principal, action, resource) when { principal.banned };
+            permit(principal == ?principal, action, resource) when { context.level + true == 1 };"#,
+        )
+        .unwrap();
+        policies
+            .link(
+                PolicyId::new("policy2"),
+                PolicyId::new("link"),
+                [(
+                    crate::SlotId::principal(),
+                    EntityUid::from_str(r#"User::"alice""#).unwrap(),
+                )]
                 .into(),
             )
-            .unwrap()
-        };
-        let p1a = make_forbid(pid_a.clone());
-        let p1b = make_forbid(pid_b.clone());
-        let p1c = make_forbid(pid_c.clone());
+            .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                { "uid": { "type": "User", "id": "alice" }, "attrs": { "banned": false }, "parents": [] },
+                { "uid": { "type": "User", "id": "bob" }, "attrs": {}, "parents": [] },
+                { "uid": { "type": "Photo", "id": "p" }, "attrs": { "public": true }, "parents": [] },
+            ]),
+            None,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new();
+        let compiled = policies.compile(&authorizer);
+        for (principal, level) in [("alice", 3), ("alice", 1), ("bob", 3)] {
+            let request = Request::new(
+                EntityUid::from_str(&format!(r#"User::"{principal}""#)).unwrap(),
+                EntityUid::from_str(r#"Action::"view""#).unwrap(),
+                EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+                Context::from_json_value(serde_json::json!({ "level": level }), None).unwrap(),
+                None,
+            )
+            .unwrap();
+            let expected = authorizer.is_authorized(&request, &policies, &entities);
+            let actual = authorizer.is_authorized_compiled(&request, &compiled, &entities);
+            assert_eq!(actual.decision(), expected.decision());
+            assert_eq!(
+                actual.diagnostics().reason().collect::<HashSet<_>>(),
+                expected.diagnostics().reason().collect::<HashSet<_>>()
+            );
+            assert_eq!(
+                actual
+                    .diagnostics()
+                    .errors()
+                    .map(ToString::to_string)
+                    .collect::<HashSet<_>>(),
+                expected
+                    .diagnostics()
+                    .errors()
+                    .map(ToString::to_string)
+                    .collect::<HashSet<_>>()
+            );
+        }
+        assert_eq!(compiled.policies().num_of_policies(), 3);
+    }
+}
+
+mod entity_loader_tests {
+    use super::{CachingEntityLoader, Entity, EntityLoader, EntityUid};
+    use std::collections::{HashMap, HashSet};
+    use std::str::FromStr;
+
+    /// Loads entities which exist for all UIDs except `User::"missing"`,
+    /// recording each batch it is asked for
+    #[derive(Debug, Default)]
+    struct RecordingLoader {
+        batches: Vec<HashSet<EntityUid>>,
+    }
+
+    impl EntityLoader for RecordingLoader {
+        fn load_entities(
+            &mut self,
+            uids: &HashSet<EntityUid>,
+        ) -> HashMap<EntityUid, Option<Entity>> {
+            self.batches.push(uids.clone());
+            uids.iter()
+                .filter(|uid| uid.id().unescaped() != "missing")
+                .map(|uid| (uid.clone(), Some(Entity::with_uid(uid.clone()))))
+                .collect()
+        }
+    }
 
-        let mut ps0 = PolicySet::from_policies([p0a, p0b, p0c]).unwrap();
-        let ps1 = PolicySet::from_policies([p1a, p1b, p1c]).unwrap();
-        let names = ps0.merge(&ps1, true).unwrap();
-        assert_eq!(names.len(), 3);
-        // All three original IDs were renamed
-        assert!(names.contains_key(&pid_a));
-        assert!(names.contains_key(&pid_b));
-        assert!(names.contains_key(&pid_c));
-        // The fresh names are all distinct
-        let fresh: HashSet<_> = names.values().collect();
-        assert_eq!(fresh.len(), 3);
+    fn uids(ids: &[&str]) -> HashSet<EntityUid> {
+        ids.iter()
+            .map(|id| EntityUid::from_str(&format!(r#"User::"{id}""#)).unwrap())
+            .collect()
     }
 
-    /// After merging a renamed template, we can still link against it
-    /// using its new name.
     #[test]
-    fn merge_policies_link_against_renamed_template() {
-        let colliding_pid = PolicyId::new("0");
-        let name_after_merge = PolicyId::new("policy0");
-        // ps0 has a principal-slot template "0"
-        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
-        // ps1 has a different resource-slot template also named "0"
-        let t1 = Template::from_pst(resource_slot_template(colliding_pid.clone())).unwrap();
+    fn fetches_only_misses() {
+        let mut loader = CachingEntityLoader::new(RecordingLoader::default(), 10);
+        let entities = loader.load_entities(&uids(&["a", "b", "missing"]));
+        assert_eq!(entities.len(), 3);
+        assert!(entities[&uids(&["a"]).into_iter().next().unwrap()].is_some());
+        assert!(entities[&uids(&["missing"]).into_iter().next().unwrap()].is_none());
 
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
+        let entities = loader.load_entities(&uids(&["a", "c", "missing"]));
+        assert_eq!(entities.len(), 3);
+        assert_eq!(
+            loader.inner().batches,
+            vec![uids(&["a", "b", "missing"]), uids(&["c"])]
+        );
+        assert_eq!(loader.len(), 4);
+    }
 
-        let names = ps0.merge(&ps1, true).unwrap();
+    #[test]
+    fn without_negative_caching() {
+        let mut loader =
+            CachingEntityLoader::new(RecordingLoader::default(), 10).with_negative_caching(false);
+        loader.load_entities(&uids(&["a", "missing"]));
+        loader.load_entities(&uids(&["a", "missing"]));
         assert_eq!(
-            names,
-            HashMap::from([(colliding_pid, name_after_merge.clone())])
+            loader.into_inner().batches,
+            vec![uids(&["a", "missing"]), uids(&["missing"])]
         );
+    }
 
-        // The renamed template should be linkable
-        let link_pid = PolicyId::new("new_link");
-        ps0.link(
-            name_after_merge,
-            link_pid.clone(),
-            HashMap::from([(SlotId::resource(), r#"User::"alice""#.parse().unwrap())]),
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut loader = CachingEntityLoader::new(RecordingLoader::default(), 2);
+        loader.load_entities(&uids(&["a"]));
+        loader.load_entities(&uids(&["b"]));
+        // `a` is now more recently used than `b`
+        loader.load_entities(&uids(&["a"]));
+        loader.load_entities(&uids(&["c"]));
+        assert_eq!(loader.len(), 2);
+        loader.load_entities(&uids(&["a", "b"]));
+        assert_eq!(
+            loader.inner().batches,
+            vec![uids(&["a"]), uids(&["b"]), uids(&["c"]), uids(&["b"])]
+        );
+    }
+
+    #[test]
+    fn invalidate_and_clear() {
+        let mut loader = CachingEntityLoader::new(RecordingLoader::default(), 10);
+        loader.load_entities(&uids(&["a", "b"]));
+        loader.invalidate(&uids(&["a"]).into_iter().next().unwrap());
+        assert_eq!(loader.len(), 1);
+        loader.load_entities(&uids(&["a", "b"]));
+        loader.clear();
+        assert!(loader.is_empty());
+        loader.load_entities(&uids(&["b"]));
+        assert_eq!(
+            loader.inner().batches,
+            vec![uids(&["a", "b"]), uids(&["a"]), uids(&["b"])]
+        );
+    }
+}
+
+mod explanation_tests {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    fn request(principal: &str, action: &str, context: serde_json::Value) -> Request {
+        Request::new(
+            EntityUid::from_strs("User", principal),
+            EntityUid::from_strs("Action", action),
+            EntityUid::from_strs("Photo", "a"),
+            Context::from_json_value(context, None).unwrap(),
+            None,
         )
-        .unwrap();
-        assert!(ps0.policy(&link_pid).is_some());
+        .unwrap()
+    }
+
+    fn entities() -> Entities {
+        Entities::from_json_value(
+            serde_json::json!([
+                { "uid": { "type": "User", "id": "alice" }, "attrs": { "age": 16 }, "parents": [] },
+                { "uid": { "type": "User", "id": "bob" }, "attrs": { "age": 40 }, "parents": [] },
+            ]),
+            None,
+        )
+        .unwrap()
     }
 
-    /// After merging with a rename, `to_pst()` should produce a PST where
-    /// the renamed static policy carries its new ID, not the old one.
-    /// This is a PST-specific concern because the lossless representation
-    /// is cloned from `other` (which still has the old ID).
     #[test]
-    fn merge_pst_roundtrip_renamed_static_policy() {
-        let colliding_pid = PolicyId::new("0");
-        let name_after_merge = PolicyId::new("policy0");
-        let p0 = Policy::from_pst(permit_all(colliding_pid.clone()).into()).unwrap();
-        let p1 = Policy::from_pst(
-            pst::StaticPolicy::try_from(pst::Template::new(
-                colliding_pid.clone(),
-                pst::Effect::Forbid,
-                pst::PrincipalConstraint::Any,
-                pst::ActionConstraint::Any,
-                pst::ResourceConstraint::Any,
-            ))
-            .unwrap()
-            .into(),
+    fn forbid_overrides_permits() {
+        let pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource);
+            permit(principal == User::"alice", action, resource);
+            forbid(principal, action == Action::"delete", resource);
+            permit(principal == User::"bob", action, resource);
+            "#,
         )
         .unwrap();
+        let explanation = Authorizer::new().explain(
+            &request("alice", "delete", serde_json::json!({})),
+            &pset,
+            &entities(),
+        );
+        assert_eq!(explanation.decision(), Decision::Deny);
+        assert_eq!(
+            explanation.reason(),
+            &DecisionReason::Forbidden {
+                forbids: vec![PolicyId::new("policy2")],
+                overridden_permits: vec![PolicyId::new("policy0"), PolicyId::new("policy1")],
+            }
+        );
+        let bob = explanation.policy(&PolicyId::new("policy3")).unwrap();
+        assert_eq!(bob.effect(), Effect::Permit);
+        assert_matches!(bob.outcome(), PolicyOutcome::ScopeNotSatisfied(clause) => {
+            assert_eq!(clause.kind(), ClauseKind::Principal);
+            assert_eq!(clause.expr(), r#"principal == User::"bob""#);
+            assert_eq!(
+                clause
+                    .operands()
+                    .map(|o| (o.expr(), o.value().clone()))
+                    .collect::<Vec<_>>(),
+                [(
+                    "principal",
+                    EvalResult::EntityUid(EntityUid::from_strs("User", "alice"))
+                )]
+            );
+        });
 
-        let mut ps0 = PolicySet::from_policies([p0]).unwrap();
-        let ps1 = PolicySet::from_policies([p1]).unwrap();
-        ps0.merge(&ps1, true).unwrap();
-
-        let pst_set = ps0.to_pst().unwrap();
-        // The renamed policy should appear under its new ID in the PST
-        assert!(pst_set
-            .policies
-            .contains_key(&name_after_merge.clone().into()));
-        // And the PST's internal ID should also be the new one
-        let renamed_pst = &pst_set.policies[&name_after_merge.clone().into()];
-        assert_eq!(PolicyId::from(renamed_pst.id().clone()), name_after_merge);
+        let explanation = Authorizer::new().explain(
+            &request("bob", "view", serde_json::json!({})),
+            &pset,
+            &entities(),
+        );
+        assert_eq!(
+            explanation.reason(),
+            &DecisionReason::Permitted {
+                permits: vec![PolicyId::new("policy0"), PolicyId::new("policy3")],
+            }
+        );
     }
 
-    /// After merging with a rename, `to_pst()` should produce a PST where
-    /// the renamed template carries its new ID. Verify the full round-trip:
-    /// `from_pst(to_pst())` should yield an equivalent PolicySet.
     #[test]
-    fn merge_pst_roundtrip_renamed_template() {
-        let colliding_pid = PolicyId::new("0");
-        let name_after_merge = PolicyId::new("policy0");
-        let t0 = Template::from_pst(principal_slot_template(colliding_pid.clone())).unwrap();
-        let t1 = Template::from_pst(resource_slot_template(colliding_pid.clone())).unwrap();
-
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        ps0.merge(&ps1, true).unwrap();
+    fn failed_conditions() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when { context.verified && principal.age >= 18 };
+            permit(principal, action, resource) when { principal.nickname == "al" };
+            permit(principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        pset.set_enabled(PolicyId::new("policy2"), false).unwrap();
+        let explanation = Authorizer::new().explain(
+            &request("alice", "view", serde_json::json!({ "verified": true })),
+            &pset,
+            &entities(),
+        );
+        assert_eq!(explanation.decision(), Decision::Deny);
+        assert_eq!(explanation.reason(), &DecisionReason::NoPermit);
 
-        let pst_set = ps0.to_pst().unwrap();
-        // The renamed template should appear under its new ID
-        assert!(pst_set
-            .templates
-            .contains_key(&name_after_merge.clone().into()));
-        // And its internal ID should match
-        let renamed_tmpl = &pst_set.templates[&name_after_merge.clone().into()];
-        assert_eq!(PolicyId::from(renamed_tmpl.id.clone()), name_after_merge);
+        let age = explanation.policy(&PolicyId::new("policy0")).unwrap();
+        assert_matches!(age.outcome(), PolicyOutcome::ConditionNotSatisfied(clause) => {
+            assert_eq!(clause.kind(), ClauseKind::Condition);
+            assert_eq!(
+                clause
+                    .operands()
+                    .map(|o| (o.expr(), o.value().clone()))
+                    .collect::<Vec<_>>(),
+                [("principal.age", EvalResult::Long(16))]
+            );
+        });
+        let nickname = explanation.policy(&PolicyId::new("policy1")).unwrap();
+        assert_matches!(nickname.outcome(), PolicyOutcome::Error { clause, .. } => {
+            assert_eq!(clause.operands().count(), 0);
+        });
+        assert_eq!(
+            explanation
+                .policy(&PolicyId::new("policy2"))
+                .unwrap()
+                .outcome(),
+            &PolicyOutcome::Disabled
+        );
 
-        // Full round-trip: from_pst(to_pst()) should be equivalent
-        let roundtripped = PolicySet::from_pst(pst_set).unwrap();
-        assert_eq!(ps0, roundtripped);
+        assert_eq!(
+            explanation.to_string(),
+            r#"Deny: no permit policy was satisfied
+  policy0 (permit): condition `principal.age >= 18` (`principal.age` is 16) was not satisfied
+  policy1 (permit): condition `principal.nickname == "al"` errored: `User::"alice"` does not have the attribute `nickname`
+  policy2 (permit): disabled
+"#
+        );
     }
 
-    /// After merging a template with a link that both get renamed,
-    /// the PST round-trip should preserve the link's reference to
-    /// the renamed template.
     #[test]
-    fn merge_pst_roundtrip_renamed_template_and_link() {
-        let colliding_template_pid = PolicyId::new("0");
-        let colliding_link_pid = PolicyId::new("2");
-        let t0 =
-            Template::from_pst(principal_slot_template(colliding_template_pid.clone())).unwrap();
-        let t1 = Template::from_pst(pst::Template::new(
-            colliding_template_pid.clone(),
-            pst::Effect::Forbid,
-            pst::PrincipalConstraint::Eq(pst::EntityOrSlot::Slot(pst::SlotId::Principal)),
-            pst::ActionConstraint::Any,
-            pst::ResourceConstraint::Any,
-        ))
-        .unwrap();
-
-        let mut ps0 = PolicySet::new();
-        ps0.add_template(t0).unwrap();
-        ps0.link(
-            colliding_template_pid.clone(),
-            colliding_link_pid.clone(),
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+    fn disabled_permits_are_not_overridden() {
+        let mut pset = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource);
+            permit(principal, action, resource);
+            forbid(principal, action, resource);
+            "#,
         )
         .unwrap();
-        let mut ps1 = PolicySet::new();
-        ps1.add_template(t1).unwrap();
-        ps1.link(
-            colliding_template_pid,
-            colliding_link_pid,
-            HashMap::from([(SlotId::principal(), r#"User::"bob""#.parse().unwrap())]),
+        pset.set_enabled(PolicyId::new("policy1"), false).unwrap();
+        let explanation = Authorizer::new().explain(
+            &request("alice", "view", serde_json::json!({})),
+            &pset,
+            &entities(),
+        );
+        assert_eq!(
+            explanation.reason(),
+            &DecisionReason::Forbidden {
+                forbids: vec![PolicyId::new("policy2")],
+                overridden_permits: vec![PolicyId::new("policy0")],
+            }
+        );
+        assert_eq!(
+            explanation
+                .policy(&PolicyId::new("policy1"))
+                .unwrap()
+                .outcome(),
+            &PolicyOutcome::Disabled
+        );
+    }
+
+    #[test]
+    fn authorizer_extensions() {
+        let extensions = ExtensionSet::builder()
+            .with_extension(
+                CustomExtension::new("tenant").with_function(CustomFunction::function(
+                    "tenant::region",
+                    vec![],
+                    DeclaredType::String,
+                    |_| Ok(EvalResult::String("eu".into())),
+                )),
+            )
+            .build()
+            .unwrap();
+        let pset = PolicySet::from_str_with_extensions(
+            r#"permit(principal, action, resource) when { tenant::region() == "eu" };"#,
+            &extensions,
         )
         .unwrap();
-        ps0.merge(&ps1, true).unwrap();
-
-        let pst_set = ps0.to_pst().unwrap();
-        // Round-trip should produce an equivalent PolicySet
-        let roundtripped = PolicySet::from_pst(pst_set).unwrap();
-        assert_eq!(ps0, roundtripped);
+        let authorizer = Authorizer::new().with_extensions(&extensions);
+        let request = request("alice", "view", serde_json::json!({}));
+        let explanation = authorizer.explain(&request, &pset, &entities());
+        assert_eq!(explanation.decision(), Decision::Allow);
+        assert_eq!(
+            explanation
+                .policy(&PolicyId::new("policy0"))
+                .unwrap()
+                .outcome(),
+            &PolicyOutcome::Satisfied
+        );
+        // policies are compiled with the authorizer's extensions
+        let compiled = pset.compile(&authorizer);
+        let response = authorizer.is_authorized_compiled(&request, &compiled, &entities());
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(response.diagnostics().errors().count(), 0);
     }
 }
 
-#[cfg(feature = "tolerant-ast")]
-mod tolerant_ast_tests {
+mod eval_environment_tests {
     use super::*;
-    use cedar_policy_core::ast;
+    use std::time::{Duration, SystemTime};
 
-    /// Helper: build a TemplateBody with ActionConstraint::ErrorConstraint
-    fn template_body_with_error_action() -> ast::TemplateBody {
-        let pc = ast::PrincipalConstraint::new(ast::PrincipalOrResourceConstraint::any());
-        let rc = ast::ResourceConstraint::new(ast::PrincipalOrResourceConstraint::any());
-        ast::TemplateBody::new(
-            ast::PolicyID::from_string("error_action_test"),
-            None,
-            ast::Annotations::default(),
-            ast::Effect::Permit,
-            pc,
-            ast::ActionConstraint::ErrorConstraint,
-            rc,
+    /// 2024-01-01T00:00:00Z
+    const NEW_YEAR_2024: u64 = 1_704_067_200;
+
+    fn authorizer(secs_since_epoch: u64) -> Authorizer {
+        let clock = FixedClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(secs_since_epoch));
+        Authorizer::new()
+            .with_environment(EvalEnvironment::new(clock).with_current_time_attr("now"))
+    }
+
+    fn request(context: Context) -> Request {
+        Request::new(
+            EntityUid::from_strs("User", "alice"),
+            EntityUid::from_strs("Action", "view"),
+            EntityUid::from_strs("Photo", "a"),
+            context,
             None,
         )
+        .unwrap()
     }
 
     #[test]
-    #[should_panic(expected = "internal ErrorConstraint cannot be represented in the public API")]
-    fn template_action_constraint_error_panics() {
-        let tb = template_body_with_error_action();
-        let ast_template = ast::Template::from(tb);
-        let template = Template::from_ast(ast_template);
-        let _ = template.action_constraint();
+    fn frozen_clock() {
+        let pset = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.now.toDate() == datetime("2024-01-01") };"#,
+        )
+        .unwrap();
+        let decide = |authorizer: &Authorizer, context: Context| {
+            authorizer
+                .is_authorized(&request(context), &pset, &Entities::empty())
+                .decision()
+        };
+        let new_year = authorizer(NEW_YEAR_2024 + 3600);
+        assert_eq!(decide(&new_year, Context::empty()), Decision::Allow);
+        assert_eq!(
+            decide(&authorizer(NEW_YEAR_2024 + 86_400), Context::empty()),
+            Decision::Deny
+        );
+
+        // A time given in the request takes precedence over the clock
+        let given = Context::from_pairs([(
+            "now".into(),
+            RestrictedExpression::new_datetime("2023-06-01"),
+        )])
+        .unwrap();
+        assert_eq!(decide(&new_year, given), Decision::Deny);
+
+        // The default environment provides no time
+        let response =
+            Authorizer::new().is_authorized(&request(Context::empty()), &pset, &Entities::empty());
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().errors().count(), 1);
+        assert_eq!(Authorizer::new().environment().current_time_attr(), None);
     }
 
     #[test]
-    #[should_panic(expected = "internal ErrorConstraint cannot be represented in the public API")]
-    fn policy_action_constraint_error_panics() {
-        let tb = template_body_with_error_action();
-        let ast_template = ast::Template::from(tb);
-        let static_policy = ast::StaticPolicy::try_from(ast_template).unwrap();
-        let ast_policy = ast::Policy::from(static_policy);
-        let policy = Policy::from_ast(ast_policy);
-        let _ = policy.action_constraint();
+    fn explain_and_audit_use_environment() {
+        let pset = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.now < datetime("2024-01-01") };"#,
+        )
+        .unwrap();
+        let authorizer = authorizer(NEW_YEAR_2024);
+        let explanation = authorizer.explain(&request(Context::empty()), &pset, &Entities::empty());
+        assert_eq!(explanation.reason(), &DecisionReason::NoPermit);
+        let policy = explanation.policy(&PolicyId::new("policy0")).unwrap();
+        assert!(matches!(
+            policy.outcome(),
+            PolicyOutcome::ConditionNotSatisfied(clause)
+                if clause.operands().map(OperandValue::expr).collect::<Vec<_>>() == ["context.now"]
+        ));
+
+        let records = std::sync::Mutex::new(Vec::new());
+        authorizer.is_authorized_audited(
+            &request(Context::empty()),
+            &pset,
+            &Entities::empty(),
+            &|record: &AuditRecord| records.lock().unwrap().push(record.clone()),
+        );
+        let records = records.into_inner().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records.first().unwrap().timestamp_millis(),
+            NEW_YEAR_2024 * 1000
+        );
     }
 }
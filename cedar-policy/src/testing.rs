@@ -0,0 +1,850 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Unit tests for policies.
+//!
+//! A [`PolicyTest`] is a request, the entities to authorize it against, and
+//! what to expect from authorizing it: the decision, the policies which
+//! determine it, and/or the number of policies which error. A [`TestSuite`]
+//! runs its tests against a [`PolicySet`] and returns a [`TestReport`], which
+//! can be printed, serialized, or asserted on in a `#[test]` function:
+//!
+//! ```
+//! # use cedar_policy::testing::{PolicyTest, TestSuite};
+//! # use cedar_policy::{Context, Decision, Entities, PolicyId, PolicySet, Request};
+//! let policies: PolicySet = r#"
+//!     permit(principal, action, resource) when { resource.owner == principal };
+//! "#.parse().unwrap();
+//! let entities = Entities::from_json_str(
+//!     r#"[{ "uid": { "type": "Photo", "id": "p" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } }, "parents": [] }]"#,
+//!     None,
+//! ).unwrap();
+//! let request = |user: &str| Request::new(
+//!     format!(r#"User::"{user}""#).parse().unwrap(),
+//!     r#"Action::"view""#.parse().unwrap(),
+//!     r#"Photo::"p""#.parse().unwrap(),
+//!     Context::empty(),
+//!     None,
+//! ).unwrap();
+//! TestSuite::new()
+//!     .with_test(
+//!         PolicyTest::new("owner can view", request("alice"), entities.clone())
+//!             .expect_determining_policies([PolicyId::new("policy0")]),
+//!     )
+//!     .with_test(
+//!         PolicyTest::new("others can't view", request("bob"), entities)
+//!             .expect_decision(Decision::Deny),
+//!     )
+//!     .run(&policies)
+//!     .assert_passed();
+//! ```
+//!
+//! Test suites can also be loaded from files (see
+//! [`TestSuite::from_json_str()`] for the format), which are in JSON or, with
+//! the `yaml` feature, YAML.
+
+use crate::{
+    Authorizer, Context, Decision, Entities, EntityUid, PolicyId, PolicySet, Request, Schema,
+};
+use itertools::Itertools;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// A request to authorize, and what to expect from authorizing it
+//
+// CAUTION: this type is publicly exported in `cedar-policy`.
+// Don't make fields `pub`, don't make breaking changes, and use caution
+// when adding public methods.
+#[derive(Debug, Clone)]
+pub struct PolicyTest {
+    name: String,
+    request: Request,
+    entities: Entities,
+    decision: Option<Decision>,
+    determining_policies: Option<BTreeSet<PolicyId>>,
+    num_errors: Option<usize>,
+}
+
+impl PolicyTest {
+    /// A test named `name` which authorizes `request` against `entities`.
+    /// It passes as long as authorization meets the expectations added with
+    /// the `expect_*` methods.
+    pub fn new(name: impl Into<String>, request: Request, entities: Entities) -> Self {
+        Self {
+            name: name.into(),
+            request,
+            entities,
+            decision: None,
+            determining_policies: None,
+            num_errors: None,
+        }
+    }
+
+    /// Expect the decision to be `decision`
+    #[must_use]
+    pub fn expect_decision(mut self, decision: Decision) -> Self {
+        self.decision = Some(decision);
+        self
+    }
+
+    /// Expect the policies which determine the decision to be exactly
+    /// `policies`. These are the satisfied `permit` policies when the
+    /// decision is `Allow`, and the satisfied `forbid` policies when it is
+    /// `Deny`.
+    #[must_use]
+    pub fn expect_determining_policies(
+        mut self,
+        policies: impl IntoIterator<Item = PolicyId>,
+    ) -> Self {
+        self.determining_policies = Some(policies.into_iter().collect());
+        self
+    }
+
+    /// Expect exactly `num_errors` policies to error during authorization
+    #[must_use]
+    pub fn expect_num_errors(mut self, num_errors: usize) -> Self {
+        self.num_errors = Some(num_errors);
+        self
+    }
+
+    /// The name of the test
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The request the test authorizes
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// The entities the test authorizes its request against
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// Authorize the request of this test against `policies`, and check the
+    /// response against the expectations
+    pub fn run(&self, policies: &PolicySet) -> TestOutcome {
+        let response = Authorizer::new().is_authorized(&self.request, policies, &self.entities);
+        let decision = response.decision();
+        let determining_policies: BTreeSet<PolicyId> =
+            response.diagnostics().reason().cloned().collect();
+        let errors: Vec<String> = response
+            .diagnostics()
+            .errors()
+            .map(ToString::to_string)
+            .collect();
+
+        let mut failures = Vec::new();
+        if let Some(expected) = self.decision {
+            if expected != decision {
+                failures.push(TestFailure::WrongDecision {
+                    expected,
+                    actual: decision,
+                });
+            }
+        }
+        if let Some(expected) = &self.determining_policies {
+            if *expected != determining_policies {
+                failures.push(TestFailure::WrongDeterminingPolicies {
+                    expected: expected.clone(),
+                    actual: determining_policies.clone(),
+                });
+            }
+        }
+        if let Some(expected) = self.num_errors {
+            if expected != errors.len() {
+                failures.push(TestFailure::WrongNumberOfErrors {
+                    expected,
+                    actual: errors.len(),
+                });
+            }
+        }
+
+        TestOutcome {
+            name: self.name.clone(),
+            decision,
+            determining_policies,
+            errors,
+            failures,
+        }
+    }
+}
+
+/// A list of [`PolicyTest`]s to run against a [`PolicySet`]
+#[derive(Debug, Clone, Default)]
+pub struct TestSuite {
+    tests: Vec<PolicyTest>,
+}
+
+impl TestSuite {
+    /// An empty test suite
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `test` to the suite
+    #[must_use]
+    pub fn with_test(mut self, test: PolicyTest) -> Self {
+        self.add_test(test);
+        self
+    }
+
+    /// Add `test` to the suite
+    pub fn add_test(&mut self, test: PolicyTest) {
+        self.tests.push(test);
+    }
+
+    /// The tests of the suite, in the order they run
+    pub fn tests(&self) -> impl Iterator<Item = &PolicyTest> {
+        self.tests.iter()
+    }
+
+    /// Run every test of the suite against `policies`
+    pub fn run(&self, policies: &PolicySet) -> TestReport {
+        TestReport {
+            results: self.tests.iter().map(|test| test.run(policies)).collect(),
+        }
+    }
+
+    /// Parse a test suite from a JSON array of tests, each of which is an
+    /// object like:
+    ///
+    /// ```json
+    /// {
+    ///     "name": "alice can view her photo",
+    ///     "request": {
+    ///         "principal": "User::\"alice\"",
+    ///         "action": "Action::\"view\"",
+    ///         "resource": "Photo::\"vacation.jpg\"",
+    ///         "context": { "authenticated": true }
+    ///     },
+    ///     "entities": [],
+    ///     "decision": "allow",
+    ///     "reason": ["policy0"],
+    ///     "num_errors": 0
+    /// }
+    /// ```
+    ///
+    /// `entities` are in the Cedar entity JSON format, and the entities in
+    /// `request` are either strings or in the entity reference JSON format.
+    /// `decision` (`"allow"` or `"deny"`), `reason` (exactly the determining
+    /// policies) and `num_errors` are the expectations of the test, at least
+    /// one of which must be present. `name` defaults to the test's position
+    /// in the array, and `context` and `entities` default to empty.
+    ///
+    /// If `schema` is provided, the requests, contexts and entities of the
+    /// tests are validated against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` isn't in the format above, or if a test's
+    /// request, context or entities are invalid.
+    pub fn from_json_str(json: &str, schema: Option<&Schema>) -> Result<Self, TestFileError> {
+        Self::from_test_files(serde_json::from_str(json)?, schema)
+    }
+
+    /// Parse a test suite from a JSON value. See [`TestSuite::from_json_str()`]
+    /// for the format.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TestSuite::from_json_str()`].
+    pub fn from_json_value(
+        json: serde_json::Value,
+        schema: Option<&Schema>,
+    ) -> Result<Self, TestFileError> {
+        Self::from_test_files(serde_json::from_value(json)?, schema)
+    }
+
+    /// Parse a test suite from YAML with the same structure as the JSON
+    /// format described in [`TestSuite::from_json_str()`]
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TestSuite::from_json_str()`], for YAML instead of JSON.
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn from_yaml_str(yaml: &str, schema: Option<&Schema>) -> Result<Self, TestFileError> {
+        Self::from_test_files(serde_yaml_ng::from_str(yaml)?, schema)
+    }
+
+    /// Read a test suite from the file at `path`. With the `yaml` feature,
+    /// files with a `.yaml` or `.yml` extension are parsed as YAML; all other
+    /// files are parsed as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, or if parsing its contents
+    /// fails.
+    pub fn from_file(
+        path: impl AsRef<Path>,
+        schema: Option<&Schema>,
+    ) -> Result<Self, TestFileError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| TestFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        #[cfg(feature = "yaml")]
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            return Self::from_yaml_str(&contents, schema);
+        }
+        Self::from_json_str(&contents, schema)
+    }
+
+    fn from_test_files(
+        tests: Vec<TestJson>,
+        schema: Option<&Schema>,
+    ) -> Result<Self, TestFileError> {
+        let tests = tests
+            .into_iter()
+            .enumerate()
+            .map(|(i, test)| test.into_test(i, schema))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { tests })
+    }
+}
+
+/// A test as written in a test file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestJson {
+    #[serde(default)]
+    name: Option<String>,
+    request: RequestJson,
+    #[serde(default = "empty_array")]
+    entities: serde_json::Value,
+    #[serde(default)]
+    decision: Option<Decision>,
+    #[serde(default)]
+    reason: Option<Vec<PolicyId>>,
+    #[serde(default)]
+    num_errors: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequestJson {
+    principal: serde_json::Value,
+    action: serde_json::Value,
+    resource: serde_json::Value,
+    #[serde(default = "empty_object")]
+    context: serde_json::Value,
+}
+
+fn empty_array() -> serde_json::Value {
+    serde_json::Value::Array(Vec::new())
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::Value::Object(serde_json::Map::new())
+}
+
+impl TestJson {
+    /// Convert to a [`PolicyTest`], where `index` is the position of the test
+    /// in its file
+    fn into_test(self, index: usize, schema: Option<&Schema>) -> Result<PolicyTest, TestFileError> {
+        let name = self.name.unwrap_or_else(|| format!("test {index}"));
+        let principal =
+            entity_uid(self.request.principal).map_err(invalid_test(&name, "principal"))?;
+        let action = entity_uid(self.request.action).map_err(invalid_test(&name, "action"))?;
+        let resource =
+            entity_uid(self.request.resource).map_err(invalid_test(&name, "resource"))?;
+        let context =
+            Context::from_json_value(self.request.context, schema.map(|schema| (schema, &action)))
+                .map_err(invalid_test(&name, "context"))?;
+        let request = Request::new(principal, action, resource, context, schema)
+            .map_err(invalid_test(&name, "request"))?;
+        let entities = Entities::from_json_value(self.entities, schema)
+            .map_err(invalid_test(&name, "entities"))?;
+
+        if self.decision.is_none() && self.reason.is_none() && self.num_errors.is_none() {
+            return Err(TestFileError::NoExpectations { test: name });
+        }
+        let mut test = PolicyTest::new(name, request, entities);
+        test.decision = self.decision;
+        test.determining_policies = self.reason.map(|reason| reason.into_iter().collect());
+        test.num_errors = self.num_errors;
+        Ok(test)
+    }
+}
+
+/// Map an error in `field` of the test named `test` to a [`TestFileError`]
+fn invalid_test<'a, E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+    test: &'a str,
+    field: &'static str,
+) -> impl FnOnce(E) -> TestFileError + 'a {
+    move |source| TestFileError::InvalidTest {
+        test: test.to_string(),
+        field,
+        source: source.into(),
+    }
+}
+
+/// Parse an entity UID written either as a string or in the entity reference
+/// JSON format
+fn entity_uid(
+    json: serde_json::Value,
+) -> Result<EntityUid, Box<dyn std::error::Error + Send + Sync>> {
+    match json {
+        serde_json::Value::String(s) => Ok(s.parse()?),
+        json => Ok(EntityUid::from_json(json)?),
+    }
+}
+
+/// Error reading a test suite from a file
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum TestFileError {
+    /// Failed to read the test file
+    #[error("failed to read test file `{}`", path.display())]
+    Io {
+        /// Path of the test file
+        path: PathBuf,
+        /// Underlying IO error
+        #[source]
+        source: std::io::Error,
+    },
+    /// The test file isn't valid JSON or doesn't have the structure of a test
+    /// file
+    #[error("failed to parse tests: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The test file isn't valid YAML or doesn't have the structure of a test
+    /// file
+    #[cfg(feature = "yaml")]
+    #[error("failed to parse tests: {0}")]
+    Yaml(#[from] serde_yaml_ng::Error),
+    /// A request, context or entities of a test are invalid
+    #[error("invalid {field} in test `{test}`")]
+    InvalidTest {
+        /// Name of the test
+        test: String,
+        /// The invalid part of the test
+        field: &'static str,
+        /// Why it is invalid
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// A test doesn't expect anything
+    #[error("test `{test}` has no expected `decision`, `reason` or `num_errors`")]
+    NoExpectations {
+        /// Name of the test
+        test: String,
+    },
+}
+
+/// The outcome of running a [`PolicyTest`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestOutcome {
+    name: String,
+    decision: Decision,
+    determining_policies: BTreeSet<PolicyId>,
+    errors: Vec<String>,
+    failures: Vec<TestFailure>,
+}
+
+impl TestOutcome {
+    /// The name of the test
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The decision of the authorizer
+    pub fn decision(&self) -> Decision {
+        self.decision
+    }
+
+    /// The policies which determined the decision
+    pub fn determining_policies(&self) -> impl Iterator<Item = &PolicyId> {
+        self.determining_policies.iter()
+    }
+
+    /// The errors encountered while authorizing
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        self.errors.iter().map(String::as_str)
+    }
+
+    /// The expectations of the test which weren't met
+    pub fn failures(&self) -> impl Iterator<Item = &TestFailure> {
+        self.failures.iter()
+    }
+
+    /// Whether the test met all its expectations
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Display for TestOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "test {} ... ", self.name)?;
+        if self.passed() {
+            return write!(f, "ok");
+        }
+        write!(f, "FAILED")?;
+        for failure in &self.failures {
+            write!(f, "\n    {failure}")?;
+        }
+        for error in &self.errors {
+            write!(f, "\n    error: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An expectation of a [`PolicyTest`] which wasn't met
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Error)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum TestFailure {
+    /// The decision wasn't the expected one
+    #[error("expected decision {expected:?}, got {actual:?}")]
+    WrongDecision {
+        /// The expected decision
+        expected: Decision,
+        /// The actual decision
+        actual: Decision,
+    },
+    /// The determining policies weren't the expected ones
+    #[error(
+        "expected determining policies [{}], got [{}]",
+        display_ids(expected),
+        display_ids(actual)
+    )]
+    WrongDeterminingPolicies {
+        /// The expected determining policies
+        expected: BTreeSet<PolicyId>,
+        /// The actual determining policies
+        actual: BTreeSet<PolicyId>,
+    },
+    /// The number of policies which errored wasn't the expected one
+    #[error("expected {expected} error(s), got {actual}")]
+    WrongNumberOfErrors {
+        /// The expected number of errors
+        expected: usize,
+        /// The actual number of errors
+        actual: usize,
+    },
+}
+
+fn display_ids(ids: &BTreeSet<PolicyId>) -> String {
+    ids.iter().map(|id| format!("`{id}`")).join(", ")
+}
+
+/// The outcomes of running a [`TestSuite`]. Its `Display` implementation
+/// lists the outcome of each test followed by a summary, similar to the
+/// output of `cargo test`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestReport {
+    results: Vec<TestOutcome>,
+}
+
+impl TestReport {
+    /// The outcome of each test, in the order they ran
+    pub fn results(&self) -> impl Iterator<Item = &TestOutcome> {
+        self.results.iter()
+    }
+
+    /// The outcomes of the tests which failed
+    pub fn failures(&self) -> impl Iterator<Item = &TestOutcome> {
+        self.results.iter().filter(|outcome| !outcome.passed())
+    }
+
+    /// The number of tests which passed
+    pub fn num_passed(&self) -> usize {
+        self.results.len() - self.num_failed()
+    }
+
+    /// The number of tests which failed
+    pub fn num_failed(&self) -> usize {
+        self.failures().count()
+    }
+
+    /// Whether all the tests passed
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Panic with the report if any test failed, for running policy tests
+    /// with `cargo test`
+    ///
+    /// # Panics
+    ///
+    /// Panics if any test failed.
+    #[track_caller]
+    pub fn assert_passed(&self) {
+        assert!(self.passed(), "policy tests failed:\n{self}");
+    }
+}
+
+impl Display for TestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for outcome in &self.results {
+            writeln!(f, "{outcome}")?;
+        }
+        write!(
+            f,
+            "test result: {}. {} passed; {} failed",
+            if self.passed() { "ok" } else { "FAILED" },
+            self.num_passed(),
+            self.num_failed()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r"
+            entity User { banned: Bool };
+            entity Photo { owner: User };
+            action view appliesTo {
+                principal: User,
+                resource: Photo,
+                context: { mfa: Bool },
+            };
+            ",
+        )
+        .unwrap()
+    }
+
+    fn policies() -> PolicySet {
+        PolicySet::from_str(
+            r"
+            // policy0
+            permit(principal, action, resource) when { resource.owner == principal };
+            // policy1
+            forbid(principal, action, resource) unless { context.mfa };
+            // policy2
+            forbid(principal, action, resource) when { principal.banned };
+            ",
+        )
+        .unwrap()
+    }
+
+    const TESTS: &str = r#"[
+        {
+            "name": "owner can view",
+            "request": {
+                "principal": "User::\"alice\"",
+                "action": "Action::\"view\"",
+                "resource": "Photo::\"p\"",
+                "context": { "mfa": true }
+            },
+            "entities": [
+                { "uid": { "type": "User", "id": "alice" }, "attrs": { "banned": false }, "parents": [] },
+                { "uid": { "type": "Photo", "id": "p" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } }, "parents": [] }
+            ],
+            "decision": "allow",
+            "reason": ["policy0"]
+        },
+        {
+            "request": {
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "Photo", "id": "p" },
+                "context": { "mfa": false }
+            },
+            "entities": [
+                { "uid": { "type": "User", "id": "alice" }, "attrs": { "banned": true }, "parents": [] },
+                { "uid": { "type": "Photo", "id": "p" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } }, "parents": [] }
+            ],
+            "reason": ["policy1"],
+            "num_errors": 0
+        }
+    ]"#;
+
+    #[test]
+    fn from_json() {
+        let schema = schema();
+        let suite = TestSuite::from_json_str(TESTS, Some(&schema)).unwrap();
+        assert_eq!(
+            suite.tests().map(PolicyTest::name).collect::<Vec<_>>(),
+            ["owner can view", "test 1"]
+        );
+        let report = suite.run(&policies());
+        assert_eq!(
+            report.to_string(),
+            r"test owner can view ... ok
+test test 1 ... FAILED
+    expected determining policies [`policy1`], got [`policy1`, `policy2`]
+test result: FAILED. 1 passed; 1 failed"
+        );
+        assert_eq!(report.num_passed(), 1);
+        let failure = report.failures().next().unwrap();
+        assert_eq!(failure.decision(), Decision::Deny);
+        assert_eq!(
+            failure.failures().collect::<Vec<_>>(),
+            [&TestFailure::WrongDeterminingPolicies {
+                expected: [PolicyId::new("policy1")].into(),
+                actual: [PolicyId::new("policy1"), PolicyId::new("policy2")].into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn serialize_report() {
+        let report = TestSuite::from_json_str(TESTS, None)
+            .unwrap()
+            .run(&policies());
+        assert_eq!(
+            serde_json::to_value(report.failures().next().unwrap()).unwrap(),
+            serde_json::json!({
+                "name": "test 1",
+                "decision": "deny",
+                "determiningPolicies": ["policy1", "policy2"],
+                "errors": [],
+                "failures": [{
+                    "kind": "wrongDeterminingPolicies",
+                    "expected": ["policy1"],
+                    "actual": ["policy1", "policy2"],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn rust_tests() {
+        let request = Request::new(
+            EntityUid::from_str(r#"User::"bob""#).unwrap(),
+            EntityUid::from_str(r#"Action::"view""#).unwrap(),
+            EntityUid::from_str(r#"Photo::"p""#).unwrap(),
+            Context::from_pairs([("mfa".into(), crate::RestrictedExpression::new_bool(true))])
+                .unwrap(),
+            None,
+        )
+        .unwrap();
+        let report = TestSuite::new()
+            .with_test(
+                PolicyTest::new("no entities", request.clone(), Entities::empty())
+                    .expect_decision(Decision::Deny)
+                    .expect_num_errors(2),
+            )
+            .with_test(
+                PolicyTest::new("wrong", request, Entities::empty())
+                    .expect_decision(Decision::Allow)
+                    .expect_num_errors(0),
+            )
+            .run(&policies());
+        assert!(!report.passed());
+        let mut outcomes = report.results();
+        let passed = outcomes.next().unwrap();
+        assert!(passed.passed());
+        assert_eq!(passed.errors().count(), 2);
+        assert_eq!(
+            outcomes.next().unwrap().failures().collect::<Vec<_>>(),
+            [
+                &TestFailure::WrongDecision {
+                    expected: Decision::Allow,
+                    actual: Decision::Deny,
+                },
+                &TestFailure::WrongNumberOfErrors {
+                    expected: 0,
+                    actual: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_test_files() {
+        let schema = schema();
+        let request = serde_json::json!({
+            "principal": "User::\"alice\"",
+            "action": "Action::\"view\"",
+            "resource": "Photo::\"p\"",
+            "context": { "mfa": true },
+        });
+        assert!(matches!(
+            TestSuite::from_json_value(serde_json::json!([{ "request": request }]), None),
+            Err(TestFileError::NoExpectations { test }) if test == "test 0"
+        ));
+        assert!(matches!(
+            TestSuite::from_json_value(
+                serde_json::json!([{ "request": request, "decision": "maybe" }]),
+                None
+            ),
+            Err(TestFileError::Json(_))
+        ));
+        assert!(matches!(
+            TestSuite::from_json_value(
+                serde_json::json!([{
+                    "request": { "principal": "User", "action": "Action::\"view\"", "resource": "Photo::\"p\"" },
+                    "decision": "deny",
+                }]),
+                None
+            ),
+            Err(TestFileError::InvalidTest {
+                field: "principal",
+                ..
+            })
+        ));
+        assert!(matches!(
+            TestSuite::from_json_value(
+                serde_json::json!([{ "name": "t", "request": request, "decision": "deny", "entities": [
+                    { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [] }
+                ] }]),
+                Some(&schema)
+            ),
+            Err(TestFileError::InvalidTest { test, field: "entities", .. }) if test == "t"
+        ));
+        assert!(matches!(
+            TestSuite::from_file("does/not/exist.json", None),
+            Err(TestFileError::Io { .. })
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn from_yaml() {
+        let yaml = r#"
+- name: owner can view
+  request:
+    principal: User::"alice"
+    action: Action::"view"
+    resource: Photo::"p"
+    context:
+      mfa: true
+  entities:
+    - uid: { type: User, id: alice }
+      attrs: { banned: false }
+      parents: []
+    - uid: { type: Photo, id: p }
+      attrs: { owner: { __entity: { type: User, id: alice } } }
+      parents: []
+  decision: allow
+  reason: [policy0]
+"#;
+        let schema = schema();
+        TestSuite::from_yaml_str(yaml, Some(&schema))
+            .unwrap()
+            .run(&policies())
+            .assert_passed();
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![cfg(feature = "codegen")]
+
+use cedar_policy::codegen::{self, Datetime, IpAddr};
+use cedar_policy::{Context, Entities, EvalResult, RestrictedExpression, Schema};
+#[cfg(feature = "maps")]
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Code generated from `codegen/photos.cedarschema`. Run the
+/// `generated_code_is_up_to_date` test to check it matches what
+/// `codegen::generate()` currently produces.
+mod photos_schema {
+    include!("codegen/photos.rs");
+}
+
+/// Code generated from `codegen/albums.cedarschema`, which uses map types.
+#[cfg(feature = "maps")]
+mod albums_schema {
+    include!("codegen/albums.rs");
+}
+
+use photos_schema::photos::{Group, Photo, User, UserAddress, ViewContext};
+use photos_schema::Color;
+
+#[expect(clippy::unwrap_used, reason = "test helper")]
+fn schema() -> Schema {
+    Schema::from_str(include_str!("codegen/photos.cedarschema")).unwrap()
+}
+
+#[cfg(feature = "maps")]
+#[expect(clippy::unwrap_used, reason = "test helper")]
+fn albums_schema() -> Schema {
+    Schema::from_str(include_str!("codegen/albums.cedarschema")).unwrap()
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    assert_eq!(
+        codegen::generate(&schema()),
+        include_str!("codegen/photos.rs"),
+        "generated code changed; regenerate `tests/codegen/photos.rs`"
+    );
+}
+
+#[cfg(feature = "maps")]
+#[test]
+fn generated_map_code_is_up_to_date() {
+    assert_eq!(
+        codegen::generate(&albums_schema()),
+        include_str!("codegen/albums.rs"),
+        "generated code changed; regenerate `tests/codegen/albums.rs`"
+    );
+}
+
+fn alice() -> User {
+    User {
+        address: Some(UserAddress {
+            street: "Main St".into(),
+            zip_code: None,
+        }),
+        age: None,
+        favorite_color: Some(Color::LightBlue),
+        name: "Alice".into(),
+        roles: vec!["admin".into()],
+    }
+}
+
+#[test]
+fn entities_conform_to_schema() {
+    let alice = alice()
+        .into_entity("alice", [Group::uid("admins")])
+        .unwrap();
+    assert_eq!(
+        alice.attr("favorite_color").unwrap().unwrap(),
+        EvalResult::EntityUid(Color::LightBlue.uid())
+    );
+    assert!(alice.attr("age").is_none());
+
+    let photo = Photo {
+        owner: User::uid("alice"),
+        taken: Datetime("2024-10-15".into()),
+        r#type: "jpeg".into(),
+    }
+    .into_entity("vacation.jpg", [])
+    .unwrap();
+    let admins = Group {}.into_entity("admins", []).unwrap();
+    Entities::from_entities([alice, photo, admins], Some(&schema())).unwrap();
+}
+
+#[cfg(feature = "maps")]
+#[test]
+fn map_entities_conform_to_schema() {
+    use albums_schema::Album;
+
+    let album = Album {
+        labels: BTreeMap::from([("cats".into(), 2)]),
+    };
+    let context = Context::from_pairs([(
+        "album".to_string(),
+        RestrictedExpression::from(album.clone()),
+    )])
+    .unwrap();
+    assert_eq!(
+        Album::try_from(context.get("album").unwrap()).unwrap(),
+        album
+    );
+    let album = album.into_entity("vacation", []).unwrap();
+    Entities::from_entities([album], Some(&albums_schema())).unwrap();
+}
+
+#[test]
+fn values_roundtrip() {
+    let context = Context::from_pairs([
+        ("user".to_string(), RestrictedExpression::from(alice())),
+        ("color".to_string(), Color::Red.into()),
+    ])
+    .unwrap();
+    assert_eq!(
+        User::try_from(context.get("user").unwrap()).unwrap(),
+        alice()
+    );
+    assert_eq!(
+        Color::try_from(context.get("color").unwrap()).unwrap(),
+        Color::Red
+    );
+
+    let view = ViewContext {
+        authenticated: true,
+        ip: IpAddr("10.0.0.1".into()),
+        reason: None,
+    };
+    let context = view.clone().into_context().unwrap();
+    assert!(context.get("reason").is_none());
+    let context =
+        Context::from_pairs([("view".to_string(), RestrictedExpression::from(view.clone()))])
+            .unwrap();
+    let value = context.get("view").unwrap();
+    assert_eq!(ViewContext::try_from(value).unwrap(), view);
+}
+
+#[test]
+fn conversion_errors() {
+    let err = Color::try_from(EvalResult::String("red".into())).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"expected an entity of type `Color`, found `"red"`"#
+    );
+
+    let context = Context::from_pairs([(
+        "user".to_string(),
+        RestrictedExpression::new_record([(
+            "address".to_string(),
+            RestrictedExpression::new_record([(
+                "street".to_string(),
+                RestrictedExpression::new_long(1),
+            )])
+            .unwrap(),
+        )])
+        .unwrap(),
+    )])
+    .unwrap();
+    let err = User::try_from(context.get("user").unwrap()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "in attribute `address.street`: expected a string, found `1`"
+    );
+    assert_eq!(err.path().collect::<Vec<_>>(), ["address", "street"]);
+}
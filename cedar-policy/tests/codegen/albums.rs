@@ -0,0 +1,63 @@
+// @generated by `cedar_policy::codegen::generate()`. Do not edit by hand.
+
+/// Attributes of an entity of type `Album`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Album {
+    /// The `labels` attribute
+    pub labels: ::std::collections::BTreeMap<::std::string::String, i64>,
+}
+
+impl Album {
+    fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+        ::std::vec![
+            ("labels", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.labels))),
+        ]
+    }
+}
+
+impl ::cedar_policy::codegen::CedarValue for Album {
+    fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+        ::cedar_policy::codegen::record(self.into_fields())
+    }
+
+    fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+        let record = ::cedar_policy::codegen::expect_record(value)?;
+        ::std::result::Result::Ok(Self {
+            labels: ::cedar_policy::codegen::attr(record, "labels")?,
+        })
+    }
+}
+
+impl ::std::convert::From<Album> for ::cedar_policy::RestrictedExpression {
+    fn from(value: Album) -> Self {
+        ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+    }
+}
+
+impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for Album {
+    type Error = ::cedar_policy::codegen::ValueConversionError;
+
+    fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+        ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+    }
+}
+
+impl Album {
+    /// Name of the entity type
+    pub const ENTITY_TYPE: &'static str = "Album";
+
+    /// Uid of the entity of this type with id `id`
+    pub fn uid(id: &str) -> ::cedar_policy::EntityUid {
+        ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+    }
+
+    /// Create the entity of this type with id `id`, these attributes, and
+    /// the given parents
+    pub fn into_entity(
+        self,
+        id: &str,
+        parents: impl ::std::iter::IntoIterator<Item = ::cedar_policy::EntityUid>,
+    ) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {
+        ::cedar_policy::codegen::entity(Self::ENTITY_TYPE, id, self.into_fields(), parents)
+    }
+}
@@ -0,0 +1,370 @@
+// @generated by `cedar_policy::codegen::generate()`. Do not edit by hand.
+
+/// An entity of the enumerated entity type `Color`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Color {
+    /// The entity with id `red`
+    Red,
+    /// The entity with id `light-blue`
+    LightBlue,
+}
+
+impl Color {
+    /// Name of the entity type
+    pub const ENTITY_TYPE: &'static str = "Color";
+
+    /// All the entities of this type, in the order they are declared
+    pub const ALL: &'static [Self] = &[Self::Red, Self::LightBlue];
+
+    /// Id of this entity
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Red => "red",
+            Self::LightBlue => "light-blue",
+        }
+    }
+
+    /// Uid of this entity
+    pub fn uid(self) -> ::cedar_policy::EntityUid {
+        ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, self.id())
+    }
+}
+
+impl ::cedar_policy::codegen::CedarValue for Color {
+    fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+        ::cedar_policy::RestrictedExpression::new_entity_uid(self.uid())
+    }
+
+    fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+        ::cedar_policy::codegen::enum_choice(value, Self::ENTITY_TYPE, &[("red", Self::Red), ("light-blue", Self::LightBlue)])
+    }
+}
+
+impl ::std::convert::From<Color> for ::cedar_policy::EntityUid {
+    fn from(value: Color) -> Self {
+        value.uid()
+    }
+}
+
+impl ::std::convert::From<Color> for ::cedar_policy::RestrictedExpression {
+    fn from(value: Color) -> Self {
+        ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+    }
+}
+
+impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for Color {
+    type Error = ::cedar_policy::codegen::ValueConversionError;
+
+    fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+        ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+    }
+}
+
+/// Types for the `Photos` namespace
+pub mod photos {
+    /// Attributes of an entity of type `Photos::Group`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Group {}
+
+    impl Group {
+        fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+            ::std::vec::Vec::new()
+        }
+    }
+
+    impl ::cedar_policy::codegen::CedarValue for Group {
+        fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+            ::cedar_policy::codegen::record(self.into_fields())
+        }
+
+        fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+            ::cedar_policy::codegen::expect_record(value)?;
+            ::std::result::Result::Ok(Self {})
+        }
+    }
+
+    impl ::std::convert::From<Group> for ::cedar_policy::RestrictedExpression {
+        fn from(value: Group) -> Self {
+            ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+        }
+    }
+
+    impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for Group {
+        type Error = ::cedar_policy::codegen::ValueConversionError;
+
+        fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+            ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+        }
+    }
+
+    impl Group {
+        /// Name of the entity type
+        pub const ENTITY_TYPE: &'static str = "Photos::Group";
+
+        /// Uid of the entity of this type with id `id`
+        pub fn uid(id: &str) -> ::cedar_policy::EntityUid {
+            ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+        }
+
+        /// Create the entity of this type with id `id`, these attributes, and
+        /// the given parents
+        pub fn into_entity(
+            self,
+            id: &str,
+            parents: impl ::std::iter::IntoIterator<Item = ::cedar_policy::EntityUid>,
+        ) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {
+            ::cedar_policy::codegen::entity(Self::ENTITY_TYPE, id, self.into_fields(), parents)
+        }
+    }
+
+    /// Attributes of an entity of type `Photos::Photo`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Photo {
+        /// The `owner` attribute
+        pub owner: ::cedar_policy::EntityUid,
+        /// The `taken` attribute
+        pub taken: ::cedar_policy::codegen::Datetime,
+        /// The `type` attribute
+        pub r#type: ::std::string::String,
+    }
+
+    impl Photo {
+        fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+            ::std::vec![
+                ("owner", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.owner))),
+                ("taken", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.taken))),
+                ("type", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.r#type))),
+            ]
+        }
+    }
+
+    impl ::cedar_policy::codegen::CedarValue for Photo {
+        fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+            ::cedar_policy::codegen::record(self.into_fields())
+        }
+
+        fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+            let record = ::cedar_policy::codegen::expect_record(value)?;
+            ::std::result::Result::Ok(Self {
+                owner: ::cedar_policy::codegen::attr(record, "owner")?,
+                taken: ::cedar_policy::codegen::attr(record, "taken")?,
+                r#type: ::cedar_policy::codegen::attr(record, "type")?,
+            })
+        }
+    }
+
+    impl ::std::convert::From<Photo> for ::cedar_policy::RestrictedExpression {
+        fn from(value: Photo) -> Self {
+            ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+        }
+    }
+
+    impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for Photo {
+        type Error = ::cedar_policy::codegen::ValueConversionError;
+
+        fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+            ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+        }
+    }
+
+    impl Photo {
+        /// Name of the entity type
+        pub const ENTITY_TYPE: &'static str = "Photos::Photo";
+
+        /// Uid of the entity of this type with id `id`
+        pub fn uid(id: &str) -> ::cedar_policy::EntityUid {
+            ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+        }
+
+        /// Create the entity of this type with id `id`, these attributes, and
+        /// the given parents
+        pub fn into_entity(
+            self,
+            id: &str,
+            parents: impl ::std::iter::IntoIterator<Item = ::cedar_policy::EntityUid>,
+        ) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {
+            ::cedar_policy::codegen::entity(Self::ENTITY_TYPE, id, self.into_fields(), parents)
+        }
+    }
+
+    /// Type of the `address` attribute of [`User`]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct UserAddress {
+        /// The `street` attribute
+        pub street: ::std::string::String,
+        /// The `zip-code` attribute
+        pub zip_code: ::std::option::Option<::std::string::String>,
+    }
+
+    impl UserAddress {
+        fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+            ::std::vec![
+                ("street", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.street))),
+                ("zip-code", self.zip_code.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),
+            ]
+        }
+    }
+
+    impl ::cedar_policy::codegen::CedarValue for UserAddress {
+        fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+            ::cedar_policy::codegen::record(self.into_fields())
+        }
+
+        fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+            let record = ::cedar_policy::codegen::expect_record(value)?;
+            ::std::result::Result::Ok(Self {
+                street: ::cedar_policy::codegen::attr(record, "street")?,
+                zip_code: ::cedar_policy::codegen::optional_attr(record, "zip-code")?,
+            })
+        }
+    }
+
+    impl ::std::convert::From<UserAddress> for ::cedar_policy::RestrictedExpression {
+        fn from(value: UserAddress) -> Self {
+            ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+        }
+    }
+
+    impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for UserAddress {
+        type Error = ::cedar_policy::codegen::ValueConversionError;
+
+        fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+            ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+        }
+    }
+
+    /// Attributes of an entity of type `Photos::User`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct User {
+        /// The `address` attribute
+        pub address: ::std::option::Option<UserAddress>,
+        /// The `age` attribute
+        pub age: ::std::option::Option<i64>,
+        /// The `favorite_color` attribute
+        pub favorite_color: ::std::option::Option<super::Color>,
+        /// The `name` attribute
+        pub name: ::std::string::String,
+        /// The `roles` attribute
+        pub roles: ::std::vec::Vec<::std::string::String>,
+    }
+
+    impl User {
+        fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+            ::std::vec![
+                ("address", self.address.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),
+                ("age", self.age.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),
+                ("favorite_color", self.favorite_color.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),
+                ("name", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.name))),
+                ("roles", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.roles))),
+            ]
+        }
+    }
+
+    impl ::cedar_policy::codegen::CedarValue for User {
+        fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+            ::cedar_policy::codegen::record(self.into_fields())
+        }
+
+        fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+            let record = ::cedar_policy::codegen::expect_record(value)?;
+            ::std::result::Result::Ok(Self {
+                address: ::cedar_policy::codegen::optional_attr(record, "address")?,
+                age: ::cedar_policy::codegen::optional_attr(record, "age")?,
+                favorite_color: ::cedar_policy::codegen::optional_attr(record, "favorite_color")?,
+                name: ::cedar_policy::codegen::attr(record, "name")?,
+                roles: ::cedar_policy::codegen::attr(record, "roles")?,
+            })
+        }
+    }
+
+    impl ::std::convert::From<User> for ::cedar_policy::RestrictedExpression {
+        fn from(value: User) -> Self {
+            ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+        }
+    }
+
+    impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for User {
+        type Error = ::cedar_policy::codegen::ValueConversionError;
+
+        fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+            ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+        }
+    }
+
+    impl User {
+        /// Name of the entity type
+        pub const ENTITY_TYPE: &'static str = "Photos::User";
+
+        /// Uid of the entity of this type with id `id`
+        pub fn uid(id: &str) -> ::cedar_policy::EntityUid {
+            ::cedar_policy::codegen::uid(Self::ENTITY_TYPE, id)
+        }
+
+        /// Create the entity of this type with id `id`, these attributes, and
+        /// the given parents
+        pub fn into_entity(
+            self,
+            id: &str,
+            parents: impl ::std::iter::IntoIterator<Item = ::cedar_policy::EntityUid>,
+        ) -> ::std::result::Result<::cedar_policy::Entity, ::cedar_policy::EntityAttrEvaluationError> {
+            ::cedar_policy::codegen::entity(Self::ENTITY_TYPE, id, self.into_fields(), parents)
+        }
+    }
+
+    /// Context of a request for the action `Photos::Action::"view"`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ViewContext {
+        /// The `authenticated` attribute
+        pub authenticated: bool,
+        /// The `ip` attribute
+        pub ip: ::cedar_policy::codegen::IpAddr,
+        /// The `reason` attribute
+        pub reason: ::std::option::Option<::std::string::String>,
+    }
+
+    impl ViewContext {
+        fn into_fields(self) -> ::std::vec::Vec<(&'static str, ::std::option::Option<::cedar_policy::RestrictedExpression>)> {
+            ::std::vec![
+                ("authenticated", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.authenticated))),
+                ("ip", ::std::option::Option::Some(::cedar_policy::codegen::CedarValue::into_restricted_expression(self.ip))),
+                ("reason", self.reason.map(::cedar_policy::codegen::CedarValue::into_restricted_expression)),
+            ]
+        }
+    }
+
+    impl ::cedar_policy::codegen::CedarValue for ViewContext {
+        fn into_restricted_expression(self) -> ::cedar_policy::RestrictedExpression {
+            ::cedar_policy::codegen::record(self.into_fields())
+        }
+
+        fn from_eval_result(value: &::cedar_policy::EvalResult) -> ::std::result::Result<Self, ::cedar_policy::codegen::ValueConversionError> {
+            let record = ::cedar_policy::codegen::expect_record(value)?;
+            ::std::result::Result::Ok(Self {
+                authenticated: ::cedar_policy::codegen::attr(record, "authenticated")?,
+                ip: ::cedar_policy::codegen::attr(record, "ip")?,
+                reason: ::cedar_policy::codegen::optional_attr(record, "reason")?,
+            })
+        }
+    }
+
+    impl ::std::convert::From<ViewContext> for ::cedar_policy::RestrictedExpression {
+        fn from(value: ViewContext) -> Self {
+            ::cedar_policy::codegen::CedarValue::into_restricted_expression(value)
+        }
+    }
+
+    impl ::std::convert::TryFrom<::cedar_policy::EvalResult> for ViewContext {
+        type Error = ::cedar_policy::codegen::ValueConversionError;
+
+        fn try_from(value: ::cedar_policy::EvalResult) -> ::std::result::Result<Self, Self::Error> {
+            ::cedar_policy::codegen::CedarValue::from_eval_result(&value)
+        }
+    }
+
+    impl ViewContext {
+        /// Create a request context with these attributes
+        pub fn into_context(self) -> ::std::result::Result<::cedar_policy::Context, ::cedar_policy::ContextCreationError> {
+            ::cedar_policy::codegen::context(self.into_fields())
+        }
+    }
+}
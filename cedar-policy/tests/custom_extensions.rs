@@ -0,0 +1,379 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Custom extensions are registered process-wide, before any policy is parsed,
+//! so these tests live in their own test binary and all go through `setup()`.
+
+use cedar_policy::evaluation_errors::ExtensionErrorKind;
+use cedar_policy::*;
+
+use cool_asserts::assert_matches;
+use std::str::FromStr;
+use std::sync::Once;
+
+fn setup() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        // errors which are detected before anything is registered
+        assert_matches!(
+            CustomExtension::new("bad")
+                .with_function(CustomFunction::method(
+                    "contains",
+                    vec![DeclaredType::String, DeclaredType::String],
+                    DeclaredType::Bool,
+                    |_| Ok(EvalResult::Bool(true)),
+                ))
+                .register(),
+            Err(CustomExtensionError::Registration(_))
+        );
+        assert_matches!(
+            CustomExtension::new("bad")
+                .with_function(CustomFunction::function(
+                    "decimal",
+                    vec![DeclaredType::String],
+                    DeclaredType::Extension("decimal".into()),
+                    |_| Ok(EvalResult::ExtensionValue(r#"decimal("1.0")"#.into())),
+                ))
+                .register(),
+            Err(CustomExtensionError::Registration(_))
+        );
+        assert_matches!(
+            CustomExtension::new("bad")
+                .with_function(CustomFunction::function(
+                    "toRecord",
+                    vec![DeclaredType::String],
+                    DeclaredType::Map(Box::new(DeclaredType::String)),
+                    |_| Ok(EvalResult::Bool(true)),
+                ))
+                .register(),
+            Err(CustomExtensionError::UnsupportedType(e)) => {
+                assert_eq!(e.function(), "toRecord");
+            }
+        );
+        assert_matches!(
+            CustomExtension::new("bad")
+                .with_function(CustomFunction::method(
+                    "now",
+                    vec![],
+                    DeclaredType::Long,
+                    |_| Ok(EvalResult::Long(0)),
+                ))
+                .register(),
+            Err(CustomExtensionError::Registration(_))
+        );
+        assert_matches!(
+            CustomExtension::new("not a name").register(),
+            Err(CustomExtensionError::InvalidName(e)) => {
+                assert_eq!(e.name(), "not a name");
+            }
+        );
+
+        CustomExtension::new("geo")
+            .with_function(
+                CustomFunction::method(
+                    "isInRegion",
+                    vec![DeclaredType::String, DeclaredType::String],
+                    DeclaredType::Bool,
+                    |args| match args {
+                        [EvalResult::String(country), EvalResult::String(region)] => {
+                            let eu = ["DE", "FR", "NL"];
+                            match region.as_str() {
+                                "eu" => Ok(EvalResult::Bool(eu.contains(&country.as_str()))),
+                                _ => Err(format!("unknown region `{region}`")),
+                            }
+                        }
+                        _ => Err("expected two strings".into()),
+                    },
+                )
+                .with_doc("Tests whether a country code is in a region."),
+            )
+            .with_function(CustomFunction::function(
+                "geo::distanceKm",
+                vec![DeclaredType::String, DeclaredType::String],
+                DeclaredType::Long,
+                |args| match args {
+                    [EvalResult::String(a), EvalResult::String(b)] if a == b => {
+                        Ok(EvalResult::Long(0))
+                    }
+                    // deliberately returns a value of the wrong type
+                    _ => Ok(EvalResult::String("far".into())),
+                },
+            ))
+            .with_function(CustomFunction::method(
+                "doubled",
+                vec![DeclaredType::Extension("decimal".into())],
+                DeclaredType::Extension("decimal".into()),
+                |args| match args {
+                    [EvalResult::ExtensionValue(d)] => {
+                        Ok(EvalResult::ExtensionValue(format!("{d}.add({d})")))
+                    }
+                    _ => Err("expected a decimal".into()),
+                },
+            ))
+            .register()
+            .unwrap();
+
+        CustomExtension::new("logic")
+            .with_function(
+                CustomFunction::method(
+                    "impliesThat",
+                    vec![DeclaredType::Bool, DeclaredType::Bool],
+                    DeclaredType::Bool,
+                    |args| match args {
+                        [EvalResult::Bool(a), EvalResult::Bool(b)] => {
+                            Ok(EvalResult::Bool(!a || *b))
+                        }
+                        _ => Err("expected two booleans".into()),
+                    },
+                )
+                .with_short_circuit(|known| match known {
+                    [EvalResult::Bool(false)] => Some(Ok(EvalResult::Bool(true))),
+                    _ => None,
+                }),
+            )
+            .with_function(
+                CustomFunction::function(
+                    "logic::first",
+                    vec![DeclaredType::Long, DeclaredType::Long],
+                    DeclaredType::Long,
+                    |args| match args {
+                        [EvalResult::Long(a), _] => Ok(EvalResult::Long(*a)),
+                        _ => Err("expected two longs".into()),
+                    },
+                )
+                // deliberately returns a value of the wrong type
+                .with_short_circuit(|_| Some(Ok(EvalResult::Bool(true)))),
+            )
+            .register()
+            .unwrap();
+
+        // `geo` already defines `isInRegion`
+        assert_matches!(
+            CustomExtension::new("geo2")
+                .with_function(CustomFunction::method(
+                    "isInRegion",
+                    vec![DeclaredType::String, DeclaredType::String],
+                    DeclaredType::Bool,
+                    |_| Ok(EvalResult::Bool(true)),
+                ))
+                .register(),
+            Err(CustomExtensionError::Registration(_))
+        );
+    });
+}
+
+fn eval(src: &str) -> Result<EvalResult, EvaluationError> {
+    let request = Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+        Context::empty(),
+        None,
+    )
+    .unwrap();
+    eval_expression(
+        &request,
+        &Entities::empty(),
+        &Expression::from_str(src).unwrap(),
+    )
+}
+
+#[test]
+fn evaluate_custom_functions() {
+    setup();
+    assert_eq!(
+        eval(r#""DE".isInRegion("eu")"#).unwrap(),
+        EvalResult::Bool(true)
+    );
+    assert_eq!(
+        eval(r#""US".isInRegion("eu")"#).unwrap(),
+        EvalResult::Bool(false)
+    );
+    assert_eq!(
+        eval(r#"geo::distanceKm("a", "a")"#).unwrap(),
+        EvalResult::Long(0)
+    );
+    assert_eq!(
+        eval(r#"decimal("1.25").doubled() == decimal("2.5")"#).unwrap(),
+        EvalResult::Bool(true)
+    );
+
+    // an `Err` from the function
+    let err = eval(r#""DE".isInRegion("us")"#).unwrap_err();
+    assert!(err.to_string().contains("unknown region `us`"), "{err}");
+    assert_eq!(extension_error_kind(&err), ExtensionErrorKind::Custom);
+    // an argument of the wrong type is rejected before calling the function
+    let err = eval(r#""DE".isInRegion(1)"#).unwrap_err();
+    assert!(err.to_string().contains("argument 2"), "{err}");
+    assert_eq!(
+        extension_error_kind(&err),
+        ExtensionErrorKind::WrongArgumentType
+    );
+    // a return value of the wrong type
+    let err = eval(r#"geo::distanceKm("a", "b")"#).unwrap_err();
+    assert!(err.to_string().contains("invalid return value"), "{err}");
+    assert_eq!(
+        extension_error_kind(&err),
+        ExtensionErrorKind::InvalidReturnValue
+    );
+}
+
+#[test]
+fn evaluate_builtin_functions_error_kinds() {
+    setup();
+    let err = eval(r#"ip("x")"#).unwrap_err();
+    assert_eq!(extension_error_kind(&err), ExtensionErrorKind::Parse);
+    assert_eq!(extension_error_kind(&err).as_str(), "parse");
+    let err = eval(r#"decimal("922337203685477.5807").add(decimal("0.0001"))"#).unwrap_err();
+    assert_eq!(extension_error_kind(&err), ExtensionErrorKind::OutOfRange);
+    assert_eq!(extension_error_kind(&err).as_str(), "out_of_range");
+    // an argument of the wrong type to a builtin function is a type error,
+    // which has no extension error kind
+    assert_matches!(eval(r#"ip(1)"#), Err(EvaluationError::TypeError(_)));
+    assert_matches!(
+        eval(r#"decimal("1.0").add("1.0")"#),
+        Err(EvaluationError::TypeError(_))
+    );
+}
+
+#[track_caller]
+fn extension_error_kind(err: &EvaluationError) -> ExtensionErrorKind {
+    match err {
+        EvaluationError::FailedExtensionFunctionExecution(err) => err.kind(),
+        _ => panic!("expected an extension function error, got {err:?}"),
+    }
+}
+
+#[test]
+fn evaluate_lazy_custom_functions() {
+    setup();
+    assert_eq!(
+        eval("false.impliesThat(context.missing)").unwrap(),
+        EvalResult::Bool(true)
+    );
+    assert_eq!(
+        eval("true.impliesThat(false)").unwrap(),
+        EvalResult::Bool(false)
+    );
+    // the second argument is evaluated when the first doesn't decide the result
+    let err = eval("true.impliesThat(context.missing)").unwrap_err();
+    assert!(err.to_string().contains("missing"), "{err}");
+    // a short-circuit result of the wrong type
+    let err = eval("logic::first(1, 2)").unwrap_err();
+    assert!(err.to_string().contains("invalid return value"), "{err}");
+}
+
+#[test]
+fn authorize_with_custom_functions() {
+    setup();
+    let policies = PolicySet::from_str(
+        r#"permit(principal, action, resource) when { context.country.isInRegion("eu") };"#,
+    )
+    .unwrap();
+    let request = |country: &str| {
+        Request::new(
+            EntityUid::from_str(r#"User::"alice""#).unwrap(),
+            EntityUid::from_str(r#"Action::"view""#).unwrap(),
+            EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+            Context::from_pairs([(
+                "country".into(),
+                RestrictedExpression::new_string(country.into()),
+            )])
+            .unwrap(),
+            None,
+        )
+        .unwrap()
+    };
+    let auth = Authorizer::new();
+    let entities = Entities::empty();
+    assert_eq!(
+        auth.is_authorized(&request("FR"), &policies, &entities)
+            .decision(),
+        Decision::Allow
+    );
+    assert_eq!(
+        auth.is_authorized(&request("US"), &policies, &entities)
+            .decision(),
+        Decision::Deny
+    );
+}
+
+#[test]
+fn validate_custom_functions() {
+    setup();
+    let schema = Schema::from_str(
+        r#"
+        entity User { country: String };
+        entity Photo;
+        action view appliesTo { principal: User, resource: Photo };
+        "#,
+    )
+    .unwrap();
+    let validator = Validator::new(schema);
+
+    let valid = PolicySet::from_str(
+        r#"permit(principal, action, resource) when { principal.country.isInRegion("eu") && geo::distanceKm(principal.country, "DE") < 100 };"#,
+    )
+    .unwrap();
+    let result = validator.validate(&valid, ValidationMode::Strict);
+    assert!(result.validation_passed(), "{result}");
+
+    let invalid = PolicySet::from_str(
+        r#"permit(principal, action, resource) when { principal.isInRegion("eu") };"#,
+    )
+    .unwrap();
+    let result = validator.validate(&invalid, ValidationMode::Strict);
+    assert!(!result.validation_passed());
+}
+
+#[test]
+fn introspect_custom_functions() {
+    setup();
+    let funcs: Vec<_> = extension_functions()
+        .filter(|f| f.extension() == "geo")
+        .collect();
+    let signatures: Vec<_> = funcs.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        signatures,
+        [
+            "decimal.doubled() -> decimal",
+            "geo::distanceKm(String, String) -> Long",
+            "String.isInRegion(String) -> Bool",
+        ]
+    );
+    assert_eq!(
+        funcs[2].doc(),
+        Some("Tests whether a country code is in a region.")
+    );
+    assert_eq!(funcs[1].doc(), None);
+}
+
+#[test]
+fn register_after_first_use() {
+    setup();
+    Expression::from_str("1 + 1").unwrap();
+    assert_matches!(
+        CustomExtension::new("late")
+            .with_function(CustomFunction::function(
+                "late::answer",
+                vec![],
+                DeclaredType::Long,
+                |_| Ok(EvalResult::Long(42)),
+            ))
+            .register(),
+        Err(CustomExtensionError::Registration(_))
+    );
+}
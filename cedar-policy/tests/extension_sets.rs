@@ -0,0 +1,325 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests for giving custom extensions to individual authorizers and
+//! validators with an `ExtensionSet`.
+
+use cedar_policy::*;
+
+use cool_asserts::assert_matches;
+use std::str::FromStr;
+
+/// An extension with the method `s.hasLevel(n)`, which tests whether the
+/// string `s` is at least `min` characters long
+fn levels(min: i64) -> CustomExtension {
+    CustomExtension::new("levels").with_function(CustomFunction::method(
+        "hasLevel",
+        vec![DeclaredType::String, DeclaredType::Long],
+        DeclaredType::Bool,
+        move |args| match args {
+            [EvalResult::String(s), EvalResult::Long(n)] => {
+                Ok(EvalResult::Bool(*n >= min && s.len() as i64 >= *n))
+            }
+            _ => Err("expected a string and a long".into()),
+        },
+    ))
+}
+
+fn request(name: &str) -> Request {
+    Request::new(
+        EntityUid::from_str(r#"User::"alice""#).unwrap(),
+        EntityUid::from_str(r#"Action::"view""#).unwrap(),
+        EntityUid::from_str(r#"Photo::"a.jpg""#).unwrap(),
+        Context::from_pairs([("name".into(), RestrictedExpression::new_string(name.into()))])
+            .unwrap(),
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn authorizers_with_different_extensions() {
+    let lenient = ExtensionSet::builder()
+        .with_extension(levels(0))
+        .build()
+        .unwrap();
+    let strict = ExtensionSet::builder()
+        .with_extension(levels(5))
+        .build()
+        .unwrap();
+    let policies = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { context.name.hasLevel(3) };"#,
+        &lenient,
+    )
+    .unwrap();
+    // any set with the method can be used for converting the policies to and
+    // from JSON, and the method is displayed as a method, so the policy can be
+    // parsed again
+    let json = policies.clone().to_json_with_extensions(&lenient).unwrap();
+    let from_json = PolicySet::from_json_value_with_extensions(json, &strict).unwrap();
+    let displayed = from_json.to_cedar_with_extensions(&strict).unwrap();
+    assert!(
+        displayed.contains("(context.name).hasLevel(3)"),
+        "{displayed}"
+    );
+    PolicySet::from_str_with_extensions(&displayed, &strict).unwrap();
+    // the default extensions don't include the method
+    PolicySet::from_str(&displayed).unwrap_err();
+
+    let entities = Entities::empty();
+    let response = Authorizer::new().with_extensions(&lenient).is_authorized(
+        &request("bob"),
+        &policies,
+        &entities,
+    );
+    assert_eq!(response.decision(), Decision::Allow);
+    let response = Authorizer::new().with_extensions(&strict).is_authorized(
+        &request("bob"),
+        &policies,
+        &entities,
+    );
+    assert_eq!(response.decision(), Decision::Deny);
+    assert_eq!(response.diagnostics().errors().count(), 0);
+
+    // authorizers without the extension can't call the method
+    let response = Authorizer::new().is_authorized(&request("bob"), &policies, &entities);
+    assert_eq!(response.decision(), Decision::Deny);
+    let err = response.diagnostics().errors().next().unwrap().to_string();
+    assert!(err.contains("hasLevel"), "{err}");
+}
+
+#[test]
+fn validators_with_different_extensions() {
+    let schema = Schema::from_str(
+        r#"
+        entity User { name: String };
+        entity Photo;
+        action view appliesTo { principal: User, resource: Photo };
+        "#,
+    )
+    .unwrap();
+    let extensions = ExtensionSet::builder()
+        .with_extension(levels(0))
+        .build()
+        .unwrap();
+    let policies = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { principal.name.hasLevel(3) };"#,
+        &extensions,
+    )
+    .unwrap();
+
+    let validator = Validator::new(schema.clone()).with_extensions(&extensions);
+    let result = validator.validate(&policies, ValidationMode::Strict);
+    assert!(result.validation_passed(), "{result}");
+    let wrong_type = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { principal.name.hasLevel("3") };"#,
+        &extensions,
+    )
+    .unwrap();
+    let result = validator.validate(&wrong_type, ValidationMode::Strict);
+    assert!(!result.validation_passed());
+
+    let result = Validator::new(schema).validate(&policies, ValidationMode::Strict);
+    assert!(!result.validation_passed());
+}
+
+#[test]
+fn invalid_extension_sets() {
+    // a function of a builtin extension
+    assert_matches!(
+        ExtensionSet::builder()
+            .with_extension(
+                CustomExtension::new("bad").with_function(CustomFunction::function(
+                    "ip",
+                    vec![DeclaredType::String],
+                    DeclaredType::Bool,
+                    |_| Ok(EvalResult::Bool(true)),
+                ))
+            )
+            .build(),
+        Err(CustomExtensionError::Registration(_))
+    );
+    // the same function in two extensions of one set
+    let answer = || {
+        CustomExtension::new("answer").with_function(CustomFunction::function(
+            "answer::get",
+            vec![],
+            DeclaredType::Long,
+            |_| Ok(EvalResult::Long(42)),
+        ))
+    };
+    assert_matches!(
+        ExtensionSet::builder()
+            .with_extension(answer())
+            .with_extension(answer())
+            .build(),
+        Err(CustomExtensionError::Registration(_))
+    );
+    ExtensionSet::builder()
+        .with_extension(answer())
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn call_styles_per_extension_set() {
+    let function = ExtensionSet::builder()
+        .with_extension(
+            CustomExtension::new("answer").with_function(CustomFunction::function(
+                "fortyTwo",
+                vec![],
+                DeclaredType::Long,
+                |_| Ok(EvalResult::Long(42)),
+            )),
+        )
+        .build()
+        .unwrap();
+    // a function in one set can be a method in another
+    let method = ExtensionSet::builder()
+        .with_extension(
+            CustomExtension::new("answer").with_function(CustomFunction::method(
+                "fortyTwo",
+                vec![DeclaredType::Long],
+                DeclaredType::Long,
+                |_| Ok(EvalResult::Long(42)),
+            )),
+        )
+        .build()
+        .unwrap();
+
+    let as_function = r#"permit(principal, action, resource) when { fortyTwo() == 42 };"#;
+    let as_method = r#"permit(principal, action, resource) when { 1.fortyTwo() == 42 };"#;
+    PolicySet::from_str_with_extensions(as_function, &function).unwrap();
+    PolicySet::from_str_with_extensions(as_method, &function).unwrap_err();
+    PolicySet::from_str_with_extensions(as_method, &method).unwrap();
+    PolicySet::from_str_with_extensions(as_function, &method).unwrap_err();
+    // without a set, only the default extensions are known
+    PolicySet::from_str(as_function).unwrap_err();
+    PolicySet::from_str(as_method).unwrap_err();
+
+    // functions are also checked when converting from JSON, and displayed with
+    // the call style of the set
+    let json = Policy::parse_with_extensions(None, as_method, &method)
+        .unwrap()
+        .to_json_with_extensions(&method)
+        .unwrap();
+    Policy::from_json(None, json.clone()).unwrap_err();
+    let policy = Policy::from_json_with_extensions(None, json, &method).unwrap();
+    let displayed = policy.to_cedar_with_extensions(&method).unwrap();
+    assert!(displayed.contains("(1.fortyTwo()) == 42"), "{displayed}");
+}
+
+#[cfg(feature = "prf")]
+#[test]
+fn prf_keys_per_extension_set() {
+    let old_key = ExtensionSet::builder()
+        .with_prf(b"Jefe".as_slice())
+        .build()
+        .unwrap();
+    let new_key = ExtensionSet::builder()
+        .with_prf(b"rotated".as_slice())
+        .build()
+        .unwrap();
+    // the parser accepts `prf` with either set
+    let policies = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { prf(context.name) == "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843" };"#,
+        &old_key,
+    )
+    .unwrap();
+    let decide = |extensions: &ExtensionSet| {
+        Authorizer::new()
+            .with_extensions(extensions)
+            .is_authorized(
+                &request("what do ya want for nothing?"),
+                &policies,
+                &Entities::empty(),
+            )
+            .decision()
+    };
+    assert_eq!(decide(&old_key), Decision::Allow);
+    assert_eq!(decide(&new_key), Decision::Deny);
+    // the default extensions don't include `prf`
+    assert_eq!(decide(&ExtensionSet::all_available()), Decision::Deny);
+
+    // `prf` can't be added twice
+    assert_matches!(
+        ExtensionSet::builder()
+            .with_prf(b"a".as_slice())
+            .with_prf(b"b".as_slice())
+            .build(),
+        Err(CustomExtensionError::Registration(_))
+    );
+}
+
+#[cfg(feature = "regions")]
+#[test]
+fn regions_per_extension_set() {
+    let eu = ExtensionSet::builder()
+        .with_regions(RegionsExtension::standard().with_region("HOME", ["FR", "BE"]))
+        .build()
+        .unwrap();
+    let us = ExtensionSet::builder()
+        .with_regions(RegionsExtension::empty().with_region("HOME", ["US"]))
+        .build()
+        .unwrap();
+    let policies = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { context.name.inRegion("HOME") };"#,
+        &eu,
+    )
+    .unwrap();
+    let response = |extensions: &ExtensionSet, country: &str| {
+        Authorizer::new().with_extensions(extensions).is_authorized(
+            &request(country),
+            &policies,
+            &Entities::empty(),
+        )
+    };
+    assert_eq!(response(&eu, "fr").decision(), Decision::Allow);
+    assert_eq!(response(&us, "fr").decision(), Decision::Deny);
+    assert_eq!(response(&us, "US").decision(), Decision::Allow);
+
+    let policies = PolicySet::from_str_with_extensions(
+        r#"permit(principal, action, resource) when { context.name.inRegion("EU") };"#,
+        &eu,
+    )
+    .unwrap();
+    let response = |extensions: &ExtensionSet| {
+        Authorizer::new().with_extensions(extensions).is_authorized(
+            &request("de"),
+            &policies,
+            &Entities::empty(),
+        )
+    };
+    assert_eq!(response(&eu).decision(), Decision::Allow);
+    // `EU` isn't declared in the other set
+    let response = response(&us);
+    assert_eq!(response.decision(), Decision::Deny);
+    let err = response.diagnostics().errors().next().unwrap().to_string();
+    assert!(err.contains("inRegion"), "{err}");
+
+    assert_matches!(
+        ExtensionSet::builder()
+            .with_regions(
+                RegionsExtension::standard()
+                    .with_region("Nordics", ["DK", "FI", "IS", "NO", "SE", "Greenland"])
+            )
+            .build(),
+        Err(CustomExtensionError::InvalidCountryCode(err)) => {
+            assert_eq!(err.region(), "Nordics");
+            assert_eq!(err.country_code(), "Greenland");
+        }
+    );
+}
@@ -0,0 +1,904 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Differential testing of a [`CedarTestImplementation`] against a reference
+//! implementation, usually [`crate::cedar_test_impl::RustEngine`].
+//!
+//! A [`DifferentialTester`] generates random inputs, each a schema, policies,
+//! entities and requests, from an [`Unstructured`] (so that it can be driven
+//! by a fuzzer, or by `proptest` via random bytes), runs both implementations
+//! on them, and reports every [`Discrepancy`] between their results. It can
+//! also check the candidate implementation against the expected results
+//! recorded in the integration and corpus tests (see
+//! [`crate::test_files`]).
+//!
+//! ```
+//! # use cedar_testing::cedar_test_impl::RustEngine;
+//! # use cedar_testing::differential::DifferentialTester;
+//! # use arbitrary::Unstructured;
+//! let tester = DifferentialTester::new(RustEngine::new(), RustEngine::new());
+//! let bytes: Vec<u8> = (0..4096u32).map(|i| (i * 7919 % 251) as u8).collect();
+//! let mut u = Unstructured::new(&bytes);
+//! let input = tester.generate(&mut u).unwrap();
+//! assert!(tester.compare(&input).is_empty());
+//! ```
+
+use crate::cedar_test_impl::{
+    CedarTestImplementation, ErrorComparisonMode, TestResult, ValidationComparisonMode,
+};
+use crate::integration_testing::{
+    parse_entities_from_test, parse_policies_from_test, parse_request_from_test,
+    parse_schema_from_test, resolve_integration_test_path, JsonTest,
+};
+use arbitrary::Unstructured;
+use cedar_policy::{
+    ffi, Decision, Entities, PolicyId, PolicySet, Request, Schema, SchemaGenerator, ValidationMode,
+};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Options controlling the size of the inputs generated by a
+/// [`DifferentialTester`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    /// The maximum number of entity types in a schema
+    pub max_entity_types: usize,
+    /// The maximum number of attributes of each entity type, and of the
+    /// context of each action
+    pub max_attributes: usize,
+    /// The maximum number of actions in a schema
+    pub max_actions: usize,
+    /// The maximum number of policies
+    pub max_policies: usize,
+    /// The maximum number of requests
+    pub max_requests: usize,
+    /// The maximum depth of the expressions in policy conditions
+    pub max_expr_depth: usize,
+    /// Options for the entities and requests generated for each schema
+    pub data: cedar_policy::GeneratorConfig,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            max_entity_types: 3,
+            max_attributes: 3,
+            max_actions: 2,
+            max_policies: 4,
+            max_requests: 4,
+            max_expr_depth: 3,
+            data: cedar_policy::GeneratorConfig::default(),
+        }
+    }
+}
+
+/// An input on which implementations are compared
+#[derive(Debug, Clone)]
+pub struct TestInput {
+    /// Schema the policies are validated against, and the entities and
+    /// requests conform to
+    pub schema: Schema,
+    /// Policies to validate and authorize the requests with
+    pub policies: PolicySet,
+    /// Entities to authorize the requests against
+    pub entities: Entities,
+    /// Requests to authorize
+    pub requests: Vec<Request>,
+}
+
+/// Which of the implementations compared by a [`DifferentialTester`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Implementation {
+    /// The reference implementation, or the expected results recorded in a
+    /// test file
+    Reference,
+    /// The implementation under test
+    Candidate,
+}
+
+/// A difference between the results of the reference and candidate
+/// implementations on a [`TestInput`]. `request` is the index of a request in
+/// [`TestInput::requests`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// An implementation failed to produce a result
+    Failure {
+        /// The implementation which failed
+        implementation: Implementation,
+        /// The request it failed on, or `None` if it failed to validate the
+        /// policies
+        request: Option<usize>,
+        /// Why it failed
+        message: String,
+    },
+    /// The implementations disagree on whether the policies validate, as
+    /// required by the candidate's [`ValidationComparisonMode`]
+    Validation {
+        /// Whether validation passed for the reference
+        reference: bool,
+        /// Whether validation passed for the candidate
+        candidate: bool,
+    },
+    /// The implementations reached different decisions
+    Decision {
+        /// Index of the request
+        request: usize,
+        /// Decision of the reference
+        reference: Decision,
+        /// Decision of the candidate
+        candidate: Decision,
+    },
+    /// The implementations reached a decision for different reasons
+    Reason {
+        /// Index of the request
+        request: usize,
+        /// Determining policies of the reference
+        reference: BTreeSet<PolicyId>,
+        /// Determining policies of the candidate
+        candidate: BTreeSet<PolicyId>,
+    },
+    /// The implementations reported different errors, as compared by the
+    /// candidate's [`ErrorComparisonMode`]
+    Errors {
+        /// Index of the request
+        request: usize,
+        /// Errors of the reference
+        reference: BTreeSet<String>,
+        /// Errors of the candidate
+        candidate: BTreeSet<String>,
+    },
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Failure {
+                implementation,
+                request: Some(request),
+                message,
+            } => write!(f, "{implementation:?} failed on request {request}: {message}"),
+            Self::Failure {
+                implementation,
+                request: None,
+                message,
+            } => write!(f, "{implementation:?} failed to validate: {message}"),
+            Self::Validation {
+                reference,
+                candidate,
+            } => write!(
+                f,
+                "validation passed for reference: {reference}, for candidate: {candidate}"
+            ),
+            Self::Decision {
+                request,
+                reference,
+                candidate,
+            } => write!(
+                f,
+                "request {request}: reference decided {reference:?}, candidate decided {candidate:?}"
+            ),
+            Self::Reason {
+                request,
+                reference,
+                candidate,
+            } => write!(
+                f,
+                "request {request}: reference reason {reference:?}, candidate reason {candidate:?}"
+            ),
+            Self::Errors {
+                request,
+                reference,
+                candidate,
+            } => write!(
+                f,
+                "request {request}: reference errors {reference:?}, candidate errors {candidate:?}"
+            ),
+        }
+    }
+}
+
+/// Compares a candidate [`CedarTestImplementation`] against a reference one
+#[derive(Debug)]
+pub struct DifferentialTester<R, C> {
+    reference: R,
+    candidate: C,
+    config: GeneratorConfig,
+}
+
+impl<R: CedarTestImplementation, C: CedarTestImplementation> DifferentialTester<R, C> {
+    /// Compare `candidate` against `reference` on inputs generated with the
+    /// default [`GeneratorConfig`]
+    pub fn new(reference: R, candidate: C) -> Self {
+        Self::with_config(reference, candidate, GeneratorConfig::default())
+    }
+
+    /// Compare `candidate` against `reference` on inputs generated with
+    /// `config`
+    pub fn with_config(reference: R, candidate: C, config: GeneratorConfig) -> Self {
+        Self {
+            reference,
+            candidate,
+            config,
+        }
+    }
+
+    /// Generate a random input: a schema, policies over the schema's entity
+    /// types and actions, and entities and requests conforming to the
+    /// schema. The policies are not necessarily valid.
+    pub fn generate(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<TestInput> {
+        let spec = SchemaSpec::arbitrary(u, &self.config)?;
+        let schema = Schema::from_cedarschema_str(&spec.to_cedarschema())
+            .map_err(|_| arbitrary::Error::IncorrectFormat)?
+            .0;
+        let mut policies = String::new();
+        for _ in 0..u.int_in_range(0..=self.config.max_policies)? {
+            spec.write_policy(u, &self.config, &mut policies)?;
+        }
+        let policies =
+            PolicySet::from_str(&policies).map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        let generator = SchemaGenerator::with_config(&schema, self.config.data.clone());
+        let entities = generator.entities(u)?;
+        let requests = (0..u.int_in_range(1..=self.config.max_requests)?)
+            .map(|_| generator.request(u))
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(TestInput {
+            schema,
+            policies,
+            entities,
+            requests,
+        })
+    }
+
+    /// Generate a random input with [`DifferentialTester::generate()`] and
+    /// compare the implementations on it
+    pub fn check(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<Vec<Discrepancy>> {
+        Ok(self.compare(&self.generate(u)?))
+    }
+
+    /// Run both implementations on `input`, returning the discrepancies
+    /// between them
+    pub fn compare(&self, input: &TestInput) -> Vec<Discrepancy> {
+        let mut discrepancies = Vec::new();
+        let reference =
+            self.reference
+                .validate(&input.schema, &input.policies, ValidationMode::default());
+        let candidate =
+            self.candidate
+                .validate(&input.schema, &input.policies, ValidationMode::default());
+        if let (Some(reference), Some(candidate)) = (
+            success(
+                Implementation::Reference,
+                None,
+                reference,
+                &mut discrepancies,
+            ),
+            success(
+                Implementation::Candidate,
+                None,
+                candidate,
+                &mut discrepancies,
+            ),
+        ) {
+            self.compare_validation(
+                reference.validation_passed(),
+                candidate.validation_passed(),
+                &mut discrepancies,
+            );
+        }
+
+        for (i, request) in input.requests.iter().enumerate() {
+            let reference = self
+                .reference
+                .is_authorized(request, &input.policies, &input.entities);
+            let candidate = self
+                .candidate
+                .is_authorized(request, &input.policies, &input.entities);
+            if let (Some(reference), Some(candidate)) = (
+                success(
+                    Implementation::Reference,
+                    Some(i),
+                    reference,
+                    &mut discrepancies,
+                ),
+                success(
+                    Implementation::Candidate,
+                    Some(i),
+                    candidate,
+                    &mut discrepancies,
+                ),
+            ) {
+                let mode = self.candidate.error_comparison_mode();
+                self.compare_responses(
+                    i,
+                    &ResponseSummary::new(&reference.response, &mode),
+                    &ResponseSummary::new(&candidate.response, &mode),
+                    &mut discrepancies,
+                );
+            }
+        }
+        discrepancies
+    }
+
+    /// Run the candidate implementation on the integration or corpus test
+    /// described by the JSON file `jsonfile`, returning the discrepancies
+    /// between its results and the expected results recorded in the test.
+    /// Relative paths are resolved as by
+    /// [`crate::integration_testing::resolve_integration_test_path()`].
+    ///
+    /// Errors are compared by the IDs of the erroring policies, unless the
+    /// candidate's [`ErrorComparisonMode`] is [`ErrorComparisonMode::Ignore`].
+    ///
+    /// # Panics
+    ///
+    /// When the test or the files it refers to can't be read or parsed.
+    pub fn check_test_file(&self, jsonfile: impl AsRef<Path>) -> Vec<Discrepancy> {
+        let jsonfile = resolve_integration_test_path(jsonfile);
+        let test_name = jsonfile.display().to_string();
+        #[expect(clippy::panic, reason = "documented to panic on invalid test files")]
+        let test: JsonTest = std::fs::read_to_string(&jsonfile)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| panic!("error reading {test_name}: {e}"));
+        let policies = parse_policies_from_test(&test);
+        let schema = parse_schema_from_test(&test);
+        let entities = parse_entities_from_test(&test, &schema);
+
+        let mut discrepancies = Vec::new();
+        let candidate = self
+            .candidate
+            .validate(&schema, &policies, ValidationMode::default());
+        if let Some(candidate) = success(
+            Implementation::Candidate,
+            None,
+            candidate,
+            &mut discrepancies,
+        ) {
+            self.compare_validation(
+                test.should_validate,
+                candidate.validation_passed(),
+                &mut discrepancies,
+            );
+        }
+
+        let mode = match self.candidate.error_comparison_mode() {
+            ErrorComparisonMode::Ignore => ErrorComparisonMode::Ignore,
+            ErrorComparisonMode::PolicyIds | ErrorComparisonMode::Full => {
+                ErrorComparisonMode::PolicyIds
+            }
+        };
+        for (i, json_request) in test.requests.iter().enumerate() {
+            let request = parse_request_from_test(json_request, &schema, &test_name);
+            let candidate = self.candidate.is_authorized(&request, &policies, &entities);
+            if let Some(candidate) = success(
+                Implementation::Candidate,
+                Some(i),
+                candidate,
+                &mut discrepancies,
+            ) {
+                let expected = ResponseSummary {
+                    decision: json_request.decision,
+                    reason: json_request.reason.iter().cloned().collect(),
+                    errors: match mode {
+                        ErrorComparisonMode::Ignore => BTreeSet::new(),
+                        _ => json_request
+                            .errors
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect(),
+                    },
+                };
+                self.compare_responses(
+                    i,
+                    &expected,
+                    &ResponseSummary::new(&candidate.response, &mode),
+                    &mut discrepancies,
+                );
+            }
+        }
+        discrepancies
+    }
+
+    fn compare_validation(
+        &self,
+        reference: bool,
+        candidate: bool,
+        discrepancies: &mut Vec<Discrepancy>,
+    ) {
+        let agree = match self.candidate.validation_comparison_mode() {
+            ValidationComparisonMode::AgreeOnAll => reference == candidate,
+            ValidationComparisonMode::AgreeOnValid => !reference || candidate,
+        };
+        if !agree {
+            discrepancies.push(Discrepancy::Validation {
+                reference,
+                candidate,
+            });
+        }
+    }
+
+    fn compare_responses(
+        &self,
+        request: usize,
+        reference: &ResponseSummary,
+        candidate: &ResponseSummary,
+        discrepancies: &mut Vec<Discrepancy>,
+    ) {
+        if reference.decision != candidate.decision {
+            discrepancies.push(Discrepancy::Decision {
+                request,
+                reference: reference.decision,
+                candidate: candidate.decision,
+            });
+        }
+        if reference.reason != candidate.reason {
+            discrepancies.push(Discrepancy::Reason {
+                request,
+                reference: reference.reason.clone(),
+                candidate: candidate.reason.clone(),
+            });
+        }
+        if reference.errors != candidate.errors {
+            discrepancies.push(Discrepancy::Errors {
+                request,
+                reference: reference.errors.clone(),
+                candidate: candidate.errors.clone(),
+            });
+        }
+    }
+}
+
+/// The parts of a response which implementations must agree on
+struct ResponseSummary {
+    decision: Decision,
+    reason: BTreeSet<PolicyId>,
+    /// Errors, as compared by an [`ErrorComparisonMode`]
+    errors: BTreeSet<String>,
+}
+
+impl ResponseSummary {
+    fn new(response: &ffi::Response, mode: &ErrorComparisonMode) -> Self {
+        Self {
+            decision: response.decision(),
+            reason: response.diagnostics().reason().cloned().collect(),
+            errors: response
+                .diagnostics()
+                .errors()
+                .filter_map(|err| match mode {
+                    ErrorComparisonMode::Ignore => None,
+                    ErrorComparisonMode::PolicyIds => Some(err.policy_id.to_string()),
+                    ErrorComparisonMode::Full => Some(err.error.message.clone()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The result of a successful `result`, recording a [`Discrepancy::Failure`]
+/// otherwise
+fn success<T>(
+    implementation: Implementation,
+    request: Option<usize>,
+    result: TestResult<T>,
+    discrepancies: &mut Vec<Discrepancy>,
+) -> Option<T> {
+    match result {
+        TestResult::Success(t) => Some(t),
+        TestResult::Failure(message) => {
+            discrepancies.push(Discrepancy::Failure {
+                implementation,
+                request,
+                message,
+            });
+            None
+        }
+    }
+}
+
+/// A randomly generated schema. Entity types are named `E0`, `E1`, ...,
+/// actions `act0`, `act1`, ..., entity attributes `a0`, `a1`, ... and context
+/// attributes `c0`, `c1`, ....
+#[derive(Debug)]
+struct SchemaSpec {
+    entity_types: Vec<EntityTypeSpec>,
+    actions: Vec<ActionSpec>,
+}
+
+#[derive(Debug)]
+struct EntityTypeSpec {
+    parents: Vec<usize>,
+    attrs: Vec<AttrSpec>,
+}
+
+#[derive(Debug)]
+struct ActionSpec {
+    principals: Vec<usize>,
+    resources: Vec<usize>,
+    context: Vec<AttrSpec>,
+}
+
+#[derive(Debug)]
+struct AttrSpec {
+    name: String,
+    ty: AttrType,
+    required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttrType {
+    Bool,
+    Long,
+    String,
+    Entity(usize),
+    LongSet,
+}
+
+impl SchemaSpec {
+    fn arbitrary(u: &mut Unstructured<'_>, config: &GeneratorConfig) -> arbitrary::Result<Self> {
+        let num_types = u.int_in_range(1..=config.max_entity_types.max(1))?;
+        let entity_types = (0..num_types)
+            .map(|_| {
+                Ok(EntityTypeSpec {
+                    parents: subset(u, num_types)?,
+                    attrs: attrs(u, "a", num_types, config.max_attributes)?,
+                })
+            })
+            .collect::<arbitrary::Result<_>>()?;
+        let actions = (0..u.int_in_range(1..=config.max_actions.max(1))?)
+            .map(|_| {
+                Ok(ActionSpec {
+                    principals: nonempty_subset(u, num_types)?,
+                    resources: nonempty_subset(u, num_types)?,
+                    context: attrs(u, "c", num_types, config.max_attributes)?,
+                })
+            })
+            .collect::<arbitrary::Result<_>>()?;
+        Ok(Self {
+            entity_types,
+            actions,
+        })
+    }
+
+    fn to_cedarschema(&self) -> String {
+        let mut schema = String::new();
+        for (i, ty) in self.entity_types.iter().enumerate() {
+            let _ = write!(schema, "entity E{i}");
+            if !ty.parents.is_empty() {
+                let _ = write!(schema, " in [{}]", type_list(&ty.parents));
+            }
+            let _ = writeln!(schema, " {{ {} }};", attr_list(&ty.attrs));
+        }
+        for (i, action) in self.actions.iter().enumerate() {
+            let _ = writeln!(
+                schema,
+                "action act{i} appliesTo {{ principal: [{}], resource: [{}], context: {{ {} }} }};",
+                type_list(&action.principals),
+                type_list(&action.resources),
+                attr_list(&action.context),
+            );
+        }
+        schema
+    }
+
+    /// Write a random policy to `policies`
+    fn write_policy(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+        policies: &mut String,
+    ) -> arbitrary::Result<()> {
+        let effect = if u.arbitrary()? { "permit" } else { "forbid" };
+        let principal = self.scope_constraint(u, config, "principal")?;
+        let action = match u.int_in_range(0..=2)? {
+            0 => "action".to_string(),
+            1 => format!("action == {}", self.action_uid(u)?),
+            _ => format!(
+                "action in [{}, {}]",
+                self.action_uid(u)?,
+                self.action_uid(u)?
+            ),
+        };
+        let resource = self.scope_constraint(u, config, "resource")?;
+        let _ = write!(policies, "{effect}({principal}, {action}, {resource})");
+        for _ in 0..u.int_in_range(0..=2)? {
+            let condition = if u.arbitrary()? { "when" } else { "unless" };
+            let expr = self.bool_expr(u, config, config.max_expr_depth)?;
+            let _ = write!(policies, " {condition} {{ {expr} }}");
+        }
+        policies.push_str(";\n");
+        Ok(())
+    }
+
+    fn scope_constraint(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+        var: &str,
+    ) -> arbitrary::Result<String> {
+        Ok(match u.int_in_range(0..=4)? {
+            0 => var.to_string(),
+            1 => format!("{var} == {}", self.entity_uid(u, config)?),
+            2 => format!("{var} in {}", self.entity_uid(u, config)?),
+            3 => format!("{var} is {}", self.entity_type(u)?),
+            _ => format!(
+                "{var} is {} in {}",
+                self.entity_type(u)?,
+                self.entity_uid(u, config)?
+            ),
+        })
+    }
+
+    fn entity_type(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+        Ok(format!("E{}", u.choose_index(self.entity_types.len())?))
+    }
+
+    /// The UID of an entity which may be generated for the schema
+    fn entity_uid(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+    ) -> arbitrary::Result<String> {
+        let ty = self.entity_type(u)?;
+        let id = u.int_in_range(0..=config.data.max_entities_per_type)?;
+        Ok(format!("{ty}::\"{id}\""))
+    }
+
+    fn action_uid(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+        Ok(format!(
+            "Action::\"act{}\"",
+            u.choose_index(self.actions.len())?
+        ))
+    }
+
+    /// An access to an attribute of type `ty` of `principal`, `resource` or
+    /// `context`, if any of their possible types has one. The access isn't
+    /// necessarily valid for the type of the variable in a given request.
+    fn attr_access(
+        &self,
+        u: &mut Unstructured<'_>,
+        ty: AttrType,
+    ) -> arbitrary::Result<Option<(&'static str, &str)>> {
+        let candidates: Vec<(&'static str, &str)> = self
+            .entity_types
+            .iter()
+            .flat_map(|entity_type| &entity_type.attrs)
+            .flat_map(|attr| [("principal", attr), ("resource", attr)])
+            .chain(
+                self.actions
+                    .iter()
+                    .flat_map(|action| &action.context)
+                    .map(|attr| ("context", attr)),
+            )
+            .filter(|(_, attr)| attr.ty == ty)
+            .map(|(var, attr)| (var, attr.name.as_str()))
+            .collect();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(*u.choose(&candidates)?))
+    }
+
+    fn bool_expr(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+        depth: usize,
+    ) -> arbitrary::Result<String> {
+        let max_choice = if depth == 0 { 4 } else { 12 };
+        let depth = depth.saturating_sub(1);
+        Ok(match u.int_in_range(0..=max_choice)? {
+            0 => u.arbitrary::<bool>()?.to_string(),
+            1 => match self.attr_access(u, AttrType::Bool)? {
+                // Guard some accesses so they don't always error when the
+                // attribute is optional or absent
+                Some((var, attr)) if u.arbitrary()? => {
+                    format!("({var} has {attr} && {var}.{attr})")
+                }
+                Some((var, attr)) => format!("{var}.{attr}"),
+                None => u.arbitrary::<bool>()?.to_string(),
+            },
+            2 => {
+                let var = u.choose(&["principal", "resource", "context"])?;
+                let attr = if u.arbitrary()? { "a" } else { "c" };
+                format!(
+                    "{var} has {attr}{}",
+                    u.int_in_range(0..=config.max_attributes)?
+                )
+            }
+            3 => format!(
+                "{} in {}",
+                u.choose(&["principal", "resource"])?,
+                self.entity_expr(u, config)?
+            ),
+            4 => format!(
+                "{} is {}",
+                u.choose(&["principal", "resource"])?,
+                self.entity_type(u)?
+            ),
+            5 => format!("!({})", self.bool_expr(u, config, depth)?),
+            6 => format!(
+                "({} && {})",
+                self.bool_expr(u, config, depth)?,
+                self.bool_expr(u, config, depth)?
+            ),
+            7 => format!(
+                "({} || {})",
+                self.bool_expr(u, config, depth)?,
+                self.bool_expr(u, config, depth)?
+            ),
+            8 => format!(
+                "({} {} {})",
+                self.long_expr(u, config, depth)?,
+                u.choose(&["<", "<=", ">", ">=", "==", "!="])?,
+                self.long_expr(u, config, depth)?
+            ),
+            9 => format!("({} == {})", self.string_expr(u)?, self.string_expr(u)?),
+            10 => format!(
+                "({} like \"{}\")",
+                self.string_expr(u)?,
+                u.choose(&["*", "a*", "*b", ""])?
+            ),
+            11 => {
+                let set = match self.attr_access(u, AttrType::LongSet)? {
+                    Some((var, attr)) if u.arbitrary()? => format!("{var}.{attr}"),
+                    _ => format!(
+                        "[{}, {}]",
+                        self.long_expr(u, config, depth)?,
+                        self.long_expr(u, config, depth)?
+                    ),
+                };
+                format!("{set}.contains({})", self.long_expr(u, config, depth)?)
+            }
+            _ => format!(
+                "(if {} then {} else {})",
+                self.bool_expr(u, config, depth)?,
+                self.bool_expr(u, config, depth)?,
+                self.bool_expr(u, config, depth)?
+            ),
+        })
+    }
+
+    fn long_expr(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+        depth: usize,
+    ) -> arbitrary::Result<String> {
+        let max_choice = if depth == 0 { 1 } else { 4 };
+        let depth = depth.saturating_sub(1);
+        Ok(match u.int_in_range(0..=max_choice)? {
+            0 => {
+                // Include extreme values to exercise overflow errors
+                let literal = u.choose(&[0, 1, -1, 2, 7, i64::MAX, i64::MIN + 1])?;
+                if *literal < 0 {
+                    format!("({literal})")
+                } else {
+                    literal.to_string()
+                }
+            }
+            1 => match self.attr_access(u, AttrType::Long)? {
+                Some((var, attr)) => format!("{var}.{attr}"),
+                None => "0".to_string(),
+            },
+            2 => format!(
+                "({} + {})",
+                self.long_expr(u, config, depth)?,
+                self.long_expr(u, config, depth)?
+            ),
+            3 => format!(
+                "({} * {})",
+                self.long_expr(u, config, depth)?,
+                self.long_expr(u, config, depth)?
+            ),
+            _ => format!("(-{})", self.long_expr(u, config, depth)?),
+        })
+    }
+
+    fn string_expr(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<String> {
+        if u.arbitrary()? {
+            if let Some((var, attr)) = self.attr_access(u, AttrType::String)? {
+                return Ok(format!("{var}.{attr}"));
+            }
+        }
+        Ok(format!("\"{}\"", u.choose(&["", "a", "ab", "b"])?))
+    }
+
+    fn entity_expr(
+        &self,
+        u: &mut Unstructured<'_>,
+        config: &GeneratorConfig,
+    ) -> arbitrary::Result<String> {
+        let ty = AttrType::Entity(u.choose_index(self.entity_types.len())?);
+        match u.int_in_range(0..=2)? {
+            0 => Ok(u.choose(&["principal", "resource"])?.to_string()),
+            1 => Ok(self.attr_access(u, ty)?.map_or_else(
+                || "principal".to_string(),
+                |(var, attr)| format!("{var}.{attr}"),
+            )),
+            _ => self.entity_uid(u, config),
+        }
+    }
+}
+
+/// Random attribute declarations, named with `prefix`, whose types may refer
+/// to the first `num_types` entity types
+fn attrs(
+    u: &mut Unstructured<'_>,
+    prefix: &str,
+    num_types: usize,
+    max_attributes: usize,
+) -> arbitrary::Result<Vec<AttrSpec>> {
+    (0..u.int_in_range(0..=max_attributes)?)
+        .map(|i| {
+            let ty = match u.int_in_range(0..=4)? {
+                0 => AttrType::Bool,
+                1 => AttrType::Long,
+                2 => AttrType::String,
+                3 => AttrType::Entity(u.choose_index(num_types)?),
+                _ => AttrType::LongSet,
+            };
+            Ok(AttrSpec {
+                name: format!("{prefix}{i}"),
+                ty,
+                required: u.arbitrary()?,
+            })
+        })
+        .collect()
+}
+
+/// A random subset of `0..len`
+fn subset(u: &mut Unstructured<'_>, len: usize) -> arbitrary::Result<Vec<usize>> {
+    (0..len)
+        .map(|i| Ok(u.arbitrary::<bool>()?.then_some(i)))
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+/// A random nonempty subset of `0..len`
+fn nonempty_subset(u: &mut Unstructured<'_>, len: usize) -> arbitrary::Result<Vec<usize>> {
+    let mut indices = subset(u, len)?;
+    if indices.is_empty() {
+        indices.push(u.choose_index(len)?);
+    }
+    Ok(indices)
+}
+
+fn type_list(types: &[usize]) -> String {
+    types
+        .iter()
+        .map(|ty| format!("E{ty}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn attr_list(attrs: &[AttrSpec]) -> String {
+    attrs
+        .iter()
+        .map(|attr| {
+            let ty = match attr.ty {
+                AttrType::Bool => "Bool".to_string(),
+                AttrType::Long => "Long".to_string(),
+                AttrType::String => "String".to_string(),
+                AttrType::Entity(ty) => format!("E{ty}"),
+                AttrType::LongSet => "Set<Long>".to_string(),
+            };
+            let optional = if attr.required { "" } else { "?" };
+            format!("{}{optional}: {ty}", attr.name)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -15,5 +15,6 @@
  */
 
 pub mod cedar_test_impl;
+pub mod differential;
 pub mod integration_testing;
 pub mod test_files;
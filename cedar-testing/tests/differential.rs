@@ -0,0 +1,218 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![allow(clippy::expect_used, clippy::panic, reason = "tests")]
+
+use arbitrary::Unstructured;
+use cedar_policy::{
+    Authorizer, Decision, Entities, EvalResult, Expression, PolicySet, Request, Schema,
+    ValidationMode,
+};
+use cedar_testing::cedar_test_impl::{
+    CedarTestImplementation, ErrorComparisonMode, RustEngine, TestResponse, TestResult,
+    TestValidationResult, ValidationComparisonMode,
+};
+use cedar_testing::differential::{DifferentialTester, Discrepancy};
+
+/// Pseudo-random bytes for the `seed`th input
+fn bytes(seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(6_364_136_223_846_793_005) | 1;
+    (0..8192)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state.to_be_bytes()[0]
+        })
+        .collect()
+}
+
+/// Authorizes with only the `forbid` policies, otherwise like [`RustEngine`]
+struct IgnorePermits(RustEngine);
+
+impl CedarTestImplementation for IgnorePermits {
+    fn is_authorized(
+        &self,
+        request: &Request,
+        policies: &PolicySet,
+        entities: &Entities,
+    ) -> TestResult<TestResponse> {
+        let forbids = PolicySet::from_policies(
+            policies
+                .policies()
+                .filter(|p| p.effect() == cedar_policy::Effect::Forbid)
+                .cloned(),
+        )
+        .expect("subset of a policy set should be a policy set");
+        self.0.is_authorized(request, &forbids, entities)
+    }
+
+    fn interpret(
+        &self,
+        request: &Request,
+        entities: &Entities,
+        expr: &Expression,
+        expected: Option<EvalResult>,
+    ) -> TestResult<bool> {
+        self.0.interpret(request, entities, expr, expected)
+    }
+
+    fn validate(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> TestResult<TestValidationResult> {
+        self.0.validate(schema, policies, mode)
+    }
+
+    fn validate_with_level(
+        &self,
+        schema: &Schema,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        level: i32,
+    ) -> TestResult<TestValidationResult> {
+        self.0.validate_with_level(schema, policies, mode, level)
+    }
+
+    fn validate_request(
+        &self,
+        schema: &Schema,
+        request: &Request,
+    ) -> TestResult<TestValidationResult> {
+        self.0.validate_request(schema, request)
+    }
+
+    fn validate_entities(
+        &self,
+        schema: &Schema,
+        entities: &Entities,
+    ) -> TestResult<TestValidationResult> {
+        self.0.validate_entities(schema, entities)
+    }
+
+    fn error_comparison_mode(&self) -> ErrorComparisonMode {
+        self.0.error_comparison_mode()
+    }
+
+    fn validation_comparison_mode(&self) -> ValidationComparisonMode {
+        self.0.validation_comparison_mode()
+    }
+}
+
+#[test]
+fn generated_inputs_exercise_the_authorizer() {
+    let tester = DifferentialTester::new(RustEngine::new(), RustEngine::new());
+    let authorizer = Authorizer::new();
+    let (mut inputs, mut allows, mut errors) = (0, 0, 0);
+    for seed in 0..200 {
+        let Ok(input) = tester.generate(&mut Unstructured::new(&bytes(seed))) else {
+            continue;
+        };
+        inputs += 1;
+        for request in &input.requests {
+            let response = authorizer.is_authorized(request, &input.policies, &input.entities);
+            if response.decision() == Decision::Allow {
+                allows += 1;
+            }
+            errors += response.diagnostics().errors().count();
+        }
+    }
+    assert!(inputs > 150, "only {inputs} inputs were generated");
+    assert!(allows > 0, "no generated request was allowed");
+    assert!(errors > 0, "no generated request errored");
+}
+
+#[test]
+fn identical_implementations_agree() {
+    let tester = DifferentialTester::new(RustEngine::new(), RustEngine::new());
+    for seed in 0..200 {
+        if let Ok(discrepancies) = tester.check(&mut Unstructured::new(&bytes(seed))) {
+            assert_eq!(discrepancies, vec![], "seed {seed}");
+        }
+    }
+}
+
+#[test]
+fn broken_implementation_is_detected() {
+    let tester = DifferentialTester::new(RustEngine::new(), IgnorePermits(RustEngine::new()));
+    let discrepancies: Vec<_> = (0..200)
+        .filter_map(|seed| tester.check(&mut Unstructured::new(&bytes(seed))).ok())
+        .flatten()
+        .collect();
+    assert!(discrepancies.iter().any(|d| matches!(
+        d,
+        Discrepancy::Decision {
+            reference: Decision::Allow,
+            candidate: Decision::Deny,
+            ..
+        }
+    )));
+    assert!(discrepancies.iter().all(|d| matches!(
+        d,
+        Discrepancy::Decision { .. } | Discrepancy::Reason { .. } | Discrepancy::Errors { .. }
+    )));
+}
+
+#[test]
+fn test_file_expectations_are_checked() {
+    let dir = tempfile::tempdir().expect("should create a temporary directory");
+    let write = |name: &str, contents: &str| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).expect("should write test file");
+        path.display().to_string()
+    };
+    let test = serde_json::json!({
+        "policies": write("policies.cedar", r#"permit(principal == User::"alice", action, resource);"#),
+        "entities": write("entities.json", "[]"),
+        "schema": write("schema.cedarschema", "entity User; action view appliesTo { principal: User, resource: User };"),
+        "shouldValidate": true,
+        "requests": [
+            {
+                "description": "alice",
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "User", "id": "bob" },
+                "context": {},
+                "decision": "allow",
+                "reason": ["policy0"],
+                "errors": []
+            },
+            {
+                "description": "bob, with a wrong expectation",
+                "principal": { "type": "User", "id": "bob" },
+                "action": { "type": "Action", "id": "view" },
+                "resource": { "type": "User", "id": "bob" },
+                "context": {},
+                "decision": "allow",
+                "reason": [],
+                "errors": []
+            }
+        ]
+    });
+    let test = write("test.json", &test.to_string());
+
+    let tester = DifferentialTester::new(RustEngine::new(), RustEngine::new());
+    assert_eq!(
+        tester.check_test_file(test),
+        vec![Discrepancy::Decision {
+            request: 1,
+            reference: Decision::Allow,
+            candidate: Decision::Deny,
+        }]
+    );
+}